@@ -0,0 +1,112 @@
+//! Benchmarks of the market scan pipeline over synthetic markets.
+//!
+//! Run with `cargo bench --bench scan`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use funding_fee_farmer::config::PairSelectionConfig;
+use funding_fee_farmer::exchange::{
+    BookTicker, FundingRate, MarginAsset, SpotSymbolInfo, Ticker24h,
+};
+use funding_fee_farmer::strategy::{MarketData, MarketScanner};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Deterministic market of `symbols` USDT perpetuals (plus a USDC perp for
+/// every tenth) with a spread of funding, volume and spread values.
+fn market(symbols: usize) -> MarketData {
+    let mut market = MarketData::default();
+    for i in 0..symbols {
+        let symbol = format!("C{i}USDT");
+        let base = format!("C{i}");
+        let sign = if i % 3 == 0 { -1 } else { 1 };
+        let price = Decimal::from(1 + i % 500);
+        market.funding_rates.push(FundingRate {
+            symbol: symbol.clone(),
+            funding_rate: Decimal::new(sign * (i % 40) as i64 * 5, 5),
+            funding_time: 1_700_000_000_000,
+            mark_price: Some(price),
+        });
+        if i % 10 == 0 {
+            market.funding_rates.push(FundingRate {
+                symbol: format!("{base}USDC"),
+                funding_rate: Decimal::new((i % 7) as i64, 5),
+                funding_time: 1_700_000_000_000,
+                mark_price: Some(price),
+            });
+        }
+        let volume = Decimal::from(1_000_000 * (1 + (i * 37) % 400));
+        market.futures_tickers.push(ticker(&symbol, price, volume));
+        market
+            .spot_tickers
+            .push(ticker(&symbol, price, volume / dec!(2)));
+        let half_spread = price * Decimal::new(1 + (i % 5) as i64, 5);
+        market.book_tickers.push(BookTicker {
+            symbol: symbol.clone(),
+            bid_price: price - half_spread,
+            bid_qty: dec!(10),
+            ask_price: price + half_spread,
+            ask_qty: dec!(10),
+        });
+        market.spot_info.push(SpotSymbolInfo {
+            symbol,
+            base_asset: base.clone(),
+            quote_asset: "USDT".to_string(),
+            status: "TRADING".to_string(),
+            is_margin_trading_allowed: i % 4 != 0,
+            filters: Vec::new(),
+        });
+        if i % 5 != 0 {
+            market.margin_assets.push(MarginAsset {
+                asset: base,
+                borrowable: true,
+                collateral: true,
+                margin_interest_rate: Some(Decimal::new(1 + (i % 9) as i64, 4)),
+            });
+        }
+    }
+    market
+}
+
+fn ticker(symbol: &str, price: Decimal, quote_volume: Decimal) -> Ticker24h {
+    Ticker24h {
+        symbol: symbol.to_string(),
+        price_change: Decimal::ZERO,
+        price_change_percent: Decimal::ZERO,
+        last_price: price,
+        high_price: price,
+        low_price: price,
+        volume: quote_volume / price,
+        quote_volume,
+        open_time: 0,
+        close_time: 0,
+    }
+}
+
+fn scanner(prefilter_top_k: usize, futures_hedges: bool) -> MarketScanner {
+    MarketScanner::new(PairSelectionConfig {
+        prefilter_top_k,
+        futures_hedges,
+        min_volume_percentile: dec!(0.2),
+        ..Default::default()
+    })
+}
+
+fn bench_qualify_market(c: &mut Criterion) {
+    let mut group = c.benchmark_group("qualify_market");
+    for symbols in [100, 450, 900] {
+        let market = market(symbols);
+        for (name, scanner) in [
+            ("all", scanner(0, false)),
+            ("all_perp_hedges", scanner(0, true)),
+            ("prefilter_20", scanner(20, false)),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, symbols), &market, |b, market| {
+                b.iter(|| scanner.qualify_market(black_box(market)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_qualify_market);
+criterion_main!(benches);
@@ -0,0 +1,234 @@
+//! Borrow rate scenarios for backtests.
+//!
+//! Short spot hedges pay an hourly borrow rate that the snapshot data doesn't
+//! carry. A scenario supplies it: a base rate, an optional weekend multiple for
+//! the regular drain of lending supply, and spike windows that either replay
+//! historical rates or stress the base rate synthetically. Running the same data
+//! under harsher scenarios shows how much of the funding carry survives.
+//!
+//! ```toml
+//! base_hourly_rate = 0.00002
+//! weekend_multiplier = 2
+//!
+//! [[spikes]]
+//! start = "2024-03-16T00:00:00Z"
+//! end = "2024-03-18T00:00:00Z"
+//! symbols = ["ALTUSDT"]
+//! hourly_rate = 0.0004
+//! ```
+
+use crate::exchange::mock::DEFAULT_HOURLY_BORROW_RATE;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Utc, Weekday};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A window of elevated borrow rates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BorrowSpikeWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Futures symbols affected; empty for every symbol
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Historical hourly rate charged inside the window
+    #[serde(default)]
+    pub hourly_rate: Option<Decimal>,
+    /// Multiple of the base rate charged inside the window (synthetic stress)
+    #[serde(default)]
+    pub multiplier: Option<Decimal>,
+}
+
+impl BorrowSpikeWindow {
+    fn applies(&self, symbol: &str, at: DateTime<Utc>) -> bool {
+        at >= self.start
+            && at < self.end
+            && (self.symbols.is_empty() || self.symbols.iter().any(|s| s == symbol))
+    }
+}
+
+/// Hourly borrow rates a backtest charges short spot hedges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BorrowScenario {
+    /// Hourly rate outside spikes
+    #[serde(default = "default_base_hourly_rate")]
+    pub base_hourly_rate: Decimal,
+    /// Multiple of the base rate charged on Saturdays and Sundays (UTC)
+    #[serde(default = "default_weekend_multiplier")]
+    pub weekend_multiplier: Decimal,
+    #[serde(default)]
+    pub spikes: Vec<BorrowSpikeWindow>,
+}
+
+fn default_base_hourly_rate() -> Decimal {
+    DEFAULT_HOURLY_BORROW_RATE
+}
+
+fn default_weekend_multiplier() -> Decimal {
+    Decimal::ONE
+}
+
+impl Default for BorrowScenario {
+    fn default() -> Self {
+        Self {
+            base_hourly_rate: default_base_hourly_rate(),
+            weekend_multiplier: default_weekend_multiplier(),
+            spikes: Vec::new(),
+        }
+    }
+}
+
+impl BorrowScenario {
+    /// Load and validate a scenario from a TOML file.
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read borrow scenario {}", path))?;
+        let scenario: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse borrow scenario {}", path))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Reject negative rates and windows that can't apply.
+    pub fn validate(&self) -> Result<()> {
+        if self.base_hourly_rate < Decimal::ZERO {
+            anyhow::bail!(
+                "base_hourly_rate must be >= 0 (got {})",
+                self.base_hourly_rate
+            );
+        }
+        if self.weekend_multiplier < Decimal::ONE {
+            anyhow::bail!(
+                "weekend_multiplier must be >= 1 (got {})",
+                self.weekend_multiplier
+            );
+        }
+        for (i, spike) in self.spikes.iter().enumerate() {
+            if spike.end <= spike.start {
+                anyhow::bail!("spike #{} ends before it starts", i + 1);
+            }
+            match (spike.hourly_rate, spike.multiplier) {
+                (None, None) => {
+                    anyhow::bail!("spike #{} needs an hourly_rate or a multiplier", i + 1)
+                }
+                (Some(rate), _) if rate < Decimal::ZERO => {
+                    anyhow::bail!("spike #{} hourly_rate must be >= 0", i + 1)
+                }
+                (_, Some(multiplier)) if multiplier < Decimal::ZERO => {
+                    anyhow::bail!("spike #{} multiplier must be >= 0", i + 1)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Hourly rate charged for borrowing `symbol`'s base asset at `at`.
+    ///
+    /// The highest applicable rate wins: a spike window's historical rate (or its
+    /// multiple of the base rate) overrides the weekend rate only when higher.
+    pub fn hourly_rate(&self, symbol: &str, at: DateTime<Utc>) -> Decimal {
+        let base = if matches!(at.weekday(), Weekday::Sat | Weekday::Sun) {
+            self.base_hourly_rate * self.weekend_multiplier
+        } else {
+            self.base_hourly_rate
+        };
+        self.spikes
+            .iter()
+            .filter(|spike| spike.applies(symbol, at))
+            .filter_map(|spike| {
+                spike
+                    .hourly_rate
+                    .or_else(|| spike.multiplier.map(|m| self.base_hourly_rate * m))
+            })
+            .fold(base, Decimal::max)
+    }
+}
+
+impl fmt::Display for BorrowScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.4}%/h base, {}x weekends, {} spike window(s)",
+            self.base_hourly_rate * Decimal::ONE_HUNDRED,
+            self.weekend_multiplier.normalize(),
+            self.spikes.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_weekend_and_spike_rates() {
+        let scenario: BorrowScenario = toml::from_str(
+            r#"
+            base_hourly_rate = 0.00002
+            weekend_multiplier = 2
+
+            [[spikes]]
+            start = "2024-03-16T00:00:00Z"
+            end = "2024-03-17T00:00:00Z"
+            symbols = ["ALTUSDT"]
+            hourly_rate = 0.0004
+
+            [[spikes]]
+            start = "2024-03-13T00:00:00Z"
+            end = "2024-03-14T00:00:00Z"
+            multiplier = 5
+            "#,
+        )
+        .unwrap();
+        scenario.validate().unwrap();
+
+        // Friday, then Saturday at the weekend rate
+        assert_eq!(scenario.hourly_rate("BTCUSDT", at(15, 12)), dec!(0.00002));
+        assert_eq!(scenario.hourly_rate("BTCUSDT", at(16, 12)), dec!(0.00004));
+        // The historical spike only hits ALT, and only inside its window
+        assert_eq!(scenario.hourly_rate("ALTUSDT", at(16, 12)), dec!(0.0004));
+        assert_eq!(scenario.hourly_rate("ALTUSDT", at(17, 12)), dec!(0.00004));
+        // A synthetic spike without symbols hits everything
+        assert_eq!(scenario.hourly_rate("ETHUSDT", at(13, 0)), dec!(0.0001));
+        assert_eq!(
+            scenario.to_string(),
+            "0.0020%/h base, 2x weekends, 2 spike window(s)"
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let window = BorrowSpikeWindow {
+            start: at(16, 0),
+            end: at(17, 0),
+            symbols: Vec::new(),
+            hourly_rate: None,
+            multiplier: None,
+        };
+        let mut scenario = BorrowScenario {
+            spikes: vec![window],
+            ..Default::default()
+        };
+        assert!(scenario.validate().is_err());
+
+        scenario.spikes[0].multiplier = Some(dec!(3));
+        assert!(scenario.validate().is_ok());
+
+        scenario.spikes[0].end = at(15, 0);
+        assert!(scenario.validate().is_err());
+
+        let scenario = BorrowScenario {
+            weekend_multiplier: dec!(0.5),
+            ..Default::default()
+        };
+        assert!(scenario.validate().is_err());
+    }
+}
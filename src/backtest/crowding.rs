@@ -0,0 +1,153 @@
+//! Crowding model for backtests.
+//!
+//! Funding pays whoever takes the crowded side's opposite; arbitrage capital
+//! that does so narrows the imbalance and the rate with it. Snapshots record
+//! funding without the backtest's own positions, so a large simulated balance
+//! would collect the same rate as a small one. The crowding model dampens each
+//! symbol's funding by the share of its open interest the simulation holds:
+//!
+//! ```text
+//! rate × max(floor, 1 / (1 + sensitivity × held_notional / open_interest))
+//! ```
+//!
+//! With the default sensitivity of 10, holding 1% of a symbol's open interest
+//! costs about 9% of its funding and holding 10% halves it. Rates shrink towards
+//! zero but never flip sign.
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Funding dampening by the simulation's share of open interest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrowdingModel {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Rate compression per unit of open interest held
+    #[serde(default = "default_sensitivity")]
+    pub sensitivity: Decimal,
+    /// Share of the rate that survives however crowded (0.0-1.0)
+    #[serde(default)]
+    pub floor: Decimal,
+}
+
+fn default_sensitivity() -> Decimal {
+    Decimal::TEN
+}
+
+impl Default for CrowdingModel {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: default_sensitivity(),
+            floor: Decimal::ZERO,
+        }
+    }
+}
+
+impl CrowdingModel {
+    /// Model enabled with `sensitivity`.
+    pub fn with_sensitivity(sensitivity: Decimal) -> Self {
+        Self {
+            enabled: true,
+            sensitivity,
+            ..Default::default()
+        }
+    }
+
+    /// Reject negative sensitivities and floors outside 0-1.
+    pub fn validate(&self) -> Result<()> {
+        if self.sensitivity < Decimal::ZERO {
+            anyhow::bail!(
+                "crowding sensitivity must be >= 0 (got {})",
+                self.sensitivity
+            );
+        }
+        if self.floor < Decimal::ZERO || self.floor > Decimal::ONE {
+            anyhow::bail!("crowding floor must be within 0-1 (got {})", self.floor);
+        }
+        Ok(())
+    }
+
+    /// Share of the funding rate left with `held_notional` of a symbol whose
+    /// open interest is `open_interest` (1 when disabled or open interest is unknown).
+    pub fn retained_share(&self, held_notional: Decimal, open_interest: Decimal) -> Decimal {
+        if !self.enabled || open_interest <= Decimal::ZERO || held_notional <= Decimal::ZERO {
+            return Decimal::ONE;
+        }
+        let crowding = self.sensitivity * held_notional / open_interest;
+        (Decimal::ONE / (Decimal::ONE + crowding)).max(self.floor)
+    }
+
+    /// `rate` dampened by `held_notional` of `open_interest`.
+    pub fn dampen(&self, rate: Decimal, held_notional: Decimal, open_interest: Decimal) -> Decimal {
+        rate * self.retained_share(held_notional, open_interest)
+    }
+}
+
+impl fmt::Display for CrowdingModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.enabled {
+            return write!(f, "off");
+        }
+        write!(
+            f,
+            "sensitivity {} to open interest held, floor {:.0}%",
+            self.sensitivity.normalize(),
+            self.floor * Decimal::ONE_HUNDRED
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_dampening_grows_with_share_held() {
+        let model = CrowdingModel::with_sensitivity(dec!(10));
+        let oi = dec!(100_000_000);
+
+        assert_eq!(model.dampen(dec!(0.001), Decimal::ZERO, oi), dec!(0.001));
+        // 10% of open interest halves the rate, negative rates included
+        assert_eq!(
+            model.dampen(dec!(0.001), dec!(10_000_000), oi),
+            dec!(0.0005)
+        );
+        assert_eq!(
+            model.dampen(dec!(-0.001), dec!(10_000_000), oi),
+            dec!(-0.0005)
+        );
+        // 30% leaves a quarter
+        assert_eq!(model.retained_share(dec!(30_000_000), oi), dec!(0.25));
+        // Unknown open interest is not dampened
+        assert_eq!(
+            model.retained_share(dec!(30_000_000), Decimal::ZERO),
+            Decimal::ONE
+        );
+
+        let floored = CrowdingModel {
+            floor: dec!(0.4),
+            ..model.clone()
+        };
+        assert_eq!(floored.retained_share(dec!(30_000_000), oi), dec!(0.4));
+
+        let off = CrowdingModel::default();
+        assert_eq!(off.retained_share(dec!(30_000_000), oi), Decimal::ONE);
+        assert_eq!(off.to_string(), "off");
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(CrowdingModel::default().validate().is_ok());
+        let negative = CrowdingModel::with_sensitivity(dec!(-1));
+        assert!(negative.validate().is_err());
+        let floor = CrowdingModel {
+            floor: dec!(1.5),
+            ..Default::default()
+        };
+        assert!(floor.validate().is_err());
+    }
+}
@@ -1,13 +1,20 @@
 //! Historical data loading for backtesting.
 //!
-//! Provides CSV import and live data collection capabilities.
+//! Provides CSV import (loaded whole or streamed through an index) and live data
+//! collection capabilities.
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
 
 /// A snapshot of market data at a specific point in time.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +77,9 @@ impl SymbolData {
     }
 }
 
+/// Snapshots in time order, produced one at a time.
+pub type SnapshotIter = Box<dyn Iterator<Item = Result<MarketSnapshot>> + Send>;
+
 /// Trait for loading historical market data.
 pub trait DataLoader: Send + Sync {
     /// Load all snapshots in the given time range.
@@ -79,6 +89,16 @@ pub trait DataLoader: Send + Sync {
         end: DateTime<Utc>,
     ) -> Result<Vec<MarketSnapshot>>;
 
+    /// Iterate the snapshots in the given time range.
+    ///
+    /// Defaults to loading them all; loaders backed by a file override it to
+    /// read snapshots as they are reached.
+    fn stream_snapshots(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<SnapshotIter> {
+        Ok(Box::new(
+            self.load_snapshots(start, end)?.into_iter().map(Ok),
+        ))
+    }
+
     /// Get the available date range in the data.
     fn available_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)>;
 
@@ -86,6 +106,29 @@ pub trait DataLoader: Send + Sync {
     fn available_symbols(&self) -> Vec<String>;
 }
 
+/// A loader shared between backtests, such as the runs of a sweep.
+impl<D: DataLoader + ?Sized> DataLoader for Arc<D> {
+    fn load_snapshots(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<MarketSnapshot>> {
+        (**self).load_snapshots(start, end)
+    }
+
+    fn stream_snapshots(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<SnapshotIter> {
+        (**self).stream_snapshots(start, end)
+    }
+
+    fn available_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        (**self).available_range()
+    }
+
+    fn available_symbols(&self) -> Vec<String> {
+        (**self).available_symbols()
+    }
+}
+
 /// CSV data loader for historical backtesting.
 ///
 /// Expected CSV format:
@@ -93,14 +136,27 @@ pub trait DataLoader: Send + Sync {
 /// timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
 /// 2024-01-01T00:00:00Z,BTCUSDT,0.0001,42000.50,1500000000,0.0001,800000000
 /// ```
+///
+/// [`CsvDataLoader::new`] parses the whole file up front; [`CsvDataLoader::streaming`]
+/// reads it as a backtest goes, for files too large to hold in memory. Either way
+/// clones share the data, so parallel sweep runs hold it once.
 #[derive(Clone)]
 pub struct CsvDataLoader {
-    /// Loaded snapshots indexed by timestamp
-    snapshots: Vec<MarketSnapshot>,
+    /// Where snapshots are read from
+    source: SnapshotSource,
     /// All available symbols
     symbols: Vec<String>,
 }
 
+/// Where a [`CsvDataLoader`] reads its snapshots from.
+#[derive(Clone)]
+enum SnapshotSource {
+    /// Every snapshot, parsed up front and sorted by timestamp
+    Memory(Arc<[MarketSnapshot]>),
+    /// The CSV file itself, read on demand through its index
+    File(Arc<CsvIndex>),
+}
+
 impl CsvDataLoader {
     /// Load data from a CSV file.
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -143,14 +199,7 @@ impl CsvDataLoader {
             by_timestamp
                 .entry(row.timestamp)
                 .or_default()
-                .push(SymbolData {
-                    symbol: row.symbol,
-                    funding_rate: row.funding_rate,
-                    price: row.price,
-                    volume_24h: row.volume_24h,
-                    spread: row.spread,
-                    open_interest: row.open_interest,
-                });
+                .push(row.into_symbol_data());
         }
 
         // Convert to sorted snapshots
@@ -164,11 +213,30 @@ impl CsvDataLoader {
         let mut symbols: Vec<String> = all_symbols.into_iter().collect();
         symbols.sort();
 
-        Ok(Self { snapshots, symbols })
+        Ok(Self {
+            source: SnapshotSource::Memory(snapshots.into()),
+            symbols,
+        })
+    }
+
+    /// Index a CSV file to stream its snapshots instead of loading them.
+    ///
+    /// A run then holds one snapshot at a time however long the file is, so
+    /// year-long minute data fits in a sweep's memory. Rows must be sorted by
+    /// timestamp. The index (each snapshot's timestamp and byte offset) is saved
+    /// next to the file as `<file>.idx` and rebuilt whenever the file's size or
+    /// modification time no longer match it.
+    pub fn streaming<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let index = CsvIndex::open(path.as_ref())?;
+        Ok(Self {
+            symbols: index.symbols.clone(),
+            source: SnapshotSource::File(Arc::new(index)),
+        })
     }
 
     /// Create a loader from in-memory snapshots.
-    pub fn from_snapshots(snapshots: Vec<MarketSnapshot>) -> Self {
+    pub fn from_snapshots(mut snapshots: Vec<MarketSnapshot>) -> Self {
+        snapshots.sort_by_key(|s| s.timestamp);
         let mut symbols: std::collections::HashSet<String> = std::collections::HashSet::new();
         for snapshot in &snapshots {
             for sym in &snapshot.symbols {
@@ -179,17 +247,53 @@ impl CsvDataLoader {
         let mut symbols: Vec<String> = symbols.into_iter().collect();
         symbols.sort();
 
-        Self { snapshots, symbols }
+        Self {
+            source: SnapshotSource::Memory(snapshots.into()),
+            symbols,
+        }
     }
 
     /// Get total number of snapshots.
     pub fn len(&self) -> usize {
-        self.snapshots.len()
+        match &self.source {
+            SnapshotSource::Memory(snapshots) => snapshots.len(),
+            SnapshotSource::File(index) => index.entries.len(),
+        }
     }
 
     /// Check if the loader has no data.
     pub fn is_empty(&self) -> bool {
-        self.snapshots.is_empty()
+        self.len() == 0
+    }
+
+    /// Check whether snapshots are read from the file as they are reached.
+    pub fn is_streaming(&self) -> bool {
+        matches!(self.source, SnapshotSource::File(_))
+    }
+}
+
+/// Parse CSV content, for fixtures and examples that don't need a file.
+///
+/// ```
+/// use funding_fee_farmer::backtest::{CsvDataLoader, DataLoader};
+///
+/// let loader: CsvDataLoader = "\
+/// timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
+/// 2024-01-01T00:00:00Z,BTCUSDT,0.0001,42000.50,1500000000,0.0001,800000000
+/// 2024-01-01T00:00:00Z,ETHUSDT,0.0002,2300,900000000,0.0001,400000000
+/// 2024-01-01T01:00:00Z,BTCUSDT,0.0001,42100,1500000000,0.0001,800000000
+/// "
+/// .parse()
+/// .unwrap();
+///
+/// assert_eq!(loader.len(), 2);
+/// assert_eq!(loader.available_symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+/// ```
+impl FromStr for CsvDataLoader {
+    type Err = anyhow::Error;
+
+    fn from_str(content: &str) -> Result<Self> {
+        Self::from_csv_content(content)
     }
 }
 
@@ -199,8 +303,12 @@ impl DataLoader for CsvDataLoader {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
     ) -> Result<Vec<MarketSnapshot>> {
-        let filtered: Vec<MarketSnapshot> = self
-            .snapshots
+        let snapshots = match &self.source {
+            SnapshotSource::Memory(snapshots) => snapshots,
+            SnapshotSource::File(index) => return index.stream(start, end)?.collect(),
+        };
+
+        let filtered: Vec<MarketSnapshot> = snapshots
             .iter()
             .filter(|s| s.timestamp >= start && s.timestamp <= end)
             .cloned()
@@ -209,14 +317,28 @@ impl DataLoader for CsvDataLoader {
         Ok(filtered)
     }
 
-    fn available_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
-        if self.snapshots.is_empty() {
-            return None;
+    /// Snapshots held in memory are copied out one at a time, so a run never
+    /// holds more than the one it is stepping besides the shared data.
+    fn stream_snapshots(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<SnapshotIter> {
+        match &self.source {
+            SnapshotSource::Memory(snapshots) => Ok(Box::new(SharedSnapshots {
+                snapshots: Arc::clone(snapshots),
+                next: snapshots.partition_point(|s| s.timestamp < start),
+                end,
+            })),
+            SnapshotSource::File(index) => index.stream(start, end),
         }
+    }
 
-        let start = self.snapshots.first().unwrap().timestamp;
-        let end = self.snapshots.last().unwrap().timestamp;
-        Some((start, end))
+    fn available_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        match &self.source {
+            SnapshotSource::Memory(snapshots) => {
+                Some((snapshots.first()?.timestamp, snapshots.last()?.timestamp))
+            }
+            SnapshotSource::File(index) => {
+                Some((index.entries.first()?.0, index.entries.last()?.0))
+            }
+        }
     }
 
     fn available_symbols(&self) -> Vec<String> {
@@ -224,6 +346,27 @@ impl DataLoader for CsvDataLoader {
     }
 }
 
+/// Snapshots in `[start, end]` of a shared in-memory dataset, cloned as reached.
+struct SharedSnapshots {
+    snapshots: Arc<[MarketSnapshot]>,
+    /// Index of the next snapshot, starting at the first at or after `start`
+    next: usize,
+    end: DateTime<Utc>,
+}
+
+impl Iterator for SharedSnapshots {
+    type Item = Result<MarketSnapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let snapshot = self.snapshots.get(self.next)?;
+        if snapshot.timestamp > self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(Ok(snapshot.clone()))
+    }
+}
+
 /// Internal struct for parsing CSV rows.
 #[derive(Debug)]
 struct CsvRow {
@@ -274,6 +417,292 @@ impl CsvRow {
                 .with_context(|| format!("Invalid open_interest: {}", parts[6]))?,
         })
     }
+
+    fn into_symbol_data(self) -> SymbolData {
+        SymbolData {
+            symbol: self.symbol,
+            funding_rate: self.funding_rate,
+            price: self.price,
+            volume_24h: self.volume_24h,
+            spread: self.spread,
+            open_interest: self.open_interest,
+        }
+    }
+}
+
+/// Size and modification time of a CSV file, to tell whether an index still
+/// describes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified_nanos: u128,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Failed to read CSV file: {}", path.display()))?;
+        let modified_nanos = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        Ok(Self {
+            len: metadata.len(),
+            modified_nanos,
+        })
+    }
+}
+
+/// Byte offsets of each snapshot in a timestamp-sorted CSV file.
+///
+/// Saved as text: a header line with the file's stamp, a line of symbols, then
+/// one `<timestamp millis>,<offset>` line per snapshot.
+struct CsvIndex {
+    path: PathBuf,
+    stamp: FileStamp,
+    /// Timestamp and first byte of each snapshot's rows, in time order
+    entries: Vec<(DateTime<Utc>, u64)>,
+    symbols: Vec<String>,
+}
+
+impl CsvIndex {
+    const HEADER: &'static str = "fff-csv-index 1";
+
+    /// Read the saved index of a file, or build (and save) it when missing or stale.
+    fn open(path: &Path) -> Result<Self> {
+        let stamp = FileStamp::of(path)?;
+        let index_path = Self::index_path(path);
+
+        match Self::read(path, &index_path, stamp) {
+            Ok(index) => return Ok(index),
+            Err(e) if index_path.exists() => {
+                debug!("Rebuilding CSV index {}: {:#}", index_path.display(), e);
+            }
+            Err(_) => {}
+        }
+
+        let index = Self::build(path, stamp)?;
+        if let Err(e) = index.write(&index_path) {
+            warn!(
+                "Could not save CSV index {} (indexing again next run): {:#}",
+                index_path.display(),
+                e
+            );
+        }
+        Ok(index)
+    }
+
+    fn index_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".idx");
+        PathBuf::from(name)
+    }
+
+    fn read(path: &Path, index_path: &Path, stamp: FileStamp) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(index_path)?).lines();
+
+        let header = lines.next().context("empty index")??;
+        let expected = format!("{} {} {}", Self::HEADER, stamp.len, stamp.modified_nanos);
+        anyhow::ensure!(
+            header == expected,
+            "index is for another version of the file"
+        );
+
+        let symbols = lines.next().context("index has no symbol line")??;
+        let symbols = symbols.split(',').map(str::to_string).collect();
+
+        let entries = lines
+            .map(|line| {
+                let line = line?;
+                let (millis, offset) = line
+                    .split_once(',')
+                    .with_context(|| format!("Invalid index entry: {}", line))?;
+                let timestamp = DateTime::from_timestamp_millis(millis.parse()?)
+                    .with_context(|| format!("Invalid index timestamp: {}", millis))?;
+                Ok((timestamp, offset.parse()?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        anyhow::ensure!(!entries.is_empty(), "index has no entries");
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            stamp,
+            entries,
+            symbols,
+        })
+    }
+
+    /// Scan the file once, validating every row and noting where each snapshot starts.
+    fn build(path: &Path, stamp: FileStamp) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to read CSV file: {}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let mut entries: Vec<(DateTime<Utc>, u64)> = Vec::new();
+        let mut symbols: HashSet<String> = HashSet::new();
+        let mut line = String::new();
+        let mut offset = 0u64;
+
+        for line_num in 1.. {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 {
+                break;
+            }
+            let row_offset = offset;
+            offset += read as u64;
+
+            let text = line.trim();
+            if text.is_empty() || (line_num == 1 && text.starts_with("timestamp")) {
+                continue;
+            }
+
+            let row = CsvRow::parse(text)
+                .with_context(|| format!("Failed to parse line {}: {}", line_num, text))?;
+            match entries.last() {
+                Some(&(last, _)) if row.timestamp < last => anyhow::bail!(
+                    "Line {} ({}) comes after {}; sort the file by timestamp to stream it",
+                    line_num,
+                    row.timestamp,
+                    last
+                ),
+                Some(&(last, _)) if row.timestamp == last => {}
+                _ => entries.push((row.timestamp, row_offset)),
+            }
+            if !symbols.contains(&row.symbol) {
+                symbols.insert(row.symbol);
+            }
+        }
+
+        if entries.is_empty() {
+            anyhow::bail!("CSV file contains no data rows");
+        }
+
+        let mut symbols: Vec<String> = symbols.into_iter().collect();
+        symbols.sort();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            stamp,
+            entries,
+            symbols,
+        })
+    }
+
+    fn write(&self, index_path: &Path) -> Result<()> {
+        let mut out = BufWriter::new(File::create(index_path)?);
+        writeln!(
+            out,
+            "{} {} {}",
+            Self::HEADER,
+            self.stamp.len,
+            self.stamp.modified_nanos
+        )?;
+        writeln!(out, "{}", self.symbols.join(","))?;
+        for (timestamp, offset) in &self.entries {
+            writeln!(out, "{},{}", timestamp.timestamp_millis(), offset)?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Read the snapshots in `[start, end]` from the file, starting at the first one's offset.
+    fn stream(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<SnapshotIter> {
+        anyhow::ensure!(
+            FileStamp::of(&self.path)? == self.stamp,
+            "CSV file {} changed since it was indexed",
+            self.path.display()
+        );
+
+        let first = self
+            .entries
+            .partition_point(|&(timestamp, _)| timestamp < start);
+        let Some(&(_, offset)) = self.entries.get(first) else {
+            return Ok(Box::new(std::iter::empty()));
+        };
+
+        let mut file = File::open(&self.path)
+            .with_context(|| format!("Failed to read CSV file: {}", self.path.display()))?;
+        file.seek(SeekFrom::Start(offset))?;
+
+        Ok(Box::new(CsvSnapshotReader {
+            reader: BufReader::new(file),
+            end,
+            line: String::new(),
+            pending: None,
+            done: false,
+        }))
+    }
+}
+
+/// Groups consecutive rows of a sorted CSV file into snapshots until `end`.
+struct CsvSnapshotReader {
+    reader: BufReader<File>,
+    end: DateTime<Utc>,
+    line: String,
+    /// First row of the next snapshot, read while finishing the previous one
+    pending: Option<CsvRow>,
+    done: bool,
+}
+
+impl CsvSnapshotReader {
+    fn read_row(&mut self) -> Result<Option<CsvRow>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            let text = self.line.trim();
+            if !text.is_empty() {
+                return CsvRow::parse(text).map(Some);
+            }
+        }
+    }
+
+    fn read_snapshot(&mut self) -> Result<Option<MarketSnapshot>> {
+        let first = match self.pending.take() {
+            Some(row) => row,
+            None => match self.read_row()? {
+                Some(row) => row,
+                None => return Ok(None),
+            },
+        };
+        if first.timestamp > self.end {
+            return Ok(None);
+        }
+
+        let mut snapshot = MarketSnapshot::new(first.timestamp);
+        snapshot.symbols.push(first.into_symbol_data());
+        while let Some(row) = self.read_row()? {
+            if row.timestamp != snapshot.timestamp {
+                self.pending = Some(row);
+                break;
+            }
+            snapshot.symbols.push(row.into_symbol_data());
+        }
+        Ok(Some(snapshot))
+    }
+}
+
+impl Iterator for CsvSnapshotReader {
+    type Item = Result<MarketSnapshot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_snapshot() {
+            Ok(Some(snapshot)) => Some(Ok(snapshot)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
 }
 
 /// Live data collector for gathering data from the real Binance API.
@@ -384,5 +813,147 @@ mod tests {
         let filtered = loader.load_snapshots(start, end).unwrap();
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].timestamp.day(), 2);
+
+        // Streaming from memory starts and stops at the same snapshots
+        let streamed = loader
+            .stream_snapshots(start, end)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(streamed.len(), 1);
+        assert_eq!(streamed[0].timestamp.day(), 2);
+    }
+
+    /// A scratch directory for CSV files, removed by the caller.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fff-data-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const HOURLY_CSV: &str = "\
+timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
+2024-01-01T00:00:00Z,BTCUSDT,0.0001,42000,1500000000,0.0001,800000000
+2024-01-01T00:00:00Z,ETHUSDT,0.0002,2300,900000000,0.0001,400000000
+2024-01-01T01:00:00Z,BTCUSDT,0.0003,42100,1500000000,0.0001,800000000
+
+2024-01-01T02:00:00Z,BTCUSDT,0.0004,42200,1500000000,0.0001,800000000
+2024-01-01T02:00:00Z,ETHUSDT,0.0005,2310,900000000,0.0001,400000000
+2024-01-01T03:00:00Z,ETHUSDT,0.0006,2320,900000000,0.0001,400000000
+";
+
+    #[test]
+    fn test_streaming_matches_loading_whole_file() {
+        let dir = scratch_dir("stream");
+        let path = dir.join("hourly.csv");
+        std::fs::write(&path, HOURLY_CSV).unwrap();
+
+        let loaded = CsvDataLoader::from_csv_content(HOURLY_CSV).unwrap();
+        let streamed = CsvDataLoader::streaming(&path).unwrap();
+        assert!(streamed.is_streaming());
+        assert_eq!(streamed.len(), 4);
+        assert_eq!(streamed.available_symbols(), loaded.available_symbols());
+        assert_eq!(streamed.available_range(), loaded.available_range());
+
+        // Ranges starting between snapshots, on one, and past the data
+        let hour = |h| Utc.with_ymd_and_hms(2024, 1, 1, h, 0, 0).unwrap();
+        for (start, end) in [
+            (hour(0), hour(23)),
+            (hour(1), hour(2)),
+            (hour(0) + chrono::Duration::minutes(30), hour(3)),
+            (hour(5), hour(6)),
+        ] {
+            let expected = loaded.load_snapshots(start, end).unwrap();
+            let actual = streamed
+                .stream_snapshots(start, end)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+
+            assert_eq!(actual.len(), expected.len());
+            for (a, e) in actual.iter().zip(&expected) {
+                assert_eq!(a.timestamp, e.timestamp);
+                assert_eq!(a.funding_rates(), e.funding_rates());
+                assert_eq!(a.prices(), e.prices());
+            }
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_index_is_reused_until_the_file_changes() {
+        let dir = scratch_dir("index");
+        let path = dir.join("hourly.csv");
+        std::fs::write(&path, HOURLY_CSV).unwrap();
+
+        CsvDataLoader::streaming(&path).unwrap();
+        let index_path = dir.join("hourly.csv.idx");
+        let index = std::fs::read_to_string(&index_path).unwrap();
+        assert!(index.starts_with(CsvIndex::HEADER));
+        assert_eq!(index.lines().count(), 2 + 4);
+        assert_eq!(CsvDataLoader::streaming(&path).unwrap().len(), 4);
+
+        // A longer file no longer matches the saved index
+        let longer = format!(
+            "{}2024-01-01T04:00:00Z,BTCUSDT,0.0007,42300,1500000000,0.0001,800000000\n",
+            HOURLY_CSV
+        );
+        std::fs::write(&path, longer).unwrap();
+        let loader = CsvDataLoader::streaming(&path).unwrap();
+        assert_eq!(loader.len(), 5);
+
+        let end = Utc.with_ymd_and_hms(2024, 1, 1, 4, 0, 0).unwrap();
+        let last = loader.load_snapshots(end, end).unwrap().pop().unwrap();
+        assert_eq!(
+            last.get_symbol("BTCUSDT").unwrap().funding_rate,
+            dec!(0.0007)
+        );
+
+        // Rewriting it under an open loader is caught rather than misread
+        std::fs::write(&path, HOURLY_CSV).unwrap();
+        assert!(loader.stream_snapshots(end, end).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_requires_sorted_rows() {
+        let dir = scratch_dir("unsorted");
+        let path = dir.join("unsorted.csv");
+        std::fs::write(
+            &path,
+            "\
+timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
+2024-01-02T00:00:00Z,BTCUSDT,0.0001,42000,1500000000,0.0001,800000000
+2024-01-01T00:00:00Z,BTCUSDT,0.0001,42500,1500000000,0.0001,800000000
+",
+        )
+        .unwrap();
+
+        let err = CsvDataLoader::streaming(&path).err().unwrap();
+        assert!(format!("{:#}", err).contains("sort the file by timestamp"));
+        assert!(!dir.join("unsorted.csv.idx").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_clones_share_loaded_snapshots() {
+        let loader = CsvDataLoader::from_csv_content(HOURLY_CSV).unwrap();
+        let clone = loader.clone();
+        match (&loader.source, &clone.source) {
+            (SnapshotSource::Memory(a), SnapshotSource::Memory(b)) => assert!(Arc::ptr_eq(a, b)),
+            _ => panic!("expected in-memory snapshots"),
+        }
+
+        // Streamed snapshots are copies of the range only
+        let hour = |h| Utc.with_ymd_and_hms(2024, 1, 1, h, 0, 0).unwrap();
+        let streamed: Vec<DateTime<Utc>> = clone
+            .stream_snapshots(hour(1), hour(2))
+            .unwrap()
+            .map(|s| s.unwrap().timestamp)
+            .collect();
+        assert_eq!(streamed, vec![hour(1), hour(2)]);
     }
 }
@@ -2,17 +2,23 @@
 //!
 //! Replays historical market data through the trading strategy.
 
-use crate::backtest::metrics::{BacktestMetrics, EquityPoint};
-use crate::backtest::{next_funding_time, BacktestConfig, DataLoader, MarketSnapshot};
+use crate::backtest::metrics::{
+    BacktestMetrics, DrawdownAttributor, EquityPoint, PnlSources, RegimeAttributor,
+};
+use crate::backtest::{BacktestConfig, DataLoader, MarketSnapshot};
 use crate::config::Config;
 use crate::exchange::mock::MockTradingState;
-use crate::exchange::{MockBinanceClient, QualifiedPair};
-use crate::strategy::CapitalAllocator;
+use crate::exchange::{
+    FundingCalendar, HedgeInstrument, MockBinanceClient, OrderType, QualifiedPair, TimeInForce,
+    Venue,
+};
+use crate::strategy::{volume_floor, CapitalAllocator};
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::{debug, info};
 
 /// Result of a single simulation step.
@@ -67,13 +73,23 @@ impl BacktestResult {
 
     /// Get a summary string.
     pub fn summary(&self) -> String {
+        let fees = self.config.execution.fee_schedule();
         format!(
-            "{}\n\nBacktest Period: {} to {}\nSnapshots: {}\nFunding Events: {}",
+            "{}\n\nBacktest Period: {} to {}\nSnapshots: {}\nFunding Events: {}\nFees: {:.3}% maker / {:.3}% taker, {} entries\nBorrow: {}\nCrowding: {}",
             self.metrics.summary(),
             self.start_time.format("%Y-%m-%d"),
             self.end_time.format("%Y-%m-%d"),
             self.snapshots_processed,
             self.funding_events,
+            fees.maker_rate * dec!(100),
+            fees.taker_rate * dec!(100),
+            if self.backtest_config.maker_entries {
+                "maker"
+            } else {
+                "taker"
+            },
+            self.backtest_config.borrow,
+            self.backtest_config.crowding,
         )
     }
 }
@@ -87,6 +103,8 @@ pub struct BacktestEngine<D: DataLoader> {
     allocator: CapitalAllocator,
     current_time: DateTime<Utc>,
     next_funding: DateTime<Utc>,
+    /// Per-symbol settlement schedules
+    funding_calendar: FundingCalendar,
 
     // Tracking for metrics
     equity_curve: Vec<EquityPoint>,
@@ -97,13 +115,19 @@ pub struct BacktestEngine<D: DataLoader> {
     positions_closed: u64,
     winning_positions: u64,
     total_position_hours: f64,
+    /// Cumulative PnL per symbol by source
+    pnl_sources: HashMap<String, PnlSources>,
+    drawdown_attributor: DrawdownAttributor,
+    regime_attributor: RegimeAttributor,
 }
 
 impl<D: DataLoader> BacktestEngine<D> {
     /// Create a new backtest engine.
     pub fn new(data_loader: D, config: Config, backtest_config: BacktestConfig) -> Self {
         let initial_balance = backtest_config.initial_balance;
-        let mock_client = MockBinanceClient::new(initial_balance);
+        let high_funding_rate = backtest_config.high_funding_rate;
+        let mut mock_client = MockBinanceClient::new(initial_balance);
+        mock_client.set_fee_schedule(config.execution.fee_schedule());
 
         let allocator = CapitalAllocator::new(
             config.capital.clone(),
@@ -119,6 +143,7 @@ impl<D: DataLoader> BacktestEngine<D> {
             allocator,
             current_time: Utc::now(),
             next_funding: Utc::now(),
+            funding_calendar: FundingCalendar::new(),
             equity_curve: Vec::new(),
             peak_equity: initial_balance,
             total_funding: Decimal::ZERO,
@@ -127,9 +152,25 @@ impl<D: DataLoader> BacktestEngine<D> {
             positions_closed: 0,
             winning_positions: 0,
             total_position_hours: 0.0,
+            pnl_sources: HashMap::new(),
+            drawdown_attributor: DrawdownAttributor::new(initial_balance),
+            regime_attributor: RegimeAttributor::new(high_funding_rate),
         }
     }
 
+    /// Settle symbols on their own schedules instead of the 8h default.
+    pub fn with_funding_calendar(mut self, calendar: FundingCalendar) -> Self {
+        self.funding_calendar = calendar;
+        self
+    }
+
+    /// First funding settlement of any symbol at or after `from`.
+    fn next_funding_time(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        self.funding_calendar
+            .finest_schedule(Venue::Binance)
+            .settlement_at_or_after(from)
+    }
+
     /// Run the backtest from start to end time.
     pub async fn run(
         &mut self,
@@ -142,17 +183,17 @@ impl<D: DataLoader> BacktestEngine<D> {
             end.format("%Y-%m-%d %H:%M")
         );
 
-        // Load historical data
-        let snapshots = self.data_loader.load_snapshots(start, end)?;
-        if snapshots.is_empty() {
-            anyhow::bail!("No data available for the specified time range");
-        }
-
-        info!("Loaded {} snapshots", snapshots.len());
+        // Stream historical data; only the snapshot being stepped is held
+        let mut snapshots = self.data_loader.stream_snapshots(start, end)?;
+        let first = match snapshots.next() {
+            Some(snapshot) => snapshot?,
+            None => anyhow::bail!("No data available for the specified time range"),
+        };
+        let span_secs = (end - first.timestamp).num_seconds().max(1) as f64;
 
         // Initialize time tracking
-        self.current_time = snapshots[0].timestamp;
-        self.next_funding = next_funding_time(self.current_time);
+        self.current_time = first.timestamp;
+        self.next_funding = self.next_funding_time(self.current_time);
         self.peak_equity = self.backtest_config.initial_balance;
 
         // Reset tracking
@@ -163,13 +204,20 @@ impl<D: DataLoader> BacktestEngine<D> {
         self.positions_closed = 0;
         self.winning_positions = 0;
         self.total_position_hours = 0.0;
+        self.pnl_sources.clear();
+        self.drawdown_attributor = DrawdownAttributor::new(self.backtest_config.initial_balance);
+        self.regime_attributor = RegimeAttributor::new(self.backtest_config.high_funding_rate);
 
         // Process each snapshot
-        for (i, snapshot) in snapshots.iter().enumerate() {
+        let mut snapshots_processed = 0;
+        let first_time = first.timestamp;
+        for (i, snapshot) in std::iter::once(Ok(first)).chain(snapshots).enumerate() {
+            let snapshot = snapshot?;
             self.current_time = snapshot.timestamp;
+            snapshots_processed += 1;
 
             // Step the simulation
-            let step_result = self.step(snapshot).await?;
+            let step_result = self.step(&snapshot).await?;
 
             // Record equity point
             if self.backtest_config.record_equity_curve {
@@ -183,6 +231,12 @@ impl<D: DataLoader> BacktestEngine<D> {
                 self.equity_curve.push(point);
             }
 
+            self.drawdown_attributor.observe(
+                step_result.timestamp,
+                step_result.total_equity,
+                &self.pnl_sources,
+            );
+
             // Update peak equity
             if step_result.total_equity > self.peak_equity {
                 self.peak_equity = step_result.total_equity;
@@ -191,15 +245,16 @@ impl<D: DataLoader> BacktestEngine<D> {
             // Progress logging
             if i % 100 == 0 {
                 debug!(
-                    "Progress: {}/{} ({:.1}%), Equity: ${:.2}",
+                    "Progress: {} snapshots ({:.1}%), Equity: ${:.2}",
                     i,
-                    snapshots.len(),
-                    (i as f64 / snapshots.len() as f64) * 100.0,
+                    ((snapshot.timestamp - first_time).num_seconds() as f64 / span_secs) * 100.0,
                     step_result.total_equity
                 );
             }
         }
 
+        info!("Processed {} snapshots", snapshots_processed);
+
         // Get final state
         let final_state = self.mock_client.get_state().await;
 
@@ -214,6 +269,21 @@ impl<D: DataLoader> BacktestEngine<D> {
             self.positions_closed,
             self.winning_positions,
             self.total_position_hours,
+            self.config.risk_free.annual_rate,
+        )
+        .with_drawdown_attribution(
+            std::mem::replace(
+                &mut self.drawdown_attributor,
+                DrawdownAttributor::new(self.backtest_config.initial_balance),
+            )
+            .finish(),
+        )
+        .with_regime_attribution(
+            std::mem::replace(
+                &mut self.regime_attributor,
+                RegimeAttributor::new(self.backtest_config.high_funding_rate),
+            )
+            .finish(),
         );
 
         info!(
@@ -228,13 +298,22 @@ impl<D: DataLoader> BacktestEngine<D> {
             equity_curve: self.equity_curve.clone(),
             start_time: start,
             end_time: end,
-            snapshots_processed: snapshots.len(),
+            snapshots_processed,
             funding_events: self.funding_events,
         })
     }
 
     /// Process a single time step.
     async fn step(&mut self, snapshot: &MarketSnapshot) -> Result<StepResult> {
+        // 0. Funding compresses under the positions already held
+        let crowded;
+        let snapshot = if self.backtest_config.crowding.enabled {
+            crowded = self.crowd(snapshot).await;
+            &crowded
+        } else {
+            snapshot
+        };
+
         // 1. Update market data in mock client
         self.mock_client
             .set_market_data(snapshot.funding_rates(), snapshot.prices())
@@ -244,13 +323,22 @@ impl<D: DataLoader> BacktestEngine<D> {
         let mut funding_collected = Decimal::ZERO;
         if self.current_time >= self.next_funding {
             funding_collected = self.process_funding().await?;
-            self.next_funding = next_funding_time(self.current_time + Duration::seconds(1));
+            self.next_funding = self.next_funding_time(self.current_time + Duration::seconds(1));
         }
 
-        // 3. Accrue interest (proportional to time since last step)
+        // 3. Accrue interest at the scenario's rates (proportional to time since last step)
+        for symbol in &snapshot.symbols {
+            let rate = self
+                .backtest_config
+                .borrow
+                .hourly_rate(&symbol.symbol, self.current_time);
+            self.mock_client.set_borrow_rate(&symbol.symbol, rate).await;
+        }
         let time_step_hours = self.backtest_config.time_step_minutes as f64 / 60.0;
         let interest_hours = Decimal::from_f64_retain(time_step_hours).unwrap_or(dec!(1));
-        self.mock_client.accrue_interest(interest_hours).await;
+        for (symbol, interest) in self.mock_client.accrue_interest(interest_hours).await {
+            self.pnl_sources.entry(symbol).or_default().interest -= interest;
+        }
 
         // 4. Run strategy (simplified - just allocation for now)
         self.run_strategy_step(snapshot).await?;
@@ -259,6 +347,24 @@ impl<D: DataLoader> BacktestEngine<D> {
         let state = self.mock_client.get_state().await;
         let (_, unrealized_pnl) = self.mock_client.calculate_pnl().await;
         let total_equity = state.balance + unrealized_pnl;
+        let mut regime_rates = HashMap::new();
+        for (symbol, position) in &state.positions {
+            if let Some(data) = snapshot.get_symbol(symbol) {
+                self.pnl_sources.entry(symbol.clone()).or_default().basis = position.futures_qty
+                    * (data.price - position.futures_entry_price)
+                    + position.spot_qty * (data.price - position.spot_entry_price);
+                // Short futures are paid positive funding, long futures negative
+                let paid = if position.futures_qty < Decimal::ZERO {
+                    data.funding_rate
+                } else {
+                    -data.funding_rate
+                };
+                let schedule = self.funding_calendar.schedule(Venue::Binance, symbol);
+                regime_rates.insert(symbol.clone(), schedule.rate_per_hours(paid, 8));
+            }
+        }
+        self.regime_attributor
+            .observe(time_step_hours, &regime_rates, &self.pnl_sources);
 
         Ok(StepResult {
             timestamp: self.current_time,
@@ -270,10 +376,45 @@ impl<D: DataLoader> BacktestEngine<D> {
         })
     }
 
-    /// Process funding collection at funding times.
+    /// `snapshot` with each symbol's funding dampened by the notional held in it.
+    async fn crowd(&self, snapshot: &MarketSnapshot) -> MarketSnapshot {
+        let positions = self.mock_client.get_state().await.positions;
+        let mut crowded = snapshot.clone();
+        for data in &mut crowded.symbols {
+            let held = positions
+                .get(&data.symbol)
+                .map_or(Decimal::ZERO, |p| p.futures_qty.abs() * data.price);
+            data.funding_rate =
+                self.backtest_config
+                    .crowding
+                    .dampen(data.funding_rate, held, data.open_interest);
+        }
+        crowded
+    }
+
+    /// Process funding collection for the symbols settling at `next_funding`.
     async fn process_funding(&mut self) -> Result<Decimal> {
-        let per_position_funding = self.mock_client.collect_funding().await;
+        let settlement = self.next_funding;
+        let settling: Vec<String> = self
+            .mock_client
+            .get_state()
+            .await
+            .positions
+            .into_keys()
+            .filter(|symbol| {
+                self.funding_calendar
+                    .schedule(Venue::Binance, symbol)
+                    .is_settlement(settlement)
+            })
+            .collect();
+        let per_position_funding = self.mock_client.collect_funding_for(&settling).await;
         let total: Decimal = per_position_funding.values().sum();
+        for (symbol, amount) in &per_position_funding {
+            self.pnl_sources
+                .entry(symbol.clone())
+                .or_default()
+                .add_funding(*amount);
+        }
 
         if total != Decimal::ZERO {
             debug!(
@@ -315,6 +456,7 @@ impl<D: DataLoader> BacktestEngine<D> {
         );
 
         // Execute allocations (enter new positions)
+        let mut fees_paid = state.total_trading_fees;
         for alloc in allocations.iter().take(5) {
             // Max 5 new positions per step
             // Skip if already have position
@@ -352,15 +494,20 @@ impl<D: DataLoader> BacktestEngine<D> {
                 )
             };
 
-            // Execute futures order
+            // Execute futures order; a post-only entry is assumed to fill at the snapshot price
+            let (order_type, limit_price, time_in_force) = if self.backtest_config.maker_entries {
+                (OrderType::Limit, Some(price), Some(TimeInForce::Gtx))
+            } else {
+                (OrderType::Market, None, None)
+            };
             let futures_order = crate::exchange::NewOrder {
                 symbol: alloc.symbol.clone(),
-                side: futures_side.clone(),
+                side: futures_side,
                 position_side: None,
-                order_type: crate::exchange::OrderType::Market,
+                order_type,
                 quantity: Some(quantity),
-                price: None,
-                time_in_force: None,
+                price: limit_price,
+                time_in_force,
                 reduce_only: Some(false),
                 new_client_order_id: None,
             };
@@ -384,11 +531,18 @@ impl<D: DataLoader> BacktestEngine<D> {
                 time_in_force: None,
                 is_isolated: Some(false),
                 side_effect_type: Some(crate::exchange::SideEffectType::AutoBorrowRepay),
+                new_client_order_id: None,
             };
 
             let _ = self.mock_client.place_margin_order(&margin_order).await;
 
             self.positions_opened += 1;
+            let total_fees = self.mock_client.get_state().await.total_trading_fees;
+            self.pnl_sources
+                .entry(alloc.symbol.clone())
+                .or_default()
+                .fees -= total_fees - fees_paid;
+            fees_paid = total_fees;
 
             debug!(
                 "Opened position: {} @ ${:.4}, qty: {:.4}",
@@ -402,13 +556,14 @@ impl<D: DataLoader> BacktestEngine<D> {
     /// Convert market snapshot to qualified pairs for the allocator.
     fn snapshot_to_qualified_pairs(&self, snapshot: &MarketSnapshot) -> Vec<QualifiedPair> {
         let config = &self.config.pair_selection;
+        let volume_floor = volume_floor(config, snapshot.symbols.iter().map(|s| s.volume_24h));
 
         snapshot
             .symbols
             .iter()
             .filter(|s| {
                 // Apply pair selection filters
-                s.volume_24h >= config.min_volume_24h
+                s.volume_24h >= volume_floor
                     && s.funding_rate.abs() >= config.min_funding_rate
                     && s.spread <= config.max_spread
                     && s.open_interest >= config.min_open_interest
@@ -439,6 +594,11 @@ impl<D: DataLoader> BacktestEngine<D> {
                     margin_available: true, // Assume available for backtesting
                     borrow_rate: None,      // Not available in snapshot
                     score,
+                    hedge: HedgeInstrument::SpotMargin,
+                    hedge_options: Vec::new(),
+                    funding_capped: false, // Caps aren't in snapshots
+                    coin_contract: None,
+                    borrow_headroom_usdt: None,
                 }
             })
             .collect()
@@ -459,6 +619,7 @@ impl<D: DataLoader> BacktestEngine<D> {
 mod tests {
     use super::*;
     use crate::backtest::data::{CsvDataLoader, SymbolData};
+    use crate::backtest::{BorrowScenario, BorrowSpikeWindow, CrowdingModel, FundingRegime};
     use chrono::TimeZone;
 
     // =========================================================================
@@ -476,6 +637,10 @@ mod tests {
             record_equity_curve: true,
             record_trades: false,
             output_path: None,
+            maker_entries: false,
+            borrow: BorrowScenario::default(),
+            high_funding_rate: dec!(0.001),
+            crowding: CrowdingModel::default(),
         }
     }
 
@@ -809,6 +974,178 @@ mod tests {
         assert_eq!(result.end_time, end);
     }
 
+    #[tokio::test]
+    async fn test_borrow_spike_scenario_charges_short_hedges() {
+        // Saturday: negative funding is hedged by borrowing and selling ALT
+        let start = Utc.with_ymd_and_hms(2024, 3, 16, 0, 0, 0).unwrap();
+        let snapshots: Vec<MarketSnapshot> = (0..6)
+            .map(|hour| {
+                make_snapshot(
+                    start + Duration::hours(hour),
+                    vec![("ALTUSDT", dec!(-0.001), dec!(10))],
+                )
+            })
+            .collect();
+
+        let mut interest = Vec::new();
+        for borrow in [
+            BorrowScenario::default(),
+            BorrowScenario {
+                spikes: vec![BorrowSpikeWindow {
+                    start,
+                    end: start + Duration::days(1),
+                    symbols: vec!["ALTUSDT".to_string()],
+                    hourly_rate: Some(dec!(0.0004)),
+                    multiplier: None,
+                }],
+                ..Default::default()
+            },
+        ] {
+            let loader = CsvDataLoader::from_snapshots(snapshots.clone());
+            let backtest_config = BacktestConfig {
+                borrow,
+                ..test_backtest_config()
+            };
+            let mut engine = BacktestEngine::new(loader, test_config(), backtest_config);
+            let result = engine
+                .run(start - Duration::hours(1), start + Duration::hours(6))
+                .await
+                .unwrap();
+            assert_eq!(result.metrics.positions_opened, 1);
+            interest.push(engine.get_state().await.total_borrow_interest);
+        }
+
+        // Same borrow, charged 0.04%/h instead of 0.002%/h
+        assert!(interest[0] > Decimal::ZERO);
+        assert_eq!(interest[1], interest[0] * dec!(20));
+    }
+
+    #[tokio::test]
+    async fn test_crowding_dampens_funding_of_large_balances() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let snapshots: Vec<MarketSnapshot> = (0..10)
+            .map(|hour| {
+                make_snapshot(
+                    start + Duration::hours(hour),
+                    vec![("ALTUSDT", dec!(0.001), dec!(10))],
+                )
+            })
+            .collect();
+
+        let funding = |initial_balance, crowding| {
+            let loader = CsvDataLoader::from_snapshots(snapshots.clone());
+            let backtest_config = BacktestConfig {
+                initial_balance,
+                crowding,
+                ..test_backtest_config()
+            };
+            async move {
+                let mut engine = BacktestEngine::new(loader, test_config(), backtest_config);
+                let result = engine.run(start, start + Duration::hours(9)).await.unwrap();
+                result.metrics.total_funding_received
+            }
+        };
+        let crowded = CrowdingModel::with_sensitivity(dec!(10));
+
+        // $10k is a rounding error against $800M of open interest
+        let small = funding(dec!(10000), CrowdingModel::default()).await;
+        let small_crowded = funding(dec!(10000), crowded.clone()).await;
+        assert!(small > Decimal::ZERO);
+        assert!(small_crowded > small * dec!(0.999));
+
+        // $100M holds a visible share, and earns a visibly smaller rate on it
+        let large = funding(dec!(100_000_000), CrowdingModel::default()).await;
+        let large_crowded = funding(dec!(100_000_000), crowded).await;
+        assert!(large_crowded > Decimal::ZERO);
+        assert!(large_crowded < large * dec!(0.9));
+    }
+
+    #[tokio::test]
+    async fn test_drawdown_attributed_to_sources() {
+        // ALT is entered on negative funding, which flips positive before 08:00
+        let start = Utc.with_ymd_and_hms(2024, 3, 13, 0, 0, 0).unwrap();
+        let snapshots: Vec<MarketSnapshot> = (0..10)
+            .map(|hour| {
+                let rate = if hour < 4 { dec!(-0.001) } else { dec!(0.001) };
+                make_snapshot(
+                    start + Duration::hours(hour),
+                    vec![("ALTUSDT", rate, dec!(10))],
+                )
+            })
+            .collect();
+        let loader = CsvDataLoader::from_snapshots(snapshots);
+        let mut engine = BacktestEngine::new(loader, test_config(), test_backtest_config());
+        let result = engine
+            .run(start - Duration::hours(1), start + Duration::hours(10))
+            .await
+            .unwrap();
+
+        let attribution = result.metrics.drawdown_attribution.clone().unwrap();
+        // Entry fees are recovered by the first settlement, which sets the peak
+        assert!(attribution.peak_at < start + Duration::hours(8));
+        assert_eq!(attribution.trough_at, start + Duration::hours(9));
+        assert_eq!(attribution.losing_symbols(), 1);
+        assert_eq!(attribution.concentration(), Some(Decimal::ONE));
+
+        let sources = attribution.by_source();
+        assert!(sources.funding_flips < Decimal::ZERO);
+        assert!(sources.interest < Decimal::ZERO);
+        assert_eq!(sources.funding, Decimal::ZERO);
+        // The sources account for the whole equity loss
+        assert_eq!(sources.total(), -attribution.drawdown_usd);
+        assert!(result.summary().contains("MAX DRAWDOWN SOURCES"));
+
+        // Held 4h while paid, then 6h paying after the flip
+        let regimes = result.metrics.regime_attribution.unwrap();
+        let high = regimes.get(FundingRegime::HighPositive).unwrap();
+        let negative = regimes.get(FundingRegime::Negative).unwrap();
+        assert_eq!(high.position_hours, 4.0);
+        assert_eq!(negative.position_hours, 6.0);
+        assert!(negative.net() < Decimal::ZERO);
+        assert_eq!(regimes.net(FundingRegime::MildlyPositive), Decimal::ZERO);
+        // Every dollar of PnL lands in a regime
+        let final_equity = result.equity_curve.last().unwrap().total_equity;
+        assert_eq!(high.net() + negative.net(), final_equity - dec!(10000));
+    }
+
+    #[tokio::test]
+    async fn test_maker_entries_earn_rebate() {
+        let timestamp = Utc::now();
+        let mut config = test_config();
+        config.execution.maker_fee_rate = dec!(-0.00005);
+
+        let mut fees = Vec::new();
+        for maker_entries in [false, true] {
+            let snapshot = make_snapshot(timestamp, vec![("BTCUSDT", dec!(0.001), dec!(50000))]);
+            let loader = CsvDataLoader::from_snapshots(vec![snapshot]);
+            let backtest_config = BacktestConfig {
+                maker_entries,
+                ..test_backtest_config()
+            };
+            let mut engine = BacktestEngine::new(loader, config.clone(), backtest_config);
+            let result = engine
+                .run(
+                    timestamp - Duration::hours(1),
+                    timestamp + Duration::hours(1),
+                )
+                .await
+                .unwrap();
+            assert!(result.summary().contains("-0.005% maker / 0.040% taker"));
+            let state = engine.get_state().await;
+            assert_eq!(state.order_count, 2);
+            fees.push((
+                state.total_trading_fees,
+                state.positions["BTCUSDT"].futures_qty,
+            ));
+        }
+
+        // Same position; the futures leg earns 0.005% instead of paying 0.04%
+        let (taker_fees, qty) = fees[0];
+        assert_eq!(fees[1].1, qty);
+        let notional = qty.abs() * dec!(50000);
+        assert_eq!(taker_fees - fees[1].0, notional * dec!(0.00045));
+    }
+
     #[tokio::test]
     async fn test_run_with_multiple_snapshots() {
         let base_time = Utc::now();
@@ -837,6 +1174,58 @@ mod tests {
         assert!(!result.equity_curve.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_streamed_run_matches_loaded_run() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut csv =
+            String::from("timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest\n");
+        for hour in 0..24 {
+            let timestamp = (start + Duration::hours(hour)).to_rfc3339();
+            csv.push_str(&format!(
+                "{timestamp},BTCUSDT,0.001,{},1500000000,0.0001,800000000\n",
+                50000 + hour * 10
+            ));
+            csv.push_str(&format!(
+                "{timestamp},ETHUSDT,0.0008,2300,900000000,0.0001,400000000\n"
+            ));
+        }
+        let dir = std::env::temp_dir().join(format!("fff-engine-stream-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("day.csv");
+        std::fs::write(&path, &csv).unwrap();
+
+        let end = start + Duration::hours(23);
+        let loaded = BacktestEngine::new(
+            CsvDataLoader::from_csv_content(&csv).unwrap(),
+            test_config(),
+            test_backtest_config(),
+        )
+        .run(start, end)
+        .await
+        .unwrap();
+        let streamed = BacktestEngine::new(
+            CsvDataLoader::streaming(&path).unwrap(),
+            test_config(),
+            test_backtest_config(),
+        )
+        .run(start, end)
+        .await
+        .unwrap();
+
+        assert_eq!(streamed.snapshots_processed, 24);
+        assert_eq!(streamed.funding_events, loaded.funding_events);
+        assert_eq!(
+            streamed.metrics.total_funding_received,
+            loaded.metrics.total_funding_received
+        );
+        assert_eq!(
+            streamed.equity_curve.last().map(|p| p.total_equity),
+            loaded.equity_curve.last().map(|p| p.total_equity)
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     // =========================================================================
     // BacktestResult Tests
     // =========================================================================
@@ -1,11 +1,17 @@
 //! Performance metrics calculation for backtesting.
 //!
 //! Provides Sharpe ratio, Sortino ratio, drawdown analysis, and more.
+//!
+//! The maximum drawdown is also decomposed per symbol into its sources: basis
+//! (mark-to-market of the two legs), funding flips (settlements paid instead of
+//! received), fees and borrow interest, so sweeps can be compared on what
+//! actually drove the losses.
 
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A point on the equity curve.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +51,406 @@ impl EquityPoint {
     }
 }
 
+/// Cumulative PnL of one symbol by source; costs are negative.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlSources {
+    /// Unrealized PnL of the futures and spot legs
+    pub basis: Decimal,
+    /// Settlements received
+    pub funding: Decimal,
+    /// Settlements paid after the rate flipped against the position
+    pub funding_flips: Decimal,
+    pub fees: Decimal,
+    pub interest: Decimal,
+}
+
+impl PnlSources {
+    /// Book a funding settlement as received or, when negative, as a flip.
+    pub fn add_funding(&mut self, amount: Decimal) {
+        if amount < Decimal::ZERO {
+            self.funding_flips += amount;
+        } else {
+            self.funding += amount;
+        }
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.basis + self.funding + self.funding_flips + self.fees + self.interest
+    }
+
+    /// Change from `earlier` to `self`.
+    fn since(&self, earlier: &PnlSources) -> PnlSources {
+        PnlSources {
+            basis: self.basis - earlier.basis,
+            funding: self.funding - earlier.funding,
+            funding_flips: self.funding_flips - earlier.funding_flips,
+            fees: self.fees - earlier.fees,
+            interest: self.interest - earlier.interest,
+        }
+    }
+
+    fn accumulate(&mut self, other: &PnlSources) {
+        self.basis += other.basis;
+        self.funding += other.funding;
+        self.funding_flips += other.funding_flips;
+        self.fees += other.fees;
+        self.interest += other.interest;
+    }
+}
+
+/// PnL change of one symbol over the drawdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolDrawdown {
+    pub symbol: String,
+    pub sources: PnlSources,
+}
+
+/// Maximum drawdown broken down by symbol and source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownAttribution {
+    pub peak_at: DateTime<Utc>,
+    pub trough_at: DateTime<Utc>,
+    /// Drawdown as a fraction of peak equity
+    pub drawdown: Decimal,
+    /// Equity lost from peak to trough
+    pub drawdown_usd: Decimal,
+    /// Per-symbol changes from peak to trough, biggest loss first
+    pub symbols: Vec<SymbolDrawdown>,
+}
+
+impl DrawdownAttribution {
+    /// Changes summed over symbols.
+    pub fn by_source(&self) -> PnlSources {
+        let mut total = PnlSources::default();
+        for symbol in &self.symbols {
+            total.accumulate(&symbol.sources);
+        }
+        total
+    }
+
+    /// Symbols that lost money over the drawdown.
+    pub fn losing_symbols(&self) -> usize {
+        self.symbols
+            .iter()
+            .filter(|s| s.sources.total() < Decimal::ZERO)
+            .count()
+    }
+
+    /// Share of the losing symbols' combined loss taken by the worst one.
+    ///
+    /// Near 1 when a single symbol caused the drawdown, near 1/n when n symbols
+    /// lost together, as correlated basis moves do.
+    pub fn concentration(&self) -> Option<Decimal> {
+        let losses: Decimal = self
+            .symbols
+            .iter()
+            .map(|s| s.sources.total().min(Decimal::ZERO))
+            .sum();
+        let worst = self.symbols.first()?.sources.total();
+        (losses < Decimal::ZERO && worst < Decimal::ZERO).then(|| worst / losses)
+    }
+
+    /// Format the breakdown for the backtest summary.
+    pub fn summary(&self) -> String {
+        let sources = self.by_source();
+        let mut summary = format!(
+            r#"
+MAX DRAWDOWN SOURCES ({} to {}, ${:.2})
+  Basis:             ${:.2}
+  Funding Flips:     ${:.2}
+  Fees:              ${:.2}
+  Interest:          ${:.2}
+  Funding Received:  ${:.2}
+  Losing Symbols:    {}{}"#,
+            self.peak_at.format("%Y-%m-%d %H:%M"),
+            self.trough_at.format("%Y-%m-%d %H:%M"),
+            self.drawdown_usd,
+            sources.basis,
+            sources.funding_flips,
+            sources.fees,
+            sources.interest,
+            sources.funding,
+            self.losing_symbols(),
+            self.concentration()
+                .map(|c| format!(" (worst {:.0}% of losses)", c * dec!(100)))
+                .unwrap_or_default(),
+        );
+        for symbol in self
+            .symbols
+            .iter()
+            .filter(|s| s.sources.total() < Decimal::ZERO)
+            .take(5)
+        {
+            let s = &symbol.sources;
+            summary.push_str(&format!(
+                "\n  {:<18} ${:.2} (basis {:.2}, flips {:.2}, fees {:.2}, interest {:.2})",
+                symbol.symbol,
+                s.total(),
+                s.basis,
+                s.funding_flips,
+                s.fees,
+                s.interest
+            ));
+        }
+        summary.push_str("\n═══════════════════════════════════════════════");
+        summary
+    }
+}
+
+/// Follows equity and per-symbol PnL sources to attribute the maximum drawdown.
+#[derive(Debug, Clone)]
+pub struct DrawdownAttributor {
+    peak_equity: Decimal,
+    peak_at: Option<DateTime<Utc>>,
+    peak_sources: HashMap<String, PnlSources>,
+    worst: Option<DrawdownAttribution>,
+}
+
+impl DrawdownAttributor {
+    pub fn new(initial_equity: Decimal) -> Self {
+        Self {
+            peak_equity: initial_equity,
+            peak_at: None,
+            peak_sources: HashMap::new(),
+            worst: None,
+        }
+    }
+
+    /// Record equity and cumulative per-symbol sources at `timestamp`.
+    pub fn observe(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        equity: Decimal,
+        sources: &HashMap<String, PnlSources>,
+    ) {
+        let peak_at = *self.peak_at.get_or_insert(timestamp);
+        if equity >= self.peak_equity {
+            self.peak_equity = equity;
+            self.peak_at = Some(timestamp);
+            self.peak_sources = sources.clone();
+            return;
+        }
+        if self.peak_equity <= Decimal::ZERO {
+            return;
+        }
+        let drawdown = (self.peak_equity - equity) / self.peak_equity;
+        if self.worst.as_ref().is_some_and(|w| drawdown <= w.drawdown) {
+            return;
+        }
+
+        let mut symbols: Vec<SymbolDrawdown> = sources
+            .iter()
+            .map(|(symbol, now)| SymbolDrawdown {
+                symbol: symbol.clone(),
+                sources: now.since(&self.peak_sources.get(symbol).copied().unwrap_or_default()),
+            })
+            .chain(
+                self.peak_sources
+                    .iter()
+                    .filter(|(symbol, _)| !sources.contains_key(*symbol))
+                    .map(|(symbol, then)| SymbolDrawdown {
+                        symbol: symbol.clone(),
+                        sources: PnlSources::default().since(then),
+                    }),
+            )
+            .collect();
+        symbols.sort_by(|a, b| {
+            a.sources
+                .total()
+                .cmp(&b.sources.total())
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+
+        self.worst = Some(DrawdownAttribution {
+            peak_at,
+            trough_at: timestamp,
+            drawdown,
+            drawdown_usd: self.peak_equity - equity,
+            symbols,
+        });
+    }
+
+    /// Attribution of the deepest drawdown seen, if equity ever fell below a peak.
+    pub fn finish(self) -> Option<DrawdownAttribution> {
+        self.worst
+    }
+}
+
+/// Funding environment of a held position, by the rate paid to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FundingRegime {
+    /// Paid at or above the high threshold
+    HighPositive,
+    /// Paid, but below the high threshold
+    MildlyPositive,
+    /// Zero or paying against the position
+    Negative,
+}
+
+impl FundingRegime {
+    pub const ALL: [FundingRegime; 3] = [
+        FundingRegime::HighPositive,
+        FundingRegime::MildlyPositive,
+        FundingRegime::Negative,
+    ];
+
+    /// Classify `rate`, the 8h-equivalent rate paid to the position (negative
+    /// when it pays), against `high_threshold`.
+    pub fn classify(rate: Decimal, high_threshold: Decimal) -> Self {
+        if rate >= high_threshold {
+            FundingRegime::HighPositive
+        } else if rate > Decimal::ZERO {
+            FundingRegime::MildlyPositive
+        } else {
+            FundingRegime::Negative
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FundingRegime::HighPositive => "high positive",
+            FundingRegime::MildlyPositive => "mildly positive",
+            FundingRegime::Negative => "negative",
+        }
+    }
+}
+
+/// PnL earned while positions were held in one funding regime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegimePnl {
+    pub regime: FundingRegime,
+    /// Hours summed over held positions
+    pub position_hours: f64,
+    pub sources: PnlSources,
+}
+
+impl RegimePnl {
+    pub fn net(&self) -> Decimal {
+        self.sources.total()
+    }
+
+    /// Net PnL per position held for a day in this regime.
+    pub fn net_per_position_day(&self) -> Option<Decimal> {
+        (self.position_hours > 0.0).then(|| {
+            self.net() * dec!(24) / Decimal::from_f64_retain(self.position_hours).unwrap_or(dec!(1))
+        })
+    }
+}
+
+/// Net PnL of held positions split by funding regime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegimeAttribution {
+    /// 8h-equivalent rate from which funding counts as high
+    pub high_threshold: Decimal,
+    /// One entry per regime, in [`FundingRegime::ALL`] order
+    pub regimes: Vec<RegimePnl>,
+}
+
+impl RegimeAttribution {
+    pub fn get(&self, regime: FundingRegime) -> Option<&RegimePnl> {
+        self.regimes.iter().find(|r| r.regime == regime)
+    }
+
+    /// Net PnL of `regime`, zero if positions were never held in it.
+    pub fn net(&self, regime: FundingRegime) -> Decimal {
+        self.get(regime).map_or(Decimal::ZERO, RegimePnl::net)
+    }
+
+    /// Format the breakdown for the backtest summary.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "\nFUNDING REGIMES (high >= {:.3}%/8h)",
+            self.high_threshold * dec!(100)
+        );
+        for regime in &self.regimes {
+            let label = match regime.regime {
+                FundingRegime::HighPositive => "High Positive:",
+                FundingRegime::MildlyPositive => "Mildly Positive:",
+                FundingRegime::Negative => "Negative:",
+            };
+            let s = &regime.sources;
+            summary.push_str(&format!(
+                "\n  {:<18} ${:.2} over {:.1} position-days{} (funding {:.2}, basis {:.2}, costs {:.2})",
+                label,
+                regime.net(),
+                regime.position_hours / 24.0,
+                regime
+                    .net_per_position_day()
+                    .map(|p| format!(", ${:.2}/day", p))
+                    .unwrap_or_default(),
+                s.funding + s.funding_flips,
+                s.basis,
+                s.fees + s.interest
+            ));
+        }
+        summary.push_str("\n═══════════════════════════════════════════════");
+        summary
+    }
+}
+
+/// Attributes each held position's PnL to the funding regime it was in.
+#[derive(Debug, Clone)]
+pub struct RegimeAttributor {
+    high_threshold: Decimal,
+    /// Per-symbol sources at the last observation
+    last: HashMap<String, PnlSources>,
+    regimes: HashMap<FundingRegime, RegimePnl>,
+}
+
+impl RegimeAttributor {
+    pub fn new(high_threshold: Decimal) -> Self {
+        Self {
+            high_threshold,
+            last: HashMap::new(),
+            regimes: HashMap::new(),
+        }
+    }
+
+    /// Record one step of `hours`.
+    ///
+    /// `rates` holds the 8h-equivalent rate paid to each held position; each
+    /// one's PnL change since the last observation goes to that rate's regime.
+    /// Symbols without a rate this step carry their change to the next one.
+    pub fn observe(
+        &mut self,
+        hours: f64,
+        rates: &HashMap<String, Decimal>,
+        sources: &HashMap<String, PnlSources>,
+    ) {
+        for (symbol, &rate) in rates {
+            let Some(now) = sources.get(symbol) else {
+                continue;
+            };
+            let regime = FundingRegime::classify(rate, self.high_threshold);
+            let change = now.since(&self.last.get(symbol).copied().unwrap_or_default());
+            let pnl = self.regimes.entry(regime).or_insert_with(|| RegimePnl {
+                regime,
+                position_hours: 0.0,
+                sources: PnlSources::default(),
+            });
+            pnl.position_hours += hours;
+            pnl.sources.accumulate(&change);
+            self.last.insert(symbol.clone(), *now);
+        }
+    }
+
+    pub fn finish(mut self) -> RegimeAttribution {
+        RegimeAttribution {
+            high_threshold: self.high_threshold,
+            regimes: FundingRegime::ALL
+                .iter()
+                .map(|regime| {
+                    self.regimes.remove(regime).unwrap_or(RegimePnl {
+                        regime: *regime,
+                        position_hours: 0.0,
+                        sources: PnlSources::default(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Comprehensive backtest performance metrics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestMetrics {
@@ -55,6 +461,12 @@ pub struct BacktestMetrics {
     pub total_return_pct: Decimal,
     /// Annualized return percentage
     pub annualized_return: Decimal,
+    /// Annual risk-free rate returns are compared against (0.045 = 4.5%)
+    #[serde(default)]
+    pub risk_free_rate: Decimal,
+    /// Annualized return above the risk-free rate, in percentage points
+    #[serde(default)]
+    pub excess_return: Decimal,
 
     // Risk
     /// Maximum drawdown percentage
@@ -65,9 +477,9 @@ pub struct BacktestMetrics {
     pub volatility: Decimal,
 
     // Risk-adjusted
-    /// Sharpe ratio (assuming 0 risk-free rate)
+    /// Sharpe ratio of returns in excess of the risk-free rate
     pub sharpe_ratio: Decimal,
-    /// Sortino ratio (downside deviation only)
+    /// Sortino ratio (downside deviation below the risk-free rate only)
     pub sortino_ratio: Decimal,
     /// Calmar ratio (return / max drawdown)
     pub calmar_ratio: Decimal,
@@ -99,10 +511,19 @@ pub struct BacktestMetrics {
     // Time
     /// Backtest duration in days
     pub duration_days: f64,
+
+    /// Maximum drawdown by symbol and source
+    #[serde(default)]
+    pub drawdown_attribution: Option<DrawdownAttribution>,
+
+    /// Net PnL by the funding regime positions were held in
+    #[serde(default)]
+    pub regime_attribution: Option<RegimeAttribution>,
 }
 
 impl BacktestMetrics {
     /// Calculate metrics from equity curve and trading state.
+    #[allow(clippy::too_many_arguments)]
     pub fn calculate(
         equity_curve: &[EquityPoint],
         initial_balance: Decimal,
@@ -113,6 +534,7 @@ impl BacktestMetrics {
         positions_closed: u64,
         winning_positions: u64,
         total_position_hours: f64,
+        risk_free_rate: Decimal,
     ) -> Self {
         if equity_curve.is_empty() {
             return Self::empty();
@@ -141,6 +563,7 @@ impl BacktestMetrics {
         } else {
             Decimal::ZERO
         };
+        let excess_return = annualized_return - risk_free_rate * dec!(100);
 
         // Drawdown
         let (max_drawdown, max_dd_duration) = calculate_max_drawdown(equity_curve);
@@ -150,8 +573,8 @@ impl BacktestMetrics {
         let volatility = calculate_volatility(&returns, duration_years);
 
         // Risk-adjusted metrics
-        let sharpe_ratio = calculate_sharpe(&returns, duration_years);
-        let sortino_ratio = calculate_sortino(&returns, duration_years);
+        let sharpe_ratio = calculate_sharpe(&returns, duration_years, risk_free_rate);
+        let sortino_ratio = calculate_sortino(&returns, duration_years, risk_free_rate);
         let calmar_ratio = if max_drawdown > Decimal::ZERO {
             annualized_return / (max_drawdown * dec!(100))
         } else {
@@ -185,6 +608,8 @@ impl BacktestMetrics {
             total_return,
             total_return_pct,
             annualized_return,
+            risk_free_rate,
+            excess_return,
             max_drawdown,
             max_drawdown_duration_hours: max_dd_duration,
             volatility,
@@ -202,15 +627,31 @@ impl BacktestMetrics {
             avg_position_duration_hours,
             win_rate,
             duration_days,
+            drawdown_attribution: None,
+            regime_attribution: None,
         }
     }
 
+    /// Attach the maximum drawdown's breakdown by symbol and source.
+    pub fn with_drawdown_attribution(mut self, attribution: Option<DrawdownAttribution>) -> Self {
+        self.drawdown_attribution = attribution;
+        self
+    }
+
+    /// Attach net PnL by funding regime.
+    pub fn with_regime_attribution(mut self, attribution: RegimeAttribution) -> Self {
+        self.regime_attribution = Some(attribution);
+        self
+    }
+
     /// Create empty metrics (for error cases).
     pub fn empty() -> Self {
         Self {
             total_return: Decimal::ZERO,
             total_return_pct: Decimal::ZERO,
             annualized_return: Decimal::ZERO,
+            risk_free_rate: Decimal::ZERO,
+            excess_return: Decimal::ZERO,
             max_drawdown: Decimal::ZERO,
             max_drawdown_duration_hours: 0,
             volatility: Decimal::ZERO,
@@ -228,18 +669,21 @@ impl BacktestMetrics {
             avg_position_duration_hours: 0.0,
             win_rate: Decimal::ZERO,
             duration_days: 0.0,
+            drawdown_attribution: None,
+            regime_attribution: None,
         }
     }
 
     /// Format metrics as a summary string.
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             r#"═══════════════════════════════════════════════
 BACKTEST RESULTS ({:.1} days)
 ═══════════════════════════════════════════════
 RETURNS
   Total Return:      ${:.2} ({:.2}%)
   Annualized:        {:.2}%
+  vs Risk-Free:      {:+.2}% ({:.2}% T-bills)
 
 RISK
   Max Drawdown:      {:.2}%
@@ -266,6 +710,8 @@ ACTIVITY
             self.total_return,
             self.total_return_pct,
             self.annualized_return,
+            self.excess_return,
+            self.risk_free_rate * dec!(100),
             self.max_drawdown * dec!(100),
             self.volatility * dec!(100),
             self.sharpe_ratio,
@@ -279,7 +725,14 @@ ACTIVITY
             self.positions_opened,
             self.positions_closed,
             self.win_rate,
-        )
+        );
+        if let Some(attribution) = &self.drawdown_attribution {
+            summary.push_str(&attribution.summary());
+        }
+        if let Some(attribution) = &self.regime_attribution {
+            summary.push_str(&attribution.summary());
+        }
+        summary
     }
 }
 
@@ -373,8 +826,8 @@ fn calculate_volatility(returns: &[Decimal], duration_years: f64) -> Decimal {
     Decimal::from_f64_retain(annualized).unwrap_or(Decimal::ZERO)
 }
 
-/// Calculate Sharpe ratio (assuming 0 risk-free rate).
-fn calculate_sharpe(returns: &[Decimal], duration_years: f64) -> Decimal {
+/// Calculate Sharpe ratio of returns in excess of the annual `risk_free_rate`.
+fn calculate_sharpe(returns: &[Decimal], duration_years: f64, risk_free_rate: Decimal) -> Decimal {
     if returns.is_empty() || duration_years <= 0.0 {
         return Decimal::ZERO;
     }
@@ -395,15 +848,16 @@ fn calculate_sharpe(returns: &[Decimal], duration_years: f64) -> Decimal {
 
     // Annualize
     let periods_per_year = n / duration_years;
-    let annualized_return = mean * periods_per_year;
+    let annualized_excess =
+        mean * periods_per_year - risk_free_rate.to_string().parse::<f64>().unwrap_or(0.0);
     let annualized_std = std_dev * periods_per_year.sqrt();
 
-    let sharpe = annualized_return / annualized_std;
+    let sharpe = annualized_excess / annualized_std;
     Decimal::from_f64_retain(sharpe).unwrap_or(Decimal::ZERO)
 }
 
-/// Calculate Sortino ratio (downside deviation only).
-fn calculate_sortino(returns: &[Decimal], duration_years: f64) -> Decimal {
+/// Calculate Sortino ratio (downside deviation below the annual `risk_free_rate` only).
+fn calculate_sortino(returns: &[Decimal], duration_years: f64, risk_free_rate: Decimal) -> Decimal {
     if returns.is_empty() || duration_years <= 0.0 {
         return Decimal::ZERO;
     }
@@ -415,9 +869,15 @@ fn calculate_sortino(returns: &[Decimal], duration_years: f64) -> Decimal {
 
     let n = returns_f64.len() as f64;
     let mean = returns_f64.iter().sum::<f64>() / n;
+    let periods_per_year = n / duration_years;
+    let target = risk_free_rate.to_string().parse::<f64>().unwrap_or(0.0) / periods_per_year;
 
-    // Downside deviation (negative returns only)
-    let downside: Vec<f64> = returns_f64.iter().filter(|&&r| r < 0.0).cloned().collect();
+    // Downside deviation (returns below the per-period risk-free rate only)
+    let downside: Vec<f64> = returns_f64
+        .iter()
+        .filter(|&&r| r < target)
+        .map(|r| r - target)
+        .collect();
 
     if downside.is_empty() {
         // No negative returns = infinite Sortino (cap at a large value)
@@ -432,11 +892,10 @@ fn calculate_sortino(returns: &[Decimal], duration_years: f64) -> Decimal {
     }
 
     // Annualize
-    let periods_per_year = n / duration_years;
-    let annualized_return = mean * periods_per_year;
+    let annualized_excess = (mean - target) * periods_per_year;
     let annualized_dd = downside_deviation * periods_per_year.sqrt();
 
-    let sortino = annualized_return / annualized_dd;
+    let sortino = annualized_excess / annualized_dd;
     Decimal::from_f64_retain(sortino).unwrap_or(Decimal::ZERO)
 }
 
@@ -589,6 +1048,121 @@ mod tests {
         assert!(max_dd >= dec!(0.095)); // ~10%
     }
 
+    // =========================================================================
+    // Drawdown Attribution Tests
+    // =========================================================================
+
+    /// Sources from (symbol, basis, settlements in order).
+    fn sources(entries: &[(&str, Decimal, &[Decimal])]) -> HashMap<String, PnlSources> {
+        entries
+            .iter()
+            .map(|&(symbol, basis, settlements)| {
+                let mut sources = PnlSources {
+                    basis,
+                    ..Default::default()
+                };
+                for &amount in settlements {
+                    sources.add_funding(amount);
+                }
+                (symbol.to_string(), sources)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_drawdown_attribution_by_symbol() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let hour = |h: i64| base_time + chrono::Duration::hours(h);
+        let mut attributor = DrawdownAttributor::new(dec!(10000));
+
+        // New peak after ETH earns funding
+        attributor.observe(
+            hour(0),
+            dec!(10050),
+            &sources(&[("ETHUSDT", dec!(0), &[dec!(50)])]),
+        );
+        // ALT's basis and a flipped ETH settlement pull equity down
+        attributor.observe(
+            hour(1),
+            dec!(9860),
+            &sources(&[
+                ("ETHUSDT", dec!(-20), &[dec!(50)]),
+                ("ALTUSDT", dec!(-170), &[]),
+            ]),
+        );
+        attributor.observe(
+            hour(2),
+            dec!(9800),
+            &sources(&[
+                ("ETHUSDT", dec!(-20), &[dec!(50), dec!(-30)]),
+                ("ALTUSDT", dec!(-200), &[]),
+            ]),
+        );
+        // Shallower later dip doesn't replace the worst one
+        attributor.observe(hour(3), dec!(10100), &HashMap::new());
+        attributor.observe(hour(4), dec!(10090), &HashMap::new());
+
+        let attribution = attributor.finish().unwrap();
+        assert_eq!(attribution.peak_at, hour(0));
+        assert_eq!(attribution.trough_at, hour(2));
+        assert_eq!(attribution.drawdown_usd, dec!(250));
+        assert_eq!(attribution.symbols[0].symbol, "ALTUSDT");
+        assert_eq!(attribution.losing_symbols(), 2);
+
+        let by_source = attribution.by_source();
+        assert_eq!(by_source.basis, dec!(-220));
+        // The 30 paid by ETH's flipped settlement
+        assert_eq!(by_source.funding_flips, dec!(-30));
+        assert_eq!(by_source.funding, Decimal::ZERO);
+        assert_eq!(by_source.total(), -attribution.drawdown_usd);
+        // ALT took 200 of the 250 lost
+        assert_eq!(attribution.concentration(), Some(dec!(0.8)));
+    }
+
+    #[test]
+    fn test_no_drawdown_no_attribution() {
+        let mut attributor = DrawdownAttributor::new(dec!(10000));
+        attributor.observe(Utc::now(), dec!(10010), &HashMap::new());
+        assert!(attributor.finish().is_none());
+    }
+
+    #[test]
+    fn test_regime_attribution() {
+        let mut attributor = RegimeAttributor::new(dec!(0.001));
+        let rates = |rate: Decimal| HashMap::from([("ALTUSDT".to_string(), rate)]);
+        // Cumulative sources after a $4 entry fee
+        let held = |basis: Decimal, settlements: &[Decimal]| {
+            let mut cumulative = sources(&[("ALTUSDT", basis, settlements)]);
+            cumulative.get_mut("ALTUSDT").unwrap().fees = dec!(-4);
+            cumulative
+        };
+
+        // Entered at a high rate: the entry fee and first settlement count as high
+        attributor.observe(8.0, &rates(dec!(0.002)), &held(dec!(0), &[dec!(10)]));
+
+        // Decays to mild, then flips against the position
+        attributor.observe(
+            8.0,
+            &rates(dec!(0.0003)),
+            &held(dec!(-1), &[dec!(10), dec!(3)]),
+        );
+        let cumulative = held(dec!(-2), &[dec!(10), dec!(3), dec!(-2)]);
+        attributor.observe(4.0, &rates(dec!(-0.0002)), &cumulative);
+        // Unpriced step: the change waits for the next observation
+        attributor.observe(4.0, &HashMap::new(), &cumulative);
+
+        let attribution = attributor.finish();
+        assert_eq!(attribution.regimes.len(), 3);
+        let high = attribution.get(FundingRegime::HighPositive).unwrap();
+        assert_eq!(high.net(), dec!(6));
+        assert_eq!(high.net_per_position_day(), Some(dec!(18)));
+        assert_eq!(attribution.net(FundingRegime::MildlyPositive), dec!(2));
+        let negative = attribution.get(FundingRegime::Negative).unwrap();
+        assert_eq!(negative.net(), dec!(-3));
+        assert_eq!(negative.position_hours, 4.0);
+        assert!(attribution.summary().contains("Mildly Positive:   $2.00"));
+    }
+
     // =========================================================================
     // Period Returns Tests
     // =========================================================================
@@ -635,7 +1209,7 @@ mod tests {
     fn test_sharpe_ratio_positive_returns() {
         // Consistently positive returns should have positive Sharpe
         let returns = vec![dec!(0.01), dec!(0.02), dec!(0.01), dec!(0.015)];
-        let sharpe = calculate_sharpe(&returns, 0.1); // ~36 days
+        let sharpe = calculate_sharpe(&returns, 0.1, Decimal::ZERO); // ~36 days
 
         assert!(sharpe > Decimal::ZERO);
     }
@@ -648,8 +1222,8 @@ mod tests {
         // Volatile: mean = 0.01, std > 0 (equal mean, higher std)
         let volatile_returns = vec![dec!(0.03), dec!(-0.01), dec!(0.02), dec!(0.00)];
 
-        let sharpe_stable = calculate_sharpe(&stable_returns, 0.1);
-        let sharpe_volatile = calculate_sharpe(&volatile_returns, 0.1);
+        let sharpe_stable = calculate_sharpe(&stable_returns, 0.1, Decimal::ZERO);
+        let sharpe_volatile = calculate_sharpe(&volatile_returns, 0.1, Decimal::ZERO);
 
         // Stable returns with zero std dev gets zero Sharpe (div by zero protection)
         // But higher mean/std ratio should be > lower mean/std ratio
@@ -660,7 +1234,7 @@ mod tests {
 
     #[test]
     fn test_sharpe_ratio_empty_returns() {
-        let sharpe = calculate_sharpe(&[], 1.0);
+        let sharpe = calculate_sharpe(&[], 1.0, Decimal::ZERO);
         assert_eq!(sharpe, Decimal::ZERO);
     }
 
@@ -672,7 +1246,7 @@ mod tests {
     fn test_sortino_ratio_no_downside() {
         // All positive returns = capped high Sortino
         let returns = vec![dec!(0.01), dec!(0.02), dec!(0.01)];
-        let sortino = calculate_sortino(&returns, 0.1);
+        let sortino = calculate_sortino(&returns, 0.1, Decimal::ZERO);
 
         assert_eq!(sortino, dec!(100)); // Capped value for no downside
     }
@@ -681,7 +1255,7 @@ mod tests {
     fn test_sortino_ratio_with_downside() {
         // Mix of positive and negative
         let returns = vec![dec!(0.02), dec!(-0.01), dec!(0.03), dec!(-0.02)];
-        let sortino = calculate_sortino(&returns, 0.1);
+        let sortino = calculate_sortino(&returns, 0.1, Decimal::ZERO);
 
         assert!(sortino > Decimal::ZERO);
         assert!(sortino < dec!(100)); // Not capped
@@ -692,8 +1266,8 @@ mod tests {
         // High upside volatility, low downside
         let returns = vec![dec!(0.05), dec!(0.01), dec!(0.08), dec!(0.02)];
 
-        let sharpe = calculate_sharpe(&returns, 0.1);
-        let sortino = calculate_sortino(&returns, 0.1);
+        let sharpe = calculate_sharpe(&returns, 0.1, Decimal::ZERO);
+        let sortino = calculate_sortino(&returns, 0.1, Decimal::ZERO);
 
         // Sortino should be higher since no downside is penalized
         assert!(sortino > sharpe);
@@ -744,6 +1318,7 @@ mod tests {
             4,           // positions closed
             3,           // winning
             100.0,       // total hours
+            Decimal::ZERO,
         );
 
         assert_eq!(metrics.total_return, dec!(300));
@@ -768,6 +1343,7 @@ mod tests {
             10,
             7,
             100.0,
+            Decimal::ZERO,
         );
 
         assert_eq!(metrics.win_rate, dec!(70)); // 70%
@@ -787,6 +1363,7 @@ mod tests {
             1,
             1,
             10.0,
+            Decimal::ZERO,
         );
 
         // funding / (fees + interest) = 600 / 100 = 6
@@ -805,6 +1382,7 @@ mod tests {
             0,
             0,
             0.0,
+            Decimal::ZERO,
         );
 
         // Should return empty metrics
@@ -826,6 +1404,7 @@ mod tests {
             1,
             1,
             10.0,
+            Decimal::ZERO,
         );
 
         // Calmar = annualized_return / (max_drawdown * 100)
@@ -835,6 +1414,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_metrics_risk_free_rate() {
+        let curve = make_equity_curve(vec![dec!(10000), dec!(10100), dec!(10150), dec!(10300)]);
+        let calculate = |risk_free_rate| {
+            BacktestMetrics::calculate(
+                &curve,
+                dec!(10000),
+                Decimal::ZERO,
+                Decimal::ZERO,
+                Decimal::ZERO,
+                1,
+                1,
+                1,
+                10.0,
+                risk_free_rate,
+            )
+        };
+        let zero = calculate(Decimal::ZERO);
+        let tbills = calculate(dec!(0.05));
+
+        assert_eq!(zero.excess_return, zero.annualized_return);
+        assert_eq!(tbills.risk_free_rate, dec!(0.05));
+        assert_eq!(tbills.excess_return, tbills.annualized_return - dec!(5));
+        assert!(tbills.sharpe_ratio < zero.sharpe_ratio);
+        assert!(tbills.summary().contains("5.00% T-bills"));
+    }
+
     // =========================================================================
     // Summary Tests
     // =========================================================================
@@ -845,6 +1451,8 @@ mod tests {
             total_return: dec!(500),
             total_return_pct: dec!(5),
             annualized_return: dec!(20),
+            risk_free_rate: dec!(0.045),
+            excess_return: dec!(15.5),
             max_drawdown: dec!(0.02),
             max_drawdown_duration_hours: 24,
             volatility: dec!(0.15),
@@ -862,6 +1470,8 @@ mod tests {
             avg_position_duration_hours: 168.0,
             win_rate: dec!(70),
             duration_days: 90.0,
+            drawdown_attribution: None,
+            regime_attribution: None,
         };
 
         let summary = metrics.summary();
@@ -5,30 +5,63 @@
 //! - Time-based simulation engine
 //! - Parameter sweep for optimization
 //! - Performance metrics calculation
+//! - Maximum drawdown attribution by symbol and source
+//! - Borrow rate scenarios (weekend drains, historical and synthetic spikes)
+//! - Funding dampening as simulated positions crowd a symbol's open interest
 //!
 //! # Example
 //!
-//! ```rust,ignore
-//! use funding_fee_farmer::backtest::{BacktestEngine, CsvDataLoader, BacktestConfig};
+//! Data usually comes from a file (`CsvDataLoader::new("data/funding_rates.csv")`);
+//! here it is inline:
 //!
-//! let loader = CsvDataLoader::new("data/funding_rates.csv")?;
-//! let config = BacktestConfig::default();
-//! let mut engine = BacktestEngine::new(loader, config);
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use funding_fee_farmer::backtest::{BacktestConfig, BacktestEngine, CsvDataLoader};
+//! use funding_fee_farmer::config::Config;
+//! use rust_decimal::Decimal;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let loader: CsvDataLoader = "\
+//! timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
+//! 2024-01-01T00:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+//! 2024-01-01T04:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+//! 2024-01-01T08:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+//! "
+//! .parse()?;
 //!
+//! let mut engine = BacktestEngine::new(loader, Config::default(), BacktestConfig::default());
+//! let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+//! let end = Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap();
 //! let result = engine.run(start, end).await?;
+//!
+//! assert_eq!(result.metrics.positions_opened, 1);
+//! assert!(result.metrics.total_funding_received > Decimal::ZERO);
 //! println!("Return: {:.2}%", result.metrics.total_return_pct);
+//! # Ok(())
+//! # }
 //! ```
 
+mod borrow;
+mod crowding;
 mod data;
 mod engine;
 mod metrics;
 mod runner;
 
-pub use data::{CsvDataLoader, DataLoader, LiveDataCollector, MarketSnapshot, SymbolData};
+pub use borrow::{BorrowScenario, BorrowSpikeWindow};
+pub use crowding::CrowdingModel;
+pub use data::{
+    CsvDataLoader, DataLoader, LiveDataCollector, MarketSnapshot, SnapshotIter, SymbolData,
+};
 pub use engine::{BacktestEngine, BacktestResult, StepResult};
-pub use metrics::{BacktestMetrics, EquityPoint};
+pub use metrics::{
+    BacktestMetrics, DrawdownAttribution, EquityPoint, FundingRegime, PnlSources, RegimeAttribution,
+    RegimePnl, SymbolDrawdown,
+};
 pub use runner::{ParameterSpace, SweepResults, SweepRunner};
 
+use crate::exchange::FundingSchedule;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -51,6 +84,28 @@ pub struct BacktestConfig {
 
     /// Path to output results (optional)
     pub output_path: Option<String>,
+
+    /// Enter the futures leg with post-only limit orders, paying the maker rate
+    /// (or earning its rebate) instead of the taker rate
+    #[serde(default)]
+    pub maker_entries: bool,
+
+    /// Hourly borrow rates charged to short spot hedges
+    #[serde(default)]
+    pub borrow: BorrowScenario,
+
+    /// 8h-equivalent funding rate from which held periods count as high
+    /// funding in the regime attribution
+    #[serde(default = "default_high_funding_rate")]
+    pub high_funding_rate: Decimal,
+
+    /// Funding dampening by the share of open interest held (off by default)
+    #[serde(default)]
+    pub crowding: CrowdingModel,
+}
+
+fn default_high_funding_rate() -> Decimal {
+    Decimal::new(1, 3) // 0.1% per 8h (~110% APR)
 }
 
 impl Default for BacktestConfig {
@@ -61,44 +116,25 @@ impl Default for BacktestConfig {
             record_equity_curve: true,
             record_trades: true,
             output_path: None,
+            maker_entries: false,
+            borrow: BorrowScenario::default(),
+            high_funding_rate: default_high_funding_rate(),
+            crowding: CrowdingModel::default(),
         }
     }
 }
 
-/// Funding times for Binance perpetual futures (UTC hours).
-pub const FUNDING_HOURS: [u32; 3] = [0, 8, 16];
+/// Funding schedule of symbols without a per-symbol override (Binance default).
+pub const FUNDING_SCHEDULE: FundingSchedule = FundingSchedule::BINANCE_DEFAULT;
 
-/// Check if a timestamp is a funding time.
+/// Check if a timestamp is a funding time on the default schedule.
 pub fn is_funding_time(timestamp: &DateTime<Utc>) -> bool {
-    use chrono::Timelike;
-    FUNDING_HOURS.contains(&timestamp.hour()) && timestamp.minute() == 0
+    FUNDING_SCHEDULE.is_settlement(*timestamp)
 }
 
-/// Calculate the next funding time from a given timestamp.
+/// Calculate the next funding time on the default schedule from a given timestamp.
 pub fn next_funding_time(from: DateTime<Utc>) -> DateTime<Utc> {
-    use chrono::{Duration, Timelike};
-
-    let hour = from.hour();
-    let minute = from.minute();
-
-    // Find next funding hour
-    let next_hour = FUNDING_HOURS
-        .iter()
-        .find(|&&h| h > hour || (h == hour && minute == 0))
-        .copied();
-
-    match next_hour {
-        Some(h) if h == hour && minute == 0 => from, // Already at funding time
-        Some(h) => from.date_naive().and_hms_opt(h, 0, 0).unwrap().and_utc(),
-        None => {
-            // Next day at 00:00
-            (from + Duration::days(1))
-                .date_naive()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_utc()
-        }
-    }
+    FUNDING_SCHEDULE.settlement_at_or_after(from)
 }
 
 #[cfg(test)]
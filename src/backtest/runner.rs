@@ -2,7 +2,7 @@
 //!
 //! Allows testing multiple config combinations in parallel.
 
-use crate::backtest::{BacktestConfig, BacktestEngine, BacktestResult, DataLoader};
+use crate::backtest::{BacktestConfig, BacktestEngine, BacktestResult, DataLoader, FundingRegime};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -171,14 +171,21 @@ impl SweepResults {
         // Header
         writeln!(
             file,
-            "min_funding_rate,min_volume_24h,max_spread,max_utilization,max_single_position,leverage,max_drawdown,total_return_pct,sharpe_ratio,sortino_ratio,calmar_ratio,max_dd_pct,funding_received,net_yield"
+            "min_funding_rate,min_volume_24h,max_spread,max_utilization,max_single_position,leverage,max_drawdown,total_return_pct,sharpe_ratio,sortino_ratio,calmar_ratio,max_dd_pct,funding_received,net_yield,dd_basis,dd_funding_flips,dd_fees,dd_interest,dd_losing_symbols,regime_high_pnl,regime_mild_pnl,regime_negative_pnl"
         )?;
 
         // Data rows
         for (config, result) in &self.runs {
+            // Drawdown sources let sweeps rank on the loss drivers, not just depth
+            let attribution = result.metrics.drawdown_attribution.as_ref();
+            let sources = attribution.map(|a| a.by_source()).unwrap_or_default();
+            // Regime PnL shows whether a threshold only pays in extreme funding
+            let regimes = result.metrics.regime_attribution.as_ref();
+            let regime_net =
+                |regime: FundingRegime| regimes.map_or(Decimal::ZERO, |r| r.net(regime));
             writeln!(
                 file,
-                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
                 config.pair_selection.min_funding_rate,
                 config.pair_selection.min_volume_24h,
                 config.pair_selection.max_spread,
@@ -193,6 +200,14 @@ impl SweepResults {
                 result.metrics.max_drawdown * dec!(100),
                 result.metrics.total_funding_received,
                 result.metrics.net_funding_yield,
+                sources.basis,
+                sources.funding_flips,
+                sources.fees,
+                sources.interest,
+                attribution.map_or(0, |a| a.losing_symbols()),
+                regime_net(FundingRegime::HighPositive),
+                regime_net(FundingRegime::MildlyPositive),
+                regime_net(FundingRegime::Negative),
             )?;
         }
 
@@ -284,7 +299,10 @@ impl SweepRunner {
     }
 
     /// Run the parameter sweep.
-    pub async fn run<D: DataLoader + Clone + Send + Sync + 'static>(
+    ///
+    /// Every run reads the same `data_loader` through an `Arc` rather than a copy
+    /// of it, so memory doesn't grow with the parallelism.
+    pub async fn run<D: DataLoader + 'static>(
         &self,
         data_loader: D,
         start: DateTime<Utc>,
@@ -319,11 +337,7 @@ impl SweepRunner {
                     ParameterSpace::describe_config(&config)
                 );
 
-                // Create a new data loader instance for this run
-                // This is needed because the loader may have internal state
-                let loader_clone = (*loader).clone();
-
-                let mut engine = BacktestEngine::new(loader_clone, config.clone(), bt_config);
+                let mut engine = BacktestEngine::new(loader, config.clone(), bt_config);
 
                 match engine.run(start, end).await {
                     Ok(result) => {
@@ -446,6 +460,56 @@ mod tests {
         assert_eq!(configs[1].pair_selection.min_funding_rate, dec!(0.0002));
     }
 
+    /// A loader that can't be copied, so a sweep can only share it.
+    struct SharedOnly(crate::backtest::CsvDataLoader);
+
+    impl DataLoader for SharedOnly {
+        fn load_snapshots(
+            &self,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+        ) -> Result<Vec<crate::backtest::MarketSnapshot>> {
+            self.0.load_snapshots(start, end)
+        }
+
+        fn available_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+            self.0.available_range()
+        }
+
+        fn available_symbols(&self) -> Vec<String> {
+            self.0.available_symbols()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_runs_share_one_loader() {
+        use chrono::TimeZone;
+
+        let loader: crate::backtest::CsvDataLoader = "\
+timestamp,symbol,funding_rate,price,volume_24h,spread,open_interest
+2024-01-01T00:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+2024-01-01T04:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+2024-01-01T08:00:00Z,ALTUSDT,0.001,10,2000000000,0.0001,900000000
+"
+        .parse()
+        .unwrap();
+        let space = ParameterSpace {
+            default_leverage: vec![3, 5],
+            ..ParameterSpace::minimal()
+        };
+        let runner = SweepRunner::new(space, Config::default(), BacktestConfig::default(), 2);
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap();
+        let results = runner.run(SharedOnly(loader), start, end).await.unwrap();
+
+        assert_eq!(results.successful_runs, 2);
+        assert!(results
+            .runs
+            .iter()
+            .all(|(_, result)| result.snapshots_processed == 3));
+    }
+
     #[test]
     fn test_describe_config() {
         let config = Config::default();
@@ -1,17 +1,147 @@
 //! Configuration management for the funding fee farmer.
 //!
 //! Loads settings from environment variables and config files.
+//!
+//! Sources are layered in order, later ones overriding earlier ones:
+//! 1. Built-in defaults
+//! 2. Base `config.toml`
+//! 3. Profile overlay `config.<profile>.toml` (e.g. `config.live.toml`)
+//! 4. `FFF__*` environment variables
+//!
+//! Both files are read from the config directory (`--config-dir`, default the
+//! working directory).
 
+use crate::exchange::Venue;
+use crate::utils::DisplayTimezone;
 use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Environment variable used to select the config profile when no CLI flag is given.
+pub const PROFILE_ENV_VAR: &str = "FFF_PROFILE";
+
+/// Hex digits kept of the configuration hash.
+const CONFIG_HASH_LEN: usize = 12;
+
+/// Placeholder written in place of credentials when exporting configuration.
+const REDACTED: &str = "<redacted>";
+
+/// Maximum drawdown allowed by the live profile.
+pub const LIVE_MAX_DRAWDOWN: Decimal = Decimal::from_parts(10, 0, 0, false, 2); // 0.10
+/// Maximum default leverage allowed by the live profile.
+pub const LIVE_MAX_LEVERAGE: u8 = 5;
+
+/// Longest strategy tag that keeps tagged client order IDs within Binance's 36 characters.
+pub const MAX_CLIENT_ORDER_TAG_LEN: usize = 6;
+
+/// Deployment profile selecting the config overlay and validation strictness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// Local development (default)
+    #[default]
+    Dev,
+    /// Paper trading against the mock client
+    Paper,
+    /// Real money - enforces stricter risk limits
+    Live,
+}
+
+impl Profile {
+    /// Profile name as used in file names and env vars.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Dev => "dev",
+            Profile::Paper => "paper",
+            Profile::Live => "live",
+        }
+    }
+
+    /// Name of the overlay config file (without extension), e.g. `config.live`.
+    pub fn config_file(&self) -> String {
+        format!("config.{}", self.as_str())
+    }
+
+    /// Resolve the active profile.
+    ///
+    /// Precedence: explicit value (CLI) > `FFF_PROFILE` env var > `fallback`.
+    pub fn resolve(explicit: Option<Profile>, fallback: Profile) -> Result<Self> {
+        if let Some(profile) = explicit {
+            return Ok(profile);
+        }
+
+        match std::env::var(PROFILE_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => value
+                .parse()
+                .with_context(|| format!("Invalid {}", PROFILE_ENV_VAR)),
+            _ => Ok(fallback),
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dev" => Ok(Profile::Dev),
+            "paper" => Ok(Profile::Paper),
+            "live" => Ok(Profile::Live),
+            other => anyhow::bail!("Unknown profile '{}' (expected dev, paper or live)", other),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Output format for exporting the effective configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl FromStr for ConfigFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            other => anyhow::bail!("Unknown format '{}' (expected toml or json)", other),
+        }
+    }
+}
 
 /// Main application configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Profile this configuration was loaded with (set by the loader, never read from files)
+    #[serde(default, skip_deserializing)]
+    pub profile: Profile,
+    /// Venue whose perpetuals and spot market are traded: "binance", "bybit" or "okx"
+    #[serde(default)]
+    pub venue: Venue,
     /// Binance API credentials
     #[serde(default)]
     pub binance: BinanceConfig,
+    /// Bybit API credentials, used when `venue = "bybit"`
+    #[serde(default)]
+    pub bybit: BybitConfig,
+    /// OKX API credentials, used when `venue = "okx"` or entries are routed to OKX
+    #[serde(default)]
+    pub okx: OkxConfig,
     /// Capital allocation settings
     #[serde(default)]
     pub capital: CapitalConfig,
@@ -24,14 +154,116 @@ pub struct Config {
     /// Execution parameters
     #[serde(default)]
     pub execution: ExecutionConfig,
+    /// Display settings for logs and reports
+    #[serde(default)]
+    pub display: DisplayConfig,
+    /// Macro event calendar for entry blackouts
+    #[serde(default)]
+    pub events: EventsConfig,
+    /// Announced exchange maintenance and the live system status
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    /// Cross-venue (Binance vs Hyperliquid) funding spread monitoring
+    #[serde(default)]
+    pub cross_venue: CrossVenueConfig,
+    /// Target hedge ratio and rebalance band
+    #[serde(default)]
+    pub hedge: HedgeConfig,
+    /// Partial closes at funding milestones
+    #[serde(default)]
+    pub take_profit: TakeProfitConfig,
+    /// Reaction to each alert class and severity
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    /// Exchange request weight and order limits of the account
+    #[serde(default)]
+    pub api_budget: ApiBudgetConfig,
+    /// Risk-free rate returns are compared against
+    #[serde(default)]
+    pub risk_free: RiskFreeConfig,
 }
 
+/// Binance credentials and endpoints.
+///
+/// `api_key` needs trading enabled and should be IP-restricted; it signs orders,
+/// leverage and margin changes, borrows and transfers. Account and order reads
+/// are signed with the read-only `read_api_key` when set, so the key used most
+/// often can't trade.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinanceConfig {
-    /// API key for authentication
+    /// Trade-enabled API key
+    #[serde(default)]
+    pub api_key: String,
+    /// Secret key for signing trading requests: the HMAC secret, or the Ed25519
+    /// private key (PKCS#8 PEM, or its base64 body on one line)
+    #[serde(default)]
+    pub secret_key: String,
+    /// Kind of every Binance key set, including the sub-account's
+    #[serde(default)]
+    pub key_type: BinanceKeyType,
+    /// Read-only API key for account and order reads (empty = use `api_key`)
+    #[serde(default)]
+    pub read_api_key: String,
+    /// Secret key of `read_api_key`
+    #[serde(default)]
+    pub read_secret_key: String,
+    /// Use testnet instead of production
+    #[serde(default)]
+    pub testnet: bool,
+    /// Isolate strategy capital in a dedicated sub-account
+    #[serde(default)]
+    pub sub_account: SubAccountConfig,
+    /// How long after its timestamp Binance accepts a signed request (ms, max 60000)
+    #[serde(default = "default_recv_window_ms")]
+    pub recv_window_ms: u64,
+    /// Minutes between calibrations of the clock offset against the server time
+    #[serde(default = "default_time_sync_minutes")]
+    pub time_sync_minutes: u32,
+}
+
+impl Default for BinanceConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            secret_key: String::new(),
+            key_type: BinanceKeyType::default(),
+            read_api_key: String::new(),
+            read_secret_key: String::new(),
+            testnet: false,
+            sub_account: SubAccountConfig::default(),
+            recv_window_ms: default_recv_window_ms(),
+            time_sync_minutes: default_time_sync_minutes(),
+        }
+    }
+}
+
+/// How Binance API keys sign requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinanceKeyType {
+    /// HMAC-SHA256 with a shared secret (default)
+    #[default]
+    Hmac,
+    /// Ed25519 with a private key that never leaves the host
+    Ed25519,
+}
+
+fn default_recv_window_ms() -> u64 {
+    5000
+}
+
+fn default_time_sync_minutes() -> u32 {
+    30
+}
+
+/// Bybit credentials and endpoints.
+///
+/// The keys need unified trading account (UTA) access with contract and spot
+/// trading enabled; one key signs both reads and orders.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BybitConfig {
     #[serde(default)]
     pub api_key: String,
-    /// Secret key for signing requests
     #[serde(default)]
     pub secret_key: String,
     /// Use testnet instead of production
@@ -39,6 +271,86 @@ pub struct BinanceConfig {
     pub testnet: bool,
 }
 
+/// OKX credentials, endpoints and cross-venue entry routing.
+///
+/// The keys need trade permission on an account in multi-currency or portfolio
+/// margin mode, which borrows spot and margins swaps from one pool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OkxConfig {
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default)]
+    pub secret_key: String,
+    /// Passphrase chosen when the API key was created
+    #[serde(default)]
+    pub passphrase: String,
+    /// Trade in the demo environment instead of production
+    #[serde(default)]
+    pub demo: bool,
+    /// With `venue = "binance"`, open each pair on whichever of Binance and OKX
+    /// pays more funding
+    #[serde(default)]
+    pub route_entries: bool,
+}
+
+/// Sub-account capital isolation.
+///
+/// When enabled, live startup tops the sub-account wallet up to `capital` from the
+/// master spot wallet and shutdown sweeps its free balance back. The master keys
+/// only move funds; trading runs on the sub-account keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAccountConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Sub-account email used by the transfer API
+    #[serde(default)]
+    pub email: String,
+    /// Sub-account API key used for trading
+    #[serde(default)]
+    pub api_key: String,
+    /// Sub-account secret key used for trading
+    #[serde(default)]
+    pub secret_key: String,
+    /// Read-only sub-account API key for account reads (empty = use `api_key`)
+    #[serde(default)]
+    pub read_api_key: String,
+    /// Secret key of `read_api_key`
+    #[serde(default)]
+    pub read_secret_key: String,
+    /// Asset transferred into the sub-account
+    #[serde(default = "default_sub_account_asset")]
+    pub asset: String,
+    /// Target balance of `asset` in the sub-account wallet
+    #[serde(default)]
+    pub capital: Decimal,
+    /// Sub-account wallet receiving the capital ("USDT_FUTURE" or "MARGIN")
+    #[serde(default = "default_sub_account_wallet")]
+    pub wallet: String,
+    /// Sweep the free balance back to the master account on shutdown
+    #[serde(default = "default_sweep_on_shutdown")]
+    pub sweep_on_shutdown: bool,
+}
+
+/// Sub-account wallets the transfer orchestration can fund and read back.
+pub const SUB_ACCOUNT_WALLETS: [&str; 2] = ["USDT_FUTURE", "MARGIN"];
+
+impl Default for SubAccountConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            email: String::new(),
+            api_key: String::new(),
+            secret_key: String::new(),
+            read_api_key: String::new(),
+            read_secret_key: String::new(),
+            asset: default_sub_account_asset(),
+            capital: Decimal::ZERO,
+            wallet: default_sub_account_wallet(),
+            sweep_on_shutdown: default_sweep_on_shutdown(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CapitalConfig {
     /// Maximum percentage of capital to deploy (0.0-1.0)
@@ -54,12 +366,323 @@ pub struct CapitalConfig {
     /// Default 0.2 = 20% drift triggers reduction
     #[serde(default = "default_rebalance_threshold")]
     pub rebalance_threshold: Decimal,
+    /// Smallest reduction in USDT traded for rebalancing or take-profits; smaller
+    /// ones wait until they grow (risk-driven reductions always run)
+    #[serde(default = "default_min_reduction_usdt")]
+    pub min_reduction_usdt: Decimal,
     /// Allocation concentration factor (1.0-3.0)
     /// 1.0 = equal weighting across positions
     /// 2.0 = geometric weighting (50%, 25%, 12.5%, ...)
     /// 1.5 = moderate concentration (recommended, ~35%, 25%, 20%, ...)
     #[serde(default = "default_allocation_concentration")]
     pub allocation_concentration: Decimal,
+    /// Absolute notional ceilings, applied on top of the percentage limits
+    #[serde(default)]
+    pub notional_caps: NotionalCapsConfig,
+    /// Spot coins already held that may serve as the long leg of a hedge
+    #[serde(default)]
+    pub existing_holdings: ExistingHoldingsConfig,
+    /// Deployment below `max_utilization` as margin headroom tightens
+    #[serde(default)]
+    pub dynamic_utilization: DynamicUtilizationConfig,
+    /// Temporary release of part of the reserve for exceptional funding
+    #[serde(default)]
+    pub flash_reserve: FlashReserveConfig,
+    /// Scheduled shift of capital between held positions toward target weights
+    #[serde(default)]
+    pub portfolio_rebalance: PortfolioRebalanceConfig,
+}
+
+/// Absolute notional ceilings per symbol and per symbol group (USDT).
+///
+/// Percentage limits scale with equity; these cap exposure to illiquid
+/// names regardless of account size. A value of 0 disables a ceiling.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotionalCapsConfig {
+    /// Default ceiling for any single symbol (0 = none)
+    #[serde(default)]
+    pub max_per_symbol: Decimal,
+    /// Per-symbol overrides of `max_per_symbol`
+    #[serde(default)]
+    pub symbols: Vec<SymbolNotionalCap>,
+    /// Ceilings on the combined notional of a group of symbols
+    #[serde(default)]
+    pub groups: Vec<GroupNotionalCap>,
+}
+
+/// Notional ceiling for a single symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolNotionalCap {
+    /// Futures symbol (e.g., "DOGEUSDT")
+    pub symbol: String,
+    /// Maximum position notional in USDT
+    pub max_notional: Decimal,
+}
+
+/// Notional ceiling shared by a group of symbols.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupNotionalCap {
+    /// Group name used in logs and alerts (e.g., "memes")
+    pub name: String,
+    /// Member futures symbols
+    pub symbols: Vec<String>,
+    /// Maximum combined notional in USDT
+    pub max_notional: Decimal,
+}
+
+/// An existing exposure above its configured notional ceiling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotionalCapBreach {
+    /// Symbol, or `group:<name>` for group ceilings
+    pub scope: String,
+    /// Current notional in USDT
+    pub notional: Decimal,
+    /// Configured ceiling in USDT
+    pub cap: Decimal,
+}
+
+impl NotionalCapsConfig {
+    /// Ceiling for a symbol, if any (explicit override first, then the default).
+    pub fn symbol_cap(&self, symbol: &str) -> Option<Decimal> {
+        let cap = self
+            .symbols
+            .iter()
+            .find(|c| c.symbol.eq_ignore_ascii_case(symbol))
+            .map(|c| c.max_notional)
+            .unwrap_or(self.max_per_symbol);
+
+        (cap > Decimal::ZERO).then_some(cap)
+    }
+
+    /// Maximum total notional `symbol` may hold given the other positions.
+    ///
+    /// Group ceilings are reduced by the notional of the other members in
+    /// `positions` (symbol -> notional). Sizing several members, pass the sizes
+    /// already settled for the others, or each is cut against the others'
+    /// notional before their own cuts. Returns `None` when uncapped.
+    pub fn max_notional_for(
+        &self,
+        symbol: &str,
+        positions: &HashMap<String, Decimal>,
+    ) -> Option<Decimal> {
+        let mut limit = self.symbol_cap(symbol);
+
+        for group in self.groups_containing(symbol) {
+            let others: Decimal = positions
+                .iter()
+                .filter(|(s, _)| !s.eq_ignore_ascii_case(symbol) && group.contains(s))
+                .map(|(_, v)| v.abs())
+                .sum();
+            let headroom = (group.max_notional - others).max(Decimal::ZERO);
+            limit = Some(limit.map_or(headroom, |l| l.min(headroom)));
+        }
+
+        limit
+    }
+
+    /// Existing positions (symbol -> notional) exceeding a symbol or group ceiling.
+    pub fn breaches(&self, positions: &HashMap<String, Decimal>) -> Vec<NotionalCapBreach> {
+        let mut breaches: Vec<_> = positions
+            .iter()
+            .filter_map(|(symbol, value)| {
+                let cap = self.symbol_cap(symbol)?;
+                (value.abs() > cap).then(|| NotionalCapBreach {
+                    scope: symbol.clone(),
+                    notional: value.abs(),
+                    cap,
+                })
+            })
+            .collect();
+        breaches.sort_by(|a, b| a.scope.cmp(&b.scope));
+
+        for group in self
+            .groups
+            .iter()
+            .filter(|g| g.max_notional > Decimal::ZERO)
+        {
+            let notional: Decimal = positions
+                .iter()
+                .filter(|(s, _)| group.contains(s))
+                .map(|(_, v)| v.abs())
+                .sum();
+            if notional > group.max_notional {
+                breaches.push(NotionalCapBreach {
+                    scope: format!("group:{}", group.name),
+                    notional,
+                    cap: group.max_notional,
+                });
+            }
+        }
+
+        breaches
+    }
+
+    fn groups_containing<'a>(
+        &'a self,
+        symbol: &'a str,
+    ) -> impl Iterator<Item = &'a GroupNotionalCap> + 'a {
+        self.groups
+            .iter()
+            .filter(move |g| g.max_notional > Decimal::ZERO && g.contains(symbol))
+    }
+}
+
+impl GroupNotionalCap {
+    /// Whether `symbol` is a member of this group.
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+}
+
+/// Existing-holdings hedge mode.
+///
+/// When enabled, positive-funding entries on a listed asset open only the futures
+/// short and count the declared spot quantity as the long leg. Exits close the
+/// short but never sell the declared coins.
+///
+/// With `coin_margined`, the coins sit in the COIN-margined futures wallet as
+/// collateral of a short on the asset's COIN-margined perpetual instead, so no
+/// USDT margin is needed at all (live Binance only).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExistingHoldingsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Coins available as the long leg
+    #[serde(default)]
+    pub assets: Vec<HeldAsset>,
+    /// Hedge the coins with COIN-margined perpetuals rather than USDT-margined ones
+    #[serde(default)]
+    pub coin_margined: bool,
+}
+
+/// A spot balance declared for existing-holdings hedging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldAsset {
+    /// Base asset (e.g., "BTC")
+    pub asset: String,
+    /// Quantity of the base asset that may be used
+    pub quantity: Decimal,
+}
+
+impl ExistingHoldingsConfig {
+    /// Declared quantity usable for `asset` (zero when disabled or not listed).
+    pub fn held_quantity(&self, asset: &str) -> Decimal {
+        if !self.enabled {
+            return Decimal::ZERO;
+        }
+        self.assets
+            .iter()
+            .filter(|h| h.asset.eq_ignore_ascii_case(asset))
+            .map(|h| h.quantity)
+            .sum::<Decimal>()
+            .max(Decimal::ZERO)
+    }
+
+    /// Whether existing holdings of `asset` hedge new entries.
+    pub fn covers(&self, asset: &str) -> bool {
+        self.held_quantity(asset) > Decimal::ZERO
+    }
+}
+
+/// Dynamic capital utilization.
+///
+/// `max_utilization` stays the ceiling; the controller deploys less while margin
+/// health, effective leverage or mark price volatility leave little headroom.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicUtilizationConfig {
+    #[serde(default = "default_dynamic_utilization_enabled")]
+    pub enabled: bool,
+    /// Utilization deployed once headroom is exhausted (0.0-1.0)
+    #[serde(default = "default_min_utilization")]
+    pub min_utilization: Decimal,
+    /// Largest utilization increase per risk cycle; decreases apply at once
+    #[serde(default = "default_utilization_step")]
+    pub step: Decimal,
+    /// Daily mark price volatility of held symbols above which utilization shrinks
+    #[serde(default = "default_target_daily_volatility")]
+    pub target_daily_volatility: Decimal,
+}
+
+impl Default for DynamicUtilizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_dynamic_utilization_enabled(),
+            min_utilization: default_min_utilization(),
+            step: default_utilization_step(),
+            target_daily_volatility: default_target_daily_volatility(),
+        }
+    }
+}
+
+/// Temporary release of part of `reserve_buffer` for exceptional funding.
+///
+/// While a symbol pays at least `min_funding_rate` per 8h, that symbol alone may
+/// deploy `release_fraction` of the reserve for `duration_hours`. One event runs
+/// at a time, and the next waits `cooldown_hours` after the last one ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashReserveConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 8h-equivalent funding rate (absolute) of a flash opportunity
+    #[serde(default = "default_flash_min_funding_rate")]
+    pub min_funding_rate: Decimal,
+    /// Share of the reserve one event may deploy (0.0-1.0)
+    #[serde(default = "default_flash_release_fraction")]
+    pub release_fraction: Decimal,
+    /// Hours a release lasts before the reserve is restored
+    #[serde(default = "default_flash_duration_hours")]
+    pub duration_hours: u32,
+    /// Hours after an event ends before another may start
+    #[serde(default = "default_flash_cooldown_hours")]
+    pub cooldown_hours: u32,
+    /// Hours after an event ends for the reserve to be whole again before alerting
+    #[serde(default = "default_flash_restore_grace_hours")]
+    pub restore_grace_hours: u32,
+}
+
+impl Default for FlashReserveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_funding_rate: default_flash_min_funding_rate(),
+            release_fraction: default_flash_release_fraction(),
+            duration_hours: default_flash_duration_hours(),
+            cooldown_hours: default_flash_cooldown_hours(),
+            restore_grace_hours: default_flash_restore_grace_hours(),
+        }
+    }
+}
+
+/// Periodic rebalance of the whole book toward its allocation targets.
+///
+/// Every `interval_hours` the allocation is re-run as if the book were flat.
+/// Held positions more than `min_drift` away from their target shrink or grow,
+/// with growth funded only by the capital the shrinks free. Shrinks respect the
+/// minimum holding period and the action cost gate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioRebalanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hours between portfolio rebalances
+    #[serde(default = "default_portfolio_rebalance_interval_hours")]
+    pub interval_hours: u32,
+    /// Drift from target, as a share of the current size, before a position moves (0.0-1.0)
+    #[serde(default = "default_portfolio_rebalance_min_drift")]
+    pub min_drift: Decimal,
+    /// Smallest shift in USDT worth trading
+    #[serde(default = "default_portfolio_rebalance_min_shift_usdt")]
+    pub min_shift_usdt: Decimal,
+}
+
+impl Default for PortfolioRebalanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_portfolio_rebalance_interval_hours(),
+            min_drift: default_portfolio_rebalance_min_drift(),
+            min_shift_usdt: default_portfolio_rebalance_min_shift_usdt(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,12 +724,46 @@ pub struct RiskConfig {
     /// Maximum allowed funding deviation (0.0-1.0)
     #[serde(default = "default_max_funding_deviation")]
     pub max_funding_deviation: Decimal,
+    /// Rolling window (hours) of observed rates used as the funding expectation
+    #[serde(default = "default_funding_expectation_window_hours")]
+    pub funding_expectation_window_hours: u32,
+    /// Flag a held position when its predicted funding falls below this fraction
+    /// of the entry rate (or flips sign) before the next settlement
+    #[serde(default = "default_funding_collapse_ratio")]
+    pub funding_collapse_ratio: Decimal,
+    /// Minutes between spot book samples pricing the exit of each held hedge (0 = disabled)
+    #[serde(default = "default_exit_liquidity_check_minutes")]
+    pub exit_liquidity_check_minutes: u32,
+    /// Alert and downsize when exiting a hedge would cost more than this fraction of
+    /// its accrued funding (at least one expected payment)
+    #[serde(default = "default_max_exit_cost_ratio")]
+    pub max_exit_cost_ratio: Decimal,
     /// Maximum absolute loss in USD before force exit (e.g., 10.0 = $10)
     #[serde(default = "default_max_loss_usd")]
     pub max_loss_usd: Decimal,
     /// Maximum negative APY before force exit (0.0-1.0, e.g., 0.50 = -50% APY)
     #[serde(default = "default_max_negative_apy")]
     pub max_negative_apy: Decimal,
+    /// Flag a held short spot hedge when its borrow rate reaches this multiple of
+    /// its median over the past week
+    #[serde(default = "default_borrow_spike_ratio")]
+    pub borrow_spike_ratio: Decimal,
+    /// Spike multiple during weekends, `borrow_holidays` and the 12 hours before
+    /// them, when lending supply tends to drain
+    #[serde(default = "default_low_supply_spike_ratio")]
+    pub low_supply_spike_ratio: Decimal,
+    /// Dates (UTC, "YYYY-MM-DD") treated like weekends for borrow spike detection
+    #[serde(default)]
+    pub borrow_holidays: Vec<NaiveDate>,
+    /// Exchange borrow limits of assets borrowed for short spot hedges
+    #[serde(default)]
+    pub borrow_limits: BorrowLimitsConfig,
+    /// Minutes a closed symbol is kept out of new entries (0 = re-enter at once)
+    #[serde(default = "default_reentry_cooldown_minutes")]
+    pub reentry_cooldown_minutes: u32,
+    /// Re-entry cool-down after a risk-forced or emergency exit
+    #[serde(default = "default_forced_exit_cooldown_minutes")]
+    pub forced_exit_cooldown_minutes: u32,
 
     // Malfunction detection
     /// Maximum API errors per minute before alert
@@ -118,118 +775,1316 @@ pub struct RiskConfig {
     /// Delta drift percentage that triggers emergency (0.0-1.0)
     #[serde(default = "default_emergency_delta_drift")]
     pub emergency_delta_drift: Decimal,
+    /// Portfolio-wide residual delta in USD that raises an alert, independent of
+    /// per-symbol drift
+    #[serde(default = "default_max_residual_delta_usd")]
+    pub max_residual_delta_usd: Decimal,
+    /// Minutes a persisted failure counter, malfunction alert or halt stays in force
+    /// across restarts (0 = start clean)
+    #[serde(default = "default_malfunction_state_max_age_minutes")]
+    pub malfunction_state_max_age_minutes: u32,
 
     // Circuit breaker
     /// Maximum consecutive risk check cycles with ERROR/CRITICAL alerts before halting
     #[serde(default = "default_max_consecutive_risk_cycles")]
     pub max_consecutive_risk_cycles: u32,
-}
+    /// Throttled return to trading after a halt is reset
+    #[serde(default)]
+    pub resumption: ResumptionConfig,
+    /// Stop adding to symbols whose funding payments keep deviating from expectations
+    #[serde(default)]
+    pub funding_quarantine: FundingQuarantineConfig,
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PairSelectionConfig {
-    /// Minimum 24h trading volume in USDT
-    #[serde(default = "default_min_volume")]
-    pub min_volume_24h: Decimal,
-    /// Minimum absolute funding rate
-    #[serde(default = "default_min_funding_rate")]
-    pub min_funding_rate: Decimal,
-    /// Maximum bid-ask spread
-    #[serde(default = "default_max_spread")]
-    pub max_spread: Decimal,
-    /// Minimum open interest in USDT
-    #[serde(default = "default_min_open_interest")]
-    pub min_open_interest: Decimal,
-    /// Maximum number of concurrent positions (concentrate capital)
-    #[serde(default = "default_max_positions")]
-    pub max_positions: u8,
-    /// Default daily borrow rate for assets with missing margin data
-    #[serde(default = "default_borrow_rate")]
-    pub default_borrow_rate: Decimal,
-    /// Minimum net funding rate per 8h (funding - borrow cost) to accept a pair
-    /// Rejects pairs where borrowing costs would eat most/all funding income
-    #[serde(default = "default_min_net_funding")]
-    pub min_net_funding: Decimal,
+    // Exchange outage
+    /// Minutes the exchange may be unreachable with open positions before
+    /// entering safe-mode (0 = never)
+    #[serde(default = "default_safe_mode_after_minutes")]
+    pub safe_mode_after_minutes: u32,
+
+    // State persistence
+    /// Escalation and fallbacks when state saves keep failing
+    #[serde(default)]
+    pub persistence_watchdog: PersistenceWatchdogConfig,
+
+    // Operator policies
+    /// Declarative rules evaluated every risk check, on top of the built-in limits
+    #[serde(default)]
+    pub rules: Vec<RiskRule>,
 }
 
+/// Supervised resumption after a halt is reset.
+///
+/// The first cycle after `reset_halt` only observes. New entries then resume one
+/// at a time (`max_entries_per_cycle`) at `initial_size_fraction` of their
+/// allocation, growing to full size over `ramp_cycles` cycles without
+/// ERROR/CRITICAL alerts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExecutionConfig {
-    /// Default leverage for positions
-    #[serde(default = "default_leverage")]
-    pub default_leverage: u8,
-    /// Maximum leverage allowed
-    #[serde(default = "default_max_leverage")]
-    pub max_leverage: u8,
-    /// Maximum slippage tolerance (0.0-1.0)
-    #[serde(default = "default_slippage_tolerance")]
-    pub slippage_tolerance: Decimal,
-    /// Order timeout in seconds
-    #[serde(default = "default_order_timeout")]
-    pub order_timeout_secs: u64,
+pub struct ResumptionConfig {
+    #[serde(default = "default_resumption_enabled")]
+    pub enabled: bool,
+    /// Clean cycles after the observation cycle until entries are back to normal
+    #[serde(default = "default_resumption_ramp_cycles")]
+    pub ramp_cycles: u32,
+    /// New positions opened per cycle during the ramp
+    #[serde(default = "default_resumption_max_entries_per_cycle")]
+    pub max_entries_per_cycle: u32,
+    /// Share of the allocated size for entries in the first ramp cycle (0.0-1.0)
+    #[serde(default = "default_resumption_initial_size_fraction")]
+    pub initial_size_fraction: Decimal,
 }
 
-// Default value functions
-fn default_max_utilization() -> Decimal {
-    Decimal::new(85, 2) // 0.85
+impl Default for ResumptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_resumption_enabled(),
+            ramp_cycles: default_resumption_ramp_cycles(),
+            max_entries_per_cycle: default_resumption_max_entries_per_cycle(),
+            initial_size_fraction: default_resumption_initial_size_fraction(),
+        }
+    }
 }
 
-fn default_reserve_buffer() -> Decimal {
-    Decimal::new(10, 2) // 0.10
+/// Quarantine of symbols with persistent funding anomalies.
+///
+/// After `anomalies` consecutive payments deviate from their expectation by more
+/// than `max_funding_deviation`, the symbol gets no new entries or top-ups until
+/// released with `funding-fee-farmer quarantine --release <SYMBOL>`. With `exit`
+/// the held position is closed as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingQuarantineConfig {
+    #[serde(default = "default_funding_quarantine_enabled")]
+    pub enabled: bool,
+    /// Consecutive anomalous payments that quarantine a symbol
+    #[serde(default = "default_funding_quarantine_anomalies")]
+    pub anomalies: u32,
+    /// Close held positions of quarantined symbols
+    #[serde(default)]
+    pub exit: bool,
 }
 
-fn default_min_position_size() -> Decimal {
-    Decimal::new(1000, 0) // 1000 USDT
+impl Default for FundingQuarantineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_funding_quarantine_enabled(),
+            anomalies: default_funding_quarantine_anomalies(),
+            exit: false,
+        }
+    }
 }
 
-fn default_rebalance_threshold() -> Decimal {
-    Decimal::new(20, 2) // 0.20 = 20% drift triggers reduction
+/// Exchange borrow limits of margin-borrowed hedge assets (live Binance only).
+///
+/// Every `refresh_minutes` the remaining borrowable amount and the borrow limit
+/// are fetched for each borrowed asset and for up to `max_candidates` scanned
+/// pairs whose hedge would borrow. Utilization of a limit at or above
+/// `alert_utilization` raises an alert, spot hedges with less than
+/// `min_headroom_usdt` left to borrow stop qualifying, and entries that would
+/// borrow more than is left are refused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BorrowLimitsConfig {
+    #[serde(default = "default_borrow_limits_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_borrow_limits_refresh_minutes")]
+    pub refresh_minutes: u32,
+    /// Share of an asset's borrow limit in use that raises an alert (0.0-1.0)
+    #[serde(default = "default_borrow_limits_alert_utilization")]
+    pub alert_utilization: Decimal,
+    /// Least USDT left to borrow for a spot hedge to qualify
+    #[serde(default = "default_borrow_limits_min_headroom_usdt")]
+    pub min_headroom_usdt: Decimal,
+    /// Scanned assets checked per refresh, best pairs first
+    #[serde(default = "default_borrow_limits_max_candidates")]
+    pub max_candidates: usize,
 }
 
-fn default_allocation_concentration() -> Decimal {
-    Decimal::new(15, 1) // 1.5 = moderate concentration (~35%, 25%, 20%, 12%, 8%)
+impl Default for BorrowLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_borrow_limits_enabled(),
+            refresh_minutes: default_borrow_limits_refresh_minutes(),
+            alert_utilization: default_borrow_limits_alert_utilization(),
+            min_headroom_usdt: default_borrow_limits_min_headroom_usdt(),
+            max_candidates: default_borrow_limits_max_candidates(),
+        }
+    }
+}
+
+/// Escalation of consecutive state save failures.
+///
+/// Every failed save is retried against the fallback databases: `secondary_db`
+/// when set, then one in the system temp directory. `error_after_failures` failed
+/// saves in a row raise an ERROR alert and `critical_after_failures` a CRITICAL
+/// one. New entries stop once saves have failed for `halt_entries_after_minutes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistenceWatchdogConfig {
+    #[serde(default = "default_persistence_error_after_failures")]
+    pub error_after_failures: u32,
+    #[serde(default = "default_persistence_critical_after_failures")]
+    pub critical_after_failures: u32,
+    /// Minutes of failing saves before new entries stop (0 = never)
+    #[serde(default = "default_persistence_halt_entries_after_minutes")]
+    pub halt_entries_after_minutes: u32,
+    /// Database tried first when the main one can't be written
+    #[serde(default)]
+    pub secondary_db: Option<String>,
+}
+
+impl Default for PersistenceWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            error_after_failures: default_persistence_error_after_failures(),
+            critical_after_failures: default_persistence_critical_after_failures(),
+            halt_entries_after_minutes: default_persistence_halt_entries_after_minutes(),
+            secondary_db: None,
+        }
+    }
+}
+
+/// Operator-defined risk rule: when every condition holds, the action is taken.
+///
+/// ```toml
+/// [[risk.rules]]
+/// name = "stale-loser"
+/// action = "consider_exit"
+/// when = [
+///   { metric = "net_pnl", op = "<", value = -5 },
+///   { metric = "hours_open", op = ">", value = 24 },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskRule {
+    /// Rule name used in logs and alerts
+    pub name: String,
+    pub action: RuleAction,
+    /// Conditions that must all hold
+    pub when: Vec<RuleCondition>,
+}
+
+impl RiskRule {
+    /// Whether the rule applies to each held position rather than the portfolio.
+    pub fn is_position_rule(&self) -> bool {
+        self.action != RuleAction::HaltEntries
+    }
+}
+
+impl fmt::Display for RiskRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions: Vec<String> = self.when.iter().map(|c| c.to_string()).collect();
+        write!(f, "if {} then {}", conditions.join(" and "), self.action)
+    }
+}
+
+/// A comparison of one metric against a threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    pub metric: RuleMetric,
+    pub op: RuleOp,
+    pub value: Decimal,
+}
+
+impl fmt::Display for RuleCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.metric, self.op, self.value)
+    }
+}
+
+/// Metric a rule condition reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMetric {
+    /// Position net PnL in USD
+    NetPnl,
+    /// Hours since the position was opened
+    HoursOpen,
+    /// Funding received by the position in USD
+    FundingReceived,
+    /// Borrow interest paid by the position in USD
+    InterestPaid,
+    /// Annualized yield of the position so far (0.10 = 10%)
+    Apy,
+    /// Portfolio funding APR net of estimated costs (0.10 = 10%)
+    PortfolioFundingApr,
+    /// Current account drawdown (0.02 = 2%)
+    Drawdown,
+    /// Number of held positions
+    PositionCount,
+}
+
+impl RuleMetric {
+    /// Whether the metric describes a single position.
+    pub fn is_position_metric(&self) -> bool {
+        matches!(
+            self,
+            RuleMetric::NetPnl
+                | RuleMetric::HoursOpen
+                | RuleMetric::FundingReceived
+                | RuleMetric::InterestPaid
+                | RuleMetric::Apy
+        )
+    }
+}
+
+impl fmt::Display for RuleMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RuleMetric::NetPnl => "net_pnl",
+            RuleMetric::HoursOpen => "hours_open",
+            RuleMetric::FundingReceived => "funding_received",
+            RuleMetric::InterestPaid => "interest_paid",
+            RuleMetric::Apy => "apy",
+            RuleMetric::PortfolioFundingApr => "portfolio_funding_apr",
+            RuleMetric::Drawdown => "drawdown",
+            RuleMetric::PositionCount => "position_count",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Comparison operator of a rule condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleOp {
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+
+impl RuleOp {
+    pub fn holds(&self, actual: Decimal, threshold: Decimal) -> bool {
+        match self {
+            RuleOp::Lt => actual < threshold,
+            RuleOp::Le => actual <= threshold,
+            RuleOp::Gt => actual > threshold,
+            RuleOp::Ge => actual >= threshold,
+        }
+    }
+}
+
+impl fmt::Display for RuleOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            RuleOp::Lt => "<",
+            RuleOp::Le => "<=",
+            RuleOp::Gt => ">",
+            RuleOp::Ge => ">=",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+/// What a rule does when it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Warn that the position should be considered for exit
+    ConsiderExit,
+    /// Close the position
+    ForceExit,
+    /// Open no new positions while the rule holds
+    HaltEntries,
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            RuleAction::ConsiderExit => "consider_exit",
+            RuleAction::ForceExit => "force_exit",
+            RuleAction::HaltEntries => "halt_entries",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairSelectionConfig {
+    /// Minimum 24h trading volume in USDT
+    #[serde(default = "default_min_volume")]
+    pub min_volume_24h: Decimal,
+    /// Percentile of the current 24h volumes of all USDT perpetuals a pair must
+    /// reach, recomputed every scan (e.g., 0.7 = top 30%; 0 = off). Combines with
+    /// `min_volume_24h`: the higher of the two floors applies.
+    #[serde(default)]
+    pub min_volume_percentile: Decimal,
+    /// Minimum absolute funding rate
+    #[serde(default = "default_min_funding_rate")]
+    pub min_funding_rate: Decimal,
+    /// Maximum bid-ask spread
+    #[serde(default = "default_max_spread")]
+    pub max_spread: Decimal,
+    /// Minimum open interest in USDT
+    #[serde(default = "default_min_open_interest")]
+    pub min_open_interest: Decimal,
+    /// Maximum number of concurrent positions (concentrate capital)
+    #[serde(default = "default_max_positions")]
+    pub max_positions: u8,
+    /// Default daily borrow rate for assets with missing margin data
+    #[serde(default = "default_borrow_rate")]
+    pub default_borrow_rate: Decimal,
+    /// Minimum net funding rate per 8h (funding - borrow cost) to accept a pair
+    /// Rejects pairs where borrowing costs would eat most/all funding income
+    #[serde(default = "default_min_net_funding")]
+    pub min_net_funding: Decimal,
+    /// Candidates kept by the coarse pre-filter (funding magnitude and futures volume)
+    /// before full qualification; 0 qualifies every symbol
+    #[serde(default = "default_prefilter_top_k")]
+    pub prefilter_top_k: usize,
+    /// How long the last successful scan may stand in for a failed one (minutes).
+    /// Cached pairs feed rebalancing and risk valuation only, never new entries.
+    #[serde(default = "default_scan_cache_max_age_minutes")]
+    pub scan_cache_max_age_minutes: u32,
+    /// Hedge symbols without a usable spot margin pair with an opposite perpetual
+    /// (USDC-margined on Binance, or on another venue). Such pairs are reported, not
+    /// entered: the executor trades spot hedges only.
+    #[serde(default)]
+    pub futures_hedges: bool,
+    /// Expected holding period (hours): the entry premium of a perp hedge is counted
+    /// as carry over it, and capacity estimates weigh slippage against its funding
+    #[serde(default = "default_basis_horizon_hours")]
+    pub basis_horizon_hours: u32,
+    /// Carry saving per 8h a cheaper hedge must offer before a switch is recommended
+    #[serde(default = "default_hedge_switch_threshold")]
+    pub hedge_switch_threshold: Decimal,
+    /// Minutes between hedge carry reviews of held positions (0 = never)
+    #[serde(default = "default_hedge_review_minutes")]
+    pub hedge_review_minutes: u32,
+    /// Days of closed positions that symbol quality scores are learned from (0 = off)
+    #[serde(default = "default_symbol_quality_lookback_days")]
+    pub symbol_quality_lookback_days: u32,
+    /// Consecutive exits with negative net carry that blacklist a symbol (0 = never)
+    #[serde(default = "default_max_symbol_failures")]
+    pub max_symbol_failures: u32,
+    /// Share of a funding rate pinned at the exchange cap expected to persist;
+    /// scales the score of capped pairs and the collapse baseline of their entries
+    #[serde(default = "default_capped_funding_persistence")]
+    pub capped_funding_persistence: Decimal,
+    /// Largest share of a pair's open interest the capacity estimate lets one
+    /// position take
+    #[serde(default = "default_max_open_interest_share")]
+    pub max_open_interest_share: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    /// Default leverage for positions
+    #[serde(default = "default_leverage")]
+    pub default_leverage: u8,
+    /// Maximum leverage allowed
+    #[serde(default = "default_max_leverage")]
+    pub max_leverage: u8,
+    /// Maximum slippage tolerance (0.0-1.0)
+    #[serde(default = "default_slippage_tolerance")]
+    pub slippage_tolerance: Decimal,
+    /// Order timeout in seconds
+    #[serde(default = "default_order_timeout")]
+    pub order_timeout_secs: u64,
+    /// Strategy tag starting every client order ID, for attribution on the exchange
+    #[serde(default = "default_client_order_tag")]
+    pub client_order_tag: String,
+    /// Orders per minute across both clients before the order valve trips (0 = unlimited)
+    #[serde(default = "default_max_orders_per_minute")]
+    pub max_orders_per_minute: u32,
+    /// Notional traded per hour in USDT before the order valve trips (0 = unlimited)
+    #[serde(default = "default_max_notional_per_hour")]
+    pub max_notional_per_hour: Decimal,
+    /// Futures maker fee of the account's tier; negative for a maker rebate
+    #[serde(default = "default_maker_fee_rate")]
+    pub maker_fee_rate: Decimal,
+    /// Futures taker fee of the account's tier
+    #[serde(default = "default_taker_fee_rate")]
+    pub taker_fee_rate: Decimal,
+    /// Reaction to held symbols whose leverage was changed on the exchange
+    #[serde(default)]
+    pub settings_mismatch: SettingsMismatchAction,
+    /// Minutes between leverage and margin type checks of held symbols in live mode
+    /// (0 = startup only)
+    #[serde(default = "default_settings_check_minutes")]
+    pub settings_check_minutes: u32,
+    /// Micro-timing of entries inside the entry window
+    #[serde(default)]
+    pub entry_timing: EntryTimingConfig,
+    /// Benefit-versus-cost check of hedge rebalances and position reductions
+    #[serde(default)]
+    pub action_cost: ActionCostConfig,
+}
+
+/// Micro-timing of entries from the futures book.
+///
+/// An entry ready to execute is held while the futures book leans towards a
+/// better price later (bids outweighing asks before a short, and the reverse
+/// before a long) or the mid is still moving that way, and executed once the
+/// pressure eases or `max_wait_minutes` pass. An entry still facing a book at
+/// least `abort_imbalance` one-sided at the deadline is dropped until the next
+/// funding period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryTimingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Top-of-book imbalance towards a better later price that holds an entry
+    #[serde(default = "default_entry_timing_wait_imbalance")]
+    pub wait_imbalance: Decimal,
+    /// Imbalance at the deadline that aborts the entry
+    #[serde(default = "default_entry_timing_abort_imbalance")]
+    pub abort_imbalance: Decimal,
+    /// Mid move since the last cycle, in bps towards a better later price, that holds an entry
+    #[serde(default = "default_entry_timing_momentum_bps")]
+    pub momentum_bps: Decimal,
+    /// Longest an entry is held before it executes or aborts
+    #[serde(default = "default_entry_timing_max_wait_minutes")]
+    pub max_wait_minutes: u32,
+}
+
+impl Default for EntryTimingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wait_imbalance: default_entry_timing_wait_imbalance(),
+            abort_imbalance: default_entry_timing_abort_imbalance(),
+            momentum_bps: default_entry_timing_momentum_bps(),
+            max_wait_minutes: default_entry_timing_max_wait_minutes(),
+        }
+    }
+}
+
+/// Benefit-versus-cost check of rebalancing trades.
+///
+/// A hedge rebalance is credited with the adverse move of `drift_move` it spares
+/// the drifted notional; a size reduction with the funding gained over
+/// `benefit_horizon_hours` by moving the freed capital to the best alternative.
+/// Either is skipped unless its benefit exceeds the execution cost (fees plus
+/// observed shortfall on every leg traded) by `min_benefit_margin` of that cost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionCostConfig {
+    #[serde(default = "default_action_cost_enabled")]
+    pub enabled: bool,
+    /// Net benefit required, as a fraction of the execution cost
+    #[serde(default = "default_action_cost_min_benefit_margin")]
+    pub min_benefit_margin: Decimal,
+    /// Adverse price move the drifted part of a hedge is exposed to until corrected
+    #[serde(default = "default_action_cost_drift_move")]
+    pub drift_move: Decimal,
+    /// Hours of funding advantage a reduction is credited with
+    #[serde(default = "default_action_cost_benefit_horizon_hours")]
+    pub benefit_horizon_hours: u32,
+}
+
+impl Default for ActionCostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_action_cost_enabled(),
+            min_benefit_margin: default_action_cost_min_benefit_margin(),
+            drift_move: default_action_cost_drift_move(),
+            benefit_horizon_hours: default_action_cost_benefit_horizon_hours(),
+        }
+    }
+}
+
+/// Reaction to a leverage mismatch between the exchange and `default_leverage`.
+///
+/// Margin type can't be changed while a position is open, so a margin type
+/// mismatch is always alerted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsMismatchAction {
+    /// Set the configured leverage again via the API (default)
+    #[default]
+    Reconcile,
+    /// Alert only, leaving the exchange settings alone
+    Alert,
+}
+
+impl ExecutionConfig {
+    /// Fees of the configured tier.
+    pub fn fee_schedule(&self) -> crate::exchange::FeeSchedule {
+        crate::exchange::FeeSchedule {
+            maker_rate: self.maker_fee_rate,
+            taker_rate: self.taker_fee_rate,
+        }
+    }
+}
+
+/// Display settings. Times are kept in UTC internally and only converted for display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    /// IANA time zone for displayed timestamps (e.g. "UTC", "Asia/Seoul")
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            timezone: default_timezone(),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// Parsed display time zone, falling back to UTC if the name is unknown.
+    pub fn display_timezone(&self) -> DisplayTimezone {
+        DisplayTimezone::parse(&self.timezone).unwrap_or_default()
+    }
+}
+
+/// Macro event calendar (FOMC, CPI, ...) that pauses or shrinks new entries around
+/// scheduled high-impact releases. Held positions are not touched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// TOML file with `[[events]]` entries (empty = none)
+    #[serde(default)]
+    pub file: String,
+    /// URL returning a JSON array of events (empty = none)
+    #[serde(default)]
+    pub calendar_url: String,
+    /// Hours between calendar URL refreshes
+    #[serde(default = "default_calendar_refresh_hours")]
+    pub refresh_hours: u32,
+    /// Minutes before an event when its window starts
+    #[serde(default = "default_event_window_before_minutes")]
+    pub window_before_minutes: u32,
+    /// Minutes after an event when its window ends
+    #[serde(default = "default_event_window_after_minutes")]
+    pub window_after_minutes: u32,
+    /// Entry size multiplier inside a medium-impact event window (high impact blocks entries)
+    #[serde(default = "default_event_reduced_size_factor")]
+    pub reduced_size_factor: Decimal,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: String::new(),
+            calendar_url: String::new(),
+            refresh_hours: default_calendar_refresh_hours(),
+            window_before_minutes: default_event_window_before_minutes(),
+            window_after_minutes: default_event_window_after_minutes(),
+            reduced_size_factor: default_event_reduced_size_factor(),
+        }
+    }
+}
+
+/// Exchange maintenance awareness.
+///
+/// Binance maintenance suspends trading while funding keeps accruing. New entries
+/// stop `entry_buffer_minutes` before an announced window and stay off until it
+/// ends or the system status reports normal again. Failures inside a window are
+/// expected downtime and don't count towards malfunction detection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    #[serde(default = "default_maintenance_enabled")]
+    pub enabled: bool,
+    /// Minutes before a window when new entries stop
+    #[serde(default = "default_maintenance_entry_buffer_minutes")]
+    pub entry_buffer_minutes: u32,
+    /// Minutes between system status polls (0 = announced windows only)
+    #[serde(default = "default_maintenance_status_check_minutes")]
+    pub status_check_minutes: u32,
+    /// Announced windows, as `[[maintenance.windows]]` tables
+    #[serde(default)]
+    pub windows: Vec<MaintenanceWindow>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_maintenance_enabled(),
+            entry_buffer_minutes: default_maintenance_entry_buffer_minutes(),
+            status_check_minutes: default_maintenance_status_check_minutes(),
+            windows: Vec::new(),
+        }
+    }
+}
+
+/// An announced maintenance window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    /// Start of the window (RFC 3339)
+    pub start: DateTime<Utc>,
+    /// End of the window (RFC 3339)
+    pub end: DateTime<Utc>,
+    /// Announcement title or scope, for logs
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Exchange API limits the bot budgets against.
+///
+/// Request weight is limited per minute and IP, separately for futures and spot;
+/// orders are limited per minute on futures and per day on spot. Usage is reported
+/// per UTC day, and projected usage of the configured scan cadence and position
+/// count is checked against `warn_fraction` of each limit at startup. Requests
+/// are delayed to stay within `throttle_fraction` of each weight limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiBudgetConfig {
+    /// Futures request weight per minute
+    #[serde(default = "default_futures_weight_per_minute")]
+    pub futures_weight_per_minute: u32,
+    /// Spot request weight per minute
+    #[serde(default = "default_spot_weight_per_minute")]
+    pub spot_weight_per_minute: u32,
+    /// Futures orders per minute
+    #[serde(default = "default_futures_orders_per_minute")]
+    pub futures_orders_per_minute: u32,
+    /// Spot (margin) orders per day
+    #[serde(default = "default_spot_orders_per_day")]
+    pub spot_orders_per_day: u32,
+    /// Share of a limit at which usage is reported as a breach (e.g., 0.8 = 80%)
+    #[serde(default = "default_api_warn_fraction")]
+    pub warn_fraction: Decimal,
+    /// Share of each weight limit requests are throttled to (e.g., 0.9 = 90%)
+    #[serde(default = "default_api_throttle_fraction")]
+    pub throttle_fraction: Decimal,
+    /// Entries and exits per position and day assumed by the projection
+    #[serde(default = "default_round_trips_per_day")]
+    pub round_trips_per_day: u32,
+    /// Minutes between usage reports (0 = daily summary only)
+    #[serde(default = "default_api_report_interval_minutes")]
+    pub report_interval_minutes: u32,
+}
+
+impl Default for ApiBudgetConfig {
+    fn default() -> Self {
+        Self {
+            futures_weight_per_minute: default_futures_weight_per_minute(),
+            spot_weight_per_minute: default_spot_weight_per_minute(),
+            futures_orders_per_minute: default_futures_orders_per_minute(),
+            spot_orders_per_day: default_spot_orders_per_day(),
+            warn_fraction: default_api_warn_fraction(),
+            throttle_fraction: default_api_throttle_fraction(),
+            round_trips_per_day: default_round_trips_per_day(),
+            report_interval_minutes: default_api_report_interval_minutes(),
+        }
+    }
+}
+
+/// Risk-free rate returns are compared against.
+///
+/// Backtest Sharpe and Sortino ratios are computed on returns in excess of it, and
+/// backtest and live reports show how far the strategy beats (or trails) parking
+/// the capital in US Treasury bills. `annual_rate` is used as given unless `fetch`
+/// is set, in which case the latest average T-bill rate is read from `url` and
+/// `annual_rate` only stands in while the fetch fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskFreeConfig {
+    /// Annual risk-free rate (e.g., 0.045 = 4.5%)
+    #[serde(default)]
+    pub annual_rate: Decimal,
+    /// Fetch the current T-bill rate instead of using `annual_rate`
+    #[serde(default)]
+    pub fetch: bool,
+    /// US Treasury FiscalData endpoint for average interest rates on T-bills
+    #[serde(default = "default_risk_free_url")]
+    pub url: String,
+    /// Hours between fetches while trading live
+    #[serde(default = "default_risk_free_refresh_hours")]
+    pub refresh_hours: u32,
+}
+
+impl Default for RiskFreeConfig {
+    fn default() -> Self {
+        Self {
+            annual_rate: Decimal::ZERO,
+            fetch: false,
+            url: default_risk_free_url(),
+            refresh_hours: default_risk_free_refresh_hours(),
+        }
+    }
+}
+
+/// Cross-venue funding spread monitoring.
+///
+/// Compares Binance funding with Hyperliquid funding on the same asset and reports
+/// spreads wide enough for a venue-neutral trade, sized under the caps below.
+/// Alert-only unless `execute` is set, in which case live trading enters new
+/// spreads on Binance futures and Hyperliquid perps (signed with
+/// `HYPERLIQUID_PRIVATE_KEY`) and closes them once the spread narrows below
+/// `exit_spread`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossVenueConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Hyperliquid info API base URL
+    #[serde(default = "default_hyperliquid_url")]
+    pub hyperliquid_url: String,
+    /// Minutes between spread checks
+    #[serde(default = "default_cross_venue_check_minutes")]
+    pub check_interval_minutes: u32,
+    /// Minimum funding spread per 8h to report (e.g., 0.0005 = 0.05%)
+    #[serde(default = "default_min_funding_spread")]
+    pub min_spread: Decimal,
+    /// Suggested notional cap per asset in USDT
+    #[serde(default = "default_cross_venue_max_notional_per_pair")]
+    pub max_notional_per_pair: Decimal,
+    /// Suggested notional cap across all cross-venue trades in USDT
+    #[serde(default = "default_cross_venue_max_total_notional")]
+    pub max_total_notional: Decimal,
+    /// Trade new spreads instead of only reporting them (live mode only)
+    #[serde(default)]
+    pub execute: bool,
+    /// Spread per 8h, in the direction held, below which a trade is closed
+    #[serde(default = "default_cross_venue_exit_spread")]
+    pub exit_spread: Decimal,
+}
+
+impl Default for CrossVenueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hyperliquid_url: default_hyperliquid_url(),
+            check_interval_minutes: default_cross_venue_check_minutes(),
+            min_spread: default_min_funding_spread(),
+            max_notional_per_pair: default_cross_venue_max_notional_per_pair(),
+            max_total_notional: default_cross_venue_max_total_notional(),
+            execute: false,
+            exit_spread: default_cross_venue_exit_spread(),
+        }
+    }
+}
+
+/// Partial closes that lock in funding gains.
+///
+/// Once a position's net funding (funding received minus borrow interest) since its
+/// last take reaches `fee_multiple` times its entry fees or `notional_pct` of its
+/// notional, whichever comes first, it is reduced by `reduce_fraction`. At most
+/// `max_takes` reductions are made per position, so a core position stays open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeProfitConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Milestone as a multiple of entry fees (0 = off)
+    #[serde(default = "default_take_profit_fee_multiple")]
+    pub fee_multiple: Decimal,
+    /// Milestone as a fraction of notional (0 = off)
+    #[serde(default = "default_take_profit_notional_pct")]
+    pub notional_pct: Decimal,
+    /// Fraction of the position closed at each milestone
+    #[serde(default = "default_take_profit_reduce_fraction")]
+    pub reduce_fraction: Decimal,
+    /// Maximum partial closes per position
+    #[serde(default = "default_take_profit_max_takes")]
+    pub max_takes: u32,
+}
+
+impl Default for TakeProfitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fee_multiple: default_take_profit_fee_multiple(),
+            notional_pct: default_take_profit_notional_pct(),
+            reduce_fraction: default_take_profit_reduce_fraction(),
+            max_takes: default_take_profit_max_takes(),
+        }
+    }
+}
+
+/// Alert routing matrix.
+///
+/// Every risk alert has a class and a severity, and each cell of that matrix
+/// maps to an action. Cells left out keep the built-in reaction, so only the
+/// reactions an operator wants to tune need to be listed:
+///
+/// ```toml
+/// [alerts.routes.margin_warning]
+/// error = "notify"        # report Orange margin health without auto-reducing
+///
+/// [alerts.routes.hedge_switch_recommended]
+/// info = "log"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    /// Overrides per alert class
+    #[serde(default)]
+    pub routes: BTreeMap<AlertClass, SeverityRoutes>,
+}
+
+/// Actions for one alert class by severity; unset severities keep the built-in reaction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeverityRoutes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<AlertAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<AlertAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<AlertAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<AlertAction>,
+}
+
+impl SeverityRoutes {
+    /// Routed severities and their actions, least severe first.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, AlertAction)> {
+        [
+            ("info", self.info),
+            ("warning", self.warning),
+            ("error", self.error),
+            ("critical", self.critical),
+        ]
+        .into_iter()
+        .filter_map(|(severity, action)| action.map(|action| (severity, action)))
+    }
+}
+
+/// Alert class, one per kind of risk alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertClass {
+    MarginWarning,
+    LiquidationRisk,
+    PositionLoss,
+    FundingAnomaly,
+    Malfunction,
+    DrawdownExceeded,
+    DeltaDrift,
+    NotionalCapExceeded,
+    FundingCollapse,
+    ExitLiquidity,
+    ResidualDelta,
+    InsufficientFunds,
+    FundingSpreadOpportunity,
+    HedgeSwitchRecommended,
+    BorrowRateSpike,
+    BorrowLimit,
+    BorrowLimitExceeded,
+    SettingsMismatch,
+    ExchangeMaintenance,
+    ExchangeOutage,
+    ExchangeRecovered,
+    ReserveNotRestored,
+    PersistenceFailure,
+}
+
+impl AlertClass {
+    /// Whether alerts of this class carry a reduction `auto_reduce` can execute.
+    pub fn can_reduce(&self) -> bool {
+        matches!(
+            self,
+            AlertClass::MarginWarning | AlertClass::LiquidationRisk | AlertClass::ExitLiquidity
+        )
+    }
+}
+
+impl fmt::Display for AlertClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertClass::MarginWarning => "margin_warning",
+            AlertClass::LiquidationRisk => "liquidation_risk",
+            AlertClass::PositionLoss => "position_loss",
+            AlertClass::FundingAnomaly => "funding_anomaly",
+            AlertClass::Malfunction => "malfunction",
+            AlertClass::DrawdownExceeded => "drawdown_exceeded",
+            AlertClass::DeltaDrift => "delta_drift",
+            AlertClass::NotionalCapExceeded => "notional_cap_exceeded",
+            AlertClass::FundingCollapse => "funding_collapse",
+            AlertClass::ExitLiquidity => "exit_liquidity",
+            AlertClass::ResidualDelta => "residual_delta",
+            AlertClass::InsufficientFunds => "insufficient_funds",
+            AlertClass::FundingSpreadOpportunity => "funding_spread_opportunity",
+            AlertClass::HedgeSwitchRecommended => "hedge_switch_recommended",
+            AlertClass::BorrowRateSpike => "borrow_rate_spike",
+            AlertClass::BorrowLimit => "borrow_limit",
+            AlertClass::BorrowLimitExceeded => "borrow_limit_exceeded",
+            AlertClass::SettingsMismatch => "settings_mismatch",
+            AlertClass::ExchangeMaintenance => "exchange_maintenance",
+            AlertClass::ExchangeOutage => "exchange_outage",
+            AlertClass::ExchangeRecovered => "exchange_recovered",
+            AlertClass::ReserveNotRestored => "reserve_not_restored",
+            AlertClass::PersistenceFailure => "persistence_failure",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Reaction to an alert, from least to most aggressive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertAction {
+    /// Plain log line only
+    Log,
+    /// Structured `RISK_ALERT` record for log monitoring
+    Notify,
+    /// Notify and execute the reduction the alert calls for
+    AutoReduce,
+    /// Notify, then close all positions and stop trading
+    Halt,
+}
+
+impl fmt::Display for AlertAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            AlertAction::Log => "log",
+            AlertAction::Notify => "notify",
+            AlertAction::AutoReduce => "auto_reduce",
+            AlertAction::Halt => "halt",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+/// Target hedge ratio and the band the rebalancer keeps it in.
+///
+/// The hedge ratio is spot quantity over futures quantity: 1.0 is fully neutral,
+/// 0.95 leaves 5% of the futures leg unhedged as a deliberate tilt in the funding
+/// direction. Drift is measured from the target, so a tilted book is not
+/// rebalanced back to neutral or reported as unintended exposure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeConfig {
+    /// Default hedge ratio (e.g., 0.95 = 95% hedged)
+    #[serde(default = "default_hedge_ratio")]
+    pub target_ratio: Decimal,
+    /// Drift from the target, as a fraction of position size, that triggers a rebalance
+    #[serde(default = "default_hedge_tolerance")]
+    pub tolerance: Decimal,
+    /// Per-symbol overrides
+    #[serde(default)]
+    pub symbols: Vec<SymbolHedgeTarget>,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            target_ratio: default_hedge_ratio(),
+            tolerance: default_hedge_tolerance(),
+            symbols: Vec::new(),
+        }
+    }
+}
+
+/// Hedge ratio override for a single symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolHedgeTarget {
+    /// Futures symbol (e.g., "ETHUSDT")
+    pub symbol: String,
+    pub target_ratio: Decimal,
+    /// Band around the target (defaults to `hedge.tolerance`)
+    #[serde(default)]
+    pub tolerance: Option<Decimal>,
+}
+
+/// Resolved hedge target of one symbol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgeTarget {
+    pub ratio: Decimal,
+    pub tolerance: Decimal,
+}
+
+impl HedgeTarget {
+    /// Intended net quantity for a futures leg of `futures_qty`.
+    ///
+    /// The unhedged part of the futures leg, so it carries the futures sign.
+    pub fn target_net_qty(&self, futures_qty: Decimal) -> Decimal {
+        futures_qty * (Decimal::ONE - self.ratio)
+    }
+}
+
+impl HedgeConfig {
+    /// Target for a symbol (explicit override first, then the defaults).
+    pub fn target(&self, symbol: &str) -> HedgeTarget {
+        match self
+            .symbols
+            .iter()
+            .find(|t| t.symbol.eq_ignore_ascii_case(symbol))
+        {
+            Some(t) => HedgeTarget {
+                ratio: t.target_ratio,
+                tolerance: t.tolerance.unwrap_or(self.tolerance),
+            },
+            None => HedgeTarget {
+                ratio: self.target_ratio,
+                tolerance: self.tolerance,
+            },
+        }
+    }
+}
+
+// Default value functions
+fn default_sub_account_asset() -> String {
+    "USDT".to_string()
+}
+
+fn default_sub_account_wallet() -> String {
+    "USDT_FUTURE".to_string()
+}
+
+fn default_sweep_on_shutdown() -> bool {
+    true
+}
+
+fn default_max_utilization() -> Decimal {
+    Decimal::new(85, 2) // 0.85
+}
+
+fn default_dynamic_utilization_enabled() -> bool {
+    true
+}
+
+fn default_min_utilization() -> Decimal {
+    Decimal::new(30, 2) // 0.30
+}
+
+fn default_utilization_step() -> Decimal {
+    Decimal::new(5, 2) // 0.05 per risk cycle
+}
+
+fn default_target_daily_volatility() -> Decimal {
+    Decimal::new(5, 2) // 5% daily moves
+}
+
+fn default_reserve_buffer() -> Decimal {
+    Decimal::new(10, 2) // 0.10
+}
+
+fn default_flash_min_funding_rate() -> Decimal {
+    Decimal::new(1, 2) // 1% per 8h
+}
+
+fn default_flash_release_fraction() -> Decimal {
+    Decimal::new(50, 2) // half the reserve
+}
+
+fn default_flash_duration_hours() -> u32 {
+    8
+}
+
+fn default_flash_cooldown_hours() -> u32 {
+    24
+}
+
+fn default_flash_restore_grace_hours() -> u32 {
+    2
+}
+
+fn default_portfolio_rebalance_interval_hours() -> u32 {
+    24
+}
+
+fn default_portfolio_rebalance_min_drift() -> Decimal {
+    Decimal::new(2, 1) // 20% of the current size
+}
+
+fn default_portfolio_rebalance_min_shift_usdt() -> Decimal {
+    Decimal::new(100, 0)
+}
+
+fn default_min_position_size() -> Decimal {
+    Decimal::new(1000, 0) // 1000 USDT
+}
+
+fn default_rebalance_threshold() -> Decimal {
+    Decimal::new(20, 2) // 0.20 = 20% drift triggers reduction
+}
+
+fn default_min_reduction_usdt() -> Decimal {
+    Decimal::new(100, 0) // 100 USDT
+}
+
+fn default_allocation_concentration() -> Decimal {
+    Decimal::new(15, 1) // 1.5 = moderate concentration (~35%, 25%, 20%, 12%, 8%)
+}
+
+fn default_max_drawdown() -> Decimal {
+    Decimal::new(5, 2) // 0.05
+}
+
+fn default_min_margin_ratio() -> Decimal {
+    Decimal::new(3, 0) // 3.0x
+}
+
+fn default_max_single_position() -> Decimal {
+    Decimal::new(35, 2) // 0.35 - allows concentrated allocation on top pair
+}
+
+fn default_min_volume() -> Decimal {
+    Decimal::new(50_000_000, 0) // $50M combined spot+futures volume
+}
+
+fn default_min_funding_rate() -> Decimal {
+    Decimal::new(1, 3) // 0.001 (0.1%) - higher threshold to ensure profitable entries
+}
+
+fn default_max_spread() -> Decimal {
+    Decimal::new(2, 4) // 0.0002 (0.02%)
+}
+
+fn default_min_open_interest() -> Decimal {
+    Decimal::new(50_000_000, 0) // $50M
+}
+
+fn default_max_positions() -> u8 {
+    5 // Concentrate capital into top pairs
+}
+
+fn default_borrow_rate() -> Decimal {
+    Decimal::new(1, 3) // 0.001 (0.1% daily) - conservative fallback for unknown assets
+}
+
+fn default_min_net_funding() -> Decimal {
+    // With ~0.04% taker fee per side, round-trip cost is ~0.08%
+    // Minimum 24h hold (3 funding cycles) to be profitable:
+    // Required: 0.08% fees / 3 cycles = 0.027% per cycle minimum
+    // Set to 0.03% to ensure profitability with some buffer
+    Decimal::new(3, 4) // 0.0003 (0.03%) minimum net funding per 8h after borrow costs
+}
+
+fn default_prefilter_top_k() -> usize {
+    20 // Spot ticker lookups for up to 20 symbols cost the minimum request weight
+}
+
+fn default_scan_cache_max_age_minutes() -> u32 {
+    10 // Funding rates rarely move much within a few scan intervals
+}
+
+fn default_basis_horizon_hours() -> u32 {
+    72 // Three days, a typical hold
+}
+
+fn default_hedge_switch_threshold() -> Decimal {
+    Decimal::new(5, 5) // 0.005% per 8h: a switch pays fees to close one hedge and open another
+}
+
+fn default_hedge_review_minutes() -> u32 {
+    60
+}
+
+fn default_symbol_quality_lookback_days() -> u32 {
+    30
+}
+
+fn default_max_symbol_failures() -> u32 {
+    3
+}
+
+fn default_capped_funding_persistence() -> Decimal {
+    Decimal::new(50, 2) // 0.50
+}
+
+fn default_max_open_interest_share() -> Decimal {
+    Decimal::new(2, 2) // 0.02
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_calendar_refresh_hours() -> u32 {
+    6
+}
+
+fn default_event_window_before_minutes() -> u32 {
+    60
+}
+
+fn default_event_window_after_minutes() -> u32 {
+    60
+}
+
+fn default_event_reduced_size_factor() -> Decimal {
+    Decimal::new(50, 2) // 0.50 (half-size entries)
+}
+
+fn default_maintenance_enabled() -> bool {
+    true
+}
+
+fn default_maintenance_entry_buffer_minutes() -> u32 {
+    60
+}
+
+fn default_maintenance_status_check_minutes() -> u32 {
+    5
+}
+
+fn default_futures_weight_per_minute() -> u32 {
+    2400
+}
+
+fn default_spot_weight_per_minute() -> u32 {
+    6000
 }
 
-fn default_max_drawdown() -> Decimal {
-    Decimal::new(5, 2) // 0.05
+fn default_futures_orders_per_minute() -> u32 {
+    1200
 }
 
-fn default_min_margin_ratio() -> Decimal {
-    Decimal::new(3, 0) // 3.0x
+fn default_spot_orders_per_day() -> u32 {
+    200_000
 }
 
-fn default_max_single_position() -> Decimal {
-    Decimal::new(35, 2) // 0.35 - allows concentrated allocation on top pair
+fn default_api_warn_fraction() -> Decimal {
+    Decimal::new(80, 2) // 0.80
 }
 
-fn default_min_volume() -> Decimal {
-    Decimal::new(50_000_000, 0) // $50M combined spot+futures volume
+fn default_api_throttle_fraction() -> Decimal {
+    Decimal::new(90, 2) // 0.90
 }
 
-fn default_min_funding_rate() -> Decimal {
-    Decimal::new(1, 3) // 0.001 (0.1%) - higher threshold to ensure profitable entries
+fn default_round_trips_per_day() -> u32 {
+    1
 }
 
-fn default_max_spread() -> Decimal {
-    Decimal::new(2, 4) // 0.0002 (0.02%)
+fn default_api_report_interval_minutes() -> u32 {
+    60
 }
 
-fn default_min_open_interest() -> Decimal {
-    Decimal::new(50_000_000, 0) // $50M
+fn default_risk_free_url() -> String {
+    "https://api.fiscaldata.treasury.gov/services/api/fiscal_service/v2/accounting/od/avg_interest_rates?filter=security_desc:eq:Treasury%20Bills&sort=-record_date&page[size]=1".to_string()
 }
 
-fn default_max_positions() -> u8 {
-    5 // Concentrate capital into top pairs
+fn default_risk_free_refresh_hours() -> u32 {
+    24
 }
 
-fn default_borrow_rate() -> Decimal {
-    Decimal::new(1, 3) // 0.001 (0.1% daily) - conservative fallback for unknown assets
+fn default_hyperliquid_url() -> String {
+    "https://api.hyperliquid.xyz".to_string()
 }
 
-fn default_min_net_funding() -> Decimal {
-    // With ~0.04% taker fee per side, round-trip cost is ~0.08%
-    // Minimum 24h hold (3 funding cycles) to be profitable:
-    // Required: 0.08% fees / 3 cycles = 0.027% per cycle minimum
-    // Set to 0.03% to ensure profitability with some buffer
-    Decimal::new(3, 4) // 0.0003 (0.03%) minimum net funding per 8h after borrow costs
+fn default_cross_venue_check_minutes() -> u32 {
+    15
+}
+
+fn default_min_funding_spread() -> Decimal {
+    Decimal::new(5, 4) // 0.0005 (0.05% per 8h)
+}
+
+fn default_cross_venue_max_notional_per_pair() -> Decimal {
+    Decimal::new(1000, 0) // $1,000
+}
+
+fn default_cross_venue_max_total_notional() -> Decimal {
+    Decimal::new(3000, 0) // $3,000
+}
+
+fn default_cross_venue_exit_spread() -> Decimal {
+    Decimal::new(1, 4) // 0.0001 (0.01% per 8h)
+}
+
+fn default_hedge_ratio() -> Decimal {
+    Decimal::ONE // Fully hedged
+}
+
+fn default_hedge_tolerance() -> Decimal {
+    Decimal::new(3, 2) // 3% drift triggers rebalance
+}
+
+fn default_take_profit_fee_multiple() -> Decimal {
+    Decimal::new(5, 0) // 5x entry fees
+}
+
+fn default_take_profit_notional_pct() -> Decimal {
+    Decimal::new(1, 2) // 1% of notional
+}
+
+fn default_take_profit_reduce_fraction() -> Decimal {
+    Decimal::new(25, 2) // Close 25% per milestone
+}
+
+fn default_take_profit_max_takes() -> u32 {
+    2
 }
 
 fn default_leverage() -> u8 {
@@ -248,115 +2103,1025 @@ fn default_order_timeout() -> u64 {
     30
 }
 
+fn default_client_order_tag() -> String {
+    "fff".to_string()
+}
+
+fn default_max_orders_per_minute() -> u32 {
+    30
+}
+
+fn default_max_notional_per_hour() -> Decimal {
+    Decimal::from(100_000)
+}
+
+fn default_maker_fee_rate() -> Decimal {
+    Decimal::new(2, 4) // 0.02%
+}
+
+fn default_taker_fee_rate() -> Decimal {
+    Decimal::new(4, 4) // 0.04%
+}
+
+fn default_settings_check_minutes() -> u32 {
+    60
+}
+
+fn default_entry_timing_wait_imbalance() -> Decimal {
+    Decimal::new(30, 2) // 0.30
+}
+
+fn default_entry_timing_abort_imbalance() -> Decimal {
+    Decimal::new(80, 2) // 0.80
+}
+
+fn default_entry_timing_momentum_bps() -> Decimal {
+    Decimal::from(2)
+}
+
+fn default_entry_timing_max_wait_minutes() -> u32 {
+    10
+}
+
+fn default_action_cost_enabled() -> bool {
+    true
+}
+
+fn default_action_cost_min_benefit_margin() -> Decimal {
+    Decimal::new(50, 2) // 0.50
+}
+
+fn default_action_cost_drift_move() -> Decimal {
+    Decimal::new(2, 2) // 2%
+}
+
+fn default_action_cost_benefit_horizon_hours() -> u32 {
+    72
+}
+
 // Position entry timing defaults
 fn default_entry_window_minutes() -> u32 {
     30 // Enter positions within 30 minutes of funding settlement (0 = anytime)
 }
 
-// Position holding rules defaults
-fn default_min_holding_period_hours() -> u32 {
-    16 // Minimum 16h hold (2 funding cycles) to ensure fees are covered
-}
+// Position holding rules defaults
+fn default_min_holding_period_hours() -> u32 {
+    16 // Minimum 16h hold (2 funding cycles) to ensure fees are covered
+}
+
+fn default_min_yield_advantage() -> Decimal {
+    Decimal::new(2, 2) // 0.02 (2%) - new position must yield 2%+ more to justify switch
+}
+
+// Position loss detection defaults
+fn default_max_unprofitable_hours() -> u32 {
+    12 // Close positions unprofitable for 12+ hours
+}
+
+fn default_min_expected_yield() -> Decimal {
+    Decimal::new(10, 2) // 0.10 (10% APY)
+}
+
+fn default_grace_period_hours() -> u32 {
+    4 // 4 hour grace period (half a funding cycle)
+}
+
+fn default_max_funding_deviation() -> Decimal {
+    Decimal::new(20, 2) // 0.20 (20%)
+}
+
+fn default_funding_expectation_window_hours() -> u32 {
+    24 // Time-weighted average over the last 3 funding periods
+}
+
+fn default_funding_collapse_ratio() -> Decimal {
+    Decimal::new(50, 2) // 0.50 (predicted rate halved)
+}
+
+fn default_exit_liquidity_check_minutes() -> u32 {
+    15
+}
+
+fn default_max_exit_cost_ratio() -> Decimal {
+    Decimal::new(50, 2) // 0.50 (exit may cost half the funding earned)
+}
+
+fn default_max_loss_usd() -> Decimal {
+    Decimal::new(10, 0) // $10 absolute loss triggers force exit
+}
+
+fn default_max_negative_apy() -> Decimal {
+    Decimal::new(50, 2) // 0.50 (-50% APY triggers force exit)
+}
+
+fn default_borrow_spike_ratio() -> Decimal {
+    Decimal::new(3, 0) // 3x the weekly median
+}
+
+fn default_low_supply_spike_ratio() -> Decimal {
+    Decimal::new(2, 0) // 2x over weekends and holidays
+}
+
+fn default_borrow_limits_enabled() -> bool {
+    true
+}
+
+fn default_borrow_limits_refresh_minutes() -> u32 {
+    30
+}
+
+fn default_borrow_limits_alert_utilization() -> Decimal {
+    Decimal::new(80, 2) // 0.80 = 80% of the borrow limit in use
+}
+
+fn default_borrow_limits_min_headroom_usdt() -> Decimal {
+    Decimal::new(1000, 0) // One minimum-size position
+}
+
+fn default_borrow_limits_max_candidates() -> usize {
+    10
+}
+
+fn default_reentry_cooldown_minutes() -> u32 {
+    60
+}
+
+fn default_forced_exit_cooldown_minutes() -> u32 {
+    480 // One 8h funding interval
+}
+
+// Malfunction detection defaults
+fn default_max_errors_per_minute() -> u32 {
+    10
+}
+
+fn default_max_consecutive_failures() -> u32 {
+    3
+}
+
+fn default_emergency_delta_drift() -> Decimal {
+    Decimal::new(10, 2) // 0.10 (10%)
+}
+
+fn default_max_residual_delta_usd() -> Decimal {
+    Decimal::new(500, 0) // $500 net unhedged exposure
+}
+
+fn default_malfunction_state_max_age_minutes() -> u32 {
+    60
+}
+
+fn default_max_consecutive_risk_cycles() -> u32 {
+    3
+}
+
+fn default_safe_mode_after_minutes() -> u32 {
+    5
+}
+
+fn default_resumption_enabled() -> bool {
+    true
+}
+
+fn default_resumption_ramp_cycles() -> u32 {
+    5
+}
+
+fn default_resumption_max_entries_per_cycle() -> u32 {
+    1
+}
+
+fn default_resumption_initial_size_fraction() -> Decimal {
+    Decimal::new(25, 2) // 0.25
+}
+
+fn default_funding_quarantine_enabled() -> bool {
+    true
+}
+
+fn default_funding_quarantine_anomalies() -> u32 {
+    3 // A full day of 8h settlements
+}
+
+fn default_persistence_error_after_failures() -> u32 {
+    3
+}
+
+fn default_persistence_critical_after_failures() -> u32 {
+    10
+}
+
+fn default_persistence_halt_entries_after_minutes() -> u32 {
+    15
+}
+
+impl Config {
+    /// Load configuration from the working directory using the profile from
+    /// `FFF_PROFILE` (default: dev).
+    pub fn load() -> Result<Self> {
+        Self::load_with_profile(Path::new("."), None, Profile::default())
+    }
+
+    /// Load configuration for a profile, layering the profile overlay on the base file.
+    ///
+    /// Files are read from `dir`. `explicit` takes precedence over `FFF_PROFILE`;
+    /// `fallback` is used when neither is set. The result is validated against the
+    /// profile's rules.
+    pub fn load_with_profile(
+        dir: &Path,
+        explicit: Option<Profile>,
+        fallback: Profile,
+    ) -> Result<Self> {
+        let config = Self::load_unvalidated(dir, explicit, fallback)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load and merge configuration sources without validating the values.
+    ///
+    /// Used by `validate-config` to report every violation instead of failing on load.
+    pub fn load_unvalidated(
+        dir: &Path,
+        explicit: Option<Profile>,
+        fallback: Profile,
+    ) -> Result<Self> {
+        // A `.env` next to the config files wins over one in the working directory
+        dotenvy::from_path(dir.join(".env")).ok();
+        dotenvy::dotenv().ok();
+
+        let profile = Profile::resolve(explicit, fallback)?;
+        let file =
+            |name: &str| config::File::with_name(&dir.join(name).to_string_lossy()).required(false);
+
+        let config = config::Config::builder()
+            .add_source(file("config"))
+            .add_source(file(&profile.config_file()))
+            .add_source(config::Environment::default().separator("__").prefix("FFF"))
+            .build()
+            .context("Failed to build configuration")?;
+
+        let mut config: Config = config
+            .try_deserialize()
+            .context("Failed to deserialize configuration")?;
+        config.profile = profile;
+
+        Ok(config)
+    }
+
+    /// Render the effective configuration (defaults, files and env merged).
+    ///
+    /// API credentials are redacted so the output can be shared and diffed safely.
+    pub fn to_effective_string(&self, format: ConfigFormat) -> Result<String> {
+        let mut redacted = self.clone();
+        for secret in [
+            &mut redacted.binance.api_key,
+            &mut redacted.binance.secret_key,
+            &mut redacted.binance.read_api_key,
+            &mut redacted.binance.read_secret_key,
+            &mut redacted.binance.sub_account.api_key,
+            &mut redacted.binance.sub_account.secret_key,
+            &mut redacted.binance.sub_account.read_api_key,
+            &mut redacted.binance.sub_account.read_secret_key,
+            &mut redacted.bybit.api_key,
+            &mut redacted.bybit.secret_key,
+            &mut redacted.okx.api_key,
+            &mut redacted.okx.secret_key,
+            &mut redacted.okx.passphrase,
+        ] {
+            if !secret.is_empty() {
+                *secret = REDACTED.to_string();
+            }
+        }
+
+        match format {
+            ConfigFormat::Toml => {
+                toml::to_string_pretty(&redacted).context("Failed to serialize config as TOML")
+            }
+            ConfigFormat::Json => serde_json::to_string_pretty(&redacted)
+                .context("Failed to serialize config as JSON"),
+        }
+    }
+
+    /// Short hash of the effective configuration, to tell runs with different settings apart.
+    ///
+    /// Computed on the redacted TOML export, so rotating credentials keeps the hash.
+    pub fn config_hash(&self) -> Result<String> {
+        let effective = self.to_effective_string(ConfigFormat::Toml)?;
+        let mut hash = hex::encode(Sha256::digest(effective.as_bytes()));
+        hash.truncate(CONFIG_HASH_LEN);
+        Ok(hash)
+    }
+
+    /// Point every venue at its test environment: the Binance and Bybit testnets
+    /// and OKX demo trading.
+    pub fn use_testnets(&mut self) {
+        self.binance.testnet = true;
+        self.bybit.testnet = true;
+        self.okx.demo = true;
+    }
+
+    /// Validate configuration values against the rules of the loaded profile.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_for_profile(self.profile)
+    }
+
+    /// Validate configuration values, including profile-specific limits.
+    ///
+    /// All violations are reported together, one per line.
+    pub fn validate_for_profile(&self, profile: Profile) -> Result<()> {
+        let violations = self.violations(profile);
+        anyhow::ensure!(
+            violations.is_empty(),
+            "Invalid configuration for '{}' profile ({} violation(s)):\n  - {}",
+            profile,
+            violations.len(),
+            violations.join("\n  - ")
+        );
+        Ok(())
+    }
+
+    /// Collect every bound and cross-field consistency violation.
+    ///
+    /// The live profile additionally refuses parameters that are only acceptable
+    /// for paper trading.
+    pub fn violations(&self, profile: Profile) -> Vec<String> {
+        let mut v = Violations::default();
+        let capital = &self.capital;
+        let risk = &self.risk;
+        let pairs = &self.pair_selection;
+        let exec = &self.execution;
+
+        // Read-only keys come with their secret
+        let binance = &self.binance;
+        for (field, key, secret) in [
+            ("binance", &binance.read_api_key, &binance.read_secret_key),
+            (
+                "binance.sub_account",
+                &binance.sub_account.read_api_key,
+                &binance.sub_account.read_secret_key,
+            ),
+        ] {
+            if key.is_empty() != secret.is_empty() {
+                v.push(format!(
+                    "{0}.read_api_key and {0}.read_secret_key must be set together",
+                    field
+                ));
+            }
+        }
+        if binance.recv_window_ms == 0 || binance.recv_window_ms > 60_000 {
+            v.push(format!(
+                "binance.recv_window_ms must be between 1 and 60000 (got {})",
+                binance.recv_window_ms
+            ));
+        }
+        v.at_least_one("binance.time_sync_minutes", binance.time_sync_minutes);
+
+        // Venue
+        if self.venue == Venue::Hyperliquid {
+            v.push(
+                "venue must be 'binance', 'bybit' or 'okx'; Hyperliquid only holds cross-venue legs"
+                    .to_string(),
+            );
+        }
+        if self.venue != Venue::Binance && self.binance.sub_account.enabled {
+            v.push(format!(
+                "binance.sub_account isolates Binance capital only; disable it for venue '{}'",
+                self.venue.to_string().to_lowercase()
+            ));
+        }
+        if self.okx.route_entries && self.venue != Venue::Binance {
+            v.push(
+                "okx.route_entries routes between Binance and OKX; it needs venue 'binance'"
+                    .to_string(),
+            );
+        }
+
+        // Sub-account isolation
+        let sub = &self.binance.sub_account;
+        if sub.enabled {
+            if sub.email.is_empty() {
+                v.push("binance.sub_account.email is required when enabled".to_string());
+            }
+            v.positive("binance.sub_account.capital", sub.capital);
+            if !SUB_ACCOUNT_WALLETS.contains(&sub.wallet.as_str()) {
+                v.push(format!(
+                    "binance.sub_account.wallet must be one of {:?} (got '{}')",
+                    SUB_ACCOUNT_WALLETS, sub.wallet
+                ));
+            }
+        }
+
+        // Capital
+        v.fraction("capital.max_utilization", capital.max_utilization);
+        v.buffer("capital.reserve_buffer", capital.reserve_buffer);
+        if capital.reserve_buffer + capital.max_utilization > Decimal::ONE {
+            v.push(format!(
+                "capital.reserve_buffer + capital.max_utilization must be <= 1 (got {} + {}); \
+                 lower one of them so the reserve is not deployed",
+                capital.reserve_buffer, capital.max_utilization
+            ));
+        }
+        v.positive("capital.min_position_size", capital.min_position_size);
+        v.positive("capital.rebalance_threshold", capital.rebalance_threshold);
+        v.non_negative("capital.min_reduction_usdt", capital.min_reduction_usdt);
+        let dynamic = &capital.dynamic_utilization;
+        if dynamic.enabled {
+            v.fraction(
+                "capital.dynamic_utilization.min_utilization",
+                dynamic.min_utilization,
+            );
+            if dynamic.min_utilization > capital.max_utilization {
+                v.push(format!(
+                    "capital.dynamic_utilization.min_utilization ({}) must not exceed capital.max_utilization ({})",
+                    dynamic.min_utilization, capital.max_utilization
+                ));
+            }
+            v.positive("capital.dynamic_utilization.step", dynamic.step);
+            v.positive(
+                "capital.dynamic_utilization.target_daily_volatility",
+                dynamic.target_daily_volatility,
+            );
+        }
+        let flash = &capital.flash_reserve;
+        if flash.enabled {
+            v.positive(
+                "capital.flash_reserve.min_funding_rate",
+                flash.min_funding_rate,
+            );
+            v.fraction(
+                "capital.flash_reserve.release_fraction",
+                flash.release_fraction,
+            );
+            v.at_least_one("capital.flash_reserve.duration_hours", flash.duration_hours);
+        }
+        let portfolio = &capital.portfolio_rebalance;
+        if portfolio.enabled {
+            v.at_least_one(
+                "capital.portfolio_rebalance.interval_hours",
+                portfolio.interval_hours,
+            );
+            v.fraction("capital.portfolio_rebalance.min_drift", portfolio.min_drift);
+            v.non_negative(
+                "capital.portfolio_rebalance.min_shift_usdt",
+                portfolio.min_shift_usdt,
+            );
+        }
+        if capital.allocation_concentration < Decimal::ONE
+            || capital.allocation_concentration > Decimal::new(3, 0)
+        {
+            v.push(format!(
+                "capital.allocation_concentration must be between 1.0 and 3.0 (got {})",
+                capital.allocation_concentration
+            ));
+        }
+        let caps = &capital.notional_caps;
+        v.non_negative("capital.notional_caps.max_per_symbol", caps.max_per_symbol);
+        for cap in &caps.symbols {
+            if cap.max_notional <= Decimal::ZERO {
+                v.push(format!(
+                    "capital.notional_caps.symbols: cap for {} must be > 0 (got {}); \
+                     remove the entry to fall back to max_per_symbol",
+                    cap.symbol, cap.max_notional
+                ));
+            }
+        }
+        for group in &caps.groups {
+            if group.max_notional <= Decimal::ZERO || group.symbols.is_empty() {
+                v.push(format!(
+                    "capital.notional_caps.groups: group '{}' needs symbols and max_notional > 0",
+                    group.name
+                ));
+            }
+        }
+
+        for held in &capital.existing_holdings.assets {
+            if held.asset.is_empty() || held.quantity <= Decimal::ZERO {
+                v.push(format!(
+                    "capital.existing_holdings.assets: '{}' needs an asset name and quantity > 0 (got {})",
+                    held.asset, held.quantity
+                ));
+            }
+        }
+        if capital.existing_holdings.coin_margined {
+            if !capital.existing_holdings.enabled {
+                v.push(
+                    "capital.existing_holdings.coin_margined requires capital.existing_holdings.enabled"
+                        .to_string(),
+                );
+            }
+            if self.venue != Venue::Binance {
+                v.push(
+                    "capital.existing_holdings.coin_margined requires venue 'binance'".to_string(),
+                );
+            }
+        }
+
+        // Risk
+        v.fraction("risk.max_drawdown", risk.max_drawdown);
+        v.positive("risk.min_margin_ratio", risk.min_margin_ratio);
+        v.fraction("risk.max_single_position", risk.max_single_position);
+        if risk.max_single_position > capital.max_utilization {
+            v.push(format!(
+                "risk.max_single_position ({}) must not exceed capital.max_utilization ({})",
+                risk.max_single_position, capital.max_utilization
+            ));
+        }
+        if risk.entry_window_minutes > 480 {
+            v.push(format!(
+                "risk.entry_window_minutes must be <= 480 (one funding period), got {}; \
+                 use 0 to allow entry anytime",
+                risk.entry_window_minutes
+            ));
+        }
+        v.non_negative("risk.min_yield_advantage", risk.min_yield_advantage);
+        v.at_least_one("risk.max_unprofitable_hours", risk.max_unprofitable_hours);
+        if risk.grace_period_hours >= risk.max_unprofitable_hours {
+            v.push(format!(
+                "risk.grace_period_hours ({}) must be < risk.max_unprofitable_hours ({}), \
+                 otherwise unprofitable positions are never checked",
+                risk.grace_period_hours, risk.max_unprofitable_hours
+            ));
+        }
+        v.non_negative("risk.min_expected_yield", risk.min_expected_yield);
+        v.fraction("risk.max_funding_deviation", risk.max_funding_deviation);
+        v.at_least_one(
+            "risk.funding_expectation_window_hours",
+            risk.funding_expectation_window_hours,
+        );
+        v.fraction("risk.funding_collapse_ratio", risk.funding_collapse_ratio);
+        v.positive("risk.max_exit_cost_ratio", risk.max_exit_cost_ratio);
+        v.positive("risk.max_loss_usd", risk.max_loss_usd);
+        v.fraction("risk.max_negative_apy", risk.max_negative_apy);
+        if risk.borrow_spike_ratio <= Decimal::ONE {
+            v.push(format!(
+                "risk.borrow_spike_ratio must be > 1 (got {})",
+                risk.borrow_spike_ratio
+            ));
+        }
+        if risk.low_supply_spike_ratio <= Decimal::ONE
+            || risk.low_supply_spike_ratio > risk.borrow_spike_ratio
+        {
+            v.push(format!(
+                "risk.low_supply_spike_ratio must be > 1 and <= risk.borrow_spike_ratio (got {})",
+                risk.low_supply_spike_ratio
+            ));
+        }
+        if risk.forced_exit_cooldown_minutes < risk.reentry_cooldown_minutes {
+            v.push(format!(
+                "risk.forced_exit_cooldown_minutes ({}) must be >= risk.reentry_cooldown_minutes ({})",
+                risk.forced_exit_cooldown_minutes, risk.reentry_cooldown_minutes
+            ));
+        }
+        v.at_least_one("risk.max_errors_per_minute", risk.max_errors_per_minute);
+        v.at_least_one(
+            "risk.max_consecutive_failures",
+            risk.max_consecutive_failures,
+        );
+        v.fraction("risk.emergency_delta_drift", risk.emergency_delta_drift);
+        v.positive("risk.max_residual_delta_usd", risk.max_residual_delta_usd);
+        v.at_least_one(
+            "risk.max_consecutive_risk_cycles",
+            risk.max_consecutive_risk_cycles,
+        );
+        if risk.resumption.enabled {
+            v.at_least_one("risk.resumption.ramp_cycles", risk.resumption.ramp_cycles);
+            v.at_least_one(
+                "risk.resumption.max_entries_per_cycle",
+                risk.resumption.max_entries_per_cycle,
+            );
+            v.fraction(
+                "risk.resumption.initial_size_fraction",
+                risk.resumption.initial_size_fraction,
+            );
+        }
+        if risk.borrow_limits.enabled {
+            v.at_least_one(
+                "risk.borrow_limits.refresh_minutes",
+                risk.borrow_limits.refresh_minutes,
+            );
+            v.fraction(
+                "risk.borrow_limits.alert_utilization",
+                risk.borrow_limits.alert_utilization,
+            );
+            v.non_negative(
+                "risk.borrow_limits.min_headroom_usdt",
+                risk.borrow_limits.min_headroom_usdt,
+            );
+        }
+        if risk.funding_quarantine.enabled {
+            v.at_least_one(
+                "risk.funding_quarantine.anomalies",
+                risk.funding_quarantine.anomalies,
+            );
+        }
+        let watchdog = &risk.persistence_watchdog;
+        v.at_least_one(
+            "risk.persistence_watchdog.error_after_failures",
+            watchdog.error_after_failures,
+        );
+        if watchdog.critical_after_failures < watchdog.error_after_failures {
+            v.push(format!(
+                "risk.persistence_watchdog.critical_after_failures must be >= error_after_failures (got {} < {})",
+                watchdog.critical_after_failures, watchdog.error_after_failures
+            ));
+        }
+        for (i, rule) in risk.rules.iter().enumerate() {
+            if rule.name.is_empty() || risk.rules[..i].iter().any(|r| r.name == rule.name) {
+                v.push(format!(
+                    "risk.rules: rule #{} needs a unique name (got '{}')",
+                    i + 1,
+                    rule.name
+                ));
+            }
+            if rule.when.is_empty() {
+                v.push(format!(
+                    "risk.rules: rule '{}' needs at least one condition",
+                    rule.name
+                ));
+            }
+            if !rule.is_position_rule() {
+                for condition in rule.when.iter().filter(|c| c.metric.is_position_metric()) {
+                    v.push(format!(
+                        "risk.rules: rule '{}' halts entries, so it can't use the position \
+                         metric {}",
+                        rule.name, condition.metric
+                    ));
+                }
+            }
+        }
+
+        // Pair selection
+        v.non_negative("pair_selection.min_volume_24h", pairs.min_volume_24h);
+        if pairs.min_volume_percentile < Decimal::ZERO
+            || pairs.min_volume_percentile >= Decimal::ONE
+        {
+            v.push(format!(
+                "pair_selection.min_volume_percentile must be in [0, 1) (got {}); 0.7 keeps \
+                 the top 30% by volume",
+                pairs.min_volume_percentile
+            ));
+        }
+        v.non_negative("pair_selection.min_funding_rate", pairs.min_funding_rate);
+        v.fraction("pair_selection.max_spread", pairs.max_spread);
+        v.non_negative("pair_selection.min_open_interest", pairs.min_open_interest);
+        v.at_least_one("pair_selection.max_positions", u32::from(pairs.max_positions));
+        v.non_negative(
+            "pair_selection.default_borrow_rate",
+            pairs.default_borrow_rate,
+        );
+        v.non_negative("pair_selection.min_net_funding", pairs.min_net_funding);
+        v.fraction(
+            "pair_selection.capped_funding_persistence",
+            pairs.capped_funding_persistence,
+        );
+        v.fraction(
+            "pair_selection.max_open_interest_share",
+            pairs.max_open_interest_share,
+        );
+        if pairs.futures_hedges {
+            v.at_least_one(
+                "pair_selection.basis_horizon_hours",
+                pairs.basis_horizon_hours,
+            );
+            v.non_negative(
+                "pair_selection.hedge_switch_threshold",
+                pairs.hedge_switch_threshold,
+            );
+        }
+
+        // Execution
+        if exec.default_leverage < 1 || exec.default_leverage > exec.max_leverage {
+            v.push(format!(
+                "execution.default_leverage must be >= 1 and <= execution.max_leverage ({}), got {}",
+                exec.max_leverage, exec.default_leverage
+            ));
+        }
+        if exec.max_leverage > 125 {
+            v.push(format!(
+                "execution.max_leverage must be <= 125 (Binance maximum), got {}",
+                exec.max_leverage
+            ));
+        }
+        v.fraction("execution.slippage_tolerance", exec.slippage_tolerance);
+        if exec.order_timeout_secs == 0 {
+            v.push("execution.order_timeout_secs must be >= 1".to_string());
+        }
+        let tag = &exec.client_order_tag;
+        if tag.is_empty()
+            || tag.len() > MAX_CLIENT_ORDER_TAG_LEN
+            || !tag.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            v.push(format!(
+                "execution.client_order_tag must be 1-{} ASCII letters or digits, got '{}'",
+                MAX_CLIENT_ORDER_TAG_LEN, tag
+            ));
+        }
+        v.non_negative(
+            "execution.max_notional_per_hour",
+            exec.max_notional_per_hour,
+        );
+        v.non_negative("execution.taker_fee_rate", exec.taker_fee_rate);
+        if exec.maker_fee_rate > exec.taker_fee_rate || exec.maker_fee_rate < -exec.taker_fee_rate {
+            v.push(format!(
+                "execution.maker_fee_rate must be within +/- execution.taker_fee_rate ({}), got {}",
+                exec.taker_fee_rate, exec.maker_fee_rate
+            ));
+        }
+        let timing = &exec.entry_timing;
+        if timing.enabled {
+            v.fraction(
+                "execution.entry_timing.wait_imbalance",
+                timing.wait_imbalance,
+            );
+            v.fraction(
+                "execution.entry_timing.abort_imbalance",
+                timing.abort_imbalance,
+            );
+            if timing.abort_imbalance < timing.wait_imbalance {
+                v.push(format!(
+                    "execution.entry_timing.abort_imbalance must be >= wait_imbalance ({}), got {}",
+                    timing.wait_imbalance, timing.abort_imbalance
+                ));
+            }
+            v.non_negative("execution.entry_timing.momentum_bps", timing.momentum_bps);
+            v.at_least_one(
+                "execution.entry_timing.max_wait_minutes",
+                timing.max_wait_minutes,
+            );
+        }
+        let action_cost = &exec.action_cost;
+        if action_cost.enabled {
+            v.non_negative(
+                "execution.action_cost.min_benefit_margin",
+                action_cost.min_benefit_margin,
+            );
+            v.fraction("execution.action_cost.drift_move", action_cost.drift_move);
+            v.at_least_one(
+                "execution.action_cost.benefit_horizon_hours",
+                action_cost.benefit_horizon_hours,
+            );
+        }
 
-fn default_min_yield_advantage() -> Decimal {
-    Decimal::new(2, 2) // 0.02 (2%) - new position must yield 2%+ more to justify switch
-}
+        // Display
+        if let Err(e) = DisplayTimezone::parse(&self.display.timezone) {
+            v.push(format!("display.timezone: {}", e));
+        }
 
-// Position loss detection defaults
-fn default_max_unprofitable_hours() -> u32 {
-    12 // Close positions unprofitable for 12+ hours
-}
+        // Event calendar
+        let events = &self.events;
+        if events.enabled {
+            if events.file.is_empty() && events.calendar_url.is_empty() {
+                v.push("events.file or events.calendar_url is required when enabled".to_string());
+            }
+            v.at_least_one("events.refresh_hours", events.refresh_hours);
+            v.fraction("events.reduced_size_factor", events.reduced_size_factor);
+        }
 
-fn default_min_expected_yield() -> Decimal {
-    Decimal::new(10, 2) // 0.10 (10% APY)
-}
+        // Maintenance windows
+        for (i, window) in self.maintenance.windows.iter().enumerate() {
+            if window.end <= window.start {
+                v.push(format!(
+                    "maintenance.windows[{}] must end after it starts ({} .. {})",
+                    i, window.start, window.end
+                ));
+            }
+        }
 
-fn default_grace_period_hours() -> u32 {
-    4 // 4 hour grace period (half a funding cycle)
-}
+        // API budget
+        let budget = &self.api_budget;
+        v.at_least_one(
+            "api_budget.futures_weight_per_minute",
+            budget.futures_weight_per_minute,
+        );
+        v.at_least_one(
+            "api_budget.spot_weight_per_minute",
+            budget.spot_weight_per_minute,
+        );
+        v.at_least_one(
+            "api_budget.futures_orders_per_minute",
+            budget.futures_orders_per_minute,
+        );
+        v.at_least_one("api_budget.spot_orders_per_day", budget.spot_orders_per_day);
+        v.fraction("api_budget.warn_fraction", budget.warn_fraction);
+        v.fraction("api_budget.throttle_fraction", budget.throttle_fraction);
 
-fn default_max_funding_deviation() -> Decimal {
-    Decimal::new(20, 2) // 0.20 (20%)
-}
+        // Risk-free rate
+        let risk_free = &self.risk_free;
+        v.buffer("risk_free.annual_rate", risk_free.annual_rate);
+        if risk_free.fetch {
+            if risk_free.url.is_empty() {
+                v.push("risk_free.url is required when fetch is set".to_string());
+            }
+            v.at_least_one("risk_free.refresh_hours", risk_free.refresh_hours);
+        }
 
-fn default_max_loss_usd() -> Decimal {
-    Decimal::new(10, 0) // $10 absolute loss triggers force exit
-}
+        // Cross-venue spreads
+        let venue = &self.cross_venue;
+        if venue.enabled {
+            if venue.hyperliquid_url.is_empty() {
+                v.push("cross_venue.hyperliquid_url is required when enabled".to_string());
+            }
+            v.at_least_one(
+                "cross_venue.check_interval_minutes",
+                venue.check_interval_minutes,
+            );
+            v.positive("cross_venue.min_spread", venue.min_spread);
+            v.positive(
+                "cross_venue.max_notional_per_pair",
+                venue.max_notional_per_pair,
+            );
+            if venue.max_notional_per_pair > venue.max_total_notional {
+                v.push(format!(
+                    "cross_venue.max_notional_per_pair ({}) must not exceed cross_venue.max_total_notional ({})",
+                    venue.max_notional_per_pair, venue.max_total_notional
+                ));
+            }
+            if venue.exit_spread >= venue.min_spread {
+                v.push(format!(
+                    "cross_venue.exit_spread ({}) must be below cross_venue.min_spread ({})",
+                    venue.exit_spread, venue.min_spread
+                ));
+            }
+        }
+        if venue.execute {
+            if !venue.enabled {
+                v.push("cross_venue.execute requires cross_venue.enabled".to_string());
+            }
+            if self.venue != Venue::Binance {
+                v.push("cross_venue.execute requires venue 'binance'".to_string());
+            }
+        }
 
-fn default_max_negative_apy() -> Decimal {
-    Decimal::new(50, 2) // 0.50 (-50% APY triggers force exit)
-}
+        // Hedge targets
+        let hedge = &self.hedge;
+        v.fraction("hedge.target_ratio", hedge.target_ratio);
+        v.fraction("hedge.tolerance", hedge.tolerance);
+        for target in &hedge.symbols {
+            v.fraction(
+                &format!("hedge.symbols.{}.target_ratio", target.symbol),
+                target.target_ratio,
+            );
+            if let Some(tolerance) = target.tolerance {
+                v.fraction(
+                    &format!("hedge.symbols.{}.tolerance", target.symbol),
+                    tolerance,
+                );
+            }
+        }
 
-// Malfunction detection defaults
-fn default_max_errors_per_minute() -> u32 {
-    10
-}
+        // Take-profit
+        let take_profit = &self.take_profit;
+        if take_profit.enabled {
+            v.non_negative("take_profit.fee_multiple", take_profit.fee_multiple);
+            v.non_negative("take_profit.notional_pct", take_profit.notional_pct);
+            if take_profit.fee_multiple.is_zero() && take_profit.notional_pct.is_zero() {
+                v.push(
+                    "take_profit requires fee_multiple or notional_pct when enabled".to_string(),
+                );
+            }
+            v.fraction("take_profit.reduce_fraction", take_profit.reduce_fraction);
+            if take_profit.reduce_fraction == Decimal::ONE {
+                v.push(
+                    "take_profit.reduce_fraction must be below 1 to keep a core position"
+                        .to_string(),
+                );
+            }
+            v.at_least_one("take_profit.max_takes", take_profit.max_takes);
+        }
 
-fn default_max_consecutive_failures() -> u32 {
-    3
-}
+        // Alert routing
+        for (class, routes) in &self.alerts.routes {
+            for (severity, action) in routes.iter() {
+                if action == AlertAction::AutoReduce && !class.can_reduce() {
+                    v.push(format!(
+                        "alerts.routes.{}.{}: {} alerts have nothing to auto_reduce",
+                        class, severity, class
+                    ));
+                }
+            }
+        }
 
-fn default_emergency_delta_drift() -> Decimal {
-    Decimal::new(10, 2) // 0.10 (10%)
-}
+        if profile == Profile::Live {
+            if risk.max_drawdown > LIVE_MAX_DRAWDOWN {
+                v.push(format!(
+                    "live profile requires risk.max_drawdown <= {} (got {})",
+                    LIVE_MAX_DRAWDOWN, risk.max_drawdown
+                ));
+            }
+            if exec.default_leverage > LIVE_MAX_LEVERAGE {
+                v.push(format!(
+                    "live profile requires execution.default_leverage <= {} (got {})",
+                    LIVE_MAX_LEVERAGE, exec.default_leverage
+                ));
+            }
+            if exec.max_orders_per_minute == 0 || exec.max_notional_per_hour.is_zero() {
+                v.push(
+                    "live profile requires execution.max_orders_per_minute and \
+                     execution.max_notional_per_hour to be set"
+                        .to_string(),
+                );
+            }
+            if risk.safe_mode_after_minutes == 0 {
+                v.push("live profile requires risk.safe_mode_after_minutes to be set".to_string());
+            }
+            let drawdown_route = self
+                .alerts
+                .routes
+                .get(&AlertClass::DrawdownExceeded)
+                .and_then(|routes| routes.critical);
+            if drawdown_route.is_some_and(|action| action != AlertAction::Halt) {
+                v.push(
+                    "live profile requires alerts.routes.drawdown_exceeded.critical to halt"
+                        .to_string(),
+                );
+            }
+        }
 
-fn default_max_consecutive_risk_cycles() -> u32 {
-    3
+        v.0
+    }
 }
 
-impl Config {
-    /// Load configuration from environment variables and config files.
-    pub fn load() -> Result<Self> {
-        dotenvy::dotenv().ok();
+/// Accumulates configuration violations so they can be reported together.
+#[derive(Default)]
+struct Violations(Vec<String>);
 
-        let config = config::Config::builder()
-            .add_source(config::File::with_name("config").required(false))
-            .add_source(config::Environment::default().separator("__").prefix("FFF"))
-            .build()
-            .context("Failed to build configuration")?;
+impl Violations {
+    fn push(&mut self, message: String) {
+        self.0.push(message);
+    }
 
-        config
-            .try_deserialize()
-            .context("Failed to deserialize configuration")
+    /// Require `0 < value <= 1`.
+    fn fraction(&mut self, field: &str, value: Decimal) {
+        if value <= Decimal::ZERO || value > Decimal::ONE {
+            self.push(format!(
+                "{} must be in (0, 1] (got {}); fractions are expressed as 0.05 = 5%",
+                field, value
+            ));
+        }
     }
 
-    /// Validate configuration values.
-    pub fn validate(&self) -> Result<()> {
-        anyhow::ensure!(
-            self.capital.max_utilization > Decimal::ZERO
-                && self.capital.max_utilization <= Decimal::ONE,
-            "max_utilization must be between 0 and 1"
-        );
+    /// Require `0 <= value < 1` (reserve buffers may be zero).
+    fn buffer(&mut self, field: &str, value: Decimal) {
+        if value < Decimal::ZERO || value >= Decimal::ONE {
+            self.push(format!(
+                "{} must be in [0, 1) (got {}); fractions are expressed as 0.05 = 5%",
+                field, value
+            ));
+        }
+    }
 
-        anyhow::ensure!(
-            self.risk.max_drawdown > Decimal::ZERO && self.risk.max_drawdown <= Decimal::ONE,
-            "max_drawdown must be between 0 and 1"
-        );
+    fn positive(&mut self, field: &str, value: Decimal) {
+        if value <= Decimal::ZERO {
+            self.push(format!("{} must be > 0 (got {})", field, value));
+        }
+    }
 
-        anyhow::ensure!(
-            self.execution.default_leverage >= 1
-                && self.execution.default_leverage <= self.execution.max_leverage,
-            "default_leverage must be >= 1 and <= max_leverage"
-        );
+    fn non_negative(&mut self, field: &str, value: Decimal) {
+        if value < Decimal::ZERO {
+            self.push(format!("{} must be >= 0 (got {})", field, value));
+        }
+    }
 
-        Ok(())
+    fn at_least_one(&mut self, field: &str, value: u32) {
+        if value == 0 {
+            self.push(format!("{} must be >= 1", field));
+        }
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            profile: Profile::default(),
+            venue: Venue::default(),
             binance: BinanceConfig {
                 api_key: String::new(),
                 secret_key: String::new(),
+                read_api_key: String::new(),
+                read_secret_key: String::new(),
+                testnet: true,
+                ..Default::default()
+            },
+            bybit: BybitConfig {
                 testnet: true,
+                ..Default::default()
+            },
+            okx: OkxConfig {
+                demo: true,
+                ..Default::default()
             },
             capital: CapitalConfig {
                 max_utilization: default_max_utilization(),
                 reserve_buffer: default_reserve_buffer(),
                 min_position_size: default_min_position_size(),
                 rebalance_threshold: default_rebalance_threshold(),
+                min_reduction_usdt: default_min_reduction_usdt(),
                 allocation_concentration: default_allocation_concentration(),
+                notional_caps: NotionalCapsConfig::default(),
+                existing_holdings: ExistingHoldingsConfig::default(),
+                dynamic_utilization: DynamicUtilizationConfig::default(),
+                flash_reserve: FlashReserveConfig::default(),
+                portfolio_rebalance: PortfolioRebalanceConfig::default(),
             },
             risk: RiskConfig {
                 max_drawdown: default_max_drawdown(),
@@ -369,38 +3134,74 @@ impl Default for Config {
                 min_expected_yield: default_min_expected_yield(),
                 grace_period_hours: default_grace_period_hours(),
                 max_funding_deviation: default_max_funding_deviation(),
+                funding_expectation_window_hours: default_funding_expectation_window_hours(),
+                funding_collapse_ratio: default_funding_collapse_ratio(),
+                exit_liquidity_check_minutes: default_exit_liquidity_check_minutes(),
+                max_exit_cost_ratio: default_max_exit_cost_ratio(),
                 max_loss_usd: default_max_loss_usd(),
                 max_negative_apy: default_max_negative_apy(),
+                borrow_spike_ratio: default_borrow_spike_ratio(),
+                low_supply_spike_ratio: default_low_supply_spike_ratio(),
+                borrow_holidays: Vec::new(),
+                borrow_limits: BorrowLimitsConfig::default(),
+                reentry_cooldown_minutes: default_reentry_cooldown_minutes(),
+                forced_exit_cooldown_minutes: default_forced_exit_cooldown_minutes(),
                 max_errors_per_minute: default_max_errors_per_minute(),
                 max_consecutive_failures: default_max_consecutive_failures(),
                 emergency_delta_drift: default_emergency_delta_drift(),
+                max_residual_delta_usd: default_max_residual_delta_usd(),
+                malfunction_state_max_age_minutes: default_malfunction_state_max_age_minutes(),
                 max_consecutive_risk_cycles: default_max_consecutive_risk_cycles(),
+                resumption: ResumptionConfig::default(),
+                funding_quarantine: FundingQuarantineConfig::default(),
+                safe_mode_after_minutes: default_safe_mode_after_minutes(),
+                persistence_watchdog: PersistenceWatchdogConfig::default(),
+                rules: Vec::new(),
             },
             pair_selection: PairSelectionConfig {
                 min_volume_24h: default_min_volume(),
+                min_volume_percentile: Decimal::ZERO,
                 min_funding_rate: default_min_funding_rate(),
                 max_spread: default_max_spread(),
                 min_open_interest: default_min_open_interest(),
                 max_positions: default_max_positions(),
                 default_borrow_rate: default_borrow_rate(),
                 min_net_funding: default_min_net_funding(),
+                prefilter_top_k: default_prefilter_top_k(),
+                scan_cache_max_age_minutes: default_scan_cache_max_age_minutes(),
+                futures_hedges: false,
+                basis_horizon_hours: default_basis_horizon_hours(),
+                hedge_switch_threshold: default_hedge_switch_threshold(),
+                hedge_review_minutes: default_hedge_review_minutes(),
+                symbol_quality_lookback_days: default_symbol_quality_lookback_days(),
+                max_symbol_failures: default_max_symbol_failures(),
+                capped_funding_persistence: default_capped_funding_persistence(),
+                max_open_interest_share: default_max_open_interest_share(),
             },
             execution: ExecutionConfig {
                 default_leverage: default_leverage(),
                 max_leverage: default_max_leverage(),
                 slippage_tolerance: default_slippage_tolerance(),
                 order_timeout_secs: default_order_timeout(),
+                client_order_tag: default_client_order_tag(),
+                max_orders_per_minute: default_max_orders_per_minute(),
+                max_notional_per_hour: default_max_notional_per_hour(),
+                maker_fee_rate: default_maker_fee_rate(),
+                taker_fee_rate: default_taker_fee_rate(),
+                settings_mismatch: SettingsMismatchAction::default(),
+                settings_check_minutes: default_settings_check_minutes(),
+                entry_timing: EntryTimingConfig::default(),
+                action_cost: ActionCostConfig::default(),
             },
-        }
-    }
-}
-
-impl Default for BinanceConfig {
-    fn default() -> Self {
-        Self {
-            api_key: String::new(),
-            secret_key: String::new(),
-            testnet: false,
+            display: DisplayConfig::default(),
+            events: EventsConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            cross_venue: CrossVenueConfig::default(),
+            hedge: HedgeConfig::default(),
+            take_profit: TakeProfitConfig::default(),
+            alerts: AlertsConfig::default(),
+            api_budget: ApiBudgetConfig::default(),
+            risk_free: RiskFreeConfig::default(),
         }
     }
 }
@@ -412,7 +3213,13 @@ impl Default for CapitalConfig {
             reserve_buffer: default_reserve_buffer(),
             min_position_size: default_min_position_size(),
             rebalance_threshold: default_rebalance_threshold(),
+            min_reduction_usdt: default_min_reduction_usdt(),
             allocation_concentration: default_allocation_concentration(),
+            notional_caps: NotionalCapsConfig::default(),
+            existing_holdings: ExistingHoldingsConfig::default(),
+            dynamic_utilization: DynamicUtilizationConfig::default(),
+            flash_reserve: FlashReserveConfig::default(),
+            portfolio_rebalance: PortfolioRebalanceConfig::default(),
         }
     }
 }
@@ -430,12 +3237,29 @@ impl Default for RiskConfig {
             min_expected_yield: default_min_expected_yield(),
             grace_period_hours: default_grace_period_hours(),
             max_funding_deviation: default_max_funding_deviation(),
+            funding_expectation_window_hours: default_funding_expectation_window_hours(),
+            funding_collapse_ratio: default_funding_collapse_ratio(),
+            exit_liquidity_check_minutes: default_exit_liquidity_check_minutes(),
+            max_exit_cost_ratio: default_max_exit_cost_ratio(),
             max_loss_usd: default_max_loss_usd(),
             max_negative_apy: default_max_negative_apy(),
+            borrow_spike_ratio: default_borrow_spike_ratio(),
+            low_supply_spike_ratio: default_low_supply_spike_ratio(),
+            borrow_holidays: Vec::new(),
+            borrow_limits: BorrowLimitsConfig::default(),
+            reentry_cooldown_minutes: default_reentry_cooldown_minutes(),
+            forced_exit_cooldown_minutes: default_forced_exit_cooldown_minutes(),
             max_errors_per_minute: default_max_errors_per_minute(),
             max_consecutive_failures: default_max_consecutive_failures(),
             emergency_delta_drift: default_emergency_delta_drift(),
+            max_residual_delta_usd: default_max_residual_delta_usd(),
+            malfunction_state_max_age_minutes: default_malfunction_state_max_age_minutes(),
             max_consecutive_risk_cycles: default_max_consecutive_risk_cycles(),
+            resumption: ResumptionConfig::default(),
+            funding_quarantine: FundingQuarantineConfig::default(),
+            safe_mode_after_minutes: default_safe_mode_after_minutes(),
+            persistence_watchdog: PersistenceWatchdogConfig::default(),
+            rules: Vec::new(),
         }
     }
 }
@@ -444,12 +3268,23 @@ impl Default for PairSelectionConfig {
     fn default() -> Self {
         Self {
             min_volume_24h: default_min_volume(),
+            min_volume_percentile: Decimal::ZERO,
             min_funding_rate: default_min_funding_rate(),
             max_spread: default_max_spread(),
             min_open_interest: default_min_open_interest(),
             max_positions: default_max_positions(),
             default_borrow_rate: default_borrow_rate(),
             min_net_funding: default_min_net_funding(),
+            prefilter_top_k: default_prefilter_top_k(),
+            scan_cache_max_age_minutes: default_scan_cache_max_age_minutes(),
+            futures_hedges: false,
+            basis_horizon_hours: default_basis_horizon_hours(),
+            hedge_switch_threshold: default_hedge_switch_threshold(),
+            hedge_review_minutes: default_hedge_review_minutes(),
+            symbol_quality_lookback_days: default_symbol_quality_lookback_days(),
+            max_symbol_failures: default_max_symbol_failures(),
+            capped_funding_persistence: default_capped_funding_persistence(),
+            max_open_interest_share: default_max_open_interest_share(),
         }
     }
 }
@@ -461,6 +3296,15 @@ impl Default for ExecutionConfig {
             max_leverage: default_max_leverage(),
             slippage_tolerance: default_slippage_tolerance(),
             order_timeout_secs: default_order_timeout(),
+            client_order_tag: default_client_order_tag(),
+            max_orders_per_minute: default_max_orders_per_minute(),
+            max_notional_per_hour: default_max_notional_per_hour(),
+            maker_fee_rate: default_maker_fee_rate(),
+            taker_fee_rate: default_taker_fee_rate(),
+            settings_mismatch: SettingsMismatchAction::default(),
+            settings_check_minutes: default_settings_check_minutes(),
+            entry_timing: EntryTimingConfig::default(),
+            action_cost: ActionCostConfig::default(),
         }
     }
 }
@@ -474,4 +3318,639 @@ mod tests {
         let config = Config::default();
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_default_config_is_valid_for_live_profile() {
+        let config = Config::default();
+        assert!(config.validate_for_profile(Profile::Live).is_ok());
+    }
+
+    #[test]
+    fn test_live_profile_rejects_risky_parameters() {
+        let mut config = Config::default();
+        config.risk.max_drawdown = Decimal::new(20, 2); // 0.20
+        assert!(config.validate_for_profile(Profile::Paper).is_ok());
+        assert!(config.validate_for_profile(Profile::Live).is_err());
+
+        let mut config = Config::default();
+        config.execution.default_leverage = 8;
+        assert!(config.validate_for_profile(Profile::Dev).is_ok());
+        assert!(config.validate_for_profile(Profile::Live).is_err());
+
+        let mut config = Config::default();
+        config.execution.max_orders_per_minute = 0;
+        assert!(config.validate_for_profile(Profile::Dev).is_ok());
+        assert!(config.validate_for_profile(Profile::Live).is_err());
+
+        let mut config = Config::default();
+        config.risk.safe_mode_after_minutes = 0;
+        assert!(config.validate_for_profile(Profile::Dev).is_ok());
+        assert!(config.validate_for_profile(Profile::Live).is_err());
+    }
+
+    #[test]
+    fn test_violations_reports_everything_at_once() {
+        let mut config = Config::default();
+        config.capital.max_utilization = Decimal::new(95, 2); // 0.95
+        config.capital.reserve_buffer = Decimal::new(10, 2); // 0.10
+        config.risk.grace_period_hours = 12;
+        config.risk.max_unprofitable_hours = 12;
+        config.execution.order_timeout_secs = 0;
+        config.execution.client_order_tag = "ff-f".to_string();
+
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 4, "{:?}", violations);
+        assert!(violations[0].contains("reserve_buffer + capital.max_utilization"));
+        assert!(violations[1].contains("grace_period_hours"));
+        assert!(violations[2].contains("order_timeout_secs"));
+        assert!(violations[3].contains("client_order_tag"));
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("4 violation(s)"));
+    }
+
+    #[test]
+    fn test_fraction_bounds() {
+        let mut config = Config::default();
+        config.risk.max_drawdown = Decimal::new(5, 0); // 5 instead of 0.05
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("risk.max_drawdown must be in (0, 1]"));
+    }
+
+    #[test]
+    fn test_maker_rebate_within_taker_fee() {
+        let mut config = Config::default();
+        config.execution.maker_fee_rate = Decimal::new(-5, 5); // -0.005% rebate
+        assert!(config.violations(Profile::Dev).is_empty());
+        assert!(config.execution.fee_schedule().has_rebate());
+
+        config.execution.maker_fee_rate = Decimal::new(-5, 4);
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("execution.maker_fee_rate must be within"));
+    }
+
+    #[test]
+    fn test_borrow_spike_settings() {
+        let risk: RiskConfig = toml::from_str(
+            r#"
+            borrow_holidays = ["2024-12-25", "2025-01-01"]
+            low_supply_spike_ratio = 4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(risk.borrow_spike_ratio, Decimal::new(3, 0));
+        assert_eq!(
+            risk.borrow_holidays[0],
+            NaiveDate::from_ymd_opt(2024, 12, 25).unwrap()
+        );
+
+        // The low-supply threshold may only be stricter
+        let config = Config {
+            risk,
+            ..Config::default()
+        };
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("risk.low_supply_spike_ratio"));
+    }
+
+    #[test]
+    fn test_dynamic_utilization_floor_below_cap() {
+        let mut config = Config::default();
+        assert!(config.capital.dynamic_utilization.enabled);
+        config.capital.dynamic_utilization.min_utilization = Decimal::new(9, 1); // 0.9
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("capital.dynamic_utilization.min_utilization"));
+
+        // Only checked while enabled
+        config.capital.dynamic_utilization.enabled = false;
+        assert!(config.violations(Profile::Dev).is_empty());
+    }
+
+    #[test]
+    fn test_flash_reserve_validation() {
+        let mut config = Config::default();
+        assert!(!config.capital.flash_reserve.enabled);
+        config.capital.flash_reserve.release_fraction = Decimal::new(15, 1); // 1.5
+        config.capital.flash_reserve.duration_hours = 0;
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        config.capital.flash_reserve.enabled = true;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].starts_with("capital.flash_reserve.release_fraction"));
+        assert!(violations[1].starts_with("capital.flash_reserve.duration_hours"));
+    }
+
+    #[test]
+    fn test_portfolio_rebalance_validation() {
+        let mut config = Config::default();
+        config.capital.portfolio_rebalance.interval_hours = 0;
+        config.capital.portfolio_rebalance.min_drift = Decimal::ZERO;
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        config.capital.portfolio_rebalance.enabled = true;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].starts_with("capital.portfolio_rebalance.interval_hours"));
+        assert!(violations[1].starts_with("capital.portfolio_rebalance.min_drift"));
+    }
+
+    #[test]
+    fn test_funding_quarantine_validation() {
+        let mut config = Config::default();
+        config.risk.funding_quarantine.anomalies = 0;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("risk.funding_quarantine.anomalies"));
+
+        config.risk.funding_quarantine.enabled = false;
+        assert!(config.violations(Profile::Dev).is_empty());
+    }
+
+    #[test]
+    fn test_persistence_watchdog_validation() {
+        let mut config = Config::default();
+        config.risk.persistence_watchdog.critical_after_failures = 2;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("risk.persistence_watchdog.critical_after_failures"));
+
+        config.risk.persistence_watchdog.error_after_failures = 0;
+        assert!(config.violations(Profile::Dev)[0]
+            .starts_with("risk.persistence_watchdog.error_after_failures"));
+    }
+
+    #[test]
+    fn test_entry_timing_validation() {
+        let mut config = Config::default();
+        config.execution.entry_timing.enabled = true;
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        config.execution.entry_timing.abort_imbalance = Decimal::new(2, 1); // 0.2
+        config.execution.entry_timing.max_wait_minutes = 0;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].starts_with("execution.entry_timing.abort_imbalance"));
+        assert!(violations[1].starts_with("execution.entry_timing.max_wait_minutes"));
+    }
+
+    #[test]
+    fn test_recv_window_within_binance_limit() {
+        let mut config = Config::default();
+        assert_eq!(config.binance.recv_window_ms, 5000);
+        config.binance.recv_window_ms = 90_000;
+        config.binance.time_sync_minutes = 0;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].starts_with("binance.recv_window_ms"));
+        assert!(violations[1].starts_with("binance.time_sync_minutes"));
+    }
+
+    #[test]
+    fn test_effective_config_round_trips_and_redacts_secrets() {
+        let mut config = Config::default();
+        config.binance.secret_key = "super-secret".to_string();
+        config.binance.read_secret_key = "read-secret".to_string();
+        config.binance.sub_account.secret_key = "sub-secret".to_string();
+        config.risk.max_drawdown = Decimal::new(8, 2); // 0.08
+
+        let toml_out = config.to_effective_string(ConfigFormat::Toml).unwrap();
+        assert!(!toml_out.contains("super-secret"));
+        assert!(!toml_out.contains("read-secret"));
+        assert!(!toml_out.contains("sub-secret"));
+        assert!(toml_out.contains("[risk]"));
+
+        let parsed: Config = toml::from_str(&toml_out).unwrap();
+        assert_eq!(parsed.risk.max_drawdown, Decimal::new(8, 2));
+        assert_eq!(parsed.binance.api_key, "");
+
+        let json_out = config.to_effective_string(ConfigFormat::Json).unwrap();
+        let parsed: Config = serde_json::from_str(&json_out).unwrap();
+        assert_eq!(parsed.risk.max_drawdown, Decimal::new(8, 2));
+        assert_eq!(parsed.binance.secret_key, "<redacted>");
+    }
+
+    #[test]
+    fn test_config_hash_ignores_credentials() {
+        let config = Config::default();
+        let hash = config.config_hash().unwrap();
+        assert_eq!(hash.len(), CONFIG_HASH_LEN);
+
+        let mut rotated = config.clone();
+        rotated.binance.secret_key = "new-secret".to_string();
+        rotated.binance.api_key = "new-key".to_string();
+        let mut original = config.clone();
+        original.binance.secret_key = "old-secret".to_string();
+        original.binance.api_key = "old-key".to_string();
+        assert_eq!(
+            rotated.config_hash().unwrap(),
+            original.config_hash().unwrap()
+        );
+
+        let mut changed = config;
+        changed.risk.max_drawdown = Decimal::new(4, 2);
+        assert_ne!(changed.config_hash().unwrap(), hash);
+    }
+
+    #[test]
+    fn test_sub_account_validation() {
+        let mut config = Config::default();
+        config.binance.sub_account.enabled = true;
+        config.binance.sub_account.wallet = "COIN_FUTURE".to_string();
+
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 3, "{:?}", violations);
+        assert!(violations[0].contains("sub_account.email"));
+        assert!(violations[1].contains("sub_account.capital"));
+        assert!(violations[2].contains("sub_account.wallet"));
+
+        config.binance.sub_account.email = "farm@example.com".to_string();
+        config.binance.sub_account.capital = Decimal::new(5000, 0);
+        config.binance.sub_account.wallet = "MARGIN".to_string();
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // A read-only key is useless without its secret
+        config.binance.sub_account.read_api_key = "read-key".to_string();
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("binance.sub_account.read_api_key"));
+    }
+
+    #[test]
+    fn test_venue_validation() {
+        let mut config: Config = toml::from_str(
+            r#"
+            venue = "bybit"
+
+            [bybit]
+            testnet = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.venue, Venue::Bybit);
+        assert!(config.bybit.testnet);
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // Sub-accounts exist on Binance only
+        config.binance.sub_account.enabled = true;
+        config.binance.sub_account.email = "farm@example.com".to_string();
+        config.binance.sub_account.capital = Decimal::new(5000, 0);
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("binance.sub_account"));
+
+        // Hyperliquid only holds the legs of cross-venue spreads
+        config.binance.sub_account.enabled = false;
+        config.venue = Venue::Hyperliquid;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("venue"));
+
+        // Routing picks between Binance and OKX, so it starts from Binance
+        let mut config: Config = toml::from_str(
+            r#"
+            venue = "okx"
+
+            [okx]
+            demo = true
+            route_entries = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.venue, Venue::Okx);
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("okx.route_entries"));
+        config.venue = Venue::Binance;
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // Spread trades put their other leg on Binance futures
+        config.okx.route_entries = false;
+        config.cross_venue.execute = true;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(
+            violations,
+            vec!["cross_venue.execute requires cross_venue.enabled"]
+        );
+        config.cross_venue.enabled = true;
+        config.cross_venue.exit_spread = config.cross_venue.min_spread;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].starts_with("cross_venue.exit_spread"));
+
+        // COIN-margined contracts are listed on Binance only
+        config.cross_venue = CrossVenueConfig::default();
+        config.capital.existing_holdings.coin_margined = true;
+        config.venue = Venue::Okx;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].ends_with("requires capital.existing_holdings.enabled"));
+        assert!(violations[1].ends_with("requires venue 'binance'"));
+        config.capital.existing_holdings.enabled = true;
+        config.venue = Venue::Binance;
+        assert!(config.violations(Profile::Dev).is_empty());
+    }
+
+    #[test]
+    fn test_events_validation() {
+        let mut config = Config::default();
+        config.events.enabled = true;
+        config.events.reduced_size_factor = Decimal::ZERO;
+
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].contains("events.file or events.calendar_url"));
+        assert!(violations[1].contains("events.reduced_size_factor"));
+
+        config.events.file = "config/events.toml".to_string();
+        config.events.reduced_size_factor = Decimal::new(25, 2);
+        assert!(config.violations(Profile::Dev).is_empty());
+    }
+
+    #[test]
+    fn test_risk_free_validation() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [risk_free]
+            annual_rate = 0.045
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.risk_free.annual_rate, Decimal::new(45, 3));
+        assert!(!config.risk_free.fetch);
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // Rates are fractions, not percentages
+        config.risk_free.annual_rate = Decimal::new(45, 1);
+        config.risk_free.fetch = true;
+        config.risk_free.url = String::new();
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].contains("risk_free.annual_rate"));
+        assert!(violations[1].contains("risk_free.url"));
+    }
+
+    #[test]
+    fn test_maintenance_windows() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[maintenance.windows]]
+            start = "2024-03-20T02:00:00Z"
+            end = "2024-03-20T04:00:00Z"
+            note = "Wallet upgrade"
+            "#,
+        )
+        .unwrap();
+        assert!(config.maintenance.enabled);
+        assert_eq!(config.maintenance.entry_buffer_minutes, 60);
+        assert_eq!(config.maintenance.windows[0].note, "Wallet upgrade");
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        config.maintenance.windows[0].end = config.maintenance.windows[0].start;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].contains("maintenance.windows[0]"));
+    }
+
+    #[test]
+    fn test_take_profit_validation() {
+        let mut config = Config::default();
+        config.take_profit.enabled = true;
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        config.take_profit.fee_multiple = Decimal::ZERO;
+        config.take_profit.notional_pct = Decimal::ZERO;
+        config.take_profit.reduce_fraction = Decimal::ONE;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].contains("fee_multiple or notional_pct"));
+        assert!(violations[1].contains("core position"));
+    }
+
+    #[test]
+    fn test_risk_rules_parse_and_validate() {
+        let mut risk: RiskConfig = toml::from_str(
+            r#"
+            [[rules]]
+            name = "stale-loser"
+            action = "consider_exit"
+            when = [
+              { metric = "net_pnl", op = "<", value = -5 },
+              { metric = "hours_open", op = ">", value = 24 },
+            ]
+
+            [[rules]]
+            name = "thin-carry"
+            action = "halt_entries"
+            when = [{ metric = "portfolio_funding_apr", op = "<", value = 0.05 }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(risk.rules.len(), 2);
+        assert_eq!(risk.rules[0].when[0].value, Decimal::new(-5, 0));
+        assert_eq!(risk.rules[1].when[0].op, RuleOp::Lt);
+        assert_eq!(
+            risk.rules[1].to_string(),
+            "if portfolio_funding_apr < 0.05 then halt_entries"
+        );
+
+        let mut config = Config {
+            risk: risk.clone(),
+            ..Default::default()
+        };
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // Entry halts can't read position metrics; names must be unique
+        let net_pnl = risk.rules[0].when[0].clone();
+        risk.rules[1].when.push(net_pnl);
+        risk.rules[0].name = "thin-carry".to_string();
+        config.risk = risk;
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 2, "{:?}", violations);
+        assert!(violations[0].contains("unique name"));
+        assert!(violations[1].contains("position metric net_pnl"));
+    }
+
+    #[test]
+    fn test_alert_routes_parse_and_validate() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [alerts.routes.margin_warning]
+            error = "notify"
+            critical = "auto_reduce"
+
+            [alerts.routes.hedge_switch_recommended]
+            info = "log"
+            "#,
+        )
+        .unwrap();
+        let margin = &config.alerts.routes[&AlertClass::MarginWarning];
+        assert_eq!(margin.error, Some(AlertAction::Notify));
+        assert_eq!(margin.warning, None);
+        assert!(config.violations(Profile::Dev).is_empty());
+
+        // The matrix survives an effective-config round trip
+        let toml_out = config.to_effective_string(ConfigFormat::Toml).unwrap();
+        let parsed: Config = toml::from_str(&toml_out).unwrap();
+        assert_eq!(parsed.alerts, config.alerts);
+
+        // Only alerts carrying a reduction can auto-reduce; live keeps the drawdown halt
+        config.alerts.routes.insert(
+            AlertClass::FundingCollapse,
+            SeverityRoutes {
+                warning: Some(AlertAction::AutoReduce),
+                ..Default::default()
+            },
+        );
+        config.alerts.routes.insert(
+            AlertClass::DrawdownExceeded,
+            SeverityRoutes {
+                critical: Some(AlertAction::Notify),
+                ..Default::default()
+            },
+        );
+        let violations = config.violations(Profile::Dev);
+        assert_eq!(violations.len(), 1, "{:?}", violations);
+        assert!(violations[0].contains("funding_collapse alerts have nothing to auto_reduce"));
+        assert!(config
+            .violations(Profile::Live)
+            .iter()
+            .any(|v| v.contains("drawdown_exceeded.critical to halt")));
+    }
+
+    fn test_caps() -> NotionalCapsConfig {
+        NotionalCapsConfig {
+            max_per_symbol: Decimal::new(50_000, 0),
+            symbols: vec![SymbolNotionalCap {
+                symbol: "DOGEUSDT".to_string(),
+                max_notional: Decimal::new(10_000, 0),
+            }],
+            groups: vec![GroupNotionalCap {
+                name: "memes".to_string(),
+                symbols: vec!["DOGEUSDT".to_string(), "PEPEUSDT".to_string()],
+                max_notional: Decimal::new(15_000, 0),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_symbol_notional_cap_override_and_default() {
+        let caps = test_caps();
+        assert_eq!(caps.symbol_cap("dogeusdt"), Some(Decimal::new(10_000, 0)));
+        assert_eq!(caps.symbol_cap("BTCUSDT"), Some(Decimal::new(50_000, 0)));
+        assert_eq!(NotionalCapsConfig::default().symbol_cap("BTCUSDT"), None);
+    }
+
+    #[test]
+    fn test_group_cap_limits_by_other_members() {
+        let caps = test_caps();
+        let mut positions = HashMap::new();
+        positions.insert("PEPEUSDT".to_string(), Decimal::new(12_000, 0));
+
+        // Group headroom (15k - 12k) is tighter than the DOGE symbol cap (10k)
+        assert_eq!(
+            caps.max_notional_for("DOGEUSDT", &positions),
+            Some(Decimal::new(3_000, 0))
+        );
+        // Non-members only see the default symbol cap
+        assert_eq!(
+            caps.max_notional_for("BTCUSDT", &positions),
+            Some(Decimal::new(50_000, 0))
+        );
+    }
+
+    #[test]
+    fn test_notional_cap_breaches() {
+        let caps = test_caps();
+        let mut positions = HashMap::new();
+        positions.insert("DOGEUSDT".to_string(), Decimal::new(11_000, 0));
+        positions.insert("PEPEUSDT".to_string(), Decimal::new(-9_000, 0));
+        positions.insert("BTCUSDT".to_string(), Decimal::new(40_000, 0));
+
+        let breaches = caps.breaches(&positions);
+        assert_eq!(breaches.len(), 2);
+        assert_eq!(breaches[0].scope, "DOGEUSDT");
+        assert_eq!(breaches[1].scope, "group:memes");
+        assert_eq!(breaches[1].notional, Decimal::new(20_000, 0));
+    }
+
+    #[test]
+    fn test_hedge_target_override_and_default() {
+        let hedge: HedgeConfig = toml::from_str(
+            r#"
+            target_ratio = 0.95
+
+            [[symbols]]
+            symbol = "ETHUSDT"
+            target_ratio = 0.90
+            tolerance = 0.02
+            "#,
+        )
+        .unwrap();
+
+        let eth = hedge.target("ethusdt");
+        assert_eq!(eth.ratio, Decimal::new(90, 2));
+        assert_eq!(eth.tolerance, Decimal::new(2, 2));
+        // 10% of a 2-unit short futures leg stays unhedged
+        assert_eq!(eth.target_net_qty(Decimal::new(-2, 0)), Decimal::new(-2, 1));
+
+        let btc = hedge.target("BTCUSDT");
+        assert_eq!(btc.ratio, Decimal::new(95, 2));
+        assert_eq!(btc.tolerance, Decimal::new(3, 2));
+
+        let mut config = Config {
+            hedge,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        config.hedge.symbols[0].target_ratio = Decimal::new(12, 1);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_profile_parsing() {
+        assert_eq!("live".parse::<Profile>().unwrap(), Profile::Live);
+        assert_eq!(" Paper ".parse::<Profile>().unwrap(), Profile::Paper);
+        assert!("prod".parse::<Profile>().is_err());
+        assert_eq!(Profile::Live.config_file(), "config.live");
+    }
+
+    #[test]
+    fn test_explicit_profile_wins() {
+        let profile = Profile::resolve(Some(Profile::Paper), Profile::Live).unwrap();
+        assert_eq!(profile, Profile::Paper);
+    }
+
+    #[test]
+    fn test_loads_files_from_config_dir() {
+        let dir = std::env::temp_dir().join(format!("fff-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            "[display]\ntimezone = \"Asia/Seoul\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("config.paper.toml"),
+            "[risk]\nmax_drawdown = 0.07\n",
+        )
+        .unwrap();
+
+        let paper = Config::load_unvalidated(&dir, Some(Profile::Paper), Profile::Dev);
+        let dev = Config::load_unvalidated(&dir, Some(Profile::Dev), Profile::Dev);
+        std::fs::remove_dir_all(&dir).ok();
+
+        let paper = paper.unwrap();
+        assert_eq!(paper.display.timezone, "Asia/Seoul");
+        assert_eq!(paper.risk.max_drawdown, Decimal::new(7, 2));
+        // The overlay applies only to its own profile
+        assert_eq!(
+            dev.unwrap().risk.max_drawdown,
+            RiskConfig::default().max_drawdown
+        );
+    }
 }
@@ -0,0 +1,506 @@
+//! REST API usage budget.
+//!
+//! Binance limits request weight per minute and IP, separately for futures and
+//! spot, and counts orders per account. `ApiUsage` sits inside the live client and
+//! counts the weight and orders of every request sent, per UTC day, along with the
+//! busiest minute seen. `UsageProjection` estimates the load the configured scan
+//! cadence and position count put on the same limits, so a configuration that
+//! can't fit is reported at startup instead of surfacing as 429s or an IP ban.
+
+use crate::config::{ApiBudgetConfig, Config};
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use rust_decimal::Decimal;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Weight of one futures scan: premium index, 24h tickers and book tickers.
+const SCAN_FUTURES_WEIGHT: u32 = 10 + 40 + 5;
+/// Weight of one spot scan without tickers: exchange info and margin assets.
+const SCAN_SPOT_WEIGHT: u32 = 20 + 1;
+/// Per-cycle account and valuation reads: mark prices, balance and positions.
+const ACCOUNT_FUTURES_WEIGHT: u32 = 10 + 5 + 5;
+/// Per-cycle cross margin account read.
+const ACCOUNT_SPOT_WEIGHT: u32 = 10;
+/// Spot order book at the exit liquidity depth (limit 100).
+const DEPTH_WEIGHT: u32 = 5;
+/// Leverage and margin type check of held symbols (position risk).
+const SETTINGS_CHECK_WEIGHT: u32 = 5;
+
+/// Exchange whose limits a request counts against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiMarket {
+    Futures,
+    Spot,
+}
+
+impl fmt::Display for ApiMarket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiMarket::Futures => write!(f, "futures"),
+            ApiMarket::Spot => write!(f, "spot"),
+        }
+    }
+}
+
+/// Request weight and order count of one REST call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestCost {
+    pub market: ApiMarket,
+    pub weight: u32,
+    /// Orders the call counts towards the order limits
+    pub orders: u32,
+}
+
+impl RequestCost {
+    fn futures(weight: u32) -> Self {
+        Self {
+            market: ApiMarket::Futures,
+            weight,
+            orders: 0,
+        }
+    }
+
+    fn spot(weight: u32) -> Self {
+        Self {
+            market: ApiMarket::Spot,
+            weight,
+            orders: 0,
+        }
+    }
+
+    fn order(market: ApiMarket) -> Self {
+        Self {
+            market,
+            weight: 1,
+            orders: 1,
+        }
+    }
+
+    /// Cost of a client operation, by its `BinanceClient` method name.
+    ///
+    /// Unknown operations count as a single futures weight.
+    pub fn of(operation: &str) -> Self {
+        match operation {
+            "get_funding_rates" => Self::futures(10),
+            "get_24h_tickers" => Self::futures(40),
+            "get_book_tickers" => Self::futures(5),
+            "get_account_balance"
+            | "get_positions"
+            | "get_recent_futures_orders"
+            | "get_futures_trades" => Self::futures(5),
+            "get_income_history" => Self::futures(30),
+            "get_open_futures_orders" => Self::futures(40),
+            "place_futures_order" | "amend_futures_order" => Self::order(ApiMarket::Futures),
+            "get_spot_24h_tickers" => Self::spot_tickers(0),
+            "get_spot_exchange_info" | "get_spot_account" => Self::spot(20),
+            "get_cross_margin_account" => Self::spot(10),
+            "get_max_borrowable" => Self::spot(50),
+            "get_open_margin_orders" | "cancel_margin_order" => Self::spot(10),
+            "get_spot_depth" => Self::spot(DEPTH_WEIGHT),
+            "get_spot_price" => Self::spot(2),
+            "place_margin_order" => Self::order(ApiMarket::Spot),
+            "get_margin_all_assets"
+            | "get_system_status"
+            | "margin_borrow"
+            | "margin_repay"
+            | "get_margin_order_by_client_id"
+            | "get_recent_margin_orders"
+            | "sub_account_transfer" => Self::spot(1),
+            _ => Self::futures(1),
+        }
+    }
+
+    /// Cost of a futures batch order request carrying `orders` orders.
+    pub fn batch_orders(orders: usize) -> Self {
+        Self {
+            orders: orders as u32,
+            ..Self::futures(5)
+        }
+    }
+
+    /// Cost of spot 24h tickers for `symbols` symbols (0 = all symbols).
+    pub fn spot_tickers(symbols: usize) -> Self {
+        Self::spot(match symbols {
+            0 => 80,
+            1..=20 => 2,
+            21..=100 => 40,
+            _ => 80,
+        })
+    }
+}
+
+/// Usage against one market's limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarketUsage {
+    pub requests: u64,
+    pub weight: u64,
+    pub orders: u64,
+    /// Highest weight used in one minute
+    pub peak_weight_per_minute: u32,
+    /// Most orders sent in one minute
+    pub peak_orders_per_minute: u32,
+}
+
+/// Usage during one UTC day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    pub futures: MarketUsage,
+    pub spot: MarketUsage,
+}
+
+impl DailyUsage {
+    fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            futures: MarketUsage::default(),
+            spot: MarketUsage::default(),
+        }
+    }
+
+    pub fn market(&self, market: ApiMarket) -> &MarketUsage {
+        match market {
+            ApiMarket::Futures => &self.futures,
+            ApiMarket::Spot => &self.spot,
+        }
+    }
+
+    fn market_mut(&mut self, market: ApiMarket) -> &mut MarketUsage {
+        match market {
+            ApiMarket::Futures => &mut self.futures,
+            ApiMarket::Spot => &mut self.spot,
+        }
+    }
+
+    /// Limits the busiest minutes and the day's spot orders came close to.
+    pub fn breaches(&self, budget: &ApiBudgetConfig) -> Vec<BudgetBreach> {
+        check(
+            budget,
+            [
+                (
+                    ApiMarket::Futures,
+                    self.futures.peak_weight_per_minute,
+                    self.futures.peak_orders_per_minute,
+                ),
+                (
+                    ApiMarket::Spot,
+                    self.spot.peak_weight_per_minute,
+                    self.spot.orders as u32,
+                ),
+            ],
+        )
+    }
+}
+
+/// Counters of the minute in progress, per market.
+#[derive(Debug, Default)]
+struct MinuteCounter {
+    minute: Option<DateTime<Utc>>,
+    weight: u32,
+    orders: u32,
+}
+
+#[derive(Debug, Default)]
+struct UsageState {
+    today: Option<DailyUsage>,
+    /// Last finished day, until handed out by `take_finished_day`
+    finished: Option<DailyUsage>,
+    futures_minute: MinuteCounter,
+    spot_minute: MinuteCounter,
+}
+
+/// Shared tracker of REST usage; clones count into the same totals.
+#[derive(Debug, Clone, Default)]
+pub struct ApiUsage {
+    state: Arc<Mutex<UsageState>>,
+}
+
+impl ApiUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a request sent at `now`.
+    ///
+    /// `used_weight_1m` is the exchange's own count of the IP's weight this minute
+    /// (the `X-MBX-USED-WEIGHT-1M` header), which also covers other processes on
+    /// the same IP. The busiest minute is the higher of that and the local count.
+    pub fn record(&self, cost: RequestCost, used_weight_1m: Option<u32>, now: DateTime<Utc>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let date = now.date_naive();
+        if state.today.is_some_and(|day| day.date != date) {
+            state.finished = state.today.take();
+        }
+
+        let minute = now
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(now);
+        let counter = match cost.market {
+            ApiMarket::Futures => &mut state.futures_minute,
+            ApiMarket::Spot => &mut state.spot_minute,
+        };
+        if counter.minute != Some(minute) {
+            *counter = MinuteCounter {
+                minute: Some(minute),
+                ..Default::default()
+            };
+        }
+        counter.weight += cost.weight;
+        counter.orders += cost.orders;
+        let (minute_weight, minute_orders) = (counter.weight, counter.orders);
+
+        let day = state.today.get_or_insert_with(|| DailyUsage::new(date));
+        let usage = day.market_mut(cost.market);
+        usage.requests += 1;
+        usage.weight += cost.weight as u64;
+        usage.orders += cost.orders as u64;
+        usage.peak_weight_per_minute = usage
+            .peak_weight_per_minute
+            .max(minute_weight)
+            .max(used_weight_1m.unwrap_or(0));
+        usage.peak_orders_per_minute = usage.peak_orders_per_minute.max(minute_orders);
+    }
+
+    /// Usage so far today, if anything was sent.
+    pub fn today(&self) -> Option<DailyUsage> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).today
+    }
+
+    /// The last finished day, returned once.
+    pub fn take_finished_day(&self) -> Option<DailyUsage> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .finished
+            .take()
+    }
+}
+
+/// Which limit a usage figure is measured against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    WeightPerMinute,
+    OrdersPerMinute,
+    OrdersPerDay,
+}
+
+/// Usage at or above the warning share of a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetBreach {
+    pub market: ApiMarket,
+    pub limit: BudgetLimit,
+    pub used: u32,
+    pub max: u32,
+}
+
+impl fmt::Display for BudgetBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.limit {
+            BudgetLimit::WeightPerMinute => "weight/min",
+            BudgetLimit::OrdersPerMinute => "orders/min",
+            BudgetLimit::OrdersPerDay => "orders/day",
+        };
+        write!(
+            f,
+            "{} {} {} of {} allowed",
+            self.market, self.used, unit, self.max
+        )
+    }
+}
+
+/// Breaches of `(market, weight per minute, orders)` figures; futures orders are
+/// per minute, spot orders per day.
+fn check(budget: &ApiBudgetConfig, figures: [(ApiMarket, u32, u32); 2]) -> Vec<BudgetBreach> {
+    let mut breaches = Vec::new();
+    for (market, weight, orders) in figures {
+        let (max_weight, order_limit, max_orders) = match market {
+            ApiMarket::Futures => (
+                budget.futures_weight_per_minute,
+                BudgetLimit::OrdersPerMinute,
+                budget.futures_orders_per_minute,
+            ),
+            ApiMarket::Spot => (
+                budget.spot_weight_per_minute,
+                BudgetLimit::OrdersPerDay,
+                budget.spot_orders_per_day,
+            ),
+        };
+        for (limit, used, max) in [
+            (BudgetLimit::WeightPerMinute, weight, max_weight),
+            (order_limit, orders, max_orders),
+        ] {
+            if Decimal::from(used) >= Decimal::from(max) * budget.warn_fraction {
+                breaches.push(BudgetBreach {
+                    market,
+                    limit,
+                    used,
+                    max,
+                });
+            }
+        }
+    }
+    breaches
+}
+
+/// Steady-state load of a configuration on the API limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageProjection {
+    pub futures_weight_per_minute: u32,
+    pub spot_weight_per_minute: u32,
+    /// Entering every position in one cycle
+    pub futures_orders_per_minute: u32,
+    pub spot_orders_per_day: u32,
+}
+
+impl UsageProjection {
+    /// Project usage of `config` with a scan every `scan_interval_secs`.
+    ///
+    /// Each cycle scans the market and reads the account; held positions add
+    /// exit liquidity checks and `round_trips_per_day` entries and exits, each
+    /// with a futures and a spot order.
+    pub fn from_config(config: &Config, scan_interval_secs: u64) -> Self {
+        let cycles_per_minute = 60.0 / scan_interval_secs.max(1) as f64;
+        let per_minute = |weight: u32, every_minutes: u32| -> f64 {
+            if every_minutes == 0 {
+                0.0
+            } else {
+                weight as f64 / every_minutes as f64
+            }
+        };
+
+        let positions = config.pair_selection.max_positions as u32;
+        let tickers = RequestCost::spot_tickers(config.pair_selection.prefilter_top_k).weight;
+        let futures = (SCAN_FUTURES_WEIGHT + ACCOUNT_FUTURES_WEIGHT) as f64 * cycles_per_minute
+            + per_minute(
+                SETTINGS_CHECK_WEIGHT,
+                config.execution.settings_check_minutes,
+            );
+        let spot = (SCAN_SPOT_WEIGHT + tickers + ACCOUNT_SPOT_WEIGHT) as f64 * cycles_per_minute
+            + per_minute(
+                DEPTH_WEIGHT * positions,
+                config.risk.exit_liquidity_check_minutes,
+            )
+            + per_minute(1, config.maintenance.status_check_minutes);
+
+        Self {
+            futures_weight_per_minute: futures.ceil() as u32,
+            spot_weight_per_minute: spot.ceil() as u32,
+            futures_orders_per_minute: (positions as f64 * cycles_per_minute).ceil() as u32,
+            spot_orders_per_day: positions * config.api_budget.round_trips_per_day * 2,
+        }
+    }
+
+    /// Limits the projection comes close to.
+    pub fn breaches(&self, budget: &ApiBudgetConfig) -> Vec<BudgetBreach> {
+        check(
+            budget,
+            [
+                (
+                    ApiMarket::Futures,
+                    self.futures_weight_per_minute,
+                    self.futures_orders_per_minute,
+                ),
+                (
+                    ApiMarket::Spot,
+                    self.spot_weight_per_minute,
+                    self.spot_orders_per_day,
+                ),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for UsageProjection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "futures {} weight/min, {} orders/min; spot {} weight/min, {} orders/day",
+            self.futures_weight_per_minute,
+            self.futures_orders_per_minute,
+            self.spot_weight_per_minute,
+            self.spot_orders_per_day
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_usage_per_day_and_minute() {
+        let usage = ApiUsage::new();
+        let now = Utc.with_ymd_and_hms(2024, 3, 20, 23, 59, 10).unwrap();
+        usage.record(RequestCost::of("get_24h_tickers"), None, now);
+        usage.record(RequestCost::of("get_funding_rates"), Some(600), now);
+        usage.record(RequestCost::of("place_futures_order"), None, now);
+        usage.record(RequestCost::spot_tickers(50), None, now);
+        usage.record(RequestCost::batch_orders(4), None, now);
+
+        let today = usage.today().unwrap();
+        assert_eq!(today.futures.requests, 4);
+        assert_eq!(today.futures.weight, 56);
+        assert_eq!(today.futures.orders, 5);
+        // The exchange's count includes other processes on the IP
+        assert_eq!(today.futures.peak_weight_per_minute, 600);
+        assert_eq!(today.spot.weight, 40);
+
+        // A new minute starts a new count; a new day hands back the last
+        usage.record(
+            RequestCost::of("place_margin_order"),
+            None,
+            now + Duration::minutes(1),
+        );
+        let finished = usage.take_finished_day().unwrap();
+        assert_eq!(finished.date, now.date_naive());
+        assert!(usage.take_finished_day().is_none());
+        let today = usage.today().unwrap();
+        assert_eq!(today.spot.orders, 1);
+        assert_eq!(today.spot.peak_weight_per_minute, 1);
+        assert_eq!(today.futures, MarketUsage::default());
+
+        let budget = ApiBudgetConfig {
+            futures_weight_per_minute: 700,
+            ..Default::default()
+        };
+        let breaches = finished.breaches(&budget);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].limit, BudgetLimit::WeightPerMinute);
+        assert_eq!(
+            breaches[0].to_string(),
+            "futures 600 weight/min of 700 allowed"
+        );
+    }
+
+    #[test]
+    fn test_projection_follows_config() {
+        let mut config = Config::default();
+        config.pair_selection.max_positions = 5;
+        config.pair_selection.prefilter_top_k = 20;
+        config.risk.exit_liquidity_check_minutes = 5;
+        config.execution.settings_check_minutes = 0;
+        config.maintenance.status_check_minutes = 0;
+
+        let projection = UsageProjection::from_config(&config, 60);
+        assert_eq!(projection.futures_weight_per_minute, 75);
+        // 21 + 2 tickers + 10 account + 5 positions' depth every 5 minutes
+        assert_eq!(projection.spot_weight_per_minute, 38);
+        assert_eq!(projection.futures_orders_per_minute, 5);
+        assert_eq!(projection.spot_orders_per_day, 10);
+        assert!(projection.breaches(&config.api_budget).is_empty());
+
+        // Scanning every 2s against a small budget doesn't fit
+        let fast = UsageProjection::from_config(&config, 2);
+        assert_eq!(fast.futures_weight_per_minute, 2250);
+        let budget = ApiBudgetConfig {
+            warn_fraction: dec!(0.9),
+            ..config.api_budget.clone()
+        };
+        let breaches = fast.breaches(&budget);
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].market, ApiMarket::Futures);
+    }
+}
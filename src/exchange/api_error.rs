@@ -0,0 +1,295 @@
+//! Classification of failed exchange calls.
+//!
+//! A timeout or a 5xx says nothing about the request and is worth retrying; a
+//! bad signature, a missing permission or an order failing a symbol filter fails
+//! the same way every time. The client retries only the first kind and returns
+//! everything else as an [`ApiError`] carrying its [`ErrorClass`], which travels
+//! inside `anyhow` errors so callers further up (the risk orchestrator's error
+//! recording) can tell an unstable API from a request the exchange refused.
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::fmt;
+
+/// Why an exchange call failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Timeouts, dropped connections and server errors; retried with backoff
+    Transient,
+    /// Request weight or order rate limit hit (429, 418 IP ban)
+    RateLimited,
+    /// Signature, timestamp, API key or permission errors
+    Auth,
+    /// Request refused on its merits: filters, precision, balance, parameters
+    Rejected,
+    /// Failures without a recognizable cause, such as unparseable responses
+    Unknown,
+}
+
+impl ErrorClass {
+    /// Class of an error response with Binance error `code`, when it has one.
+    pub fn of_response(status: StatusCode, code: Option<i64>) -> Self {
+        match code {
+            Some(-1003 | -1015) => return ErrorClass::RateLimited,
+            Some(-1000 | -1001 | -1006 | -1007 | -1008) => return ErrorClass::Transient,
+            Some(-1002 | -1021 | -1022 | -2014 | -2015) => return ErrorClass::Auth,
+            _ => {}
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+            ErrorClass::RateLimited
+        } else if status.is_server_error() {
+            ErrorClass::Transient
+        } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            ErrorClass::Auth
+        } else if status.is_client_error() {
+            ErrorClass::Rejected
+        } else {
+            ErrorClass::Unknown
+        }
+    }
+
+    /// Class of a request that got no response.
+    pub fn of_network(error: &reqwest::Error) -> Self {
+        if error.is_timeout() || error.is_connect() || error.is_request() {
+            ErrorClass::Transient
+        } else {
+            ErrorClass::Unknown
+        }
+    }
+
+    /// Whether the same request may succeed later.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ErrorClass::Transient | ErrorClass::RateLimited)
+    }
+
+    /// Whether a failed order request may have placed its order anyway.
+    ///
+    /// Timeouts, server errors and unrecognized failures leave the outcome
+    /// unknown; rate limits, auth errors and rejections refused the order.
+    pub fn may_have_executed(&self) -> bool {
+        matches!(self, ErrorClass::Transient | ErrorClass::Unknown)
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self {
+            ErrorClass::Transient => "transient",
+            ErrorClass::RateLimited => "rate_limited",
+            ErrorClass::Auth => "auth",
+            ErrorClass::Rejected => "rejected",
+            ErrorClass::Unknown => "unknown",
+        };
+        write!(f, "{}", class)
+    }
+}
+
+/// Error body of a Binance REST response.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    code: i64,
+    msg: String,
+}
+
+/// A failed exchange call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub operation: String,
+    pub class: ErrorClass,
+    /// HTTP status, if the exchange answered
+    pub status: Option<u16>,
+    /// Binance error code, if the body carried one
+    pub code: Option<i64>,
+    pub message: String,
+}
+
+impl ApiError {
+    /// Error of a response with `status` and `body`.
+    pub fn from_response(operation: &str, status: StatusCode, body: &str) -> Self {
+        let parsed = serde_json::from_str::<ErrorBody>(body).ok();
+        Self {
+            operation: operation.to_string(),
+            class: ErrorClass::of_response(status, parsed.as_ref().map(|b| b.code)),
+            status: Some(status.as_u16()),
+            code: parsed.as_ref().map(|b| b.code),
+            message: parsed.map(|b| b.msg).unwrap_or_else(|| body.to_string()),
+        }
+    }
+
+    /// Error of a request that got no response.
+    pub fn from_network(operation: &str, error: &reqwest::Error) -> Self {
+        Self {
+            operation: operation.to_string(),
+            class: ErrorClass::of_network(error),
+            status: None,
+            code: None,
+            message: error.to_string(),
+        }
+    }
+
+    /// Error of one order a batch request refused; the request itself succeeded.
+    pub fn from_batch_item(operation: &str, code: i64, msg: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            class: ErrorClass::of_response(StatusCode::BAD_REQUEST, Some(code)),
+            status: None,
+            code: Some(code),
+            message: msg.to_string(),
+        }
+    }
+
+    /// The `ApiError` anywhere in `error`'s chain.
+    pub fn find(error: &anyhow::Error) -> Option<&ApiError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<ApiError>())
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed ({}", self.operation, self.class)?;
+        if let Some(status) = self.status {
+            write!(f, ", HTTP {}", status)?;
+        }
+        if let Some(code) = self.code {
+            write!(f, ", code {}", code)?;
+        }
+        write!(f, "): {}", self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Copy of `error` for each order of a batch request that failed as a whole.
+///
+/// Keeps its [`ApiError`] so every copy is classed like the original.
+pub fn replicate_error(error: &anyhow::Error) -> anyhow::Error {
+    match ApiError::find(error) {
+        Some(api) => anyhow::Error::new(api.clone()),
+        None => anyhow::anyhow!("{:#}", error),
+    }
+}
+
+/// Class of any error: that of its [`ApiError`] or network error, else unknown.
+pub fn error_class(error: &anyhow::Error) -> ErrorClass {
+    error
+        .chain()
+        .find_map(|cause| {
+            cause
+                .downcast_ref::<ApiError>()
+                .map(|api| api.class)
+                .or_else(|| {
+                    cause
+                        .downcast_ref::<reqwest::Error>()
+                        .map(ErrorClass::of_network)
+                })
+        })
+        .unwrap_or(ErrorClass::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classifies_binance_codes_before_status() {
+        let signature = ApiError::from_response(
+            "place_futures_order",
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-1022,"msg":"Signature for this request is not valid."}"#,
+        );
+        assert_eq!(signature.class, ErrorClass::Auth);
+        assert_eq!(signature.code, Some(-1022));
+        assert_eq!(
+            signature.message,
+            "Signature for this request is not valid."
+        );
+
+        let filter = ApiError::from_response(
+            "place_futures_order",
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#,
+        );
+        assert_eq!(filter.class, ErrorClass::Rejected);
+        assert!(!filter.class.is_transient());
+
+        // A backend timeout is transient whatever the status
+        let timeout = ApiError::from_response(
+            "get_positions",
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-1007,"msg":"Timeout waiting for response from backend server."}"#,
+        );
+        assert!(timeout.class.is_transient());
+
+        let gateway = ApiError::from_response("get_positions", StatusCode::BAD_GATEWAY, "<html>");
+        assert_eq!(gateway.class, ErrorClass::Transient);
+        assert_eq!(gateway.code, None);
+        assert_eq!(gateway.message, "<html>");
+        assert_eq!(
+            ErrorClass::of_response(StatusCode::from_u16(418).unwrap(), None),
+            ErrorClass::RateLimited
+        );
+    }
+
+    #[test]
+    fn test_only_refusals_rule_out_an_order() {
+        let timeout = ApiError::from_response(
+            "place_futures_order",
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-1007,"msg":"Timeout waiting for response from backend server."}"#,
+        );
+        assert!(timeout.class.may_have_executed());
+        assert!(ErrorClass::Unknown.may_have_executed());
+
+        for refused in [
+            ErrorClass::RateLimited,
+            ErrorClass::Auth,
+            ErrorClass::Rejected,
+        ] {
+            assert!(!refused.may_have_executed());
+        }
+    }
+
+    #[test]
+    fn test_class_survives_context() {
+        let error = anyhow::Error::new(ApiError::from_response(
+            "margin_borrow",
+            StatusCode::UNAUTHORIZED,
+            r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#,
+        ))
+        .context("Spot hedge failed");
+        assert_eq!(error_class(&error), ErrorClass::Auth);
+        assert_eq!(ApiError::find(&error).unwrap().code, Some(-2015));
+        assert_eq!(
+            error.root_cause().to_string(),
+            "margin_borrow failed (auth, HTTP 401, code -2015): Invalid API-key, IP, or \
+             permissions for action."
+        );
+
+        let plain: anyhow::Result<()> = Err(anyhow::anyhow!("no price")).context("valuation");
+        assert_eq!(error_class(&plain.unwrap_err()), ErrorClass::Unknown);
+    }
+
+    #[test]
+    fn test_batch_errors_keep_their_class() {
+        let item =
+            ApiError::from_batch_item("place_futures_orders", -2019, "Margin is insufficient.");
+        assert_eq!(item.class, ErrorClass::Rejected);
+        assert_eq!(item.status, None);
+
+        let failed = anyhow::Error::new(ApiError::from_response(
+            "place_futures_orders",
+            StatusCode::SERVICE_UNAVAILABLE,
+            "",
+        ))
+        .context("batch");
+        let copy = replicate_error(&failed);
+        assert_eq!(error_class(&copy), ErrorClass::Transient);
+        assert_eq!(
+            error_class(&replicate_error(&anyhow::anyhow!("no response"))),
+            ErrorClass::Unknown
+        );
+    }
+}
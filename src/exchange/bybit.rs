@@ -0,0 +1,1908 @@
+//! Bybit V5 REST API client.
+//!
+//! Trades USDT linear perpetuals and spot on margin through a unified trading
+//! account, so the delta-neutral strategy can run on Bybit as well as Binance.
+//! Bybit responses are translated into the Binance-shaped exchange types here;
+//! the strategies never see Bybit's field names, enums or paging.
+//!
+//! Spot borrowing is implicit in a unified account: a spot order sent with
+//! `isLeverage` borrows what it lacks, and liabilities are repaid automatically
+//! once the account holds the coin again.
+
+use crate::config::BybitConfig;
+use crate::exchange::client::{
+    is_retryable_error, is_retryable_status, with_jitter, BACKOFF_MULTIPLIER, INITIAL_BACKOFF_MS,
+    MAX_RETRIES,
+};
+use crate::exchange::types::*;
+use crate::exchange::{ApiError, ExchangeClient, OrderValve, TradeLane, TradeQueue};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder, Response};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+use tracing::{debug, instrument, warn};
+
+const BASE_URL: &str = "https://api.bybit.com";
+const TESTNET_URL: &str = "https://api-testnet.bybit.com";
+
+/// Milliseconds a signed request stays valid after its timestamp
+const RECV_WINDOW: &str = "5000";
+
+/// "Leverage not modified": the symbol already has the requested leverage
+const LEVERAGE_NOT_MODIFIED: i64 = 110043;
+
+/// Longest span the transaction log can be queried over in one request
+const TRANSACTION_LOG_WINDOW_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Attempts to read back a placed order before reporting it as still open
+const ORDER_QUERY_ATTEMPTS: u32 = 5;
+const ORDER_QUERY_DELAY_MS: u64 = 200;
+
+/// Response envelope of every V5 endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<T> {
+    ret_code: i64,
+    #[serde(default)]
+    ret_msg: String,
+    result: Option<T>,
+}
+
+impl<T> Envelope<T> {
+    fn into_result(self, operation: &str) -> Result<T> {
+        if self.ret_code != 0 {
+            bail!(
+                "{} rejected by Bybit ({}): {}",
+                operation,
+                self.ret_code,
+                self.ret_msg
+            );
+        }
+        self.result
+            .ok_or_else(|| anyhow!("{} returned no result", operation))
+    }
+}
+
+/// One page of a list result.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    list: Vec<T>,
+    #[serde(default)]
+    next_page_cursor: String,
+}
+
+/// Decimal sent as a string; Bybit leaves fields that don't apply empty.
+fn lenient_decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    if text.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+/// Millisecond timestamp sent as a string, or empty.
+fn lenient_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    if text.is_empty() {
+        return Ok(0);
+    }
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+/// Ticker of a linear or spot symbol; fields absent from a category stay zero.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTicker {
+    symbol: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    last_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    prev_price_24h: Decimal,
+    /// Fractional change, e.g. "0.0125" for +1.25%
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    price_24h_pcnt: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    high_price_24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    low_price_24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    volume_24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    turnover_24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    bid1_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    bid1_size: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    ask1_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    ask1_size: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    funding_rate: Decimal,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    next_funding_time: i64,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    mark_price: Decimal,
+}
+
+impl RawTicker {
+    /// Funding of a perpetual; dated futures have no next settlement.
+    fn funding_rate(&self) -> Option<FundingRate> {
+        (self.next_funding_time > 0).then(|| FundingRate {
+            symbol: self.symbol.clone(),
+            funding_rate: self.funding_rate,
+            funding_time: self.next_funding_time,
+            mark_price: Some(self.mark_price).filter(|price| !price.is_zero()),
+        })
+    }
+
+    /// 24h statistics over the day ending at `now` (ms).
+    fn ticker_24h(&self, now: i64) -> Ticker24h {
+        Ticker24h {
+            symbol: self.symbol.clone(),
+            price_change: self.last_price - self.prev_price_24h,
+            price_change_percent: self.price_24h_pcnt * Decimal::ONE_HUNDRED,
+            last_price: self.last_price,
+            high_price: self.high_price_24h,
+            low_price: self.low_price_24h,
+            volume: self.volume_24h,
+            quote_volume: self.turnover_24h,
+            open_time: now - 24 * 60 * 60 * 1000,
+            close_time: now,
+        }
+    }
+
+    fn book_ticker(&self) -> BookTicker {
+        BookTicker {
+            symbol: self.symbol.clone(),
+            bid_price: self.bid1_price,
+            bid_qty: self.bid1_size,
+            ask_price: self.ask1_price,
+            ask_qty: self.ask1_size,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LotSizeFilter {
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    qty_step: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    min_notional_value: Decimal,
+}
+
+/// Linear instrument from `/v5/market/instruments-info`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLinearInstrument {
+    symbol: String,
+    contract_type: String,
+    status: String,
+    base_coin: String,
+    quote_coin: String,
+    /// Minutes between funding settlements
+    #[serde(default)]
+    funding_interval: u32,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    price_scale: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    upper_funding_rate: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    lower_funding_rate: Decimal,
+    lot_size_filter: LotSizeFilter,
+}
+
+impl RawLinearInstrument {
+    fn is_perpetual(&self) -> bool {
+        self.contract_type == "LinearPerpetual"
+    }
+
+    fn symbol_info(&self) -> FuturesSymbolInfo {
+        FuturesSymbolInfo {
+            symbol: self.symbol.clone(),
+            quantity_precision: self.lot_size_filter.qty_step.normalize().scale() as u8,
+            price_precision: self.price_scale.to_u8().unwrap_or_default(),
+            contract_type: if self.is_perpetual() {
+                "PERPETUAL".to_string()
+            } else {
+                self.contract_type.clone()
+            },
+            status: trading_status(&self.status),
+            base_asset: self.base_coin.clone(),
+            quote_asset: self.quote_coin.clone(),
+            filters: (self.lot_size_filter.min_notional_value > Decimal::ZERO)
+                .then_some(SymbolFilter::MinNotional {
+                    notional: self.lot_size_filter.min_notional_value,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn funding_info(&self) -> FundingInfo {
+        let has_cap = !self.upper_funding_rate.is_zero();
+        FundingInfo {
+            symbol: self.symbol.clone(),
+            funding_interval_hours: self.funding_interval / 60,
+            adjusted_funding_rate_cap: has_cap.then_some(self.upper_funding_rate),
+            adjusted_funding_rate_floor: has_cap.then_some(self.lower_funding_rate),
+        }
+    }
+}
+
+/// Spot instrument from `/v5/market/instruments-info`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSpotInstrument {
+    symbol: String,
+    base_coin: String,
+    quote_coin: String,
+    status: String,
+    /// "none", "utaOnly", "normalSpotOnly" or "both"
+    #[serde(default)]
+    margin_trading: String,
+}
+
+impl RawSpotInstrument {
+    fn symbol_info(&self) -> SpotSymbolInfo {
+        SpotSymbolInfo {
+            symbol: self.symbol.clone(),
+            base_asset: self.base_coin.clone(),
+            quote_asset: self.quote_coin.clone(),
+            status: trading_status(&self.status),
+            is_margin_trading_allowed: matches!(self.margin_trading.as_str(), "utaOnly" | "both"),
+            filters: Vec::new(),
+        }
+    }
+}
+
+/// Binance's status name for a tradable symbol, so filters work on both venues.
+fn trading_status(status: &str) -> String {
+    match status {
+        "Trading" => "TRADING".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawFundingRecord {
+    symbol: String,
+    #[serde(deserialize_with = "lenient_decimal")]
+    funding_rate: Decimal,
+    #[serde(deserialize_with = "lenient_millis")]
+    funding_rate_timestamp: i64,
+}
+
+/// Order book from `/v5/market/orderbook`, levels as `[price, size]` strings.
+#[derive(Debug, Deserialize)]
+struct RawOrderBook {
+    /// Bids, best first
+    b: Vec<[String; 2]>,
+    /// Asks, best first
+    a: Vec<[String; 2]>,
+    /// Update ID
+    u: i64,
+}
+
+impl RawOrderBook {
+    fn order_book(&self) -> Result<OrderBook> {
+        let levels = |side: &[[String; 2]]| -> Result<Vec<(Decimal, Decimal)>> {
+            side.iter()
+                .map(|[price, size]| Ok((price.parse()?, size.parse()?)))
+                .collect()
+        };
+        Ok(OrderBook {
+            last_update_id: self.u,
+            bids: levels(&self.b).context("Failed to parse order book bids")?,
+            asks: levels(&self.a).context("Failed to parse order book asks")?,
+        })
+    }
+}
+
+/// Risk limit tier from `/v5/market/risk-limit`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawRiskLimit {
+    symbol: String,
+    #[serde(deserialize_with = "lenient_decimal")]
+    risk_limit_value: Decimal,
+    #[serde(deserialize_with = "lenient_decimal")]
+    maintenance_margin: Decimal,
+    #[serde(deserialize_with = "lenient_decimal")]
+    max_leverage: Decimal,
+}
+
+/// Group risk limit tiers into Binance-style notional brackets per symbol.
+///
+/// Each tier covers notional up to its risk limit, starting where the previous
+/// tier ends; the cumulative maintenance amount is derived the way Binance
+/// publishes it, so the liquidation maths is the same on both venues.
+fn leverage_brackets(tiers: Vec<RawRiskLimit>) -> Vec<LeverageBracket> {
+    let mut by_symbol: Vec<(String, Vec<RawRiskLimit>)> = Vec::new();
+    for tier in tiers {
+        match by_symbol
+            .iter_mut()
+            .find(|(symbol, _)| *symbol == tier.symbol)
+        {
+            Some((_, symbol_tiers)) => symbol_tiers.push(tier),
+            None => by_symbol.push((tier.symbol.clone(), vec![tier])),
+        }
+    }
+
+    by_symbol
+        .into_iter()
+        .map(|(symbol, mut tiers)| {
+            tiers.sort_by_key(|tier| tier.risk_limit_value);
+            let mut brackets = Vec::with_capacity(tiers.len());
+            let mut floor = Decimal::ZERO;
+            let mut previous_rate = Decimal::ZERO;
+            let mut cum = Decimal::ZERO;
+            for (index, tier) in tiers.iter().enumerate() {
+                cum += floor * (tier.maintenance_margin - previous_rate);
+                brackets.push(NotionalBracket {
+                    bracket: (index + 1) as u8,
+                    initial_leverage: tier.max_leverage.to_u8().unwrap_or(u8::MAX),
+                    notional_cap: tier.risk_limit_value,
+                    notional_floor: floor,
+                    maint_margin_ratio: tier.maintenance_margin,
+                    cum,
+                });
+                floor = tier.risk_limit_value;
+                previous_rate = tier.maintenance_margin;
+            }
+            LeverageBracket { symbol, brackets }
+        })
+        .collect()
+}
+
+/// Coin of the unified account from `/v5/account/collateral-info`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCollateral {
+    currency: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    hourly_borrow_rate: Decimal,
+    #[serde(default)]
+    borrowable: bool,
+    #[serde(default)]
+    margin_collateral: bool,
+}
+
+impl RawCollateral {
+    fn margin_asset(&self) -> MarginAsset {
+        MarginAsset {
+            asset: self.currency.clone(),
+            borrowable: self.borrowable,
+            collateral: self.margin_collateral,
+            margin_interest_rate: Some(self.hourly_borrow_rate * Decimal::from(24)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderAck {
+    order_id: String,
+}
+
+/// Order from `/v5/order/realtime` or `/v5/order/history`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOrder {
+    order_id: String,
+    #[serde(default)]
+    order_link_id: String,
+    symbol: String,
+    side: String,
+    order_type: String,
+    order_status: String,
+    #[serde(default)]
+    time_in_force: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avg_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    qty: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    cum_exec_qty: Decimal,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    created_time: i64,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    updated_time: i64,
+}
+
+impl RawOrder {
+    fn status(&self) -> OrderStatus {
+        match self.order_status.as_str() {
+            "PartiallyFilled" => OrderStatus::PartiallyFilled,
+            "Filled" => OrderStatus::Filled,
+            "Cancelled" | "PartiallyFilledCanceled" => OrderStatus::Canceled,
+            "Rejected" => OrderStatus::Rejected,
+            "Deactivated" => OrderStatus::Expired,
+            // "New", "Untriggered", "Triggered"
+            _ => OrderStatus::New,
+        }
+    }
+
+    /// Whether the order has stopped trading.
+    fn is_settled(&self) -> bool {
+        !matches!(
+            self.status(),
+            OrderStatus::New | OrderStatus::PartiallyFilled
+        )
+    }
+
+    fn side(&self) -> OrderSide {
+        if self.side == "Sell" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        }
+    }
+
+    fn order_response(&self) -> OrderResponse {
+        OrderResponse {
+            order_id: numeric_id(&self.order_id),
+            symbol: self.symbol.clone(),
+            status: self.status(),
+            client_order_id: self.order_link_id.clone(),
+            price: self.price,
+            avg_price: self.avg_price,
+            orig_qty: self.qty,
+            executed_qty: self.cum_exec_qty,
+            side: self.side(),
+            order_type: if self.order_type == "Limit" {
+                OrderType::Limit
+            } else {
+                OrderType::Market
+            },
+            time_in_force: match self.time_in_force.as_str() {
+                "GTC" => Some(TimeInForce::Gtc),
+                "IOC" => Some(TimeInForce::Ioc),
+                "FOK" => Some(TimeInForce::Fok),
+                "PostOnly" => Some(TimeInForce::Gtx),
+                _ => None,
+            },
+            update_time: self.updated_time,
+        }
+    }
+
+    fn order_lookup(&self) -> OrderLookup {
+        OrderLookup {
+            order_id: numeric_id(&self.order_id),
+            symbol: self.symbol.clone(),
+            status: self.status(),
+            client_order_id: self.order_link_id.clone(),
+            executed_qty: self.cum_exec_qty,
+            avg_price: Some(self.avg_price),
+            cummulative_quote_qty: None,
+            side: self.side(),
+            time: self.created_time,
+        }
+    }
+}
+
+/// Numeric form of a Bybit ID: the ID itself when numeric, else a stable hash.
+fn numeric_id(id: &str) -> i64 {
+    if let Ok(numeric) = id.parse() {
+        return numeric;
+    }
+    // FNV-1a, so the same ID always maps to the same number
+    let hash = id.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    (hash >> 1) as i64
+}
+
+/// Linear position from `/v5/position/list`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPosition {
+    symbol: String,
+    /// "Buy", "Sell", or empty when flat
+    #[serde(default)]
+    side: String,
+    #[serde(deserialize_with = "lenient_decimal")]
+    size: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avg_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    mark_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    unrealised_pnl: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    liq_price: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    leverage: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    position_value: Decimal,
+    #[serde(default, rename = "positionIM", deserialize_with = "lenient_decimal")]
+    position_im: Decimal,
+    /// 0 = cross, 1 = isolated
+    #[serde(default)]
+    trade_mode: u8,
+}
+
+impl RawPosition {
+    fn position(&self) -> Position {
+        let sign = if self.side == "Sell" {
+            -Decimal::ONE
+        } else {
+            Decimal::ONE
+        };
+        let margin_type = if self.trade_mode == 1 {
+            MarginType::Isolated
+        } else {
+            MarginType::Cross
+        };
+        Position {
+            symbol: self.symbol.clone(),
+            position_amt: self.size * sign,
+            entry_price: self.avg_price,
+            mark_price: self.mark_price,
+            unrealized_profit: self.unrealised_pnl,
+            liquidation_price: self.liq_price,
+            leverage: self.leverage.to_u8().unwrap_or_default(),
+            position_side: PositionSide::Both,
+            notional: self.position_value * sign,
+            isolated_margin: if margin_type == MarginType::Isolated {
+                self.position_im
+            } else {
+                Decimal::ZERO
+            },
+            margin_type,
+        }
+    }
+}
+
+/// Unified account from `/v5/account/wallet-balance`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawWallet {
+    /// Margin available for new positions, in USD
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    total_available_balance: Decimal,
+    #[serde(default)]
+    coin: Vec<RawCoinBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCoinBalance {
+    coin: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    wallet_balance: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    equity: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    unrealised_pnl: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    available_to_withdraw: Decimal,
+}
+
+impl RawWallet {
+    /// Per-coin balances; USDT falls back to the account's available margin
+    /// where Bybit no longer reports a withdrawable amount.
+    fn balances(&self) -> Vec<AccountBalance> {
+        self.coin
+            .iter()
+            .map(|coin| AccountBalance {
+                asset: coin.coin.clone(),
+                wallet_balance: coin.wallet_balance,
+                unrealized_profit: coin.unrealised_pnl,
+                margin_balance: coin.equity,
+                available_balance: if coin.available_to_withdraw.is_zero() && coin.coin == "USDT" {
+                    self.total_available_balance
+                } else {
+                    coin.available_to_withdraw
+                },
+            })
+            .collect()
+    }
+}
+
+/// Entry of `/v5/account/transaction-log`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTransaction {
+    id: String,
+    #[serde(default)]
+    symbol: String,
+    #[serde(default)]
+    currency: String,
+    /// Cash balance change (positive = received)
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    change: Decimal,
+    /// Trading fee (positive = paid)
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    fee: Decimal,
+    #[serde(deserialize_with = "lenient_millis")]
+    transaction_time: i64,
+}
+
+/// Transaction log entries holding a Binance income type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IncomeSource {
+    /// Funding settlements ("FUNDING_FEE")
+    Settlement,
+    /// Trade fees ("COMMISSION")
+    Trade,
+}
+
+impl IncomeSource {
+    fn for_income_type(income_type: &str) -> Result<Self> {
+        match income_type {
+            "FUNDING_FEE" => Ok(IncomeSource::Settlement),
+            "COMMISSION" => Ok(IncomeSource::Trade),
+            other => bail!(
+                "Income type {} has no Bybit transaction log equivalent",
+                other
+            ),
+        }
+    }
+
+    /// Transaction log `type` filter.
+    fn log_type(&self) -> &'static str {
+        match self {
+            IncomeSource::Settlement => "SETTLEMENT",
+            IncomeSource::Trade => "TRADE",
+        }
+    }
+
+    /// Income of `tx`, positive when received.
+    fn income(&self, tx: &RawTransaction) -> Decimal {
+        match self {
+            IncomeSource::Settlement => tx.change,
+            IncomeSource::Trade => -tx.fee,
+        }
+    }
+}
+
+/// Bybit API key and secret.
+#[derive(Clone)]
+struct Credentials {
+    api_key: String,
+    secret_key: String,
+}
+
+impl Credentials {
+    /// HMAC-SHA256 over timestamp, key, receive window and the query or body.
+    fn sign(&self, timestamp: u64, payload: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(self.api_key.as_bytes());
+        mac.update(RECV_WINDOW.as_bytes());
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Bybit client for linear perpetuals and spot margin of a unified account.
+pub struct BybitClient {
+    http: Client,
+    credentials: Credentials,
+    base_url: String,
+    /// Order rate and notional velocity limits
+    order_valve: OrderValve,
+    /// Serializes account-mutating calls
+    trade_queue: TradeQueue,
+}
+
+impl BybitClient {
+    /// Create a new Bybit client from configuration.
+    pub fn new(config: &BybitConfig) -> Result<Self> {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        let base_url = if config.testnet {
+            TESTNET_URL
+        } else {
+            BASE_URL
+        };
+
+        Ok(Self {
+            http,
+            credentials: Credentials {
+                api_key: config.api_key.clone(),
+                secret_key: config.secret_key.clone(),
+            },
+            base_url: base_url.to_string(),
+            order_valve: OrderValve::default(),
+            trade_queue: TradeQueue::new(),
+        })
+    }
+
+    /// Guard order placement with `valve`.
+    pub fn set_order_valve(&mut self, valve: OrderValve) {
+        self.order_valve = valve;
+    }
+
+    /// Get current timestamp in milliseconds.
+    fn timestamp() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+
+    /// Execute an HTTP request with retry and jittered exponential backoff, on the same
+    /// terms as the Binance client.
+    async fn send<F>(&self, operation: &str, request_fn: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut backoff_ms = INITIAL_BACKOFF_MS;
+        for attempt in 1..=MAX_RETRIES {
+            match request_fn().send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if is_retryable_status(status) && attempt < MAX_RETRIES {
+                        warn!(
+                            %operation,
+                            attempt,
+                            status = %status,
+                            backoff_ms,
+                            "Retryable HTTP status, backing off"
+                        );
+                    } else if status.is_success() {
+                        return Ok(response);
+                    } else {
+                        let error_text = response.text().await.unwrap_or_default();
+                        return Err(ApiError::from_response(operation, status, &error_text).into());
+                    }
+                }
+                Err(e) if is_retryable_error(&e) && attempt < MAX_RETRIES => {
+                    warn!(
+                        %operation,
+                        attempt,
+                        error = %e,
+                        backoff_ms,
+                        "Retryable network error, backing off"
+                    );
+                }
+                Err(e) => return Err(ApiError::from_network(operation, &e).into()),
+            }
+            sleep(with_jitter(backoff_ms)).await;
+            backoff_ms *= BACKOFF_MULTIPLIER;
+        }
+        Err(anyhow!(
+            "{} failed after {} retries",
+            operation,
+            MAX_RETRIES
+        ))
+    }
+
+    /// GET an endpoint, signing the query when `signed`.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        query: &str,
+        signed: bool,
+    ) -> Result<Envelope<T>> {
+        let url = format!("{}{}?{}", self.base_url, path, query);
+        let timestamp = Self::timestamp();
+        let signature = signed.then(|| self.credentials.sign(timestamp, query));
+
+        let response = self
+            .send(operation, || {
+                let request = self.http.get(&url);
+                match &signature {
+                    Some(signature) => self.authenticate(request, timestamp, signature),
+                    None => request,
+                }
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", operation))
+    }
+
+    /// POST a signed JSON body.
+    async fn post<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<Envelope<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let body = body.to_string();
+        let timestamp = Self::timestamp();
+        let signature = self.credentials.sign(timestamp, &body);
+
+        let response = self
+            .send(operation, || {
+                let request = self
+                    .http
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .body(body.clone());
+                self.authenticate(request, timestamp, &signature)
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", operation))
+    }
+
+    fn authenticate(
+        &self,
+        request: RequestBuilder,
+        timestamp: u64,
+        signature: &str,
+    ) -> RequestBuilder {
+        request
+            .header("X-BAPI-API-KEY", &self.credentials.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("X-BAPI-SIGN", signature)
+    }
+
+    /// Every page of a cursor-paged list.
+    async fn get_all<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        query: &str,
+        signed: bool,
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let page_query = if cursor.is_empty() {
+                query.to_string()
+            } else {
+                format!("{}&cursor={}", query, urlencoding::encode(&cursor))
+            };
+            let page: Page<T> = self
+                .get(operation, path, &page_query, signed)
+                .await?
+                .into_result(operation)?;
+            items.extend(page.list);
+            if page.next_page_cursor.is_empty() || page.next_page_cursor == cursor {
+                return Ok(items);
+            }
+            cursor = page.next_page_cursor;
+        }
+    }
+
+    async fn get_tickers(&self, category: &str) -> Result<Vec<RawTicker>> {
+        let query = format!("category={}", category);
+        let page: Page<RawTicker> = self
+            .get("get_tickers", "/v5/market/tickers", &query, false)
+            .await?
+            .into_result("get_tickers")?;
+        Ok(page.list)
+    }
+
+    async fn get_linear_instruments(&self) -> Result<Vec<RawLinearInstrument>> {
+        self.get_all(
+            "get_linear_instruments",
+            "/v5/market/instruments-info",
+            "category=linear&limit=1000",
+            false,
+        )
+        .await
+    }
+
+    // ==================== Market Data (Public) ====================
+
+    /// Get funding rates for all linear perpetuals.
+    #[instrument(skip(self))]
+    pub async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        let tickers = self.get_tickers("linear").await?;
+        Ok(tickers.iter().filter_map(RawTicker::funding_rate).collect())
+    }
+
+    /// Get funding intervals and caps of all linear perpetuals.
+    ///
+    /// Bybit publishes these for every symbol, not only those off the default.
+    #[instrument(skip(self))]
+    pub async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        let instruments = self.get_linear_instruments().await?;
+        Ok(instruments
+            .iter()
+            .filter(|instrument| instrument.is_perpetual() && instrument.funding_interval > 0)
+            .map(RawLinearInstrument::funding_info)
+            .collect())
+    }
+
+    /// Get settled funding rates of `symbol` between `start_time` and `end_time` (ms),
+    /// oldest first.
+    #[instrument(skip(self))]
+    pub async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        const PAGE_LIMIT: usize = 200;
+        let mut records = Vec::new();
+        let mut page_end = end_time;
+
+        // Pages run newest first; walk back until the start is reached
+        loop {
+            let query = format!(
+                "category=linear&symbol={}&startTime={}&endTime={}&limit={}",
+                symbol, start_time, page_end, PAGE_LIMIT
+            );
+            let page: Page<RawFundingRecord> = self
+                .get(
+                    "get_funding_rate_history",
+                    "/v5/market/funding/history",
+                    &query,
+                    false,
+                )
+                .await?
+                .into_result("get_funding_rate_history")?;
+
+            let page_len = page.list.len();
+            let oldest = page.list.iter().map(|r| r.funding_rate_timestamp).min();
+            records.extend(page.list.into_iter().map(|record| FundingRateRecord {
+                symbol: record.symbol,
+                funding_rate: record.funding_rate,
+                funding_time: record.funding_rate_timestamp,
+            }));
+
+            match oldest {
+                Some(time) if page_len == PAGE_LIMIT && time > start_time => page_end = time - 1,
+                _ => break,
+            }
+        }
+
+        records.sort_by_key(|record| record.funding_time);
+        Ok(records)
+    }
+
+    /// Get 24h ticker statistics for all linear perpetuals.
+    #[instrument(skip(self))]
+    pub async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let now = Self::timestamp() as i64;
+        let tickers = self.get_tickers("linear").await?;
+        Ok(tickers.iter().map(|t| t.ticker_24h(now)).collect())
+    }
+
+    /// Get 24h ticker statistics for all spot symbols.
+    #[instrument(skip(self))]
+    pub async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let now = Self::timestamp() as i64;
+        let tickers = self.get_tickers("spot").await?;
+        Ok(tickers.iter().map(|t| t.ticker_24h(now)).collect())
+    }
+
+    /// Get 24h ticker statistics for the given spot symbols.
+    ///
+    /// Bybit returns every spot ticker in one response, so this filters it.
+    #[instrument(skip(self, symbols), fields(count = symbols.len()))]
+    pub async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        let tickers = self.get_spot_24h_tickers().await?;
+        Ok(tickers
+            .into_iter()
+            .filter(|ticker| symbols.contains(&ticker.symbol))
+            .collect())
+    }
+
+    /// Get best bid and ask of all linear perpetuals.
+    #[instrument(skip(self))]
+    pub async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        let tickers = self.get_tickers("linear").await?;
+        Ok(tickers.iter().map(RawTicker::book_ticker).collect())
+    }
+
+    /// Get precision and status of all linear instruments.
+    #[instrument(skip(self))]
+    pub async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        let instruments = self.get_linear_instruments().await?;
+        Ok(FuturesExchangeInfo {
+            symbols: instruments
+                .iter()
+                .map(RawLinearInstrument::symbol_info)
+                .collect(),
+        })
+    }
+
+    /// Get risk limit tiers of all linear perpetuals as notional brackets.
+    #[instrument(skip(self))]
+    pub async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        let tiers = self
+            .get_all(
+                "get_leverage_brackets",
+                "/v5/market/risk-limit",
+                "category=linear",
+                false,
+            )
+            .await?;
+        Ok(leverage_brackets(tiers))
+    }
+
+    /// Get spot symbols and whether they trade on margin.
+    #[instrument(skip(self))]
+    pub async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        let instruments: Vec<RawSpotInstrument> = self
+            .get_all(
+                "get_spot_exchange_info",
+                "/v5/market/instruments-info",
+                "category=spot&limit=1000",
+                false,
+            )
+            .await?;
+        Ok(instruments
+            .iter()
+            .map(RawSpotInstrument::symbol_info)
+            .collect())
+    }
+
+    /// Get spot order book depth for a symbol.
+    #[instrument(skip(self))]
+    pub async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        let query = format!("category=spot&symbol={}&limit={}", symbol, limit);
+        let book: RawOrderBook = self
+            .get("get_spot_depth", "/v5/market/orderbook", &query, false)
+            .await?
+            .into_result("get_spot_depth")?;
+        book.order_book()
+    }
+
+    // ==================== Account (Authenticated) ====================
+
+    /// Get coins of the unified account, whether they can be borrowed and
+    /// their daily borrow rate.
+    #[instrument(skip(self))]
+    pub async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        let page: Page<RawCollateral> = self
+            .get(
+                "get_margin_all_assets",
+                "/v5/account/collateral-info",
+                "",
+                true,
+            )
+            .await?
+            .into_result("get_margin_all_assets")?;
+        Ok(page.list.iter().map(RawCollateral::margin_asset).collect())
+    }
+
+    /// Get balances of the unified account.
+    #[instrument(skip(self))]
+    pub async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        let page: Page<RawWallet> = self
+            .get(
+                "get_account_balance",
+                "/v5/account/wallet-balance",
+                "accountType=UNIFIED",
+                true,
+            )
+            .await?
+            .into_result("get_account_balance")?;
+        Ok(page.list.iter().flat_map(RawWallet::balances).collect())
+    }
+
+    /// Get open USDT linear positions.
+    #[instrument(skip(self))]
+    pub async fn get_positions(&self) -> Result<Vec<Position>> {
+        let positions: Vec<RawPosition> = self
+            .get_all(
+                "get_positions",
+                "/v5/position/list",
+                "category=linear&settleCoin=USDT&limit=200",
+                true,
+            )
+            .await?;
+        Ok(positions
+            .iter()
+            .filter(|position| !position.size.is_zero())
+            .map(RawPosition::position)
+            .collect())
+    }
+
+    /// Get linear income of a Binance income type since `start_time` (ms).
+    ///
+    /// Funding fees come from settlement entries of the transaction log and
+    /// commissions from trade entries, queried a week at a time.
+    #[instrument(skip(self))]
+    pub async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        let source = IncomeSource::for_income_type(income_type)?;
+        let now = Self::timestamp() as i64;
+        let mut records = Vec::new();
+        let mut window_start = start_time;
+
+        while window_start < now {
+            let window_end = (window_start + TRANSACTION_LOG_WINDOW_MS).min(now);
+            let query = format!(
+                "accountType=UNIFIED&category=linear&type={}&startTime={}&endTime={}&limit=50",
+                source.log_type(),
+                window_start,
+                window_end
+            );
+            let transactions: Vec<RawTransaction> = self
+                .get_all(
+                    "get_income_history",
+                    "/v5/account/transaction-log",
+                    &query,
+                    true,
+                )
+                .await?;
+            records.extend(transactions.iter().map(|tx| IncomeRecord {
+                symbol: tx.symbol.clone(),
+                income_type: income_type.to_string(),
+                income: source.income(tx),
+                asset: tx.currency.clone(),
+                time: tx.transaction_time,
+                tran_id: numeric_id(&tx.id),
+            }));
+            window_start = window_end + 1;
+        }
+
+        records.sort_by_key(|record| record.time);
+        Ok(records)
+    }
+
+    // ==================== Orders (Authenticated) ====================
+
+    /// Place a linear perpetual order.
+    #[instrument(skip(self))]
+    pub async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        let lane = if order.reduce_only == Some(true) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let mut body = order_body(
+            "linear",
+            &order.symbol,
+            order.side,
+            order.order_type,
+            order.quantity,
+            order.price,
+            order.time_in_force,
+            order.new_client_order_id.as_deref(),
+        )?;
+        if let Some(reduce_only) = order.reduce_only {
+            body["reduceOnly"] = reduce_only.into();
+        }
+
+        debug!("Placing futures order: {:?}", order);
+        self.place_order("place_futures_order", "linear", lane, &body)
+            .await
+    }
+
+    /// Place a spot order that borrows and repays through the unified account.
+    #[instrument(skip(self))]
+    pub async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        let lane = if order.side_effect_type == Some(SideEffectType::AutoRepay) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let mut body = order_body(
+            "spot",
+            &order.symbol,
+            order.side,
+            order.order_type,
+            order.quantity,
+            order.price,
+            order.time_in_force,
+            order.new_client_order_id.as_deref(),
+        )?;
+        let uses_margin = !matches!(
+            order.side_effect_type,
+            None | Some(SideEffectType::NoSideEffect)
+        );
+        body["isLeverage"] = u8::from(uses_margin).into();
+        if order.order_type == OrderType::Market {
+            // Market buys are sized in the quote coin unless told otherwise
+            body["marketUnit"] = "baseCoin".into();
+        }
+
+        debug!("Placing margin order: {:?}", order);
+        self.place_order("place_margin_order", "spot", lane, &body)
+            .await
+    }
+
+    /// Send an order and read back its state.
+    ///
+    /// Bybit only acknowledges the order ID on creation, so fills are read from
+    /// the order itself, briefly waiting for a market or IOC order to settle.
+    async fn place_order(
+        &self,
+        operation: &str,
+        category: &str,
+        lane: TradeLane,
+        body: &serde_json::Value,
+    ) -> Result<OrderResponse> {
+        let _turn = self.trade_queue.turn(lane).await;
+        self.order_valve.admit(chrono::Utc::now())?;
+
+        let ack: OrderAck = self
+            .post(operation, "/v5/order/create", body)
+            .await?
+            .into_result(operation)?;
+        let symbol = body["symbol"].as_str().unwrap_or_default();
+        let resting = matches!(body["timeInForce"].as_str(), Some("GTC" | "PostOnly"));
+
+        let mut last_seen = None;
+        for attempt in 1..=ORDER_QUERY_ATTEMPTS {
+            let query = format!(
+                "category={}&symbol={}&orderId={}",
+                category, symbol, ack.order_id
+            );
+            let page: Page<RawOrder> = self
+                .get("get_order", "/v5/order/realtime", &query, true)
+                .await?
+                .into_result("get_order")?;
+            if let Some(order) = page.list.into_iter().next() {
+                if order.is_settled() || resting {
+                    last_seen = Some(order);
+                    break;
+                }
+                last_seen = Some(order);
+            }
+            if attempt < ORDER_QUERY_ATTEMPTS {
+                sleep(Duration::from_millis(ORDER_QUERY_DELAY_MS)).await;
+            }
+        }
+
+        let response = match last_seen {
+            Some(order) => order.order_response(),
+            None => {
+                warn!(%symbol, order_id = %ack.order_id, "Placed order not yet visible, reporting it as new");
+                unseen_order_response(&ack.order_id, body)
+            }
+        };
+        self.order_valve.record_fill(chrono::Utc::now(), &response);
+        Ok(response)
+    }
+
+    /// Look up an order of `category` by client order ID, open or closed.
+    async fn get_order_by_client_id(
+        &self,
+        category: &str,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let query = format!(
+            "category={}&symbol={}&orderLinkId={}",
+            category,
+            symbol,
+            urlencoding::encode(client_order_id)
+        );
+        for path in ["/v5/order/realtime", "/v5/order/history"] {
+            let page: Page<RawOrder> = self
+                .get("get_order_by_client_id", path, &query, true)
+                .await?
+                .into_result("get_order_by_client_id")?;
+            if let Some(order) = page.list.first() {
+                return Ok(Some(order.order_lookup()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up a linear order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.get_order_by_client_id("linear", symbol, client_order_id)
+            .await
+    }
+
+    /// Look up a spot order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.get_order_by_client_id("spot", symbol, client_order_id)
+            .await
+    }
+
+    /// Open orders of `category` across symbols. Linear orders are listed by
+    /// settle coin, so only USDT perpetuals are covered.
+    async fn get_open_orders(&self, category: &str) -> Result<Vec<RawOrder>> {
+        let query = if category == "linear" {
+            "category=linear&settleCoin=USDT".to_string()
+        } else {
+            format!("category={}", category)
+        };
+        self.get_all("get_open_orders", "/v5/order/realtime", &query, true)
+            .await
+    }
+
+    /// Cancel an open order of `category` and read back its state.
+    ///
+    /// Bybit order IDs are UUIDs reported as a hash, so the order is first found
+    /// among the symbol's open orders to recover the ID to cancel.
+    async fn cancel_order(&self, category: &str, symbol: &str, order_id: i64) -> Result<RawOrder> {
+        let _turn = self.trade_queue.turn(TradeLane::Close).await;
+        let query = format!("category={}&symbol={}", category, symbol);
+        let open: Vec<RawOrder> = self
+            .get_all("cancel_order", "/v5/order/realtime", &query, true)
+            .await?;
+        let Some(order) = open
+            .into_iter()
+            .find(|order| numeric_id(&order.order_id) == order_id)
+        else {
+            bail!("{} order {} is not open on Bybit", symbol, order_id);
+        };
+
+        let body = serde_json::json!({
+            "category": category,
+            "symbol": symbol,
+            "orderId": order.order_id,
+        });
+        self.post::<OrderAck>("cancel_order", "/v5/order/cancel", &body)
+            .await?
+            .into_result("cancel_order")?;
+
+        let query = format!(
+            "category={}&symbol={}&orderId={}",
+            category, symbol, order.order_id
+        );
+        for path in ["/v5/order/realtime", "/v5/order/history"] {
+            let page: Page<RawOrder> = self
+                .get("get_order", path, &query, true)
+                .await?
+                .into_result("get_order")?;
+            if let Some(cancelled) = page.list.into_iter().next() {
+                return Ok(cancelled);
+            }
+        }
+        // Acknowledged but not readable yet
+        Ok(RawOrder {
+            order_status: "Cancelled".to_string(),
+            ..order
+        })
+    }
+
+    /// Linear orders still open, across USDT perpetuals.
+    #[instrument(skip(self))]
+    pub async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        let orders = self.get_open_orders("linear").await?;
+        Ok(orders.iter().map(RawOrder::order_lookup).collect())
+    }
+
+    /// Cancel an open linear order.
+    #[instrument(skip(self))]
+    pub async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        let order = self.cancel_order("linear", symbol, order_id).await?;
+        Ok(order.order_response())
+    }
+
+    /// Spot orders still open, across symbols.
+    #[instrument(skip(self))]
+    pub async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        let orders = self.get_open_orders("spot").await?;
+        Ok(orders.iter().map(RawOrder::order_lookup).collect())
+    }
+
+    /// Cancel an open spot order.
+    #[instrument(skip(self))]
+    pub async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        let order = self.cancel_order("spot", symbol, order_id).await?;
+        Ok(order.order_lookup())
+    }
+
+    /// Set leverage of a linear perpetual for both sides.
+    #[instrument(skip(self))]
+    pub async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let body = serde_json::json!({
+            "category": "linear",
+            "symbol": symbol,
+            "buyLeverage": leverage.to_string(),
+            "sellLeverage": leverage.to_string(),
+        });
+        let envelope: Envelope<serde_json::Value> = self
+            .post("set_leverage", "/v5/position/set-leverage", &body)
+            .await?;
+        if envelope.ret_code == LEVERAGE_NOT_MODIFIED {
+            return Ok(());
+        }
+        envelope.into_result("set_leverage").map(|_| ())
+    }
+
+    /// Set the margin mode of the unified account.
+    ///
+    /// A unified account has one margin mode for all symbols, so this changes
+    /// every position, not only `symbol`'s.
+    #[instrument(skip(self))]
+    pub async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let mode = match margin_type {
+            MarginType::Cross => "REGULAR_MARGIN",
+            MarginType::Isolated => "ISOLATED_MARGIN",
+        };
+        let body = serde_json::json!({ "setMarginMode": mode });
+        self.post::<serde_json::Value>("set_margin_type", "/v5/account/set-margin-mode", &body)
+            .await?
+            .into_result("set_margin_type")
+            .map(|_| ())
+    }
+}
+
+/// Create-order body shared by linear and spot orders.
+#[allow(clippy::too_many_arguments)]
+fn order_body(
+    category: &str,
+    symbol: &str,
+    side: OrderSide,
+    order_type: OrderType,
+    quantity: Option<Decimal>,
+    price: Option<Decimal>,
+    time_in_force: Option<TimeInForce>,
+    client_order_id: Option<&str>,
+) -> Result<serde_json::Value> {
+    let order_type = match order_type {
+        OrderType::Market => "Market",
+        OrderType::Limit => "Limit",
+        other => bail!("{:?} orders are not supported on Bybit", other),
+    };
+    let mut body = serde_json::json!({
+        "category": category,
+        "symbol": symbol,
+        "side": format!("{:?}", side),
+        "orderType": order_type,
+    });
+    if let Some(qty) = quantity {
+        body["qty"] = qty.to_string().into();
+    }
+    if let Some(price) = price {
+        body["price"] = price.to_string().into();
+    }
+    if let Some(tif) = time_in_force {
+        body["timeInForce"] = match tif {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Fok => "FOK",
+            TimeInForce::Gtx => "PostOnly",
+        }
+        .into();
+    }
+    if let Some(client_id) = client_order_id {
+        body["orderLinkId"] = client_id.into();
+    }
+    Ok(body)
+}
+
+/// Response for an accepted order whose state could not be read back yet.
+fn unseen_order_response(order_id: &str, body: &serde_json::Value) -> OrderResponse {
+    let decimal = |field: &str| {
+        body[field]
+            .as_str()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    };
+    OrderResponse {
+        order_id: numeric_id(order_id),
+        symbol: body["symbol"].as_str().unwrap_or_default().to_string(),
+        status: OrderStatus::New,
+        client_order_id: body["orderLinkId"].as_str().unwrap_or_default().to_string(),
+        price: decimal("price"),
+        avg_price: Decimal::ZERO,
+        orig_qty: decimal("qty"),
+        executed_qty: Decimal::ZERO,
+        side: if body["side"] == "Sell" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        },
+        order_type: if body["orderType"] == "Limit" {
+            OrderType::Limit
+        } else {
+            OrderType::Market
+        },
+        time_in_force: None,
+        update_time: BybitClient::timestamp() as i64,
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BybitClient {
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        BybitClient::get_funding_rates(self).await
+    }
+
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        BybitClient::get_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        BybitClient::get_spot_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        BybitClient::get_spot_24h_tickers_for(self, symbols).await
+    }
+
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        BybitClient::get_book_tickers(self).await
+    }
+
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        BybitClient::get_spot_exchange_info(self).await
+    }
+
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        BybitClient::get_margin_all_assets(self).await
+    }
+
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        BybitClient::get_funding_info(self).await
+    }
+
+    async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        BybitClient::get_funding_rate_history(self, symbol, start_time, end_time).await
+    }
+
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        BybitClient::get_futures_exchange_info(self).await
+    }
+
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        BybitClient::get_leverage_brackets(self).await
+    }
+
+    async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        BybitClient::get_spot_depth(self, symbol, limit).await
+    }
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        BybitClient::place_futures_order(self, order).await
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        BybitClient::place_margin_order(self, order).await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        BybitClient::set_leverage(self, symbol, leverage).await
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        BybitClient::set_margin_type(self, symbol, margin_type).await
+    }
+
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        BybitClient::get_futures_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        BybitClient::get_margin_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        BybitClient::get_open_futures_orders(self).await
+    }
+
+    async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        BybitClient::cancel_futures_order(self, symbol, order_id).await
+    }
+
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        BybitClient::get_open_margin_orders(self).await
+    }
+
+    async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        BybitClient::cancel_margin_order(self, symbol, order_id).await
+    }
+
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        BybitClient::get_positions(self).await
+    }
+
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        BybitClient::get_account_balance(self).await
+    }
+
+    async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        BybitClient::get_income_history(self, income_type, start_time).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_linear_tickers() {
+        let json = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {
+                "category": "linear",
+                "list": [
+                    {
+                        "symbol": "BTCUSDT",
+                        "lastPrice": "50500",
+                        "prevPrice24h": "50000",
+                        "price24hPcnt": "0.01",
+                        "highPrice24h": "51000",
+                        "lowPrice24h": "49500",
+                        "volume24h": "1200",
+                        "turnover24h": "60600000",
+                        "bid1Price": "50499.9",
+                        "bid1Size": "2.5",
+                        "ask1Price": "50500.1",
+                        "ask1Size": "1.5",
+                        "fundingRate": "0.0001",
+                        "nextFundingTime": "1700006400000",
+                        "markPrice": "50501"
+                    },
+                    {
+                        "symbol": "BTC-27DEC24",
+                        "lastPrice": "51000",
+                        "fundingRate": "",
+                        "nextFundingTime": "0",
+                        "markPrice": "51000"
+                    }
+                ]
+            }
+        }"#;
+        let envelope: Envelope<Page<RawTicker>> = serde_json::from_str(json).unwrap();
+        let tickers = envelope.into_result("get_tickers").unwrap().list;
+
+        // Dated futures have no funding
+        let funding: Vec<FundingRate> =
+            tickers.iter().filter_map(RawTicker::funding_rate).collect();
+        assert_eq!(funding.len(), 1);
+        assert_eq!(funding[0].funding_rate, dec!(0.0001));
+        assert_eq!(funding[0].funding_time, 1_700_006_400_000);
+        assert_eq!(funding[0].mark_price, Some(dec!(50501)));
+
+        let ticker = tickers[0].ticker_24h(1_700_000_000_000);
+        assert_eq!(ticker.price_change, dec!(500));
+        assert_eq!(ticker.price_change_percent, dec!(1));
+        assert_eq!(ticker.quote_volume, dec!(60600000));
+
+        let book = tickers[0].book_ticker();
+        assert_eq!(book.bid_price, dec!(50499.9));
+        assert_eq!(book.ask_qty, dec!(1.5));
+    }
+
+    #[test]
+    fn test_rejected_request_is_an_error() {
+        let json = r#"{"retCode": 10003, "retMsg": "API key is invalid.", "result": {}}"#;
+        let envelope: Envelope<serde_json::Value> = serde_json::from_str(json).unwrap();
+        let err = envelope.into_result("get_positions").unwrap_err();
+        assert!(err.to_string().contains("API key is invalid"));
+    }
+
+    #[test]
+    fn test_instruments_map_to_binance_shapes() {
+        let linear: RawLinearInstrument = serde_json::from_str(
+            r#"{
+                "symbol": "SOLUSDT",
+                "contractType": "LinearPerpetual",
+                "status": "Trading",
+                "baseCoin": "SOL",
+                "quoteCoin": "USDT",
+                "fundingInterval": 240,
+                "priceScale": "3",
+                "upperFundingRate": "0.02",
+                "lowerFundingRate": "-0.02",
+                "lotSizeFilter": {"qtyStep": "0.10", "minOrderQty": "0.1", "minNotionalValue": "5"}
+            }"#,
+        )
+        .unwrap();
+        let info = linear.symbol_info();
+        assert_eq!(info.contract_type, "PERPETUAL");
+        assert_eq!(info.status, "TRADING");
+        assert_eq!(info.quantity_precision, 1);
+        assert_eq!(info.price_precision, 3);
+        assert_eq!(info.min_notional(), Some(dec!(5)));
+
+        let funding = linear.funding_info();
+        assert_eq!(funding.funding_interval_hours, 4);
+        assert_eq!(
+            funding.cap(),
+            Some(FundingCap {
+                cap: dec!(0.02),
+                floor: dec!(-0.02),
+            })
+        );
+
+        let spot: RawSpotInstrument = serde_json::from_str(
+            r#"{"symbol": "SOLUSDT", "baseCoin": "SOL", "quoteCoin": "USDT",
+                "status": "Trading", "marginTrading": "utaOnly"}"#,
+        )
+        .unwrap();
+        assert!(spot.symbol_info().is_margin_trading_allowed);
+    }
+
+    #[test]
+    fn test_risk_limits_become_notional_brackets() {
+        let tiers: Vec<RawRiskLimit> = serde_json::from_str(
+            r#"[
+                {"symbol": "BTCUSDT", "riskLimitValue": "4000000", "maintenanceMargin": "0.01", "maxLeverage": "50.00"},
+                {"symbol": "BTCUSDT", "riskLimitValue": "2000000", "maintenanceMargin": "0.005", "maxLeverage": "100.00"},
+                {"symbol": "ETHUSDT", "riskLimitValue": "1000000", "maintenanceMargin": "0.005", "maxLeverage": "100.00"}
+            ]"#,
+        )
+        .unwrap();
+        let brackets = leverage_brackets(tiers);
+        assert_eq!(brackets.len(), 2);
+
+        let btc = &brackets[0].brackets;
+        assert_eq!(btc[0].notional_floor, dec!(0));
+        assert_eq!(btc[0].notional_cap, dec!(2000000));
+        assert_eq!(btc[0].initial_leverage, 100);
+        assert_eq!(btc[1].notional_floor, dec!(2000000));
+        // Maintenance stays continuous across the tier boundary
+        assert_eq!(btc[1].cum, dec!(10000));
+        assert_eq!(
+            dec!(2000000) * btc[0].maint_margin_ratio - btc[0].cum,
+            dec!(2000000) * btc[1].maint_margin_ratio - btc[1].cum
+        );
+    }
+
+    #[test]
+    fn test_positions_and_orders() {
+        let position: RawPosition = serde_json::from_str(
+            r#"{
+                "symbol": "ETHUSDT", "side": "Sell", "size": "2", "avgPrice": "3000",
+                "markPrice": "3010", "unrealisedPnl": "-20", "liqPrice": "",
+                "leverage": "5", "positionValue": "6000", "positionIM": "1200", "tradeMode": 0
+            }"#,
+        )
+        .unwrap();
+        let position = position.position();
+        assert_eq!(position.position_amt, dec!(-2));
+        assert_eq!(position.notional, dec!(-6000));
+        assert_eq!(position.liquidation_price, Decimal::ZERO);
+        assert_eq!(position.leverage, 5);
+        assert_eq!(position.margin_type, MarginType::Cross);
+        assert_eq!(position.isolated_margin, Decimal::ZERO);
+
+        let order: RawOrder = serde_json::from_str(
+            r#"{
+                "orderId": "fd4300ae-7847-404e-b947-b46980a4d140", "orderLinkId": "ffe-entry-1",
+                "symbol": "ETHUSDT", "side": "Sell", "orderType": "Market",
+                "orderStatus": "PartiallyFilledCanceled", "timeInForce": "IOC", "price": "",
+                "avgPrice": "2999.5", "qty": "2", "cumExecQty": "1.5", "updatedTime": "1700000000123"
+            }"#,
+        )
+        .unwrap();
+        let response = order.order_response();
+        assert_eq!(response.status, OrderStatus::Canceled);
+        assert_eq!(response.executed_qty, dec!(1.5));
+        assert_eq!(response.side, OrderSide::Sell);
+        assert_eq!(response.time_in_force, Some(TimeInForce::Ioc));
+        assert_eq!(response.client_order_id, "ffe-entry-1");
+        assert_eq!(response.order_id, numeric_id(&order.order_id));
+        assert!(response.order_id >= 0);
+        assert_eq!(numeric_id("1321003749386327552"), 1321003749386327552);
+    }
+
+    #[test]
+    fn test_open_orders_keep_client_ids() {
+        let json = r#"{
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {
+                "list": [
+                    {
+                        "orderId": "1b6bb44c-1b4b-4b2b-9f3c-6f0a5d6e7a01", "orderLinkId": "ffea1b2c3-p1-0-4",
+                        "symbol": "ETHUSDT", "side": "Buy", "orderType": "Limit",
+                        "orderStatus": "New", "timeInForce": "GTC", "price": "2950",
+                        "avgPrice": "", "qty": "1", "cumExecQty": "0", "createdTime": "1700000000000"
+                    }
+                ],
+                "nextPageCursor": ""
+            }
+        }"#;
+        let envelope: Envelope<Page<RawOrder>> = serde_json::from_str(json).unwrap();
+        let orders = envelope.into_result("get_open_orders").unwrap().list;
+        let lookups: Vec<OrderLookup> = orders.iter().map(RawOrder::order_lookup).collect();
+
+        assert_eq!(lookups[0].status, OrderStatus::New);
+        assert_eq!(lookups[0].client_order_id, "ffea1b2c3-p1-0-4");
+        assert_eq!(lookups[0].time, 1_700_000_000_000);
+        // A cancel finds the order again by the ID it was listed with
+        assert_eq!(lookups[0].order_id, numeric_id(&orders[0].order_id));
+    }
+
+    #[test]
+    fn test_order_body_and_signature() {
+        let body = order_body(
+            "linear",
+            "BTCUSDT",
+            OrderSide::Buy,
+            OrderType::Limit,
+            Some(dec!(0.01)),
+            Some(dec!(50000)),
+            Some(TimeInForce::Gtx),
+            Some("ffe-1"),
+        )
+        .unwrap();
+        assert_eq!(body["side"], "Buy");
+        assert_eq!(body["orderType"], "Limit");
+        assert_eq!(body["timeInForce"], "PostOnly");
+        assert_eq!(body["qty"], "0.01");
+        assert_eq!(body["orderLinkId"], "ffe-1");
+        assert!(order_body(
+            "linear",
+            "BTCUSDT",
+            OrderSide::Buy,
+            OrderType::StopMarket,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+
+        let credentials = Credentials {
+            api_key: "key".to_string(),
+            secret_key: "secret".to_string(),
+        };
+        // HMAC-SHA256("secret", "1700000000000" + "key" + "5000" + "category=linear")
+        assert_eq!(
+            credentials.sign(1_700_000_000_000, "category=linear"),
+            "e6c3e971c517d999338172674f1c633b9016addf8f8c632372232076767b4c07"
+        );
+    }
+
+    #[test]
+    fn test_transaction_log_income() {
+        let tx: RawTransaction = serde_json::from_str(
+            r#"{"id": "592324_XRPUSDT_161440249321", "symbol": "XRPUSDT", "currency": "USDT",
+                "change": "-0.0432", "fee": "0.012", "transactionTime": "1700000000000"}"#,
+        )
+        .unwrap();
+        let funding = IncomeSource::for_income_type("FUNDING_FEE").unwrap();
+        assert_eq!(funding.log_type(), "SETTLEMENT");
+        assert_eq!(funding.income(&tx), dec!(-0.0432));
+        let commission = IncomeSource::for_income_type("COMMISSION").unwrap();
+        assert_eq!(commission.log_type(), "TRADE");
+        assert_eq!(commission.income(&tx), dec!(-0.012));
+        assert!(IncomeSource::for_income_type("REALIZED_PNL").is_err());
+    }
+}
@@ -2,43 +2,114 @@
 
 use crate::config::BinanceConfig;
 use crate::exchange::types::*;
-use anyhow::{anyhow, Context, Result};
-use hmac::{Hmac, Mac};
+use crate::exchange::{
+    replicate_error, ApiError, ApiMarket, ApiUsage, ExchangeClient, OrderValve, RequestCost,
+    RequestSigner, ServerClock, TradeLane, TradeQueue, WeightLimiter,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
 use reqwest::{Client, Response, StatusCode};
 use serde::Deserialize;
-use sha2::Sha256;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::sleep;
 use tracing::{debug, instrument, warn};
 
 /// Default retry configuration
-const MAX_RETRIES: u32 = 3;
-const INITIAL_BACKOFF_MS: u64 = 100;
-const BACKOFF_MULTIPLIER: u64 = 5; // 100ms -> 500ms -> 2500ms
+pub(super) const MAX_RETRIES: u32 = 3;
+pub(super) const INITIAL_BACKOFF_MS: u64 = 100;
+pub(super) const BACKOFF_MULTIPLIER: u64 = 5; // 100ms -> 500ms -> 2500ms
 
 /// Check if an HTTP status code is retryable
-fn is_retryable_status(status: StatusCode) -> bool {
+pub(super) fn is_retryable_status(status: StatusCode) -> bool {
     // Retry on server errors (5xx) and rate limiting (429)
     status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
 }
 
+/// `backoff_ms` plus up to half of it again, so clients failing together don't
+/// retry in lockstep.
+pub(super) fn with_jitter(backoff_ms: u64) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    Duration::from_millis(backoff_ms + nanos % (backoff_ms / 2 + 1))
+}
+
 /// Check if an error is retryable (network errors, timeouts)
-fn is_retryable_error(error: &reqwest::Error) -> bool {
+pub(super) fn is_retryable_error(error: &reqwest::Error) -> bool {
     error.is_timeout() || error.is_connect() || error.is_request()
 }
 
 const FUTURES_BASE_URL: &str = "https://fapi.binance.com";
 const FUTURES_TESTNET_URL: &str = "https://testnet.binancefuture.com";
+const COIN_FUTURES_BASE_URL: &str = "https://dapi.binance.com";
+const COIN_FUTURES_TESTNET_URL: &str = "https://testnet.binancefuture.com";
 const SPOT_BASE_URL: &str = "https://api.binance.com";
 const SPOT_TESTNET_URL: &str = "https://testnet.binance.vision";
 
+/// Most orders `/fapi/v1/batchOrders` accepts per request.
+const MAX_BATCH_ORDERS: usize = 5;
+
+/// Binance error code of a timestamp outside the receive window.
+const TIMESTAMP_OUTSIDE_WINDOW: i64 = -1021;
+
+/// API key and signer of one class of requests.
+#[derive(Clone)]
+struct Credentials {
+    api_key: String,
+    signer: RequestSigner,
+}
+
+impl Credentials {
+    fn new(config: &BinanceConfig, api_key: &str, secret_key: &str) -> Result<Self> {
+        Ok(Self {
+            api_key: api_key.to_string(),
+            signer: RequestSigner::new(config.key_type, secret_key)?,
+        })
+    }
+
+    /// Signature of an authenticated request's query string.
+    fn sign(&self, query_string: &str) -> String {
+        self.signer.sign(query_string)
+    }
+}
+
 /// Binance API client for both spot and futures markets.
+///
+/// Signed requests use one of two key sets: account and order reads go through
+/// the read-only key, anything that changes the account through the trading key.
+/// Without a read-only key both use the trading key.
+///
+/// With `testnet` set every request goes to the Binance testnets. The spot
+/// testnet serves no margin or sub-account API, so spot legs become plain spot
+/// orders there, the spot account stands in for the cross margin account and
+/// nothing is borrowable.
 pub struct BinanceClient {
     http: Client,
-    api_key: String,
-    secret_key: String,
+    /// Signs account and order reads
+    read: Credentials,
+    /// Signs orders, leverage and margin changes, borrows and transfers
+    trade: Credentials,
     futures_base_url: String,
+    /// COIN-margined futures (dapi)
+    coin_futures_base_url: String,
     spot_base_url: String,
+    /// Requests go to the testnets
+    testnet: bool,
+    /// Order rate and notional velocity limits
+    order_valve: OrderValve,
+    /// Request weight and order counts against the account's limits
+    api_usage: ApiUsage,
+    /// Delays requests that would run into the weight limits
+    rate_limiter: WeightLimiter,
+    /// Offset of the local clock from the exchange's, applied to signed requests
+    server_clock: ServerClock,
+    /// How long after its timestamp the exchange accepts a signed request (ms)
+    recv_window_ms: u64,
+    /// Serializes account-mutating calls
+    trade_queue: TradeQueue,
 }
 
 impl BinanceClient {
@@ -49,118 +120,330 @@ impl BinanceClient {
             .build()
             .context("Failed to create HTTP client")?;
 
-        let (futures_base_url, spot_base_url) = if config.testnet {
+        let (futures_base_url, coin_futures_base_url, spot_base_url) = if config.testnet {
             (
                 FUTURES_TESTNET_URL.to_string(),
+                COIN_FUTURES_TESTNET_URL.to_string(),
                 SPOT_TESTNET_URL.to_string(),
             )
         } else {
-            (FUTURES_BASE_URL.to_string(), SPOT_BASE_URL.to_string())
+            (
+                FUTURES_BASE_URL.to_string(),
+                COIN_FUTURES_BASE_URL.to_string(),
+                SPOT_BASE_URL.to_string(),
+            )
+        };
+
+        let trade = Credentials::new(config, &config.api_key, &config.secret_key)
+            .context("Failed to load the trading key")?;
+        let read = if config.read_api_key.is_empty() {
+            trade.clone()
+        } else {
+            Credentials::new(config, &config.read_api_key, &config.read_secret_key)
+                .context("Failed to load the read-only key")?
         };
 
         Ok(Self {
             http,
-            api_key: config.api_key.clone(),
-            secret_key: config.secret_key.clone(),
+            read,
+            trade,
             futures_base_url,
+            coin_futures_base_url,
             spot_base_url,
+            testnet: config.testnet,
+            order_valve: OrderValve::default(),
+            api_usage: ApiUsage::default(),
+            rate_limiter: WeightLimiter::default(),
+            server_clock: ServerClock::new(),
+            recv_window_ms: config.recv_window_ms,
+            trade_queue: TradeQueue::new(),
         })
     }
 
-    /// Generate HMAC-SHA256 signature for authenticated requests.
-    fn sign(&self, query_string: &str) -> String {
-        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
-            .expect("HMAC can take key of any size");
-        mac.update(query_string.as_bytes());
-        hex::encode(mac.finalize().into_bytes())
+    /// Whether requests go to the Binance testnets.
+    pub fn is_testnet(&self) -> bool {
+        self.testnet
+    }
+
+    /// Whether an API key is loaded for signed read-only requests.
+    pub fn has_read_key(&self) -> bool {
+        !self.read.api_key.is_empty()
+    }
+
+    /// Fail `operation` on the testnet, which serves no margin or sub-account API.
+    fn require_production(&self, operation: &str) -> Result<()> {
+        if self.testnet {
+            anyhow::bail!("{} is not available on the Binance testnet", operation);
+        }
+        Ok(())
+    }
+
+    /// Endpoint of spot leg orders: cross margin, or plain spot on the testnet.
+    fn spot_order_path(&self) -> &'static str {
+        if self.testnet {
+            "/api/v3/order"
+        } else {
+            "/sapi/v1/margin/order"
+        }
     }
 
-    /// Get current timestamp in milliseconds.
-    fn timestamp() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis() as u64
+    /// Guard order placement with `valve`.
+    pub fn set_order_valve(&mut self, valve: OrderValve) {
+        self.order_valve = valve;
     }
 
-    /// Execute an HTTP request with retry and exponential backoff.
+    /// Count requests into `usage`.
+    pub fn set_api_usage(&mut self, usage: ApiUsage) {
+        self.api_usage = usage;
+    }
+
+    /// Throttle requests with `limiter`, shared by every client on the same IP.
+    pub fn set_rate_limiter(&mut self, limiter: WeightLimiter) {
+        self.rate_limiter = limiter;
+    }
+
+    /// Timestamp signed requests with `clock`, shared by every client of the exchange.
+    pub fn set_server_clock(&mut self, clock: ServerClock) {
+        self.server_clock = clock;
+    }
+
+    /// Count a request that reached the exchange.
+    ///
+    /// COIN-margined weight is a separate pool, so its header is ignored.
+    fn record_usage(&self, cost: RequestCost, response: &Response) {
+        let now = chrono::Utc::now();
+        if response.url().path().starts_with("/dapi/") {
+            self.api_usage.record(cost, None, now);
+            return;
+        }
+        let used_weight = response
+            .headers()
+            .get("x-mbx-used-weight-1m")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        if let Some(used) = used_weight {
+            self.rate_limiter.observe(cost.market, used, now);
+        }
+        self.api_usage.record(cost, used_weight, now);
+    }
+
+    /// Hold `market` after a 429 or an IP ban (418) until the exchange's
+    /// `Retry-After`, or a minute without one.
+    fn back_off_rate_limit(&self, market: ApiMarket, response: &Response) {
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(60);
+        warn!(
+            %market,
+            status = %response.status(),
+            retry_after,
+            "Rate limited by the exchange, pausing requests"
+        );
+        self.rate_limiter.pause_until(
+            market,
+            chrono::Utc::now() + chrono::Duration::seconds(retry_after),
+        );
+    }
+
+    /// Current timestamp in milliseconds on the exchange's clock.
+    fn timestamp(&self) -> u64 {
+        self.server_clock.server_ms(chrono::Utc::now())
+    }
+
+    /// `params` stamped with the exchange's current time and the receive window,
+    /// with their signature by `credentials` appended.
+    fn signed_query(&self, credentials: &Credentials, params: &str) -> String {
+        let stamp = format!(
+            "timestamp={}&recvWindow={}",
+            self.timestamp(),
+            self.recv_window_ms
+        );
+        let query = if params.is_empty() {
+            stamp
+        } else {
+            format!("{}&{}", params, stamp)
+        };
+        let signature = credentials.sign(&query);
+        format!("{}&signature={}", query, signature)
+    }
+
+    /// Execute an HTTP request with retry and jittered exponential backoff.
+    ///
+    /// Each attempt first waits for its weight in the rate limiter. Only a
+    /// successful response is returned; failures come back as an [`ApiError`]
+    /// classified by status and Binance error code.
     ///
     /// Retries on:
-    /// - 5xx server errors
+    /// - 5xx server errors and backend timeouts
     /// - 429 rate limit errors
     /// - Network timeouts and connection errors
     ///
-    /// Does NOT retry on:
-    /// - 4xx client errors (except 429)
-    /// - Authentication errors
-    /// - Validation errors
+    /// Fails fast on:
+    /// - Signature, timestamp and permission errors
+    /// - Filter, precision, balance and other validation errors
+    /// - 418 IP bans
+    ///
+    /// Signed requests go through `retry_signed`, order placement through
+    /// `send_order`.
     async fn retry_with_backoff<F, Fut>(&self, operation: &str, request_fn: F) -> Result<Response>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.retry_with_cost(operation, RequestCost::of(operation), request_fn)
+            .await
+    }
+
+    /// `retry_with_backoff` for requests whose weight depends on their parameters.
+    async fn retry_with_cost<F, Fut>(
+        &self,
+        operation: &str,
+        cost: RequestCost,
+        request_fn: F,
+    ) -> Result<Response>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.send_with_retry(operation, cost, true, request_fn)
+            .await
+    }
+
+    /// `retry_with_backoff` for a signed request; see `send_signed`.
+    async fn retry_signed<F, Fut>(
+        &self,
+        operation: &str,
+        credentials: &Credentials,
+        params: &str,
+        request_fn: F,
+    ) -> Result<Response>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.send_signed(
+            operation,
+            RequestCost::of(operation),
+            true,
+            credentials,
+            params,
+            request_fn,
+        )
+        .await
+    }
+
+    /// Send an order request signed by the trading key, which is not idempotent.
+    ///
+    /// Only rate limits, which refuse the order unexecuted, are retried. A
+    /// timeout or server error may have placed the order anyway, so it is
+    /// returned for the caller to look up by client order ID rather than sent
+    /// again.
+    async fn send_order<F, Fut>(
+        &self,
+        operation: &str,
+        params: &str,
+        request_fn: F,
+    ) -> Result<Response>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.send_signed(
+            operation,
+            RequestCost::of(operation),
+            false,
+            &self.trade,
+            params,
+            request_fn,
+        )
+        .await
+    }
+
+    /// `send_with_retry` for a signed request; `request_fn` gets the signed query.
+    ///
+    /// `params` are stamped and signed on every attempt, once the rate limiter
+    /// has granted its weight. A request held by the limiter, a rate limit pause
+    /// or a backoff would otherwise reach the exchange outside its receive
+    /// window and fail with -1021.
+    async fn send_signed<F, Fut>(
+        &self,
+        operation: &str,
+        cost: RequestCost,
+        idempotent: bool,
+        credentials: &Credentials,
+        params: &str,
+        request_fn: F,
+    ) -> Result<Response>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
+    {
+        self.send_with_retry(operation, cost, idempotent, || {
+            request_fn(self.signed_query(credentials, params))
+        })
+        .await
+    }
+
+    /// Request loop of `retry_with_cost` and `send_signed`; only `idempotent`
+    /// requests are retried on errors that may have executed them.
+    async fn send_with_retry<F, Fut>(
+        &self,
+        operation: &str,
+        cost: RequestCost,
+        idempotent: bool,
+        request_fn: F,
+    ) -> Result<Response>
     where
         F: Fn() -> Fut,
         Fut: std::future::Future<Output = Result<Response, reqwest::Error>>,
     {
         let mut backoff_ms = INITIAL_BACKOFF_MS;
-        let mut last_error = None;
+        let mut attempt = 0;
 
-        for attempt in 1..=MAX_RETRIES {
-            match request_fn().await {
+        loop {
+            attempt += 1;
+            self.rate_limiter.acquire(operation, cost).await;
+            let error = match request_fn().await {
                 Ok(response) => {
+                    self.record_usage(cost, &response);
                     let status = response.status();
-
-                    // Success or non-retryable client error
-                    if status.is_success()
-                        || (status.is_client_error() && !is_retryable_status(status))
-                    {
+                    if status.is_success() {
                         return Ok(response);
                     }
-
-                    // Retryable status code
-                    if is_retryable_status(status) && attempt < MAX_RETRIES {
-                        warn!(
-                            %operation,
-                            attempt,
-                            status = %status,
-                            backoff_ms,
-                            "Retryable HTTP status, backing off"
-                        );
-                        sleep(Duration::from_millis(backoff_ms)).await;
-                        backoff_ms *= BACKOFF_MULTIPLIER;
-                        last_error = Some(anyhow!("HTTP {} for {}", status, operation));
-                        continue;
+                    if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+                        self.back_off_rate_limit(cost.market, &response);
                     }
-
-                    // Non-retryable or exhausted retries
-                    return Ok(response);
+                    let body = response.text().await.unwrap_or_default();
+                    ApiError::from_response(operation, status, &body)
                 }
-                Err(e) => {
-                    if is_retryable_error(&e) && attempt < MAX_RETRIES {
-                        warn!(
-                            %operation,
-                            attempt,
-                            error = %e,
-                            backoff_ms,
-                            "Retryable network error, backing off"
-                        );
-                        sleep(Duration::from_millis(backoff_ms)).await;
-                        backoff_ms *= BACKOFF_MULTIPLIER;
-                        last_error = Some(anyhow!("Network error for {}: {}", operation, e));
-                        continue;
-                    }
+                Err(e) => ApiError::from_network(operation, &e),
+            };
+            if error.code == Some(TIMESTAMP_OUTSIDE_WINDOW) {
+                self.server_clock.invalidate();
+            }
 
-                    // Non-retryable error or exhausted retries
-                    return Err(anyhow!(
-                        "{} failed after {} attempts: {}",
-                        operation,
-                        attempt,
-                        e
-                    ));
-                }
+            let retryable =
+                error.class.is_transient() && (idempotent || !error.class.may_have_executed());
+            // An IP ban outlasts any backoff; the rate limiter holds requests until it ends
+            if !retryable || error.status == Some(418) || attempt >= MAX_RETRIES {
+                return Err(error.into());
             }
+            let delay = with_jitter(backoff_ms);
+            warn!(
+                %operation,
+                attempt,
+                class = %error.class,
+                status = ?error.status,
+                error = %error.message,
+                backoff_ms = delay.as_millis() as u64,
+                "Transient API error, backing off"
+            );
+            sleep(delay).await;
+            backoff_ms *= BACKOFF_MULTIPLIER;
         }
-
-        // Exhausted all retries
-        Err(last_error
-            .unwrap_or_else(|| anyhow!("{} failed after {} retries", operation, MAX_RETRIES)))
     }
 
     // ==================== Market Data (Public) ====================
@@ -179,6 +462,42 @@ impl BinanceClient {
             .context("Failed to parse funding rates response")
     }
 
+    /// Get funding settings of symbols adjusted from the defaults (e.g. 4h intervals).
+    #[instrument(skip(self))]
+    pub async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        let url = format!("{}/fapi/v1/fundingInfo", self.futures_base_url);
+        let response = self
+            .retry_with_backoff("get_funding_info", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse funding info response")
+    }
+
+    /// Get settled funding rates for a symbol between `start_time` and `end_time` (ms).
+    #[instrument(skip(self))]
+    pub async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        let url = format!(
+            "{}/fapi/v1/fundingRate?symbol={}&startTime={}&endTime={}&limit=1000",
+            self.futures_base_url, symbol, start_time, end_time
+        );
+        let response = self
+            .retry_with_backoff("get_funding_rate_history", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse funding rate history response")
+    }
+
     /// Get 24-hour ticker for all symbols.
     #[instrument(skip(self))]
     pub async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
@@ -207,6 +526,37 @@ impl BinanceClient {
             .context("Failed to parse spot 24h ticker response")
     }
 
+    /// Get 24-hour ticker for specific spot symbols.
+    ///
+    /// Request weight is 2 for up to 20 symbols versus 80 for all symbols. Every
+    /// symbol must exist on spot or the whole request is rejected.
+    #[instrument(skip(self, symbols), fields(count = symbols.len()))]
+    pub async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let symbols_json =
+            serde_json::to_string(symbols).context("Failed to encode spot symbol list")?;
+        let url = format!(
+            "{}/api/v3/ticker/24hr?symbols={}",
+            self.spot_base_url,
+            urlencoding::encode(&symbols_json)
+        );
+        let response = self
+            .retry_with_cost(
+                "get_spot_24h_tickers_for",
+                RequestCost::spot_tickers(symbols.len()),
+                || self.http.get(&url).send(),
+            )
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse spot 24h ticker response")
+    }
+
     /// Get best bid/ask for all symbols.
     #[instrument(skip(self))]
     pub async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
@@ -238,6 +588,58 @@ impl BinanceClient {
             .context("Failed to parse open interest response")
     }
 
+    /// Calibrate the clock offset against the futures server time.
+    ///
+    /// Returns the offset: server time minus local time (ms).
+    #[instrument(skip(self))]
+    pub async fn sync_server_time(&self) -> Result<i64> {
+        let url = format!("{}/fapi/v1/time", self.futures_base_url);
+        // Measured per attempt, so throttling and retries stay out of the round trip
+        let sent = Cell::new(chrono::Utc::now());
+        let response = self
+            .retry_with_backoff("get_server_time", || {
+                sent.set(chrono::Utc::now());
+                self.http.get(&url).send()
+            })
+            .await?;
+        let received = chrono::Utc::now();
+        let time: ServerTime = response
+            .json()
+            .await
+            .context("Failed to parse server time response")?;
+
+        let offset_ms = self
+            .server_clock
+            .calibrate(time.server_time, sent.get(), received);
+        debug!(
+            offset_ms,
+            round_trip_ms = self.server_clock.round_trip_ms(),
+            "Calibrated clock offset"
+        );
+        Ok(offset_ms)
+    }
+
+    /// Get the exchange system status (normal or under maintenance).
+    #[instrument(skip(self))]
+    pub async fn get_system_status(&self) -> Result<SystemStatus> {
+        // The testnet has no status endpoint and no announced maintenance
+        if self.testnet {
+            return Ok(SystemStatus {
+                status: 0,
+                msg: "normal".to_string(),
+            });
+        }
+        let url = format!("{}/sapi/v1/system/status", self.spot_base_url);
+        let response = self
+            .retry_with_backoff("get_system_status", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse system status response")
+    }
+
     /// Get futures exchange info (for precision and rules).
     #[instrument(skip(self))]
     pub async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
@@ -253,22 +655,18 @@ impl BinanceClient {
     }
 
     /// Get leverage brackets for all symbols (maintenance margin rates).
+    ///
+    /// Signed `GET /fapi/v1/leverageBracket`: Binance serves no public bracket
+    /// endpoint, so this needs an API key.
     #[instrument(skip(self))]
     pub async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
-        let timestamp = Self::timestamp();
-        let query = format!("timestamp={}", timestamp);
-        let signature = self.sign(&query);
-
-        let url = format!(
-            "{}/fapi/v1/leverageBracket?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+        let url = format!("{}/fapi/v1/leverageBracket", self.futures_base_url);
 
         let response = self
-            .retry_with_backoff("get_leverage_brackets", || {
+            .retry_signed("get_leverage_brackets", &self.read, "", |query| {
                 self.http
-                    .get(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
@@ -284,20 +682,13 @@ impl BinanceClient {
     /// Get account balance information.
     #[instrument(skip(self))]
     pub async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
-        let timestamp = Self::timestamp();
-        let query = format!("timestamp={}", timestamp);
-        let signature = self.sign(&query);
-
-        let url = format!(
-            "{}/fapi/v2/balance?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+        let url = format!("{}/fapi/v2/balance", self.futures_base_url);
 
         let response = self
-            .retry_with_backoff("get_account_balance", || {
+            .retry_signed("get_account_balance", &self.read, "", |query| {
                 self.http
-                    .get(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
@@ -311,20 +702,13 @@ impl BinanceClient {
     /// Get current positions.
     #[instrument(skip(self))]
     pub async fn get_positions(&self) -> Result<Vec<Position>> {
-        let timestamp = Self::timestamp();
-        let query = format!("timestamp={}", timestamp);
-        let signature = self.sign(&query);
-
-        let url = format!(
-            "{}/fapi/v2/positionRisk?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+        let url = format!("{}/fapi/v2/positionRisk", self.futures_base_url);
 
         let response = self
-            .retry_with_backoff("get_positions", || {
+            .retry_signed("get_positions", &self.read, "", |query| {
                 self.http
-                    .get(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
@@ -335,167 +719,646 @@ impl BinanceClient {
             .context("Failed to parse positions response")
     }
 
-    // ==================== Orders (Authenticated) ====================
-
-    /// Place a new futures order.
+    /// Get futures income history of one type (e.g. "FUNDING_FEE") since `start_time` (ms).
+    ///
+    /// Pages through results until fewer than a full page is returned. Each page
+    /// starts at the last one's final time rather than after it: every symbol's
+    /// funding fee of a settlement shares one time, so a page ending inside a
+    /// settlement would otherwise drop the rest of it.
     #[instrument(skip(self))]
-    pub async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
-        let timestamp = Self::timestamp();
-        let mut params = vec![
-            ("symbol".to_string(), order.symbol.clone()),
-            (
-                "side".to_string(),
-                format!("{:?}", order.side).to_uppercase(),
-            ),
-            (
-                "type".to_string(),
-                format!("{:?}", order.order_type).to_uppercase(),
-            ),
-            ("timestamp".to_string(), timestamp.to_string()),
-        ];
+    pub async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        const PAGE_LIMIT: usize = 1000;
+        let mut records: Vec<IncomeRecord> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut cursor = start_time;
+
+        loop {
+            let params = format!(
+                "incomeType={}&startTime={}&limit={}",
+                income_type, cursor, PAGE_LIMIT
+            );
 
-        if let Some(qty) = &order.quantity {
-            params.push(("quantity".to_string(), qty.to_string()));
+            let url = format!("{}/fapi/v1/income", self.futures_base_url);
+
+            let response = self
+                .retry_signed("get_income_history", &self.read, &params, |query| {
+                    self.http
+                        .get(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.read.api_key)
+                        .send()
+                })
+                .await?;
+
+            let page: Vec<IncomeRecord> = response
+                .json()
+                .await
+                .context("Failed to parse income history response")?;
+
+            let full_page = page.len() == PAGE_LIMIT;
+            let last_time = page.last().map(|r| r.time);
+            let added = Self::add_new_income(&mut records, &mut seen, page);
+
+            match last_time {
+                Some(time) if full_page && added > 0 => cursor = time,
+                _ => break,
+            }
         }
 
-        if let Some(price) = &order.price {
-            params.push(("price".to_string(), price.to_string()));
-        }
+        Ok(records)
+    }
 
-        if let Some(tif) = &order.time_in_force {
-            params.push((
-                "timeInForce".to_string(),
-                format!("{:?}", tif).to_uppercase(),
-            ));
-        }
+    /// Append the records of `page` not already in `seen`, returning how many.
+    fn add_new_income(
+        records: &mut Vec<IncomeRecord>,
+        seen: &mut HashSet<(String, i64, i64)>,
+        page: Vec<IncomeRecord>,
+    ) -> usize {
+        let before = records.len();
+        records.extend(
+            page.into_iter()
+                .filter(|r| seen.insert((r.symbol.clone(), r.time, r.tran_id))),
+        );
+        records.len() - before
+    }
 
-        if let Some(reduce_only) = order.reduce_only {
-            params.push(("reduceOnly".to_string(), reduce_only.to_string()));
-        }
+    // ==================== User Data Stream ====================
 
-        if let Some(client_id) = &order.new_client_order_id {
-            params.push(("newClientOrderId".to_string(), client_id.clone()));
+    /// Start a futures user data stream and return its listen key.
+    ///
+    /// Returns the key of the stream already open for the account, if any.
+    #[instrument(skip(self))]
+    pub async fn create_listen_key(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ListenKeyResponse {
+            listen_key: String,
         }
 
-        let query_string: String = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
-            .collect::<Vec<_>>()
-            .join("&");
-
-        let signature = self.sign(&query_string);
-        let url = format!(
-            "{}/fapi/v1/order?{}&signature={}",
-            self.futures_base_url, query_string, signature
-        );
-
-        debug!("Placing futures order: {:?}", order);
-
+        let url = format!("{}/fapi/v1/listenKey", self.futures_base_url);
         let response = self
-            .retry_with_backoff("place_futures_order", || {
+            .retry_with_backoff("create_listen_key", || {
                 self.http
                     .post(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
 
-        response
+        let body: ListenKeyResponse = response
             .json()
             .await
-            .context("Failed to parse order response")
+            .context("Failed to parse listen key response")?;
+        Ok(body.listen_key)
     }
 
-    /// Cancel a futures order.
+    /// Extend the futures user data stream by 60 minutes.
     #[instrument(skip(self))]
-    pub async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
-        let timestamp = Self::timestamp();
-        let query = format!(
-            "symbol={}&orderId={}&timestamp={}",
-            symbol, order_id, timestamp
-        );
-        let signature = self.sign(&query);
+    pub async fn keepalive_listen_key(&self) -> Result<()> {
+        let url = format!("{}/fapi/v1/listenKey", self.futures_base_url);
+        self.retry_with_backoff("keepalive_listen_key", || {
+            self.http
+                .put(&url)
+                .header("X-MBX-APIKEY", &self.read.api_key)
+                .send()
+        })
+        .await?;
+        Ok(())
+    }
 
-        let url = format!(
-            "{}/fapi/v1/order?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+    // ==================== Orders (Authenticated) ====================
+
+    /// Place a new futures order.
+    #[instrument(skip(self))]
+    pub async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        let lane = if order.reduce_only == Some(true) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let _turn = self.trade_queue.turn(lane).await;
+        self.order_valve.admit(chrono::Utc::now())?;
+        let params = Self::order_query(order);
+        let url = format!("{}/fapi/v1/order", self.futures_base_url);
+
+        debug!("Placing futures order: {:?}", order);
 
         let response = self
-            .retry_with_backoff("cancel_futures_order", || {
+            .send_order("place_futures_order", &params, |query| {
                 self.http
-                    .delete(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .post(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
                     .send()
             })
             .await?;
 
-        response
+        let response: OrderResponse = response
             .json()
             .await
-            .context("Failed to parse cancel response")
+            .context("Failed to parse order response")?;
+        self.order_valve.record_fill(chrono::Utc::now(), &response);
+        Ok(response)
     }
 
-    /// Set leverage for a symbol.
+    /// Place up to five futures orders per request through `/fapi/v1/batchOrders`.
+    ///
+    /// Returns one result per order, in order. Orders the valve refuses are not
+    /// sent, and a request that fails as a whole fails each of its orders.
     #[instrument(skip(self))]
-    pub async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
-        let timestamp = Self::timestamp();
-        let query = format!(
-            "symbol={}&leverage={}&timestamp={}",
-            symbol, leverage, timestamp
-        );
-        let signature = self.sign(&query);
-
-        let url = format!(
-            "{}/fapi/v1/leverage?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+    pub async fn place_futures_orders(&self, orders: &[NewOrder]) -> Vec<Result<OrderResponse>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for batch in orders.chunks(MAX_BATCH_ORDERS) {
+            results.extend(self.place_futures_order_batch(batch).await);
+        }
+        results
+    }
 
-        self.retry_with_backoff("set_leverage", || {
-            self.http
-                .post(&url)
-                .header("X-MBX-APIKEY", &self.api_key)
-                .send()
-        })
-        .await?;
+    /// One `/fapi/v1/batchOrders` request of at most [`MAX_BATCH_ORDERS`] orders.
+    async fn place_futures_order_batch(&self, orders: &[NewOrder]) -> Vec<Result<OrderResponse>> {
+        let lane = if orders.iter().all(|o| o.reduce_only == Some(true)) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let _turn = self.trade_queue.turn(lane).await;
+        let now = chrono::Utc::now();
+        let admitted: Vec<Result<()>> =
+            orders.iter().map(|_| self.order_valve.admit(now)).collect();
+        let batch: Vec<serde_json::Value> = orders
+            .iter()
+            .zip(&admitted)
+            .filter(|(_, admitted)| admitted.is_ok())
+            .map(|(order, _)| {
+                Self::order_params(order)
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), serde_json::Value::String(v)))
+                    .collect()
+            })
+            .collect();
 
-        Ok(())
+        let responses = if batch.is_empty() {
+            Vec::new()
+        } else {
+            debug!(orders = batch.len(), "Placing futures batch order");
+            match self.send_batch_orders(&batch).await {
+                Ok(responses) => responses,
+                Err(e) => batch.iter().map(|_| Err(replicate_error(&e))).collect(),
+            }
+        };
+        let mut responses = responses.into_iter();
+        admitted
+            .into_iter()
+            .map(|admitted| {
+                let response = admitted.and_then(|()| {
+                    responses
+                        .next()
+                        .unwrap_or_else(|| Err(anyhow!("Batch order response missing")))
+                })?;
+                self.order_valve.record_fill(chrono::Utc::now(), &response);
+                Ok(response)
+            })
+            .collect()
     }
 
-    /// Set margin type (isolated or cross) for a symbol.
+    /// Send orders already passed by the valve, one result per order.
+    async fn send_batch_orders(
+        &self,
+        batch: &[serde_json::Value],
+    ) -> Result<Vec<Result<OrderResponse>>> {
+        /// Element of a batch response: the order, or why it was refused.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum BatchItem {
+            Placed(OrderResponse),
+            Refused { code: i64, msg: String },
+        }
+
+        let params = format!(
+            "batchOrders={}",
+            urlencoding::encode(&serde_json::to_string(batch)?)
+        );
+        let url = format!("{}/fapi/v1/batchOrders", self.futures_base_url);
+
+        // Not idempotent: see `send_order`
+        let response = self
+            .send_signed(
+                "place_futures_orders",
+                RequestCost::batch_orders(batch.len()),
+                false,
+                &self.trade,
+                &params,
+                |query| {
+                    self.http
+                        .post(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.trade.api_key)
+                        .send()
+                },
+            )
+            .await?;
+
+        let items: Vec<BatchItem> = response
+            .json()
+            .await
+            .context("Failed to parse batch order response")?;
+        if items.len() != batch.len() {
+            bail!(
+                "Batch order response has {} results for {} orders",
+                items.len(),
+                batch.len()
+            );
+        }
+        Ok(items
+            .into_iter()
+            .map(|item| match item {
+                BatchItem::Placed(order) => Ok(order),
+                BatchItem::Refused { code, msg } => {
+                    Err(ApiError::from_batch_item("place_futures_orders", code, &msg).into())
+                }
+            })
+            .collect())
+    }
+
+    /// Parameters of a futures order, as Binance names them.
+    fn order_params(order: &NewOrder) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("symbol", order.symbol.clone()),
+            ("side", format!("{:?}", order.side).to_uppercase()),
+            ("type", format!("{:?}", order.order_type).to_uppercase()),
+        ];
+
+        if let Some(qty) = &order.quantity {
+            params.push(("quantity", qty.to_string()));
+        }
+
+        if let Some(price) = &order.price {
+            params.push(("price", price.to_string()));
+        }
+
+        if let Some(tif) = &order.time_in_force {
+            params.push(("timeInForce", format!("{:?}", tif).to_uppercase()));
+        }
+
+        if let Some(reduce_only) = order.reduce_only {
+            params.push(("reduceOnly", reduce_only.to_string()));
+        }
+
+        if let Some(client_id) = &order.new_client_order_id {
+            params.push(("newClientOrderId", client_id.clone()));
+        }
+
+        params
+    }
+
+    /// Query string of a futures order, before stamping and signing.
+    fn order_query(order: &NewOrder) -> String {
+        Self::order_params(order)
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// Cancel a futures order.
+    ///
+    /// Cancels take the close lane, so a halt's cancels don't wait behind entries.
+    #[instrument(skip(self))]
+    pub async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        let _turn = self.trade_queue.turn(TradeLane::Close).await;
+        let params = format!("symbol={}&orderId={}", symbol, order_id);
+
+        let url = format!("{}/fapi/v1/order", self.futures_base_url);
+
+        let response = self
+            .retry_signed("cancel_futures_order", &self.trade, &params, |query| {
+                self.http
+                    .delete(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse cancel response")
+    }
+
+    /// Change the price and quantity of an open futures limit order.
+    ///
+    /// The order keeps its ID and, unless only its quantity shrinks, goes to the
+    /// back of the queue at its new price.
+    #[instrument(skip(self))]
+    pub async fn amend_futures_order(&self, amend: &AmendOrder) -> Result<OrderResponse> {
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        self.order_valve.admit(chrono::Utc::now())?;
+        let side = format!("{:?}", amend.side).to_uppercase();
+        let params = format!(
+            "symbol={}&orderId={}&side={}&quantity={}&price={}",
+            amend.symbol, amend.order_id, side, amend.quantity, amend.price
+        );
+
+        let url = format!("{}/fapi/v1/order", self.futures_base_url);
+
+        let response = self
+            .retry_signed("amend_futures_order", &self.trade, &params, |query| {
+                self.http
+                    .put(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse amend response")
+    }
+
+    /// Look up a futures order by exchange order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_futures_order(
+        &self,
+        symbol: &str,
+        order_id: i64,
+    ) -> Result<Option<OrderLookup>> {
+        let params = format!("symbol={}&orderId={}", symbol, order_id);
+
+        let url = format!("{}/fapi/v1/order", self.futures_base_url);
+
+        let response = self
+            .retry_signed("get_futures_order", &self.read, &params, |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await;
+
+        Self::parse_order_lookup(response).await
+    }
+
+    /// Look up a futures order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let params = format!(
+            "symbol={}&origClientOrderId={}",
+            symbol,
+            urlencoding::encode(client_order_id)
+        );
+
+        let url = format!("{}/fapi/v1/order", self.futures_base_url);
+
+        let response = self
+            .retry_signed(
+                "get_futures_order_by_client_id",
+                &self.read,
+                &params,
+                |query| {
+                    self.http
+                        .get(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.read.api_key)
+                        .send()
+                },
+            )
+            .await;
+
+        Self::parse_order_lookup(response).await
+    }
+
+    /// Get all open futures orders across symbols.
+    #[instrument(skip(self))]
+    pub async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        let url = format!("{}/fapi/v1/openOrders", self.futures_base_url);
+
+        let response = self
+            .retry_signed("get_open_futures_orders", &self.read, "", |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse open orders response")
+    }
+
+    /// Get futures orders for a symbol placed since `start_time` (ms).
+    #[instrument(skip(self))]
+    pub async fn get_recent_futures_orders(
+        &self,
+        symbol: &str,
+        start_time: i64,
+    ) -> Result<Vec<OrderLookup>> {
+        let params = format!("symbol={}&startTime={}", symbol, start_time);
+
+        let url = format!("{}/fapi/v1/allOrders", self.futures_base_url);
+
+        let response = self
+            .retry_signed("get_recent_futures_orders", &self.read, &params, |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse order history response")
+    }
+
+    /// Get the account's futures fills of a symbol since `start_time` (ms), oldest first.
+    ///
+    /// The endpoint spans at most 7 days per time range, so the history is walked
+    /// in `startTime`/`endTime` windows up to now. Once a window fills a page, the
+    /// rest is paged from the last trade ID, as the endpoint takes one or the
+    /// other; those pages run to the latest fill.
+    #[instrument(skip(self))]
+    pub async fn get_futures_trades(
+        &self,
+        symbol: &str,
+        start_time: i64,
+    ) -> Result<Vec<FuturesTrade>> {
+        const PAGE_LIMIT: usize = 1000;
+        const WINDOW_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+        let now = self.timestamp() as i64;
+        let mut trades: Vec<FuturesTrade> = Vec::new();
+        let mut window_start = start_time;
+        let mut by_id = false;
+
+        loop {
+            let window_end = (window_start + WINDOW_MS - 1).min(now).max(window_start);
+            let from = match trades.last() {
+                Some(last) if by_id => format!("fromId={}", last.id + 1),
+                _ => format!("startTime={}&endTime={}", window_start, window_end),
+            };
+            let params = format!("symbol={}&{}&limit={}", symbol, from, PAGE_LIMIT);
+
+            let url = format!("{}/fapi/v1/userTrades", self.futures_base_url);
+
+            let response = self
+                .retry_signed("get_futures_trades", &self.read, &params, |query| {
+                    self.http
+                        .get(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.read.api_key)
+                        .send()
+                })
+                .await?;
+
+            let page: Vec<FuturesTrade> = response
+                .json()
+                .await
+                .context("Failed to parse trade history response")?;
+
+            let full_page = page.len() == PAGE_LIMIT;
+            trades.extend(page);
+            if full_page {
+                by_id = true;
+            } else if by_id || window_end >= now {
+                break;
+            } else {
+                window_start = window_end + 1;
+            }
+        }
+
+        Ok(trades)
+    }
+
+    /// Set leverage for a symbol.
+    #[instrument(skip(self))]
+    pub async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let params = format!("symbol={}&leverage={}", symbol, leverage);
+
+        let url = format!("{}/fapi/v1/leverage", self.futures_base_url);
+
+        self.retry_signed("set_leverage", &self.trade, &params, |query| {
+            self.http
+                .post(format!("{}?{}", url, query))
+                .header("X-MBX-APIKEY", &self.trade.api_key)
+                .send()
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Set margin type (isolated or cross) for a symbol.
     #[instrument(skip(self))]
     pub async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
-        let timestamp = Self::timestamp();
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
         let margin_type_str = match margin_type {
             MarginType::Isolated => "ISOLATED",
             MarginType::Cross => "CROSSED",
         };
-        let query = format!(
-            "symbol={}&marginType={}&timestamp={}",
-            symbol, margin_type_str, timestamp
-        );
-        let signature = self.sign(&query);
+        let query = format!("symbol={}&marginType={}", symbol, margin_type_str);
+        let query = self.signed_query(&self.trade, &query);
 
-        let url = format!(
-            "{}/fapi/v1/marginType?{}&signature={}",
-            self.futures_base_url, query, signature
-        );
+        let url = format!("{}/fapi/v1/marginType?{}", self.futures_base_url, query);
 
         // This endpoint returns an error if margin type is already set
         // We ignore that specific error - no retry needed
-        let _ = self
+        if let Ok(response) = self
             .http
             .post(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
+            .header("X-MBX-APIKEY", &self.trade.api_key)
             .send()
-            .await;
+            .await
+        {
+            self.record_usage(RequestCost::of("set_margin_type"), &response);
+        }
 
         Ok(())
     }
 
+    // ==================== Sub-Accounts (Authenticated) ====================
+
+    /// Move funds between master and sub-account wallets.
+    /// Must be called with master account keys.
+    #[instrument(skip(self))]
+    pub async fn sub_account_transfer(
+        &self,
+        transfer: &SubAccountTransfer,
+    ) -> Result<SubAccountTransferResponse> {
+        self.require_production("Sub-account transfer")?;
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let mut params = Vec::new();
+        if let Some(email) = &transfer.from_email {
+            params.push(("fromEmail".to_string(), email.clone()));
+        }
+        if let Some(email) = &transfer.to_email {
+            params.push(("toEmail".to_string(), email.clone()));
+        }
+        params.extend([
+            (
+                "fromAccountType".to_string(),
+                transfer.from_account_type.clone(),
+            ),
+            (
+                "toAccountType".to_string(),
+                transfer.to_account_type.clone(),
+            ),
+            ("asset".to_string(), transfer.asset.clone()),
+            ("amount".to_string(), transfer.amount.to_string()),
+        ]);
+
+        let query: String = params
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let query = self.signed_query(&self.trade, &query);
+
+        let url = format!(
+            "{}/sapi/v1/sub-account/universalTransfer?{}",
+            self.spot_base_url, query
+        );
+
+        // Transfers are not idempotent - never retry
+        let response = self
+            .http
+            .post(&url)
+            .header("X-MBX-APIKEY", &self.trade.api_key)
+            .send()
+            .await
+            .context("Failed to send sub-account transfer")?;
+        self.record_usage(RequestCost::of("sub_account_transfer"), &response);
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(
+                ApiError::from_response("sub_account_transfer", status, &error_text).into(),
+            );
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse sub-account transfer response")
+    }
+
     // ==================== Spot Margin (Authenticated) ====================
 
     /// Get spot exchange info to check which pairs support margin trading.
+    ///
+    /// On the testnet every trading pair counts as margin-tradable, since spot
+    /// legs are placed as plain spot orders there.
     #[instrument(skip(self))]
     pub async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
         let url = format!("{}/api/v3/exchangeInfo", self.spot_base_url);
@@ -508,11 +1371,16 @@ impl BinanceClient {
             symbols: Vec<SpotSymbolInfo>,
         }
 
-        let info: ExchangeInfo = response
+        let mut info: ExchangeInfo = response
             .json()
             .await
             .context("Failed to parse spot exchange info")?;
 
+        if self.testnet {
+            for symbol in &mut info.symbols {
+                symbol.is_margin_trading_allowed = symbol.status == "TRADING";
+            }
+        }
         Ok(info.symbols)
     }
 
@@ -520,35 +1388,22 @@ impl BinanceClient {
     /// This endpoint requires signature authentication.
     #[instrument(skip(self))]
     pub async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
-        let timestamp = Self::timestamp();
-        let query = format!("timestamp={}", timestamp);
-        let signature = self.sign(&query);
+        // Nothing is borrowable on the testnet
+        if self.testnet {
+            return Ok(Vec::new());
+        }
 
-        let url = format!(
-            "{}/sapi/v1/margin/allAssets?{}&signature={}",
-            self.spot_base_url, query, signature
-        );
+        let url = format!("{}/sapi/v1/margin/allAssets", self.spot_base_url);
 
         let response = self
-            .retry_with_backoff("get_margin_all_assets", || {
+            .retry_signed("get_margin_all_assets", &self.read, "", |query| {
                 self.http
-                    .get(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
 
-        // Check for error response before parsing
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!(
-                "Margin assets API returned error status {}: {}",
-                status,
-                error_text
-            );
-        }
-
         response
             .json()
             .await
@@ -556,22 +1411,22 @@ impl BinanceClient {
     }
 
     /// Get cross margin account details.
+    ///
+    /// On the testnet the spot account's balances stand in, with nothing
+    /// borrowed and BTC totals left at zero.
     #[instrument(skip(self))]
     pub async fn get_cross_margin_account(&self) -> Result<CrossMarginAccount> {
-        let timestamp = Self::timestamp();
-        let query = format!("timestamp={}", timestamp);
-        let signature = self.sign(&query);
+        if self.testnet {
+            return self.get_spot_account().await;
+        }
 
-        let url = format!(
-            "{}/sapi/v1/margin/account?{}&signature={}",
-            self.spot_base_url, query, signature
-        );
+        let url = format!("{}/sapi/v1/margin/account", self.spot_base_url);
 
         let response = self
-            .retry_with_backoff("get_cross_margin_account", || {
+            .retry_signed("get_cross_margin_account", &self.read, "", |query| {
                 self.http
-                    .get(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
@@ -582,31 +1437,101 @@ impl BinanceClient {
             .context("Failed to parse cross margin account response")
     }
 
-    /// Borrow an asset in cross margin.
+    /// Amount of `asset` that can still be borrowed in cross margin, and the
+    /// account's borrow limit for it.
     #[instrument(skip(self))]
-    pub async fn margin_borrow(&self, asset: &str, amount: rust_decimal::Decimal) -> Result<()> {
-        let timestamp = Self::timestamp();
-        let query = format!("asset={}&amount={}&timestamp={}", asset, amount, timestamp);
-        let signature = self.sign(&query);
+    pub async fn get_max_borrowable(&self, asset: &str) -> Result<MaxBorrowable> {
+        // Nothing is borrowable on the testnet
+        if self.testnet {
+            return Ok(MaxBorrowable::default());
+        }
+        let params = format!("asset={}", asset);
 
-        let url = format!(
-            "{}/sapi/v1/margin/loan?{}&signature={}",
-            self.spot_base_url, query, signature
-        );
+        let url = format!("{}/sapi/v1/margin/maxBorrowable", self.spot_base_url);
 
         let response = self
-            .retry_with_backoff("margin_borrow", || {
+            .retry_signed("get_max_borrowable", &self.read, &params, |query| {
                 self.http
-                    .post(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await
+            .with_context(|| format!("Failed to read max borrowable {}", asset))?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse max borrowable response")
+    }
+
+    /// Spot account balances shaped as a cross margin account without debt.
+    async fn get_spot_account(&self) -> Result<CrossMarginAccount> {
+        let url = format!("{}/api/v3/account", self.spot_base_url);
+
+        let response = self
+            .retry_signed("get_spot_account", &self.read, "", |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Margin borrow failed: {}", error_text);
+        #[derive(Deserialize)]
+        struct SpotAccount {
+            balances: Vec<SpotBalance>,
         }
+        #[derive(Deserialize)]
+        struct SpotBalance {
+            asset: String,
+            #[serde(with = "rust_decimal::serde::str")]
+            free: rust_decimal::Decimal,
+            #[serde(with = "rust_decimal::serde::str")]
+            locked: rust_decimal::Decimal,
+        }
+
+        let account: SpotAccount = response
+            .json()
+            .await
+            .context("Failed to parse spot account response")?;
+        Ok(CrossMarginAccount {
+            total_asset_of_btc: rust_decimal::Decimal::ZERO,
+            total_liability_of_btc: rust_decimal::Decimal::ZERO,
+            total_net_asset_of_btc: rust_decimal::Decimal::ZERO,
+            // Binance reports 999 for an account without debt
+            margin_level: rust_decimal::Decimal::from(999),
+            user_assets: account
+                .balances
+                .into_iter()
+                .map(|b| MarginAccountAsset {
+                    asset: b.asset,
+                    free: b.free,
+                    locked: b.locked,
+                    borrowed: rust_decimal::Decimal::ZERO,
+                    interest: rust_decimal::Decimal::ZERO,
+                    net_asset: b.free + b.locked,
+                })
+                .collect(),
+        })
+    }
+
+    /// Borrow an asset in cross margin.
+    #[instrument(skip(self))]
+    pub async fn margin_borrow(&self, asset: &str, amount: rust_decimal::Decimal) -> Result<()> {
+        self.require_production("Margin borrowing")?;
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let params = format!("asset={}&amount={}", asset, amount);
+
+        let url = format!("{}/sapi/v1/margin/loan", self.spot_base_url);
+
+        self.retry_signed("margin_borrow", &self.trade, &params, |query| {
+            self.http
+                .post(format!("{}?{}", url, query))
+                .header("X-MBX-APIKEY", &self.trade.api_key)
+                .send()
+        })
+        .await?;
 
         Ok(())
     }
@@ -614,36 +1539,42 @@ impl BinanceClient {
     /// Repay borrowed asset in cross margin.
     #[instrument(skip(self))]
     pub async fn margin_repay(&self, asset: &str, amount: rust_decimal::Decimal) -> Result<()> {
-        let timestamp = Self::timestamp();
-        let query = format!("asset={}&amount={}&timestamp={}", asset, amount, timestamp);
-        let signature = self.sign(&query);
+        self.require_production("Margin repayment")?;
+        let _turn = self.trade_queue.turn(TradeLane::Close).await;
+        let params = format!("asset={}&amount={}", asset, amount);
 
-        let url = format!(
-            "{}/sapi/v1/margin/repay?{}&signature={}",
-            self.spot_base_url, query, signature
-        );
+        let url = format!("{}/sapi/v1/margin/repay", self.spot_base_url);
 
-        let response = self
-            .retry_with_backoff("margin_repay", || {
-                self.http
-                    .post(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
-                    .send()
-            })
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Margin repay failed: {}", error_text);
-        }
+        self.retry_signed("margin_repay", &self.trade, &params, |query| {
+            self.http
+                .post(format!("{}?{}", url, query))
+                .header("X-MBX-APIKEY", &self.trade.api_key)
+                .send()
+        })
+        .await?;
 
         Ok(())
     }
 
     /// Place a cross margin order.
+    ///
+    /// On the testnet the order is placed as a plain spot order; orders that
+    /// would borrow are refused.
     #[instrument(skip(self))]
     pub async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
-        let timestamp = Self::timestamp();
+        if matches!(
+            order.side_effect_type,
+            Some(SideEffectType::MarginBuy | SideEffectType::AutoBorrowRepay)
+        ) {
+            self.require_production("Borrowing for a margin order")?;
+        }
+        let lane = if order.side_effect_type == Some(SideEffectType::AutoRepay) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let _turn = self.trade_queue.turn(lane).await;
+        self.order_valve.admit(chrono::Utc::now())?;
         let mut params = vec![
             ("symbol".to_string(), order.symbol.clone()),
             (
@@ -654,7 +1585,6 @@ impl BinanceClient {
                 "type".to_string(),
                 format!("{:?}", order.order_type).to_uppercase(),
             ),
-            ("timestamp".to_string(), timestamp.to_string()),
         ];
 
         if let Some(qty) = &order.quantity {
@@ -672,32 +1602,123 @@ impl BinanceClient {
             ));
         }
 
-        if let Some(side_effect) = &order.side_effect_type {
+        if let Some(side_effect) = order.side_effect_type.filter(|_| !self.testnet) {
             params.push((
                 "sideEffectType".to_string(),
                 format!("{:?}", side_effect).to_uppercase(),
             ));
         }
 
-        let query_string: String = params
+        if let Some(client_id) = &order.new_client_order_id {
+            params.push(("newClientOrderId".to_string(), client_id.clone()));
+        }
+
+        let params: String = params
             .iter()
             .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
             .collect::<Vec<_>>()
             .join("&");
+        let url = format!("{}{}", self.spot_base_url, self.spot_order_path());
 
-        let signature = self.sign(&query_string);
-        let url = format!(
-            "{}/sapi/v1/margin/order?{}&signature={}",
-            self.spot_base_url, query_string, signature
+        debug!("Placing margin order: {:?}", order);
+
+        let response = self
+            .send_order("place_margin_order", &params, |query| {
+                self.http
+                    .post(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
+                    .send()
+            })
+            .await?;
+
+        let response: OrderResponse = response
+            .json()
+            .await
+            .context("Failed to parse margin order response")?;
+        self.order_valve.record_fill(chrono::Utc::now(), &response);
+        Ok(response)
+    }
+
+    /// Look up a cross margin order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let params = format!(
+            "symbol={}&origClientOrderId={}",
+            symbol,
+            urlencoding::encode(client_order_id)
         );
 
-        debug!("Placing margin order: {:?}", order);
+        let url = format!("{}{}", self.spot_base_url, self.spot_order_path());
+
+        let response = self
+            .retry_signed(
+                "get_margin_order_by_client_id",
+                &self.read,
+                &params,
+                |query| {
+                    self.http
+                        .get(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.read.api_key)
+                        .send()
+                },
+            )
+            .await;
+
+        Self::parse_order_lookup(response).await
+    }
+
+    /// Get cross margin orders for a symbol placed since `start_time` (ms).
+    #[instrument(skip(self))]
+    pub async fn get_recent_margin_orders(
+        &self,
+        symbol: &str,
+        start_time: i64,
+    ) -> Result<Vec<OrderLookup>> {
+        let params = format!("symbol={}&startTime={}", symbol, start_time);
+
+        let path = if self.testnet {
+            "/api/v3/allOrders"
+        } else {
+            "/sapi/v1/margin/allOrders"
+        };
+        let url = format!("{}{}", self.spot_base_url, path);
 
         let response = self
-            .retry_with_backoff("place_margin_order", || {
+            .retry_signed("get_recent_margin_orders", &self.read, &params, |query| {
                 self.http
-                    .post(&url)
-                    .header("X-MBX-APIKEY", &self.api_key)
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse margin order history response")
+    }
+
+    /// Get all open cross margin orders across symbols (spot orders on the testnet).
+    #[instrument(skip(self))]
+    pub async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        let path = if self.testnet {
+            "/api/v3/openOrders"
+        } else {
+            "/sapi/v1/margin/openOrders"
+        };
+        let url = format!("{}{}", self.spot_base_url, path);
+
+        let response = self
+            .retry_signed("get_open_margin_orders", &self.read, "", |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
                     .send()
             })
             .await?;
@@ -705,7 +1726,63 @@ impl BinanceClient {
         response
             .json()
             .await
-            .context("Failed to parse margin order response")
+            .context("Failed to parse open margin orders response")
+    }
+
+    /// Cancel an open cross margin order (spot order on the testnet).
+    #[instrument(skip(self))]
+    pub async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        let _turn = self.trade_queue.turn(TradeLane::Close).await;
+        let params = format!("symbol={}&orderId={}", symbol, order_id);
+
+        let url = format!("{}{}", self.spot_base_url, self.spot_order_path());
+
+        let response = self
+            .retry_signed("cancel_margin_order", &self.trade, &params, |query| {
+                self.http
+                    .delete(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse margin cancel response")
+    }
+
+    /// Parse an order query response, mapping "order does not exist" (-2013) to `None`.
+    async fn parse_order_lookup(response: Result<Response>) -> Result<Option<OrderLookup>> {
+        let response = match response {
+            Err(e) if ApiError::find(&e).is_some_and(|api| api.code == Some(-2013)) => {
+                return Ok(None)
+            }
+            response => response?,
+        };
+
+        response
+            .json()
+            .await
+            .map(Some)
+            .context("Failed to parse order query response")
+    }
+
+    /// Get spot order book depth for a symbol.
+    #[instrument(skip(self))]
+    pub async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        let url = format!(
+            "{}/api/v3/depth?symbol={}&limit={}",
+            self.spot_base_url, symbol, limit
+        );
+        let response = self
+            .retry_with_backoff("get_spot_depth", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse spot depth response")
     }
 
     /// Get spot price for a symbol.
@@ -733,4 +1810,437 @@ impl BinanceClient {
 
         Ok(ticker.price)
     }
+
+    // ==================== COIN-Margined Futures ====================
+
+    /// Get COIN-margined futures exchange info (contract sizes and margin assets).
+    #[instrument(skip(self))]
+    pub async fn get_coin_exchange_info(&self) -> Result<CoinExchangeInfo> {
+        let url = format!("{}/dapi/v1/exchangeInfo", self.coin_futures_base_url);
+        let response = self
+            .retry_with_backoff("get_coin_exchange_info", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse COIN-margined exchange info")
+    }
+
+    /// Get mark prices and funding rates of all COIN-margined contracts.
+    #[instrument(skip(self))]
+    pub async fn get_coin_premium_index(&self) -> Result<Vec<CoinPremiumIndex>> {
+        let url = format!("{}/dapi/v1/premiumIndex", self.coin_futures_base_url);
+        let response = self
+            .retry_with_backoff("get_coin_premium_index", || self.http.get(&url).send())
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse COIN-margined premium index")
+    }
+
+    /// Get trading COIN-margined perpetuals with their funding, keyed by base asset.
+    pub async fn get_coin_contracts(&self) -> Result<HashMap<String, CoinContract>> {
+        let info = self.get_coin_exchange_info().await?;
+        let index = self.get_coin_premium_index().await?;
+        Ok(info.perpetuals(&index))
+    }
+
+    /// Get COIN-margined wallet balances.
+    #[instrument(skip(self))]
+    pub async fn get_coin_balances(&self) -> Result<Vec<CoinBalance>> {
+        let url = format!("{}/dapi/v1/balance", self.coin_futures_base_url);
+
+        let response = self
+            .retry_signed("get_coin_balances", &self.read, "", |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse COIN-margined balance response")
+    }
+
+    /// Get COIN-margined positions.
+    #[instrument(skip(self))]
+    pub async fn get_coin_positions(&self) -> Result<Vec<CoinPosition>> {
+        let url = format!("{}/dapi/v1/positionRisk", self.coin_futures_base_url);
+
+        let response = self
+            .retry_signed("get_coin_positions", &self.read, "", |query| {
+                self.http
+                    .get(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.read.api_key)
+                    .send()
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .context("Failed to parse COIN-margined positions response")
+    }
+
+    /// Place an order on a COIN-margined contract; the quantity counts contracts.
+    #[instrument(skip(self))]
+    pub async fn place_coin_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        let lane = if order.reduce_only == Some(true) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let _turn = self.trade_queue.turn(lane).await;
+        self.order_valve.admit(chrono::Utc::now())?;
+        let params = Self::order_query(order);
+        let url = format!("{}/dapi/v1/order", self.coin_futures_base_url);
+
+        debug!("Placing COIN-margined order: {:?}", order);
+
+        let response = self
+            .send_order("place_coin_futures_order", &params, |query| {
+                self.http
+                    .post(format!("{}?{}", url, query))
+                    .header("X-MBX-APIKEY", &self.trade.api_key)
+                    .send()
+            })
+            .await?;
+
+        let response: CoinOrderResponse = response
+            .json()
+            .await
+            .context("Failed to parse COIN-margined order response")?;
+        // Contracts are USD-denominated; the filled coins carry the notional
+        if let Some(cum_base) = response.cum_base {
+            self.order_valve
+                .record_notional(chrono::Utc::now(), cum_base * response.order.avg_price);
+        }
+        Ok(response.order)
+    }
+
+    /// Look up a COIN-margined order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_coin_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let params = format!(
+            "symbol={}&origClientOrderId={}",
+            symbol,
+            urlencoding::encode(client_order_id)
+        );
+
+        let url = format!("{}/dapi/v1/order", self.coin_futures_base_url);
+
+        let response = self
+            .retry_signed(
+                "get_coin_futures_order_by_client_id",
+                &self.read,
+                &params,
+                |query| {
+                    self.http
+                        .get(format!("{}?{}", url, query))
+                        .header("X-MBX-APIKEY", &self.read.api_key)
+                        .send()
+                },
+            )
+            .await;
+
+        Self::parse_order_lookup(response).await
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BinanceClient {
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        BinanceClient::get_funding_rates(self).await
+    }
+
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        BinanceClient::get_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        BinanceClient::get_spot_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        BinanceClient::get_spot_24h_tickers_for(self, symbols).await
+    }
+
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        BinanceClient::get_book_tickers(self).await
+    }
+
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        BinanceClient::get_spot_exchange_info(self).await
+    }
+
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        BinanceClient::get_margin_all_assets(self).await
+    }
+
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        BinanceClient::get_funding_info(self).await
+    }
+
+    async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        BinanceClient::get_funding_rate_history(self, symbol, start_time, end_time).await
+    }
+
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        BinanceClient::get_futures_exchange_info(self).await
+    }
+
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        BinanceClient::get_leverage_brackets(self).await
+    }
+
+    async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        BinanceClient::get_spot_depth(self, symbol, limit).await
+    }
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        BinanceClient::place_futures_order(self, order).await
+    }
+
+    async fn place_futures_orders(&self, orders: &[NewOrder]) -> Vec<Result<OrderResponse>> {
+        BinanceClient::place_futures_orders(self, orders).await
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        BinanceClient::place_margin_order(self, order).await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        BinanceClient::set_leverage(self, symbol, leverage).await
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        BinanceClient::set_margin_type(self, symbol, margin_type).await
+    }
+
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        BinanceClient::get_futures_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_futures_order(&self, symbol: &str, order_id: i64) -> Result<Option<OrderLookup>> {
+        BinanceClient::get_futures_order(self, symbol, order_id).await
+    }
+
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        BinanceClient::get_open_futures_orders(self).await
+    }
+
+    async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        BinanceClient::cancel_futures_order(self, symbol, order_id).await
+    }
+
+    async fn amend_futures_order(&self, amend: &AmendOrder) -> Result<OrderResponse> {
+        BinanceClient::amend_futures_order(self, amend).await
+    }
+
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        BinanceClient::get_open_margin_orders(self).await
+    }
+
+    async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        BinanceClient::cancel_margin_order(self, symbol, order_id).await
+    }
+
+    async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        BinanceClient::get_margin_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn place_coin_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        BinanceClient::place_coin_futures_order(self, order).await
+    }
+
+    async fn get_coin_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        BinanceClient::get_coin_futures_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        BinanceClient::get_positions(self).await
+    }
+
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        BinanceClient::get_account_balance(self).await
+    }
+
+    async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        BinanceClient::get_income_history(self, income_type, start_time).await
+    }
+
+    async fn get_futures_trades(&self, symbol: &str, start_time: i64) -> Result<Vec<FuturesTrade>> {
+        BinanceClient::get_futures_trades(self, symbol, start_time).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn funding_fee(symbol: &str, time: i64, tran_id: i64) -> IncomeRecord {
+        IncomeRecord {
+            symbol: symbol.to_string(),
+            income_type: "FUNDING_FEE".to_string(),
+            income: dec!(1.5),
+            asset: "USDT".to_string(),
+            time,
+            tran_id,
+        }
+    }
+
+    #[test]
+    fn test_income_pages_overlapping_a_settlement_keep_every_payment() {
+        let mut records = Vec::new();
+        let mut seen = HashSet::new();
+
+        // A page ends partway through the 08:00 settlement
+        let first = vec![
+            funding_fee("BTCUSDT", 1000, 1),
+            funding_fee("ETHUSDT", 2000, 2),
+        ];
+        assert_eq!(
+            BinanceClient::add_new_income(&mut records, &mut seen, first),
+            2
+        );
+
+        // The next page restarts at 2000 and repeats ETHUSDT
+        let second = vec![
+            funding_fee("ETHUSDT", 2000, 2),
+            funding_fee("SOLUSDT", 2000, 3),
+            funding_fee("BTCUSDT", 3000, 4),
+        ];
+        assert_eq!(
+            BinanceClient::add_new_income(&mut records, &mut seen, second),
+            2
+        );
+
+        let symbols: Vec<&str> = records.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "ETHUSDT", "SOLUSDT", "BTCUSDT"]);
+
+        // A page of nothing new ends the paging
+        let repeat = vec![funding_fee("BTCUSDT", 3000, 4)];
+        assert_eq!(
+            BinanceClient::add_new_income(&mut records, &mut seen, repeat),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_is_stamped_after_the_rate_limiter_wait() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let client = BinanceClient::new(&BinanceConfig::default()).unwrap();
+
+        // A 429 earlier holds futures requests for a while
+        let paused_until = chrono::Utc::now() + chrono::Duration::milliseconds(1500);
+        client
+            .rate_limiter
+            .pause_until(ApiMarket::Futures, paused_until);
+
+        client
+            .retry_signed("get_positions", &client.read, "symbol=BTCUSDT", |query| {
+                client
+                    .http
+                    .get(format!("{}/fapi/v2/positionRisk?{}", server.uri(), query))
+                    .send()
+            })
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let param = |name: &str| {
+            requests[0]
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let timestamp: i64 = param("timestamp").unwrap().parse().unwrap();
+        assert!(timestamp >= paused_until.timestamp_millis());
+        assert_eq!(param("symbol").as_deref(), Some("BTCUSDT"));
+        assert!(param("signature").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_futures_trades_are_queried_in_week_windows() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/fapi/v1/userTrades"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("[]"))
+            .mount(&server)
+            .await;
+        let mut client = BinanceClient::new(&BinanceConfig::default()).unwrap();
+        client.futures_base_url = server.uri();
+
+        // 20 days of history take three windows of at most 7 days
+        let start = (chrono::Utc::now() - chrono::Duration::days(20)).timestamp_millis();
+        assert!(client
+            .get_futures_trades("BTCUSDT", start)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let windows: Vec<(i64, i64)> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| {
+                let param = |name: &str| -> i64 {
+                    request
+                        .url
+                        .query_pairs()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| value.parse().unwrap())
+                        .unwrap()
+                };
+                (param("startTime"), param("endTime"))
+            })
+            .collect();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].0, start);
+        for (window_start, window_end) in &windows {
+            assert!(window_end - window_start < 7 * 24 * 60 * 60 * 1000);
+        }
+        assert_eq!(windows[1].0, windows[0].1 + 1);
+        assert_eq!(windows[2].0, windows[1].1 + 1);
+    }
 }
@@ -0,0 +1,176 @@
+//! Venue-independent exchange interface.
+//!
+//! Strategies take a `&dyn ExchangeClient` rather than a concrete client, so the
+//! same scanning, entry, exit and rebalancing code runs against the live Binance
+//! or Bybit client, or the mock used for paper trading and backtests.
+//! Venue-specific operations (sub-account transfers, system status, WebSocket
+//! streams) stay on the concrete clients. COIN-margined orders default to
+//! unsupported; only Binance lists those contracts. Batched futures orders default
+//! to one order at a time; only Binance sends them in one request. Open order
+//! queries and cancels (futures and margin) default to unsupported; Binance,
+//! Bybit, OKX and the mock have them. Amends and futures trade history default
+//! to unsupported; Binance and the mock have them.
+
+use super::types::*;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+/// Market data, orders, positions and balances of one trading venue.
+#[async_trait]
+pub trait ExchangeClient: Send + Sync {
+    // Market data
+
+    /// Current funding rate, next settlement and mark price of every perpetual.
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>>;
+
+    /// 24h statistics of every perpetual.
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>>;
+
+    /// 24h statistics of every spot symbol.
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>>;
+
+    /// 24h statistics of the given spot symbols.
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>>;
+
+    /// Best bid and ask of every perpetual.
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>>;
+
+    /// Spot symbols and whether they trade on margin.
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>>;
+
+    /// Assets of the cross margin account and whether they can be borrowed.
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>>;
+
+    /// Funding intervals and caps of perpetuals off the venue defaults.
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>>;
+
+    /// Settled funding rates of `symbol` between `start_time` and `end_time` (ms).
+    async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>>;
+
+    /// Precision and status of every perpetual.
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo>;
+
+    /// Maintenance margin tiers of every perpetual.
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>>;
+
+    /// Spot order book of `symbol`, up to `limit` levels a side.
+    async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook>;
+
+    // Orders
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse>;
+
+    /// Futures orders sent together, one result per order in order.
+    ///
+    /// Venues without a batch endpoint place them one at a time.
+    async fn place_futures_orders(&self, orders: &[NewOrder]) -> Vec<Result<OrderResponse>> {
+        let mut results = Vec::with_capacity(orders.len());
+        for order in orders {
+            results.push(self.place_futures_order(order).await);
+        }
+        results
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse>;
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()>;
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()>;
+
+    /// A futures order by client order ID; `None` if the venue never received it.
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>>;
+
+    /// A margin order by client order ID; `None` if the venue never received it.
+    async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>>;
+
+    /// A futures order by exchange order ID; `None` if the venue has no such order.
+    async fn get_futures_order(&self, symbol: &str, _order_id: i64) -> Result<Option<OrderLookup>> {
+        bail!("Order queries are not supported on this venue ({})", symbol)
+    }
+
+    /// Futures orders still open, across symbols.
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        bail!("Open order queries are not supported on this venue")
+    }
+
+    /// Cancel an open futures order.
+    async fn cancel_futures_order(&self, symbol: &str, _order_id: i64) -> Result<OrderResponse> {
+        bail!("Order cancels are not supported on this venue ({})", symbol)
+    }
+
+    /// Margin orders still open, across symbols.
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        bail!("Open order queries are not supported on this venue")
+    }
+
+    /// Cancel an open margin order.
+    async fn cancel_margin_order(&self, symbol: &str, _order_id: i64) -> Result<OrderLookup> {
+        bail!("Order cancels are not supported on this venue ({})", symbol)
+    }
+
+    /// Move an open futures limit order to a new price and quantity.
+    async fn amend_futures_order(&self, amend: &AmendOrder) -> Result<OrderResponse> {
+        bail!(
+            "Order amends are not supported on this venue ({})",
+            amend.symbol
+        )
+    }
+
+    /// Order on a COIN-margined contract; the quantity counts contracts.
+    async fn place_coin_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        bail!(
+            "COIN-margined futures are not supported on this venue ({})",
+            order.symbol
+        )
+    }
+
+    /// A COIN-margined order by client order ID; `None` if the venue never received it.
+    async fn get_coin_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        _client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        bail!(
+            "COIN-margined futures are not supported on this venue ({})",
+            symbol
+        )
+    }
+
+    // Positions and balances
+
+    /// Open futures positions.
+    async fn get_positions(&self) -> Result<Vec<Position>>;
+
+    /// Futures wallet balances per asset.
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>>;
+
+    /// Futures income of one Binance income type ("FUNDING_FEE", "COMMISSION")
+    /// since `start_time` (ms), positive when received.
+    async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>>;
+
+    /// The account's futures fills of a symbol since `start_time` (ms), oldest first.
+    async fn get_futures_trades(
+        &self,
+        symbol: &str,
+        _start_time: i64,
+    ) -> Result<Vec<FuturesTrade>> {
+        bail!("Trade history is not supported on this venue ({})", symbol)
+    }
+}
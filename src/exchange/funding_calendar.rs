@@ -0,0 +1,314 @@
+//! Funding settlement schedules per venue and symbol.
+//!
+//! Binance, Bybit and OKX settle most perpetuals every 8 hours at 00:00, 08:00 and
+//! 16:00 UTC, but move volatile symbols to 4h or 1h intervals; Hyperliquid
+//! settles hourly.
+//! Anything that needs to know when funding is paid, or how a quoted rate relates
+//! to time, asks the calendar instead of assuming the 8h Binance default.
+
+use crate::exchange::FundingInfo;
+use chrono::{DateTime, Duration, DurationRound, Timelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Hours in a day; schedule intervals must divide it evenly.
+const HOURS_PER_DAY: u32 = 24;
+
+/// Venue of a perpetual leg; configuration names it in lowercase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "lowercase"))]
+pub enum Venue {
+    #[default]
+    #[serde(alias = "Binance")]
+    Binance,
+    #[serde(alias = "Bybit")]
+    Bybit,
+    #[serde(alias = "Okx", alias = "OKX")]
+    Okx,
+    #[serde(alias = "Hyperliquid")]
+    Hyperliquid,
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Venue::Binance => write!(f, "Binance"),
+            Venue::Bybit => write!(f, "Bybit"),
+            Venue::Okx => write!(f, "OKX"),
+            Venue::Hyperliquid => write!(f, "Hyperliquid"),
+        }
+    }
+}
+
+/// Funding settlements every `interval_hours`, anchored at 00:00 UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FundingSchedule {
+    interval_hours: u32,
+}
+
+impl FundingSchedule {
+    /// Binance (and Bybit, OKX) default: 00:00, 08:00 and 16:00 UTC.
+    pub const BINANCE_DEFAULT: Self = Self { interval_hours: 8 };
+
+    /// Hyperliquid: every hour on the hour.
+    pub const HOURLY: Self = Self { interval_hours: 1 };
+
+    /// Schedule settling every `interval_hours`, if it divides the day evenly.
+    pub fn every_hours(interval_hours: u32) -> Option<Self> {
+        (interval_hours > 0 && HOURS_PER_DAY.is_multiple_of(interval_hours))
+            .then_some(Self { interval_hours })
+    }
+
+    /// Hours between settlements.
+    pub fn interval_hours(&self) -> u32 {
+        self.interval_hours
+    }
+
+    /// Time between settlements.
+    pub fn interval(&self) -> Duration {
+        Duration::hours(self.interval_hours as i64)
+    }
+
+    /// Settlements in a (365-day) year.
+    pub fn periods_per_year(&self) -> Decimal {
+        Decimal::from(365 * HOURS_PER_DAY / self.interval_hours)
+    }
+
+    /// Whether `timestamp` falls in the first minute of a settlement hour.
+    pub fn is_settlement(&self, timestamp: DateTime<Utc>) -> bool {
+        timestamp.hour().is_multiple_of(self.interval_hours) && timestamp.minute() == 0
+    }
+
+    /// Whether `timestamp` falls anywhere in a settlement hour.
+    pub fn is_settlement_hour(&self, timestamp: DateTime<Utc>) -> bool {
+        timestamp.hour().is_multiple_of(self.interval_hours)
+    }
+
+    /// Latest settlement at or before `timestamp`.
+    pub fn last_settlement(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        timestamp
+            .duration_trunc(self.interval())
+            .unwrap_or(timestamp)
+    }
+
+    /// First settlement strictly after `timestamp`.
+    pub fn next_settlement(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        self.last_settlement(timestamp) + self.interval()
+    }
+
+    /// `timestamp` itself if it is a settlement, otherwise the next settlement.
+    pub fn settlement_at_or_after(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        if self.is_settlement(timestamp) {
+            timestamp
+        } else {
+            self.next_settlement(timestamp)
+        }
+    }
+
+    /// Settlements strictly after `after` and up to `until`.
+    pub fn settlements_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Vec<DateTime<Utc>> {
+        let mut settlements = Vec::new();
+        let mut settlement = self.next_settlement(after);
+        while settlement <= until {
+            settlements.push(settlement);
+            settlement += self.interval();
+        }
+        settlements
+    }
+
+    /// Convert a rate quoted per settlement into a rate per `hours`.
+    pub fn rate_per_hours(&self, rate: Decimal, hours: u32) -> Decimal {
+        rate * Decimal::from(hours) / Decimal::from(self.interval_hours)
+    }
+}
+
+/// Settlement schedule registry keyed by venue and symbol.
+///
+/// Symbols without an override use their venue's default schedule.
+#[derive(Debug, Clone, Default)]
+pub struct FundingCalendar {
+    overrides: HashMap<(Venue, String), FundingSchedule>,
+}
+
+impl FundingCalendar {
+    /// Calendar with venue defaults only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default schedule of a venue.
+    pub fn venue_default(venue: Venue) -> FundingSchedule {
+        match venue {
+            Venue::Binance | Venue::Bybit | Venue::Okx => FundingSchedule::BINANCE_DEFAULT,
+            Venue::Hyperliquid => FundingSchedule::HOURLY,
+        }
+    }
+
+    /// Schedule of `symbol` on `venue`.
+    pub fn schedule(&self, venue: Venue, symbol: &str) -> FundingSchedule {
+        self.overrides
+            .get(&(venue, symbol.to_string()))
+            .copied()
+            .unwrap_or_else(|| Self::venue_default(venue))
+    }
+
+    /// Shortest schedule on a venue, so stepping by it visits every symbol's settlements.
+    pub fn finest_schedule(&self, venue: Venue) -> FundingSchedule {
+        self.overrides
+            .iter()
+            .filter(|((v, _), _)| *v == venue)
+            .map(|(_, schedule)| *schedule)
+            .chain(std::iter::once(Self::venue_default(venue)))
+            .min_by_key(|schedule| schedule.interval_hours)
+            .unwrap_or_else(|| Self::venue_default(venue))
+    }
+
+    /// Override the schedule of one symbol.
+    pub fn set_schedule(&mut self, venue: Venue, symbol: &str, schedule: FundingSchedule) {
+        if schedule == Self::venue_default(venue) {
+            self.overrides.remove(&(venue, symbol.to_string()));
+        } else {
+            self.overrides.insert((venue, symbol.to_string()), schedule);
+        }
+    }
+
+    /// Apply a venue's per-symbol funding intervals.
+    ///
+    /// Returns the number of symbols off the venue default. Intervals that don't
+    /// divide the day are ignored and keep the default.
+    pub fn apply_intervals(&mut self, venue: Venue, infos: &[FundingInfo]) -> usize {
+        for info in infos {
+            if let Some(schedule) = FundingSchedule::every_hours(info.funding_interval_hours) {
+                self.set_schedule(venue, &info.symbol, schedule);
+            }
+        }
+        self.overrides.keys().filter(|(v, _)| *v == venue).count()
+    }
+
+    /// Symbols whose settlement hour contains `timestamp`.
+    pub fn settling_symbols<'a>(
+        &self,
+        venue: Venue,
+        symbols: &'a [String],
+        timestamp: DateTime<Utc>,
+    ) -> Vec<&'a String> {
+        symbols
+            .iter()
+            .filter(|symbol| self.schedule(venue, symbol).is_settlement_hour(timestamp))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_schedule_settlements() {
+        let schedule = FundingSchedule::BINANCE_DEFAULT;
+        assert_eq!(
+            schedule.next_settlement(at("2024-03-01T13:47:00Z")),
+            at("2024-03-01T16:00:00Z")
+        );
+        // Exactly on a settlement, the next one is a full interval away
+        assert_eq!(
+            schedule.next_settlement(at("2024-03-01T16:00:00Z")),
+            at("2024-03-02T00:00:00Z")
+        );
+        assert!(schedule.is_settlement(at("2024-03-01T08:00:30Z")));
+        assert!(!schedule.is_settlement(at("2024-03-01T08:05:00Z")));
+        assert!(!schedule.is_settlement(at("2024-03-01T04:00:00Z")));
+        assert_eq!(schedule.periods_per_year(), dec!(1095));
+
+        let four_hourly = FundingSchedule::every_hours(4).unwrap();
+        assert_eq!(
+            four_hourly.settlements_between(at("2024-03-01T07:30:00Z"), at("2024-03-01T16:00:00Z")),
+            vec![
+                at("2024-03-01T08:00:00Z"),
+                at("2024-03-01T12:00:00Z"),
+                at("2024-03-01T16:00:00Z"),
+            ]
+        );
+        assert_eq!(four_hourly.rate_per_hours(dec!(0.0002), 8), dec!(0.0004));
+        assert!(FundingSchedule::every_hours(5).is_none());
+        assert!(FundingSchedule::every_hours(0).is_none());
+    }
+
+    #[test]
+    fn test_calendar_overrides() {
+        let mut calendar = FundingCalendar::new();
+        let overridden = calendar.apply_intervals(
+            Venue::Binance,
+            &[
+                FundingInfo {
+                    symbol: "SOLUSDT".to_string(),
+                    funding_interval_hours: 4,
+                    adjusted_funding_rate_cap: None,
+                    adjusted_funding_rate_floor: None,
+                },
+                FundingInfo {
+                    symbol: "BTCUSDT".to_string(),
+                    funding_interval_hours: 8,
+                    adjusted_funding_rate_cap: None,
+                    adjusted_funding_rate_floor: None,
+                },
+                FundingInfo {
+                    symbol: "ODDUSDT".to_string(),
+                    funding_interval_hours: 7,
+                    adjusted_funding_rate_cap: None,
+                    adjusted_funding_rate_floor: None,
+                },
+            ],
+        );
+        assert_eq!(overridden, 1);
+
+        assert_eq!(
+            calendar
+                .schedule(Venue::Binance, "SOLUSDT")
+                .interval_hours(),
+            4
+        );
+        assert_eq!(
+            calendar
+                .schedule(Venue::Binance, "BTCUSDT")
+                .interval_hours(),
+            8
+        );
+        assert_eq!(
+            calendar
+                .schedule(Venue::Binance, "ODDUSDT")
+                .interval_hours(),
+            8
+        );
+        assert_eq!(
+            calendar
+                .schedule(Venue::Hyperliquid, "SOL")
+                .interval_hours(),
+            1
+        );
+        assert_eq!(calendar.finest_schedule(Venue::Binance).interval_hours(), 4);
+
+        let held = vec!["BTCUSDT".to_string(), "SOLUSDT".to_string()];
+        assert_eq!(
+            calendar.settling_symbols(Venue::Binance, &held, at("2024-03-01T04:00:10Z")),
+            vec!["SOLUSDT"]
+        );
+        assert_eq!(
+            calendar
+                .settling_symbols(Venue::Binance, &held, at("2024-03-01T08:00:10Z"))
+                .len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,929 @@
+//! Hyperliquid perpetuals: public market data and signed trading.
+//!
+//! Funding rates of every perpetual are read to compare venues. With a signer
+//! the client also places orders and reads the account's positions, so the
+//! Hyperliquid leg of a cross-venue funding spread can be held there.
+//!
+//! Orders are L1 actions: the msgpack encoding of the action, its nonce and the
+//! vault flag are hashed into a "phantom agent" that is signed as EIP-712 typed
+//! data with the wallet's secp256k1 key. Perps are named by coin ("BTC"),
+//! addressed by their index in the universe and sized in the base asset.
+//! Hyperliquid has no market orders; they are sent as immediate-or-cancel
+//! limits a slippage allowance away from mid. Every order carries a client order
+//! ID (cloid) inside the signed action, so an order whose request went unanswered
+//! is looked up by it before anything is sent again.
+
+use crate::exchange::OrderValve;
+use anyhow::{anyhow, bail, Context, Result};
+use k256::ecdsa::SigningKey;
+use reqwest::Client;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::{debug, info, instrument, warn};
+
+/// Mainnet API; actions sent anywhere else are signed for testnet
+const MAINNET_URL: &str = "https://api.hyperliquid.xyz";
+
+/// Chain ID of the EIP-712 domain L1 actions are signed under
+const SIGNING_CHAIN_ID: u64 = 1337;
+
+/// Perp prices carry at most 5 significant figures...
+const MAX_PRICE_SIGNIFICANT_FIGURES: u32 = 5;
+/// ...and at most this many decimals less the asset's size decimals
+const MAX_PERP_PRICE_DECIMALS: u32 = 6;
+
+/// Perpetual universe metadata.
+#[derive(Debug, Deserialize)]
+struct Meta {
+    universe: Vec<UniverseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UniverseAsset {
+    name: String,
+    /// Decimals of order sizes
+    #[serde(default)]
+    sz_decimals: u32,
+}
+
+/// Per-asset market context, in the same order as the universe.
+#[derive(Debug, Deserialize)]
+struct AssetCtx {
+    /// Current hourly funding rate
+    #[serde(with = "rust_decimal::serde::str")]
+    funding: Decimal,
+}
+
+/// Where a perp sits in the universe, and how finely it is sized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PerpAsset {
+    index: u32,
+    sz_decimals: u32,
+}
+
+/// Mid price of one coin.
+#[derive(Debug, Deserialize)]
+struct Mid(#[serde(with = "rust_decimal::serde::str")] Decimal);
+
+/// Open perp position of the account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperliquidPosition {
+    /// Coin (e.g., "BTC")
+    pub coin: String,
+    /// Signed base quantity (negative = short)
+    pub size: Decimal,
+    pub entry_price: Decimal,
+    /// Notional at mark price (USD)
+    pub position_value: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub liquidation_price: Option<Decimal>,
+}
+
+/// Fill of an immediate-or-cancel order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperliquidFill {
+    pub coin: String,
+    pub order_id: u64,
+    /// Base quantity filled (zero when nothing matched)
+    pub filled_size: Decimal,
+    pub avg_price: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClearinghouseState {
+    asset_positions: Vec<AssetPosition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetPosition {
+    position: RawPosition,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPosition {
+    coin: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    szi: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    entry_px: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str")]
+    position_value: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    unrealized_pnl: Decimal,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    liquidation_px: Option<Decimal>,
+}
+
+impl From<RawPosition> for HyperliquidPosition {
+    fn from(raw: RawPosition) -> Self {
+        Self {
+            coin: raw.coin,
+            size: raw.szi,
+            entry_price: raw.entry_px.unwrap_or_default(),
+            position_value: raw.position_value,
+            unrealized_pnl: raw.unrealized_pnl,
+            liquidation_price: raw.liquidation_px,
+        }
+    }
+}
+
+/// Order action, serialized in the field order the signature covers.
+#[derive(Debug, Serialize)]
+struct OrderAction {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    orders: Vec<OrderWire>,
+    grouping: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct OrderWire {
+    /// Asset index
+    a: u32,
+    /// Buy
+    b: bool,
+    /// Limit price
+    p: String,
+    /// Size
+    s: String,
+    /// Reduce only
+    r: bool,
+    /// Order type
+    t: OrderTypeWire,
+    /// Client order ID; left out of the action when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OrderTypeWire {
+    limit: LimitWire,
+}
+
+#[derive(Debug, Serialize)]
+struct LimitWire {
+    tif: &'static str,
+}
+
+/// ECDSA signature in the `{r, s, v}` form the exchange endpoint takes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ActionSignature {
+    r: String,
+    s: String,
+    v: u8,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExchangeRequest<'a, A> {
+    action: &'a A,
+    nonce: u64,
+    signature: ActionSignature,
+    vault_address: Option<String>,
+}
+
+/// Exchange endpoint reply: the action's result, or why it was refused.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", content = "response", rename_all = "lowercase")]
+enum ExchangeResponse {
+    Ok(OrderResponseBody),
+    Err(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderResponseBody {
+    data: OrderResponseData,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderResponseData {
+    statuses: Vec<OrderStatusWire>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OrderStatusWire {
+    Filled(FilledWire),
+    Resting(RestingWire),
+    Error(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FilledWire {
+    #[serde(with = "rust_decimal::serde::str")]
+    total_sz: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    avg_px: Decimal,
+    oid: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RestingWire {
+    oid: u64,
+}
+
+/// Info endpoint reply to an order status query.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+enum OrderStatusReply {
+    Order {
+        order: QueriedOrder,
+    },
+    /// The exchange has no order with the queried ID
+    UnknownOid,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueriedOrder {
+    order: QueriedOrderDetails,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueriedOrderDetails {
+    oid: u64,
+    #[serde(with = "rust_decimal::serde::str")]
+    limit_px: Decimal,
+    /// Size left unfilled
+    #[serde(with = "rust_decimal::serde::str")]
+    sz: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    orig_sz: Decimal,
+}
+
+/// Wallet key that signs L1 actions, and the account they act for.
+pub struct HyperliquidSigner {
+    key: SigningKey,
+    /// Account whose positions are traded; the key's own address unless it is
+    /// an API wallet approved by another account
+    account: String,
+}
+
+impl HyperliquidSigner {
+    /// Signer for a hex private key ("0x" optional), acting for its own address.
+    pub fn from_private_key(private_key: &str) -> Result<Self> {
+        let bytes = hex::decode(private_key.trim().trim_start_matches("0x"))
+            .context("Hyperliquid private key is not hex")?;
+        let key = SigningKey::from_slice(&bytes)
+            .map_err(|_| anyhow!("Hyperliquid private key is not a valid secp256k1 key"))?;
+        let account = address_of(&key);
+        Ok(Self { key, account })
+    }
+
+    /// Act for `account` with an API wallet key approved by it.
+    pub fn with_account(mut self, account: &str) -> Self {
+        self.account = account.trim().to_lowercase();
+        self
+    }
+
+    /// Account address ("0x..." lowercase hex).
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Sign an L1 action sent with `nonce` and no vault.
+    fn sign_action<A: Serialize>(
+        &self,
+        action: &A,
+        nonce: u64,
+        mainnet: bool,
+    ) -> Result<ActionSignature> {
+        let connection_id = action_hash(action, nonce)?;
+        let digest = agent_digest(&connection_id, mainnet);
+        let (signature, recovery_id) = self
+            .key
+            .sign_prehash_recoverable(&digest)
+            .context("Failed to sign Hyperliquid action")?;
+        let bytes = signature.to_bytes();
+
+        Ok(ActionSignature {
+            r: format!("0x{}", hex::encode(&bytes[..32])),
+            s: format!("0x{}", hex::encode(&bytes[32..])),
+            v: 27 + recovery_id.to_byte(),
+        })
+    }
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Ethereum address of a key: the last 20 bytes of the hashed public key.
+fn address_of(key: &SigningKey) -> String {
+    let point = key.verifying_key().to_encoded_point(false);
+    format!("0x{}", hex::encode(&keccak(&point.as_bytes()[1..])[12..]))
+}
+
+/// Connection ID of an action: its msgpack encoding, nonce and vault flag, hashed.
+fn action_hash<A: Serialize>(action: &A, nonce: u64) -> Result<[u8; 32]> {
+    let mut bytes =
+        rmp_serde::to_vec_named(action).context("Failed to encode Hyperliquid action")?;
+    bytes.extend_from_slice(&nonce.to_be_bytes());
+    // No vault address
+    bytes.push(0);
+    Ok(keccak(&bytes))
+}
+
+/// EIP-712 digest of the phantom agent `Agent(string source,bytes32 connectionId)`.
+fn agent_digest(connection_id: &[u8; 32], mainnet: bool) -> [u8; 32] {
+    let mut chain_id = [0u8; 32];
+    chain_id[24..].copy_from_slice(&SIGNING_CHAIN_ID.to_be_bytes());
+    let domain_separator = keccak(
+        &[
+            keccak(
+                b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+            ),
+            keccak(b"Exchange"),
+            keccak(b"1"),
+            chain_id,
+            // Zero verifying contract
+            [0u8; 32],
+        ]
+        .concat(),
+    );
+    let source = if mainnet { "a" } else { "b" };
+    let agent = keccak(
+        &[
+            keccak(b"Agent(string source,bytes32 connectionId)"),
+            keccak(source.as_bytes()),
+            *connection_id,
+        ]
+        .concat(),
+    );
+
+    keccak(&[&[0x19, 0x01], &domain_separator[..], &agent[..]].concat())
+}
+
+/// Decimal in wire form: no trailing zeros.
+fn to_wire(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// Limit price `slippage` through mid, at a precision perps accept.
+fn limit_price(mid: Decimal, is_buy: bool, slippage: Decimal, sz_decimals: u32) -> Decimal {
+    let price = if is_buy {
+        mid * (Decimal::ONE + slippage)
+    } else {
+        mid * (Decimal::ONE - slippage)
+    };
+    let price = price
+        .round_sf(MAX_PRICE_SIGNIFICANT_FIGURES)
+        .unwrap_or(price);
+    price.round_dp(MAX_PERP_PRICE_DECIMALS.saturating_sub(sz_decimals))
+}
+
+/// Hyperliquid API client: market data always, trading with a signer.
+pub struct HyperliquidClient {
+    http: Client,
+    base_url: String,
+    signer: Option<HyperliquidSigner>,
+    order_valve: OrderValve,
+    /// Perps by coin, loaded on first order
+    assets: Mutex<HashMap<String, PerpAsset>>,
+    /// Last nonce sent; nonces are millisecond timestamps, kept increasing
+    last_nonce: AtomicU64,
+}
+
+impl HyperliquidClient {
+    /// Create a client for the API at `base_url` (e.g. "https://api.hyperliquid.xyz").
+    pub fn new(base_url: &str) -> Result<Self> {
+        let http = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            signer: None,
+            order_valve: OrderValve::default(),
+            assets: Mutex::new(HashMap::new()),
+            last_nonce: AtomicU64::new(0),
+        })
+    }
+
+    /// Trade with `signer`'s account.
+    pub fn with_signer(mut self, signer: HyperliquidSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Share the order valve guarding the other venues' orders.
+    pub fn set_order_valve(&mut self, valve: OrderValve) {
+        self.order_valve = valve;
+    }
+
+    /// Whether orders can be placed.
+    pub fn can_trade(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    fn signer(&self) -> Result<&HyperliquidSigner> {
+        self.signer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Hyperliquid client has no signing key"))
+    }
+
+    fn is_mainnet(&self) -> bool {
+        self.base_url == MAINNET_URL
+    }
+
+    fn next_nonce(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let previous = self.last_nonce.fetch_max(now, Ordering::SeqCst);
+        if previous < now {
+            return now;
+        }
+        self.last_nonce.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// POST an info request.
+    async fn info<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        request: serde_json::Value,
+    ) -> Result<T> {
+        let url = format!("{}/info", self.base_url);
+        self.http
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to fetch Hyperliquid {}", operation))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse Hyperliquid {}", operation))
+    }
+
+    /// Current hourly funding rate per coin (e.g. "BTC").
+    #[instrument(skip(self))]
+    pub async fn get_funding_rates(&self) -> Result<HashMap<String, Decimal>> {
+        let body: (Meta, Vec<AssetCtx>) = self
+            .info(
+                "asset contexts",
+                serde_json::json!({ "type": "metaAndAssetCtxs" }),
+            )
+            .await?;
+
+        Ok(funding_by_coin(body))
+    }
+
+    /// Mid price of `coin`.
+    async fn mid_price(&self, coin: &str) -> Result<Decimal> {
+        let mids: HashMap<String, Mid> = self
+            .info("mid prices", serde_json::json!({ "type": "allMids" }))
+            .await?;
+        mids.get(coin)
+            .map(|mid| mid.0)
+            .ok_or_else(|| anyhow!("Hyperliquid has no mid price for {}", coin))
+    }
+
+    /// Universe entry of `coin`, loading the universe on first use.
+    async fn perp_asset(&self, coin: &str) -> Result<PerpAsset> {
+        if let Some(asset) = self.lock_assets().get(coin) {
+            return Ok(*asset);
+        }
+        let meta: Meta = self
+            .info("universe", serde_json::json!({ "type": "meta" }))
+            .await?;
+        let mut assets = self.lock_assets();
+        *assets = perp_assets(meta);
+        assets
+            .get(coin)
+            .copied()
+            .ok_or_else(|| anyhow!("{} is not listed on Hyperliquid", coin))
+    }
+
+    fn lock_assets(&self) -> std::sync::MutexGuard<'_, HashMap<String, PerpAsset>> {
+        self.assets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Decimals `coin` orders are sized in.
+    pub async fn size_decimals(&self, coin: &str) -> Result<u32> {
+        Ok(self.perp_asset(coin).await?.sz_decimals)
+    }
+
+    /// Open positions of the signer's account.
+    #[instrument(skip(self))]
+    pub async fn get_positions(&self) -> Result<Vec<HyperliquidPosition>> {
+        let account = self.signer()?.account();
+        let state: ClearinghouseState = self
+            .info(
+                "positions",
+                serde_json::json!({ "type": "clearinghouseState", "user": account }),
+            )
+            .await?;
+
+        Ok(state
+            .asset_positions
+            .into_iter()
+            .map(|entry| HyperliquidPosition::from(entry.position))
+            .filter(|position| position.size != Decimal::ZERO)
+            .collect())
+    }
+
+    /// Cloid of an order known as `client_order_id`: the first 16 bytes of its
+    /// hash, as "0x" and 32 hex digits.
+    pub fn cloid(client_order_id: &str) -> String {
+        format!(
+            "0x{}",
+            hex::encode(&keccak(client_order_id.as_bytes())[..16])
+        )
+    }
+
+    /// Fill of the account's order tagged `cloid`; `None` if the exchange never
+    /// received it.
+    ///
+    /// The status reply has no average price, so a filled order is reported at
+    /// its limit, the worst price it could have filled at.
+    #[instrument(skip(self))]
+    pub async fn get_order_by_cloid(
+        &self,
+        coin: &str,
+        cloid: &str,
+    ) -> Result<Option<HyperliquidFill>> {
+        let account = self.signer()?.account();
+        let reply: OrderStatusReply = self
+            .info(
+                "order status",
+                serde_json::json!({ "type": "orderStatus", "user": account, "oid": cloid }),
+            )
+            .await?;
+        Ok(queried_fill(coin, reply))
+    }
+
+    /// Place an immediate-or-cancel order for `size` of `coin` at up to
+    /// `slippage` through mid, tagged with `cloid` (see [`Self::cloid`]).
+    ///
+    /// `size` is rounded down to the coin's size decimals. The fill may be
+    /// partial or empty; an order the exchange refuses is an error. When the
+    /// request fails without an answer, the order is looked up by its cloid
+    /// first: a found order returns its fill, and only an order the exchange
+    /// never received, safe to send again, returns the error.
+    #[instrument(skip(self))]
+    pub async fn place_ioc_order(
+        &self,
+        coin: &str,
+        is_buy: bool,
+        size: Decimal,
+        slippage: Decimal,
+        reduce_only: bool,
+        cloid: &str,
+    ) -> Result<HyperliquidFill> {
+        let signer = self.signer()?;
+        let asset = self.perp_asset(coin).await?;
+        let size = size.round_dp_with_strategy(asset.sz_decimals, RoundingStrategy::ToZero);
+        if size <= Decimal::ZERO {
+            bail!("{} order size rounds to zero", coin);
+        }
+        let mid = self.mid_price(coin).await?;
+        let price = limit_price(mid, is_buy, slippage, asset.sz_decimals);
+
+        let action = OrderAction {
+            kind: "order",
+            orders: vec![OrderWire {
+                a: asset.index,
+                b: is_buy,
+                p: to_wire(price),
+                s: to_wire(size),
+                r: reduce_only,
+                t: OrderTypeWire {
+                    limit: LimitWire { tif: "Ioc" },
+                },
+                c: Some(cloid.to_string()),
+            }],
+            grouping: "na",
+        };
+        let nonce = self.next_nonce();
+        let request = ExchangeRequest {
+            action: &action,
+            nonce,
+            signature: signer.sign_action(&action, nonce, self.is_mainnet())?,
+            vault_address: None,
+        };
+
+        self.order_valve.admit(chrono::Utc::now())?;
+        let url = format!("{}/exchange", self.base_url);
+        let sent: Result<ExchangeResponse> = async {
+            self.http
+                .post(&url)
+                .json(&request)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context("Failed to send Hyperliquid order")?
+                .json()
+                .await
+                .context("Failed to parse Hyperliquid order response")
+        }
+        .await;
+
+        let fill = match sent {
+            Ok(response) => order_fill(coin, response)?,
+            // The order may have reached the book: look it up before reporting a failure
+            Err(e) => match self.get_order_by_cloid(coin, cloid).await {
+                Ok(Some(fill)) => {
+                    warn!(%coin, %cloid, error = %e, "Hyperliquid order went unanswered but was placed");
+                    fill
+                }
+                Ok(None) => return Err(e),
+                Err(lookup) => {
+                    return Err(e.context(format!(
+                        "Hyperliquid {} order {} outcome unknown - lookup failed: {:#}",
+                        coin, cloid, lookup
+                    )))
+                }
+            },
+        };
+        self.order_valve
+            .record_notional(chrono::Utc::now(), fill.filled_size * fill.avg_price);
+        if fill.filled_size > Decimal::ZERO {
+            info!(
+                %coin,
+                is_buy,
+                reduce_only,
+                filled = %fill.filled_size,
+                avg_price = %fill.avg_price,
+                "Hyperliquid order filled"
+            );
+        } else {
+            debug!(%coin, is_buy, %size, %price, "Hyperliquid order did not fill");
+        }
+        Ok(fill)
+    }
+}
+
+/// Fill of a queried order, `None` for an unknown one.
+fn queried_fill(coin: &str, reply: OrderStatusReply) -> Option<HyperliquidFill> {
+    let OrderStatusReply::Order { order } = reply else {
+        return None;
+    };
+    let details = order.order;
+    let filled_size = details.orig_sz - details.sz;
+    Some(HyperliquidFill {
+        coin: coin.to_string(),
+        order_id: details.oid,
+        filled_size,
+        avg_price: if filled_size > Decimal::ZERO {
+            details.limit_px
+        } else {
+            Decimal::ZERO
+        },
+    })
+}
+
+/// Pair universe names with their asset contexts.
+fn funding_by_coin((meta, contexts): (Meta, Vec<AssetCtx>)) -> HashMap<String, Decimal> {
+    meta.universe
+        .into_iter()
+        .zip(contexts)
+        .map(|(asset, ctx)| (asset.name, ctx.funding))
+        .collect()
+}
+
+/// Perps by coin, indexed by their place in the universe.
+fn perp_assets(meta: Meta) -> HashMap<String, PerpAsset> {
+    meta.universe
+        .into_iter()
+        .enumerate()
+        .map(|(index, asset)| {
+            let perp = PerpAsset {
+                index: index as u32,
+                sz_decimals: asset.sz_decimals,
+            };
+            (asset.name, perp)
+        })
+        .collect()
+}
+
+/// Fill of a single IOC order from the exchange reply.
+fn order_fill(coin: &str, response: ExchangeResponse) -> Result<HyperliquidFill> {
+    let body = match response {
+        ExchangeResponse::Ok(body) => body,
+        ExchangeResponse::Err(reason) => bail!("Hyperliquid refused {} order: {}", coin, reason),
+    };
+    let status = body
+        .data
+        .statuses
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Hyperliquid returned no status for {} order", coin))?;
+
+    let (order_id, filled_size, avg_price) = match status {
+        OrderStatusWire::Filled(filled) => (filled.oid, filled.total_sz, filled.avg_px),
+        // An IOC never rests; treat it as unfilled if it is reported so
+        OrderStatusWire::Resting(resting) => (resting.oid, Decimal::ZERO, Decimal::ZERO),
+        // IOC orders with nothing to match against are reported as errors
+        OrderStatusWire::Error(reason) if reason.contains("could not immediately match") => {
+            (0, Decimal::ZERO, Decimal::ZERO)
+        }
+        OrderStatusWire::Error(reason) => {
+            bail!("Hyperliquid rejected {} order: {}", coin, reason)
+        }
+    };
+
+    Ok(HyperliquidFill {
+        coin: coin.to_string(),
+        order_id,
+        filled_size,
+        avg_price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_meta_and_asset_ctxs() {
+        let body: (Meta, Vec<AssetCtx>) = serde_json::from_str(
+            r#"[
+                {"universe": [
+                    {"name": "BTC", "szDecimals": 5, "maxLeverage": 40},
+                    {"name": "ETH", "szDecimals": 4, "maxLeverage": 25}
+                ]},
+                [
+                    {"funding": "0.0000125", "openInterest": "9000.1", "markPx": "65000.0"},
+                    {"funding": "-0.00002", "openInterest": "120000.5", "markPx": "3200.0"}
+                ]
+            ]"#,
+        )
+        .unwrap();
+
+        let rates = funding_by_coin(body);
+        assert_eq!(rates["BTC"], dec!(0.0000125));
+        assert_eq!(rates["ETH"], dec!(-0.00002));
+
+        let meta: Meta = serde_json::from_str(
+            r#"{"universe": [{"name": "BTC", "szDecimals": 5}, {"name": "ETH", "szDecimals": 4}]}"#,
+        )
+        .unwrap();
+        let assets = perp_assets(meta);
+        assert_eq!(assets["ETH"].index, 1);
+        assert_eq!(assets["ETH"].sz_decimals, 4);
+    }
+
+    fn order_action(asset: u32, tif: &'static str, price: &str, size: &str) -> OrderAction {
+        OrderAction {
+            kind: "order",
+            orders: vec![OrderWire {
+                a: asset,
+                b: true,
+                p: price.to_string(),
+                s: size.to_string(),
+                r: false,
+                t: OrderTypeWire {
+                    limit: LimitWire { tif },
+                },
+                c: None,
+            }],
+            grouping: "na",
+        }
+    }
+
+    #[test]
+    fn test_action_signing_matches_reference_vectors() {
+        // Vectors of the reference Python SDK
+        let action = order_action(4, "Ioc", "1670.1", "0.0147");
+        let connection_id = action_hash(&action, 1677777606040).unwrap();
+        assert_eq!(
+            hex::encode(connection_id),
+            "0fcbeda5ae3c4950a548021552a4fea2226858c4453571bf3f24ba017eac2908"
+        );
+
+        let signer = HyperliquidSigner::from_private_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        let action = order_action(1, "Gtc", "100", "100");
+        let signature = signer.sign_action(&action, 0, true).unwrap();
+        assert_eq!(
+            signature.r,
+            "0xd65369825a9df5d80099e513cce430311d7d26ddf477f5b3a33d2806b100d78e"
+        );
+        assert_eq!(
+            signature.s,
+            "0x2b54116ff64054968aa237c20ca9ff68000f977c93289157748a3162b6ea940e"
+        );
+        assert_eq!(signature.v, 28);
+    }
+
+    #[test]
+    fn test_limit_price_precision() {
+        // 5 significant figures, and no more than 6 - szDecimals decimals
+        assert_eq!(limit_price(dec!(65012.5), true, dec!(0.01), 5), dec!(65663));
+        assert_eq!(
+            limit_price(dec!(0.123456), false, dec!(0), 0),
+            dec!(0.12346)
+        );
+        assert_eq!(limit_price(dec!(1.234567), true, dec!(0), 4), dec!(1.23));
+        assert_eq!(to_wire(dec!(100.000)), "100");
+        assert_eq!(to_wire(dec!(0.0150)), "0.015");
+    }
+
+    #[test]
+    fn test_parse_order_statuses_and_positions() {
+        let filled: ExchangeResponse = serde_json::from_str(
+            r#"{"status": "ok", "response": {"type": "order", "data": {"statuses": [
+                {"filled": {"totalSz": "0.02", "avgPx": "1891.4", "oid": 77747314}}
+            ]}}}"#,
+        )
+        .unwrap();
+        let fill = order_fill("ETH", filled).unwrap();
+        assert_eq!(fill.filled_size, dec!(0.02));
+        assert_eq!(fill.avg_price, dec!(1891.4));
+
+        let unmatched: ExchangeResponse = serde_json::from_str(
+            r#"{"status": "ok", "response": {"type": "order", "data": {"statuses": [
+                {"error": "Order could not immediately match against any resting orders. asset=4"}
+            ]}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            order_fill("ETH", unmatched).unwrap().filled_size,
+            Decimal::ZERO
+        );
+
+        let refused: ExchangeResponse = serde_json::from_str(
+            r#"{"status": "err", "response": "User or API Wallet 0x0 does not exist."}"#,
+        )
+        .unwrap();
+        assert!(order_fill("ETH", refused).is_err());
+
+        let state: ClearinghouseState = serde_json::from_str(
+            r#"{"assetPositions": [{"type": "oneWay", "position": {
+                "coin": "ETH", "szi": "-0.5", "entryPx": "2986.3", "positionValue": "1493.1",
+                "unrealizedPnl": "0.05", "liquidationPx": null, "marginUsed": "74.6",
+                "leverage": {"type": "cross", "value": 20}
+            }}], "withdrawable": "13.1"}"#,
+        )
+        .unwrap();
+        let position =
+            HyperliquidPosition::from(state.asset_positions.into_iter().next().unwrap().position);
+        assert_eq!(position.size, dec!(-0.5));
+        assert_eq!(position.entry_price, dec!(2986.3));
+        assert_eq!(position.liquidation_price, None);
+    }
+
+    #[test]
+    fn test_cloid_is_signed_and_looked_up() {
+        let id = HyperliquidClient::cloid("fffa1b2c3-lq2x9k1a-0-1");
+        assert_eq!(id.len(), 34);
+        assert!(id.starts_with("0x"));
+        assert_eq!(id, HyperliquidClient::cloid("fffa1b2c3-lq2x9k1a-0-1"));
+        assert_ne!(id, HyperliquidClient::cloid("fffa1b2c3-lq2x9k1a-0-2"));
+
+        // The cloid is part of the signed action, after the order type
+        let mut action = order_action(4, "Ioc", "1670.1", "0.0147");
+        let unset = action_hash(&action, 1677777606040).unwrap();
+        action.orders[0].c = Some(id.clone());
+        assert_ne!(action_hash(&action, 1677777606040).unwrap(), unset);
+        let wire = serde_json::to_value(&action.orders[0]).unwrap();
+        assert_eq!(wire["c"], id);
+
+        let placed: OrderStatusReply = serde_json::from_str(
+            r#"{"status": "order", "order": {"order": {
+                "coin": "ETH", "side": "B", "limitPx": "1900.5", "sz": "0.005",
+                "oid": 77747314, "timestamp": 1700000000000, "origSz": "0.02",
+                "cloid": "0x1234567890abcdef1234567890abcdef"
+            }, "status": "canceled", "statusTimestamp": 1700000000001}}"#,
+        )
+        .unwrap();
+        let fill = queried_fill("ETH", placed).unwrap();
+        assert_eq!(fill.order_id, 77747314);
+        assert_eq!(fill.filled_size, dec!(0.015));
+        assert_eq!(fill.avg_price, dec!(1900.5));
+
+        let unknown: OrderStatusReply =
+            serde_json::from_str(r#"{"status": "unknownOid"}"#).unwrap();
+        assert!(queried_fill("ETH", unknown).is_none());
+    }
+
+    #[test]
+    fn test_signer_address_and_nonces() {
+        let signer = HyperliquidSigner::from_private_key(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        )
+        .unwrap();
+        assert_eq!(
+            signer.account(),
+            "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+
+        let client = HyperliquidClient::new(MAINNET_URL).unwrap();
+        assert!(client.is_mainnet());
+        let first = client.next_nonce();
+        assert!(client.next_nonce() > first);
+    }
+}
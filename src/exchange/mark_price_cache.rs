@@ -0,0 +1,199 @@
+//! Mark prices and funding rates streamed from Binance.
+//!
+//! `!markPrice@arr@1s` pushes the mark price and predicted funding rate of every
+//! perpetual each second. The cache keeps the latest of each per symbol. Readers
+//! get a set of symbols only when every one was updated within the freshness
+//! window, so a stalled or disconnected stream falls back to REST snapshots
+//! instead of serving old prices.
+
+use super::websocket::{BinanceWebSocket, MarkPriceUpdate, WsEvent};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// Latest streamed values of one symbol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkPriceEntry {
+    pub mark_price: Decimal,
+    /// Predicted rate of the next settlement
+    pub funding_rate: Decimal,
+    /// Exchange time of the update
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, MarkPriceEntry>,
+    connected: bool,
+}
+
+/// Shared mark price cache; clones read and write the same entries.
+#[derive(Debug, Clone)]
+pub struct MarkPriceCache {
+    state: Arc<Mutex<CacheState>>,
+    /// Oldest update still served
+    max_age: Duration,
+}
+
+impl MarkPriceCache {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState::default())),
+            max_age,
+        }
+    }
+
+    /// Subscribe to the mark price stream and keep the cache current until it ends.
+    pub async fn connect(&self, ws: &BinanceWebSocket) -> Result<()> {
+        let (tx, mut rx) = mpsc::channel(1000);
+        ws.subscribe_mark_price_all(tx).await?;
+        self.lock().connected = true;
+
+        let cache = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                match event {
+                    WsEvent::MarkPrice(update) => cache.apply(&update),
+                    WsEvent::Disconnected => break,
+                    _ => {}
+                }
+            }
+            debug!("Mark price stream ended");
+            cache.lock().connected = false;
+        });
+        Ok(())
+    }
+
+    /// Whether the stream is still delivering; once ended it must be reconnected.
+    pub fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    /// Number of symbols with a streamed price.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Store a streamed update; unparseable updates are skipped.
+    pub fn apply(&self, update: &MarkPriceUpdate) {
+        let (Ok(mark_price), Ok(funding_rate), Some(updated_at)) = (
+            Decimal::from_str(&update.mark_price),
+            Decimal::from_str(&update.funding_rate),
+            DateTime::from_timestamp_millis(update.event_time),
+        ) else {
+            return;
+        };
+        self.lock().entries.insert(
+            update.symbol.clone(),
+            MarkPriceEntry {
+                mark_price,
+                funding_rate,
+                updated_at,
+            },
+        );
+    }
+
+    /// Mark prices of `symbols`, or `None` unless all of them are fresh.
+    pub fn mark_prices(
+        &self,
+        symbols: &[String],
+        now: DateTime<Utc>,
+    ) -> Option<HashMap<String, Decimal>> {
+        self.fresh(symbols, now, |entry| entry.mark_price)
+    }
+
+    /// Predicted funding rates of `symbols`, or `None` unless all of them are fresh.
+    pub fn funding_rates(
+        &self,
+        symbols: &[String],
+        now: DateTime<Utc>,
+    ) -> Option<HashMap<String, Decimal>> {
+        self.fresh(symbols, now, |entry| entry.funding_rate)
+    }
+
+    fn fresh(
+        &self,
+        symbols: &[String],
+        now: DateTime<Utc>,
+        value: impl Fn(&MarkPriceEntry) -> Decimal,
+    ) -> Option<HashMap<String, Decimal>> {
+        let state = self.lock();
+        symbols
+            .iter()
+            .map(|symbol| {
+                let entry = state.entries.get(symbol)?;
+                (now - entry.updated_at <= self.max_age).then(|| (symbol.clone(), value(entry)))
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(symbol: &str, mark: &str, rate: &str, at: DateTime<Utc>) -> MarkPriceUpdate {
+        MarkPriceUpdate {
+            event_time: at.timestamp_millis(),
+            symbol: symbol.to_string(),
+            mark_price: mark.to_string(),
+            funding_rate: rate.to_string(),
+            next_funding_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_serves_only_fresh_symbol_sets() {
+        let now = Utc::now();
+        let cache = MarkPriceCache::new(Duration::seconds(5));
+        assert!(!cache.is_connected());
+        cache.apply(&update("BTCUSDT", "62000.5", "0.0001", now));
+        cache.apply(&update(
+            "ETHUSDT",
+            "3000",
+            "0.0002",
+            now - Duration::seconds(10),
+        ));
+        cache.apply(&update("SOLUSDT", "bad", "0.0003", now));
+        assert_eq!(cache.len(), 2);
+
+        let btc = vec!["BTCUSDT".to_string()];
+        assert_eq!(
+            cache.mark_prices(&btc, now).unwrap().get("BTCUSDT"),
+            Some(&dec!(62000.5))
+        );
+        assert_eq!(
+            cache.funding_rates(&btc, now).unwrap().get("BTCUSDT"),
+            Some(&dec!(0.0001))
+        );
+
+        // A stale or missing symbol sends the whole set back to REST
+        let both = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        assert!(cache.mark_prices(&both, now).is_none());
+        assert!(cache.mark_prices(&["SOLUSDT".to_string()], now).is_none());
+
+        // A newer update refreshes the symbol
+        cache.apply(&update("ETHUSDT", "3010", "0.0002", now));
+        assert_eq!(
+            cache.mark_prices(&both, now).unwrap().get("ETHUSDT"),
+            Some(&dec!(3010))
+        );
+        assert!(cache
+            .mark_prices(&both, now + Duration::seconds(6))
+            .is_none());
+    }
+}
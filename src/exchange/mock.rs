@@ -1,17 +1,23 @@
 //! Mock trading client for paper trading / backtesting.
 
+use super::scenario::{MarketScenario, ScenarioEvent, ScenarioPlayback};
 use super::types::*;
+use super::{ExchangeClient, FundingCalendar, OrderValve, Venue};
 use crate::persistence::{PersistedPosition, PersistedState};
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
+/// Hourly borrow rate charged when no per-symbol rate is set (~0.002%, typical Binance).
+pub const DEFAULT_HOURLY_BORROW_RATE: Decimal = dec!(0.00002);
+
 /// Simulated position state with per-position tracking.
 #[derive(Debug, Clone)]
 pub struct MockPosition {
@@ -24,8 +30,10 @@ pub struct MockPosition {
     // Per-position lifecycle tracking
     /// When the position was opened
     pub opened_at: DateTime<Utc>,
-    /// Total funding received for this position
+    /// Net funding for this position (received minus paid)
     pub total_funding_received: Decimal,
+    /// Funding paid on settlements that found the futures leg on the paying side
+    pub total_funding_paid: Decimal,
     /// Total interest paid for this position (margin borrowing)
     pub total_interest_paid: Decimal,
     /// Number of funding collections for this position
@@ -45,6 +53,7 @@ impl Default for MockPosition {
             borrowed_amount: Decimal::ZERO,
             opened_at: Utc::now(),
             total_funding_received: Decimal::ZERO,
+            total_funding_paid: Decimal::ZERO,
             total_interest_paid: Decimal::ZERO,
             funding_collections: 0,
             expected_funding_rate: Decimal::ZERO,
@@ -58,7 +67,10 @@ pub struct MockTradingState {
     pub initial_balance: Decimal,
     pub balance: Decimal,
     pub positions: HashMap<String, MockPosition>,
+    /// Net funding (received minus paid)
     pub total_funding_received: Decimal,
+    /// Funding paid, kept apart so wrong-side settlements aren't hidden in the net
+    pub total_funding_paid: Decimal,
     pub total_trading_fees: Decimal,
     pub total_borrow_interest: Decimal,
     pub order_count: u64,
@@ -71,6 +83,7 @@ impl Default for MockTradingState {
             balance: dec!(10000),
             positions: HashMap::new(),
             total_funding_received: Decimal::ZERO,
+            total_funding_paid: Decimal::ZERO,
             total_trading_fees: Decimal::ZERO,
             total_borrow_interest: Decimal::ZERO,
             order_count: 0,
@@ -78,7 +91,98 @@ impl Default for MockTradingState {
     }
 }
 
+/// Existing spot coins used as hedge legs instead of being traded.
+#[derive(Debug, Clone, Copy, Default)]
+struct SpotHolding {
+    /// Quantity declared as usable
+    declared: Decimal,
+    /// Quantity not yet committed to a position
+    available: Decimal,
+}
+
+/// Limit order resting on the mock book, which never fills it.
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    order: OrderResponse,
+    /// When it was placed (ms)
+    placed_at: i64,
+    /// Margin (spot leg) order rather than futures
+    margin: bool,
+}
+
+impl RestingOrder {
+    fn lookup(&self) -> OrderLookup {
+        OrderLookup {
+            order_id: self.order.order_id,
+            symbol: self.order.symbol.clone(),
+            status: self.order.status,
+            client_order_id: self.order.client_order_id.clone(),
+            executed_qty: self.order.executed_qty,
+            avg_price: Some(self.order.avg_price),
+            cummulative_quote_qty: None,
+            side: self.order.side,
+            time: self.placed_at,
+        }
+    }
+}
+
+/// Funding settlements of each symbol, on its own schedule in `calendar`, strictly
+/// after `last_saved` and up to `now` - the payments a mock bot offline over that
+/// span never collected. Symbols that missed nothing are left out.
+pub fn missed_funding_settlements(
+    calendar: &FundingCalendar,
+    symbols: &[String],
+    last_saved: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> HashMap<String, Vec<DateTime<Utc>>> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let schedule = calendar.schedule(Venue::Binance, symbol);
+            (
+                symbol.clone(),
+                schedule.settlements_between(last_saved, now),
+            )
+        })
+        .filter(|(_, settlements)| !settlements.is_empty())
+        .collect()
+}
+
+/// Pay one funding settlement on a position's futures notional.
+/// Short futures with positive funding (and long with negative) receive; the
+/// other side pays, which is also recorded as funding paid.
+fn settle_funding(position: &mut MockPosition, rate: Decimal, price: Decimal) -> Decimal {
+    let futures_value = position.futures_qty * price;
+    let funding = -futures_value * rate;
+    position.total_funding_received += funding;
+    if funding < Decimal::ZERO {
+        position.total_funding_paid -= funding;
+    }
+    position.funding_collections += 1;
+    funding
+}
+
 /// Mock client that simulates Binance API responses.
+///
+/// Fixtures can be seeded directly, without placing orders:
+///
+/// ```
+/// use funding_fee_farmer::exchange::MockBinanceClient;
+/// use rust_decimal_macros::dec;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = MockBinanceClient::new(dec!(10000));
+/// client.seed_market("BTCUSDT", dec!(0.0005), dec!(50000)).await;
+/// // Short 0.1 BTC futures hedged by 0.1 BTC spot
+/// client.seed_position("BTCUSDT", dec!(-0.1), dec!(50000)).await;
+///
+/// // 0.05% of $5,000 notional
+/// let funding = client.collect_funding().await;
+/// assert_eq!(funding["BTCUSDT"], dec!(2.5));
+/// assert_eq!(client.get_state().await.balance, dec!(10002.5));
+/// # }
+/// ```
 pub struct MockBinanceClient {
     state: Arc<RwLock<MockTradingState>>,
     order_id_counter: AtomicU64,
@@ -86,36 +190,189 @@ pub struct MockBinanceClient {
     funding_rates: Arc<RwLock<HashMap<String, Decimal>>>,
     /// Simulated prices
     prices: Arc<RwLock<HashMap<String, Decimal>>>,
-    /// Trading fee rate (0.04% taker)
-    fee_rate: Decimal,
+    /// Futures fees by order type; spot orders pay the taker rate
+    fees: FeeSchedule,
+    /// Hourly borrow rate per symbol, overriding the default
+    borrow_rates: Arc<RwLock<HashMap<String, Decimal>>>,
+    /// Existing spot holdings per spot symbol (existing-holdings hedge)
+    spot_holdings: Arc<RwLock<HashMap<String, SpotHolding>>>,
+    /// Bumped on every balance or position mutation (dirty tracking for persistence)
+    revision: AtomicU64,
+    /// Order rate and notional velocity limits
+    order_valve: OrderValve,
+    /// Scripted market scenario being played back
+    scenario: Arc<RwLock<Option<ScenarioPlayback>>>,
+    /// Seeded limit orders left open on the book
+    resting_orders: Arc<RwLock<Vec<RestingOrder>>>,
+    /// Whether listing open margin orders fails, as in a margin API outage
+    margin_listing_down: AtomicBool,
 }
 
 impl MockBinanceClient {
     /// Create a new mock client with initial balance.
     pub fn new(initial_balance: Decimal) -> Self {
-        let mut state = MockTradingState::default();
-        state.initial_balance = initial_balance;
-        state.balance = initial_balance;
+        let state = MockTradingState {
+            initial_balance,
+            balance: initial_balance,
+            ..Default::default()
+        };
 
         Self {
             state: Arc::new(RwLock::new(state)),
             order_id_counter: AtomicU64::new(1),
             funding_rates: Arc::new(RwLock::new(HashMap::new())),
             prices: Arc::new(RwLock::new(HashMap::new())),
-            fee_rate: dec!(0.0004), // 0.04% taker fee
+            fees: FeeSchedule::default(),
+            borrow_rates: Arc::new(RwLock::new(HashMap::new())),
+            spot_holdings: Arc::new(RwLock::new(HashMap::new())),
+            revision: AtomicU64::new(0),
+            order_valve: OrderValve::default(),
+            scenario: Arc::new(RwLock::new(None)),
+            resting_orders: Arc::new(RwLock::new(Vec::new())),
+            margin_listing_down: AtomicBool::new(false),
         }
     }
 
+    /// Create a mock client that plays back `scenario` from `start`.
+    ///
+    /// Scripted symbols keep their scenario prices and funding rates over live
+    /// market data; call [`Self::advance_scenario`] each cycle to play due events.
+    pub fn from_scenario(scenario: &MarketScenario, start: DateTime<Utc>) -> Self {
+        let client = Self::new(scenario.initial_balance);
+        *client.scenario.try_write().expect("new client is unshared") =
+            Some(ScenarioPlayback::new(scenario, start));
+        client
+    }
+
+    /// Play the scenario events due by `now`, returning them in order.
+    pub async fn advance_scenario(&self, now: DateTime<Utc>) -> Vec<ScenarioEvent> {
+        let mut scenario = self.scenario.write().await;
+        let Some(playback) = scenario.as_mut() else {
+            return Vec::new();
+        };
+        let played = playback.advance(now);
+        if !played.is_empty() {
+            playback.pin(
+                &mut *self.funding_rates.write().await,
+                &mut *self.prices.write().await,
+            );
+        }
+        played
+    }
+
+    /// Fail the order on `symbol` if the scenario scripts a failure for it.
+    async fn scripted_failure(&self, symbol: &str) -> Result<()> {
+        if let Some(playback) = self.scenario.write().await.as_mut() {
+            if playback.take_failure(symbol) {
+                anyhow::bail!("Scenario order failure for {}", symbol);
+            }
+        }
+        Ok(())
+    }
+
+    /// Charge futures orders by `fees`; a negative maker rate credits limit fills.
+    pub fn set_fee_schedule(&mut self, fees: FeeSchedule) {
+        self.fees = fees;
+    }
+
+    /// Charge `hourly_rate` on the borrowed base asset of `symbol`'s position.
+    pub async fn set_borrow_rate(&self, symbol: &str, hourly_rate: Decimal) {
+        self.borrow_rates
+            .write()
+            .await
+            .insert(symbol.to_string(), hourly_rate);
+    }
+
+    /// Fail listings of open margin orders while `down`, as in a margin API outage.
+    pub fn set_margin_listing_down(&self, down: bool) {
+        self.margin_listing_down.store(down, Ordering::SeqCst);
+    }
+
+    /// Guard order placement with `valve`.
+    pub fn set_order_valve(&mut self, valve: OrderValve) {
+        self.order_valve = valve;
+    }
+
+    /// Mutation revision of the trading state.
+    ///
+    /// Changes whenever the balance or a position changes, so callers can skip
+    /// persisting a state that is already saved.
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::SeqCst)
+    }
+
+    fn mark_dirty(&self) {
+        self.revision.fetch_add(1, Ordering::SeqCst);
+    }
+
     /// Update simulated market data (call this with real data).
     pub async fn update_market_data(
         &self,
-        funding_rates: HashMap<String, Decimal>,
-        prices: HashMap<String, Decimal>,
+        mut funding_rates: HashMap<String, Decimal>,
+        mut prices: HashMap<String, Decimal>,
     ) {
+        if let Some(playback) = self.scenario.write().await.as_mut() {
+            playback.pin(&mut funding_rates, &mut prices);
+        }
         *self.funding_rates.write().await = funding_rates;
         *self.prices.write().await = prices;
     }
 
+    /// Set the funding rate and price of one symbol, keeping the others.
+    pub async fn seed_market(&self, symbol: &str, funding_rate: Decimal, price: Decimal) {
+        self.funding_rates
+            .write()
+            .await
+            .insert(symbol.to_string(), funding_rate);
+        self.prices.write().await.insert(symbol.to_string(), price);
+    }
+
+    /// Open a delta-neutral position at `price` without trading or fees.
+    ///
+    /// `futures_qty` is negative for a short futures leg; the spot leg takes the
+    /// opposite side, borrowing the base asset when short. Meant for fixtures.
+    pub async fn seed_position(&self, symbol: &str, futures_qty: Decimal, price: Decimal) {
+        let mut state = self.state.write().await;
+        state.positions.insert(
+            symbol.to_string(),
+            MockPosition {
+                symbol: symbol.to_string(),
+                futures_qty,
+                futures_entry_price: price,
+                spot_qty: -futures_qty,
+                spot_entry_price: price,
+                borrowed_amount: futures_qty.max(Decimal::ZERO),
+                ..Default::default()
+            },
+        );
+        self.mark_dirty();
+    }
+
+    /// Declare existing spot holdings (spot symbol -> quantity).
+    ///
+    /// Buys of a held coin commit holdings instead of trading, and sells release
+    /// them back, both without fees. Long spot already in restored positions
+    /// counts as committed.
+    pub async fn set_spot_holdings(&self, holdings: HashMap<String, Decimal>) {
+        let state = self.state.read().await;
+        *self.spot_holdings.write().await = holdings
+            .into_iter()
+            .map(|(symbol, declared)| {
+                let committed = state
+                    .positions
+                    .get(&symbol)
+                    .map(|p| p.spot_qty.max(Decimal::ZERO))
+                    .unwrap_or(Decimal::ZERO)
+                    .min(declared);
+                let holding = SpotHolding {
+                    declared,
+                    available: declared - committed,
+                };
+                (symbol, holding)
+            })
+            .collect();
+    }
+
     /// Alias for update_market_data (used by backtesting engine).
     pub async fn set_market_data(
         &self,
@@ -125,6 +382,86 @@ impl MockBinanceClient {
         self.update_market_data(funding_rates, prices).await;
     }
 
+    /// Rest a futures limit order on the book without filling it, as a post-only
+    /// order the market moved away from would, placed at `placed_at`.
+    pub async fn seed_resting_order(
+        &self,
+        order: &NewOrder,
+        placed_at: DateTime<Utc>,
+    ) -> OrderResponse {
+        self.rest_order(order, placed_at, false).await
+    }
+
+    /// Rest a margin limit order on the book without filling it, like
+    /// [`Self::seed_resting_order`].
+    pub async fn seed_resting_margin_order(
+        &self,
+        order: &NewOrder,
+        placed_at: DateTime<Utc>,
+    ) -> OrderResponse {
+        self.rest_order(order, placed_at, true).await
+    }
+
+    async fn rest_order(
+        &self,
+        order: &NewOrder,
+        placed_at: DateTime<Utc>,
+        margin: bool,
+    ) -> OrderResponse {
+        let quantity = order.quantity.unwrap_or(Decimal::ZERO);
+        let price = order.price.unwrap_or(Decimal::ZERO);
+        let response = OrderResponse {
+            order_id: self.next_order_id() as i64,
+            symbol: order.symbol.clone(),
+            status: OrderStatus::New,
+            client_order_id: order.new_client_order_id.clone().unwrap_or_default(),
+            price,
+            avg_price: Decimal::ZERO,
+            orig_qty: quantity,
+            executed_qty: Decimal::ZERO,
+            side: order.side,
+            order_type: order.order_type,
+            time_in_force: order.time_in_force,
+            update_time: placed_at.timestamp_millis(),
+        };
+        self.resting_orders.write().await.push(RestingOrder {
+            order: response.clone(),
+            placed_at: placed_at.timestamp_millis(),
+            margin,
+        });
+        response
+    }
+
+    async fn open_resting_orders(&self, margin: bool) -> Vec<OrderLookup> {
+        self.resting_orders
+            .read()
+            .await
+            .iter()
+            .filter(|resting| resting.margin == margin)
+            .map(RestingOrder::lookup)
+            .collect()
+    }
+
+    async fn cancel_resting_order(
+        &self,
+        symbol: &str,
+        order_id: i64,
+        margin: bool,
+    ) -> Result<OrderResponse> {
+        let mut resting_orders = self.resting_orders.write().await;
+        let Some(index) = resting_orders.iter().position(|resting| {
+            resting.margin == margin
+                && resting.order.symbol == symbol
+                && resting.order.order_id == order_id
+        }) else {
+            anyhow::bail!("Unknown order {} on {}", order_id, symbol);
+        };
+        let mut order = resting_orders.remove(index).order;
+        order.status = OrderStatus::Canceled;
+        order.update_time = Utc::now().timestamp_millis();
+        Ok(order)
+    }
+
     /// Reset all state for a new backtest run (parameter sweep).
     pub async fn reset(&self, initial_balance: Decimal) {
         let mut state = self.state.write().await;
@@ -132,9 +469,11 @@ impl MockBinanceClient {
         state.balance = initial_balance;
         state.positions.clear();
         state.total_funding_received = Decimal::ZERO;
+        state.total_funding_paid = Decimal::ZERO;
         state.total_trading_fees = Decimal::ZERO;
         state.total_borrow_interest = Decimal::ZERO;
         state.order_count = 0;
+        self.mark_dirty();
 
         // Reset order ID counter
         self.order_id_counter.store(1, Ordering::SeqCst);
@@ -142,6 +481,12 @@ impl MockBinanceClient {
         // Clear market data
         self.funding_rates.write().await.clear();
         self.prices.write().await.clear();
+        self.resting_orders.write().await.clear();
+
+        // Return all existing holdings
+        for holding in self.spot_holdings.write().await.values_mut() {
+            holding.available = holding.declared;
+        }
 
         debug!(balance = %initial_balance, "Mock client state reset");
     }
@@ -154,6 +499,7 @@ impl MockBinanceClient {
             balance: state.balance,
             positions: state.positions.clone(),
             total_funding_received: state.total_funding_received,
+            total_funding_paid: state.total_funding_paid,
             total_trading_fees: state.total_trading_fees,
             total_borrow_interest: state.total_borrow_interest,
             order_count: state.order_count,
@@ -164,31 +510,40 @@ impl MockBinanceClient {
     /// Collect funding payments for all positions.
     /// Returns a map of symbol -> funding received for verification purposes.
     pub async fn collect_funding(&self) -> HashMap<String, Decimal> {
+        let symbols: Vec<String> = self.state.read().await.positions.keys().cloned().collect();
+        self.collect_funding_for(&symbols).await
+    }
+
+    /// Collect one funding payment for the positions in `symbols` only.
+    ///
+    /// Used when symbols settle on different schedules. Returns a map of
+    /// symbol -> funding received.
+    pub async fn collect_funding_for(&self, symbols: &[String]) -> HashMap<String, Decimal> {
         let mut state = self.state.write().await;
         let funding_rates = self.funding_rates.read().await;
         let prices = self.prices.read().await;
 
         let mut total_funding = Decimal::ZERO;
+        let mut funding_paid = Decimal::ZERO;
         let mut per_position_funding: HashMap<String, Decimal> = HashMap::new();
 
-        // Collect symbols first to avoid borrow conflicts
-        let symbols: Vec<String> = state.positions.keys().cloned().collect();
-
         for symbol in symbols {
-            if let Some(&rate) = funding_rates.get(&symbol) {
-                if let Some(&price) = prices.get(&symbol) {
-                    if let Some(position) = state.positions.get_mut(&symbol) {
+            if let Some(&rate) = funding_rates.get(symbol) {
+                if let Some(&price) = prices.get(symbol) {
+                    if let Some(position) = state.positions.get_mut(symbol) {
                         // Funding = position_value * funding_rate
-                        // Short futures with positive funding = receive
-                        // Long futures with negative funding = receive
-                        let futures_value = position.futures_qty * price;
-                        let funding = -futures_value * rate; // Negative qty (short) * positive rate = positive funding
+                        let funding = settle_funding(position, rate, price);
 
                         total_funding += funding;
-
-                        // Track per-position funding
-                        position.total_funding_received += funding;
-                        position.funding_collections += 1;
+                        if funding < Decimal::ZERO {
+                            funding_paid -= funding;
+                            debug!(
+                                %symbol,
+                                funding_rate = %rate,
+                                funding_paid = %-funding,
+                                "Funding paid: futures leg on the paying side"
+                            );
+                        }
                         per_position_funding.insert(symbol.clone(), funding);
 
                         debug!(
@@ -206,11 +561,17 @@ impl MockBinanceClient {
         }
 
         state.total_funding_received += total_funding;
+        state.total_funding_paid += funding_paid;
         state.balance += total_funding;
+        if !per_position_funding.is_empty() {
+            self.mark_dirty();
+        }
 
         info!(
             funding_this_period = %total_funding,
+            funding_paid_this_period = %funding_paid,
             total_funding = %state.total_funding_received,
+            total_funding_paid = %state.total_funding_paid,
             balance = %state.balance,
             "Funding collected"
         );
@@ -218,17 +579,61 @@ impl MockBinanceClient {
         per_position_funding
     }
 
+    /// Apply funding settlements missed while the bot was offline.
+    ///
+    /// `settlements` lists the historical rate of each missed settlement per symbol;
+    /// each is paid on the position's current futures quantity at `prices`. Returns
+    /// the total caught-up funding per symbol.
+    pub async fn apply_missed_funding(
+        &self,
+        settlements: &HashMap<String, Vec<Decimal>>,
+        prices: &HashMap<String, Decimal>,
+    ) -> HashMap<String, Decimal> {
+        let mut state = self.state.write().await;
+        let mut per_position_funding: HashMap<String, Decimal> = HashMap::new();
+        let mut funding_paid = Decimal::ZERO;
+
+        for (symbol, rates) in settlements {
+            let (Some(position), Some(&price)) =
+                (state.positions.get_mut(symbol), prices.get(symbol))
+            else {
+                continue;
+            };
+            let paid_before = position.total_funding_paid;
+            let caught_up: Decimal = rates
+                .iter()
+                .map(|&rate| settle_funding(position, rate, price))
+                .sum();
+            funding_paid += position.total_funding_paid - paid_before;
+            per_position_funding.insert(symbol.clone(), caught_up);
+        }
+
+        let total: Decimal = per_position_funding.values().sum();
+        state.total_funding_received += total;
+        state.total_funding_paid += funding_paid;
+        state.balance += total;
+        if !per_position_funding.is_empty() {
+            self.mark_dirty();
+        }
+
+        per_position_funding
+    }
+
     /// Simulate borrow interest accrual (call periodically).
     /// Returns a map of symbol -> interest paid for tracking purposes.
     pub async fn accrue_interest(&self, hours: Decimal) -> HashMap<String, Decimal> {
         let mut state = self.state.write().await;
-        let hourly_rate = dec!(0.00002); // ~0.002% per hour (typical Binance rate)
+        let borrow_rates = self.borrow_rates.read().await;
 
         let mut total_interest = Decimal::ZERO;
         let mut per_position_interest: HashMap<String, Decimal> = HashMap::new();
 
         for (symbol, position) in state.positions.iter_mut() {
             if position.borrowed_amount > Decimal::ZERO {
+                let hourly_rate = borrow_rates
+                    .get(symbol)
+                    .copied()
+                    .unwrap_or(DEFAULT_HOURLY_BORROW_RATE);
                 let interest = position.borrowed_amount * hourly_rate * hours;
                 total_interest += interest;
 
@@ -240,6 +645,9 @@ impl MockBinanceClient {
 
         state.total_borrow_interest += total_interest;
         state.balance -= total_interest;
+        if !per_position_interest.is_empty() {
+            self.mark_dirty();
+        }
 
         if total_interest > Decimal::ZERO {
             debug!(
@@ -258,6 +666,8 @@ impl MockBinanceClient {
 
     /// Simulate placing a futures order.
     pub async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        self.order_valve.admit(Utc::now())?;
+        self.scripted_failure(&order.symbol).await?;
         let mut state = self.state.write().await;
         let prices = self.prices.read().await;
 
@@ -273,7 +683,7 @@ impl MockBinanceClient {
         let price = prices.get(&order.symbol).copied().unwrap_or(fallback_price);
         let quantity = order.quantity.unwrap_or(Decimal::ZERO);
         let notional = quantity * price;
-        let fee = notional * self.fee_rate;
+        let fee = notional * self.fees.rate(order.order_type);
 
         // Update position
         let position = state
@@ -298,6 +708,7 @@ impl MockBinanceClient {
         state.balance -= fee;
         state.total_trading_fees += fee;
         state.order_count += 1;
+        self.mark_dirty();
 
         let order_id = self.next_order_id() as i64;
 
@@ -311,7 +722,7 @@ impl MockBinanceClient {
             "Mock futures order executed"
         );
 
-        Ok(OrderResponse {
+        let response = OrderResponse {
             order_id,
             symbol: order.symbol.clone(),
             status: OrderStatus::Filled,
@@ -324,11 +735,15 @@ impl MockBinanceClient {
             order_type: order.order_type,
             side: order.side,
             update_time: chrono::Utc::now().timestamp_millis(),
-        })
+        };
+        self.order_valve.record_fill(Utc::now(), &response);
+        Ok(response)
     }
 
     /// Simulate placing a margin order.
     pub async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        self.order_valve.admit(Utc::now())?;
+        self.scripted_failure(&order.symbol).await?;
         let mut state = self.state.write().await;
         let prices = self.prices.read().await;
 
@@ -343,8 +758,33 @@ impl MockBinanceClient {
 
         let price = prices.get(&order.symbol).copied().unwrap_or(fallback_price);
         let quantity = order.quantity.unwrap_or(Decimal::ZERO);
-        let notional = quantity * price;
-        let fee = notional * self.fee_rate;
+
+        // Existing holdings are committed or released, not traded
+        let held_qty = match self.spot_holdings.write().await.get_mut(&order.symbol) {
+            Some(holding) => match order.side {
+                OrderSide::Buy => {
+                    let committed = quantity.min(holding.available);
+                    holding.available -= committed;
+                    committed
+                }
+                OrderSide::Sell => {
+                    let long_qty = state
+                        .positions
+                        .get(&order.symbol)
+                        .map(|p| p.spot_qty.max(Decimal::ZERO))
+                        .unwrap_or(Decimal::ZERO);
+                    let released = quantity
+                        .min(holding.declared - holding.available)
+                        .min(long_qty);
+                    holding.available += released;
+                    released
+                }
+            },
+            None => Decimal::ZERO,
+        };
+
+        let notional = (quantity - held_qty) * price;
+        let fee = notional * self.fees.taker_rate;
 
         // Update position
         let borrowed_amount = {
@@ -376,6 +816,7 @@ impl MockBinanceClient {
         state.balance -= fee;
         state.total_trading_fees += fee;
         state.order_count += 1;
+        self.mark_dirty();
 
         let order_id = self.next_order_id() as i64;
 
@@ -386,15 +827,16 @@ impl MockBinanceClient {
             quantity = %quantity,
             price = %price,
             fee = %fee,
+            held = %held_qty,
             borrowed = %borrowed_amount,
             "Mock margin order executed"
         );
 
-        Ok(OrderResponse {
+        let response = OrderResponse {
             order_id,
             symbol: order.symbol.clone(),
             status: OrderStatus::Filled,
-            client_order_id: String::new(),
+            client_order_id: order.new_client_order_id.clone().unwrap_or_default(),
             price,
             avg_price: price,
             orig_qty: quantity,
@@ -403,7 +845,9 @@ impl MockBinanceClient {
             order_type: order.order_type,
             side: order.side,
             update_time: chrono::Utc::now().timestamp_millis(),
-        })
+        };
+        self.order_valve.record_fill(Utc::now(), &response);
+        Ok(response)
     }
 
     /// Set leverage (no-op in mock).
@@ -475,6 +919,7 @@ impl MockBinanceClient {
         let mut state = self.state.write().await;
         if let Some(position) = state.positions.get_mut(symbol) {
             position.expected_funding_rate = rate;
+            self.mark_dirty();
             debug!(
                 %symbol, %rate,
                 "Set expected funding rate for position"
@@ -507,6 +952,7 @@ impl MockBinanceClient {
                         borrowed_amount: pos.borrowed_amount,
                         opened_at: pos.opened_at,
                         total_funding_received: pos.total_funding_received,
+                        total_funding_paid: pos.total_funding_paid,
                         total_interest_paid: pos.total_interest_paid,
                         funding_collections: pos.funding_collections,
                         expected_funding_rate: pos.expected_funding_rate,
@@ -519,6 +965,7 @@ impl MockBinanceClient {
             initial_balance: state.initial_balance,
             balance: state.balance,
             total_funding_received: state.total_funding_received,
+            total_funding_paid: state.total_funding_paid,
             total_trading_fees: state.total_trading_fees,
             total_borrow_interest: state.total_borrow_interest,
             order_count: state.order_count,
@@ -536,6 +983,7 @@ impl MockBinanceClient {
         state.initial_balance = persisted.initial_balance;
         state.balance = persisted.balance;
         state.total_funding_received = persisted.total_funding_received;
+        state.total_funding_paid = persisted.total_funding_paid;
         state.total_trading_fees = persisted.total_trading_fees;
         state.total_borrow_interest = persisted.total_borrow_interest;
         state.order_count = persisted.order_count;
@@ -555,6 +1003,7 @@ impl MockBinanceClient {
                         borrowed_amount: pos.borrowed_amount,
                         opened_at: pos.opened_at,
                         total_funding_received: pos.total_funding_received,
+                        total_funding_paid: pos.total_funding_paid,
                         total_interest_paid: pos.total_interest_paid,
                         funding_collections: pos.funding_collections,
                         expected_funding_rate: pos.expected_funding_rate,
@@ -566,6 +1015,7 @@ impl MockBinanceClient {
         // Update order counter to be higher than persisted count
         self.order_id_counter
             .store(persisted.order_count + 1, Ordering::SeqCst);
+        self.mark_dirty();
 
         info!(
             balance = %state.balance,
@@ -574,6 +1024,322 @@ impl MockBinanceClient {
             "Mock client state restored from persistence"
         );
     }
+
+    /// Simulated 24h statistics of every priced symbol (volume isn't simulated).
+    async fn simulated_tickers(&self) -> Vec<Ticker24h> {
+        let now = Utc::now().timestamp_millis();
+        self.prices
+            .read()
+            .await
+            .iter()
+            .map(|(symbol, &price)| Ticker24h {
+                symbol: symbol.clone(),
+                price_change: Decimal::ZERO,
+                price_change_percent: Decimal::ZERO,
+                last_price: price,
+                high_price: price,
+                low_price: price,
+                volume: Decimal::ZERO,
+                quote_volume: Decimal::ZERO,
+                open_time: now - 86_400_000,
+                close_time: now,
+            })
+            .collect()
+    }
+}
+
+/// Market data comes from the simulated rates and prices, so a mock only knows
+/// the symbols it was seeded or updated with. Spot and futures share a symbol,
+/// books have no spread and every base asset can be borrowed.
+#[async_trait]
+impl ExchangeClient for MockBinanceClient {
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        let calendar = FundingCalendar::default();
+        let now = Utc::now();
+        let prices = self.prices.read().await;
+        Ok(self
+            .funding_rates
+            .read()
+            .await
+            .iter()
+            .map(|(symbol, &rate)| FundingRate {
+                symbol: symbol.clone(),
+                funding_rate: rate,
+                funding_time: calendar
+                    .schedule(Venue::Binance, symbol)
+                    .next_settlement(now)
+                    .timestamp_millis(),
+                mark_price: prices.get(symbol).copied(),
+            })
+            .collect())
+    }
+
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        Ok(self.simulated_tickers().await)
+    }
+
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        Ok(self.simulated_tickers().await)
+    }
+
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        let mut tickers = self.simulated_tickers().await;
+        tickers.retain(|t| symbols.contains(&t.symbol));
+        Ok(tickers)
+    }
+
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        Ok(self
+            .prices
+            .read()
+            .await
+            .iter()
+            .map(|(symbol, &price)| BookTicker {
+                symbol: symbol.clone(),
+                bid_price: price,
+                bid_qty: Decimal::ZERO,
+                ask_price: price,
+                ask_qty: Decimal::ZERO,
+            })
+            .collect())
+    }
+
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        Ok(self
+            .prices
+            .read()
+            .await
+            .keys()
+            .filter_map(|symbol| {
+                let base = symbol.strip_suffix("USDT")?;
+                Some(SpotSymbolInfo {
+                    symbol: symbol.clone(),
+                    base_asset: base.to_string(),
+                    quote_asset: "USDT".to_string(),
+                    status: "TRADING".to_string(),
+                    is_margin_trading_allowed: true,
+                    filters: Vec::new(),
+                })
+            })
+            .collect())
+    }
+
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        let borrow_rates = self.borrow_rates.read().await;
+        Ok(self
+            .prices
+            .read()
+            .await
+            .keys()
+            .filter_map(|symbol| {
+                let base = symbol.strip_suffix("USDT")?;
+                let hourly = borrow_rates
+                    .get(symbol)
+                    .copied()
+                    .unwrap_or(DEFAULT_HOURLY_BORROW_RATE);
+                Some(MarginAsset {
+                    asset: base.to_string(),
+                    borrowable: true,
+                    collateral: true,
+                    margin_interest_rate: Some(hourly * dec!(24)),
+                })
+            })
+            .collect())
+    }
+
+    /// Every simulated symbol settles on the venue default without a cap.
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// Only the current rates are simulated.
+    async fn get_funding_rate_history(
+        &self,
+        _symbol: &str,
+        _start_time: i64,
+        _end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// No trading rules are simulated; the executor's default precision applies.
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        Ok(FuturesExchangeInfo {
+            symbols: Vec::new(),
+        })
+    }
+
+    /// Liquidation isn't simulated, so there are no margin tiers.
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        Ok(Vec::new())
+    }
+
+    /// Spot books aren't simulated; depth is sampled from the live venue.
+    async fn get_spot_depth(&self, symbol: &str, _limit: u16) -> Result<OrderBook> {
+        anyhow::bail!("No simulated order book for {}", symbol)
+    }
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        MockBinanceClient::place_futures_order(self, order).await
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        MockBinanceClient::place_margin_order(self, order).await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        MockBinanceClient::set_leverage(self, symbol, leverage).await
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        MockBinanceClient::set_margin_type(self, symbol, margin_type).await
+    }
+
+    /// Mock orders fill at once and aren't kept; only seeded resting orders are found.
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        Ok(self
+            .resting_orders
+            .read()
+            .await
+            .iter()
+            .filter(|resting| !resting.margin)
+            .find(|resting| {
+                resting.order.symbol == symbol && resting.order.client_order_id == client_order_id
+            })
+            .map(RestingOrder::lookup))
+    }
+
+    /// Mock orders fill at once and aren't kept, so no order is ever found.
+    async fn get_margin_order_by_client_id(
+        &self,
+        _symbol: &str,
+        _client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        Ok(None)
+    }
+
+    /// Only seeded resting orders are found; canceled ones are forgotten.
+    async fn get_futures_order(&self, symbol: &str, order_id: i64) -> Result<Option<OrderLookup>> {
+        Ok(self
+            .resting_orders
+            .read()
+            .await
+            .iter()
+            .filter(|resting| !resting.margin)
+            .find(|resting| resting.order.symbol == symbol && resting.order.order_id == order_id)
+            .map(RestingOrder::lookup))
+    }
+
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        Ok(self.open_resting_orders(false).await)
+    }
+
+    async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        self.cancel_resting_order(symbol, order_id, false).await
+    }
+
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        if self.margin_listing_down.load(Ordering::SeqCst) {
+            anyhow::bail!("Margin open order listing unavailable");
+        }
+        Ok(self.open_resting_orders(true).await)
+    }
+
+    async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        let order = self.cancel_resting_order(symbol, order_id, true).await?;
+        Ok(OrderLookup {
+            order_id: order.order_id,
+            symbol: order.symbol,
+            status: order.status,
+            client_order_id: order.client_order_id,
+            executed_qty: order.executed_qty,
+            avg_price: Some(order.avg_price),
+            cummulative_quote_qty: None,
+            side: order.side,
+            time: 0,
+        })
+    }
+
+    async fn amend_futures_order(&self, amend: &AmendOrder) -> Result<OrderResponse> {
+        let mut resting_orders = self.resting_orders.write().await;
+        let Some(resting) = resting_orders.iter_mut().find(|resting| {
+            !resting.margin
+                && resting.order.symbol == amend.symbol
+                && resting.order.order_id == amend.order_id
+        }) else {
+            anyhow::bail!("Unknown order {} on {}", amend.order_id, amend.symbol);
+        };
+        resting.order.price = amend.price;
+        resting.order.orig_qty = amend.quantity;
+        resting.order.update_time = Utc::now().timestamp_millis();
+        Ok(resting.order.clone())
+    }
+
+    /// Futures legs of the simulated positions; leverage and liquidation
+    /// aren't simulated.
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        let state = self.state.read().await;
+        let prices = self.prices.read().await;
+        Ok(state
+            .positions
+            .values()
+            .filter(|p| !p.futures_qty.is_zero())
+            .map(|p| {
+                let mark_price = prices
+                    .get(&p.symbol)
+                    .copied()
+                    .unwrap_or(p.futures_entry_price);
+                Position {
+                    symbol: p.symbol.clone(),
+                    position_amt: p.futures_qty,
+                    entry_price: p.futures_entry_price,
+                    mark_price,
+                    unrealized_profit: p.futures_qty * (mark_price - p.futures_entry_price),
+                    liquidation_price: Decimal::ZERO,
+                    leverage: 1,
+                    position_side: PositionSide::Both,
+                    notional: p.futures_qty * mark_price,
+                    isolated_margin: Decimal::ZERO,
+                    margin_type: MarginType::Cross,
+                }
+            })
+            .collect())
+    }
+
+    /// The simulated USDT balance; no margin is locked, so all of it is available.
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        let (_, unrealized) = self.calculate_pnl().await;
+        let balance = self.state.read().await.balance;
+        Ok(vec![AccountBalance {
+            asset: "USDT".to_string(),
+            wallet_balance: balance,
+            unrealized_profit: unrealized,
+            margin_balance: balance + unrealized,
+            available_balance: balance,
+        }])
+    }
+
+    /// Funding is settled into the simulated balance without an income ledger.
+    async fn get_income_history(
+        &self,
+        _income_type: &str,
+        _start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// Fills are applied to the simulated positions without a trade ledger.
+    async fn get_futures_trades(
+        &self,
+        _symbol: &str,
+        _start_time: i64,
+    ) -> Result<Vec<FuturesTrade>> {
+        Ok(Vec::new())
+    }
 }
 
 #[cfg(test)]
@@ -648,6 +1414,7 @@ mod tests {
             time_in_force: None,
             side_effect_type: Some(SideEffectType::MarginBuy),
             is_isolated: None,
+            new_client_order_id: None,
         };
         client.place_margin_order(&order).await.unwrap()
     }
@@ -805,6 +1572,42 @@ mod tests {
         assert!(balance_after > balance_before);
     }
 
+    #[tokio::test]
+    async fn test_funding_on_paying_side_recorded_as_paid() {
+        let client = create_test_client();
+
+        // Setup: Short position after the rate flipped negative
+        let mut prices = HashMap::new();
+        let mut rates = HashMap::new();
+        prices.insert("BTCUSDT".to_string(), dec!(50000));
+        rates.insert("BTCUSDT".to_string(), dec!(0.001));
+        client
+            .update_market_data(rates.clone(), prices.clone())
+            .await;
+        open_short_futures_position(&client, "BTCUSDT", dec!(1.0)).await;
+        client.collect_funding().await;
+
+        rates.insert("BTCUSDT".to_string(), dec!(-0.0004));
+        client.update_market_data(rates, prices).await;
+        let funding_map = client.collect_funding().await;
+
+        // Short (-1.0) pays 50000 * 0.0004 = $20: netted, and counted as paid
+        assert_eq!(funding_map["BTCUSDT"], dec!(-20));
+        let state = client.get_state().await;
+        assert_eq!(state.total_funding_received, dec!(30));
+        assert_eq!(state.total_funding_paid, dec!(20));
+        let position = &state.positions["BTCUSDT"];
+        assert_eq!(position.total_funding_received, dec!(30));
+        assert_eq!(position.total_funding_paid, dec!(20));
+
+        // Funding paid survives a restart
+        let client2 = create_test_client();
+        client2.restore_state(client.export_state().await).await;
+        let restored = client2.get_state().await;
+        assert_eq!(restored.total_funding_paid, dec!(20));
+        assert_eq!(restored.positions["BTCUSDT"].total_funding_paid, dec!(20));
+    }
+
     #[tokio::test]
     async fn test_funding_zero_rate_no_payment() {
         let client = create_test_client();
@@ -881,6 +1684,176 @@ mod tests {
         assert_eq!(eth_pos.total_funding_received, dec!(6));
     }
 
+    #[tokio::test]
+    async fn test_seeded_short_spot_hedge_borrows() {
+        let client = create_test_client();
+        client.seed_market("ALTUSDT", dec!(-0.001), dec!(10)).await;
+        client.seed_position("ALTUSDT", dec!(100), dec!(10)).await;
+
+        let positions = client.get_delta_neutral_positions().await;
+        assert_eq!(positions[0].spot_qty, dec!(-100));
+        assert_eq!(positions[0].borrowed_amount, dec!(100));
+
+        // Long futures earn negative funding
+        let funding = client.collect_funding().await;
+        assert_eq!(funding["ALTUSDT"], dec!(1));
+        assert_eq!(client.calculate_pnl().await.1, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_scenario_playback() {
+        let scenario: MarketScenario = toml::from_str(
+            r#"
+            initial_balance = 5000
+
+            [[symbols]]
+            symbol = "ALTUSDT"
+            price = 10
+            funding_rate = 0.001
+
+            [[events]]
+            at = "8h"
+            symbol = "ALTUSDT"
+            funding_rate = -0.001
+            price_change = -0.15
+
+            [[events]]
+            at = "9h"
+            order_failures = 1
+            "#,
+        )
+        .unwrap();
+        let start = Utc::now();
+        let client = MockBinanceClient::from_scenario(&scenario, start);
+        assert_eq!(client.get_state().await.balance, dec!(5000));
+
+        // Scripted values override live data
+        let live = HashMap::from([("ALTUSDT".to_string(), dec!(12))]);
+        client.update_market_data(live.clone(), live).await;
+        open_short_futures_position(&client, "ALTUSDT", dec!(100)).await;
+        assert_eq!(client.collect_funding().await["ALTUSDT"], dec!(1));
+
+        // Funding flips and the price crashes at T+8h
+        let played = client
+            .advance_scenario(start + chrono::Duration::hours(8))
+            .await;
+        assert_eq!(played.len(), 1);
+        assert_eq!(client.collect_funding().await["ALTUSDT"], dec!(-0.85));
+
+        // The next order fails once
+        client
+            .advance_scenario(start + chrono::Duration::hours(9))
+            .await;
+        let order = NewOrder {
+            symbol: "ALTUSDT".to_string(),
+            side: OrderSide::Buy,
+            position_side: None,
+            order_type: OrderType::Market,
+            quantity: Some(dec!(100)),
+            price: None,
+            time_in_force: None,
+            reduce_only: Some(true),
+            new_client_order_id: None,
+        };
+        assert!(client.place_futures_order(&order).await.is_err());
+        assert!(client.place_futures_order(&order).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_collect_funding_for_settling_symbols_only() {
+        let client = create_test_client();
+
+        let mut prices = HashMap::new();
+        let mut rates = HashMap::new();
+        prices.insert("BTCUSDT".to_string(), dec!(50000));
+        prices.insert("SOLUSDT".to_string(), dec!(100));
+        rates.insert("BTCUSDT".to_string(), dec!(0.0005));
+        rates.insert("SOLUSDT".to_string(), dec!(0.001));
+        client.update_market_data(rates, prices).await;
+
+        open_short_futures_position(&client, "BTCUSDT", dec!(0.1)).await;
+        open_short_futures_position(&client, "SOLUSDT", dec!(10)).await;
+
+        // A 4h-only settlement pays SOL and leaves BTC untouched
+        let funding = client.collect_funding_for(&["SOLUSDT".to_string()]).await;
+        assert_eq!(funding.len(), 1);
+        assert_eq!(funding["SOLUSDT"], dec!(1));
+
+        let state = client.get_state().await;
+        assert_eq!(state.positions["BTCUSDT"].funding_collections, 0);
+        assert_eq!(state.positions["SOLUSDT"].funding_collections, 1);
+    }
+
+    #[test]
+    fn test_missed_funding_settlements() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let mut calendar = FundingCalendar::new();
+        calendar.set_schedule(
+            Venue::Binance,
+            "SOLUSDT",
+            crate::exchange::FundingSchedule::every_hours(4).unwrap(),
+        );
+        let symbols = vec!["BTCUSDT".to_string(), "SOLUSDT".to_string()];
+
+        // Offline from 07:30 to 17:10 misses 08:00 and 16:00, plus 12:00 on a 4h schedule
+        let missed = missed_funding_settlements(
+            &calendar,
+            &symbols,
+            at("2024-03-01T07:30:00Z"),
+            at("2024-03-01T17:10:00Z"),
+        );
+        assert_eq!(
+            missed["BTCUSDT"],
+            vec![at("2024-03-01T08:00:00Z"), at("2024-03-01T16:00:00Z")]
+        );
+        assert_eq!(missed["SOLUSDT"].len(), 3);
+
+        // Saved exactly at a settlement: that one was already collected
+        let missed = missed_funding_settlements(
+            &calendar,
+            &symbols,
+            at("2024-03-01T08:00:00Z"),
+            at("2024-03-01T11:59:00Z"),
+        );
+        assert!(missed.is_empty());
+
+        // Across midnight
+        let missed = missed_funding_settlements(
+            &calendar,
+            &symbols[..1],
+            at("2024-03-01T23:00:00Z"),
+            at("2024-03-02T00:00:00Z"),
+        );
+        assert_eq!(missed["BTCUSDT"], vec![at("2024-03-02T00:00:00Z")]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_missed_funding() {
+        let client = setup_client_with_price(dec!(50000)).await;
+        open_short_futures_position(&client, "BTCUSDT", dec!(0.1)).await;
+        let balance_before = client.get_state().await.balance;
+
+        let mut settlements = HashMap::new();
+        settlements.insert("BTCUSDT".to_string(), vec![dec!(0.0005), dec!(-0.0001)]);
+        // Symbols without a position are ignored
+        settlements.insert("ETHUSDT".to_string(), vec![dec!(0.001)]);
+        let mut prices = HashMap::new();
+        prices.insert("BTCUSDT".to_string(), dec!(50000));
+
+        let caught_up = client.apply_missed_funding(&settlements, &prices).await;
+
+        // 5000 * 0.0005 - 5000 * 0.0001 = $2.00
+        assert_eq!(caught_up.len(), 1);
+        assert_eq!(caught_up["BTCUSDT"], dec!(2));
+
+        let state = client.get_state().await;
+        let position = &state.positions["BTCUSDT"];
+        assert_eq!(position.funding_collections, 2);
+        assert_eq!(state.balance, balance_before + dec!(2));
+        assert_eq!(state.total_funding_received, dec!(2));
+        assert_eq!(state.total_funding_paid, dec!(0.5));
+    }
+
     // =========================================================================
     // Interest Accrual Tests
     // =========================================================================
@@ -954,6 +1927,29 @@ mod tests {
         assert_eq!(balance_before, balance_after);
     }
 
+    #[tokio::test]
+    async fn test_revision_changes_only_on_mutation() {
+        let client = setup_client_with_price(dec!(50000)).await;
+        let start = client.revision();
+
+        // Nothing held: funding and interest leave the state untouched
+        client.collect_funding().await;
+        client.accrue_interest(dec!(1)).await;
+        assert_eq!(client.revision(), start);
+
+        open_short_futures_position(&client, "BTCUSDT", dec!(0.1)).await;
+        let after_order = client.revision();
+        assert!(after_order > start);
+
+        // Futures-only position has no borrow, so no interest mutation
+        client.accrue_interest(dec!(1)).await;
+        assert_eq!(client.revision(), after_order);
+
+        open_margin_short(&client, "BTCUSDT", dec!(0.1)).await;
+        client.accrue_interest(dec!(1)).await;
+        assert!(client.revision() > after_order + 1);
+    }
+
     #[tokio::test]
     async fn test_interest_tracks_per_position() {
         let client = create_test_client();
@@ -1020,6 +2016,37 @@ mod tests {
         assert_eq!(state.order_count, 3);
     }
 
+    #[tokio::test]
+    async fn test_maker_rebate_credits_limit_fills() {
+        let mut client = setup_client_with_price(dec!(50000)).await;
+        client.set_fee_schedule(FeeSchedule {
+            maker_rate: dec!(-0.00005),
+            taker_rate: dec!(0.0004),
+        });
+        let balance_before = client.get_state().await.balance;
+
+        // Post-only sell resting at the price: 1.0 * 50000 * 0.00005 = $2.50 rebate
+        let order = NewOrder {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::Limit,
+            quantity: Some(dec!(1.0)),
+            price: Some(dec!(50000)),
+            time_in_force: Some(TimeInForce::Gtx),
+            reduce_only: None,
+            new_client_order_id: None,
+        };
+        client.place_futures_order(&order).await.unwrap();
+        let state = client.get_state().await;
+        assert_eq!(state.total_trading_fees, dec!(-2.5));
+        assert_eq!(state.balance, balance_before + dec!(2.5));
+
+        // Market orders still pay the taker rate
+        open_long_futures_position(&client, "BTCUSDT", dec!(1.0)).await;
+        assert_eq!(client.get_state().await.total_trading_fees, dec!(17.5));
+    }
+
     #[tokio::test]
     async fn test_margin_order_fee_calculation() {
         let client = setup_client_with_price(dec!(50000)).await;
@@ -1066,6 +2093,7 @@ mod tests {
             time_in_force: None,
             side_effect_type: Some(SideEffectType::MarginBuy),
             is_isolated: None,
+            new_client_order_id: None,
         };
         client.place_margin_order(&order).await.unwrap();
 
@@ -1218,6 +2246,7 @@ mod tests {
             time_in_force: None,
             side_effect_type: Some(SideEffectType::MarginBuy),
             is_isolated: None,
+            new_client_order_id: None,
         };
         client.place_margin_order(&order).await.unwrap();
 
@@ -1306,4 +2335,125 @@ mod tests {
         let sol = state.positions.get("SOLUSDT").unwrap();
         assert_eq!(sol.total_funding_received, dec!(0.2));
     }
+
+    // =========================================================================
+    // Existing Holdings Tests
+    // =========================================================================
+
+    #[tokio::test]
+    async fn test_existing_holdings_committed_without_fees() {
+        let client = setup_client_with_price(dec!(50000)).await;
+        let mut holdings = HashMap::new();
+        holdings.insert("BTCUSDT".to_string(), dec!(0.5));
+        client.set_spot_holdings(holdings).await;
+
+        let spot_order = |side, quantity| MarginOrder {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            order_type: OrderType::Market,
+            quantity: Some(quantity),
+            price: None,
+            time_in_force: None,
+            side_effect_type: Some(SideEffectType::NoSideEffect),
+            is_isolated: None,
+            new_client_order_id: None,
+        };
+
+        // 0.5 comes from holdings, only the extra 0.1 is bought: fee = 0.1 * 50000 * 0.0004
+        client
+            .place_margin_order(&spot_order(OrderSide::Buy, dec!(0.6)))
+            .await
+            .unwrap();
+        assert_eq!(client.get_state().await.total_trading_fees, dec!(2));
+
+        // Selling releases holdings first; only bought coins beyond them pay fees
+        client
+            .place_margin_order(&spot_order(OrderSide::Sell, dec!(0.6)))
+            .await
+            .unwrap();
+        let state = client.get_state().await;
+        assert_eq!(state.total_trading_fees, dec!(4));
+        assert_eq!(state.positions["BTCUSDT"].spot_qty, Decimal::ZERO);
+
+        // Released holdings can be committed again
+        client
+            .place_margin_order(&spot_order(OrderSide::Buy, dec!(0.5)))
+            .await
+            .unwrap();
+        assert_eq!(client.get_state().await.total_trading_fees, dec!(4));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_client_view() {
+        let mock = MockBinanceClient::new(dec!(10000));
+        mock.seed_market("BTCUSDT", dec!(0.0005), dec!(50000)).await;
+        mock.seed_position("BTCUSDT", dec!(-0.1), dec!(49000)).await;
+        let client: &dyn ExchangeClient = &mock;
+
+        let rates = client.get_funding_rates().await.unwrap();
+        assert_eq!(rates[0].funding_rate, dec!(0.0005));
+        assert_eq!(rates[0].mark_price, Some(dec!(50000)));
+        assert!(rates[0].funding_time > Utc::now().timestamp_millis());
+        let spot = client.get_spot_exchange_info().await.unwrap();
+        assert_eq!(spot[0].base_asset, "BTC");
+
+        // Short 0.1 from 49000, marked at 50000
+        let positions = client.get_positions().await.unwrap();
+        assert_eq!(positions[0].position_amt, dec!(-0.1));
+        assert_eq!(positions[0].unrealized_profit, dec!(-100));
+        let balance = &client.get_account_balance().await.unwrap()[0];
+        assert_eq!(balance.wallet_balance, dec!(10000));
+        // The spot leg offsets the futures loss
+        assert_eq!(balance.unrealized_profit, Decimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_resting_orders_can_be_amended_and_cancelled() {
+        let mock = MockBinanceClient::new(dec!(10000));
+        let order = NewOrder {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::Limit,
+            quantity: Some(dec!(0.1)),
+            price: Some(dec!(50000)),
+            time_in_force: Some(TimeInForce::Gtx),
+            reduce_only: None,
+            new_client_order_id: Some("fff-lq2x9k1a-0-1".to_string()),
+        };
+        let resting = mock.seed_resting_order(&order, Utc::now()).await;
+        let client: &dyn ExchangeClient = &mock;
+
+        let amended = client
+            .amend_futures_order(&AmendOrder {
+                symbol: "BTCUSDT".to_string(),
+                order_id: resting.order_id,
+                side: OrderSide::Sell,
+                quantity: dec!(0.05),
+                price: dec!(50100),
+            })
+            .await
+            .unwrap();
+        assert_eq!(amended.price, dec!(50100));
+        assert_eq!(amended.orig_qty, dec!(0.05));
+
+        let found = client
+            .get_futures_order("BTCUSDT", resting.order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.status, OrderStatus::New);
+        assert_eq!(client.get_open_futures_orders().await.unwrap().len(), 1);
+
+        let cancelled = client
+            .cancel_futures_order("BTCUSDT", resting.order_id)
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, OrderStatus::Canceled);
+        assert!(client.get_open_futures_orders().await.unwrap().is_empty());
+        assert!(client
+            .cancel_futures_order("BTCUSDT", resting.order_id)
+            .await
+            .is_err());
+    }
 }
@@ -1,16 +1,78 @@
-//! Binance exchange integration.
+//! Binance, Bybit and OKX exchange integration.
 //!
-//! Provides both REST API and WebSocket connectivity for:
+//! Binance provides both REST API and WebSocket connectivity for:
 //! - Market data (funding rates, orderbook, trades)
 //! - Account operations (orders, positions, balance)
 //! - User data streams (order updates, position changes)
+//! - A mark price and funding rate cache fed by the mark price stream
+//! - A best bid/ask feed of held symbols fed by their book ticker streams
+//! - Order rate and notional velocity limits on both clients
+//! - REST weight and order usage against the account's API limits
+//! - Request throttling that keeps REST weight under those limits
+//! - Retries of transient failures, with failed calls classified by cause
+//! - Signed request timestamps on the server clock, calibrated against its time
+//! - HMAC or Ed25519 request signing
+//! - One-at-a-time account-mutating calls, closes first
+//! - Leverage and margin type checks of held symbols
+//! - COIN-margined perpetuals, collateralized by the coins they settle in
+//! - Scripted market scenarios played back by the mock client
+//! - A venue-independent `ExchangeClient` trait implemented by both clients
+//!
+//! Bybit and OKX are REST-only alternative trading venues behind the same trait.
+//! A venue router can put each symbol on whichever of Binance and OKX pays more
+//! funding.
+//!
+//! Funding settlement schedules are kept per venue and symbol. Hyperliquid funding
+//! rates are fetched for cross-venue comparison, and with a signing key its perps
+//! hold the Hyperliquid leg of cross-venue spread trades.
 
+mod api_budget;
+mod api_error;
+mod bybit;
 mod client;
+mod exchange_client;
+mod funding_calendar;
+mod hyperliquid;
+mod mark_price_cache;
 pub mod mock;
+mod okx;
+mod order_valve;
+mod price_feed;
+mod rate_limiter;
+mod scenario;
+mod server_clock;
+mod signing;
+mod sub_account;
+mod symbol_settings;
+mod trade_queue;
 mod types;
+mod venue_router;
 mod websocket;
 
+pub use api_budget::{
+    ApiMarket, ApiUsage, BudgetBreach, BudgetLimit, DailyUsage, MarketUsage, RequestCost,
+    UsageProjection,
+};
+pub use api_error::{error_class, replicate_error, ApiError, ErrorClass};
+pub use bybit::BybitClient;
 pub use client::BinanceClient;
-pub use mock::MockBinanceClient;
+pub use exchange_client::ExchangeClient;
+pub use funding_calendar::{FundingCalendar, FundingSchedule, Venue};
+pub use hyperliquid::{HyperliquidClient, HyperliquidFill, HyperliquidPosition, HyperliquidSigner};
+pub use mark_price_cache::{MarkPriceCache, MarkPriceEntry};
+pub use mock::{missed_funding_settlements, MockBinanceClient};
+pub use okx::OkxClient;
+pub use order_valve::{OrderValve, ValveTrip};
+pub use price_feed::PriceFeed;
+pub use rate_limiter::WeightLimiter;
+pub use scenario::{MarketScenario, ScenarioEvent, ScenarioOffset, ScenarioSymbol};
+pub use server_clock::ServerClock;
+pub use signing::RequestSigner;
+pub use sub_account::{
+    top_up_amount, transfer_request, CapitalTransfer, SubAccountManager, TransferDirection,
+};
+pub use symbol_settings::{settings_mismatches, SettingsMismatch};
+pub use trade_queue::{TradeLane, TradeQueue, TradeTurn};
 pub use types::*;
-pub use websocket::BinanceWebSocket;
+pub use venue_router::VenueRouter;
+pub use websocket::{BinanceWebSocket, OrderUpdate, UserDataStream, WsEvent};
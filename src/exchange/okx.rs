@@ -0,0 +1,2158 @@
+//! OKX V5 REST API client.
+//!
+//! Trades USDT-margined perpetual swaps and spot on margin through a
+//! multi-currency margin account, so the delta-neutral strategy can run on OKX
+//! or route symbols to it when it pays more funding than Binance. OKX responses
+//! are translated into the Binance-shaped exchange types here: instrument IDs
+//! ("BTC-USDT-SWAP", "BTC-USDT") become "BTCUSDT", and swap sizes quoted in
+//! contracts become base-asset quantities.
+//!
+//! Margin mode is chosen per order on OKX (`tdMode`), not per symbol; the mode
+//! set with `set_margin_type` is remembered and sent with that symbol's orders.
+//! Spot borrowing is implicit in a multi-currency margin account: a spot order
+//! in cross mode borrows what it lacks, and liabilities are repaid once the
+//! account holds the coin again.
+
+use crate::config::OkxConfig;
+use crate::exchange::client::{
+    is_retryable_error, is_retryable_status, with_jitter, BACKOFF_MULTIPLIER, INITIAL_BACKOFF_MS,
+    MAX_RETRIES,
+};
+use crate::exchange::types::*;
+use crate::exchange::{ApiError, ExchangeClient, OrderValve, TradeLane, TradeQueue};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder, Response};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+use tracing::{debug, instrument, warn};
+
+const BASE_URL: &str = "https://www.okx.com";
+
+/// "Order does not exist"
+const ORDER_NOT_FOUND: &str = "51603";
+
+/// Longest client order ID OKX accepts
+const MAX_CLIENT_ORDER_ID_LEN: usize = 32;
+
+/// Bill types of the account bill archive
+const BILL_TYPE_TRADE: &str = "2";
+const BILL_TYPE_FUNDING: &str = "8";
+
+/// Attempts to read back a placed order before reporting it as still open
+const ORDER_QUERY_ATTEMPTS: u32 = 5;
+const ORDER_QUERY_DELAY_MS: u64 = 200;
+
+/// Response envelope of every V5 endpoint.
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default = "Vec::new")]
+    data: Vec<T>,
+}
+
+impl<T> Envelope<T> {
+    fn into_result(self, operation: &str) -> Result<Vec<T>> {
+        if self.code != "0" {
+            bail!(
+                "{} rejected by OKX ({}): {}",
+                operation,
+                self.code,
+                self.msg
+            );
+        }
+        Ok(self.data)
+    }
+}
+
+/// Decimal sent as a string; OKX leaves fields that don't apply empty.
+fn lenient_decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    if text.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+/// Millisecond timestamp sent as a string, or empty.
+fn lenient_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    if text.is_empty() {
+        return Ok(0);
+    }
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+/// Binance-style symbol of an OKX instrument ("BTC-USDT-SWAP" -> "BTCUSDT").
+fn symbol_of(inst_id: &str) -> String {
+    inst_id.trim_end_matches("-SWAP").replace('-', "")
+}
+
+/// OKX coin pair of a USDT-quoted Binance-style symbol ("BTCUSDT" -> "BTC-USDT").
+fn pair_of(symbol: &str) -> Result<String> {
+    match symbol.strip_suffix("USDT") {
+        Some(base) if !base.is_empty() => Ok(format!("{}-USDT", base)),
+        _ => bail!(
+            "{} is not a USDT pair; OKX symbols are mapped for USDT only",
+            symbol
+        ),
+    }
+}
+
+/// Perpetual swap instrument ID of a symbol ("BTCUSDT" -> "BTC-USDT-SWAP").
+fn swap_inst_id(symbol: &str) -> Result<String> {
+    Ok(format!("{}-SWAP", pair_of(symbol)?))
+}
+
+/// Client order ID in the form OKX accepts: letters and digits, at most 32.
+///
+/// IDs that are too long keep their start and end a stable hash of the whole
+/// ID, so placing and looking up an order map it the same way.
+fn okx_client_order_id(client_order_id: &str) -> String {
+    let id: String = client_order_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    if id.len() <= MAX_CLIENT_ORDER_ID_LEN {
+        return id;
+    }
+    // FNV-1a, so the same ID always maps to the same suffix
+    let hash = client_order_id
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{}{:016x}", &id[..MAX_CLIENT_ORDER_ID_LEN - 16], hash)
+}
+
+/// Numeric form of an OKX ID: the ID itself when numeric, else zero.
+fn numeric_id(id: &str) -> i64 {
+    id.parse().unwrap_or_default()
+}
+
+/// Ticker of a swap or spot instrument.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTicker {
+    inst_id: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    last: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    open24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    high24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    low24h: Decimal,
+    /// Base volume for spot; contracts for swaps
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    vol24h: Decimal,
+    /// Quote volume for spot; base volume for swaps
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    vol_ccy24h: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    bid_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    bid_sz: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    ask_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    ask_sz: Decimal,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    ts: i64,
+}
+
+impl RawTicker {
+    fn is_swap(&self) -> bool {
+        self.inst_id.ends_with("-SWAP")
+    }
+
+    /// 24h statistics; OKX windows end at the ticker's timestamp.
+    fn ticker_24h(&self) -> Ticker24h {
+        let (volume, quote_volume) = if self.is_swap() {
+            (self.vol_ccy24h, self.vol_ccy24h * self.last)
+        } else {
+            (self.vol24h, self.vol_ccy24h)
+        };
+        let price_change = self.last - self.open24h;
+        Ticker24h {
+            symbol: symbol_of(&self.inst_id),
+            price_change,
+            price_change_percent: if self.open24h.is_zero() {
+                Decimal::ZERO
+            } else {
+                price_change / self.open24h * Decimal::ONE_HUNDRED
+            },
+            last_price: self.last,
+            high_price: self.high24h,
+            low_price: self.low24h,
+            volume,
+            quote_volume,
+            open_time: self.ts - 24 * 60 * 60 * 1000,
+            close_time: self.ts,
+        }
+    }
+
+    /// Best bid and ask, with swap sizes converted from `contract_value` contracts.
+    fn book_ticker(&self, contract_value: Decimal) -> BookTicker {
+        BookTicker {
+            symbol: symbol_of(&self.inst_id),
+            bid_price: self.bid_px,
+            bid_qty: self.bid_sz * contract_value,
+            ask_price: self.ask_px,
+            ask_qty: self.ask_sz * contract_value,
+        }
+    }
+}
+
+/// Swap instrument from `/api/v5/public/instruments`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSwapInstrument {
+    inst_id: String,
+    #[serde(default)]
+    inst_family: String,
+    #[serde(default)]
+    settle_ccy: String,
+    /// "linear" or "inverse"
+    #[serde(default)]
+    ct_type: String,
+    /// Base asset per contract
+    #[serde(deserialize_with = "lenient_decimal")]
+    ct_val: Decimal,
+    #[serde(default)]
+    ct_val_ccy: String,
+    /// Order size step, in contracts
+    #[serde(deserialize_with = "lenient_decimal")]
+    lot_sz: Decimal,
+    #[serde(deserialize_with = "lenient_decimal")]
+    tick_sz: Decimal,
+    /// "live", "suspend", "preopen" or "test"
+    state: String,
+}
+
+impl RawSwapInstrument {
+    /// USDT-margined swaps, the only ones the strategy trades.
+    fn is_usdt_linear(&self) -> bool {
+        self.ct_type == "linear" && self.settle_ccy == "USDT"
+    }
+
+    fn symbol_info(&self) -> FuturesSymbolInfo {
+        FuturesSymbolInfo {
+            symbol: symbol_of(&self.inst_id),
+            // Quantities are base assets: one lot is `lot_sz` contracts of `ct_val` each
+            quantity_precision: (self.lot_sz * self.ct_val).normalize().scale() as u8,
+            price_precision: self.tick_sz.normalize().scale() as u8,
+            contract_type: "PERPETUAL".to_string(),
+            status: trading_status(&self.state),
+            base_asset: self.ct_val_ccy.clone(),
+            quote_asset: self.settle_ccy.clone(),
+            filters: Vec::new(),
+        }
+    }
+}
+
+/// Spot or margin instrument from `/api/v5/public/instruments`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSpotInstrument {
+    inst_id: String,
+    base_ccy: String,
+    quote_ccy: String,
+    state: String,
+    /// Order size step, in base asset
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    lot_sz: Decimal,
+}
+
+/// Binance's status name for a tradable symbol, so filters work on both venues.
+fn trading_status(state: &str) -> String {
+    match state {
+        "live" => "TRADING".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+/// Current funding of a swap from `/api/v5/public/funding-rate`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawFundingRate {
+    inst_id: String,
+    #[serde(deserialize_with = "lenient_decimal")]
+    funding_rate: Decimal,
+    /// Next settlement, which `funding_rate` applies to
+    #[serde(deserialize_with = "lenient_millis")]
+    funding_time: i64,
+    /// Settlement after the next one
+    #[serde(default, deserialize_with = "lenient_millis")]
+    next_funding_time: i64,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    max_funding_rate: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    min_funding_rate: Decimal,
+}
+
+impl RawFundingRate {
+    fn funding_rate(&self) -> FundingRate {
+        FundingRate {
+            symbol: symbol_of(&self.inst_id),
+            funding_rate: self.funding_rate,
+            funding_time: self.funding_time,
+            mark_price: None,
+        }
+    }
+
+    /// Settlement interval and caps; `None` until the settlement after next is published.
+    fn funding_info(&self) -> Option<FundingInfo> {
+        let interval_ms = self.next_funding_time - self.funding_time;
+        if self.next_funding_time == 0 || interval_ms <= 0 {
+            return None;
+        }
+        let has_cap = !self.max_funding_rate.is_zero();
+        Some(FundingInfo {
+            symbol: symbol_of(&self.inst_id),
+            funding_interval_hours: (interval_ms / (60 * 60 * 1000)) as u32,
+            adjusted_funding_rate_cap: has_cap.then_some(self.max_funding_rate),
+            adjusted_funding_rate_floor: has_cap.then_some(self.min_funding_rate),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawFundingRecord {
+    inst_id: String,
+    #[serde(deserialize_with = "lenient_decimal")]
+    funding_rate: Decimal,
+    /// Rate actually settled, empty for settlements not yet realized
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    realized_rate: Decimal,
+    #[serde(deserialize_with = "lenient_millis")]
+    funding_time: i64,
+}
+
+impl RawFundingRecord {
+    fn record(&self) -> FundingRateRecord {
+        FundingRateRecord {
+            symbol: symbol_of(&self.inst_id),
+            funding_rate: if self.realized_rate.is_zero() {
+                self.funding_rate
+            } else {
+                self.realized_rate
+            },
+            funding_time: self.funding_time,
+        }
+    }
+}
+
+/// Order book from `/api/v5/market/books`, levels as `[price, size, _, orders]`.
+#[derive(Debug, Deserialize)]
+struct RawOrderBook {
+    bids: Vec<Vec<String>>,
+    asks: Vec<Vec<String>>,
+    #[serde(deserialize_with = "lenient_millis")]
+    ts: i64,
+}
+
+impl RawOrderBook {
+    fn order_book(&self) -> Result<OrderBook> {
+        let levels = |side: &[Vec<String>]| -> Result<Vec<(Decimal, Decimal)>> {
+            side.iter()
+                .map(|level| match level.as_slice() {
+                    [price, size, ..] => Ok((price.parse()?, size.parse()?)),
+                    _ => bail!("Order book level without price and size"),
+                })
+                .collect()
+        };
+        Ok(OrderBook {
+            last_update_id: self.ts,
+            bids: levels(&self.bids).context("Failed to parse order book bids")?,
+            asks: levels(&self.asks).context("Failed to parse order book asks")?,
+        })
+    }
+}
+
+/// Position tier from `/api/v5/public/position-tiers`, sizes in contracts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPositionTier {
+    #[serde(deserialize_with = "lenient_decimal")]
+    tier: Decimal,
+    #[serde(deserialize_with = "lenient_decimal")]
+    max_sz: Decimal,
+    /// Maintenance margin ratio
+    #[serde(deserialize_with = "lenient_decimal")]
+    mmr: Decimal,
+    #[serde(deserialize_with = "lenient_decimal")]
+    max_lever: Decimal,
+}
+
+/// Binance-style notional brackets of one symbol from its position tiers.
+///
+/// Tier sizes are converted to notional at `notional_per_contract`; the
+/// cumulative maintenance amount is derived the way Binance publishes it, so the
+/// liquidation maths is the same on every venue.
+fn tier_brackets(
+    symbol: &str,
+    mut tiers: Vec<RawPositionTier>,
+    notional_per_contract: Decimal,
+) -> LeverageBracket {
+    tiers.sort_by_key(|tier| tier.tier);
+    let mut brackets = Vec::with_capacity(tiers.len());
+    let mut floor = Decimal::ZERO;
+    let mut previous_rate = Decimal::ZERO;
+    let mut cum = Decimal::ZERO;
+    for (index, tier) in tiers.iter().enumerate() {
+        let cap = tier.max_sz * notional_per_contract;
+        cum += floor * (tier.mmr - previous_rate);
+        brackets.push(NotionalBracket {
+            bracket: (index + 1) as u8,
+            initial_leverage: tier.max_lever.to_u8().unwrap_or(u8::MAX),
+            notional_cap: cap,
+            notional_floor: floor,
+            maint_margin_ratio: tier.mmr,
+            cum,
+        });
+        floor = cap;
+        previous_rate = tier.mmr;
+    }
+    LeverageBracket {
+        symbol: symbol.to_string(),
+        brackets,
+    }
+}
+
+/// Borrow rate of a coin from `/api/v5/account/interest-rate`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawInterestRate {
+    ccy: String,
+    /// Hourly rate
+    #[serde(deserialize_with = "lenient_decimal")]
+    interest_rate: Decimal,
+}
+
+impl RawInterestRate {
+    /// Every coin with a borrow rate can be borrowed and, in a multi-currency
+    /// margin account, serves as collateral.
+    fn margin_asset(&self) -> MarginAsset {
+        MarginAsset {
+            asset: self.ccy.clone(),
+            borrowable: true,
+            collateral: true,
+            margin_interest_rate: Some(self.interest_rate * Decimal::from(24)),
+        }
+    }
+}
+
+/// Acknowledgement of `/api/v5/trade/order`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderAck {
+    #[serde(default)]
+    ord_id: String,
+    #[serde(default)]
+    s_code: String,
+    #[serde(default)]
+    s_msg: String,
+}
+
+/// Accepted order ID of a placement, or why it was rejected.
+fn accepted_order_id(envelope: Envelope<OrderAck>, operation: &str) -> Result<String> {
+    let (code, msg) = (envelope.code.clone(), envelope.msg.clone());
+    match envelope.data.into_iter().next() {
+        Some(ack) if ack.s_code == "0" && code == "0" => Ok(ack.ord_id),
+        Some(ack) => bail!(
+            "{} rejected by OKX ({}): {}",
+            operation,
+            ack.s_code,
+            ack.s_msg
+        ),
+        None => bail!("{} rejected by OKX ({}): {}", operation, code, msg),
+    }
+}
+
+/// Order from `/api/v5/trade/order`; swap sizes in contracts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOrder {
+    inst_id: String,
+    ord_id: String,
+    /// Client order ID in OKX's form; see `okx_client_order_id`
+    #[serde(default)]
+    cl_ord_id: String,
+    side: String,
+    ord_type: String,
+    state: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avg_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    sz: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    acc_fill_sz: Decimal,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    c_time: i64,
+    #[serde(default, deserialize_with = "lenient_millis")]
+    u_time: i64,
+}
+
+impl RawOrder {
+    fn status(&self) -> OrderStatus {
+        match self.state.as_str() {
+            "partially_filled" => OrderStatus::PartiallyFilled,
+            "filled" => OrderStatus::Filled,
+            "canceled" | "mmp_canceled" => OrderStatus::Canceled,
+            // "live"
+            _ => OrderStatus::New,
+        }
+    }
+
+    /// Whether the order has stopped trading.
+    fn is_settled(&self) -> bool {
+        !matches!(
+            self.status(),
+            OrderStatus::New | OrderStatus::PartiallyFilled
+        )
+    }
+
+    fn side(&self) -> OrderSide {
+        if self.side == "sell" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        }
+    }
+
+    /// Response with sizes converted at `contract_value` (one for spot), tagged
+    /// with the caller's `client_order_id`.
+    fn order_response(&self, contract_value: Decimal, client_order_id: &str) -> OrderResponse {
+        OrderResponse {
+            order_id: numeric_id(&self.ord_id),
+            symbol: symbol_of(&self.inst_id),
+            status: self.status(),
+            client_order_id: client_order_id.to_string(),
+            price: self.px,
+            avg_price: self.avg_px,
+            orig_qty: self.sz * contract_value,
+            executed_qty: self.acc_fill_sz * contract_value,
+            side: self.side(),
+            order_type: if self.ord_type == "market" {
+                OrderType::Market
+            } else {
+                OrderType::Limit
+            },
+            time_in_force: match self.ord_type.as_str() {
+                "limit" => Some(TimeInForce::Gtc),
+                "ioc" | "optimal_limit_ioc" => Some(TimeInForce::Ioc),
+                "fok" => Some(TimeInForce::Fok),
+                "post_only" => Some(TimeInForce::Gtx),
+                _ => None,
+            },
+            update_time: self.u_time,
+        }
+    }
+
+    fn order_lookup(&self, contract_value: Decimal, client_order_id: &str) -> OrderLookup {
+        OrderLookup {
+            order_id: numeric_id(&self.ord_id),
+            symbol: symbol_of(&self.inst_id),
+            status: self.status(),
+            client_order_id: client_order_id.to_string(),
+            executed_qty: self.acc_fill_sz * contract_value,
+            avg_price: Some(self.avg_px),
+            cummulative_quote_qty: None,
+            side: self.side(),
+            time: self.c_time,
+        }
+    }
+}
+
+/// Swap position from `/api/v5/account/positions`, size in contracts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPosition {
+    inst_id: String,
+    /// Signed in net mode; positive with `pos_side` in long/short mode
+    #[serde(deserialize_with = "lenient_decimal")]
+    pos: Decimal,
+    /// "net", "long" or "short"
+    #[serde(default)]
+    pos_side: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avg_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    mark_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    upl: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    liq_px: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    lever: Decimal,
+    /// "cross" or "isolated"
+    #[serde(default)]
+    mgn_mode: String,
+    /// Isolated margin
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    margin: Decimal,
+}
+
+impl RawPosition {
+    /// Position in base-asset quantity at `contract_value` per contract.
+    fn position(&self, contract_value: Decimal) -> Position {
+        let contracts = if self.pos_side == "short" {
+            -self.pos.abs()
+        } else {
+            self.pos
+        };
+        let position_amt = contracts * contract_value;
+        let margin_type = if self.mgn_mode == "isolated" {
+            MarginType::Isolated
+        } else {
+            MarginType::Cross
+        };
+        Position {
+            symbol: symbol_of(&self.inst_id),
+            position_amt,
+            entry_price: self.avg_px,
+            mark_price: self.mark_px,
+            unrealized_profit: self.upl,
+            liquidation_price: self.liq_px,
+            leverage: self.lever.to_u8().unwrap_or_default(),
+            position_side: PositionSide::Both,
+            notional: position_amt * self.mark_px,
+            isolated_margin: if margin_type == MarginType::Isolated {
+                self.margin
+            } else {
+                Decimal::ZERO
+            },
+            margin_type,
+        }
+    }
+}
+
+/// Trading account from `/api/v5/account/balance`.
+#[derive(Debug, Deserialize)]
+struct RawAccount {
+    #[serde(default)]
+    details: Vec<RawCoinBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCoinBalance {
+    ccy: String,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    cash_bal: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    eq: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    upl: Decimal,
+    /// Equity available for margin (multi-currency and portfolio margin modes)
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avail_eq: Decimal,
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    avail_bal: Decimal,
+}
+
+impl RawCoinBalance {
+    fn balance(&self) -> AccountBalance {
+        AccountBalance {
+            asset: self.ccy.clone(),
+            wallet_balance: self.cash_bal,
+            unrealized_profit: self.upl,
+            margin_balance: self.eq,
+            available_balance: if self.avail_eq.is_zero() {
+                self.avail_bal
+            } else {
+                self.avail_eq
+            },
+        }
+    }
+}
+
+/// Entry of `/api/v5/account/bills-archive`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBill {
+    bill_id: String,
+    #[serde(default)]
+    inst_id: String,
+    #[serde(default)]
+    ccy: String,
+    /// Balance change (positive = received)
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    bal_chg: Decimal,
+    /// Trading fee (negative = charged, positive = rebate)
+    #[serde(default, deserialize_with = "lenient_decimal")]
+    fee: Decimal,
+    #[serde(deserialize_with = "lenient_millis")]
+    ts: i64,
+}
+
+/// Bills holding a Binance income type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IncomeSource {
+    /// Funding fees ("FUNDING_FEE")
+    Funding,
+    /// Trade fees ("COMMISSION")
+    Trade,
+}
+
+impl IncomeSource {
+    fn for_income_type(income_type: &str) -> Result<Self> {
+        match income_type {
+            "FUNDING_FEE" => Ok(IncomeSource::Funding),
+            "COMMISSION" => Ok(IncomeSource::Trade),
+            other => bail!("Income type {} has no OKX bill equivalent", other),
+        }
+    }
+
+    /// Bill `type` filter.
+    fn bill_type(&self) -> &'static str {
+        match self {
+            IncomeSource::Funding => BILL_TYPE_FUNDING,
+            IncomeSource::Trade => BILL_TYPE_TRADE,
+        }
+    }
+
+    /// Income of `bill`, positive when received.
+    fn income(&self, bill: &RawBill) -> Decimal {
+        match self {
+            IncomeSource::Funding => bill.bal_chg,
+            IncomeSource::Trade => bill.fee,
+        }
+    }
+}
+
+/// OKX API key, secret and passphrase.
+#[derive(Clone)]
+struct Credentials {
+    api_key: String,
+    secret_key: String,
+    passphrase: String,
+}
+
+impl Credentials {
+    /// Base64 HMAC-SHA256 over timestamp, method, request path and body.
+    fn sign(&self, timestamp: &str, method: &str, request_path: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(timestamp.as_bytes());
+        mac.update(method.as_bytes());
+        mac.update(request_path.as_bytes());
+        mac.update(body.as_bytes());
+        base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Size and tier family of a swap.
+#[derive(Debug, Clone)]
+struct SwapContract {
+    /// Base asset per contract
+    contract_value: Decimal,
+    inst_family: String,
+}
+
+/// OKX client for USDT perpetual swaps and spot margin of a multi-currency
+/// margin account.
+pub struct OkxClient {
+    http: Client,
+    credentials: Credentials,
+    base_url: String,
+    /// Send orders to the demo trading environment
+    demo: bool,
+    /// Contract sizes of USDT swaps by symbol, loaded on first use
+    contracts: Mutex<HashMap<String, SwapContract>>,
+    /// Margin mode set per symbol, sent as each order's `tdMode`
+    margin_modes: Mutex<HashMap<String, MarginType>>,
+    /// Order rate and notional velocity limits
+    order_valve: OrderValve,
+    /// Serializes account-mutating calls
+    trade_queue: TradeQueue,
+}
+
+impl OkxClient {
+    /// Create a new OKX client from configuration.
+    pub fn new(config: &OkxConfig) -> Result<Self> {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            http,
+            credentials: Credentials {
+                api_key: config.api_key.clone(),
+                secret_key: config.secret_key.clone(),
+                passphrase: config.passphrase.clone(),
+            },
+            base_url: BASE_URL.to_string(),
+            demo: config.demo,
+            contracts: Mutex::new(HashMap::new()),
+            margin_modes: Mutex::new(HashMap::new()),
+            order_valve: OrderValve::default(),
+            trade_queue: TradeQueue::new(),
+        })
+    }
+
+    /// Guard order placement with `valve`.
+    pub fn set_order_valve(&mut self, valve: OrderValve) {
+        self.order_valve = valve;
+    }
+
+    /// Get current timestamp in milliseconds.
+    fn timestamp() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+
+    /// Execute an HTTP request with retry and jittered exponential backoff, on the same
+    /// terms as the Binance client.
+    async fn send<F>(&self, operation: &str, request_fn: F) -> Result<Response>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let mut backoff_ms = INITIAL_BACKOFF_MS;
+        for attempt in 1..=MAX_RETRIES {
+            match request_fn().send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if is_retryable_status(status) && attempt < MAX_RETRIES {
+                        warn!(
+                            %operation,
+                            attempt,
+                            status = %status,
+                            backoff_ms,
+                            "Retryable HTTP status, backing off"
+                        );
+                    } else if status.is_success() {
+                        return Ok(response);
+                    } else {
+                        let error_text = response.text().await.unwrap_or_default();
+                        return Err(ApiError::from_response(operation, status, &error_text).into());
+                    }
+                }
+                Err(e) if is_retryable_error(&e) && attempt < MAX_RETRIES => {
+                    warn!(
+                        %operation,
+                        attempt,
+                        error = %e,
+                        backoff_ms,
+                        "Retryable network error, backing off"
+                    );
+                }
+                Err(e) => return Err(ApiError::from_network(operation, &e).into()),
+            }
+            sleep(with_jitter(backoff_ms)).await;
+            backoff_ms *= BACKOFF_MULTIPLIER;
+        }
+        Err(anyhow!(
+            "{} failed after {} retries",
+            operation,
+            MAX_RETRIES
+        ))
+    }
+
+    /// GET an endpoint, signing the request when `signed`.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        query: &str,
+        signed: bool,
+    ) -> Result<Envelope<T>> {
+        // The signed path must match the URL exactly, so no `?` without a query
+        let request_path = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, query)
+        };
+        let url = format!("{}{}", self.base_url, request_path);
+        let timestamp = chrono::Utc::now()
+            .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .to_string();
+        let signature = signed.then(|| self.credentials.sign(&timestamp, "GET", &request_path, ""));
+
+        let response = self
+            .send(operation, || {
+                let request = self.http.get(&url);
+                match &signature {
+                    Some(signature) => self.authenticate(request, &timestamp, signature),
+                    None => request,
+                }
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", operation))
+    }
+
+    /// POST a signed JSON body.
+    async fn post<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<Envelope<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let body = body.to_string();
+        let timestamp = chrono::Utc::now()
+            .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .to_string();
+        let signature = self.credentials.sign(&timestamp, "POST", path, &body);
+
+        let response = self
+            .send(operation, || {
+                let request = self
+                    .http
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .body(body.clone());
+                self.authenticate(request, &timestamp, &signature)
+            })
+            .await?;
+
+        response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {} response", operation))
+    }
+
+    fn authenticate(
+        &self,
+        request: RequestBuilder,
+        timestamp: &str,
+        signature: &str,
+    ) -> RequestBuilder {
+        let request = request
+            .header("OK-ACCESS-KEY", &self.credentials.api_key)
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &self.credentials.passphrase);
+        if self.demo {
+            request.header("x-simulated-trading", "1")
+        } else {
+            request
+        }
+    }
+
+    async fn get_tickers(&self, inst_type: &str) -> Result<Vec<RawTicker>> {
+        let query = format!("instType={}", inst_type);
+        self.get("get_tickers", "/api/v5/market/tickers", &query, false)
+            .await?
+            .into_result("get_tickers")
+    }
+
+    async fn get_swap_instruments(&self) -> Result<Vec<RawSwapInstrument>> {
+        let instruments: Vec<RawSwapInstrument> = self
+            .get(
+                "get_swap_instruments",
+                "/api/v5/public/instruments",
+                "instType=SWAP",
+                false,
+            )
+            .await?
+            .into_result("get_swap_instruments")?;
+        Ok(instruments
+            .into_iter()
+            .filter(RawSwapInstrument::is_usdt_linear)
+            .collect())
+    }
+
+    /// Contract sizes of every USDT swap, fetched once.
+    async fn swap_contracts(&self) -> Result<HashMap<String, SwapContract>> {
+        {
+            let contracts = self.contracts.lock().unwrap_or_else(|e| e.into_inner());
+            if !contracts.is_empty() {
+                return Ok(contracts.clone());
+            }
+        }
+        let loaded: HashMap<String, SwapContract> = self
+            .get_swap_instruments()
+            .await?
+            .into_iter()
+            .map(|instrument| {
+                (
+                    symbol_of(&instrument.inst_id),
+                    SwapContract {
+                        contract_value: instrument.ct_val,
+                        inst_family: instrument.inst_family,
+                    },
+                )
+            })
+            .collect();
+        *self.contracts.lock().unwrap_or_else(|e| e.into_inner()) = loaded.clone();
+        Ok(loaded)
+    }
+
+    /// Base asset per contract of `symbol`'s swap.
+    async fn contract_value(&self, symbol: &str) -> Result<Decimal> {
+        self.swap_contracts()
+            .await?
+            .get(symbol)
+            .map(|contract| contract.contract_value)
+            .with_context(|| format!("{} has no USDT swap on OKX", symbol))
+    }
+
+    async fn get_raw_positions(&self) -> Result<Vec<RawPosition>> {
+        let positions: Vec<RawPosition> = self
+            .get(
+                "get_positions",
+                "/api/v5/account/positions",
+                "instType=SWAP",
+                true,
+            )
+            .await?
+            .into_result("get_positions")?;
+        Ok(positions
+            .into_iter()
+            .filter(|position| !position.pos.is_zero() && position.inst_id.ends_with("-USDT-SWAP"))
+            .collect())
+    }
+
+    // ==================== Market Data (Public) ====================
+
+    /// Get funding rates for all USDT perpetual swaps.
+    #[instrument(skip(self))]
+    pub async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        let rates = self.get_raw_funding_rates().await?;
+        Ok(rates.iter().map(RawFundingRate::funding_rate).collect())
+    }
+
+    async fn get_raw_funding_rates(&self) -> Result<Vec<RawFundingRate>> {
+        let rates: Vec<RawFundingRate> = self
+            .get(
+                "get_funding_rates",
+                "/api/v5/public/funding-rate",
+                "instId=ANY",
+                false,
+            )
+            .await?
+            .into_result("get_funding_rates")?;
+        Ok(rates
+            .into_iter()
+            .filter(|rate| rate.inst_id.ends_with("-USDT-SWAP"))
+            .collect())
+    }
+
+    /// Get funding intervals and caps of all USDT perpetual swaps.
+    ///
+    /// OKX publishes these for every swap, not only those off the default.
+    #[instrument(skip(self))]
+    pub async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        let rates = self.get_raw_funding_rates().await?;
+        Ok(rates
+            .iter()
+            .filter_map(RawFundingRate::funding_info)
+            .collect())
+    }
+
+    /// Get settled funding rates of `symbol` between `start_time` and `end_time` (ms),
+    /// oldest first.
+    #[instrument(skip(self))]
+    pub async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        const PAGE_LIMIT: usize = 100;
+        let inst_id = swap_inst_id(symbol)?;
+        let mut records = Vec::new();
+        // `after` returns settlements strictly earlier than it
+        let mut after = end_time + 1;
+
+        // Pages run newest first; walk back until the start is reached
+        loop {
+            let query = format!(
+                "instId={}&before={}&after={}&limit={}",
+                inst_id,
+                start_time - 1,
+                after,
+                PAGE_LIMIT
+            );
+            let page: Vec<RawFundingRecord> = self
+                .get(
+                    "get_funding_rate_history",
+                    "/api/v5/public/funding-rate-history",
+                    &query,
+                    false,
+                )
+                .await?
+                .into_result("get_funding_rate_history")?;
+
+            let page_len = page.len();
+            let oldest = page.iter().map(|r| r.funding_time).min();
+            records.extend(page.iter().map(RawFundingRecord::record));
+
+            match oldest {
+                Some(time) if page_len == PAGE_LIMIT && time > start_time => after = time,
+                _ => break,
+            }
+        }
+
+        records.retain(|record| (start_time..=end_time).contains(&record.funding_time));
+        records.sort_by_key(|record| record.funding_time);
+        Ok(records)
+    }
+
+    /// Get 24h ticker statistics for all USDT perpetual swaps.
+    #[instrument(skip(self))]
+    pub async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let tickers = self.get_tickers("SWAP").await?;
+        Ok(tickers
+            .iter()
+            .filter(|ticker| ticker.inst_id.ends_with("-USDT-SWAP"))
+            .map(RawTicker::ticker_24h)
+            .collect())
+    }
+
+    /// Get 24h ticker statistics for all spot symbols.
+    #[instrument(skip(self))]
+    pub async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let tickers = self.get_tickers("SPOT").await?;
+        Ok(tickers.iter().map(RawTicker::ticker_24h).collect())
+    }
+
+    /// Get 24h ticker statistics for the given spot symbols.
+    ///
+    /// OKX returns every spot ticker in one response, so this filters it.
+    #[instrument(skip(self, symbols), fields(count = symbols.len()))]
+    pub async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        let tickers = self.get_spot_24h_tickers().await?;
+        Ok(tickers
+            .into_iter()
+            .filter(|ticker| symbols.contains(&ticker.symbol))
+            .collect())
+    }
+
+    /// Get best bid and ask of all USDT perpetual swaps.
+    #[instrument(skip(self))]
+    pub async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        let contracts = self.swap_contracts().await?;
+        let tickers = self.get_tickers("SWAP").await?;
+        Ok(tickers
+            .iter()
+            .filter_map(|ticker| {
+                let contract = contracts.get(&symbol_of(&ticker.inst_id))?;
+                Some(ticker.book_ticker(contract.contract_value))
+            })
+            .collect())
+    }
+
+    /// Get precision and status of all USDT perpetual swaps.
+    #[instrument(skip(self))]
+    pub async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        let instruments = self.get_swap_instruments().await?;
+        Ok(FuturesExchangeInfo {
+            symbols: instruments
+                .iter()
+                .map(RawSwapInstrument::symbol_info)
+                .collect(),
+        })
+    }
+
+    /// Get position tiers of the held swaps as notional brackets.
+    ///
+    /// OKX publishes tiers per instrument family, so only the families of open
+    /// positions are fetched, sized at their mark price; symbols without a
+    /// position use the default maintenance rate.
+    #[instrument(skip(self))]
+    pub async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        let contracts = self.swap_contracts().await?;
+        let positions = self.get_raw_positions().await?;
+        let mut brackets = Vec::new();
+        let mut seen = HashSet::new();
+        for position in positions {
+            let symbol = symbol_of(&position.inst_id);
+            let Some(contract) = contracts.get(&symbol) else {
+                continue;
+            };
+            if !seen.insert(symbol.clone()) {
+                continue;
+            }
+            let query = format!(
+                "instType=SWAP&tdMode=cross&instFamily={}",
+                contract.inst_family
+            );
+            let tiers: Vec<RawPositionTier> = self
+                .get(
+                    "get_leverage_brackets",
+                    "/api/v5/public/position-tiers",
+                    &query,
+                    false,
+                )
+                .await?
+                .into_result("get_leverage_brackets")?;
+            brackets.push(tier_brackets(
+                &symbol,
+                tiers,
+                contract.contract_value * position.mark_px,
+            ));
+        }
+        Ok(brackets)
+    }
+
+    /// Get spot symbols and whether they trade on margin.
+    #[instrument(skip(self))]
+    pub async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        let spot: Vec<RawSpotInstrument> = self
+            .get(
+                "get_spot_exchange_info",
+                "/api/v5/public/instruments",
+                "instType=SPOT",
+                false,
+            )
+            .await?
+            .into_result("get_spot_exchange_info")?;
+        let margin: Vec<RawSpotInstrument> = self
+            .get(
+                "get_spot_exchange_info",
+                "/api/v5/public/instruments",
+                "instType=MARGIN",
+                false,
+            )
+            .await?
+            .into_result("get_spot_exchange_info")?;
+        let on_margin: HashSet<&str> = margin.iter().map(|i| i.inst_id.as_str()).collect();
+
+        Ok(spot
+            .iter()
+            .map(|instrument| SpotSymbolInfo {
+                symbol: symbol_of(&instrument.inst_id),
+                base_asset: instrument.base_ccy.clone(),
+                quote_asset: instrument.quote_ccy.clone(),
+                status: trading_status(&instrument.state),
+                is_margin_trading_allowed: on_margin.contains(instrument.inst_id.as_str()),
+                filters: vec![SpotSymbolFilter::LotSize {
+                    step_size: instrument.lot_sz,
+                }],
+            })
+            .collect())
+    }
+
+    /// Get spot order book depth for a symbol.
+    #[instrument(skip(self))]
+    pub async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        let query = format!("instId={}&sz={}", pair_of(symbol)?, limit);
+        let books: Vec<RawOrderBook> = self
+            .get("get_spot_depth", "/api/v5/market/books", &query, false)
+            .await?
+            .into_result("get_spot_depth")?;
+        books
+            .first()
+            .context("get_spot_depth returned no book")?
+            .order_book()
+    }
+
+    // ==================== Account (Authenticated) ====================
+
+    /// Get coins that can be borrowed and their daily borrow rate.
+    #[instrument(skip(self))]
+    pub async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        let rates: Vec<RawInterestRate> = self
+            .get(
+                "get_margin_all_assets",
+                "/api/v5/account/interest-rate",
+                "",
+                true,
+            )
+            .await?
+            .into_result("get_margin_all_assets")?;
+        Ok(rates.iter().map(RawInterestRate::margin_asset).collect())
+    }
+
+    /// Get balances of the trading account.
+    #[instrument(skip(self))]
+    pub async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        let accounts: Vec<RawAccount> = self
+            .get("get_account_balance", "/api/v5/account/balance", "", true)
+            .await?
+            .into_result("get_account_balance")?;
+        Ok(accounts
+            .iter()
+            .flat_map(|account| account.details.iter().map(RawCoinBalance::balance))
+            .collect())
+    }
+
+    /// Get open USDT swap positions.
+    #[instrument(skip(self))]
+    pub async fn get_positions(&self) -> Result<Vec<Position>> {
+        let contracts = self.swap_contracts().await?;
+        let positions = self.get_raw_positions().await?;
+        Ok(positions
+            .iter()
+            .filter_map(|position| {
+                let contract = contracts.get(&symbol_of(&position.inst_id))?;
+                Some(position.position(contract.contract_value))
+            })
+            .collect())
+    }
+
+    /// Get swap income of a Binance income type since `start_time` (ms).
+    ///
+    /// Funding fees and commissions come from funding and trade bills of the
+    /// bill archive, paged newest first.
+    #[instrument(skip(self))]
+    pub async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        const PAGE_LIMIT: usize = 100;
+        let source = IncomeSource::for_income_type(income_type)?;
+        let mut records = Vec::new();
+        let mut after = String::new();
+
+        loop {
+            let mut query = format!(
+                "instType=SWAP&type={}&begin={}&limit={}",
+                source.bill_type(),
+                start_time,
+                PAGE_LIMIT
+            );
+            if !after.is_empty() {
+                query.push_str(&format!("&after={}", after));
+            }
+            let bills: Vec<RawBill> = self
+                .get(
+                    "get_income_history",
+                    "/api/v5/account/bills-archive",
+                    &query,
+                    true,
+                )
+                .await?
+                .into_result("get_income_history")?;
+
+            let page_len = bills.len();
+            records.extend(bills.iter().map(|bill| IncomeRecord {
+                symbol: symbol_of(&bill.inst_id),
+                income_type: income_type.to_string(),
+                income: source.income(bill),
+                asset: bill.ccy.clone(),
+                time: bill.ts,
+                tran_id: numeric_id(&bill.bill_id),
+            }));
+            match bills.last() {
+                Some(oldest) if page_len == PAGE_LIMIT => after = oldest.bill_id.clone(),
+                _ => break,
+            }
+        }
+
+        records.sort_by_key(|record| record.time);
+        Ok(records)
+    }
+
+    // ==================== Orders (Authenticated) ====================
+
+    /// Margin mode of `symbol`'s orders (cross unless set otherwise).
+    fn margin_mode(&self, symbol: &str) -> MarginType {
+        self.margin_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(symbol)
+            .copied()
+            .unwrap_or(MarginType::Cross)
+    }
+
+    /// Place a USDT perpetual swap order, sized in base asset.
+    #[instrument(skip(self))]
+    pub async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        let lane = if order.reduce_only == Some(true) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let contract_value = self.contract_value(&order.symbol).await?;
+        let mut body = order_body(
+            &swap_inst_id(&order.symbol)?,
+            td_mode(self.margin_mode(&order.symbol)),
+            order.side,
+            order.order_type,
+            order.quantity.map(|qty| (qty / contract_value).normalize()),
+            order.price,
+            order.time_in_force,
+            order.new_client_order_id.as_deref(),
+        )?;
+        if let Some(reduce_only) = order.reduce_only {
+            body["reduceOnly"] = reduce_only.into();
+        }
+
+        debug!("Placing futures order: {:?}", order);
+        self.place_order(
+            "place_futures_order",
+            lane,
+            &body,
+            contract_value,
+            order.new_client_order_id.as_deref(),
+        )
+        .await
+    }
+
+    /// Place a spot order that borrows and repays through the margin account.
+    #[instrument(skip(self))]
+    pub async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        let lane = if order.side_effect_type == Some(SideEffectType::AutoRepay) {
+            TradeLane::Close
+        } else {
+            TradeLane::Normal
+        };
+        let uses_margin = !matches!(
+            order.side_effect_type,
+            None | Some(SideEffectType::NoSideEffect)
+        );
+        let mut body = order_body(
+            &pair_of(&order.symbol)?,
+            if uses_margin { "cross" } else { "cash" },
+            order.side,
+            order.order_type,
+            order.quantity,
+            order.price,
+            order.time_in_force,
+            order.new_client_order_id.as_deref(),
+        )?;
+        if order.order_type == OrderType::Market {
+            // Market buys are sized in the quote coin unless told otherwise
+            body["tgtCcy"] = "base_ccy".into();
+        }
+
+        debug!("Placing margin order: {:?}", order);
+        self.place_order(
+            "place_margin_order",
+            lane,
+            &body,
+            Decimal::ONE,
+            order.new_client_order_id.as_deref(),
+        )
+        .await
+    }
+
+    /// Send an order and read back its state.
+    ///
+    /// OKX only acknowledges the order ID on creation, so fills are read from
+    /// the order itself, briefly waiting for a market or IOC order to settle.
+    async fn place_order(
+        &self,
+        operation: &str,
+        lane: TradeLane,
+        body: &serde_json::Value,
+        contract_value: Decimal,
+        client_order_id: Option<&str>,
+    ) -> Result<OrderResponse> {
+        let _turn = self.trade_queue.turn(lane).await;
+        self.order_valve.admit(chrono::Utc::now())?;
+
+        let envelope: Envelope<OrderAck> =
+            self.post(operation, "/api/v5/trade/order", body).await?;
+        let order_id = accepted_order_id(envelope, operation)?;
+        let inst_id = body["instId"].as_str().unwrap_or_default();
+        let client_order_id = client_order_id.unwrap_or_default();
+        let resting = matches!(body["ordType"].as_str(), Some("limit" | "post_only"));
+
+        let mut last_seen = None;
+        for attempt in 1..=ORDER_QUERY_ATTEMPTS {
+            let query = format!("instId={}&ordId={}", inst_id, order_id);
+            let orders: Vec<RawOrder> = self
+                .get("get_order", "/api/v5/trade/order", &query, true)
+                .await?
+                .into_result("get_order")?;
+            if let Some(order) = orders.into_iter().next() {
+                let settled = order.is_settled() || resting;
+                last_seen = Some(order);
+                if settled {
+                    break;
+                }
+            }
+            if attempt < ORDER_QUERY_ATTEMPTS {
+                sleep(Duration::from_millis(ORDER_QUERY_DELAY_MS)).await;
+            }
+        }
+
+        let response = match last_seen {
+            Some(order) => order.order_response(contract_value, client_order_id),
+            None => {
+                warn!(%inst_id, %order_id, "Placed order not yet visible, reporting it as new");
+                unseen_order_response(&order_id, body, contract_value, client_order_id)
+            }
+        };
+        self.order_valve.record_fill(chrono::Utc::now(), &response);
+        Ok(response)
+    }
+
+    /// Look up an order of `inst_id` by client order ID, open or closed.
+    async fn get_order_by_client_id(
+        &self,
+        inst_id: &str,
+        contract_value: Decimal,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let query = format!(
+            "instId={}&clOrdId={}",
+            inst_id,
+            okx_client_order_id(client_order_id)
+        );
+        let envelope: Envelope<RawOrder> = self
+            .get(
+                "get_order_by_client_id",
+                "/api/v5/trade/order",
+                &query,
+                true,
+            )
+            .await?;
+        if envelope.code == ORDER_NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(envelope
+            .into_result("get_order_by_client_id")?
+            .first()
+            .map(|order| order.order_lookup(contract_value, client_order_id)))
+    }
+
+    /// Look up a swap order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        let contract_value = self.contract_value(symbol).await?;
+        self.get_order_by_client_id(&swap_inst_id(symbol)?, contract_value, client_order_id)
+            .await
+    }
+
+    /// Look up a spot order by client order ID.
+    ///
+    /// Returns `None` when the exchange has no such order.
+    #[instrument(skip(self))]
+    pub async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.get_order_by_client_id(&pair_of(symbol)?, Decimal::ONE, client_order_id)
+            .await
+    }
+
+    /// Pending orders of `inst_type` across instruments, newest first.
+    async fn get_pending_orders(&self, inst_type: &str) -> Result<Vec<RawOrder>> {
+        const PAGE_LIMIT: usize = 100;
+        let mut orders: Vec<RawOrder> = Vec::new();
+        loop {
+            let mut query = format!("instType={}&limit={}", inst_type, PAGE_LIMIT);
+            if let Some(last) = orders.last() {
+                query.push_str(&format!("&after={}", last.ord_id));
+            }
+            let page: Vec<RawOrder> = self
+                .get(
+                    "get_pending_orders",
+                    "/api/v5/trade/orders-pending",
+                    &query,
+                    true,
+                )
+                .await?
+                .into_result("get_pending_orders")?;
+            let full_page = page.len() == PAGE_LIMIT;
+            orders.extend(page);
+            if !full_page {
+                return Ok(orders);
+            }
+        }
+    }
+
+    /// Cancel an open order of `inst_id` and read back its state.
+    async fn cancel_order(&self, inst_id: &str, order_id: i64) -> Result<RawOrder> {
+        let _turn = self.trade_queue.turn(TradeLane::Close).await;
+        let body = serde_json::json!({
+            "instId": inst_id,
+            "ordId": order_id.to_string(),
+        });
+        let envelope: Envelope<OrderAck> = self
+            .post("cancel_order", "/api/v5/trade/cancel-order", &body)
+            .await?;
+        accepted_order_id(envelope, "cancel_order")?;
+
+        let query = format!("instId={}&ordId={}", inst_id, order_id);
+        let orders: Vec<RawOrder> = self
+            .get("get_order", "/api/v5/trade/order", &query, true)
+            .await?
+            .into_result("get_order")?;
+        orders
+            .into_iter()
+            .next()
+            .with_context(|| format!("Cancelled order {} of {} not found", order_id, inst_id))
+    }
+
+    /// Swap orders still open, across USDT perpetuals, sized in base asset.
+    #[instrument(skip(self))]
+    pub async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        let orders = self.get_pending_orders("SWAP").await?;
+        let contracts = self.swap_contracts().await?;
+        Ok(orders
+            .iter()
+            .filter_map(|order| {
+                let contract = contracts.get(&symbol_of(&order.inst_id))?;
+                Some(order.order_lookup(contract.contract_value, &order.cl_ord_id))
+            })
+            .collect())
+    }
+
+    /// Cancel an open swap order.
+    #[instrument(skip(self))]
+    pub async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        let contract_value = self.contract_value(symbol).await?;
+        let order = self.cancel_order(&swap_inst_id(symbol)?, order_id).await?;
+        Ok(order.order_response(contract_value, &order.cl_ord_id))
+    }
+
+    /// Spot and margin orders still open, across pairs.
+    #[instrument(skip(self))]
+    pub async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        let mut lookups = Vec::new();
+        for inst_type in ["SPOT", "MARGIN"] {
+            let orders = self.get_pending_orders(inst_type).await?;
+            lookups.extend(
+                orders
+                    .iter()
+                    .map(|order| order.order_lookup(Decimal::ONE, &order.cl_ord_id)),
+            );
+        }
+        Ok(lookups)
+    }
+
+    /// Cancel an open spot or margin order.
+    #[instrument(skip(self))]
+    pub async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        let order = self.cancel_order(&pair_of(symbol)?, order_id).await?;
+        Ok(order.order_lookup(Decimal::ONE, &order.cl_ord_id))
+    }
+
+    /// Set leverage of a USDT perpetual swap in its margin mode.
+    #[instrument(skip(self))]
+    pub async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        let _turn = self.trade_queue.turn(TradeLane::Normal).await;
+        let body = serde_json::json!({
+            "instId": swap_inst_id(symbol)?,
+            "lever": leverage.to_string(),
+            "mgnMode": td_mode(self.margin_mode(symbol)),
+        });
+        self.post::<serde_json::Value>("set_leverage", "/api/v5/account/set-leverage", &body)
+            .await?
+            .into_result("set_leverage")
+            .map(|_| ())
+    }
+
+    /// Set the margin mode of `symbol`'s orders.
+    ///
+    /// OKX takes the margin mode with each order, so this only changes the mode
+    /// later orders are sent with; nothing is sent to the exchange.
+    #[instrument(skip(self))]
+    pub async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        self.margin_modes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(symbol.to_string(), margin_type);
+        Ok(())
+    }
+}
+
+/// `tdMode` of a swap order in `margin_type`.
+fn td_mode(margin_type: MarginType) -> &'static str {
+    match margin_type {
+        MarginType::Cross => "cross",
+        MarginType::Isolated => "isolated",
+    }
+}
+
+/// Place-order body shared by swap and spot orders; `size` is in contracts for
+/// swaps and base asset for spot.
+#[allow(clippy::too_many_arguments)]
+fn order_body(
+    inst_id: &str,
+    td_mode: &str,
+    side: OrderSide,
+    order_type: OrderType,
+    size: Option<Decimal>,
+    price: Option<Decimal>,
+    time_in_force: Option<TimeInForce>,
+    client_order_id: Option<&str>,
+) -> Result<serde_json::Value> {
+    let ord_type = match (order_type, time_in_force) {
+        (OrderType::Market, _) => "market",
+        (OrderType::Limit, None | Some(TimeInForce::Gtc)) => "limit",
+        (OrderType::Limit, Some(TimeInForce::Ioc)) => "ioc",
+        (OrderType::Limit, Some(TimeInForce::Fok)) => "fok",
+        (OrderType::Limit, Some(TimeInForce::Gtx)) => "post_only",
+        (other, _) => bail!("{:?} orders are not supported on OKX", other),
+    };
+    let mut body = serde_json::json!({
+        "instId": inst_id,
+        "tdMode": td_mode,
+        "side": format!("{:?}", side).to_lowercase(),
+        "ordType": ord_type,
+    });
+    if let Some(size) = size {
+        body["sz"] = size.to_string().into();
+    }
+    if let Some(price) = price {
+        body["px"] = price.to_string().into();
+    }
+    if let Some(client_id) = client_order_id {
+        body["clOrdId"] = okx_client_order_id(client_id).into();
+    }
+    Ok(body)
+}
+
+/// Response for an accepted order whose state could not be read back yet.
+fn unseen_order_response(
+    order_id: &str,
+    body: &serde_json::Value,
+    contract_value: Decimal,
+    client_order_id: &str,
+) -> OrderResponse {
+    let decimal = |field: &str| {
+        body[field]
+            .as_str()
+            .and_then(|value| value.parse::<Decimal>().ok())
+            .unwrap_or_default()
+    };
+    OrderResponse {
+        order_id: numeric_id(order_id),
+        symbol: symbol_of(body["instId"].as_str().unwrap_or_default()),
+        status: OrderStatus::New,
+        client_order_id: client_order_id.to_string(),
+        price: decimal("px"),
+        avg_price: Decimal::ZERO,
+        orig_qty: decimal("sz") * contract_value,
+        executed_qty: Decimal::ZERO,
+        side: if body["side"] == "sell" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        },
+        order_type: if body["ordType"] == "market" {
+            OrderType::Market
+        } else {
+            OrderType::Limit
+        },
+        time_in_force: None,
+        update_time: OkxClient::timestamp() as i64,
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for OkxClient {
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        OkxClient::get_funding_rates(self).await
+    }
+
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        OkxClient::get_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        OkxClient::get_spot_24h_tickers(self).await
+    }
+
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        OkxClient::get_spot_24h_tickers_for(self, symbols).await
+    }
+
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        OkxClient::get_book_tickers(self).await
+    }
+
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        OkxClient::get_spot_exchange_info(self).await
+    }
+
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        OkxClient::get_margin_all_assets(self).await
+    }
+
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        OkxClient::get_funding_info(self).await
+    }
+
+    async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        OkxClient::get_funding_rate_history(self, symbol, start_time, end_time).await
+    }
+
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        OkxClient::get_futures_exchange_info(self).await
+    }
+
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        OkxClient::get_leverage_brackets(self).await
+    }
+
+    async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        OkxClient::get_spot_depth(self, symbol, limit).await
+    }
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        OkxClient::place_futures_order(self, order).await
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        OkxClient::place_margin_order(self, order).await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        OkxClient::set_leverage(self, symbol, leverage).await
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        OkxClient::set_margin_type(self, symbol, margin_type).await
+    }
+
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        OkxClient::get_futures_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        OkxClient::get_margin_order_by_client_id(self, symbol, client_order_id).await
+    }
+
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        OkxClient::get_open_futures_orders(self).await
+    }
+
+    async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        OkxClient::cancel_futures_order(self, symbol, order_id).await
+    }
+
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        OkxClient::get_open_margin_orders(self).await
+    }
+
+    async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        OkxClient::cancel_margin_order(self, symbol, order_id).await
+    }
+
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        OkxClient::get_positions(self).await
+    }
+
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        OkxClient::get_account_balance(self).await
+    }
+
+    async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        OkxClient::get_income_history(self, income_type, start_time).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_symbol_mapping() {
+        assert_eq!(symbol_of("BTC-USDT-SWAP"), "BTCUSDT");
+        assert_eq!(symbol_of("ETH-USDT"), "ETHUSDT");
+        assert_eq!(swap_inst_id("BTCUSDT").unwrap(), "BTC-USDT-SWAP");
+        assert_eq!(pair_of("1000PEPEUSDT").unwrap(), "1000PEPE-USDT");
+        assert!(pair_of("BTCUSDC").is_err());
+        assert!(pair_of("USDT").is_err());
+    }
+
+    #[test]
+    fn test_funding_rates_and_intervals() {
+        let json = r#"{
+            "code": "0",
+            "msg": "",
+            "data": [
+                {
+                    "instId": "BTC-USDT-SWAP",
+                    "fundingRate": "0.0001",
+                    "fundingTime": "1700006400000",
+                    "nextFundingTime": "1700035200000",
+                    "maxFundingRate": "0.0075",
+                    "minFundingRate": "-0.0075"
+                },
+                {
+                    "instId": "WIF-USDT-SWAP",
+                    "fundingRate": "-0.0004",
+                    "fundingTime": "1700006400000",
+                    "nextFundingTime": "1700020800000",
+                    "maxFundingRate": "0.02",
+                    "minFundingRate": "-0.02"
+                },
+                {
+                    "instId": "SOL-USDT-SWAP",
+                    "fundingRate": "0.0002",
+                    "fundingTime": "1700006400000",
+                    "nextFundingTime": ""
+                }
+            ]
+        }"#;
+        let envelope: Envelope<RawFundingRate> = serde_json::from_str(json).unwrap();
+        let rates = envelope.into_result("get_funding_rates").unwrap();
+
+        let rate = rates[1].funding_rate();
+        assert_eq!(rate.symbol, "WIFUSDT");
+        assert_eq!(rate.funding_rate, dec!(-0.0004));
+        assert_eq!(rate.funding_time, 1700006400000);
+
+        let info: Vec<FundingInfo> = rates
+            .iter()
+            .filter_map(RawFundingRate::funding_info)
+            .collect();
+        assert_eq!(info.len(), 2, "SOL has no published interval yet");
+        assert_eq!(info[0].funding_interval_hours, 8);
+        assert_eq!(info[1].funding_interval_hours, 4);
+        assert_eq!(info[1].adjusted_funding_rate_cap, Some(dec!(0.02)));
+        assert_eq!(info[1].adjusted_funding_rate_floor, Some(dec!(-0.02)));
+    }
+
+    #[test]
+    fn test_rejected_request_is_an_error() {
+        let json = r#"{"code": "50111", "msg": "Invalid OK-ACCESS-KEY", "data": []}"#;
+        let envelope: Envelope<RawAccount> = serde_json::from_str(json).unwrap();
+        let err = envelope.into_result("get_account_balance").unwrap_err();
+        assert!(err.to_string().contains("50111"));
+
+        // Order placements report the per-order code
+        let json = r#"{
+            "code": "1",
+            "msg": "Operation failed.",
+            "data": [{"ordId": "", "clOrdId": "", "sCode": "51008", "sMsg": "Insufficient margin"}]
+        }"#;
+        let envelope: Envelope<OrderAck> = serde_json::from_str(json).unwrap();
+        let err = accepted_order_id(envelope, "place_futures_order").unwrap_err();
+        assert!(err.to_string().contains("Insufficient margin"));
+    }
+
+    #[test]
+    fn test_contract_sizes_become_base_quantities() {
+        let instrument: RawSwapInstrument = serde_json::from_str(
+            r#"{
+                "instId": "ETH-USDT-SWAP",
+                "instFamily": "ETH-USDT",
+                "settleCcy": "USDT",
+                "ctType": "linear",
+                "ctVal": "0.1",
+                "ctValCcy": "ETH",
+                "lotSz": "0.01",
+                "tickSz": "0.01",
+                "state": "live"
+            }"#,
+        )
+        .unwrap();
+        assert!(instrument.is_usdt_linear());
+        let info = instrument.symbol_info();
+        assert_eq!(info.symbol, "ETHUSDT");
+        assert_eq!(info.quantity_precision, 3, "0.01 contracts of 0.1 ETH");
+        assert_eq!(info.price_precision, 2);
+        assert_eq!(info.status, "TRADING");
+
+        let ticker: RawTicker = serde_json::from_str(
+            r#"{
+                "instId": "ETH-USDT-SWAP",
+                "last": "2000",
+                "open24h": "1900",
+                "high24h": "2050",
+                "low24h": "1880",
+                "vol24h": "500000",
+                "volCcy24h": "50000",
+                "bidPx": "1999.9",
+                "bidSz": "120",
+                "askPx": "2000.1",
+                "askSz": "80",
+                "ts": "1700006400000"
+            }"#,
+        )
+        .unwrap();
+        let stats = ticker.ticker_24h();
+        assert_eq!(stats.volume, dec!(50000));
+        assert_eq!(stats.quote_volume, dec!(100000000));
+        let book = ticker.book_ticker(dec!(0.1));
+        assert_eq!(book.bid_qty, dec!(12));
+        assert_eq!(book.ask_qty, dec!(8));
+
+        let position: RawPosition = serde_json::from_str(
+            r#"{
+                "instId": "ETH-USDT-SWAP",
+                "pos": "25",
+                "posSide": "short",
+                "avgPx": "2010",
+                "markPx": "2000",
+                "upl": "25",
+                "liqPx": "2600",
+                "lever": "3",
+                "mgnMode": "cross",
+                "margin": ""
+            }"#,
+        )
+        .unwrap();
+        let position = position.position(dec!(0.1));
+        assert_eq!(position.position_amt, dec!(-2.5));
+        assert_eq!(position.notional, dec!(-5000));
+        assert_eq!(position.margin_type, MarginType::Cross);
+        assert_eq!(position.leverage, 3);
+    }
+
+    #[test]
+    fn test_position_tiers_become_notional_brackets() {
+        let tiers: Vec<RawPositionTier> = serde_json::from_str(
+            r#"[
+                {"tier": "2", "maxSz": "5000", "mmr": "0.01", "maxLever": "50"},
+                {"tier": "1", "maxSz": "1000", "mmr": "0.005", "maxLever": "100"}
+            ]"#,
+        )
+        .unwrap();
+        // 0.01 BTC contracts at 50,000
+        let bracket = tier_brackets("BTCUSDT", tiers, dec!(500));
+        assert_eq!(bracket.brackets.len(), 2);
+        assert_eq!(bracket.brackets[0].notional_cap, dec!(500000));
+        assert_eq!(bracket.brackets[0].cum, Decimal::ZERO);
+        assert_eq!(bracket.brackets[1].notional_floor, dec!(500000));
+        assert_eq!(bracket.brackets[1].cum, dec!(2500));
+        assert_eq!(bracket.brackets[1].initial_leverage, 50);
+    }
+
+    #[test]
+    fn test_orders_and_client_ids() {
+        // Hyphens are dropped; long IDs keep a stable hash of the whole ID
+        assert_eq!(okx_client_order_id("fff-7f3a-12"), "fff7f3a12");
+        let long = "fffa1b2c3d4e5-0123456789abcdef-12345";
+        let mapped = okx_client_order_id(long);
+        assert_eq!(mapped.len(), MAX_CLIENT_ORDER_ID_LEN);
+        assert!(mapped.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_eq!(mapped, okx_client_order_id(long));
+        assert_ne!(
+            mapped,
+            okx_client_order_id("fffa1b2c3d4e5-0123456789abcdef-12346")
+        );
+
+        let body = order_body(
+            "BTC-USDT-SWAP",
+            "cross",
+            OrderSide::Sell,
+            OrderType::Limit,
+            Some(dec!(15)),
+            Some(dec!(50000)),
+            Some(TimeInForce::Ioc),
+            Some("fff-7f3a-12"),
+        )
+        .unwrap();
+        assert_eq!(body["ordType"], "ioc");
+        assert_eq!(body["side"], "sell");
+        assert_eq!(body["sz"], "15");
+        assert_eq!(body["clOrdId"], "fff7f3a12");
+
+        let order: RawOrder = serde_json::from_str(
+            r#"{
+                "instId": "BTC-USDT-SWAP",
+                "ordId": "590908157585625111",
+                "clOrdId": "fff7f3a12",
+                "side": "sell",
+                "ordType": "ioc",
+                "state": "partially_filled",
+                "px": "50000",
+                "avgPx": "50000",
+                "sz": "15",
+                "accFillSz": "10",
+                "uTime": "1700006400000"
+            }"#,
+        )
+        .unwrap();
+        assert!(!order.is_settled());
+        let response = order.order_response(dec!(0.01), "fff-7f3a-12");
+        assert_eq!(response.client_order_id, "fff-7f3a-12");
+        assert_eq!(response.status, OrderStatus::PartiallyFilled);
+        assert_eq!(response.orig_qty, dec!(0.15));
+        assert_eq!(response.executed_qty, dec!(0.1));
+        assert_eq!(response.time_in_force, Some(TimeInForce::Ioc));
+
+        // Listed orders carry OKX's form of the ID, which keeps the instance prefix
+        let listed = order.order_lookup(dec!(0.01), &order.cl_ord_id);
+        assert_eq!(listed.client_order_id, "fff7f3a12");
+        assert!(listed.client_order_id.starts_with("fff7f3a"));
+        assert_eq!(listed.order_id, 590908157585625111);
+    }
+
+    #[test]
+    fn test_signature() {
+        let credentials = Credentials {
+            api_key: "key".to_string(),
+            secret_key: "secret".to_string(),
+            passphrase: "pass".to_string(),
+        };
+        assert_eq!(
+            credentials.sign(
+                "2020-12-08T09:08:57.715Z",
+                "GET",
+                "/api/v5/account/balance?ccy=BTC",
+                ""
+            ),
+            "wpDvCwYCprcMQsQkxWJiWy+YADoQE4ep+OEKKLimMoY="
+        );
+        assert_eq!(
+            credentials.sign(
+                "2020-12-08T09:08:57.715Z",
+                "POST",
+                "/api/v5/trade/order",
+                r#"{"instId":"BTC-USDT"}"#
+            ),
+            "I64FCA5BpQbPVAu81/jgcqwMMz/badKJDsMDOMpXB0Y="
+        );
+    }
+
+    #[test]
+    fn test_bills_become_income() {
+        let bill: RawBill = serde_json::from_str(
+            r#"{
+                "billId": "623950854533513219",
+                "instId": "BTC-USDT-SWAP",
+                "ccy": "USDT",
+                "balChg": "-1.25",
+                "fee": "-0.5",
+                "ts": "1700006400000"
+            }"#,
+        )
+        .unwrap();
+        let funding = IncomeSource::for_income_type("FUNDING_FEE").unwrap();
+        assert_eq!(funding.bill_type(), "8");
+        assert_eq!(funding.income(&bill), dec!(-1.25));
+        let trade = IncomeSource::for_income_type("COMMISSION").unwrap();
+        assert_eq!(trade.income(&bill), dec!(-0.5));
+        assert!(IncomeSource::for_income_type("REALIZED_PNL").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Order rate and notional velocity limits.
+//!
+//! A bug that loops on order placement can trade away an account in minutes. The
+//! valve sits inside both the live and the mock client and counts every order
+//! before it is sent and every fill after it returns. Once a limit is exceeded it
+//! trips and refuses all further orders until the process is restarted; the trip
+//! is reported once so the caller can raise a malfunction alert and halt trading.
+
+use crate::exchange::OrderResponse;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Limit that tripped the valve.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValveTrip {
+    /// More orders in the last minute than allowed
+    OrderRate { orders: u32, max_per_minute: u32 },
+    /// More notional filled in the last hour than allowed (USDT)
+    NotionalVelocity {
+        notional: Decimal,
+        max_per_hour: Decimal,
+    },
+}
+
+impl fmt::Display for ValveTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValveTrip::OrderRate {
+                orders,
+                max_per_minute,
+            } => write!(
+                f,
+                "{} orders in the last minute (max {})",
+                orders, max_per_minute
+            ),
+            ValveTrip::NotionalVelocity {
+                notional,
+                max_per_hour,
+            } => write!(
+                f,
+                "${:.2} traded in the last hour (max ${:.2})",
+                notional, max_per_hour
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ValveState {
+    /// Maximum orders per minute (0 = unlimited)
+    max_orders_per_minute: u32,
+    /// Maximum filled notional per hour in USDT (0 = unlimited)
+    max_notional_per_hour: Decimal,
+    /// Send times of orders in the last minute
+    orders: VecDeque<DateTime<Utc>>,
+    /// Fill times and notionals in the last hour
+    fills: VecDeque<(DateTime<Utc>, Decimal)>,
+    trip: Option<ValveTrip>,
+    /// Whether the trip has been handed out by `take_new_trip`
+    trip_reported: bool,
+}
+
+impl ValveState {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let minute_ago = now - Duration::minutes(1);
+        while self.orders.front().is_some_and(|t| *t <= minute_ago) {
+            self.orders.pop_front();
+        }
+        let hour_ago = now - Duration::hours(1);
+        while self.fills.front().is_some_and(|(t, _)| *t <= hour_ago) {
+            self.fills.pop_front();
+        }
+    }
+
+    fn hourly_notional(&self) -> Decimal {
+        self.fills.iter().map(|(_, notional)| *notional).sum()
+    }
+}
+
+/// Shared safety valve on order placement.
+///
+/// Clones share the same counters, so one valve can guard several clients. The
+/// default valve has no limits.
+#[derive(Debug, Clone, Default)]
+pub struct OrderValve {
+    state: Arc<Mutex<ValveState>>,
+}
+
+impl OrderValve {
+    /// Valve with the given limits; 0 disables a limit.
+    pub fn new(max_orders_per_minute: u32, max_notional_per_hour: Decimal) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ValveState {
+                max_orders_per_minute,
+                max_notional_per_hour,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Admit an order about to be sent at `now`.
+    ///
+    /// Fails when the valve has tripped, or trips it when this order would exceed
+    /// the order rate or the hourly notional is already used up.
+    pub fn admit(&self, now: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.prune(now);
+
+        // Once tripped, the valve stays closed until restart
+        if state.trip.is_none() {
+            let orders = state.orders.len() as u32 + 1;
+            let hourly = state.hourly_notional();
+            if state.max_orders_per_minute > 0 && orders > state.max_orders_per_minute {
+                state.trip = Some(ValveTrip::OrderRate {
+                    orders,
+                    max_per_minute: state.max_orders_per_minute,
+                });
+            } else if state.max_notional_per_hour > Decimal::ZERO
+                && hourly >= state.max_notional_per_hour
+            {
+                state.trip = Some(ValveTrip::NotionalVelocity {
+                    notional: hourly,
+                    max_per_hour: state.max_notional_per_hour,
+                });
+            }
+        }
+        if let Some(trip) = &state.trip {
+            bail!("Order valve tripped ({}), refusing order", trip);
+        }
+
+        state.orders.push_back(now);
+        Ok(())
+    }
+
+    /// Record the filled notional of an order returned at `now`.
+    ///
+    /// Trips the valve when the hourly notional exceeds its limit; the order itself
+    /// has already executed, later ones are refused.
+    pub fn record_fill(&self, now: DateTime<Utc>, order: &OrderResponse) {
+        let price = if order.avg_price > Decimal::ZERO {
+            order.avg_price
+        } else {
+            order.price
+        };
+        self.record_notional(now, order.executed_qty * price);
+    }
+
+    /// Record notional filled at `now` by an order placed outside the Binance
+    /// order types.
+    pub fn record_notional(&self, now: DateTime<Utc>, notional: Decimal) {
+        if notional <= Decimal::ZERO {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.prune(now);
+        state.fills.push_back((now, notional));
+        let hourly = state.hourly_notional();
+        if state.trip.is_none()
+            && state.max_notional_per_hour > Decimal::ZERO
+            && hourly > state.max_notional_per_hour
+        {
+            state.trip = Some(ValveTrip::NotionalVelocity {
+                notional: hourly,
+                max_per_hour: state.max_notional_per_hour,
+            });
+        }
+    }
+
+    /// Current trip, if any.
+    pub fn trip(&self) -> Option<ValveTrip> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .trip
+            .clone()
+    }
+
+    /// The trip, the first time it is asked for after tripping.
+    pub fn take_new_trip(&self) -> Option<ValveTrip> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.trip_reported {
+            return None;
+        }
+        state.trip_reported = state.trip.is_some();
+        state.trip.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{OrderSide, OrderStatus, OrderType};
+    use rust_decimal_macros::dec;
+
+    fn filled(qty: Decimal, avg_price: Decimal) -> OrderResponse {
+        OrderResponse {
+            order_id: 1,
+            symbol: "BTCUSDT".to_string(),
+            status: OrderStatus::Filled,
+            client_order_id: String::new(),
+            price: Decimal::ZERO,
+            avg_price,
+            orig_qty: qty,
+            executed_qty: qty,
+            side: OrderSide::Sell,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            update_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_order_rate_trips_and_latches() {
+        let valve = OrderValve::new(3, Decimal::ZERO);
+        let now = Utc::now();
+        for _ in 0..3 {
+            valve.admit(now).unwrap();
+        }
+        // A minute later the window is clear again
+        let later = now + Duration::seconds(61);
+        valve.admit(later).unwrap();
+        valve.admit(later).unwrap();
+        valve.admit(later).unwrap();
+
+        assert!(valve.admit(later).is_err());
+        assert_eq!(
+            valve.take_new_trip(),
+            Some(ValveTrip::OrderRate {
+                orders: 4,
+                max_per_minute: 3
+            })
+        );
+        assert_eq!(valve.take_new_trip(), None);
+
+        // Tripped: refuses even once the window has cleared
+        assert!(valve.admit(later + Duration::minutes(5)).is_err());
+        assert!(valve.trip().is_some());
+    }
+
+    #[test]
+    fn test_notional_velocity_shared_across_clones() {
+        let valve = OrderValve::new(0, dec!(10000));
+        let other_client = valve.clone();
+        let now = Utc::now();
+
+        valve.admit(now).unwrap();
+        valve.record_fill(now, &filled(dec!(0.1), dec!(50000)));
+        other_client.admit(now).unwrap();
+        other_client.record_fill(now, &filled(dec!(0.08), dec!(50000)));
+        assert!(valve.trip().is_none());
+
+        // 9000 traded; the next fill of 1500 pushes the hour over the limit
+        valve.admit(now).unwrap();
+        valve.record_fill(now, &filled(dec!(0.03), dec!(50000)));
+        assert_eq!(
+            other_client.take_new_trip(),
+            Some(ValveTrip::NotionalVelocity {
+                notional: dec!(10500),
+                max_per_hour: dec!(10000)
+            })
+        );
+        assert!(other_client.admit(now).is_err());
+    }
+
+    #[test]
+    fn test_default_valve_is_unlimited() {
+        let valve = OrderValve::default();
+        let now = Utc::now();
+        for _ in 0..1000 {
+            valve.admit(now).unwrap();
+            valve.record_fill(now, &filled(dec!(10), dec!(50000)));
+        }
+        assert!(valve.trip().is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Best bid/ask of held symbols streamed from Binance.
+//!
+//! Polling `/fapi/v1/ticker/bookTicker` every loop returns the book of every
+//! symbol to price a handful of positions. The feed subscribes to the
+//! `<symbol>@bookTicker` streams of the held symbols instead and keeps the latest
+//! top of book per symbol. A combined stream's symbols are fixed when it connects,
+//! so a changed set of held symbols resubscribes. Like the mark price cache,
+//! readers get a set of symbols only when every one is fresh.
+
+use super::types::BookTicker;
+use super::websocket::{BinanceWebSocket, BookTickerUpdate, WsEvent};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+struct FeedState {
+    tickers: HashMap<String, (BookTicker, DateTime<Utc>)>,
+    /// Symbols of the current subscription, sorted
+    symbols: Vec<String>,
+    /// Bumped by every subscription; consumers of older ones stop
+    generation: u64,
+    connected: bool,
+}
+
+/// Shared book ticker feed; clones read and write the same books.
+#[derive(Debug, Clone)]
+pub struct PriceFeed {
+    state: Arc<Mutex<FeedState>>,
+    /// Oldest update still served
+    max_age: Duration,
+}
+
+impl PriceFeed {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(FeedState::default())),
+            max_age,
+        }
+    }
+
+    /// Whether the feed must (re)subscribe to stream `symbols`.
+    pub fn needs_subscribe(&self, symbols: &[String]) -> bool {
+        let state = self.lock();
+        let wanted = sorted(symbols);
+        state.symbols != wanted || (!wanted.is_empty() && !state.connected)
+    }
+
+    /// Stream the books of `symbols`, replacing the previous subscription.
+    ///
+    /// Books of symbols no longer subscribed are dropped.
+    pub async fn subscribe(&self, ws: &BinanceWebSocket, symbols: &[String]) -> Result<()> {
+        let symbols = sorted(symbols);
+        let generation = {
+            let mut state = self.lock();
+            state.generation += 1;
+            state.connected = false;
+            state.tickers.retain(|symbol, _| symbols.contains(symbol));
+            state.symbols = symbols.clone();
+            state.generation
+        };
+        if symbols.is_empty() {
+            return Ok(());
+        }
+
+        let (tx, mut rx) = mpsc::channel(1000);
+        ws.subscribe_book_tickers(symbols, tx).await?;
+        {
+            let mut state = self.lock();
+            if state.generation != generation {
+                return Ok(());
+            }
+            state.connected = true;
+        }
+
+        let feed = self.clone();
+        tokio::spawn(async move {
+            // Dropping the receiver closes the superseded socket on its next message
+            while let Some(event) = rx.recv().await {
+                if feed.lock().generation != generation {
+                    return;
+                }
+                match event {
+                    WsEvent::BookTicker(update) => feed.apply(&update),
+                    WsEvent::Disconnected => break,
+                    _ => {}
+                }
+            }
+            debug!("Book ticker stream ended");
+            let mut state = feed.lock();
+            if state.generation == generation {
+                state.connected = false;
+            }
+        });
+        Ok(())
+    }
+
+    /// Whether the current subscription is still delivering.
+    pub fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    /// Store a streamed update; unparseable updates are skipped.
+    pub fn apply(&self, update: &BookTickerUpdate) {
+        let (Ok(bid_price), Ok(bid_qty), Ok(ask_price), Ok(ask_qty), Some(updated_at)) = (
+            Decimal::from_str(&update.bid_price),
+            Decimal::from_str(&update.bid_qty),
+            Decimal::from_str(&update.ask_price),
+            Decimal::from_str(&update.ask_qty),
+            DateTime::from_timestamp_millis(update.event_time),
+        ) else {
+            return;
+        };
+        let ticker = BookTicker {
+            symbol: update.symbol.clone(),
+            bid_price,
+            bid_qty,
+            ask_price,
+            ask_qty,
+        };
+        self.lock()
+            .tickers
+            .insert(update.symbol.clone(), (ticker, updated_at));
+    }
+
+    /// Books of `symbols`, or `None` unless all of them are fresh.
+    pub fn tickers(
+        &self,
+        symbols: &[String],
+        now: DateTime<Utc>,
+    ) -> Option<HashMap<String, BookTicker>> {
+        let state = self.lock();
+        symbols
+            .iter()
+            .map(|symbol| {
+                let (ticker, updated_at) = state.tickers.get(symbol)?;
+                (now - *updated_at <= self.max_age).then(|| (symbol.clone(), ticker.clone()))
+            })
+            .collect()
+    }
+
+    /// Mid prices of `symbols`, or `None` unless all of them are fresh.
+    pub fn mid_prices(
+        &self,
+        symbols: &[String],
+        now: DateTime<Utc>,
+    ) -> Option<HashMap<String, Decimal>> {
+        Some(
+            self.tickers(symbols, now)?
+                .into_iter()
+                .map(|(symbol, t)| (symbol, (t.bid_price + t.ask_price) / Decimal::TWO))
+                .collect(),
+        )
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FeedState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn sorted(symbols: &[String]) -> Vec<String> {
+    let mut symbols = symbols.to_vec();
+    symbols.sort();
+    symbols.dedup();
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(symbol: &str, bid: &str, ask: &str, at: DateTime<Utc>) -> BookTickerUpdate {
+        BookTickerUpdate {
+            event_time: at.timestamp_millis(),
+            symbol: symbol.to_string(),
+            bid_price: bid.to_string(),
+            bid_qty: "1.5".to_string(),
+            ask_price: ask.to_string(),
+            ask_qty: "2".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_serves_only_fresh_books() {
+        let now = Utc::now();
+        let feed = PriceFeed::new(Duration::seconds(5));
+        feed.apply(&update("BTCUSDT", "62000", "62001", now));
+        feed.apply(&update(
+            "ETHUSDT",
+            "3000",
+            "3001",
+            now - Duration::seconds(10),
+        ));
+        feed.apply(&update("SOLUSDT", "bad", "150", now));
+
+        let btc = vec!["BTCUSDT".to_string()];
+        let tickers = feed.tickers(&btc, now).unwrap();
+        assert_eq!(tickers["BTCUSDT"].bid_price, dec!(62000));
+        assert_eq!(tickers["BTCUSDT"].ask_qty, dec!(2));
+        assert_eq!(
+            feed.mid_prices(&btc, now).unwrap()["BTCUSDT"],
+            dec!(62000.5)
+        );
+
+        // A stale or missing symbol sends the whole set back to REST
+        let both = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        assert!(feed.tickers(&both, now).is_none());
+        assert!(feed.mid_prices(&["SOLUSDT".to_string()], now).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscription_follows_held_symbols() {
+        let feed = PriceFeed::new(Duration::seconds(5));
+        let ws = BinanceWebSocket::new(true);
+        assert!(!feed.needs_subscribe(&[]));
+        let held = vec!["ETHUSDT".to_string(), "BTCUSDT".to_string()];
+        assert!(feed.needs_subscribe(&held));
+
+        // Nothing held: no stream, and books of dropped symbols go
+        feed.apply(&update("BTCUSDT", "62000", "62001", Utc::now()));
+        feed.subscribe(&ws, &[]).await.unwrap();
+        assert!(!feed.is_connected());
+        assert!(!feed.needs_subscribe(&[]));
+        assert!(feed.tickers(&["BTCUSDT".to_string()], Utc::now()).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! Request weight throttle in front of the Binance REST API.
+//!
+//! `ApiUsage` only counts what was sent. A wide scan or a burst of entries can
+//! still spend a minute's weight in seconds, and past the limit Binance answers
+//! 429 and then bans the IP with 418, mid-trade. The limiter keeps one token
+//! bucket per market holding `throttle_fraction` of its per-minute weight and
+//! refilling over a minute. Each request takes its weight before it is sent and
+//! waits when the bucket is short. The used weight the exchange reports caps the
+//! bucket, so other processes on the same IP are accounted for, and a 429 or 418
+//! empties it until the exchange's `Retry-After`.
+
+use super::api_budget::{ApiMarket, RequestCost};
+use crate::config::ApiBudgetConfig;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// Wait logged as a warning rather than at debug level.
+const WARN_WAIT_MS: i64 = 1_000;
+
+/// Weight tokens of one market.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    /// Tokens regained per second
+    refill_per_sec: f64,
+    /// Negative while reservations are queued
+    tokens: f64,
+    /// Time `tokens` is valid at; a pause puts it in the future
+    updated_at: DateTime<Utc>,
+}
+
+impl Bucket {
+    fn new(weight_per_minute: u32, fraction: f64, now: DateTime<Utc>) -> Self {
+        let capacity = (weight_per_minute as f64 * fraction).max(1.0);
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            tokens: capacity,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: DateTime<Utc>) {
+        if now <= self.updated_at {
+            return;
+        }
+        let elapsed = (now - self.updated_at).num_milliseconds() as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    /// Take `weight` and return how long to wait before sending.
+    fn reserve(&mut self, weight: u32, now: DateTime<Utc>) -> Duration {
+        self.refill(now);
+        self.tokens -= weight as f64;
+        let deficit = (-self.tokens).max(0.0);
+        let refill_ms = (deficit / self.refill_per_sec * 1000.0).ceil() as i64;
+        (self.updated_at - now) + Duration::milliseconds(refill_ms)
+    }
+
+    fn observe(&mut self, used_weight: u32, now: DateTime<Utc>) {
+        self.refill(now);
+        self.tokens = self.tokens.min(self.capacity - used_weight as f64);
+    }
+
+    fn pause_until(&mut self, until: DateTime<Utc>) {
+        self.tokens = self.tokens.min(0.0);
+        self.updated_at = self.updated_at.max(until);
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    futures: Bucket,
+    spot: Bucket,
+}
+
+impl LimiterState {
+    fn bucket(&mut self, market: ApiMarket) -> &mut Bucket {
+        match market {
+            ApiMarket::Futures => &mut self.futures,
+            ApiMarket::Spot => &mut self.spot,
+        }
+    }
+}
+
+/// Shared request weight throttle; clones draw from the same buckets.
+#[derive(Debug, Clone)]
+pub struct WeightLimiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl Default for WeightLimiter {
+    fn default() -> Self {
+        Self::new(&ApiBudgetConfig::default())
+    }
+}
+
+impl WeightLimiter {
+    pub fn new(budget: &ApiBudgetConfig) -> Self {
+        let fraction = budget.throttle_fraction.to_f64().unwrap_or(1.0);
+        let now = Utc::now();
+        Self {
+            state: Arc::new(Mutex::new(LimiterState {
+                futures: Bucket::new(budget.futures_weight_per_minute, fraction, now),
+                spot: Bucket::new(budget.spot_weight_per_minute, fraction, now),
+            })),
+        }
+    }
+
+    /// Reserve the weight of `cost` at `now`; returns how long to wait before sending.
+    ///
+    /// Reservations queue: a caller arriving while the bucket is short waits
+    /// behind those already waiting.
+    pub fn reserve(&self, cost: RequestCost, now: DateTime<Utc>) -> Duration {
+        self.lock().bucket(cost.market).reserve(cost.weight, now)
+    }
+
+    /// Wait until the weight of `cost` is available.
+    pub async fn acquire(&self, operation: &str, cost: RequestCost) {
+        let wait = self.reserve(cost, Utc::now());
+        let Ok(wait_std) = wait.to_std() else {
+            return;
+        };
+        if wait_std.is_zero() {
+            return;
+        }
+        let wait_ms = wait.num_milliseconds();
+        if wait_ms >= WARN_WAIT_MS {
+            warn!(%operation, market = %cost.market, wait_ms, "Request weight near limit, delaying request");
+        } else {
+            debug!(%operation, market = %cost.market, wait_ms, "Throttling request");
+        }
+        tokio::time::sleep(wait_std).await;
+    }
+
+    /// Cap `market`'s bucket by the weight the exchange reports used this minute.
+    pub fn observe(&self, market: ApiMarket, used_weight: u32, now: DateTime<Utc>) {
+        self.lock().bucket(market).observe(used_weight, now);
+    }
+
+    /// Hold every request of `market` until `until`, after a 429 or 418.
+    pub fn pause_until(&self, market: ApiMarket, until: DateTime<Utc>) {
+        self.lock().bucket(market).pause_until(until);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn limiter() -> WeightLimiter {
+        WeightLimiter::new(&ApiBudgetConfig {
+            futures_weight_per_minute: 1200,
+            spot_weight_per_minute: 600,
+            throttle_fraction: dec!(0.5),
+            ..Default::default()
+        })
+    }
+
+    fn futures(weight: u32) -> RequestCost {
+        RequestCost {
+            market: ApiMarket::Futures,
+            weight,
+            orders: 0,
+        }
+    }
+
+    #[test]
+    fn test_queues_requests_beyond_capacity() {
+        let limiter = limiter();
+        let now = Utc::now();
+        // 600 tokens, refilling 10 per second
+        assert_eq!(limiter.reserve(futures(500), now), Duration::zero());
+        assert_eq!(limiter.reserve(futures(100), now), Duration::zero());
+        assert_eq!(limiter.reserve(futures(20), now), Duration::seconds(2));
+        // Later callers wait behind the queue
+        assert_eq!(limiter.reserve(futures(10), now), Duration::seconds(3));
+        // Refill pays back the queue
+        assert_eq!(
+            limiter.reserve(futures(10), now + Duration::seconds(5)),
+            Duration::zero()
+        );
+
+        // Markets have separate buckets
+        let spot = RequestCost {
+            market: ApiMarket::Spot,
+            weight: 300,
+            orders: 0,
+        };
+        assert_eq!(limiter.reserve(spot, now), Duration::zero());
+    }
+
+    #[test]
+    fn test_exchange_usage_and_bans_drain_the_bucket() {
+        let limiter = limiter();
+        let banned = self::limiter();
+        let now = Utc::now();
+        // Another process on the IP already used most of the minute
+        limiter.observe(ApiMarket::Futures, 590, now);
+        assert_eq!(limiter.reserve(futures(20), now), Duration::seconds(1));
+
+        banned.pause_until(ApiMarket::Futures, now + Duration::seconds(30));
+        assert_eq!(banned.reserve(futures(10), now), Duration::seconds(31));
+        assert_eq!(
+            banned.reserve(futures(10), now + Duration::seconds(40)),
+            Duration::zero()
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Scripted market scenarios for the mock client.
+//!
+//! A scenario pins the prices and funding rates of some symbols and changes them
+//! at offsets from the start of the run, and can make upcoming orders fail, so a
+//! paper-trading session reproduces a situation on demand ("funding flips
+//! negative at T+8h", "flash crash -15%"). Symbols without a starting price or
+//! rate take the first live value and stay pinned to it afterwards. Scripted
+//! values apply to the paper account (fills, funding and PnL), not to data
+//! fetched from the exchange.
+//!
+//! ```toml
+//! initial_balance = 10000
+//!
+//! [[symbols]]
+//! symbol = "ALTUSDT"
+//! price = 10
+//! funding_rate = 0.0005
+//! funding_interval_hours = 4
+//!
+//! [[events]]
+//! at = "8h"
+//! symbol = "ALTUSDT"
+//! funding_rate = -0.0003
+//! note = "Funding flips negative"
+//!
+//! [[events]]
+//! at = "12h"
+//! price_change = -0.15
+//! note = "Flash crash"
+//!
+//! [[events]]
+//! at = "12h5m"
+//! order_failures = 3
+//! ```
+//!
+//! JSON files with the same layout are accepted too.
+
+use super::{FundingCalendar, FundingSchedule, Venue};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Offset from the start of a scenario, written like `90m`, `8h` or `1d12h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ScenarioOffset(Duration);
+
+impl ScenarioOffset {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl TryFrom<String> for ScenarioOffset {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let mut total = Duration::zero();
+        let mut digits = String::new();
+        for c in value.trim().chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+            let amount: i64 = digits
+                .parse()
+                .map_err(|_| format!("invalid offset '{}': expected e.g. 8h or 1d12h", value))?;
+            total += match c {
+                'd' => Duration::days(amount),
+                'h' => Duration::hours(amount),
+                'm' => Duration::minutes(amount),
+                's' => Duration::seconds(amount),
+                _ => return Err(format!("invalid offset unit '{}' in '{}'", c, value)),
+            };
+            digits.clear();
+        }
+        if !digits.is_empty() || value.trim().is_empty() {
+            return Err(format!(
+                "invalid offset '{}': expected e.g. 8h or 1d12h",
+                value
+            ));
+        }
+        Ok(Self(total))
+    }
+}
+
+impl From<ScenarioOffset> for String {
+    fn from(offset: ScenarioOffset) -> Self {
+        offset.to_string()
+    }
+}
+
+impl fmt::Display for ScenarioOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.0.num_minutes();
+        let seconds = self.0.num_seconds() % 60;
+        match (minutes / 60, minutes % 60, seconds) {
+            (0, 0, s) => write!(f, "{}s", s),
+            (h, 0, 0) => write!(f, "{}h", h),
+            (0, m, 0) => write!(f, "{}m", m),
+            (h, m, 0) => write!(f, "{}h{}m", h, m),
+            (h, m, s) => write!(f, "{}h{}m{}s", h, m, s),
+        }
+    }
+}
+
+/// A symbol whose market data the scenario controls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioSymbol {
+    pub symbol: String,
+    /// Starting price; the first live price when omitted
+    #[serde(default)]
+    pub price: Option<Decimal>,
+    /// Starting funding rate; the first live rate when omitted
+    #[serde(default)]
+    pub funding_rate: Option<Decimal>,
+    /// Settlement interval overriding the exchange schedule
+    #[serde(default)]
+    pub funding_interval_hours: Option<u32>,
+}
+
+/// A change applied at an offset from the start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    pub at: ScenarioOffset,
+    /// Scripted symbol affected; every scripted symbol when omitted (and any
+    /// order for `order_failures`)
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// New price
+    #[serde(default)]
+    pub price: Option<Decimal>,
+    /// Relative price move, e.g. -0.15 for a 15% drop
+    #[serde(default)]
+    pub price_change: Option<Decimal>,
+    #[serde(default)]
+    pub funding_rate: Option<Decimal>,
+    /// Number of upcoming orders that fail
+    #[serde(default)]
+    pub order_failures: Option<u32>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl fmt::Display for ScenarioEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut changes = Vec::new();
+        if let Some(price) = self.price {
+            changes.push(format!("price {}", price));
+        }
+        if let Some(change) = self.price_change {
+            changes.push(format!("price {:+.1}%", change * dec!(100)));
+        }
+        if let Some(rate) = self.funding_rate {
+            changes.push(format!("funding {:.4}%", rate * dec!(100)));
+        }
+        if let Some(failures) = self.order_failures {
+            changes.push(format!("next {} order(s) fail", failures));
+        }
+        write!(
+            f,
+            "T+{} {}: {}",
+            self.at,
+            self.symbol.as_deref().unwrap_or("all symbols"),
+            changes.join(", ")
+        )?;
+        if let Some(note) = &self.note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// A scripted market scenario.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketScenario {
+    #[serde(default = "default_initial_balance")]
+    pub initial_balance: Decimal,
+    #[serde(default)]
+    pub symbols: Vec<ScenarioSymbol>,
+    #[serde(default)]
+    pub events: Vec<ScenarioEvent>,
+}
+
+fn default_initial_balance() -> Decimal {
+    dec!(10000)
+}
+
+impl MarketScenario {
+    /// Load and validate a scenario from a TOML or (`.json`) JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let scenario: Self = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse scenario {}", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Failed to parse scenario {}", path.display()))?
+        };
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Reject events that can't be played back.
+    pub fn validate(&self) -> Result<()> {
+        if self.initial_balance <= Decimal::ZERO {
+            anyhow::bail!("initial_balance must be > 0");
+        }
+        for symbol in &self.symbols {
+            if symbol.price.is_some_and(|p| p <= Decimal::ZERO) {
+                anyhow::bail!("{} price must be > 0", symbol.symbol);
+            }
+            if let Some(hours) = symbol.funding_interval_hours {
+                if FundingSchedule::every_hours(hours).is_none() {
+                    anyhow::bail!(
+                        "{} funding_interval_hours must divide 24 (got {})",
+                        symbol.symbol,
+                        hours
+                    );
+                }
+            }
+        }
+        for (i, event) in self.events.iter().enumerate() {
+            let n = i + 1;
+            if let Some(symbol) = &event.symbol {
+                if event.order_failures.is_none() && !self.is_scripted(symbol) {
+                    anyhow::bail!(
+                        "event #{} changes {} which is not in [[symbols]]",
+                        n,
+                        symbol
+                    );
+                }
+            } else if event.price.is_some() {
+                anyhow::bail!("event #{} sets a price without a symbol", n);
+            }
+            if event.price.is_some() && event.price_change.is_some() {
+                anyhow::bail!("event #{} sets both price and price_change", n);
+            }
+            if event.price.is_some_and(|p| p <= Decimal::ZERO) {
+                anyhow::bail!("event #{} price must be > 0", n);
+            }
+            if event.price_change.is_some_and(|c| c <= -Decimal::ONE) {
+                anyhow::bail!("event #{} price_change must be above -1", n);
+            }
+            if event.price.is_none()
+                && event.price_change.is_none()
+                && event.funding_rate.is_none()
+                && event.order_failures.is_none()
+            {
+                anyhow::bail!("event #{} changes nothing", n);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_scripted(&self, symbol: &str) -> bool {
+        self.symbols.iter().any(|s| s.symbol == symbol)
+    }
+
+    /// Apply the scripted settlement intervals to `calendar` for `venue`.
+    pub fn apply_schedules(&self, calendar: &mut FundingCalendar, venue: Venue) {
+        for symbol in &self.symbols {
+            if let Some(schedule) = symbol
+                .funding_interval_hours
+                .and_then(FundingSchedule::every_hours)
+            {
+                calendar.set_schedule(venue, &symbol.symbol, schedule);
+            }
+        }
+    }
+}
+
+/// Playback state of a scenario started at `start`.
+#[derive(Debug, Clone)]
+pub(crate) struct ScenarioPlayback {
+    start: DateTime<Utc>,
+    /// Events sorted by offset
+    events: Vec<ScenarioEvent>,
+    /// Index of the next event to play
+    next: usize,
+    /// Pinned price and funding rate per scripted symbol, once known
+    prices: HashMap<String, Option<Decimal>>,
+    rates: HashMap<String, Option<Decimal>>,
+    /// Remaining forced failures per symbol (None = any order)
+    failures: HashMap<Option<String>, u32>,
+}
+
+impl ScenarioPlayback {
+    pub(crate) fn new(scenario: &MarketScenario, start: DateTime<Utc>) -> Self {
+        let mut events = scenario.events.clone();
+        events.sort_by_key(|event| event.at);
+        Self {
+            start,
+            events,
+            next: 0,
+            prices: scenario
+                .symbols
+                .iter()
+                .map(|s| (s.symbol.clone(), s.price))
+                .collect(),
+            rates: scenario
+                .symbols
+                .iter()
+                .map(|s| (s.symbol.clone(), s.funding_rate))
+                .collect(),
+            failures: HashMap::new(),
+        }
+    }
+
+    /// Play the events due by `now`, returning them in order.
+    pub(crate) fn advance(&mut self, now: DateTime<Utc>) -> Vec<ScenarioEvent> {
+        let mut played = Vec::new();
+        while let Some(event) = self.events.get(self.next) {
+            if self.start + event.at.duration() > now {
+                break;
+            }
+            let event = event.clone();
+            self.apply(&event);
+            played.push(event);
+            self.next += 1;
+        }
+        played
+    }
+
+    fn apply(&mut self, event: &ScenarioEvent) {
+        if let Some(failures) = event.order_failures {
+            *self.failures.entry(event.symbol.clone()).or_default() += failures;
+        }
+        for (symbol, price) in self.prices.iter_mut() {
+            if event.symbol.as_ref().is_some_and(|s| s != symbol) {
+                continue;
+            }
+            if let Some(new_price) = event.price {
+                *price = Some(new_price);
+            }
+            if let (Some(change), Some(current)) = (event.price_change, price.as_mut()) {
+                *current *= Decimal::ONE + change;
+            }
+        }
+        if let Some(rate) = event.funding_rate {
+            for (symbol, pinned) in self.rates.iter_mut() {
+                if event.symbol.as_ref().is_none_or(|s| s == symbol) {
+                    *pinned = Some(rate);
+                }
+            }
+        }
+    }
+
+    /// Override scripted symbols in live market data; unpinned symbols take the
+    /// live value as their starting point.
+    pub(crate) fn pin(
+        &mut self,
+        funding_rates: &mut HashMap<String, Decimal>,
+        prices: &mut HashMap<String, Decimal>,
+    ) {
+        pin_values(&mut self.prices, prices);
+        pin_values(&mut self.rates, funding_rates);
+    }
+
+    /// Consume a forced failure for an order on `symbol`, if one is pending.
+    pub(crate) fn take_failure(&mut self, symbol: &str) -> bool {
+        [Some(symbol.to_string()), None]
+            .into_iter()
+            .any(|key| match self.failures.get_mut(&key) {
+                Some(remaining) if *remaining > 0 => {
+                    *remaining -= 1;
+                    true
+                }
+                _ => false,
+            })
+    }
+}
+
+fn pin_values(pinned: &mut HashMap<String, Option<Decimal>>, live: &mut HashMap<String, Decimal>) {
+    for (symbol, value) in pinned.iter_mut() {
+        match value {
+            Some(value) => {
+                live.insert(symbol.clone(), *value);
+            }
+            None => *value = live.get(symbol).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn scenario() -> MarketScenario {
+        toml::from_str(
+            r#"
+            [[symbols]]
+            symbol = "ALTUSDT"
+            price = 10
+            funding_rate = 0.0005
+            funding_interval_hours = 4
+
+            [[symbols]]
+            symbol = "BTCUSDT"
+
+            [[events]]
+            at = "12h"
+            price_change = -0.15
+            note = "Flash crash"
+
+            [[events]]
+            at = "8h"
+            symbol = "ALTUSDT"
+            funding_rate = -0.0003
+
+            [[events]]
+            at = "12h5m"
+            order_failures = 2
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_offsets() {
+        let offset = ScenarioOffset::try_from("1d12h30m".to_string()).unwrap();
+        assert_eq!(offset.duration(), Duration::minutes(36 * 60 + 30));
+        assert_eq!(offset.to_string(), "36h30m");
+        assert!(ScenarioOffset::try_from("8".to_string()).is_err());
+        assert!(ScenarioOffset::try_from("8w".to_string()).is_err());
+        assert!(ScenarioOffset::try_from(String::new()).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        let scenario = scenario();
+        scenario.validate().unwrap();
+        assert_eq!(scenario.initial_balance, dec!(10000));
+
+        let mut bad = scenario.clone();
+        bad.events[0].symbol = Some("ETHUSDT".to_string());
+        assert!(bad.validate().is_err());
+
+        let mut bad = scenario.clone();
+        bad.events[0].price_change = Some(dec!(-1));
+        assert!(bad.validate().is_err());
+
+        let mut bad = scenario;
+        bad.symbols[0].funding_interval_hours = Some(5);
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_playback() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 20, 0, 0, 0).unwrap();
+        let mut playback = ScenarioPlayback::new(&scenario(), start);
+
+        // BTC takes its first live price; ALT is pinned to the scripted one
+        let mut rates = HashMap::from([("ALTUSDT".to_string(), dec!(0.0001))]);
+        let mut prices = HashMap::from([
+            ("ALTUSDT".to_string(), dec!(11)),
+            ("BTCUSDT".to_string(), dec!(60000)),
+        ]);
+        playback.pin(&mut rates, &mut prices);
+        assert_eq!(prices["ALTUSDT"], dec!(10));
+        assert_eq!(rates["ALTUSDT"], dec!(0.0005));
+        assert!(playback.advance(start + Duration::hours(7)).is_empty());
+
+        // Events play in offset order, once each
+        let played = playback.advance(start + Duration::hours(12));
+        assert_eq!(played.len(), 2);
+        assert_eq!(played[0].to_string(), "T+8h ALTUSDT: funding -0.0300%");
+        assert!(playback.advance(start + Duration::hours(12)).is_empty());
+
+        let mut prices = HashMap::from([("BTCUSDT".to_string(), dec!(61000))]);
+        playback.pin(&mut rates, &mut prices);
+        assert_eq!(rates["ALTUSDT"], dec!(-0.0003));
+        assert_eq!(prices["ALTUSDT"], dec!(8.5));
+        assert_eq!(prices["BTCUSDT"], dec!(51000));
+
+        // Two failures for any symbol
+        assert!(!playback.take_failure("ALTUSDT"));
+        playback.advance(start + Duration::hours(13));
+        assert!(playback.take_failure("ALTUSDT"));
+        assert!(playback.take_failure("BTCUSDT"));
+        assert!(!playback.take_failure("ALTUSDT"));
+    }
+
+    #[test]
+    fn test_schedules_and_json() {
+        let mut calendar = FundingCalendar::new();
+        scenario().apply_schedules(&mut calendar, Venue::Binance);
+        assert_eq!(
+            calendar
+                .schedule(Venue::Binance, "ALTUSDT")
+                .interval_hours(),
+            4
+        );
+        assert_eq!(
+            calendar
+                .schedule(Venue::Binance, "BTCUSDT")
+                .interval_hours(),
+            8
+        );
+
+        let json = serde_json::to_string(&scenario()).unwrap();
+        let parsed: MarketScenario = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, scenario());
+    }
+}
@@ -0,0 +1,115 @@
+//! Offset of the local clock from the Binance server clock.
+//!
+//! Binance rejects a signed request (-1021) whose timestamp is ahead of its
+//! clock or older than the request's `recvWindow`. A system clock drifting by a
+//! second or two, common on VMs without NTP, fails every signed request while
+//! public ones keep working. The clock measures its offset against the server
+//! time, taking the server to have answered halfway through the round trip, and
+//! signed requests are timestamped on the server's clock. It recalibrates every
+//! interval, and on the next check after the exchange rejects a timestamp.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct ClockState {
+    /// Server time minus local time (ms)
+    offset_ms: i64,
+    /// Round trip of the last calibration (ms)
+    round_trip_ms: i64,
+    synced_at: Option<DateTime<Utc>>,
+    /// The exchange rejected a timestamp since the last calibration
+    stale: bool,
+}
+
+/// Shared server clock offset; clones read and calibrate the same offset.
+#[derive(Debug, Clone, Default)]
+pub struct ServerClock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl ServerClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Milliseconds since the epoch on the server's clock at local time `now`.
+    pub fn server_ms(&self, now: DateTime<Utc>) -> u64 {
+        (now.timestamp_millis() + self.offset_ms()).max(0) as u64
+    }
+
+    /// Server time minus local time (ms); zero until calibrated.
+    pub fn offset_ms(&self) -> i64 {
+        self.lock().offset_ms
+    }
+
+    /// Round trip of the last calibration (ms), bounding the offset's error.
+    pub fn round_trip_ms(&self) -> i64 {
+        self.lock().round_trip_ms
+    }
+
+    /// Record `server_ms`, read by a request sent at `sent` and answered at
+    /// `received` (local times). Returns the new offset.
+    pub fn calibrate(&self, server_ms: i64, sent: DateTime<Utc>, received: DateTime<Utc>) -> i64 {
+        let round_trip_ms = (received - sent).num_milliseconds().max(0);
+        let midpoint_ms = sent.timestamp_millis() + round_trip_ms / 2;
+        let mut state = self.lock();
+        state.offset_ms = server_ms - midpoint_ms;
+        state.round_trip_ms = round_trip_ms;
+        state.synced_at = Some(received);
+        state.stale = false;
+        state.offset_ms
+    }
+
+    /// Have the next check recalibrate, after the exchange rejected a timestamp.
+    pub fn invalidate(&self) {
+        self.lock().stale = true;
+    }
+
+    /// Whether the offset is due for a calibration `interval` after the last one.
+    pub fn is_due(&self, now: DateTime<Utc>, interval: Duration) -> bool {
+        let state = self.lock();
+        state.stale || state.synced_at.is_none_or(|at| now - at >= interval)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ClockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_from_round_trip_midpoint() {
+        let clock = ServerClock::new();
+        let sent = Utc::now();
+        assert_eq!(clock.server_ms(sent), sent.timestamp_millis() as u64);
+
+        // Local clock 1.5s behind; the server answered 100ms into a 200ms round trip
+        let server_ms = sent.timestamp_millis() + 100 + 1_500;
+        let offset = clock.calibrate(server_ms, sent, sent + Duration::milliseconds(200));
+        assert_eq!(offset, 1_500);
+        assert_eq!(clock.round_trip_ms(), 200);
+        assert_eq!(
+            clock.clone().server_ms(sent),
+            (sent.timestamp_millis() + 1_500) as u64
+        );
+    }
+
+    #[test]
+    fn test_recalibrates_on_interval_or_rejection() {
+        let clock = ServerClock::new();
+        let now = Utc::now();
+        let interval = Duration::minutes(30);
+        assert!(clock.is_due(now, interval));
+
+        clock.calibrate(now.timestamp_millis(), now, now);
+        assert!(!clock.is_due(now + Duration::minutes(29), interval));
+        assert!(clock.is_due(now + Duration::minutes(30), interval));
+
+        clock.invalidate();
+        assert!(clock.is_due(now, interval));
+    }
+}
@@ -0,0 +1,227 @@
+//! Sub-account capital isolation.
+//!
+//! Strategy capital lives in a dedicated sub-account so the strategy can never touch
+//! the rest of the master account. At startup the sub-account wallet is topped up to
+//! the configured capital; on shutdown its free balance is swept back to master.
+
+use crate::config::SubAccountConfig;
+use crate::exchange::{BinanceClient, SubAccountTransfer};
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use tracing::info;
+
+/// Master wallet that funds and receives sub-account capital.
+const MASTER_WALLET: &str = "SPOT";
+
+/// Direction of a capital transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    ToSubAccount,
+    ToMaster,
+}
+
+impl TransferDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferDirection::ToSubAccount => "to_sub_account",
+            TransferDirection::ToMaster => "to_master",
+        }
+    }
+}
+
+/// A completed capital transfer.
+#[derive(Debug, Clone)]
+pub struct CapitalTransfer {
+    pub direction: TransferDirection,
+    pub asset: String,
+    pub amount: Decimal,
+    /// Sub-account wallet involved in the transfer
+    pub wallet: String,
+    /// Exchange transfer ID
+    pub tran_id: i64,
+}
+
+/// Amount to transfer so the sub-account holds `target` (zero when already funded).
+pub fn top_up_amount(target: Decimal, current: Decimal) -> Decimal {
+    (target - current).max(Decimal::ZERO)
+}
+
+/// Build the universal transfer request for a direction.
+pub fn transfer_request(
+    config: &SubAccountConfig,
+    direction: TransferDirection,
+    amount: Decimal,
+) -> SubAccountTransfer {
+    let (from_email, to_email, from_account_type, to_account_type) = match direction {
+        TransferDirection::ToSubAccount => (
+            None,
+            Some(config.email.clone()),
+            MASTER_WALLET.to_string(),
+            config.wallet.clone(),
+        ),
+        TransferDirection::ToMaster => (
+            Some(config.email.clone()),
+            None,
+            config.wallet.clone(),
+            MASTER_WALLET.to_string(),
+        ),
+    };
+
+    SubAccountTransfer {
+        from_email,
+        to_email,
+        from_account_type,
+        to_account_type,
+        asset: config.asset.clone(),
+        amount,
+    }
+}
+
+/// Moves strategy capital between the master account and the sub-account.
+pub struct SubAccountManager<'a> {
+    config: &'a SubAccountConfig,
+    /// Client with master keys (transfers)
+    master: &'a BinanceClient,
+    /// Client with sub-account keys (balance lookups)
+    sub: &'a BinanceClient,
+}
+
+impl<'a> SubAccountManager<'a> {
+    pub fn new(
+        config: &'a SubAccountConfig,
+        master: &'a BinanceClient,
+        sub: &'a BinanceClient,
+    ) -> Self {
+        Self {
+            config,
+            master,
+            sub,
+        }
+    }
+
+    /// Total and free balance of the configured asset in the sub-account wallet.
+    ///
+    /// Total includes margin in use and is what funding tops up; only the free
+    /// part can be swept back.
+    pub async fn balances(&self) -> Result<(Decimal, Decimal)> {
+        let asset = &self.config.asset;
+        if self.config.wallet == "MARGIN" {
+            let account = self.sub.get_cross_margin_account().await?;
+            Ok(account
+                .user_assets
+                .iter()
+                .find(|a| a.asset == *asset)
+                .map(|a| (a.net_asset, a.free))
+                .unwrap_or_default())
+        } else {
+            let balances = self.sub.get_account_balance().await?;
+            Ok(balances
+                .iter()
+                .find(|b| b.asset == *asset)
+                .map(|b| (b.wallet_balance, b.available_balance))
+                .unwrap_or_default())
+        }
+    }
+
+    /// Top the sub-account up to the configured capital.
+    ///
+    /// Returns `None` when the sub-account already holds enough.
+    pub async fn fund(&self) -> Result<Option<CapitalTransfer>> {
+        let (total, _) = self
+            .balances()
+            .await
+            .context("Failed to read sub-account balance")?;
+        let amount = top_up_amount(self.config.capital, total);
+        if amount.is_zero() {
+            info!(
+                balance = %total,
+                target = %self.config.capital,
+                "Sub-account already funded"
+            );
+            return Ok(None);
+        }
+
+        self.transfer(TransferDirection::ToSubAccount, amount)
+            .await
+            .map(Some)
+    }
+
+    /// Sweep the sub-account's free balance back to the master account.
+    ///
+    /// Margin held by open positions stays in the sub-account.
+    pub async fn sweep(&self) -> Result<Option<CapitalTransfer>> {
+        let (_, free) = self
+            .balances()
+            .await
+            .context("Failed to read sub-account balance")?;
+        if free <= Decimal::ZERO {
+            return Ok(None);
+        }
+
+        self.transfer(TransferDirection::ToMaster, free)
+            .await
+            .map(Some)
+    }
+
+    async fn transfer(
+        &self,
+        direction: TransferDirection,
+        amount: Decimal,
+    ) -> Result<CapitalTransfer> {
+        let request = transfer_request(self.config, direction, amount);
+        let response = self.master.sub_account_transfer(&request).await?;
+
+        info!(
+            direction = direction.as_str(),
+            asset = %self.config.asset,
+            %amount,
+            tran_id = response.tran_id,
+            "Sub-account transfer completed"
+        );
+
+        Ok(CapitalTransfer {
+            direction,
+            asset: self.config.asset.clone(),
+            amount,
+            wallet: self.config.wallet.clone(),
+            tran_id: response.tran_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_top_up_amount() {
+        assert_eq!(top_up_amount(dec!(5000), dec!(1200)), dec!(3800));
+        assert_eq!(top_up_amount(dec!(5000), dec!(5000)), Decimal::ZERO);
+        // Never pulls funds out when the sub-account holds more than the target
+        assert_eq!(top_up_amount(dec!(5000), dec!(6500)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_transfer_request_directions() {
+        let config = SubAccountConfig {
+            enabled: true,
+            email: "farm@example.com".to_string(),
+            capital: dec!(5000),
+            ..Default::default()
+        };
+
+        let fund = transfer_request(&config, TransferDirection::ToSubAccount, dec!(100));
+        assert_eq!(fund.from_email, None);
+        assert_eq!(fund.to_email.as_deref(), Some("farm@example.com"));
+        assert_eq!(fund.from_account_type, "SPOT");
+        assert_eq!(fund.to_account_type, "USDT_FUTURE");
+        assert_eq!(fund.asset, "USDT");
+
+        let sweep = transfer_request(&config, TransferDirection::ToMaster, dec!(100));
+        assert_eq!(sweep.from_email.as_deref(), Some("farm@example.com"));
+        assert_eq!(sweep.to_email, None);
+        assert_eq!(sweep.from_account_type, "USDT_FUTURE");
+        assert_eq!(sweep.to_account_type, "SPOT");
+    }
+}
@@ -0,0 +1,106 @@
+//! Leverage and margin type of held symbols against the configuration.
+//!
+//! The executor sizes margin from `execution.default_leverage` and assumes cross
+//! margin, setting both when it enters a symbol. Someone changing either by hand
+//! on the exchange afterwards silently breaks those assumptions, so held symbols
+//! are compared with the configuration at startup and periodically in live mode.
+
+use super::{MarginType, Position};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// A held symbol whose exchange settings differ from the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsMismatch {
+    pub symbol: String,
+    pub leverage: u8,
+    pub expected_leverage: u8,
+    pub margin_type: MarginType,
+    pub expected_margin_type: MarginType,
+}
+
+impl SettingsMismatch {
+    pub fn leverage_differs(&self) -> bool {
+        self.leverage != self.expected_leverage
+    }
+
+    pub fn margin_type_differs(&self) -> bool {
+        self.margin_type != self.expected_margin_type
+    }
+}
+
+impl fmt::Display for SettingsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut differences = Vec::new();
+        if self.leverage_differs() {
+            differences.push(format!(
+                "leverage {}x (configured {}x)",
+                self.leverage, self.expected_leverage
+            ));
+        }
+        if self.margin_type_differs() {
+            differences.push(format!(
+                "{:?} margin (configured {:?})",
+                self.margin_type, self.expected_margin_type
+            ));
+        }
+        write!(f, "{}: {}", self.symbol, differences.join(", "))
+    }
+}
+
+/// Open positions whose leverage or margin type differs from the expected values.
+pub fn settings_mismatches(
+    positions: &[Position],
+    expected_leverage: u8,
+    expected_margin_type: MarginType,
+) -> Vec<SettingsMismatch> {
+    positions
+        .iter()
+        .filter(|p| p.position_amt != Decimal::ZERO)
+        .filter(|p| p.leverage != expected_leverage || p.margin_type != expected_margin_type)
+        .map(|p| SettingsMismatch {
+            symbol: p.symbol.clone(),
+            leverage: p.leverage,
+            expected_leverage,
+            margin_type: p.margin_type,
+            expected_margin_type,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position_risk(symbol: &str, amount: &str, leverage: &str, margin_type: &str) -> Position {
+        serde_json::from_str(&format!(
+            r#"{{"symbol":"{}","positionAmt":"{}","entryPrice":"50000","markPrice":"50100",
+                "unRealizedProfit":"0","liquidationPrice":"0","leverage":"{}",
+                "positionSide":"BOTH","notional":"0","isolatedMargin":"0",
+                "marginType":"{}"}}"#,
+            symbol, amount, leverage, margin_type
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mismatches_of_open_positions() {
+        let positions = vec![
+            position_risk("BTCUSDT", "-0.2", "5", "cross"),
+            position_risk("ETHUSDT", "3", "20", "cross"),
+            position_risk("SOLUSDT", "-10", "5", "isolated"),
+            // Flat symbols keep whatever was set last
+            position_risk("DOGEUSDT", "0", "50", "isolated"),
+        ];
+
+        let mismatches = settings_mismatches(&positions, 5, MarginType::Cross);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].leverage_differs() && !mismatches[0].margin_type_differs());
+        assert_eq!(
+            mismatches[0].to_string(),
+            "ETHUSDT: leverage 20x (configured 5x)"
+        );
+        assert_eq!(mismatches[1].symbol, "SOLUSDT");
+        assert!(mismatches[1].margin_type_differs() && !mismatches[1].leverage_differs());
+    }
+}
@@ -0,0 +1,192 @@
+//! Serialized execution of account-mutating requests.
+//!
+//! Orders, cancels, leverage and margin changes, borrows and transfers sent
+//! concurrently can reach the exchange out of order: a repay racing the order
+//! that frees its funds, or a leverage change landing after the entry it was
+//! meant for. The live client takes a turn on its `TradeQueue` before each such
+//! call and holds it until the response is read, so one runs at a time. Waiting
+//! closes, reductions and cancels go ahead of everything else; within a lane
+//! calls run in arrival order. Market data and account reads never wait.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Priority of a queued call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TradeLane {
+    /// Entries, leverage and margin changes, borrows and transfers
+    Normal,
+    /// Reduce-only orders, cancels and repays, served first
+    Close,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    lane: TradeLane,
+    /// Arrival order within the queue
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    /// Higher lane first, then earlier arrival.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.lane
+            .cmp(&other.lane)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    busy: bool,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+/// One-at-a-time queue for account-mutating calls; clones share the queue.
+#[derive(Debug, Clone, Default)]
+pub struct TradeQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl TradeQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait for the turn to send a call in `lane`; it lasts until the returned
+    /// `TradeTurn` is dropped.
+    pub async fn turn(&self, lane: TradeLane) -> TradeTurn {
+        let wake = {
+            let mut state = self.lock();
+            if !state.busy {
+                state.busy = true;
+                return TradeTurn {
+                    queue: self.clone(),
+                };
+            }
+            let (wake, woken) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter { lane, seq, wake });
+            woken
+        };
+
+        let mut pending = PendingTurn {
+            queue: self.clone(),
+            woken: Some(wake),
+        };
+        if let Some(woken) = pending.woken.as_mut() {
+            // The sender is only dropped unsent once this receiver is gone
+            let _ = woken.await;
+        }
+        pending.woken = None;
+        TradeTurn {
+            queue: self.clone(),
+        }
+    }
+
+    /// Calls waiting for a turn.
+    pub fn waiting(&self) -> usize {
+        self.lock().waiting.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hand the turn to the next waiter still listening, or free the queue.
+    fn release(&self) {
+        let mut state = self.lock();
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.busy = false;
+    }
+}
+
+/// The right to send one account-mutating call; passes on when dropped.
+#[derive(Debug)]
+pub struct TradeTurn {
+    queue: TradeQueue,
+}
+
+impl Drop for TradeTurn {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// A waiter that may be cancelled after the turn was already handed to it.
+struct PendingTurn {
+    queue: TradeQueue,
+    woken: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingTurn {
+    fn drop(&mut self) {
+        if let Some(mut woken) = self.woken.take() {
+            if woken.try_recv().is_ok() {
+                self.queue.release();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_closes_go_first_and_cancelled_waiters_are_skipped() {
+        let queue = TradeQueue::new();
+        let first = queue.turn(TradeLane::Normal).await;
+
+        let (done_tx, mut done) = tokio::sync::mpsc::unbounded_channel();
+        let mut tasks = Vec::new();
+        for (name, lane) in [
+            ("entry", TradeLane::Normal),
+            ("cancelled", TradeLane::Close),
+            ("close", TradeLane::Close),
+        ] {
+            let queue = queue.clone();
+            let done_tx = done_tx.clone();
+            tasks.push(tokio::spawn(async move {
+                let _turn = queue.turn(lane).await;
+                done_tx.send(name).unwrap();
+            }));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(queue.waiting(), 3);
+
+        // A waiter that gives up doesn't hold up the queue
+        tasks[1].abort();
+        drop(first);
+        assert_eq!(done.recv().await, Some("close"));
+        assert_eq!(done.recv().await, Some("entry"));
+
+        // Once idle, a turn is granted at once
+        let _turn = queue.turn(TradeLane::Normal).await;
+        assert_eq!(queue.waiting(), 0);
+    }
+}
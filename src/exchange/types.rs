@@ -1,7 +1,10 @@
 //! Type definitions for Binance API responses.
 
+use super::Venue;
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
 /// Trading pair symbol information.
 #[derive(Debug, Clone, Deserialize)]
@@ -35,6 +38,32 @@ pub struct FuturesSymbolInfo {
     pub status: String,
     pub base_asset: String,
     pub quote_asset: String,
+    /// Trading rules; the testnet's differ from production
+    #[serde(default)]
+    pub filters: Vec<SymbolFilter>,
+}
+
+impl FuturesSymbolInfo {
+    /// Smallest order notional the symbol accepts, if it has a limit.
+    pub fn min_notional(&self) -> Option<Decimal> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::MinNotional { notional } => Some(*notional),
+            _ => None,
+        })
+    }
+}
+
+/// A trading rule of a futures symbol.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolFilter {
+    MinNotional {
+        #[serde(with = "rust_decimal::serde::str")]
+        notional: Decimal,
+    },
+    /// Any rule not checked before ordering
+    #[serde(other)]
+    Other,
 }
 
 /// Funding rate information for a perpetual contract.
@@ -50,6 +79,56 @@ pub struct FundingRate {
     pub mark_price: Option<Decimal>,
 }
 
+/// Funding settings of a symbol whose parameters were adjusted from the defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingInfo {
+    pub symbol: String,
+    /// Hours between funding settlements
+    pub funding_interval_hours: u32,
+    /// Highest funding rate per settlement
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub adjusted_funding_rate_cap: Option<Decimal>,
+    /// Lowest funding rate per settlement
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub adjusted_funding_rate_floor: Option<Decimal>,
+}
+
+impl FundingInfo {
+    /// Funding cap and floor, when both are published.
+    pub fn cap(&self) -> Option<FundingCap> {
+        Some(FundingCap {
+            cap: self.adjusted_funding_rate_cap?,
+            floor: self.adjusted_funding_rate_floor?,
+        })
+    }
+}
+
+/// Bounds the exchange clamps a symbol's funding rate to, per settlement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FundingCap {
+    pub cap: Decimal,
+    pub floor: Decimal,
+}
+
+impl FundingCap {
+    /// Whether `rate` is pinned at the cap or the floor.
+    pub fn pins(&self, rate: Decimal) -> bool {
+        rate >= self.cap || rate <= self.floor
+    }
+}
+
+/// Historical funding settlement for a perpetual contract.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateRecord {
+    pub symbol: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub funding_rate: Decimal,
+    /// Settlement time (milliseconds since epoch)
+    pub funding_time: i64,
+}
+
 /// 24-hour ticker statistics.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -88,6 +167,17 @@ pub struct BookTicker {
     pub ask_qty: Decimal,
 }
 
+/// Order book depth snapshot.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    pub last_update_id: i64,
+    /// (price, quantity) levels, best first
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// (price, quantity) levels, best first
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
 /// Account balance information.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -103,6 +193,49 @@ pub struct AccountBalance {
     pub available_balance: Decimal,
 }
 
+/// Futures income history entry (funding fees, commissions, realized PnL, ...).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeRecord {
+    /// Empty for account-level income such as transfers
+    pub symbol: String,
+    /// e.g. "FUNDING_FEE", "COMMISSION", "REALIZED_PNL"
+    pub income_type: String,
+    /// Signed amount (positive = received)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub income: Decimal,
+    pub asset: String,
+    /// Event time in milliseconds
+    pub time: i64,
+    pub tran_id: i64,
+}
+
+/// Fill of one of the account's futures orders.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesTrade {
+    pub id: i64,
+    pub symbol: String,
+    pub order_id: i64,
+    pub side: OrderSide,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub qty: Decimal,
+    /// Fill time in milliseconds
+    pub time: i64,
+}
+
+impl FuturesTrade {
+    /// Quantity signed by side: positive for buys, negative for sells.
+    pub fn signed_qty(&self) -> Decimal {
+        match self.side {
+            OrderSide::Buy => self.qty,
+            OrderSide::Sell => -self.qty,
+        }
+    }
+}
+
 /// Futures position information.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -114,10 +247,11 @@ pub struct Position {
     pub entry_price: Decimal,
     #[serde(with = "rust_decimal::serde::str")]
     pub mark_price: Decimal,
-    #[serde(with = "rust_decimal::serde::str")]
+    #[serde(with = "rust_decimal::serde::str", alias = "unRealizedProfit")]
     pub unrealized_profit: Decimal,
     #[serde(with = "rust_decimal::serde::str")]
     pub liquidation_price: Decimal,
+    #[serde(deserialize_with = "leverage_from_str_or_number")]
     pub leverage: u8,
     pub position_side: PositionSide,
     #[serde(with = "rust_decimal::serde::str")]
@@ -127,6 +261,20 @@ pub struct Position {
     pub margin_type: MarginType,
 }
 
+/// Position risk reports leverage as a string ("20"); accept plain numbers too.
+fn leverage_from_str_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u8),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(leverage) => Ok(leverage),
+        Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+    }
+}
+
 /// Position side (long, short, or both for hedge mode).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -163,6 +311,41 @@ pub enum OrderType {
     TrailingStopMarket,
 }
 
+/// Futures trading fees as fractions of notional.
+///
+/// A negative maker rate is a rebate: tiers that pay for liquidity credit the
+/// account on every fill of a resting order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub maker_rate: Decimal,
+    pub taker_rate: Decimal,
+}
+
+impl Default for FeeSchedule {
+    /// Regular tier with the BNB discount: 0.02% maker, 0.04% taker.
+    fn default() -> Self {
+        Self {
+            maker_rate: Decimal::new(2, 4),
+            taker_rate: Decimal::new(4, 4),
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// Fee rate of a futures order: limit orders rest on the book and pay the maker rate.
+    pub fn rate(&self, order_type: OrderType) -> Decimal {
+        match order_type {
+            OrderType::Limit => self.maker_rate,
+            _ => self.taker_rate,
+        }
+    }
+
+    /// Whether resting orders earn a rebate.
+    pub fn has_rebate(&self) -> bool {
+        self.maker_rate < Decimal::ZERO
+    }
+}
+
 /// Time in force for limit orders.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -202,6 +385,17 @@ pub struct NewOrder {
     pub new_client_order_id: Option<String>,
 }
 
+/// New price and quantity for an open futures limit order.
+#[derive(Debug, Clone)]
+pub struct AmendOrder {
+    pub symbol: String,
+    pub order_id: i64,
+    /// Side of the order; Binance requires it and it can't change
+    pub side: OrderSide,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
 /// Order response from the exchange.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -225,6 +419,40 @@ pub struct OrderResponse {
     pub update_time: i64,
 }
 
+/// Order looked up by order or client order ID, or listed as open (futures or margin).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderLookup {
+    pub order_id: i64,
+    pub symbol: String,
+    pub status: OrderStatus,
+    pub client_order_id: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub executed_qty: Decimal,
+    /// Average fill price (futures orders)
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub avg_price: Option<Decimal>,
+    /// Quote amount filled (spot and margin orders, which report no average price)
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub cummulative_quote_qty: Option<Decimal>,
+    pub side: OrderSide,
+    /// When the order was placed (ms); 0 if the venue didn't say
+    #[serde(default)]
+    pub time: i64,
+}
+
+impl OrderLookup {
+    /// Average price of the executed quantity, `None` if the venue didn't say.
+    pub fn fill_price(&self) -> Option<Decimal> {
+        if let Some(avg_price) = self.avg_price.filter(|price| !price.is_zero()) {
+            return Some(avg_price);
+        }
+        self.cummulative_quote_qty
+            .filter(|_| !self.executed_qty.is_zero())
+            .map(|quote| quote / self.executed_qty)
+    }
+}
+
 /// Open interest for a symbol.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -234,6 +462,125 @@ pub struct OpenInterest {
     pub open_interest: Decimal,
 }
 
+/// Exchange system status (`/sapi/v1/system/status`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SystemStatus {
+    /// 0 = normal, 1 = system maintenance
+    pub status: u8,
+    #[serde(default)]
+    pub msg: String,
+}
+
+/// Exchange server time (`/fapi/v1/time`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTime {
+    /// Milliseconds since the epoch
+    pub server_time: i64,
+}
+
+impl SystemStatus {
+    pub fn is_maintenance(&self) -> bool {
+        self.status != 0
+    }
+}
+
+/// Instrument that offsets the delta of the futures leg.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum HedgeInstrument {
+    /// Opposite position in the USDT spot pair (borrowed through margin when short)
+    #[default]
+    SpotMargin,
+    /// Opposite position in the asset's USDC-margined perpetual on Binance
+    UsdcPerp {
+        /// Perpetual symbol (e.g., "BTCUSDC")
+        symbol: String,
+        /// Funding rate of the perpetual
+        funding_rate: Decimal,
+    },
+    /// Opposite position in the asset's perpetual on another venue
+    VenuePerp {
+        venue: Venue,
+        /// Funding rate of the perpetual, scaled to 8h
+        funding_rate: Decimal,
+    },
+}
+
+impl HedgeInstrument {
+    /// Whether the hedge is the spot leg the executor trades.
+    pub fn is_spot(&self) -> bool {
+        matches!(self, HedgeInstrument::SpotMargin)
+    }
+
+    /// Whether `other` is the same instrument, whatever its current funding rate.
+    pub fn same_instrument(&self, other: &HedgeInstrument) -> bool {
+        match (self, other) {
+            (HedgeInstrument::SpotMargin, HedgeInstrument::SpotMargin) => true,
+            (
+                HedgeInstrument::UsdcPerp { symbol: a, .. },
+                HedgeInstrument::UsdcPerp { symbol: b, .. },
+            ) => a == b,
+            (
+                HedgeInstrument::VenuePerp { venue: a, .. },
+                HedgeInstrument::VenuePerp { venue: b, .. },
+            ) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Funding paid by the hedge leg per period when the futures leg earns
+    /// `funding_rate` (negative = the hedge receives funding).
+    ///
+    /// The hedge takes the opposite side: long when the futures leg is short for
+    /// positive funding, short otherwise. Spot hedges pay no funding.
+    pub fn funding_carry(&self, funding_rate: Decimal) -> Decimal {
+        let hedge_rate = match self {
+            HedgeInstrument::SpotMargin => return Decimal::ZERO,
+            HedgeInstrument::UsdcPerp { funding_rate, .. }
+            | HedgeInstrument::VenuePerp { funding_rate, .. } => *funding_rate,
+        };
+        if funding_rate >= Decimal::ZERO {
+            hedge_rate
+        } else {
+            -hedge_rate
+        }
+    }
+}
+
+/// Cost of holding a hedge per 8h, as fractions of notional (negative = income).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HedgeCarry {
+    /// Borrow interest of a short spot hedge
+    pub borrow: Decimal,
+    /// Funding paid by a perp hedge
+    pub funding: Decimal,
+    /// Entry premium of a perp hedge over the futures leg, spread over the holding horizon
+    pub basis: Decimal,
+}
+
+impl HedgeCarry {
+    pub fn total(&self) -> Decimal {
+        self.borrow + self.funding + self.basis
+    }
+}
+
+/// A hedge that is available for a symbol, with its carry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HedgeOption {
+    pub instrument: HedgeInstrument,
+    pub carry: HedgeCarry,
+}
+
+impl fmt::Display for HedgeInstrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HedgeInstrument::SpotMargin => write!(f, "spot margin"),
+            HedgeInstrument::UsdcPerp { symbol, .. } => write!(f, "{} perp", symbol),
+            HedgeInstrument::VenuePerp { venue, .. } => write!(f, "{} perp", venue),
+        }
+    }
+}
+
 /// Qualified trading pair with all required metrics.
 #[derive(Debug, Clone)]
 pub struct QualifiedPair {
@@ -254,6 +601,157 @@ pub struct QualifiedPair {
     /// Hourly borrow rate for the base asset (for shorting)
     pub borrow_rate: Option<Decimal>,
     pub score: Decimal,
+    /// Instrument hedging the futures leg (the cheapest to carry)
+    pub hedge: HedgeInstrument,
+    /// Hedges considered, cheapest carry first
+    pub hedge_options: Vec<HedgeOption>,
+    /// Funding pinned at the symbol's cap or floor, expected to snap back
+    pub funding_capped: bool,
+    /// COIN-margined perpetual of the base asset, when one is listed
+    pub coin_contract: Option<CoinContract>,
+    /// USDT left to borrow of the base asset, when checked
+    pub borrow_headroom_usdt: Option<Decimal>,
+}
+
+// ==================== COIN-Margined Futures Types ====================
+
+/// Exchange information for COIN-margined futures.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinExchangeInfo {
+    pub symbols: Vec<CoinSymbolInfo>,
+}
+
+/// Symbol information for a COIN-margined contract.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinSymbolInfo {
+    /// Contract symbol (e.g., "BTCUSD_PERP")
+    pub symbol: String,
+    pub pair: String,
+    pub contract_type: String,
+    pub contract_status: String,
+    pub base_asset: String,
+    /// Asset posted as margin and paid out as PnL
+    pub margin_asset: String,
+    /// USD value of one contract
+    pub contract_size: Decimal,
+}
+
+/// Mark price and funding of a COIN-margined contract.
+///
+/// Delivery contracts report an empty funding rate.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinPremiumIndex {
+    pub symbol: String,
+    #[serde(default)]
+    pub last_funding_rate: String,
+    #[serde(default)]
+    pub next_funding_time: i64,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub mark_price: Decimal,
+}
+
+impl CoinExchangeInfo {
+    /// Trading perpetuals margined in their own base coin, keyed by base asset.
+    pub fn perpetuals(&self, index: &[CoinPremiumIndex]) -> HashMap<String, CoinContract> {
+        self.symbols
+            .iter()
+            .filter(|s| {
+                s.contract_type == "PERPETUAL"
+                    && s.contract_status == "TRADING"
+                    && s.margin_asset == s.base_asset
+            })
+            .filter_map(|s| {
+                let premium = index.iter().find(|p| p.symbol == s.symbol)?;
+                let funding_rate = premium.last_funding_rate.parse().ok()?;
+                Some((
+                    s.base_asset.clone(),
+                    CoinContract {
+                        symbol: s.symbol.clone(),
+                        base_asset: s.base_asset.clone(),
+                        contract_size: s.contract_size,
+                        funding_rate,
+                        next_funding_time: premium.next_funding_time,
+                        mark_price: premium.mark_price,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// A COIN-margined perpetual: the base coin is both its collateral and its
+/// settlement asset, so a short against held coins needs no USDT.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinContract {
+    /// Contract symbol (e.g., "BTCUSD_PERP")
+    pub symbol: String,
+    pub base_asset: String,
+    /// USD value of one contract
+    pub contract_size: Decimal,
+    pub funding_rate: Decimal,
+    /// Next funding settlement time (milliseconds since epoch)
+    pub next_funding_time: i64,
+    pub mark_price: Decimal,
+}
+
+impl CoinContract {
+    /// Whole contracts whose USD value `quantity` coins cover at the mark price.
+    pub fn contracts_for(&self, quantity: Decimal) -> Decimal {
+        if self.contract_size <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        (quantity * self.mark_price / self.contract_size)
+            .floor()
+            .max(Decimal::ZERO)
+    }
+
+    /// USD value of `contracts` contracts.
+    pub fn notional(&self, contracts: Decimal) -> Decimal {
+        contracts.abs() * self.contract_size
+    }
+}
+
+/// COIN-margined wallet balance of one asset.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinBalance {
+    pub asset: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub cross_un_pnl: Decimal,
+}
+
+/// COIN-margined position; sizes are in contracts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinPosition {
+    pub symbol: String,
+    /// Contracts held (negative = short)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub position_amt: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub entry_price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub mark_price: Decimal,
+    /// Unrealized PnL in the margin coin
+    #[serde(with = "rust_decimal::serde::str", rename = "unRealizedProfit")]
+    pub unrealized_profit: Decimal,
+}
+
+/// COIN-margined order response: `executedQty` counts contracts, `cumBase` coins.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinOrderResponse {
+    #[serde(flatten)]
+    pub order: OrderResponse,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub cum_base: Option<Decimal>,
 }
 
 // ==================== Spot Margin Types ====================
@@ -269,6 +767,34 @@ pub struct SpotSymbolInfo {
     /// Whether margin trading is permitted
     #[serde(default)]
     pub is_margin_trading_allowed: bool,
+    /// Trading rules
+    #[serde(default)]
+    pub filters: Vec<SpotSymbolFilter>,
+}
+
+impl SpotSymbolInfo {
+    /// Decimal places of the order quantity step, if the symbol has one.
+    pub fn quantity_precision(&self) -> Option<u8> {
+        self.filters.iter().find_map(|filter| match filter {
+            SpotSymbolFilter::LotSize { step_size } if *step_size > Decimal::ZERO => {
+                Some(step_size.normalize().scale() as u8)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// A trading rule of a spot symbol.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SpotSymbolFilter {
+    LotSize {
+        #[serde(rename = "stepSize", with = "rust_decimal::serde::str")]
+        step_size: Decimal,
+    },
+    /// Any rule not checked before ordering
+    #[serde(other)]
+    Other,
 }
 
 /// Margin asset information.
@@ -320,6 +846,18 @@ pub struct MarginAccountAsset {
     pub net_asset: Decimal,
 }
 
+/// What is left to borrow of one asset in cross margin.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxBorrowable {
+    /// Amount that can still be borrowed, in the asset
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    /// Most of the asset the account may borrow in total
+    #[serde(with = "rust_decimal::serde::str")]
+    pub borrow_limit: Decimal,
+}
+
 /// Margin borrow/repay request.
 #[derive(Debug, Clone, Serialize)]
 pub struct MarginLoanRequest {
@@ -342,6 +880,7 @@ pub struct MarginOrder {
     pub is_isolated: Option<bool>,
     /// MARGIN_BUY, AUTO_REPAY, etc.
     pub side_effect_type: Option<SideEffectType>,
+    pub new_client_order_id: Option<String>,
 }
 
 /// Side effect type for margin orders.
@@ -358,6 +897,27 @@ pub enum SideEffectType {
     AutoBorrowRepay,
 }
 
+/// Universal transfer between master and sub-account wallets.
+///
+/// Leaving an email empty refers to the master account.
+#[derive(Debug, Clone)]
+pub struct SubAccountTransfer {
+    pub from_email: Option<String>,
+    pub to_email: Option<String>,
+    /// e.g. "SPOT", "USDT_FUTURE", "MARGIN"
+    pub from_account_type: String,
+    pub to_account_type: String,
+    pub asset: String,
+    pub amount: Decimal,
+}
+
+/// Universal transfer acknowledgement.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubAccountTransferResponse {
+    pub tran_id: i64,
+}
+
 /// Represents a delta-neutral position (futures + spot hedge).
 #[derive(Debug, Clone)]
 pub struct DeltaNeutralPosition {
@@ -0,0 +1,606 @@
+//! Per-symbol routing between trading venues.
+//!
+//! `VenueRouter` puts several venue clients behind one `ExchangeClient`, so the
+//! scanner, executor and rebalancer can trade each symbol on whichever venue
+//! pays more funding without knowing there is more than one. Every funding
+//! refresh routes each symbol to the venue with the largest funding per hour;
+//! market data, orders and lookups of that symbol then go to that venue, while
+//! positions, balances and income are combined across venues.
+//!
+//! A symbol stays on its venue once it is traded there, until no position of it
+//! is left, so both legs of a pair are always opened and closed on the same venue.
+
+use super::types::*;
+use super::{ExchangeClient, FundingCalendar, Venue};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use tracing::{info, warn};
+
+/// Quote asset of every routed symbol, for mapping assets to symbols.
+const QUOTE_ASSET: &str = "USDT";
+
+/// Symbol routes and the pins keeping traded symbols on their venue.
+#[derive(Debug, Default)]
+struct RouteState {
+    routes: HashMap<String, Venue>,
+    /// Symbols traded or held, kept on their venue
+    pinned: HashSet<String>,
+}
+
+/// Exchange client routing each symbol to the venue paying it more funding.
+pub struct VenueRouter<'a> {
+    /// Venue clients, the primary first
+    venues: Vec<(Venue, &'a dyn ExchangeClient)>,
+    state: Mutex<RouteState>,
+    /// Funding intervals of every venue, for comparing rates per hour
+    calendar: Mutex<FundingCalendar>,
+}
+
+impl<'a> VenueRouter<'a> {
+    /// Router over `primary` and `others`.
+    ///
+    /// Symbols go to the primary until funding rates route them, and rates are
+    /// reported on the primary's settlement interval.
+    pub fn new(
+        primary: (Venue, &'a dyn ExchangeClient),
+        others: Vec<(Venue, &'a dyn ExchangeClient)>,
+    ) -> Self {
+        let mut venues = vec![primary];
+        venues.extend(others);
+        Self {
+            venues,
+            state: Mutex::new(RouteState::default()),
+            calendar: Mutex::new(FundingCalendar::new()),
+        }
+    }
+
+    /// Venue `symbol` is traded on.
+    pub fn venue_of(&self, symbol: &str) -> Venue {
+        self.lock_state()
+            .routes
+            .get(symbol)
+            .copied()
+            .unwrap_or_else(|| self.primary())
+    }
+
+    /// Every venue routed to, the primary first.
+    pub fn venues(&self) -> &[(Venue, &'a dyn ExchangeClient)] {
+        &self.venues
+    }
+
+    fn primary(&self) -> Venue {
+        self.venues[0].0
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, RouteState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_calendar(&self) -> MutexGuard<'_, FundingCalendar> {
+        self.calendar.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Client of the venue `symbol` is traded on.
+    fn client_for(&self, symbol: &str) -> &'a dyn ExchangeClient {
+        let venue = self.venue_of(symbol);
+        self.venues
+            .iter()
+            .find(|(v, _)| *v == venue)
+            .map(|(_, client)| *client)
+            .unwrap_or(self.venues[0].1)
+    }
+
+    /// Keep `symbol` on its current venue until no position of it is left.
+    fn pin(&self, symbol: &str) {
+        self.lock_state().pinned.insert(symbol.to_string());
+    }
+
+    /// Entries of `venue` for the symbols routed to it.
+    fn routed_to<T>(
+        &self,
+        venue: Venue,
+        entries: Vec<T>,
+        symbol_of: impl Fn(&T) -> &str,
+    ) -> Vec<T> {
+        entries
+            .into_iter()
+            .filter(|entry| self.venue_of(symbol_of(entry)) == venue)
+            .collect()
+    }
+
+    /// Route every symbol to the venue with the largest funding per hour.
+    ///
+    /// Pinned symbols keep their venue. Returns each symbol's rate on its venue,
+    /// scaled to the primary's settlement interval.
+    fn route(&self, rates_by_venue: Vec<(Venue, Vec<FundingRate>)>) -> Vec<FundingRate> {
+        let calendar = self.lock_calendar();
+        let mut state = self.lock_state();
+        let primary = self.primary();
+
+        let mut best: HashMap<String, (Venue, Decimal, FundingRate)> = HashMap::new();
+        for (venue, rates) in rates_by_venue {
+            for rate in rates {
+                let hourly = calendar
+                    .schedule(venue, &rate.symbol)
+                    .rate_per_hours(rate.funding_rate, 1);
+                let current = state.routes.get(&rate.symbol).copied();
+                let keeps_venue = state.pinned.contains(&rate.symbol);
+                let better = match best.get(&rate.symbol) {
+                    _ if keeps_venue => current.unwrap_or(primary) == venue,
+                    Some((_, best_hourly, _)) => hourly.abs() > best_hourly.abs(),
+                    None => true,
+                };
+                if better {
+                    best.insert(rate.symbol.clone(), (venue, hourly, rate));
+                }
+            }
+        }
+
+        let mut rerouted = 0;
+        let routed = best
+            .into_iter()
+            .map(|(symbol, (venue, hourly, mut rate))| {
+                let previous = state.routes.insert(symbol.clone(), venue);
+                if previous.unwrap_or(primary) != venue {
+                    rerouted += 1;
+                }
+                let primary_hours = calendar.schedule(primary, &symbol).interval_hours();
+                rate.funding_rate = hourly * Decimal::from(primary_hours);
+                rate
+            })
+            .collect();
+        if rerouted > 0 {
+            info!(rerouted, "Symbols moved to the venue paying more funding");
+        }
+        routed
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for VenueRouter<'_> {
+    async fn get_funding_rates(&self) -> Result<Vec<FundingRate>> {
+        let mut rates_by_venue = Vec::with_capacity(self.venues.len());
+        for (index, (venue, client)) in self.venues.iter().enumerate() {
+            match client.get_funding_rates().await {
+                Ok(rates) => rates_by_venue.push((*venue, rates)),
+                Err(e) if index == 0 => return Err(e),
+                // Without a venue's rates its symbols fall back to the others
+                Err(e) => {
+                    warn!(%venue, error = %e, "Funding rates unavailable, not routing to venue")
+                }
+            }
+        }
+        Ok(self.route(rates_by_venue))
+    }
+
+    async fn get_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let mut tickers = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_24h_tickers().await?;
+            tickers.extend(self.routed_to(*venue, entries, |t| &t.symbol));
+        }
+        Ok(tickers)
+    }
+
+    async fn get_spot_24h_tickers(&self) -> Result<Vec<Ticker24h>> {
+        let mut tickers = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_spot_24h_tickers().await?;
+            tickers.extend(self.routed_to(*venue, entries, |t| &t.symbol));
+        }
+        Ok(tickers)
+    }
+
+    async fn get_spot_24h_tickers_for(&self, symbols: &[String]) -> Result<Vec<Ticker24h>> {
+        let mut tickers = Vec::new();
+        for (venue, client) in &self.venues {
+            let venue_symbols: Vec<String> = symbols
+                .iter()
+                .filter(|symbol| self.venue_of(symbol) == *venue)
+                .cloned()
+                .collect();
+            if !venue_symbols.is_empty() {
+                tickers.extend(client.get_spot_24h_tickers_for(&venue_symbols).await?);
+            }
+        }
+        Ok(tickers)
+    }
+
+    async fn get_book_tickers(&self) -> Result<Vec<BookTicker>> {
+        let mut tickers = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_book_tickers().await?;
+            tickers.extend(self.routed_to(*venue, entries, |t| &t.symbol));
+        }
+        Ok(tickers)
+    }
+
+    async fn get_spot_exchange_info(&self) -> Result<Vec<SpotSymbolInfo>> {
+        let mut symbols = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_spot_exchange_info().await?;
+            symbols.extend(self.routed_to(*venue, entries, |s| &s.symbol));
+        }
+        Ok(symbols)
+    }
+
+    async fn get_margin_all_assets(&self) -> Result<Vec<MarginAsset>> {
+        // A base asset is borrowed on its symbol's venue; the quote on the primary
+        let mut assets = Vec::new();
+        for (index, (venue, client)) in self.venues.iter().enumerate() {
+            let entries = client.get_margin_all_assets().await?;
+            assets.extend(entries.into_iter().filter(|a| {
+                if a.asset == QUOTE_ASSET {
+                    index == 0
+                } else {
+                    self.venue_of(&format!("{}{}", a.asset, QUOTE_ASSET)) == *venue
+                }
+            }));
+        }
+        Ok(assets)
+    }
+
+    /// Intervals of every venue go into the router's calendar; the primary's
+    /// are returned, as routed rates are quoted on its intervals.
+    async fn get_funding_info(&self) -> Result<Vec<FundingInfo>> {
+        let mut primary_info = Vec::new();
+        for (index, (venue, client)) in self.venues.iter().enumerate() {
+            let info = client.get_funding_info().await?;
+            self.lock_calendar().apply_intervals(*venue, &info);
+            if index == 0 {
+                primary_info = info;
+            }
+        }
+        Ok(primary_info)
+    }
+
+    async fn get_funding_rate_history(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<FundingRateRecord>> {
+        self.client_for(symbol)
+            .get_funding_rate_history(symbol, start_time, end_time)
+            .await
+    }
+
+    async fn get_futures_exchange_info(&self) -> Result<FuturesExchangeInfo> {
+        let mut symbols = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_futures_exchange_info().await?.symbols;
+            symbols.extend(self.routed_to(*venue, entries, |s| &s.symbol));
+        }
+        Ok(FuturesExchangeInfo { symbols })
+    }
+
+    async fn get_leverage_brackets(&self) -> Result<Vec<LeverageBracket>> {
+        let mut brackets = Vec::new();
+        for (venue, client) in &self.venues {
+            let entries = client.get_leverage_brackets().await?;
+            brackets.extend(self.routed_to(*venue, entries, |b| &b.symbol));
+        }
+        Ok(brackets)
+    }
+
+    async fn get_spot_depth(&self, symbol: &str, limit: u16) -> Result<OrderBook> {
+        self.client_for(symbol).get_spot_depth(symbol, limit).await
+    }
+
+    async fn place_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        self.pin(&order.symbol);
+        self.client_for(&order.symbol)
+            .place_futures_order(order)
+            .await
+    }
+
+    /// Each venue gets one batch of the orders routed to it.
+    async fn place_futures_orders(&self, orders: &[NewOrder]) -> Vec<Result<OrderResponse>> {
+        let venue_index = |symbol: &str| {
+            let venue = self.venue_of(symbol);
+            self.venues
+                .iter()
+                .position(|(v, _)| *v == venue)
+                .unwrap_or(0)
+        };
+        let routes: Vec<usize> = orders.iter().map(|o| venue_index(&o.symbol)).collect();
+        let mut results: Vec<Option<Result<OrderResponse>>> = orders.iter().map(|_| None).collect();
+        for (index, (_, client)) in self.venues.iter().enumerate() {
+            let (slots, batch): (Vec<usize>, Vec<NewOrder>) = orders
+                .iter()
+                .enumerate()
+                .filter(|(slot, _)| routes[*slot] == index)
+                .map(|(slot, order)| (slot, order.clone()))
+                .unzip();
+            if batch.is_empty() {
+                continue;
+            }
+            for order in &batch {
+                self.pin(&order.symbol);
+            }
+            let placed = client.place_futures_orders(&batch).await;
+            for (slot, result) in slots.into_iter().zip(placed) {
+                results[slot] = Some(result);
+            }
+        }
+        // A venue answering fewer orders than it was sent leaves the others unanswered
+        results
+            .into_iter()
+            .zip(orders)
+            .map(|(result, order)| {
+                result.unwrap_or_else(|| {
+                    Err(anyhow!(
+                        "No venue placed or refused the {} order",
+                        order.symbol
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    async fn place_margin_order(&self, order: &MarginOrder) -> Result<OrderResponse> {
+        self.pin(&order.symbol);
+        self.client_for(&order.symbol)
+            .place_margin_order(order)
+            .await
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<()> {
+        self.client_for(symbol).set_leverage(symbol, leverage).await
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<()> {
+        self.client_for(symbol)
+            .set_margin_type(symbol, margin_type)
+            .await
+    }
+
+    async fn get_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.client_for(symbol)
+            .get_futures_order_by_client_id(symbol, client_order_id)
+            .await
+    }
+
+    async fn get_futures_order(&self, symbol: &str, order_id: i64) -> Result<Option<OrderLookup>> {
+        self.client_for(symbol)
+            .get_futures_order(symbol, order_id)
+            .await
+    }
+
+    async fn get_open_futures_orders(&self) -> Result<Vec<OrderLookup>> {
+        let mut orders = Vec::new();
+        for (_, client) in &self.venues {
+            orders.extend(client.get_open_futures_orders().await?);
+        }
+        Ok(orders)
+    }
+
+    async fn cancel_futures_order(&self, symbol: &str, order_id: i64) -> Result<OrderResponse> {
+        self.client_for(symbol)
+            .cancel_futures_order(symbol, order_id)
+            .await
+    }
+
+    async fn amend_futures_order(&self, amend: &AmendOrder) -> Result<OrderResponse> {
+        self.client_for(&amend.symbol)
+            .amend_futures_order(amend)
+            .await
+    }
+
+    async fn get_open_margin_orders(&self) -> Result<Vec<OrderLookup>> {
+        let mut orders = Vec::new();
+        for (_, client) in &self.venues {
+            orders.extend(client.get_open_margin_orders().await?);
+        }
+        Ok(orders)
+    }
+
+    async fn cancel_margin_order(&self, symbol: &str, order_id: i64) -> Result<OrderLookup> {
+        self.client_for(symbol)
+            .cancel_margin_order(symbol, order_id)
+            .await
+    }
+
+    async fn get_margin_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.client_for(symbol)
+            .get_margin_order_by_client_id(symbol, client_order_id)
+            .await
+    }
+
+    /// COIN-margined contracts are never routed; they trade on the primary.
+    async fn place_coin_futures_order(&self, order: &NewOrder) -> Result<OrderResponse> {
+        self.venues[0].1.place_coin_futures_order(order).await
+    }
+
+    async fn get_coin_futures_order_by_client_id(
+        &self,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<OrderLookup>> {
+        self.venues[0]
+            .1
+            .get_coin_futures_order_by_client_id(symbol, client_order_id)
+            .await
+    }
+
+    /// Positions of every venue; each held symbol is pinned to its venue, and
+    /// symbols no longer held are free to move.
+    async fn get_positions(&self) -> Result<Vec<Position>> {
+        let mut positions = Vec::new();
+        let mut held = HashMap::new();
+        for (venue, client) in &self.venues {
+            let venue_positions = client.get_positions().await?;
+            for position in &venue_positions {
+                held.insert(position.symbol.clone(), *venue);
+            }
+            positions.extend(venue_positions);
+        }
+
+        let mut state = self.lock_state();
+        state.pinned = held.keys().cloned().collect();
+        state.routes.extend(held);
+        Ok(positions)
+    }
+
+    /// Balances summed per asset across venues.
+    async fn get_account_balance(&self) -> Result<Vec<AccountBalance>> {
+        let mut totals: Vec<AccountBalance> = Vec::new();
+        for (_, client) in &self.venues {
+            for balance in client.get_account_balance().await? {
+                match totals.iter_mut().find(|b| b.asset == balance.asset) {
+                    Some(total) => {
+                        total.wallet_balance += balance.wallet_balance;
+                        total.unrealized_profit += balance.unrealized_profit;
+                        total.margin_balance += balance.margin_balance;
+                        total.available_balance += balance.available_balance;
+                    }
+                    None => totals.push(balance),
+                }
+            }
+        }
+        Ok(totals)
+    }
+
+    async fn get_income_history(
+        &self,
+        income_type: &str,
+        start_time: i64,
+    ) -> Result<Vec<IncomeRecord>> {
+        let mut records = Vec::new();
+        for (_, client) in &self.venues {
+            records.extend(client.get_income_history(income_type, start_time).await?);
+        }
+        records.sort_by_key(|record| record.time);
+        Ok(records)
+    }
+
+    async fn get_futures_trades(&self, symbol: &str, start_time: i64) -> Result<Vec<FuturesTrade>> {
+        self.client_for(symbol)
+            .get_futures_trades(symbol, start_time)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{FundingSchedule, MockBinanceClient};
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_symbols_route_to_the_higher_hourly_funding() {
+        let binance = MockBinanceClient::new(dec!(10000));
+        let okx = MockBinanceClient::new(dec!(5000));
+        binance
+            .seed_market("BTCUSDT", dec!(0.0001), dec!(50000))
+            .await;
+        binance
+            .seed_market("ETHUSDT", dec!(0.0003), dec!(2500))
+            .await;
+        okx.seed_market("BTCUSDT", dec!(0.0003), dec!(50000)).await;
+        okx.seed_market("ETHUSDT", dec!(0.0002), dec!(2500)).await;
+        okx.seed_market("SOLUSDT", dec!(0.0002), dec!(100)).await;
+
+        let router = VenueRouter::new((Venue::Binance, &binance), vec![(Venue::Okx, &okx)]);
+        // ETH settles every 4h on OKX: 0.0002 per 4h beats 0.0003 per 8h
+        router.lock_calendar().set_schedule(
+            Venue::Okx,
+            "ETHUSDT",
+            FundingSchedule::every_hours(4).unwrap(),
+        );
+
+        let rates = router.get_funding_rates().await.unwrap();
+        let rate_of = |symbol: &str| {
+            rates
+                .iter()
+                .find(|r| r.symbol == symbol)
+                .map(|r| r.funding_rate)
+                .unwrap()
+        };
+        assert_eq!(router.venue_of("BTCUSDT"), Venue::Okx);
+        assert_eq!(router.venue_of("ETHUSDT"), Venue::Okx);
+        assert_eq!(router.venue_of("SOLUSDT"), Venue::Okx);
+        assert_eq!(rate_of("BTCUSDT"), dec!(0.0003));
+        // Quoted per 8h, the primary's interval
+        assert_eq!(rate_of("ETHUSDT"), dec!(0.0004));
+
+        // Market data of a symbol comes from its venue only
+        let books = router.get_book_tickers().await.unwrap();
+        assert_eq!(books.iter().filter(|b| b.symbol == "BTCUSDT").count(), 1);
+
+        // Balances add up across venues
+        let balances = router.get_account_balance().await.unwrap();
+        let usdt = balances.iter().find(|b| b.asset == "USDT").unwrap();
+        assert_eq!(usdt.wallet_balance, dec!(15000));
+    }
+
+    #[tokio::test]
+    async fn test_held_symbols_stay_on_their_venue() {
+        let binance = MockBinanceClient::new(dec!(10000));
+        let okx = MockBinanceClient::new(dec!(10000));
+        binance
+            .seed_market("BTCUSDT", dec!(0.0001), dec!(50000))
+            .await;
+        okx.seed_market("BTCUSDT", dec!(0.0001), dec!(50000)).await;
+        binance
+            .seed_position("BTCUSDT", dec!(-0.1), dec!(50000))
+            .await;
+
+        let router = VenueRouter::new((Venue::Binance, &binance), vec![(Venue::Okx, &okx)]);
+        let positions = router.get_positions().await.unwrap();
+        assert_eq!(positions.len(), 1);
+
+        // OKX pays more now, but the open pair is closed where it was opened
+        okx.seed_market("BTCUSDT", dec!(0.0005), dec!(50000)).await;
+        let rates = router.get_funding_rates().await.unwrap();
+        assert_eq!(router.venue_of("BTCUSDT"), Venue::Binance);
+        assert_eq!(rates[0].funding_rate, dec!(0.0001));
+    }
+
+    #[tokio::test]
+    async fn test_batch_orders_split_by_venue() {
+        let binance = MockBinanceClient::new(dec!(10000));
+        let okx = MockBinanceClient::new(dec!(10000));
+        binance
+            .seed_market("XRPUSDT", dec!(0.0002), dec!(0.5))
+            .await;
+        okx.seed_market("BTCUSDT", dec!(0.0003), dec!(50000)).await;
+        okx.seed_market("ETHUSDT", dec!(0.0003), dec!(2500)).await;
+
+        let router = VenueRouter::new((Venue::Binance, &binance), vec![(Venue::Okx, &okx)]);
+        router.get_funding_rates().await.unwrap();
+        let order = |symbol: &str| NewOrder {
+            symbol: symbol.to_string(),
+            side: OrderSide::Sell,
+            position_side: None,
+            order_type: OrderType::Market,
+            quantity: Some(dec!(1)),
+            price: None,
+            time_in_force: None,
+            reduce_only: None,
+            new_client_order_id: None,
+        };
+
+        let placed = router
+            .place_futures_orders(&[order("BTCUSDT"), order("XRPUSDT"), order("ETHUSDT")])
+            .await;
+        let symbols: Vec<_> = placed
+            .iter()
+            .map(|r| r.as_ref().unwrap().symbol.as_str())
+            .collect();
+        assert_eq!(symbols, ["BTCUSDT", "XRPUSDT", "ETHUSDT"]);
+        assert_eq!(binance.get_state().await.order_count, 1);
+        assert_eq!(okx.get_state().await.order_count, 2);
+    }
+}
@@ -1,8 +1,17 @@
 //! Binance WebSocket client for real-time market data and user updates.
+//!
+//! The user data stream pushes order and position changes of the account as they
+//! happen. It is opened with a listen key from the REST API that expires unless
+//! kept alive, and Binance drops every connection after 24 hours, so a consumer
+//! reconnects with a fresh key whenever the stream ends.
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
 use futures_util::StreamExt;
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
@@ -10,6 +19,9 @@ use tracing::{debug, error, info, warn};
 const FUTURES_WS_URL: &str = "wss://fstream.binance.com";
 const FUTURES_TESTNET_WS_URL: &str = "wss://stream.binancefuture.com";
 
+/// Listen keys expire 60 minutes after the last keepalive.
+const LISTEN_KEY_KEEPALIVE_MINUTES: i64 = 30;
+
 /// WebSocket event types.
 #[derive(Debug, Clone)]
 pub enum WsEvent {
@@ -23,6 +35,8 @@ pub enum WsEvent {
     AccountUpdate(AccountUpdateEvent),
     /// Order update
     OrderUpdate(OrderUpdateEvent),
+    /// Listen key of the user data stream expired; the stream has ended
+    ListenKeyExpired,
     /// Connection established
     Connected,
     /// Connection lost
@@ -41,6 +55,8 @@ pub struct FundingRateUpdate {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct BookTickerUpdate {
+    #[serde(rename = "E")]
+    pub event_time: i64,
     #[serde(rename = "s")]
     pub symbol: String,
     #[serde(rename = "b")]
@@ -55,6 +71,8 @@ pub struct BookTickerUpdate {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct MarkPriceUpdate {
+    #[serde(rename = "E")]
+    pub event_time: i64,
     #[serde(rename = "s")]
     pub symbol: String,
     #[serde(rename = "p")]
@@ -99,6 +117,17 @@ pub struct PositionUpdate {
     pub unrealized_profit: String,
 }
 
+impl PositionUpdate {
+    /// Signed position amount; `None` if unparseable.
+    pub fn amount(&self) -> Option<Decimal> {
+        Decimal::from_str(&self.position_amount).ok()
+    }
+
+    pub fn unrealized(&self) -> Option<Decimal> {
+        Decimal::from_str(&self.unrealized_profit).ok()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct OrderUpdateEvent {
     #[serde(rename = "o")]
@@ -109,8 +138,13 @@ pub struct OrderUpdateEvent {
 pub struct OrderUpdate {
     #[serde(rename = "s")]
     pub symbol: String,
+    #[serde(rename = "c")]
+    pub client_order_id: String,
     #[serde(rename = "i")]
     pub order_id: i64,
+    /// Execution type of this update, e.g. "NEW" or "TRADE"
+    #[serde(rename = "x")]
+    pub execution_type: String,
     #[serde(rename = "X")]
     pub status: String,
     #[serde(rename = "S")]
@@ -125,6 +159,146 @@ pub struct OrderUpdate {
     pub avg_price: String,
 }
 
+impl OrderUpdate {
+    /// Whether the order reached a status it never leaves.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "FILLED" | "CANCELED" | "EXPIRED" | "EXPIRED_IN_MATCH" | "REJECTED"
+        )
+    }
+
+    /// Cumulative filled quantity; `None` if unparseable.
+    pub fn filled(&self) -> Option<Decimal> {
+        Decimal::from_str(&self.filled_qty).ok()
+    }
+}
+
+/// Parse a user data stream message.
+///
+/// Returns `None` for event types the bot doesn't consume, such as margin calls
+/// and account configuration updates.
+pub fn parse_user_data(msg: &str) -> Option<WsEvent> {
+    #[derive(Deserialize)]
+    struct EventType {
+        #[serde(rename = "e")]
+        event: String,
+    }
+
+    match serde_json::from_str::<EventType>(msg).ok()?.event.as_str() {
+        "ORDER_TRADE_UPDATE" => serde_json::from_str(msg).ok().map(WsEvent::OrderUpdate),
+        "ACCOUNT_UPDATE" => serde_json::from_str(msg).ok().map(WsEvent::AccountUpdate),
+        "listenKeyExpired" => Some(WsEvent::ListenKeyExpired),
+        _ => None,
+    }
+}
+
+/// A connected user data stream and the futures positions it reported.
+///
+/// The stream only pushes positions that changed, so the book is seeded from a
+/// REST snapshot taken when the stream connects.
+pub struct UserDataStream {
+    listen_key: String,
+    rx: mpsc::Receiver<WsEvent>,
+    connected: bool,
+    kept_alive_at: DateTime<Utc>,
+    /// Non-zero position amounts by symbol
+    positions: HashMap<String, Decimal>,
+}
+
+impl UserDataStream {
+    /// Connect the stream of `listen_key`, seeded with position amounts by symbol.
+    pub async fn connect(
+        ws: &BinanceWebSocket,
+        listen_key: String,
+        positions: HashMap<String, Decimal>,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let (tx, rx) = mpsc::channel(1000);
+        ws.subscribe_user_data(&listen_key, tx).await?;
+        Ok(Self::new(listen_key, rx, positions, now))
+    }
+
+    fn new(
+        listen_key: String,
+        rx: mpsc::Receiver<WsEvent>,
+        positions: HashMap<String, Decimal>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            listen_key,
+            rx,
+            connected: true,
+            kept_alive_at: now,
+            positions: positions
+                .into_iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect(),
+        }
+    }
+
+    pub fn listen_key(&self) -> &str {
+        &self.listen_key
+    }
+
+    /// Whether the stream is still delivering; once ended it must be reconnected.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Position amounts by symbol, current as of the last `drain`.
+    pub fn positions(&self) -> &HashMap<String, Decimal> {
+        &self.positions
+    }
+
+    pub fn keepalive_due(&self, now: DateTime<Utc>) -> bool {
+        now - self.kept_alive_at >= Duration::minutes(LISTEN_KEY_KEEPALIVE_MINUTES)
+    }
+
+    pub fn mark_kept_alive(&mut self, now: DateTime<Utc>) {
+        self.kept_alive_at = now;
+    }
+
+    /// Take the order and account updates received since the last call.
+    ///
+    /// Position changes are applied to the book first. A disconnect or an
+    /// expired listen key ends the stream.
+    pub fn drain(&mut self) -> Vec<WsEvent> {
+        let mut updates = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(WsEvent::Disconnected | WsEvent::ListenKeyExpired) => self.connected = false,
+                Ok(WsEvent::AccountUpdate(update)) => {
+                    for position in &update.data.positions {
+                        match position.amount() {
+                            Some(amount) if amount.is_zero() => {
+                                self.positions.remove(&position.symbol);
+                            }
+                            Some(amount) => {
+                                self.positions.insert(position.symbol.clone(), amount);
+                            }
+                            None => warn!(
+                                symbol = %position.symbol,
+                                amount = %position.position_amount,
+                                "Unparseable position amount on user data stream"
+                            ),
+                        }
+                    }
+                    updates.push(WsEvent::AccountUpdate(update));
+                }
+                Ok(event @ WsEvent::OrderUpdate(_)) => updates.push(event),
+                Ok(_) => {}
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+        updates
+    }
+}
+
 /// Binance WebSocket client.
 pub struct BinanceWebSocket {
     base_url: String,
@@ -185,6 +359,17 @@ impl BinanceWebSocket {
         .await
     }
 
+    /// Subscribe to the futures user data stream of `listen_key`.
+    pub async fn subscribe_user_data(
+        &self,
+        listen_key: &str,
+        tx: mpsc::Sender<WsEvent>,
+    ) -> Result<()> {
+        let url = format!("{}/ws/{}", self.base_url, listen_key);
+        self.connect_and_handle(url, tx, |msg| parse_user_data(&msg).into_iter().collect())
+            .await
+    }
+
     /// Generic WebSocket connection handler.
     async fn connect_and_handle<F>(
         &self,
@@ -240,3 +425,79 @@ impl BinanceWebSocket {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const ORDER_TRADE_UPDATE: &str = r#"{"e":"ORDER_TRADE_UPDATE","E":1760000000000,"T":1760000000000,
+        "o":{"s":"BTCUSDT","c":"fffa1b2c3-1-4","S":"SELL","o":"MARKET","f":"GTC","q":"0.010",
+        "p":"0","ap":"62000.5","sp":"0","x":"TRADE","X":"FILLED","i":8886774,"l":"0.010",
+        "z":"0.010","L":"62000.5","N":"USDT","n":"0.248","T":1760000000000,"t":1,"R":true}}"#;
+
+    const ACCOUNT_UPDATE: &str = r#"{"e":"ACCOUNT_UPDATE","E":1760000000000,"T":1760000000000,
+        "a":{"m":"ORDER","B":[{"a":"USDT","wb":"10000.5","cw":"10000.5","bc":"0"}],
+        "P":[{"s":"BTCUSDT","pa":"0","ep":"0","cr":"0","up":"0","mt":"cross","iw":"0","ps":"BOTH"},
+             {"s":"ETHUSDT","pa":"-1.5","ep":"3000","cr":"0","up":"-12.5","mt":"cross","iw":"0","ps":"BOTH"}]}}"#;
+
+    #[test]
+    fn test_parse_user_data() {
+        let Some(WsEvent::OrderUpdate(update)) = parse_user_data(ORDER_TRADE_UPDATE) else {
+            panic!("expected an order update");
+        };
+        assert_eq!(update.order.client_order_id, "fffa1b2c3-1-4");
+        assert_eq!(update.order.execution_type, "TRADE");
+        assert!(update.order.is_final());
+        assert_eq!(update.order.filled(), Some(dec!(0.010)));
+
+        let Some(WsEvent::AccountUpdate(update)) = parse_user_data(ACCOUNT_UPDATE) else {
+            panic!("expected an account update");
+        };
+        assert_eq!(update.data.positions.len(), 2);
+        assert_eq!(update.data.positions[1].amount(), Some(dec!(-1.5)));
+        assert_eq!(update.data.positions[1].unrealized(), Some(dec!(-12.5)));
+
+        assert!(matches!(
+            parse_user_data(r#"{"e":"listenKeyExpired","E":1760000000000}"#),
+            Some(WsEvent::ListenKeyExpired)
+        ));
+        assert!(parse_user_data(r#"{"e":"MARGIN_CALL","E":1760000000000}"#).is_none());
+        assert!(parse_user_data("not json").is_none());
+    }
+
+    #[test]
+    fn test_stream_tracks_positions() {
+        let now = Utc::now();
+        let (tx, rx) = mpsc::channel(10);
+        let seed: HashMap<String, Decimal> = [
+            ("BTCUSDT".to_string(), dec!(-0.01)),
+            ("SOLUSDT".to_string(), dec!(0)),
+        ]
+        .into_iter()
+        .collect();
+        let mut stream = UserDataStream::new("key".to_string(), rx, seed, now);
+        assert_eq!(stream.positions().len(), 1);
+
+        tx.try_send(parse_user_data(ORDER_TRADE_UPDATE).unwrap())
+            .unwrap();
+        tx.try_send(parse_user_data(ACCOUNT_UPDATE).unwrap())
+            .unwrap();
+        let updates = stream.drain();
+        assert_eq!(updates.len(), 2);
+
+        // BTC was closed and ETH opened
+        assert_eq!(stream.positions().len(), 1);
+        assert_eq!(stream.positions().get("ETHUSDT"), Some(&dec!(-1.5)));
+        assert!(stream.is_connected());
+
+        assert!(!stream.keepalive_due(now + Duration::minutes(29)));
+        assert!(stream.keepalive_due(now + Duration::minutes(30)));
+        stream.mark_kept_alive(now + Duration::minutes(30));
+        assert!(!stream.keepalive_due(now + Duration::minutes(31)));
+
+        tx.try_send(WsEvent::ListenKeyExpired).unwrap();
+        assert!(stream.drain().is_empty());
+        assert!(!stream.is_connected());
+    }
+}
@@ -10,6 +10,7 @@
 //! - `strategy`: Trading logic, opportunity scanning, and execution
 //! - `risk`: Position monitoring, margin management, and MDD tracking
 //! - `persistence`: SQLite-based state persistence for mock trading
+//! - `report`: Read-only HTML status report served from the persistence database
 //! - `backtest`: Historical backtesting and parameter optimization
 //! - `utils`: Shared utilities and decimal arithmetic
 
@@ -17,6 +18,7 @@ pub mod backtest;
 pub mod config;
 pub mod exchange;
 pub mod persistence;
+pub mod report;
 pub mod risk;
 pub mod strategy;
 pub mod utils;
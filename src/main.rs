@@ -2,28 +2,64 @@
 //!
 //! MVP version with mock trading support for paper trading and testing.
 
-use anyhow::Result;
-use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
 use funding_fee_farmer::backtest::{
-    BacktestConfig, BacktestEngine, CsvDataLoader, DataLoader, ParameterSpace, SweepRunner,
+    BacktestConfig, BacktestEngine, BorrowScenario, CrowdingModel, CsvDataLoader, DataLoader,
+    ParameterSpace, SweepRunner,
+};
+use funding_fee_farmer::config::{
+    AlertAction, ApiBudgetConfig, Config, ConfigFormat, ExecutionConfig, ExistingHoldingsConfig,
+    HedgeConfig, MaintenanceWindow, PairSelectionConfig, Profile, SettingsMismatchAction,
+};
+use funding_fee_farmer::exchange::{
+    missed_funding_settlements, settings_mismatches, AccountBalance, ApiUsage, BinanceClient,
+    BinanceWebSocket, BookTicker, BybitClient, CapitalTransfer, CoinContract, DailyUsage,
+    ExchangeClient, FeeSchedule, FundingCalendar, FundingCap, FundingRate, HedgeInstrument,
+    HyperliquidClient, HyperliquidSigner, LeverageBracket, MarginType, MarkPriceCache,
+    MarketScenario, MockBinanceClient, OkxClient, OrderLookup, OrderResponse, OrderSide,
+    OrderValve, PriceFeed, QualifiedPair, ServerClock, SubAccountManager, TransferDirection,
+    UsageProjection, UserDataStream, ValveTrip, Venue, VenueRouter, WeightLimiter, WsEvent,
+};
+use funding_fee_farmer::persistence::{
+    DirtyTracker, EquityEventKind, InstanceLock, LockAcquisition, PersistedEquityAnnotation,
+    PersistedPosition, PersistedPositionExit, PersistedQuarantine, PersistedRun, PersistedState,
+    PersistenceManager, RecordStamp,
 };
-use funding_fee_farmer::config::Config;
-use funding_fee_farmer::exchange::{BinanceClient, MockBinanceClient};
-use funding_fee_farmer::persistence::PersistenceManager;
 use funding_fee_farmer::risk::{
-    LiquidationAction, MarginHealth, MarginMonitor, PositionAction, PositionEntry, RiskAlertType,
-    RiskOrchestrator, RiskOrchestratorConfig,
+    position_opened_at, AlertSeverity, BorrowLimit, BorrowLimits, BorrowShortfall, CaptureLedger,
+    ConfirmedSettlement, EntryRestriction, EventCalendar, ExitKind, ExposureReport,
+    FundingCaptureTracker, FundingSettlements, LiquidationAction, MaintenanceSchedule,
+    MaintenanceState, MarginHealth, MarginMonitor, OutageMonitor, OutageRecovery,
+    PendingSettlement, PersistenceWatchdog, PositionAction, PositionEntry, RiskAlert,
+    RiskAlertType, RiskOrchestrator, RiskOrchestratorConfig, SymbolExposure,
 };
 use funding_fee_farmer::strategy::{
-    CapitalAllocator, HedgeRebalancer, MarginContext, MarketScanner, OrderExecutor, RebalanceConfig,
+    coin_margined_entries, coin_margined_exits, entry_notional, fetch_treasury_bill_rate,
+    foreign_instance_orders, funding_spreads, leg_fill, spread_exits, AttributionReport,
+    BorrowCostReport, BorrowRecommendation, CapacityEstimate, CapacityInput, CapacityTerms,
+    CapitalAllocator, ClientOrderTag, CoinMarginedPosition, CrossVenueMonitor, EntryCostModel,
+    EntryIntentRegistry, EntryTimer, EntryTimingSummary, ExecutionKind, ExecutionQualityReport,
+    ExecutionShortfall, FlashEnd, FlashReserve, FlashReserveEvent, FundingSpread, FundsShortfall,
+    HedgeRebalancer, HedgeSwitchMonitor, HeldFunding, JournalReconciliation, MarginContext,
+    MarketScanner, OrderExecutor, OrderLeg, PairCapacity, PortfolioApr, PortfolioRebalance,
+    PortfolioRebalancer, PositionAllocation, PositionReduction, RebalanceConfig,
+    ReductionDirective, ReductionPlan, ReductionUrgency, RiskActionExecutor, RiskFreeRate,
+    RoundTripFees, ScanCache, SegregatedFunds, SpreadPosition, SymbolQuality, TakeProfitMonitor,
+    TimedEntry, TimingDecision, UtilizationController, COST_MODEL_WEEKS, TAKER_FEE_RATE,
 };
+use funding_fee_farmer::utils::{humanize_duration, DisplayTimezone};
+use futures_util::stream::{self, StreamExt};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Notify;
 use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::EnvFilter;
@@ -33,6 +69,36 @@ use tracing_subscriber::EnvFilter;
 #[command(name = "funding-fee-farmer")]
 #[command(version, about = "Delta-neutral funding fee farming on Binance")]
 struct Cli {
+    /// Config profile (dev, paper, live); overrides FFF_PROFILE
+    #[arg(long, global = true)]
+    profile: Option<Profile>,
+
+    /// Take over trading from another instance holding the instance lock
+    #[arg(long)]
+    takeover: bool,
+
+    /// Play back a scripted market scenario (TOML or JSON) in mock mode, on a
+    /// fresh paper account in <data-dir>/scenario_state.db
+    #[arg(long)]
+    scenario: Option<String>,
+
+    /// Trade on the venues' test environments (Binance and Bybit testnets, OKX
+    /// demo trading) regardless of the config
+    #[arg(long, global = true)]
+    testnet: bool,
+
+    /// Directory holding config.toml, the profile overlays and .env
+    #[arg(long, global = true, default_value = ".")]
+    config_dir: PathBuf,
+
+    /// Directory of the state database
+    #[arg(long, global = true, default_value = "data")]
+    data_dir: PathBuf,
+
+    /// Directory of the hourly log files
+    #[arg(long, global = true, default_value = "logs")]
+    log_dir: PathBuf,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -60,6 +126,35 @@ enum Commands {
         /// Output directory for results
         #[arg(short, long)]
         output: Option<String>,
+
+        /// 8h-equivalent funding rate from which held periods count as high
+        /// funding in the regime attribution
+        #[arg(long, default_value = "0.001")]
+        high_funding_rate: Decimal,
+
+        /// Futures maker fee to simulate, overriding execution.maker_fee_rate
+        /// (negative for a rebate, e.g. -0.00005)
+        #[arg(long, allow_negative_numbers = true)]
+        maker_fee: Option<Decimal>,
+
+        /// Enter the futures leg with post-only limit orders at the maker rate
+        #[arg(long)]
+        maker_entries: bool,
+
+        /// TOML file with borrow rates to charge: base rate, weekend multiplier
+        /// and historical or synthetic spike windows
+        #[arg(long)]
+        borrow_scenario: Option<String>,
+
+        /// Dampen funding by the share of each symbol's open interest held, with
+        /// this sensitivity (10 halves the rate at 10% held)
+        #[arg(long)]
+        crowding: Option<Decimal>,
+
+        /// Read snapshots from the file as the run reaches them instead of
+        /// loading it whole (rows sorted by timestamp; indexed to <data>.idx)
+        #[arg(long)]
+        stream: bool,
     },
 
     /// Run a parameter sweep optimization
@@ -91,17 +186,137 @@ enum Commands {
         /// Use minimal parameter space (faster, for testing)
         #[arg(long)]
         minimal: bool,
+
+        /// Read snapshots from the file as the run reaches them instead of
+        /// loading it whole (rows sorted by timestamp; indexed to <data>.idx)
+        #[arg(long)]
+        stream: bool,
     },
 
     /// Show current mock farmer status from persisted state
     Status {
-        /// Path to SQLite database (default: data/mock_state.db)
-        #[arg(short, long, default_value = "data/mock_state.db")]
-        db: String,
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
 
         /// Show detailed position information
         #[arg(short, long)]
         verbose: bool,
+
+        /// Compare against the equity snapshot closest to this long ago (e.g. 24h, 7d)
+        #[arg(long, value_name = "WINDOW", value_parser = parse_diff_window)]
+        diff: Option<chrono::Duration>,
+    },
+
+    /// Serve a read-only HTML status report from persisted state
+    ServeReport {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+    },
+
+    /// Attribute exchange orders to positions and reconcile them with the order journal
+    ReconcileOrders {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Hours of history to reconcile (max 168)
+        #[arg(long, default_value = "24")]
+        hours: i64,
+    },
+
+    /// Report implementation shortfall of entries and exits by week
+    ExecutionQuality {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Weeks of history to report
+        #[arg(long, default_value = "8")]
+        weeks: i64,
+    },
+
+    /// Report borrow interest against funding income per asset, with cap/exclude advice
+    BorrowCosts {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Weeks of history to report
+        #[arg(long, default_value = "8")]
+        weeks: i64,
+    },
+
+    /// List recorded process runs with their KPIs
+    Runs {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Number of most recent runs to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// List the configurations the bot has run with, newest first
+    ConfigHistory {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Number of most recent changes to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+
+        /// Print the full configuration recorded under this hash
+        #[arg(long)]
+        show: Option<String>,
+    },
+
+    /// List symbols quarantined for persistent funding anomalies, or release one
+    Quarantine {
+        /// Path to SQLite database (default: <data-dir>/mock_state.db)
+        #[arg(short, long)]
+        db: Option<String>,
+
+        /// Release this symbol; a running bot picks it up next cycle
+        #[arg(long)]
+        release: Option<String>,
+    },
+
+    /// Estimate the capital the best candidate pairs absorb before slippage
+    /// erodes their funding edge, from live Binance books and open interest
+    Capacity {
+        /// Candidate pairs to sample, best scored first
+        #[arg(long, default_value = "10")]
+        pairs: usize,
+
+        /// Account equity in USDT to compare the capacity with
+        #[arg(long)]
+        equity: Option<Decimal>,
+    },
+
+    /// Validate the merged configuration and print every violation
+    ValidateConfig,
+
+    /// Inspect the configuration the bot would run with
+    Config {
+        /// Print the fully merged configuration (defaults + files + env)
+        #[arg(long)]
+        print_effective: bool,
+
+        /// Output format (toml or json)
+        #[arg(long, default_value = "toml")]
+        format: ConfigFormat,
     },
 }
 
@@ -117,12 +332,18 @@ enum TradingMode {
 struct AppMetrics {
     start_time: DateTime<Utc>,
     scan_count: u64,
+    /// Cycles run on a cached scan after the live scan failed
+    stale_scan_cycles: u64,
+    /// Cycles run inside an exchange maintenance window
+    maintenance_cycles: u64,
     opportunities_found: u64,
     positions_entered: u64,
     positions_exited: u64,
     rebalances_triggered: u64,
     funding_collections: u64,
     errors_count: u64,
+    /// Residual delta of the book as of the last risk check
+    residual_delta: ExposureReport,
 }
 
 impl Default for AppMetrics {
@@ -130,61 +351,31 @@ impl Default for AppMetrics {
         Self {
             start_time: Utc::now(),
             scan_count: 0,
+            stale_scan_cycles: 0,
+            maintenance_cycles: 0,
             opportunities_found: 0,
             positions_entered: 0,
             positions_exited: 0,
             rebalances_triggered: 0,
             funding_collections: 0,
             errors_count: 0,
+            residual_delta: ExposureReport::default(),
         }
     }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
     // Initialize comprehensive logging
-    init_logging()?;
+    init_logging(&cli.log_dir)?;
 
     // Handle subcommands
-    match cli.command {
-        Some(Commands::Backtest {
-            data,
-            start,
-            end,
-            initial_balance,
-            output,
-        }) => {
-            return run_backtest(&data, &start, &end, initial_balance, output.as_deref()).await;
-        }
-        Some(Commands::Sweep {
-            data,
-            start,
-            end,
-            initial_balance,
-            parallelism,
-            output,
-            minimal,
-        }) => {
-            return run_sweep(
-                &data,
-                &start,
-                &end,
-                initial_balance,
-                parallelism,
-                output.as_deref(),
-                minimal,
-            )
-            .await;
-        }
-        Some(Commands::Status { db, verbose }) => {
-            return show_status(&db, verbose);
-        }
-        None => {
-            // Default: run trading mode
-        }
+    if let Some(command) = cli.command.take() {
+        run_command(command, &cli).await?;
+        return Ok(ExitCode::SUCCESS);
     }
 
     info!("╔════════════════════════════════════════════════════════════╗");
@@ -203,29 +394,205 @@ async fn main() -> Result<()> {
         TradingMode::Mock
     };
 
-    // Load configuration
-    let config = Config::load()?;
+    // Load configuration - live trading defaults to the live profile and refuses any other
+    let fallback_profile = match trading_mode {
+        TradingMode::Live => Profile::Live,
+        TradingMode::Mock => Profile::Paper,
+    };
+    let mut config = Config::load_with_profile(&cli.config_dir, cli.profile, fallback_profile)?;
+    if cli.testnet {
+        config.use_testnets();
+    }
+    if trading_mode == TradingMode::Live && config.profile != Profile::Live {
+        anyhow::bail!(
+            "LIVE_TRADING=true requires the live profile (got '{}')",
+            config.profile
+        );
+    }
     log_config(&config);
+    let display_tz = config.display.display_timezone();
+
+    // Scripted market scenario, replayed by the mock client from startup
+    let scenario = match cli.scenario.as_deref() {
+        Some(_) if trading_mode == TradingMode::Live => {
+            anyhow::bail!("--scenario is only available in mock trading mode")
+        }
+        Some(path) => {
+            let scenario = MarketScenario::load(std::path::Path::new(path))?;
+            info!(
+                "🎬 [SCENARIO] Playing {} with {} scripted symbol(s) and {} event(s)",
+                path,
+                scenario.symbols.len(),
+                scenario.events.len()
+            );
+            Some(scenario)
+        }
+        None => None,
+    };
 
     // Initialize components
-    let scanner = MarketScanner::new(config.pair_selection.clone());
-    let allocator = CapitalAllocator::new(
+    let mut scanner = MarketScanner::new(config.pair_selection.clone());
+    let mut scan_cache = ScanCache::new(chrono::Duration::minutes(
+        config.pair_selection.scan_cache_max_age_minutes as i64,
+    ));
+    let mut allocator = CapitalAllocator::new(
         config.capital.clone(),
         config.risk.clone(),
         config.execution.default_leverage,
     );
+    let mut utilization = UtilizationController::new(
+        config.capital.dynamic_utilization.clone(),
+        config.capital.max_utilization,
+        config.execution.max_leverage,
+    );
     let mut executor = OrderExecutor::new(config.execution.clone());
-    let rebalancer = HedgeRebalancer::new(RebalanceConfig::default());
+    let mut entry_intents = EntryIntentRegistry::new();
+    let mut entry_timer = EntryTimer::new(config.execution.entry_timing.clone());
+    let mut funding_capture = FundingCaptureTracker::new();
+    let mut flash_reserve = FlashReserve::new(
+        config.capital.flash_reserve.clone(),
+        config.capital.reserve_buffer,
+    );
+    executor.set_notional_caps(config.capital.notional_caps.clone());
+    let mut rebalancer = HedgeRebalancer::new(RebalanceConfig {
+        hedge: config.hedge.clone(),
+        action_cost: config.execution.action_cost.clone(),
+        ..Default::default()
+    });
+
+    // Macro event calendar gating new entries around scheduled releases
+    let mut event_calendar = EventCalendar::load(&config.events)
+        .await
+        .context("Failed to load macro event calendar")?;
+    let mut event_calendar_loaded_at = Utc::now();
+    if config.events.enabled {
+        info!("🗓️  [EVENTS] Loaded {} macro events", event_calendar.len());
+    }
+
+    // Risk-free rate the portfolio APR is compared against
+    let mut risk_free = RiskFreeRate::load(&config.risk_free).await;
+    let mut risk_free_loaded_at = Utc::now();
+    info!("🏛️  [RISK-FREE] Comparing returns against {}", risk_free);
+
+    // Exchange maintenance: announced windows plus the polled system status
+    let mut maintenance = MaintenanceSchedule::new(&config.maintenance);
+    let mut maintenance_checked_at: Option<DateTime<Utc>> = None;
+    if let Some(window) = maintenance.next_window(Utc::now()) {
+        info!(
+            "🛠️  [MAINTENANCE] Next announced window {} - {}",
+            display_tz.short(window.start),
+            display_tz.short(window.end)
+        );
+    }
+
+    // Cross-venue funding spreads: reported, and traded live when `execute` is set
+    let trade_spreads = config.cross_venue.execute && trading_mode == TradingMode::Live;
+    let mut hyperliquid_client = if config.cross_venue.enabled {
+        let client = HyperliquidClient::new(&config.cross_venue.hyperliquid_url)?;
+        if trade_spreads {
+            let private_key = std::env::var("HYPERLIQUID_PRIVATE_KEY")
+                .context("cross_venue.execute requires HYPERLIQUID_PRIVATE_KEY")?;
+            let mut signer = HyperliquidSigner::from_private_key(&private_key)?;
+            if let Ok(account) = std::env::var("HYPERLIQUID_ACCOUNT_ADDRESS") {
+                signer = signer.with_account(&account);
+            }
+            info!(
+                "🔀 [VENUE] Trading cross-venue spreads with Hyperliquid account {}",
+                signer.account()
+            );
+            Some(client.with_signer(signer))
+        } else {
+            Some(client)
+        }
+    } else {
+        None
+    };
+    // Held spreads are the account's Hyperliquid positions, each hedged on Binance
+    let mut spread_positions: Vec<SpreadPosition> = Vec::new();
+    if let Some(hyperliquid) = hyperliquid_client.as_ref().filter(|c| c.can_trade()) {
+        spread_positions = hyperliquid
+            .get_positions()
+            .await
+            .context("Failed to load Hyperliquid positions")?
+            .iter()
+            .map(|p| SpreadPosition::from_hyperliquid(&p.coin, p.size, p.position_value))
+            .collect();
+        for position in &spread_positions {
+            info!(
+                "🔀 [VENUE] Holding {} spread: {} short on {}",
+                position.symbol, position.quantity, position.short_venue
+            );
+        }
+    }
+    let mut cross_venue_monitor = CrossVenueMonitor::new();
+    let mut cross_venue_checked_at: Option<DateTime<Utc>> = None;
+
+    // Held hedges are reviewed against cheaper alternatives (advisory: spot hedges only)
+    let mut hedge_switches = HedgeSwitchMonitor::new(config.pair_selection.hedge_switch_threshold);
+    let mut hedges_reviewed_at: Option<DateTime<Utc>> = None;
+
+    // Live: leverage and margin type of held symbols, checked at startup and periodically
+    let mut settings_checked_at: Option<DateTime<Utc>> = None;
+
+    // Latest predicted funding and borrow rates of held symbols, for the portfolio APR
+    let mut predicted_rates: HashMap<String, Decimal> = HashMap::new();
+    let mut borrow_rates: HashMap<String, Decimal> = HashMap::new();
+
+    // Mock borrow interest accrues every loop; it is recorded hourly per symbol
+    let mut pending_interest: HashMap<String, (Decimal, Decimal)> = HashMap::new();
+    let mut interest_recorded_at = Utc::now();
 
     // Initialize clients
     // For MVP mock trading, we create a real client only if credentials are available
     let binance_config = funding_fee_farmer::config::BinanceConfig {
         api_key: std::env::var("BINANCE_API_KEY").unwrap_or_default(),
         secret_key: std::env::var("BINANCE_SECRET_KEY").unwrap_or_default(),
-        testnet: false,
+        read_api_key: std::env::var("BINANCE_READ_API_KEY").unwrap_or_default(),
+        read_secret_key: std::env::var("BINANCE_READ_SECRET_KEY").unwrap_or_default(),
+        ..config.binance.clone()
+    };
+    if binance_config.testnet {
+        info!("🧪 [TESTNET] Binance requests go to the testnets: spot legs are plain spot orders, nothing is borrowable");
+    }
+
+    // Sub-account isolation (live only): master keys move funds, sub-account keys trade
+    let sub_account = &binance_config.sub_account;
+    let isolate_capital = sub_account.enabled && trading_mode == TradingMode::Live;
+    if isolate_capital && binance_config.testnet {
+        anyhow::bail!("binance.sub_account is not available on the Binance testnet; disable it");
+    }
+    if sub_account.enabled && !isolate_capital {
+        info!("🏦 [SUB-ACCOUNT] Capital isolation configured but skipped in mock mode");
+    }
+    // Both clients share one IP and account, so their requests count into one budget
+    // and draw from one weight throttle
+    let api_usage = ApiUsage::new();
+    let rate_limiter = WeightLimiter::new(&config.api_budget);
+    let mut master_client = BinanceClient::new(&binance_config)?;
+    master_client.set_api_usage(api_usage.clone());
+    master_client.set_rate_limiter(rate_limiter.clone());
+    let trading_config = if isolate_capital {
+        funding_fee_farmer::config::BinanceConfig {
+            api_key: sub_account.api_key.clone(),
+            secret_key: sub_account.secret_key.clone(),
+            read_api_key: sub_account.read_api_key.clone(),
+            read_secret_key: sub_account.read_secret_key.clone(),
+            ..binance_config.clone()
+        }
+    } else {
+        binance_config.clone()
     };
+    if !trading_config.read_api_key.is_empty() {
+        info!("🔑 [KEYS] Account reads use the read-only key; orders use the trading key");
+    }
+
+    // One valve guards both clients: a runaway order loop trips it and halts trading
+    let order_valve = OrderValve::new(
+        config.execution.max_orders_per_minute,
+        config.execution.max_notional_per_hour,
+    );
 
-    let real_client = match BinanceClient::new(&binance_config) {
+    let mut real_client = match BinanceClient::new(&trading_config) {
         Ok(client) => {
             if binance_config.api_key.is_empty() {
                 info!("⚠️  No API keys provided. Running in Read-Only/Mock mode.");
@@ -238,34 +605,309 @@ async fn main() -> Result<()> {
         }
     };
 
-    let mock_client = MockBinanceClient::new(dec!(10000)); // $10k paper trading default
+    real_client.set_order_valve(order_valve.clone());
+    real_client.set_api_usage(api_usage.clone());
+    real_client.set_rate_limiter(rate_limiter);
+
+    // Signed requests are timestamped on the exchange's clock, so a drifting system
+    // clock doesn't get them rejected
+    let server_clock = ServerClock::new();
+    master_client.set_server_clock(server_clock.clone());
+    real_client.set_server_clock(server_clock.clone());
+    sync_server_clock(&real_client).await;
+    if let Some(client) = &mut hyperliquid_client {
+        client.set_order_valve(order_valve.clone());
+    }
+
+    // Strategy market data and orders go to the configured venue; Binance-only
+    // features (sub-accounts, system status, the margin account) stay on Binance
+    let venue = config.venue;
+    let bybit_client = if venue == Venue::Bybit {
+        let bybit_config = funding_fee_farmer::config::BybitConfig {
+            api_key: std::env::var("BYBIT_API_KEY").unwrap_or_default(),
+            secret_key: std::env::var("BYBIT_SECRET_KEY").unwrap_or_default(),
+            ..config.bybit.clone()
+        };
+        let mut client = BybitClient::new(&bybit_config)?;
+        client.set_order_valve(order_valve.clone());
+        Some(client)
+    } else {
+        None
+    };
+    let okx_client = if venue == Venue::Okx || config.okx.route_entries {
+        let okx_config = funding_fee_farmer::config::OkxConfig {
+            api_key: std::env::var("OKX_API_KEY").unwrap_or_default(),
+            secret_key: std::env::var("OKX_SECRET_KEY").unwrap_or_default(),
+            passphrase: std::env::var("OKX_PASSPHRASE").unwrap_or_default(),
+            ..config.okx.clone()
+        };
+        let mut client = OkxClient::new(&okx_config)?;
+        client.set_order_valve(order_valve.clone());
+        Some(client)
+    } else {
+        None
+    };
+    // Routed entries go to whichever of Binance and OKX pays the symbol more funding
+    let venue_router = match &okx_client {
+        Some(okx) if config.okx.route_entries => Some(VenueRouter::new(
+            (Venue::Binance, &real_client),
+            vec![(Venue::Okx, okx as &dyn ExchangeClient)],
+        )),
+        _ => None,
+    };
+    let venue_client: &dyn ExchangeClient = match (&venue_router, &bybit_client, &okx_client) {
+        (Some(router), _, _) => router,
+        (None, Some(client), _) => client,
+        (None, None, Some(client)) => client,
+        (None, None, None) => &real_client,
+    };
+    // Halts and shutdown cancel resting orders on each venue separately
+    let trading_venues: Vec<(Venue, &dyn ExchangeClient)> = match &venue_router {
+        Some(router) => router.venues().to_vec(),
+        None => vec![(venue, venue_client)],
+    };
+    if venue_router.is_some() {
+        info!("🌐 [VENUE] Trading on Binance and OKX, each symbol where funding pays more");
+    } else {
+        info!("🌐 [VENUE] Trading on {}", venue);
+    }
+
+    // Binance mark prices and funding rates stream in every second, and so do the
+    // books of held symbols; REST snapshots stand in while the streams are down
+    let binance_ws = BinanceWebSocket::new(real_client.is_testnet());
+    let use_mark_stream = venue == Venue::Binance && venue_router.is_none();
+    let mark_cache = MarkPriceCache::new(chrono::Duration::seconds(MARK_PRICE_MAX_AGE_SECS));
+    let price_feed = PriceFeed::new(chrono::Duration::seconds(BOOK_TICKER_MAX_AGE_SECS));
+    if use_mark_stream {
+        connect_mark_price_stream(&mark_cache, &binance_ws).await;
+    }
+
+    let mut mock_client = match &scenario {
+        Some(scenario) => MockBinanceClient::from_scenario(scenario, Utc::now()),
+        None => MockBinanceClient::new(dec!(10000)), // $10k paper trading default
+    };
+    mock_client.set_order_valve(order_valve.clone());
+    mock_client.set_fee_schedule(config.execution.fee_schedule());
 
     // Initialize SQLite persistence for mock state
-    let persistence = PersistenceManager::new("data/mock_state.db")
-        .expect("Failed to initialize persistence database");
+    // Every record is stamped with the configuration and build it was written under
+    let stamp = record_stamp(&config);
+    std::fs::create_dir_all(&cli.data_dir)
+        .with_context(|| format!("Failed to create data directory {}", cli.data_dir.display()))?;
+    // Scenarios start from a fresh account of their own so every run is reproducible
+    let state_db = cli.data_dir.join(if scenario.is_some() {
+        SCENARIO_STATE_DB_FILE
+    } else {
+        STATE_DB_FILE
+    });
+    let persistence = {
+        let mut persistence =
+            PersistenceManager::new(&state_db).expect("Failed to initialize persistence database");
+        persistence.set_stamp(stamp.clone());
+        if scenario.is_some() {
+            persistence
+                .clear_all()
+                .context("Failed to reset the scenario database")?;
+        }
+        persistence
+    };
+    // Failing state saves fall back to other databases, escalate and eventually stop entries
+    let mut persistence_watchdog =
+        PersistenceWatchdog::new(config.risk.persistence_watchdog.clone());
+    // Exchange borrow limits of short spot hedge assets, refreshed on live Binance
+    let mut borrow_limits = BorrowLimits::new(config.risk.borrow_limits.clone());
+    let new_config_era = record_config_change(&persistence, &config, &stamp);
+    check_api_budget(&config, new_config_era);
+
+    // Entry costs include the implementation shortfall observed over recent weeks
+    let mut entry_cost_model = load_entry_cost_model(&persistence, config.execution.fee_schedule());
+
+    // Only one instance may trade: take the instance lock before restoring or trading anything
+    let instance = InstanceLock::for_current_process(Utc::now());
+    // Other instances' orders can only be looked up on Binance
+    let lock_client =
+        (trading_mode == TradingMode::Live && venue == Venue::Binance).then_some(&real_client);
+    take_instance_lock(
+        &persistence,
+        lock_client,
+        &instance,
+        &config.execution.client_order_tag,
+        cli.takeover,
+    )
+    .await?;
+    executor.set_instance_id(&instance.instance_id);
+    // Cancels entry legs left resting past execution.order_timeout_secs
+    let mut order_tracker = executor.order_tracker();
 
     // Try to restore previous state
     // Clone positions before restore_state consumes the persisted_state
     // These will be registered with the risk orchestrator's position tracker
-    let (initial_balance, restored_positions, restored_funding_period) =
-        if let Ok(Some(persisted_state)) = persistence.load_state() {
-            info!("📂 [PERSISTENCE] Restoring state from database");
-            info!(
-                "   Balance: ${:.2}, Positions: {}, Total Funding: ${:.4}, Last Funding Period: {:?}",
-                persisted_state.balance,
-                persisted_state.positions.len(),
-                persisted_state.total_funding_received,
-                persisted_state.last_funding_period
+    let (initial_balance, restored_positions, mut restored_funding_period, restored_last_saved) =
+        match persistence
+            .load_state()
+            .context("Persisted state failed verification - refusing to start over it")?
+        {
+            Some(persisted_state) => {
+                info!("📂 [PERSISTENCE] Restoring state from database");
+                info!(
+                    "   Balance: ${:.2}, Positions: {}, Total Funding: ${:.4}, Last Funding Period: {:?}",
+                    persisted_state.balance,
+                    persisted_state.positions.len(),
+                    persisted_state.total_funding_received,
+                    persisted_state.last_funding_period
+                );
+                let balance = persisted_state.balance;
+                let positions = persisted_state.positions.clone();
+                let funding_period = persisted_state.last_funding_period;
+                let last_saved = persisted_state.last_saved;
+                mock_client.restore_state(persisted_state).await;
+                (balance, positions, funding_period, Some(last_saved))
+            }
+            None => {
+                let balance = mock_client.get_state().await.balance;
+                info!(
+                    "📂 [PERSISTENCE] No previous state found, starting fresh with ${:.2}",
+                    balance
+                );
+                (balance, HashMap::new(), None, None)
+            }
+        };
+
+    // Move strategy capital into the sub-account before anything trades
+    if isolate_capital {
+        let manager = SubAccountManager::new(sub_account, &master_client, &real_client);
+        match manager.fund().await {
+            Ok(Some(transfer)) => record_capital_transfer(&persistence, &transfer),
+            Ok(None) => info!(
+                "🏦 [SUB-ACCOUNT] {} already holds {} {}",
+                sub_account.email, sub_account.capital, sub_account.asset
+            ),
+            Err(e) => warn!(
+                "⚠️  [SUB-ACCOUNT] Failed to fund sub-account, trading with its current balance: {}",
+                e
+            ),
+        }
+    }
+
+    // Existing-holdings hedge: declared coins form the long leg of positive-funding entries,
+    // live COIN-margined farming posts them as collateral of COIN-margined shorts instead
+    let mut trade_coin_margined = config.capital.existing_holdings.enabled
+        && config.capital.existing_holdings.coin_margined
+        && trading_mode == TradingMode::Live;
+    let mut coin_holdings = ExistingHoldingsConfig::default();
+    if config.capital.existing_holdings.coin_margined && trading_mode == TradingMode::Mock {
+        info!("🪙 [HOLDINGS] COIN-margined farming is live-only; paper trading hedges holdings with USDT-margined futures");
+    }
+    if config.capital.existing_holdings.enabled {
+        let mut holdings = config.capital.existing_holdings.clone();
+        if trading_mode == TradingMode::Mock {
+            mock_client
+                .set_spot_holdings(
+                    holdings
+                        .assets
+                        .iter()
+                        .map(|h| (format!("{}USDT", h.asset.to_uppercase()), h.quantity))
+                        .collect(),
+                )
+                .await;
+        } else if venue != Venue::Binance {
+            warn!(
+                "⚠️  [HOLDINGS] Holdings can only be verified in the Binance margin account, disabling existing-holdings hedge on {}",
+                venue
             );
-            let balance = persisted_state.balance;
-            let positions = persisted_state.positions.clone();
-            let funding_period = persisted_state.last_funding_period;
-            mock_client.restore_state(persisted_state).await;
-            (balance, positions, funding_period)
+            holdings.enabled = false;
         } else {
-            info!("📂 [PERSISTENCE] No previous state found, starting fresh with $10,000");
-            (dec!(10000), HashMap::new(), None)
-        };
+            // Never short more than the coins actually sitting in the account holding them:
+            // the COIN-margined wallet posts them as collateral, otherwise the margin account
+            let (account, balances) = if holdings.coin_margined {
+                (
+                    "COIN-margined wallet",
+                    real_client.get_coin_balances().await.map(|balances| {
+                        balances
+                            .into_iter()
+                            .map(|b| (b.asset, b.balance))
+                            .collect::<Vec<_>>()
+                    }),
+                )
+            } else {
+                (
+                    "margin account",
+                    real_client.get_cross_margin_account().await.map(|account| {
+                        account
+                            .user_assets
+                            .into_iter()
+                            .map(|a| (a.asset, a.free))
+                            .collect()
+                    }),
+                )
+            };
+            match balances {
+                Ok(balances) => {
+                    for held in &mut holdings.assets {
+                        let free = balances
+                            .iter()
+                            .find(|(asset, _)| asset.eq_ignore_ascii_case(&held.asset))
+                            .map(|(_, free)| *free)
+                            .unwrap_or(Decimal::ZERO);
+                        if free < held.quantity {
+                            warn!(
+                                "⚠️  [HOLDINGS] {} declared {} but only {} free in {} - using {}",
+                                held.asset, held.quantity, free, account, free
+                            );
+                            held.quantity = free;
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "⚠️  [HOLDINGS] Failed to verify holdings, disabling existing-holdings hedge: {}",
+                        e
+                    );
+                    holdings.enabled = false;
+                }
+            }
+        }
+        if holdings.enabled {
+            for held in &holdings.assets {
+                info!(
+                    "🪙 [HOLDINGS] {} {} available as {}",
+                    held.quantity,
+                    held.asset,
+                    if trade_coin_margined {
+                        "COIN-margined collateral"
+                    } else {
+                        "hedge leg"
+                    }
+                );
+            }
+        }
+        if trade_coin_margined {
+            trade_coin_margined = holdings.enabled;
+            coin_holdings = holdings;
+        } else {
+            executor.set_existing_holdings(holdings);
+        }
+    }
+    // Held COIN-margined shorts; their contracts' funding is refreshed every scan
+    let mut coin_positions: Vec<CoinMarginedPosition> = Vec::new();
+    let mut coin_contracts: HashMap<String, CoinContract> = HashMap::new();
+    if trade_coin_margined {
+        coin_positions = real_client
+            .get_coin_positions()
+            .await
+            .context("Failed to load COIN-margined positions")?
+            .iter()
+            .filter_map(CoinMarginedPosition::from_position)
+            .collect();
+        for position in &coin_positions {
+            info!(
+                "🪙 [COIN-M] Holding {} short: {} contract(s)",
+                position.symbol, position.contracts
+            );
+        }
+    } else if config.capital.existing_holdings.coin_margined && trading_mode == TradingMode::Live {
+        warn!("⚠️  [COIN-M] Holdings unverified - COIN-margined shorts are left unmanaged");
+    }
 
     // Initialize RiskOrchestrator with comprehensive risk monitoring
     let risk_config = RiskOrchestratorConfig {
@@ -278,14 +920,86 @@ async fn main() -> Result<()> {
         min_expected_yield: config.risk.min_expected_yield,
         grace_period_hours: config.risk.grace_period_hours,
         max_funding_deviation: config.risk.max_funding_deviation,
+        funding_expectation_window_hours: config.risk.funding_expectation_window_hours,
+        funding_collapse_ratio: config.risk.funding_collapse_ratio,
+        exit_liquidity_check_minutes: config.risk.exit_liquidity_check_minutes,
+        max_exit_cost_ratio: config.risk.max_exit_cost_ratio,
         max_loss_usd: config.risk.max_loss_usd,
         max_negative_apy: config.risk.max_negative_apy,
+        borrow_spike_ratio: config.risk.borrow_spike_ratio,
+        low_supply_spike_ratio: config.risk.low_supply_spike_ratio,
+        borrow_holidays: config.risk.borrow_holidays.clone(),
+        reentry_cooldown_minutes: config.risk.reentry_cooldown_minutes,
+        forced_exit_cooldown_minutes: config.risk.forced_exit_cooldown_minutes,
         max_errors_per_minute: config.risk.max_errors_per_minute,
         max_consecutive_failures: config.risk.max_consecutive_failures,
         emergency_delta_drift: config.risk.emergency_delta_drift,
+        max_residual_delta_usd: config.risk.max_residual_delta_usd,
+        malfunction_state_max_age_minutes: config.risk.malfunction_state_max_age_minutes,
         max_consecutive_risk_cycles: config.risk.max_consecutive_risk_cycles,
+        resumption: config.risk.resumption.clone(),
+        funding_quarantine: config.risk.funding_quarantine.clone(),
+        rules: config.risk.rules.clone(),
+        alert_routes: config.alerts.routes.clone(),
     };
+    for rule in &config.risk.rules {
+        info!("📜 [RULES] {}: {}", rule.name, rule);
+    }
     let mut risk_orchestrator = RiskOrchestrator::new(risk_config, initial_balance);
+    for (class, severity, action) in risk_orchestrator.alert_router().overrides() {
+        info!(
+            "🔀 [ALERTS] {} {} alerts route to {}",
+            severity, class, action
+        );
+    }
+
+    // Error bursts, failure counters and a halt survive restarts (crash loops included)
+    match persistence.load_malfunction_state() {
+        Ok(Some(state)) => {
+            let restored = risk_orchestrator.restore_malfunction_state(&state, Utc::now());
+            if !restored.is_empty() {
+                info!(
+                    "📂 [PERSISTENCE] Restored malfunction state saved {}: {} errors, {} failing symbols, {} alerts, {} risk cycles",
+                    state.saved_at.format("%Y-%m-%d %H:%M UTC"),
+                    restored.errors,
+                    restored.failing_symbols,
+                    restored.alerts,
+                    restored.consecutive_risk_cycles
+                );
+            }
+            if restored.halted {
+                error!("🚨 [RISK] Trading halt from the previous run is still in force");
+            } else if restored.halt_expired {
+                warn!("🐢 [RESUME] Previous run's halt has expired - resuming under supervision");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("⚠️  [PERSISTENCE] Failed to load malfunction state: {}", e),
+    }
+
+    // Quarantines last until the operator releases them
+    match persistence.get_quarantined() {
+        Ok(quarantined) => {
+            for quarantine in quarantined {
+                warn!(
+                    "🚫 [QUARANTINE] {} still quarantined since {} - release with `quarantine --release {}`",
+                    quarantine.symbol,
+                    quarantine.quarantined_at.format("%Y-%m-%d %H:%M UTC"),
+                    quarantine.symbol
+                );
+                risk_orchestrator.restore_quarantine(quarantine);
+            }
+        }
+        Err(e) => warn!("⚠️  [PERSISTENCE] Failed to load quarantines: {}", e),
+    }
+
+    // Funding settlement schedules; the venue moves some symbols off the 8h default
+    let (mut funding_calendar, funding_caps) = load_funding_info(venue_client, venue).await;
+    scanner.set_funding_caps(funding_caps);
+    if let Some(scenario) = &scenario {
+        scenario.apply_schedules(&mut funding_calendar, venue);
+    }
+    risk_orchestrator.set_funding_calendar(funding_calendar.clone(), venue);
 
     // Register restored positions with risk orchestrator's position tracker
     // This is CRITICAL for auto-close logic to evaluate existing positions
@@ -325,89 +1039,672 @@ async fn main() -> Result<()> {
                 expected_funding_rate: pos.expected_funding_rate, // Restored from persistence
                 entry_fees: position_value * dec!(0.0004), // Estimate ~0.04% taker fee
                 opened_at: Some(pos.opened_at), // Use original opened_at for proper grace period
+                first_funding_at: None,         // Not persisted; funding history covers it
             };
 
             risk_orchestrator.open_position(entry);
+            // Capture is measured from the restart; earlier settlements aren't replayed
+            funding_capture.open(
+                symbol,
+                position_value,
+                pos.futures_qty,
+                pos.expected_funding_rate,
+                funding_calendar.schedule(venue, symbol),
+                Utc::now(),
+            );
 
             // Restore the funding and interest data to the tracked position
             // This is critical for accurate profitability calculations
             risk_orchestrator.record_funding(symbol, pos.total_funding_received);
+            risk_orchestrator.restore_funding_paid(symbol, pos.total_funding_paid);
             risk_orchestrator.record_interest(symbol, pos.total_interest_paid);
 
             info!(
-                "   Registered: {} | Value: ${:.2} | Funding: ${:.4} | Interest: ${:.4}",
-                symbol, position_value, pos.total_funding_received, pos.total_interest_paid
+                "   Registered: {} | Value: ${:.2} | Funding: ${:.4} (paid ${:.4}) | Interest: ${:.4}",
+                symbol,
+                position_value,
+                pos.total_funding_received,
+                pos.total_funding_paid,
+                pos.total_interest_paid
             );
         }
     }
 
-    // Initialize precisions
-    match real_client.get_futures_exchange_info().await {
-        Ok(info) => {
-            let precisions = info
-                .symbols
-                .into_iter()
-                .map(|s| (s.symbol, s.quantity_precision))
-                .collect();
-            executor.set_precisions(precisions);
-            info!("✅ [INIT] Futures exchange info loaded");
-        }
-        Err(e) => {
-            warn!("⚠️  [INIT] Failed to load exchange info: {}", e);
-            if trading_mode == TradingMode::Live {
-                // In Live mode, we might want to panic, but for now we warn
-                error!(
-                    "❌ LIVE Mode warning: Exchange info failed. Precision defaults will be used."
+    // Mock restarts: pay funding settlements missed while offline at their historical rates
+    if let (TradingMode::Mock, Some(last_saved)) = (trading_mode, restored_last_saved) {
+        let held_symbols: Vec<String> = mock_client
+            .get_delta_neutral_positions()
+            .await
+            .into_iter()
+            .map(|p| p.symbol)
+            .collect();
+        let missed =
+            missed_funding_settlements(&funding_calendar, &held_symbols, last_saved, Utc::now());
+        if !missed.is_empty() {
+            let caught_up = catch_up_missed_funding(
+                venue_client,
+                &mark_cache,
+                &price_feed,
+                &mock_client,
+                &missed,
+            )
+            .await;
+            for (symbol, amount) in &caught_up {
+                risk_orchestrator.record_funding(symbol, *amount);
+                info!(
+                    "💸 [CATCH-UP] {} | ${:.4} over {} missed settlement(s)",
+                    symbol,
+                    amount,
+                    missed.get(symbol).map_or(0, Vec::len)
+                );
+            }
+            info!(
+                "💸 [CATCH-UP] Offline since {} - caught up {} position(s), total ${:.4}",
+                display_tz.short(last_saved),
+                missed.len(),
+                caught_up.values().sum::<Decimal>()
+            );
+
+            // Mark the latest settlement as collected so the loop doesn't pay it again
+            if let Some(latest) = missed
+                .values()
+                .filter_map(|settlements| settlements.last())
+                .max()
+            {
+                restored_funding_period = Some(get_funding_period_id(*latest));
+            }
+            let mut state_to_save = mock_client.export_state().await;
+            state_to_save.last_funding_period = restored_funding_period;
+            if let Err(e) =
+                save_state_watched(&persistence, &mut persistence_watchdog, &state_to_save)
+            {
+                warn!(
+                    "⚠️  [PERSISTENCE] Failed to save state after catch-up: {}",
+                    e
                 );
             }
         }
     }
 
-    // Metrics tracking
-    let mut metrics = AppMetrics::default();
-
-    // Shutdown signal
-    let shutdown = Arc::new(AtomicBool::new(false));
-    let shutdown_clone = shutdown.clone();
-    tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.ok();
-        info!("🛑 Shutdown signal received");
+    // Live: journal every order before it is sent, and resolve orders a crash left pending
+    if trading_mode == TradingMode::Live {
+        let mut journal = PersistenceManager::new(cli.data_dir.join(STATE_DB_FILE))?;
+        journal.set_stamp(stamp.clone());
+        executor.set_journal(journal);
+        match executor.reconcile_journal(venue_client).await {
+            Ok(resolved) => log_journal_reconciliation(&resolved),
+            Err(e) => warn!("⚠️  [JOURNAL] Failed to reconcile order journal: {}", e),
+        }
+        if let Some(hyperliquid) = hyperliquid_client.as_ref().filter(|c| c.can_trade()) {
+            match executor.reconcile_hyperliquid_journal(hyperliquid).await {
+                Ok(0) => {}
+                Ok(count) => info!(
+                    "🧾 [JOURNAL] Resolved {} pending Hyperliquid order(s)",
+                    count
+                ),
+                Err(e) => warn!("⚠️  [JOURNAL] Failed to reconcile Hyperliquid orders: {}", e),
+            }
+        }
+        // Later orders of positions opened before the restart keep their position ID
+        match executor.restore_position_ids() {
+            Ok(count) => info!("🧾 [JOURNAL] Restored {} open position ID(s)", count),
+            Err(e) => warn!("⚠️  [JOURNAL] Failed to restore position IDs: {}", e),
+        }
+    }
+
+    // Live restarts: register open exchange positions and seed their funding history
+    if trading_mode == TradingMode::Live {
+        match seed_live_positions(venue_client, &mut risk_orchestrator).await {
+            Ok(count) => info!("📂 [INIT] Seeded {} live positions from exchange", count),
+            Err(e) => warn!("⚠️  [INIT] Failed to seed live positions: {}", e),
+        }
+        check_symbol_settings(venue_client, &config.execution, &mut risk_orchestrator).await;
+        settings_checked_at = Some(Utc::now());
+    }
+
+    // Live Binance: order and position changes are pushed on the user data stream;
+    // REST polling stands in while it is down
+    let use_user_stream = trading_mode == TradingMode::Live
+        && venue == Venue::Binance
+        && venue_router.is_none()
+        && !trading_config.api_key.is_empty();
+    let mut user_stream: Option<UserDataStream> = None;
+
+    // Alert on restored positions above absolute notional caps (e.g. caps lowered since last run).
+    // The reduction phase shrinks them back under the cap.
+    let restored_notionals: HashMap<String, Decimal> = restored_positions
+        .iter()
+        .map(|(symbol, pos)| {
+            (
+                symbol.clone(),
+                pos.futures_qty.abs() * pos.futures_entry_price,
+            )
+        })
+        .filter(|(_, value)| *value > Decimal::ZERO)
+        .collect();
+    for breach in allocator.notional_cap_breaches(&restored_notionals) {
+        let alert = RiskAlert::new(
+            RiskAlertType::NotionalCapExceeded {
+                scope: breach.scope.clone(),
+                notional: breach.notional,
+                cap: breach.cap,
+            },
+            AlertSeverity::Warning,
+            Some(breach.scope.clone()),
+            format!(
+                "{} notional ${:.2} exceeds cap ${:.2}",
+                breach.scope, breach.notional, breach.cap
+            ),
+            "Position will be reduced to the cap".to_string(),
+        );
+        risk_orchestrator.raise(alert);
+    }
+
+    // Initialize precisions
+    match venue_client.get_futures_exchange_info().await {
+        Ok(info) => {
+            executor.set_min_notionals(
+                info.symbols
+                    .iter()
+                    .filter_map(|s| Some((s.symbol.clone(), s.min_notional()?)))
+                    .collect(),
+            );
+            let precisions = info
+                .symbols
+                .into_iter()
+                .map(|s| (s.symbol, s.quantity_precision))
+                .collect();
+            executor.set_precisions(precisions);
+            info!("✅ [INIT] Futures exchange info loaded");
+        }
+        Err(e) => {
+            warn!("⚠️  [INIT] Failed to load exchange info: {}", e);
+            if trading_mode == TradingMode::Live {
+                // In Live mode, we might want to panic, but for now we warn
+                error!(
+                    "❌ LIVE Mode warning: Exchange info failed. Precision defaults will be used."
+                );
+            }
+        }
+    }
+    // Spot hedges round to their own lot size where the venue reports one
+    match venue_client.get_spot_exchange_info().await {
+        Ok(info) => executor.set_spot_precisions(
+            info.iter()
+                .filter_map(|s| Some((s.symbol.clone(), s.quantity_precision()?)))
+                .collect(),
+        ),
+        Err(e) => warn!(
+            "⚠️  [INIT] Failed to load spot exchange info, spot quantities use futures precision: {}",
+            e
+        ),
+    }
+
+    // Mock mode: cache leverage brackets once so paper risk checks use the same
+    // maintenance margin tiers as live. Binance only serves them signed, so
+    // keyless paper runs keep the default maintenance rate
+    let binance_brackets_unavailable = venue == Venue::Binance && !real_client.has_read_key();
+    let cached_brackets: Vec<LeverageBracket> = if trading_mode != TradingMode::Mock {
+        Vec::new()
+    } else if binance_brackets_unavailable {
+        info!("ℹ️  [INIT] No API keys: leverage brackets need a signed request, using default maintenance rate");
+        Vec::new()
+    } else {
+        match venue_client.get_leverage_brackets().await {
+            Ok(brackets) => {
+                info!(
+                    "✅ [INIT] Leverage brackets cached for {} symbols",
+                    brackets.len()
+                );
+                brackets
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  [INIT] Failed to load leverage brackets, using default maintenance rate: {}",
+                    e
+                );
+                Vec::new()
+            }
+        }
+    };
+
+    // Metrics tracking
+    let mut metrics = AppMetrics::default();
+
+    // Drawdown, margin and liquidation reductions all go through one executor
+    let risk_actions = RiskActionExecutor::default();
+
+    // One record per process run: refreshed hourly so a crashed run keeps its KPIs
+    let mock_totals_at_start = {
+        let state = mock_client.get_state().await;
+        (state.total_funding_received, state.total_trading_fees)
+    };
+    let mut run = PersistedRun {
+        run_id: instance.instance_id.clone(),
+        mode: match trading_mode {
+            TradingMode::Live => "live",
+            TradingMode::Mock => "mock",
+        }
+        .to_string(),
+        started_at: metrics.start_time,
+        ended_at: None,
+        config_hash: stamp.config_hash.clone(),
+        version: stamp.version.clone(),
+        scans: 0,
+        entries: 0,
+        exits: 0,
+        errors: 0,
+        funding: Decimal::ZERO,
+        fees: Decimal::ZERO,
+        final_equity: None,
+    };
+    save_run_record(&persistence, &run);
+    info!(
+        "🧾 [RUN] Run {} started (config {}, v{})",
+        run.run_id, run.config_hash, run.version
+    );
+    let mut last_run_update = Utc::now();
+    let mut last_api_report = Utc::now();
+
+    // Shutdown on Ctrl-C, or SIGTERM from a supervisor (systemd, docker stop). The signal
+    // also cuts short whatever wait the loop is in, so the final save runs within seconds
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_wake = Arc::new(Notify::new());
+    let (shutdown_clone, wake_clone) = (shutdown.clone(), shutdown_wake.clone());
+    tokio::spawn(async move {
+        let signal = shutdown_signal().await;
+        info!("🛑 Shutdown signal received ({})", signal);
         shutdown_clone.store(true, Ordering::SeqCst);
+        wake_clone.notify_one();
     });
 
     info!("🚀 Starting main trading loop...");
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
     // Track last funding collection time and state saves
-    // Funding period ID: day_of_year * 3 + period_of_day (0, 1, or 2 for 0:00, 8:00, 16:00 UTC)
+    // Funding period ID: the settlement hour (hours since the Unix epoch). Symbols settle
+    // on their own schedules, so every hour with a settlement is its own period.
     // This prevents double-collection across restarts
     let mut last_funding_period: Option<u32> = restored_funding_period;
     let mut last_status_log = Utc::now();
-    let mut last_state_save = Utc::now();
+    let mut last_equity_snapshot = Utc::now();
+
+    // Mock state is saved within seconds of any balance/position change, and never when unchanged
+    let mut state_tracker = DirtyTracker::new(
+        chrono::Duration::seconds(STATE_SAVE_DEBOUNCE_SECS),
+        (mock_client.revision(), last_funding_period),
+    );
 
     // Helper function to calculate funding period ID
     fn get_funding_period_id(dt: DateTime<Utc>) -> u32 {
-        use chrono::Datelike;
-        let day = dt.ordinal(); // Day of year (1-366)
-        let hour = dt.hour();
-        let period = hour / 8; // 0, 1, or 2
-        day * 3 + period
+        (dt.timestamp() / 3600) as u32
+    }
+
+    // Set when another instance took over the lock; this one then stands down untouched
+    let mut lost_instance_lock = false;
+
+    // Enters safe-mode when the exchange stays unreachable with positions open
+    let mut outage_monitor = OutageMonitor::new(config.risk.safe_mode_after_minutes);
+
+    // Partial closes at funding milestones
+    let mut take_profit = TakeProfitMonitor::new(config.take_profit.clone());
+    let mut portfolio_rebalancer =
+        PortfolioRebalancer::new(config.capital.portfolio_rebalance.clone());
+
+    // Funding counts as income once the exchange confirms it; resume settlements
+    // that were still awaiting confirmation when the last run stopped
+    let mut funding_settlements = FundingSettlements::new(chrono::Duration::minutes(
+        FUNDING_CONFIRMATION_GRACE_MINUTES,
+    ));
+    match persistence.get_expected_funding_events() {
+        Ok(events) => {
+            if !events.is_empty() {
+                info!(
+                    "🧾 [FUNDING] {} settlement(s) still awaiting exchange confirmation",
+                    events.len()
+                );
+            }
+            for (id, event) in events {
+                funding_settlements.expect(PendingSettlement::new(
+                    Some(id),
+                    &event.symbol,
+                    event.timestamp,
+                    event.expected_amount.unwrap_or_default(),
+                    event.position_value,
+                ));
+            }
+        }
+        Err(e) => warn!("⚠️  [PERSISTENCE] Failed to load expected funding: {}", e),
     }
+    let mut last_confirmation_poll: Option<DateTime<Utc>> = None;
+
+    // Whether the current malfunction halt is already marked on the equity curve
+    let mut malfunction_halt_annotated = false;
+    // Whether the current malfunction halt has cancelled every open order
+    let mut malfunction_halt_orders_cleared = false;
+
+    // Set when the risk orchestrator halted trading; the process then exits with EXIT_RISK_HALT
+    let mut risk_halted = false;
+    // Set when a halt or shutdown left orders unconfirmed; exits with EXIT_ORDERS_LEFT_OPEN
+    let mut orders_left_open = false;
 
     // Main trading loop
     while !shutdown.load(Ordering::SeqCst) {
         let loop_start = Utc::now();
 
+        match persistence.heartbeat_instance_lock(&instance.instance_id, loop_start) {
+            Ok(true) => {}
+            Ok(false) => {
+                error!("🔒 [LOCK] Another instance took over trading - standing down, positions left to it");
+                lost_instance_lock = true;
+                break;
+            }
+            Err(e) => warn!("⚠️  [LOCK] Failed to refresh instance lock: {}", e),
+        }
+
+        let time_sync_interval = chrono::Duration::minutes(config.binance.time_sync_minutes as i64);
+        if server_clock.is_due(loop_start, time_sync_interval) {
+            sync_server_clock(&real_client).await;
+        }
+
+        if config.events.enabled
+            && !config.events.calendar_url.is_empty()
+            && loop_start - event_calendar_loaded_at
+                >= chrono::Duration::hours(config.events.refresh_hours as i64)
+        {
+            match EventCalendar::load(&config.events).await {
+                Ok(calendar) => {
+                    debug!(
+                        "🗓️  [EVENTS] Calendar refreshed ({} events)",
+                        calendar.len()
+                    );
+                    event_calendar = calendar;
+                }
+                Err(e) => warn!(
+                    "⚠️  [EVENTS] Calendar refresh failed, keeping {} known events: {:#}",
+                    event_calendar.len(),
+                    e
+                ),
+            }
+            event_calendar_loaded_at = loop_start;
+        }
+
+        if config.risk_free.fetch
+            && loop_start - risk_free_loaded_at
+                >= chrono::Duration::hours(config.risk_free.refresh_hours as i64)
+        {
+            match fetch_treasury_bill_rate(&config.risk_free.url).await {
+                Ok(rate) => {
+                    debug!("🏛️  [RISK-FREE] T-bill rate refreshed: {}", rate);
+                    risk_free = rate;
+                }
+                Err(e) => warn!(
+                    "⚠️  [RISK-FREE] T-bill rate refresh failed, keeping {}: {:#}",
+                    risk_free, e
+                ),
+            }
+            risk_free_loaded_at = loop_start;
+        }
+
+        if trading_mode == TradingMode::Live
+            && config.execution.settings_check_minutes > 0
+            && !outage_monitor.is_safe_mode()
+            && settings_checked_at.is_none_or(|at| {
+                loop_start - at
+                    >= chrono::Duration::minutes(config.execution.settings_check_minutes as i64)
+            })
+        {
+            check_symbol_settings(venue_client, &config.execution, &mut risk_orchestrator).await;
+            settings_checked_at = Some(loop_start);
+        }
+
+        if config.maintenance.enabled
+            && venue == Venue::Binance
+            && config.maintenance.status_check_minutes > 0
+            && maintenance_checked_at.is_none_or(|at| {
+                loop_start - at
+                    >= chrono::Duration::minutes(config.maintenance.status_check_minutes as i64)
+            })
+        {
+            match real_client.get_system_status().await {
+                Ok(status) => {
+                    if maintenance.set_system_status(status.is_maintenance()) {
+                        info!(
+                            "🛠️  [MAINTENANCE] System status changed: {} ({})",
+                            if status.is_maintenance() {
+                                "maintenance"
+                            } else {
+                                "normal"
+                            },
+                            status.msg
+                        );
+                    }
+                }
+                Err(e) => debug!("🛠️  [MAINTENANCE] System status check failed: {:#}", e),
+            }
+            maintenance_checked_at = Some(loop_start);
+        }
+
+        // Cycles inside a maintenance window are expected downtime, not malfunctions
+        let maintenance_state = maintenance.state_at(loop_start);
+        risk_orchestrator.set_expected_downtime(maintenance_state.is_active());
+        if maintenance_state.is_active() {
+            metrics.maintenance_cycles += 1;
+        }
+        if let Some(state) = maintenance.announce(loop_start) {
+            report_maintenance(&mut risk_orchestrator, &state, loop_start, &display_tz);
+        }
+
+        // ═══════════════════════════════════════════════════════════════
+        // SAFE-MODE: only risk-critical reads until the exchange is back
+        // ═══════════════════════════════════════════════════════════════
+        if outage_monitor.is_safe_mode() {
+            match probe_risk_reads(venue_client, trading_mode).await {
+                Ok(()) => {
+                    if let Some(recovery) = outage_monitor.record_success(Utc::now()) {
+                        recover_from_outage(
+                            &recovery,
+                            trading_mode,
+                            venue_client,
+                            &executor,
+                            &mut risk_orchestrator,
+                            &persistence,
+                        )
+                        .await;
+                    }
+                }
+                Err(e) => {
+                    let now = Utc::now();
+                    outage_monitor.record_failure(now, true);
+                    let symbols: Vec<String> = risk_orchestrator
+                        .get_all_tracked_positions()
+                        .iter()
+                        .map(|p| p.symbol.clone())
+                        .collect();
+                    for symbol in &symbols {
+                        let (action, reason) = match risk_orchestrator.evaluate_position(symbol) {
+                            PositionAction::ForceExit { reason } => ("force_exit", reason),
+                            PositionAction::ConsiderExit { reason, .. } => {
+                                ("consider_exit", reason)
+                            }
+                            _ => continue,
+                        };
+                        if outage_monitor.defer(symbol, action, &reason, now) {
+                            warn!(
+                                "🛟 [SAFE-MODE] Queued {} for {}: {}",
+                                action, symbol, reason
+                            );
+                        }
+                    }
+                    warn!(
+                        "🛟 [SAFE-MODE] Exchange unreachable for {} ({}) - {} action(s) queued, retrying in {}s",
+                        humanize_duration(outage_monitor.outage_duration(now).unwrap_or_default()),
+                        e,
+                        outage_monitor.deferred().len(),
+                        SAFE_MODE_RETRY_SECS
+                    );
+                    idle(SAFE_MODE_RETRY_SECS, &shutdown_wake).await;
+                    continue;
+                }
+            }
+        }
+
+        if use_mark_stream {
+            if !mark_cache.is_connected() {
+                connect_mark_price_stream(&mark_cache, &binance_ws).await;
+            }
+            sync_price_feed(&price_feed, &binance_ws, &risk_orchestrator).await;
+        }
+
+        if use_user_stream {
+            if !user_stream.as_ref().is_some_and(|s| s.is_connected()) {
+                if user_stream.is_some() {
+                    warn!("📨 [STREAM] User data stream ended, reconnecting");
+                }
+                user_stream = match connect_user_data_stream(&real_client, &binance_ws).await {
+                    Ok(stream) => {
+                        info!(
+                            "📨 [STREAM] User data stream connected ({} open positions)",
+                            stream.positions().len()
+                        );
+                        Some(stream)
+                    }
+                    Err(e) => {
+                        warn!(
+                            "⚠️  [STREAM] User data stream unavailable, polling positions: {:#}",
+                            e
+                        );
+                        None
+                    }
+                };
+            }
+            if let Some(stream) = user_stream.as_mut() {
+                let now = Utc::now();
+                if stream.keepalive_due(now) {
+                    match real_client.keepalive_listen_key().await {
+                        Ok(()) => stream.mark_kept_alive(now),
+                        Err(e) => warn!("⚠️  [STREAM] Listen key keepalive failed: {:#}", e),
+                    }
+                }
+                apply_user_data(stream.drain(), &executor, &mut risk_orchestrator);
+            }
+        }
+
+        report_persistence_failure(&mut persistence_watchdog, &mut risk_orchestrator);
+
         // ═══════════════════════════════════════════════════════════════
         // PHASE 1: Market Scanning
         // ═══════════════════════════════════════════════════════════════
         info!("📡 [SCAN] Starting market scan #{}", metrics.scan_count + 1);
 
-        let scan_result = scanner.scan(&real_client).await;
+        let symbol_quality = load_symbol_quality(&persistence, &config.pair_selection);
+        let blacklisted = symbol_quality.blacklisted();
+        if !blacklisted.is_empty() {
+            info!(
+                "🚫 [QUALITY] Skipping symbols with repeated negative carry: {}",
+                blacklisted.join(", ")
+            );
+        }
+        scanner.set_symbol_quality(symbol_quality);
+
+        for event in mock_client.advance_scenario(Utc::now()).await {
+            info!("🎬 [SCENARIO] {}", event);
+        }
+
+        if trade_coin_margined {
+            match real_client.get_coin_contracts().await {
+                Ok(contracts) => {
+                    coin_contracts = contracts;
+                    scanner.set_coin_contracts(coin_contracts.clone());
+                }
+                Err(e) => warn!(
+                    "⚠️  [COIN-M] Failed to fetch COIN-margined contracts: {:#}",
+                    e
+                ),
+            }
+        }
+
+        let scan_result = scanner.scan(venue_client).await;
         metrics.scan_count += 1;
+        // Pairs of coins farmed on COIN-margined perpetuals (fresh scans only)
+        let mut coin_pairs: Vec<QualifiedPair> = Vec::new();
 
-        let qualified_pairs = match scan_result {
+        // A failed scan falls back to the last successful one (within the freshness window)
+        // so held positions keep their rebalancing and risk context; stale data never
+        // opens new positions.
+        let (qualified_pairs, scan_stale) = match scan_result {
             Ok(pairs) => {
+                for pair in &pairs {
+                    if let Some(borrow_rate) = pair.borrow_rate {
+                        risk_orchestrator.observe_borrow_rate(
+                            &pair.symbol,
+                            borrow_rate,
+                            loop_start,
+                        );
+                    }
+                }
+                for spike in risk_orchestrator.check_borrow_spikes(loop_start) {
+                    warn!(
+                        "💸 [BORROW] {} borrow rate {:.4}% is {:.1}x its weekly median{}",
+                        spike.symbol,
+                        spike.rate * dec!(100),
+                        spike.multiple(),
+                        if spike.low_supply {
+                            " (low-supply period)"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+
+                let held: Vec<String> = risk_orchestrator
+                    .get_all_tracked_positions()
+                    .iter()
+                    .map(|p| p.symbol.clone())
+                    .collect();
+                hedge_switches.retain_held(&held);
+                let review_interval =
+                    chrono::Duration::minutes(config.pair_selection.hedge_review_minutes as i64);
+                if config.pair_selection.futures_hedges
+                    && config.pair_selection.hedge_review_minutes > 0
+                    && hedges_reviewed_at.is_none_or(|at| loop_start - at >= review_interval)
+                {
+                    hedges_reviewed_at = Some(loop_start);
+                    review_held_hedges(&mut hedge_switches, &mut risk_orchestrator, &pairs, &held);
+                } else if config.pair_selection.futures_hedges {
+                    // A spiking borrow rate can't wait for the next review
+                    let spiking = risk_orchestrator.borrow_spiking();
+                    review_held_hedges(
+                        &mut hedge_switches,
+                        &mut risk_orchestrator,
+                        &pairs,
+                        &spiking,
+                    );
+                }
+
+                // Coins posted as COIN-margined collateral stay out of the USDT-margined book
+                let (farmed, pairs): (Vec<_>, Vec<_>) = pairs
+                    .into_iter()
+                    .partition(|p| trade_coin_margined && coin_holdings.covers(&p.base_asset));
+                coin_pairs = farmed;
+
+                // The executor trades spot hedges only: perp-hedged pairs are reported
+                let (mut pairs, perp_hedged): (Vec<_>, Vec<_>) =
+                    pairs.into_iter().partition(|p| p.hedge.is_spot());
+                // Symbols held as cross-venue spreads already have a Binance futures leg
+                pairs.retain(|p| !spread_positions.iter().any(|s| s.symbol == p.symbol));
+                for pair in &perp_hedged {
+                    info!(
+                        "🔀 [HEDGE] {} funding {:.4}% hedgeable with {} (carry {:.4}%) - not entered, spot hedges only",
+                        pair.symbol,
+                        pair.funding_rate * dec!(100),
+                        pair.hedge,
+                        pair.hedge_options
+                            .first()
+                            .map_or(Decimal::ZERO, |option| option.carry.total())
+                            * dec!(100)
+                    );
+                }
                 info!("📊 [SCAN] Found {} qualified pairs", pairs.len());
                 for (i, pair) in pairs.iter().take(5).enumerate() {
                     info!(
@@ -420,28 +1717,247 @@ async fn main() -> Result<()> {
                     );
                 }
                 metrics.opportunities_found += pairs.len() as u64;
-                pairs
+                scan_cache.store(&pairs, Utc::now());
+                if let Some(recovery) = outage_monitor.record_success(Utc::now()) {
+                    info!(
+                        "📡 [SCAN] Exchange reachable again after {}",
+                        humanize_duration(recovery.duration)
+                    );
+                }
+                (pairs, false)
             }
             Err(e) => {
-                error!("❌ [SCAN] Failed: {}", e);
-                metrics.errors_count += 1;
-                Vec::new()
+                let now = Utc::now();
+                let open_positions = risk_orchestrator.get_all_tracked_positions().len();
+                if maintenance_state.is_active() {
+                    warn!(
+                        "🛠️  [MAINTENANCE] Scan failed during exchange maintenance (expected): {}",
+                        e
+                    );
+                    // Safe-mode still protects open positions, without an outage alert
+                    if outage_monitor.record_failure(now, open_positions > 0) {
+                        warn!(
+                            "🛟 [SAFE-MODE] Entering safe-mode for the maintenance window with {} open position(s)",
+                            open_positions
+                        );
+                    }
+                } else {
+                    error!("❌ [SCAN] Failed: {}", e);
+                    metrics.errors_count += 1;
+                    if outage_monitor.record_failure(now, open_positions > 0) {
+                        report_exchange_outage(
+                            &mut risk_orchestrator,
+                            outage_monitor.outage_duration(now).unwrap_or_default(),
+                            open_positions,
+                        );
+                    }
+                }
+                match scan_cache.fresh(now) {
+                    Some(cached) => {
+                        warn!(
+                            "⚠️  [SCAN] Using {} cached pairs from {}m ago - stale data, no new entries",
+                            cached.len(),
+                            scan_cache.age(now).unwrap_or_default().num_minutes()
+                        );
+                        metrics.stale_scan_cycles += 1;
+                        (cached.to_vec(), true)
+                    }
+                    None => (Vec::new(), false),
+                }
             }
         };
 
+        // Feed observed rates for held symbols into the rolling funding expectation
+        // (cached rates were already observed when they were fresh)
+        if !scan_stale {
+            for pair in &qualified_pairs {
+                if risk_orchestrator
+                    .get_tracked_position(&pair.symbol)
+                    .is_some()
+                {
+                    risk_orchestrator.observe_funding_rate(&pair.symbol, pair.funding_rate);
+                    predicted_rates.insert(pair.symbol.clone(), pair.funding_rate);
+                    if let Some(borrow_rate) = pair.borrow_rate {
+                        borrow_rates.insert(pair.symbol.clone(), borrow_rate);
+                    }
+                }
+            }
+        }
+
+        // Borrow limits of borrowed assets and of the best candidates that would borrow
+        if trading_mode == TradingMode::Live
+            && venue == Venue::Binance
+            && !real_client.is_testnet()
+            && !scan_stale
+            && borrow_limits.is_due(loop_start)
+        {
+            borrow_limits.mark_refreshed(loop_start);
+            refresh_borrow_limits(
+                &real_client,
+                &mark_cache,
+                &price_feed,
+                &mut borrow_limits,
+                &mut risk_orchestrator,
+                &qualified_pairs,
+            )
+            .await;
+            scanner.set_borrow_headroom(
+                borrow_limits.headroom_usdt(),
+                borrow_limits.min_headroom_usdt(),
+            );
+        }
+
+        // Held symbols that dropped out of the scan are often the ones whose rate collapsed
+        let unscanned_held: Vec<String> = risk_orchestrator
+            .get_all_tracked_positions()
+            .iter()
+            .map(|p| p.symbol.clone())
+            .filter(|symbol| scan_stale || !qualified_pairs.iter().any(|p| &p.symbol == symbol))
+            .collect();
+        for (symbol, rate) in
+            fetch_funding_rates_for_symbols(venue_client, &mark_cache, &unscanned_held).await
+        {
+            risk_orchestrator.observe_funding_rate(&symbol, rate);
+            predicted_rates.insert(symbol, rate);
+        }
+
+        // Headline yield: held positions at their latest predicted rates, net of borrow
+        // and of entry and exit fees spread over the expected hold
+        let held_funding: Vec<HeldFunding> = risk_orchestrator
+            .get_all_tracked_positions()
+            .iter()
+            .map(|p| HeldFunding {
+                symbol: p.symbol.clone(),
+                notional: p.position_value,
+                funding_rate: predicted_rates
+                    .get(&p.symbol)
+                    .copied()
+                    .unwrap_or(p.expected_funding_rate),
+                schedule: funding_calendar.schedule(venue, &p.symbol),
+                short_futures: p.expected_funding_rate >= Decimal::ZERO,
+                borrow_rate: borrow_rates.get(&p.symbol).copied(),
+            })
+            .collect();
+        predicted_rates.retain(|symbol, _| held_funding.iter().any(|h| &h.symbol == symbol));
+        borrow_rates.retain(|symbol, _| held_funding.iter().any(|h| &h.symbol == symbol));
+        risk_orchestrator.set_portfolio_funding_apr(None);
+        if !held_funding.is_empty() {
+            let fees = RoundTripFees {
+                entry_cost_rate: entry_cost_model.cost_rate(),
+                holding_hours: config.pair_selection.basis_horizon_hours,
+            };
+            let portfolio_apr = PortfolioApr::compute(&held_funding, &fees);
+            risk_orchestrator.set_portfolio_funding_apr(Some(portfolio_apr.net_apr()));
+            info!(
+                "📈 [APR] Portfolio funding APR {:.2}% net ({:.2}% gross, {:.2}% costs) on ${:.2} across {} position(s), {:+.2}% vs T-bills at {:.2}%",
+                portfolio_apr.net_apr() * dec!(100),
+                portfolio_apr.gross_apr() * dec!(100),
+                portfolio_apr.cost_apr() * dec!(100),
+                portfolio_apr.notional(),
+                portfolio_apr.positions.len(),
+                risk_free.excess(portfolio_apr.net_apr()) * dec!(100),
+                risk_free.annual_rate * dec!(100)
+            );
+            let record = portfolio_apr.record(loop_start, risk_free.annual_rate);
+            if let Err(e) = persistence.record_funding_apr(&record) {
+                warn!("⚠️  [PERSISTENCE] Failed to record portfolio APR: {}", e);
+            }
+        }
+
+        // ═══════════════════════════════════════════════════════════════
+        // PHASE 1.5: Cross-Venue Funding Spreads
+        // ═══════════════════════════════════════════════════════════════
+        if let Some(hyperliquid) = &hyperliquid_client {
+            let interval =
+                chrono::Duration::minutes(config.cross_venue.check_interval_minutes as i64);
+            if cross_venue_checked_at.is_none_or(|at| loop_start - at >= interval) {
+                cross_venue_checked_at = Some(loop_start);
+                let check = check_funding_spreads(
+                    &real_client,
+                    hyperliquid,
+                    &mut cross_venue_monitor,
+                    &mut risk_orchestrator,
+                    &funding_calendar,
+                    &config.cross_venue,
+                )
+                .await;
+                if let Some(check) = check {
+                    if trade_spreads {
+                        trade_funding_spreads(
+                            &real_client,
+                            hyperliquid,
+                            &executor,
+                            &mut spread_positions,
+                            &check,
+                            &funding_calendar,
+                            &config,
+                        )
+                        .await;
+                    }
+                    // Hyperliquid perps can hedge symbols without a spot margin pair
+                    if config.pair_selection.futures_hedges {
+                        let rates =
+                            hyperliquid_rates_per_8h(&check.hyperliquid_rates, &funding_calendar);
+                        scanner.set_venue_hedge_rates(Venue::Hyperliquid, rates);
+                    }
+                }
+            }
+        }
+
+        // ═══════════════════════════════════════════════════════════════
+        // PHASE 1.6: COIN-Margined Funding
+        // ═══════════════════════════════════════════════════════════════
+        if trade_coin_margined {
+            farm_coin_margined(
+                &real_client,
+                &executor,
+                &mut coin_positions,
+                &coin_pairs,
+                &coin_contracts,
+                &coin_holdings,
+                config.pair_selection.min_funding_rate,
+            )
+            .await;
+        }
+
         // ═══════════════════════════════════════════════════════════════
         // PHASE 2: Malfunction Check
         // ═══════════════════════════════════════════════════════════════
+        if let Some(trip) = order_valve.take_new_trip() {
+            report_order_valve_trip(&mut risk_orchestrator, &trip);
+        }
         if risk_orchestrator.check_malfunctions() {
             error!("🚨 [RISK] Trading halted due to detected malfunction!");
             // Log active alerts
             for alert in risk_orchestrator.get_active_alerts() {
                 error!("   Alert: {} - {:?}", alert.message, alert.malfunction_type);
             }
+            if !malfunction_halt_annotated {
+                let reasons: Vec<String> = risk_orchestrator
+                    .get_active_alerts()
+                    .iter()
+                    .map(|alert| alert.message.clone())
+                    .collect();
+                annotate_equity(
+                    &persistence,
+                    EquityEventKind::Halt,
+                    format!("Malfunction halt: {}", reasons.join("; ")),
+                );
+                malfunction_halt_annotated = true;
+            }
+            // Once per halt, repeated only while the venue hasn't confirmed none is left
+            if trading_mode == TradingMode::Live && !malfunction_halt_orders_cleared {
+                malfunction_halt_orders_cleared =
+                    cancel_open_orders_on_halt(&executor, &trading_venues, "Malfunction halt")
+                        .await;
+            }
+            save_malfunction_state(&persistence, &risk_orchestrator);
             // Wait longer before retrying
-            tokio::time::sleep(Duration::from_secs(300)).await;
+            idle(300, &shutdown_wake).await;
             continue;
         }
+        malfunction_halt_annotated = false;
+        malfunction_halt_orders_cleared = false;
 
         // ═══════════════════════════════════════════════════════════════
         // PHASE 3: Capital Allocation
@@ -469,7 +1985,9 @@ async fn main() -> Result<()> {
             }
 
             // Fetch prices for all symbols (qualified + current positions)
-            let prices = fetch_prices_for_symbols(&real_client, &all_symbols).await;
+            let prices =
+                fetch_prices_for_symbols(venue_client, &mark_cache, &price_feed, &all_symbols)
+                    .await;
 
             // CRITICAL: Check if price fetch failed completely
             // If no prices returned, skip trading to avoid silent failures
@@ -481,7 +1999,7 @@ async fn main() -> Result<()> {
                 metrics.errors_count += 1;
                 risk_orchestrator.record_error("Price fetch returned empty - API unavailable");
                 // Continue to next cycle instead of making uninformed trades
-                tokio::time::sleep(Duration::from_secs(60)).await;
+                idle(60, &shutdown_wake).await;
                 continue;
             }
 
@@ -501,13 +2019,33 @@ async fn main() -> Result<()> {
                         (p.symbol, position_value_usdt)
                     })
                     .collect()
+            } else if let Some(stream) = user_stream.as_mut().filter(|s| s.is_connected()) {
+                apply_user_data(stream.drain(), &executor, &mut risk_orchestrator);
+                stream.positions().clone()
             } else {
-                match fetch_real_positions(&real_client).await {
-                    Ok(pos) => pos,
-                    Err(_) => HashMap::new(),
-                }
+                fetch_real_positions(venue_client).await.unwrap_or_default()
             };
 
+            // Settle in-flight entries from earlier cycles before allocating again
+            for symbol in entry_intents.reconcile(&current_positions) {
+                info!("✅ [INTENT] {} entry confirmed on exchange", symbol);
+            }
+            let stale_intents: Vec<String> = entry_intents
+                .stale(
+                    chrono::Duration::minutes(ENTRY_INTENT_TIMEOUT_MINUTES),
+                    Utc::now(),
+                )
+                .into_iter()
+                .map(|intent| intent.symbol.clone())
+                .collect();
+            for symbol in stale_intents {
+                warn!(
+                    "⚠️  [INTENT] {} entry not confirmed after {} min - cancelling intent",
+                    symbol, ENTRY_INTENT_TIMEOUT_MINUTES
+                );
+                entry_intents.cancel(&symbol);
+            }
+
             let mock_state = mock_client.get_state().await;
 
             // DEBUG: Log current positions with values (elevated to INFO for visibility)
@@ -520,13 +2058,194 @@ async fn main() -> Result<()> {
                     .collect::<Vec<_>>()
             );
 
-            let allocations = allocator.calculate_allocation(
-                &qualified_pairs,
-                mock_state.balance,
-                &current_positions,
-            );
+            // Quarantines released from the CLI take effect here
+            sync_quarantines(&mut risk_orchestrator, &persistence);
+            let quarantined: Vec<String> = risk_orchestrator
+                .quarantined()
+                .iter()
+                .map(|q| q.symbol.clone())
+                .collect();
+            let quarantined_qualified: Vec<&str> = qualified_pairs
+                .iter()
+                .map(|p| p.symbol.as_str())
+                .filter(|symbol| quarantined.iter().any(|s| s == symbol))
+                .collect();
+            if !quarantined_qualified.is_empty() {
+                info!(
+                    "🚫 [QUARANTINE] Not adding to quarantined: {}",
+                    quarantined_qualified.join(", ")
+                );
+            }
+            allocator.set_quarantined(quarantined);
 
-            // ═══════════════════════════════════════════════════════════════
+            let cooling_down = risk_orchestrator.cooling_down(Utc::now());
+            let cooling_qualified: Vec<&str> = qualified_pairs
+                .iter()
+                .map(|p| p.symbol.as_str())
+                .filter(|symbol| cooling_down.iter().any(|s| s == symbol))
+                .collect();
+            if !cooling_qualified.is_empty() {
+                info!(
+                    "⏳ [COOLDOWN] Not re-entering recently closed: {}",
+                    cooling_qualified.join(", ")
+                );
+            }
+            allocator.set_cooling_down(cooling_down);
+            allocator.set_utilization(utilization.current());
+
+            // Exceptional funding may borrow part of the reserve for a limited time
+            if !scan_stale {
+                let flash_rates: Vec<(&str, Decimal)> = qualified_pairs
+                    .iter()
+                    .map(|p| {
+                        let schedule = funding_calendar.schedule(venue, &p.symbol);
+                        (
+                            p.symbol.as_str(),
+                            schedule.rate_per_hours(p.funding_rate, 8),
+                        )
+                    })
+                    .collect();
+                match flash_reserve.update(flash_rates, Utc::now()) {
+                    Some(FlashReserveEvent::Started(release)) => {
+                        info!("⚡ [FLASH] Reserve released to {}", release);
+                    }
+                    Some(FlashReserveEvent::Ended { release, reason }) => {
+                        let why = match reason {
+                            FlashEnd::Expired => "expired",
+                            FlashEnd::RateFaded => "rate fell below the flash threshold",
+                        };
+                        info!(
+                            "⚡ [FLASH] Reserve release to {} ended ({}) - restoring the reserve",
+                            release.symbol, why
+                        );
+                    }
+                    None => {}
+                }
+            }
+            allocator.set_flash_release(flash_reserve.active());
+            let free_margin = allocator.free_margin_share(mock_state.balance, &current_positions);
+            if let Some(shortfall) = flash_reserve.check_restored(free_margin, Utc::now()) {
+                report_reserve_not_restored(&mut risk_orchestrator, shortfall);
+            }
+
+            let allocations = if scan_stale {
+                info!("⏸️  [ALLOCATE] Scan data is stale - skipping new entries this cycle");
+                Vec::new()
+            } else {
+                allocator.calculate_allocation(
+                    &qualified_pairs,
+                    mock_state.balance,
+                    &current_positions,
+                )
+            };
+            // Late entries are measured from the first allocation, before any restriction
+            if !scan_stale {
+                funding_capture.note_allocated(
+                    allocations.iter().map(|alloc| alloc.symbol.as_str()),
+                    Utc::now(),
+                );
+            }
+
+            // Macro event windows block or shrink new entries; held positions are unaffected
+            let allocations = match event_calendar.restriction_at(Utc::now()) {
+                EntryRestriction::Open => allocations,
+                EntryRestriction::Blackout { event, until } => {
+                    info!(
+                        "🗓️  [EVENTS] Entry blackout for {} ({}) - no new entries until {}",
+                        event.name,
+                        display_tz.short(event.time),
+                        display_tz.short(until)
+                    );
+                    Vec::new()
+                }
+                EntryRestriction::Reduced {
+                    event,
+                    factor,
+                    until,
+                } => {
+                    info!(
+                        "🗓️  [EVENTS] {} ({}) - entries sized at {:.0}% until {}",
+                        event.name,
+                        display_tz.short(event.time),
+                        factor * dec!(100),
+                        display_tz.short(until)
+                    );
+                    allocations
+                        .into_iter()
+                        .map(|mut alloc| {
+                            alloc.target_size_usdt *= factor;
+                            alloc
+                        })
+                        .filter(|alloc| alloc.target_size_usdt >= config.capital.min_position_size)
+                        .collect()
+                }
+            };
+
+            // Maintenance windows hold back new entries; held positions are unaffected
+            let allocations = if maintenance_state.blocks_entries() && !allocations.is_empty() {
+                info!(
+                    "🛠️  [MAINTENANCE] {} - holding back {} new entr(ies)",
+                    describe_maintenance(&maintenance_state, &display_tz),
+                    allocations.len()
+                );
+                Vec::new()
+            } else {
+                allocations
+            };
+
+            // State that can't be saved would be lost on restart, so nothing new is entered
+            let save_failures = persistence_watchdog
+                .failing_for(Utc::now())
+                .filter(|_| persistence_watchdog.blocks_entries(Utc::now()));
+            let allocations = match save_failures {
+                Some(failing_for) if !allocations.is_empty() => {
+                    warn!(
+                        "💾 [PERSISTENCE] State saves failing for {} - holding back {} new entr(ies)",
+                        humanize_duration(failing_for),
+                        allocations.len()
+                    );
+                    Vec::new()
+                }
+                _ => allocations,
+            };
+
+            // Operator rules may halt new entries; held positions are unaffected
+            let allocations = match risk_orchestrator.entry_halt_rule() {
+                Some(rule) if !allocations.is_empty() => {
+                    info!(
+                        "📜 [RULES] Entries halted by rule '{}' ({})",
+                        rule.name, rule
+                    );
+                    Vec::new()
+                }
+                _ => allocations,
+            };
+
+            // After a halt is reset, new entries resume one at a time at reduced size
+            let allocations = match risk_orchestrator.entry_throttle() {
+                Some(throttle) => {
+                    let mut new_entries = 0;
+                    let allocations: Vec<_> = allocations
+                        .into_iter()
+                        .filter_map(|mut alloc| {
+                            if current_positions.contains_key(&alloc.symbol) {
+                                return Some(alloc);
+                            }
+                            if new_entries >= throttle.max_entries {
+                                return None;
+                            }
+                            new_entries += 1;
+                            alloc.target_size_usdt *= throttle.size_scale;
+                            Some(alloc)
+                        })
+                        .collect();
+                    info!("🐢 [RESUME] Supervised resumption: {}", throttle);
+                    allocations
+                }
+                None => allocations,
+            };
+
+            // ═══════════════════════════════════════════════════════════════
             // JIT Entry Window Check (Per-Symbol)
             // Only enter new positions within X minutes of funding settlement
             // This reduces pre-first-funding borrow interest and confirms rate
@@ -540,6 +2259,11 @@ async fn main() -> Result<()> {
                 .iter()
                 .map(|p| (p.symbol.clone(), p.next_funding_time))
                 .collect();
+            let capped_symbols: BTreeSet<&str> = qualified_pairs
+                .iter()
+                .filter(|p| p.funding_capped)
+                .map(|p| p.symbol.as_str())
+                .collect();
 
             // Filter allocations to only those within their entry window
             let (ready_allocations, waiting_allocations): (Vec<_>, Vec<_>) = allocations
@@ -559,17 +2283,30 @@ async fn main() -> Result<()> {
             // Log waiting pairs
             for alloc in &waiting_allocations {
                 let next_funding = funding_times.get(&alloc.symbol).copied().unwrap_or(0);
-                let seconds_to_funding = (next_funding - now_ms) / 1000;
-                let minutes_to_funding = seconds_to_funding / 60;
-                let minutes_to_window = minutes_to_funding - config.risk.entry_window_minutes as i64;
+                let Some(funding_at) = DateTime::from_timestamp_millis(next_funding) else {
+                    continue;
+                };
+                let now = Utc::now();
+                let window_opens =
+                    funding_at - chrono::Duration::minutes(config.risk.entry_window_minutes as i64);
                 info!(
-                    "⏳ [JIT] {} - {} min until funding, waiting {} min before entry",
+                    "⏳ [JIT] {} - next funding {}, waiting {} before entry",
                     alloc.symbol,
-                    minutes_to_funding,
-                    minutes_to_window
+                    display_tz.countdown(funding_at, now),
+                    humanize_duration(window_opens - now)
                 );
             }
 
+            // Micro-timing: hold ready entries while the futures book leans against them
+            let (ready_allocations, timed_entries) = time_entries(
+                venue_client,
+                &mut entry_timer,
+                &persistence,
+                ready_allocations,
+                &funding_times,
+            )
+            .await;
+
             if !ready_allocations.is_empty() {
                 info!("💰 [ALLOCATE] {} positions ready to enter ({} waiting for window)",
                     ready_allocations.len(), waiting_allocations.len());
@@ -675,13 +2412,31 @@ async fn main() -> Result<()> {
                             }
                         }
 
+                        if !entry_intents.register(
+                            &alloc.symbol,
+                            alloc.target_size_usdt,
+                            alloc.funding_rate,
+                            Utc::now(),
+                        ) {
+                            info!(
+                                "⏩ [SKIP] {} entry from a previous cycle still in flight",
+                                alloc.symbol
+                            );
+                            continue;
+                        }
+
                         info!(
                             "📈 [EXECUTE] Entering NEW position: {} (qty: {:.4})",
                             alloc.symbol, target_qty
                         );
 
                         // Calculate quantity - only enter new positions, not adjustments
-                        let quantity = target_qty;
+                        // (limited to declared coins when existing holdings form the long leg)
+                        let quantity = executor.entry_quantity(alloc, target_qty);
+                        if quantity <= Decimal::ZERO {
+                            entry_intents.cancel(&alloc.symbol);
+                            continue;
+                        }
 
                         // Determine sides based on funding direction
                         let (futures_side, spot_side) = if alloc.funding_rate > Decimal::ZERO {
@@ -709,11 +2464,13 @@ async fn main() -> Result<()> {
                             new_client_order_id: None,
                         };
 
-                        if let Err(e) = mock_client.place_futures_order(&futures_order).await {
+                        let futures_result = mock_client.place_futures_order(&futures_order).await;
+                        if let Err(e) = &futures_result {
                             error!("❌ [EXECUTE] Futures order failed: {}", e);
                             metrics.errors_count += 1;
-                            risk_orchestrator.record_error(&format!("Futures order failed: {}", e));
+                            risk_orchestrator.record_api_error("Futures order failed", e);
                             risk_orchestrator.record_order_failure(&alloc.symbol);
+                            entry_intents.cancel(&alloc.symbol);
                             continue;
                         }
                         risk_orchestrator.record_order_success(&alloc.symbol);
@@ -730,12 +2487,14 @@ async fn main() -> Result<()> {
                             side_effect_type: Some(
                                 funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
                             ),
+                            new_client_order_id: None,
                         };
 
-                        if let Err(e) = mock_client.place_margin_order(&spot_order).await {
+                        let spot_result = mock_client.place_margin_order(&spot_order).await;
+                        if let Err(e) = &spot_result {
                             error!("❌ [EXECUTE] Spot hedge failed: {}", e);
                             metrics.errors_count += 1;
-                            risk_orchestrator.record_error(&format!("Spot hedge failed: {}", e));
+                            risk_orchestrator.record_api_error("Spot hedge failed", e);
                             risk_orchestrator.record_order_failure(&alloc.spot_symbol);
 
                             // Unwind the futures position to avoid directional exposure
@@ -772,15 +2531,36 @@ async fn main() -> Result<()> {
                                     "⚠️  [EXECUTE] Unwound futures for {} due to spot hedge failure",
                                     alloc.symbol
                                 );
+                                entry_intents.cancel(&alloc.symbol);
                             }
                             continue;
                         }
+                        entry_intents.mark_submitted(&alloc.symbol);
 
                         info!(
                             "✅ [EXECUTE] Position entered: {} | Qty: {} | Price: ${}",
                             alloc.symbol, quantity, price
                         );
                         metrics.positions_entered += 1;
+                        annotate_equity(
+                            &persistence,
+                            EquityEventKind::Entry,
+                            format!("{} entered", alloc.symbol),
+                        );
+                        if let Some(timed) = timed_entries.get(&alloc.symbol) {
+                            record_entry_timing(&persistence, timed, "executed");
+                        }
+                        record_execution_quality(
+                            &persistence,
+                            &mut entry_cost_model,
+                            ExecutionKind::Entry,
+                            &alloc.symbol,
+                            price,
+                            &[
+                                ("futures", futures_result.as_ref().ok()),
+                                ("spot", spot_result.as_ref().ok()),
+                            ],
+                        );
 
                         // Track position for risk monitoring
                         let entry = PositionEntry {
@@ -789,10 +2569,27 @@ async fn main() -> Result<()> {
                             quantity,
                             position_value: alloc.target_size_usdt,
                             expected_funding_rate: alloc.funding_rate,
-                            entry_fees: alloc.target_size_usdt * dec!(0.0004), // ~0.04% taker fee
+                            entry_fees: entry_cost_model.entry_cost(alloc.target_size_usdt),
                             opened_at: None, // New position - use current time
+                            first_funding_at: funding_times
+                                .get(&alloc.symbol)
+                                .and_then(|ms| DateTime::from_timestamp_millis(*ms)),
                         };
                         risk_orchestrator.open_position(entry);
+                        if capped_symbols.contains(alloc.symbol.as_str()) {
+                            risk_orchestrator.discount_capped_entry(
+                                &alloc.symbol,
+                                config.pair_selection.capped_funding_persistence,
+                            );
+                        }
+                        funding_capture.open(
+                            &alloc.symbol,
+                            alloc.target_size_usdt,
+                            quantity,
+                            alloc.funding_rate,
+                            funding_calendar.schedule(venue, &alloc.symbol),
+                            Utc::now(),
+                        );
 
                         // Persist expected funding rate to MockPosition for state restoration
                         mock_client
@@ -801,10 +2598,57 @@ async fn main() -> Result<()> {
                     }
                 } else {
                     // LIVE TRADING EXECUTION
-                    let prices = fetch_prices(&real_client, &qualified_pairs).await;
+                    let prices =
+                        fetch_prices(venue_client, &mark_cache, &price_feed, &qualified_pairs)
+                            .await;
+
+                    // Fetch account balance for pre-entry margin validation; routed
+                    // entries are funded by their own venue, so Binance is checked alone
+                    let futures_balances = match &venue_router {
+                        Some(_) => real_client.get_account_balance().await,
+                        None => venue_client.get_account_balance().await,
+                    };
+
+                    // Each wallet must fund its own leg; refuse entries when that can't be checked
+                    let mut funds = match &futures_balances {
+                        Ok(balances) if matches!(venue, Venue::Bybit | Venue::Okx) => {
+                            unified_funds(balances, config.capital.reserve_buffer)
+                        }
+                        Ok(balances) => {
+                            fetch_segregated_funds(
+                                &real_client,
+                                balances,
+                                config.capital.reserve_buffer,
+                            )
+                            .await
+                        }
+                        Err(e) => Err(anyhow::anyhow!("futures balance unavailable: {}", e)),
+                    };
+                    if let Err(e) = &funds {
+                        warn!(
+                            "🚫 [FUNDS] Wallet balances unavailable, refusing live entries: {}",
+                            e
+                        );
+                    }
+                    if let (Ok(funds), Some(release)) = (funds.as_mut(), flash_reserve.active()) {
+                        funds.release_reserve(release);
+                    }
+                    let mut okx_funds = match (&venue_router, &okx_client) {
+                        (Some(_), Some(okx)) => Some(
+                            okx.get_account_balance()
+                                .await
+                                .and_then(|b| unified_funds(&b, config.capital.reserve_buffer)),
+                        ),
+                        _ => None,
+                    };
+                    if let Some(Err(e)) = &okx_funds {
+                        warn!(
+                            "🚫 [FUNDS] OKX balances unavailable, refusing entries routed to OKX: {}",
+                            e
+                        );
+                    }
 
-                    // Fetch account balance for pre-entry margin validation
-                    let margin_context = match real_client.get_account_balance().await {
+                    let margin_context = match futures_balances {
                         Ok(balances) => {
                             let usdt_balance = balances
                                 .iter()
@@ -823,6 +2667,7 @@ async fn main() -> Result<()> {
                                 available_balance: usdt_balance,
                                 margin_balance: usdt_balance,
                                 total_position_value,
+                                position_notionals: current_positions.clone(),
                                 min_margin_ratio: config.risk.min_margin_ratio,
                             })
                         }
@@ -835,27 +2680,110 @@ async fn main() -> Result<()> {
                         }
                     };
 
+                    // Entries passing every check go out together, their futures legs
+                    // in one batch order request
+                    let mut entries = Vec::new();
                     for alloc in &allocations {
+                        let venue_funds = match (&venue_router, okx_funds.as_mut()) {
+                            (Some(router), Some(okx_funds))
+                                if router.venue_of(&alloc.symbol) == Venue::Okx =>
+                            {
+                                okx_funds
+                            }
+                            _ => &mut funds,
+                        };
+                        let Ok(funds) = venue_funds.as_mut() else {
+                            continue;
+                        };
                         let price = prices.get(&alloc.symbol).copied().unwrap_or(dec!(0));
                         if price == Decimal::ZERO {
                             warn!("Skipping {} due to missing price", alloc.symbol);
                             continue;
                         }
 
-                        // Use validated entry if margin context available, otherwise fallback
-                        let entry_result = if let Some(ref ctx) = margin_context {
-                            executor
-                                .enter_position_validated(&real_client, alloc, price, ctx)
-                                .await
-                        } else {
-                            executor.enter_position(&real_client, alloc, price).await
-                        };
+                        // Both legs must be fundable before the first order is placed
+                        let shortfalls = funds.shortfalls(alloc);
+                        if !shortfalls.is_empty() {
+                            for shortfall in &shortfalls {
+                                alert_insufficient_funds(
+                                    &mut risk_orchestrator,
+                                    &alloc.symbol,
+                                    shortfall,
+                                );
+                            }
+                            continue;
+                        }
+
+                        // A spot short must fit in what Binance has left to lend
+                        let borrows_on_binance = alloc.funding_rate < Decimal::ZERO
+                            && !alloc.hedge_with_holdings
+                            && venue_router.as_ref().is_none_or(|router| {
+                                router.venue_of(&alloc.symbol) == Venue::Binance
+                            });
+                        if let Some(shortfall) = borrows_on_binance
+                            .then(|| {
+                                borrow_limits
+                                    .shortfall(&alloc.base_asset, alloc.target_size_usdt / price)
+                            })
+                            .flatten()
+                        {
+                            alert_borrow_limit_exceeded(
+                                &mut risk_orchestrator,
+                                &alloc.symbol,
+                                &shortfall,
+                                price,
+                            );
+                            continue;
+                        }
+
+                        if !entry_intents.register(
+                            &alloc.symbol,
+                            alloc.target_size_usdt,
+                            alloc.funding_rate,
+                            Utc::now(),
+                        ) {
+                            info!(
+                                "⏩ [SKIP] {} entry from a previous cycle still in flight",
+                                alloc.symbol
+                            );
+                            continue;
+                        }
+
+                        funds.commit(alloc);
+                        entries.push((alloc, price));
+                    }
+
+                    // Entries are validated against the margin context when it is available
+                    let entry_results = executor
+                        .enter_positions(venue_client, &entries, margin_context.as_ref())
+                        .await;
 
+                    for ((alloc, price), entry_result) in entries.into_iter().zip(entry_results) {
                         match entry_result {
                             Ok(result) => {
                                 if result.success {
+                                    entry_intents.mark_submitted(&alloc.symbol);
                                     info!("✅ [EXECUTE] Entered position for {}", result.symbol);
                                     metrics.positions_entered += 1;
+                                    annotate_equity(
+                                        &persistence,
+                                        EquityEventKind::Entry,
+                                        format!("{} entered", result.symbol),
+                                    );
+                                    if let Some(timed) = timed_entries.get(&alloc.symbol) {
+                                        record_entry_timing(&persistence, timed, "executed");
+                                    }
+                                    record_execution_quality(
+                                        &persistence,
+                                        &mut entry_cost_model,
+                                        ExecutionKind::Entry,
+                                        &alloc.symbol,
+                                        price,
+                                        &[
+                                            ("futures", result.futures_order.as_ref()),
+                                            ("spot", result.spot_order.as_ref()),
+                                        ],
+                                    );
 
                                     // CRITICAL: Register position with risk orchestrator for monitoring
                                     // This was missing, causing "Active Positions: X, Tracked: 0" discrepancy
@@ -869,15 +2797,26 @@ async fn main() -> Result<()> {
                                             .unwrap_or(alloc.target_size_usdt / price),
                                         position_value: alloc.target_size_usdt,
                                         expected_funding_rate: alloc.funding_rate,
-                                        entry_fees: alloc.target_size_usdt * dec!(0.0004),
+                                        entry_fees: entry_cost_model
+                                            .entry_cost(alloc.target_size_usdt),
                                         opened_at: None,
+                                        first_funding_at: funding_times
+                                            .get(&alloc.symbol)
+                                            .and_then(|ms| DateTime::from_timestamp_millis(*ms)),
                                     };
                                     risk_orchestrator.open_position(entry);
+                                    if capped_symbols.contains(alloc.symbol.as_str()) {
+                                        risk_orchestrator.discount_capped_entry(
+                                            &alloc.symbol,
+                                            config.pair_selection.capped_funding_persistence,
+                                        );
+                                    }
                                     info!(
                                         "   📊 Registered with risk tracker: {} @ ${:.2}",
                                         alloc.symbol, price
                                     );
                                 } else {
+                                    entry_intents.cancel(&alloc.symbol);
                                     error!(
                                         "❌ [EXECUTE] Failed to enter {}: {:?}",
                                         result.symbol, result.error
@@ -886,6 +2825,8 @@ async fn main() -> Result<()> {
                                 }
                             }
                             Err(e) => {
+                                // Keep the intent: the entry may have left a position behind,
+                                // which the next reconciliation will confirm
                                 error!("❌ [EXECUTE] Error executing {}: {}", alloc.symbol, e);
                                 metrics.errors_count += 1;
                             }
@@ -904,11 +2845,26 @@ async fn main() -> Result<()> {
                 &current_positions,
             );
 
-            // Filter reductions based on minimum holding period and yield advantage
-            // Exception: ForceExit from risk orchestrator bypasses holding protection
-            let reductions: Vec<_> = candidate_reductions
+            // Filter reductions based on minimum holding period, yield advantage and
+            // execution cost. Exception: ForceExit from risk orchestrator bypasses both
+            rebalancer.set_leg_cost_rate(entry_cost_model.cost_rate());
+            let rate_per_8h = |symbol: &str, rate: Decimal| {
+                funding_calendar
+                    .schedule(venue, symbol)
+                    .rate_per_hours(rate.abs(), 8)
+            };
+            let mut reductions: Vec<_> = candidate_reductions
                 .into_iter()
                 .filter(|reduction| {
+                    // Absolute notional caps are hard limits - bypass holding protection
+                    if reduction.exceeds_notional_cap {
+                        info!(
+                            "🧢 [CAP] {} ${:.2} above notional cap, reducing to ${:.2}",
+                            reduction.symbol, reduction.current_size_usdt, reduction.target_size_usdt
+                        );
+                        return true;
+                    }
+
                     // Check if risk orchestrator wants to force exit this position
                     let position_action = risk_orchestrator.evaluate_position(&reduction.symbol);
                     if matches!(position_action, PositionAction::ForceExit { .. }) {
@@ -963,16 +2919,231 @@ async fn main() -> Result<()> {
                             }
                         }
                     }
+
+                    // Redeploying the freed capital must repay closing and re-entering
+                    let best_alternative_8h = qualified_pairs
+                        .iter()
+                        .filter(|p| p.symbol != reduction.symbol)
+                        .map(|p| rate_per_8h(&p.symbol, p.funding_rate))
+                        .max()
+                        .unwrap_or(Decimal::ZERO);
+                    let rate_gain =
+                        best_alternative_8h - rate_per_8h(&reduction.symbol, reduction.funding_rate);
+                    let estimate = rebalancer.reduction_estimate(reduction.reduction_usdt, rate_gain);
+                    if !rebalancer.is_worthwhile(&estimate) {
+                        info!(
+                            "💸 [COST] {} reduction of ${:.2} skipped: {}",
+                            reduction.symbol, reduction.reduction_usdt, estimate
+                        );
+                        return false;
+                    }
                     true // Allow reduction
                 })
                 .collect();
+            for reduction in reductions.iter_mut() {
+                let position_action = risk_orchestrator.evaluate_position(&reduction.symbol);
+                if matches!(position_action, PositionAction::ForceExit { .. }) {
+                    reduction.urgency = ReductionUrgency::RiskExit;
+                }
+            }
+
+            // Downsize hedges the spot book could not unwind within their funding budget.
+            // Each step only sells what the book absorbs now, so it bypasses holding protection.
+            let held_positions: Vec<(String, Decimal)> = if trading_mode == TradingMode::Mock {
+                mock_client
+                    .get_delta_neutral_positions()
+                    .await
+                    .into_iter()
+                    .map(|p| (p.symbol, p.futures_qty))
+                    .collect()
+            } else {
+                venue_client
+                    .get_positions()
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|p| (p.symbol, p.position_amt))
+                    .collect()
+            };
+            for reduction in exit_liquidity_reductions(
+                venue_client,
+                &mut risk_orchestrator,
+                &held_positions,
+                &qualified_pairs,
+                &config,
+            )
+            .await
+            {
+                if reductions.iter().any(|r| r.symbol == reduction.symbol) {
+                    continue;
+                }
+                info!(
+                    "💧 [LIQUIDITY] {} exit too costly for the book, reducing ${:.2} -> ${:.2}",
+                    reduction.symbol, reduction.current_size_usdt, reduction.target_size_usdt
+                );
+                reductions.push(reduction);
+            }
+
+            // Lock in funding gains at milestones; recorded once the reduction fills
+            let mut take_profit_symbols: Vec<String> = Vec::new();
+            if config.take_profit.enabled {
+                let held_symbols: Vec<String> = held_positions
+                    .iter()
+                    .filter(|(_, qty)| !qty.is_zero())
+                    .map(|(symbol, _)| symbol.clone())
+                    .collect();
+                take_profit.retain_held(&held_symbols);
+                let prices =
+                    fetch_prices_for_symbols(venue_client, &mark_cache, &price_feed, &held_symbols)
+                        .await;
+                for reduction in take_profit_reductions(
+                    &mut take_profit,
+                    &risk_orchestrator,
+                    &held_positions,
+                    &prices,
+                    &qualified_pairs,
+                ) {
+                    if reductions.iter().any(|r| r.symbol == reduction.symbol) {
+                        continue;
+                    }
+                    take_profit_symbols.push(reduction.symbol.clone());
+                    reductions.push(reduction);
+                }
+            }
+
+            // Quarantines configured to exit close the whole position
+            let quarantine_exits: Vec<(String, Decimal)> = held_positions
+                .iter()
+                .filter(|(symbol, qty)| {
+                    !qty.is_zero()
+                        && risk_orchestrator.quarantine_requires_exit(symbol)
+                        && !reductions.iter().any(|r| &r.symbol == symbol)
+                })
+                .cloned()
+                .collect();
+            if !quarantine_exits.is_empty() {
+                let symbols: Vec<String> =
+                    quarantine_exits.iter().map(|(s, _)| s.clone()).collect();
+                let prices =
+                    fetch_prices_for_symbols(venue_client, &mark_cache, &price_feed, &symbols)
+                        .await;
+                for reduction in
+                    quarantine_exit_reductions(&quarantine_exits, &prices, &qualified_pairs)
+                {
+                    info!(
+                        "🚫 [QUARANTINE] Exiting {} (${:.2}) for persistent funding anomalies",
+                        reduction.symbol, reduction.current_size_usdt
+                    );
+                    reductions.push(reduction);
+                }
+            }
+
+            // Scheduled portfolio rebalance: capital moves from overweight held positions
+            // to underweight ones, through the same holding and cost gates
+            let mut portfolio_rebalance = PortfolioRebalance::default();
+            let now = Utc::now();
+            if portfolio_rebalancer.is_due(now) {
+                let entries_restricted = scan_stale
+                    || maintenance_state.blocks_entries()
+                    || persistence_watchdog.blocks_entries(now)
+                    || risk_orchestrator.entry_halt_rule().is_some()
+                    || risk_orchestrator.entry_throttle().is_some()
+                    || !matches!(event_calendar.restriction_at(now), EntryRestriction::Open);
+                if entries_restricted {
+                    debug!("⏸️  [PORTFOLIO] Rebalance due but entries are restricted - retrying next cycle");
+                } else {
+                    portfolio_rebalancer.mark_run(now);
+                    let targets = allocator.calculate_allocation(
+                        &qualified_pairs,
+                        mock_state.balance,
+                        &HashMap::new(),
+                    );
+                    let mut planned = portfolio_rebalancer.plan(&targets, &current_positions);
+                    let best_growth_8h = planned
+                        .grow
+                        .iter()
+                        .map(|alloc| rate_per_8h(&alloc.symbol, alloc.funding_rate))
+                        .max()
+                        .unwrap_or(Decimal::ZERO);
+                    planned
+                        .grow
+                        .retain(|alloc| !reductions.iter().any(|r| r.symbol == alloc.symbol));
+                    planned.shrink.retain(|shrink| {
+                        if reductions.iter().any(|r| r.symbol == shrink.symbol) {
+                            return false;
+                        }
+                        if let Some(tracked) = risk_orchestrator.get_tracked_position(&shrink.symbol) {
+                            if tracked.is_within_holding_period(config.risk.min_holding_period_hours) {
+                                info!(
+                                    "🛡️  [PORTFOLIO] {} within {}h holding period (opened {:.1}h ago) - not shrinking",
+                                    shrink.symbol,
+                                    config.risk.min_holding_period_hours,
+                                    tracked.hours_open()
+                                );
+                                return false;
+                            }
+                        }
+                        let rate_gain =
+                            best_growth_8h - rate_per_8h(&shrink.symbol, shrink.funding_rate);
+                        let estimate = rebalancer.reduction_estimate(shrink.reduction_usdt, rate_gain);
+                        if !rebalancer.is_worthwhile(&estimate) {
+                            info!(
+                                "💸 [PORTFOLIO] {} shift of ${:.2} skipped: {}",
+                                shrink.symbol, shrink.reduction_usdt, estimate
+                            );
+                            return false;
+                        }
+                        true
+                    });
+                    if planned.is_empty() {
+                        info!(
+                            "⚖️  [PORTFOLIO] Held positions near target weights - nothing to shift"
+                        );
+                    } else {
+                        info!(
+                            "⚖️  [PORTFOLIO] Shifting up to ${:.2} from {} overweight to {} underweight position(s)",
+                            planned.shrink.iter().map(|s| s.reduction_usdt).sum::<Decimal>(),
+                            planned.shrink.len(),
+                            planned.grow.len()
+                        );
+                        for alloc in &planned.grow {
+                            info!(
+                                "   {} | Add: ${:.2} | Funding: {:.4}%",
+                                alloc.symbol,
+                                alloc.target_size_usdt,
+                                alloc.funding_rate * dec!(100)
+                            );
+                        }
+                        reductions.extend(planned.shrink.iter().cloned());
+                        portfolio_rebalance = planned;
+                    }
+                }
+            }
 
+            // One batch per cycle: most urgent first, small discretionary reductions wait
+            let plan = ReductionPlan::build(reductions, config.capital.min_reduction_usdt);
+            for reduction in &plan.deferred {
+                debug!(
+                    "⏸️  [REDUCE] {} {} reduction of ${:.2} below ${:.2} minimum - deferred",
+                    reduction.symbol,
+                    reduction.urgency,
+                    reduction.reduction_usdt,
+                    config.capital.min_reduction_usdt
+                );
+            }
+            let reductions = plan.batch;
+            let mut portfolio_freed = Decimal::ZERO;
             if !reductions.is_empty() {
-                info!("📉 [REDUCE] {} positions need reduction", reductions.len());
+                info!(
+                    "📉 [REDUCE] {} positions need reduction (${:.2} total)",
+                    reductions.len(),
+                    reductions.iter().map(|r| r.reduction_usdt).sum::<Decimal>()
+                );
                 for reduction in &reductions {
                     info!(
-                        "   {} | Current: ${:.2} | Target: ${:.2} | Reduce: ${:.2}",
+                        "   {} | {} | Current: ${:.2} | Target: ${:.2} | Reduce: ${:.2}",
                         reduction.symbol,
+                        reduction.urgency,
                         reduction.current_size_usdt,
                         reduction.target_size_usdt,
                         reduction.reduction_usdt
@@ -984,7 +3155,13 @@ async fn main() -> Result<()> {
                     // This fixes orphaned positions where the symbol no longer qualifies
                     let reduction_symbols: Vec<String> =
                         reductions.iter().map(|r| r.symbol.clone()).collect();
-                    let prices = fetch_prices_for_symbols(&real_client, &reduction_symbols).await;
+                    let prices = fetch_prices_for_symbols(
+                        venue_client,
+                        &mark_cache,
+                        &price_feed,
+                        &reduction_symbols,
+                    )
+                    .await;
 
                     for reduction in &reductions {
                         let price = match prices.get(&reduction.symbol).copied() {
@@ -1037,22 +3214,24 @@ async fn main() -> Result<()> {
                             new_client_order_id: None,
                         };
 
-                        match mock_client.place_futures_order(&futures_order).await {
-                            Ok(_) => {
-                                info!(
-                                    "✅ [REDUCE] Reduced futures position for {}",
-                                    reduction.symbol
-                                );
-                            }
-                            Err(e) => {
-                                error!(
-                                    "❌ [REDUCE] Failed to reduce futures for {}: {}",
-                                    reduction.symbol, e
-                                );
-                                metrics.errors_count += 1;
-                                continue;
-                            }
-                        }
+                        let futures_fill =
+                            match mock_client.place_futures_order(&futures_order).await {
+                                Ok(response) => {
+                                    info!(
+                                        "✅ [REDUCE] Reduced futures position for {}",
+                                        reduction.symbol
+                                    );
+                                    response
+                                }
+                                Err(e) => {
+                                    error!(
+                                        "❌ [REDUCE] Failed to reduce futures for {}: {}",
+                                        reduction.symbol, e
+                                    );
+                                    metrics.errors_count += 1;
+                                    continue;
+                                }
+                            };
 
                         // Close matching spot position
                         let spot_close_side = if is_short {
@@ -1076,29 +3255,59 @@ async fn main() -> Result<()> {
                             time_in_force: None,
                             is_isolated: Some(false),
                             side_effect_type: Some(side_effect),
+                            new_client_order_id: None,
                         };
 
-                        match mock_client.place_margin_order(&spot_order).await {
-                            Ok(_) => {
+                        let spot_fill = match mock_client.place_margin_order(&spot_order).await {
+                            Ok(response) => {
                                 info!(
                                     "✅ [REDUCE] Reduced spot position for {}",
                                     reduction.spot_symbol
                                 );
                                 metrics.rebalances_triggered += 1;
+                                Some(response)
                             }
                             Err(e) => {
                                 warn!("⚠️  [REDUCE] Spot reduction failed for {}: {} (delta drift may occur)",
                                     reduction.spot_symbol, e);
+                                None
+                            }
+                        };
+                        record_execution_quality(
+                            &persistence,
+                            &mut entry_cost_model,
+                            ExecutionKind::Exit,
+                            &reduction.symbol,
+                            price,
+                            &[
+                                ("futures", Some(&futures_fill)),
+                                ("spot", spot_fill.as_ref()),
+                            ],
+                        );
+                        if take_profit_symbols.contains(&reduction.symbol) {
+                            if let Some(tracked) =
+                                risk_orchestrator.get_tracked_position(&reduction.symbol)
+                            {
+                                take_profit.record_take(tracked);
                             }
                         }
+                        if reduction.urgency == ReductionUrgency::PortfolioRebalance {
+                            portfolio_freed += reduction.reduction_usdt;
+                        }
                     }
                 } else {
                     // LIVE TRADING: Execute reductions
                     // Fetch prices for reduction symbols (not qualified_pairs) to handle orphaned positions
                     let reduction_symbols: Vec<String> =
                         reductions.iter().map(|r| r.symbol.clone()).collect();
-                    let prices = fetch_prices_for_symbols(&real_client, &reduction_symbols).await;
-                    let positions = real_client.get_positions().await.unwrap_or_default();
+                    let prices = fetch_prices_for_symbols(
+                        venue_client,
+                        &mark_cache,
+                        &price_feed,
+                        &reduction_symbols,
+                    )
+                    .await;
+                    let positions = venue_client.get_positions().await.unwrap_or_default();
 
                     for reduction in &reductions {
                         let price = prices
@@ -1120,13 +3329,34 @@ async fn main() -> Result<()> {
                             .unwrap_or(Decimal::ZERO);
 
                         match executor
-                            .reduce_position(&real_client, reduction, price, futures_position)
+                            .reduce_position(venue_client, reduction, price, futures_position)
                             .await
                         {
                             Ok(result) => {
+                                record_execution_quality(
+                                    &persistence,
+                                    &mut entry_cost_model,
+                                    ExecutionKind::Exit,
+                                    &reduction.symbol,
+                                    price,
+                                    &[
+                                        ("futures", result.futures_order.as_ref()),
+                                        ("spot", result.spot_order.as_ref()),
+                                    ],
+                                );
                                 if result.success {
                                     info!("✅ [REDUCE] Reduced position for {}", result.symbol);
                                     metrics.rebalances_triggered += 1;
+                                    if reduction.urgency == ReductionUrgency::PortfolioRebalance {
+                                        portfolio_freed += reduction.reduction_usdt;
+                                    }
+                                    if take_profit_symbols.contains(&reduction.symbol) {
+                                        if let Some(tracked) = risk_orchestrator
+                                            .get_tracked_position(&reduction.symbol)
+                                        {
+                                            take_profit.record_take(tracked);
+                                        }
+                                    }
                                 } else {
                                     error!(
                                         "❌ [REDUCE] Failed to reduce {}: {:?}",
@@ -1143,20 +3373,85 @@ async fn main() -> Result<()> {
                     }
                 }
             }
-        }
-
-        // ═══════════════════════════════════════════════════════════════
-        // PHASE 5: Hedge Rebalancing
-        // ═══════════════════════════════════════════════════════════════
-        if trading_mode == TradingMode::Mock {
-            let positions = mock_client.get_delta_neutral_positions().await;
-            if !positions.is_empty() {
-                debug!(
-                    "⚖️  [REBALANCE] Checking {} positions for delta drift",
-                    positions.len()
-                );
 
-                let funding_rates: HashMap<String, Decimal> = qualified_pairs
+            // Portfolio top-ups, funded by the overweight positions just reduced
+            let top_ups = portfolio_rebalance.funded_growth(
+                portfolio_freed,
+                config.capital.portfolio_rebalance.min_shift_usdt,
+            );
+            if !top_ups.is_empty() {
+                let top_up_client: &dyn ExchangeClient = if trading_mode == TradingMode::Mock {
+                    &mock_client
+                } else {
+                    venue_client
+                };
+                let top_up_symbols: Vec<String> =
+                    top_ups.iter().map(|alloc| alloc.symbol.clone()).collect();
+                let prices = fetch_prices_for_symbols(
+                    venue_client,
+                    &mark_cache,
+                    &price_feed,
+                    &top_up_symbols,
+                )
+                .await;
+                for alloc in &top_ups {
+                    let Some(price) = prices
+                        .get(&alloc.symbol)
+                        .copied()
+                        .filter(|p| *p > Decimal::ZERO)
+                    else {
+                        warn!(
+                            "⚠️  [PORTFOLIO] No valid price for {} - skipping top-up",
+                            alloc.symbol
+                        );
+                        continue;
+                    };
+                    match executor.enter_position(top_up_client, alloc, price).await {
+                        Ok(result) if result.success => {
+                            info!(
+                                "✅ [PORTFOLIO] Added ${:.2} to {}",
+                                alloc.target_size_usdt, alloc.symbol
+                            );
+                            record_execution_quality(
+                                &persistence,
+                                &mut entry_cost_model,
+                                ExecutionKind::Entry,
+                                &alloc.symbol,
+                                price,
+                                &[
+                                    ("futures", result.futures_order.as_ref()),
+                                    ("spot", result.spot_order.as_ref()),
+                                ],
+                            );
+                        }
+                        Ok(result) => {
+                            error!(
+                                "❌ [PORTFOLIO] Failed to add to {}: {:?}",
+                                alloc.symbol, result.error
+                            );
+                            metrics.errors_count += 1;
+                        }
+                        Err(e) => {
+                            error!("❌ [PORTFOLIO] Error adding to {}: {}", alloc.symbol, e);
+                            metrics.errors_count += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        // ═══════════════════════════════════════════════════════════════
+        // PHASE 5: Hedge Rebalancing
+        // ═══════════════════════════════════════════════════════════════
+        if trading_mode == TradingMode::Mock {
+            let positions = mock_client.get_delta_neutral_positions().await;
+            if !positions.is_empty() {
+                debug!(
+                    "⚖️  [REBALANCE] Checking {} positions for delta drift",
+                    positions.len()
+                );
+
+                let funding_rates: HashMap<String, Decimal> = qualified_pairs
                     .iter()
                     .map(|p| (p.symbol.clone(), p.funding_rate))
                     .collect();
@@ -1164,7 +3459,13 @@ async fn main() -> Result<()> {
                 // to properly rebalance orphaned positions
                 let position_symbols: Vec<String> =
                     positions.iter().map(|p| p.symbol.clone()).collect();
-                let prices = fetch_prices_for_symbols(&real_client, &position_symbols).await;
+                let prices = fetch_prices_for_symbols(
+                    venue_client,
+                    &mark_cache,
+                    &price_feed,
+                    &position_symbols,
+                )
+                .await;
 
                 // Collect positions that need to be closed due to funding direction flip
                 let mut flip_positions_to_close: Vec<String> = Vec::new();
@@ -1209,6 +3510,7 @@ async fn main() -> Result<()> {
                                     side_effect_type: Some(
                                         funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
                                     ),
+                                    new_client_order_id: None,
                                 };
 
                                 match mock_client.place_margin_order(&order).await {
@@ -1329,6 +3631,7 @@ async fn main() -> Result<()> {
                                         side_effect_type: Some(
                                             funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
                                         ),
+                                        new_client_order_id: None,
                                     };
 
                                     match mock_client.place_margin_order(&spot_order).await {
@@ -1346,7 +3649,12 @@ async fn main() -> Result<()> {
                                 if close_success {
                                     info!("✅ [CLOSE] Position {} fully closed via rebalance", symbol);
                                     // Remove from position tracker
-                                    risk_orchestrator.close_position(symbol);
+                                    close_tracked_position(
+                                        &mut risk_orchestrator,
+                                        &persistence,
+                                        symbol,
+                                        ExitKind::Routine,
+                                    );
                                 } else {
                                     error!("❌ [CLOSE] Position {} close incomplete - manual intervention may be needed", symbol);
                                 }
@@ -1408,6 +3716,7 @@ async fn main() -> Result<()> {
                                 side_effect_type: Some(
                                     funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
                                 ),
+                                new_client_order_id: None,
                             };
 
                             if let Err(e) = mock_client.place_margin_order(&spot_order).await {
@@ -1417,9 +3726,17 @@ async fn main() -> Result<()> {
                         }
 
                         if close_success {
-                            info!("✅ [FLIP] Closed {} - scanner will re-enter with new direction", symbol);
+                            info!(
+                                "✅ [FLIP] Closed {} - scanner may re-enter with the new direction after the cool-down",
+                                symbol
+                            );
                             // Remove from tracking
-                            risk_orchestrator.close_position(symbol);
+                            close_tracked_position(
+                                &mut risk_orchestrator,
+                                &persistence,
+                                symbol,
+                                ExitKind::Routine,
+                            );
                         } else {
                             metrics.errors_count += 1;
                         }
@@ -1428,19 +3745,112 @@ async fn main() -> Result<()> {
             }
         }
 
+        // Orders of this instance left open past the order timeout are cancelled
+        if trading_mode == TradingMode::Live && venue == Venue::Binance {
+            match order_tracker.poll(&real_client, loop_start).await {
+                Ok(cancelled) if !cancelled.is_empty() => {
+                    warn!(
+                        "🧹 [ORDERS] Cancelled {} order(s) open longer than {}s",
+                        cancelled.len(),
+                        config.execution.order_timeout_secs
+                    );
+                    let hedges = executor
+                        .rehedge_cancelled_orders(&real_client, &cancelled)
+                        .await;
+                    if !hedges.is_empty() {
+                        warn!(
+                            "🛡️  [ORDERS] Re-submitted {} hedge(s) of cancelled futures legs",
+                            hedges.len()
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️  [ORDERS] Failed to poll open orders: {}", e),
+            }
+        }
+
+        // Checkpoint entries, reductions and rebalancing trades from this cycle
+        if trading_mode == TradingMode::Mock {
+            if let Err(e) = checkpoint_mock_state(
+                &persistence,
+                &mock_client,
+                &mut state_tracker,
+                &mut persistence_watchdog,
+                last_funding_period,
+                false,
+            )
+            .await
+            {
+                warn!("⚠️  [PERSISTENCE] Failed to checkpoint state: {}", e);
+            }
+        }
+
         // ═══════════════════════════════════════════════════════════════
         // PHASE 6: Funding Collection & Verification
         // ═══════════════════════════════════════════════════════════════
         // Use funding period ID to prevent double-collection across restarts
         let now = Utc::now();
-        let current_hour = now.hour();
-        let is_funding_hour = current_hour == 0 || current_hour == 8 || current_hour == 16;
+        let held_qty: HashMap<String, Decimal> = mock_client
+            .get_delta_neutral_positions()
+            .await
+            .into_iter()
+            .map(|p| (p.symbol, p.futures_qty))
+            .collect();
+        let held_symbols: Vec<String> = held_qty.keys().cloned().collect();
+        for ledger in funding_capture.retain_held(&held_symbols) {
+            info!(
+                "🎯 [CAPTURE] {} closed | {}",
+                ledger.symbol,
+                describe_capture(&ledger)
+            );
+        }
+        let settling: Vec<String> = funding_calendar
+            .settling_symbols(venue, &held_symbols, now)
+            .into_iter()
+            .cloned()
+            .collect();
+        let is_funding_hour =
+            !settling.is_empty() || FundingCalendar::venue_default(venue).is_settlement_hour(now);
         let current_funding_period = get_funding_period_id(now);
 
         if is_funding_hour && last_funding_period != Some(current_funding_period) {
+            // Project this settlement of each held position; it is counted as
+            // income only once the exchange confirms the payment
+            let due: Vec<String> = if trading_mode == TradingMode::Mock {
+                settling.clone()
+            } else {
+                let tracked: Vec<String> = risk_orchestrator
+                    .get_all_tracked_positions()
+                    .iter()
+                    .map(|p| p.symbol.clone())
+                    .collect();
+                funding_calendar
+                    .settling_symbols(venue, &tracked, now)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            };
+            expect_settlements(
+                &mut funding_settlements,
+                &risk_orchestrator,
+                &persistence,
+                &funding_calendar,
+                venue,
+                &due,
+                now,
+            );
+
             if trading_mode == TradingMode::Mock {
                 info!("💸 [FUNDING] Collecting funding payments...");
-                let per_position_funding = mock_client.collect_funding().await;
+                let per_position_funding = mock_client.collect_funding_for(&settling).await;
+                let mark_prices = fetch_mark_prices(venue_client, &mark_cache, &settling).await;
+                for (symbol, amount) in &per_position_funding {
+                    if let (Some(&qty), Some(&mark)) =
+                        (held_qty.get(symbol), mark_prices.get(symbol))
+                    {
+                        funding_capture.record_settlement(symbol, *amount, qty, mark);
+                    }
+                }
                 let total_funding: Decimal = per_position_funding.values().sum();
                 info!(
                     "💸 [FUNDING] Received: ${:.4} across {} positions",
@@ -1449,43 +3859,74 @@ async fn main() -> Result<()> {
                 );
                 metrics.funding_collections += 1;
 
-                // Verify funding for each position using actual per-position data
-                for (symbol, actual_funding) in &per_position_funding {
-                    if risk_orchestrator.get_tracked_position(symbol).is_some() {
-                        // Record and verify funding with actual per-position amount
-                        risk_orchestrator.record_funding(symbol, *actual_funding);
-                        let verification =
-                            risk_orchestrator.verify_funding(symbol, *actual_funding);
-
-                        if verification.is_anomaly {
-                            warn!(
-                                "⚠️  [FUNDING] Anomaly for {}: expected ${:.4}, got ${:.4} ({:.1}% deviation)",
-                                symbol,
-                                verification.funding_expected,
-                                verification.funding_received,
-                                verification.deviation_pct * dec!(100)
-                            );
-                        }
-                    }
-                }
+                // The mock ledger is the exchange: its payments confirm the settlements
+                let payments: Vec<(String, DateTime<Utc>, Decimal)> = per_position_funding
+                    .iter()
+                    .map(|(symbol, amount)| {
+                        let settled_at = funding_calendar
+                            .schedule(venue, symbol)
+                            .last_settlement(now);
+                        (symbol.clone(), settled_at, *amount)
+                    })
+                    .collect();
+                let confirmed = funding_settlements.confirm(&payments);
+                apply_confirmed_funding(confirmed, &mut risk_orchestrator, &persistence);
+                report_funding_capture(&funding_capture);
             }
             // Update funding period BEFORE saving state (ensures it's persisted)
             last_funding_period = Some(current_funding_period);
 
             // Save state after funding collection (critical checkpoint)
             if trading_mode == TradingMode::Mock {
-                let mut state_to_save = mock_client.export_state().await;
-                state_to_save.last_funding_period = last_funding_period;
-                if let Err(e) = persistence.save_state(&state_to_save) {
-                    warn!(
+                match checkpoint_mock_state(
+                    &persistence,
+                    &mock_client,
+                    &mut state_tracker,
+                    &mut persistence_watchdog,
+                    last_funding_period,
+                    true,
+                )
+                .await
+                {
+                    Ok(_) => debug!(
+                        "💾 [PERSISTENCE] State saved after funding collection (period {})",
+                        current_funding_period
+                    ),
+                    Err(e) => warn!(
                         "⚠️  [PERSISTENCE] Failed to save state after funding: {}",
                         e
-                    );
-                } else {
-                    debug!("💾 [PERSISTENCE] State saved after funding collection (period {})", current_funding_period);
+                    ),
+                }
+            }
+        }
+
+        // Live settlements are confirmed by the exchange's funding income history
+        if trading_mode != TradingMode::Mock && !funding_settlements.pending().is_empty() {
+            let poll_due = last_confirmation_poll.is_none_or(|at| {
+                now - at >= chrono::Duration::minutes(FUNDING_CONFIRMATION_POLL_MINUTES)
+            });
+            if let (true, Some(oldest)) = (poll_due, funding_settlements.oldest()) {
+                last_confirmation_poll = Some(now);
+                match fetch_funding_payments(venue_client, oldest - chrono::Duration::hours(1))
+                    .await
+                {
+                    Ok(payments) => {
+                        let confirmed = funding_settlements.confirm(&payments);
+                        apply_confirmed_funding(confirmed, &mut risk_orchestrator, &persistence);
+                    }
+                    Err(e) => warn!("⚠️  [FUNDING] Failed to fetch funding income: {}", e),
                 }
             }
         }
+        for gap in funding_settlements.newly_overdue(now) {
+            warn!(
+                "🧾 [FUNDING] {} settlement at {} not confirmed after {}m - projected ${:.4} not counted",
+                gap.symbol,
+                display_tz.short(gap.settled_at),
+                FUNDING_CONFIRMATION_GRACE_MINUTES,
+                gap.expected
+            );
+        }
 
         // Accrue interest periodically
         if trading_mode == TradingMode::Mock {
@@ -1496,6 +3937,31 @@ async fn main() -> Result<()> {
             for (symbol, interest) in &per_position_interest {
                 risk_orchestrator.record_interest(symbol, *interest);
             }
+
+            if !per_position_interest.is_empty() {
+                let borrowed: HashMap<String, Decimal> = mock_client
+                    .get_delta_neutral_positions()
+                    .await
+                    .into_iter()
+                    .map(|p| (p.symbol, p.borrowed_amount * p.spot_entry_price))
+                    .collect();
+                for (symbol, interest) in per_position_interest {
+                    let pending = pending_interest.entry(symbol.clone()).or_default();
+                    pending.0 += interest;
+                    pending.1 = borrowed.get(&symbol).copied().unwrap_or(pending.1);
+                }
+            }
+            if loop_start - interest_recorded_at >= chrono::Duration::hours(1) {
+                interest_recorded_at = loop_start;
+                for (symbol, (interest, borrowed_usd)) in pending_interest.drain() {
+                    let borrowed_usd = (borrowed_usd > Decimal::ZERO).then_some(borrowed_usd);
+                    if let Err(e) =
+                        persistence.record_interest_event(&symbol, interest, borrowed_usd)
+                    {
+                        warn!("⚠️  [PERSISTENCE] Failed to record interest event: {}", e);
+                    }
+                }
+            }
         }
 
         // ═══════════════════════════════════════════════════════════════
@@ -1508,26 +3974,47 @@ async fn main() -> Result<()> {
 
             // Build position list for risk checks
             let positions = mock_client.get_delta_neutral_positions().await;
-            let exchange_positions: Vec<funding_fee_farmer::exchange::Position> = positions
+            let mut exchange_positions: Vec<funding_fee_farmer::exchange::Position> = positions
                 .iter()
                 .map(|p| funding_fee_farmer::exchange::Position {
                     symbol: p.symbol.clone(),
                     position_amt: p.futures_qty,
                     entry_price: p.futures_entry_price,
-                    unrealized_profit: p.funding_pnl - p.interest_paid, // Net PnL
+                    unrealized_profit: Decimal::ZERO,
                     leverage: 5,
-                    notional: p.futures_entry_price * p.futures_qty.abs(),
+                    notional: p.futures_entry_price * p.futures_qty,
                     isolated_margin: Decimal::ZERO,
-                    mark_price: p.futures_entry_price, // Simplified
+                    mark_price: p.futures_entry_price,
                     liquidation_price: Decimal::ZERO,
                     position_side: funding_fee_farmer::exchange::PositionSide::Both,
                     margin_type: funding_fee_farmer::exchange::MarginType::Cross,
                 })
                 .collect();
 
+            // Revalue at real mark prices so margin health reflects the market
+            let position_symbols: Vec<String> =
+                positions.iter().map(|p| p.symbol.clone()).collect();
+            let mark_prices = fetch_mark_prices(venue_client, &mark_cache, &position_symbols).await;
+            MarginMonitor::apply_mark_prices(&mut exchange_positions, &mark_prices);
+
+            // Residual delta left by drift and partial fills, per symbol and book-wide
+            metrics.residual_delta =
+                ExposureReport::from_positions(&positions, &mark_prices, &config.hedge);
+            debug!(
+                "⚖️  [EXPOSURE] Residual delta net ${:.2} / gross ${:.2} (${:.2} beyond targets), {} positions",
+                metrics.residual_delta.net_delta_usd,
+                metrics.residual_delta.gross_delta_usd,
+                metrics.residual_delta.net_drift_usd,
+                positions.len()
+            );
+            if let Some(alert) = risk_orchestrator.check_residual_delta(&metrics.residual_delta) {
+                warn!("⚖️  [EXPOSURE] {}", alert.message);
+                risk_orchestrator.raise(alert);
+            }
+
             // Run comprehensive risk check
-            // Mock mode: use default maintenance rate since we don't have real leverage brackets
-            let maintenance_rates: HashMap<String, Decimal> = HashMap::new();
+            let maintenance_rates =
+                MarginMonitor::build_maintenance_rate_map(&cached_brackets, &exchange_positions);
             let risk_result = risk_orchestrator.check_all(
                 &exchange_positions,
                 total_equity,
@@ -1535,6 +4022,24 @@ async fn main() -> Result<()> {
                 &maintenance_rates,
             );
 
+            // Deploy less while margin headroom is tight, more as it returns
+            if config.capital.dynamic_utilization.enabled {
+                utilization.observe_prices(&mark_prices, Utc::now());
+                let gross_notional: Decimal =
+                    exchange_positions.iter().map(|p| p.notional.abs()).sum();
+                let leverage = if total_equity > Decimal::ZERO {
+                    gross_notional / total_equity
+                } else {
+                    Decimal::ZERO
+                };
+                let decision = utilization.update(risk_result.margin_health, leverage);
+                if decision.changed() {
+                    info!("🎚️  [UTILIZATION] {}", decision);
+                } else {
+                    debug!("🎚️  [UTILIZATION] {}", decision);
+                }
+            }
+
             // Check for drawdown warnings
             let drawdown_stats = risk_orchestrator.get_drawdown_stats();
             let max_drawdown = config.risk.max_drawdown;
@@ -1555,72 +4060,16 @@ async fn main() -> Result<()> {
                     // Within 5% of limit (95% threshold)
                     warn!("🚨 Drawdown at 95% of limit - reducing all positions by 25%");
 
-                    for pos in &positions {
-                        if pos.futures_qty.abs() < dec!(0.0001) {
-                            continue; // Skip positions with negligible size
-                        }
-
-                        let reduce_qty = pos.futures_qty.abs() * dec!(0.25);
-
-                        // Close 25% of futures position
-                        let futures_side = if pos.futures_qty > Decimal::ZERO {
-                            funding_fee_farmer::exchange::OrderSide::Sell
-                        } else {
-                            funding_fee_farmer::exchange::OrderSide::Buy
-                        };
-
-                        let futures_order = funding_fee_farmer::exchange::NewOrder {
-                            symbol: pos.symbol.clone(),
-                            side: futures_side,
-                            position_side: None,
-                            order_type: funding_fee_farmer::exchange::OrderType::Market,
-                            quantity: Some(reduce_qty),
-                            price: None,
-                            time_in_force: None,
-                            reduce_only: Some(true),
-                            new_client_order_id: None,
-                        };
-
-                        if let Err(e) = mock_client.place_futures_order(&futures_order).await {
-                            error!(
-                                "❌ Failed to reduce futures position for {}: {}",
-                                pos.symbol, e
-                            );
-                        } else {
-                            info!("✅ Reduced futures position for {} by 25%", pos.symbol);
-                        }
-
-                        // Close 25% of spot position
-                        if pos.spot_qty.abs() >= dec!(0.0001) {
-                            let spot_side = if pos.spot_qty > Decimal::ZERO {
-                                funding_fee_farmer::exchange::OrderSide::Sell
-                            } else {
-                                funding_fee_farmer::exchange::OrderSide::Buy
-                            };
-
-                            let spot_order = funding_fee_farmer::exchange::MarginOrder {
-                                symbol: pos.spot_symbol.clone(),
-                                side: spot_side,
-                                order_type: funding_fee_farmer::exchange::OrderType::Market,
-                                quantity: Some(pos.spot_qty.abs() * dec!(0.25)),
-                                price: None,
-                                time_in_force: None,
-                                is_isolated: Some(false),
-                                side_effect_type: Some(
-                                    funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
-                                ),
-                            };
-
-                            if let Err(e) = mock_client.place_margin_order(&spot_order).await {
-                                error!(
-                                    "❌ Failed to reduce spot position for {}: {}",
-                                    pos.spot_symbol, e
-                                );
-                            } else {
-                                info!("✅ Reduced spot position for {} by 25%", pos.spot_symbol);
-                            }
-                        }
-                    }
+                    let directive = ReductionDirective::all(dec!(0.25), "drawdown at 95% of limit");
+                    execute_reduction(
+                        &risk_actions,
+                        &mock_client,
+                        &positions,
+                        &directive,
+                        &persistence,
+                        &mut metrics,
+                    )
+                    .await;
                 } else if distance_pct <= dec!(0.10) {
                     // Within 10% of limit (90% threshold)
                     warn!("⚠️  Drawdown at 90% of limit - stopping new positions");
@@ -1648,6 +4097,7 @@ async fn main() -> Result<()> {
 
                             // Automatic position reduction for margin health warnings
                             let reduction_pct = match health {
+                                _ if alert.action < AlertAction::AutoReduce => None,
                                 MarginHealth::Red => Some(dec!(0.50)), // 50% reduction for critical
                                 MarginHealth::Orange => Some(dec!(0.25)), // 25% reduction for warning
                                 _ => None,
@@ -1656,83 +4106,19 @@ async fn main() -> Result<()> {
                             if let Some(pct) = reduction_pct {
                                 info!("🤖 [AUTO-REDUCE] Executing {}% reduction for all positions due to {:?} margin health",
                                     pct * dec!(100), health);
-
-                                for pos in &positions {
-                                    if pos.futures_qty.abs() < dec!(0.0001) {
-                                        continue;
-                                    }
-
-                                    let reduce_qty = pos.futures_qty.abs() * pct;
-
-                                    // Reduce futures
-                                    let futures_side = if pos.futures_qty > Decimal::ZERO {
-                                        funding_fee_farmer::exchange::OrderSide::Sell
-                                    } else {
-                                        funding_fee_farmer::exchange::OrderSide::Buy
-                                    };
-
-                                    let futures_order = funding_fee_farmer::exchange::NewOrder {
-                                        symbol: pos.symbol.clone(),
-                                        side: futures_side,
-                                        position_side: None,
-                                        order_type: funding_fee_farmer::exchange::OrderType::Market,
-                                        quantity: Some(reduce_qty),
-                                        price: None,
-                                        time_in_force: None,
-                                        reduce_only: Some(true),
-                                        new_client_order_id: None,
-                                    };
-
-                                    match mock_client.place_futures_order(&futures_order).await {
-                                        Ok(_) => {
-                                            info!(
-                                                "✅ [AUTO-REDUCE] Reduced futures {} by {}%",
-                                                pos.symbol,
-                                                pct * dec!(100)
-                                            );
-                                            metrics.rebalances_triggered += 1;
-                                        }
-                                        Err(e) => {
-                                            error!("❌ [AUTO-REDUCE] Futures reduction failed for {}: {}", pos.symbol, e);
-                                            metrics.errors_count += 1;
-                                        }
-                                    }
-
-                                    // Reduce spot
-                                    if pos.spot_qty.abs() >= dec!(0.0001) {
-                                        let spot_side = if pos.spot_qty > Decimal::ZERO {
-                                            funding_fee_farmer::exchange::OrderSide::Sell
-                                        } else {
-                                            funding_fee_farmer::exchange::OrderSide::Buy
-                                        };
-
-                                        let spot_order = funding_fee_farmer::exchange::MarginOrder {
-                                            symbol: pos.spot_symbol.clone(),
-                                            side: spot_side,
-                                            order_type: funding_fee_farmer::exchange::OrderType::Market,
-                                            quantity: Some(pos.spot_qty.abs() * pct),
-                                            price: None,
-                                            time_in_force: None,
-                                            is_isolated: Some(false),
-                                            side_effect_type: Some(funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay),
-                                        };
-
-                                        if let Err(e) =
-                                            mock_client.place_margin_order(&spot_order).await
-                                        {
-                                            error!(
-                                                "❌ [AUTO-REDUCE] Spot reduction failed for {}: {}",
-                                                pos.spot_symbol, e
-                                            );
-                                        } else {
-                                            info!(
-                                                "✅ [AUTO-REDUCE] Reduced spot {} by {}%",
-                                                pos.spot_symbol,
-                                                pct * dec!(100)
-                                            );
-                                        }
-                                    }
-                                }
+                                let directive = ReductionDirective::all(
+                                    pct,
+                                    format!("{:?} margin health", health),
+                                );
+                                execute_reduction(
+                                    &risk_actions,
+                                    &mock_client,
+                                    &positions,
+                                    &directive,
+                                    &persistence,
+                                    &mut metrics,
+                                )
+                                .await;
                             }
                         }
                         RiskAlertType::PositionLoss {
@@ -1763,90 +4149,31 @@ async fn main() -> Result<()> {
                                 LiquidationAction::ReducePosition {
                                     symbol,
                                     reduction_pct,
-                                } => {
+                                } if alert.action >= AlertAction::AutoReduce => {
                                     info!(
                                         "🤖 [AUTO-REDUCE] Executing {}% reduction for {}",
                                         reduction_pct * dec!(100),
                                         symbol
                                     );
 
-                                    if let Some(pos) =
-                                        positions.iter().find(|p| &p.symbol == symbol)
-                                    {
-                                        let reduce_qty = pos.futures_qty.abs() * *reduction_pct;
-
-                                        if reduce_qty >= dec!(0.0001) {
-                                            // Close portion of futures
-                                            let futures_side = if pos.futures_qty > Decimal::ZERO {
-                                                funding_fee_farmer::exchange::OrderSide::Sell
-                                            } else {
-                                                funding_fee_farmer::exchange::OrderSide::Buy
-                                            };
-
-                                            let futures_order = funding_fee_farmer::exchange::NewOrder {
-                                                symbol: symbol.clone(),
-                                                side: futures_side,
-                                                position_side: None,
-                                                order_type: funding_fee_farmer::exchange::OrderType::Market,
-                                                quantity: Some(reduce_qty),
-                                                price: None,
-                                                time_in_force: None,
-                                                reduce_only: Some(true),
-                                                new_client_order_id: None,
-                                            };
-
-                                            match mock_client
-                                                .place_futures_order(&futures_order)
-                                                .await
-                                            {
-                                                Ok(_) => {
-                                                    info!("✅ [AUTO-REDUCE] Reduced futures {} by {}%", symbol, reduction_pct * dec!(100));
-                                                    metrics.rebalances_triggered += 1;
-                                                }
-                                                Err(e) => {
-                                                    error!("❌ [AUTO-REDUCE] Futures reduction failed for {}: {}", symbol, e);
-                                                    metrics.errors_count += 1;
-                                                }
-                                            }
-
-                                            // Close matching spot position
-                                            let spot_reduce_qty =
-                                                pos.spot_qty.abs() * *reduction_pct;
-                                            if spot_reduce_qty >= dec!(0.0001) {
-                                                let spot_side = if pos.spot_qty > Decimal::ZERO {
-                                                    funding_fee_farmer::exchange::OrderSide::Sell
-                                                } else {
-                                                    funding_fee_farmer::exchange::OrderSide::Buy
-                                                };
-
-                                                let spot_order = funding_fee_farmer::exchange::MarginOrder {
-                                                    symbol: pos.spot_symbol.clone(),
-                                                    side: spot_side,
-                                                    order_type: funding_fee_farmer::exchange::OrderType::Market,
-                                                    quantity: Some(spot_reduce_qty),
-                                                    price: None,
-                                                    time_in_force: None,
-                                                    is_isolated: Some(false),
-                                                    side_effect_type: Some(funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay),
-                                                };
-
-                                                if let Err(e) = mock_client
-                                                    .place_margin_order(&spot_order)
-                                                    .await
-                                                {
-                                                    error!("❌ [AUTO-REDUCE] Spot reduction failed for {}: {}", pos.spot_symbol, e);
-                                                } else {
-                                                    info!(
-                                                        "✅ [AUTO-REDUCE] Reduced spot {} by {}%",
-                                                        pos.spot_symbol,
-                                                        reduction_pct * dec!(100)
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
+                                    let directive = ReductionDirective::symbol(
+                                        symbol.clone(),
+                                        *reduction_pct,
+                                        "liquidation risk",
+                                    );
+                                    execute_reduction(
+                                        &risk_actions,
+                                        &mock_client,
+                                        &positions,
+                                        &directive,
+                                        &persistence,
+                                        &mut metrics,
+                                    )
+                                    .await;
                                 }
-                                LiquidationAction::ClosePosition { symbol } => {
+                                LiquidationAction::ClosePosition { symbol }
+                                    if alert.action >= AlertAction::AutoReduce =>
+                                {
                                     warn!(
                                         "🤖 [AUTO-CLOSE] Position {} flagged for emergency close",
                                         symbol
@@ -1863,6 +4190,52 @@ async fn main() -> Result<()> {
                                 drift_pct * dec!(100)
                             );
                         }
+                        RiskAlertType::NotionalCapExceeded {
+                            scope,
+                            notional,
+                            cap,
+                        } => {
+                            warn!(
+                                "⚠️  [RISK] {} notional ${:.2} exceeds cap ${:.2}",
+                                scope, notional, cap
+                            );
+                        }
+                        RiskAlertType::FundingCollapse {
+                            symbol,
+                            entry_rate,
+                            predicted_rate,
+                        } => {
+                            warn!(
+                                "📉 [TREND] {} predicted funding {:.4}% vs {:.4}% at entry - consider exiting before settlement",
+                                symbol,
+                                predicted_rate * dec!(100),
+                                entry_rate * dec!(100)
+                            );
+                        }
+                        RiskAlertType::ExitLiquidity {
+                            symbol,
+                            exit_cost,
+                            budget,
+                        } => {
+                            warn!(
+                                "💧 [LIQUIDITY] {} exit would cost ${:.2} vs ${:.2} budget",
+                                symbol, exit_cost, budget
+                            );
+                        }
+                        // Raised outside the risk check (exposure, entry, scan and venue checks)
+                        RiskAlertType::ResidualDelta { .. }
+                        | RiskAlertType::InsufficientFunds { .. }
+                        | RiskAlertType::FundingSpreadOpportunity { .. }
+                        | RiskAlertType::HedgeSwitchRecommended { .. }
+                        | RiskAlertType::BorrowRateSpike { .. }
+                        | RiskAlertType::BorrowLimit { .. }
+                        | RiskAlertType::BorrowLimitExceeded { .. }
+                        | RiskAlertType::SettingsMismatch { .. }
+                        | RiskAlertType::ExchangeMaintenance { .. }
+                        | RiskAlertType::ExchangeOutage { .. }
+                        | RiskAlertType::ExchangeRecovered { .. }
+                        | RiskAlertType::ReserveNotRestored { .. }
+                        | RiskAlertType::PersistenceFailure { .. } => {}
                     }
                 }
             }
@@ -1872,14 +4245,14 @@ async fn main() -> Result<()> {
             // Without this, the mock client uses default $50,000 which causes massive fee errors
             if !risk_result.positions_to_close.is_empty() {
                 // Fetch current prices for positions to close
-                let close_symbols: Vec<String> = risk_result
-                    .positions_to_close
-                    .iter()
-                    .cloned()
-                    .collect();
+                let close_symbols: Vec<String> = risk_result.positions_to_close.to_vec();
 
-                // Get book tickers for accurate prices
-                if let Ok(tickers) = real_client.get_book_tickers().await {
+                // Get book tickers for accurate prices, streamed when fresh
+                let tickers = match price_feed.tickers(&close_symbols, Utc::now()) {
+                    Some(tickers) => Ok(tickers.into_values().collect()),
+                    None => venue_client.get_book_tickers().await,
+                };
+                if let Ok(tickers) = tickers {
                     let mut close_prices: HashMap<String, Decimal> = HashMap::new();
                     for ticker in tickers {
                         if close_symbols.contains(&ticker.symbol) {
@@ -1915,77 +4288,35 @@ async fn main() -> Result<()> {
                         symbol, pos.futures_qty, pos.spot_qty
                     );
 
-                    let mut close_success = true;
-                    let mut close_errors = Vec::new();
-
-                    // Step 1: Close futures leg
-                    if pos.futures_qty != Decimal::ZERO {
-                        let futures_side = if pos.futures_qty > Decimal::ZERO {
-                            funding_fee_farmer::exchange::OrderSide::Sell
-                        } else {
-                            funding_fee_farmer::exchange::OrderSide::Buy
-                        };
-
-                        let futures_order = funding_fee_farmer::exchange::NewOrder {
-                            symbol: pos.symbol.clone(),
-                            side: futures_side,
-                            position_side: None,
-                            order_type: funding_fee_farmer::exchange::OrderType::Market,
-                            quantity: Some(pos.futures_qty.abs()),
-                            price: None,
-                            time_in_force: None,
-                            reduce_only: Some(true),
-                            new_client_order_id: None,
-                        };
+                    let directive =
+                        ReductionDirective::symbol(symbol.clone(), Decimal::ONE, "risk closure");
+                    let close_errors: Vec<String> = risk_actions
+                        .execute(&mock_client, &positions, &directive, Some(&persistence))
+                        .await
+                        .into_iter()
+                        .flat_map(|result| result.errors)
+                        .collect();
 
-                        if let Err(e) = mock_client.place_futures_order(&futures_order).await {
-                            close_success = false;
-                            close_errors.push(format!("Futures: {}", e));
-                        }
-                    }
-
-                    // Step 2: Close spot leg
-                    if pos.spot_qty != Decimal::ZERO {
-                        let spot_side = if pos.spot_qty > Decimal::ZERO {
-                            funding_fee_farmer::exchange::OrderSide::Sell
-                        } else {
-                            funding_fee_farmer::exchange::OrderSide::Buy
-                        };
-
-                        let spot_order = funding_fee_farmer::exchange::MarginOrder {
-                            symbol: pos.spot_symbol.clone(),
-                            side: spot_side,
-                            order_type: funding_fee_farmer::exchange::OrderType::Market,
-                            quantity: Some(pos.spot_qty.abs()),
-                            price: None,
-                            time_in_force: None,
-                            is_isolated: Some(false),
-                            side_effect_type: Some(
-                                funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
-                            ),
-                        };
-
-                        if let Err(e) = mock_client.place_margin_order(&spot_order).await {
-                            close_success = false;
-                            close_errors.push(format!("Spot: {}", e));
-                        }
-                    }
-
-                    if close_success {
-                        info!("✅ [RISK] Successfully closed position {}", symbol);
-                        risk_orchestrator.close_position(symbol);
-                        metrics.positions_exited += 1;
-                    } else {
-                        error!(
-                            "❌ [RISK] Failed to close position {}: {}",
-                            symbol,
-                            close_errors.join("; ")
-                        );
-                        risk_orchestrator.record_error(&format!(
-                            "Position close failed for {}: {}",
-                            symbol,
-                            close_errors.join("; ")
-                        ));
+                    if close_errors.is_empty() {
+                        info!("✅ [RISK] Successfully closed position {}", symbol);
+                        close_tracked_position(
+                            &mut risk_orchestrator,
+                            &persistence,
+                            symbol,
+                            ExitKind::Forced,
+                        );
+                        metrics.positions_exited += 1;
+                    } else {
+                        error!(
+                            "❌ [RISK] Failed to close position {}: {}",
+                            symbol,
+                            close_errors.join("; ")
+                        );
+                        risk_orchestrator.record_error(&format!(
+                            "Position close failed for {}: {}",
+                            symbol,
+                            close_errors.join("; ")
+                        ));
                     }
                 } else {
                     warn!(
@@ -1999,6 +4330,11 @@ async fn main() -> Result<()> {
             if risk_result.should_halt {
                 error!("🚨 [RISK] CRITICAL: Trading halted by risk orchestrator!");
                 error!("🚨 [HALT] Initiating emergency close of ALL positions before shutdown...");
+                annotate_equity(
+                    &persistence,
+                    EquityEventKind::Halt,
+                    "Risk halt: emergency close of all positions".to_string(),
+                );
 
                 // Re-fetch positions for emergency close (in case they changed during risk actions)
                 let positions_to_close = mock_client.get_delta_neutral_positions().await;
@@ -2008,6 +4344,7 @@ async fn main() -> Result<()> {
                         &mock_client,
                         &positions_to_close,
                         &mut risk_orchestrator,
+                        &persistence,
                     ).await;
 
                     error!(
@@ -2016,17 +4353,29 @@ async fn main() -> Result<()> {
                     );
 
                     // Save state after emergency close
-                    let mut state_to_save = mock_client.export_state().await;
-                    state_to_save.last_funding_period = last_funding_period;
-                    if let Err(e) = persistence.save_state(&state_to_save) {
-                        error!("❌ [HALT] Failed to save state after emergency close: {}", e);
-                    } else {
-                        info!("✅ [HALT] State saved after emergency close");
+                    match checkpoint_mock_state(
+                        &persistence,
+                        &mock_client,
+                        &mut state_tracker,
+                        &mut persistence_watchdog,
+                        last_funding_period,
+                        true,
+                    )
+                    .await
+                    {
+                        Ok(_) => info!("✅ [HALT] State saved after emergency close"),
+                        Err(e) => {
+                            error!(
+                                "❌ [HALT] Failed to save state after emergency close: {}",
+                                e
+                            )
+                        }
                     }
                 } else {
                     info!("ℹ️ [HALT] No positions to close");
                 }
 
+                risk_halted = true;
                 break;
             }
 
@@ -2038,12 +4387,15 @@ async fn main() -> Result<()> {
                     realized_pnl,
                     unrealized_pnl,
                     &risk_orchestrator,
+                    &funding_calendar,
+                    venue,
+                    &display_tz,
                 );
                 last_status_log = Utc::now();
             }
         } else {
             // Live Mode Risk Check
-            if let Ok(balances) = real_client.get_account_balance().await {
+            if let Ok(balances) = venue_client.get_account_balance().await {
                 let total_equity: Decimal = balances
                     .iter()
                     .map(|b| b.wallet_balance + b.unrealized_profit)
@@ -2052,16 +4404,24 @@ async fn main() -> Result<()> {
                 let margin_balance: Decimal = balances.iter().map(|b| b.wallet_balance).sum();
 
                 // Get positions for live mode
-                let live_positions = match real_client.get_positions().await {
-                    Ok(pos) => pos
-                        .into_iter()
-                        .filter(|p| p.position_amt != Decimal::ZERO)
-                        .collect(),
-                    Err(_) => vec![],
-                };
+                let mut live_positions: Vec<funding_fee_farmer::exchange::Position> =
+                    match venue_client.get_positions().await {
+                        Ok(pos) => pos
+                            .into_iter()
+                            .filter(|p| p.position_amt != Decimal::ZERO)
+                            .collect(),
+                        Err(_) => vec![],
+                    };
+
+                // Refresh with the latest premium index marks; position risk can lag
+                let position_symbols: Vec<String> =
+                    live_positions.iter().map(|p| p.symbol.clone()).collect();
+                let mark_prices =
+                    fetch_mark_prices(venue_client, &mark_cache, &position_symbols).await;
+                MarginMonitor::apply_mark_prices(&mut live_positions, &mark_prices);
 
                 // Build maintenance rate map from leverage brackets
-                let maintenance_rates = match real_client.get_leverage_brackets().await {
+                let maintenance_rates = match venue_client.get_leverage_brackets().await {
                     Ok(brackets) => {
                         MarginMonitor::build_maintenance_rate_map(&brackets, &live_positions)
                     }
@@ -2078,6 +4438,19 @@ async fn main() -> Result<()> {
                 if risk_result.should_halt {
                     error!("🚨 [RISK] CRITICAL: Trading halted by risk orchestrator!");
                     error!("🚨 [HALT] Initiating emergency close of ALL positions before shutdown...");
+                    annotate_equity(
+                        &persistence,
+                        EquityEventKind::Halt,
+                        "Risk halt: emergency close of all positions".to_string(),
+                    );
+
+                    // Resting orders are cancelled first, so none reopens a closed leg
+                    if trading_mode == TradingMode::Live
+                        && !cancel_open_orders_on_halt(&executor, &trading_venues, "Risk halt")
+                            .await
+                    {
+                        orders_left_open = true;
+                    }
 
                     // Close all live positions
                     for pos in &live_positions {
@@ -2085,25 +4458,10 @@ async fn main() -> Result<()> {
                             continue;
                         }
 
-                        let close_side = if pos.position_amt > Decimal::ZERO {
-                            funding_fee_farmer::exchange::OrderSide::Sell
-                        } else {
-                            funding_fee_farmer::exchange::OrderSide::Buy
-                        };
-
-                        let close_order = funding_fee_farmer::exchange::NewOrder {
-                            symbol: pos.symbol.clone(),
-                            side: close_side,
-                            position_side: None,
-                            order_type: funding_fee_farmer::exchange::OrderType::Market,
-                            quantity: Some(pos.position_amt.abs()),
-                            price: None,
-                            time_in_force: None,
-                            reduce_only: Some(true),
-                            new_client_order_id: None,
-                        };
-
-                        match real_client.place_futures_order(&close_order).await {
+                        match executor
+                            .close_futures_position(venue_client, &pos.symbol, pos.position_amt)
+                            .await
+                        {
                             Ok(_) => {
                                 info!("✅ [HALT] Emergency closed futures position for {}", pos.symbol);
                             }
@@ -2116,55 +4474,190 @@ async fn main() -> Result<()> {
                         }
                     }
 
+                    // COIN-margined shorts are closed too; the coins stay in the wallet
+                    for position in &coin_positions {
+                        match executor
+                            .place_coin_margined_leg(
+                                &real_client,
+                                &position.symbol,
+                                position.contracts,
+                                true,
+                            )
+                            .await
+                        {
+                            Ok(_) => info!(
+                                "✅ [HALT] Emergency closed COIN-margined short for {}",
+                                position.symbol
+                            ),
+                            Err(e) => error!(
+                                "🚨 [HALT] FAILED to close COIN-margined short for {}: {}",
+                                position.symbol, e
+                            ),
+                        }
+                    }
+
+                    // Spread legs on Binance were closed above; close their Hyperliquid legs
+                    if let Some(hyperliquid) = hyperliquid_client.as_ref().filter(|c| c.can_trade())
+                    {
+                        for position in &spread_positions {
+                            let is_buy = position.short_venue == Venue::Hyperliquid;
+                            match executor
+                                .place_hyperliquid_leg(
+                                    hyperliquid,
+                                    &position.symbol,
+                                    &position.coin,
+                                    is_buy,
+                                    position.quantity,
+                                    HALT_CLOSE_SLIPPAGE,
+                                    true,
+                                )
+                                .await
+                            {
+                                Ok(fill) if fill.filled_size >= position.quantity => {
+                                    info!(
+                                        "✅ [HALT] Emergency closed Hyperliquid position for {}",
+                                        position.coin
+                                    );
+                                }
+                                Ok(fill) => {
+                                    error!(
+                                        "🚨 [HALT] Hyperliquid {} closed {} of {}",
+                                        position.coin, fill.filled_size, position.quantity
+                                    );
+                                }
+                                Err(e) => {
+                                    error!(
+                                        "🚨 [HALT] FAILED to close Hyperliquid position for {}: {}",
+                                        position.coin, e
+                                    );
+                                }
+                            }
+                        }
+                    }
+
                     error!("🚨 [HALT] Emergency close complete - manual verification required!");
+                    risk_halted = true;
                     break;
                 }
             }
         }
 
-        // Periodic state save (hourly) for crash recovery
+        // Flush any remaining changes before idling, so a crash loses seconds rather than a cycle
         if trading_mode == TradingMode::Mock {
+            if let Err(e) = checkpoint_mock_state(
+                &persistence,
+                &mock_client,
+                &mut state_tracker,
+                &mut persistence_watchdog,
+                last_funding_period,
+                true,
+            )
+            .await
+            {
+                warn!("⚠️  [PERSISTENCE] Failed to checkpoint state: {}", e);
+            }
+
+            // Hourly equity snapshot for analysis
             let now = Utc::now();
-            if (now - last_state_save).num_minutes() >= 60 {
-                let mut state_to_save = mock_client.export_state().await;
-                state_to_save.last_funding_period = last_funding_period;
-                if let Err(e) = persistence.save_state(&state_to_save) {
-                    warn!("⚠️  [PERSISTENCE] Failed periodic state save: {}", e);
+            if (now - last_equity_snapshot).num_minutes() >= 60 {
+                let state = mock_client.get_state().await;
+                let (realized_pnl, unrealized_pnl) = mock_client.calculate_pnl().await;
+                let total_equity = state.balance + unrealized_pnl;
+                let max_drawdown = risk_orchestrator.get_drawdown_stats().session_mdd;
+                let open_positions = mock_client.get_delta_neutral_positions().await.len();
+                if let Err(e) = persistence.record_snapshot(
+                    state.balance,
+                    unrealized_pnl,
+                    total_equity,
+                    realized_pnl,
+                    open_positions,
+                    max_drawdown,
+                    metrics.residual_delta.net_delta_usd,
+                    metrics.residual_delta.gross_delta_usd,
+                    state.total_trading_fees,
+                ) {
+                    warn!("⚠️  [PERSISTENCE] Failed to record equity snapshot: {}", e);
                 } else {
-                    info!("💾 [PERSISTENCE] Hourly state checkpoint saved");
-                    // Also record equity snapshot for analysis
-                    let (realized_pnl, unrealized_pnl) = mock_client.calculate_pnl().await;
-                    let total_equity = state_to_save.balance + unrealized_pnl;
-                    let max_drawdown = risk_orchestrator.get_drawdown_stats().session_mdd;
-                    let _ = persistence.record_snapshot(
-                        state_to_save.balance,
-                        unrealized_pnl,
-                        total_equity,
-                        realized_pnl,
-                        state_to_save.positions.len(),
-                        max_drawdown,
-                    );
+                    info!("💾 [PERSISTENCE] Hourly equity snapshot recorded");
                 }
-                last_state_save = now;
+                last_equity_snapshot = now;
             }
         }
 
+        if Utc::now() - last_run_update >= chrono::Duration::minutes(RUN_RECORD_INTERVAL_MINUTES) {
+            refresh_run_record(
+                &mut run,
+                &metrics,
+                trading_mode,
+                &mock_client,
+                venue_client,
+                mock_totals_at_start,
+            )
+            .await;
+            save_run_record(&persistence, &run);
+            last_run_update = Utc::now();
+        }
+
+        if let Some(day) = api_usage.take_finished_day() {
+            report_api_usage(&day, &config.api_budget);
+        }
+        let report_minutes = config.api_budget.report_interval_minutes;
+        if report_minutes > 0
+            && Utc::now() - last_api_report >= chrono::Duration::minutes(report_minutes as i64)
+        {
+            if let Some(today) = api_usage.today() {
+                report_api_usage(&today, &config.api_budget);
+            }
+            last_api_report = Utc::now();
+        }
+
+        save_malfunction_state(&persistence, &risk_orchestrator);
+
         // Sleep before next iteration
         let loop_duration = (Utc::now() - loop_start).num_milliseconds();
         debug!("⏱️  Loop completed in {}ms", loop_duration);
 
-        tokio::time::sleep(Duration::from_secs(60)).await; // 1 minute between scans
+        idle(SCAN_INTERVAL_SECS, &shutdown_wake).await;
     }
 
-    // Save final state before shutdown
-    if trading_mode == TradingMode::Mock {
+    // Save final state before shutdown (after a takeover the state belongs to the new instance)
+    if trading_mode == TradingMode::Mock && !lost_instance_lock {
         info!("💾 [PERSISTENCE] Saving final state before shutdown...");
-        let mut state_to_save = mock_client.export_state().await;
-        state_to_save.last_funding_period = last_funding_period;
-        if let Err(e) = persistence.save_state(&state_to_save) {
-            error!("❌ [PERSISTENCE] Failed to save final state: {}", e);
-        } else {
-            info!("✅ [PERSISTENCE] Final state saved successfully");
+        match checkpoint_mock_state(
+            &persistence,
+            &mock_client,
+            &mut state_tracker,
+            &mut persistence_watchdog,
+            last_funding_period,
+            true,
+        )
+        .await
+        {
+            Ok(true) => info!("✅ [PERSISTENCE] Final state saved successfully"),
+            Ok(false) => info!("✅ [PERSISTENCE] State already up to date"),
+            Err(e) => error!("❌ [PERSISTENCE] Failed to save final state: {}", e),
+        }
+    }
+
+    if !lost_instance_lock {
+        save_malfunction_state(&persistence, &risk_orchestrator);
+    }
+
+    // No order of this instance is left resting on the book after it stops
+    if trading_mode == TradingMode::Live
+        && !lost_instance_lock
+        && !cancel_open_orders_on_halt(&executor, &trading_venues, "Shutdown").await
+    {
+        orders_left_open = true;
+    }
+
+    // Sweep free sub-account capital back to master; margin of open positions stays
+    if isolate_capital && sub_account.sweep_on_shutdown && !lost_instance_lock {
+        let manager = SubAccountManager::new(sub_account, &master_client, &real_client);
+        match manager.sweep().await {
+            Ok(Some(transfer)) => record_capital_transfer(&persistence, &transfer),
+            Ok(None) => info!("🏦 [SUB-ACCOUNT] Nothing to sweep back to master"),
+            Err(e) => error!("❌ [SUB-ACCOUNT] Failed to sweep capital back: {}", e),
         }
     }
 
@@ -2180,22 +4673,228 @@ async fn main() -> Result<()> {
             realized_pnl,
             unrealized_pnl,
             &risk_orchestrator,
+            &funding_calendar,
+            venue,
+            &display_tz,
         );
     }
 
+    refresh_run_record(
+        &mut run,
+        &metrics,
+        trading_mode,
+        &mock_client,
+        venue_client,
+        mock_totals_at_start,
+    )
+    .await;
+    run.ended_at = Some(Utc::now());
+    log_run_summary(&run);
+    save_run_record(&persistence, &run);
+
+    if let Err(e) = persistence.release_instance_lock(&instance.instance_id) {
+        warn!("⚠️  [LOCK] Failed to release instance lock: {}", e);
+    }
+
     info!("👋 Funding Fee Farmer shutdown complete");
-    Ok(())
+    Ok(if orders_left_open {
+        error!("🚨 [ORDERS] Exiting with open orders unconfirmed - check every venue before restarting");
+        ExitCode::from(EXIT_ORDERS_LEFT_OPEN)
+    } else if risk_halted {
+        ExitCode::from(EXIT_RISK_HALT)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// File name of the state database in the data directory.
+const STATE_DB_FILE: &str = "mock_state.db";
+
+/// File name of the scenario playback database in the data directory.
+const SCENARIO_STATE_DB_FILE: &str = "scenario_state.db";
+
+/// Exit status after a risk halt closed every position. Supervisors should not
+/// restart on it (systemd `RestartPreventExitStatus=3`): the halt needs a human.
+/// A clean shutdown exits 0 and a fatal error 1.
+const EXIT_RISK_HALT: u8 = 3;
+
+/// Exit status when a halt or shutdown could not confirm every open order of
+/// this instance cancelled. Like a risk halt it needs a human, who checks the
+/// venues' books before restarting (`RestartPreventExitStatus=3 4`).
+const EXIT_ORDERS_LEFT_OPEN: u8 = 4;
+
+/// Run a subcommand to completion.
+async fn run_command(command: Commands, cli: &Cli) -> Result<()> {
+    let db_or_default = |db: Option<String>| {
+        db.unwrap_or_else(|| cli.data_dir.join(STATE_DB_FILE).display().to_string())
+    };
+    match command {
+        Commands::Backtest {
+            data,
+            start,
+            end,
+            initial_balance,
+            output,
+            high_funding_rate,
+            maker_fee,
+            maker_entries,
+            borrow_scenario,
+            crowding,
+            stream,
+        } => {
+            run_backtest(
+                &data,
+                &start,
+                &end,
+                initial_balance,
+                output.as_deref(),
+                high_funding_rate,
+                maker_fee,
+                maker_entries,
+                borrow_scenario.as_deref(),
+                crowding,
+                stream,
+                cli.profile,
+                &cli.config_dir,
+            )
+            .await
+        }
+        Commands::Sweep {
+            data,
+            start,
+            end,
+            initial_balance,
+            parallelism,
+            output,
+            minimal,
+            stream,
+        } => {
+            run_sweep(
+                &data,
+                &start,
+                &end,
+                initial_balance,
+                parallelism,
+                output.as_deref(),
+                minimal,
+                stream,
+                cli.profile,
+                &cli.config_dir,
+            )
+            .await
+        }
+        Commands::Status { db, verbose, diff } => {
+            let db = db_or_default(db);
+            show_status(&db, verbose, diff, cli.profile, &cli.config_dir).await
+        }
+        Commands::ServeReport { db, port, bind } => {
+            let db = db_or_default(db);
+            anyhow::ensure!(Path::new(&db).exists(), "Database not found: {}", db);
+            let display_tz = Config::load_unvalidated(&cli.config_dir, cli.profile, Profile::Dev)
+                .map(|config| config.display.display_timezone())
+                .unwrap_or_default();
+            let addr = std::net::SocketAddr::new(bind, port);
+            println!("📄 [REPORT] Serving {} at http://{}", db, addr);
+            funding_fee_farmer::report::serve(db.into(), addr, display_tz).await
+        }
+        Commands::ReconcileOrders { db, hours } => {
+            let db = db_or_default(db);
+            reconcile_orders(&db, hours, cli.profile, &cli.config_dir, cli.testnet).await
+        }
+        Commands::ExecutionQuality { db, weeks } => {
+            let db = db_or_default(db);
+            show_execution_quality(&db, weeks)
+        }
+        Commands::BorrowCosts { db, weeks } => {
+            let db = db_or_default(db);
+            show_borrow_costs(&db, weeks)
+        }
+        Commands::Runs { db, limit } => {
+            let db = db_or_default(db);
+            show_runs(&db, limit)
+        }
+        Commands::ConfigHistory { db, limit, show } => {
+            let db = db_or_default(db);
+            show_config_history(&db, limit, show.as_deref())
+        }
+        Commands::Quarantine { db, release } => {
+            let db = db_or_default(db);
+            manage_quarantine(&db, release.as_deref())
+        }
+        Commands::Capacity { pairs, equity } => {
+            show_capacity(pairs, equity, cli.profile, &cli.config_dir).await
+        }
+        Commands::ValidateConfig => validate_config(cli.profile, &cli.config_dir),
+        Commands::Config {
+            print_effective,
+            format,
+        } => {
+            anyhow::ensure!(print_effective, "Nothing to do: pass --print-effective");
+            let config = Config::load_unvalidated(&cli.config_dir, cli.profile, Profile::Dev)?;
+            print!("{}", config.to_effective_string(format)?);
+            Ok(())
+        }
+    }
+}
+
+/// Wait for Ctrl-C or, on Unix, SIGTERM; returns the signal's name.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = sigterm.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                warn!("⚠️  Failed to listen for SIGTERM: {}", e);
+                tokio::signal::ctrl_c().await.ok();
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.ok();
+        "Ctrl-C"
+    }
+}
+
+/// Sleep for `secs`, or until a shutdown signal arrives.
+async fn idle(secs: u64, shutdown_wake: &Notify) {
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_secs(secs)) => {}
+        _ = shutdown_wake.notified() => {}
+    }
 }
 
-/// Initialize comprehensive logging with file output.
-fn init_logging() -> Result<()> {
+/// Clock offset logged as a warning (ms).
+const CLOCK_DRIFT_WARN_MS: i64 = 1_000;
+
+/// Calibrate the clock offset of signed requests against the Binance server time.
+async fn sync_server_clock(client: &BinanceClient) {
+    match client.sync_server_time().await {
+        Ok(offset_ms) if offset_ms.abs() >= CLOCK_DRIFT_WARN_MS => warn!(
+            "🕐 [CLOCK] System clock is {}ms {} the exchange's; signed requests use the exchange clock",
+            offset_ms.abs(),
+            if offset_ms > 0 { "behind" } else { "ahead of" }
+        ),
+        Ok(offset_ms) => debug!("🕐 [CLOCK] Offset from the exchange clock: {}ms", offset_ms),
+        Err(e) => warn!("⚠️  [CLOCK] Failed to sync with the exchange clock: {}", e),
+    }
+}
+
+/// Initialize comprehensive logging with file output in `log_dir`.
+fn init_logging(log_dir: &Path) -> Result<()> {
     use tracing_subscriber::fmt::writer::MakeWriterExt;
 
     // Create logs directory
-    std::fs::create_dir_all("logs")?;
+    std::fs::create_dir_all(log_dir)
+        .with_context(|| format!("Failed to create log directory {}", log_dir.display()))?;
 
     // File appender for detailed logs
-    let file_appender = tracing_appender::rolling::hourly("logs", "funding-farmer.log");
+    let file_appender = tracing_appender::rolling::hourly(log_dir, "funding-farmer.log");
     let (file_writer, _guard) = tracing_appender::non_blocking(file_appender);
 
     // Leak the guard to keep it alive for the program duration
@@ -2221,11 +4920,20 @@ fn init_logging() -> Result<()> {
 
 /// Log configuration on startup.
 fn log_config(config: &Config) {
-    info!("📋 Configuration:");
+    info!("📋 Configuration (profile: {}):", config.profile);
+    info!("   Display Time Zone: {}", config.display.timezone);
     info!(
         "   Capital Utilization: {:.0}%",
         config.capital.max_utilization * dec!(100)
     );
+    if config.capital.dynamic_utilization.enabled {
+        info!(
+            "   Dynamic Utilization: {:.0}% floor, +{:.0}% per cycle, {:.1}% daily volatility target",
+            config.capital.dynamic_utilization.min_utilization * dec!(100),
+            config.capital.dynamic_utilization.step * dec!(100),
+            config.capital.dynamic_utilization.target_daily_volatility * dec!(100)
+        );
+    }
     info!(
         "   Reserve Buffer: {:.0}%",
         config.capital.reserve_buffer * dec!(100)
@@ -2251,192 +4959,2554 @@ fn log_config(config: &Config) {
         "   Min Volume 24h: ${:.0}M",
         config.pair_selection.min_volume_24h / dec!(1_000_000)
     );
+    if !config.pair_selection.min_volume_percentile.is_zero() {
+        info!(
+            "   Volume Percentile: top {:.0}% of the market",
+            (Decimal::ONE - config.pair_selection.min_volume_percentile) * dec!(100)
+        );
+    }
 }
 
-/// Fetch real positions.
-async fn fetch_real_positions(client: &BinanceClient) -> Result<HashMap<String, Decimal>> {
-    match client.get_positions().await {
-        Ok(positions) => Ok(positions
-            .into_iter()
-            .filter(|p| p.position_amt != Decimal::ZERO)
-            .map(|p| (p.symbol, p.position_amt))
-            .collect()),
-        Err(e) => {
-            error!("Failed to fetch real positions: {}", e);
-            Err(e.into())
+/// Minutes an entry intent may stay unconfirmed before it is cancelled.
+const ENTRY_INTENT_TIMEOUT_MINUTES: i64 = 30;
+
+/// An instance lock holder silent for this long is presumed dead.
+const INSTANCE_LOCK_STALE_SECS: i64 = 300;
+
+/// Wait after a takeover so the previous instance sees the lost lock on its next
+/// cycle and stands down before this one trades.
+const INSTANCE_TAKEOVER_GRACE_SECS: u64 = 120;
+
+/// How far back exchange orders are checked for another instance's tag.
+const INSTANCE_ORDER_LOOKBACK_MINUTES: i64 = 15;
+
+/// Take the single-instance trading lock, refusing to start while another instance trades.
+///
+/// The database lock coordinates instances sharing this database. In live mode,
+/// recent exchange orders are also checked for client order IDs tagged by an
+/// instance this database has never seen, which catches a second bot running
+/// elsewhere on the same API keys.
+async fn take_instance_lock(
+    persistence: &PersistenceManager,
+    client: Option<&BinanceClient>,
+    instance: &InstanceLock,
+    strategy_tag: &str,
+    takeover: bool,
+) -> Result<()> {
+    let stale_after = chrono::Duration::seconds(INSTANCE_LOCK_STALE_SECS);
+    match persistence.acquire_instance_lock(instance, stale_after, takeover)? {
+        LockAcquisition::Held(holder) => anyhow::bail!(
+            "Instance {} (pid {}) holds the trading lock, last heartbeat {} - refusing to trade. Stop it or restart with --takeover",
+            holder.instance_id,
+            holder.pid,
+            holder.heartbeat_at.to_rfc3339()
+        ),
+        LockAcquisition::TookOver(holder) => {
+            warn!(
+                "🔒 [LOCK] Took over from instance {} (pid {}) - waiting {}s for it to stand down",
+                holder.instance_id, holder.pid, INSTANCE_TAKEOVER_GRACE_SECS
+            );
+            tokio::time::sleep(Duration::from_secs(INSTANCE_TAKEOVER_GRACE_SECS)).await;
         }
+        LockAcquisition::Acquired(Some(stale)) => info!(
+            "🔒 [LOCK] Replaced stale lock of instance {} (last heartbeat {})",
+            stale.instance_id,
+            stale.heartbeat_at.to_rfc3339()
+        ),
+        LockAcquisition::Acquired(None) => {}
     }
-}
-
-/// Fetch current prices from real client for qualified pairs.
-async fn fetch_prices(
-    client: &BinanceClient,
-    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
-) -> HashMap<String, Decimal> {
-    let symbols: Vec<String> = pairs.iter().map(|p| p.symbol.clone()).collect();
-    fetch_prices_for_symbols(client, &symbols).await
-}
+    info!(
+        "🔒 [LOCK] Instance {} holds the trading lock",
+        instance.instance_id
+    );
 
-/// Fetch current prices from real client for specific symbols.
-async fn fetch_prices_for_symbols(
-    client: &BinanceClient,
-    symbols: &[String],
-) -> HashMap<String, Decimal> {
-    match client.get_book_tickers().await {
-        Ok(tickers) => tickers
-            .into_iter()
-            .filter(|t| symbols.iter().any(|s| s == &t.symbol))
-            .map(|t| {
-                let mid_price = (t.bid_price + t.ask_price) / dec!(2);
-                (t.symbol, mid_price)
-            })
-            .collect(),
+    let Some(client) = client else {
+        return Ok(());
+    };
+    let orders = match recent_exchange_orders(client).await {
+        Ok(orders) => orders,
         Err(e) => {
-            warn!("Failed to fetch prices: {}", e);
-            HashMap::new()
+            warn!(
+                "⚠️  [LOCK] Failed to check exchange orders for other instances: {}",
+                e
+            );
+            return Ok(());
         }
-    }
-}
-
-/// Execute emergency close of ALL positions during halt condition.
-/// This function will retry each position close up to max_retries times.
-/// Returns the number of positions successfully closed.
-async fn execute_emergency_close_all(
-    mock_client: &MockBinanceClient,
-    positions: &[funding_fee_farmer::exchange::DeltaNeutralPosition],
-    risk_orchestrator: &mut RiskOrchestrator,
-) -> usize {
-    let total_positions = positions.len();
-    let mut closed_count = 0;
-    let max_retries = 5;
+    };
+    let known = persistence.known_instance_ids()?;
+    let known: Vec<&str> = known.iter().map(String::as_str).collect();
+    let Some(order) = foreign_instance_orders(&orders, strategy_tag, &known)
+        .into_iter()
+        .next()
+    else {
+        return Ok(());
+    };
 
+    let other = ClientOrderTag::parse(strategy_tag, &order.client_order_id)
+        .map(|tag| tag.instance_id)
+        .unwrap_or_default();
+    if !takeover {
+        persistence.release_instance_lock(&instance.instance_id)?;
+        anyhow::bail!(
+            "Instance {} placed order {} on {} within the last {} minutes but does not use this database - refusing to trade. Stop it or restart with --takeover",
+            other,
+            order.client_order_id,
+            order.symbol,
+            INSTANCE_ORDER_LOOKBACK_MINUTES
+        );
+    }
     error!(
-        "🚨 [EMERGENCY] Beginning emergency close of {} positions",
-        total_positions
+        "🔒 [LOCK] Instance {} trades this account from another database and cannot be told to stand down - stop it manually",
+        other
     );
+    Ok(())
+}
 
-    for pos in positions {
-        if pos.futures_qty.abs() < dec!(0.0001) && pos.spot_qty.abs() < dec!(0.0001) {
-            // Skip positions with negligible size
-            closed_count += 1;
+/// Open futures orders plus recent orders on symbols with an open position.
+async fn recent_exchange_orders(client: &BinanceClient) -> Result<Vec<OrderLookup>> {
+    let since = Utc::now() - chrono::Duration::minutes(INSTANCE_ORDER_LOOKBACK_MINUTES);
+    let mut orders = client.get_open_futures_orders().await?;
+    for position in client.get_positions().await? {
+        if position.position_amt.is_zero() {
             continue;
         }
-
-        info!(
-            "🔄 [EMERGENCY] Closing position {} (futures: {}, spot: {})",
-            pos.symbol, pos.futures_qty, pos.spot_qty
+        orders.extend(
+            client
+                .get_recent_futures_orders(&position.symbol, since.timestamp_millis())
+                .await?,
         );
+    }
+    Ok(orders)
+}
 
-        let mut futures_closed = pos.futures_qty == Decimal::ZERO;
-        let mut spot_closed = pos.spot_qty == Decimal::ZERO;
-
-        // Close futures leg with retries
-        if !futures_closed {
-            let futures_side = if pos.futures_qty > Decimal::ZERO {
-                funding_fee_farmer::exchange::OrderSide::Sell
-            } else {
-                funding_fee_farmer::exchange::OrderSide::Buy
-            };
-
-            for attempt in 1..=max_retries {
-                let futures_order = funding_fee_farmer::exchange::NewOrder {
-                    symbol: pos.symbol.clone(),
-                    side: futures_side.clone(),
-                    position_side: None,
-                    order_type: funding_fee_farmer::exchange::OrderType::Market,
-                    quantity: Some(pos.futures_qty.abs()),
-                    price: None,
-                    time_in_force: None,
-                    reduce_only: Some(true),
-                    new_client_order_id: None,
-                };
+/// Compare leverage and margin type of held symbols with the configuration.
+///
+/// Leverage is set back to `default_leverage` when `settings_mismatch` is
+/// `reconcile`; margin type can't change under an open position, so it and any
+/// leverage left different raise an alert.
+async fn check_symbol_settings(
+    client: &dyn ExchangeClient,
+    execution: &ExecutionConfig,
+    risk_orchestrator: &mut RiskOrchestrator,
+) {
+    let positions = match client.get_positions().await {
+        Ok(positions) => positions,
+        Err(e) => {
+            warn!("⚠️  [SETTINGS] Failed to fetch position settings: {}", e);
+            return;
+        }
+    };
+    let mismatches = settings_mismatches(&positions, execution.default_leverage, MarginType::Cross);
+    if mismatches.is_empty() {
+        debug!("🔧 [SETTINGS] Held symbols match the configured leverage and margin type");
+        return;
+    }
+
+    for mismatch in mismatches {
+        let mut leverage = mismatch.leverage;
+        if mismatch.leverage_differs()
+            && execution.settings_mismatch == SettingsMismatchAction::Reconcile
+        {
+            match client
+                .set_leverage(&mismatch.symbol, mismatch.expected_leverage)
+                .await
+            {
+                Ok(()) => {
+                    info!(
+                        "🔧 [SETTINGS] {} leverage reset from {}x to {}x",
+                        mismatch.symbol, mismatch.leverage, mismatch.expected_leverage
+                    );
+                    leverage = mismatch.expected_leverage;
+                }
+                Err(e) => warn!(
+                    "⚠️  [SETTINGS] Failed to reset {} leverage: {}",
+                    mismatch.symbol, e
+                ),
+            }
+        }
+        if leverage == mismatch.expected_leverage && !mismatch.margin_type_differs() {
+            continue;
+        }
+
+        warn!("🔧 [SETTINGS] Exchange settings differ: {}", mismatch);
+        let alert = RiskAlert::new(
+            RiskAlertType::SettingsMismatch {
+                symbol: mismatch.symbol.clone(),
+                leverage,
+                expected_leverage: mismatch.expected_leverage,
+                margin_type: mismatch.margin_type,
+            },
+            AlertSeverity::Warning,
+            Some(mismatch.symbol.clone()),
+            format!("Exchange settings differ from configuration: {}", mismatch),
+            "Restore leverage and margin type on the exchange".to_string(),
+        )
+        .with_metric("leverage", Decimal::from(leverage))
+        .with_metric(
+            "expected_leverage",
+            Decimal::from(mismatch.expected_leverage),
+        );
+        risk_orchestrator.raise(alert);
+    }
+}
+
+/// How far back funding income is imported when seeding live positions.
+const FUNDING_HISTORY_LOOKBACK_DAYS: i64 = 7;
+
+/// Minutes after a settlement before a missing exchange payment is reported.
+const FUNDING_CONFIRMATION_GRACE_MINUTES: i64 = 30;
+
+/// Minutes between funding income polls while settlements await confirmation.
+const FUNDING_CONFIRMATION_POLL_MINUTES: i64 = 5;
+
+/// Record the settlement of each of `symbols` at `now` as expected.
+///
+/// The projection is the tracked notional at the entry funding rate.
+fn expect_settlements(
+    settlements: &mut FundingSettlements,
+    risk_orchestrator: &RiskOrchestrator,
+    persistence: &PersistenceManager,
+    calendar: &FundingCalendar,
+    venue: Venue,
+    symbols: &[String],
+    now: DateTime<Utc>,
+) {
+    for symbol in symbols {
+        let Some(tracked) = risk_orchestrator.get_tracked_position(symbol) else {
+            continue;
+        };
+        let settled_at = calendar.schedule(venue, symbol).last_settlement(now);
+        let expected = tracked.expected_funding_rate.abs() * tracked.position_value;
+        let position_value = Some(tracked.position_value);
+        let event_id = persistence
+            .record_expected_funding(symbol, settled_at, expected, position_value)
+            .map_err(|e| warn!("⚠️  [PERSISTENCE] Failed to record expected funding: {}", e))
+            .ok();
+        settlements.expect(PendingSettlement::new(
+            event_id,
+            symbol,
+            settled_at,
+            expected,
+            position_value,
+        ));
+    }
+}
+
+/// Release quarantines the operator removed from the database.
+fn sync_quarantines(risk_orchestrator: &mut RiskOrchestrator, persistence: &PersistenceManager) {
+    let persisted = match persistence.get_quarantined() {
+        Ok(persisted) => persisted,
+        Err(e) => {
+            warn!("⚠️  [PERSISTENCE] Failed to load quarantines: {}", e);
+            return;
+        }
+    };
+    let released: Vec<String> = risk_orchestrator
+        .quarantined()
+        .iter()
+        .filter(|q| !persisted.iter().any(|p| p.symbol == q.symbol))
+        .map(|q| q.symbol.clone())
+        .collect();
+    for symbol in released {
+        risk_orchestrator.release_quarantine(&symbol);
+        info!("🔓 [QUARANTINE] {} released", symbol);
+    }
+}
+
+/// Count confirmed settlements as income and verify them against expectations.
+///
+/// A payment that matches its expectation is marked verified; an anomaly stays
+/// settled and counts towards quarantining the symbol. Positions closed since
+/// their settlement are persisted but not tracked.
+fn apply_confirmed_funding(
+    confirmed: Vec<ConfirmedSettlement>,
+    risk_orchestrator: &mut RiskOrchestrator,
+    persistence: &PersistenceManager,
+) {
+    for ConfirmedSettlement { settlement, amount } in confirmed {
+        let symbol = &settlement.symbol;
+        if let Some(id) = settlement.event_id {
+            if let Err(e) = persistence.settle_funding_event(id, amount) {
+                warn!("⚠️  [PERSISTENCE] Failed to record funding event: {}", e);
+            }
+        }
+        if risk_orchestrator.get_tracked_position(symbol).is_none() {
+            continue;
+        }
+        risk_orchestrator.record_funding(symbol, amount);
+        let verification = risk_orchestrator.verify_funding(symbol, amount);
+        if verification.is_anomaly {
+            warn!(
+                "⚠️  [FUNDING] Anomaly for {}: expected ${:.4} ({}), got ${:.4} ({:.1}% deviation)",
+                symbol,
+                verification.funding_expected,
+                verification.basis,
+                verification.funding_received,
+                verification.deviation_pct * dec!(100)
+            );
+        } else if let Some(id) = settlement.event_id {
+            if let Err(e) = persistence.verify_funding_event(id) {
+                warn!("⚠️  [PERSISTENCE] Failed to verify funding event: {}", e);
+            }
+        }
+        if let Some(quarantine) =
+            risk_orchestrator.record_funding_verification(&verification, Utc::now())
+        {
+            warn!(
+                "🚫 [QUARANTINE] {} quarantined after {} consecutive funding anomalies ({:.1}% last deviation){}",
+                symbol,
+                quarantine.anomalies,
+                quarantine.last_deviation * dec!(100),
+                if risk_orchestrator.quarantine_requires_exit(symbol) {
+                    ", exiting"
+                } else {
+                    ", no new capital"
+                }
+            );
+            if let Err(e) = persistence.save_quarantine(&quarantine) {
+                warn!("⚠️  [PERSISTENCE] Failed to save quarantine: {}", e);
+            }
+        }
+    }
+}
+
+/// Funding payments `(symbol, time, amount)` the exchange reported since `since`.
+async fn fetch_funding_payments(
+    client: &dyn ExchangeClient,
+    since: DateTime<Utc>,
+) -> Result<Vec<(String, DateTime<Utc>, Decimal)>> {
+    let records = client
+        .get_income_history("FUNDING_FEE", since.timestamp_millis())
+        .await?;
+    Ok(records
+        .into_iter()
+        .filter_map(|record| {
+            DateTime::from_timestamp_millis(record.time)
+                .map(|time| (record.symbol, time, record.income))
+        })
+        .collect())
+}
+
+/// Register open live positions with the risk tracker and import their funding history.
+///
+/// Keeps per-position funding totals and collection counts accurate across restarts.
+/// Positions already tracked are left alone.
+async fn seed_live_positions(
+    client: &dyn ExchangeClient,
+    risk_orchestrator: &mut RiskOrchestrator,
+) -> Result<usize> {
+    let positions: Vec<_> = client
+        .get_positions()
+        .await?
+        .into_iter()
+        .filter(|p| p.position_amt != Decimal::ZERO)
+        .filter(|p| risk_orchestrator.get_tracked_position(&p.symbol).is_none())
+        .collect();
+    if positions.is_empty() {
+        return Ok(0);
+    }
+
+    let start = Utc::now() - chrono::Duration::days(FUNDING_HISTORY_LOOKBACK_DAYS);
+    let income = client
+        .get_income_history("FUNDING_FEE", start.timestamp_millis())
+        .await?;
+
+    let mut payments: HashMap<String, Vec<(DateTime<Utc>, Decimal)>> = HashMap::new();
+    for record in income {
+        if let Some(timestamp) = DateTime::from_timestamp_millis(record.time) {
+            payments
+                .entry(record.symbol)
+                .or_default()
+                .push((timestamp, record.income));
+        }
+    }
+
+    let rates: HashMap<String, Decimal> = client
+        .get_funding_rates()
+        .await
+        .map(|rates| {
+            rates
+                .into_iter()
+                .map(|r| (r.symbol, r.funding_rate))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for position in &positions {
+        let mut symbol_payments = payments.remove(&position.symbol).unwrap_or_default();
+        symbol_payments.sort_by_key(|(timestamp, _)| *timestamp);
+
+        // The exchange does not report when a position was opened; its fills do.
+        // Funding of an earlier position on the symbol then predates it.
+        let opened_at = match client
+            .get_futures_trades(&position.symbol, start.timestamp_millis())
+            .await
+        {
+            Ok(trades) => {
+                let fills: Vec<_> = trades
+                    .iter()
+                    .filter_map(|trade| {
+                        Some((
+                            DateTime::from_timestamp_millis(trade.time)?,
+                            trade.signed_qty(),
+                        ))
+                    })
+                    .collect();
+                // Open since before the window: every payment in it is this position's
+                position_opened_at(position.position_amt, &fills).unwrap_or(start)
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  [FUNDING] Failed to fetch {} fills, attributing all funding since {} to the open position: {}",
+                    position.symbol,
+                    start.format("%Y-%m-%d %H:%M"),
+                    e
+                );
+                start
+            }
+        };
+        let position_value = position.position_amt.abs() * position.entry_price;
+
+        risk_orchestrator.open_position(PositionEntry {
+            symbol: position.symbol.clone(),
+            entry_price: position.entry_price,
+            quantity: position.position_amt.abs(),
+            position_value,
+            expected_funding_rate: rates.get(&position.symbol).copied().unwrap_or_default(),
+            entry_fees: position_value * dec!(0.0004), // Estimate ~0.04% taker fee
+            opened_at: Some(opened_at),
+            first_funding_at: symbol_payments
+                .iter()
+                .map(|(timestamp, _)| *timestamp)
+                .find(|timestamp| *timestamp >= opened_at),
+        });
+
+        let imported = risk_orchestrator.import_funding_history(
+            &position.symbol,
+            &symbol_payments,
+            position.position_amt > Decimal::ZERO,
+        );
+        let total: Decimal = symbol_payments
+            .iter()
+            .filter(|(timestamp, _)| *timestamp >= opened_at)
+            .map(|(_, amount)| *amount)
+            .sum();
+        info!(
+            "   Seeded: {} | Value: ${:.2} | Funding: ${:.4} over {} payments",
+            position.symbol, position_value, total, imported
+        );
+    }
+
+    Ok(positions.len())
+}
+
+/// Load per-symbol funding intervals and funding caps of `venue`.
+///
+/// Falls back to the 8h default for every symbol, and no known caps, when the
+/// funding info can't be fetched.
+async fn load_funding_info(
+    client: &dyn ExchangeClient,
+    venue: Venue,
+) -> (FundingCalendar, HashMap<String, FundingCap>) {
+    let mut calendar = FundingCalendar::new();
+    let mut caps = HashMap::new();
+    match client.get_funding_info().await {
+        Ok(infos) => {
+            let adjusted = calendar.apply_intervals(venue, &infos);
+            caps = infos
+                .iter()
+                .filter_map(|info| Some((info.symbol.clone(), info.cap()?)))
+                .collect();
+            info!(
+                "⏱️  [FUNDING] {} symbols settle off the 8h schedule, {} with adjusted funding caps",
+                adjusted,
+                caps.len()
+            );
+        }
+        Err(e) => warn!(
+            "⚠️  [FUNDING] Failed to load funding intervals, assuming 8h for all symbols: {}",
+            e
+        ),
+    }
+    (calendar, caps)
+}
+
+/// Compare the hedges of `held` positions with the cheapest available and alert on flips.
+///
+/// Held positions are hedged with spot (the only hedge the executor trades); held
+/// symbols missing from `pairs` are not reviewed.
+fn review_held_hedges(
+    monitor: &mut HedgeSwitchMonitor,
+    risk_orchestrator: &mut RiskOrchestrator,
+    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
+    held: &[String],
+) {
+    for pair in pairs.iter().filter(|p| held.contains(&p.symbol)) {
+        let Some(switch) = monitor.review(
+            &pair.symbol,
+            &HedgeInstrument::SpotMargin,
+            &pair.hedge_options,
+        ) else {
+            continue;
+        };
+        let alert = RiskAlert::new(
+            RiskAlertType::HedgeSwitchRecommended {
+                symbol: switch.symbol.clone(),
+                from: switch.from.instrument.to_string(),
+                to: switch.to.instrument.to_string(),
+                savings: switch.savings(),
+            },
+            AlertSeverity::Info,
+            Some(switch.symbol.clone()),
+            format!(
+                "{} hedge carry {:.4}% per 8h on {} vs {:.4}% on {}",
+                switch.symbol,
+                switch.from.carry.total() * dec!(100),
+                switch.from.instrument,
+                switch.to.carry.total() * dec!(100),
+                switch.to.instrument
+            ),
+            format!(
+                "Consider moving the hedge to {} (saves {:.4}% per 8h); perp hedges are not traded automatically",
+                switch.to.instrument,
+                switch.savings() * dec!(100)
+            ),
+        )
+        .with_metric("savings", switch.savings());
+        risk_orchestrator.raise(alert);
+    }
+}
+
+/// Funding compared across venues by one spread check.
+struct SpreadCheck {
+    binance_rates: Vec<FundingRate>,
+    /// Hourly Hyperliquid rates by coin
+    hyperliquid_rates: HashMap<String, Decimal>,
+    /// Spreads opened since the last check
+    opportunities: Vec<FundingSpread>,
+}
+
+/// Compare Binance and Hyperliquid funding and alert on newly opened spreads.
+///
+/// Returns `None` when the check failed.
+async fn check_funding_spreads(
+    binance: &BinanceClient,
+    hyperliquid: &HyperliquidClient,
+    monitor: &mut CrossVenueMonitor,
+    risk_orchestrator: &mut RiskOrchestrator,
+    calendar: &FundingCalendar,
+    config: &funding_fee_farmer::config::CrossVenueConfig,
+) -> Option<SpreadCheck> {
+    let (binance_rates, hyperliquid_rates) =
+        match tokio::try_join!(binance.get_funding_rates(), hyperliquid.get_funding_rates()) {
+            Ok(rates) => rates,
+            Err(e) => {
+                warn!("⚠️  [VENUE] Funding spread check failed: {:#}", e);
+                return None;
+            }
+        };
+
+    let spreads = funding_spreads(&binance_rates, &hyperliquid_rates, calendar, config);
+    debug!(
+        "🔀 [VENUE] {} open cross-venue funding spreads",
+        spreads.len()
+    );
+
+    let opportunities = monitor.new_opportunities(&spreads);
+    for spread in &opportunities {
+        let action = if spread.suggested_notional > Decimal::ZERO {
+            format!(
+                "Short {} on {} and long on {}, up to ${:.0} per leg",
+                spread.coin,
+                spread.short_venue,
+                spread.long_venue(),
+                spread.suggested_notional
+            )
+        } else {
+            "No room left under cross_venue.max_total_notional".to_string()
+        };
+        let alert = RiskAlert::new(
+            RiskAlertType::FundingSpreadOpportunity {
+                symbol: spread.symbol.clone(),
+                short_venue: spread.short_venue.to_string(),
+                spread: spread.spread,
+                apr: spread.apr,
+            },
+            AlertSeverity::Info,
+            Some(spread.symbol.clone()),
+            format!(
+                "{} funding {:.4}% on Binance vs {:.4}% on Hyperliquid per 8h ({:.1}% APR gross)",
+                spread.coin,
+                spread.binance_rate * dec!(100),
+                spread.hyperliquid_rate * dec!(100),
+                spread.apr * dec!(100)
+            ),
+            action,
+        )
+        .with_metric("spread", spread.spread)
+        .with_metric("apr", spread.apr)
+        .with_metric("suggested_notional", spread.suggested_notional);
+        risk_orchestrator.raise(alert);
+    }
+
+    Some(SpreadCheck {
+        binance_rates,
+        hyperliquid_rates,
+        opportunities,
+    })
+}
+
+/// Hyperliquid rates scaled to 8h.
+fn hyperliquid_rates_per_8h(
+    rates: &HashMap<String, Decimal>,
+    calendar: &FundingCalendar,
+) -> HashMap<String, Decimal> {
+    rates
+        .iter()
+        .map(|(coin, rate)| {
+            let per_8h = calendar
+                .schedule(Venue::Hyperliquid, coin)
+                .rate_per_hours(*rate, 8);
+            (coin.clone(), per_8h)
+        })
+        .collect()
+}
+
+/// Slippage through mid allowed when closing Hyperliquid legs in a halt.
+const HALT_CLOSE_SLIPPAGE: Decimal = dec!(0.02);
+
+/// Close held spreads that have narrowed and enter newly opened ones.
+///
+/// Each trade fills its Hyperliquid leg first with an IOC order, then trades
+/// what filled on Binance futures; a Binance failure unwinds the Hyperliquid
+/// fill. Symbols the spot-hedged book holds on Binance futures are not entered.
+async fn trade_funding_spreads(
+    binance: &BinanceClient,
+    hyperliquid: &HyperliquidClient,
+    executor: &OrderExecutor,
+    positions: &mut Vec<SpreadPosition>,
+    check: &SpreadCheck,
+    calendar: &FundingCalendar,
+    config: &Config,
+) {
+    let slippage = config.execution.slippage_tolerance;
+    let exits = spread_exits(
+        positions,
+        &check.binance_rates,
+        &check.hyperliquid_rates,
+        calendar,
+        config.cross_venue.exit_spread,
+    );
+    for symbol in exits {
+        let Some(index) = positions.iter().position(|p| p.symbol == symbol) else {
+            continue;
+        };
+        let position = positions[index].clone();
+        let closed = match close_spread(binance, hyperliquid, executor, &position, slippage).await {
+            Ok(closed) => closed,
+            Err(e) => {
+                warn!("⚠️  [VENUE] Failed to close {} spread: {:#}", symbol, e);
+                continue;
+            }
+        };
+        let remaining = position.quantity - closed;
+        if remaining <= Decimal::ZERO {
+            info!("🔀 [VENUE] Closed {} spread", symbol);
+            positions.remove(index);
+        } else {
+            info!(
+                "🔀 [VENUE] Closed {} of {} spread, {} left",
+                closed, symbol, remaining
+            );
+            positions[index].notional = position.notional * remaining / position.quantity;
+            positions[index].quantity = remaining;
+        }
+    }
+
+    if check.opportunities.is_empty() {
+        return;
+    }
+    let held_futures: HashSet<String> = match binance.get_positions().await {
+        Ok(held) => held
+            .into_iter()
+            .filter(|p| p.position_amt != Decimal::ZERO)
+            .map(|p| p.symbol)
+            .collect(),
+        Err(e) => {
+            warn!(
+                "⚠️  [VENUE] Skipping spread entries, positions unavailable: {:#}",
+                e
+            );
+            return;
+        }
+    };
+    for spread in &check.opportunities {
+        if held_futures.contains(&spread.symbol) {
+            debug!(
+                "🔀 [VENUE] {} spread not entered: held by the spot-hedged book",
+                spread.symbol
+            );
+            continue;
+        }
+        let notional = entry_notional(spread, positions, &config.cross_venue);
+        let mark_price = check
+            .binance_rates
+            .iter()
+            .find(|rate| rate.symbol == spread.symbol)
+            .and_then(|rate| rate.mark_price)
+            .unwrap_or_default();
+        if notional <= Decimal::ZERO || mark_price <= Decimal::ZERO {
+            continue;
+        }
+        let quantity = executor.floor_quantity(notional / mark_price, &spread.symbol);
+        match open_spread(binance, hyperliquid, executor, spread, quantity, slippage).await {
+            Ok(Some(position)) => {
+                info!(
+                    "🔀 [VENUE] Entered {} spread: {} short on {}, ${:.0} per leg",
+                    position.symbol, position.quantity, position.short_venue, position.notional
+                );
+                positions.push(position);
+            }
+            Ok(None) => info!(
+                "🔀 [VENUE] {} spread not entered: no Hyperliquid fill",
+                spread.symbol
+            ),
+            Err(e) => warn!(
+                "⚠️  [VENUE] Failed to enter {} spread: {:#}",
+                spread.symbol, e
+            ),
+        }
+    }
+}
+
+/// Enter a spread; `None` when the Hyperliquid leg did not fill.
+async fn open_spread(
+    binance: &BinanceClient,
+    hyperliquid: &HyperliquidClient,
+    executor: &OrderExecutor,
+    spread: &FundingSpread,
+    quantity: Decimal,
+    slippage: Decimal,
+) -> Result<Option<SpreadPosition>> {
+    let short_hyperliquid = spread.short_venue == Venue::Hyperliquid;
+    let fill = executor
+        .place_hyperliquid_leg(
+            hyperliquid,
+            &spread.symbol,
+            &spread.coin,
+            !short_hyperliquid,
+            quantity,
+            slippage,
+            false,
+        )
+        .await?;
+    if fill.filled_size <= Decimal::ZERO {
+        return Ok(None);
+    }
+
+    let side = if short_hyperliquid {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    };
+    if let Err(e) = executor
+        .place_spread_leg(binance, &spread.symbol, side, fill.filled_size, false)
+        .await
+    {
+        // Don't leave the Hyperliquid leg unhedged
+        let unwind = executor
+            .place_hyperliquid_leg(
+                hyperliquid,
+                &spread.symbol,
+                &spread.coin,
+                short_hyperliquid,
+                fill.filled_size,
+                slippage,
+                true,
+            )
+            .await;
+        if !unwind.is_ok_and(|unwound| unwound.filled_size >= fill.filled_size) {
+            error!(
+                "🚨 [VENUE] {} Hyperliquid leg left unhedged after Binance failure - manual intervention required",
+                spread.coin
+            );
+        }
+        return Err(e);
+    }
+
+    Ok(Some(SpreadPosition {
+        symbol: spread.symbol.clone(),
+        coin: spread.coin.clone(),
+        short_venue: spread.short_venue,
+        quantity: fill.filled_size,
+        notional: fill.filled_size * fill.avg_price,
+    }))
+}
+
+/// Close a spread's Hyperliquid leg, then as much of the Binance leg as closed.
+///
+/// Returns the base quantity closed on both venues.
+async fn close_spread(
+    binance: &BinanceClient,
+    hyperliquid: &HyperliquidClient,
+    executor: &OrderExecutor,
+    position: &SpreadPosition,
+    slippage: Decimal,
+) -> Result<Decimal> {
+    let short_hyperliquid = position.short_venue == Venue::Hyperliquid;
+    let fill = executor
+        .place_hyperliquid_leg(
+            hyperliquid,
+            &position.symbol,
+            &position.coin,
+            short_hyperliquid,
+            position.quantity,
+            slippage,
+            true,
+        )
+        .await?;
+    if fill.filled_size <= Decimal::ZERO {
+        return Ok(Decimal::ZERO);
+    }
+
+    let side = if short_hyperliquid {
+        OrderSide::Sell
+    } else {
+        OrderSide::Buy
+    };
+    if let Err(e) = executor
+        .place_spread_leg(binance, &position.symbol, side, fill.filled_size, true)
+        .await
+    {
+        error!(
+            "🚨 [VENUE] {} Binance leg left open after closing on Hyperliquid - manual intervention required",
+            position.symbol
+        );
+        return Err(e);
+    }
+    Ok(fill.filled_size)
+}
+
+/// Close COIN-margined shorts whose funding turned negative, then short the
+/// perpetuals of farmed coins not yet held.
+async fn farm_coin_margined(
+    binance: &BinanceClient,
+    executor: &OrderExecutor,
+    positions: &mut Vec<CoinMarginedPosition>,
+    pairs: &[QualifiedPair],
+    contracts: &HashMap<String, CoinContract>,
+    holdings: &ExistingHoldingsConfig,
+    min_funding_rate: Decimal,
+) {
+    let exits: Vec<CoinMarginedPosition> = coin_margined_exits(positions, contracts, holdings)
+        .into_iter()
+        .cloned()
+        .collect();
+    for exit in exits {
+        match executor
+            .place_coin_margined_leg(binance, &exit.symbol, exit.contracts, true)
+            .await
+        {
+            Ok(order) => {
+                let remaining = exit.contracts - order.executed_qty;
+                if remaining <= Decimal::ZERO {
+                    info!("🪙 [COIN-M] Closed {} short", exit.symbol);
+                    positions.retain(|p| p.symbol != exit.symbol);
+                } else {
+                    info!(
+                        "🪙 [COIN-M] Closed {} of {} contract(s) of {} short",
+                        order.executed_qty, exit.contracts, exit.symbol
+                    );
+                    if let Some(position) = positions.iter_mut().find(|p| p.symbol == exit.symbol) {
+                        position.contracts = remaining;
+                    }
+                }
+            }
+            Err(e) => warn!(
+                "⚠️  [COIN-M] Failed to close {} short: {:#}",
+                exit.symbol, e
+            ),
+        }
+    }
+
+    for entry in coin_margined_entries(pairs, positions, holdings, min_funding_rate) {
+        match executor
+            .place_coin_margined_leg(binance, &entry.symbol, entry.contracts, false)
+            .await
+        {
+            Ok(order) if order.executed_qty > Decimal::ZERO => {
+                info!(
+                    "🪙 [COIN-M] Shorted {} {} contract(s) (${:.0}) against held {}, funding {:.4}%",
+                    order.executed_qty,
+                    entry.symbol,
+                    entry.notional * order.executed_qty / entry.contracts,
+                    entry.base_asset,
+                    entry.funding_rate * dec!(100)
+                );
+                positions.push(CoinMarginedPosition {
+                    symbol: entry.symbol,
+                    base_asset: entry.base_asset,
+                    contracts: order.executed_qty,
+                });
+            }
+            Ok(_) => info!("🪙 [COIN-M] {} short not filled", entry.symbol),
+            Err(e) => warn!("⚠️  [COIN-M] Failed to short {}: {:#}", entry.symbol, e),
+        }
+    }
+}
+
+/// Spot book levels sampled per side when pricing a hedge exit.
+const EXIT_LIQUIDITY_DEPTH_LIMIT: u16 = 100;
+
+/// Sample spot depth for held hedges that are due and turn exit liquidity breaches
+/// into downsizing steps.
+///
+/// `positions` are futures symbols with their signed futures quantity; the spot
+/// hedge is the opposite side. Long hedges made of declared holdings are never
+/// sold, so they are skipped.
+async fn exit_liquidity_reductions(
+    client: &dyn ExchangeClient,
+    risk_orchestrator: &mut RiskOrchestrator,
+    positions: &[(String, Decimal)],
+    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
+    config: &Config,
+) -> Vec<PositionReduction> {
+    let now = Utc::now();
+    let mut reductions = Vec::new();
+
+    let mut due = Vec::new();
+    for (symbol, futures_qty) in positions {
+        if futures_qty.is_zero() || !risk_orchestrator.exit_liquidity_due(symbol, now) {
+            continue;
+        }
+        let pair = pairs.iter().find(|p| &p.symbol == symbol);
+        let spot_symbol = pair.map_or(symbol.clone(), |p| p.spot_symbol.clone());
+        let base_asset = pair.map_or_else(
+            || symbol.strip_suffix("USDT").unwrap_or(symbol).to_string(),
+            |p| p.base_asset.clone(),
+        );
+        let hedge_qty = -*futures_qty;
+        if hedge_qty > Decimal::ZERO && config.capital.existing_holdings.covers(&base_asset) {
+            continue;
+        }
+        due.push((symbol, pair, spot_symbol, base_asset, hedge_qty));
+    }
+
+    let books: Vec<_> = stream::iter(&due)
+        .map(|(_, _, spot_symbol, _, _)| {
+            client.get_spot_depth(spot_symbol, EXIT_LIQUIDITY_DEPTH_LIMIT)
+        })
+        .buffered(SYMBOL_LOOKUP_CONCURRENCY)
+        .collect()
+        .await;
+
+    for ((symbol, pair, spot_symbol, base_asset, hedge_qty), book) in due.into_iter().zip(books) {
+        let book = match book {
+            Ok(book) => book,
+            Err(e) => {
+                warn!(
+                    "⚠️  [LIQUIDITY] Failed to sample {} depth: {}",
+                    spot_symbol, e
+                );
+                continue;
+            }
+        };
+        let Some(breach) = risk_orchestrator.assess_exit_liquidity(symbol, &book, hedge_qty, now)
+        else {
+            continue;
+        };
+
+        let cost = &breach.exit_cost;
+        let alert = RiskAlert::new(
+            RiskAlertType::ExitLiquidity {
+                symbol: symbol.clone(),
+                exit_cost: cost.slippage_cost,
+                budget: breach.budget,
+            },
+            AlertSeverity::Warning,
+            Some(symbol.clone()),
+            if breach.insufficient_depth() {
+                format!(
+                    "{} book absorbs only {} of {} hedge",
+                    spot_symbol,
+                    cost.fillable_qty,
+                    hedge_qty.abs()
+                )
+            } else {
+                format!(
+                    "Exiting {} {} costs ${:.2} ({:.3}%) vs ${:.2} budget",
+                    hedge_qty.abs(),
+                    spot_symbol,
+                    cost.slippage_cost,
+                    cost.slippage_pct(breach.mid_price) * dec!(100),
+                    breach.budget
+                )
+            },
+            format!("Downsize {} toward {}", symbol, breach.affordable_qty),
+        )
+        .with_metric("hedge_qty", hedge_qty)
+        .with_metric("fillable_qty", cost.fillable_qty)
+        .with_metric("affordable_qty", breach.affordable_qty);
+        // Downsizing is skipped when the route doesn't allow automatic reductions
+        let alert = risk_orchestrator.raise(alert);
+
+        let target_qty = breach.target_qty();
+        if alert.action < AlertAction::AutoReduce || target_qty >= hedge_qty.abs() {
+            continue;
+        }
+        reductions.push(PositionReduction {
+            symbol: symbol.clone(),
+            spot_symbol,
+            base_asset,
+            current_size_usdt: hedge_qty.abs() * breach.mid_price,
+            target_size_usdt: target_qty * breach.mid_price,
+            reduction_usdt: (hedge_qty.abs() - target_qty) * breach.mid_price,
+            funding_rate: pair.map_or(Decimal::ZERO, |p| p.funding_rate),
+            exceeds_notional_cap: false,
+            urgency: ReductionUrgency::ExitLiquidity,
+        });
+    }
+
+    reductions
+}
+
+/// Partial closes of held positions that reached their next funding milestone.
+///
+/// Positions without a tracked record or a price are skipped.
+fn take_profit_reductions(
+    take_profit: &mut TakeProfitMonitor,
+    risk_orchestrator: &RiskOrchestrator,
+    positions: &[(String, Decimal)],
+    prices: &HashMap<String, Decimal>,
+    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
+) -> Vec<PositionReduction> {
+    let mut reductions = Vec::new();
+
+    for (symbol, futures_qty) in positions {
+        let Some(price) = prices.get(symbol).copied().filter(|p| *p > Decimal::ZERO) else {
+            continue;
+        };
+        let Some(tracked) = risk_orchestrator.get_tracked_position(symbol) else {
+            continue;
+        };
+        let notional = futures_qty.abs() * price;
+        let Some(trigger) = take_profit.check(tracked, notional) else {
+            continue;
+        };
+
+        info!(
+            "💰 [TAKE-PROFIT] {} take #{}: ${:.2} net funding >= ${:.2} milestone, closing ${:.2} of ${:.2}",
+            symbol,
+            trigger.take,
+            trigger.funding_gain,
+            trigger.milestone,
+            trigger.reduction_usdt,
+            notional
+        );
+        let pair = pairs.iter().find(|p| &p.symbol == symbol);
+        reductions.push(PositionReduction {
+            symbol: symbol.clone(),
+            spot_symbol: pair.map_or(symbol.clone(), |p| p.spot_symbol.clone()),
+            base_asset: pair.map_or_else(
+                || symbol.strip_suffix("USDT").unwrap_or(symbol).to_string(),
+                |p| p.base_asset.clone(),
+            ),
+            current_size_usdt: notional,
+            target_size_usdt: notional - trigger.reduction_usdt,
+            reduction_usdt: trigger.reduction_usdt,
+            funding_rate: pair.map_or(Decimal::ZERO, |p| p.funding_rate),
+            exceeds_notional_cap: false,
+            urgency: ReductionUrgency::TakeProfit,
+        });
+    }
+
+    reductions
+}
+
+/// Full closes of held positions whose symbols are quarantined.
+///
+/// Positions without a price are skipped.
+fn quarantine_exit_reductions(
+    positions: &[(String, Decimal)],
+    prices: &HashMap<String, Decimal>,
+    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
+) -> Vec<PositionReduction> {
+    let mut reductions = Vec::new();
+
+    for (symbol, futures_qty) in positions {
+        let Some(price) = prices.get(symbol).copied().filter(|p| *p > Decimal::ZERO) else {
+            continue;
+        };
+        let notional = futures_qty.abs() * price;
+        let pair = pairs.iter().find(|p| &p.symbol == symbol);
+        reductions.push(PositionReduction {
+            symbol: symbol.clone(),
+            spot_symbol: pair.map_or(symbol.clone(), |p| p.spot_symbol.clone()),
+            base_asset: pair.map_or_else(
+                || symbol.strip_suffix("USDT").unwrap_or(symbol).to_string(),
+                |p| p.base_asset.clone(),
+            ),
+            current_size_usdt: notional,
+            target_size_usdt: Decimal::ZERO,
+            reduction_usdt: notional,
+            funding_rate: pair.map_or(Decimal::ZERO, |p| p.funding_rate),
+            exceeds_notional_cap: false,
+            urgency: ReductionUrgency::RiskExit,
+        });
+    }
+
+    reductions
+}
+
+/// Open a user data stream, seeded with the futures positions held right now.
+async fn connect_user_data_stream(
+    client: &BinanceClient,
+    ws: &BinanceWebSocket,
+) -> Result<UserDataStream> {
+    let listen_key = client
+        .create_listen_key()
+        .await
+        .context("Failed to create listen key")?;
+    let positions = fetch_real_positions(client).await?;
+    UserDataStream::connect(ws, listen_key, positions, Utc::now()).await
+}
+
+/// Apply user data stream updates.
+///
+/// Final order updates resolve the order journal and position changes update the
+/// risk orchestrator.
+fn apply_user_data(
+    updates: Vec<WsEvent>,
+    executor: &OrderExecutor,
+    risk_orchestrator: &mut RiskOrchestrator,
+) {
+    for update in updates {
+        match update {
+            WsEvent::OrderUpdate(event) => {
+                let order = &event.order;
+                match executor.apply_order_update(order) {
+                    Ok(true) => debug!(
+                        "📨 [STREAM] {} order {} {} ({} filled)",
+                        order.symbol, order.client_order_id, order.status, order.filled_qty
+                    ),
+                    Ok(false) => {}
+                    Err(e) => warn!(
+                        "⚠️  [STREAM] Failed to resolve order {}: {:#}",
+                        order.client_order_id, e
+                    ),
+                }
+            }
+            WsEvent::AccountUpdate(event) => {
+                for position in &event.data.positions {
+                    let (Some(amount), Some(unrealized)) =
+                        (position.amount(), position.unrealized())
+                    else {
+                        continue;
+                    };
+                    if risk_orchestrator.apply_position_update(&position.symbol, amount, unrealized)
+                    {
+                        warn!(
+                            "⚠️  [STREAM] {} futures position was closed on the exchange, not by the bot (liquidation, ADL or manual close)",
+                            position.symbol
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fetch real positions.
+async fn fetch_real_positions(client: &dyn ExchangeClient) -> Result<HashMap<String, Decimal>> {
+    match client.get_positions().await {
+        Ok(positions) => Ok(positions
+            .into_iter()
+            .filter(|p| p.position_amt != Decimal::ZERO)
+            .map(|p| (p.symbol, p.position_amt))
+            .collect()),
+        Err(e) => {
+            error!("Failed to fetch real positions: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Fetch current prices from real client for qualified pairs.
+async fn fetch_prices(
+    client: &dyn ExchangeClient,
+    marks: &MarkPriceCache,
+    books: &PriceFeed,
+    pairs: &[funding_fee_farmer::exchange::QualifiedPair],
+) -> HashMap<String, Decimal> {
+    let symbols: Vec<String> = pairs.iter().map(|p| p.symbol.clone()).collect();
+    fetch_prices_for_symbols(client, marks, books, &symbols).await
+}
+
+/// Balances of the futures wallet and cross margin account for the pre-entry funds check.
+async fn fetch_segregated_funds(
+    client: &BinanceClient,
+    futures_balances: &[AccountBalance],
+    reserve_buffer: Decimal,
+) -> Result<SegregatedFunds> {
+    let usdt = futures_balances
+        .iter()
+        .find(|b| b.asset == "USDT")
+        .context("No USDT balance in futures wallet")?;
+    let margin = client
+        .get_cross_margin_account()
+        .await
+        .context("Failed to fetch cross margin account")?;
+    // Margin equity is reported in BTC
+    let btc_price = client
+        .get_spot_price("BTCUSDT")
+        .await
+        .context("Failed to fetch BTC price for margin equity")?;
+    let margin_free_usdt = margin
+        .user_assets
+        .iter()
+        .find(|a| a.asset == "USDT")
+        .map_or(Decimal::ZERO, |a| a.free);
+
+    Ok(SegregatedFunds::new(
+        usdt.available_balance,
+        usdt.margin_balance,
+        margin_free_usdt,
+        margin.total_net_asset_of_btc * btc_price,
+        reserve_buffer,
+    ))
+}
+
+/// Balance of a unified account, which funds both legs, for the pre-entry funds check.
+fn unified_funds(balances: &[AccountBalance], reserve_buffer: Decimal) -> Result<SegregatedFunds> {
+    let usdt = balances
+        .iter()
+        .find(|b| b.asset == "USDT")
+        .context("No USDT balance in unified account")?;
+    Ok(SegregatedFunds::unified(
+        usdt.available_balance,
+        usdt.margin_balance,
+        reserve_buffer,
+    ))
+}
+
+/// Alert that a live entry was refused because a wallet cannot fund its leg.
+/// Fetch the borrow limits of borrowed assets and of the best scanned pairs
+/// whose spot hedge would borrow, alerting on assets newly near their limit.
+async fn refresh_borrow_limits(
+    client: &BinanceClient,
+    marks: &MarkPriceCache,
+    books: &PriceFeed,
+    borrow_limits: &mut BorrowLimits,
+    risk_orchestrator: &mut RiskOrchestrator,
+    pairs: &[QualifiedPair],
+) {
+    let borrowed: HashMap<String, Decimal> = match client.get_cross_margin_account().await {
+        Ok(account) => account
+            .user_assets
+            .into_iter()
+            .map(|a| (a.asset, a.borrowed))
+            .collect(),
+        Err(e) => {
+            warn!("⚠️  [BORROW] Failed to fetch borrowed amounts: {:#}", e);
+            return;
+        }
+    };
+    let assets = borrow_limits.assets_to_check(&borrowed, pairs);
+    let symbols: Vec<String> = assets.iter().map(|a| format!("{}USDT", a)).collect();
+    let prices = fetch_prices_for_symbols(client, marks, books, &symbols).await;
+
+    let now = Utc::now();
+    for (asset, symbol) in assets.iter().zip(&symbols) {
+        let Some(price) = prices.get(symbol).copied() else {
+            continue;
+        };
+        let max = match client.get_max_borrowable(asset).await {
+            Ok(max) => max,
+            Err(e) => {
+                warn!(
+                    "⚠️  [BORROW] Failed to fetch borrow limit of {}: {:#}",
+                    asset, e
+                );
+                continue;
+            }
+        };
+        let borrowed = borrowed.get(asset).copied().unwrap_or(Decimal::ZERO);
+        if let Some(limit) = borrow_limits.update(asset, borrowed, &max, price, now) {
+            alert_borrow_limit(risk_orchestrator, &limit);
+        }
+    }
+
+    for limit in borrow_limits.limits() {
+        info!(
+            "📐 [BORROW] {}: {} of {} limit borrowed ({}), ${:.0} left to borrow",
+            limit.asset,
+            limit.borrowed,
+            limit.limit,
+            limit
+                .utilization()
+                .map_or("no limit".to_string(), |u| format!("{:.1}%", u * dec!(100))),
+            limit.headroom_usdt()
+        );
+    }
+}
+
+fn alert_borrow_limit(risk_orchestrator: &mut RiskOrchestrator, limit: &BorrowLimit) {
+    let utilization = limit.utilization().unwrap_or_default();
+    warn!(
+        "📐 [BORROW] {} borrow limit {:.1}% used, ${:.0} left to borrow",
+        limit.asset,
+        utilization * dec!(100),
+        limit.headroom_usdt()
+    );
+    let alert = RiskAlert::new(
+        RiskAlertType::BorrowLimit {
+            asset: limit.asset.clone(),
+            utilization,
+            headroom_usdt: limit.headroom_usdt(),
+        },
+        AlertSeverity::Warning,
+        None,
+        format!(
+            "{:.1}% of the {} borrow limit is in use ({} of {}), ${:.0} left to borrow",
+            utilization * dec!(100),
+            limit.asset,
+            limit.borrowed,
+            limit.limit,
+            limit.headroom_usdt()
+        ),
+        format!(
+            "Expect {} short hedges to be capped; hedge with a perp or repay other {} debt",
+            limit.asset, limit.asset
+        ),
+    )
+    .with_metric("utilization", utilization)
+    .with_metric("headroom_usdt", limit.headroom_usdt());
+    risk_orchestrator.raise(alert);
+}
+
+fn alert_borrow_limit_exceeded(
+    risk_orchestrator: &mut RiskOrchestrator,
+    symbol: &str,
+    shortfall: &BorrowShortfall,
+    price: Decimal,
+) {
+    warn!(
+        "🚫 [BORROW] {} entry refused: needs {:.4} {} borrowed, only {:.4} left to borrow",
+        symbol, shortfall.required, shortfall.asset, shortfall.available
+    );
+    let alert = RiskAlert::new(
+        RiskAlertType::BorrowLimitExceeded {
+            symbol: symbol.to_string(),
+            asset: shortfall.asset.clone(),
+            required: shortfall.required,
+            available: shortfall.available,
+        },
+        AlertSeverity::Warning,
+        Some(symbol.to_string()),
+        format!(
+            "{} spot short needs {:.4} {} (${:.0}) but only {:.4} can still be borrowed",
+            symbol,
+            shortfall.required,
+            shortfall.asset,
+            shortfall.required * price,
+            shortfall.available
+        ),
+        format!(
+            "Lower the {} allocation or hedge it with a perp instead",
+            symbol
+        ),
+    )
+    .with_metric("required", shortfall.required)
+    .with_metric("available", shortfall.available);
+    risk_orchestrator.raise(alert);
+}
+
+fn alert_insufficient_funds(
+    risk_orchestrator: &mut RiskOrchestrator,
+    symbol: &str,
+    shortfall: &FundsShortfall,
+) {
+    warn!(
+        "🚫 [FUNDS] {} entry refused: {} wallet has ${:.2}, needs ${:.2}",
+        symbol, shortfall.wallet, shortfall.available, shortfall.required
+    );
+    let alert = RiskAlert::new(
+        RiskAlertType::InsufficientFunds {
+            symbol: symbol.to_string(),
+            wallet: shortfall.wallet.to_string(),
+            required: shortfall.required,
+            available: shortfall.available,
+        },
+        AlertSeverity::Warning,
+        Some(symbol.to_string()),
+        format!(
+            "{} wallet has ${:.2} but {} needs ${:.2} (initial margin + reserve)",
+            shortfall.wallet, shortfall.available, symbol, shortfall.required
+        ),
+        format!(
+            "Transfer funds to the {} wallet or lower position sizes",
+            shortfall.wallet
+        ),
+    )
+    .with_metric("required", shortfall.required)
+    .with_metric("available", shortfall.available);
+    risk_orchestrator.raise(alert);
+}
+
+/// Oldest streamed mark price served instead of a REST snapshot.
+const MARK_PRICE_MAX_AGE_SECS: i64 = 5;
+
+/// Oldest streamed book ticker served instead of a REST snapshot.
+const BOOK_TICKER_MAX_AGE_SECS: i64 = 5;
+
+/// Stream the books of the held symbols, resubscribing when they change.
+async fn sync_price_feed(
+    feed: &PriceFeed,
+    ws: &BinanceWebSocket,
+    risk_orchestrator: &RiskOrchestrator,
+) {
+    let held: Vec<String> = risk_orchestrator
+        .get_all_tracked_positions()
+        .iter()
+        .map(|p| p.symbol.clone())
+        .collect();
+    if !feed.needs_subscribe(&held) {
+        return;
+    }
+    match feed.subscribe(ws, &held).await {
+        Ok(()) if held.is_empty() => debug!("📗 [BOOKS] Nothing held, book ticker stream closed"),
+        Ok(()) => info!(
+            "📗 [BOOKS] Book ticker stream connected for {} held symbols",
+            held.len()
+        ),
+        Err(e) => warn!(
+            "⚠️  [BOOKS] Book ticker stream unavailable, polling books: {:#}",
+            e
+        ),
+    }
+}
+
+/// Connect the mark price stream; prices come from REST until it delivers.
+async fn connect_mark_price_stream(cache: &MarkPriceCache, ws: &BinanceWebSocket) {
+    match cache.connect(ws).await {
+        Ok(()) => info!("📈 [MARKS] Mark price stream connected"),
+        Err(e) => warn!(
+            "⚠️  [MARKS] Mark price stream unavailable, polling prices: {:#}",
+            e
+        ),
+    }
+}
+
+/// Fetch current prices for specific symbols.
+///
+/// The streamed book ticker mid is used when all symbols are fresh in the feed,
+/// then streamed mark prices, otherwise the polled book ticker mid.
+async fn fetch_prices_for_symbols(
+    client: &dyn ExchangeClient,
+    marks: &MarkPriceCache,
+    books: &PriceFeed,
+    symbols: &[String],
+) -> HashMap<String, Decimal> {
+    let now = Utc::now();
+    if let Some(prices) = books.mid_prices(symbols, now) {
+        return prices;
+    }
+    if let Some(prices) = marks.mark_prices(symbols, now) {
+        return prices;
+    }
+    match client.get_book_tickers().await {
+        Ok(tickers) => tickers
+            .into_iter()
+            .filter(|t| symbols.iter().any(|s| s == &t.symbol))
+            .map(|t| {
+                let mid_price = (t.bid_price + t.ask_price) / dec!(2);
+                (t.symbol, mid_price)
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Failed to fetch prices: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Fetch current predicted funding rates for specific symbols, streamed or from the
+/// premium index.
+async fn fetch_funding_rates_for_symbols(
+    client: &dyn ExchangeClient,
+    marks: &MarkPriceCache,
+    symbols: &[String],
+) -> HashMap<String, Decimal> {
+    if symbols.is_empty() {
+        return HashMap::new();
+    }
+    if let Some(rates) = marks.funding_rates(symbols, Utc::now()) {
+        return rates;
+    }
+
+    match client.get_funding_rates().await {
+        Ok(rates) => rates
+            .into_iter()
+            .filter(|r| symbols.iter().any(|s| s == &r.symbol))
+            .map(|r| (r.symbol, r.funding_rate))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to fetch funding rates: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Pay mock positions the funding settlements missed while offline.
+///
+/// Each symbol's missed settlements (on its own schedule) are matched to its
+/// historical funding record (settlement times carry a few milliseconds of jitter).
+/// Symbols whose history can't be fetched are skipped with a warning. Returns
+/// caught-up funding per symbol.
+async fn catch_up_missed_funding(
+    client: &dyn ExchangeClient,
+    marks: &MarkPriceCache,
+    books: &PriceFeed,
+    mock_client: &MockBinanceClient,
+    missed: &HashMap<String, Vec<DateTime<Utc>>>,
+) -> HashMap<String, Decimal> {
+    const MATCH_TOLERANCE_MS: i64 = 60_000;
+
+    let mut settlements: HashMap<String, Vec<Decimal>> = HashMap::new();
+    for (symbol, missed) in missed {
+        let (Some(first), Some(last)) = (missed.first(), missed.last()) else {
+            continue;
+        };
+        let start = first.timestamp_millis() - MATCH_TOLERANCE_MS;
+        let end = last.timestamp_millis() + MATCH_TOLERANCE_MS;
+        let history = match client.get_funding_rate_history(symbol, start, end).await {
+            Ok(history) => history,
+            Err(e) => {
+                warn!(
+                    "⚠️  [CATCH-UP] Failed to fetch funding history for {}: {}",
+                    symbol, e
+                );
+                continue;
+            }
+        };
+        let rates: Vec<Decimal> = missed
+            .iter()
+            .filter_map(|settlement| {
+                let at = settlement.timestamp_millis();
+                history
+                    .iter()
+                    .find(|r| (r.funding_time - at).abs() <= MATCH_TOLERANCE_MS)
+                    .map(|r| r.funding_rate)
+            })
+            .collect();
+        if rates.len() < missed.len() {
+            warn!(
+                "⚠️  [CATCH-UP] {} has history for only {}/{} missed settlements",
+                symbol,
+                rates.len(),
+                missed.len()
+            );
+        }
+        settlements.insert(symbol.clone(), rates);
+    }
+
+    let symbols: Vec<String> = missed.keys().cloned().collect();
+    let prices = fetch_prices_for_symbols(client, marks, books, &symbols).await;
+    mock_client
+        .apply_missed_funding(&settlements, &prices)
+        .await
+}
+
+/// File name of the fallback state database in the system temp directory.
+const FALLBACK_STATE_DB: &str = "funding-fee-farmer-fallback.db";
+
+/// Save `state`, tracking failures with the watchdog.
+///
+/// A failed save is repeated against the fallback databases so the state survives
+/// somewhere; the error is still returned, since the main database is behind.
+fn save_state_watched(
+    persistence: &PersistenceManager,
+    watchdog: &mut PersistenceWatchdog,
+    state: &PersistedState,
+) -> Result<()> {
+    let now = Utc::now();
+    match persistence.save_state(state) {
+        Ok(()) => {
+            if let Some(recovery) = watchdog.record_success(now) {
+                info!(
+                    "💾 [PERSISTENCE] State saves recovered after {} failure(s) over {}{}",
+                    recovery.failures,
+                    humanize_duration(recovery.duration),
+                    if recovery.blocked_entries {
+                        " - new entries resume"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            Ok(())
+        }
+        Err(e) => {
+            watchdog.record_failure(now);
+            save_state_fallback(state, watchdog.secondary_db());
+            Err(e)
+        }
+    }
+}
+
+/// Save `state` to the first fallback database that accepts it: the secondary
+/// database when configured, then one in the system temp directory.
+fn save_state_fallback(state: &PersistedState, secondary_db: Option<&str>) {
+    let temp_db = std::env::temp_dir().join(FALLBACK_STATE_DB);
+    let targets = secondary_db
+        .map(std::path::PathBuf::from)
+        .into_iter()
+        .chain(std::iter::once(temp_db));
+    for path in targets {
+        match PersistenceManager::new(&path).and_then(|fallback| fallback.save_state(state)) {
+            Ok(()) => {
+                warn!(
+                    "💾 [PERSISTENCE] State saved to fallback database {}",
+                    path.display()
+                );
+                return;
+            }
+            Err(e) => warn!(
+                "⚠️  [PERSISTENCE] Fallback save to {} failed: {:#}",
+                path.display(),
+                e
+            ),
+        }
+    }
+    error!("❌ [PERSISTENCE] State could not be saved to any database");
+}
+
+/// Raise the alert for a newly crossed state save failure threshold.
+fn report_persistence_failure(
+    watchdog: &mut PersistenceWatchdog,
+    risk_orchestrator: &mut RiskOrchestrator,
+) {
+    let Some(severity) = watchdog.take_escalation() else {
+        return;
+    };
+    let duration = watchdog.failing_for(Utc::now()).unwrap_or_default();
+    error!(
+        "❌ [PERSISTENCE] {} state saves failed in a row over {}",
+        watchdog.failures(),
+        humanize_duration(duration)
+    );
+    let alert = RiskAlert::new(
+        RiskAlertType::PersistenceFailure {
+            failures: watchdog.failures(),
+            minutes: duration.num_minutes(),
+        },
+        severity,
+        None,
+        format!(
+            "{} state saves failed in a row over {}",
+            watchdog.failures(),
+            humanize_duration(duration)
+        ),
+        "Check disk space and database permissions; a restart would reload stale state".to_string(),
+    )
+    .with_metric("failures", Decimal::from(watchdog.failures()));
+    risk_orchestrator.raise(alert);
+}
+
+/// Minimum seconds between non-forced mock state saves.
+const STATE_SAVE_DEBOUNCE_SECS: i64 = 5;
+
+/// Save the mock state if it changed since the last save.
+///
+/// Non-forced checkpoints are debounced by `STATE_SAVE_DEBOUNCE_SECS`; forced ones
+/// only skip unchanged state. Returns whether a save was written.
+async fn checkpoint_mock_state(
+    persistence: &PersistenceManager,
+    mock_client: &MockBinanceClient,
+    tracker: &mut DirtyTracker,
+    watchdog: &mut PersistenceWatchdog,
+    last_funding_period: Option<u32>,
+    force: bool,
+) -> Result<bool> {
+    let now = Utc::now();
+    let version = (mock_client.revision(), last_funding_period);
+    if !tracker.save_due(version, now, force) {
+        return Ok(false);
+    }
+
+    let mut state_to_save = mock_client.export_state().await;
+    state_to_save.last_funding_period = last_funding_period;
+    save_state_watched(persistence, watchdog, &state_to_save)?;
+    tracker.mark_saved(version, now);
+    debug!("💾 [PERSISTENCE] State saved (revision {})", version.0);
+    Ok(true)
+}
+
+/// Log journaled orders resolved at startup.
+///
+/// Orders that filled before the crash may have left a leg the strategy no longer
+/// tracks; they are logged loudly so the hedge can be verified.
+fn log_journal_reconciliation(resolved: &[JournalReconciliation]) {
+    if resolved.is_empty() {
+        return;
+    }
+
+    for entry in resolved {
+        let intent = &entry.intent;
+        match &entry.order {
+            Some(order) if order.executed_qty > Decimal::ZERO => warn!(
+                "🧾 [JOURNAL] Interrupted {} {} {} {} filled {} ({:?}, order {}, correlation {}) - verify the hedge",
+                intent.leg,
+                intent.side,
+                intent.quantity,
+                intent.symbol,
+                order.executed_qty,
+                order.status,
+                order.order_id,
+                intent.correlation_id
+            ),
+            Some(order) => info!(
+                "🧾 [JOURNAL] Interrupted {} {} {} unfilled ({:?})",
+                intent.leg, intent.symbol, intent.client_order_id, order.status
+            ),
+            None => info!(
+                "🧾 [JOURNAL] Interrupted {} {} {} never reached the exchange",
+                intent.leg, intent.symbol, intent.client_order_id
+            ),
+        }
+    }
+    info!(
+        "🧾 [JOURNAL] Reconciled {} pending order intents",
+        resolved.len()
+    );
+}
+
+/// Seconds between risk-critical read retries in safe-mode.
+const SAFE_MODE_RETRY_SECS: u64 = 15;
+
+/// Retry the reads risk checks depend on.
+///
+/// Live: positions and account balances. Mock: the premium index the paper
+/// positions are marked against.
+async fn probe_risk_reads(client: &dyn ExchangeClient, trading_mode: TradingMode) -> Result<()> {
+    if trading_mode == TradingMode::Live {
+        client.get_positions().await?;
+        client.get_account_balance().await?;
+    } else {
+        client.get_funding_rates().await?;
+    }
+    Ok(())
+}
+
+/// Human-readable maintenance state for logs.
+fn describe_maintenance(state: &MaintenanceState, display_tz: &DisplayTimezone) -> String {
+    let note = |window: &MaintenanceWindow| {
+        if window.note.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", window.note)
+        }
+    };
+    match state {
+        MaintenanceState::Normal => "No maintenance".to_string(),
+        MaintenanceState::Upcoming(window) => format!(
+            "Maintenance {} - {}{}",
+            display_tz.short(window.start),
+            display_tz.short(window.end),
+            note(window)
+        ),
+        MaintenanceState::Active {
+            window: Some(window),
+        } => format!(
+            "Maintenance in progress until {}{}",
+            display_tz.short(window.end),
+            note(window)
+        ),
+        MaintenanceState::Active { window: None } => {
+            "Maintenance reported by the system status".to_string()
+        }
+    }
+}
+
+/// Warn that the reserve is still dipped into after a flash release ended.
+fn report_reserve_not_restored(risk_orchestrator: &mut RiskOrchestrator, shortfall: Decimal) {
+    let message = format!(
+        "Reserve still {:.2}% of equity short after the flash release ended",
+        shortfall * dec!(100)
+    );
+    warn!("⚡ [FLASH] {}", message);
+    let alert = RiskAlert::new(
+        RiskAlertType::ReserveNotRestored { shortfall },
+        AlertSeverity::Warning,
+        None,
+        message,
+        "Reduce the flash position back to its normal target".to_string(),
+    );
+    risk_orchestrator.raise(alert);
+}
 
-                match mock_client.place_futures_order(&futures_order).await {
-                    Ok(_) => {
-                        info!(
-                            "✅ [EMERGENCY] Futures closed for {} on attempt {}",
-                            pos.symbol, attempt
-                        );
-                        futures_closed = true;
-                        break;
-                    }
-                    Err(e) => {
-                        let backoff_ms = 100 * 2_u64.pow(attempt.min(5));
-                        warn!(
-                            "⚠️ [EMERGENCY] Futures close attempt {}/{} failed for {}: {} - retrying in {}ms",
-                            attempt, max_retries, pos.symbol, e, backoff_ms
-                        );
-                        tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                    }
+/// Announce upcoming or running exchange maintenance.
+fn report_maintenance(
+    risk_orchestrator: &mut RiskOrchestrator,
+    state: &MaintenanceState,
+    now: DateTime<Utc>,
+    display_tz: &DisplayTimezone,
+) {
+    let message = describe_maintenance(state, display_tz);
+    info!("🛠️  [MAINTENANCE] {} - new entries paused", message);
+    let (start, end) = match state {
+        MaintenanceState::Upcoming(window)
+        | MaintenanceState::Active {
+            window: Some(window),
+        } => (window.start, Some(window.end)),
+        _ => (now, None),
+    };
+    let alert = RiskAlert::new(
+        RiskAlertType::ExchangeMaintenance { start, end },
+        AlertSeverity::Info,
+        None,
+        message,
+        "New entries paused; errors during the window are expected downtime".to_string(),
+    );
+    risk_orchestrator.raise(alert);
+}
+
+/// Notify the operator that safe-mode was entered.
+fn report_exchange_outage(
+    risk_orchestrator: &mut RiskOrchestrator,
+    duration: chrono::Duration,
+    open_positions: usize,
+) {
+    error!(
+        "🛟 [SAFE-MODE] Exchange unreachable for {} with {} open position(s) - entering safe-mode",
+        humanize_duration(duration),
+        open_positions
+    );
+    let alert = RiskAlert::new(
+        RiskAlertType::ExchangeOutage {
+            minutes: duration.num_minutes(),
+            open_positions,
+        },
+        AlertSeverity::Critical,
+        None,
+        format!(
+            "Exchange unreachable for {} with {} open position(s)",
+            humanize_duration(duration),
+            open_positions
+        ),
+        "Safe-mode: new entries and rebalancing suspended, exits queued until connectivity returns"
+            .to_string(),
+    );
+    risk_orchestrator.raise(alert);
+}
+
+/// Reconcile state after safe-mode and hand queued actions back to the normal cycle.
+///
+/// Live: pending journal intents are resolved, exchange positions the tracker
+/// doesn't know are registered, and tracked positions gone from the exchange are
+/// dropped. Queued exits are not replayed blindly; the cycle that follows
+/// re-evaluates every position with fresh data.
+async fn recover_from_outage(
+    recovery: &OutageRecovery,
+    trading_mode: TradingMode,
+    client: &dyn ExchangeClient,
+    executor: &OrderExecutor,
+    risk_orchestrator: &mut RiskOrchestrator,
+    persistence: &PersistenceManager,
+) {
+    info!(
+        "🛟 [SAFE-MODE] Exchange reachable again after {} - reconciling",
+        humanize_duration(recovery.duration)
+    );
+
+    if trading_mode == TradingMode::Live {
+        match executor.reconcile_journal(client).await {
+            Ok(resolved) => log_journal_reconciliation(&resolved),
+            Err(e) => warn!("⚠️  [JOURNAL] Failed to reconcile order journal: {}", e),
+        }
+        match seed_live_positions(client, risk_orchestrator).await {
+            Ok(0) => {}
+            Ok(count) => warn!(
+                "🛟 [SAFE-MODE] Registered {} exchange position(s) the tracker didn't know",
+                count
+            ),
+            Err(e) => warn!(
+                "⚠️  [SAFE-MODE] Failed to register exchange positions: {}",
+                e
+            ),
+        }
+        match fetch_real_positions(client).await {
+            Ok(exchange_positions) => {
+                let gone: Vec<String> = risk_orchestrator
+                    .get_all_tracked_positions()
+                    .iter()
+                    .map(|p| p.symbol.clone())
+                    .filter(|symbol| !exchange_positions.contains_key(symbol))
+                    .collect();
+                for symbol in gone {
+                    warn!(
+                        "🛟 [SAFE-MODE] {} closed on the exchange during the outage - untracked",
+                        symbol
+                    );
+                    close_tracked_position(
+                        risk_orchestrator,
+                        persistence,
+                        &symbol,
+                        ExitKind::Forced,
+                    );
                 }
             }
+            Err(e) => warn!("⚠️  [SAFE-MODE] Failed to compare positions: {}", e),
+        }
+    }
 
-            if !futures_closed {
-                error!(
-                    "🚨 [EMERGENCY] FAILED to close futures for {} after {} attempts!",
-                    pos.symbol, max_retries
+    for deferred in &recovery.deferred {
+        warn!(
+            "🛟 [SAFE-MODE] Queued {} for {} since {}: {} - re-evaluating this cycle",
+            deferred.action,
+            deferred.symbol,
+            deferred.deferred_at.format("%H:%M:%S"),
+            deferred.reason
+        );
+    }
+
+    let alert = RiskAlert::new(
+        RiskAlertType::ExchangeRecovered {
+            minutes: recovery.duration.num_minutes(),
+            deferred_actions: recovery.deferred.len(),
+        },
+        AlertSeverity::Info,
+        None,
+        format!(
+            "Exchange reachable again after {}; {} queued action(s)",
+            humanize_duration(recovery.duration),
+            recovery.deferred.len()
+        ),
+        "Safe-mode left; positions reconciled and re-evaluated".to_string(),
+    );
+    risk_orchestrator.raise(alert);
+}
+
+/// Minutes between updates of the run record.
+const RUN_RECORD_INTERVAL_MINUTES: i64 = 60;
+
+/// Update the run record with the KPIs so far.
+///
+/// Mock funding and fees are the change in the paper account's totals since start;
+/// live ones come from the futures income history since the run started.
+async fn refresh_run_record(
+    run: &mut PersistedRun,
+    metrics: &AppMetrics,
+    trading_mode: TradingMode,
+    mock_client: &MockBinanceClient,
+    real_client: &dyn ExchangeClient,
+    mock_totals_at_start: (Decimal, Decimal),
+) {
+    run.scans = metrics.scan_count;
+    run.entries = metrics.positions_entered;
+    run.exits = metrics.positions_exited;
+    run.errors = metrics.errors_count;
+
+    if trading_mode == TradingMode::Mock {
+        let state = mock_client.get_state().await;
+        let (_, unrealized_pnl) = mock_client.calculate_pnl().await;
+        run.funding = state.total_funding_received - mock_totals_at_start.0;
+        run.fees = state.total_trading_fees - mock_totals_at_start.1;
+        run.final_equity = Some(state.balance + unrealized_pnl);
+        return;
+    }
+
+    let since = run.started_at.timestamp_millis();
+    match real_client.get_income_history("FUNDING_FEE", since).await {
+        Ok(records) => run.funding = records.iter().map(|r| r.income).sum(),
+        Err(e) => warn!("⚠️  [RUN] Failed to fetch funding income: {}", e),
+    }
+    // Commissions are reported as negative income
+    match real_client.get_income_history("COMMISSION", since).await {
+        Ok(records) => run.fees = -records.iter().map(|r| r.income).sum::<Decimal>(),
+        Err(e) => warn!("⚠️  [RUN] Failed to fetch commissions: {}", e),
+    }
+    match real_client.get_account_balance().await {
+        Ok(balances) => {
+            run.final_equity = Some(
+                balances
+                    .iter()
+                    .map(|b| b.wallet_balance + b.unrealized_profit)
+                    .sum(),
+            )
+        }
+        Err(e) => warn!("⚠️  [RUN] Failed to fetch final equity: {}", e),
+    }
+}
+
+/// Stamp for records written under `config` by this build.
+fn record_stamp(config: &Config) -> RecordStamp {
+    RecordStamp {
+        config_hash: config.config_hash().unwrap_or_else(|e| {
+            warn!("⚠️  [CONFIG] Failed to hash configuration: {}", e);
+            String::new()
+        }),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Add the effective configuration to the config history if it changed since the last run.
+///
+/// Returns whether the configuration differs from the last one recorded.
+fn record_config_change(
+    persistence: &PersistenceManager,
+    config: &Config,
+    stamp: &RecordStamp,
+) -> bool {
+    let recorded = config
+        .to_effective_string(ConfigFormat::Toml)
+        .and_then(|effective| persistence.record_config_change(stamp, &effective, Utc::now()));
+    match recorded {
+        Ok(true) => {
+            info!(
+                "🧾 [CONFIG] New configuration era: config {}, v{}",
+                stamp.config_hash, stamp.version
+            );
+            annotate_equity(
+                persistence,
+                EquityEventKind::ConfigChange,
+                format!("config {}, v{}", stamp.config_hash, stamp.version),
+            );
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            warn!("⚠️  [CONFIG] Failed to record configuration change: {}", e);
+            false
+        }
+    }
+}
+
+/// Seconds between main loop iterations (one scan each).
+const SCAN_INTERVAL_SECS: u64 = 60;
+
+/// Log the projected API load of `config` and warn about limits it comes close to.
+fn check_api_budget(config: &Config, new_config_era: bool) {
+    let projection = UsageProjection::from_config(config, SCAN_INTERVAL_SECS);
+    info!("📶 [API] Projected usage: {}", projection);
+    let breaches = projection.breaches(&config.api_budget);
+    if breaches.is_empty() {
+        return;
+    }
+    let cause = if new_config_era {
+        "The configuration change"
+    } else {
+        "The configuration"
+    };
+    for breach in breaches {
+        warn!(
+            "⚠️  [API] {} exceeds {:.0}% of the API budget: {}",
+            cause,
+            config.api_budget.warn_fraction * dec!(100),
+            breach
+        );
+    }
+}
+
+/// Log a day's API usage and warn about limits its busiest minutes came close to.
+fn report_api_usage(day: &DailyUsage, budget: &ApiBudgetConfig) {
+    for (name, usage) in [("futures", &day.futures), ("spot", &day.spot)] {
+        info!(
+            "📶 [API] {} {}: {} requests, weight {} (peak {}/min), {} orders (peak {}/min)",
+            day.date,
+            name,
+            usage.requests,
+            usage.weight,
+            usage.peak_weight_per_minute,
+            usage.orders,
+            usage.peak_orders_per_minute
+        );
+    }
+    for breach in day.breaches(budget) {
+        warn!("⚠️  [API] Usage on {} near the limit: {}", day.date, breach);
+    }
+}
+
+/// Stop tracking a closed position and record its realized carry.
+fn close_tracked_position(
+    risk_orchestrator: &mut RiskOrchestrator,
+    persistence: &PersistenceManager,
+    symbol: &str,
+    kind: ExitKind,
+) {
+    let Some(position) = risk_orchestrator.close_position(symbol, kind) else {
+        return;
+    };
+    let exit = PersistedPositionExit {
+        closed_at: Utc::now(),
+        symbol: symbol.to_string(),
+        opened_at: position.opened_at,
+        exit_kind: kind.as_str().to_string(),
+        funding_received: position.total_funding_received,
+        costs: position.total_costs(),
+        net_carry: position.net_pnl(),
+    };
+    if let Err(e) = persistence.record_position_exit(&exit) {
+        error!("❌ [PERSISTENCE] Failed to record position exit: {}", e);
+    }
+    annotate_equity(
+        persistence,
+        EquityEventKind::Exit,
+        format!(
+            "{} closed ({}), net carry ${:.2}",
+            symbol,
+            kind.as_str(),
+            exit.net_carry
+        ),
+    );
+}
+
+/// Mark a notable event on the persisted equity curve.
+fn annotate_equity(persistence: &PersistenceManager, kind: EquityEventKind, label: String) {
+    let annotation = PersistedEquityAnnotation {
+        timestamp: Utc::now(),
+        kind,
+        label,
+    };
+    if let Err(e) = persistence.record_equity_annotation(&annotation) {
+        warn!(
+            "⚠️  [PERSISTENCE] Failed to record equity annotation: {}",
+            e
+        );
+    }
+}
+
+/// Symbol quality learned from the positions closed in the lookback window.
+fn load_symbol_quality(
+    persistence: &PersistenceManager,
+    config: &PairSelectionConfig,
+) -> SymbolQuality {
+    if config.symbol_quality_lookback_days == 0 {
+        return SymbolQuality::default();
+    }
+    let since = Utc::now() - chrono::Duration::days(config.symbol_quality_lookback_days as i64);
+    match persistence.get_position_exits_since(since) {
+        Ok(exits) => SymbolQuality::from_exits(&exits, config.max_symbol_failures),
+        Err(e) => {
+            warn!("⚠️  [QUALITY] Failed to load position exits: {}", e);
+            SymbolQuality::default()
+        }
+    }
+}
+
+/// Persist the malfunction detector state so a restart keeps the circuit breaker armed.
+fn save_malfunction_state(persistence: &PersistenceManager, risk_orchestrator: &RiskOrchestrator) {
+    let state = risk_orchestrator.malfunction_state(Utc::now());
+    if let Err(e) = persistence.save_malfunction_state(&state) {
+        warn!("⚠️  [PERSISTENCE] Failed to save malfunction state: {}", e);
+    }
+}
+
+fn save_run_record(persistence: &PersistenceManager, run: &PersistedRun) {
+    if let Err(e) = persistence.save_run(run) {
+        warn!(
+            "⚠️  [PERSISTENCE] Failed to record run {}: {}",
+            run.run_id, e
+        );
+    }
+}
+
+/// Log the KPIs of a finished run.
+fn log_run_summary(run: &PersistedRun) {
+    let duration = run.ended_at.unwrap_or_else(Utc::now) - run.started_at;
+    info!(
+        "🧾 [RUN] {} ({} mode, config {}, v{}) ran {}",
+        run.run_id,
+        run.mode,
+        run.config_hash,
+        run.version,
+        humanize_duration(duration)
+    );
+    info!(
+        "   Scans: {} | Entries: {} | Exits: {} | Errors: {}",
+        run.scans, run.entries, run.exits, run.errors
+    );
+    info!(
+        "   Funding: ${:.4} | Fees: ${:.4} | Final equity: {}",
+        run.funding,
+        run.fees,
+        run.final_equity
+            .map_or("unknown".to_string(), |e| format!("${:.2}", e))
+    );
+}
+
+/// Raise the critical malfunction for a tripped order valve, halting trading.
+fn report_order_valve_trip(risk_orchestrator: &mut RiskOrchestrator, trip: &ValveTrip) {
+    error!(
+        "🚨 [VALVE] Order valve tripped: {} - all orders refused",
+        trip
+    );
+    let (limit, observed, max) = match trip {
+        ValveTrip::OrderRate {
+            orders,
+            max_per_minute,
+        } => (
+            "orders_per_minute",
+            Decimal::from(*orders),
+            Decimal::from(*max_per_minute),
+        ),
+        ValveTrip::NotionalVelocity {
+            notional,
+            max_per_hour,
+        } => ("notional_per_hour", *notional, *max_per_hour),
+    };
+    risk_orchestrator.record_order_velocity_breach(limit, observed, max);
+}
+
+/// Log a sub-account capital transfer and record it in persistence.
+fn record_capital_transfer(persistence: &PersistenceManager, transfer: &CapitalTransfer) {
+    info!(
+        "🏦 [SUB-ACCOUNT] Transferred {} {} ({}, {}) | tranId {}",
+        transfer.amount,
+        transfer.asset,
+        transfer.direction.as_str(),
+        transfer.wallet,
+        transfer.tran_id
+    );
+    let kind = match transfer.direction {
+        TransferDirection::ToSubAccount => EquityEventKind::Deposit,
+        TransferDirection::ToMaster => EquityEventKind::Withdrawal,
+    };
+    annotate_equity(
+        persistence,
+        kind,
+        format!(
+            "{} {} ({})",
+            transfer.amount, transfer.asset, transfer.wallet
+        ),
+    );
+    if let Err(e) = persistence.record_transfer(
+        transfer.direction.as_str(),
+        &transfer.asset,
+        transfer.amount,
+        &transfer.wallet,
+        transfer.tran_id,
+    ) {
+        error!("❌ [PERSISTENCE] Failed to record transfer: {}", e);
+    }
+}
+
+/// Entry cost model fitted on the entries of the last `COST_MODEL_WEEKS` weeks.
+///
+/// Entries are market orders, so they are charged the taker rate of `fees`.
+fn load_entry_cost_model(persistence: &PersistenceManager, fees: FeeSchedule) -> EntryCostModel {
+    let since = Utc::now() - chrono::Duration::weeks(COST_MODEL_WEEKS);
+    let model = match persistence.get_execution_fills_since(since) {
+        Ok(fills) => EntryCostModel::from_executions(&ExecutionShortfall::from_fills(&fills)),
+        Err(e) => {
+            warn!("⚠️  [EXEC-QUALITY] Failed to load execution fills: {}", e);
+            EntryCostModel::default()
+        }
+    };
+    model.with_fees(fees, false)
+}
+
+/// Pass ready entries through the entry timer.
+///
+/// Returns the entries to execute this cycle and, for those the timer judged,
+/// their timing against immediate execution. Without the futures book every
+/// entry executes at once.
+async fn time_entries<'a>(
+    client: &dyn ExchangeClient,
+    timer: &mut EntryTimer,
+    persistence: &PersistenceManager,
+    ready: Vec<&'a PositionAllocation>,
+    funding_times: &HashMap<String, i64>,
+) -> (Vec<&'a PositionAllocation>, HashMap<String, TimedEntry>) {
+    let mut timed = HashMap::new();
+    if !timer.is_enabled() {
+        return (ready, timed);
+    }
+    timer.retain_ready(&ready.iter().map(|alloc| alloc.symbol.as_str()).collect());
+    if ready.is_empty() {
+        return (ready, timed);
+    }
+    let tickers: HashMap<String, BookTicker> = match client.get_book_tickers().await {
+        Ok(tickers) => tickers.into_iter().map(|t| (t.symbol.clone(), t)).collect(),
+        Err(e) => {
+            warn!(
+                "⚠️  [TIMING] Futures book unavailable, entering without timing: {}",
+                e
+            );
+            return (ready, timed);
+        }
+    };
+
+    let now = Utc::now();
+    let mut release = Vec::new();
+    for alloc in ready {
+        let Some(ticker) = tickers.get(&alloc.symbol) else {
+            release.push(alloc);
+            continue;
+        };
+        let side = if alloc.funding_rate > Decimal::ZERO {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        };
+        let retry_after = funding_times
+            .get(&alloc.symbol)
+            .and_then(|ms| DateTime::from_timestamp_millis(*ms))
+            .filter(|at| *at > now)
+            .unwrap_or(now + chrono::Duration::hours(8));
+        match timer.decide(&alloc.symbol, side, ticker, now, retry_after) {
+            TimingDecision::Execute(entry) => {
+                if entry.waited > chrono::Duration::zero() {
+                    info!(
+                        "⏱️  [TIMING] {} released after {}{} - {:+.1} bps vs immediate",
+                        alloc.symbol,
+                        humanize_duration(entry.waited),
+                        if entry.forced { " (deadline)" } else { "" },
+                        entry.gain_bps()
+                    );
+                }
+                timed.insert(alloc.symbol.clone(), entry);
+                release.push(alloc);
+            }
+            TimingDecision::Wait { reason } => {
+                info!("⏱️  [TIMING] Holding {} entry: {}", alloc.symbol, reason);
+            }
+            TimingDecision::Abort {
+                entry,
+                imbalance,
+                until,
+            } => {
+                warn!(
+                    "⏱️  [TIMING] Aborting {} entry: book still {:.0}% one-sided after {} ({:+.1} bps vs immediate), retry after {}",
+                    alloc.symbol,
+                    imbalance * dec!(100),
+                    humanize_duration(entry.waited),
+                    entry.gain_bps(),
+                    until.format("%H:%M UTC")
+                );
+                record_entry_timing(persistence, &entry, "aborted");
+            }
+            TimingDecision::Aborted { until } => {
+                debug!(
+                    "⏱️  [TIMING] {} entry aborted until {}",
+                    alloc.symbol,
+                    until.format("%H:%M UTC")
                 );
             }
         }
+    }
+    (release, timed)
+}
 
-        // Close spot leg with retries
-        if !spot_closed {
-            let spot_side = if pos.spot_qty > Decimal::ZERO {
-                funding_fee_farmer::exchange::OrderSide::Sell
-            } else {
-                funding_fee_farmer::exchange::OrderSide::Buy
-            };
+/// Log funding capture per held position and across the portfolio.
+fn report_funding_capture(capture: &FundingCaptureTracker) {
+    let summary = capture.summary();
+    if summary.positions == 0 {
+        return;
+    }
+    for ledger in capture.ledgers() {
+        info!(
+            "🎯 [CAPTURE] {} | {}",
+            ledger.symbol,
+            describe_capture(ledger)
+        );
+    }
+    info!(
+        "🎯 [CAPTURE] Portfolio {} | ${:.4} of ${:.4} | lost: late entry ${:.4}, reductions ${:.4}, drift ${:.4}",
+        format_capture_efficiency(summary.efficiency()),
+        summary.received,
+        summary.theoretical,
+        summary.late_entry_loss,
+        summary.reduction_loss,
+        summary.drift_loss
+    );
+}
 
-            for attempt in 1..=max_retries {
-                let spot_order = funding_fee_farmer::exchange::MarginOrder {
-                    symbol: pos.spot_symbol.clone(),
-                    side: spot_side.clone(),
-                    order_type: funding_fee_farmer::exchange::OrderType::Market,
-                    quantity: Some(pos.spot_qty.abs()),
-                    price: None,
-                    time_in_force: None,
-                    is_isolated: Some(false),
-                    side_effect_type: Some(
-                        funding_fee_farmer::exchange::SideEffectType::AutoBorrowRepay,
-                    ),
-                };
+fn describe_capture(ledger: &CaptureLedger) -> String {
+    format!(
+        "{} | ${:.4} of ${:.4} over {} settlement(s) | lost: late entry ${:.4} ({} missed), reductions ${:.4}, drift ${:.4}",
+        format_capture_efficiency(ledger.efficiency()),
+        ledger.received,
+        ledger.theoretical,
+        ledger.settlements,
+        ledger.late_entry_loss,
+        ledger.missed_settlements,
+        ledger.reduction_loss,
+        ledger.drift_loss
+    )
+}
 
-                match mock_client.place_margin_order(&spot_order).await {
-                    Ok(_) => {
-                        info!(
-                            "✅ [EMERGENCY] Spot closed for {} on attempt {}",
-                            pos.symbol, attempt
-                        );
-                        spot_closed = true;
-                        break;
-                    }
-                    Err(e) => {
-                        let backoff_ms = 100 * 2_u64.pow(attempt.min(5));
-                        warn!(
-                            "⚠️ [EMERGENCY] Spot close attempt {}/{} failed for {}: {} - retrying in {}ms",
-                            attempt, max_retries, pos.symbol, e, backoff_ms
-                        );
-                        tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
-                    }
-                }
+fn format_capture_efficiency(efficiency: Option<Decimal>) -> String {
+    efficiency
+        .map(|e| format!("{:.1}% captured", e * dec!(100)))
+        .unwrap_or_else(|| "nothing to capture yet".to_string())
+}
+
+fn record_entry_timing(persistence: &PersistenceManager, entry: &TimedEntry, outcome: &str) {
+    if let Err(e) = persistence.record_entry_timing(&entry.to_persisted(outcome, Utc::now())) {
+        error!("❌ [PERSISTENCE] Failed to record entry timing: {}", e);
+    }
+}
+
+/// Record the filled legs of an entry or exit against its decision price.
+///
+/// Logs the implementation shortfall and refits the entry cost model.
+fn record_execution_quality(
+    persistence: &PersistenceManager,
+    cost_model: &mut EntryCostModel,
+    kind: ExecutionKind,
+    symbol: &str,
+    decision_price: Decimal,
+    legs: &[(&str, Option<&OrderResponse>)],
+) {
+    let execution_id = format!(
+        "{}-{}-{}",
+        kind.as_str(),
+        symbol,
+        Utc::now().timestamp_millis()
+    );
+    let fills: Vec<_> = legs
+        .iter()
+        .filter_map(|(leg, order)| leg_fill(&execution_id, kind, leg, decision_price, (*order)?))
+        .collect();
+    for fill in &fills {
+        if let Err(e) = persistence.record_execution_fill(fill) {
+            error!("❌ [PERSISTENCE] Failed to record execution fill: {}", e);
+        }
+    }
+    for execution in ExecutionShortfall::from_fills(&fills) {
+        info!(
+            "📐 [EXEC-QUALITY] {} {} shortfall {:.1} bps (${:.2}) vs decision price ${}",
+            symbol,
+            kind.as_str(),
+            execution.shortfall_bps(),
+            execution.shortfall_usd,
+            decision_price
+        );
+    }
+    *cost_model = load_entry_cost_model(persistence, cost_model.fees());
+}
+
+/// Fetch current mark prices for specific symbols, streamed or from the premium index.
+async fn fetch_mark_prices(
+    client: &dyn ExchangeClient,
+    marks: &MarkPriceCache,
+    symbols: &[String],
+) -> HashMap<String, Decimal> {
+    if symbols.is_empty() {
+        return HashMap::new();
+    }
+    if let Some(prices) = marks.mark_prices(symbols, Utc::now()) {
+        return prices;
+    }
+
+    match client.get_funding_rates().await {
+        Ok(rates) => rates
+            .into_iter()
+            .filter(|r| symbols.iter().any(|s| s == &r.symbol))
+            .filter_map(|r| r.mark_price.map(|mark| (r.symbol, mark)))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to fetch mark prices: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Cancel every open order of this instance on each trading venue on a halt or
+/// shutdown, so nothing resting on a book fills once the bot stopped managing
+/// its positions.
+///
+/// `reason` names the trigger in the logs. Orders still open after the retries
+/// are logged one by one for manual cancellation. Returns whether every venue
+/// confirmed none is left.
+async fn cancel_open_orders_on_halt(
+    executor: &OrderExecutor,
+    venues: &[(Venue, &dyn ExchangeClient)],
+    reason: &str,
+) -> bool {
+    let mut confirmed = true;
+    for (venue, client) in venues {
+        let report = executor.cancel_all_open_orders(*client).await;
+        if report.confirmed() {
+            if report.cancelled > 0 {
+                warn!(
+                    "🧹 [ORDERS] {} on {}: cancelled {} open order(s), none left open",
+                    reason, venue, report.cancelled
+                );
             }
+            continue;
+        }
+        confirmed = false;
 
-            if !spot_closed {
+        if !report.remaining.is_empty() {
+            error!(
+                "🚨 [ORDERS] {} on {}: cancelled {} open order(s) but {} are STILL OPEN - cancel them manually!",
+                reason,
+                venue,
+                report.cancelled,
+                report.remaining.len()
+            );
+            for order in &report.remaining {
                 error!(
-                    "🚨 [EMERGENCY] FAILED to close spot for {} after {} attempts!",
-                    pos.symbol, max_retries
+                    "   Open order: {} #{} ({}, {:?})",
+                    order.symbol, order.order_id, order.client_order_id, order.side
                 );
             }
         }
+        if !report.unconfirmed.is_empty() {
+            error!(
+                "🚨 [ORDERS] {} on {}: could not list {} orders to confirm them cancelled - verify manually!",
+                reason,
+                venue,
+                report.unconfirmed.join(" and ")
+            );
+        }
+    }
+    confirmed
+}
+
+/// Execute emergency close of ALL positions during halt condition.
+/// This function will retry each position close up to max_retries times.
+/// Returns the number of positions successfully closed.
+async fn execute_emergency_close_all(
+    mock_client: &MockBinanceClient,
+    positions: &[funding_fee_farmer::exchange::DeltaNeutralPosition],
+    risk_orchestrator: &mut RiskOrchestrator,
+    persistence: &PersistenceManager,
+) -> usize {
+    let total_positions = positions.len();
+
+    error!(
+        "🚨 [EMERGENCY] Beginning emergency close of {} positions",
+        total_positions
+    );
 
-        if futures_closed && spot_closed {
-            info!("✅ [EMERGENCY] Position {} fully closed", pos.symbol);
-            risk_orchestrator.close_position(&pos.symbol);
+    // More patient than routine reductions: nothing else runs after this
+    let executor = RiskActionExecutor::new(5, Duration::from_millis(200));
+    let directive = ReductionDirective::all(Decimal::ONE, "emergency close");
+    let results = executor
+        .execute(mock_client, positions, &directive, Some(persistence))
+        .await;
+
+    // Positions with negligible size need no orders and count as closed
+    let mut closed_count = total_positions - results.len();
+    for result in &results {
+        if result.is_complete() {
+            info!("✅ [EMERGENCY] Position {} fully closed", result.symbol);
+            close_tracked_position(
+                risk_orchestrator,
+                persistence,
+                &result.symbol,
+                ExitKind::Forced,
+            );
             closed_count += 1;
         } else {
             error!(
-                "🚨 [EMERGENCY] Position {} partially closed (futures: {}, spot: {})",
-                pos.symbol, futures_closed, spot_closed
+                "🚨 [EMERGENCY] Position {} partially closed: {}",
+                result.symbol,
+                result.errors.join("; ")
             );
         }
     }
@@ -2449,21 +7519,65 @@ async fn execute_emergency_close_all(
     closed_count
 }
 
+/// Execute a risk-driven reduction and count the outcome in the metrics.
+async fn execute_reduction(
+    executor: &RiskActionExecutor,
+    mock_client: &MockBinanceClient,
+    positions: &[funding_fee_farmer::exchange::DeltaNeutralPosition],
+    directive: &ReductionDirective,
+    persistence: &PersistenceManager,
+    metrics: &mut AppMetrics,
+) {
+    let results = executor
+        .execute(mock_client, positions, directive, Some(persistence))
+        .await;
+    for result in results {
+        if result.is_complete() {
+            info!(
+                "✅ [AUTO-REDUCE] Reduced {} by {}% (futures: {}, spot: {})",
+                result.symbol,
+                directive.fraction * dec!(100),
+                result.futures_qty,
+                result.spot_qty
+            );
+            metrics.rebalances_triggered += 1;
+        } else {
+            error!(
+                "❌ [AUTO-REDUCE] Reduction failed for {}: {}",
+                result.symbol,
+                result.errors.join("; ")
+            );
+            metrics.errors_count += 1;
+        }
+    }
+}
+
 /// Log comprehensive status with risk orchestrator metrics.
+#[allow(clippy::too_many_arguments)]
 fn log_status_with_risk(
     metrics: &AppMetrics,
     state: &funding_fee_farmer::exchange::mock::MockTradingState,
     realized_pnl: Decimal,
     unrealized_pnl: Decimal,
     risk_orchestrator: &RiskOrchestrator,
+    funding_calendar: &FundingCalendar,
+    venue: Venue,
+    display_tz: &DisplayTimezone,
 ) {
-    let runtime = Utc::now() - metrics.start_time;
+    let now = Utc::now();
+    let runtime = now - metrics.start_time;
     let hours = runtime.num_hours();
     let minutes = runtime.num_minutes() % 60;
 
     let drawdown_stats = risk_orchestrator.get_drawdown_stats();
     let active_alerts = risk_orchestrator.get_active_alerts();
     let tracked_positions = risk_orchestrator.get_all_tracked_positions();
+    let next_funding = tracked_positions
+        .iter()
+        .map(|p| funding_calendar.schedule(venue, &p.symbol))
+        .map(|schedule| schedule.next_settlement(now))
+        .min()
+        .unwrap_or_else(|| FundingCalendar::venue_default(venue).next_settlement(now));
 
     info!("╔════════════════════════════════════════════════════════════╗");
     info!("║                    STATUS REPORT                           ║");
@@ -2472,6 +7586,10 @@ fn log_status_with_risk(
         "║ Runtime: {}h {}m                                           ",
         hours, minutes
     );
+    info!(
+        "║ Next Funding: {}                                  ",
+        display_tz.countdown(next_funding, now)
+    );
     info!("╠════════════════════════════════════════════════════════════╣");
     info!("║ 💰 ACCOUNT                                                 ║");
     info!(
@@ -2494,7 +7612,11 @@ fn log_status_with_risk(
     info!("║ 📊 P&L BREAKDOWN                                          ║");
     info!(
         "║    Funding Received:    ${:>12.4}                     ",
-        state.total_funding_received
+        state.total_funding_received + state.total_funding_paid
+    );
+    info!(
+        "║    Funding Paid:       -${:>12.4}                     ",
+        state.total_funding_paid
     );
     info!(
         "║    Trading Fees:       -${:>12.4}                     ",
@@ -2514,6 +7636,14 @@ fn log_status_with_risk(
         "║    Scans:              {:>6}                              ",
         metrics.scan_count
     );
+    info!(
+        "║    Stale Scan Cycles:  {:>6}                              ",
+        metrics.stale_scan_cycles
+    );
+    info!(
+        "║    Maintenance Cycles: {:>6}                              ",
+        metrics.maintenance_cycles
+    );
     info!(
         "║    Opportunities:      {:>6}                              ",
         metrics.opportunities_found
@@ -2564,159 +7694,1103 @@ fn log_status_with_risk(
         "║    Active Alerts:      {:>6}                              ",
         active_alerts.len()
     );
-    info!("╚════════════════════════════════════════════════════════════╝");
+    info!(
+        "║    Residual Delta:     ${:>12.2} net / ${:.2} gross      ",
+        metrics.residual_delta.net_delta_usd, metrics.residual_delta.gross_delta_usd
+    );
+    if !metrics.residual_delta.net_target_usd.is_zero() {
+        info!(
+            "║    Hedge Tilt:         ${:>12.2} target / ${:.2} drift   ",
+            metrics.residual_delta.net_target_usd, metrics.residual_delta.net_drift_usd
+        );
+    }
+    info!("╚════════════════════════════════════════════════════════════╝");
+
+    // Log per-position health if any positions tracked
+    if !tracked_positions.is_empty() {
+        info!("╔════════════════════════════════════════════════════════════╗");
+        info!("║                 POSITION HEALTH                            ║");
+        info!("╠════════════════════════════════════════════════════════════╣");
+        for pos in &tracked_positions {
+            let net_pnl = pos.net_pnl();
+            let status = if net_pnl >= Decimal::ZERO {
+                "✅"
+            } else {
+                "⚠️"
+            };
+            let residual = metrics
+                .residual_delta
+                .symbols
+                .iter()
+                .find(|s| s.symbol == pos.symbol)
+                .map_or(Decimal::ZERO, |s| s.residual_usd);
+            info!(
+                "║ {} {:12} | Fund: ${:>8.4} | Net: ${:>8.4} | ${:>7.4}/h held | Δ ${:>8.2}",
+                status,
+                pos.symbol,
+                pos.total_funding_received,
+                net_pnl,
+                pos.funding_per_hour_held(),
+                residual
+            );
+        }
+        info!("╚════════════════════════════════════════════════════════════╝");
+    }
+}
+
+/// Print residual delta per symbol and book-wide, valued at entry prices.
+fn print_residual_delta(positions: &HashMap<String, PersistedPosition>, hedge: &HedgeConfig) {
+    let report = ExposureReport::new(
+        positions
+            .iter()
+            .map(|(symbol, pos)| {
+                SymbolExposure::new(
+                    symbol,
+                    pos.futures_qty,
+                    pos.spot_qty,
+                    pos.futures_entry_price,
+                )
+                .with_target_qty(hedge.target(symbol).target_net_qty(pos.futures_qty))
+            })
+            .collect(),
+    );
+
+    println!("\n⚖️  Residual Delta (at entry prices)");
+    for exposure in report.symbols.iter().filter(|s| !s.residual_qty.is_zero()) {
+        println!(
+            "   ├─ {:12} {:>+12} qty  ${:>+10.2}",
+            exposure.symbol, exposure.residual_qty, exposure.residual_usd
+        );
+        if !exposure.target_usd.is_zero() {
+            println!(
+                "   │  └─ target ${:>+10.2}, drift ${:>+10.2}",
+                exposure.target_usd, exposure.drift_usd
+            );
+        }
+    }
+    println!(
+        "   └─ Portfolio:    ${:+.2} net / ${:.2} gross ({:+.3}% of hedged notional)",
+        report.net_delta_usd,
+        report.gross_delta_usd,
+        report.net_delta_pct() * dec!(100)
+    );
+    if !report.net_target_usd.is_zero() {
+        println!(
+            "      Hedge tilt:   ${:+.2} target / ${:+.2} drift",
+            report.net_target_usd, report.net_drift_usd
+        );
+    }
+}
+
+/// Show current mock farmer status from persisted state.
+async fn show_status(
+    db_path: &str,
+    verbose: bool,
+    diff: Option<chrono::Duration>,
+    profile: Option<Profile>,
+    config_dir: &Path,
+) -> Result<()> {
+    use std::path::Path;
+
+    let config = Config::load_unvalidated(config_dir, profile, Profile::Dev).ok();
+    let display_tz = config
+        .as_ref()
+        .map(|config| config.display.display_timezone())
+        .unwrap_or_default();
+
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              MOCK FARMER STATUS                            ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+
+    if !Path::new(db_path).exists() {
+        println!("\n❌ Database not found: {}", db_path);
+        println!("   The mock farmer has not been started yet, or the database path is incorrect.");
+        return Ok(());
+    }
+
+    let persistence = PersistenceManager::new(db_path)?;
+
+    let Some(state) = persistence.load_state()? else {
+        println!("\n❌ No saved state found in database.");
+        println!("   The mock farmer may not have run yet.");
+        return Ok(());
+    };
+
+    // Calculate stats
+    let pnl = state.balance - state.initial_balance;
+    let pnl_pct = if state.initial_balance > Decimal::ZERO {
+        (pnl / state.initial_balance) * dec!(100)
+    } else {
+        Decimal::ZERO
+    };
+    let net_yield =
+        state.total_funding_received - state.total_trading_fees - state.total_borrow_interest;
+
+    println!("\n📊 Account Summary");
+    println!("   ├─ Initial Balance:  ${:.2}", state.initial_balance);
+    println!("   ├─ Current Balance:  ${:.2}", state.balance);
+    println!("   ├─ PnL:              ${:.2} ({:+.2}%)", pnl, pnl_pct);
+    println!(
+        "   └─ Last Updated:     {}",
+        display_tz.datetime(state.last_saved)
+    );
+
+    println!("\n💰 Funding & Costs");
+    println!(
+        "   ├─ Funding Received: ${:.4}",
+        state.total_funding_received + state.total_funding_paid
+    );
+    println!("   ├─ Funding Paid:     ${:.4}", state.total_funding_paid);
+    println!(
+        "   ├─ Net Funding:      ${:.4}",
+        state.total_funding_received
+    );
+    println!("   ├─ Trading Fees:     ${:.4}", state.total_trading_fees);
+    println!(
+        "   ├─ Borrow Interest:  ${:.4}",
+        state.total_borrow_interest
+    );
+    println!("   └─ Net Yield:        ${:.4}", net_yield);
+
+    println!("\n📈 Activity");
+    println!("   ├─ Total Orders:     {}", state.order_count);
+    println!("   └─ Open Positions:   {}", state.positions.len());
+
+    if !state.positions.is_empty() {
+        let hedge = config.as_ref().map(|c| c.hedge.clone()).unwrap_or_default();
+        print_residual_delta(&state.positions, &hedge);
+    }
+
+    if let Some(events) = config.as_ref().map(|c| &c.events).filter(|e| e.enabled) {
+        print_event_calendar(events, &display_tz).await;
+    }
+
+    if !state.positions.is_empty() {
+        println!("\n🔓 Open Positions");
+        for (symbol, pos) in &state.positions {
+            let pos_pnl = pos.total_funding_received - pos.total_interest_paid;
+            println!("   ┌─ {}", symbol);
+            println!(
+                "   ├─ Futures: {} @ ${:.2}",
+                pos.futures_qty, pos.futures_entry_price
+            );
+            println!(
+                "   ├─ Spot:    {} @ ${:.2}",
+                pos.spot_qty, pos.spot_entry_price
+            );
+            if pos.borrowed_amount > Decimal::ZERO {
+                println!("   ├─ Borrowed: ${:.2}", pos.borrowed_amount);
+            }
+            println!(
+                "   ├─ Funding Collected: ${:.4} ({} times)",
+                pos.total_funding_received, pos.funding_collections
+            );
+            if pos.total_funding_paid > Decimal::ZERO {
+                println!("   ├─ Funding Paid:     ${:.4}", pos.total_funding_paid);
+            }
+            if pos.total_interest_paid > Decimal::ZERO {
+                println!("   ├─ Interest Paid:    ${:.4}", pos.total_interest_paid);
+            }
+            println!("   ├─ Net P/L:          ${:.4}", pos_pnl);
+            println!(
+                "   └─ Opened:           {}",
+                display_tz.datetime(pos.opened_at)
+            );
+
+            if verbose {
+                let now = Utc::now();
+                let hours = (now - pos.opened_at).num_hours();
+                let funding_periods = FundingCalendar::venue_default(Venue::Binance)
+                    .settlements_between(pos.opened_at, now)
+                    .len();
+                println!(
+                    "       Duration: {}h ({} funding periods)",
+                    hours, funding_periods
+                );
+            }
+        }
+    }
+
+    let quarantined = persistence.get_quarantined().unwrap_or_default();
+    if !quarantined.is_empty() {
+        println!("\n🚫 Funding Quarantine");
+        for quarantine in &quarantined {
+            println!(
+                "   ├─ {}: {} anomalies in a row ({:.1}% last deviation), since {}",
+                quarantine.symbol,
+                quarantine.anomalies,
+                quarantine.last_deviation * dec!(100),
+                display_tz.datetime(quarantine.quarantined_at)
+            );
+        }
+        println!("   └─ Release with: funding-fee-farmer quarantine --release <SYMBOL>");
+    }
+
+    if let Some(window) = diff {
+        print_status_diff(&persistence, &state, window, &display_tz)?;
+    }
+
+    // Get funding stats per symbol
+    if verbose {
+        if let Ok(funding_stats) = persistence.get_funding_stats() {
+            if !funding_stats.is_empty() {
+                println!("\n📊 Funding by Symbol");
+                for (symbol, total) in &funding_stats {
+                    println!("   ├─ {}: ${:.4}", symbol, total);
+                }
+            }
+        }
+        if let Ok(confirmation) = persistence.get_funding_confirmation() {
+            if confirmation.pending > 0 {
+                println!(
+                    "   └─ Awaiting exchange confirmation: {} settlement(s), ${:.4} projected",
+                    confirmation.pending, confirmation.projected
+                );
+            }
+        }
+
+        if let Ok(snapshots) = persistence.get_recent_snapshots(5) {
+            if !snapshots.is_empty() {
+                println!("\n📉 Recent Equity Snapshots");
+                for (ts, equity) in &snapshots {
+                    println!("   ├─ {}: ${:.2}", display_tz.short(*ts), equity);
+                }
+            }
+        }
+
+        if let Ok(transfers) = persistence.get_recent_transfers(5) {
+            if !transfers.is_empty() {
+                println!("\n🏦 Recent Sub-Account Transfers");
+                for t in &transfers {
+                    println!(
+                        "   ├─ {}: {} {} {} ({})",
+                        display_tz.short(t.timestamp),
+                        t.direction,
+                        t.amount,
+                        t.asset,
+                        t.wallet
+                    );
+                }
+            }
+        }
+    }
+
+    println!();
+    Ok(())
+}
+
+/// Parse a `--diff` window of hours or days, e.g. `24h` or `7d`.
+fn parse_diff_window(value: &str) -> Result<chrono::Duration, String> {
+    let (count, unit) = value.split_at(value.len().saturating_sub(1));
+    let count: i64 = count
+        .parse()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| format!("expected a window like 24h or 7d, got '{}'", value))?;
+    match unit {
+        "h" => Ok(chrono::Duration::hours(count)),
+        "d" => Ok(chrono::Duration::days(count)),
+        _ => Err(format!("expected a window like 24h or 7d, got '{}'", value)),
+    }
+}
+
+/// Print what changed between the equity snapshot closest to `window` ago and
+/// the latest saved state.
+fn print_status_diff(
+    persistence: &PersistenceManager,
+    state: &PersistedState,
+    window: chrono::Duration,
+    display_tz: &DisplayTimezone,
+) -> Result<()> {
+    println!("\n🔀 Changes Over {}h", window.num_hours());
+    let Some(then) = persistence.get_snapshot_near(state.last_saved - window)? else {
+        println!("   └─ No equity snapshots recorded yet");
+        return Ok(());
+    };
+    let since = then.timestamp;
+
+    let balance_change = state.balance - then.balance;
+    let balance_change_pct = if then.balance > Decimal::ZERO {
+        balance_change / then.balance * dec!(100)
+    } else {
+        Decimal::ZERO
+    };
+    println!(
+        "   ├─ Compared With:    snapshot at {} ({}h before last save)",
+        display_tz.datetime(since),
+        (state.last_saved - since).num_hours()
+    );
+    println!(
+        "   ├─ Balance:          ${:.2} → ${:.2} ({:+.2}, {:+.2}%)",
+        then.balance, state.balance, balance_change, balance_change_pct
+    );
+    if let Some(latest) = persistence
+        .get_snapshot_near(state.last_saved)?
+        .filter(|latest| latest.timestamp > since)
+    {
+        println!(
+            "   ├─ Equity:           ${:.2} → ${:.2} ({:+.2}, as of {})",
+            then.total_equity,
+            latest.total_equity,
+            latest.total_equity - then.total_equity,
+            display_tz.short(latest.timestamp)
+        );
+    }
+
+    let funding = persistence.get_funding_events_since(since)?;
+    println!(
+        "   ├─ Funding Collected: ${:.4} ({} payments)",
+        funding.iter().map(|event| event.amount).sum::<Decimal>(),
+        funding.len()
+    );
+    match then.total_trading_fees {
+        Some(fees) => println!(
+            "   ├─ Fee Spend:        ${:.4}",
+            state.total_trading_fees - fees
+        ),
+        None => println!("   ├─ Fee Spend:        n/a (snapshot predates fee tracking)"),
+    }
+
+    let exits = persistence.get_position_exits_since(since)?;
+    let mut opened: Vec<&str> = state
+        .positions
+        .iter()
+        .filter(|(_, pos)| pos.opened_at >= since)
+        .map(|(symbol, _)| symbol.as_str())
+        .chain(
+            exits
+                .iter()
+                .filter(|exit| exit.opened_at >= since)
+                .map(|exit| exit.symbol.as_str()),
+        )
+        .collect();
+    opened.sort_unstable();
+    let closed: Vec<&str> = exits.iter().map(|exit| exit.symbol.as_str()).collect();
+    println!(
+        "   ├─ Positions Opened: {} {}",
+        opened.len(),
+        symbol_list(&opened)
+    );
+    println!(
+        "   ├─ Positions Closed: {} {}",
+        closed.len(),
+        symbol_list(&closed)
+    );
+    println!(
+        "   └─ Open Positions:   {} → {}",
+        then.position_count,
+        state.positions.len()
+    );
+    Ok(())
+}
+
+/// Symbols in parentheses, or nothing when there are none.
+fn symbol_list(symbols: &[&str]) -> String {
+    if symbols.is_empty() {
+        String::new()
+    } else {
+        format!("({})", symbols.join(", "))
+    }
+}
+
+/// Print the active macro event restriction and the next scheduled events.
+async fn print_event_calendar(
+    events: &funding_fee_farmer::config::EventsConfig,
+    display_tz: &DisplayTimezone,
+) {
+    println!("\n🗓️  Event Calendar");
+    let calendar = match EventCalendar::load(events).await {
+        Ok(calendar) => calendar,
+        Err(e) => {
+            println!("   └─ ⚠️  Failed to load: {:#}", e);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    match calendar.restriction_at(now) {
+        EntryRestriction::Open => println!("   ├─ Entries:          open"),
+        EntryRestriction::Blackout { event, until } => println!(
+            "   ├─ Entries:          🚫 BLACKOUT for {} until {}",
+            event.name,
+            display_tz.datetime(until)
+        ),
+        EntryRestriction::Reduced {
+            event,
+            factor,
+            until,
+        } => println!(
+            "   ├─ Entries:          ⚠️  {:.0}% size for {} until {}",
+            factor * dec!(100),
+            event.name,
+            display_tz.datetime(until)
+        ),
+    }
+
+    for event in calendar.upcoming(now, 3) {
+        println!(
+            "   ├─ {} [{:?}] {}",
+            event.name,
+            event.impact,
+            display_tz.countdown(event.time, now)
+        );
+    }
+    println!("   └─ Known Events:     {}", calendar.len());
+}
+
+/// Match exchange order history to journaled orders by client order ID and print
+/// which orders belong to which position, plus anything that does not line up.
+async fn reconcile_orders(
+    db_path: &str,
+    hours: i64,
+    profile: Option<Profile>,
+    config_dir: &Path,
+    testnet: bool,
+) -> Result<()> {
+    use std::path::Path;
+
+    // Binance only serves order history in windows of up to 7 days
+    anyhow::ensure!(
+        (1..=168).contains(&hours),
+        "--hours must be between 1 and 168, got {}",
+        hours
+    );
+    anyhow::ensure!(
+        Path::new(db_path).exists(),
+        "Database not found: {}",
+        db_path
+    );
+
+    let config = Config::load_unvalidated(config_dir, profile, Profile::Live)?;
+    let strategy_tag = &config.execution.client_order_tag;
+    let sub_account = &config.binance.sub_account;
+    let client = BinanceClient::new(&funding_fee_farmer::config::BinanceConfig {
+        api_key: if sub_account.enabled {
+            sub_account.api_key.clone()
+        } else {
+            std::env::var("BINANCE_API_KEY").unwrap_or_default()
+        },
+        secret_key: if sub_account.enabled {
+            sub_account.secret_key.clone()
+        } else {
+            std::env::var("BINANCE_SECRET_KEY").unwrap_or_default()
+        },
+        // Order history only needs the read-only key
+        read_api_key: if sub_account.enabled {
+            sub_account.read_api_key.clone()
+        } else {
+            std::env::var("BINANCE_READ_API_KEY").unwrap_or_default()
+        },
+        read_secret_key: if sub_account.enabled {
+            sub_account.read_secret_key.clone()
+        } else {
+            std::env::var("BINANCE_READ_SECRET_KEY").unwrap_or_default()
+        },
+        testnet: testnet || config.binance.testnet,
+        ..config.binance.clone()
+    })?;
+    sync_server_clock(&client).await;
+
+    let persistence = PersistenceManager::new(db_path)?;
+    let since = Utc::now() - chrono::Duration::hours(hours);
+    let intents = persistence.get_order_intents_since(since)?;
+
+    // Fetch exchange history for every symbol the journal traded in the window
+    let mut futures_symbols = BTreeSet::new();
+    let mut spot_symbols = BTreeSet::new();
+    for intent in &intents {
+        if OrderLeg::parse(&intent.leg).is_some_and(|leg| leg.is_spot()) {
+            spot_symbols.insert(intent.symbol.clone());
+        } else {
+            futures_symbols.insert(intent.symbol.clone());
+        }
+    }
+    let mut orders = Vec::new();
+    for symbol in &futures_symbols {
+        orders.extend(
+            client
+                .get_recent_futures_orders(symbol, since.timestamp_millis())
+                .await?,
+        );
+    }
+    for symbol in &spot_symbols {
+        orders.extend(
+            client
+                .get_recent_margin_orders(symbol, since.timestamp_millis())
+                .await?,
+        );
+    }
+
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              ORDER ATTRIBUTION                             ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+    println!(
+        "\nLast {}h: {} journaled order(s), {} exchange order(s), tag '{}'",
+        hours,
+        intents.len(),
+        orders.len(),
+        strategy_tag
+    );
 
-    // Log per-position health if any positions tracked
-    if !tracked_positions.is_empty() {
-        info!("╔════════════════════════════════════════════════════════════╗");
-        info!("║                 POSITION HEALTH                            ║");
-        info!("╠════════════════════════════════════════════════════════════╣");
-        for pos in &tracked_positions {
-            let net_pnl = pos.net_pnl();
-            let status = if net_pnl >= Decimal::ZERO {
-                "✅"
-            } else {
-                "⚠️"
-            };
-            info!(
-                "║ {} {:12} | Fund: ${:>8.4} | Net: ${:>8.4}          ",
-                status, pos.symbol, pos.total_funding_received, net_pnl
+    let report = AttributionReport::build(strategy_tag, intents, orders);
+
+    let positions = report.by_position();
+    if !positions.is_empty() {
+        println!("\n📌 Positions");
+        for (position_id, legs) in &positions {
+            println!("   ┌─ {} ({})", position_id, legs[0].intent.symbol);
+            for attributed in legs {
+                println!(
+                    "   ├─ {:<18} {:?} {} (order {})",
+                    attributed.intent.leg,
+                    attributed.order.side,
+                    attributed.order.executed_qty,
+                    attributed.order.order_id
+                );
+            }
+        }
+    }
+
+    let mismatches = report.fill_mismatches();
+    if !mismatches.is_empty() {
+        println!("\n⚠️  Fill Mismatches (journal vs exchange)");
+        for attributed in mismatches {
+            println!(
+                "   ├─ {} {}: {:?} vs {}",
+                attributed.intent.symbol,
+                attributed.intent.client_order_id,
+                attributed.intent.executed_qty,
+                attributed.order.executed_qty
             );
         }
-        info!("╚════════════════════════════════════════════════════════════╝");
+    }
+
+    if !report.unjournaled.is_empty() {
+        println!("\n❓ Tagged on Exchange but Not Journaled");
+        for order in &report.unjournaled {
+            println!(
+                "   ├─ {} {} {:?} {}",
+                order.symbol, order.client_order_id, order.side, order.executed_qty
+            );
+        }
+    }
+
+    if !report.missing.is_empty() {
+        println!("\n❌ Journaled but Missing from Exchange History");
+        for intent in &report.missing {
+            println!(
+                "   ├─ {} {} {} {}",
+                intent.symbol, intent.client_order_id, intent.side, intent.quantity
+            );
+        }
+    }
+
+    if !report.untagged.is_empty() {
+        println!(
+            "\n   {} untagged exchange order(s) (manual or other software)",
+            report.untagged.len()
+        );
+    }
+
+    println!();
+    if report.is_consistent() {
+        println!("✅ Exchange history matches the order journal");
+        Ok(())
+    } else {
+        anyhow::bail!("Exchange history does not match the order journal")
     }
 }
 
-/// Show current mock farmer status from persisted state.
-fn show_status(db_path: &str, verbose: bool) -> Result<()> {
-    use std::path::Path;
+/// Print weekly implementation shortfall and the worst executions.
+fn show_execution_quality(db_path: &str, weeks: i64) -> Result<()> {
+    anyhow::ensure!(weeks > 0, "--weeks must be positive, got {}", weeks);
+    let persistence = PersistenceManager::open_read_only(db_path)?;
+    let fills = persistence
+        .get_execution_fills_since(Utc::now() - chrono::Duration::weeks(weeks))
+        .context("Failed to read execution fills")?;
+    let report = ExecutionQualityReport::build(&fills);
 
     println!("╔════════════════════════════════════════════════════════════╗");
-    println!("║              MOCK FARMER STATUS                            ║");
+    println!("║              EXECUTION QUALITY                             ║");
     println!("╚════════════════════════════════════════════════════════════╝");
-
-    if !Path::new(db_path).exists() {
-        println!("\n❌ Database not found: {}", db_path);
-        println!("   The mock farmer has not been started yet, or the database path is incorrect.");
+    println!(
+        "\nLast {} week(s): {} execution(s), {} leg fill(s)",
+        weeks,
+        report.executions.len(),
+        fills.len()
+    );
+    if report.executions.is_empty() {
         return Ok(());
     }
 
-    let persistence = PersistenceManager::new(db_path)?;
+    println!("\n📅 Implementation Shortfall by Week");
+    println!(
+        "   {:<10} {:>7} {:>5} {:>12} {:>10} {:>9} {:>9}",
+        "Week of", "Entries", "Exits", "Notional", "Shortfall", "Avg bps", "Worst bps"
+    );
+    for week in &report.weeks {
+        println!(
+            "   {:<10} {:>7} {:>5} {:>12.2} {:>10.2} {:>9.1} {:>9.1}",
+            week.week_start,
+            week.entries,
+            week.exits,
+            week.notional,
+            week.shortfall_usd,
+            week.shortfall_bps(),
+            week.worst_bps
+        );
+    }
 
-    let Some(state) = persistence.load_state()? else {
-        println!("\n❌ No saved state found in database.");
-        println!("   The mock farmer may not have run yet.");
-        return Ok(());
-    };
+    println!("\n🐌 Worst Executions");
+    for execution in report.worst(5) {
+        println!(
+            "   ├─ {} {} {:<5} ${:.2} on ${:.2} ({:.1} bps)",
+            execution.timestamp.format("%Y-%m-%d %H:%M"),
+            execution.symbol,
+            execution.kind,
+            execution.shortfall_usd,
+            execution.notional,
+            execution.shortfall_bps()
+        );
+    }
 
-    // Calculate stats
-    let pnl = state.balance - state.initial_balance;
-    let pnl_pct = if state.initial_balance > Decimal::ZERO {
-        (pnl / state.initial_balance) * dec!(100)
-    } else {
-        Decimal::ZERO
+    let model = EntryCostModel::from_executions(&report.executions);
+    println!(
+        "\n💸 Entry cost over this window: {:.1} bps fee + {:.1} bps shortfall",
+        TAKER_FEE_RATE * dec!(10000),
+        model.shortfall_rate() * dec!(10000)
+    );
+
+    let timings = persistence
+        .get_entry_timings_since(Utc::now() - chrono::Duration::weeks(weeks))
+        .context("Failed to read entry timings")?;
+    if !timings.is_empty() {
+        let timing = EntryTimingSummary::from_timings(&timings);
+        println!(
+            "\n⏱️  Entry timing: {} executed ({} held, avg {}s), {} aborted, {:+.1} bps mid vs immediate",
+            timing.executed,
+            timing.held,
+            timing.avg_wait_secs,
+            timing.aborted,
+            timing.avg_gain_bps
+        );
+    }
+    Ok(())
+}
+
+/// Most per-symbol market data requests (depth, open interest) in flight at once.
+const SYMBOL_LOOKUP_CONCURRENCY: usize = 8;
+
+/// Book levels sampled per candidate when estimating capacity.
+const CAPACITY_DEPTH_LEVELS: u16 = 500;
+
+/// Estimate how much capital the best candidate pairs absorb before slippage
+/// erodes their carry, from live Binance spot books and futures open interest.
+async fn show_capacity(
+    pairs: usize,
+    equity: Option<Decimal>,
+    profile: Option<Profile>,
+    config_dir: &Path,
+) -> Result<()> {
+    anyhow::ensure!(pairs > 0, "--pairs must be positive, got {}", pairs);
+    let config = Config::load_unvalidated(config_dir, profile, Profile::Live)?;
+    let client = BinanceClient::new(&funding_fee_farmer::config::BinanceConfig {
+        // Margin assets need an API key; the rest is public market data
+        api_key: std::env::var("BINANCE_API_KEY").unwrap_or_default(),
+        ..Default::default()
+    })?;
+
+    let (calendar, _) = load_funding_info(&client, Venue::Binance).await;
+    let candidates = MarketScanner::new(config.pair_selection.clone())
+        .scan(&client)
+        .await
+        .context("Failed to scan candidate pairs")?;
+    let terms = CapacityTerms {
+        slippage_tolerance: config.execution.slippage_tolerance,
+        horizon_hours: config.pair_selection.basis_horizon_hours,
+        round_trip_fee: config.execution.taker_fee_rate * Decimal::TWO,
+        max_open_interest_share: config.pair_selection.max_open_interest_share,
     };
-    let net_yield =
-        state.total_funding_received - state.total_trading_fees - state.total_borrow_interest;
 
-    println!("\n📊 Account Summary");
-    println!("   ├─ Initial Balance:  ${:.2}", state.initial_balance);
-    println!("   ├─ Current Balance:  ${:.2}", state.balance);
-    println!("   ├─ PnL:              ${:.2} ({:+.2}%)", pnl, pnl_pct);
+    // Only spot hedges are traded, so only spot books bound the capacity
+    let sampled: Vec<_> = candidates
+        .iter()
+        .filter(|p| matches!(p.hedge, HedgeInstrument::SpotMargin))
+        .take(pairs)
+        .collect();
+    let samples: Vec<_> = stream::iter(&sampled)
+        .map(|pair| async {
+            tokio::try_join!(
+                client.get_spot_depth(&pair.spot_symbol, CAPACITY_DEPTH_LEVELS),
+                client.get_open_interest(&pair.symbol),
+            )
+        })
+        .buffered(SYMBOL_LOOKUP_CONCURRENCY)
+        .collect()
+        .await;
+    let mut capacities = Vec::new();
+    let mut without_edge = Vec::new();
+    for (pair, sample) in sampled.iter().zip(samples) {
+        let (book, open_interest) = match sample {
+            Ok(sample) => sample,
+            Err(e) => {
+                warn!("⚠️  [CAPACITY] Skipping {}: {}", pair.symbol, e);
+                continue;
+            }
+        };
+        let (Some(&(best_bid, _)), Some(&(best_ask, _))) = (book.bids.first(), book.asks.first())
+        else {
+            warn!("⚠️  [CAPACITY] Skipping {}: empty spot book", pair.symbol);
+            continue;
+        };
+        // A short perp is hedged by buying spot, a long one by selling spot short
+        let levels = if pair.funding_rate >= Decimal::ZERO {
+            &book.asks
+        } else {
+            &book.bids
+        };
+        let input = CapacityInput {
+            symbol: &pair.symbol,
+            funding_rate: pair.funding_rate,
+            schedule: calendar.schedule(Venue::Binance, &pair.symbol),
+            borrow_rate: pair.borrow_rate,
+            levels,
+            mid_price: (best_bid + best_ask) / Decimal::TWO,
+            open_interest: open_interest.open_interest,
+        };
+        match PairCapacity::estimate(&input, &terms) {
+            Some(capacity) => capacities.push(capacity),
+            None => without_edge.push(pair.symbol.clone()),
+        }
+    }
+    let estimate = CapacityEstimate::new(capacities, config.pair_selection.max_positions as usize);
+
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              STRATEGY CAPACITY                             ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
     println!(
-        "   └─ Last Updated:     {}",
-        state.last_saved.format("%Y-%m-%d %H:%M:%S UTC")
+        "\n{} of {} candidate pair(s) sampled | slippage tolerance {:.2}% | {}h hold | {:.1}% of open interest",
+        sampled.len(),
+        candidates.len(),
+        terms.slippage_tolerance * dec!(100),
+        terms.horizon_hours,
+        terms.max_open_interest_share * dec!(100)
     );
+    if !without_edge.is_empty() {
+        println!(
+            "   No edge left after fees and borrow: {}",
+            without_edge.join(", ")
+        );
+    }
+    if estimate.pairs.is_empty() {
+        return Ok(());
+    }
 
-    println!("\n💰 Funding & Costs");
+    println!("\n📏 Capacity by Pair (largest first)");
     println!(
-        "   ├─ Total Funding:    ${:.4}",
-        state.total_funding_received
+        "   {:<14} {:>9} {:>8} {:>9} {:>12} {:>12} {:>12}  Limit",
+        "Symbol", "Funding", "Edge", "Max slip", "Depth $", "OI cap $", "Capacity $"
     );
-    println!("   ├─ Trading Fees:     ${:.4}", state.total_trading_fees);
+    for pair in &estimate.pairs {
+        println!(
+            "   {:<14} {:>8.4}% {:>7.3}% {:>8.3}% {:>12.0} {:>12.0} {:>12.0}  {}",
+            pair.symbol,
+            pair.funding_rate * dec!(100),
+            pair.edge * dec!(100),
+            pair.max_slippage * dec!(100),
+            pair.depth_notional,
+            pair.open_interest_notional,
+            pair.capacity_notional,
+            pair.limit
+        );
+    }
+
+    let utilization = config.capital.max_utilization;
+    let equity_capacity = estimate.equity_capacity(utilization);
     println!(
-        "   ├─ Borrow Interest:  ${:.4}",
-        state.total_borrow_interest
+        "\n💰 Capacity ${:.0} notional across the top {} pair(s), about ${:.0} of equity at {:.0}% utilization",
+        estimate.total_notional(),
+        estimate.max_positions.min(estimate.pairs.len()),
+        equity_capacity,
+        utilization * dec!(100)
     );
-    println!("   └─ Net Yield:        ${:.4}", net_yield);
+    match equity {
+        Some(equity) if equity < equity_capacity => println!(
+            "   ✅ ${:.0} of equity leaves room: up to ${:.0} more can be deployed at these books",
+            equity,
+            equity_capacity - equity
+        ),
+        Some(equity) => println!(
+            "   ⚠️  ${:.0} of equity is ${:.0} past capacity: more capital would slip past the edge",
+            equity,
+            equity - equity_capacity
+        ),
+        None => {}
+    }
+    Ok(())
+}
 
-    println!("\n📈 Activity");
-    println!("   ├─ Total Orders:     {}", state.order_count);
-    println!("   └─ Open Positions:   {}", state.positions.len());
+/// Print borrow interest against funding income per asset.
+///
+/// The cost ratio counts funding only from the weeks an asset was borrowed, so
+/// income earned before borrow rates rose doesn't hide the drag.
+fn show_borrow_costs(db_path: &str, weeks: i64) -> Result<()> {
+    anyhow::ensure!(weeks > 0, "--weeks must be positive, got {}", weeks);
+    let persistence = PersistenceManager::open_read_only(db_path)?;
+    let since = Utc::now() - chrono::Duration::weeks(weeks);
+    let funding = persistence
+        .get_funding_events_since(since)
+        .context("Failed to read funding events")?;
+    let interest = persistence
+        .get_interest_events_since(since)
+        .context("Failed to read interest events")?;
+    let report = BorrowCostReport::build(&funding, &interest);
 
-    if !state.positions.is_empty() {
-        println!("\n🔓 Open Positions");
-        for (symbol, pos) in &state.positions {
-            let pos_pnl = pos.total_funding_received - pos.total_interest_paid;
-            println!("   ┌─ {}", symbol);
-            println!(
-                "   ├─ Futures: {} @ ${:.2}",
-                pos.futures_qty, pos.futures_entry_price
-            );
-            println!(
-                "   ├─ Spot:    {} @ ${:.2}",
-                pos.spot_qty, pos.spot_entry_price
-            );
-            if pos.borrowed_amount > Decimal::ZERO {
-                println!("   ├─ Borrowed: ${:.2}", pos.borrowed_amount);
-            }
-            println!(
-                "   ├─ Funding Collected: ${:.4} ({} times)",
-                pos.total_funding_received, pos.funding_collections
-            );
-            if pos.total_interest_paid > Decimal::ZERO {
-                println!("   ├─ Interest Paid:    ${:.4}", pos.total_interest_paid);
-            }
-            println!("   ├─ Net P/L:          ${:.4}", pos_pnl);
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              BORROW COSTS                                  ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+    println!(
+        "\nLast {} week(s): {} funding payment(s), {} interest charge(s), {} borrowed asset(s)",
+        weeks,
+        funding.len(),
+        interest.len(),
+        report.assets.len()
+    );
+    if report.assets.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n🏦 Interest vs Funding by Asset (worst first)");
+    println!(
+        "   {:<8} {:>10} {:>10} {:>8} {:>12} {:>6}  Advice",
+        "Asset", "Funding", "Interest", "Ratio", "Avg borrow", "Eaten"
+    );
+    for asset in &report.assets {
+        let ratio = asset
+            .cost_ratio()
+            .map_or("n/a".to_string(), |r| format!("{:.1}%", r * dec!(100)));
+        let avg_borrowed = asset
+            .avg_borrowed_usd
+            .map_or("n/a".to_string(), |b| format!("{:.2}", b));
+        println!(
+            "   {:<8} {:>10.2} {:>10.2} {:>8} {:>12} {:>6}  {}",
+            asset.asset,
+            asset.funding_income,
+            asset.interest_paid,
+            ratio,
+            avg_borrowed,
+            format!("{}/{}", asset.weeks_eaten(), asset.weeks.len()),
+            asset.recommendation
+        );
+    }
+
+    for asset in report.flagged() {
+        let icon = match asset.recommendation {
+            BorrowRecommendation::Exclude => "🚫",
+            _ => "⚠️ ",
+        };
+        println!("\n{} {} by week", icon, asset.asset);
+        for week in &asset.weeks {
+            let ratio = week
+                .cost_ratio()
+                .map_or("n/a".to_string(), |r| format!("{:.1}%", r * dec!(100)));
             println!(
-                "   └─ Opened:           {}",
-                pos.opened_at.format("%Y-%m-%d %H:%M:%S UTC")
+                "   ├─ {}  funding ${:.2}  interest ${:.2}  ({})",
+                week.week_start, week.funding_income, week.interest_paid, ratio
             );
-
-            if verbose {
-                let duration = Utc::now() - pos.opened_at;
-                let hours = duration.num_hours();
-                let funding_periods = hours / 8;
-                println!(
-                    "       Duration: {}h ({} funding periods)",
-                    hours, funding_periods
-                );
-            }
         }
     }
+    Ok(())
+}
 
-    // Get funding stats per symbol
-    if verbose {
-        if let Ok(funding_stats) = persistence.get_funding_stats() {
-            if !funding_stats.is_empty() {
-                println!("\n📊 Funding by Symbol");
-                for (symbol, total) in &funding_stats {
-                    println!("   ├─ {}: ${:.4}", symbol, total);
-                }
-            }
-        }
+/// Print recorded runs, newest first.
+///
+/// Runs without an end time are still going or died without a clean shutdown;
+/// their KPIs are as of the last hourly update.
+fn show_runs(db_path: &str, limit: usize) -> Result<()> {
+    let persistence = PersistenceManager::open_read_only(db_path)?;
+    let runs = persistence
+        .get_runs(limit)
+        .context("Failed to read run records")?;
 
-        if let Ok(snapshots) = persistence.get_recent_snapshots(5) {
-            if !snapshots.is_empty() {
-                println!("\n📉 Recent Equity Snapshots");
-                for (ts, equity) in &snapshots {
-                    println!("   ├─ {}: ${:.2}", ts.format("%Y-%m-%d %H:%M"), equity);
-                }
-            }
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              RUNS                                          ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+    if runs.is_empty() {
+        println!("\nNo runs recorded");
+        return Ok(());
+    }
+
+    println!(
+        "\n   {:<6} {:<4} {:<16} {:>9} {:<12} {:<8} {:>6} {:>4} {:>4} {:>5} {:>10} {:>8} {:>12}",
+        "Run",
+        "Mode",
+        "Started",
+        "Duration",
+        "Config",
+        "Version",
+        "Scans",
+        "In",
+        "Out",
+        "Errs",
+        "Funding",
+        "Fees",
+        "Equity"
+    );
+    for run in &runs {
+        let duration = match run.ended_at {
+            Some(ended_at) => humanize_duration(ended_at - run.started_at),
+            None => "-".to_string(),
+        };
+        println!(
+            "   {:<6} {:<4} {:<16} {:>9} {:<12} {:<8} {:>6} {:>4} {:>4} {:>5} {:>10.4} {:>8.4} {:>12}",
+            run.run_id,
+            run.mode,
+            run.started_at.format("%Y-%m-%d %H:%M"),
+            duration,
+            run.config_hash,
+            run.version,
+            run.scans,
+            run.entries,
+            run.exits,
+            run.errors,
+            run.funding,
+            run.fees,
+            run.final_equity
+                .map_or("-".to_string(), |e| format!("{:.2}", e))
+        );
+    }
+    Ok(())
+}
+
+/// List quarantined symbols, or release one.
+fn manage_quarantine(db_path: &str, release: Option<&str>) -> Result<()> {
+    if let Some(symbol) = release {
+        let persistence = PersistenceManager::new(db_path)?;
+        let symbol = symbol.to_uppercase();
+        if persistence
+            .release_quarantine(&symbol)
+            .context("Failed to release quarantine")?
+        {
+            println!("🔓 Released {} from quarantine", symbol);
+        } else {
+            println!("{} is not quarantined", symbol);
         }
+        return Ok(());
     }
 
-    println!();
+    let persistence = PersistenceManager::open_read_only(db_path)?;
+    let quarantined = persistence
+        .get_quarantined()
+        .context("Failed to read quarantines")?;
+
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              FUNDING QUARANTINE                            ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+    if quarantined.is_empty() {
+        println!("\nNo symbols quarantined");
+        return Ok(());
+    }
+
+    print_quarantine_table(&quarantined);
+    println!("\n   Release with: funding-fee-farmer quarantine --release <SYMBOL>");
+    Ok(())
+}
+
+/// Print quarantined symbols with when and why they were quarantined.
+fn print_quarantine_table(quarantined: &[PersistedQuarantine]) {
+    println!(
+        "\n   {:<14} {:<16} {:>9} {:>14}",
+        "Symbol", "Since", "Anomalies", "Last deviation"
+    );
+    for quarantine in quarantined {
+        println!(
+            "   {:<14} {:<16} {:>9} {:>13.1}%",
+            quarantine.symbol,
+            quarantine.quarantined_at.format("%Y-%m-%d %H:%M"),
+            quarantine.anomalies,
+            quarantine.last_deviation * dec!(100)
+        );
+    }
+}
+
+/// List configuration changes, or print one recorded configuration in full.
+fn show_config_history(db_path: &str, limit: usize, show: Option<&str>) -> Result<()> {
+    let persistence = PersistenceManager::open_read_only(db_path)?;
+    let history = persistence
+        .get_config_history(if show.is_some() { usize::MAX } else { limit })
+        .context("Failed to read configuration history")?;
+
+    if let Some(hash) = show {
+        let change = history
+            .iter()
+            .find(|c| c.config_hash == hash)
+            .with_context(|| format!("No configuration recorded with hash {}", hash))?;
+        print!("{}", change.config);
+        return Ok(());
+    }
+
+    println!("╔════════════════════════════════════════════════════════════╗");
+    println!("║              CONFIGURATION HISTORY                         ║");
+    println!("╚════════════════════════════════════════════════════════════╝");
+    if history.is_empty() {
+        println!("\nNo configurations recorded");
+        return Ok(());
+    }
+
+    println!("\n   {:<16} {:<12} {:<8}", "Recorded", "Config", "Version");
+    for change in &history {
+        println!(
+            "   {:<16} {:<12} {:<8}",
+            change.recorded_at.format("%Y-%m-%d %H:%M"),
+            change.config_hash,
+            change.version
+        );
+    }
     Ok(())
 }
 
+/// Validate the merged configuration, printing all violations at once.
+fn validate_config(profile: Option<Profile>, config_dir: &Path) -> Result<()> {
+    let config = Config::load_unvalidated(config_dir, profile, Profile::Dev)?;
+    let violations = config.violations(config.profile);
+
+    println!("Profile: {}", config.profile);
+
+    let projection = UsageProjection::from_config(&config, SCAN_INTERVAL_SECS);
+    for breach in projection.breaches(&config.api_budget) {
+        println!("⚠️  Projected API usage near the limit: {}", breach);
+    }
+
+    if violations.is_empty() {
+        println!("✅ Configuration is valid");
+        return Ok(());
+    }
+
+    println!("❌ {} configuration violation(s):", violations.len());
+    for violation in &violations {
+        println!("   - {}", violation);
+    }
+
+    anyhow::bail!("Configuration is invalid")
+}
+
 /// Run a single backtest with the given parameters.
+#[allow(clippy::too_many_arguments)]
 async fn run_backtest(
     data_path: &str,
     start_str: &str,
     end_str: &str,
     initial_balance: f64,
     output_dir: Option<&str>,
+    high_funding_rate: Decimal,
+    maker_fee: Option<Decimal>,
+    maker_entries: bool,
+    borrow_scenario: Option<&str>,
+    crowding: Option<Decimal>,
+    stream: bool,
+    profile: Option<Profile>,
+    config_dir: &Path,
 ) -> Result<()> {
     info!("╔════════════════════════════════════════════════════════════╗");
     info!("║              BACKTEST MODE                                 ║");
@@ -2731,8 +8805,13 @@ async fn run_backtest(
     let start = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
     let end = end_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-    info!("📊 Loading data from: {}", data_path);
-    let data_loader = CsvDataLoader::new(data_path)?;
+    let data_loader = if stream {
+        info!("📊 Streaming data from: {}", data_path);
+        CsvDataLoader::streaming(data_path)?
+    } else {
+        info!("📊 Loading data from: {}", data_path);
+        CsvDataLoader::new(data_path)?
+    };
 
     if let Some((data_start, data_end)) = data_loader.available_range() {
         info!(
@@ -2746,7 +8825,21 @@ async fn run_backtest(
     info!("   Snapshots: {}", data_loader.len());
 
     // Load trading config
-    let config = Config::load()?;
+    let mut config = Config::load_with_profile(config_dir, profile, Profile::Dev)?;
+    if let Some(rate) = maker_fee {
+        config.execution.maker_fee_rate = rate;
+        config.validate()?;
+    }
+    let risk_free = RiskFreeRate::load(&config.risk_free).await;
+    config.risk_free.annual_rate = risk_free.annual_rate;
+    let borrow = match borrow_scenario {
+        Some(path) => BorrowScenario::load(path)?,
+        None => BorrowScenario::default(),
+    };
+    let crowding = crowding
+        .map(CrowdingModel::with_sensitivity)
+        .unwrap_or_default();
+    crowding.validate()?;
 
     // Create backtest config
     let backtest_config = BacktestConfig {
@@ -2755,8 +8848,23 @@ async fn run_backtest(
         record_equity_curve: true,
         record_trades: true,
         output_path: output_dir.map(String::from),
+        maker_entries,
+        borrow,
+        high_funding_rate,
+        crowding,
     };
 
+    let fees = config.execution.fee_schedule();
+    info!(
+        "💸 Fees: {:.3}% maker / {:.3}% taker, {} entries",
+        fees.maker_rate * dec!(100),
+        fees.taker_rate * dec!(100),
+        if maker_entries { "maker" } else { "taker" }
+    );
+    info!("🏦 Borrow: {}", backtest_config.borrow);
+    info!("👥 Crowding: {}", backtest_config.crowding);
+    info!("🏛️  Risk-free: {}", risk_free);
+
     info!("💰 Initial balance: ${:.2}", initial_balance);
     info!("📅 Period: {} to {}", start_str, end_str);
 
@@ -2780,6 +8888,7 @@ async fn run_backtest(
 }
 
 /// Run a parameter sweep optimization.
+#[allow(clippy::too_many_arguments)]
 async fn run_sweep(
     data_path: &str,
     start_str: &str,
@@ -2788,6 +8897,9 @@ async fn run_sweep(
     parallelism: usize,
     output_dir: Option<&str>,
     minimal: bool,
+    stream: bool,
+    profile: Option<Profile>,
+    config_dir: &Path,
 ) -> Result<()> {
     info!("╔════════════════════════════════════════════════════════════╗");
     info!("║           PARAMETER SWEEP MODE                             ║");
@@ -2802,8 +8914,13 @@ async fn run_sweep(
     let start = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
     let end = end_date.and_hms_opt(23, 59, 59).unwrap().and_utc();
 
-    info!("📊 Loading data from: {}", data_path);
-    let data_loader = CsvDataLoader::new(data_path)?;
+    let data_loader = if stream {
+        info!("📊 Streaming data from: {}", data_path);
+        CsvDataLoader::streaming(data_path)?
+    } else {
+        info!("📊 Loading data from: {}", data_path);
+        CsvDataLoader::new(data_path)?
+    };
 
     if let Some((data_start, data_end)) = data_loader.available_range() {
         info!(
@@ -2814,7 +8931,10 @@ async fn run_sweep(
     }
 
     // Load base config
-    let base_config = Config::load()?;
+    let mut base_config = Config::load_with_profile(config_dir, profile, Profile::Dev)?;
+    let risk_free = RiskFreeRate::load(&base_config.risk_free).await;
+    base_config.risk_free.annual_rate = risk_free.annual_rate;
+    info!("🏛️  Risk-free: {}", risk_free);
 
     // Create parameter space
     let param_space = if minimal {
@@ -2837,6 +8957,9 @@ async fn run_sweep(
         record_equity_curve: false, // Save memory during sweeps
         record_trades: false,
         output_path: None,
+        maker_entries: false,
+        borrow: BorrowScenario::default(),
+        ..Default::default()
     };
 
     info!("💰 Initial balance: ${:.2}", initial_balance);
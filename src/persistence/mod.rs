@@ -1,16 +1,32 @@
 //! SQLite persistence for mock trading state.
 //!
 //! Persists trading state to survive restarts:
-//! - Account balance and positions
-//! - Funding collection history
+//! - Account balance and positions (versioned, checksummed snapshots)
+//! - Funding collection history, with each payment's confirmation state
 //! - Interest payment history
 //! - Trade execution history
+//! - Decision vs fill prices per order leg (execution quality)
+//! - Timed entries against immediate execution
 //! - Periodic equity snapshots
+//! - Realized performance of closed positions
+//! - Sub-account capital transfers
+//! - Write-ahead journal of live order intents
+//! - Single-instance trading lock
+//! - Per-run KPI records (config hash, counts, funding, fees, final equity)
+//! - History of configuration changes
+//! - Malfunction detector state (error window, failure counters, alerts, halt)
+//!
+//! Trades, funding and interest events, execution fills, journaled orders and
+//! snapshots are stamped with the config hash and crate version they were written
+//! under, so results can be segmented by configuration era.
 
-use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, OptionalExtension};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{
+    params, Connection, OpenFlags, OptionalExtension, Row, Transaction, TransactionBehavior,
+};
 use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
@@ -26,19 +42,432 @@ pub struct PersistedPosition {
     pub spot_entry_price: Decimal,
     pub borrowed_amount: Decimal,
     pub opened_at: DateTime<Utc>,
+    /// Net funding (received minus paid)
     pub total_funding_received: Decimal,
+    pub total_funding_paid: Decimal,
     pub total_interest_paid: Decimal,
     pub funding_collections: u32,
     /// Expected funding rate at position entry (for anomaly detection)
     pub expected_funding_rate: Decimal,
 }
 
+/// Confirmation state of a funding payment.
+///
+/// A settlement is projected as `Expected` when it falls due, becomes `Settled`
+/// once the exchange reports the payment and `Verified` when the payment also
+/// matches the expectation. Only settled and verified payments count as income.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FundingState {
+    Expected,
+    Settled,
+    Verified,
+}
+
+impl FundingState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FundingState::Expected => "expected",
+            FundingState::Settled => "settled",
+            FundingState::Verified => "verified",
+        }
+    }
+
+    pub fn parse(state: &str) -> Option<Self> {
+        match state {
+            "expected" => Some(FundingState::Expected),
+            "settled" => Some(FundingState::Settled),
+            "verified" => Some(FundingState::Verified),
+            _ => None,
+        }
+    }
+
+    /// Whether the exchange has confirmed the payment.
+    pub fn is_confirmed(&self) -> bool {
+        *self != FundingState::Expected
+    }
+}
+
+/// Persisted funding payment.
+#[derive(Debug, Clone)]
+pub struct PersistedFundingEvent {
+    /// Settlement time
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    /// Confirmed amount; zero while only expected
+    pub amount: Decimal,
+    /// Position notional at settlement, when known
+    pub position_value: Option<Decimal>,
+    pub state: FundingState,
+    /// Projected amount, when the settlement was expected before it was paid
+    pub expected_amount: Option<Decimal>,
+}
+
+/// Funding income by confirmation state.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FundingConfirmation {
+    /// Settlements still awaiting the exchange
+    pub pending: usize,
+    /// Projected income of the pending settlements
+    pub projected: Decimal,
+    /// Confirmed income that did not match its expectation, or had none
+    pub settled: Decimal,
+    /// Confirmed income that matched its expectation
+    pub verified: Decimal,
+}
+
+/// Persisted borrow interest charge.
+#[derive(Debug, Clone)]
+pub struct PersistedInterestEvent {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    pub amount: Decimal,
+    /// Borrowed value (USDT) the interest accrued on, when known
+    pub borrowed_amount: Option<Decimal>,
+}
+
+/// Persisted trade.
+#[derive(Debug, Clone)]
+pub struct PersistedTrade {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    /// "BUY" or "SELL"
+    pub side: String,
+    pub order_type: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub is_futures: bool,
+}
+
+/// Persisted sub-account capital transfer.
+#[derive(Debug, Clone)]
+pub struct PersistedTransfer {
+    pub timestamp: DateTime<Utc>,
+    /// "to_sub_account" or "to_master"
+    pub direction: String,
+    pub asset: String,
+    pub amount: Decimal,
+    /// Sub-account wallet involved in the transfer
+    pub wallet: String,
+    pub tran_id: i64,
+}
+
+/// Journaled live order intent.
+///
+/// Written before the order is sent and marked completed once the exchange
+/// acknowledges it, so a crash in between leaves a pending row to reconcile.
+#[derive(Debug, Clone)]
+pub struct PersistedOrderIntent {
+    /// Client order ID sent as `newClientOrderId`
+    pub client_order_id: String,
+    /// Groups the legs of one entry, exit or reduction
+    pub correlation_id: String,
+    pub symbol: String,
+    /// Leg role, e.g. "entry_futures" or "entry_spot"
+    pub leg: String,
+    /// "BUY" or "SELL"
+    pub side: String,
+    pub quantity: Decimal,
+    /// "pending", "completed" or "failed"
+    pub status: String,
+    pub order_id: Option<i64>,
+    pub executed_qty: Option<Decimal>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Portfolio funding APR at one cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedFundingApr {
+    pub timestamp: DateTime<Utc>,
+    /// Entry notional of the held positions (USDT)
+    pub notional: Decimal,
+    /// Notional-weighted funding APR before costs
+    pub gross_apr: Decimal,
+    /// Notional-weighted estimated cost APR
+    pub cost_apr: Decimal,
+    pub net_apr: Decimal,
+    pub position_count: usize,
+    /// Annual risk-free rate the APR was compared against
+    pub risk_free_rate: Decimal,
+}
+
+/// Hourly equity snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedEquitySnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub balance: Decimal,
+    pub total_equity: Decimal,
+    pub position_count: usize,
+    /// Trading fees paid since the start; `None` for snapshots taken before they were recorded
+    pub total_trading_fees: Option<Decimal>,
+}
+
+/// Kind of event marked on the equity curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquityEventKind {
+    Entry,
+    Exit,
+    Halt,
+    ConfigChange,
+    Deposit,
+    Withdrawal,
+}
+
+impl EquityEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EquityEventKind::Entry => "entry",
+            EquityEventKind::Exit => "exit",
+            EquityEventKind::Halt => "halt",
+            EquityEventKind::ConfigChange => "config",
+            EquityEventKind::Deposit => "deposit",
+            EquityEventKind::Withdrawal => "withdrawal",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "entry" => Some(EquityEventKind::Entry),
+            "exit" => Some(EquityEventKind::Exit),
+            "halt" => Some(EquityEventKind::Halt),
+            "config" => Some(EquityEventKind::ConfigChange),
+            "deposit" => Some(EquityEventKind::Deposit),
+            "withdrawal" => Some(EquityEventKind::Withdrawal),
+            _ => None,
+        }
+    }
+}
+
+/// Notable bot action annotated on the equity curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedEquityAnnotation {
+    pub timestamp: DateTime<Utc>,
+    pub kind: EquityEventKind,
+    /// Short description, e.g. "BTCUSDT entered"
+    pub label: String,
+}
+
+/// Malfunction detector state carried across restarts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersistedMalfunctionState {
+    pub saved_at: DateTime<Utc>,
+    /// When trading was halted, if it still is
+    pub halted_at: Option<DateTime<Utc>>,
+    /// Consecutive risk cycles with ERROR/CRITICAL alerts (circuit breaker)
+    pub consecutive_risk_cycles: u32,
+    /// Errors in the rolling window, oldest first
+    pub errors: Vec<PersistedMalfunctionError>,
+    pub failures: Vec<PersistedOrderFailures>,
+    pub alerts: Vec<PersistedMalfunctionAlert>,
+}
+
+/// An API or execution error in the malfunction error window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedMalfunctionError {
+    pub timestamp: DateTime<Utc>,
+    pub error: String,
+}
+
+/// Consecutive order failures of one symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedOrderFailures {
+    pub symbol: String,
+    pub consecutive_failures: u32,
+    pub last_failure: DateTime<Utc>,
+}
+
+/// An active malfunction alert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedMalfunctionAlert {
+    pub alert_id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Severity name (INFO, WARNING, ERROR, CRITICAL)
+    pub severity: String,
+    /// Malfunction type as JSON
+    pub malfunction_type: String,
+    pub message: String,
+    pub should_halt: bool,
+    pub suggested_action: String,
+}
+
+/// A symbol quarantined after consecutive funding anomalies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedQuarantine {
+    pub symbol: String,
+    pub quarantined_at: DateTime<Utc>,
+    /// Consecutive anomalous payments that triggered it
+    pub anomalies: u32,
+    /// Deviation of the last anomalous payment (0.35 = 35%)
+    pub last_deviation: Decimal,
+}
+
+/// Realized performance of a closed position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedPositionExit {
+    pub closed_at: DateTime<Utc>,
+    pub symbol: String,
+    pub opened_at: DateTime<Utc>,
+    /// "routine" or "forced"
+    pub exit_kind: String,
+    /// Funding collected over the holding period (USDT)
+    pub funding_received: Decimal,
+    /// Entry fees, borrow interest and rebalance fees (USDT)
+    pub costs: Decimal,
+    /// Funding minus costs (USDT)
+    pub net_carry: Decimal,
+}
+
+/// Fill of one order leg against the price the trade was decided at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedExecutionFill {
+    pub timestamp: DateTime<Utc>,
+    /// Groups the legs of one entry or exit
+    pub execution_id: String,
+    pub symbol: String,
+    /// "entry" or "exit"
+    pub kind: String,
+    /// "futures" or "spot"
+    pub leg: String,
+    /// "BUY" or "SELL"
+    pub side: String,
+    pub quantity: Decimal,
+    /// Price at allocation time
+    pub decision_price: Decimal,
+    /// Achieved average fill price
+    pub fill_price: Decimal,
+}
+
+/// A timed entry against executing as soon as it was ready.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedEntryTiming {
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    /// Futures leg side, "BUY" or "SELL"
+    pub side: String,
+    /// "executed" or "aborted"
+    pub outcome: String,
+    /// Seconds the entry was held
+    pub waited_secs: i64,
+    /// Futures mid when the entry first became ready
+    pub baseline_mid: Decimal,
+    /// Futures mid when it executed or aborted
+    pub decision_mid: Decimal,
+}
+
+/// KPIs of one process run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedRun {
+    /// Instance ID of the process
+    pub run_id: String,
+    /// "live" or "mock"
+    pub mode: String,
+    pub started_at: DateTime<Utc>,
+    /// None while running, or when the process died without a clean shutdown
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Hash of the effective configuration
+    pub config_hash: String,
+    /// Crate version of the binary
+    pub version: String,
+    pub scans: u64,
+    pub entries: u64,
+    pub exits: u64,
+    pub errors: u64,
+    /// Funding received during the run (USDT)
+    pub funding: Decimal,
+    /// Trading fees paid during the run (USDT)
+    pub fees: Decimal,
+    /// Equity at the last update
+    pub final_equity: Option<Decimal>,
+}
+
+/// Configuration and code version that records are stamped with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordStamp {
+    /// Hash of the effective configuration
+    pub config_hash: String,
+    /// Crate version of the binary
+    pub version: String,
+}
+
+/// A configuration in effect from `recorded_at` until the next change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedConfigChange {
+    pub recorded_at: DateTime<Utc>,
+    pub config_hash: String,
+    pub version: String,
+    /// Effective configuration as TOML, credentials redacted
+    pub config: String,
+}
+
+/// Tables whose rows carry a `RecordStamp`.
+const STAMPED_TABLES: [&str; 10] = [
+    "trades",
+    "funding_events",
+    "interest_events",
+    "execution_fills",
+    "entry_timings",
+    "position_exits",
+    "order_journal",
+    "equity_snapshots",
+    "funding_apr_snapshots",
+    "state_snapshots",
+];
+
+/// Holder of the single-instance trading lock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceLock {
+    /// Random per-process ID, also embedded in client order IDs
+    pub instance_id: String,
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+    /// Refreshed every trading cycle; a lock without heartbeats goes stale
+    pub heartbeat_at: DateTime<Utc>,
+}
+
+impl InstanceLock {
+    /// Length of an instance ID.
+    pub const ID_LEN: usize = 6;
+
+    /// Lock record for the current process with a fresh instance ID.
+    pub fn for_current_process(now: DateTime<Utc>) -> Self {
+        let pid = std::process::id();
+        let seed = format!("{}:{}", pid, now.timestamp_nanos_opt().unwrap_or_default());
+        let mut instance_id = hex::encode(Sha256::digest(seed.as_bytes()));
+        instance_id.truncate(Self::ID_LEN);
+
+        Self {
+            instance_id,
+            pid,
+            acquired_at: now,
+            heartbeat_at: now,
+        }
+    }
+
+    /// Whether the holder stopped heartbeating more than `stale_after` ago.
+    pub fn is_stale(&self, now: DateTime<Utc>, stale_after: Duration) -> bool {
+        now - self.heartbeat_at > stale_after
+    }
+}
+
+/// Outcome of trying to take the instance lock.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockAcquisition {
+    /// Lock was free or stale; carries the stale previous holder, if any
+    Acquired(Option<InstanceLock>),
+    /// A live holder was replaced on request (takeover)
+    TookOver(InstanceLock),
+    /// A live holder owns the lock; nothing was changed
+    Held(InstanceLock),
+}
+
 /// Persisted trading state.
 #[derive(Debug, Clone)]
 pub struct PersistedState {
     pub initial_balance: Decimal,
     pub balance: Decimal,
+    /// Net funding (received minus paid)
     pub total_funding_received: Decimal,
+    pub total_funding_paid: Decimal,
     pub total_trading_fees: Decimal,
     pub total_borrow_interest: Decimal,
     pub order_count: u64,
@@ -48,9 +477,59 @@ pub struct PersistedState {
     pub last_funding_period: Option<u32>,
 }
 
+/// Version of the mock state: the client's mutation revision plus the last
+/// collected funding period (tracked outside the client).
+pub type StateVersion = (u64, Option<u32>);
+
+/// Decides when the mock state needs saving.
+///
+/// A save is due only when the state changed since the last save, and at most
+/// once per `min_interval` so bursts of mutations (several orders in one phase)
+/// coalesce into one write. Forced checkpoints skip the interval but still skip
+/// unchanged state.
+#[derive(Debug, Clone)]
+pub struct DirtyTracker {
+    min_interval: Duration,
+    saved: StateVersion,
+    last_save: Option<DateTime<Utc>>,
+}
+
+impl DirtyTracker {
+    /// Create a tracker for a state currently saved at `saved`.
+    pub fn new(min_interval: Duration, saved: StateVersion) -> Self {
+        Self {
+            min_interval,
+            saved,
+            last_save: None,
+        }
+    }
+
+    /// Whether `version` differs from the last saved state.
+    pub fn is_dirty(&self, version: StateVersion) -> bool {
+        version != self.saved
+    }
+
+    /// Whether the state at `version` should be saved now.
+    pub fn save_due(&self, version: StateVersion, now: DateTime<Utc>, force: bool) -> bool {
+        self.is_dirty(version)
+            && (force
+                || self
+                    .last_save
+                    .is_none_or(|saved_at| now - saved_at >= self.min_interval))
+    }
+
+    /// Record a successful save of `version`.
+    pub fn mark_saved(&mut self, version: StateVersion, now: DateTime<Utc>) {
+        self.saved = version;
+        self.last_save = Some(now);
+    }
+}
+
 /// SQLite-based persistence manager.
 pub struct PersistenceManager {
     conn: Connection,
+    /// Stamp written with every new record (empty until set)
+    stamp: RecordStamp,
 }
 
 impl PersistenceManager {
@@ -59,13 +538,41 @@ impl PersistenceManager {
         let conn = Connection::open(db_path.as_ref())
             .with_context(|| format!("Failed to open database at {:?}", db_path.as_ref()))?;
 
-        let manager = Self { conn };
+        // WAL lets the status reader see the last committed snapshot while a save is running
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to enable WAL journal mode")?;
+
+        let manager = Self {
+            conn,
+            stamp: RecordStamp::default(),
+        };
         manager.init_schema()?;
 
         info!("Persistence manager initialized at {:?}", db_path.as_ref());
         Ok(manager)
     }
 
+    /// Open an existing database read-only, without touching the schema.
+    ///
+    /// Used by reporting tools that must never write to a bot's database.
+    pub fn open_read_only<P: AsRef<Path>>(db_path: P) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            db_path.as_ref(),
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("Failed to open database at {:?}", db_path.as_ref()))?;
+
+        Ok(Self {
+            conn,
+            stamp: RecordStamp::default(),
+        })
+    }
+
+    /// Stamp records written from now on with `stamp`.
+    pub fn set_stamp(&mut self, stamp: RecordStamp) {
+        self.stamp = stamp;
+    }
+
     /// Initialize database schema.
     fn init_schema(&self) -> Result<()> {
         self.conn.execute_batch(
@@ -104,7 +611,10 @@ impl PersistenceManager {
                 timestamp TEXT NOT NULL,
                 symbol TEXT NOT NULL,
                 amount TEXT NOT NULL,
-                position_value TEXT
+                position_value TEXT,
+                state TEXT NOT NULL DEFAULT 'settled',
+                expected_amount TEXT,
+                confirmed_at TEXT
             );
             CREATE INDEX IF NOT EXISTS idx_funding_timestamp ON funding_events(timestamp);
             CREATE INDEX IF NOT EXISTS idx_funding_symbol ON funding_events(symbol);
@@ -134,6 +644,47 @@ impl PersistenceManager {
             CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp);
             CREATE INDEX IF NOT EXISTS idx_trades_symbol ON trades(symbol);
 
+            -- Decision vs achieved prices per order leg
+            CREATE TABLE IF NOT EXISTS execution_fills (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                execution_id TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                leg TEXT NOT NULL,
+                side TEXT NOT NULL,
+                quantity TEXT NOT NULL,
+                decision_price TEXT NOT NULL,
+                fill_price TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_execution_fills_timestamp ON execution_fills(timestamp);
+
+            -- Each configuration (or code version) the bot ran with, in order
+            CREATE TABLE IF NOT EXISTS config_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at TEXT NOT NULL,
+                config_hash TEXT NOT NULL,
+                version TEXT NOT NULL,
+                config TEXT NOT NULL
+            );
+
+            -- One row per process run, updated while it runs
+            CREATE TABLE IF NOT EXISTS runs (
+                run_id TEXT PRIMARY KEY,
+                mode TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                config_hash TEXT NOT NULL,
+                version TEXT NOT NULL,
+                scans INTEGER NOT NULL,
+                entries INTEGER NOT NULL,
+                exits INTEGER NOT NULL,
+                errors INTEGER NOT NULL,
+                funding TEXT NOT NULL,
+                fees TEXT NOT NULL,
+                final_equity TEXT
+            );
+
             -- Equity snapshots (hourly)
             CREATE TABLE IF NOT EXISTS equity_snapshots (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -143,9 +694,176 @@ impl PersistenceManager {
                 total_equity TEXT NOT NULL,
                 realized_pnl TEXT NOT NULL,
                 position_count INTEGER NOT NULL,
-                max_drawdown TEXT NOT NULL
+                max_drawdown TEXT NOT NULL,
+                net_delta_usd TEXT NOT NULL DEFAULT '0',
+                gross_delta_usd TEXT NOT NULL DEFAULT '0',
+                total_trading_fees TEXT
             );
             CREATE INDEX IF NOT EXISTS idx_snapshots_timestamp ON equity_snapshots(timestamp);
+
+            -- Entries, exits, halts, config changes and transfers marked on the equity curve
+            CREATE TABLE IF NOT EXISTS equity_annotations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                label TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_equity_annotations_timestamp
+                ON equity_annotations(timestamp);
+
+            -- Realized performance of closed positions
+            CREATE TABLE IF NOT EXISTS position_exits (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                closed_at TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                opened_at TEXT NOT NULL,
+                exit_kind TEXT NOT NULL,
+                funding_received TEXT NOT NULL,
+                costs TEXT NOT NULL,
+                net_carry TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_position_exits_closed_at ON position_exits(closed_at);
+
+            -- Timed entries against immediate execution
+            CREATE TABLE IF NOT EXISTS entry_timings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                side TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                waited_secs INTEGER NOT NULL,
+                baseline_mid TEXT NOT NULL,
+                decision_mid TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_entry_timings_timestamp ON entry_timings(timestamp);
+
+            -- Portfolio funding APR (every cycle with positions held)
+            CREATE TABLE IF NOT EXISTS funding_apr_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                notional TEXT NOT NULL,
+                gross_apr TEXT NOT NULL,
+                cost_apr TEXT NOT NULL,
+                net_apr TEXT NOT NULL,
+                position_count INTEGER NOT NULL,
+                risk_free_rate TEXT NOT NULL DEFAULT '0'
+            );
+            CREATE INDEX IF NOT EXISTS idx_funding_apr_timestamp
+                ON funding_apr_snapshots(timestamp);
+
+            -- Sub-account capital transfers
+            CREATE TABLE IF NOT EXISTS transfers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                direction TEXT NOT NULL,
+                asset TEXT NOT NULL,
+                amount TEXT NOT NULL,
+                wallet TEXT NOT NULL,
+                tran_id INTEGER NOT NULL
+            );
+
+            -- Write-ahead journal of live order intents
+            CREATE TABLE IF NOT EXISTS order_journal (
+                client_order_id TEXT PRIMARY KEY,
+                correlation_id TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                leg TEXT NOT NULL,
+                side TEXT NOT NULL,
+                quantity TEXT NOT NULL,
+                status TEXT NOT NULL,
+                order_id INTEGER,
+                executed_qty TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_order_journal_status ON order_journal(status);
+
+            -- Versioned state snapshots (write-then-swap)
+            CREATE TABLE IF NOT EXISTS state_snapshots (
+                generation INTEGER PRIMARY KEY,
+                initial_balance TEXT NOT NULL,
+                balance TEXT NOT NULL,
+                total_funding_received TEXT NOT NULL,
+                total_trading_fees TEXT NOT NULL,
+                total_borrow_interest TEXT NOT NULL,
+                order_count INTEGER NOT NULL,
+                last_saved TEXT NOT NULL,
+                last_funding_period INTEGER,
+                checksum TEXT NOT NULL,
+                total_funding_paid TEXT NOT NULL DEFAULT '0'
+            );
+            CREATE TABLE IF NOT EXISTS snapshot_positions (
+                generation INTEGER NOT NULL,
+                symbol TEXT NOT NULL,
+                futures_qty TEXT NOT NULL,
+                futures_entry_price TEXT NOT NULL,
+                spot_qty TEXT NOT NULL,
+                spot_entry_price TEXT NOT NULL,
+                borrowed_amount TEXT NOT NULL,
+                opened_at TEXT NOT NULL,
+                total_funding_received TEXT NOT NULL,
+                total_interest_paid TEXT NOT NULL,
+                funding_collections INTEGER NOT NULL,
+                expected_funding_rate TEXT NOT NULL,
+                total_funding_paid TEXT NOT NULL DEFAULT '0',
+                PRIMARY KEY (generation, symbol)
+            );
+
+            -- Single-instance trading lock (singleton row)
+            CREATE TABLE IF NOT EXISTS instance_lock (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                instance_id TEXT NOT NULL,
+                pid INTEGER NOT NULL,
+                acquired_at TEXT NOT NULL,
+                heartbeat_at TEXT NOT NULL
+            );
+            -- Every instance that ever took the lock, to recognise our own exchange orders
+            CREATE TABLE IF NOT EXISTS instance_history (
+                instance_id TEXT PRIMARY KEY,
+                pid INTEGER NOT NULL,
+                acquired_at TEXT NOT NULL
+            );
+
+            -- Malfunction detector state (singleton row plus its rolling windows)
+            CREATE TABLE IF NOT EXISTS malfunction_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                saved_at TEXT NOT NULL,
+                halted_at TEXT,
+                consecutive_risk_cycles INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS malfunction_errors (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                error TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS malfunction_failures (
+                symbol TEXT PRIMARY KEY,
+                consecutive_failures INTEGER NOT NULL,
+                last_failure TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS malfunction_alerts (
+                alert_id TEXT PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                malfunction_type TEXT NOT NULL,
+                message TEXT NOT NULL,
+                should_halt INTEGER NOT NULL,
+                suggested_action TEXT NOT NULL
+            );
+
+            -- Symbols quarantined after repeated funding anomalies
+            CREATE TABLE IF NOT EXISTS funding_quarantine (
+                symbol TEXT PRIMARY KEY,
+                quarantined_at TEXT NOT NULL,
+                anomalies INTEGER NOT NULL,
+                last_deviation TEXT NOT NULL
+            );
+
+            -- Committed snapshot generation (singleton row)
+            CREATE TABLE IF NOT EXISTS state_pointer (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                generation INTEGER NOT NULL
+            );
             "#,
         )?;
 
@@ -161,32 +879,97 @@ impl PersistenceManager {
             [],
         ); // Ignore error if column already exists
 
+        // Migration: Add funding paid columns to snapshots (for existing DBs)
+        for table in ["state_snapshots", "snapshot_positions"] {
+            let _ = self.conn.execute(
+                &format!(
+                    "ALTER TABLE {} ADD COLUMN total_funding_paid TEXT NOT NULL DEFAULT '0'",
+                    table
+                ),
+                [],
+            ); // Ignore error if column already exists
+        }
+
+        // Migration: Add residual delta columns to equity snapshots (for existing DBs)
+        for column in ["net_delta_usd", "gross_delta_usd"] {
+            let _ = self.conn.execute(
+                &format!(
+                    "ALTER TABLE equity_snapshots ADD COLUMN {} TEXT NOT NULL DEFAULT '0'",
+                    column
+                ),
+                [],
+            ); // Ignore error if column already exists
+        }
+
+        // Migration: Add cumulative trading fees to equity snapshots (for existing DBs)
+        let _ = self.conn.execute(
+            "ALTER TABLE equity_snapshots ADD COLUMN total_trading_fees TEXT",
+            [],
+        ); // Ignore error if column already exists
+
+        // Migration: Add risk-free rate to funding APR snapshots (for existing DBs)
+        let _ = self.conn.execute(
+            "ALTER TABLE funding_apr_snapshots ADD COLUMN risk_free_rate TEXT NOT NULL DEFAULT '0'",
+            [],
+        ); // Ignore error if column already exists
+
+        // Migration: Add confirmation state to funding events (for existing DBs);
+        // payments recorded before it were counted as paid
+        for column in [
+            "state TEXT NOT NULL DEFAULT 'settled'",
+            "expected_amount TEXT",
+            "confirmed_at TEXT",
+        ] {
+            let _ = self.conn.execute(
+                &format!("ALTER TABLE funding_events ADD COLUMN {}", column),
+                [],
+            ); // Ignore error if column already exists
+        }
+
+        // Migration: Stamp columns (rows written before stamping have empty values)
+        for table in STAMPED_TABLES {
+            for column in ["config_hash", "version"] {
+                let _ = self.conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {} TEXT NOT NULL DEFAULT ''",
+                        table, column
+                    ),
+                    [],
+                ); // Ignore error if column already exists
+            }
+        }
+
         debug!("Database schema initialized");
         Ok(())
     }
 
     /// Save the complete trading state.
+    ///
+    /// Two-phase write-then-swap: phase one writes the state as a new snapshot
+    /// generation next to the current one, phase two points `state_pointer` at it
+    /// and prunes all but the previous generation. A crash in phase one leaves the
+    /// current snapshot untouched; readers only ever follow the pointer.
     pub fn save_state(&self, state: &PersistedState) -> Result<()> {
+        let checksum = state_checksum(state);
+
+        // Phase 1: write the new generation alongside the current one
         let tx = self.conn.unchecked_transaction()?;
+        let generation: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(generation), 0) + 1 FROM state_snapshots",
+            [],
+            |row| row.get(0),
+        )?;
 
-        // Upsert trading state
         tx.execute(
             r#"
-            INSERT INTO trading_state (id, initial_balance, balance, total_funding_received,
-                                       total_trading_fees, total_borrow_interest, order_count, last_saved,
-                                       last_funding_period)
-            VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-            ON CONFLICT(id) DO UPDATE SET
-                initial_balance = ?1,
-                balance = ?2,
-                total_funding_received = ?3,
-                total_trading_fees = ?4,
-                total_borrow_interest = ?5,
-                order_count = ?6,
-                last_saved = ?7,
-                last_funding_period = ?8
+            INSERT INTO state_snapshots (generation, initial_balance, balance, total_funding_received,
+                                         total_trading_fees, total_borrow_interest, order_count, last_saved,
+                                         last_funding_period, checksum, config_hash, version,
+                                         total_funding_paid)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
             "#,
             params![
+                generation,
                 state.initial_balance.to_string(),
                 state.balance.to_string(),
                 state.total_funding_received.to_string(),
@@ -195,22 +978,25 @@ impl PersistenceManager {
                 state.order_count,
                 state.last_saved.to_rfc3339(),
                 state.last_funding_period,
+                checksum,
+                self.stamp.config_hash,
+                self.stamp.version,
+                state.total_funding_paid.to_string(),
             ],
         )?;
 
-        // Clear and reinsert positions
-        tx.execute("DELETE FROM positions", [])?;
-
         for pos in state.positions.values() {
             tx.execute(
                 r#"
-                INSERT INTO positions (symbol, futures_qty, futures_entry_price, spot_qty,
-                                       spot_entry_price, borrowed_amount, opened_at,
-                                       total_funding_received, total_interest_paid, funding_collections,
-                                       expected_funding_rate)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                INSERT INTO snapshot_positions (generation, symbol, futures_qty, futures_entry_price,
+                                                spot_qty, spot_entry_price, borrowed_amount, opened_at,
+                                                total_funding_received, total_interest_paid,
+                                                funding_collections, expected_funding_rate,
+                                                total_funding_paid)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
                 "#,
                 params![
+                    generation,
                     pos.symbol,
                     pos.futures_qty.to_string(),
                     pos.futures_entry_price.to_string(),
@@ -222,13 +1008,33 @@ impl PersistenceManager {
                     pos.total_interest_paid.to_string(),
                     pos.funding_collections,
                     pos.expected_funding_rate.to_string(),
+                    pos.total_funding_paid.to_string(),
                 ],
             )?;
         }
+        tx.commit()?;
 
+        // Phase 2: swap the pointer and prune everything older than the previous generation
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            r#"
+            INSERT INTO state_pointer (id, generation) VALUES (1, ?1)
+            ON CONFLICT(id) DO UPDATE SET generation = ?1
+            "#,
+            params![generation],
+        )?;
+        tx.execute(
+            "DELETE FROM snapshot_positions WHERE generation < ?1",
+            params![generation - 1],
+        )?;
+        tx.execute(
+            "DELETE FROM state_snapshots WHERE generation < ?1",
+            params![generation - 1],
+        )?;
         tx.commit()?;
 
         debug!(
+            generation,
             balance = %state.balance,
             positions = state.positions.len(),
             "State saved to database"
@@ -237,127 +1043,296 @@ impl PersistenceManager {
     }
 
     /// Load the trading state from database.
+    ///
+    /// Reads inside one transaction so a concurrent save is never half-visible.
+    /// If the current snapshot fails checksum verification, the previous generation
+    /// is used instead; an error is returned only when neither verifies. Databases
+    /// written before snapshots existed are read from the legacy tables.
     pub fn load_state(&self) -> Result<Option<PersistedState>> {
-        // Load trading state
-        let state_row: Option<(String, String, String, String, String, u64, String, Option<u32>)> = self
-            .conn
+        let tx = self.conn.unchecked_transaction()?;
+        let generation: Option<i64> = tx
             .query_row(
-                r#"
-                SELECT initial_balance, balance, total_funding_received, total_trading_fees,
-                       total_borrow_interest, order_count, last_saved, last_funding_period
-                FROM trading_state WHERE id = 1
-                "#,
+                "SELECT generation FROM state_pointer WHERE id = 1",
                 [],
-                |row| {
-                    Ok((
-                        row.get(0)?,
-                        row.get(1)?,
-                        row.get(2)?,
-                        row.get(3)?,
-                        row.get(4)?,
-                        row.get(5)?,
-                        row.get(6)?,
-                        row.get(7)?,
-                    ))
-                },
+                |row| row.get(0),
             )
             .optional()?;
 
-        let Some((initial_balance, balance, funding, fees, interest, order_count, last_saved, last_funding_period)) =
-            state_row
-        else {
-            return Ok(None);
+        let state = match generation {
+            Some(generation) => match Self::load_generation(&tx, generation) {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    warn!(
+                        generation,
+                        error = %e,
+                        "Latest state snapshot failed verification, falling back to previous"
+                    );
+                    let previous = Self::load_generation(&tx, generation - 1)
+                        .context("No verifiable state snapshot")?;
+                    Some(previous)
+                }
+            },
+            None => Self::load_legacy_state(&tx)?,
         };
+        tx.finish()?;
 
-        // Load positions
-        let mut stmt = self.conn.prepare(
+        if let Some(state) = &state {
+            info!(
+                balance = %state.balance,
+                positions = state.positions.len(),
+                last_saved = %state.last_saved,
+                last_funding_period = ?state.last_funding_period,
+                "Loaded state from database"
+            );
+        }
+        Ok(state)
+    }
+
+    /// Load one snapshot generation and verify its checksum.
+    fn load_generation(conn: &Connection, generation: i64) -> Result<PersistedState> {
+        let (mut state, checksum) = conn
+            .query_row(
+                r#"
+                SELECT initial_balance, balance, total_funding_received, total_trading_fees,
+                       total_borrow_interest, order_count, last_saved, last_funding_period,
+                       total_funding_paid, checksum
+                FROM state_snapshots WHERE generation = ?1
+                "#,
+                params![generation],
+                |row| Ok((read_state_row(row)?, row.get::<_, String>(9)?)),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("State snapshot generation {} not found", generation))?;
+
+        let mut stmt = conn.prepare(
             r#"
             SELECT symbol, futures_qty, futures_entry_price, spot_qty, spot_entry_price,
                    borrowed_amount, opened_at, total_funding_received, total_interest_paid,
-                   funding_collections, expected_funding_rate
-            FROM positions
+                   funding_collections, expected_funding_rate, total_funding_paid
+            FROM snapshot_positions WHERE generation = ?1
             "#,
         )?;
+        state.positions = stmt
+            .query_map(params![generation], read_position_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|pos| (pos.symbol.clone(), pos))
+            .collect();
 
-        let positions: HashMap<String, PersistedPosition> = stmt
-            .query_map([], |row| {
-                let symbol: String = row.get(0)?;
-                Ok((
-                    symbol.clone(),
-                    PersistedPosition {
-                        symbol,
-                        futures_qty: Decimal::from_str(&row.get::<_, String>(1)?)
-                            .unwrap_or_default(),
-                        futures_entry_price: Decimal::from_str(&row.get::<_, String>(2)?)
-                            .unwrap_or_default(),
-                        spot_qty: Decimal::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
-                        spot_entry_price: Decimal::from_str(&row.get::<_, String>(4)?)
-                            .unwrap_or_default(),
-                        borrowed_amount: Decimal::from_str(&row.get::<_, String>(5)?)
-                            .unwrap_or_default(),
-                        opened_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
-                            .map(|dt| dt.with_timezone(&Utc))
-                            .unwrap_or_else(|_| Utc::now()),
-                        total_funding_received: Decimal::from_str(&row.get::<_, String>(7)?)
-                            .unwrap_or_default(),
-                        total_interest_paid: Decimal::from_str(&row.get::<_, String>(8)?)
-                            .unwrap_or_default(),
-                        funding_collections: row.get(9)?,
-                        expected_funding_rate: Decimal::from_str(&row.get::<_, String>(10)?)
-                            .unwrap_or_default(),
-                    },
-                ))
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
+        let actual = state_checksum(&state);
+        if actual != checksum {
+            bail!(
+                "State snapshot generation {} checksum mismatch (stored {}, computed {})",
+                generation,
+                checksum,
+                actual
+            );
+        }
+        Ok(state)
+    }
 
-        let state = PersistedState {
-            initial_balance: Decimal::from_str(&initial_balance).unwrap_or_default(),
-            balance: Decimal::from_str(&balance).unwrap_or_default(),
-            total_funding_received: Decimal::from_str(&funding).unwrap_or_default(),
-            total_trading_fees: Decimal::from_str(&fees).unwrap_or_default(),
-            total_borrow_interest: Decimal::from_str(&interest).unwrap_or_default(),
-            order_count,
-            positions,
-            last_saved: DateTime::parse_from_rfc3339(&last_saved)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now()),
-            last_funding_period,
+    /// Load state written before snapshots existed (no checksum).
+    fn load_legacy_state(conn: &Connection) -> Result<Option<PersistedState>> {
+        let Some(mut state) = conn
+            .query_row(
+                r#"
+                SELECT initial_balance, balance, total_funding_received, total_trading_fees,
+                       total_borrow_interest, order_count, last_saved, last_funding_period, '0'
+                FROM trading_state WHERE id = 1
+                "#,
+                [],
+                read_state_row,
+            )
+            .optional()?
+        else {
+            return Ok(None);
         };
 
-        info!(
-            balance = %state.balance,
-            positions = state.positions.len(),
-            last_saved = %state.last_saved,
-            last_funding_period = ?state.last_funding_period,
-            "Loaded state from database"
-        );
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT symbol, futures_qty, futures_entry_price, spot_qty, spot_entry_price,
+                   borrowed_amount, opened_at, total_funding_received, total_interest_paid,
+                   funding_collections, expected_funding_rate, '0'
+            FROM positions
+            "#,
+        )?;
+        state.positions = stmt
+            .query_map([], read_position_row)?
+            .filter_map(|r| r.ok())
+            .map(|pos| (pos.symbol.clone(), pos))
+            .collect();
 
+        info!("Loaded legacy state; it will be migrated to snapshots on the next save");
         Ok(Some(state))
     }
 
-    /// Record a funding event.
+    /// Record a funding payment confirmed without a prior expectation.
     pub fn record_funding_event(
         &self,
         symbol: &str,
         amount: Decimal,
         position_value: Option<Decimal>,
     ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
         self.conn.execute(
             r#"
-            INSERT INTO funding_events (timestamp, symbol, amount, position_value)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO funding_events (timestamp, symbol, amount, position_value, state,
+                confirmed_at, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, 'settled', ?1, ?5, ?6)
             "#,
             params![
-                Utc::now().to_rfc3339(),
+                now,
                 symbol,
                 amount.to_string(),
                 position_value.map(|v| v.to_string()),
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record a settlement that fell due and is awaiting the exchange.
+    ///
+    /// Returns the event id to settle it with.
+    pub fn record_expected_funding(
+        &self,
+        symbol: &str,
+        settled_at: DateTime<Utc>,
+        expected_amount: Decimal,
+        position_value: Option<Decimal>,
+    ) -> Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT INTO funding_events (timestamp, symbol, amount, position_value, state,
+                expected_amount, config_hash, version)
+            VALUES (?1, ?2, '0', ?3, 'expected', ?4, ?5, ?6)
+            "#,
+            params![
+                settled_at.to_rfc3339(),
+                symbol,
+                position_value.map(|v| v.to_string()),
+                expected_amount.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
             ],
         )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Record the exchange-confirmed amount of an expected settlement.
+    pub fn settle_funding_event(&self, id: i64, amount: Decimal) -> Result<()> {
+        let updated = self.conn.execute(
+            r#"
+            UPDATE funding_events SET amount = ?2, state = 'settled', confirmed_at = ?3
+            WHERE id = ?1 AND state = 'expected'
+            "#,
+            params![id, amount.to_string(), Utc::now().to_rfc3339()],
+        )?;
+        if updated == 0 {
+            bail!("No expected funding event {}", id);
+        }
+        Ok(())
+    }
+
+    /// Mark a settled payment as matching its expectation.
+    pub fn verify_funding_event(&self, id: i64) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE funding_events SET state = 'verified' WHERE id = ?1 AND state = 'settled'",
+            [id],
+        )?;
+        if updated == 0 {
+            bail!("No settled funding event {}", id);
+        }
         Ok(())
     }
 
+    /// Settlements still awaiting the exchange, oldest first, with their ids.
+    pub fn get_expected_funding_events(&self) -> Result<Vec<(i64, PersistedFundingEvent)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, amount, position_value, state, expected_amount, id
+            FROM funding_events
+            WHERE state = 'expected'
+            ORDER BY id ASC
+            "#,
+        )?;
+        let events = stmt
+            .query_map([], |row| Ok((row.get(6)?, read_funding_event_row(row)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(events)
+    }
+
+    /// Funding income by confirmation state.
+    pub fn get_funding_confirmation(&self) -> Result<FundingConfirmation> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT state, amount, expected_amount FROM funding_events")?;
+        let mut confirmation = FundingConfirmation::default();
+        let rows = stmt.query_map([], |row| {
+            let state: String = row.get(0)?;
+            let amount: String = row.get(1)?;
+            let expected: Option<String> = row.get(2)?;
+            Ok((state, amount, expected))
+        })?;
+        for (state, amount, expected) in rows.filter_map(|r| r.ok()) {
+            let amount = Decimal::from_str(&amount).unwrap_or_default();
+            match FundingState::parse(&state) {
+                Some(FundingState::Expected) => {
+                    confirmation.pending += 1;
+                    confirmation.projected += expected
+                        .and_then(|v| Decimal::from_str(&v).ok())
+                        .unwrap_or_default();
+                }
+                Some(FundingState::Verified) => confirmation.verified += amount,
+                Some(FundingState::Settled) | None => confirmation.settled += amount,
+            }
+        }
+        Ok(confirmation)
+    }
+
+    /// Get recent funding payments, newest first.
+    pub fn get_recent_funding_events(&self, limit: usize) -> Result<Vec<PersistedFundingEvent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, amount, position_value, state, expected_amount
+            FROM funding_events
+            ORDER BY id DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let events: Vec<PersistedFundingEvent> = stmt
+            .query_map([limit], read_funding_event_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Confirmed funding payments since `since`, oldest first.
+    pub fn get_funding_events_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedFundingEvent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, amount, position_value, state, expected_amount
+            FROM funding_events
+            WHERE timestamp >= ?1 AND state != 'expected'
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let events: Vec<PersistedFundingEvent> = stmt
+            .query_map([since.to_rfc3339()], read_funding_event_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
     /// Record an interest event.
     pub fn record_interest_event(
         &self,
@@ -367,20 +1342,181 @@ impl PersistenceManager {
     ) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT INTO interest_events (timestamp, symbol, amount, borrowed_amount)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO interest_events (timestamp, symbol, amount, borrowed_amount, config_hash,
+                version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
             params![
                 Utc::now().to_rfc3339(),
                 symbol,
                 amount.to_string(),
                 borrowed_amount.map(|v| v.to_string()),
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Interest charges recorded since `since`, oldest first.
+    pub fn get_interest_events_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedInterestEvent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, amount, borrowed_amount
+            FROM interest_events
+            WHERE timestamp >= ?1
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let events: Vec<PersistedInterestEvent> = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                let ts: String = row.get(0)?;
+                let amount: String = row.get(2)?;
+                let borrowed_amount: Option<String> = row.get(3)?;
+                Ok(PersistedInterestEvent {
+                    timestamp: DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    symbol: row.get(1)?,
+                    amount: Decimal::from_str(&amount).unwrap_or_default(),
+                    borrowed_amount: borrowed_amount.and_then(|v| Decimal::from_str(&v).ok()),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Record the realized performance of a closed position.
+    pub fn record_position_exit(&self, exit: &PersistedPositionExit) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO position_exits (closed_at, symbol, opened_at, exit_kind, funding_received,
+                                        costs, net_carry, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            params![
+                exit.closed_at.to_rfc3339(),
+                exit.symbol,
+                exit.opened_at.to_rfc3339(),
+                exit.exit_kind,
+                exit.funding_received.to_string(),
+                exit.costs.to_string(),
+                exit.net_carry.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Positions closed since `since`, oldest first.
+    pub fn get_position_exits_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedPositionExit>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT closed_at, symbol, opened_at, exit_kind, funding_received, costs, net_carry
+            FROM position_exits
+            WHERE closed_at >= ?1
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let exits: Vec<PersistedPositionExit> = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                let timestamp = |idx: usize| -> rusqlite::Result<DateTime<Utc>> {
+                    Ok(DateTime::parse_from_rfc3339(&row.get::<_, String>(idx)?)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()))
+                };
+                let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+                    Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+                };
+                Ok(PersistedPositionExit {
+                    closed_at: timestamp(0)?,
+                    symbol: row.get(1)?,
+                    opened_at: timestamp(2)?,
+                    exit_kind: row.get(3)?,
+                    funding_received: decimal(4)?,
+                    costs: decimal(5)?,
+                    net_carry: decimal(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(exits)
+    }
+
+    /// Record a timed entry.
+    pub fn record_entry_timing(&self, timing: &PersistedEntryTiming) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO entry_timings (timestamp, symbol, side, outcome, waited_secs, baseline_mid,
+                                       decision_mid, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            params![
+                timing.timestamp.to_rfc3339(),
+                timing.symbol,
+                timing.side,
+                timing.outcome,
+                timing.waited_secs,
+                timing.baseline_mid.to_string(),
+                timing.decision_mid.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
             ],
         )?;
         Ok(())
     }
 
+    /// Timed entries since `since`, oldest first.
+    pub fn get_entry_timings_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedEntryTiming>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, side, outcome, waited_secs, baseline_mid, decision_mid
+            FROM entry_timings
+            WHERE timestamp >= ?1
+            ORDER BY id ASC
+            "#,
+        )?;
+
+        let timings: Vec<PersistedEntryTiming> = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+                    Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+                };
+                Ok(PersistedEntryTiming {
+                    timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    symbol: row.get(1)?,
+                    side: row.get(2)?,
+                    outcome: row.get(3)?,
+                    waited_secs: row.get(4)?,
+                    baseline_mid: decimal(5)?,
+                    decision_mid: decimal(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(timings)
+    }
+
     /// Record a trade.
+    #[allow(clippy::too_many_arguments)]
     pub fn record_trade(
         &self,
         symbol: &str,
@@ -393,8 +1529,9 @@ impl PersistenceManager {
     ) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT INTO trades (timestamp, symbol, side, order_type, quantity, price, fee, is_futures)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT INTO trades (timestamp, symbol, side, order_type, quantity, price, fee,
+                                is_futures, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
             params![
                 Utc::now().to_rfc3339(),
@@ -405,120 +1542,1185 @@ impl PersistenceManager {
                 price.to_string(),
                 fee.to_string(),
                 is_futures as i32,
+                self.stamp.config_hash,
+                self.stamp.version,
             ],
         )?;
         Ok(())
     }
 
-    /// Record an equity snapshot.
-    pub fn record_snapshot(
-        &self,
-        balance: Decimal,
-        unrealized_pnl: Decimal,
-        total_equity: Decimal,
-        realized_pnl: Decimal,
-        position_count: usize,
-        max_drawdown: Decimal,
-    ) -> Result<()> {
+    /// Get recent trades, newest first.
+    pub fn get_recent_trades(&self, limit: usize) -> Result<Vec<PersistedTrade>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, symbol, side, order_type, quantity, price, fee, is_futures
+            FROM trades
+            ORDER BY id DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let trades: Vec<PersistedTrade> = stmt
+            .query_map([limit], |row| {
+                let ts: String = row.get(0)?;
+                let quantity: String = row.get(4)?;
+                let price: String = row.get(5)?;
+                let fee: String = row.get(6)?;
+                let is_futures: i32 = row.get(7)?;
+                Ok(PersistedTrade {
+                    timestamp: DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    symbol: row.get(1)?,
+                    side: row.get(2)?,
+                    order_type: row.get(3)?,
+                    quantity: Decimal::from_str(&quantity).unwrap_or_default(),
+                    price: Decimal::from_str(&price).unwrap_or_default(),
+                    fee: Decimal::from_str(&fee).unwrap_or_default(),
+                    is_futures: is_futures != 0,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(trades)
+    }
+
+    /// Record the fill of one order leg.
+    pub fn record_execution_fill(&self, fill: &PersistedExecutionFill) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT INTO equity_snapshots (timestamp, balance, unrealized_pnl, total_equity,
-                                          realized_pnl, position_count, max_drawdown)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT INTO execution_fills (timestamp, execution_id, symbol, kind, leg, side,
+                quantity, decision_price, fill_price, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
             "#,
             params![
-                Utc::now().to_rfc3339(),
-                balance.to_string(),
-                unrealized_pnl.to_string(),
-                total_equity.to_string(),
-                realized_pnl.to_string(),
-                position_count,
-                max_drawdown.to_string(),
+                fill.timestamp.to_rfc3339(),
+                fill.execution_id,
+                fill.symbol,
+                fill.kind,
+                fill.leg,
+                fill.side,
+                fill.quantity.to_string(),
+                fill.decision_price.to_string(),
+                fill.fill_price.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
             ],
         )?;
         Ok(())
     }
 
-    /// Get total funding received by symbol.
-    pub fn get_funding_stats(&self) -> Result<HashMap<String, Decimal>> {
+    /// Order leg fills recorded since `since`, oldest first.
+    pub fn get_execution_fills_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedExecutionFill>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT symbol, SUM(CAST(amount AS REAL)) as total
-            FROM funding_events
-            GROUP BY symbol
+            SELECT timestamp, execution_id, symbol, kind, leg, side, quantity,
+                decision_price, fill_price
+            FROM execution_fills
+            WHERE timestamp >= ?1
+            ORDER BY id ASC
             "#,
         )?;
 
-        let stats: HashMap<String, Decimal> = stmt
-            .query_map([], |row| {
-                let symbol: String = row.get(0)?;
-                let total: f64 = row.get(1)?;
-                Ok((symbol, Decimal::from_f64_retain(total).unwrap_or_default()))
+        let fills: Vec<PersistedExecutionFill> = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+                    Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+                };
+                let ts: String = row.get(0)?;
+                Ok(PersistedExecutionFill {
+                    timestamp: DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    execution_id: row.get(1)?,
+                    symbol: row.get(2)?,
+                    kind: row.get(3)?,
+                    leg: row.get(4)?,
+                    side: row.get(5)?,
+                    quantity: decimal(6)?,
+                    decision_price: decimal(7)?,
+                    fill_price: decimal(8)?,
+                })
             })?
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(stats)
+        Ok(fills)
     }
 
-    /// Get recent equity snapshots for performance analysis.
-    pub fn get_recent_snapshots(&self, limit: usize) -> Result<Vec<(DateTime<Utc>, Decimal)>> {
+    /// Record the configuration in effect if it differs from the last one recorded.
+    ///
+    /// A new code version with an unchanged configuration counts as a change too.
+    /// Returns true when a change was recorded.
+    pub fn record_config_change(
+        &self,
+        stamp: &RecordStamp,
+        config: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let unchanged = self
+            .get_config_history(1)?
+            .first()
+            .is_some_and(|p| p.config_hash == stamp.config_hash && p.version == stamp.version);
+        if unchanged {
+            return Ok(false);
+        }
+
+        self.conn.execute(
+            r#"
+            INSERT INTO config_history (recorded_at, config_hash, version, config)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![now.to_rfc3339(), stamp.config_hash, stamp.version, config],
+        )?;
+        Ok(true)
+    }
+
+    /// The `limit` most recent configuration changes, newest first.
+    pub fn get_config_history(&self, limit: usize) -> Result<Vec<PersistedConfigChange>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT timestamp, total_equity
-            FROM equity_snapshots
-            ORDER BY timestamp DESC
+            SELECT recorded_at, config_hash, version, config
+            FROM config_history
+            ORDER BY id DESC
             LIMIT ?1
             "#,
         )?;
 
-        let snapshots: Vec<(DateTime<Utc>, Decimal)> = stmt
-            .query_map([limit], |row| {
+        let changes: Vec<PersistedConfigChange> = stmt
+            .query_map([limit as i64], |row| {
                 let ts: String = row.get(0)?;
-                let equity: String = row.get(1)?;
-                Ok((
-                    DateTime::parse_from_rfc3339(&ts)
+                Ok(PersistedConfigChange {
+                    recorded_at: DateTime::parse_from_rfc3339(&ts)
                         .map(|dt| dt.with_timezone(&Utc))
                         .unwrap_or_else(|_| Utc::now()),
-                    Decimal::from_str(&equity).unwrap_or_default(),
-                ))
+                    config_hash: row.get(1)?,
+                    version: row.get(2)?,
+                    config: row.get(3)?,
+                })
             })?
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(snapshots)
+        Ok(changes)
     }
 
-    /// Check if we have any saved state.
-    pub fn has_state(&self) -> Result<bool> {
-        let count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM trading_state WHERE id = 1",
-            [],
-            |row| row.get(0),
+    /// Insert or update the record of a run.
+    pub fn save_run(&self, run: &PersistedRun) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO runs (run_id, mode, started_at, ended_at, config_hash, version, scans,
+                entries, exits, errors, funding, fees, final_equity)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT(run_id) DO UPDATE SET
+                ended_at = excluded.ended_at,
+                scans = excluded.scans,
+                entries = excluded.entries,
+                exits = excluded.exits,
+                errors = excluded.errors,
+                funding = excluded.funding,
+                fees = excluded.fees,
+                final_equity = excluded.final_equity
+            "#,
+            params![
+                run.run_id,
+                run.mode,
+                run.started_at.to_rfc3339(),
+                run.ended_at.map(|t| t.to_rfc3339()),
+                run.config_hash,
+                run.version,
+                run.scans as i64,
+                run.entries as i64,
+                run.exits as i64,
+                run.errors as i64,
+                run.funding.to_string(),
+                run.fees.to_string(),
+                run.final_equity.map(|e| e.to_string()),
+            ],
         )?;
-        Ok(count > 0)
+        Ok(())
     }
 
-    /// Clear all data (for testing or reset).
-    pub fn clear_all(&self) -> Result<()> {
-        warn!("Clearing all persistence data");
-        self.conn.execute_batch(
+    /// The `limit` most recent runs, newest first.
+    pub fn get_runs(&self, limit: usize) -> Result<Vec<PersistedRun>> {
+        let mut stmt = self.conn.prepare(
             r#"
-            DELETE FROM trading_state;
-            DELETE FROM positions;
-            DELETE FROM funding_events;
-            DELETE FROM interest_events;
-            DELETE FROM trades;
-            DELETE FROM equity_snapshots;
+            SELECT run_id, mode, started_at, ended_at, config_hash, version, scans, entries,
+                exits, errors, funding, fees, final_equity
+            FROM runs
+            ORDER BY started_at DESC
+            LIMIT ?1
             "#,
         )?;
-        Ok(())
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let runs: Vec<PersistedRun> = stmt
+            .query_map([limit as i64], |row| {
+                let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+                    Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+                };
+                let timestamp = |value: String| {
+                    DateTime::parse_from_rfc3339(&value)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now())
+                };
+                Ok(PersistedRun {
+                    run_id: row.get(0)?,
+                    mode: row.get(1)?,
+                    started_at: timestamp(row.get(2)?),
+                    ended_at: row.get::<_, Option<String>>(3)?.map(timestamp),
+                    config_hash: row.get(4)?,
+                    version: row.get(5)?,
+                    scans: row.get::<_, i64>(6)? as u64,
+                    entries: row.get::<_, i64>(7)? as u64,
+                    exits: row.get::<_, i64>(8)? as u64,
+                    errors: row.get::<_, i64>(9)? as u64,
+                    funding: decimal(10)?,
+                    fees: decimal(11)?,
+                    final_equity: row
+                        .get::<_, Option<String>>(12)?
+                        .and_then(|e| Decimal::from_str(&e).ok()),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(runs)
+    }
+
+    /// Record an equity snapshot with the book's net and gross residual delta
+    /// and the trading fees paid so far.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_snapshot(
+        &self,
+        balance: Decimal,
+        unrealized_pnl: Decimal,
+        total_equity: Decimal,
+        realized_pnl: Decimal,
+        position_count: usize,
+        max_drawdown: Decimal,
+        net_delta_usd: Decimal,
+        gross_delta_usd: Decimal,
+        total_trading_fees: Decimal,
+    ) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO equity_snapshots (timestamp, balance, unrealized_pnl, total_equity,
+                                          realized_pnl, position_count, max_drawdown,
+                                          net_delta_usd, gross_delta_usd, total_trading_fees,
+                                          config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+            params![
+                Utc::now().to_rfc3339(),
+                balance.to_string(),
+                unrealized_pnl.to_string(),
+                total_equity.to_string(),
+                realized_pnl.to_string(),
+                position_count,
+                max_drawdown.to_string(),
+                net_delta_usd.to_string(),
+                gross_delta_usd.to_string(),
+                total_trading_fees.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a notable event on the equity curve.
+    pub fn record_equity_annotation(&self, annotation: &PersistedEquityAnnotation) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO equity_annotations (timestamp, kind, label)
+            VALUES (?1, ?2, ?3)
+            "#,
+            params![
+                annotation.timestamp.to_rfc3339(),
+                annotation.kind.as_str(),
+                annotation.label,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Equity curve annotations since `since`, oldest first.
+    ///
+    /// Rows of an unknown kind (written by a newer version) are skipped.
+    pub fn get_equity_annotations_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedEquityAnnotation>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, kind, label
+            FROM equity_annotations
+            WHERE timestamp >= ?1
+            ORDER BY timestamp ASC, id ASC
+            "#,
+        )?;
+
+        let annotations: Vec<PersistedEquityAnnotation> = stmt
+            .query_map([since.to_rfc3339()], |row| {
+                let ts: String = row.get(0)?;
+                let kind: String = row.get(1)?;
+                Ok((ts, kind, row.get::<_, String>(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(ts, kind, label)| {
+                Some(PersistedEquityAnnotation {
+                    timestamp: DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .ok()?,
+                    kind: EquityEventKind::parse(&kind)?,
+                    label,
+                })
+            })
+            .collect();
+
+        Ok(annotations)
+    }
+
+    /// Record the portfolio funding APR of a cycle.
+    pub fn record_funding_apr(&self, apr: &PersistedFundingApr) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO funding_apr_snapshots (timestamp, notional, gross_apr, cost_apr, net_apr,
+                                               position_count, risk_free_rate, config_hash,
+                                               version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            params![
+                apr.timestamp.to_rfc3339(),
+                apr.notional.to_string(),
+                apr.gross_apr.to_string(),
+                apr.cost_apr.to_string(),
+                apr.net_apr.to_string(),
+                apr.position_count,
+                apr.risk_free_rate.to_string(),
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Most recently recorded portfolio funding APR.
+    pub fn get_latest_funding_apr(&self) -> Result<Option<PersistedFundingApr>> {
+        let apr = self
+            .conn
+            .query_row(
+                r#"
+                SELECT timestamp, notional, gross_apr, cost_apr, net_apr, position_count,
+                       risk_free_rate
+                FROM funding_apr_snapshots
+                ORDER BY id DESC
+                LIMIT 1
+                "#,
+                [],
+                |row| {
+                    let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+                        Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+                    };
+                    let ts: String = row.get(0)?;
+                    Ok(PersistedFundingApr {
+                        timestamp: DateTime::parse_from_rfc3339(&ts)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        notional: decimal(1)?,
+                        gross_apr: decimal(2)?,
+                        cost_apr: decimal(3)?,
+                        net_apr: decimal(4)?,
+                        position_count: row.get(5)?,
+                        risk_free_rate: decimal(6)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(apr)
+    }
+
+    /// Record a sub-account capital transfer.
+    pub fn record_transfer(
+        &self,
+        direction: &str,
+        asset: &str,
+        amount: Decimal,
+        wallet: &str,
+        tran_id: i64,
+    ) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO transfers (timestamp, direction, asset, amount, wallet, tran_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![
+                Utc::now().to_rfc3339(),
+                direction,
+                asset,
+                amount.to_string(),
+                wallet,
+                tran_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get recent sub-account transfers, newest first.
+    pub fn get_recent_transfers(&self, limit: usize) -> Result<Vec<PersistedTransfer>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, direction, asset, amount, wallet, tran_id
+            FROM transfers
+            ORDER BY id DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let transfers: Vec<PersistedTransfer> = stmt
+            .query_map([limit], |row| {
+                let ts: String = row.get(0)?;
+                let amount: String = row.get(3)?;
+                Ok(PersistedTransfer {
+                    timestamp: DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    direction: row.get(1)?,
+                    asset: row.get(2)?,
+                    amount: Decimal::from_str(&amount).unwrap_or_default(),
+                    wallet: row.get(4)?,
+                    tran_id: row.get(5)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(transfers)
+    }
+
+    /// Journal an order intent before it is sent to the exchange.
+    pub fn journal_order_intent(
+        &self,
+        client_order_id: &str,
+        correlation_id: &str,
+        symbol: &str,
+        leg: &str,
+        side: &str,
+        quantity: Decimal,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"
+            INSERT INTO order_journal (client_order_id, correlation_id, symbol, leg, side,
+                quantity, status, created_at, updated_at, config_hash, version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'pending', ?7, ?7, ?8, ?9)
+            "#,
+            params![
+                client_order_id,
+                correlation_id,
+                symbol,
+                leg,
+                side,
+                quantity.to_string(),
+                now,
+                self.stamp.config_hash,
+                self.stamp.version,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a journaled order as acknowledged by the exchange.
+    pub fn complete_order_intent(
+        &self,
+        client_order_id: &str,
+        order_id: i64,
+        executed_qty: Decimal,
+    ) -> Result<()> {
+        self.conn.execute(
+            r#"
+            UPDATE order_journal
+            SET status = 'completed', order_id = ?2, executed_qty = ?3, updated_at = ?4
+            WHERE client_order_id = ?1
+            "#,
+            params![
+                client_order_id,
+                order_id,
+                executed_qty.to_string(),
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a journaled order as never having reached the exchange.
+    pub fn fail_order_intent(&self, client_order_id: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE order_journal SET status = 'failed', updated_at = ?2 WHERE client_order_id = ?1",
+            params![client_order_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Journaled orders whose outcome is unknown, oldest first.
+    pub fn get_incomplete_order_intents(&self) -> Result<Vec<PersistedOrderIntent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT client_order_id, correlation_id, symbol, leg, side, quantity, status,
+                order_id, executed_qty, created_at
+            FROM order_journal
+            WHERE status = 'pending'
+            ORDER BY rowid ASC
+            "#,
+        )?;
+
+        let intents: Vec<PersistedOrderIntent> = stmt
+            .query_map([], read_order_intent_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(intents)
+    }
+
+    /// Latest journaled order per symbol among `legs`, ignoring failed orders.
+    pub fn get_latest_order_intents(&self, legs: &[&str]) -> Result<Vec<PersistedOrderIntent>> {
+        if legs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; legs.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT client_order_id, correlation_id, symbol, leg, side, quantity, status,
+                order_id, executed_qty, created_at
+            FROM order_journal
+            WHERE rowid IN (
+                SELECT MAX(rowid) FROM order_journal
+                WHERE status != 'failed' AND leg IN ({})
+                GROUP BY symbol
+            )
+            ORDER BY rowid ASC
+            "#,
+            placeholders
+        ))?;
+
+        let intents: Vec<PersistedOrderIntent> = stmt
+            .query_map(rusqlite::params_from_iter(legs), read_order_intent_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(intents)
+    }
+
+    /// Journaled orders created since `since`, oldest first.
+    pub fn get_order_intents_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<PersistedOrderIntent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT client_order_id, correlation_id, symbol, leg, side, quantity, status,
+                order_id, executed_qty, created_at
+            FROM order_journal
+            WHERE created_at >= ?1
+            ORDER BY rowid ASC
+            "#,
+        )?;
+
+        let intents: Vec<PersistedOrderIntent> = stmt
+            .query_map([since.to_rfc3339()], read_order_intent_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(intents)
+    }
+
+    /// Journaled orders sharing the correlation ID of `client_order_id`, itself
+    /// included, oldest first. Empty if it was never journaled.
+    pub fn get_correlated_order_intents(
+        &self,
+        client_order_id: &str,
+    ) -> Result<Vec<PersistedOrderIntent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT client_order_id, correlation_id, symbol, leg, side, quantity, status,
+                order_id, executed_qty, created_at
+            FROM order_journal
+            WHERE correlation_id = (
+                SELECT correlation_id FROM order_journal WHERE client_order_id = ?1
+            )
+            ORDER BY rowid ASC
+            "#,
+        )?;
+
+        let intents: Vec<PersistedOrderIntent> = stmt
+            .query_map([client_order_id], read_order_intent_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(intents)
+    }
+
+    /// Take the single-instance trading lock.
+    ///
+    /// The lock is free when no row exists or its holder stopped heartbeating for
+    /// longer than `stale_after` (e.g. it crashed). A live holder is only replaced
+    /// when `takeover` is set; it notices on its next heartbeat and stands down.
+    pub fn acquire_instance_lock(
+        &self,
+        lock: &InstanceLock,
+        stale_after: Duration,
+        takeover: bool,
+    ) -> Result<LockAcquisition> {
+        // IMMEDIATE so two instances starting together cannot both see a free lock
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)?;
+
+        let current = tx
+            .query_row(
+                "SELECT instance_id, pid, acquired_at, heartbeat_at FROM instance_lock WHERE id = 1",
+                [],
+                |row| {
+                    let acquired_at: String = row.get(2)?;
+                    let heartbeat_at: String = row.get(3)?;
+                    Ok(InstanceLock {
+                        instance_id: row.get(0)?,
+                        pid: row.get(1)?,
+                        acquired_at: DateTime::parse_from_rfc3339(&acquired_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        heartbeat_at: DateTime::parse_from_rfc3339(&heartbeat_at)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                    })
+                },
+            )
+            .optional()?
+            .filter(|current| current.instance_id != lock.instance_id);
+
+        let outcome = match current {
+            Some(current) if !current.is_stale(lock.heartbeat_at, stale_after) => {
+                if !takeover {
+                    return Ok(LockAcquisition::Held(current));
+                }
+                LockAcquisition::TookOver(current)
+            }
+            previous => LockAcquisition::Acquired(previous),
+        };
+
+        tx.execute(
+            r#"
+            INSERT OR REPLACE INTO instance_lock (id, instance_id, pid, acquired_at, heartbeat_at)
+            VALUES (1, ?1, ?2, ?3, ?4)
+            "#,
+            params![
+                lock.instance_id,
+                lock.pid,
+                lock.acquired_at.to_rfc3339(),
+                lock.heartbeat_at.to_rfc3339(),
+            ],
+        )?;
+        tx.execute(
+            "INSERT OR IGNORE INTO instance_history (instance_id, pid, acquired_at) VALUES (?1, ?2, ?3)",
+            params![lock.instance_id, lock.pid, lock.acquired_at.to_rfc3339()],
+        )?;
+        tx.commit()?;
+
+        Ok(outcome)
+    }
+
+    /// Refresh the lock heartbeat.
+    ///
+    /// Returns `false` when the lock is no longer held by `instance_id`
+    /// (another instance took over).
+    pub fn heartbeat_instance_lock(&self, instance_id: &str, now: DateTime<Utc>) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE instance_lock SET heartbeat_at = ?2 WHERE id = 1 AND instance_id = ?1",
+            params![instance_id, now.to_rfc3339()],
+        )?;
+        Ok(updated == 1)
+    }
+
+    /// Release the lock if `instance_id` still holds it.
+    pub fn release_instance_lock(&self, instance_id: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM instance_lock WHERE id = 1 AND instance_id = ?1",
+            params![instance_id],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the saved malfunction detector state.
+    pub fn save_malfunction_state(&self, state: &PersistedMalfunctionState) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(
+            r#"
+            DELETE FROM malfunction_errors;
+            DELETE FROM malfunction_failures;
+            DELETE FROM malfunction_alerts;
+            "#,
+        )?;
+        tx.execute(
+            r#"
+            INSERT OR REPLACE INTO malfunction_state (id, saved_at, halted_at, consecutive_risk_cycles)
+            VALUES (1, ?1, ?2, ?3)
+            "#,
+            params![
+                state.saved_at.to_rfc3339(),
+                state.halted_at.map(|at| at.to_rfc3339()),
+                state.consecutive_risk_cycles,
+            ],
+        )?;
+        for error in &state.errors {
+            tx.execute(
+                "INSERT INTO malfunction_errors (timestamp, error) VALUES (?1, ?2)",
+                params![error.timestamp.to_rfc3339(), error.error],
+            )?;
+        }
+        for failures in &state.failures {
+            tx.execute(
+                r#"
+                INSERT INTO malfunction_failures (symbol, consecutive_failures, last_failure)
+                VALUES (?1, ?2, ?3)
+                "#,
+                params![
+                    failures.symbol,
+                    failures.consecutive_failures,
+                    failures.last_failure.to_rfc3339(),
+                ],
+            )?;
+        }
+        for alert in &state.alerts {
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO malfunction_alerts (alert_id, timestamp, severity,
+                    malfunction_type, message, should_halt, suggested_action)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "#,
+                params![
+                    alert.alert_id,
+                    alert.timestamp.to_rfc3339(),
+                    alert.severity,
+                    alert.malfunction_type,
+                    alert.message,
+                    alert.should_halt,
+                    alert.suggested_action,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Load the saved malfunction detector state, if any.
+    pub fn load_malfunction_state(&self) -> Result<Option<PersistedMalfunctionState>> {
+        let parse = |ts: &str| {
+            DateTime::parse_from_rfc3339(ts)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now())
+        };
+        let Some(mut state) = self
+            .conn
+            .query_row(
+                "SELECT saved_at, halted_at, consecutive_risk_cycles FROM malfunction_state WHERE id = 1",
+                [],
+                |row| {
+                    let saved_at: String = row.get(0)?;
+                    let halted_at: Option<String> = row.get(1)?;
+                    Ok(PersistedMalfunctionState {
+                        saved_at: parse(&saved_at),
+                        halted_at: halted_at.as_deref().map(parse),
+                        consecutive_risk_cycles: row.get(2)?,
+                        ..Default::default()
+                    })
+                },
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT timestamp, error FROM malfunction_errors ORDER BY id ASC")?;
+        state.errors = stmt
+            .query_map([], |row| {
+                let timestamp: String = row.get(0)?;
+                Ok(PersistedMalfunctionError {
+                    timestamp: parse(&timestamp),
+                    error: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, consecutive_failures, last_failure FROM malfunction_failures ORDER BY symbol",
+        )?;
+        state.failures = stmt
+            .query_map([], |row| {
+                let last_failure: String = row.get(2)?;
+                Ok(PersistedOrderFailures {
+                    symbol: row.get(0)?,
+                    consecutive_failures: row.get(1)?,
+                    last_failure: parse(&last_failure),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT alert_id, timestamp, severity, malfunction_type, message, should_halt,
+                   suggested_action
+            FROM malfunction_alerts
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+        state.alerts = stmt
+            .query_map([], |row| {
+                let timestamp: String = row.get(1)?;
+                Ok(PersistedMalfunctionAlert {
+                    alert_id: row.get(0)?,
+                    timestamp: parse(&timestamp),
+                    severity: row.get(2)?,
+                    malfunction_type: row.get(3)?,
+                    message: row.get(4)?,
+                    should_halt: row.get(5)?,
+                    suggested_action: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Some(state))
+    }
+
+    /// Record a quarantined symbol, replacing an earlier quarantine of it.
+    pub fn save_quarantine(&self, quarantine: &PersistedQuarantine) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO funding_quarantine
+                (symbol, quarantined_at, anomalies, last_deviation)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                quarantine.symbol,
+                quarantine.quarantined_at.to_rfc3339(),
+                quarantine.anomalies,
+                quarantine.last_deviation.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Release a quarantined symbol; returns whether it was quarantined.
+    pub fn release_quarantine(&self, symbol: &str) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM funding_quarantine WHERE symbol = ?1",
+            params![symbol],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Quarantined symbols, oldest first.
+    pub fn get_quarantined(&self) -> Result<Vec<PersistedQuarantine>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, quarantined_at, anomalies, last_deviation
+            FROM funding_quarantine
+            ORDER BY quarantined_at ASC, symbol ASC
+            "#,
+        )?;
+        let quarantined = stmt
+            .query_map([], |row| {
+                let quarantined_at: String = row.get(1)?;
+                let last_deviation: String = row.get(3)?;
+                Ok(PersistedQuarantine {
+                    symbol: row.get(0)?,
+                    quarantined_at: DateTime::parse_from_rfc3339(&quarantined_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    anomalies: row.get(2)?,
+                    last_deviation: Decimal::from_str(&last_deviation).unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(quarantined)
+    }
+
+    /// IDs of every instance that has held the lock on this database.
+    pub fn known_instance_ids(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT instance_id FROM instance_history ORDER BY acquired_at ASC")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    /// Get total confirmed funding received by symbol.
+    pub fn get_funding_stats(&self) -> Result<HashMap<String, Decimal>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT symbol, SUM(CAST(amount AS REAL)) as total
+            FROM funding_events
+            WHERE state != 'expected'
+            GROUP BY symbol
+            "#,
+        )?;
+
+        let stats: HashMap<String, Decimal> = stmt
+            .query_map([], |row| {
+                let symbol: String = row.get(0)?;
+                let total: f64 = row.get(1)?;
+                Ok((symbol, Decimal::from_f64_retain(total).unwrap_or_default()))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(stats)
+    }
+
+    /// Get recent equity snapshots for performance analysis.
+    pub fn get_recent_snapshots(&self, limit: usize) -> Result<Vec<(DateTime<Utc>, Decimal)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT timestamp, total_equity
+            FROM equity_snapshots
+            ORDER BY timestamp DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let snapshots: Vec<(DateTime<Utc>, Decimal)> = stmt
+            .query_map([limit], |row| {
+                let ts: String = row.get(0)?;
+                let equity: String = row.get(1)?;
+                Ok((
+                    DateTime::parse_from_rfc3339(&ts)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    Decimal::from_str(&equity).unwrap_or_default(),
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(snapshots)
+    }
+
+    /// Equity snapshot taken closest to `at`, if any.
+    pub fn get_snapshot_near(&self, at: DateTime<Utc>) -> Result<Option<PersistedEquitySnapshot>> {
+        let snapshot = self
+            .conn
+            .query_row(
+                r#"
+                SELECT timestamp, balance, total_equity, position_count, total_trading_fees
+                FROM equity_snapshots
+                ORDER BY ABS(julianday(timestamp) - julianday(?1)), id DESC
+                LIMIT 1
+                "#,
+                [at.to_rfc3339()],
+                |row| {
+                    let ts: String = row.get(0)?;
+                    let balance: String = row.get(1)?;
+                    let total_equity: String = row.get(2)?;
+                    let fees: Option<String> = row.get(4)?;
+                    Ok(PersistedEquitySnapshot {
+                        timestamp: DateTime::parse_from_rfc3339(&ts)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_else(|_| Utc::now()),
+                        balance: Decimal::from_str(&balance).unwrap_or_default(),
+                        total_equity: Decimal::from_str(&total_equity).unwrap_or_default(),
+                        position_count: row.get(3)?,
+                        total_trading_fees: fees.and_then(|f| Decimal::from_str(&f).ok()),
+                    })
+                },
+            )
+            .optional()?;
+        Ok(snapshot)
+    }
+
+    /// Check if we have any saved state.
+    pub fn has_state(&self) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            r#"
+            SELECT (SELECT COUNT(*) FROM state_pointer) + (SELECT COUNT(*) FROM trading_state)
+            "#,
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Clear all data (for testing or reset).
+    pub fn clear_all(&self) -> Result<()> {
+        warn!("Clearing all persistence data");
+        self.conn.execute_batch(
+            r#"
+            DELETE FROM trading_state;
+            DELETE FROM positions;
+            DELETE FROM funding_events;
+            DELETE FROM interest_events;
+            DELETE FROM trades;
+            DELETE FROM execution_fills;
+            DELETE FROM position_exits;
+            DELETE FROM entry_timings;
+            DELETE FROM runs;
+            DELETE FROM config_history;
+            DELETE FROM equity_snapshots;
+            DELETE FROM equity_annotations;
+            DELETE FROM funding_apr_snapshots;
+            DELETE FROM transfers;
+            DELETE FROM order_journal;
+            DELETE FROM state_pointer;
+            DELETE FROM snapshot_positions;
+            DELETE FROM state_snapshots;
+            DELETE FROM malfunction_state;
+            DELETE FROM malfunction_errors;
+            DELETE FROM malfunction_failures;
+            DELETE FROM malfunction_alerts;
+            "#,
+        )?;
+        Ok(())
+    }
+}
+
+/// Read the scalar state columns (initial_balance .. total_funding_paid) of a row.
+fn read_state_row(row: &Row) -> rusqlite::Result<PersistedState> {
+    let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+        Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+    };
+    Ok(PersistedState {
+        initial_balance: decimal(0)?,
+        balance: decimal(1)?,
+        total_funding_received: decimal(2)?,
+        total_trading_fees: decimal(3)?,
+        total_borrow_interest: decimal(4)?,
+        order_count: row.get(5)?,
+        positions: HashMap::new(),
+        last_saved: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        last_funding_period: row.get(7)?,
+        total_funding_paid: decimal(8)?,
+    })
+}
+
+/// Read an order journal row (client_order_id .. created_at).
+fn read_order_intent_row(row: &Row) -> rusqlite::Result<PersistedOrderIntent> {
+    let quantity: String = row.get(5)?;
+    let executed_qty: Option<String> = row.get(8)?;
+    let ts: String = row.get(9)?;
+    Ok(PersistedOrderIntent {
+        client_order_id: row.get(0)?,
+        correlation_id: row.get(1)?,
+        symbol: row.get(2)?,
+        leg: row.get(3)?,
+        side: row.get(4)?,
+        quantity: Decimal::from_str(&quantity).unwrap_or_default(),
+        status: row.get(6)?,
+        order_id: row.get(7)?,
+        executed_qty: executed_qty.and_then(|q| Decimal::from_str(&q).ok()),
+        created_at: DateTime::parse_from_rfc3339(&ts)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Read a funding event row (timestamp .. expected_amount).
+fn read_funding_event_row(row: &Row) -> rusqlite::Result<PersistedFundingEvent> {
+    let ts: String = row.get(0)?;
+    let amount: String = row.get(2)?;
+    let position_value: Option<String> = row.get(3)?;
+    let state: String = row.get(4)?;
+    let expected_amount: Option<String> = row.get(5)?;
+    Ok(PersistedFundingEvent {
+        timestamp: DateTime::parse_from_rfc3339(&ts)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        symbol: row.get(1)?,
+        amount: Decimal::from_str(&amount).unwrap_or_default(),
+        position_value: position_value.and_then(|v| Decimal::from_str(&v).ok()),
+        state: FundingState::parse(&state).unwrap_or(FundingState::Settled),
+        expected_amount: expected_amount.and_then(|v| Decimal::from_str(&v).ok()),
+    })
+}
+
+/// Read a position row (symbol .. total_funding_paid).
+fn read_position_row(row: &Row) -> rusqlite::Result<PersistedPosition> {
+    let decimal = |idx: usize| -> rusqlite::Result<Decimal> {
+        Ok(Decimal::from_str(&row.get::<_, String>(idx)?).unwrap_or_default())
+    };
+    Ok(PersistedPosition {
+        symbol: row.get(0)?,
+        futures_qty: decimal(1)?,
+        futures_entry_price: decimal(2)?,
+        spot_qty: decimal(3)?,
+        spot_entry_price: decimal(4)?,
+        borrowed_amount: decimal(5)?,
+        opened_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        total_funding_received: decimal(7)?,
+        total_interest_paid: decimal(8)?,
+        funding_collections: row.get(9)?,
+        expected_funding_rate: decimal(10)?,
+        total_funding_paid: decimal(11)?,
+    })
+}
+
+/// SHA-256 over the canonical string form of a state, positions ordered by symbol.
+///
+/// Uses the same representations that are written to the database, so a snapshot
+/// read back intact always reproduces its stored checksum. Funding paid is only
+/// hashed when non-zero, so snapshots written before it was tracked still verify.
+fn state_checksum(state: &PersistedState) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "{}|{}|{}|{}|{}|{}|{}|{:?}",
+        state.initial_balance,
+        state.balance,
+        state.total_funding_received,
+        state.total_trading_fees,
+        state.total_borrow_interest,
+        state.order_count,
+        state.last_saved.to_rfc3339(),
+        state.last_funding_period,
+    ));
+    if !state.total_funding_paid.is_zero() {
+        hasher.update(format!("|paid={}", state.total_funding_paid));
+    }
+
+    let mut positions: Vec<&PersistedPosition> = state.positions.values().collect();
+    positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    for pos in positions {
+        hasher.update(format!(
+            "\n{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            pos.symbol,
+            pos.futures_qty,
+            pos.futures_entry_price,
+            pos.spot_qty,
+            pos.spot_entry_price,
+            pos.borrowed_amount,
+            pos.opened_at.to_rfc3339(),
+            pos.total_funding_received,
+            pos.total_interest_paid,
+            pos.funding_collections,
+            pos.expected_funding_rate,
+        ));
+        if !pos.total_funding_paid.is_zero() {
+            hasher.update(format!("|paid={}", pos.total_funding_paid));
+        }
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
     use rust_decimal_macros::dec;
 
     #[test]
@@ -537,6 +2739,7 @@ mod tests {
                 borrowed_amount: Decimal::ZERO,
                 opened_at: Utc::now(),
                 total_funding_received: dec!(10),
+                total_funding_paid: dec!(2),
                 total_interest_paid: dec!(1),
                 funding_collections: 2,
                 expected_funding_rate: dec!(0.0001), // 0.01% expected funding rate
@@ -547,6 +2750,7 @@ mod tests {
             initial_balance: dec!(10000),
             balance: dec!(10009),
             total_funding_received: dec!(10),
+            total_funding_paid: dec!(2),
             total_trading_fees: dec!(1),
             total_borrow_interest: Decimal::ZERO,
             order_count: 4,
@@ -562,6 +2766,140 @@ mod tests {
         assert_eq!(loaded.positions.len(), 1);
         assert_eq!(loaded.positions["BTCUSDT"].futures_qty, dec!(-0.1));
         assert_eq!(loaded.last_funding_period, Some(42));
+        assert_eq!(loaded.total_funding_paid, dec!(2));
+        assert_eq!(loaded.positions["BTCUSDT"].total_funding_paid, dec!(2));
+    }
+
+    fn sample_state(balance: Decimal) -> PersistedState {
+        let mut positions = HashMap::new();
+        positions.insert(
+            "ETHUSDT".to_string(),
+            PersistedPosition {
+                symbol: "ETHUSDT".to_string(),
+                futures_qty: dec!(-2),
+                futures_entry_price: dec!(3000),
+                spot_qty: dec!(2),
+                spot_entry_price: dec!(3000),
+                borrowed_amount: Decimal::ZERO,
+                opened_at: Utc::now(),
+                total_funding_received: dec!(1.25),
+                total_funding_paid: Decimal::ZERO,
+                total_interest_paid: Decimal::ZERO,
+                funding_collections: 1,
+                expected_funding_rate: dec!(0.0003),
+            },
+        );
+
+        PersistedState {
+            initial_balance: dec!(10000),
+            balance,
+            total_funding_received: dec!(1.25),
+            total_funding_paid: Decimal::ZERO,
+            total_trading_fees: dec!(4.8),
+            total_borrow_interest: Decimal::ZERO,
+            order_count: 2,
+            positions,
+            last_saved: Utc::now(),
+            last_funding_period: Some(7),
+        }
+    }
+
+    #[test]
+    fn test_snapshots_keep_previous_generation() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        for balance in [dec!(10001), dec!(10002), dec!(10003)] {
+            manager.save_state(&sample_state(balance)).unwrap();
+        }
+
+        let generations: Vec<i64> = manager
+            .conn
+            .prepare("SELECT generation FROM state_snapshots ORDER BY generation")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(generations, vec![2, 3]);
+        assert_eq!(manager.load_state().unwrap().unwrap().balance, dec!(10003));
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_falls_back_to_previous() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        manager.save_state(&sample_state(dec!(10001))).unwrap();
+        manager.save_state(&sample_state(dec!(10002))).unwrap();
+
+        // A position row of the latest generation no longer matches its checksum
+        manager
+            .conn
+            .execute(
+                "UPDATE snapshot_positions SET futures_qty = '-20' WHERE generation = 2",
+                [],
+            )
+            .unwrap();
+        let loaded = manager.load_state().unwrap().unwrap();
+        assert_eq!(loaded.balance, dec!(10001));
+        assert_eq!(loaded.positions["ETHUSDT"].futures_qty, dec!(-2));
+
+        // Neither generation verifies: refuse to load rather than start from bad data
+        manager
+            .conn
+            .execute("UPDATE state_snapshots SET balance = '1'", [])
+            .unwrap();
+        assert!(manager.load_state().is_err());
+    }
+
+    #[test]
+    fn test_legacy_state_is_loaded_and_migrated() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        manager
+            .conn
+            .execute_batch(
+                r#"
+                INSERT INTO trading_state (id, initial_balance, balance, total_funding_received,
+                    total_trading_fees, total_borrow_interest, order_count, last_saved)
+                VALUES (1, '10000', '10050', '55', '5', '0', 6, '2024-03-01T00:00:00+00:00');
+                INSERT INTO positions (symbol, futures_qty, futures_entry_price, spot_qty,
+                    spot_entry_price, borrowed_amount, opened_at, total_funding_received,
+                    total_interest_paid, funding_collections)
+                VALUES ('BTCUSDT', '-0.1', '50000', '0.1', '50000', '0',
+                    '2024-02-29T00:00:00+00:00', '55', '0', 3);
+                "#,
+            )
+            .unwrap();
+        assert!(manager.has_state().unwrap());
+
+        let legacy = manager.load_state().unwrap().unwrap();
+        assert_eq!(legacy.balance, dec!(10050));
+        assert_eq!(legacy.positions["BTCUSDT"].funding_collections, 3);
+
+        // The next save moves it to a verified snapshot
+        manager.save_state(&legacy).unwrap();
+        let migrated = manager.load_state().unwrap().unwrap();
+        assert_eq!(migrated.balance, dec!(10050));
+        assert_eq!(migrated.positions.len(), 1);
+    }
+
+    #[test]
+    fn test_dirty_tracker_debounces_and_skips_clean_state() {
+        let now = Utc::now();
+        let mut tracker = DirtyTracker::new(Duration::seconds(5), (3, Some(10)));
+
+        // Unchanged state is never written, even when forced
+        assert!(!tracker.save_due((3, Some(10)), now, true));
+
+        // First change saves immediately
+        assert!(tracker.save_due((4, Some(10)), now, false));
+        tracker.mark_saved((4, Some(10)), now);
+
+        // Further changes within the interval wait, unless forced
+        let soon = now + Duration::seconds(2);
+        assert!(!tracker.save_due((5, Some(10)), soon, false));
+        assert!(tracker.save_due((5, Some(10)), soon, true));
+        assert!(tracker.save_due((5, Some(10)), now + Duration::seconds(5), false));
+
+        // A new funding period alone makes the state dirty
+        assert!(tracker.is_dirty((4, Some(11))));
     }
 
     #[test]
@@ -580,5 +2918,609 @@ mod tests {
 
         let stats = manager.get_funding_stats().unwrap();
         assert_eq!(stats.len(), 2);
+
+        let recent = manager.get_recent_funding_events(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].symbol, "ETHUSDT");
+        assert_eq!(recent[0].position_value, Some(dec!(3000)));
+        assert_eq!(recent[1].amount, dec!(4.5));
+    }
+
+    #[test]
+    fn test_funding_confirmation_states() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let settled_at = Utc::now() - Duration::minutes(10);
+
+        let btc = manager
+            .record_expected_funding("BTCUSDT", settled_at, dec!(5), Some(dec!(50000)))
+            .unwrap();
+        let eth = manager
+            .record_expected_funding("ETHUSDT", settled_at, dec!(2), None)
+            .unwrap();
+        manager
+            .record_expected_funding("SOLUSDT", settled_at, dec!(1), None)
+            .unwrap();
+
+        // Projections are not income
+        assert!(manager.get_funding_stats().unwrap().is_empty());
+        let pending = manager.get_expected_funding_events().unwrap();
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[0].0, btc);
+        assert_eq!(pending[0].1.state, FundingState::Expected);
+        assert_eq!(pending[0].1.expected_amount, Some(dec!(5)));
+
+        manager.settle_funding_event(btc, dec!(4.9)).unwrap();
+        manager.verify_funding_event(btc).unwrap();
+        manager.settle_funding_event(eth, dec!(0.5)).unwrap();
+        // Each transition happens once
+        assert!(manager.settle_funding_event(btc, dec!(4.9)).is_err());
+        assert!(manager.verify_funding_event(btc).is_err());
+
+        let confirmation = manager.get_funding_confirmation().unwrap();
+        assert_eq!(
+            confirmation,
+            FundingConfirmation {
+                pending: 1,
+                projected: dec!(1),
+                settled: dec!(0.5),
+                verified: dec!(4.9),
+            }
+        );
+        let recent = manager.get_recent_funding_events(3).unwrap();
+        assert_eq!(recent[0].state, FundingState::Expected);
+        assert_eq!(recent[1].state, FundingState::Settled);
+        assert_eq!(recent[2].state, FundingState::Verified);
+        assert_eq!(
+            manager.get_funding_events_since(settled_at).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_funding_and_interest_events_since() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let start = Utc::now() - Duration::seconds(1);
+
+        manager
+            .record_funding_event("BTCUSDT", dec!(5.5), Some(dec!(50000)))
+            .unwrap();
+        manager
+            .record_interest_event("BTCUSDT", dec!(0.4), Some(dec!(20000)))
+            .unwrap();
+        manager
+            .record_interest_event("ETHUSDT", dec!(0.1), None)
+            .unwrap();
+
+        let funding = manager.get_funding_events_since(start).unwrap();
+        assert_eq!(funding.len(), 1);
+        assert_eq!(funding[0].amount, dec!(5.5));
+
+        let interest = manager.get_interest_events_since(start).unwrap();
+        assert_eq!(interest.len(), 2);
+        assert_eq!(interest[0].symbol, "BTCUSDT");
+        assert_eq!(interest[0].borrowed_amount, Some(dec!(20000)));
+        assert_eq!(interest[1].borrowed_amount, None);
+
+        let later = Utc::now() + Duration::hours(1);
+        assert!(manager.get_interest_events_since(later).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_position_exits_since() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        let outcomes = [("ALTUSDT", 48, dec!(-3)), ("BTCUSDT", 2, dec!(12))];
+        for (symbol, hours_ago, net_carry) in outcomes {
+            manager
+                .record_position_exit(&PersistedPositionExit {
+                    closed_at: now - Duration::hours(hours_ago),
+                    symbol: symbol.to_string(),
+                    opened_at: now - Duration::hours(hours_ago + 24),
+                    exit_kind: "routine".to_string(),
+                    funding_received: net_carry + dec!(8),
+                    costs: dec!(8),
+                    net_carry,
+                })
+                .unwrap();
+        }
+
+        let exits = manager
+            .get_position_exits_since(now - Duration::days(7))
+            .unwrap();
+        assert_eq!(exits.len(), 2);
+        assert_eq!(exits[0].symbol, "ALTUSDT");
+        assert_eq!(exits[0].net_carry, dec!(-3));
+        assert_eq!(exits[1].funding_received, dec!(20));
+
+        let recent = manager
+            .get_position_exits_since(now - Duration::hours(12))
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].symbol, "BTCUSDT");
+    }
+
+    #[test]
+    fn test_entry_timings_since() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        for (symbol, outcome, hours_ago) in [("ALTUSDT", "aborted", 30), ("BTCUSDT", "executed", 1)]
+        {
+            manager
+                .record_entry_timing(&PersistedEntryTiming {
+                    timestamp: now - Duration::hours(hours_ago),
+                    symbol: symbol.to_string(),
+                    side: "SELL".to_string(),
+                    outcome: outcome.to_string(),
+                    waited_secs: 180,
+                    baseline_mid: dec!(100),
+                    decision_mid: dec!(100.05),
+                })
+                .unwrap();
+        }
+
+        let timings = manager
+            .get_entry_timings_since(now - Duration::days(2))
+            .unwrap();
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].outcome, "aborted");
+        assert_eq!(timings[1].decision_mid, dec!(100.05));
+        assert_eq!(timings[1].waited_secs, 180);
+
+        let recent = manager
+            .get_entry_timings_since(now - Duration::hours(2))
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].symbol, "BTCUSDT");
+    }
+
+    #[test]
+    fn test_latest_funding_apr() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        assert_eq!(manager.get_latest_funding_apr().unwrap(), None);
+
+        let mut apr = PersistedFundingApr {
+            timestamp: Utc::now(),
+            notional: dec!(4000),
+            gross_apr: dec!(0.19),
+            cost_apr: dec!(0.02),
+            net_apr: dec!(0.17),
+            position_count: 2,
+            risk_free_rate: dec!(0.045),
+        };
+        manager.record_funding_apr(&apr).unwrap();
+        apr.net_apr = dec!(0.15);
+        manager.record_funding_apr(&apr).unwrap();
+
+        let latest = manager.get_latest_funding_apr().unwrap().unwrap();
+        assert_eq!(latest.net_apr, dec!(0.15));
+        assert_eq!(latest.notional, dec!(4000));
+        assert_eq!(latest.position_count, 2);
+        assert_eq!(latest.risk_free_rate, dec!(0.045));
+    }
+
+    #[test]
+    fn test_snapshot_near() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        assert_eq!(manager.get_snapshot_near(Utc::now()).unwrap(), None);
+
+        let now = Utc::now();
+        for (hours_ago, balance) in [(30, dec!(10000)), (23, dec!(10040)), (1, dec!(10100))] {
+            manager
+                .record_snapshot(
+                    balance,
+                    dec!(5),
+                    balance + dec!(5),
+                    Decimal::ZERO,
+                    2,
+                    Decimal::ZERO,
+                    Decimal::ZERO,
+                    Decimal::ZERO,
+                    dec!(12),
+                )
+                .unwrap();
+            manager
+                .conn
+                .execute(
+                    "UPDATE equity_snapshots SET timestamp = ?1 WHERE id = last_insert_rowid()",
+                    [(now - chrono::Duration::hours(hours_ago)).to_rfc3339()],
+                )
+                .unwrap();
+        }
+        // Snapshots from before fees were recorded
+        manager
+            .conn
+            .execute(
+                "UPDATE equity_snapshots SET total_trading_fees = NULL WHERE balance = '10000'",
+                [],
+            )
+            .unwrap();
+
+        let day_ago = manager
+            .get_snapshot_near(now - chrono::Duration::hours(24))
+            .unwrap()
+            .unwrap();
+        assert_eq!(day_ago.balance, dec!(10040));
+        assert_eq!(day_ago.total_equity, dec!(10045));
+        assert_eq!(day_ago.position_count, 2);
+        assert_eq!(day_ago.total_trading_fees, Some(dec!(12)));
+
+        let oldest = manager
+            .get_snapshot_near(now - chrono::Duration::days(7))
+            .unwrap()
+            .unwrap();
+        assert_eq!(oldest.balance, dec!(10000));
+        assert_eq!(oldest.total_trading_fees, None);
+    }
+
+    #[test]
+    fn test_equity_annotations_since() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        for (kind, label, hours_ago) in [
+            (EquityEventKind::Entry, "BTCUSDT entered", 30),
+            (EquityEventKind::Halt, "Order valve tripped", 1),
+            (EquityEventKind::ConfigChange, "config abc123, v0.1.0", 2),
+        ] {
+            manager
+                .record_equity_annotation(&PersistedEquityAnnotation {
+                    timestamp: now - Duration::hours(hours_ago),
+                    kind,
+                    label: label.to_string(),
+                })
+                .unwrap();
+        }
+
+        // Oldest first, within the window only
+        let annotations = manager
+            .get_equity_annotations_since(now - Duration::hours(12))
+            .unwrap();
+        let kinds: Vec<_> = annotations.iter().map(|a| a.kind).collect();
+        assert_eq!(
+            kinds,
+            [EquityEventKind::ConfigChange, EquityEventKind::Halt]
+        );
+        assert_eq!(annotations[1].label, "Order valve tripped");
+    }
+
+    #[test]
+    fn test_funding_quarantine_roundtrip() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        assert!(manager.get_quarantined().unwrap().is_empty());
+
+        let quarantine = PersistedQuarantine {
+            symbol: "BTCUSDT".to_string(),
+            quarantined_at: DateTime::parse_from_rfc3339("2024-03-20T08:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            anomalies: 3,
+            last_deviation: dec!(0.35),
+        };
+        manager.save_quarantine(&quarantine).unwrap();
+        assert_eq!(manager.get_quarantined().unwrap(), vec![quarantine]);
+
+        assert!(manager.release_quarantine("BTCUSDT").unwrap());
+        assert!(!manager.release_quarantine("BTCUSDT").unwrap());
+        assert!(manager.get_quarantined().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malfunction_state_roundtrip() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        assert_eq!(manager.load_malfunction_state().unwrap(), None);
+
+        let now = DateTime::parse_from_rfc3339("2024-03-20T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut state = PersistedMalfunctionState {
+            saved_at: now,
+            halted_at: Some(now),
+            consecutive_risk_cycles: 2,
+            errors: vec![PersistedMalfunctionError {
+                timestamp: now,
+                error: "timeout".to_string(),
+            }],
+            failures: vec![PersistedOrderFailures {
+                symbol: "BTCUSDT".to_string(),
+                consecutive_failures: 3,
+                last_failure: now,
+            }],
+            alerts: vec![PersistedMalfunctionAlert {
+                alert_id: "malfunction-1".to_string(),
+                timestamp: now,
+                severity: "CRITICAL".to_string(),
+                malfunction_type: r#"{"type":"RateLimitHit","endpoint":"/fapi"}"#.to_string(),
+                message: "Rate limit hit".to_string(),
+                should_halt: true,
+                suggested_action: "Back off".to_string(),
+            }],
+        };
+        manager.save_malfunction_state(&state).unwrap();
+        assert_eq!(
+            manager.load_malfunction_state().unwrap(),
+            Some(state.clone())
+        );
+
+        // Saving replaces the previous windows
+        state.halted_at = None;
+        state.errors.clear();
+        manager.save_malfunction_state(&state).unwrap();
+        let loaded = manager.load_malfunction_state().unwrap().unwrap();
+        assert_eq!(loaded.halted_at, None);
+        assert!(loaded.errors.is_empty());
+        assert_eq!(loaded.failures.len(), 1);
+    }
+
+    #[test]
+    fn test_transfers_newest_first() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+
+        manager
+            .record_transfer("to_sub_account", "USDT", dec!(5000), "USDT_FUTURE", 1)
+            .unwrap();
+        manager
+            .record_transfer("to_master", "USDT", dec!(5012.5), "USDT_FUTURE", 2)
+            .unwrap();
+
+        let transfers = manager.get_recent_transfers(10).unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].direction, "to_master");
+        assert_eq!(transfers[0].amount, dec!(5012.5));
+        assert_eq!(transfers[1].tran_id, 1);
+    }
+
+    #[test]
+    fn test_execution_fills_since() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        let fill = |hours_ago: i64, leg: &str| PersistedExecutionFill {
+            timestamp: now - Duration::hours(hours_ago),
+            execution_id: format!("BTCUSDT-{}", hours_ago),
+            symbol: "BTCUSDT".to_string(),
+            kind: "entry".to_string(),
+            leg: leg.to_string(),
+            side: "SELL".to_string(),
+            quantity: dec!(0.1),
+            decision_price: dec!(50000),
+            fill_price: dec!(49990),
+        };
+
+        manager.record_execution_fill(&fill(48, "futures")).unwrap();
+        manager.record_execution_fill(&fill(1, "futures")).unwrap();
+        manager.record_execution_fill(&fill(1, "spot")).unwrap();
+
+        let fills = manager
+            .get_execution_fills_since(now - Duration::hours(24))
+            .unwrap();
+        assert_eq!(fills, vec![fill(1, "futures"), fill(1, "spot")]);
+    }
+
+    #[test]
+    fn test_runs_saved_and_updated() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        let run = |run_id: &str, hours_ago: i64| PersistedRun {
+            run_id: run_id.to_string(),
+            mode: "mock".to_string(),
+            started_at: now - Duration::hours(hours_ago),
+            ended_at: None,
+            config_hash: "abc123".to_string(),
+            version: "0.1.0".to_string(),
+            scans: 0,
+            entries: 0,
+            exits: 0,
+            errors: 0,
+            funding: Decimal::ZERO,
+            fees: Decimal::ZERO,
+            final_equity: None,
+        };
+
+        manager.save_run(&run("a1b2c3", 48)).unwrap();
+        let mut current = run("d4e5f6", 2);
+        manager.save_run(&current).unwrap();
+
+        current.ended_at = Some(now);
+        current.scans = 120;
+        current.entries = 3;
+        current.exits = 1;
+        current.errors = 2;
+        current.funding = dec!(12.5);
+        current.fees = dec!(4.2);
+        current.final_equity = Some(dec!(10008.3));
+        manager.save_run(&current).unwrap();
+
+        let runs = manager.get_runs(10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].run_id, "d4e5f6");
+        assert_eq!(runs[0].scans, 120);
+        assert_eq!(runs[0].funding, dec!(12.5));
+        assert_eq!(runs[0].final_equity, Some(dec!(10008.3)));
+        assert!(runs[0].ended_at.is_some());
+        assert_eq!(runs[1].ended_at, None);
+        assert_eq!(manager.get_runs(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_records_stamped_with_config_and_version() {
+        let mut manager = PersistenceManager::new(":memory:").unwrap();
+        manager
+            .record_funding_event("BTCUSDT", dec!(1.5), None)
+            .unwrap();
+        manager.set_stamp(RecordStamp {
+            config_hash: "0123456789ab".to_string(),
+            version: "0.3.0".to_string(),
+        });
+        manager
+            .record_funding_event("ETHUSDT", dec!(2.5), None)
+            .unwrap();
+        manager
+            .journal_order_intent("ffo-1", "corr-1", "ETHUSDT", "futures", "SELL", dec!(1))
+            .unwrap();
+
+        let stamps: Vec<(String, String, String)> = manager
+            .conn
+            .prepare("SELECT symbol, config_hash, version FROM funding_events ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        // Rows written before the stamp was set stay unattributed
+        assert_eq!(stamps[0], ("BTCUSDT".into(), String::new(), String::new()));
+        assert_eq!(
+            stamps[1],
+            ("ETHUSDT".into(), "0123456789ab".into(), "0.3.0".into())
+        );
+        let journal_hash: String = manager
+            .conn
+            .query_row("SELECT config_hash FROM order_journal", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(journal_hash, "0123456789ab");
+    }
+
+    #[test]
+    fn test_config_history_records_changes_only() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let now = Utc::now();
+        let stamp = RecordStamp {
+            config_hash: "aaaaaaaaaaaa".to_string(),
+            version: "0.3.0".to_string(),
+        };
+        assert!(manager
+            .record_config_change(&stamp, "[risk]\n", now)
+            .unwrap());
+        assert!(!manager
+            .record_config_change(&stamp, "[risk]\n", now)
+            .unwrap());
+
+        // Same config under a new build is a new era
+        let upgraded = RecordStamp {
+            version: "0.3.1".to_string(),
+            ..stamp.clone()
+        };
+        assert!(manager
+            .record_config_change(&upgraded, "[risk]\n", now)
+            .unwrap());
+
+        let history = manager.get_config_history(10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, "0.3.1");
+        assert_eq!(history[1].config_hash, "aaaaaaaaaaaa");
+        assert_eq!(history[1].config, "[risk]\n");
+    }
+
+    #[test]
+    fn test_instance_lock_held_stale_and_takeover() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+        let stale_after = Duration::minutes(5);
+        let now = Utc::now();
+
+        let first = InstanceLock::for_current_process(now - Duration::minutes(1));
+        assert_eq!(first.instance_id.len(), InstanceLock::ID_LEN);
+        assert_eq!(
+            manager
+                .acquire_instance_lock(&first, stale_after, false)
+                .unwrap(),
+            LockAcquisition::Acquired(None)
+        );
+
+        // A second instance is refused while the first heartbeats
+        let second = InstanceLock::for_current_process(now);
+        assert_ne!(first.instance_id, second.instance_id);
+        assert!(matches!(
+            manager.acquire_instance_lock(&second, stale_after, false).unwrap(),
+            LockAcquisition::Held(holder) if holder.instance_id == first.instance_id
+        ));
+        assert!(manager
+            .heartbeat_instance_lock(&first.instance_id, now)
+            .unwrap());
+
+        // Takeover transfers the lock; the first instance sees it lost control
+        assert!(matches!(
+            manager.acquire_instance_lock(&second, stale_after, true).unwrap(),
+            LockAcquisition::TookOver(holder) if holder.instance_id == first.instance_id
+        ));
+        assert!(!manager
+            .heartbeat_instance_lock(&first.instance_id, now)
+            .unwrap());
+
+        // Releasing a lost lock leaves the new holder in place
+        manager.release_instance_lock(&first.instance_id).unwrap();
+        let third = InstanceLock::for_current_process(now + Duration::seconds(1));
+        assert!(matches!(
+            manager
+                .acquire_instance_lock(&third, stale_after, false)
+                .unwrap(),
+            LockAcquisition::Held(_)
+        ));
+
+        // A holder that stopped heartbeating is replaced without takeover
+        let later = InstanceLock::for_current_process(now + Duration::minutes(10));
+        assert!(matches!(
+            manager.acquire_instance_lock(&later, stale_after, false).unwrap(),
+            LockAcquisition::Acquired(Some(holder)) if holder.instance_id == second.instance_id
+        ));
+
+        manager.release_instance_lock(&later.instance_id).unwrap();
+        assert_eq!(
+            manager
+                .acquire_instance_lock(&third, stale_after, false)
+                .unwrap(),
+            LockAcquisition::Acquired(None)
+        );
+
+        // Every instance that held the lock is remembered, oldest first
+        let known = manager.known_instance_ids().unwrap();
+        assert_eq!(known.len(), 4);
+        assert_eq!(known[0], first.instance_id);
+    }
+
+    #[test]
+    fn test_order_journal_lifecycle() {
+        let manager = PersistenceManager::new(":memory:").unwrap();
+
+        manager
+            .journal_order_intent(
+                "fff1-1",
+                "fff1",
+                "BTCUSDT",
+                "entry_futures",
+                "SELL",
+                dec!(0.1),
+            )
+            .unwrap();
+        manager
+            .journal_order_intent("fff1-2", "fff1", "BTCUSDT", "entry_spot", "BUY", dec!(0.1))
+            .unwrap();
+        manager
+            .journal_order_intent("fff2-3", "fff2", "ETHUSDT", "exit_futures", "BUY", dec!(2))
+            .unwrap();
+
+        // Futures leg filled, spot leg interrupted, exit rejected
+        manager
+            .complete_order_intent("fff1-1", 42, dec!(0.1))
+            .unwrap();
+        manager.fail_order_intent("fff2-3").unwrap();
+
+        let pending = manager.get_incomplete_order_intents().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].client_order_id, "fff1-2");
+        assert_eq!(pending[0].correlation_id, "fff1");
+        assert_eq!(pending[0].leg, "entry_spot");
+        assert_eq!(pending[0].quantity, dec!(0.1));
+        assert_eq!(pending[0].order_id, None);
+
+        // Client order IDs are unique
+        assert!(manager
+            .journal_order_intent(
+                "fff1-1",
+                "fff1",
+                "BTCUSDT",
+                "entry_futures",
+                "SELL",
+                dec!(0.1)
+            )
+            .is_err());
     }
 }
@@ -0,0 +1,667 @@
+//! Read-only HTML status report served straight from the persistence database.
+//!
+//! `serve-report` opens the SQLite file read-only for every request, so it can
+//! inspect a stopped bot or a copied database and never writes to a running one.
+//! Pages:
+//! - `/` portfolio funding APR, overview and open positions
+//! - `/equity` equity curve from the hourly snapshots, marked with entries,
+//!   exits, halts, config changes and transfers
+//! - `/funding` funding payment history, confirmed income apart from projected
+
+use crate::persistence::{
+    EquityEventKind, FundingConfirmation, FundingState, PersistedEquityAnnotation,
+    PersistedFundingApr, PersistedFundingEvent, PersistedState, PersistenceManager,
+};
+use crate::utils::DisplayTimezone;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// Equity snapshots shown on the curve (30 days of hourly snapshots).
+const EQUITY_POINTS: usize = 720;
+/// Funding payments listed on the history page.
+const FUNDING_EVENTS: usize = 200;
+
+/// Everything a report page needs, loaded from one database read.
+#[derive(Debug, Clone, Default)]
+pub struct ReportData {
+    pub state: Option<PersistedState>,
+    /// Equity snapshots, oldest first
+    pub equity: Vec<(DateTime<Utc>, Decimal)>,
+    /// Events within the equity curve's span, oldest first
+    pub annotations: Vec<PersistedEquityAnnotation>,
+    /// Funding payments, newest first
+    pub funding_events: Vec<PersistedFundingEvent>,
+    /// Total confirmed funding per symbol
+    pub funding_by_symbol: HashMap<String, Decimal>,
+    /// Funding income by confirmation state
+    pub funding_confirmation: FundingConfirmation,
+    /// Latest portfolio funding APR
+    pub funding_apr: Option<PersistedFundingApr>,
+}
+
+impl ReportData {
+    /// Load report data from a persistence database.
+    pub fn load(persistence: &PersistenceManager) -> Result<Self> {
+        let mut equity = persistence.get_recent_snapshots(EQUITY_POINTS)?;
+        equity.reverse();
+        let annotations = match equity.first() {
+            Some((since, _)) => persistence.get_equity_annotations_since(*since)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            state: persistence.load_state()?,
+            equity,
+            annotations,
+            funding_events: persistence.get_recent_funding_events(FUNDING_EVENTS)?,
+            funding_by_symbol: persistence.get_funding_stats()?,
+            funding_confirmation: persistence.get_funding_confirmation()?,
+            funding_apr: persistence.get_latest_funding_apr()?,
+        })
+    }
+}
+
+/// A report page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Overview,
+    Equity,
+    Funding,
+}
+
+impl Page {
+    /// Page for a request path (query strings ignored).
+    pub fn from_path(path: &str) -> Option<Self> {
+        match path.split('?').next().unwrap_or_default() {
+            "/" | "/index.html" => Some(Page::Overview),
+            "/equity" => Some(Page::Equity),
+            "/funding" => Some(Page::Funding),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Page::Overview => "Overview",
+            Page::Equity => "Equity Curve",
+            Page::Funding => "Funding History",
+        }
+    }
+}
+
+/// Render a page as a complete HTML document.
+pub fn render_page(page: Page, data: &ReportData, tz: &DisplayTimezone) -> String {
+    let body = match page {
+        Page::Overview => overview_html(data, tz),
+        Page::Equity => equity_html(data, tz),
+        Page::Funding => funding_html(data, tz),
+    };
+    layout(page.title(), &body)
+}
+
+fn layout(title: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Funding Fee Farmer - {title}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; color: #222; }}
+nav a {{ margin-right: 1rem; }}
+table {{ border-collapse: collapse; margin: 1rem 0; }}
+th, td {{ border: 1px solid #ccc; padding: 0.3rem 0.6rem; text-align: right; }}
+th:first-child, td:first-child {{ text-align: left; }}
+.neg {{ color: #b00; }}
+.muted {{ color: #777; }}
+</style>
+</head>
+<body>
+<nav><a href="/">Overview</a><a href="/equity">Equity</a><a href="/funding">Funding</a></nav>
+<h1>{title}</h1>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+fn overview_html(data: &ReportData, tz: &DisplayTimezone) -> String {
+    let mut html = String::new();
+    if let Some(apr) = &data.funding_apr {
+        let _ = writeln!(
+            html,
+            r#"<h2>Portfolio funding APR <span{}>{:.2}%</span></h2>
+<p class="muted">{:.2}% gross, {:.2}% costs on ${:.2} across {} position(s) as of {}</p>
+<p>Versus T-bills at {:.2}%: <span{}>{:+.2}%</span></p>"#,
+            sign_class(apr.net_apr),
+            apr.net_apr * Decimal::ONE_HUNDRED,
+            apr.gross_apr * Decimal::ONE_HUNDRED,
+            apr.cost_apr * Decimal::ONE_HUNDRED,
+            apr.notional,
+            apr.position_count,
+            escape_html(&tz.datetime(apr.timestamp)),
+            apr.risk_free_rate * Decimal::ONE_HUNDRED,
+            sign_class(apr.net_apr - apr.risk_free_rate),
+            (apr.net_apr - apr.risk_free_rate) * Decimal::ONE_HUNDRED,
+        );
+    }
+
+    let Some(state) = &data.state else {
+        html.push_str("<p>No saved state found in database.</p>");
+        return html;
+    };
+
+    let pnl = state.balance - state.initial_balance;
+    let net_yield =
+        state.total_funding_received - state.total_trading_fees - state.total_borrow_interest;
+
+    let _ = write!(
+        html,
+        r#"<p class="muted">Last saved {}</p>
+<table>
+<tr><td>Initial balance</td><td>${:.2}</td></tr>
+<tr><td>Current balance</td><td>${:.2}</td></tr>
+<tr><td>PnL</td><td{}>${:.2}</td></tr>
+<tr><td>Net funding</td><td>${:.4}</td></tr>
+<tr><td>Funding paid</td><td>${:.4}</td></tr>
+<tr><td>Trading fees</td><td>${:.4}</td></tr>
+<tr><td>Borrow interest</td><td>${:.4}</td></tr>
+<tr><td>Net yield</td><td{}>${:.4}</td></tr>
+<tr><td>Total orders</td><td>{}</td></tr>
+</table>
+"#,
+        escape_html(&tz.datetime(state.last_saved)),
+        state.initial_balance,
+        state.balance,
+        sign_class(pnl),
+        pnl,
+        state.total_funding_received,
+        state.total_funding_paid,
+        state.total_trading_fees,
+        state.total_borrow_interest,
+        sign_class(net_yield),
+        net_yield,
+        state.order_count,
+    );
+
+    let _ = writeln!(html, "<h2>Open Positions ({})</h2>", state.positions.len());
+    if state.positions.is_empty() {
+        html.push_str("<p>No open positions.</p>\n");
+        return html;
+    }
+
+    html.push_str(
+        "<table>\n<tr><th>Symbol</th><th>Futures</th><th>Entry</th><th>Spot</th>\
+         <th>Borrowed</th><th>Funding</th><th>Paid</th><th>Collections</th>\
+         <th>Interest</th><th>Opened</th></tr>\n",
+    );
+    let mut positions: Vec<_> = state.positions.values().collect();
+    positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    for pos in positions {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>${:.4}</td><td>{}</td><td>{}</td>\
+             <td>${:.4}</td><td>${:.4}</td><td>{}</td><td>${:.4}</td><td>{}</td></tr>",
+            escape_html(&pos.symbol),
+            pos.futures_qty,
+            pos.futures_entry_price,
+            pos.spot_qty,
+            pos.borrowed_amount,
+            pos.total_funding_received,
+            pos.total_funding_paid,
+            pos.funding_collections,
+            pos.total_interest_paid,
+            escape_html(&tz.short(pos.opened_at)),
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn equity_html(data: &ReportData, tz: &DisplayTimezone) -> String {
+    let (Some((first_at, first)), Some((last_at, last))) =
+        (data.equity.first(), data.equity.last())
+    else {
+        return "<p>No equity snapshots recorded yet.</p>".to_string();
+    };
+
+    let mut html = format!(
+        "<p>{} snapshots from {} to {}: ${:.2} &rarr; ${:.2}</p>\n{}",
+        data.equity.len(),
+        escape_html(&tz.short(*first_at)),
+        escape_html(&tz.short(*last_at)),
+        first,
+        last,
+        equity_svg(&data.equity, &data.annotations)
+    );
+    if data.annotations.is_empty() {
+        return html;
+    }
+
+    let _ = writeln!(html, "<h2>Events ({})</h2>", data.annotations.len());
+    html.push_str("<table>\n<tr><th>Time</th><th>Event</th><th>Detail</th></tr>\n");
+    for annotation in data.annotations.iter().rev() {
+        let _ = writeln!(
+            html,
+            r#"<tr><td>{}</td><td style="color:{}">{}</td><td>{}</td></tr>"#,
+            escape_html(&tz.short(annotation.timestamp)),
+            event_color(annotation.kind),
+            annotation.kind.as_str(),
+            escape_html(&annotation.label),
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// Marker color of an equity curve event.
+fn event_color(kind: EquityEventKind) -> &'static str {
+    match kind {
+        EquityEventKind::Entry => "#26a",
+        EquityEventKind::Exit => "#a62",
+        EquityEventKind::Halt => "#b00",
+        EquityEventKind::ConfigChange => "#829",
+        EquityEventKind::Deposit | EquityEventKind::Withdrawal => "#777",
+    }
+}
+
+/// Inline SVG line chart of equity over time, with a vertical marker at each
+/// annotation placed at the first snapshot taken at or after it.
+pub fn equity_svg(
+    points: &[(DateTime<Utc>, Decimal)],
+    annotations: &[PersistedEquityAnnotation],
+) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 240.0;
+
+    let values: Vec<f64> = points
+        .iter()
+        .map(|(_, equity)| equity.to_string().parse().unwrap_or_default())
+        .collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let step = if values.len() > 1 {
+        WIDTH / (values.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let polyline: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let x = i as f64 * step;
+            let y = HEIGHT - (v - min) / range * HEIGHT;
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+
+    let mut markers = String::new();
+    for annotation in annotations {
+        let index = points
+            .partition_point(|(at, _)| *at < annotation.timestamp)
+            .min(points.len().saturating_sub(1));
+        let x = index as f64 * step;
+        let _ = writeln!(
+            markers,
+            r#"<line x1="{x:.1}" y1="0" x2="{x:.1}" y2="{HEIGHT}" stroke="{}" stroke-dasharray="4 3"><title>{}: {}</title></line>"#,
+            event_color(annotation.kind),
+            annotation.kind.as_str(),
+            escape_html(&annotation.label),
+        );
+    }
+
+    format!(
+        r##"<svg width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" style="border:1px solid #ccc">
+{markers}<polyline fill="none" stroke="#2a6" stroke-width="2" points="{}"/>
+</svg>
+"##,
+        polyline.join(" ")
+    )
+}
+
+fn funding_html(data: &ReportData, tz: &DisplayTimezone) -> String {
+    let mut html = String::new();
+
+    let confirmation = &data.funding_confirmation;
+    let _ = writeln!(
+        html,
+        "<p>Confirmed income: <span{}>${:.4}</span> (${:.4} verified against expectations). \
+         Projected, awaiting the exchange: ${:.4} over {} settlement(s).</p>",
+        sign_class(confirmation.settled + confirmation.verified),
+        confirmation.settled + confirmation.verified,
+        confirmation.verified,
+        confirmation.projected,
+        confirmation.pending
+    );
+
+    if !data.funding_by_symbol.is_empty() {
+        html.push_str("<h2>By Symbol</h2>\n<table>\n<tr><th>Symbol</th><th>Total</th></tr>\n");
+        let mut totals: Vec<_> = data.funding_by_symbol.iter().collect();
+        totals.sort_by(|a, b| b.1.cmp(a.1));
+        for (symbol, total) in totals {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td{}>${:.4}</td></tr>",
+                escape_html(symbol),
+                sign_class(*total),
+                total
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<h2>Recent Payments ({})</h2>",
+        data.funding_events.len()
+    );
+    if data.funding_events.is_empty() {
+        html.push_str("<p>No funding payments recorded yet.</p>\n");
+        return html;
+    }
+
+    html.push_str(
+        "<table>\n<tr><th>Time</th><th>Symbol</th><th>Amount</th><th>Expected</th><th>State</th><th>Position</th></tr>\n",
+    );
+    for event in &data.funding_events {
+        let position = event
+            .position_value
+            .map(|v| format!("${:.2}", v))
+            .unwrap_or_default();
+        let expected = event
+            .expected_amount
+            .map(|v| format!("${:.4}", v))
+            .unwrap_or_default();
+        // A projection is not income yet
+        let amount = if event.state == FundingState::Expected {
+            "<td>-</td>".to_string()
+        } else {
+            format!("<td{}>${:.4}</td>", sign_class(event.amount), event.amount)
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td>{}<td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&tz.short(event.timestamp)),
+            escape_html(&event.symbol),
+            amount,
+            expected,
+            event.state.as_str(),
+            position
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn sign_class(value: Decimal) -> &'static str {
+    if value < Decimal::ZERO {
+        r#" class="neg""#
+    } else {
+        ""
+    }
+}
+
+/// Escape text for inclusion in HTML.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Serve the report over HTTP until the process is stopped.
+pub async fn serve(db_path: PathBuf, addr: SocketAddr, tz: DisplayTimezone) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind report server to {}", addr))?;
+    info!(%addr, db = ?db_path, "Serving read-only status report");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        if let Err(e) = handle_connection(stream, &db_path, &tz).await {
+            warn!(%peer, error = %e, "Report request failed");
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    db_path: &Path,
+    tz: &DisplayTimezone,
+) -> Result<()> {
+    let mut reader = BufReader::new(&mut stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    // Drain headers; requests have no body
+    let mut header = String::new();
+    while reader.read_line(&mut header).await? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = respond(&request_line, db_path, tz);
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Status line and body for a request line such as `GET /equity HTTP/1.1`.
+fn respond(request_line: &str, db_path: &Path, tz: &DisplayTimezone) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return ("400 Bad Request", layout("Bad Request", ""));
+    };
+    if method != "GET" {
+        return ("405 Method Not Allowed", layout("Method Not Allowed", ""));
+    }
+    let Some(page) = Page::from_path(path) else {
+        return ("404 Not Found", layout("Not Found", ""));
+    };
+
+    let data = PersistenceManager::open_read_only(db_path).and_then(|p| ReportData::load(&p));
+    match data {
+        Ok(data) => ("200 OK", render_page(page, &data, tz)),
+        Err(e) => (
+            "500 Internal Server Error",
+            layout(
+                "Error",
+                &format!("<pre>{}</pre>", escape_html(&format!("{:#}", e))),
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::PersistedPosition;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn sample_data() -> ReportData {
+        let now = Utc::now();
+        let mut positions = HashMap::new();
+        positions.insert(
+            "BTCUSDT".to_string(),
+            PersistedPosition {
+                symbol: "BTCUSDT".to_string(),
+                futures_qty: dec!(-0.1),
+                futures_entry_price: dec!(50000),
+                spot_qty: dec!(0.1),
+                spot_entry_price: dec!(50000),
+                borrowed_amount: Decimal::ZERO,
+                opened_at: now,
+                total_funding_received: dec!(5),
+                total_funding_paid: dec!(0.75),
+                total_interest_paid: Decimal::ZERO,
+                funding_collections: 1,
+                expected_funding_rate: dec!(0.001),
+            },
+        );
+
+        ReportData {
+            state: Some(PersistedState {
+                initial_balance: dec!(10000),
+                balance: dec!(9990),
+                total_funding_received: dec!(5),
+                total_funding_paid: dec!(0.75),
+                total_trading_fees: dec!(15),
+                total_borrow_interest: Decimal::ZERO,
+                order_count: 2,
+                positions,
+                last_saved: now,
+                last_funding_period: None,
+            }),
+            equity: vec![
+                (now - Duration::hours(2), dec!(10000)),
+                (now - Duration::hours(1), dec!(9980)),
+                (now, dec!(9990)),
+            ],
+            annotations: vec![PersistedEquityAnnotation {
+                timestamp: now - Duration::minutes(90),
+                kind: EquityEventKind::Entry,
+                label: "<b>BTCUSDT</b> entered".to_string(),
+            }],
+            funding_events: vec![PersistedFundingEvent {
+                timestamp: now,
+                symbol: "<script>".to_string(),
+                amount: dec!(-1.5),
+                position_value: None,
+                state: FundingState::Settled,
+                expected_amount: None,
+            }],
+            funding_by_symbol: HashMap::new(),
+            funding_confirmation: FundingConfirmation {
+                pending: 1,
+                projected: dec!(2),
+                settled: dec!(-1.5),
+                verified: dec!(4),
+            },
+            funding_apr: Some(PersistedFundingApr {
+                timestamp: now,
+                notional: dec!(5000),
+                gross_apr: dec!(0.2),
+                cost_apr: dec!(0.0125),
+                net_apr: dec!(0.1875),
+                position_count: 1,
+                risk_free_rate: dec!(0.0425),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_page_routing() {
+        assert_eq!(Page::from_path("/"), Some(Page::Overview));
+        assert_eq!(Page::from_path("/equity?range=all"), Some(Page::Equity));
+        assert_eq!(Page::from_path("/funding"), Some(Page::Funding));
+        assert_eq!(Page::from_path("/admin"), None);
+    }
+
+    #[test]
+    fn test_pages_render_from_data() {
+        let data = sample_data();
+        let tz = DisplayTimezone::default();
+
+        let overview = render_page(Page::Overview, &data, &tz);
+        assert!(overview.contains("<td>BTCUSDT</td>"));
+        assert!(overview.contains("Portfolio funding APR <span>18.75%</span>"));
+        assert!(overview.contains("Versus T-bills at 4.25%: <span>+14.50%</span>"));
+        assert!(overview.contains(r#"<td class="neg">$-10.00</td>"#));
+        assert!(overview.contains("<tr><td>Funding paid</td><td>$0.7500</td></tr>"));
+
+        // Symbols are escaped
+        let funding = render_page(Page::Funding, &data, &tz);
+        assert!(funding.contains("&lt;script&gt;"));
+        assert!(!funding.contains("<script>"));
+        assert!(funding.contains("<td>settled</td>"));
+        assert!(funding.contains("Confirmed income: <span>$2.5000</span>"));
+        assert!(funding.contains("Projected, awaiting the exchange: $2.0000 over 1 settlement(s)"));
+
+        let equity = render_page(Page::Equity, &data, &tz);
+        assert!(equity.contains("<h2>Events (1)</h2>"));
+        assert!(equity.contains("&lt;b&gt;BTCUSDT&lt;/b&gt; entered"));
+
+        let empty = render_page(Page::Equity, &ReportData::default(), &tz);
+        assert!(empty.contains("No equity snapshots"));
+    }
+
+    #[test]
+    fn test_equity_svg_scales_to_box() {
+        let now = Utc::now();
+        let svg = equity_svg(&[(now, dec!(100)), (now, dec!(150)), (now, dec!(200))], &[]);
+        // Lowest value at the bottom, highest at the top
+        assert!(svg.contains("0.0,240.0 400.0,120.0 800.0,0.0"));
+
+        // A single point does not divide by zero
+        assert!(equity_svg(&[(now, dec!(100))], &[]).contains("0.0,240.0"));
+    }
+
+    #[test]
+    fn test_equity_svg_marks_events_at_next_snapshot() {
+        let now = Utc::now();
+        let points = [
+            (now - Duration::hours(2), dec!(100)),
+            (now - Duration::hours(1), dec!(90)),
+            (now, dec!(95)),
+        ];
+        let annotation = |minutes_ago: i64, kind: EquityEventKind| PersistedEquityAnnotation {
+            timestamp: now - Duration::minutes(minutes_ago),
+            kind,
+            label: "event".to_string(),
+        };
+        let svg = equity_svg(
+            &points,
+            &[
+                annotation(90, EquityEventKind::Halt),
+                // After the last snapshot: pinned to the right edge
+                annotation(-10, EquityEventKind::Deposit),
+            ],
+        );
+
+        assert!(svg.contains(r##"x1="400.0" y1="0" x2="400.0" y2="240" stroke="#b00""##));
+        assert!(svg.contains("<title>halt: event</title>"));
+        assert!(svg.contains(r#"x1="800.0""#));
+    }
+
+    #[test]
+    fn test_respond_rejects_unknown_requests() {
+        let tz = DisplayTimezone::default();
+        let db = Path::new("/nonexistent/state.db");
+
+        assert_eq!(
+            respond("POST / HTTP/1.1", db, &tz).0,
+            "405 Method Not Allowed"
+        );
+        assert_eq!(respond("GET /nope HTTP/1.1", db, &tz).0, "404 Not Found");
+        assert_eq!(respond("", db, &tz).0, "400 Bad Request");
+        assert_eq!(
+            respond("GET / HTTP/1.1", db, &tz).0,
+            "500 Internal Server Error"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Alert routing: the reaction to each alert class and severity.
+//!
+//! Built-in reactions keep the long-standing behavior: a breached drawdown
+//! limit, critical margin health and a tripped circuit breaker halt trading,
+//! margin and liquidation warnings above `Warning` and exit liquidity breaches
+//! reduce positions, and everything else is notified. `[alerts.routes]`
+//! overrides single cells, making automation more or less aggressive per class.
+
+use crate::config::{AlertAction, AlertClass, SeverityRoutes};
+use std::collections::BTreeMap;
+
+use super::AlertSeverity;
+
+/// Maps alert class and severity to an action.
+#[derive(Debug, Clone, Default)]
+pub struct AlertRouter {
+    routes: BTreeMap<AlertClass, SeverityRoutes>,
+}
+
+impl AlertRouter {
+    pub fn new(routes: BTreeMap<AlertClass, SeverityRoutes>) -> Self {
+        Self { routes }
+    }
+
+    /// Action for an alert of `class` at `severity`.
+    pub fn action(&self, class: AlertClass, severity: AlertSeverity) -> AlertAction {
+        self.routes
+            .get(&class)
+            .and_then(|routes| match severity {
+                AlertSeverity::Info => routes.info,
+                AlertSeverity::Warning => routes.warning,
+                AlertSeverity::Error => routes.error,
+                AlertSeverity::Critical => routes.critical,
+            })
+            .unwrap_or_else(|| default_action(class, severity))
+    }
+
+    /// Configured overrides as (class, severity, action), for startup logs.
+    pub fn overrides(&self) -> impl Iterator<Item = (AlertClass, &'static str, AlertAction)> + '_ {
+        self.routes.iter().flat_map(|(class, routes)| {
+            routes
+                .iter()
+                .map(move |(severity, action)| (*class, severity, action))
+        })
+    }
+}
+
+/// Built-in reaction to an alert of `class` at `severity`.
+pub fn default_action(class: AlertClass, severity: AlertSeverity) -> AlertAction {
+    match (class, severity) {
+        (AlertClass::DrawdownExceeded, AlertSeverity::Critical)
+        | (AlertClass::MarginWarning, AlertSeverity::Critical)
+        | (AlertClass::Malfunction, AlertSeverity::Critical) => AlertAction::Halt,
+        (AlertClass::MarginWarning | AlertClass::LiquidationRisk, severity)
+            if severity >= AlertSeverity::Error =>
+        {
+            AlertAction::AutoReduce
+        }
+        (AlertClass::ExitLiquidity, _) => AlertAction::AutoReduce,
+        _ => AlertAction::Notify,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matrix() {
+        let router = AlertRouter::default();
+        assert_eq!(
+            router.action(AlertClass::DrawdownExceeded, AlertSeverity::Critical),
+            AlertAction::Halt
+        );
+        assert_eq!(
+            router.action(AlertClass::MarginWarning, AlertSeverity::Error),
+            AlertAction::AutoReduce
+        );
+        assert_eq!(
+            router.action(AlertClass::MarginWarning, AlertSeverity::Warning),
+            AlertAction::Notify
+        );
+        assert_eq!(
+            router.action(AlertClass::LiquidationRisk, AlertSeverity::Critical),
+            AlertAction::AutoReduce
+        );
+        assert_eq!(
+            router.action(AlertClass::FundingCollapse, AlertSeverity::Warning),
+            AlertAction::Notify
+        );
+        assert_eq!(router.overrides().count(), 0);
+    }
+
+    #[test]
+    fn test_overrides_replace_single_cells() {
+        let router = AlertRouter::new(BTreeMap::from([(
+            AlertClass::MarginWarning,
+            SeverityRoutes {
+                error: Some(AlertAction::Notify),
+                warning: Some(AlertAction::Log),
+                ..Default::default()
+            },
+        )]));
+
+        assert_eq!(
+            router.action(AlertClass::MarginWarning, AlertSeverity::Error),
+            AlertAction::Notify
+        );
+        assert_eq!(
+            router.action(AlertClass::MarginWarning, AlertSeverity::Warning),
+            AlertAction::Log
+        );
+        // Cells left out keep the built-in reaction
+        assert_eq!(
+            router.action(AlertClass::MarginWarning, AlertSeverity::Critical),
+            AlertAction::Halt
+        );
+        let overrides: Vec<_> = router.overrides().collect();
+        assert_eq!(
+            overrides,
+            vec![
+                (AlertClass::MarginWarning, "warning", AlertAction::Log),
+                (AlertClass::MarginWarning, "error", AlertAction::Notify),
+            ]
+        );
+    }
+}
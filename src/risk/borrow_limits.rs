@@ -0,0 +1,286 @@
+//! Exchange borrow limits of assets borrowed for short spot hedges.
+//!
+//! Cross margin caps how much of each asset an account may borrow, and the
+//! lending pool can run dry before that. An entry whose spot leg needs more than
+//! is left fails after the futures leg has filled, and a hedge can't be topped
+//! up once its asset is exhausted. The tracker keeps the latest borrowed amount,
+//! remaining borrowable amount and limit per asset, reports an asset once when
+//! its utilization reaches the alert threshold, and answers whether a planned
+//! borrow still fits.
+
+use crate::config::BorrowLimitsConfig;
+use crate::exchange::{MaxBorrowable, QualifiedPair};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// Borrow position of one asset against the exchange's limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowLimit {
+    pub asset: String,
+    /// Amount currently borrowed, in the asset
+    pub borrowed: Decimal,
+    /// Amount that can still be borrowed, in the asset
+    pub available: Decimal,
+    /// Most the account may borrow in total, in the asset
+    pub limit: Decimal,
+    /// USDT price the headroom is valued at
+    pub price: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BorrowLimit {
+    /// Share of the borrow limit in use; `None` without a limit.
+    pub fn utilization(&self) -> Option<Decimal> {
+        (self.limit > Decimal::ZERO).then(|| self.borrowed / self.limit)
+    }
+
+    /// Value of what can still be borrowed (USDT).
+    pub fn headroom_usdt(&self) -> Decimal {
+        self.available * self.price
+    }
+}
+
+/// A planned borrow larger than what is left to borrow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorrowShortfall {
+    pub asset: String,
+    /// Amount the entry would borrow, in the asset
+    pub required: Decimal,
+    /// Amount that can still be borrowed, in the asset
+    pub available: Decimal,
+}
+
+/// Latest borrow limits per asset.
+#[derive(Debug)]
+pub struct BorrowLimits {
+    config: BorrowLimitsConfig,
+    limits: HashMap<String, BorrowLimit>,
+    /// Assets reported at or above the alert utilization
+    alerted: HashSet<String>,
+    refreshed_at: Option<DateTime<Utc>>,
+}
+
+impl BorrowLimits {
+    pub fn new(config: BorrowLimitsConfig) -> Self {
+        Self {
+            config,
+            limits: HashMap::new(),
+            alerted: HashSet::new(),
+            refreshed_at: None,
+        }
+    }
+
+    /// Whether limits are due for a refresh; the first is due once enabled.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.config.enabled
+            && self
+                .refreshed_at
+                .is_none_or(|at| now - at >= Duration::minutes(self.config.refresh_minutes as i64))
+    }
+
+    /// Start the next refresh interval.
+    pub fn mark_refreshed(&mut self, now: DateTime<Utc>) {
+        self.refreshed_at = Some(now);
+    }
+
+    /// Assets to check: those with a `borrowed` amount, then the base assets of
+    /// the best `pairs` whose spot hedge would borrow.
+    pub fn assets_to_check(
+        &self,
+        borrowed: &HashMap<String, Decimal>,
+        pairs: &[QualifiedPair],
+    ) -> Vec<String> {
+        let mut assets: Vec<String> = borrowed
+            .iter()
+            .filter(|(_, amount)| **amount > Decimal::ZERO)
+            .map(|(asset, _)| asset.clone())
+            .collect();
+        assets.sort();
+        let candidates = pairs
+            .iter()
+            .filter(|p| p.hedge.is_spot() && p.funding_rate < Decimal::ZERO)
+            .map(|p| &p.base_asset)
+            .filter(|asset| !assets.contains(asset))
+            .take(self.config.max_candidates)
+            .cloned()
+            .collect::<Vec<_>>();
+        assets.extend(candidates);
+        assets
+    }
+
+    /// Record the limits of `asset`, with `borrowed` of it currently borrowed.
+    ///
+    /// Returns the limit when its utilization newly reached the alert threshold.
+    pub fn update(
+        &mut self,
+        asset: &str,
+        borrowed: Decimal,
+        max: &MaxBorrowable,
+        price: Decimal,
+        now: DateTime<Utc>,
+    ) -> Option<BorrowLimit> {
+        let limit = BorrowLimit {
+            asset: asset.to_string(),
+            borrowed,
+            available: max.amount,
+            limit: max.borrow_limit,
+            price,
+            updated_at: now,
+        };
+        let high = limit
+            .utilization()
+            .is_some_and(|u| u >= self.config.alert_utilization);
+        self.limits.insert(asset.to_string(), limit.clone());
+        if !high {
+            self.alerted.remove(asset);
+            return None;
+        }
+        self.alerted.insert(asset.to_string()).then_some(limit)
+    }
+
+    /// Latest limits of `asset`.
+    pub fn get(&self, asset: &str) -> Option<&BorrowLimit> {
+        self.limits.get(asset)
+    }
+
+    /// Latest limits of every checked asset, most utilized first.
+    pub fn limits(&self) -> Vec<&BorrowLimit> {
+        let mut limits: Vec<&BorrowLimit> = self.limits.values().collect();
+        limits.sort_by_key(|limit| std::cmp::Reverse(limit.utilization()));
+        limits
+    }
+
+    /// USDT left to borrow per checked asset.
+    pub fn headroom_usdt(&self) -> HashMap<String, Decimal> {
+        self.limits
+            .iter()
+            .map(|(asset, limit)| (asset.clone(), limit.headroom_usdt()))
+            .collect()
+    }
+
+    /// Least USDT left to borrow for a spot hedge to qualify.
+    pub fn min_headroom_usdt(&self) -> Decimal {
+        self.config.min_headroom_usdt
+    }
+
+    /// Shortfall if borrowing `quantity` of `asset` would exceed what is left.
+    ///
+    /// Assets never checked pass: the exchange still refuses the borrow itself.
+    pub fn shortfall(&self, asset: &str, quantity: Decimal) -> Option<BorrowShortfall> {
+        let limit = self.limits.get(asset)?;
+        (quantity > limit.available).then(|| BorrowShortfall {
+            asset: asset.to_string(),
+            required: quantity,
+            available: limit.available,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn max(amount: Decimal, borrow_limit: Decimal) -> MaxBorrowable {
+        MaxBorrowable {
+            amount,
+            borrow_limit,
+        }
+    }
+
+    #[test]
+    fn test_alerts_once_per_high_utilization() {
+        let now = Utc::now();
+        let mut limits = BorrowLimits::new(BorrowLimitsConfig::default());
+        assert!(limits.is_due(now));
+        limits.mark_refreshed(now);
+        assert!(!limits.is_due(now + Duration::minutes(29)));
+        assert!(limits.is_due(now + Duration::minutes(30)));
+
+        assert!(limits
+            .update("SOL", dec!(50), &max(dec!(50), dec!(100)), dec!(150), now)
+            .is_none());
+        let high = limits
+            .update("SOL", dec!(85), &max(dec!(15), dec!(100)), dec!(150), now)
+            .unwrap();
+        assert_eq!(high.utilization(), Some(dec!(0.85)));
+        assert_eq!(high.headroom_usdt(), dec!(2250));
+        assert!(limits
+            .update("SOL", dec!(90), &max(dec!(10), dec!(100)), dec!(150), now)
+            .is_none());
+
+        // Falling back below the threshold re-arms the alert
+        limits.update("SOL", dec!(20), &max(dec!(80), dec!(100)), dec!(150), now);
+        assert!(limits
+            .update("SOL", dec!(95), &max(dec!(5), dec!(100)), dec!(150), now)
+            .is_some());
+
+        // An asset without a limit has no utilization
+        limits.update(
+            "ETH",
+            Decimal::ZERO,
+            &max(dec!(3), Decimal::ZERO),
+            dec!(3000),
+            now,
+        );
+        assert_eq!(limits.get("ETH").unwrap().utilization(), None);
+        assert_eq!(limits.limits()[0].asset, "SOL");
+        assert_eq!(limits.headroom_usdt()["ETH"], dec!(9000));
+    }
+
+    #[test]
+    fn test_checks_borrowed_assets_and_best_short_candidates() {
+        let limits = BorrowLimits::new(BorrowLimitsConfig {
+            max_candidates: 2,
+            ..Default::default()
+        });
+        let pair = |base: &str, funding_rate: Decimal| QualifiedPair {
+            symbol: format!("{}USDT", base),
+            spot_symbol: format!("{}USDT", base),
+            base_asset: base.to_string(),
+            funding_rate,
+            next_funding_time: 0,
+            volume_24h: Decimal::ZERO,
+            spread: Decimal::ZERO,
+            open_interest: Decimal::ZERO,
+            margin_available: true,
+            borrow_rate: None,
+            score: Decimal::ZERO,
+            hedge: Default::default(),
+            hedge_options: Vec::new(),
+            funding_capped: false,
+            coin_contract: None,
+            borrow_headroom_usdt: None,
+        };
+        let borrowed = HashMap::from([
+            ("SOL".to_string(), dec!(12)),
+            ("USDT".to_string(), Decimal::ZERO),
+        ]);
+        let pairs = vec![
+            pair("BTC", dec!(0.001)),
+            pair("SOL", dec!(-0.002)),
+            pair("DOGE", dec!(-0.001)),
+            pair("ETH", dec!(-0.0008)),
+            pair("XRP", dec!(-0.0005)),
+        ];
+        // Longs borrow nothing; SOL is already checked as a borrowed asset
+        assert_eq!(
+            limits.assets_to_check(&borrowed, &pairs),
+            vec!["SOL", "DOGE", "ETH"]
+        );
+    }
+
+    #[test]
+    fn test_shortfall_against_remaining_amount() {
+        let now = Utc::now();
+        let mut limits = BorrowLimits::new(BorrowLimitsConfig::default());
+        limits.update("SOL", dec!(90), &max(dec!(10), dec!(100)), dec!(150), now);
+
+        assert!(limits.shortfall("SOL", dec!(10)).is_none());
+        let shortfall = limits.shortfall("SOL", dec!(12)).unwrap();
+        assert_eq!(shortfall.required, dec!(12));
+        assert_eq!(shortfall.available, dec!(10));
+        assert!(limits.shortfall("DOGE", dec!(1_000_000)).is_none());
+    }
+}
@@ -0,0 +1,279 @@
+//! Borrow rate spike detection for short spot hedges.
+//!
+//! Margin borrow rates jump when lending supply drains, typically over weekends
+//! and holidays when lenders withdraw and desks are thin. A held short spot hedge
+//! keeps paying the new rate every hour, so a spike can eat days of funding
+//! before the next review. Each borrowing position's rate is compared with its
+//! own baseline, the median of hourly samples over the past week; during
+//! low-supply periods, and in the hours leading into them, a lower multiple of
+//! the baseline already counts as a spike so hedges can be moved pre-emptively.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::debug;
+
+/// Samples closer together than this are folded into the latest rate.
+const SAMPLE_INTERVAL: Duration = Duration::hours(1);
+
+/// Baseline samples needed before a rate can be judged.
+const MIN_BASELINE_SAMPLES: usize = 6;
+
+/// How far ahead a weekend or holiday already counts as low supply.
+const LOW_SUPPLY_LEAD: Duration = Duration::hours(12);
+
+/// A borrowing position whose borrow rate spiked above its baseline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BorrowSpike {
+    pub symbol: String,
+    /// Latest borrow rate
+    pub rate: Decimal,
+    /// Median borrow rate over the baseline window
+    pub baseline: Decimal,
+    /// Whether the spike was judged against the low-supply threshold
+    pub low_supply: bool,
+}
+
+impl BorrowSpike {
+    /// Latest rate as a multiple of the baseline.
+    pub fn multiple(&self) -> Decimal {
+        if self.baseline.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.rate / self.baseline
+        }
+    }
+}
+
+/// Tracks borrow rates of held short spot hedges.
+pub struct BorrowSpikeMonitor {
+    /// Rate at or above this multiple of the baseline is a spike
+    spike_ratio: Decimal,
+    /// Spike multiple during weekends, holidays and the hours before them
+    low_supply_ratio: Decimal,
+    /// Dates (UTC) treated like weekends
+    holidays: HashSet<NaiveDate>,
+    /// Window the baseline median is taken over
+    window: Duration,
+    /// Symbols whose hedge borrows
+    tracked: HashSet<String>,
+    /// Hourly samples per symbol, oldest first
+    samples: HashMap<String, VecDeque<(DateTime<Utc>, Decimal)>>,
+    /// Latest observed rate per symbol
+    latest: HashMap<String, Decimal>,
+    /// Symbols already flagged, so each spike is reported once
+    flagged: HashSet<String>,
+}
+
+impl BorrowSpikeMonitor {
+    /// Create a new monitor.
+    pub fn new(spike_ratio: Decimal, low_supply_ratio: Decimal, holidays: &[NaiveDate]) -> Self {
+        Self {
+            spike_ratio,
+            low_supply_ratio,
+            holidays: holidays.iter().copied().collect(),
+            window: Duration::days(7),
+            tracked: HashSet::new(),
+            samples: HashMap::new(),
+            latest: HashMap::new(),
+            flagged: HashSet::new(),
+        }
+    }
+
+    /// Start monitoring a borrowing position.
+    pub fn track(&mut self, symbol: &str) {
+        self.tracked.insert(symbol.to_string());
+        self.samples.remove(symbol);
+        self.latest.remove(symbol);
+        self.flagged.remove(symbol);
+    }
+
+    /// Stop monitoring a symbol.
+    pub fn untrack(&mut self, symbol: &str) {
+        self.tracked.remove(symbol);
+        self.samples.remove(symbol);
+        self.latest.remove(symbol);
+        self.flagged.remove(symbol);
+    }
+
+    /// Record a borrow rate observed at `timestamp`.
+    pub fn record_at(&mut self, symbol: &str, rate: Decimal, timestamp: DateTime<Utc>) {
+        if !self.tracked.contains(symbol) {
+            return;
+        }
+        self.latest.insert(symbol.to_string(), rate);
+
+        let samples = self.samples.entry(symbol.to_string()).or_default();
+        if samples
+            .back()
+            .is_none_or(|(at, _)| timestamp - *at >= SAMPLE_INTERVAL)
+        {
+            samples.push_back((timestamp, rate));
+        }
+
+        let cutoff = timestamp - self.window;
+        while samples.front().is_some_and(|(t, _)| *t < cutoff) {
+            samples.pop_front();
+        }
+    }
+
+    /// Median sampled rate, once enough samples exist.
+    pub fn baseline(&self, symbol: &str) -> Option<Decimal> {
+        let samples = self.samples.get(symbol)?;
+        if samples.len() < MIN_BASELINE_SAMPLES {
+            return None;
+        }
+        let mut rates: Vec<Decimal> = samples.iter().map(|(_, rate)| *rate).collect();
+        rates.sort();
+        let mid = rates.len() / 2;
+        Some(if rates.len().is_multiple_of(2) {
+            (rates[mid - 1] + rates[mid]) / Decimal::TWO
+        } else {
+            rates[mid]
+        })
+    }
+
+    /// Whether `at` falls in, or within the lead time of, a weekend or holiday.
+    pub fn is_low_supply(&self, at: DateTime<Utc>) -> bool {
+        [at, at + LOW_SUPPLY_LEAD].iter().any(|t| {
+            let date = t.date_naive();
+            matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || self.holidays.contains(&date)
+        })
+    }
+
+    /// Newly spiked symbols as of `now`.
+    ///
+    /// Each spike is reported once; a symbol is re-armed when its rate falls
+    /// back below the threshold.
+    pub fn check(&mut self, now: DateTime<Utc>) -> Vec<BorrowSpike> {
+        let low_supply = self.is_low_supply(now);
+        let ratio = if low_supply {
+            self.low_supply_ratio
+        } else {
+            self.spike_ratio
+        };
+
+        let mut spikes = Vec::new();
+        let symbols: Vec<String> = self.tracked.iter().cloned().collect();
+        for symbol in symbols {
+            let (Some(&rate), Some(baseline)) = (self.latest.get(&symbol), self.baseline(&symbol))
+            else {
+                continue;
+            };
+            if baseline <= Decimal::ZERO || rate < baseline * ratio {
+                if self.flagged.remove(&symbol) {
+                    debug!(symbol = %symbol, %rate, %baseline, "Borrow rate back to baseline");
+                }
+                continue;
+            }
+            if !self.flagged.insert(symbol.clone()) {
+                continue;
+            }
+            spikes.push(BorrowSpike {
+                symbol,
+                rate,
+                baseline,
+                low_supply,
+            });
+        }
+
+        spikes
+    }
+
+    /// Symbols whose borrow rate is currently flagged as spiking.
+    pub fn spiking(&self) -> impl Iterator<Item = &str> {
+        self.flagged.iter().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    /// Wednesday 2024-03-13 00:00 UTC.
+    fn wednesday() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 13, 0, 0, 0).unwrap()
+    }
+
+    fn monitor_with_baseline(start: DateTime<Utc>) -> BorrowSpikeMonitor {
+        let mut monitor = BorrowSpikeMonitor::new(dec!(3), dec!(2), &[]);
+        monitor.track("ALTUSDT");
+        for hour in 0..8 {
+            monitor.record_at("ALTUSDT", dec!(0.0001), start + Duration::hours(hour));
+        }
+        monitor
+    }
+
+    #[test]
+    fn test_spike_flagged_once_and_rearmed() {
+        let start = wednesday();
+        let mut monitor = monitor_with_baseline(start);
+        let now = start + Duration::hours(8);
+
+        monitor.record_at("ALTUSDT", dec!(0.00025), now);
+        assert!(monitor.check(now).is_empty());
+
+        monitor.record_at("ALTUSDT", dec!(0.0004), now + Duration::minutes(5));
+        let spikes = monitor.check(now);
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].baseline, dec!(0.0001));
+        assert_eq!(spikes[0].multiple(), dec!(4));
+        assert!(!spikes[0].low_supply);
+        assert_eq!(monitor.spiking().collect::<Vec<_>>(), vec!["ALTUSDT"]);
+
+        // Reported only once while spiking
+        assert!(monitor.check(now).is_empty());
+
+        // Falling back re-arms the symbol
+        monitor.record_at("ALTUSDT", dec!(0.0001), now + Duration::minutes(10));
+        assert!(monitor.check(now).is_empty());
+        assert_eq!(monitor.spiking().count(), 0);
+        monitor.record_at("ALTUSDT", dec!(0.0005), now + Duration::minutes(15));
+        assert_eq!(monitor.check(now).len(), 1);
+    }
+
+    #[test]
+    fn test_low_supply_threshold_ahead_of_weekend() {
+        // Friday 06:00: the weekend is more than 12h away
+        let friday = Utc.with_ymd_and_hms(2024, 3, 15, 6, 0, 0).unwrap();
+        let mut monitor = monitor_with_baseline(friday - Duration::hours(8));
+        monitor.record_at("ALTUSDT", dec!(0.00025), friday);
+        assert!(!monitor.is_low_supply(friday));
+        assert!(monitor.check(friday).is_empty());
+
+        // Friday 14:00 already counts as the weekend
+        let evening = friday + Duration::hours(8);
+        assert!(monitor.is_low_supply(evening));
+        let spikes = monitor.check(evening);
+        assert_eq!(spikes.len(), 1);
+        assert!(spikes[0].low_supply);
+
+        // Holidays count like weekends
+        let holiday = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let monitor = BorrowSpikeMonitor::new(dec!(3), dec!(2), &[holiday]);
+        assert!(monitor.is_low_supply(wednesday()));
+    }
+
+    #[test]
+    fn test_needs_baseline_and_tracking() {
+        let start = wednesday();
+        let mut monitor = BorrowSpikeMonitor::new(dec!(3), dec!(2), &[]);
+        monitor.record_at("ALTUSDT", dec!(0.0001), start);
+        assert!(monitor.baseline("ALTUSDT").is_none());
+
+        // Samples within the hour fold into the latest rate
+        monitor.track("ALTUSDT");
+        for minute in 0..30 {
+            monitor.record_at("ALTUSDT", dec!(0.0001), start + Duration::minutes(minute));
+        }
+        monitor.record_at("ALTUSDT", dec!(0.01), start + Duration::minutes(30));
+        assert!(monitor.baseline("ALTUSDT").is_none());
+        assert!(monitor.check(start).is_empty());
+
+        monitor.untrack("ALTUSDT");
+        assert!(monitor.check(start).is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! Macro event calendar for entry blackouts.
+//!
+//! Scheduled releases such as FOMC decisions or CPI prints move prices and funding
+//! sharply. New entries are blocked around high-impact events and shrunk around
+//! medium-impact ones; held positions are left to the usual risk checks.
+
+use crate::config::EventsConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Timeout for calendar URL requests.
+const CALENDAR_FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// Market impact of a scheduled event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventImpact {
+    Low,
+    Medium,
+    High,
+}
+
+/// A scheduled macro event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroEvent {
+    pub name: String,
+    /// Scheduled release time (RFC 3339)
+    pub time: DateTime<Utc>,
+    #[serde(default = "default_impact")]
+    pub impact: EventImpact,
+}
+
+fn default_impact() -> EventImpact {
+    EventImpact::High
+}
+
+/// Events file layout: a list of `[[events]]` tables.
+#[derive(Debug, Deserialize)]
+struct EventsFile {
+    #[serde(default)]
+    events: Vec<MacroEvent>,
+}
+
+/// What the calendar allows for new entries at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryRestriction {
+    /// No event window is active
+    Open,
+    /// Entries are scaled down by `factor` until `until`
+    Reduced {
+        event: MacroEvent,
+        factor: Decimal,
+        until: DateTime<Utc>,
+    },
+    /// No new entries until `until`
+    Blackout {
+        event: MacroEvent,
+        until: DateTime<Utc>,
+    },
+}
+
+impl EntryRestriction {
+    /// Multiplier applied to new entry sizes.
+    pub fn size_factor(&self) -> Decimal {
+        match self {
+            EntryRestriction::Open => Decimal::ONE,
+            EntryRestriction::Reduced { factor, .. } => *factor,
+            EntryRestriction::Blackout { .. } => Decimal::ZERO,
+        }
+    }
+}
+
+/// Known macro events and the windows around them.
+#[derive(Debug, Clone)]
+pub struct EventCalendar {
+    /// Events sorted by time
+    events: Vec<MacroEvent>,
+    window_before: Duration,
+    window_after: Duration,
+    reduced_size_factor: Decimal,
+}
+
+impl EventCalendar {
+    /// Create a calendar from a list of events.
+    pub fn new(config: &EventsConfig, mut events: Vec<MacroEvent>) -> Self {
+        events.sort_by_key(|event| event.time);
+        events.dedup_by(|a, b| a.time == b.time && a.name == b.name);
+        Self {
+            events,
+            window_before: Duration::minutes(config.window_before_minutes as i64),
+            window_after: Duration::minutes(config.window_after_minutes as i64),
+            reduced_size_factor: config.reduced_size_factor,
+        }
+    }
+
+    /// Load events from the configured file and calendar URL.
+    ///
+    /// Returns an empty calendar when the calendar is disabled.
+    pub async fn load(config: &EventsConfig) -> Result<Self> {
+        let mut events = Vec::new();
+        if config.enabled {
+            if !config.file.is_empty() {
+                events.extend(load_events_file(Path::new(&config.file))?);
+            }
+            if !config.calendar_url.is_empty() {
+                events.extend(fetch_calendar(&config.calendar_url).await?);
+            }
+        }
+        Ok(Self::new(config, events))
+    }
+
+    /// Number of known events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no events are known.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Entry restriction at `now`.
+    ///
+    /// The highest-impact event whose window contains `now` wins; among events of
+    /// that impact the restriction lasts until the latest window ends.
+    pub fn restriction_at(&self, now: DateTime<Utc>) -> EntryRestriction {
+        let active = self
+            .events
+            .iter()
+            .filter(|event| event.impact > EventImpact::Low)
+            .filter(|event| {
+                now >= event.time - self.window_before && now <= event.time + self.window_after
+            });
+        let Some(event) = active
+            .max_by_key(|event| (event.impact, event.time))
+            .cloned()
+        else {
+            return EntryRestriction::Open;
+        };
+
+        let until = event.time + self.window_after;
+        match event.impact {
+            EventImpact::High => EntryRestriction::Blackout { event, until },
+            _ => EntryRestriction::Reduced {
+                event,
+                factor: self.reduced_size_factor,
+                until,
+            },
+        }
+    }
+
+    /// Events scheduled at or after `now`, soonest first.
+    pub fn upcoming(&self, now: DateTime<Utc>, limit: usize) -> Vec<&MacroEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.time + self.window_after >= now)
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Read events from a TOML file of `[[events]]` tables.
+pub fn load_events_file(path: &Path) -> Result<Vec<MacroEvent>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read events file {}", path.display()))?;
+    let file: EventsFile = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse events file {}", path.display()))?;
+    Ok(file.events)
+}
+
+/// Fetch events from a calendar URL returning a JSON array.
+pub async fn fetch_calendar(url: &str) -> Result<Vec<MacroEvent>> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(CALENDAR_FETCH_TIMEOUT_SECS))
+        .build()?;
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to fetch event calendar from {}", url))?
+        .json()
+        .await
+        .context("Failed to parse event calendar response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn calendar() -> EventCalendar {
+        let events: EventsFile = toml::from_str(
+            r#"
+            [[events]]
+            name = "FOMC"
+            time = "2024-03-20T18:00:00Z"
+
+            [[events]]
+            name = "CPI"
+            time = "2024-03-20T17:30:00Z"
+            impact = "medium"
+
+            [[events]]
+            name = "Jobless Claims"
+            time = "2024-03-21T12:30:00Z"
+            impact = "low"
+            "#,
+        )
+        .unwrap();
+        EventCalendar::new(&EventsConfig::default(), events.events)
+    }
+
+    #[test]
+    fn test_restriction_windows() {
+        let calendar = calendar();
+        assert_eq!(calendar.len(), 3);
+
+        assert_eq!(
+            calendar.restriction_at(at("2024-03-20T16:00:00Z")),
+            EntryRestriction::Open
+        );
+
+        // Only the medium-impact CPI window is open
+        let reduced = calendar.restriction_at(at("2024-03-20T16:45:00Z"));
+        assert!(
+            matches!(reduced, EntryRestriction::Reduced { ref event, .. } if event.name == "CPI")
+        );
+        assert_eq!(reduced.size_factor(), dec!(0.50));
+
+        // FOMC overlaps and outranks CPI
+        let blackout = calendar.restriction_at(at("2024-03-20T17:30:00Z"));
+        assert_eq!(
+            blackout,
+            EntryRestriction::Blackout {
+                event: MacroEvent {
+                    name: "FOMC".to_string(),
+                    time: at("2024-03-20T18:00:00Z"),
+                    impact: EventImpact::High,
+                },
+                until: at("2024-03-20T19:00:00Z"),
+            }
+        );
+        assert_eq!(blackout.size_factor(), Decimal::ZERO);
+
+        assert_eq!(
+            calendar.restriction_at(at("2024-03-20T19:01:00Z")),
+            EntryRestriction::Open
+        );
+
+        // Low-impact events never restrict entries
+        assert_eq!(
+            calendar.restriction_at(at("2024-03-21T12:30:00Z")),
+            EntryRestriction::Open
+        );
+    }
+
+    #[test]
+    fn test_upcoming_includes_active_events() {
+        let calendar = calendar();
+        let upcoming = calendar.upcoming(at("2024-03-20T18:45:00Z"), 5);
+        let names: Vec<&str> = upcoming.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["FOMC", "Jobless Claims"]);
+    }
+
+    #[test]
+    fn test_calendar_json_format() {
+        let events: Vec<MacroEvent> = serde_json::from_str(
+            r#"[{"name": "CPI", "time": "2024-04-10T12:30:00Z", "impact": "medium"},
+                {"name": "FOMC", "time": "2024-05-01T18:00:00Z"}]"#,
+        )
+        .unwrap();
+        assert_eq!(events[0].impact, EventImpact::Medium);
+        assert_eq!(events[1].impact, EventImpact::High);
+    }
+}
@@ -0,0 +1,343 @@
+//! Exit liquidity monitoring for held spot hedges.
+//!
+//! A hedge in a thin spot market can cost more to unwind than the funding it has
+//! earned. Sampling the book periodically and pricing a full exit against it flags
+//! those positions while there is still time to shrink them in affordable steps.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Cost of walking the book to exit a quantity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExitCost {
+    /// Quantity the book can absorb (at most the requested quantity)
+    pub fillable_qty: Decimal,
+    /// Slippage versus the mid price for the fillable quantity, in quote currency
+    pub slippage_cost: Decimal,
+    /// Average fill price of the fillable quantity
+    pub avg_price: Decimal,
+}
+
+impl ExitCost {
+    /// Slippage as a fraction of the notional filled.
+    pub fn slippage_pct(&self, mid: Decimal) -> Decimal {
+        let notional = self.fillable_qty * mid;
+        if notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.slippage_cost / notional
+        }
+    }
+}
+
+/// Price exiting `quantity` against one side of the book, best level first.
+pub fn estimate_exit_cost(
+    levels: &[(Decimal, Decimal)],
+    mid: Decimal,
+    quantity: Decimal,
+) -> ExitCost {
+    let mut remaining = quantity.abs();
+    let mut filled = Decimal::ZERO;
+    let mut notional = Decimal::ZERO;
+    let mut slippage_cost = Decimal::ZERO;
+
+    for &(price, level_qty) in levels {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let take = level_qty.min(remaining);
+        filled += take;
+        notional += take * price;
+        slippage_cost += take * (price - mid).abs();
+        remaining -= take;
+    }
+
+    ExitCost {
+        fillable_qty: filled,
+        slippage_cost,
+        avg_price: if filled.is_zero() {
+            Decimal::ZERO
+        } else {
+            notional / filled
+        },
+    }
+}
+
+/// Largest quantity that can be exited against the book for at most `budget` slippage.
+pub fn max_exit_quantity(levels: &[(Decimal, Decimal)], mid: Decimal, budget: Decimal) -> Decimal {
+    let mut remaining_budget = budget.max(Decimal::ZERO);
+    let mut quantity = Decimal::ZERO;
+
+    for &(price, level_qty) in levels {
+        let unit_cost = (price - mid).abs();
+        if unit_cost.is_zero() {
+            quantity += level_qty;
+            continue;
+        }
+        let affordable = remaining_budget / unit_cost;
+        if affordable < level_qty {
+            quantity += affordable;
+            break;
+        }
+        quantity += level_qty;
+        remaining_budget -= level_qty * unit_cost;
+    }
+
+    quantity
+}
+
+/// A held hedge whose full exit would cost more than its funding justifies.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitLiquidityBreach {
+    /// Futures symbol of the position
+    pub symbol: String,
+    /// Spot hedge quantity (positive = long spot, negative = short spot)
+    pub hedge_qty: Decimal,
+    /// Book mid price at sampling
+    pub mid_price: Decimal,
+    /// Cost of exiting the full hedge against the sampled book
+    pub exit_cost: ExitCost,
+    /// Slippage the position can absorb
+    pub budget: Decimal,
+    /// Largest quantity exitable within the budget right now
+    pub affordable_qty: Decimal,
+}
+
+impl ExitLiquidityBreach {
+    /// The book cannot absorb the full hedge at any price.
+    pub fn insufficient_depth(&self) -> bool {
+        self.exit_cost.fillable_qty < self.hedge_qty.abs()
+    }
+
+    /// Hedge quantity to keep after one downsizing step.
+    ///
+    /// Each step only sells what the current book absorbs within budget, so the
+    /// downsizing itself stays affordable; repeated samples walk the hedge down
+    /// to the affordable size as the book refills.
+    pub fn target_qty(&self) -> Decimal {
+        let hedge = self.hedge_qty.abs();
+        let excess = (hedge - self.affordable_qty).max(Decimal::ZERO);
+        hedge - excess.min(self.affordable_qty)
+    }
+}
+
+/// Samples spot book depth for held hedges and prices their exit.
+pub struct ExitLiquidityMonitor {
+    /// Minimum time between samples of the same symbol (zero disables the monitor)
+    sample_interval: Duration,
+    /// Exit slippage allowed as a fraction of the position's funding
+    max_cost_ratio: Decimal,
+    /// Last sample time per symbol
+    last_sampled: HashMap<String, DateTime<Utc>>,
+}
+
+impl ExitLiquidityMonitor {
+    /// Create a new monitor.
+    pub fn new(sample_interval: Duration, max_cost_ratio: Decimal) -> Self {
+        Self {
+            sample_interval,
+            max_cost_ratio,
+            last_sampled: HashMap::new(),
+        }
+    }
+
+    /// Whether sampling is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.sample_interval > Duration::zero()
+    }
+
+    /// Whether a symbol is due for a new book sample.
+    pub fn is_due(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.is_enabled()
+            && self
+                .last_sampled
+                .get(symbol)
+                .is_none_or(|last| now - *last >= self.sample_interval)
+    }
+
+    /// Price a full exit of the hedge against a book sample.
+    ///
+    /// The slippage budget is `max_cost_ratio` of the funding accrued so far, but
+    /// never less than that fraction of one expected payment so fresh positions are
+    /// not flagged for having earned nothing yet. A long hedge exits into the bids,
+    /// a short hedge into the asks. Returns a breach when the exit costs more than
+    /// the budget or the book cannot absorb the full hedge.
+    #[allow(clippy::too_many_arguments)]
+    pub fn assess(
+        &mut self,
+        symbol: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+        hedge_qty: Decimal,
+        accrued_funding: Decimal,
+        expected_payment: Decimal,
+        now: DateTime<Utc>,
+    ) -> Option<ExitLiquidityBreach> {
+        self.last_sampled.insert(symbol.to_string(), now);
+
+        let (&(best_bid, _), &(best_ask, _)) = (bids.first()?, asks.first()?);
+        if hedge_qty.is_zero() {
+            return None;
+        }
+        let mid = (best_bid + best_ask) / Decimal::TWO;
+        let levels = if hedge_qty > Decimal::ZERO {
+            bids
+        } else {
+            asks
+        };
+
+        let budget = self.max_cost_ratio * accrued_funding.max(expected_payment.abs());
+        let exit_cost = estimate_exit_cost(levels, mid, hedge_qty);
+        let breach = ExitLiquidityBreach {
+            symbol: symbol.to_string(),
+            hedge_qty,
+            mid_price: mid,
+            affordable_qty: max_exit_quantity(levels, mid, budget).min(hedge_qty.abs()),
+            exit_cost,
+            budget,
+        };
+
+        (breach.insufficient_depth() || breach.exit_cost.slippage_cost > budget).then_some(breach)
+    }
+
+    /// Forget a closed position.
+    pub fn untrack(&mut self, symbol: &str) {
+        self.last_sampled.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn bids() -> Vec<(Decimal, Decimal)> {
+        vec![
+            (dec!(99), dec!(10)),
+            (dec!(98), dec!(10)),
+            (dec!(95), dec!(20)),
+        ]
+    }
+
+    fn asks() -> Vec<(Decimal, Decimal)> {
+        vec![(dec!(101), dec!(10)), (dec!(102), dec!(10))]
+    }
+
+    #[test]
+    fn test_exit_cost_walks_the_book() {
+        let cost = estimate_exit_cost(&bids(), dec!(100), dec!(15));
+        assert_eq!(cost.fillable_qty, dec!(15));
+        // 10 @ 99 (1 below mid) + 5 @ 98 (2 below mid)
+        assert_eq!(cost.slippage_cost, dec!(20));
+        assert_eq!(cost.avg_price.round_dp(4), dec!(98.6667));
+
+        let thin = estimate_exit_cost(&asks(), dec!(100), dec!(-50));
+        assert_eq!(thin.fillable_qty, dec!(20));
+    }
+
+    #[test]
+    fn test_max_exit_quantity_within_budget() {
+        assert_eq!(max_exit_quantity(&bids(), dec!(100), dec!(10)), dec!(10));
+        assert_eq!(max_exit_quantity(&bids(), dec!(100), dec!(20)), dec!(15));
+        assert_eq!(
+            max_exit_quantity(&bids(), dec!(100), Decimal::ZERO),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_breach_against_accrued_funding() {
+        let mut monitor = ExitLiquidityMonitor::new(Duration::minutes(15), dec!(0.5));
+        let now = Utc::now();
+
+        // Budget 0.5 * 100 = 50 covers exiting 20 (cost 30)
+        let ok = monitor.assess(
+            "BTCUSDT",
+            &bids(),
+            &asks(),
+            dec!(20),
+            dec!(100),
+            dec!(5),
+            now,
+        );
+        assert!(ok.is_none());
+
+        // Budget 0.5 * 40 = 20 affords 15; the first step keeps 20 - min(5, 15) = 15
+        let breach = monitor
+            .assess(
+                "BTCUSDT",
+                &bids(),
+                &asks(),
+                dec!(20),
+                dec!(40),
+                dec!(5),
+                now,
+            )
+            .unwrap();
+        assert_eq!(breach.exit_cost.slippage_cost, dec!(30));
+        assert_eq!(breach.affordable_qty, dec!(15));
+        assert_eq!(breach.target_qty(), dec!(15));
+        assert!(!breach.insufficient_depth());
+
+        // Fresh position: the budget falls back to one expected payment
+        let breach = monitor
+            .assess(
+                "BTCUSDT",
+                &bids(),
+                &asks(),
+                dec!(20),
+                Decimal::ZERO,
+                dec!(8),
+                now,
+            )
+            .unwrap();
+        assert_eq!(breach.budget, dec!(4));
+        assert_eq!(breach.affordable_qty, dec!(4));
+        assert_eq!(breach.target_qty(), dec!(16));
+    }
+
+    #[test]
+    fn test_short_hedge_exits_into_asks() {
+        let mut monitor = ExitLiquidityMonitor::new(Duration::minutes(15), dec!(0.5));
+        let breach = monitor
+            .assess(
+                "ETHUSDT",
+                &bids(),
+                &asks(),
+                dec!(-30),
+                dec!(1000),
+                dec!(5),
+                Utc::now(),
+            )
+            .unwrap();
+        assert!(breach.insufficient_depth());
+        assert_eq!(breach.exit_cost.fillable_qty, dec!(20));
+    }
+
+    #[test]
+    fn test_sampling_interval() {
+        let mut monitor = ExitLiquidityMonitor::new(Duration::minutes(15), dec!(0.5));
+        let now = Utc::now();
+        assert!(monitor.is_due("BTCUSDT", now));
+
+        monitor.assess(
+            "BTCUSDT",
+            &bids(),
+            &asks(),
+            dec!(1),
+            dec!(100),
+            dec!(5),
+            now,
+        );
+        assert!(!monitor.is_due("BTCUSDT", now + Duration::minutes(10)));
+        assert!(monitor.is_due("BTCUSDT", now + Duration::minutes(15)));
+
+        monitor.untrack("BTCUSDT");
+        assert!(monitor.is_due("BTCUSDT", now));
+
+        let disabled = ExitLiquidityMonitor::new(Duration::zero(), dec!(0.5));
+        assert!(!disabled.is_due("BTCUSDT", now));
+    }
+}
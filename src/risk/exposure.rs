@@ -0,0 +1,214 @@
+//! Residual delta exposure of the hedged book.
+//!
+//! Each position is meant to be flat, but rebalancing bands, partial fills and lot
+//! rounding leave a small unhedged quantity behind. Summed across the book those
+//! residuals are real directional exposure, so they are reported in USD per symbol
+//! and portfolio-wide alongside the per-symbol drift checks.
+//!
+//! Symbols with a partial hedge target carry an intended residual; only the part
+//! beyond it counts as drift.
+
+use crate::config::HedgeConfig;
+use crate::exchange::DeltaNeutralPosition;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Residual delta of one hedged position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolExposure {
+    pub symbol: String,
+    /// Futures quantity plus spot quantity (positive = net long)
+    pub residual_qty: Decimal,
+    /// Price the residual is valued at
+    pub price: Decimal,
+    /// Residual quantity valued in USD (positive = net long)
+    pub residual_usd: Decimal,
+    /// Notional of the hedged leg (the larger of the two)
+    pub hedged_notional: Decimal,
+    /// Residual intended by the hedge target in USD
+    pub target_usd: Decimal,
+    /// Residual beyond the hedge target in USD
+    pub drift_usd: Decimal,
+}
+
+impl SymbolExposure {
+    /// Value the unhedged quantity of a futures/spot pair at `price`.
+    pub fn new(symbol: &str, futures_qty: Decimal, spot_qty: Decimal, price: Decimal) -> Self {
+        let residual_qty = futures_qty + spot_qty;
+        Self {
+            symbol: symbol.to_string(),
+            residual_qty,
+            price,
+            residual_usd: residual_qty * price,
+            hedged_notional: futures_qty.abs().max(spot_qty.abs()) * price,
+            target_usd: Decimal::ZERO,
+            drift_usd: residual_qty * price,
+        }
+    }
+
+    /// Measure drift against an intended net quantity instead of zero.
+    pub fn with_target_qty(mut self, target_qty: Decimal) -> Self {
+        self.target_usd = target_qty * self.price;
+        self.drift_usd = self.residual_usd - self.target_usd;
+        self
+    }
+}
+
+/// Residual delta across the book.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExposureReport {
+    /// Per-symbol residuals, largest absolute drift first
+    pub symbols: Vec<SymbolExposure>,
+    /// Sum of signed residuals in USD (long and short residuals offset)
+    pub net_delta_usd: Decimal,
+    /// Sum of absolute residuals in USD
+    pub gross_delta_usd: Decimal,
+    /// Total hedged notional in USD
+    pub hedged_notional: Decimal,
+    /// Sum of residuals intended by hedge targets in USD
+    pub net_target_usd: Decimal,
+    /// Sum of signed residuals beyond the hedge targets in USD
+    pub net_drift_usd: Decimal,
+}
+
+impl ExposureReport {
+    /// Aggregate per-symbol residuals.
+    pub fn new(mut symbols: Vec<SymbolExposure>) -> Self {
+        symbols.sort_by_key(|s| std::cmp::Reverse(s.drift_usd.abs()));
+        Self {
+            net_delta_usd: symbols.iter().map(|s| s.residual_usd).sum(),
+            gross_delta_usd: symbols.iter().map(|s| s.residual_usd.abs()).sum(),
+            hedged_notional: symbols.iter().map(|s| s.hedged_notional).sum(),
+            net_target_usd: symbols.iter().map(|s| s.target_usd).sum(),
+            net_drift_usd: symbols.iter().map(|s| s.drift_usd).sum(),
+            symbols,
+        }
+    }
+
+    /// Report for delta-neutral positions at current prices.
+    ///
+    /// Drift is measured against each symbol's target in `hedge`. Positions
+    /// without a price are valued at their futures entry price.
+    pub fn from_positions(
+        positions: &[DeltaNeutralPosition],
+        prices: &HashMap<String, Decimal>,
+        hedge: &HedgeConfig,
+    ) -> Self {
+        Self::new(
+            positions
+                .iter()
+                .map(|p| {
+                    let price = prices
+                        .get(&p.symbol)
+                        .copied()
+                        .filter(|price| *price > Decimal::ZERO)
+                        .unwrap_or(p.futures_entry_price);
+                    let target = hedge.target(&p.symbol).target_net_qty(p.futures_qty);
+                    SymbolExposure::new(&p.symbol, p.futures_qty, p.spot_qty, price)
+                        .with_target_qty(target)
+                })
+                .collect(),
+        )
+    }
+
+    /// Net residual as a fraction of the hedged notional.
+    pub fn net_delta_pct(&self) -> Decimal {
+        if self.hedged_notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.net_delta_usd / self.hedged_notional
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn position(symbol: &str, futures_qty: Decimal, spot_qty: Decimal) -> DeltaNeutralPosition {
+        DeltaNeutralPosition {
+            symbol: symbol.to_string(),
+            spot_symbol: symbol.to_string(),
+            base_asset: symbol.trim_end_matches("USDT").to_string(),
+            futures_qty,
+            futures_entry_price: dec!(100),
+            spot_qty,
+            spot_entry_price: dec!(100),
+            net_delta: futures_qty + spot_qty,
+            borrowed_amount: Decimal::ZERO,
+            funding_pnl: Decimal::ZERO,
+            interest_paid: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_residuals_net_and_gross() {
+        let prices = HashMap::from([
+            ("BTCUSDT".to_string(), dec!(50000)),
+            ("ETHUSDT".to_string(), dec!(3000)),
+        ]);
+        let report = ExposureReport::from_positions(
+            &[
+                // Short futures hedge slightly larger than the spot leg: net short
+                position("BTCUSDT", dec!(-0.102), dec!(0.1)),
+                // Partial spot fill: net long
+                position("ETHUSDT", dec!(-2), dec!(2.05)),
+                // No price: valued at entry
+                position("SOLUSDT", dec!(10), dec!(-10)),
+            ],
+            &prices,
+            &HedgeConfig::default(),
+        );
+
+        assert_eq!(report.symbols[0].symbol, "ETHUSDT");
+        assert_eq!(report.symbols[0].residual_usd, dec!(150));
+        assert_eq!(report.symbols[1].residual_usd, dec!(-100));
+        assert_eq!(report.symbols[2].residual_usd, Decimal::ZERO);
+        assert_eq!(report.symbols[2].price, dec!(100));
+
+        assert_eq!(report.net_delta_usd, dec!(50));
+        assert_eq!(report.gross_delta_usd, dec!(250));
+        // 5100 + 6150 + 1000
+        assert_eq!(report.hedged_notional, dec!(12250));
+        assert_eq!(report.net_delta_pct().round_dp(4), dec!(0.0041));
+        assert_eq!(report.net_drift_usd, report.net_delta_usd);
+    }
+
+    #[test]
+    fn test_drift_measured_against_hedge_target() {
+        let hedge: HedgeConfig = toml::from_str(
+            r#"
+            [[symbols]]
+            symbol = "BTCUSDT"
+            target_ratio = 0.95
+            "#,
+        )
+        .unwrap();
+        let report = ExposureReport::from_positions(
+            &[
+                // 96% hedged against a 95% target: 1% of the short leg is drift
+                position("BTCUSDT", dec!(-1), dec!(0.96)),
+                position("ETHUSDT", dec!(-1), dec!(1)),
+            ],
+            &HashMap::new(),
+            &hedge,
+        );
+
+        let btc = &report.symbols[0];
+        assert_eq!(btc.residual_usd, dec!(-4));
+        assert_eq!(btc.target_usd, dec!(-5));
+        assert_eq!(btc.drift_usd, dec!(1));
+        assert_eq!(report.net_delta_usd, dec!(-4));
+        assert_eq!(report.net_target_usd, dec!(-5));
+        assert_eq!(report.net_drift_usd, dec!(1));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = ExposureReport::from_positions(&[], &HashMap::new(), &HedgeConfig::default());
+        assert_eq!(report, ExposureReport::default());
+        assert_eq!(report.net_delta_pct(), Decimal::ZERO);
+    }
+}
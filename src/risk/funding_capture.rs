@@ -0,0 +1,300 @@
+//! Funding capture efficiency.
+//!
+//! Funding verification compares each payment with what the position as held
+//! should have earned. Capture efficiency asks a wider question: how much of the
+//! funding an ideal hold would have collected actually arrived. The ideal is the
+//! target size held at its target notional through every settlement since the
+//! opportunity was first allocated. The shortfall splits into three parts:
+//! settlements missed between allocation and entry (late entry), quantity below
+//! target after partial fills or reductions (reduction), and notional moving away
+//! from target with the price (drift).
+
+use crate::exchange::FundingSchedule;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Funding captured by one position against an ideal hold at target size.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaptureLedger {
+    pub symbol: String,
+    /// Notional the position was sized for at entry
+    pub target_value: Decimal,
+    /// Quantity the position was sized for at entry
+    pub target_qty: Decimal,
+    /// When the opportunity was first allocated
+    pub allocated_at: DateTime<Utc>,
+    pub opened_at: DateTime<Utc>,
+    /// Settlements collected while held
+    pub settlements: u32,
+    /// Settlements missed between allocation and entry
+    pub missed_settlements: u32,
+    /// Funding an ideal hold at target size would have received
+    pub theoretical: Decimal,
+    pub received: Decimal,
+    pub late_entry_loss: Decimal,
+    pub reduction_loss: Decimal,
+    pub drift_loss: Decimal,
+}
+
+impl CaptureLedger {
+    /// Received over theoretical, once the ideal hold would have earned anything.
+    pub fn efficiency(&self) -> Option<Decimal> {
+        (self.theoretical > Decimal::ZERO).then(|| self.received / self.theoretical)
+    }
+
+    /// Funding the ideal hold would have received on top of what arrived.
+    pub fn shortfall(&self) -> Decimal {
+        self.theoretical - self.received
+    }
+}
+
+/// Capture totals across positions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CaptureSummary {
+    pub positions: usize,
+    pub theoretical: Decimal,
+    pub received: Decimal,
+    pub late_entry_loss: Decimal,
+    pub reduction_loss: Decimal,
+    pub drift_loss: Decimal,
+}
+
+impl CaptureSummary {
+    pub fn from_ledgers<'a>(ledgers: impl IntoIterator<Item = &'a CaptureLedger>) -> Self {
+        let mut summary = Self::default();
+        for ledger in ledgers {
+            summary.positions += 1;
+            summary.theoretical += ledger.theoretical;
+            summary.received += ledger.received;
+            summary.late_entry_loss += ledger.late_entry_loss;
+            summary.reduction_loss += ledger.reduction_loss;
+            summary.drift_loss += ledger.drift_loss;
+        }
+        summary
+    }
+
+    pub fn efficiency(&self) -> Option<Decimal> {
+        (self.theoretical > Decimal::ZERO).then(|| self.received / self.theoretical)
+    }
+}
+
+/// Tracks capture ledgers of held positions and when candidates were allocated.
+#[derive(Debug, Clone, Default)]
+pub struct FundingCaptureTracker {
+    ledgers: HashMap<String, CaptureLedger>,
+    /// First allocation time of symbols not yet entered
+    allocated: HashMap<String, DateTime<Utc>>,
+}
+
+impl FundingCaptureTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record this cycle's allocated symbols.
+    ///
+    /// Keeps the first allocation time of each symbol not yet held and forgets
+    /// symbols that dropped out of the allocation.
+    pub fn note_allocated<'a>(
+        &mut self,
+        symbols: impl IntoIterator<Item = &'a str>,
+        now: DateTime<Utc>,
+    ) {
+        let current: HashSet<&str> = symbols.into_iter().collect();
+        self.allocated
+            .retain(|symbol, _| current.contains(symbol.as_str()));
+        for symbol in current {
+            if !self.ledgers.contains_key(symbol) {
+                self.allocated.entry(symbol.to_string()).or_insert(now);
+            }
+        }
+    }
+
+    /// Start a ledger for a newly entered position.
+    ///
+    /// Settlements between the first allocation and `opened_at` count as missed,
+    /// each worth the target notional at `expected_rate`.
+    pub fn open(
+        &mut self,
+        symbol: &str,
+        target_value: Decimal,
+        target_qty: Decimal,
+        expected_rate: Decimal,
+        schedule: FundingSchedule,
+        opened_at: DateTime<Utc>,
+    ) -> &CaptureLedger {
+        let allocated_at = self
+            .allocated
+            .remove(symbol)
+            .filter(|at| *at < opened_at)
+            .unwrap_or(opened_at);
+        let missed = schedule.settlements_between(allocated_at, opened_at).len() as u32;
+        let late_entry_loss = target_value * expected_rate.abs() * Decimal::from(missed);
+        self.ledgers.insert(
+            symbol.to_string(),
+            CaptureLedger {
+                symbol: symbol.to_string(),
+                target_value,
+                target_qty: target_qty.abs(),
+                allocated_at,
+                opened_at,
+                settlements: 0,
+                missed_settlements: missed,
+                theoretical: late_entry_loss,
+                received: Decimal::ZERO,
+                late_entry_loss,
+                reduction_loss: Decimal::ZERO,
+                drift_loss: Decimal::ZERO,
+            },
+        );
+        &self.ledgers[symbol]
+    }
+
+    /// Record a settlement paid on `qty` held at `mark_price`.
+    ///
+    /// The rate is implied by the payment, so the ideal hold is credited the same
+    /// rate on the target notional and the gap is attributed to quantity and price.
+    pub fn record_settlement(
+        &mut self,
+        symbol: &str,
+        received: Decimal,
+        qty: Decimal,
+        mark_price: Decimal,
+    ) -> Option<&CaptureLedger> {
+        let ledger = self.ledgers.get_mut(symbol)?;
+        let held_value = qty.abs() * mark_price;
+        if held_value <= Decimal::ZERO {
+            return Some(ledger);
+        }
+        let rate = received / held_value;
+        let target_qty_value = ledger.target_qty * mark_price;
+
+        ledger.settlements += 1;
+        ledger.received += received;
+        ledger.theoretical += ledger.target_value * rate;
+        ledger.reduction_loss += (target_qty_value - held_value) * rate;
+        ledger.drift_loss += (ledger.target_value - target_qty_value) * rate;
+        Some(ledger)
+    }
+
+    /// Ledgers of positions no longer held, removed from tracking.
+    pub fn retain_held(&mut self, held: &[String]) -> Vec<CaptureLedger> {
+        let closed: Vec<String> = self
+            .ledgers
+            .keys()
+            .filter(|symbol| !held.contains(symbol))
+            .cloned()
+            .collect();
+        closed
+            .iter()
+            .filter_map(|symbol| self.ledgers.remove(symbol))
+            .collect()
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&CaptureLedger> {
+        self.ledgers.get(symbol)
+    }
+
+    /// Ledgers of held positions, sorted by symbol.
+    pub fn ledgers(&self) -> Vec<&CaptureLedger> {
+        let mut ledgers: Vec<&CaptureLedger> = self.ledgers.values().collect();
+        ledgers.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        ledgers
+    }
+
+    pub fn summary(&self) -> CaptureSummary {
+        CaptureSummary::from_ledgers(self.ledgers.values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 20, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_late_entry_counts_missed_settlements() {
+        let mut tracker = FundingCaptureTracker::new();
+        tracker.note_allocated(["ALTUSDT", "BTCUSDT"], at(7, 0));
+        // BTCUSDT drops out and is forgotten; ALTUSDT keeps its first allocation
+        tracker.note_allocated(["ALTUSDT"], at(9, 0));
+        assert!(!tracker.allocated.contains_key("BTCUSDT"));
+
+        let ledger = tracker.open(
+            "ALTUSDT",
+            dec!(1000),
+            dec!(10),
+            dec!(0.001),
+            FundingSchedule::BINANCE_DEFAULT,
+            at(16, 30),
+        );
+        // Missed 08:00 and 16:00
+        assert_eq!(ledger.allocated_at, at(7, 0));
+        assert_eq!(ledger.missed_settlements, 2);
+        assert_eq!(ledger.late_entry_loss, dec!(2));
+        assert_eq!(ledger.efficiency(), Some(Decimal::ZERO));
+
+        // Entered right away: nothing missed
+        let ledger = tracker.open(
+            "ETHUSDT",
+            dec!(1000),
+            dec!(1),
+            dec!(0.001),
+            FundingSchedule::BINANCE_DEFAULT,
+            at(16, 30) + Duration::minutes(5),
+        );
+        assert_eq!(ledger.missed_settlements, 0);
+        assert_eq!(ledger.efficiency(), None);
+    }
+
+    #[test]
+    fn test_shortfall_attributed_to_reduction_and_drift() {
+        let mut tracker = FundingCaptureTracker::new();
+        tracker.open(
+            "ALTUSDT",
+            dec!(1000),
+            dec!(10),
+            dec!(0.001),
+            FundingSchedule::BINANCE_DEFAULT,
+            at(7, 50),
+        );
+
+        // Held at target: full capture
+        let ledger = tracker
+            .record_settlement("ALTUSDT", dec!(1), dec!(-10), dec!(100))
+            .unwrap();
+        assert_eq!(ledger.efficiency(), Some(Decimal::ONE));
+
+        // Half the quantity after a reduction, price down 10%
+        let ledger = tracker
+            .record_settlement("ALTUSDT", dec!(0.45), dec!(5), dec!(90))
+            .unwrap();
+        assert_eq!(ledger.theoretical, dec!(2));
+        assert_eq!(ledger.received, dec!(1.45));
+        assert_eq!(ledger.reduction_loss, dec!(0.45));
+        assert_eq!(ledger.drift_loss, dec!(0.10));
+        assert_eq!(
+            ledger.shortfall(),
+            ledger.late_entry_loss + ledger.reduction_loss + ledger.drift_loss
+        );
+
+        let summary = tracker.summary();
+        assert_eq!(summary.positions, 1);
+        assert_eq!(summary.efficiency(), Some(dec!(0.725)));
+
+        // Closing hands back the final ledger
+        let closed = tracker.retain_held(&[]);
+        assert_eq!(closed.len(), 1);
+        assert!(tracker.get("ALTUSDT").is_none());
+        assert!(tracker
+            .record_settlement("ALTUSDT", dec!(1), dec!(10), dec!(100))
+            .is_none());
+    }
+}
@@ -0,0 +1,153 @@
+//! Quarantine of symbols whose funding keeps deviating from expectations.
+//!
+//! A single anomalous payment is often noise: a rate that moved late in the
+//! interval, or a settlement that landed in the next poll. A run of them means the
+//! symbol's funding can't be predicted, so the allocator can't size it. After
+//! `anomalies` consecutive anomalies the symbol is quarantined: it gets no new
+//! entries or top-ups, and optionally its position is closed. A quarantine lasts
+//! until the operator releases it.
+
+use crate::config::FundingQuarantineConfig;
+use crate::persistence::PersistedQuarantine;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+/// Tracks anomaly streaks per symbol and the symbols they quarantined.
+#[derive(Debug)]
+pub struct FundingQuarantine {
+    config: FundingQuarantineConfig,
+    /// Consecutive anomalous payments per symbol
+    streaks: HashMap<String, u32>,
+    quarantined: BTreeMap<String, PersistedQuarantine>,
+}
+
+impl FundingQuarantine {
+    pub fn new(config: FundingQuarantineConfig) -> Self {
+        Self {
+            config,
+            streaks: HashMap::new(),
+            quarantined: BTreeMap::new(),
+        }
+    }
+
+    /// Record a verified payment of `symbol`.
+    ///
+    /// Returns the quarantine when this payment completes a streak of anomalies.
+    /// A payment within expectations ends the streak.
+    pub fn record(
+        &mut self,
+        symbol: &str,
+        is_anomaly: bool,
+        deviation: Decimal,
+        now: DateTime<Utc>,
+    ) -> Option<PersistedQuarantine> {
+        if !self.config.enabled {
+            return None;
+        }
+        if !is_anomaly {
+            self.streaks.remove(symbol);
+            return None;
+        }
+
+        let streak = self.streaks.entry(symbol.to_string()).or_insert(0);
+        *streak += 1;
+        if *streak < self.config.anomalies || self.quarantined.contains_key(symbol) {
+            return None;
+        }
+
+        let quarantine = PersistedQuarantine {
+            symbol: symbol.to_string(),
+            quarantined_at: now,
+            anomalies: *streak,
+            last_deviation: deviation,
+        };
+        self.quarantined
+            .insert(symbol.to_string(), quarantine.clone());
+        Some(quarantine)
+    }
+
+    pub fn is_quarantined(&self, symbol: &str) -> bool {
+        self.quarantined.contains_key(symbol)
+    }
+
+    /// Whether the held position of `symbol` should be closed.
+    pub fn requires_exit(&self, symbol: &str) -> bool {
+        self.config.exit && self.is_quarantined(symbol)
+    }
+
+    /// Quarantined symbols, alphabetically.
+    pub fn quarantined(&self) -> Vec<&PersistedQuarantine> {
+        self.quarantined.values().collect()
+    }
+
+    /// Reinstate a quarantine persisted by an earlier run.
+    pub fn restore(&mut self, quarantine: PersistedQuarantine) {
+        self.quarantined
+            .insert(quarantine.symbol.clone(), quarantine);
+    }
+
+    /// Release a quarantine; the symbol starts a fresh streak. Returns whether it
+    /// was quarantined.
+    pub fn release(&mut self, symbol: &str) -> bool {
+        self.streaks.remove(symbol);
+        self.quarantined.remove(symbol).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_consecutive_anomalies_quarantine() {
+        let now = Utc::now();
+        let mut quarantine = FundingQuarantine::new(FundingQuarantineConfig::default());
+
+        // A normal payment breaks the streak
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.3), now).is_none());
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.3), now).is_none());
+        assert!(quarantine
+            .record("BTCUSDT", false, dec!(0.05), now)
+            .is_none());
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.3), now).is_none());
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.3), now).is_none());
+        assert!(!quarantine.is_quarantined("BTCUSDT"));
+
+        let entry = quarantine.record("BTCUSDT", true, dec!(0.45), now).unwrap();
+        assert_eq!(entry.anomalies, 3);
+        assert_eq!(entry.last_deviation, dec!(0.45));
+        assert!(quarantine.is_quarantined("BTCUSDT"));
+        // Exits only when configured
+        assert!(!quarantine.requires_exit("BTCUSDT"));
+
+        // Further anomalies don't quarantine it again
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.5), now).is_none());
+        assert_eq!(quarantine.quarantined().len(), 1);
+
+        assert!(quarantine.release("BTCUSDT"));
+        assert!(!quarantine.release("BTCUSDT"));
+        assert!(quarantine.record("BTCUSDT", true, dec!(0.3), now).is_none());
+    }
+
+    #[test]
+    fn test_exit_and_disabled() {
+        let now = Utc::now();
+        let mut quarantine = FundingQuarantine::new(FundingQuarantineConfig {
+            anomalies: 1,
+            exit: true,
+            ..Default::default()
+        });
+        assert!(quarantine.record("ETHUSDT", true, dec!(0.3), now).is_some());
+        assert!(quarantine.requires_exit("ETHUSDT"));
+
+        let mut disabled = FundingQuarantine::new(FundingQuarantineConfig {
+            enabled: false,
+            anomalies: 1,
+            exit: true,
+        });
+        assert!(disabled.record("ETHUSDT", true, dec!(0.3), now).is_none());
+        assert!(!disabled.requires_exit("ETHUSDT"));
+    }
+}
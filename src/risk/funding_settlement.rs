@@ -0,0 +1,189 @@
+//! Confirmation of funding settlements by the exchange.
+//!
+//! Funding is only income once the exchange has paid it. Each settlement of a held
+//! position is recorded as expected when it falls due and stays pending until a
+//! payment the exchange reports for the same symbol and settlement time confirms
+//! it. Pending settlements are persisted, so confirmation survives restarts, and a
+//! settlement still unconfirmed after the grace period is a reconciliation gap.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+
+/// Furthest an exchange payment may lie from its settlement time and still match.
+const MATCH_TOLERANCE_MINUTES: i64 = 5;
+
+/// A settlement awaiting confirmation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSettlement {
+    /// Persisted funding event id; `None` if it could not be persisted
+    pub event_id: Option<i64>,
+    pub symbol: String,
+    pub settled_at: DateTime<Utc>,
+    /// Projected payment
+    pub expected: Decimal,
+    /// Position notional at settlement, when known
+    pub position_value: Option<Decimal>,
+    /// Whether the missing confirmation was already reported
+    reported: bool,
+}
+
+impl PendingSettlement {
+    pub fn new(
+        event_id: Option<i64>,
+        symbol: &str,
+        settled_at: DateTime<Utc>,
+        expected: Decimal,
+        position_value: Option<Decimal>,
+    ) -> Self {
+        Self {
+            event_id,
+            symbol: symbol.to_string(),
+            settled_at,
+            expected,
+            position_value,
+            reported: false,
+        }
+    }
+}
+
+/// A settlement confirmed by an exchange payment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedSettlement {
+    pub settlement: PendingSettlement,
+    /// Amount the exchange paid
+    pub amount: Decimal,
+}
+
+/// Settlements awaiting confirmation by the exchange.
+#[derive(Debug)]
+pub struct FundingSettlements {
+    pending: Vec<PendingSettlement>,
+    /// Time after settlement before a missing payment is a gap
+    grace: Duration,
+}
+
+impl FundingSettlements {
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            pending: Vec::new(),
+            grace,
+        }
+    }
+
+    /// Track a settlement that fell due.
+    pub fn expect(&mut self, settlement: PendingSettlement) {
+        self.pending.push(settlement);
+    }
+
+    /// Settlements awaiting confirmation, oldest first.
+    pub fn pending(&self) -> &[PendingSettlement] {
+        &self.pending
+    }
+
+    /// Settlement time of the oldest pending settlement, to fetch payments from.
+    pub fn oldest(&self) -> Option<DateTime<Utc>> {
+        self.pending.iter().map(|s| s.settled_at).min()
+    }
+
+    /// Match exchange payments `(symbol, time, amount)` against pending settlements.
+    ///
+    /// Each payment confirms at most one settlement of its symbol within a few
+    /// minutes of it. Confirmed settlements stop being pending.
+    pub fn confirm(
+        &mut self,
+        payments: &[(String, DateTime<Utc>, Decimal)],
+    ) -> Vec<ConfirmedSettlement> {
+        let tolerance = Duration::minutes(MATCH_TOLERANCE_MINUTES);
+        let mut used = vec![false; payments.len()];
+        let mut confirmed = Vec::new();
+
+        self.pending.retain(|settlement| {
+            let matched = payments.iter().enumerate().position(|(idx, payment)| {
+                !used[idx]
+                    && payment.0 == settlement.symbol
+                    && (payment.1 - settlement.settled_at).abs() <= tolerance
+            });
+            let Some(idx) = matched else {
+                return true;
+            };
+            used[idx] = true;
+            confirmed.push(ConfirmedSettlement {
+                settlement: settlement.clone(),
+                amount: payments[idx].2,
+            });
+            false
+        });
+
+        confirmed
+    }
+
+    /// Settlements newly past the grace period without a payment.
+    ///
+    /// Each gap is returned once; it stays pending in case the payment is late.
+    pub fn newly_overdue(&mut self, now: DateTime<Utc>) -> Vec<PendingSettlement> {
+        let mut overdue = Vec::new();
+        for settlement in &mut self.pending {
+            if !settlement.reported && now - settlement.settled_at > self.grace {
+                settlement.reported = true;
+                overdue.push(settlement.clone());
+            }
+        }
+        overdue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_confirmation_matches_symbol_and_time() {
+        let settled_at = Utc::now() - Duration::hours(1);
+        let mut settlements = FundingSettlements::new(Duration::minutes(30));
+        settlements.expect(PendingSettlement::new(
+            Some(1),
+            "BTCUSDT",
+            settled_at,
+            dec!(5),
+            Some(dec!(50000)),
+        ));
+        settlements.expect(PendingSettlement::new(
+            Some(2),
+            "ETHUSDT",
+            settled_at,
+            dec!(2),
+            None,
+        ));
+        assert_eq!(settlements.oldest(), Some(settled_at));
+
+        let confirmed = settlements.confirm(&[
+            // Previous settlement of the same symbol doesn't match
+            (
+                "BTCUSDT".to_string(),
+                settled_at - Duration::hours(8),
+                dec!(4),
+            ),
+            (
+                "BTCUSDT".to_string(),
+                settled_at + Duration::seconds(2),
+                dec!(4.8),
+            ),
+        ]);
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!(confirmed[0].settlement.event_id, Some(1));
+        assert_eq!(confirmed[0].amount, dec!(4.8));
+        assert_eq!(settlements.pending().len(), 1);
+
+        // ETH stays pending and is reported once past the grace period
+        let overdue = settlements.newly_overdue(Utc::now());
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].symbol, "ETHUSDT");
+        assert!(settlements.newly_overdue(Utc::now()).is_empty());
+
+        // A late payment still confirms it
+        let confirmed = settlements.confirm(&[("ETHUSDT".to_string(), settled_at, dec!(2.1))]);
+        assert_eq!(confirmed.len(), 1);
+        assert!(settlements.pending().is_empty());
+    }
+}
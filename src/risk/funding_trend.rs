@@ -0,0 +1,231 @@
+//! Funding rate trajectory monitoring for open positions.
+//!
+//! The predicted funding rate updates on every scan, long before the realized
+//! payment lands. Tracking its trajectory lets us flag positions whose next
+//! payment has already collapsed instead of waiting for verification to fail.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::debug;
+
+/// A held symbol whose predicted funding has collapsed relative to entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingCollapse {
+    pub symbol: String,
+    /// Funding rate at entry
+    pub entry_rate: Decimal,
+    /// Latest predicted funding rate
+    pub predicted_rate: Decimal,
+    /// Change in predicted rate per hour over the monitoring window
+    pub change_per_hour: Decimal,
+}
+
+/// Tracks predicted funding rates of held symbols between settlements.
+pub struct FundingTrendMonitor {
+    /// Predicted rate below this fraction of the entry rate counts as collapsed
+    collapse_ratio: Decimal,
+    /// Window for rate-of-change calculation
+    window: Duration,
+    /// Funding rate at entry per symbol
+    entry_rates: HashMap<String, Decimal>,
+    /// Predicted rates per symbol, oldest first
+    predictions: HashMap<String, VecDeque<(DateTime<Utc>, Decimal)>>,
+    /// Symbols already flagged, so each collapse is reported once
+    flagged: HashSet<String>,
+}
+
+impl FundingTrendMonitor {
+    /// Create a new monitor.
+    pub fn new(collapse_ratio: Decimal) -> Self {
+        Self {
+            collapse_ratio,
+            window: Duration::hours(8),
+            entry_rates: HashMap::new(),
+            predictions: HashMap::new(),
+            flagged: HashSet::new(),
+        }
+    }
+
+    /// Set the window used for the rate-of-change calculation.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Start monitoring a symbol at its entry funding rate.
+    pub fn track(&mut self, symbol: &str, entry_rate: Decimal) {
+        self.entry_rates.insert(symbol.to_string(), entry_rate);
+        self.predictions.remove(symbol);
+        self.flagged.remove(symbol);
+    }
+
+    /// Stop monitoring a symbol.
+    pub fn untrack(&mut self, symbol: &str) {
+        self.entry_rates.remove(symbol);
+        self.predictions.remove(symbol);
+        self.flagged.remove(symbol);
+    }
+
+    /// Record a predicted funding rate observed now.
+    pub fn record(&mut self, symbol: &str, predicted_rate: Decimal) {
+        self.record_at(symbol, predicted_rate, Utc::now());
+    }
+
+    /// Record a predicted funding rate observed at `timestamp`.
+    pub fn record_at(&mut self, symbol: &str, predicted_rate: Decimal, timestamp: DateTime<Utc>) {
+        if !self.entry_rates.contains_key(symbol) {
+            return;
+        }
+
+        let samples = self.predictions.entry(symbol.to_string()).or_default();
+        samples.push_back((timestamp, predicted_rate));
+
+        let cutoff = timestamp - self.window;
+        while samples.front().is_some_and(|(t, _)| *t < cutoff) {
+            samples.pop_front();
+        }
+    }
+
+    /// Latest predicted funding rate for a symbol.
+    pub fn latest_rate(&self, symbol: &str) -> Option<Decimal> {
+        self.predictions
+            .get(symbol)
+            .and_then(|samples| samples.back())
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Change in predicted rate per hour across the window.
+    /// Returns None until two samples at least a minute apart exist.
+    pub fn rate_of_change(&self, symbol: &str) -> Option<Decimal> {
+        let samples = self.predictions.get(symbol)?;
+        let (first_at, first_rate) = samples.front()?;
+        let (last_at, last_rate) = samples.back()?;
+
+        let minutes = (*last_at - *first_at).num_minutes();
+        if minutes < 1 {
+            return None;
+        }
+
+        Some((last_rate - first_rate) * Decimal::from(60) / Decimal::from(minutes))
+    }
+
+    /// Whether the latest prediction has collapsed relative to the entry rate.
+    ///
+    /// A prediction collapses when it flips sign or its magnitude falls below
+    /// `collapse_ratio` of the entry rate.
+    pub fn is_collapsed(&self, symbol: &str) -> bool {
+        let (Some(&entry_rate), Some(predicted)) =
+            (self.entry_rates.get(symbol), self.latest_rate(symbol))
+        else {
+            return false;
+        };
+        if entry_rate.is_zero() {
+            return false;
+        }
+
+        let flipped =
+            predicted.is_sign_negative() != entry_rate.is_sign_negative() && !predicted.is_zero();
+        flipped || predicted.abs() < entry_rate.abs() * self.collapse_ratio
+    }
+
+    /// Newly collapsed symbols since the last check.
+    ///
+    /// Each collapse is reported once; a symbol is re-armed when its predicted
+    /// rate recovers.
+    pub fn check(&mut self) -> Vec<FundingCollapse> {
+        let mut collapses = Vec::new();
+        let symbols: Vec<String> = self.entry_rates.keys().cloned().collect();
+
+        for symbol in symbols {
+            if !self.is_collapsed(&symbol) {
+                if self.flagged.remove(&symbol) {
+                    debug!(symbol = %symbol, "Predicted funding recovered");
+                }
+                continue;
+            }
+            if !self.flagged.insert(symbol.clone()) {
+                continue;
+            }
+
+            collapses.push(FundingCollapse {
+                entry_rate: self.entry_rates[&symbol],
+                predicted_rate: self.latest_rate(&symbol).unwrap_or_default(),
+                change_per_hour: self.rate_of_change(&symbol).unwrap_or_default(),
+                symbol,
+            });
+        }
+
+        collapses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_collapse_flagged_once_and_rearmed() {
+        let mut monitor = FundingTrendMonitor::new(dec!(0.5));
+        let start = Utc::now() - Duration::hours(2);
+        monitor.track("BTCUSDT", dec!(0.001));
+
+        monitor.record_at("BTCUSDT", dec!(0.0009), start);
+        assert!(monitor.check().is_empty());
+
+        monitor.record_at("BTCUSDT", dec!(0.0003), start + Duration::hours(2));
+        let collapses = monitor.check();
+        assert_eq!(collapses.len(), 1);
+        assert_eq!(collapses[0].predicted_rate, dec!(0.0003));
+        assert_eq!(collapses[0].change_per_hour, dec!(-0.0003));
+
+        // Reported only once while collapsed
+        assert!(monitor.check().is_empty());
+
+        // Recovery re-arms the symbol
+        monitor.record_at("BTCUSDT", dec!(0.0008), start + Duration::hours(2));
+        assert!(monitor.check().is_empty());
+        monitor.record_at("BTCUSDT", dec!(0.0001), start + Duration::hours(2));
+        assert_eq!(monitor.check().len(), 1);
+    }
+
+    #[test]
+    fn test_sign_flip_is_collapse() {
+        let mut monitor = FundingTrendMonitor::new(dec!(0.5));
+        monitor.track("ETHUSDT", dec!(-0.0005));
+
+        monitor.record("ETHUSDT", dec!(-0.0004));
+        assert!(!monitor.is_collapsed("ETHUSDT"));
+
+        monitor.record("ETHUSDT", dec!(0.0004));
+        assert!(monitor.is_collapsed("ETHUSDT"));
+    }
+
+    #[test]
+    fn test_untracked_symbols_ignored() {
+        let mut monitor = FundingTrendMonitor::new(dec!(0.5));
+        monitor.record("BTCUSDT", dec!(0.0001));
+        assert!(monitor.latest_rate("BTCUSDT").is_none());
+
+        monitor.track("BTCUSDT", dec!(0.001));
+        monitor.record("BTCUSDT", dec!(0.0001));
+        monitor.untrack("BTCUSDT");
+        assert!(monitor.check().is_empty());
+    }
+
+    #[test]
+    fn test_window_prunes_old_samples() {
+        let mut monitor = FundingTrendMonitor::new(dec!(0.5)).with_window(Duration::hours(1));
+        let now = Utc::now();
+        monitor.track("BTCUSDT", dec!(0.001));
+
+        monitor.record_at("BTCUSDT", dec!(0.002), now - Duration::hours(3));
+        monitor.record_at("BTCUSDT", dec!(0.001), now - Duration::minutes(30));
+        monitor.record_at("BTCUSDT", dec!(0.0008), now);
+
+        // Only the last two samples are inside the window
+        assert_eq!(monitor.rate_of_change("BTCUSDT"), Some(dec!(-0.0004)));
+    }
+}
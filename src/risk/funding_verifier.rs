@@ -5,20 +5,48 @@
 //! - Missed funding payments
 //! - Execution timing issues (entered after snapshot)
 //! - Exchange calculation discrepancies
+//!
+//! Binance settles funding at the time-weighted average of the premium index over
+//! the interval, not at the rate predicted just before settlement. Predicted rates
+//! of held symbols are sampled every cycle, and a payment is checked against the
+//! time-weighted average of the samples over the interval(s) it settles.
 
-use chrono::{DateTime, Utc};
+use crate::exchange::{FundingCalendar, Venue};
+use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use tracing::{debug, warn};
 
+/// What the expected rate of a payment was estimated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ExpectationBasis {
+    /// Time-weighted predicted rate over the settled interval(s)
+    IntervalTwap,
+    /// Time-weighted rate over the rolling window
+    RollingAverage,
+    /// Rate at position entry
+    EntryRate,
+}
+
+impl std::fmt::Display for ExpectationBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpectationBasis::IntervalTwap => write!(f, "interval TWAP"),
+            ExpectationBasis::RollingAverage => write!(f, "rolling average"),
+            ExpectationBasis::EntryRate => write!(f, "entry rate"),
+        }
+    }
+}
+
 /// Records a funding payment for verification.
 #[derive(Debug, Clone, Serialize)]
 pub struct FundingRecord {
     pub symbol: String,
     pub timestamp: DateTime<Utc>,
     pub expected_rate: Decimal,
+    pub basis: ExpectationBasis,
     pub actual_received: Decimal,
     pub expected_amount: Decimal,
     pub position_value: Decimal,
@@ -32,6 +60,8 @@ pub struct FundingVerificationResult {
     pub funding_received: Decimal,
     pub funding_expected: Decimal,
     pub deviation_pct: Decimal,
+    /// What the expectation was estimated from
+    pub basis: ExpectationBasis,
     pub is_anomaly: bool,
     pub anomaly_reason: Option<String>,
 }
@@ -49,11 +79,25 @@ pub struct FundingStats {
 }
 
 /// Verifies funding payments match expectations.
+///
+/// Expectations use the time-weighted average of rates observed over the settled
+/// interval, then over a rolling window, then the entry-time rate until rates have
+/// been observed. This keeps normal rate drift from being flagged as an anomaly.
 pub struct FundingVerifier {
     /// Maximum allowed deviation before flagging as anomaly
     max_deviation: Decimal,
     /// Expected funding rates per symbol (set at position entry)
     expected_rates: HashMap<String, Decimal>,
+    /// Observed funding rates per symbol, oldest first
+    observed_rates: HashMap<String, VecDeque<(DateTime<Utc>, Decimal)>>,
+    /// Rolling window for the time-weighted expected rate
+    rate_window: Duration,
+    /// Settlement schedules, to know how many settlements a payment covers
+    calendar: FundingCalendar,
+    /// Venue whose schedules apply
+    venue: Venue,
+    /// Time of the last verified or imported payment per symbol
+    last_payment: HashMap<String, DateTime<Utc>>,
     /// History of funding records
     history: Vec<FundingRecord>,
     /// Maximum history size
@@ -68,6 +112,11 @@ impl FundingVerifier {
         Self {
             max_deviation,
             expected_rates: HashMap::new(),
+            observed_rates: HashMap::new(),
+            rate_window: Duration::hours(24),
+            calendar: FundingCalendar::new(),
+            venue: Venue::Binance,
+            last_payment: HashMap::new(),
             history: Vec::new(),
             max_history: 1000,
             stats: HashMap::new(),
@@ -84,9 +133,131 @@ impl FundingVerifier {
         );
     }
 
+    /// Set the rolling window used for time-weighted expected rates.
+    pub fn with_rate_window(mut self, window: Duration) -> Self {
+        self.rate_window = window;
+        self
+    }
+
+    /// Set the settlement schedules payments on `venue` are checked against.
+    pub fn set_calendar(&mut self, calendar: FundingCalendar, venue: Venue) {
+        self.calendar = calendar;
+        self.venue = venue;
+    }
+
     /// Clear expected rate (when position is closed).
     pub fn clear_expected_rate(&mut self, symbol: &str) {
         self.expected_rates.remove(symbol);
+        self.observed_rates.remove(symbol);
+        self.last_payment.remove(symbol);
+    }
+
+    /// Settlements of the symbol's schedule since its last payment, at least one.
+    ///
+    /// A payment arriving after skipped settlements covers all of them.
+    pub fn settlements_since_last_payment(&self, symbol: &str, now: DateTime<Utc>) -> u32 {
+        let Some(&last) = self.last_payment.get(symbol) else {
+            return 1;
+        };
+        let settlements = self
+            .calendar
+            .schedule(self.venue, symbol)
+            .settlements_between(last, now)
+            .len();
+        settlements.max(1) as u32
+    }
+
+    /// Record a funding rate observed now (e.g. from a market scan).
+    pub fn record_observed_rate(&mut self, symbol: &str, rate: Decimal) {
+        self.record_observed_rate_at(symbol, rate, Utc::now());
+    }
+
+    /// Record a funding rate observed at `timestamp`.
+    pub fn record_observed_rate_at(
+        &mut self,
+        symbol: &str,
+        rate: Decimal,
+        timestamp: DateTime<Utc>,
+    ) {
+        let window_start = timestamp - self.rate_window;
+        let observations = self.observed_rates.entry(symbol.to_string()).or_default();
+        observations.push_back((timestamp, rate));
+
+        // Keep one observation before the window: it is the rate in effect at window start
+        while observations.len() > 1 && observations[1].0 <= window_start {
+            observations.pop_front();
+        }
+    }
+
+    /// Time-weighted average of rates observed in the window ending at `now`.
+    ///
+    /// Each observation is weighted by how long it was the latest known rate.
+    /// Returns `None` if no rates have been observed for the symbol.
+    pub fn time_weighted_rate(&self, symbol: &str, now: DateTime<Utc>) -> Option<Decimal> {
+        self.time_weighted_rate_between(symbol, now - self.rate_window, now)
+    }
+
+    /// Time-weighted average of rates in effect between `start` and `end`.
+    ///
+    /// Observations after `end` are ignored. Returns `None` if no rate had been
+    /// observed by `end`.
+    pub fn time_weighted_rate_between(
+        &self,
+        symbol: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Option<Decimal> {
+        let observations = self.observed_rates.get(symbol)?;
+        let mut weighted_sum = Decimal::ZERO;
+        let mut total_weight = Decimal::ZERO;
+        let mut latest = None;
+
+        for (i, (timestamp, rate)) in observations.iter().enumerate() {
+            if *timestamp > end {
+                break;
+            }
+            latest = Some(*rate);
+            let from = (*timestamp).max(start);
+            let to = observations
+                .get(i + 1)
+                .map(|(next, _)| *next)
+                .unwrap_or(end)
+                .min(end);
+            if to <= from {
+                continue;
+            }
+
+            let weight = Decimal::from((to - from).num_seconds());
+            weighted_sum += *rate * weight;
+            total_weight += weight;
+        }
+
+        if total_weight > Decimal::ZERO {
+            Some(weighted_sum / total_weight)
+        } else {
+            // Only observed at `end`: that rate is the one in effect
+            latest
+        }
+    }
+
+    /// Time-weighted predicted rate over the `settlements` intervals settled by `now`.
+    pub fn interval_twap(
+        &self,
+        symbol: &str,
+        settlements: u32,
+        now: DateTime<Utc>,
+    ) -> Option<Decimal> {
+        let schedule = self.calendar.schedule(self.venue, symbol);
+        let end = schedule.last_settlement(now);
+        let start = end - schedule.interval() * settlements as i32;
+        self.time_weighted_rate_between(symbol, start, end)
+    }
+
+    /// Rate to verify the next payment against: rolling average, else entry rate.
+    pub fn expected_rate(&self, symbol: &str) -> Decimal {
+        self.time_weighted_rate(symbol, Utc::now())
+            .or_else(|| self.expected_rates.get(symbol).copied())
+            .unwrap_or(Decimal::ZERO)
     }
 
     /// Verify a funding payment.
@@ -96,15 +267,33 @@ impl FundingVerifier {
         position_value: Decimal,
         actual_received: Decimal,
     ) -> FundingVerificationResult {
-        let expected_rate = self
-            .expected_rates
-            .get(symbol)
-            .copied()
-            .unwrap_or(Decimal::ZERO);
+        self.verify_funding_at(symbol, position_value, actual_received, Utc::now())
+    }
+
+    /// Verify a funding payment received at `now`.
+    pub fn verify_funding_at(
+        &mut self,
+        symbol: &str,
+        position_value: Decimal,
+        actual_received: Decimal,
+        now: DateTime<Utc>,
+    ) -> FundingVerificationResult {
+        let settlements = self.settlements_since_last_payment(symbol, now);
+        let (expected_rate, basis) = match self.interval_twap(symbol, settlements, now) {
+            Some(rate) => (rate, ExpectationBasis::IntervalTwap),
+            None => match self.time_weighted_rate(symbol, now) {
+                Some(rate) => (rate, ExpectationBasis::RollingAverage),
+                None => (
+                    self.expected_rates.get(symbol).copied().unwrap_or_default(),
+                    ExpectationBasis::EntryRate,
+                ),
+            },
+        };
+        self.last_payment.insert(symbol.to_string(), now);
 
-        // Expected amount = position_value * funding_rate
+        // Expected amount = position_value * funding_rate per settlement covered
         // For shorts, funding rate > 0 means we receive payment
-        let expected_amount = position_value * expected_rate.abs();
+        let expected_amount = position_value * expected_rate.abs() * Decimal::from(settlements);
 
         // Calculate deviation
         let deviation_pct = if expected_amount != Decimal::ZERO {
@@ -123,8 +312,9 @@ impl FundingVerifier {
         // Record the funding
         let record = FundingRecord {
             symbol: symbol.to_string(),
-            timestamp: Utc::now(),
+            timestamp: now,
             expected_rate,
+            basis,
             actual_received,
             expected_amount,
             position_value,
@@ -152,6 +342,7 @@ impl FundingVerifier {
                 symbol = %symbol,
                 actual = %actual_received,
                 expected = %expected_amount,
+                %basis,
                 deviation = %deviation_pct,
                 reason = ?anomaly_reason,
                 "Funding anomaly detected"
@@ -170,11 +361,61 @@ impl FundingVerifier {
             funding_received: actual_received,
             funding_expected: expected_amount,
             deviation_pct,
+            basis,
             is_anomaly,
             anomaly_reason,
         }
     }
 
+    /// Import a historical payment (e.g. exchange income history after a restart).
+    ///
+    /// The expectation at the time is unknown, so the payment counts as matching
+    /// it. The implied rate also seeds the rolling expected rate. A payment is
+    /// signed by whether it was received, the market rate by who pays: long
+    /// futures receive negative rates, so their implied rate is negated to sit
+    /// alongside observed market rates.
+    pub fn import_payment(
+        &mut self,
+        symbol: &str,
+        position_value: Decimal,
+        amount: Decimal,
+        long_futures: bool,
+        timestamp: DateTime<Utc>,
+    ) {
+        let received_rate = if position_value > Decimal::ZERO {
+            amount / position_value
+        } else {
+            Decimal::ZERO
+        };
+        let implied_rate = if long_futures {
+            -received_rate
+        } else {
+            received_rate
+        };
+
+        self.history.push(FundingRecord {
+            symbol: symbol.to_string(),
+            timestamp,
+            expected_rate: implied_rate,
+            basis: ExpectationBasis::EntryRate,
+            actual_received: amount,
+            expected_amount: amount,
+            position_value,
+            deviation_pct: Decimal::ZERO,
+        });
+        while self.history.len() > self.max_history {
+            self.history.remove(0);
+        }
+
+        self.update_stats(symbol, amount, amount, Decimal::ZERO, false);
+        self.record_observed_rate_at(symbol, implied_rate, timestamp);
+        let last = self
+            .last_payment
+            .entry(symbol.to_string())
+            .or_insert(timestamp);
+        *last = (*last).max(timestamp);
+    }
+
     /// Check if a funding payment is anomalous.
     fn check_anomaly(
         &self,
@@ -334,6 +575,166 @@ mod tests {
         assert_eq!(stats.total_received, dec!(3));
     }
 
+    #[test]
+    fn test_time_weighted_rate_weights_by_duration() {
+        let mut verifier = FundingVerifier::new(dec!(0.20)).with_rate_window(Duration::hours(24));
+        let now = Utc::now();
+
+        // 0.0001 for 6h, then 0.0003 for the last 2h
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0001), now - Duration::hours(8));
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0003), now - Duration::hours(2));
+
+        let twa = verifier.time_weighted_rate("BTCUSDT", now).unwrap();
+        assert_eq!(twa, dec!(0.00015)); // (0.0001*6 + 0.0003*2) / 8
+        assert!(verifier.time_weighted_rate("ETHUSDT", now).is_none());
+    }
+
+    #[test]
+    fn test_time_weighted_rate_clamps_to_window() {
+        let mut verifier = FundingVerifier::new(dec!(0.20)).with_rate_window(Duration::hours(8));
+        let now = Utc::now();
+
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0010), now - Duration::hours(30));
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0002), now - Duration::hours(20));
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0004), now - Duration::hours(4));
+
+        // Stale 0.0010 pruned; 0.0002 in effect for the first 4h of the window
+        assert_eq!(verifier.observed_rates["BTCUSDT"].len(), 2);
+        let twa = verifier.time_weighted_rate("BTCUSDT", now).unwrap();
+        assert_eq!(twa, dec!(0.0003));
+    }
+
+    #[test]
+    fn test_rate_drift_not_flagged_with_rolling_average() {
+        let mut verifier = FundingVerifier::new(dec!(0.20));
+        verifier.set_expected_rate("BTCUSDT", dec!(0.0001));
+
+        // Rate drifted to 0.0002 since entry; payment reflects the new rate
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0002), Utc::now() - Duration::hours(8));
+        let result = verifier.verify_funding("BTCUSDT", dec!(10000), dec!(2));
+
+        assert!(!result.is_anomaly);
+        assert_eq!(result.funding_expected, dec!(2));
+    }
+
+    #[test]
+    fn test_settlement_verified_against_interval_twap() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let mut verifier = FundingVerifier::new(dec!(0.20));
+        verifier.set_expected_rate("BTCUSDT", dec!(0.0001));
+
+        // 0.01% for most of the interval, spiking to 0.05% in the last half hour
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0001), at("2024-03-01T00:00:00Z"));
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0001), at("2024-03-01T04:00:00Z"));
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0005), at("2024-03-01T07:30:00Z"));
+        // Sampled after settlement: belongs to the next interval
+        verifier.record_observed_rate_at("BTCUSDT", dec!(0.0009), at("2024-03-01T08:00:10Z"));
+
+        // (0.0001 * 7.5h + 0.0005 * 0.5h) / 8h
+        let result = verifier.verify_funding_at(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(1.25),
+            at("2024-03-01T08:00:30Z"),
+        );
+        assert_eq!(result.basis, ExpectationBasis::IntervalTwap);
+        assert_eq!(result.funding_expected, dec!(1.25));
+        assert!(!result.is_anomaly);
+
+        // Without samples, the entry rate is all there is
+        let result =
+            verifier.verify_funding_at("ETHUSDT", dec!(5000), dec!(0), at("2024-03-01T08:00:30Z"));
+        assert_eq!(result.basis, ExpectationBasis::EntryRate);
+    }
+
+    #[test]
+    fn test_import_payment_seeds_stats_and_rate() {
+        let mut verifier = FundingVerifier::new(dec!(0.20));
+        let now = Utc::now();
+
+        verifier.import_payment(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(2),
+            false,
+            now - Duration::hours(16),
+        );
+        verifier.import_payment(
+            "BTCUSDT",
+            dec!(10000),
+            dec!(2),
+            false,
+            now - Duration::hours(8),
+        );
+
+        let stats = verifier.get_stats("BTCUSDT").unwrap();
+        assert_eq!(stats.payment_count, 2);
+        assert_eq!(stats.total_received, dec!(4));
+        assert_eq!(stats.anomaly_count, 0);
+        assert_eq!(verifier.expected_rate("BTCUSDT"), dec!(0.0002));
+    }
+
+    #[test]
+    fn test_imported_long_futures_payments_keep_market_sign() {
+        let mut verifier = FundingVerifier::new(dec!(0.20));
+        let now = Utc::now();
+
+        let ago = |hours| now - Duration::hours(hours);
+
+        // Long futures received 2 on 10000 at a -0.02% market rate
+        verifier.record_observed_rate_at("ETHUSDT", dec!(-0.0002), ago(20));
+        verifier.import_payment("ETHUSDT", dec!(10000), dec!(2), true, ago(16));
+        verifier.import_payment("ETHUSDT", dec!(10000), dec!(2), true, ago(8));
+        verifier.record_observed_rate_at("ETHUSDT", dec!(-0.0002), ago(4));
+
+        assert_eq!(
+            verifier.time_weighted_rate("ETHUSDT", now),
+            Some(dec!(-0.0002))
+        );
+        let result = verifier.verify_funding_at("ETHUSDT", dec!(10000), dec!(2), now);
+        assert_eq!(result.funding_expected, dec!(2));
+        assert!(!result.is_anomaly);
+    }
+
+    #[test]
+    fn test_payment_covering_skipped_settlements() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let mut calendar = FundingCalendar::new();
+        calendar.set_schedule(
+            Venue::Binance,
+            "SOLUSDT",
+            crate::exchange::FundingSchedule::every_hours(4).unwrap(),
+        );
+        let mut verifier = FundingVerifier::new(dec!(0.20));
+        verifier.set_calendar(calendar, Venue::Binance);
+        verifier.set_expected_rate("BTCUSDT", dec!(0.0001));
+        verifier.set_expected_rate("SOLUSDT", dec!(0.0001));
+
+        // First payment covers one settlement
+        let first =
+            verifier.verify_funding_at("BTCUSDT", dec!(10000), dec!(1), at("2024-03-01T00:00:30Z"));
+        assert_eq!(first.funding_expected, dec!(1));
+
+        // 08:00 was skipped; the 16:00 payment covers two settlements
+        let late =
+            verifier.verify_funding_at("BTCUSDT", dec!(10000), dec!(2), at("2024-03-01T16:00:30Z"));
+        assert_eq!(late.funding_expected, dec!(2));
+        assert!(!late.is_anomaly);
+
+        // SOL settles every 4h: 00:00 to 08:00 spans two settlements
+        verifier.import_payment(
+            "SOLUSDT",
+            dec!(10000),
+            dec!(1),
+            false,
+            at("2024-03-01T00:00:00Z"),
+        );
+        assert_eq!(
+            verifier.settlements_since_last_payment("SOLUSDT", at("2024-03-01T08:00:30Z")),
+            2
+        );
+    }
+
     #[test]
     fn test_zero_expected_funding() {
         let mut verifier = FundingVerifier::new(dec!(0.20));
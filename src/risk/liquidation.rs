@@ -188,12 +188,29 @@ mod tests {
             min_expected_yield: dec!(0.10),
             grace_period_hours: 4,
             max_funding_deviation: dec!(0.20),
+            funding_expectation_window_hours: 24,
+            funding_collapse_ratio: dec!(0.50),
+            exit_liquidity_check_minutes: 15,
+            max_exit_cost_ratio: dec!(0.50),
             max_loss_usd: dec!(10),
             max_negative_apy: dec!(0.50),
+            borrow_spike_ratio: dec!(3),
+            low_supply_spike_ratio: dec!(2),
+            borrow_holidays: Vec::new(),
+            borrow_limits: Default::default(),
+            reentry_cooldown_minutes: 60,
+            forced_exit_cooldown_minutes: 480,
             max_errors_per_minute: 10,
             max_consecutive_failures: 3,
             emergency_delta_drift: dec!(0.10),
+            max_residual_delta_usd: dec!(500),
+            malfunction_state_max_age_minutes: 60,
             max_consecutive_risk_cycles: 3,
+            resumption: Default::default(),
+            funding_quarantine: Default::default(),
+            safe_mode_after_minutes: 5,
+            persistence_watchdog: Default::default(),
+            rules: Vec::new(),
         }
     }
 
@@ -0,0 +1,195 @@
+//! Exchange maintenance awareness.
+//!
+//! Binance takes trading offline for scheduled maintenance while funding keeps
+//! accruing, so a position entered just before a window can't be hedged,
+//! rebalanced or closed until it ends. Announced windows come from the
+//! configuration, and the system status endpoint reports maintenance in progress,
+//! including unannounced or overrunning windows. New entries stop a buffer before
+//! a window; cycles inside one are expected downtime, so failed requests aren't
+//! mistaken for a malfunction or an outage.
+
+use crate::config::{MaintenanceConfig, MaintenanceWindow};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+/// Maintenance situation at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceState {
+    /// No maintenance within the entry buffer
+    Normal,
+    /// An announced window starts within the entry buffer
+    Upcoming(MaintenanceWindow),
+    /// Inside an announced window, or the system status reports maintenance
+    /// (`window` is None when no announced window covers it)
+    Active { window: Option<MaintenanceWindow> },
+}
+
+impl MaintenanceState {
+    /// Whether new entries are held back.
+    pub fn blocks_entries(&self) -> bool {
+        !matches!(self, MaintenanceState::Normal)
+    }
+
+    /// Whether the exchange is expected to be down.
+    pub fn is_active(&self) -> bool {
+        matches!(self, MaintenanceState::Active { .. })
+    }
+}
+
+/// Announced maintenance windows combined with the live system status.
+#[derive(Debug, Clone)]
+pub struct MaintenanceSchedule {
+    enabled: bool,
+    /// Windows sorted by start
+    windows: Vec<MaintenanceWindow>,
+    entry_buffer: Duration,
+    /// Whether the last system status poll reported maintenance
+    status_maintenance: bool,
+    /// Starts of the windows already announced
+    announced: HashSet<DateTime<Utc>>,
+    /// Whether the current status-reported maintenance was announced
+    status_announced: bool,
+}
+
+impl MaintenanceSchedule {
+    /// Schedule of the configured windows; a disabled schedule is always Normal.
+    pub fn new(config: &MaintenanceConfig) -> Self {
+        let mut windows = config.windows.clone();
+        windows.sort_by_key(|window| window.start);
+        Self {
+            enabled: config.enabled,
+            windows,
+            entry_buffer: Duration::minutes(config.entry_buffer_minutes as i64),
+            status_maintenance: false,
+            announced: HashSet::new(),
+            status_announced: false,
+        }
+    }
+
+    /// Record the latest system status; returns true when it changed.
+    pub fn set_system_status(&mut self, maintenance: bool) -> bool {
+        if !maintenance {
+            self.status_announced = false;
+        }
+        let changed = self.status_maintenance != maintenance;
+        self.status_maintenance = maintenance;
+        changed
+    }
+
+    /// Maintenance state at `now`.
+    pub fn state_at(&self, now: DateTime<Utc>) -> MaintenanceState {
+        if !self.enabled {
+            return MaintenanceState::Normal;
+        }
+        let active = self
+            .windows
+            .iter()
+            .find(|window| now >= window.start && now < window.end);
+        if active.is_some() || self.status_maintenance {
+            return MaintenanceState::Active {
+                window: active.cloned(),
+            };
+        }
+        self.windows
+            .iter()
+            .find(|window| now < window.start && now >= window.start - self.entry_buffer)
+            .map_or(MaintenanceState::Normal, |window| {
+                MaintenanceState::Upcoming(window.clone())
+            })
+    }
+
+    /// State at `now` when it hasn't been announced yet.
+    ///
+    /// Each announced window is reported once, when it first enters the entry
+    /// buffer (or at once if it is already running). Maintenance reported only by
+    /// the system status is reported once until the status clears.
+    pub fn announce(&mut self, now: DateTime<Utc>) -> Option<MaintenanceState> {
+        let state = self.state_at(now);
+        let first = match &state {
+            MaintenanceState::Normal => false,
+            MaintenanceState::Upcoming(window)
+            | MaintenanceState::Active {
+                window: Some(window),
+            } => self.announced.insert(window.start),
+            MaintenanceState::Active { window: None } => {
+                !std::mem::replace(&mut self.status_announced, true)
+            }
+        };
+        first.then_some(state)
+    }
+
+    /// Next announced window that hasn't ended by `now`.
+    pub fn next_window(&self, now: DateTime<Utc>) -> Option<&MaintenanceWindow> {
+        self.windows.iter().find(|window| window.end > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 20, hour, minute, 0).unwrap()
+    }
+
+    fn config() -> MaintenanceConfig {
+        MaintenanceConfig {
+            windows: vec![MaintenanceWindow {
+                start: at(2, 0),
+                end: at(4, 0),
+                note: "Wallet upgrade".to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_buffer_and_window() {
+        let mut schedule = MaintenanceSchedule::new(&config());
+        assert_eq!(schedule.state_at(at(0, 59)), MaintenanceState::Normal);
+        assert!(schedule.announce(at(0, 59)).is_none());
+
+        // An hour ahead entries stop; the window is announced once
+        let state = schedule.state_at(at(1, 0));
+        assert!(matches!(state, MaintenanceState::Upcoming(_)));
+        assert!(state.blocks_entries() && !state.is_active());
+        assert!(schedule.announce(at(1, 0)).is_some());
+        assert!(schedule.announce(at(2, 30)).is_none());
+        assert!(schedule.state_at(at(2, 30)).is_active());
+
+        // Ends with the window
+        assert_eq!(schedule.state_at(at(4, 0)), MaintenanceState::Normal);
+        assert!(schedule.next_window(at(4, 0)).is_none());
+
+        // Disabled schedules never restrict
+        let disabled = MaintenanceSchedule::new(&MaintenanceConfig {
+            enabled: false,
+            ..config()
+        });
+        assert_eq!(disabled.state_at(at(2, 30)), MaintenanceState::Normal);
+    }
+
+    #[test]
+    fn test_system_status_overrides() {
+        let mut schedule = MaintenanceSchedule::new(&config());
+        // Unannounced maintenance from the status endpoint
+        assert!(schedule.set_system_status(true));
+        assert!(!schedule.set_system_status(true));
+        assert_eq!(
+            schedule.state_at(at(6, 0)),
+            MaintenanceState::Active { window: None }
+        );
+        assert!(schedule.announce(at(6, 0)).is_some());
+        assert!(schedule.announce(at(6, 5)).is_none());
+
+        // An overrunning window stays active until the status clears
+        assert!(schedule.state_at(at(4, 30)).is_active());
+        assert!(schedule.set_system_status(false));
+        assert_eq!(schedule.state_at(at(4, 30)), MaintenanceState::Normal);
+
+        // The next status-reported maintenance is announced again
+        schedule.set_system_status(true);
+        assert!(schedule.announce(at(8, 0)).is_some());
+    }
+}
@@ -6,18 +6,30 @@
 //! - Emergency delta drift (hedge breakdown)
 //! - Balance/position discrepancies
 //! - Rate limiting
+//! - Signature and permission failures
+//! - Order rate or notional velocity beyond the configured limits
 //!
 //! Provides structured alerts for the log analysis workflow.
-
+//!
+//! The error window, failure counters, active alerts and halt survive restarts
+//! through [`PersistedMalfunctionState`], so a crash loop can't reset the circuit
+//! breaker. Restored entries decay: errors leave with the error window, and
+//! counters, alerts and a halt older than `state_max_age_minutes` are dropped.
+
+use crate::exchange::{error_class, ApiError, ErrorClass};
+use crate::persistence::{
+    PersistedMalfunctionAlert, PersistedMalfunctionError, PersistedMalfunctionState,
+    PersistedOrderFailures,
+};
 use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use tracing::{debug, error, info, warn};
 
 /// Types of malfunctions that can be detected.
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum MalfunctionType {
     /// API error rate exceeded threshold
@@ -46,8 +58,19 @@ pub enum MalfunctionType {
     },
     /// Rate limit hit on API
     RateLimitHit { endpoint: String },
+    /// Signature, timestamp or permission error from the exchange
+    ApiAuthFailure {
+        operation: String,
+        code: Option<i64>,
+    },
     /// WebSocket connection issues
     WebSocketDisconnect { duration_secs: u64 },
+    /// Order valve tripped: orders or traded notional above the configured limit
+    OrderVelocityExceeded {
+        limit: String,
+        observed: Decimal,
+        max: Decimal,
+    },
 }
 
 /// Severity levels for alerts.
@@ -69,10 +92,21 @@ impl AlertSeverity {
             AlertSeverity::Critical => "CRITICAL",
         }
     }
+
+    /// Parse a display name written by [`as_str`](Self::as_str).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "INFO" => Some(AlertSeverity::Info),
+            "WARNING" => Some(AlertSeverity::Warning),
+            "ERROR" => Some(AlertSeverity::Error),
+            "CRITICAL" => Some(AlertSeverity::Critical),
+            _ => None,
+        }
+    }
 }
 
 /// A malfunction alert.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct MalfunctionAlert {
     pub alert_id: String,
     pub timestamp: DateTime<Utc>,
@@ -119,6 +153,14 @@ impl MalfunctionAlert {
     }
 }
 
+fn is_auth_failure(malfunction_type: &MalfunctionType) -> bool {
+    matches!(malfunction_type, MalfunctionType::ApiAuthFailure { .. })
+}
+
+fn is_rate_limit(malfunction_type: &MalfunctionType) -> bool {
+    matches!(malfunction_type, MalfunctionType::RateLimitHit { .. })
+}
+
 /// Generate a random suffix for alert IDs.
 fn rand_suffix() -> String {
     use std::time::SystemTime;
@@ -142,6 +184,8 @@ pub struct MalfunctionConfig {
     pub balance_discrepancy_threshold: Decimal,
     /// Error window size in minutes
     pub error_window_minutes: u32,
+    /// Age beyond which restored failure counters, alerts and halts are dropped
+    pub state_max_age_minutes: u32,
 }
 
 impl Default for MalfunctionConfig {
@@ -152,10 +196,31 @@ impl Default for MalfunctionConfig {
             emergency_delta_drift: dec!(0.10), // 10%
             balance_discrepancy_threshold: dec!(100),
             error_window_minutes: 5,
+            state_max_age_minutes: 60,
         }
     }
 }
 
+/// What was carried over from a previous run's detector state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MalfunctionRestore {
+    pub errors: usize,
+    pub failing_symbols: usize,
+    pub alerts: usize,
+    /// Circuit breaker cycles carried over (restored by the orchestrator)
+    pub consecutive_risk_cycles: u32,
+    /// The previous run's halt is still in force
+    pub halted: bool,
+    /// The previous run was halted but the halt has decayed
+    pub halt_expired: bool,
+}
+
+impl MalfunctionRestore {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Detects trading malfunctions.
 pub struct MalfunctionDetector {
     config: MalfunctionConfig,
@@ -163,12 +228,16 @@ pub struct MalfunctionDetector {
     error_history: VecDeque<(DateTime<Utc>, String)>,
     /// Consecutive failure count per symbol
     failure_counts: HashMap<String, u32>,
+    /// Time of the last order failure per symbol
+    last_failures: HashMap<String, DateTime<Utc>>,
     /// Active alerts (not yet resolved)
     active_alerts: Vec<MalfunctionAlert>,
     /// Last recorded balance for discrepancy detection
     last_balance: Option<Decimal>,
-    /// Whether trading should be halted
-    halt_trading: bool,
+    /// When trading was halted, if it is
+    halted_at: Option<DateTime<Utc>>,
+    /// Exchange maintenance in progress: errors and order failures are expected
+    expected_downtime: bool,
 }
 
 impl MalfunctionDetector {
@@ -178,14 +247,27 @@ impl MalfunctionDetector {
             config,
             error_history: VecDeque::new(),
             failure_counts: HashMap::new(),
+            last_failures: HashMap::new(),
             active_alerts: Vec::new(),
             last_balance: None,
-            halt_trading: false,
+            halted_at: None,
+            expected_downtime: false,
         }
     }
 
+    /// Mark whether the exchange is in scheduled maintenance.
+    ///
+    /// While set, errors and order failures are logged but not counted.
+    pub fn set_expected_downtime(&mut self, expected: bool) {
+        self.expected_downtime = expected;
+    }
+
     /// Record an API or execution error.
     pub fn record_error(&mut self, error: &str) -> Option<MalfunctionAlert> {
+        if self.expected_downtime {
+            debug!(error = %error, "Error during exchange maintenance, not counted");
+            return None;
+        }
         let now = Utc::now();
 
         self.error_history.push_back((now, error.to_string()));
@@ -230,8 +312,74 @@ impl MalfunctionDetector {
         None
     }
 
+    /// Record a failed exchange call by the class of its error.
+    ///
+    /// Transient failures count towards the error spike, and so do rate limits,
+    /// which also raise a rate limit alert. Signature and permission failures
+    /// won't clear by themselves and raise an alert of their own. Requests the
+    /// exchange refused on their merits say nothing about the API's health and
+    /// are only logged.
+    pub fn record_api_error(
+        &mut self,
+        context: &str,
+        error: &anyhow::Error,
+    ) -> Option<MalfunctionAlert> {
+        let class = error_class(error);
+        let message = format!("{}: {:#}", context, error);
+        match class {
+            ErrorClass::Rejected => {
+                debug!(error = %message, "Request rejected by the exchange, not counted");
+                None
+            }
+            ErrorClass::Auth => {
+                if self.expected_downtime || self.has_recent_alert(is_auth_failure) {
+                    return None;
+                }
+                let api_error = ApiError::find(error);
+                let alert = MalfunctionAlert::new(
+                    MalfunctionType::ApiAuthFailure {
+                        operation: api_error
+                            .map_or_else(|| context.to_string(), |e| e.operation.clone()),
+                        code: api_error.and_then(|e| e.code),
+                    },
+                    AlertSeverity::Error,
+                    message,
+                    false,
+                    "Check API key permissions, IP whitelist and clock sync".to_string(),
+                );
+                self.add_alert(alert.clone());
+                Some(alert)
+            }
+            ErrorClass::RateLimited => {
+                let spike = self.record_error(&format!("[{}] {}", class, message));
+                if self.expected_downtime || self.has_recent_alert(is_rate_limit) {
+                    return spike;
+                }
+                let operation = ApiError::find(error).map_or(context, |e| e.operation.as_str());
+                let alert = self.record_rate_limit(operation);
+                spike.or(Some(alert))
+            }
+            ErrorClass::Transient | ErrorClass::Unknown => {
+                self.record_error(&format!("[{}] {}", class, message))
+            }
+        }
+    }
+
+    /// Whether an alert matching `matches` was raised within the error window.
+    fn has_recent_alert(&self, matches: fn(&MalfunctionType) -> bool) -> bool {
+        let window_start = Utc::now() - Duration::minutes(self.config.error_window_minutes as i64);
+        self.active_alerts
+            .iter()
+            .any(|alert| alert.timestamp >= window_start && matches(&alert.malfunction_type))
+    }
+
     /// Record an order execution failure.
     pub fn record_order_failure(&mut self, symbol: &str) -> Option<MalfunctionAlert> {
+        if self.expected_downtime {
+            debug!(symbol = %symbol, "Order failure during exchange maintenance, not counted");
+            return None;
+        }
+        self.last_failures.insert(symbol.to_string(), Utc::now());
         let count = self.failure_counts.entry(symbol.to_string()).or_insert(0);
         *count += 1;
 
@@ -299,7 +447,7 @@ impl MalfunctionDetector {
                 format!("Immediately rebalance or close {}", symbol),
             );
 
-            self.halt_trading = true;
+            self.halted_at = Some(alert.timestamp);
             self.add_alert(alert.clone());
             return Some(alert);
         }
@@ -396,6 +544,36 @@ impl MalfunctionDetector {
         alert
     }
 
+    /// Record a tripped order valve and halt trading.
+    ///
+    /// Placing orders faster or in larger size than configured points at a bug,
+    /// so this is always critical.
+    pub fn record_order_velocity_breach(
+        &mut self,
+        limit: &str,
+        observed: Decimal,
+        max: Decimal,
+    ) -> MalfunctionAlert {
+        let alert = MalfunctionAlert::new(
+            MalfunctionType::OrderVelocityExceeded {
+                limit: limit.to_string(),
+                observed,
+                max,
+            },
+            AlertSeverity::Critical,
+            format!(
+                "Order valve tripped: {} at {} (max {}) - orders refused",
+                limit, observed, max
+            ),
+            true,
+            "Investigate the order flow before restarting; positions are left as they are"
+                .to_string(),
+        );
+
+        self.add_alert(alert.clone());
+        alert
+    }
+
     /// Record WebSocket disconnect.
     pub fn record_ws_disconnect(&mut self, duration_secs: u64) -> Option<MalfunctionAlert> {
         // Only alert if disconnect > 30 seconds
@@ -427,7 +605,7 @@ impl MalfunctionDetector {
     fn add_alert(&mut self, alert: MalfunctionAlert) {
         // Check for halt condition
         if alert.should_halt {
-            self.halt_trading = true;
+            self.halted_at.get_or_insert(alert.timestamp);
         }
 
         alert.emit();
@@ -454,18 +632,19 @@ impl MalfunctionDetector {
 
     /// Check if trading should be halted.
     pub fn should_halt_trading(&self) -> bool {
-        self.halt_trading
+        self.halted_at.is_some()
     }
 
     /// Reset halt flag (after manual review).
     pub fn reset_halt(&mut self) {
-        self.halt_trading = false;
+        self.halted_at = None;
         info!("Trading halt reset by operator");
     }
 
     /// Clear alerts for a symbol (e.g., when position is closed).
     pub fn clear_symbol_alerts(&mut self, symbol: &str) {
         self.failure_counts.remove(symbol);
+        self.last_failures.remove(symbol);
     }
 
     /// Get recent error count.
@@ -477,6 +656,110 @@ impl MalfunctionDetector {
     pub fn get_failure_count(&self, symbol: &str) -> u32 {
         self.failure_counts.get(symbol).copied().unwrap_or(0)
     }
+
+    /// State to persist; the circuit breaker counter is left to the caller.
+    pub fn persisted_state(&self, now: DateTime<Utc>) -> PersistedMalfunctionState {
+        let mut failures: Vec<PersistedOrderFailures> = self
+            .failure_counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(symbol, count)| PersistedOrderFailures {
+                symbol: symbol.clone(),
+                consecutive_failures: *count,
+                last_failure: self.last_failures.get(symbol).copied().unwrap_or(now),
+            })
+            .collect();
+        failures.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        PersistedMalfunctionState {
+            saved_at: now,
+            halted_at: self.halted_at,
+            consecutive_risk_cycles: 0,
+            errors: self
+                .error_history
+                .iter()
+                .map(|(timestamp, error)| PersistedMalfunctionError {
+                    timestamp: *timestamp,
+                    error: error.clone(),
+                })
+                .collect(),
+            failures,
+            alerts: self
+                .active_alerts
+                .iter()
+                .map(|alert| PersistedMalfunctionAlert {
+                    alert_id: alert.alert_id.clone(),
+                    timestamp: alert.timestamp,
+                    severity: alert.severity.as_str().to_string(),
+                    malfunction_type: serde_json::to_string(&alert.malfunction_type)
+                        .unwrap_or_default(),
+                    message: alert.message.clone(),
+                    should_halt: alert.should_halt,
+                    suggested_action: alert.suggested_action.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Restore the state saved by a previous run.
+    ///
+    /// Errors are kept while inside the error window. Failure counters, alerts
+    /// and the halt are kept while at most `state_max_age_minutes` old; restored
+    /// alerts aren't emitted again.
+    pub fn restore(
+        &mut self,
+        state: &PersistedMalfunctionState,
+        now: DateTime<Utc>,
+    ) -> MalfunctionRestore {
+        let window_start = now - Duration::minutes(self.config.error_window_minutes as i64);
+        let fresh_since = now - Duration::minutes(self.config.state_max_age_minutes as i64);
+        let mut restored = MalfunctionRestore::default();
+
+        for error in state.errors.iter().filter(|e| e.timestamp >= window_start) {
+            self.error_history
+                .push_back((error.timestamp, error.error.clone()));
+            restored.errors += 1;
+        }
+        for failures in state
+            .failures
+            .iter()
+            .filter(|f| f.last_failure >= fresh_since)
+        {
+            self.failure_counts
+                .insert(failures.symbol.clone(), failures.consecutive_failures);
+            self.last_failures
+                .insert(failures.symbol.clone(), failures.last_failure);
+            restored.failing_symbols += 1;
+        }
+        for alert in state.alerts.iter().filter(|a| a.timestamp >= fresh_since) {
+            let (Some(severity), Ok(malfunction_type)) = (
+                AlertSeverity::parse(&alert.severity),
+                serde_json::from_str(&alert.malfunction_type),
+            ) else {
+                warn!(alert_id = %alert.alert_id, "Skipping unreadable persisted malfunction alert");
+                continue;
+            };
+            self.active_alerts.push(MalfunctionAlert {
+                alert_id: alert.alert_id.clone(),
+                timestamp: alert.timestamp,
+                malfunction_type,
+                severity,
+                message: alert.message.clone(),
+                should_halt: alert.should_halt,
+                suggested_action: alert.suggested_action.clone(),
+            });
+            restored.alerts += 1;
+        }
+        match state.halted_at {
+            Some(halted_at) if halted_at >= fresh_since => {
+                self.halted_at = Some(halted_at);
+                restored.halted = true;
+            }
+            Some(_) => restored.halt_expired = true,
+            None => {}
+        }
+        restored
+    }
 }
 
 #[cfg(test)]
@@ -490,6 +773,7 @@ mod tests {
             emergency_delta_drift: dec!(0.10),
             balance_discrepancy_threshold: dec!(100),
             error_window_minutes: 1,
+            state_max_age_minutes: 60,
         }
     }
 
@@ -511,6 +795,66 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_api_errors_weighed_by_class() {
+        use reqwest::StatusCode;
+        let api_error = |status: StatusCode, body: &str| {
+            anyhow::Error::new(ApiError::from_response("place_futures_order", status, body))
+        };
+        let mut detector = MalfunctionDetector::new(test_config());
+
+        // Filter rejections don't count towards the spike
+        let filter = api_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#,
+        );
+        for _ in 0..10 {
+            assert!(detector
+                .record_api_error("Futures order failed", &filter)
+                .is_none());
+        }
+        assert_eq!(detector.recent_error_count(), 0);
+
+        // A permission error alerts once per window
+        let auth = api_error(
+            StatusCode::UNAUTHORIZED,
+            r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#,
+        );
+        let alert = detector
+            .record_api_error("Futures order failed", &auth)
+            .unwrap();
+        assert_eq!(
+            alert.malfunction_type,
+            MalfunctionType::ApiAuthFailure {
+                operation: "place_futures_order".to_string(),
+                code: Some(-2015),
+            }
+        );
+        assert!(detector
+            .record_api_error("Futures order failed", &auth)
+            .is_none());
+
+        // Rate limits count and alert; server errors count
+        let limited = api_error(StatusCode::TOO_MANY_REQUESTS, "");
+        let alert = detector
+            .record_api_error("Futures order failed", &limited)
+            .unwrap();
+        assert!(matches!(
+            alert.malfunction_type,
+            MalfunctionType::RateLimitHit { .. }
+        ));
+        let unavailable = api_error(StatusCode::SERVICE_UNAVAILABLE, "");
+        for _ in 0..3 {
+            assert!(detector
+                .record_api_error("Futures order failed", &unavailable)
+                .is_none());
+        }
+        assert!(detector
+            .record_api_error("Futures order failed", &unavailable)
+            .is_some());
+        assert_eq!(detector.recent_error_count(), 5);
+    }
+
     #[test]
     fn test_order_failure_tracking() {
         let mut detector = MalfunctionDetector::new(test_config());
@@ -528,6 +872,22 @@ mod tests {
         assert_eq!(detector.get_failure_count("BTCUSDT"), 0);
     }
 
+    #[test]
+    fn test_expected_downtime_not_counted() {
+        let mut detector = MalfunctionDetector::new(test_config());
+        detector.set_expected_downtime(true);
+        for _ in 0..10 {
+            assert!(detector.record_error("maintenance").is_none());
+            assert!(detector.record_order_failure("BTCUSDT").is_none());
+        }
+        assert_eq!(detector.recent_error_count(), 0);
+        assert_eq!(detector.get_failure_count("BTCUSDT"), 0);
+
+        detector.set_expected_downtime(false);
+        assert!(detector.record_order_failure("BTCUSDT").is_none());
+        assert_eq!(detector.get_failure_count("BTCUSDT"), 1);
+    }
+
     #[test]
     fn test_delta_drift_emergency() {
         let mut detector = MalfunctionDetector::new(test_config());
@@ -552,4 +912,48 @@ mod tests {
         let alert = detector.check_balance(dec!(1000), dec!(800));
         assert!(alert.is_some());
     }
+
+    #[test]
+    fn test_order_velocity_breach_halts() {
+        let mut detector = MalfunctionDetector::new(test_config());
+
+        let alert = detector.record_order_velocity_breach("orders_per_minute", dec!(31), dec!(30));
+        assert_eq!(alert.severity, AlertSeverity::Critical);
+        assert!(alert.should_halt);
+        assert!(detector.should_halt_trading());
+    }
+
+    #[test]
+    fn test_state_survives_restart_and_decays() {
+        let mut detector = MalfunctionDetector::new(test_config());
+        for _ in 0..4 {
+            detector.record_error("timeout");
+        }
+        detector.record_order_failure("BTCUSDT");
+        detector.record_order_failure("BTCUSDT");
+        detector.check_delta_drift("ETHUSDT", dec!(0.15));
+        let state = detector.persisted_state(Utc::now());
+
+        // Restarted at once: the next error completes the burst
+        let mut restarted = MalfunctionDetector::new(test_config());
+        let restored = restarted.restore(&state, Utc::now());
+        assert_eq!(restored.errors, 4);
+        assert_eq!(restored.failing_symbols, 1);
+        assert_eq!(restored.alerts, 1);
+        assert!(restored.halted);
+        assert!(restarted.should_halt_trading());
+        assert_eq!(restarted.get_failure_count("BTCUSDT"), 2);
+        assert_eq!(restarted.get_active_alerts(), detector.get_active_alerts());
+        assert!(restarted.record_error("timeout").is_some());
+
+        // Two hours later everything has decayed; the halt is reported expired
+        let mut later = MalfunctionDetector::new(test_config());
+        let restored = later.restore(&state, Utc::now() + Duration::hours(2));
+        assert!(restored.halt_expired && !restored.halted);
+        assert_eq!(
+            restored.errors + restored.failing_symbols + restored.alerts,
+            0
+        );
+        assert!(!later.should_halt_trading());
+    }
 }
@@ -142,6 +142,27 @@ impl MarginMonitor {
         rate_map
     }
 
+    /// Revalue positions at current mark prices.
+    ///
+    /// Updates `mark_price`, `notional` and `unrealized_profit` for every position with
+    /// a known mark so that margin ratios and liquidation distances reflect the market
+    /// rather than the entry price. Positions without a mark are left untouched.
+    pub fn apply_mark_prices(positions: &mut [Position], mark_prices: &HashMap<String, Decimal>) {
+        for position in positions.iter_mut() {
+            let Some(&mark_price) = mark_prices.get(&position.symbol) else {
+                continue;
+            };
+            if mark_price <= Decimal::ZERO {
+                continue;
+            }
+
+            position.mark_price = mark_price;
+            position.notional = position.position_amt * mark_price;
+            position.unrealized_profit =
+                position.position_amt * (mark_price - position.entry_price);
+        }
+    }
+
     /// Get overall margin health based on ratio.
     pub fn get_health(&self, margin_ratio: Decimal) -> MarginHealth {
         if margin_ratio >= dec!(5.0) {
@@ -321,12 +342,29 @@ mod tests {
             min_expected_yield: dec!(0.10),
             grace_period_hours: 4,
             max_funding_deviation: dec!(0.20),
+            funding_expectation_window_hours: 24,
+            funding_collapse_ratio: dec!(0.50),
+            exit_liquidity_check_minutes: 15,
+            max_exit_cost_ratio: dec!(0.50),
             max_loss_usd: dec!(10),
             max_negative_apy: dec!(0.50),
+            borrow_spike_ratio: dec!(3),
+            low_supply_spike_ratio: dec!(2),
+            borrow_holidays: Vec::new(),
+            borrow_limits: Default::default(),
+            reentry_cooldown_minutes: 60,
+            forced_exit_cooldown_minutes: 480,
             max_errors_per_minute: 10,
             max_consecutive_failures: 3,
             emergency_delta_drift: dec!(0.10),
+            max_residual_delta_usd: dec!(500),
+            malfunction_state_max_age_minutes: 60,
             max_consecutive_risk_cycles: 3,
+            resumption: Default::default(),
+            funding_quarantine: Default::default(),
+            safe_mode_after_minutes: 5,
+            persistence_watchdog: Default::default(),
+            rules: Vec::new(),
         })
     }
 
@@ -1019,4 +1057,53 @@ mod tests {
         // Zero notional = zero margin allocation
         assert_eq!(margin, Decimal::ZERO);
     }
+
+    #[test]
+    fn test_apply_mark_prices_revalues_positions() {
+        use crate::exchange::PositionSide;
+
+        let mut positions = vec![
+            Position {
+                symbol: "BTCUSDT".to_string(),
+                position_amt: dec!(-1.0),
+                entry_price: dec!(50000),
+                mark_price: dec!(50000),
+                unrealized_profit: Decimal::ZERO,
+                liquidation_price: Decimal::ZERO,
+                leverage: 5,
+                position_side: PositionSide::Both,
+                notional: dec!(-50000),
+                isolated_margin: Decimal::ZERO,
+                margin_type: MarginType::Cross,
+            },
+            Position {
+                symbol: "ETHUSDT".to_string(),
+                position_amt: dec!(10.0),
+                entry_price: dec!(3000),
+                mark_price: dec!(3000),
+                unrealized_profit: Decimal::ZERO,
+                liquidation_price: Decimal::ZERO,
+                leverage: 5,
+                position_side: PositionSide::Both,
+                notional: dec!(30000),
+                isolated_margin: Decimal::ZERO,
+                margin_type: MarginType::Cross,
+            },
+        ];
+
+        let mut marks = HashMap::new();
+        marks.insert("BTCUSDT".to_string(), dec!(55000));
+
+        MarginMonitor::apply_mark_prices(&mut positions, &marks);
+
+        // Short BTC loses $5,000 as the mark rises 10%
+        assert_eq!(positions[0].mark_price, dec!(55000));
+        assert_eq!(positions[0].notional, dec!(-55000));
+        assert_eq!(positions[0].unrealized_profit, dec!(-5000));
+
+        // ETH has no mark and keeps its previous valuation
+        assert_eq!(positions[1].mark_price, dec!(3000));
+        assert_eq!(positions[1].notional, dec!(30000));
+        assert_eq!(positions[1].unrealized_profit, Decimal::ZERO);
+    }
 }
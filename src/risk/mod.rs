@@ -6,28 +6,79 @@
 //! - Maximum drawdown tracking
 //! - Per-position loss detection
 //! - Funding payment verification
+//! - Quarantine of symbols with persistent funding anomalies
+//! - Confirmation of funding settlements by the exchange
+//! - Funding capture efficiency against an ideal hold
+//! - Predicted funding rate trajectory monitoring
+//! - Borrow rate spike detection
+//! - Exchange borrow limit utilization
+//! - Spot hedge exit liquidity monitoring
+//! - Portfolio residual delta reporting
+//! - Macro event entry blackouts
+//! - Exchange outage safe-mode
+//! - Exchange maintenance windows
+//! - State save failure escalation
+//! - Operator-defined declarative risk rules
+//! - Alert routing by class and severity
 //! - Malfunction detection
+//! - Supervised resumption after a halt
 
+mod alert_routing;
+mod borrow_limits;
+mod borrow_spike;
+mod event_calendar;
+mod exit_liquidity;
+mod exposure;
+mod funding_capture;
+mod funding_quarantine;
+mod funding_settlement;
+mod funding_trend;
 mod funding_verifier;
 mod liquidation;
+mod maintenance;
 mod malfunction;
 mod margin;
 mod mdd;
 mod orchestrator;
+mod outage;
+mod persistence_watchdog;
 mod position_tracker;
+mod resumption;
+mod rules;
 
+pub use alert_routing::{default_action, AlertRouter};
+pub use borrow_limits::{BorrowLimit, BorrowLimits, BorrowShortfall};
+pub use borrow_spike::{BorrowSpike, BorrowSpikeMonitor};
+pub use event_calendar::{
+    fetch_calendar, load_events_file, EntryRestriction, EventCalendar, EventImpact, MacroEvent,
+};
+pub use exit_liquidity::{
+    estimate_exit_cost, max_exit_quantity, ExitCost, ExitLiquidityBreach, ExitLiquidityMonitor,
+};
+pub use exposure::{ExposureReport, SymbolExposure};
+pub use funding_capture::{CaptureLedger, CaptureSummary, FundingCaptureTracker};
+pub use funding_quarantine::FundingQuarantine;
+pub use funding_settlement::{ConfirmedSettlement, FundingSettlements, PendingSettlement};
+pub use funding_trend::{FundingCollapse, FundingTrendMonitor};
 pub use funding_verifier::{
-    FundingRecord, FundingStats, FundingVerificationResult, FundingVerifier,
+    ExpectationBasis, FundingRecord, FundingStats, FundingVerificationResult, FundingVerifier,
 };
 pub use liquidation::{LiquidationAction, LiquidationGuard};
+pub use maintenance::{MaintenanceSchedule, MaintenanceState};
 pub use malfunction::{
-    AlertSeverity, MalfunctionAlert, MalfunctionConfig, MalfunctionDetector, MalfunctionType,
+    AlertSeverity, MalfunctionAlert, MalfunctionConfig, MalfunctionDetector, MalfunctionRestore,
+    MalfunctionType,
 };
 pub use margin::{MarginHealth, MarginMonitor};
 pub use mdd::{DrawdownStats, DrawdownTracker};
 pub use orchestrator::{
     RiskAlert, RiskAlertType, RiskCheckResult, RiskOrchestrator, RiskOrchestratorConfig,
 };
+pub use outage::{DeferredAction, OutageMonitor, OutageRecovery};
+pub use persistence_watchdog::{PersistenceRecovery, PersistenceWatchdog};
 pub use position_tracker::{
-    PositionAction, PositionEntry, PositionLossConfig, PositionTracker, TrackedPosition,
+    position_opened_at, ExitKind, PositionAction, PositionEntry, PositionLossConfig,
+    PositionTracker, TrackedPosition,
 };
+pub use resumption::{EntryThrottle, ResumptionEvent, ResumptionPhase, SupervisedResumption};
+pub use rules::{PortfolioMetrics, RuleEngine};
@@ -7,22 +7,35 @@
 //! - LiquidationGuard (liquidation prevention)
 //! - PositionTracker (per-position PnL)
 //! - FundingVerifier (funding accuracy)
+//! - BorrowSpikeMonitor (borrow rates of short spot hedges)
+//! - ExitLiquidityMonitor (cost of unwinding spot hedges)
 //! - MalfunctionDetector (operational health)
+//! - RuleEngine (operator-defined rules)
+//! - SupervisedResumption (throttled entries after a halt is reset)
+//! - AlertRouter (reaction to each alert class and severity)
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, error, info, warn};
 
-use crate::exchange::Position;
+use crate::config::{
+    AlertAction, AlertClass, FundingQuarantineConfig, ResumptionConfig, RiskRule, RuleAction,
+    SeverityRoutes,
+};
+use crate::exchange::{FundingCalendar, MarginType, OrderBook, Position, Venue};
+use crate::persistence::{PersistedMalfunctionState, PersistedQuarantine};
 
 use super::{
-    AlertSeverity, DrawdownTracker, FundingVerificationResult, FundingVerifier, LiquidationAction,
-    LiquidationGuard, MalfunctionAlert, MalfunctionConfig, MalfunctionDetector, MarginHealth,
-    MarginMonitor, PositionAction, PositionEntry, PositionLossConfig, PositionTracker,
-    TrackedPosition,
+    AlertRouter, AlertSeverity, BorrowSpike, BorrowSpikeMonitor, DrawdownTracker, EntryThrottle,
+    ExitKind, ExitLiquidityBreach, ExitLiquidityMonitor, ExpectationBasis, ExposureReport,
+    FundingQuarantine, FundingTrendMonitor, FundingVerificationResult, FundingVerifier,
+    LiquidationAction, LiquidationGuard, MalfunctionAlert, MalfunctionConfig, MalfunctionDetector,
+    MalfunctionRestore, MarginHealth, MarginMonitor, PortfolioMetrics, PositionAction,
+    PositionEntry, PositionLossConfig, PositionTracker, ResumptionEvent, RuleEngine,
+    SupervisedResumption, TrackedPosition,
 };
 
 /// Unified risk configuration.
@@ -44,16 +57,39 @@ pub struct RiskOrchestratorConfig {
     pub min_expected_yield: Decimal,
     pub grace_period_hours: u32,
     pub max_funding_deviation: Decimal,
+    pub funding_expectation_window_hours: u32,
+    pub funding_collapse_ratio: Decimal,
+    pub exit_liquidity_check_minutes: u32,
+    pub max_exit_cost_ratio: Decimal,
     pub max_loss_usd: Decimal,
     pub max_negative_apy: Decimal,
 
+    // Borrow rate spikes
+    pub borrow_spike_ratio: Decimal,
+    pub low_supply_spike_ratio: Decimal,
+    pub borrow_holidays: Vec<NaiveDate>,
+
+    // Re-entry cool-downs
+    pub reentry_cooldown_minutes: u32,
+    pub forced_exit_cooldown_minutes: u32,
+
     // Malfunction detection
     pub max_errors_per_minute: u32,
     pub max_consecutive_failures: u32,
     pub emergency_delta_drift: Decimal,
+    pub max_residual_delta_usd: Decimal,
+    pub malfunction_state_max_age_minutes: u32,
 
     // Circuit breaker
     pub max_consecutive_risk_cycles: u32,
+    pub resumption: ResumptionConfig,
+
+    // Funding anomalies
+    pub funding_quarantine: FundingQuarantineConfig,
+
+    // Operator policies
+    pub rules: Vec<RiskRule>,
+    pub alert_routes: BTreeMap<AlertClass, SeverityRoutes>,
 }
 
 impl Default for RiskOrchestratorConfig {
@@ -68,12 +104,27 @@ impl Default for RiskOrchestratorConfig {
             min_expected_yield: dec!(0.10),
             grace_period_hours: 4,
             max_funding_deviation: dec!(0.20),
+            funding_expectation_window_hours: 24,
+            funding_collapse_ratio: dec!(0.50),
+            exit_liquidity_check_minutes: 15,
+            max_exit_cost_ratio: dec!(0.50),
             max_loss_usd: dec!(10),
             max_negative_apy: dec!(0.50),
+            borrow_spike_ratio: dec!(3),
+            low_supply_spike_ratio: dec!(2),
+            borrow_holidays: Vec::new(),
+            reentry_cooldown_minutes: 60,
+            forced_exit_cooldown_minutes: 480,
             max_errors_per_minute: 10,
             max_consecutive_failures: 3,
             emergency_delta_drift: dec!(0.10),
+            max_residual_delta_usd: dec!(500),
+            malfunction_state_max_age_minutes: 60,
             max_consecutive_risk_cycles: 3,
+            resumption: ResumptionConfig::default(),
+            funding_quarantine: FundingQuarantineConfig::default(),
+            rules: Vec::new(),
+            alert_routes: BTreeMap::new(),
         }
     }
 }
@@ -103,6 +154,123 @@ pub enum RiskAlertType {
     DrawdownExceeded { current: Decimal, limit: Decimal },
     /// Delta drift detected
     DeltaDrift { symbol: String, drift_pct: Decimal },
+    /// Exposure above an absolute notional cap (symbol or `group:<name>`)
+    NotionalCapExceeded {
+        scope: String,
+        notional: Decimal,
+        cap: Decimal,
+    },
+    /// Predicted funding collapsed before the next settlement
+    FundingCollapse {
+        symbol: String,
+        entry_rate: Decimal,
+        predicted_rate: Decimal,
+    },
+    /// Unwinding a held spot hedge would cost more than its funding justifies
+    ExitLiquidity {
+        symbol: String,
+        exit_cost: Decimal,
+        budget: Decimal,
+    },
+    /// Net unhedged delta beyond the hedge targets above the portfolio threshold
+    ResidualDelta {
+        net_drift_usd: Decimal,
+        threshold: Decimal,
+    },
+    /// A wallet cannot fund its leg of a live entry; the entry was refused
+    InsufficientFunds {
+        symbol: String,
+        wallet: String,
+        required: Decimal,
+        available: Decimal,
+    },
+    /// Funding on one asset diverges between venues (opportunity, not a risk)
+    FundingSpreadOpportunity {
+        symbol: String,
+        short_venue: String,
+        spread: Decimal,
+        apr: Decimal,
+    },
+    /// A cheaper hedge is available for a held position (advisory)
+    HedgeSwitchRecommended {
+        symbol: String,
+        from: String,
+        to: String,
+        savings: Decimal,
+    },
+    /// Borrow rate of a held short spot hedge spiked above its baseline
+    BorrowRateSpike {
+        symbol: String,
+        rate: Decimal,
+        baseline: Decimal,
+    },
+    /// Most of an asset's exchange borrow limit is in use
+    BorrowLimit {
+        asset: String,
+        utilization: Decimal,
+        headroom_usdt: Decimal,
+    },
+    /// An entry would borrow more than the exchange has left; the entry was refused
+    BorrowLimitExceeded {
+        symbol: String,
+        asset: String,
+        required: Decimal,
+        available: Decimal,
+    },
+    /// Leverage or margin type of a held symbol differs from the configuration
+    SettingsMismatch {
+        symbol: String,
+        leverage: u8,
+        expected_leverage: u8,
+        margin_type: MarginType,
+    },
+    /// Exchange maintenance announced or in progress (informational)
+    ExchangeMaintenance {
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+    },
+    /// Exchange unreachable with open positions; safe-mode entered
+    ExchangeOutage { minutes: i64, open_positions: usize },
+    /// Exchange reachable again after an outage
+    ExchangeRecovered {
+        minutes: i64,
+        deferred_actions: usize,
+    },
+    /// Reserve still dipped into after a flash reserve release ended
+    ReserveNotRestored { shortfall: Decimal },
+    /// State saves keep failing; a restart would lose recent changes
+    PersistenceFailure { failures: u32, minutes: i64 },
+}
+
+impl RiskAlertType {
+    /// Class the alert is routed by.
+    pub fn class(&self) -> AlertClass {
+        match self {
+            RiskAlertType::MarginWarning { .. } => AlertClass::MarginWarning,
+            RiskAlertType::LiquidationRisk { .. } => AlertClass::LiquidationRisk,
+            RiskAlertType::PositionLoss { .. } => AlertClass::PositionLoss,
+            RiskAlertType::FundingAnomaly { .. } => AlertClass::FundingAnomaly,
+            RiskAlertType::Malfunction { .. } => AlertClass::Malfunction,
+            RiskAlertType::DrawdownExceeded { .. } => AlertClass::DrawdownExceeded,
+            RiskAlertType::DeltaDrift { .. } => AlertClass::DeltaDrift,
+            RiskAlertType::NotionalCapExceeded { .. } => AlertClass::NotionalCapExceeded,
+            RiskAlertType::FundingCollapse { .. } => AlertClass::FundingCollapse,
+            RiskAlertType::ExitLiquidity { .. } => AlertClass::ExitLiquidity,
+            RiskAlertType::ResidualDelta { .. } => AlertClass::ResidualDelta,
+            RiskAlertType::InsufficientFunds { .. } => AlertClass::InsufficientFunds,
+            RiskAlertType::FundingSpreadOpportunity { .. } => AlertClass::FundingSpreadOpportunity,
+            RiskAlertType::HedgeSwitchRecommended { .. } => AlertClass::HedgeSwitchRecommended,
+            RiskAlertType::BorrowRateSpike { .. } => AlertClass::BorrowRateSpike,
+            RiskAlertType::BorrowLimit { .. } => AlertClass::BorrowLimit,
+            RiskAlertType::BorrowLimitExceeded { .. } => AlertClass::BorrowLimitExceeded,
+            RiskAlertType::SettingsMismatch { .. } => AlertClass::SettingsMismatch,
+            RiskAlertType::ExchangeMaintenance { .. } => AlertClass::ExchangeMaintenance,
+            RiskAlertType::ExchangeOutage { .. } => AlertClass::ExchangeOutage,
+            RiskAlertType::ExchangeRecovered { .. } => AlertClass::ExchangeRecovered,
+            RiskAlertType::ReserveNotRestored { .. } => AlertClass::ReserveNotRestored,
+            RiskAlertType::PersistenceFailure { .. } => AlertClass::PersistenceFailure,
+        }
+    }
 }
 
 /// A unified risk alert.
@@ -116,6 +284,8 @@ pub struct RiskAlert {
     pub message: String,
     pub metrics: HashMap<String, Decimal>,
     pub suggested_action: String,
+    /// Reaction chosen by the alert router
+    pub action: AlertAction,
 }
 
 impl RiskAlert {
@@ -143,6 +313,7 @@ impl RiskAlert {
             message,
             metrics: HashMap::new(),
             suggested_action,
+            action: AlertAction::Notify,
         }
     }
 
@@ -163,6 +334,15 @@ impl RiskAlert {
             AlertSeverity::Critical => error!(target: "risk_alert", "RISK_ALERT: {}", json),
         }
     }
+
+    /// Emit per the routed action; log-only alerts skip the structured record.
+    pub fn dispatch(&self) {
+        if self.action == AlertAction::Log {
+            info!(target: "risk_alert", "{}: {}", self.severity.as_str(), self.message);
+        } else {
+            self.emit();
+        }
+    }
 }
 
 /// Result of comprehensive risk check.
@@ -201,8 +381,21 @@ pub struct RiskOrchestrator {
     liquidation_guard: LiquidationGuard,
     position_tracker: PositionTracker,
     funding_verifier: FundingVerifier,
+    funding_trend: FundingTrendMonitor,
+    funding_quarantine: FundingQuarantine,
+    borrow_spikes: BorrowSpikeMonitor,
+    exit_liquidity: ExitLiquidityMonitor,
+    /// Whether the current residual delta breach has been reported
+    residual_delta_alerted: bool,
     malfunction_detector: MalfunctionDetector,
+    rules: RuleEngine,
+    /// Latest net portfolio funding APR, `None` while nothing is held
+    portfolio_funding_apr: Option<Decimal>,
+    alert_router: AlertRouter,
+    /// An alert raised outside the risk check was routed to halt
+    halt_requested: bool,
     consecutive_risk_cycles: u32,
+    resumption: SupervisedResumption,
 }
 
 impl RiskOrchestrator {
@@ -221,6 +414,7 @@ impl RiskOrchestrator {
             max_errors_per_minute: config.max_errors_per_minute,
             max_consecutive_failures: config.max_consecutive_failures,
             emergency_delta_drift: config.emergency_delta_drift,
+            state_max_age_minutes: config.malfunction_state_max_age_minutes,
             ..Default::default()
         };
 
@@ -236,12 +430,29 @@ impl RiskOrchestrator {
             min_expected_yield: config.min_expected_yield,
             grace_period_hours: config.grace_period_hours,
             max_funding_deviation: config.max_funding_deviation,
+            funding_expectation_window_hours: config.funding_expectation_window_hours,
+            funding_collapse_ratio: config.funding_collapse_ratio,
+            exit_liquidity_check_minutes: config.exit_liquidity_check_minutes,
+            max_exit_cost_ratio: config.max_exit_cost_ratio,
             max_loss_usd: config.max_loss_usd,
             max_negative_apy: config.max_negative_apy,
+            borrow_spike_ratio: config.borrow_spike_ratio,
+            low_supply_spike_ratio: config.low_supply_spike_ratio,
+            borrow_holidays: Vec::new(), // Evaluated by the orchestrator's borrow spike monitor
+            borrow_limits: Default::default(), // Tracked alongside the live entry checks
+            reentry_cooldown_minutes: config.reentry_cooldown_minutes,
+            forced_exit_cooldown_minutes: config.forced_exit_cooldown_minutes,
             max_errors_per_minute: config.max_errors_per_minute,
             max_consecutive_failures: config.max_consecutive_failures,
             emergency_delta_drift: config.emergency_delta_drift,
+            max_residual_delta_usd: config.max_residual_delta_usd,
+            malfunction_state_max_age_minutes: config.malfunction_state_max_age_minutes,
             max_consecutive_risk_cycles: config.max_consecutive_risk_cycles,
+            resumption: config.resumption.clone(),
+            funding_quarantine: config.funding_quarantine.clone(),
+            safe_mode_after_minutes: 0, // Not used by risk orchestrator
+            persistence_watchdog: Default::default(),
+            rules: Vec::new(), // Evaluated by the orchestrator's rule engine
         };
 
         let margin_monitor = MarginMonitor::new(risk_config.clone());
@@ -251,10 +462,32 @@ impl RiskOrchestrator {
             drawdown_tracker: DrawdownTracker::new(config.max_drawdown, initial_equity),
             margin_monitor,
             liquidation_guard,
-            position_tracker: PositionTracker::new(position_loss_config),
-            funding_verifier: FundingVerifier::new(config.max_funding_deviation),
+            position_tracker: PositionTracker::new(position_loss_config).with_cooldowns(
+                chrono::Duration::minutes(config.reentry_cooldown_minutes as i64),
+                chrono::Duration::minutes(config.forced_exit_cooldown_minutes as i64),
+            ),
+            funding_verifier: FundingVerifier::new(config.max_funding_deviation).with_rate_window(
+                chrono::Duration::hours(config.funding_expectation_window_hours as i64),
+            ),
+            funding_trend: FundingTrendMonitor::new(config.funding_collapse_ratio),
+            funding_quarantine: FundingQuarantine::new(config.funding_quarantine.clone()),
+            borrow_spikes: BorrowSpikeMonitor::new(
+                config.borrow_spike_ratio,
+                config.low_supply_spike_ratio,
+                &config.borrow_holidays,
+            ),
+            exit_liquidity: ExitLiquidityMonitor::new(
+                chrono::Duration::minutes(config.exit_liquidity_check_minutes as i64),
+                config.max_exit_cost_ratio,
+            ),
             malfunction_detector: MalfunctionDetector::new(malfunction_config),
+            residual_delta_alerted: false,
+            rules: RuleEngine::new(config.rules.clone()),
+            portfolio_funding_apr: None,
+            alert_router: AlertRouter::new(config.alert_routes.clone()),
+            halt_requested: false,
             consecutive_risk_cycles: 0,
+            resumption: SupervisedResumption::new(config.resumption.clone()),
             config,
         }
     }
@@ -280,7 +513,6 @@ impl RiskOrchestrator {
         result.drawdown_pct = self.drawdown_tracker.current_drawdown();
 
         if drawdown_exceeded {
-            result.should_halt = true;
             result.alerts.push(
                 RiskAlert::new(
                     RiskAlertType::DrawdownExceeded {
@@ -308,7 +540,6 @@ impl RiskOrchestrator {
 
         match worst_health {
             MarginHealth::Red => {
-                result.should_halt = true;
                 result.should_reduce_exposure = true;
                 result.alerts.push(RiskAlert::new(
                     RiskAlertType::MarginWarning {
@@ -390,8 +621,13 @@ impl RiskOrchestrator {
                 format!("{:?}", action),
             ));
 
-            // Add to positions to close if critical
-            if matches!(action, LiquidationAction::ClosePosition { .. }) {
+            // Close the position if its route allows automatic reductions
+            if matches!(action, LiquidationAction::ClosePosition { .. })
+                && self
+                    .alert_router
+                    .action(AlertClass::LiquidationRisk, severity)
+                    >= AlertAction::AutoReduce
+            {
                 result.positions_to_close.push(symbol);
             }
         }
@@ -404,7 +640,7 @@ impl RiskOrchestrator {
             .cloned()
             .collect::<Vec<_>>()
         {
-            match self.position_tracker.evaluate_position(&symbol) {
+            match self.evaluate_position(&symbol) {
                 PositionAction::ForceExit { reason } => {
                     result.positions_to_close.push(symbol.clone());
                     result.alerts.push(RiskAlert::new(
@@ -442,15 +678,53 @@ impl RiskOrchestrator {
             }
         }
 
-        // 5. Check for malfunctions
+        // 5. Check predicted funding trajectory of held positions
+        for collapse in self.funding_trend.check() {
+            result.alerts.push(
+                RiskAlert::new(
+                    RiskAlertType::FundingCollapse {
+                        symbol: collapse.symbol.clone(),
+                        entry_rate: collapse.entry_rate,
+                        predicted_rate: collapse.predicted_rate,
+                    },
+                    AlertSeverity::Warning,
+                    Some(collapse.symbol.clone()),
+                    format!(
+                        "Predicted funding for {} collapsed to {:.4}% (entry {:.4}%)",
+                        collapse.symbol,
+                        collapse.predicted_rate * dec!(100),
+                        collapse.entry_rate * dec!(100)
+                    ),
+                    format!(
+                        "Consider exiting {} before the next settlement",
+                        collapse.symbol
+                    ),
+                )
+                .with_metric("entry_rate", collapse.entry_rate)
+                .with_metric("predicted_rate", collapse.predicted_rate)
+                .with_metric("change_per_hour", collapse.change_per_hour),
+            );
+        }
+
+        // 6. Check for malfunctions
         if self.malfunction_detector.should_halt_trading() {
             result.should_halt = true;
             result.malfunction_detected = true;
         }
 
-        // Emit all alerts
+        // Route alerts; halting routes stop trading
+        for alert in &mut result.alerts {
+            self.route(alert);
+            if alert.action == AlertAction::Halt {
+                result.should_halt = true;
+            }
+        }
+        if std::mem::take(&mut self.halt_requested) {
+            result.should_halt = true;
+        }
+
         for alert in &result.alerts {
-            alert.emit();
+            alert.dispatch();
         }
 
         // Circuit breaker: track consecutive cycles with ERROR/CRITICAL alerts
@@ -469,27 +743,34 @@ impl RiskOrchestrator {
             );
 
             if self.consecutive_risk_cycles >= self.config.max_consecutive_risk_cycles {
-                result.should_halt = true;
-                error!(
-                    "🚨 [CIRCUIT BREAKER] Trading halted after {} consecutive cycles with ERROR/CRITICAL alerts",
-                    self.consecutive_risk_cycles
-                );
-
-                result.alerts.push(
-                    RiskAlert::new(
-                        RiskAlertType::Malfunction {
-                            malfunction_type: "CircuitBreakerTripped".to_string(),
-                        },
-                        AlertSeverity::Critical,
-                        None,
-                        format!(
-                            "Circuit breaker triggered: {} consecutive risk cycles with critical alerts",
-                            self.consecutive_risk_cycles
-                        ),
-                        "Halt all trading immediately - manual intervention required".to_string(),
-                    )
-                    .with_metric("consecutive_risk_cycles", Decimal::from(self.consecutive_risk_cycles)),
-                );
+                let mut alert = RiskAlert::new(
+                    RiskAlertType::Malfunction {
+                        malfunction_type: "CircuitBreakerTripped".to_string(),
+                    },
+                    AlertSeverity::Critical,
+                    None,
+                    format!(
+                        "Circuit breaker triggered: {} consecutive risk cycles with critical alerts",
+                        self.consecutive_risk_cycles
+                    ),
+                    "Halt all trading immediately - manual intervention required".to_string(),
+                )
+                .with_metric("consecutive_risk_cycles", Decimal::from(self.consecutive_risk_cycles));
+                self.route(&mut alert);
+
+                if alert.action == AlertAction::Halt {
+                    result.should_halt = true;
+                    error!(
+                        "🚨 [CIRCUIT BREAKER] Trading halted after {} consecutive cycles with ERROR/CRITICAL alerts",
+                        self.consecutive_risk_cycles
+                    );
+                } else {
+                    warn!(
+                        "⚠️  [CIRCUIT BREAKER] {} consecutive cycles with ERROR/CRITICAL alerts; routed to {}, not halting",
+                        self.consecutive_risk_cycles, alert.action
+                    );
+                }
+                result.alerts.push(alert);
             }
         } else {
             if self.consecutive_risk_cycles > 0 {
@@ -501,25 +782,112 @@ impl RiskOrchestrator {
             self.consecutive_risk_cycles = 0;
         }
 
+        if !result.should_halt {
+            match self.resumption.record_cycle(!has_critical_alerts) {
+                Some(ResumptionEvent::Advanced(throttle)) => {
+                    info!(%throttle, "Resumption advanced after a clean risk cycle");
+                }
+                Some(ResumptionEvent::Restarted(throttle)) => {
+                    warn!(%throttle, "Resumption ramp restarted after ERROR/CRITICAL alerts");
+                }
+                Some(ResumptionEvent::Completed) => {
+                    info!("Resumption complete, new entries back to normal");
+                }
+                None => {}
+            }
+        }
+
         result
     }
 
+    /// Route an alert raised outside [`check_all`](Self::check_all) and dispatch it.
+    ///
+    /// A halting route stops trading at the next risk check. The routed alert is
+    /// returned so callers can tell whether to execute its reduction.
+    pub fn raise(&mut self, mut alert: RiskAlert) -> RiskAlert {
+        self.route(&mut alert);
+        if alert.action == AlertAction::Halt {
+            warn!(
+                "🚨 [RISK] {} alert routed to halt - trading stops at the next risk check",
+                alert.alert_type.class()
+            );
+            self.halt_requested = true;
+        }
+        alert.dispatch();
+        alert
+    }
+
+    /// Configured alert routing.
+    pub fn alert_router(&self) -> &AlertRouter {
+        &self.alert_router
+    }
+
+    fn route(&self, alert: &mut RiskAlert) {
+        alert.action = self
+            .alert_router
+            .action(alert.alert_type.class(), alert.severity);
+    }
+
     /// Check for malfunctions only (lighter check for each loop iteration).
     /// Returns true if trading should be halted due to malfunctions.
     pub fn check_malfunctions(&self) -> bool {
         self.malfunction_detector.should_halt_trading()
     }
 
+    /// Malfunction detector and circuit breaker state to persist.
+    pub fn malfunction_state(&self, now: DateTime<Utc>) -> PersistedMalfunctionState {
+        let mut state = self.malfunction_detector.persisted_state(now);
+        state.consecutive_risk_cycles = self.consecutive_risk_cycles;
+        state
+    }
+
+    /// Restore the malfunction state persisted by a previous run.
+    ///
+    /// The circuit breaker count is kept when the state is recent. A previous
+    /// halt that has decayed is treated as reset: trading resumes under
+    /// supervision.
+    pub fn restore_malfunction_state(
+        &mut self,
+        state: &PersistedMalfunctionState,
+        now: DateTime<Utc>,
+    ) -> MalfunctionRestore {
+        let mut restored = self.malfunction_detector.restore(state, now);
+        let max_age =
+            chrono::Duration::minutes(self.config.malfunction_state_max_age_minutes as i64);
+        if now - state.saved_at <= max_age {
+            self.consecutive_risk_cycles = state.consecutive_risk_cycles;
+            restored.consecutive_risk_cycles = state.consecutive_risk_cycles;
+        }
+        if restored.halt_expired {
+            self.resumption.start();
+        }
+        restored
+    }
+
     /// Get active alerts.
     pub fn get_active_alerts(&self) -> &[MalfunctionAlert] {
         self.malfunction_detector.get_active_alerts()
     }
 
+    /// Mark whether the exchange is in scheduled maintenance (errors not counted).
+    pub fn set_expected_downtime(&mut self, expected: bool) {
+        self.malfunction_detector.set_expected_downtime(expected);
+    }
+
     /// Record an API/execution error.
     pub fn record_error(&mut self, error: &str) -> Option<MalfunctionAlert> {
         self.malfunction_detector.record_error(error)
     }
 
+    /// Record a failed exchange call, weighed by the class of its error.
+    pub fn record_api_error(
+        &mut self,
+        context: &str,
+        error: &anyhow::Error,
+    ) -> Option<MalfunctionAlert> {
+        self.malfunction_detector.record_api_error(context, error)
+    }
+
     /// Record order failure for a symbol.
     pub fn record_order_failure(&mut self, symbol: &str) -> Option<MalfunctionAlert> {
         self.malfunction_detector.record_order_failure(symbol)
@@ -530,6 +898,17 @@ impl RiskOrchestrator {
         self.malfunction_detector.record_order_success(symbol)
     }
 
+    /// Record a tripped order valve (halts trading).
+    pub fn record_order_velocity_breach(
+        &mut self,
+        limit: &str,
+        observed: Decimal,
+        max: Decimal,
+    ) -> MalfunctionAlert {
+        self.malfunction_detector
+            .record_order_velocity_breach(limit, observed, max)
+    }
+
     /// Check delta drift.
     pub fn check_delta_drift(
         &mut self,
@@ -540,6 +919,56 @@ impl RiskOrchestrator {
             .check_delta_drift(symbol, drift_pct)
     }
 
+    /// Check the portfolio's net residual delta against its threshold.
+    ///
+    /// Residuals intended by partial hedge targets are excluded: only the net drift
+    /// beyond them counts. Alerts once when it first exceeds `max_residual_delta_usd`
+    /// and re-arms after it falls back within the threshold.
+    pub fn check_residual_delta(&mut self, report: &ExposureReport) -> Option<RiskAlert> {
+        let threshold = self.config.max_residual_delta_usd;
+        if report.net_drift_usd.abs() <= threshold {
+            self.residual_delta_alerted = false;
+            return None;
+        }
+        if self.residual_delta_alerted {
+            return None;
+        }
+        self.residual_delta_alerted = true;
+
+        let direction = if report.net_drift_usd > Decimal::ZERO {
+            "long"
+        } else {
+            "short"
+        };
+        let largest = report
+            .symbols
+            .first()
+            .map(|s| format!(", largest {} ${:.2}", s.symbol, s.drift_usd))
+            .unwrap_or_default();
+        Some(
+            RiskAlert::new(
+                RiskAlertType::ResidualDelta {
+                    net_drift_usd: report.net_drift_usd,
+                    threshold,
+                },
+                AlertSeverity::Warning,
+                None,
+                format!(
+                    "Book is net {} ${:.2} beyond its hedge targets (threshold ${:.2}{})",
+                    direction,
+                    report.net_drift_usd.abs(),
+                    threshold,
+                    largest
+                ),
+                "Rebalance the largest residuals back to their targets".to_string(),
+            )
+            .with_metric("net_delta_usd", report.net_delta_usd)
+            .with_metric("net_drift_usd", report.net_drift_usd)
+            .with_metric("gross_delta_usd", report.gross_delta_usd)
+            .with_metric("net_delta_pct", report.net_delta_pct()),
+        )
+    }
+
     /// Open a tracked position (entry contains symbol).
     pub fn open_position(&mut self, entry: PositionEntry) {
         let symbol = entry.symbol.clone();
@@ -547,6 +976,28 @@ impl RiskOrchestrator {
         self.position_tracker.open_position(&symbol, entry);
         self.funding_verifier
             .set_expected_rate(&symbol, expected_rate);
+        self.funding_trend.track(&symbol, expected_rate);
+        // Negative funding is hedged by borrowing and selling the base asset
+        if expected_rate < Decimal::ZERO {
+            self.borrow_spikes.track(&symbol);
+        }
+    }
+
+    /// Lower the collapse baseline of a position entered at a capped funding rate.
+    ///
+    /// A rate pinned at the exchange cap is expected to fall back, so only
+    /// `persistence` of it counts as the entry rate when watching for a collapse.
+    pub fn discount_capped_entry(&mut self, symbol: &str, persistence: Decimal) {
+        if let Some(pos) = self.position_tracker.get_position(symbol) {
+            let baseline = pos.expected_funding_rate * persistence;
+            self.funding_trend.track(symbol, baseline);
+        }
+    }
+
+    /// Set the settlement schedules of the traded venue, which funding payments
+    /// are verified against.
+    pub fn set_funding_calendar(&mut self, calendar: FundingCalendar, venue: Venue) {
+        self.funding_verifier.set_calendar(calendar, venue);
     }
 
     /// Record funding payment for a symbol.
@@ -558,6 +1009,136 @@ impl RiskOrchestrator {
         }
     }
 
+    /// Record an observed funding rate for rolling-average verification
+    /// and trajectory monitoring.
+    pub fn observe_funding_rate(&mut self, symbol: &str, rate: Decimal) {
+        self.funding_verifier.record_observed_rate(symbol, rate);
+        self.funding_trend.record(symbol, rate);
+    }
+
+    /// Record an observed borrow rate of a held position's base asset.
+    ///
+    /// Only positions hedged by borrowing are monitored; other symbols are ignored.
+    pub fn observe_borrow_rate(&mut self, symbol: &str, rate: Decimal, now: DateTime<Utc>) {
+        self.borrow_spikes.record_at(symbol, rate, now);
+    }
+
+    /// Raise an alert for each newly spiked borrow rate and return the spikes.
+    pub fn check_borrow_spikes(&mut self, now: DateTime<Utc>) -> Vec<BorrowSpike> {
+        let spikes = self.borrow_spikes.check(now);
+        for spike in &spikes {
+            let alert = RiskAlert::new(
+                RiskAlertType::BorrowRateSpike {
+                    symbol: spike.symbol.clone(),
+                    rate: spike.rate,
+                    baseline: spike.baseline,
+                },
+                AlertSeverity::Warning,
+                Some(spike.symbol.clone()),
+                format!(
+                    "Borrow rate for {} spiked to {:.4}% ({:.1}x its weekly median{})",
+                    spike.symbol,
+                    spike.rate * dec!(100),
+                    spike.multiple(),
+                    if spike.low_supply {
+                        ", low-supply period"
+                    } else {
+                        ""
+                    }
+                ),
+                format!(
+                    "Move the {} hedge off margin or exit before interest outruns funding",
+                    spike.symbol
+                ),
+            )
+            .with_metric("rate", spike.rate)
+            .with_metric("baseline", spike.baseline)
+            .with_metric("multiple", spike.multiple());
+            self.raise(alert);
+        }
+        spikes
+    }
+
+    /// Held symbols whose borrow rate is currently spiking.
+    pub fn borrow_spiking(&self) -> Vec<String> {
+        self.borrow_spikes.spiking().map(str::to_string).collect()
+    }
+
+    /// Whether a tracked position is due for an exit liquidity sample.
+    pub fn exit_liquidity_due(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.position_tracker.get_position(symbol).is_some()
+            && self.exit_liquidity.is_due(symbol, now)
+    }
+
+    /// Price unwinding a tracked position's spot hedge against a book sample.
+    ///
+    /// `hedge_qty` is positive for a long spot hedge and negative for a short one.
+    /// Returns a breach when the exit would cost more than the position's funding
+    /// budget allows.
+    pub fn assess_exit_liquidity(
+        &mut self,
+        symbol: &str,
+        book: &OrderBook,
+        hedge_qty: Decimal,
+        now: DateTime<Utc>,
+    ) -> Option<ExitLiquidityBreach> {
+        let pos = self.position_tracker.get_position(symbol)?;
+        let expected_payment = pos.expected_funding_rate * pos.position_value;
+        let accrued_funding = pos.total_funding_received;
+        self.exit_liquidity.assess(
+            symbol,
+            &book.bids,
+            &book.asks,
+            hedge_qty,
+            accrued_funding,
+            expected_payment,
+            now,
+        )
+    }
+
+    /// Import historical funding payments for a tracked position.
+    ///
+    /// Restores cumulative funding and collection counts after a restart. Payments
+    /// before the position was opened are ignored. `long_futures` is the side of
+    /// the futures leg. Returns the number imported.
+    pub fn import_funding_history(
+        &mut self,
+        symbol: &str,
+        payments: &[(DateTime<Utc>, Decimal)],
+        long_futures: bool,
+    ) -> usize {
+        let Some(pos) = self.position_tracker.get_position(symbol) else {
+            return 0;
+        };
+        let opened_at = pos.opened_at;
+        let position_value = pos.position_value;
+
+        let mut imported = 0;
+        for (timestamp, amount) in payments.iter().filter(|(t, _)| *t >= opened_at) {
+            // Historical expectation is unknown - count the payment as expected
+            self.position_tracker
+                .record_funding(symbol, *amount, *amount);
+            self.funding_verifier.import_payment(
+                symbol,
+                position_value,
+                *amount,
+                long_futures,
+                *timestamp,
+            );
+            imported += 1;
+        }
+
+        if imported > 0 {
+            info!(
+                symbol = %symbol,
+                imported,
+                "Imported funding history"
+            );
+        }
+
+        imported
+    }
+
     /// Verify funding payment against expected.
     pub fn verify_funding(
         &mut self,
@@ -573,12 +1154,55 @@ impl RiskOrchestrator {
                 funding_received: actual_funding,
                 funding_expected: Decimal::ZERO,
                 deviation_pct: Decimal::ZERO,
+                basis: ExpectationBasis::EntryRate,
                 is_anomaly: false,
                 anomaly_reason: None,
             }
         }
     }
 
+    /// Count a verified payment towards the symbol's anomaly streak.
+    ///
+    /// Returns the quarantine when the payment completes a streak.
+    pub fn record_funding_verification(
+        &mut self,
+        result: &FundingVerificationResult,
+        now: DateTime<Utc>,
+    ) -> Option<PersistedQuarantine> {
+        self.funding_quarantine
+            .record(&result.symbol, result.is_anomaly, result.deviation_pct, now)
+    }
+
+    /// Whether `symbol` is quarantined for funding anomalies.
+    pub fn is_quarantined(&self, symbol: &str) -> bool {
+        self.funding_quarantine.is_quarantined(symbol)
+    }
+
+    /// Symbols quarantined for funding anomalies, alphabetically.
+    pub fn quarantined(&self) -> Vec<&PersistedQuarantine> {
+        self.funding_quarantine.quarantined()
+    }
+
+    /// Whether the held position of `symbol` is closed for its quarantine.
+    pub fn quarantine_requires_exit(&self, symbol: &str) -> bool {
+        self.funding_quarantine.requires_exit(symbol)
+    }
+
+    /// Reinstate a quarantine persisted by an earlier run.
+    pub fn restore_quarantine(&mut self, quarantine: PersistedQuarantine) {
+        self.funding_quarantine.restore(quarantine);
+    }
+
+    /// Release a quarantine. Returns whether the symbol was quarantined.
+    pub fn release_quarantine(&mut self, symbol: &str) -> bool {
+        self.funding_quarantine.release(symbol)
+    }
+
+    /// Restore the funding paid of a persisted position.
+    pub fn restore_funding_paid(&mut self, symbol: &str, paid: Decimal) {
+        self.position_tracker.restore_funding_paid(symbol, paid);
+    }
+
     /// Record interest payment.
     pub fn record_interest(&mut self, symbol: &str, amount: Decimal) {
         self.position_tracker.record_interest(symbol, amount);
@@ -589,17 +1213,94 @@ impl RiskOrchestrator {
         self.position_tracker.update_pnl(symbol, unrealized);
     }
 
-    /// Evaluate a position.
+    /// Apply a futures position change pushed by the exchange.
+    ///
+    /// Updates the PnL of a tracked position. Returns whether a tracked position
+    /// is now flat on the exchange without the bot having closed it, as after a
+    /// liquidation, an auto-deleverage or a manual close.
+    pub fn apply_position_update(
+        &mut self,
+        symbol: &str,
+        position_amt: Decimal,
+        unrealized: Decimal,
+    ) -> bool {
+        if self.position_tracker.get_position(symbol).is_none() {
+            return false;
+        }
+        if position_amt.is_zero() {
+            return true;
+        }
+        self.update_position_pnl(symbol, unrealized);
+        false
+    }
+
+    /// Evaluate a position against the loss limits and the operator rules.
+    ///
+    /// A rule can only escalate the tracker's verdict, never soften it. A
+    /// quarantine configured to exit closes the position.
     pub fn evaluate_position(&mut self, symbol: &str) -> PositionAction {
-        self.position_tracker.evaluate_position(symbol)
+        let action = self.position_tracker.evaluate_position(symbol);
+        if action.requires_close() {
+            return action;
+        }
+        if self.funding_quarantine.requires_exit(symbol) {
+            return PositionAction::ForceExit {
+                reason: "Quarantined for persistent funding anomalies".to_string(),
+            };
+        }
+        let portfolio = self.portfolio_metrics();
+        let Some(position) = self.position_tracker.get_position(symbol) else {
+            return action;
+        };
+        let Some(rule) = self.rules.exit_rule(position, &portfolio) else {
+            return action;
+        };
+        let reason = format!("Rule '{}': {}", rule.name, rule);
+        match rule.action {
+            RuleAction::ForceExit => PositionAction::ForceExit { reason },
+            RuleAction::ConsiderExit if !matches!(action, PositionAction::ConsiderExit { .. }) => {
+                PositionAction::ConsiderExit {
+                    reason,
+                    hours_unprofitable: position.hours_open() as u32,
+                }
+            }
+            _ => action,
+        }
+    }
+
+    /// Record the latest net portfolio funding APR for the rules.
+    pub fn set_portfolio_funding_apr(&mut self, apr: Option<Decimal>) {
+        self.portfolio_funding_apr = apr;
+    }
+
+    /// Portfolio-wide values the rules read.
+    pub fn portfolio_metrics(&self) -> PortfolioMetrics {
+        PortfolioMetrics {
+            funding_apr: self.portfolio_funding_apr,
+            drawdown: self.drawdown_tracker.current_drawdown(),
+            position_count: self.position_tracker.all_positions().len(),
+        }
+    }
+
+    /// The operator rule halting new entries, if any fires.
+    pub fn entry_halt_rule(&self) -> Option<&RiskRule> {
+        self.rules.entry_halt(&self.portfolio_metrics())
     }
 
-    /// Close a tracked position.
-    pub fn close_position(&mut self, symbol: &str) -> Option<TrackedPosition> {
+    /// Close a tracked position, starting its re-entry cool-down.
+    pub fn close_position(&mut self, symbol: &str, kind: ExitKind) -> Option<TrackedPosition> {
         self.funding_verifier.clear_expected_rate(symbol);
         self.funding_verifier.clear_stats(symbol);
         self.malfunction_detector.clear_symbol_alerts(symbol);
-        self.position_tracker.close_position(symbol)
+        self.funding_trend.untrack(symbol);
+        self.borrow_spikes.untrack(symbol);
+        self.exit_liquidity.untrack(symbol);
+        self.position_tracker.close_position(symbol, kind)
+    }
+
+    /// Symbols held out of new entries after a recent exit.
+    pub fn cooling_down(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.position_tracker.cooling_down(now)
     }
 
     /// Get positions requiring forced closure.
@@ -629,14 +1330,30 @@ impl RiskOrchestrator {
     }
 
     /// Reset halt condition.
+    ///
+    /// Trading resumes under supervision: the next cycle only observes, then new
+    /// entries ramp back up (see [`entry_throttle`](Self::entry_throttle)).
     pub fn reset_halt(&mut self) {
         self.malfunction_detector.reset_halt();
+        self.consecutive_risk_cycles = 0;
+        if self.resumption.start() {
+            info!(
+                ramp_cycles = self.config.resumption.ramp_cycles,
+                "Supervised resumption started, next cycle is observe-only"
+            );
+        }
+    }
+
+    /// Limits on new entries while trading resumes after a halt, `None` otherwise.
+    pub fn entry_throttle(&self) -> Option<EntryThrottle> {
+        self.resumption.throttle()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::risk::SymbolExposure;
 
     #[test]
     fn test_orchestrator_creation() {
@@ -659,13 +1376,15 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
 
         orchestrator.open_position(entry);
         assert!(orchestrator.get_tracked_position("BTCUSDT").is_some());
 
-        orchestrator.close_position("BTCUSDT");
+        orchestrator.close_position("BTCUSDT", ExitKind::Routine);
         assert!(orchestrator.get_tracked_position("BTCUSDT").is_none());
+        assert_eq!(orchestrator.cooling_down(Utc::now()), vec!["BTCUSDT"]);
     }
 
     #[test]
@@ -720,7 +1439,7 @@ mod tests {
 
         // First cycle with ERROR alert - should not halt
         let result1 = orchestrator.check_all(
-            &[position.clone()],
+            std::slice::from_ref(&position),
             equity,
             margin_balance,
             &maintenance_rates,
@@ -730,7 +1449,7 @@ mod tests {
 
         // Second cycle with ERROR alert - should not halt
         let result2 = orchestrator.check_all(
-            &[position.clone()],
+            std::slice::from_ref(&position),
             equity,
             margin_balance,
             &maintenance_rates,
@@ -739,7 +1458,7 @@ mod tests {
 
         // Third cycle with ERROR alert - SHOULD HALT (circuit breaker triggered)
         let result3 = orchestrator.check_all(
-            &[position.clone()],
+            std::slice::from_ref(&position),
             equity,
             margin_balance,
             &maintenance_rates,
@@ -784,13 +1503,13 @@ mod tests {
 
         // Two cycles with ERROR alerts
         orchestrator.check_all(
-            &[error_position.clone()],
+            std::slice::from_ref(&error_position),
             equity,
             margin_balance,
             &maintenance_rates,
         );
         orchestrator.check_all(
-            &[error_position.clone()],
+            std::slice::from_ref(&error_position),
             equity,
             margin_balance,
             &maintenance_rates,
@@ -803,13 +1522,13 @@ mod tests {
 
         // Now even after 2 more cycles with alerts, should not halt (counter was reset)
         orchestrator.check_all(
-            &[error_position.clone()],
+            std::slice::from_ref(&error_position),
             equity,
             margin_balance,
             &maintenance_rates,
         );
         let result = orchestrator.check_all(
-            &[error_position.clone()],
+            std::slice::from_ref(&error_position),
             equity,
             margin_balance,
             &maintenance_rates,
@@ -1008,6 +1727,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_residual_delta_alerts_once_until_rearmed() {
+        let mut orchestrator = RiskOrchestrator::new(
+            RiskOrchestratorConfig {
+                max_residual_delta_usd: dec!(500),
+                ..Default::default()
+            },
+            dec!(10000),
+        );
+        let report = |residual_qty: Decimal| {
+            ExposureReport::new(vec![SymbolExposure::new(
+                "BTCUSDT",
+                dec!(-1),
+                dec!(1) + residual_qty,
+                dec!(50000),
+            )])
+        };
+
+        // $250 net long is within the threshold
+        assert!(orchestrator
+            .check_residual_delta(&report(dec!(0.005)))
+            .is_none());
+
+        // $1000 net short breaches it once
+        let alert = orchestrator
+            .check_residual_delta(&report(dec!(-0.02)))
+            .unwrap();
+        assert!(matches!(
+            alert.alert_type,
+            RiskAlertType::ResidualDelta { net_drift_usd, .. } if net_drift_usd == dec!(-1000)
+        ));
+        assert!(orchestrator
+            .check_residual_delta(&report(dec!(-0.02)))
+            .is_none());
+
+        // Back within the threshold re-arms the alert
+        assert!(orchestrator
+            .check_residual_delta(&report(Decimal::ZERO))
+            .is_none());
+        assert!(orchestrator
+            .check_residual_delta(&report(dec!(0.02)))
+            .is_some());
+    }
+
     // =========================================================================
     // Drawdown Check Tests
     // =========================================================================
@@ -1118,6 +1881,76 @@ mod tests {
         assert_eq!(result.margin_health, MarginHealth::Orange);
     }
 
+    #[test]
+    fn test_alert_routes_override_reactions() {
+        let notify_critical = SeverityRoutes {
+            critical: Some(AlertAction::Notify),
+            ..Default::default()
+        };
+        let config = RiskOrchestratorConfig {
+            alert_routes: BTreeMap::from([
+                (AlertClass::MarginWarning, notify_critical.clone()),
+                (AlertClass::LiquidationRisk, notify_critical),
+                (
+                    AlertClass::ExchangeOutage,
+                    SeverityRoutes {
+                        critical: Some(AlertAction::Halt),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+        let mut orchestrator = RiskOrchestrator::new(config, dec!(10000));
+
+        // RED margin health: reported, but neither halts nor closes
+        let position = crate::exchange::Position {
+            symbol: "BTCUSDT".to_string(),
+            position_amt: dec!(1.0),
+            entry_price: dec!(50000),
+            unrealized_profit: Decimal::ZERO,
+            leverage: 10,
+            notional: dec!(50000),
+            isolated_margin: dec!(50),
+            mark_price: dec!(50000),
+            liquidation_price: dec!(45000),
+            position_side: crate::exchange::PositionSide::Both,
+            margin_type: crate::exchange::MarginType::Isolated,
+        };
+        let rates = HashMap::from([("BTCUSDT".to_string(), dec!(0.004))]);
+        let result = orchestrator.check_all(&[position], dec!(10000), dec!(100000), &rates);
+        assert_eq!(result.margin_health, MarginHealth::Red);
+        assert!(!result.should_halt);
+        assert!(result.positions_to_close.is_empty());
+        assert!(result
+            .alerts
+            .iter()
+            .all(|alert| alert.action == AlertAction::Notify));
+
+        // Alerts raised outside the risk check halt at the next one
+        let outage = orchestrator.raise(RiskAlert::new(
+            RiskAlertType::ExchangeOutage {
+                minutes: 10,
+                open_positions: 1,
+            },
+            AlertSeverity::Critical,
+            None,
+            "Exchange unreachable".to_string(),
+            "Safe-mode".to_string(),
+        ));
+        assert_eq!(outage.action, AlertAction::Halt);
+        assert!(
+            orchestrator
+                .check_all(&[], dec!(10000), dec!(10000), &HashMap::new())
+                .should_halt
+        );
+        assert!(
+            !orchestrator
+                .check_all(&[], dec!(10000), dec!(10000), &HashMap::new())
+                .should_halt
+        );
+    }
+
     // =========================================================================
     // Order Recording Tests
     // =========================================================================
@@ -1194,6 +2027,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
         orchestrator.open_position(entry);
 
@@ -1205,6 +2039,90 @@ mod tests {
         assert_eq!(pos.total_funding_received, dec!(0.5));
     }
 
+    #[test]
+    fn test_quarantine_forces_exit() {
+        let config = RiskOrchestratorConfig {
+            funding_quarantine: FundingQuarantineConfig {
+                anomalies: 2,
+                exit: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut orchestrator = RiskOrchestrator::new(config, dec!(10000));
+        orchestrator.open_position(PositionEntry {
+            symbol: "BTCUSDT".to_string(),
+            entry_price: dec!(50000),
+            quantity: dec!(0.1),
+            expected_funding_rate: dec!(0.0001),
+            entry_fees: dec!(2),
+            position_value: dec!(5000),
+            opened_at: None,
+            first_funding_at: None,
+        });
+
+        let anomaly = FundingVerificationResult {
+            symbol: "BTCUSDT".to_string(),
+            funding_received: dec!(0.1),
+            funding_expected: dec!(0.5),
+            deviation_pct: dec!(0.8),
+            basis: ExpectationBasis::EntryRate,
+            is_anomaly: true,
+            anomaly_reason: None,
+        };
+        let now = Utc::now();
+        assert!(orchestrator
+            .record_funding_verification(&anomaly, now)
+            .is_none());
+        assert!(!orchestrator.is_quarantined("BTCUSDT"));
+        assert!(orchestrator
+            .record_funding_verification(&anomaly, now)
+            .is_some());
+        assert!(orchestrator.quarantine_requires_exit("BTCUSDT"));
+        assert!(matches!(
+            orchestrator.evaluate_position("BTCUSDT"),
+            PositionAction::ForceExit { .. }
+        ));
+
+        assert!(orchestrator.release_quarantine("BTCUSDT"));
+        assert!(orchestrator.quarantined().is_empty());
+        assert!(!orchestrator.evaluate_position("BTCUSDT").requires_close());
+    }
+
+    #[test]
+    fn test_import_funding_history_skips_payments_before_open() {
+        let config = RiskOrchestratorConfig::default();
+        let mut orchestrator = RiskOrchestrator::new(config, dec!(10000));
+        let opened_at = Utc::now() - chrono::Duration::hours(20);
+
+        orchestrator.open_position(PositionEntry {
+            symbol: "BTCUSDT".to_string(),
+            entry_price: dec!(50000),
+            quantity: dec!(0.1),
+            expected_funding_rate: dec!(0.0001),
+            entry_fees: dec!(2),
+            position_value: dec!(5000),
+            opened_at: Some(opened_at),
+            first_funding_at: None,
+        });
+
+        let payments = vec![
+            (opened_at - chrono::Duration::hours(4), dec!(9)), // previous position
+            (opened_at + chrono::Duration::hours(4), dec!(0.5)),
+            (opened_at + chrono::Duration::hours(12), dec!(0.7)),
+        ];
+        let imported = orchestrator.import_funding_history("BTCUSDT", &payments, false);
+
+        assert_eq!(imported, 2);
+        let pos = orchestrator.get_tracked_position("BTCUSDT").unwrap();
+        assert_eq!(pos.total_funding_received, dec!(1.2));
+        assert_eq!(pos.funding_collections, 2);
+        assert_eq!(
+            orchestrator.import_funding_history("ETHUSDT", &payments, false),
+            0
+        );
+    }
+
     // =========================================================================
     // Should Halt Tests
     // =========================================================================
@@ -1247,6 +2165,17 @@ mod tests {
         // Reset halt
         orchestrator.reset_halt();
         assert!(!orchestrator.check_malfunctions());
+
+        // Observe first, then one entry at a time as clean cycles pass
+        assert!(orchestrator.entry_throttle().unwrap().is_observe_only());
+        orchestrator.check_all(&[], dec!(10000), dec!(10000), &HashMap::new());
+        let throttle = orchestrator.entry_throttle().unwrap();
+        assert_eq!(throttle.max_entries, 1);
+        assert_eq!(throttle.size_scale, dec!(0.25));
+        for _ in 0..5 {
+            orchestrator.check_all(&[], dec!(10000), dec!(10000), &HashMap::new());
+        }
+        assert!(orchestrator.entry_throttle().is_none());
     }
 
     // =========================================================================
@@ -1267,6 +2196,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
         orchestrator.open_position(entry);
 
@@ -1296,6 +2226,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
         orchestrator.open_position(entry);
 
@@ -1305,6 +2236,14 @@ mod tests {
         // Position should be updated
         let pos = orchestrator.get_tracked_position("BTCUSDT").unwrap();
         assert_eq!(pos.unrealized_pnl, dec!(100));
+
+        // Pushed updates set the PnL and report a tracked position flattened
+        // on the exchange
+        assert!(!orchestrator.apply_position_update("BTCUSDT", dec!(-0.1), dec!(-40)));
+        let pos = orchestrator.get_tracked_position("BTCUSDT").unwrap();
+        assert_eq!(pos.unrealized_pnl, dec!(-40));
+        assert!(orchestrator.apply_position_update("BTCUSDT", dec!(0), dec!(0)));
+        assert!(!orchestrator.apply_position_update("ETHUSDT", dec!(0), dec!(0)));
     }
 
     // =========================================================================
@@ -1325,6 +2264,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         });
 
         orchestrator.open_position(PositionEntry {
@@ -1335,6 +2275,7 @@ mod tests {
             entry_fees: dec!(1),
             position_value: dec!(3000),
             opened_at: None,
+            first_funding_at: None,
         });
 
         let positions = orchestrator.get_all_tracked_positions();
@@ -1359,4 +2300,74 @@ mod tests {
         assert_eq!(stats.peak_equity, dec!(11000));
         assert_eq!(stats.current_equity, dec!(10500));
     }
+
+    // =========================================================================
+    // Funding Trend Tests
+    // =========================================================================
+
+    #[test]
+    fn test_collapsed_prediction_raises_alert() {
+        let config = RiskOrchestratorConfig::default();
+        let mut orchestrator = RiskOrchestrator::new(config, dec!(10000));
+
+        orchestrator.open_position(PositionEntry {
+            symbol: "BTCUSDT".to_string(),
+            entry_price: dec!(50000),
+            quantity: dec!(0.1),
+            expected_funding_rate: dec!(0.001),
+            entry_fees: dec!(2),
+            position_value: dec!(5000),
+            opened_at: None,
+            first_funding_at: None,
+        });
+
+        orchestrator.observe_funding_rate("BTCUSDT", dec!(0.0002));
+        let result = orchestrator.check_all(&[], dec!(10000), dec!(10000), &HashMap::new());
+
+        assert!(result.alerts.iter().any(|alert| matches!(
+            &alert.alert_type,
+            RiskAlertType::FundingCollapse { symbol, predicted_rate, .. }
+                if symbol == "BTCUSDT" && *predicted_rate == dec!(0.0002)
+        )));
+    }
+
+    #[test]
+    fn test_borrow_spikes_only_watch_borrowing_hedges() {
+        let mut orchestrator =
+            RiskOrchestrator::new(RiskOrchestratorConfig::default(), dec!(10000));
+        for (symbol, rate) in [("BTCUSDT", dec!(0.001)), ("ALTUSDT", dec!(-0.001))] {
+            orchestrator.open_position(PositionEntry {
+                symbol: symbol.to_string(),
+                entry_price: dec!(10),
+                quantity: dec!(100),
+                expected_funding_rate: rate,
+                entry_fees: dec!(1),
+                position_value: dec!(1000),
+                opened_at: None,
+                first_funding_at: None,
+            });
+        }
+
+        // Wednesday, well clear of the weekend
+        let start = DateTime::parse_from_rfc3339("2024-03-13T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for hour in 0..8 {
+            let at = start + chrono::Duration::hours(hour);
+            orchestrator.observe_borrow_rate("BTCUSDT", dec!(0.0001), at);
+            orchestrator.observe_borrow_rate("ALTUSDT", dec!(0.0001), at);
+        }
+        let now = start + chrono::Duration::hours(8);
+        orchestrator.observe_borrow_rate("BTCUSDT", dec!(0.001), now);
+        orchestrator.observe_borrow_rate("ALTUSDT", dec!(0.001), now);
+
+        // BTC is hedged with long spot and borrows nothing
+        let spikes = orchestrator.check_borrow_spikes(now);
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].symbol, "ALTUSDT");
+        assert_eq!(orchestrator.borrow_spiking(), vec!["ALTUSDT".to_string()]);
+
+        orchestrator.close_position("ALTUSDT", ExitKind::Routine);
+        assert!(orchestrator.borrow_spiking().is_empty());
+    }
 }
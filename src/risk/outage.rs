@@ -0,0 +1,192 @@
+//! Exchange outage detection and safe-mode.
+//!
+//! A single failed request is noise; an exchange that stays unreachable while
+//! positions are open is a risk in itself, because nothing can be closed or
+//! rebalanced. After the configured number of minutes the bot enters safe-mode:
+//! it keeps retrying the reads that risk checks depend on, skips scanning,
+//! allocation and rebalancing, and queues the exits it would have taken. When
+//! connectivity returns the queued actions are handed back for reconciliation and
+//! re-evaluation by the normal cycle.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Exit the risk checks wanted while the exchange was unreachable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeferredAction {
+    pub symbol: String,
+    /// What would have been done (e.g. "force_exit")
+    pub action: String,
+    pub reason: String,
+    /// When the action was first wanted
+    pub deferred_at: DateTime<Utc>,
+}
+
+/// Outage that ended, with the actions queued during safe-mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutageRecovery {
+    /// First failed request of the outage
+    pub started_at: DateTime<Utc>,
+    pub duration: Duration,
+    /// Whether safe-mode was entered
+    pub was_safe_mode: bool,
+    pub deferred: Vec<DeferredAction>,
+}
+
+/// Tracks exchange reachability and the safe-mode state.
+#[derive(Debug)]
+pub struct OutageMonitor {
+    /// Unreachable time before safe-mode (None = never)
+    safe_mode_after: Option<Duration>,
+    /// First failure of the current outage
+    first_failure: Option<DateTime<Utc>>,
+    safe_mode_since: Option<DateTime<Utc>>,
+    deferred: Vec<DeferredAction>,
+}
+
+impl OutageMonitor {
+    /// Monitor entering safe-mode after `safe_mode_after_minutes` (0 = never).
+    pub fn new(safe_mode_after_minutes: u32) -> Self {
+        Self {
+            safe_mode_after: (safe_mode_after_minutes > 0)
+                .then(|| Duration::minutes(safe_mode_after_minutes as i64)),
+            first_failure: None,
+            safe_mode_since: None,
+            deferred: Vec::new(),
+        }
+    }
+
+    /// Record a failed exchange request at `now`.
+    ///
+    /// Returns true when this failure enters safe-mode. Without open positions an
+    /// outage is only tracked; safe-mode is entered once positions are at stake.
+    pub fn record_failure(&mut self, now: DateTime<Utc>, has_open_positions: bool) -> bool {
+        let first_failure = *self.first_failure.get_or_insert(now);
+        let Some(safe_mode_after) = self.safe_mode_after else {
+            return false;
+        };
+        if self.safe_mode_since.is_some()
+            || !has_open_positions
+            || now - first_failure < safe_mode_after
+        {
+            return false;
+        }
+        self.safe_mode_since = Some(now);
+        true
+    }
+
+    /// Record a successful exchange request at `now`.
+    ///
+    /// Ends the current outage, if any, and hands back the deferred actions.
+    pub fn record_success(&mut self, now: DateTime<Utc>) -> Option<OutageRecovery> {
+        let started_at = self.first_failure.take()?;
+        Some(OutageRecovery {
+            started_at,
+            duration: now - started_at,
+            was_safe_mode: self.safe_mode_since.take().is_some(),
+            deferred: std::mem::take(&mut self.deferred),
+        })
+    }
+
+    /// Whether safe-mode is active.
+    pub fn is_safe_mode(&self) -> bool {
+        self.safe_mode_since.is_some()
+    }
+
+    /// How long the exchange has been unreachable.
+    pub fn outage_duration(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.first_failure.map(|started_at| now - started_at)
+    }
+
+    /// Queue an action for when connectivity returns.
+    ///
+    /// One action is kept per symbol; a later request replaces the reason but keeps
+    /// the time it was first wanted. Returns true when the symbol is newly queued.
+    pub fn defer(&mut self, symbol: &str, action: &str, reason: &str, now: DateTime<Utc>) -> bool {
+        if let Some(existing) = self.deferred.iter_mut().find(|d| d.symbol == symbol) {
+            existing.action = action.to_string();
+            existing.reason = reason.to_string();
+            return false;
+        }
+        self.deferred.push(DeferredAction {
+            symbol: symbol.to_string(),
+            action: action.to_string(),
+            reason: reason.to_string(),
+            deferred_at: now,
+        });
+        true
+    }
+
+    /// Actions queued so far.
+    pub fn deferred(&self) -> &[DeferredAction] {
+        &self.deferred
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_mode_after_sustained_outage_with_positions() {
+        let mut monitor = OutageMonitor::new(5);
+        let start = Utc::now();
+
+        assert!(!monitor.record_failure(start, true));
+        assert!(!monitor.record_failure(start + Duration::minutes(4), true));
+        assert!(!monitor.is_safe_mode());
+        // Outage measured from the first failure
+        assert!(monitor.record_failure(start + Duration::minutes(5), true));
+        assert!(monitor.is_safe_mode());
+        // Entered once
+        assert!(!monitor.record_failure(start + Duration::minutes(6), true));
+
+        let later = start + Duration::minutes(6);
+        assert!(monitor.defer("BTCUSDT", "consider_exit", "unprofitable 30h", later));
+        assert!(!monitor.defer("BTCUSDT", "force_exit", "max loss", later));
+        assert!(monitor.defer("ETHUSDT", "force_exit", "funding collapsed", later));
+        assert_eq!(monitor.deferred().len(), 2);
+        assert_eq!(monitor.deferred()[0].action, "force_exit");
+
+        let recovery = monitor
+            .record_success(start + Duration::minutes(10))
+            .unwrap();
+        assert_eq!(recovery.started_at, start);
+        assert_eq!(recovery.duration, Duration::minutes(10));
+        assert!(recovery.was_safe_mode);
+        assert_eq!(recovery.deferred.len(), 2);
+        assert!(!monitor.is_safe_mode());
+        assert!(monitor.deferred().is_empty());
+        assert!(monitor
+            .record_success(start + Duration::minutes(11))
+            .is_none());
+    }
+
+    #[test]
+    fn test_no_safe_mode_without_positions_or_when_disabled() {
+        let start = Utc::now();
+        let mut monitor = OutageMonitor::new(5);
+        assert!(!monitor.record_failure(start, false));
+        assert!(!monitor.record_failure(start + Duration::minutes(30), false));
+        assert_eq!(
+            monitor.outage_duration(start + Duration::minutes(30)),
+            Some(Duration::minutes(30))
+        );
+        // Once positions are at stake the elapsed outage counts in full
+        assert!(monitor.record_failure(start + Duration::minutes(31), true));
+
+        let recovery = monitor
+            .record_success(start + Duration::minutes(32))
+            .unwrap();
+        assert!(recovery.was_safe_mode);
+
+        let mut disabled = OutageMonitor::new(0);
+        assert!(!disabled.record_failure(start, true));
+        assert!(!disabled.record_failure(start + Duration::hours(2), true));
+        assert!(
+            !disabled
+                .record_success(start + Duration::hours(3))
+                .unwrap()
+                .was_safe_mode
+        );
+    }
+}
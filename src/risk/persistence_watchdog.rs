@@ -0,0 +1,161 @@
+//! Watchdog on state save failures.
+//!
+//! A failed save loses nothing while the next one succeeds, so one failure is
+//! only logged. A run of them means a restart would reload state that no longer
+//! matches the exchange: positions opened since the last good save would be
+//! unknown to the bot. The watchdog counts consecutive failures, escalates them
+//! once to ERROR and once to CRITICAL, and stops new entries once saves have been
+//! failing for the configured time. The first successful save ends the episode.
+
+use crate::config::PersistenceWatchdogConfig;
+use chrono::{DateTime, Duration, Utc};
+
+use super::AlertSeverity;
+
+/// Episode of failing saves that ended with a successful one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistenceRecovery {
+    pub failures: u32,
+    pub duration: Duration,
+    /// Whether new entries had been stopped
+    pub blocked_entries: bool,
+}
+
+/// Tracks consecutive state save failures.
+#[derive(Debug)]
+pub struct PersistenceWatchdog {
+    config: PersistenceWatchdogConfig,
+    failures: u32,
+    /// First failure of the current episode
+    failing_since: Option<DateTime<Utc>>,
+    /// Escalation not yet reported
+    pending: Option<AlertSeverity>,
+}
+
+impl PersistenceWatchdog {
+    pub fn new(config: PersistenceWatchdogConfig) -> Self {
+        Self {
+            config,
+            failures: 0,
+            failing_since: None,
+            pending: None,
+        }
+    }
+
+    /// Database tried first when the main one can't be written.
+    pub fn secondary_db(&self) -> Option<&str> {
+        self.config.secondary_db.as_deref()
+    }
+
+    /// Record a failed save at `now`.
+    ///
+    /// Returns the severity when this failure crosses an escalation threshold.
+    pub fn record_failure(&mut self, now: DateTime<Utc>) -> Option<AlertSeverity> {
+        self.failing_since.get_or_insert(now);
+        self.failures += 1;
+        let severity = if self.failures == self.config.critical_after_failures {
+            AlertSeverity::Critical
+        } else if self.failures == self.config.error_after_failures {
+            AlertSeverity::Error
+        } else {
+            return None;
+        };
+        self.pending = Some(severity);
+        Some(severity)
+    }
+
+    /// Record a successful save at `now`, ending the current episode if any.
+    pub fn record_success(&mut self, now: DateTime<Utc>) -> Option<PersistenceRecovery> {
+        let failing_since = self.failing_since.take()?;
+        let recovery = PersistenceRecovery {
+            failures: self.failures,
+            duration: now - failing_since,
+            blocked_entries: self.blocks_entries_since(failing_since, now),
+        };
+        self.failures = 0;
+        self.pending = None;
+        Some(recovery)
+    }
+
+    /// Escalation raised since the last call, if any.
+    pub fn take_escalation(&mut self) -> Option<AlertSeverity> {
+        self.pending.take()
+    }
+
+    /// Consecutive failed saves.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// How long saves have been failing.
+    pub fn failing_for(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.failing_since.map(|since| now - since)
+    }
+
+    /// Whether saves have failed long enough to stop new entries.
+    pub fn blocks_entries(&self, now: DateTime<Utc>) -> bool {
+        self.failing_since
+            .is_some_and(|since| self.blocks_entries_since(since, now))
+    }
+
+    fn blocks_entries_since(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.config.halt_entries_after_minutes > 0
+            && now - since >= Duration::minutes(self.config.halt_entries_after_minutes as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escalates_and_blocks_entries() {
+        let mut watchdog = PersistenceWatchdog::new(PersistenceWatchdogConfig::default());
+        let start = Utc::now();
+
+        assert_eq!(watchdog.record_failure(start), None);
+        assert_eq!(watchdog.record_failure(start), None);
+        assert_eq!(watchdog.record_failure(start), Some(AlertSeverity::Error));
+        assert_eq!(watchdog.take_escalation(), Some(AlertSeverity::Error));
+        assert_eq!(watchdog.take_escalation(), None);
+        for _ in 4..10 {
+            assert_eq!(watchdog.record_failure(start), None);
+        }
+        assert_eq!(
+            watchdog.record_failure(start),
+            Some(AlertSeverity::Critical)
+        );
+        // Escalates once per threshold
+        assert_eq!(watchdog.record_failure(start), None);
+        assert_eq!(watchdog.failures(), 11);
+
+        // Blocking is measured from the first failure
+        assert!(!watchdog.blocks_entries(start + Duration::minutes(14)));
+        assert!(watchdog.blocks_entries(start + Duration::minutes(15)));
+
+        let recovery = watchdog
+            .record_success(start + Duration::minutes(20))
+            .unwrap();
+        assert_eq!(recovery.failures, 11);
+        assert_eq!(recovery.duration, Duration::minutes(20));
+        assert!(recovery.blocked_entries);
+        assert!(!watchdog.blocks_entries(start + Duration::minutes(20)));
+        assert!(watchdog.record_success(start).is_none());
+        assert_eq!(watchdog.failures(), 0);
+    }
+
+    #[test]
+    fn test_never_blocks_when_disabled() {
+        let mut watchdog = PersistenceWatchdog::new(PersistenceWatchdogConfig {
+            halt_entries_after_minutes: 0,
+            ..Default::default()
+        });
+        let start = Utc::now();
+        watchdog.record_failure(start);
+        assert!(!watchdog.blocks_entries(start + Duration::days(1)));
+        assert_eq!(
+            watchdog.failing_for(start + Duration::hours(1)),
+            Some(Duration::hours(1))
+        );
+    }
+}
@@ -5,8 +5,9 @@
 //! - Accumulated funding payments vs costs
 //! - Net PnL calculation
 //! - Loss detection and exit recommendations
+//! - Re-entry cool-downs after a position is closed
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::Serialize;
@@ -55,6 +56,31 @@ pub struct PositionEntry {
     /// Optional: When the position was originally opened (for restored positions).
     /// If None, uses current time (for new positions).
     pub opened_at: Option<DateTime<Utc>>,
+    /// First funding settlement the position qualifies for, if known.
+    /// Entries just after a settlement wait a full interval before earning anything.
+    pub first_funding_at: Option<DateTime<Utc>>,
+}
+
+/// When a futures position of `position_amt` (signed) was opened, from the signed
+/// fills of its symbol (positive = bought), oldest first.
+///
+/// Walks back from the newest fill to the one that took the position from flat,
+/// or from the other side, to its current side. Funding of an earlier position on
+/// the same symbol predates it. Returns `None` if the position was already open
+/// before the first fill.
+pub fn position_opened_at(
+    position_amt: Decimal,
+    fills: &[(DateTime<Utc>, Decimal)],
+) -> Option<DateTime<Utc>> {
+    let mut amount = position_amt;
+    for (time, qty) in fills.iter().rev() {
+        let before = amount - qty;
+        if before.is_zero() || before.is_sign_positive() != position_amt.is_sign_positive() {
+            return Some(*time);
+        }
+        amount = before;
+    }
+    None
 }
 
 /// Tracks a position's lifecycle and profitability.
@@ -62,6 +88,7 @@ pub struct PositionEntry {
 pub struct TrackedPosition {
     pub symbol: String,
     pub opened_at: DateTime<Utc>,
+    pub first_funding_at: Option<DateTime<Utc>>,
     pub entry_price: Decimal,
     pub quantity: Decimal,
     pub position_value: Decimal,
@@ -69,7 +96,10 @@ pub struct TrackedPosition {
     // Funding tracking
     pub expected_funding_rate: Decimal,
     pub funding_collections: u32,
+    /// Net of any funding paid
     pub total_funding_received: Decimal,
+    /// Settlements paid with the futures leg on the wrong side of the rate
+    pub total_funding_paid: Decimal,
     pub expected_total_funding: Decimal,
 
     // Cost tracking
@@ -93,12 +123,14 @@ impl TrackedPosition {
         Self {
             symbol,
             opened_at: entry.opened_at.unwrap_or_else(Utc::now),
+            first_funding_at: entry.first_funding_at,
             entry_price: entry.entry_price,
             quantity: entry.quantity,
             position_value: entry.position_value,
             expected_funding_rate: entry.expected_funding_rate,
             funding_collections: 0,
             total_funding_received: Decimal::ZERO,
+            total_funding_paid: Decimal::ZERO,
             expected_total_funding: Decimal::ZERO,
             entry_fees: entry.entry_fees,
             interest_paid: Decimal::ZERO,
@@ -170,6 +202,31 @@ impl TrackedPosition {
         duration.num_seconds() as f64 / 3600.0
     }
 
+    /// Minutes between entry and the first funding settlement, if known.
+    pub fn minutes_to_first_funding(&self) -> Option<i64> {
+        self.first_funding_at
+            .map(|first| (first - self.opened_at).num_minutes().max(0))
+    }
+
+    /// Check if the position has not yet reached its first funding settlement.
+    /// Always false once funding has been collected or if the settlement time is unknown.
+    pub fn awaiting_first_funding(&self) -> bool {
+        self.funding_collections == 0
+            && self
+                .first_funding_at
+                .is_some_and(|first| Utc::now() < first)
+    }
+
+    /// Funding captured per hour held, including time spent waiting for the first
+    /// settlement. Late entries score lower than entries timed just before settlement.
+    pub fn funding_per_hour_held(&self) -> Decimal {
+        let hours = Decimal::from_f64_retain(self.hours_open()).unwrap_or(Decimal::ZERO);
+        if hours < dec!(0.0167) {
+            return Decimal::ZERO; // Less than a minute held
+        }
+        self.total_funding_received / hours
+    }
+
     /// Check if position is within the minimum holding period.
     /// During this period, positions should not be exited voluntarily
     /// (to ensure funding fees cover trading costs).
@@ -179,6 +236,10 @@ impl TrackedPosition {
 
     /// Calculate estimated time to break-even based on current funding rate.
     /// Returns None if already profitable or funding rate is zero/negative.
+    ///
+    /// Once funding has been collected the realized funding per hour held is used
+    /// instead of the expected rate; before that, the wait until the first settlement
+    /// is added on top.
     pub fn estimated_breakeven_hours(&self) -> Option<Decimal> {
         let net = self.net_pnl();
         if net >= Decimal::ZERO {
@@ -187,14 +248,25 @@ impl TrackedPosition {
 
         // Calculate hourly funding income
         // funding_rate is per 8 hours, so hourly = rate / 8
-        let hourly_funding = (self.expected_funding_rate.abs() * self.position_value) / dec!(8);
+        let hourly_funding = if self.funding_collections > 0 {
+            self.funding_per_hour_held()
+        } else {
+            (self.expected_funding_rate.abs() * self.position_value) / dec!(8)
+        };
 
         if hourly_funding <= Decimal::ZERO {
             return None; // Won't reach breakeven
         }
 
-        // Hours needed = remaining loss / hourly income
-        Some(net.abs() / hourly_funding)
+        let wait_hours = match self.first_funding_at {
+            Some(first) if self.awaiting_first_funding() => {
+                Decimal::from((first - Utc::now()).num_minutes().max(0)) / dec!(60)
+            }
+            _ => Decimal::ZERO,
+        };
+
+        // Hours needed = wait for first settlement + remaining loss / hourly income
+        Some(wait_hours + net.abs() / hourly_funding)
     }
 }
 
@@ -221,10 +293,34 @@ impl PositionAction {
     }
 }
 
+/// How a position was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitKind {
+    /// Planned exit: funding flipped or no longer covers costs
+    Routine,
+    /// Exit forced by a risk check, an emergency or the exchange
+    Forced,
+}
+
+impl ExitKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitKind::Routine => "routine",
+            ExitKind::Forced => "forced",
+        }
+    }
+}
+
 /// Manages position tracking and loss detection.
 pub struct PositionTracker {
     config: PositionLossConfig,
     positions: HashMap<String, TrackedPosition>,
+    /// Cool-down after a routine exit before the symbol may be re-entered
+    routine_cooldown: Duration,
+    /// Cool-down after a forced exit
+    forced_cooldown: Duration,
+    /// End of each closed symbol's cool-down
+    cooldowns: HashMap<String, DateTime<Utc>>,
 }
 
 impl PositionTracker {
@@ -233,9 +329,20 @@ impl PositionTracker {
         Self {
             config,
             positions: HashMap::new(),
+            routine_cooldown: Duration::zero(),
+            forced_cooldown: Duration::zero(),
+            cooldowns: HashMap::new(),
         }
     }
 
+    /// Hold closed symbols out of new entries for `routine` after a routine exit
+    /// and `forced` after a forced one.
+    pub fn with_cooldowns(mut self, routine: Duration, forced: Duration) -> Self {
+        self.routine_cooldown = routine;
+        self.forced_cooldown = forced;
+        self
+    }
+
     /// Open a new tracked position.
     pub fn open_position(&mut self, symbol: &str, entry: PositionEntry) -> &TrackedPosition {
         let position = TrackedPosition::new(symbol.to_string(), entry);
@@ -245,6 +352,7 @@ impl PositionTracker {
             entry_price = %position.entry_price,
             quantity = %position.quantity,
             expected_funding = %position.expected_funding_rate,
+            minutes_to_first_funding = ?position.minutes_to_first_funding(),
             "Opened tracked position"
         );
 
@@ -258,6 +366,9 @@ impl PositionTracker {
             pos.total_funding_received += amount;
             pos.expected_total_funding += expected;
             pos.funding_collections += 1;
+            if amount < Decimal::ZERO {
+                pos.total_funding_paid -= amount;
+            }
 
             let deviation = if expected != Decimal::ZERO {
                 ((amount - expected) / expected).abs()
@@ -279,12 +390,21 @@ impl PositionTracker {
                 symbol = %symbol,
                 amount = %amount,
                 total = %pos.total_funding_received,
+                paid = %pos.total_funding_paid,
                 collections = pos.funding_collections,
                 "Recorded funding payment"
             );
         }
     }
 
+    /// Restore the funding paid of a position reloaded from persistence,
+    /// where only its net funding was replayed.
+    pub fn restore_funding_paid(&mut self, symbol: &str, paid: Decimal) {
+        if let Some(pos) = self.positions.get_mut(symbol) {
+            pos.total_funding_paid = paid;
+        }
+    }
+
     /// Record interest payment for a position.
     pub fn record_interest(&mut self, symbol: &str, amount: Decimal) {
         if let Some(pos) = self.positions.get_mut(symbol) {
@@ -369,8 +489,11 @@ impl PositionTracker {
 
         // Check if position is unprofitable
         if net_pnl < Decimal::ZERO {
-            pos.hours_unprofitable =
-                (pos.hours_open - self.config.grace_period_hours as f64).max(0.0) as u32;
+            // A late entry cannot earn anything before its first settlement, so the
+            // unprofitable clock starts at whichever comes later
+            let unfunded_hours = pos.minutes_to_first_funding().unwrap_or(0) as f64 / 60.0;
+            let clock_start = (self.config.grace_period_hours as f64).max(unfunded_hours);
+            pos.hours_unprofitable = (pos.hours_open - clock_start).max(0.0) as u32;
 
             // CRITICAL: Force exit if absolute loss exceeds threshold
             if net_pnl.abs() >= self.config.max_loss_usd {
@@ -389,6 +512,19 @@ impl PositionTracker {
                 };
             }
 
+            // Yield cannot be judged before the first settlement has paid out
+            if pos.awaiting_first_funding() {
+                return PositionAction::MonitorClosely {
+                    reason: format!(
+                        "Awaiting first funding in {}m (net PnL: ${:.2})",
+                        pos.first_funding_at
+                            .map(|first| (first - Utc::now()).num_minutes().max(0))
+                            .unwrap_or(0),
+                        net_pnl
+                    ),
+                };
+            }
+
             // CRITICAL: Force exit if APY is deeply negative
             if annualized < Decimal::ZERO && annualized.abs() >= self.config.max_negative_apy {
                 warn!(
@@ -453,7 +589,9 @@ impl PositionTracker {
     }
 
     /// Close a position and return its final state.
-    pub fn close_position(&mut self, symbol: &str) -> Option<TrackedPosition> {
+    ///
+    /// A closed position starts its symbol's cool-down, longer after a forced exit.
+    pub fn close_position(&mut self, symbol: &str, kind: ExitKind) -> Option<TrackedPosition> {
         let position = self.positions.remove(symbol);
 
         if let Some(ref pos) = position {
@@ -462,14 +600,43 @@ impl PositionTracker {
                 hours_open = pos.hours_open,
                 net_pnl = %pos.net_pnl(),
                 funding_received = %pos.total_funding_received,
+                funding_per_hour = %pos.funding_per_hour_held().round_dp(4),
                 total_costs = %pos.total_costs(),
+                exit = ?kind,
                 "Closed tracked position"
             );
+
+            let now = Utc::now();
+            self.cooldowns.retain(|_, until| *until > now);
+            let cooldown = match kind {
+                ExitKind::Routine => self.routine_cooldown,
+                ExitKind::Forced => self.forced_cooldown,
+            };
+            if cooldown > Duration::zero() {
+                self.cooldowns.insert(symbol.to_string(), now + cooldown);
+            }
         }
 
         position
     }
 
+    /// End of `symbol`'s cool-down, if it is still cooling down at `now`.
+    pub fn cooldown_until(&self, symbol: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cooldowns
+            .get(symbol)
+            .copied()
+            .filter(|until| *until > now)
+    }
+
+    /// Symbols that may not be re-entered at `now`.
+    pub fn cooling_down(&self, now: DateTime<Utc>) -> Vec<String> {
+        self.cooldowns
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(symbol, _)| symbol.clone())
+            .collect()
+    }
+
     /// Get all unprofitable positions.
     pub fn get_unprofitable_positions(&self) -> Vec<(&str, &TrackedPosition)> {
         self.positions
@@ -515,6 +682,7 @@ impl PositionTracker {
     /// Get aggregate metrics across all positions for monitoring.
     pub fn get_aggregate_metrics(&self) -> AggregateMetrics {
         let mut total_funding_received = Decimal::ZERO;
+        let mut total_funding_paid = Decimal::ZERO;
         let mut total_interest_paid = Decimal::ZERO;
         let mut total_fees = Decimal::ZERO;
         let mut total_net_pnl = Decimal::ZERO;
@@ -524,6 +692,7 @@ impl PositionTracker {
 
         for pos in self.positions.values() {
             total_funding_received += pos.total_funding_received;
+            total_funding_paid += pos.total_funding_paid;
             total_interest_paid += pos.interest_paid;
             total_fees += pos.entry_fees + pos.rebalance_fees;
             total_net_pnl += pos.net_pnl();
@@ -542,6 +711,7 @@ impl PositionTracker {
             unprofitable_count,
             total_position_value,
             total_funding_received,
+            total_funding_paid,
             total_interest_paid,
             total_fees,
             total_net_pnl,
@@ -567,6 +737,7 @@ impl PositionTracker {
             unprofitable = metrics.unprofitable_count,
             total_value = %metrics.total_position_value,
             funding_received = %metrics.total_funding_received,
+            funding_paid = %metrics.total_funding_paid,
             interest_paid = %metrics.total_interest_paid,
             total_fees = %metrics.total_fees,
             net_pnl = %metrics.total_net_pnl,
@@ -592,6 +763,7 @@ pub struct AggregateMetrics {
     pub unprofitable_count: usize,
     pub total_position_value: Decimal,
     pub total_funding_received: Decimal,
+    pub total_funding_paid: Decimal,
     pub total_interest_paid: Decimal,
     pub total_fees: Decimal,
     pub total_net_pnl: Decimal,
@@ -613,6 +785,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_position_opened_at_skips_earlier_closed_position() {
+        let at = |hours| Utc::now() - Duration::hours(hours);
+        let fills = vec![
+            // Earlier short, opened and closed
+            (at(150), dec!(-1)),
+            (at(100), dec!(1)),
+            // Current short, opened in two fills then partly reduced
+            (at(50), dec!(-0.5)),
+            (at(40), dec!(-0.5)),
+            (at(10), dec!(0.2)),
+        ];
+        assert_eq!(position_opened_at(dec!(-0.8), &fills), Some(fills[2].0));
+
+        // A flip from long to short opens the short
+        let flip = vec![(at(30), dec!(1)), (at(20), dec!(-1.5))];
+        assert_eq!(position_opened_at(dec!(-0.5), &flip), Some(flip[1].0));
+
+        // Open since before the first fill
+        assert_eq!(position_opened_at(dec!(-2), &fills[2..]), None);
+        assert_eq!(position_opened_at(dec!(1), &[]), None);
+    }
+
     #[test]
     fn test_open_and_track_position() {
         let mut tracker = PositionTracker::new(test_config());
@@ -625,6 +820,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
 
         tracker.open_position("BTCUSDT", entry);
@@ -645,6 +841,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
 
         tracker.open_position("BTCUSDT", entry);
@@ -653,6 +850,16 @@ mod tests {
         let pos = tracker.get_position("BTCUSDT").unwrap();
         assert_eq!(pos.total_funding_received, dec!(5));
         assert_eq!(pos.funding_collections, 1);
+
+        // A settlement on the paying side is netted and also counted as paid
+        tracker.record_funding("BTCUSDT", dec!(-1.5), dec!(5));
+        let pos = tracker.get_position("BTCUSDT").unwrap();
+        assert_eq!(pos.total_funding_received, dec!(3.5));
+        assert_eq!(pos.total_funding_paid, dec!(1.5));
+        assert_eq!(
+            tracker.get_aggregate_metrics().total_funding_paid,
+            dec!(1.5)
+        );
     }
 
     #[test]
@@ -667,6 +874,7 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
 
         tracker.open_position("BTCUSDT", entry);
@@ -692,12 +900,119 @@ mod tests {
             entry_fees: dec!(2),
             position_value: dec!(5000),
             opened_at: None,
+            first_funding_at: None,
         };
 
         tracker.open_position("BTCUSDT", entry);
-        let closed = tracker.close_position("BTCUSDT");
+        let closed = tracker.close_position("BTCUSDT", ExitKind::Routine);
 
         assert!(closed.is_some());
         assert!(tracker.get_position("BTCUSDT").is_none());
+        // No cool-downs configured
+        assert!(tracker.cooling_down(Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_cooldown_longer_after_forced_exit() {
+        let mut tracker = PositionTracker::new(test_config())
+            .with_cooldowns(Duration::hours(1), Duration::hours(8));
+        for symbol in ["BTCUSDT", "ETHUSDT"] {
+            tracker.open_position(
+                symbol,
+                PositionEntry {
+                    symbol: symbol.to_string(),
+                    entry_price: dec!(100),
+                    quantity: dec!(1),
+                    expected_funding_rate: dec!(0.0001),
+                    entry_fees: dec!(0.1),
+                    position_value: dec!(100),
+                    opened_at: None,
+                    first_funding_at: None,
+                },
+            );
+        }
+        tracker.close_position("BTCUSDT", ExitKind::Routine);
+        tracker.close_position("ETHUSDT", ExitKind::Forced);
+        // Closing an untracked symbol starts nothing
+        tracker.close_position("SOLUSDT", ExitKind::Forced);
+
+        let now = Utc::now();
+        let mut cooling = tracker.cooling_down(now);
+        cooling.sort();
+        assert_eq!(cooling, vec!["BTCUSDT", "ETHUSDT"]);
+
+        let later = now + Duration::hours(2);
+        assert!(tracker.cooldown_until("BTCUSDT", later).is_none());
+        let until = tracker.cooldown_until("ETHUSDT", later).unwrap();
+        assert!(until > now + Duration::hours(7));
+        assert_eq!(tracker.cooling_down(later), vec!["ETHUSDT"]);
+    }
+
+    fn late_entry(first_funding_in_minutes: i64) -> PositionEntry {
+        let opened_at = Utc::now() - chrono::Duration::hours(6);
+        PositionEntry {
+            symbol: "BTCUSDT".to_string(),
+            entry_price: dec!(50000),
+            quantity: dec!(0.1),
+            expected_funding_rate: dec!(0.0001),
+            entry_fees: dec!(2),
+            position_value: dec!(5000),
+            opened_at: Some(opened_at),
+            first_funding_at: Some(
+                Utc::now() + chrono::Duration::minutes(first_funding_in_minutes),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_late_entry_not_judged_before_first_funding() {
+        let mut tracker = PositionTracker::new(test_config());
+
+        // Entered 6h ago, just after a settlement: first funding still 2h away
+        tracker.open_position("BTCUSDT", late_entry(120));
+
+        let pos = tracker.get_position("BTCUSDT").unwrap();
+        assert!(pos.awaiting_first_funding());
+        assert_eq!(pos.minutes_to_first_funding(), Some(480));
+
+        // Past the grace period and losing the entry fee, but yield is not judged yet
+        match tracker.evaluate_position("BTCUSDT") {
+            PositionAction::MonitorClosely { reason } => {
+                assert!(reason.contains("Awaiting first funding"));
+            }
+            other => panic!("expected MonitorClosely, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_funding_per_hour_held() {
+        let mut tracker = PositionTracker::new(test_config());
+        tracker.open_position("BTCUSDT", late_entry(-60));
+        tracker.record_funding("BTCUSDT", dec!(6), dec!(5));
+
+        let pos = tracker.get_position("BTCUSDT").unwrap();
+        assert!(!pos.awaiting_first_funding());
+
+        // $6 over ~6 hours held
+        let per_hour = pos.funding_per_hour_held();
+        assert!(
+            per_hour > dec!(0.99) && per_hour <= dec!(1.0),
+            "{}",
+            per_hour
+        );
+    }
+
+    #[test]
+    fn test_breakeven_includes_wait_for_first_funding() {
+        let mut tracker = PositionTracker::new(test_config());
+        tracker.open_position("BTCUSDT", late_entry(120));
+
+        // $2 fee at $0.0625/h expected = 32h, plus ~2h until first settlement
+        let hours = tracker
+            .get_position("BTCUSDT")
+            .unwrap()
+            .estimated_breakeven_hours()
+            .unwrap();
+        assert!(hours > dec!(33.9) && hours <= dec!(34), "{}", hours);
     }
 }
@@ -0,0 +1,211 @@
+//! Supervised resumption after a trading halt.
+//!
+//! Clearing a halt with `reset_halt` doesn't return straight to full activity.
+//! The first risk cycle afterwards only observes: held positions are managed as
+//! usual but nothing new is entered. Each following cycle may open up to
+//! `max_entries_per_cycle` new positions, sized at `initial_size_fraction` of
+//! their allocation and growing linearly to full size over `ramp_cycles` clean
+//! cycles. A cycle with ERROR or CRITICAL alerts starts the ramp over.
+
+use crate::config::ResumptionConfig;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Limits on new entries while trading resumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryThrottle {
+    /// New positions that may be opened this cycle
+    pub max_entries: usize,
+    /// Multiplier on the allocated size of new positions
+    pub size_scale: Decimal,
+}
+
+impl EntryThrottle {
+    pub fn is_observe_only(&self) -> bool {
+        self.max_entries == 0
+    }
+}
+
+impl fmt::Display for EntryThrottle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_observe_only() {
+            write!(f, "observe only, no new entries")
+        } else {
+            write!(
+                f,
+                "up to {} new entr{} at {:.0}% size",
+                self.max_entries,
+                if self.max_entries == 1 { "y" } else { "ies" },
+                self.size_scale * Decimal::ONE_HUNDRED
+            )
+        }
+    }
+}
+
+/// Stage of a resumption in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumptionPhase {
+    /// First cycle after the reset: no new entries
+    Observing,
+    /// Throttled entries; `clean_cycles` clean cycles into the ramp
+    Ramping { clean_cycles: u32 },
+}
+
+/// Outcome of a risk cycle during resumption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumptionEvent {
+    /// Clean cycle: the next cycle runs under this throttle
+    Advanced(EntryThrottle),
+    /// Cycle with ERROR/CRITICAL alerts: the ramp starts over
+    Restarted(EntryThrottle),
+    /// Ramp finished: back to normal activity
+    Completed,
+}
+
+/// Throttles new entries after a halt is reset.
+#[derive(Debug, Clone)]
+pub struct SupervisedResumption {
+    config: ResumptionConfig,
+    phase: Option<ResumptionPhase>,
+}
+
+impl SupervisedResumption {
+    pub fn new(config: ResumptionConfig) -> Self {
+        Self {
+            config,
+            phase: None,
+        }
+    }
+
+    /// Begin resuming; returns false when supervised resumption is disabled.
+    pub fn start(&mut self) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        self.phase = Some(ResumptionPhase::Observing);
+        true
+    }
+
+    pub fn phase(&self) -> Option<ResumptionPhase> {
+        self.phase
+    }
+
+    /// Entry limits for the current cycle, `None` at normal activity.
+    pub fn throttle(&self) -> Option<EntryThrottle> {
+        match self.phase? {
+            ResumptionPhase::Observing => Some(EntryThrottle {
+                max_entries: 0,
+                size_scale: Decimal::ZERO,
+            }),
+            ResumptionPhase::Ramping { clean_cycles } => {
+                let initial = self.config.initial_size_fraction;
+                let progress =
+                    Decimal::from(clean_cycles) / Decimal::from(self.config.ramp_cycles.max(1));
+                Some(EntryThrottle {
+                    max_entries: self.config.max_entries_per_cycle as usize,
+                    size_scale: initial + (Decimal::ONE - initial) * progress,
+                })
+            }
+        }
+    }
+
+    /// Advance after a risk cycle; `clean` when it raised no ERROR/CRITICAL alerts.
+    ///
+    /// A troubled observation cycle is simply repeated, so only clean cycles
+    /// lead into the ramp.
+    pub fn record_cycle(&mut self, clean: bool) -> Option<ResumptionEvent> {
+        let next = match (self.phase?, clean) {
+            (ResumptionPhase::Observing, false) => return None,
+            (ResumptionPhase::Observing, true) => ResumptionPhase::Ramping { clean_cycles: 0 },
+            (ResumptionPhase::Ramping { .. }, false) => {
+                self.phase = Some(ResumptionPhase::Ramping { clean_cycles: 0 });
+                return self.throttle().map(ResumptionEvent::Restarted);
+            }
+            (ResumptionPhase::Ramping { clean_cycles }, true) => ResumptionPhase::Ramping {
+                clean_cycles: clean_cycles + 1,
+            },
+        };
+        let ramp_done = matches!(
+            next,
+            ResumptionPhase::Ramping { clean_cycles } if clean_cycles >= self.config.ramp_cycles
+        );
+        if ramp_done {
+            self.phase = None;
+            return Some(ResumptionEvent::Completed);
+        }
+        self.phase = Some(next);
+        self.throttle().map(ResumptionEvent::Advanced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn resumption() -> SupervisedResumption {
+        SupervisedResumption::new(ResumptionConfig {
+            ramp_cycles: 3,
+            initial_size_fraction: dec!(0.25),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_ramp_after_reset() {
+        let mut resumption = resumption();
+        assert!(resumption.throttle().is_none());
+        assert!(resumption.record_cycle(true).is_none());
+
+        assert!(resumption.start());
+        assert!(resumption.throttle().unwrap().is_observe_only());
+        // A troubled observation cycle is repeated
+        assert!(resumption.record_cycle(false).is_none());
+        assert_eq!(resumption.phase(), Some(ResumptionPhase::Observing));
+
+        // One entry at a quarter size, growing with each clean cycle
+        let quarter = EntryThrottle {
+            max_entries: 1,
+            size_scale: dec!(0.25),
+        };
+        assert_eq!(
+            resumption.record_cycle(true),
+            Some(ResumptionEvent::Advanced(quarter))
+        );
+        assert_eq!(
+            resumption.record_cycle(true),
+            Some(ResumptionEvent::Advanced(EntryThrottle {
+                max_entries: 1,
+                size_scale: dec!(0.50),
+            }))
+        );
+
+        // Alerts start the ramp over
+        assert_eq!(
+            resumption.record_cycle(false),
+            Some(ResumptionEvent::Restarted(quarter))
+        );
+        for _ in 0..2 {
+            assert!(matches!(
+                resumption.record_cycle(true),
+                Some(ResumptionEvent::Advanced(_))
+            ));
+        }
+        assert_eq!(resumption.throttle().unwrap().size_scale, dec!(0.75));
+        assert_eq!(
+            resumption.record_cycle(true),
+            Some(ResumptionEvent::Completed)
+        );
+        assert!(resumption.throttle().is_none());
+    }
+
+    #[test]
+    fn test_disabled_resumes_at_once() {
+        let mut resumption = SupervisedResumption::new(ResumptionConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        assert!(!resumption.start());
+        assert!(resumption.throttle().is_none());
+    }
+}
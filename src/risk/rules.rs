@@ -0,0 +1,196 @@
+//! Operator-defined risk rules.
+//!
+//! `risk.rules` expresses policy in configuration instead of code: each rule is
+//! a list of metric comparisons that must all hold, and an action. Exit rules are
+//! checked against every held position and may also read portfolio metrics;
+//! entry-halting rules read portfolio metrics only. A metric without a value,
+//! such as the portfolio funding APR while nothing is held, never satisfies a
+//! condition, so a rule can't fire on missing data.
+
+use crate::config::{RiskRule, RuleAction, RuleMetric};
+use rust_decimal::Decimal;
+
+use super::TrackedPosition;
+
+/// Portfolio-wide values rules can read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortfolioMetrics {
+    /// Net portfolio funding APR, `None` while nothing is held
+    pub funding_apr: Option<Decimal>,
+    pub drawdown: Decimal,
+    pub position_count: usize,
+}
+
+/// Evaluates the configured rules.
+#[derive(Debug, Clone, Default)]
+pub struct RuleEngine {
+    rules: Vec<RiskRule>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<RiskRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &[RiskRule] {
+        &self.rules
+    }
+
+    /// The most severe exit rule that fires for `position`; force exits come first.
+    pub fn exit_rule(
+        &self,
+        position: &TrackedPosition,
+        portfolio: &PortfolioMetrics,
+    ) -> Option<&RiskRule> {
+        let fired = |action: RuleAction| {
+            self.rules
+                .iter()
+                .filter(move |rule| rule.action == action)
+                .find(|rule| fires(rule, Some(position), portfolio))
+        };
+        fired(RuleAction::ForceExit).or_else(|| fired(RuleAction::ConsiderExit))
+    }
+
+    /// The first entry-halting rule that fires.
+    pub fn entry_halt(&self, portfolio: &PortfolioMetrics) -> Option<&RiskRule> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.is_position_rule())
+            .find(|rule| fires(rule, None, portfolio))
+    }
+}
+
+fn fires(
+    rule: &RiskRule,
+    position: Option<&TrackedPosition>,
+    portfolio: &PortfolioMetrics,
+) -> bool {
+    !rule.when.is_empty()
+        && rule.when.iter().all(|condition| {
+            metric_value(condition.metric, position, portfolio)
+                .is_some_and(|value| condition.op.holds(value, condition.value))
+        })
+}
+
+fn metric_value(
+    metric: RuleMetric,
+    position: Option<&TrackedPosition>,
+    portfolio: &PortfolioMetrics,
+) -> Option<Decimal> {
+    match metric {
+        RuleMetric::NetPnl => position.map(|p| p.net_pnl()),
+        RuleMetric::HoursOpen => position.and_then(|p| Decimal::from_f64_retain(p.hours_open())),
+        RuleMetric::FundingReceived => position.map(|p| p.total_funding_received),
+        RuleMetric::InterestPaid => position.map(|p| p.interest_paid),
+        RuleMetric::Apy => position.map(|p| p.annualized_yield()),
+        RuleMetric::PortfolioFundingApr => portfolio.funding_apr,
+        RuleMetric::Drawdown => Some(portfolio.drawdown),
+        RuleMetric::PositionCount => Some(Decimal::from(portfolio.position_count)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RuleCondition, RuleOp};
+    use crate::risk::PositionEntry;
+    use chrono::{Duration, Utc};
+    use rust_decimal_macros::dec;
+
+    fn rule(name: &str, action: RuleAction, when: &[(RuleMetric, RuleOp, Decimal)]) -> RiskRule {
+        RiskRule {
+            name: name.to_string(),
+            action,
+            when: when
+                .iter()
+                .map(|&(metric, op, value)| RuleCondition { metric, op, value })
+                .collect(),
+        }
+    }
+
+    fn position(hours_open: i64, funding: Decimal) -> TrackedPosition {
+        let mut position = TrackedPosition::new(
+            "BTCUSDT".to_string(),
+            PositionEntry {
+                symbol: "BTCUSDT".to_string(),
+                entry_price: dec!(50000),
+                quantity: dec!(0.2),
+                position_value: dec!(10000),
+                expected_funding_rate: dec!(0.0003),
+                entry_fees: dec!(8),
+                opened_at: Some(Utc::now() - Duration::hours(hours_open)),
+                first_funding_at: None,
+            },
+        );
+        position.total_funding_received = funding;
+        position
+    }
+
+    fn engine() -> RuleEngine {
+        RuleEngine::new(vec![
+            rule(
+                "stale-loser",
+                RuleAction::ConsiderExit,
+                &[
+                    (RuleMetric::NetPnl, RuleOp::Lt, dec!(-5)),
+                    (RuleMetric::HoursOpen, RuleOp::Gt, dec!(24)),
+                ],
+            ),
+            rule(
+                "deep-loser",
+                RuleAction::ForceExit,
+                &[(RuleMetric::NetPnl, RuleOp::Le, dec!(-20))],
+            ),
+            rule(
+                "thin-carry",
+                RuleAction::HaltEntries,
+                &[(RuleMetric::PortfolioFundingApr, RuleOp::Lt, dec!(0.05))],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_exit_rules_need_every_condition() {
+        let engine = engine();
+        let portfolio = PortfolioMetrics::default();
+
+        // $8 of fees, nothing earned: -$8 but only open for 2 hours
+        assert_eq!(
+            engine.exit_rule(&position(2, Decimal::ZERO), &portfolio),
+            None
+        );
+        let stale = engine
+            .exit_rule(&position(30, Decimal::ZERO), &portfolio)
+            .unwrap();
+        assert_eq!(stale.name, "stale-loser");
+        assert_eq!(
+            stale.to_string(),
+            "if net_pnl < -5 and hours_open > 24 then consider_exit"
+        );
+
+        // Force exits win over softer rules that also fire
+        let deep = engine
+            .exit_rule(&position(30, dec!(-15)), &portfolio)
+            .unwrap();
+        assert_eq!(deep.action, RuleAction::ForceExit);
+    }
+
+    #[test]
+    fn test_entry_halt_on_portfolio_metrics() {
+        let engine = engine();
+        // Nothing held: the APR is unknown and never satisfies a condition
+        assert_eq!(engine.entry_halt(&PortfolioMetrics::default()), None);
+
+        let thin = PortfolioMetrics {
+            funding_apr: Some(dec!(0.03)),
+            drawdown: dec!(0.01),
+            position_count: 2,
+        };
+        assert_eq!(engine.entry_halt(&thin).unwrap().name, "thin-carry");
+        let healthy = PortfolioMetrics {
+            funding_apr: Some(dec!(0.12)),
+            ..thin
+        };
+        assert_eq!(engine.entry_halt(&healthy), None);
+    }
+}
@@ -1,10 +1,11 @@
 //! Capital allocation logic for position sizing.
 
-use crate::config::{CapitalConfig, RiskConfig};
+use crate::config::{CapitalConfig, NotionalCapBreach, RiskConfig};
 use crate::exchange::QualifiedPair;
+use crate::strategy::{FlashRelease, ReductionUrgency};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::debug;
 
 /// Target allocation for a single position.
@@ -24,6 +25,8 @@ pub struct PositionAllocation {
     pub funding_rate: Decimal,
     /// Priority rank (1 = highest)
     pub priority: u8,
+    /// Use existing spot holdings as the long leg (futures short only)
+    pub hedge_with_holdings: bool,
 }
 
 /// Position reduction target for rebalancing.
@@ -43,6 +46,10 @@ pub struct PositionReduction {
     pub reduction_usdt: Decimal,
     /// Current funding rate
     pub funding_rate: Decimal,
+    /// Position exceeds an absolute notional cap (reduce even within holding period)
+    pub exceeds_notional_cap: bool,
+    /// Why the position is reduced; orders the reduction batch
+    pub urgency: ReductionUrgency,
 }
 
 /// Manages capital allocation across multiple positions.
@@ -52,6 +59,14 @@ pub struct CapitalAllocator {
     default_leverage: u8,
     /// Precomputed allocation weights based on concentration factor
     allocation_weights: Vec<Decimal>,
+    /// Recently closed symbols that may not be entered again yet
+    cooling_down: HashSet<String>,
+    /// Symbols quarantined for funding anomalies: neither entered nor topped up
+    quarantined: HashSet<String>,
+    /// Share of equity deployed, at most `max_utilization`
+    utilization: Decimal,
+    /// Symbol allowed into the reserve, and the share of equity released to it
+    flash_release: Option<(String, Decimal)>,
 }
 
 impl CapitalAllocator {
@@ -66,13 +81,59 @@ impl CapitalAllocator {
             Self::compute_allocation_weights(capital_config.allocation_concentration);
 
         Self {
+            utilization: capital_config.max_utilization,
             capital_config,
             risk_config,
             default_leverage,
             allocation_weights,
+            cooling_down: HashSet::new(),
+            quarantined: HashSet::new(),
+            flash_release: None,
         }
     }
 
+    /// Keep `symbols` out of new entries until the next call.
+    pub fn set_cooling_down(&mut self, symbols: impl IntoIterator<Item = String>) {
+        self.cooling_down = symbols.into_iter().collect();
+    }
+
+    /// Keep `symbols` out of new entries and top-ups until the next call.
+    pub fn set_quarantined(&mut self, symbols: impl IntoIterator<Item = String>) {
+        self.quarantined = symbols.into_iter().collect();
+    }
+
+    /// Deploy `utilization` of equity instead of `max_utilization`, capped at it.
+    pub fn set_utilization(&mut self, utilization: Decimal) {
+        self.utilization = utilization.min(self.capital_config.max_utilization);
+    }
+
+    /// Let `release`'s symbol deploy its share of the reserve (None restores it).
+    pub fn set_flash_release(&mut self, release: Option<&FlashRelease>) {
+        self.flash_release = release.map(|r| (r.symbol.clone(), r.release));
+    }
+
+    /// Equity released from the reserve to `symbol`.
+    fn released_to(&self, symbol: &str, total_equity: Decimal) -> Decimal {
+        match &self.flash_release {
+            Some((released, share)) if released == symbol => total_equity * share,
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Share of equity not locked as margin by `current_positions`.
+    pub fn free_margin_share(
+        &self,
+        total_equity: Decimal,
+        current_positions: &HashMap<String, Decimal>,
+    ) -> Decimal {
+        if total_equity <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        let locked: Decimal = current_positions.values().map(|v| v.abs()).sum::<Decimal>()
+            / Decimal::from(self.default_leverage);
+        (total_equity - locked) / total_equity
+    }
+
     /// Compute allocation weights based on concentration factor.
     ///
     /// concentration = 1.0: Equal weights [20%, 20%, 20%, 20%, 20%]
@@ -133,7 +194,7 @@ impl CapitalAllocator {
         total_equity: Decimal,
         current_positions: &HashMap<String, Decimal>,
     ) -> Vec<PositionAllocation> {
-        let deployable_capital = total_equity * self.capital_config.max_utilization;
+        let deployable_capital = total_equity * self.utilization;
         let max_per_position = total_equity * self.risk_config.max_single_position;
         let leverage = Decimal::from(self.default_leverage);
 
@@ -169,26 +230,71 @@ impl CapitalAllocator {
 
         let mut allocations = Vec::new();
         let mut allocated = Decimal::ZERO;
+        // Projected notional per symbol, for group notional caps
+        let mut projected: HashMap<String, Decimal> = current_positions
+            .iter()
+            .map(|(symbol, value)| (symbol.clone(), value.abs()))
+            .collect();
 
         for (idx, pair) in pairs.iter().enumerate() {
+            // A flash release lets its symbol past the budgets by the released equity
+            let released = self.released_to(&pair.symbol, total_equity);
+            let flash_pending = self.flash_release.is_some() && released == Decimal::ZERO;
+
             // Stop if we've allocated enough capital
-            if allocated >= deployable_capital {
+            if allocated >= deployable_capital + released {
                 debug!("Stopping allocation: capital budget exhausted");
+                if flash_pending {
+                    continue;
+                }
                 break;
             }
 
             // Stop if margin budget exhausted
-            if margin_consumed >= margin_budget {
+            if margin_consumed >= margin_budget + released {
                 debug!(%margin_consumed, %margin_budget, "Stopping allocation: margin budget exhausted");
+                if flash_pending {
+                    continue;
+                }
                 break;
             }
 
+            // Recently closed symbols are not re-entered until their cool-down ends
+            if self.cooling_down.contains(&pair.symbol)
+                && !current_positions.contains_key(&pair.symbol)
+            {
+                debug!(symbol = %pair.symbol, "Skipping allocation: symbol cooling down");
+                continue;
+            }
+
+            // Quarantined symbols keep what they hold but get no more capital
+            if self.quarantined.contains(&pair.symbol) {
+                debug!(symbol = %pair.symbol, "Skipping allocation: symbol quarantined");
+                if let Some(current) = current_positions.get(&pair.symbol) {
+                    allocated += current.abs();
+                }
+                continue;
+            }
+
             // Calculate target size based on score and remaining capital
             let remaining = deployable_capital - allocated;
             let score_weight = self.score_to_weight(pair.score, idx);
-            let target_size = (remaining * score_weight)
+            let mut target_size = (remaining * score_weight)
                 .min(max_per_position)
-                .max(self.capital_config.min_position_size);
+                .max(self.capital_config.min_position_size)
+                + released;
+
+            // Apply absolute symbol/group notional caps
+            if let Some(cap) = self
+                .capital_config
+                .notional_caps
+                .max_notional_for(&pair.symbol, &projected)
+            {
+                if target_size > cap {
+                    debug!(symbol = %pair.symbol, %target_size, %cap, "Target clamped by notional cap");
+                    target_size = cap;
+                }
+            }
 
             // Skip if target is below minimum
             if target_size < self.capital_config.min_position_size {
@@ -201,7 +307,7 @@ impl CapitalAllocator {
             let margin_required = target_size / (leverage * self.risk_config.min_margin_ratio);
 
             // Check if we have enough margin budget
-            if margin_consumed + margin_required > margin_budget {
+            if margin_consumed + margin_required > margin_budget + released {
                 debug!(
                     symbol = %pair.symbol,
                     %margin_required,
@@ -243,8 +349,14 @@ impl CapitalAllocator {
                 leverage: self.default_leverage,
                 funding_rate: pair.funding_rate,
                 priority: (idx + 1) as u8,
+                hedge_with_holdings: pair.funding_rate > Decimal::ZERO
+                    && self
+                        .capital_config
+                        .existing_holdings
+                        .covers(&pair.base_asset),
             });
 
+            projected.insert(pair.symbol.clone(), target_size);
             allocated += target_size;
         }
 
@@ -269,12 +381,14 @@ impl CapitalAllocator {
         total_equity: Decimal,
         current_positions: &HashMap<String, Decimal>,
     ) -> Vec<PositionReduction> {
-        let deployable_capital = total_equity * self.capital_config.max_utilization;
+        let deployable_capital = total_equity * self.utilization;
         let max_per_position = total_equity * self.risk_config.max_single_position;
         let threshold = self.capital_config.rebalance_threshold;
 
         let mut reductions = Vec::new();
         let mut remaining_capital = deployable_capital;
+        // Sizes after the reductions so far: group caps count reduced members at their target
+        let mut projected = current_positions.clone();
 
         // Build target sizes for qualified pairs
         for (idx, pair) in pairs.iter().enumerate() {
@@ -283,7 +397,7 @@ impl CapitalAllocator {
             }
 
             let score_weight = self.score_to_weight(pair.score, idx);
-            let target_size = (remaining_capital * score_weight)
+            let mut target_size = (remaining_capital * score_weight)
                 .min(max_per_position)
                 .max(self.capital_config.min_position_size);
 
@@ -293,11 +407,24 @@ impl CapitalAllocator {
                 .unwrap_or(Decimal::ZERO)
                 .abs();
 
+            // Absolute notional caps override the percentage target and the threshold
+            let cap = self
+                .capital_config
+                .notional_caps
+                .max_notional_for(&pair.symbol, &projected);
+            let exceeds_notional_cap = cap.is_some_and(|cap| current > cap);
+            if let Some(cap) = cap {
+                target_size = target_size.min(cap);
+            }
+
             // Check if position exceeds target by more than threshold
             let max_acceptable = target_size * (Decimal::ONE + threshold);
 
-            if current > max_acceptable && current > self.capital_config.min_position_size {
+            if (current > max_acceptable || exceeds_notional_cap)
+                && current > self.capital_config.min_position_size
+            {
                 let reduction = current - target_size;
+                projected.insert(pair.symbol.clone(), target_size);
                 debug!(
                     symbol = %pair.symbol,
                     %current,
@@ -314,6 +441,12 @@ impl CapitalAllocator {
                     target_size_usdt: target_size,
                     reduction_usdt: reduction,
                     funding_rate: pair.funding_rate,
+                    exceeds_notional_cap,
+                    urgency: if exceeds_notional_cap {
+                        ReductionUrgency::NotionalCap
+                    } else {
+                        ReductionUrgency::Rebalance
+                    },
                 });
             }
 
@@ -347,6 +480,8 @@ impl CapitalAllocator {
                     target_size_usdt: Decimal::ZERO,
                     reduction_usdt: current,
                     funding_rate: Decimal::ZERO, // Unknown for orphaned positions
+                    exceeds_notional_cap: false,
+                    urgency: ReductionUrgency::Orphaned,
                 });
             }
         }
@@ -354,6 +489,16 @@ impl CapitalAllocator {
         reductions
     }
 
+    /// Existing positions exceeding absolute notional caps (e.g. after a config change).
+    pub fn notional_cap_breaches(
+        &self,
+        current_positions: &HashMap<String, Decimal>,
+    ) -> Vec<NotionalCapBreach> {
+        self.capital_config
+            .notional_caps
+            .breaches(current_positions)
+    }
+
     /// Convert pair score to allocation weight using precomputed concentration weights.
     fn score_to_weight(&self, score: Decimal, rank: usize) -> Decimal {
         // Get base weight from precomputed weights (based on concentration factor)
@@ -387,6 +532,7 @@ impl CapitalAllocator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exchange::HedgeInstrument;
 
     // =========================================================================
     // Test Helpers
@@ -399,7 +545,13 @@ mod tests {
                 reserve_buffer: dec!(0.10),
                 min_position_size: dec!(1000),
                 rebalance_threshold: dec!(0.20),
+                min_reduction_usdt: dec!(100),
                 allocation_concentration: dec!(1.5), // Moderate concentration
+                notional_caps: Default::default(),
+                existing_holdings: Default::default(),
+                dynamic_utilization: Default::default(),
+                flash_reserve: Default::default(),
+                portfolio_rebalance: Default::default(),
             },
             RiskConfig {
                 max_drawdown: dec!(0.05),
@@ -412,12 +564,29 @@ mod tests {
                 min_expected_yield: dec!(0.10),
                 grace_period_hours: 4,
                 max_funding_deviation: dec!(0.20),
+                funding_expectation_window_hours: 24,
+                funding_collapse_ratio: dec!(0.50),
+                exit_liquidity_check_minutes: 15,
+                max_exit_cost_ratio: dec!(0.50),
                 max_loss_usd: dec!(10),
                 max_negative_apy: dec!(0.50),
+                borrow_spike_ratio: dec!(3),
+                low_supply_spike_ratio: dec!(2),
+                borrow_holidays: Vec::new(),
+                borrow_limits: Default::default(),
+                reentry_cooldown_minutes: 60,
+                forced_exit_cooldown_minutes: 480,
                 max_errors_per_minute: 10,
                 max_consecutive_failures: 3,
                 emergency_delta_drift: dec!(0.10),
+                max_residual_delta_usd: dec!(500),
+                malfunction_state_max_age_minutes: 60,
                 max_consecutive_risk_cycles: 3,
+                resumption: Default::default(),
+                funding_quarantine: Default::default(),
+                safe_mode_after_minutes: 5,
+                persistence_watchdog: Default::default(),
+                rules: Vec::new(),
             },
             5,
         )
@@ -437,6 +606,11 @@ mod tests {
             margin_available: true,
             borrow_rate: Some(dec!(0.0001)),
             score,
+            hedge: HedgeInstrument::SpotMargin,
+            hedge_options: Vec::new(),
+            funding_capped: false,
+            coin_contract: None,
+            borrow_headroom_usdt: None,
         }
     }
 
@@ -446,7 +620,7 @@ mod tests {
 
     #[test]
     fn test_allocation_respects_max_utilization() {
-        let allocator = test_allocator();
+        let mut allocator = test_allocator();
         let pairs = vec![
             test_pair("BTCUSDT", dec!(0.001), dec!(15)),
             test_pair("ETHUSDT", dec!(0.0008), dec!(12)),
@@ -457,6 +631,16 @@ mod tests {
         let total_allocated: Decimal = allocations.iter().map(|a| a.target_size_usdt).sum();
 
         assert!(total_allocated <= dec!(85_000)); // 85% max utilization
+
+        // A lower dynamic utilization deploys less; it never exceeds the cap
+        allocator.set_utilization(dec!(0.4));
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        let reduced: Decimal = allocations.iter().map(|a| a.target_size_usdt).sum();
+        assert!(reduced <= dec!(40_000) && reduced < total_allocated);
+        allocator.set_utilization(dec!(2));
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        let capped: Decimal = allocations.iter().map(|a| a.target_size_usdt).sum();
+        assert_eq!(capped, total_allocated);
     }
 
     #[test]
@@ -769,4 +953,211 @@ mod tests {
         // Should skip since within 5% tolerance
         assert!(allocations.is_empty());
     }
+
+    #[test]
+    fn test_cooling_down_symbols_not_entered() {
+        let mut allocator = test_allocator();
+        allocator.set_cooling_down(["BTCUSDT".to_string()]);
+        let pairs = vec![
+            test_pair("BTCUSDT", dec!(0.001), dec!(15)),
+            test_pair("ETHUSDT", dec!(0.0008), dec!(12)),
+        ];
+
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert_eq!(allocations.len(), 1);
+        // ETH takes the top rank's share of capital
+        assert_eq!(allocations[0].symbol, "ETHUSDT");
+
+        allocator.set_cooling_down(Vec::new());
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert_eq!(allocations[0].symbol, "BTCUSDT");
+    }
+
+    #[test]
+    fn test_quarantined_symbols_not_added_to() {
+        let mut allocator = test_allocator();
+        allocator.set_quarantined(["BTCUSDT".to_string(), "SOLUSDT".to_string()]);
+        let pairs = vec![
+            test_pair("BTCUSDT", dec!(0.001), dec!(15)),
+            test_pair("ETHUSDT", dec!(0.0008), dec!(12)),
+            test_pair("SOLUSDT", dec!(0.0007), dec!(11)),
+        ];
+        // SOL is held below its target but isn't topped up
+        let current: HashMap<String, Decimal> =
+            [("SOLUSDT".to_string(), dec!(1000))].into_iter().collect();
+
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &current);
+        assert_eq!(allocations.len(), 1);
+        assert_eq!(allocations[0].symbol, "ETHUSDT");
+    }
+
+    #[test]
+    fn test_flash_release_extends_only_its_symbol() {
+        let mut allocator = test_allocator();
+        let pairs = vec![
+            test_pair("BTCUSDT", dec!(0.001), dec!(15)),
+            test_pair("MEMEUSDT", dec!(0.012), dec!(14)),
+        ];
+        let normal = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+
+        // Half of the 10% reserve goes to MEME alone
+        let now = chrono::Utc::now();
+        allocator.set_flash_release(Some(&FlashRelease {
+            symbol: "MEMEUSDT".to_string(),
+            funding_rate: dec!(0.012),
+            release: dec!(0.05),
+            started_at: now,
+            expires_at: now + chrono::Duration::hours(8),
+        }));
+        let flash = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert_eq!(flash[0].target_size_usdt, normal[0].target_size_usdt);
+        assert_eq!(
+            flash[1].target_size_usdt,
+            normal[1].target_size_usdt + dec!(5_000)
+        );
+
+        // With the regular budget spent, only the flash symbol still enters
+        allocator.set_utilization(Decimal::ZERO);
+        let flash = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert_eq!(flash.len(), 1);
+        assert_eq!(flash[0].symbol, "MEMEUSDT");
+        assert_eq!(flash[0].target_size_usdt, dec!(6_000));
+
+        allocator.set_flash_release(None);
+        assert!(allocator
+            .calculate_allocation(&pairs, dec!(100_000), &HashMap::new())
+            .is_empty());
+
+        // 45k notional at 5x locks 9% of equity
+        let held = HashMap::from([("MEMEUSDT".to_string(), dec!(45_000))]);
+        assert_eq!(
+            allocator.free_margin_share(dec!(100_000), &held),
+            dec!(0.91)
+        );
+    }
+
+    // =========================================================================
+    // Notional Cap Tests
+    // =========================================================================
+
+    fn capped_allocator() -> CapitalAllocator {
+        let mut allocator = test_allocator();
+        allocator.capital_config.notional_caps = crate::config::NotionalCapsConfig {
+            max_per_symbol: dec!(20_000),
+            symbols: vec![crate::config::SymbolNotionalCap {
+                symbol: "DOGEUSDT".to_string(),
+                max_notional: dec!(5_000),
+            }],
+            groups: vec![],
+        };
+        allocator
+    }
+
+    #[test]
+    fn test_allocation_respects_notional_caps() {
+        let allocator = capped_allocator();
+        let pairs = vec![
+            test_pair("BTCUSDT", dec!(0.01), dec!(100)),
+            test_pair("DOGEUSDT", dec!(0.01), dec!(100)),
+        ];
+
+        let allocations = allocator.calculate_allocation(&pairs, dec!(1_000_000), &HashMap::new());
+
+        assert_eq!(allocations[0].target_size_usdt, dec!(20_000));
+        assert_eq!(allocations[1].target_size_usdt, dec!(5_000));
+    }
+
+    #[test]
+    fn test_reduction_for_position_above_notional_cap() {
+        let allocator = capped_allocator();
+        let pairs = vec![test_pair("DOGEUSDT", dec!(0.001), dec!(15))];
+
+        // 5.5k is within the 20% rebalance threshold of the 5k cap, but still above it
+        let mut current = HashMap::new();
+        current.insert("DOGEUSDT".to_string(), dec!(5_500));
+
+        let reductions = allocator.calculate_reductions(&pairs, dec!(100_000), &current);
+
+        assert_eq!(reductions.len(), 1);
+        assert!(reductions[0].exceeds_notional_cap);
+        assert_eq!(reductions[0].target_size_usdt, dec!(5_000));
+        assert_eq!(reductions[0].reduction_usdt, dec!(500));
+        assert_eq!(allocator.notional_cap_breaches(&current).len(), 1);
+    }
+
+    #[test]
+    fn test_group_cap_reductions_count_earlier_cuts() {
+        let mut allocator = test_allocator();
+        allocator.capital_config.notional_caps = crate::config::NotionalCapsConfig {
+            max_per_symbol: Decimal::ZERO,
+            symbols: vec![],
+            groups: vec![crate::config::GroupNotionalCap {
+                name: "memes".to_string(),
+                symbols: vec!["DOGEUSDT".to_string(), "PEPEUSDT".to_string()],
+                max_notional: dec!(10_000),
+            }],
+        };
+        let pairs = vec![
+            test_pair("DOGEUSDT", dec!(0.001), dec!(15)),
+            test_pair("PEPEUSDT", dec!(0.001), dec!(14)),
+        ];
+
+        // Both members are over the 10k group cap on their own
+        let current = HashMap::from([
+            ("DOGEUSDT".to_string(), dec!(11_000)),
+            ("PEPEUSDT".to_string(), dec!(11_000)),
+        ]);
+
+        // The first is cut to nothing; the second then fills the whole cap
+        let reductions = allocator.calculate_reductions(&pairs, dec!(1_000_000), &current);
+        let target = |symbol: &str| {
+            reductions
+                .iter()
+                .find(|r| r.symbol == symbol)
+                .map(|r| r.target_size_usdt)
+                .unwrap()
+        };
+        assert!(reductions.iter().all(|r| r.exceeds_notional_cap));
+        assert_eq!(target("DOGEUSDT"), Decimal::ZERO);
+        assert_eq!(target("PEPEUSDT"), dec!(10_000));
+
+        // 8k each: one is cut to 2k and the other kept, not both cut to 2k
+        let current = HashMap::from([
+            ("DOGEUSDT".to_string(), dec!(8_000)),
+            ("PEPEUSDT".to_string(), dec!(8_000)),
+        ]);
+        let reductions = allocator.calculate_reductions(&pairs, dec!(1_000_000), &current);
+        let capped: Vec<_> = reductions
+            .iter()
+            .filter(|r| r.exceeds_notional_cap)
+            .collect();
+        assert_eq!(capped.len(), 1);
+        assert_eq!(capped[0].target_size_usdt, dec!(2_000));
+    }
+
+    #[test]
+    fn test_allocation_flags_existing_holdings_hedge() {
+        let mut allocator = test_allocator();
+        allocator.capital_config.existing_holdings = crate::config::ExistingHoldingsConfig {
+            enabled: true,
+            assets: vec![crate::config::HeldAsset {
+                asset: "BTC".to_string(),
+                quantity: dec!(1),
+            }],
+            ..Default::default()
+        };
+        let pairs = vec![
+            test_pair("BTCUSDT", dec!(0.01), dec!(100)),
+            test_pair("ETHUSDT", dec!(0.01), dec!(90)),
+        ];
+
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert!(allocations[0].hedge_with_holdings);
+        assert!(!allocations[1].hedge_with_holdings);
+
+        // Negative funding needs a spot short, so holdings cannot hedge it
+        let pairs = vec![test_pair("BTCUSDT", dec!(-0.01), dec!(100))];
+        let allocations = allocator.calculate_allocation(&pairs, dec!(100_000), &HashMap::new());
+        assert!(!allocations[0].hedge_with_holdings);
+    }
 }
@@ -0,0 +1,203 @@
+//! Attribution of exchange orders to bot positions.
+//!
+//! Every live order carries a client order ID tagged with the strategy tag, the
+//! bot instance and the position it belongs to. Matching the exchange's order
+//! history against the order journal by client order ID shows which exchange
+//! activity belongs to which position, and surfaces anything that does not line up.
+
+use crate::exchange::OrderLookup;
+use crate::persistence::PersistedOrderIntent;
+use crate::strategy::executor::ClientOrderTag;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+/// An exchange order matched to its journal entry.
+#[derive(Debug, Clone)]
+pub struct AttributedOrder {
+    pub position_id: String,
+    pub intent: PersistedOrderIntent,
+    pub order: OrderLookup,
+}
+
+impl AttributedOrder {
+    /// Whether the exchange fill differs from the quantity recorded in the journal.
+    pub fn fill_mismatch(&self) -> bool {
+        self.intent
+            .executed_qty
+            .is_some_and(|qty| qty != self.order.executed_qty)
+    }
+}
+
+/// Exchange order history reconciled against the order journal.
+#[derive(Debug, Clone, Default)]
+pub struct AttributionReport {
+    /// Exchange orders matched to a journal entry
+    pub matched: Vec<AttributedOrder>,
+    /// Exchange orders carrying this strategy's tag with no journal entry
+    pub unjournaled: Vec<OrderLookup>,
+    /// Journal entries acknowledged by the exchange but missing from its history
+    pub missing: Vec<PersistedOrderIntent>,
+    /// Exchange orders not sent by this strategy (manual or other software)
+    pub untagged: Vec<OrderLookup>,
+}
+
+impl AttributionReport {
+    /// Match exchange orders to journal entries by client order ID.
+    pub fn build(
+        strategy_tag: &str,
+        intents: Vec<PersistedOrderIntent>,
+        orders: Vec<OrderLookup>,
+    ) -> Self {
+        let mut journal: HashMap<String, PersistedOrderIntent> = intents
+            .into_iter()
+            .map(|intent| (intent.client_order_id.clone(), intent))
+            .collect();
+
+        let mut report = Self::default();
+        for order in orders {
+            if let Some(intent) = journal.remove(&order.client_order_id) {
+                let position_id = intent
+                    .correlation_id
+                    .split('-')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                report.matched.push(AttributedOrder {
+                    position_id,
+                    intent,
+                    order,
+                });
+            } else if ClientOrderTag::parse(strategy_tag, &order.client_order_id).is_some() {
+                report.unjournaled.push(order);
+            } else {
+                report.untagged.push(order);
+            }
+        }
+
+        report.missing = journal
+            .into_values()
+            .filter(|intent| intent.status == "completed")
+            .collect();
+        report.missing.sort_by_key(|intent| intent.created_at);
+        report
+    }
+
+    /// Matched orders grouped by position ID.
+    pub fn by_position(&self) -> BTreeMap<&str, Vec<&AttributedOrder>> {
+        let mut positions: BTreeMap<&str, Vec<&AttributedOrder>> = BTreeMap::new();
+        for attributed in &self.matched {
+            positions
+                .entry(attributed.position_id.as_str())
+                .or_default()
+                .push(attributed);
+        }
+        positions
+    }
+
+    /// Matched orders whose exchange fill differs from the journal.
+    pub fn fill_mismatches(&self) -> Vec<&AttributedOrder> {
+        self.matched.iter().filter(|a| a.fill_mismatch()).collect()
+    }
+
+    /// Total quantity filled on the exchange for a position.
+    pub fn position_fills(&self, position_id: &str) -> Decimal {
+        self.matched
+            .iter()
+            .filter(|a| a.position_id == position_id)
+            .map(|a| a.order.executed_qty)
+            .sum()
+    }
+
+    /// Whether every tagged exchange order and every acknowledged journal entry matched.
+    pub fn is_consistent(&self) -> bool {
+        self.unjournaled.is_empty() && self.missing.is_empty() && self.fill_mismatches().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{OrderSide, OrderStatus};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn intent(client_order_id: &str, correlation_id: &str, status: &str) -> PersistedOrderIntent {
+        PersistedOrderIntent {
+            client_order_id: client_order_id.to_string(),
+            correlation_id: correlation_id.to_string(),
+            symbol: "BTCUSDT".to_string(),
+            leg: "entry_futures".to_string(),
+            side: "SELL".to_string(),
+            quantity: dec!(0.1),
+            status: status.to_string(),
+            order_id: Some(1),
+            executed_qty: Some(dec!(0.1)),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn order(client_order_id: &str, executed_qty: Decimal) -> OrderLookup {
+        OrderLookup {
+            order_id: 1,
+            symbol: "BTCUSDT".to_string(),
+            status: OrderStatus::Filled,
+            client_order_id: client_order_id.to_string(),
+            executed_qty,
+            avg_price: None,
+            cummulative_quote_qty: None,
+            side: OrderSide::Sell,
+            time: 0,
+        }
+    }
+
+    #[test]
+    fn test_orders_attributed_to_positions() {
+        let report = AttributionReport::build(
+            "fff",
+            vec![
+                intent("fffa1b2c3-lq2x9k1a-0-1", "lq2x9k1a-0", "completed"),
+                intent("fffa1b2c3-lq2x9k1a-0-2", "lq2x9k1a-0", "completed"),
+                intent("fffa1b2c3-lq2xbbbb-3-4", "lq2xbbbb-3", "completed"),
+                intent("fffa1b2c3-lq2xcccc-5-6", "lq2xcccc-5", "failed"),
+            ],
+            vec![
+                order("fffa1b2c3-lq2x9k1a-0-1", dec!(0.1)),
+                order("fffa1b2c3-lq2x9k1a-0-2", dec!(0.08)),
+                order("fff9f8e7d-lq2xdddd-0-1", dec!(1)),
+                order("web_8Fh3kLq", dec!(2)),
+            ],
+        );
+
+        let positions = report.by_position();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions["lq2x9k1a"].len(), 2);
+        assert_eq!(report.position_fills("lq2x9k1a"), dec!(0.18));
+
+        // Partial fill on the exchange disagrees with the journal
+        let mismatches = report.fill_mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].order.executed_qty, dec!(0.08));
+
+        // Tagged but never journaled, and journaled but absent from the exchange
+        assert_eq!(report.unjournaled.len(), 1);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].correlation_id, "lq2xbbbb-3");
+        assert_eq!(report.untagged.len(), 1);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn test_consistent_report() {
+        let report = AttributionReport::build(
+            "ffarm",
+            vec![intent(
+                "ffarma1b2c3-lq2x9k1a-0-1",
+                "lq2x9k1a-0",
+                "completed",
+            )],
+            vec![order("ffarma1b2c3-lq2x9k1a-0-1", dec!(0.1))],
+        );
+        assert!(report.is_consistent());
+        assert_eq!(report.matched.len(), 1);
+    }
+}
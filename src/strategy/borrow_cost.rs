@@ -0,0 +1,335 @@
+//! Borrow cost versus funding income per asset.
+//!
+//! A short spot hedge pays interest on the borrowed asset for as long as the
+//! position is held. Borrow rates drift, and an asset whose funding looked ample
+//! at entry can end up paying most of it back as interest without any single
+//! cycle looking wrong. Recorded interest and funding are aggregated per asset
+//! and ISO week; the share of funding income consumed by interest, counted over
+//! the weeks the asset was borrowed, drives a recommendation to keep, cap or
+//! exclude the asset.
+
+use crate::persistence::{PersistedFundingEvent, PersistedInterestEvent};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Interest above this share of funding income excludes the asset.
+pub const EXCLUDE_COST_RATIO: Decimal = dec!(0.8);
+
+/// Interest above this share of funding income caps the asset's borrow exposure.
+pub const CAP_COST_RATIO: Decimal = dec!(0.4);
+
+/// Share of funding income a capped asset's interest is sized down towards.
+const TARGET_COST_RATIO: Decimal = dec!(0.25);
+
+/// What to do with an asset's borrow exposure.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum BorrowRecommendation {
+    Keep,
+    /// Limit the borrowed value; `None` when no exposure was recorded to size it
+    Cap {
+        max_borrow_usd: Option<Decimal>,
+    },
+    Exclude,
+}
+
+impl fmt::Display for BorrowRecommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowRecommendation::Keep => write!(f, "keep"),
+            BorrowRecommendation::Cap {
+                max_borrow_usd: Some(max),
+            } => write!(f, "cap borrow at ${:.0}", max),
+            BorrowRecommendation::Cap {
+                max_borrow_usd: None,
+            } => write!(f, "cap borrow"),
+            BorrowRecommendation::Exclude => write!(f, "exclude"),
+        }
+    }
+}
+
+/// Funding and interest of one asset in one ISO week (Monday to Sunday, UTC).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetWeek {
+    pub week_start: NaiveDate,
+    pub funding_income: Decimal,
+    pub interest_paid: Decimal,
+}
+
+impl AssetWeek {
+    /// Interest as a share of funding income; `None` without funding income.
+    pub fn cost_ratio(&self) -> Option<Decimal> {
+        cost_ratio(self.interest_paid, self.funding_income)
+    }
+}
+
+/// Borrow cost of one asset over the report window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetBorrowCost {
+    /// Base asset (e.g. "BTC" for BTCUSDT)
+    pub asset: String,
+    /// Funding income over the whole window
+    pub funding_income: Decimal,
+    /// Funding income in the weeks interest was paid
+    pub borrowed_funding_income: Decimal,
+    pub interest_paid: Decimal,
+    /// Average borrowed value (USDT) over interest charges that recorded it
+    pub avg_borrowed_usd: Option<Decimal>,
+    /// Weeks with funding or interest, oldest first
+    pub weeks: Vec<AssetWeek>,
+    pub recommendation: BorrowRecommendation,
+}
+
+impl AssetBorrowCost {
+    /// Interest as a share of funding income while borrowed.
+    ///
+    /// `None` when interest was paid in weeks without any funding income: the
+    /// borrow ate all of the carry and then some.
+    pub fn cost_ratio(&self) -> Option<Decimal> {
+        cost_ratio(self.interest_paid, self.borrowed_funding_income)
+    }
+
+    /// Weeks in which interest matched or exceeded funding income.
+    pub fn weeks_eaten(&self) -> usize {
+        self.weeks
+            .iter()
+            .filter(|w| w.interest_paid > Decimal::ZERO && w.interest_paid >= w.funding_income)
+            .count()
+    }
+}
+
+/// Per-asset borrow cost, worst first.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BorrowCostReport {
+    /// Assets that paid interest, by cost ratio (highest first)
+    pub assets: Vec<AssetBorrowCost>,
+}
+
+impl BorrowCostReport {
+    /// Aggregate funding and interest events; assets that never paid interest are left out.
+    pub fn build(funding: &[PersistedFundingEvent], interest: &[PersistedInterestEvent]) -> Self {
+        #[derive(Default)]
+        struct Totals {
+            weeks: BTreeMap<NaiveDate, (Decimal, Decimal)>,
+            borrowed_sum: Decimal,
+            borrowed_count: u32,
+        }
+
+        let mut assets: BTreeMap<String, Totals> = BTreeMap::new();
+        for event in funding {
+            let totals = assets.entry(base_asset(&event.symbol)).or_default();
+            let week = totals.weeks.entry(week_start(event.timestamp)).or_default();
+            week.0 += event.amount;
+        }
+        for event in interest {
+            let totals = assets.entry(base_asset(&event.symbol)).or_default();
+            let week = totals.weeks.entry(week_start(event.timestamp)).or_default();
+            week.1 += event.amount;
+            if let Some(borrowed) = event.borrowed_amount {
+                totals.borrowed_sum += borrowed;
+                totals.borrowed_count += 1;
+            }
+        }
+
+        let mut assets: Vec<AssetBorrowCost> = assets
+            .into_iter()
+            .filter_map(|(asset, totals)| {
+                let weeks: Vec<AssetWeek> = totals
+                    .weeks
+                    .into_iter()
+                    .map(|(week_start, (funding_income, interest_paid))| AssetWeek {
+                        week_start,
+                        funding_income,
+                        interest_paid,
+                    })
+                    .collect();
+                let interest_paid: Decimal = weeks.iter().map(|w| w.interest_paid).sum();
+                if interest_paid <= Decimal::ZERO {
+                    return None;
+                }
+                let avg_borrowed_usd = (totals.borrowed_count > 0)
+                    .then(|| totals.borrowed_sum / Decimal::from(totals.borrowed_count));
+                let mut cost = AssetBorrowCost {
+                    asset,
+                    funding_income: weeks.iter().map(|w| w.funding_income).sum(),
+                    borrowed_funding_income: weeks
+                        .iter()
+                        .filter(|w| w.interest_paid > Decimal::ZERO)
+                        .map(|w| w.funding_income)
+                        .sum(),
+                    interest_paid,
+                    avg_borrowed_usd,
+                    weeks,
+                    recommendation: BorrowRecommendation::Keep,
+                };
+                cost.recommendation = recommend(cost.cost_ratio(), avg_borrowed_usd);
+                Some(cost)
+            })
+            .collect();
+        // Uncovered interest (no ratio) sorts first
+        assets.sort_by_key(|a| std::cmp::Reverse(a.cost_ratio().unwrap_or(Decimal::MAX)));
+
+        Self { assets }
+    }
+
+    /// Assets recommended for a cap or exclusion.
+    pub fn flagged(&self) -> impl Iterator<Item = &AssetBorrowCost> {
+        self.assets
+            .iter()
+            .filter(|a| a.recommendation != BorrowRecommendation::Keep)
+    }
+}
+
+/// Recommendation for an asset whose interest is `ratio` of its funding income.
+///
+/// A capped asset's average borrowed value is scaled down by how far its ratio
+/// exceeds the target, limiting what a further rise in the rate can cost.
+fn recommend(ratio: Option<Decimal>, avg_borrowed_usd: Option<Decimal>) -> BorrowRecommendation {
+    match ratio {
+        None => BorrowRecommendation::Exclude,
+        Some(ratio) if ratio >= EXCLUDE_COST_RATIO => BorrowRecommendation::Exclude,
+        Some(ratio) if ratio >= CAP_COST_RATIO => BorrowRecommendation::Cap {
+            max_borrow_usd: avg_borrowed_usd.map(|avg| avg * TARGET_COST_RATIO / ratio),
+        },
+        Some(_) => BorrowRecommendation::Keep,
+    }
+}
+
+fn cost_ratio(interest: Decimal, funding_income: Decimal) -> Option<Decimal> {
+    if funding_income <= Decimal::ZERO {
+        None
+    } else {
+        Some(interest / funding_income)
+    }
+}
+
+fn base_asset(symbol: &str) -> String {
+    symbol.strip_suffix("USDT").unwrap_or(symbol).to_string()
+}
+
+fn week_start(timestamp: DateTime<Utc>) -> NaiveDate {
+    let date = timestamp.date_naive();
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::FundingState;
+
+    fn at(day: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", day))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn funding(symbol: &str, day: &str, amount: Decimal) -> PersistedFundingEvent {
+        PersistedFundingEvent {
+            timestamp: at(day),
+            symbol: symbol.to_string(),
+            amount,
+            position_value: None,
+            state: FundingState::Verified,
+            expected_amount: None,
+        }
+    }
+
+    fn interest(
+        symbol: &str,
+        day: &str,
+        amount: Decimal,
+        borrowed: Option<Decimal>,
+    ) -> PersistedInterestEvent {
+        PersistedInterestEvent {
+            timestamp: at(day),
+            symbol: symbol.to_string(),
+            amount,
+            borrowed_amount: borrowed,
+        }
+    }
+
+    #[test]
+    fn test_cost_ratio_counts_borrowed_weeks() {
+        let report = BorrowCostReport::build(
+            &[
+                // ALT earned 100 before it was ever borrowed
+                funding("ALTUSDT", "2024-03-04", dec!(100)),
+                funding("ALTUSDT", "2024-03-11", dec!(20)),
+                funding("ALTUSDT", "2024-03-18", dec!(20)),
+                funding("BTCUSDT", "2024-03-11", dec!(50)),
+                funding("ETHUSDT", "2024-03-11", dec!(30)),
+            ],
+            &[
+                interest("ALTUSDT", "2024-03-12", dec!(10), Some(dec!(4000))),
+                interest("ALTUSDT", "2024-03-19", dec!(14), Some(dec!(6000))),
+                interest("BTCUSDT", "2024-03-12", dec!(5), Some(dec!(10000))),
+                // Borrowed through a week without funding
+                interest("DOGEUSDT", "2024-03-12", dec!(2), None),
+            ],
+        );
+
+        // ETH never borrowed
+        assert_eq!(report.assets.len(), 3);
+        assert_eq!(report.assets[0].asset, "DOGE");
+        assert_eq!(report.assets[0].cost_ratio(), None);
+        assert_eq!(
+            report.assets[0].recommendation,
+            BorrowRecommendation::Exclude
+        );
+
+        // 24 of the 40 earned while borrowed, not of the 140 overall
+        let alt = &report.assets[1];
+        assert_eq!(alt.asset, "ALT");
+        assert_eq!(alt.funding_income, dec!(140));
+        assert_eq!(alt.cost_ratio(), Some(dec!(0.6)));
+        assert_eq!(alt.weeks.len(), 3);
+        assert_eq!(alt.weeks[1].cost_ratio(), Some(dec!(0.5)));
+        assert_eq!(alt.weeks_eaten(), 0);
+        assert_eq!(alt.avg_borrowed_usd, Some(dec!(5000)));
+        assert_eq!(
+            alt.recommendation,
+            BorrowRecommendation::Cap {
+                max_borrow_usd: Some(dec!(5000) * dec!(0.25) / dec!(0.6))
+            }
+        );
+
+        assert_eq!(report.assets[2].asset, "BTC");
+        assert_eq!(report.assets[2].cost_ratio(), Some(dec!(0.1)));
+        assert_eq!(report.assets[2].recommendation, BorrowRecommendation::Keep);
+        assert_eq!(report.flagged().count(), 2);
+    }
+
+    #[test]
+    fn test_recommendation_thresholds() {
+        assert_eq!(
+            recommend(Some(dec!(0.8)), Some(dec!(1000))),
+            BorrowRecommendation::Exclude
+        );
+        assert_eq!(
+            recommend(Some(dec!(0.5)), Some(dec!(1000))),
+            BorrowRecommendation::Cap {
+                max_borrow_usd: Some(dec!(500))
+            }
+        );
+        assert_eq!(
+            recommend(Some(dec!(0.4)), None),
+            BorrowRecommendation::Cap {
+                max_borrow_usd: None
+            }
+        );
+        assert_eq!(
+            recommend(Some(dec!(0.39)), Some(dec!(1000))),
+            BorrowRecommendation::Keep
+        );
+        assert_eq!(
+            BorrowRecommendation::Cap {
+                max_borrow_usd: Some(dec!(1234.4))
+            }
+            .to_string(),
+            "cap borrow at $1234"
+        );
+    }
+}
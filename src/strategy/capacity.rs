@@ -0,0 +1,295 @@
+//! Strategy capacity: how much capital the candidate pairs can absorb.
+//!
+//! Each extra dollar deployed into a pair walks deeper into its spot book, so the
+//! average fill drifts away from mid until entry and exit slippage eat the carry
+//! the position is held for. A pair's capacity is the largest notional whose
+//! round-trip slippage stays within both `execution.slippage_tolerance` and the
+//! net funding expected over the holding period, further capped at a share of
+//! its open interest so the position doesn't dominate the market it farms. The
+//! strategy's capacity is the sum over the best pairs it would hold at once.
+
+use crate::exchange::FundingSchedule;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// What stops a pair from absorbing more capital.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CapacityLimit {
+    /// Slippage reaches `execution.slippage_tolerance`
+    SlippageTolerance,
+    /// Round-trip slippage would eat the expected carry
+    Edge,
+    /// Position reaches its share of open interest
+    OpenInterest,
+    /// The sampled book runs out first; capacity is at least this much
+    BookDepth,
+}
+
+impl fmt::Display for CapacityLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CapacityLimit::SlippageTolerance => "slippage tolerance",
+            CapacityLimit::Edge => "edge",
+            CapacityLimit::OpenInterest => "open interest",
+            CapacityLimit::BookDepth => "book depth",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Terms capacity is estimated under.
+#[derive(Debug, Clone, Copy)]
+pub struct CapacityTerms {
+    /// Largest average slippage of one side, as a fraction of mid
+    pub slippage_tolerance: Decimal,
+    /// Holding period the carry is earned over (hours)
+    pub horizon_hours: u32,
+    /// Futures fee of entering and exiting, as a fraction of notional
+    pub round_trip_fee: Decimal,
+    /// Largest share of a pair's open interest to hold
+    pub max_open_interest_share: Decimal,
+}
+
+/// Market inputs of one candidate pair.
+#[derive(Debug, Clone)]
+pub struct CapacityInput<'a> {
+    pub symbol: &'a str,
+    /// Funding rate per settlement
+    pub funding_rate: Decimal,
+    pub schedule: FundingSchedule,
+    /// Hourly borrow rate of the base asset, paid when the spot leg is short
+    pub borrow_rate: Option<Decimal>,
+    /// Spot levels the entry trades against, best first: the asks when buying
+    /// spot, the bids when selling it short
+    pub levels: &'a [(Decimal, Decimal)],
+    pub mid_price: Decimal,
+    /// Futures open interest in base asset
+    pub open_interest: Decimal,
+}
+
+/// Deployable notional of one pair.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairCapacity {
+    pub symbol: String,
+    pub funding_rate: Decimal,
+    /// Net carry expected over the horizon, as a fraction of notional
+    pub edge: Decimal,
+    /// Average slippage allowed per side
+    pub max_slippage: Decimal,
+    /// Notional the book absorbs within `max_slippage`
+    pub depth_notional: Decimal,
+    /// Notional at the open interest share
+    pub open_interest_notional: Decimal,
+    /// Smaller of the two
+    pub capacity_notional: Decimal,
+    pub limit: CapacityLimit,
+}
+
+impl PairCapacity {
+    /// Capacity of a pair; `None` when it has no edge to erode.
+    ///
+    /// Exits are assumed to cost what entries do, so each side may slip half
+    /// the edge. The futures book is assumed deeper than spot and not walked.
+    pub fn estimate(input: &CapacityInput<'_>, terms: &CapacityTerms) -> Option<Self> {
+        if input.mid_price <= Decimal::ZERO {
+            return None;
+        }
+        let hours = Decimal::from(terms.horizon_hours);
+        let funding = input
+            .schedule
+            .rate_per_hours(input.funding_rate.abs(), terms.horizon_hours);
+        let borrow = if input.funding_rate < Decimal::ZERO {
+            input.borrow_rate.unwrap_or_default() * hours
+        } else {
+            Decimal::ZERO
+        };
+        let edge = funding - borrow - terms.round_trip_fee;
+        if edge <= Decimal::ZERO {
+            return None;
+        }
+
+        let edge_slippage = edge / Decimal::TWO;
+        let max_slippage = terms.slippage_tolerance.min(edge_slippage);
+        let (depth_qty, exhausted) =
+            max_quantity_within(input.levels, input.mid_price, max_slippage);
+        let depth_notional = depth_qty * input.mid_price;
+        let open_interest_notional =
+            input.open_interest * input.mid_price * terms.max_open_interest_share;
+
+        let (capacity_notional, limit) = if open_interest_notional < depth_notional {
+            (open_interest_notional, CapacityLimit::OpenInterest)
+        } else if exhausted {
+            (depth_notional, CapacityLimit::BookDepth)
+        } else if edge_slippage < terms.slippage_tolerance {
+            (depth_notional, CapacityLimit::Edge)
+        } else {
+            (depth_notional, CapacityLimit::SlippageTolerance)
+        };
+
+        Some(Self {
+            symbol: input.symbol.to_string(),
+            funding_rate: input.funding_rate,
+            edge,
+            max_slippage,
+            depth_notional,
+            open_interest_notional,
+            capacity_notional,
+            limit,
+        })
+    }
+}
+
+/// Largest quantity whose average slippage against `levels` stays within
+/// `max_slippage` of `mid`, and whether the levels ran out first.
+fn max_quantity_within(
+    levels: &[(Decimal, Decimal)],
+    mid: Decimal,
+    max_slippage: Decimal,
+) -> (Decimal, bool) {
+    let allowed_per_unit = max_slippage * mid;
+    let mut quantity = Decimal::ZERO;
+    let mut cost = Decimal::ZERO;
+
+    for &(price, level_qty) in levels {
+        let unit_cost = (price - mid).abs();
+        if unit_cost <= allowed_per_unit {
+            quantity += level_qty;
+            cost += level_qty * unit_cost;
+            continue;
+        }
+        // Take this level until the average reaches the allowance
+        let take = (allowed_per_unit * quantity - cost) / (unit_cost - allowed_per_unit);
+        return (quantity + take.clamp(Decimal::ZERO, level_qty), false);
+    }
+    (quantity, true)
+}
+
+/// Capacity of the strategy across its candidate pairs.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityEstimate {
+    /// Pairs by capacity, largest first
+    pub pairs: Vec<PairCapacity>,
+    /// Pairs held at once
+    pub max_positions: usize,
+}
+
+impl CapacityEstimate {
+    pub fn new(mut pairs: Vec<PairCapacity>, max_positions: usize) -> Self {
+        pairs.sort_by_key(|p| Reverse(p.capacity_notional));
+        Self {
+            pairs,
+            max_positions,
+        }
+    }
+
+    /// Notional deployable across the `max_positions` largest pairs.
+    pub fn total_notional(&self) -> Decimal {
+        self.pairs
+            .iter()
+            .take(self.max_positions)
+            .map(|p| p.capacity_notional)
+            .sum()
+    }
+
+    /// Equity that deploys the total notional at `utilization`.
+    pub fn equity_capacity(&self, utilization: Decimal) -> Decimal {
+        if utilization <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        self.total_notional() / utilization
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn terms() -> CapacityTerms {
+        CapacityTerms {
+            slippage_tolerance: dec!(0.001),
+            horizon_hours: 72,
+            round_trip_fee: dec!(0.0008),
+            max_open_interest_share: dec!(0.02),
+        }
+    }
+
+    #[test]
+    fn test_book_walk_stops_at_the_allowed_average() {
+        let asks = [(dec!(100.05), dec!(10)), (dec!(100.2), dec!(10))];
+        // 10 at 0.05 plus x at 0.2 averages 0.1: x = 5
+        let (qty, exhausted) = max_quantity_within(&asks, dec!(100), dec!(0.001));
+        assert_eq!(qty, dec!(15));
+        assert!(!exhausted);
+
+        let (qty, exhausted) = max_quantity_within(&asks, dec!(100), dec!(0.01));
+        assert_eq!(qty, dec!(20));
+        assert!(exhausted);
+    }
+
+    #[test]
+    fn test_pair_capacity_limits() {
+        let asks = [(dec!(100.05), dec!(10)), (dec!(100.2), dec!(10))];
+        let mut input = CapacityInput {
+            symbol: "SOLUSDT",
+            funding_rate: dec!(0.0005),
+            schedule: FundingSchedule::BINANCE_DEFAULT,
+            borrow_rate: None,
+            levels: &asks,
+            mid_price: dec!(100),
+            open_interest: dec!(1_000_000),
+        };
+
+        // 9 settlements at 0.05% less fees leave a 0.37% edge: tolerance binds
+        let pair = PairCapacity::estimate(&input, &terms()).unwrap();
+        assert_eq!(pair.edge, dec!(0.0037));
+        assert_eq!(pair.depth_notional, dec!(1500));
+        assert_eq!(pair.limit, CapacityLimit::SlippageTolerance);
+
+        // A thin carry allows less slippage than the tolerance
+        input.funding_rate = dec!(0.0001);
+        let pair = PairCapacity::estimate(&input, &terms()).unwrap();
+        assert_eq!(pair.max_slippage, dec!(0.00005));
+        assert_eq!(pair.limit, CapacityLimit::Edge);
+        assert_eq!(pair.capacity_notional, Decimal::ZERO);
+
+        // Shorting spot pays borrow, which can leave no edge at all
+        input.funding_rate = dec!(-0.0001);
+        input.borrow_rate = Some(dec!(0.00001));
+        assert!(PairCapacity::estimate(&input, &terms()).is_none());
+
+        // A small market caps the position at its open interest share
+        input.funding_rate = dec!(0.0005);
+        input.open_interest = dec!(500);
+        let pair = PairCapacity::estimate(&input, &terms()).unwrap();
+        assert_eq!(pair.capacity_notional, dec!(1000));
+        assert_eq!(pair.limit, CapacityLimit::OpenInterest);
+    }
+
+    #[test]
+    fn test_total_over_held_pairs() {
+        let pair = |symbol: &str, notional: Decimal| PairCapacity {
+            symbol: symbol.to_string(),
+            funding_rate: dec!(0.0005),
+            edge: dec!(0.004),
+            max_slippage: dec!(0.001),
+            depth_notional: notional,
+            open_interest_notional: notional,
+            capacity_notional: notional,
+            limit: CapacityLimit::SlippageTolerance,
+        };
+        let estimate = CapacityEstimate::new(
+            vec![
+                pair("ETHUSDT", dec!(20000)),
+                pair("SOLUSDT", dec!(50000)),
+                pair("DOGEUSDT", dec!(5000)),
+            ],
+            2,
+        );
+        assert_eq!(estimate.pairs[0].symbol, "SOLUSDT");
+        assert_eq!(estimate.total_notional(), dec!(70000));
+        assert_eq!(estimate.equity_capacity(dec!(0.7)), dec!(100000));
+    }
+}
@@ -0,0 +1,239 @@
+//! Funding farming on COIN-margined perpetuals with held coins as collateral.
+//!
+//! Coins declared under `capital.existing_holdings` sit in the COIN-margined
+//! futures wallet. A short on the coin's USD-quoted perpetual worth the coins'
+//! USD value leaves the account flat in USD terms, so positive funding is
+//! collected without borrowing or posting any USDT. Shorts are opened on
+//! qualified pairs whose COIN-margined perpetual pays at least the minimum
+//! funding rate, and closed once funding turns negative or the coins are no
+//! longer declared.
+
+use crate::config::ExistingHoldingsConfig;
+use crate::exchange::{CoinContract, CoinPosition, QualifiedPair};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// A short held on a COIN-margined perpetual.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinMarginedPosition {
+    /// Contract symbol (e.g., "BTCUSD_PERP")
+    pub symbol: String,
+    /// Coin posted as collateral (e.g., "BTC")
+    pub base_asset: String,
+    /// Contracts short
+    pub contracts: Decimal,
+}
+
+impl CoinMarginedPosition {
+    /// The short a COIN-margined perpetual position holds, if it is one.
+    pub fn from_position(position: &CoinPosition) -> Option<Self> {
+        let base_asset = position.symbol.strip_suffix("USD_PERP")?;
+        (position.position_amt < Decimal::ZERO).then(|| Self {
+            symbol: position.symbol.clone(),
+            base_asset: base_asset.to_string(),
+            contracts: -position.position_amt,
+        })
+    }
+}
+
+/// A COIN-margined short to open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoinMarginedEntry {
+    /// Contract symbol (e.g., "BTCUSD_PERP")
+    pub symbol: String,
+    pub base_asset: String,
+    /// Contracts to short
+    pub contracts: Decimal,
+    pub funding_rate: Decimal,
+    /// USD value of the short
+    pub notional: Decimal,
+}
+
+/// Shorts to open on qualified pairs whose COIN-margined perpetual pays at least
+/// `min_funding_rate`, each covering the declared coins of its base asset.
+///
+/// Assets already shorted are not added to.
+pub fn coin_margined_entries(
+    pairs: &[QualifiedPair],
+    held: &[CoinMarginedPosition],
+    holdings: &ExistingHoldingsConfig,
+    min_funding_rate: Decimal,
+) -> Vec<CoinMarginedEntry> {
+    pairs
+        .iter()
+        .filter_map(|pair| {
+            let contract = pair.coin_contract.as_ref()?;
+            if contract.funding_rate < min_funding_rate
+                || held.iter().any(|p| p.symbol == contract.symbol)
+            {
+                return None;
+            }
+            let contracts = contract.contracts_for(holdings.held_quantity(&pair.base_asset));
+            (contracts > Decimal::ZERO).then(|| CoinMarginedEntry {
+                symbol: contract.symbol.clone(),
+                base_asset: contract.base_asset.clone(),
+                contracts,
+                funding_rate: contract.funding_rate,
+                notional: contract.notional(contracts),
+            })
+        })
+        .collect()
+}
+
+/// Held shorts to close: funding turned negative, or the coins are no longer
+/// declared.
+///
+/// Shorts without a current rate for their contract are kept.
+pub fn coin_margined_exits<'a>(
+    held: &'a [CoinMarginedPosition],
+    contracts: &HashMap<String, CoinContract>,
+    holdings: &ExistingHoldingsConfig,
+) -> Vec<&'a CoinMarginedPosition> {
+    held.iter()
+        .filter(|position| {
+            !holdings.covers(&position.base_asset)
+                || contracts
+                    .get(&position.base_asset)
+                    .is_some_and(|contract| contract.funding_rate < Decimal::ZERO)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HeldAsset;
+    use crate::exchange::{CoinExchangeInfo, CoinPremiumIndex, HedgeInstrument};
+    use rust_decimal_macros::dec;
+
+    fn contract(funding_rate: Decimal) -> CoinContract {
+        CoinContract {
+            symbol: "BTCUSD_PERP".to_string(),
+            base_asset: "BTC".to_string(),
+            contract_size: dec!(100),
+            funding_rate,
+            next_funding_time: 0,
+            mark_price: dec!(60000),
+        }
+    }
+
+    fn pair(coin_contract: Option<CoinContract>) -> QualifiedPair {
+        QualifiedPair {
+            symbol: "BTCUSDT".to_string(),
+            spot_symbol: "BTCUSDT".to_string(),
+            base_asset: "BTC".to_string(),
+            funding_rate: dec!(0.0003),
+            next_funding_time: 0,
+            volume_24h: dec!(1_000_000_000),
+            spread: dec!(0.0001),
+            open_interest: Decimal::ZERO,
+            margin_available: true,
+            borrow_rate: None,
+            score: dec!(1),
+            hedge: HedgeInstrument::SpotMargin,
+            hedge_options: Vec::new(),
+            funding_capped: false,
+            coin_contract,
+            borrow_headroom_usdt: None,
+        }
+    }
+
+    fn holdings(quantity: Decimal) -> ExistingHoldingsConfig {
+        ExistingHoldingsConfig {
+            enabled: true,
+            assets: vec![HeldAsset {
+                asset: "BTC".to_string(),
+                quantity,
+            }],
+            coin_margined: true,
+        }
+    }
+
+    #[test]
+    fn test_perpetuals_from_exchange_info() {
+        let info: CoinExchangeInfo = serde_json::from_str(
+            r#"{"symbols": [
+                {"symbol": "BTCUSD_PERP", "pair": "BTCUSD", "contractType": "PERPETUAL",
+                 "contractStatus": "TRADING", "baseAsset": "BTC", "marginAsset": "BTC",
+                 "contractSize": 100},
+                {"symbol": "BTCUSD_250926", "pair": "BTCUSD", "contractType": "CURRENT_QUARTER",
+                 "contractStatus": "TRADING", "baseAsset": "BTC", "marginAsset": "BTC",
+                 "contractSize": 100},
+                {"symbol": "ETHUSD_PERP", "pair": "ETHUSD", "contractType": "PERPETUAL",
+                 "contractStatus": "TRADING", "baseAsset": "ETH", "marginAsset": "ETH",
+                 "contractSize": 10}
+            ]}"#,
+        )
+        .unwrap();
+        let index: Vec<CoinPremiumIndex> = serde_json::from_str(
+            r#"[
+                {"symbol": "BTCUSD_PERP", "markPrice": "60000.1", "lastFundingRate": "0.00010000",
+                 "nextFundingTime": 1700000000000},
+                {"symbol": "BTCUSD_250926", "markPrice": "61000", "lastFundingRate": "",
+                 "nextFundingTime": 0}
+            ]"#,
+        )
+        .unwrap();
+
+        // Quarterlies have no funding; ETH has no premium index entry
+        let contracts = info.perpetuals(&index);
+        assert_eq!(contracts.len(), 1);
+        let btc = &contracts["BTC"];
+        assert_eq!(btc.symbol, "BTCUSD_PERP");
+        assert_eq!(btc.contract_size, dec!(100));
+        assert_eq!(btc.funding_rate, dec!(0.0001));
+        assert_eq!(btc.mark_price, dec!(60000.1));
+    }
+
+    #[test]
+    fn test_contract_sizing() {
+        let btc = contract(dec!(0.0001));
+        // 0.5 BTC at 60k is $30,000: 300 contracts of $100
+        assert_eq!(btc.contracts_for(dec!(0.5)), dec!(300));
+        // Partial contracts are dropped
+        assert_eq!(btc.contracts_for(dec!(0.0049)), dec!(2));
+        assert_eq!(btc.notional(dec!(-300)), dec!(30000));
+    }
+
+    #[test]
+    fn test_entries_and_exits() {
+        let pairs = vec![pair(Some(contract(dec!(0.0002)))), pair(None)];
+        let entries = coin_margined_entries(&pairs, &[], &holdings(dec!(0.5)), dec!(0.0001));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].symbol, "BTCUSD_PERP");
+        assert_eq!(entries[0].contracts, dec!(300));
+        assert_eq!(entries[0].notional, dec!(30000));
+
+        // Too little funding, no coins, or already short: nothing to open
+        assert!(coin_margined_entries(&pairs, &[], &holdings(dec!(0.5)), dec!(0.0005)).is_empty());
+        assert!(
+            coin_margined_entries(&pairs, &[], &holdings(dec!(0.001)), dec!(0.0001)).is_empty()
+        );
+        let position: CoinPosition = serde_json::from_str(
+            r#"{"symbol": "BTCUSD_PERP", "positionAmt": "-300", "entryPrice": "60000",
+                "markPrice": "60100", "unRealizedProfit": "-0.0008"}"#,
+        )
+        .unwrap();
+        let held = vec![CoinMarginedPosition::from_position(&position).unwrap()];
+        assert_eq!(held[0].base_asset, "BTC");
+        assert_eq!(held[0].contracts, dec!(300));
+        assert!(
+            coin_margined_entries(&pairs, &held, &holdings(dec!(0.5)), dec!(0.0001)).is_empty()
+        );
+
+        // Held while funding stays positive or is unknown
+        let rates = |rate| HashMap::from([("BTC".to_string(), contract(rate))]);
+        assert!(coin_margined_exits(&held, &rates(dec!(0)), &holdings(dec!(0.5))).is_empty());
+        assert!(coin_margined_exits(&held, &HashMap::new(), &holdings(dec!(0.5))).is_empty());
+        assert_eq!(
+            coin_margined_exits(&held, &rates(dec!(-0.0001)), &holdings(dec!(0.5))).len(),
+            1
+        );
+        let mut released = holdings(dec!(0.5));
+        released.enabled = false;
+        assert_eq!(
+            coin_margined_exits(&held, &rates(dec!(0.0001)), &released).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! Cross-venue funding spread detection (Binance vs Hyperliquid).
+//!
+//! When the same asset pays very different funding on two venues, a venue-neutral
+//! trade (short the perp where funding is higher, long it where funding is lower)
+//! collects the difference without a spot leg. Spreads are reported as
+//! opportunities with a suggested size under their own caps. With execution on,
+//! new opportunities are entered on Binance futures and Hyperliquid perps and
+//! held until the spread narrows below the exit threshold or flips.
+
+use crate::config::CrossVenueConfig;
+use crate::exchange::{FundingCalendar, FundingRate, Venue};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Rates are compared per 8 hours, the Binance default period.
+const COMPARISON_HOURS: u32 = 8;
+
+/// Funding divergence on one asset between the two venues.
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingSpread {
+    /// Binance futures symbol (e.g., "BTCUSDT")
+    pub symbol: String,
+    /// Hyperliquid coin (e.g., "BTC")
+    pub coin: String,
+    /// Binance funding rate scaled to 8h
+    pub binance_rate: Decimal,
+    /// Hyperliquid funding rate scaled to 8h
+    pub hyperliquid_rate: Decimal,
+    /// Absolute rate difference per 8h
+    pub spread: Decimal,
+    /// Gross annualized return of the venue-neutral trade (before fees)
+    pub apr: Decimal,
+    /// Venue to short (higher funding, shorts receive)
+    pub short_venue: Venue,
+    /// Suggested notional per leg in USDT under the cross-venue caps
+    pub suggested_notional: Decimal,
+}
+
+impl FundingSpread {
+    /// Venue to go long (lower funding).
+    pub fn long_venue(&self) -> Venue {
+        // Spreads compare Binance with Hyperliquid only
+        match self.short_venue {
+            Venue::Binance | Venue::Bybit | Venue::Okx => Venue::Hyperliquid,
+            Venue::Hyperliquid => Venue::Binance,
+        }
+    }
+}
+
+/// Binance and Hyperliquid rates of one asset, both scaled to 8h.
+fn rates_per_8h(
+    rate: &FundingRate,
+    hyperliquid: &HashMap<String, Decimal>,
+    calendar: &FundingCalendar,
+) -> Option<(String, Decimal, Decimal)> {
+    let coin = rate.symbol.strip_suffix("USDT")?;
+    let binance_rate = calendar
+        .schedule(Venue::Binance, &rate.symbol)
+        .rate_per_hours(rate.funding_rate, COMPARISON_HOURS);
+    let hyperliquid_rate = calendar
+        .schedule(Venue::Hyperliquid, coin)
+        .rate_per_hours(*hyperliquid.get(coin)?, COMPARISON_HOURS);
+    Some((coin.to_string(), binance_rate, hyperliquid_rate))
+}
+
+/// Spreads at or above `min_spread`, widest first, sized under the caps.
+///
+/// Each venue's rate is scaled from its settlement interval in `calendar` to 8h
+/// before comparing. Sizes are assigned in spread order: each asset gets up to
+/// `max_notional_per_pair` until `max_total_notional` is used up.
+pub fn funding_spreads(
+    binance: &[FundingRate],
+    hyperliquid: &HashMap<String, Decimal>,
+    calendar: &FundingCalendar,
+    config: &CrossVenueConfig,
+) -> Vec<FundingSpread> {
+    let mut spreads: Vec<FundingSpread> = binance
+        .iter()
+        .filter_map(|rate| {
+            let (coin, binance_rate, hyperliquid_rate) = rates_per_8h(rate, hyperliquid, calendar)?;
+            let spread = (binance_rate - hyperliquid_rate).abs();
+            if spread < config.min_spread {
+                return None;
+            }
+
+            Some(FundingSpread {
+                symbol: rate.symbol.clone(),
+                coin,
+                binance_rate,
+                hyperliquid_rate,
+                spread,
+                apr: spread * Decimal::from(365 * 24 / COMPARISON_HOURS),
+                short_venue: if binance_rate > hyperliquid_rate {
+                    Venue::Binance
+                } else {
+                    Venue::Hyperliquid
+                },
+                suggested_notional: Decimal::ZERO,
+            })
+        })
+        .collect();
+    spreads.sort_by_key(|spread| Reverse(spread.spread));
+
+    let mut remaining = config.max_total_notional;
+    for spread in &mut spreads {
+        spread.suggested_notional = config.max_notional_per_pair.min(remaining);
+        remaining -= spread.suggested_notional;
+    }
+
+    spreads
+}
+
+/// A spread trade held on both venues.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadPosition {
+    /// Binance futures symbol (e.g., "BTCUSDT")
+    pub symbol: String,
+    /// Hyperliquid coin (e.g., "BTC")
+    pub coin: String,
+    /// Venue holding the short leg
+    pub short_venue: Venue,
+    /// Base quantity of each leg
+    pub quantity: Decimal,
+    /// Notional per leg at entry in USDT
+    pub notional: Decimal,
+}
+
+impl SpreadPosition {
+    /// Spread trade a Hyperliquid position belongs to, its Binance leg being
+    /// the opposite side of the coin's USDT perpetual.
+    pub fn from_hyperliquid(coin: &str, size: Decimal, position_value: Decimal) -> Self {
+        Self {
+            symbol: format!("{}USDT", coin),
+            coin: coin.to_string(),
+            short_venue: if size < Decimal::ZERO {
+                Venue::Hyperliquid
+            } else {
+                Venue::Binance
+            },
+            quantity: size.abs(),
+            notional: position_value.abs(),
+        }
+    }
+}
+
+/// Notional to enter `spread` with, given the trades already held.
+///
+/// Held symbols are not added to; others get up to `max_notional_per_pair` of
+/// what is left under `max_total_notional`.
+pub fn entry_notional(
+    spread: &FundingSpread,
+    held: &[SpreadPosition],
+    config: &CrossVenueConfig,
+) -> Decimal {
+    if held.iter().any(|position| position.symbol == spread.symbol) {
+        return Decimal::ZERO;
+    }
+    let used: Decimal = held.iter().map(|position| position.notional).sum();
+    config
+        .max_notional_per_pair
+        .min(config.max_total_notional - used)
+        .max(Decimal::ZERO)
+}
+
+/// Held spread trades to close: those whose spread, in the direction they are
+/// held, has narrowed below `exit_spread` or flipped.
+///
+/// Trades without current rates on both venues are kept.
+pub fn spread_exits(
+    held: &[SpreadPosition],
+    binance: &[FundingRate],
+    hyperliquid: &HashMap<String, Decimal>,
+    calendar: &FundingCalendar,
+    exit_spread: Decimal,
+) -> Vec<String> {
+    held.iter()
+        .filter(|position| {
+            binance
+                .iter()
+                .find(|rate| rate.symbol == position.symbol)
+                .and_then(|rate| rates_per_8h(rate, hyperliquid, calendar))
+                .is_some_and(|(_, binance_rate, hyperliquid_rate)| {
+                    let earned = match position.short_venue {
+                        Venue::Hyperliquid => hyperliquid_rate - binance_rate,
+                        _ => binance_rate - hyperliquid_rate,
+                    };
+                    earned < exit_spread
+                })
+        })
+        .map(|position| position.symbol.clone())
+        .collect()
+}
+
+/// Reports each spread once while it stays open.
+#[derive(Debug, Default)]
+pub struct CrossVenueMonitor {
+    /// Symbols with an open, already reported spread
+    reported: HashSet<String>,
+}
+
+impl CrossVenueMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spreads not reported yet.
+    ///
+    /// Symbols missing from `spreads` have closed and are re-armed.
+    pub fn new_opportunities(&mut self, spreads: &[FundingSpread]) -> Vec<FundingSpread> {
+        let open: HashSet<&str> = spreads.iter().map(|s| s.symbol.as_str()).collect();
+        self.reported
+            .retain(|symbol| open.contains(symbol.as_str()));
+
+        spreads
+            .iter()
+            .filter(|spread| self.reported.insert(spread.symbol.clone()))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::FundingSchedule;
+    use rust_decimal_macros::dec;
+
+    fn binance(symbol: &str, rate: Decimal) -> FundingRate {
+        FundingRate {
+            symbol: symbol.to_string(),
+            funding_rate: rate,
+            funding_time: 0,
+            mark_price: None,
+        }
+    }
+
+    fn config() -> CrossVenueConfig {
+        CrossVenueConfig {
+            enabled: true,
+            min_spread: dec!(0.0005),
+            max_notional_per_pair: dec!(1000),
+            max_total_notional: dec!(1500),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spreads_normalized_sorted_and_capped() {
+        let hyperliquid = HashMap::from([
+            ("BTC".to_string(), dec!(0.0000125)),
+            ("ETH".to_string(), dec!(0.0002)),
+            ("SOL".to_string(), dec!(0.00002)),
+        ]);
+        let spreads = funding_spreads(
+            &[
+                binance("BTCUSDT", dec!(0.001)),
+                binance("ETHUSDT", dec!(0.0001)),
+                binance("SOLUSDT", dec!(0.0003)),
+                binance("DOGEUSDT", dec!(0.002)),
+            ],
+            &hyperliquid,
+            &FundingCalendar::new(),
+            &config(),
+        );
+
+        // SOL (0.0003 vs 0.00016) is below the threshold; DOGE is not listed on Hyperliquid
+        assert_eq!(spreads.len(), 2);
+
+        // ETH: Hyperliquid 0.0016 per 8h vs Binance 0.0001
+        assert_eq!(spreads[0].symbol, "ETHUSDT");
+        assert_eq!(spreads[0].hyperliquid_rate, dec!(0.0016));
+        assert_eq!(spreads[0].spread, dec!(0.0015));
+        assert_eq!(spreads[0].short_venue, Venue::Hyperliquid);
+        assert_eq!(spreads[0].long_venue(), Venue::Binance);
+        assert_eq!(spreads[0].apr, dec!(1.6425));
+        assert_eq!(spreads[0].suggested_notional, dec!(1000));
+
+        // BTC: Binance 0.001 vs Hyperliquid 0.0001; only $500 left under the total cap
+        assert_eq!(spreads[1].short_venue, Venue::Binance);
+        assert_eq!(spreads[1].spread, dec!(0.0009));
+        assert_eq!(spreads[1].suggested_notional, dec!(500));
+    }
+
+    #[test]
+    fn test_binance_rate_scaled_by_symbol_interval() {
+        let mut calendar = FundingCalendar::new();
+        calendar.set_schedule(
+            Venue::Binance,
+            "SOLUSDT",
+            FundingSchedule::every_hours(4).unwrap(),
+        );
+        let hyperliquid = HashMap::from([("SOL".to_string(), dec!(0.00001))]);
+
+        // 0.0003 per 4h is 0.0006 per 8h against Hyperliquid's 0.00008
+        let spreads = funding_spreads(
+            &[binance("SOLUSDT", dec!(0.0003))],
+            &hyperliquid,
+            &calendar,
+            &config(),
+        );
+        assert_eq!(spreads.len(), 1);
+        assert_eq!(spreads[0].binance_rate, dec!(0.0006));
+        assert_eq!(spreads[0].spread, dec!(0.00052));
+    }
+
+    #[test]
+    fn test_monitor_reports_once_until_closed() {
+        let hyperliquid = HashMap::from([("BTC".to_string(), dec!(0.0000125))]);
+        let mut monitor = CrossVenueMonitor::new();
+
+        let wide = funding_spreads(
+            &[binance("BTCUSDT", dec!(0.001))],
+            &hyperliquid,
+            &FundingCalendar::new(),
+            &config(),
+        );
+        assert_eq!(monitor.new_opportunities(&wide).len(), 1);
+        assert!(monitor.new_opportunities(&wide).is_empty());
+
+        // Spread closes, then reopens
+        assert!(monitor.new_opportunities(&[]).is_empty());
+        assert_eq!(monitor.new_opportunities(&wide).len(), 1);
+    }
+
+    #[test]
+    fn test_entry_sizing_and_exits() {
+        let hyperliquid = HashMap::from([
+            ("BTC".to_string(), dec!(0.0000125)),
+            ("ETH".to_string(), dec!(0.0002)),
+        ]);
+        let spreads = funding_spreads(
+            &[
+                binance("BTCUSDT", dec!(0.001)),
+                binance("ETHUSDT", dec!(0.0001)),
+            ],
+            &hyperliquid,
+            &FundingCalendar::new(),
+            &config(),
+        );
+        let held = vec![SpreadPosition::from_hyperliquid(
+            "ETH",
+            dec!(-0.3),
+            dec!(900),
+        )];
+        assert_eq!(held[0].symbol, "ETHUSDT");
+        assert_eq!(held[0].short_venue, Venue::Hyperliquid);
+
+        // ETH is held; BTC gets what is left under the total cap
+        assert_eq!(entry_notional(&spreads[0], &held, &config()), Decimal::ZERO);
+        assert_eq!(entry_notional(&spreads[1], &held, &config()), dec!(600));
+
+        // ETH still earns 0.0015 per 8h short on Hyperliquid
+        let exits = |binance_eth: Decimal| {
+            spread_exits(
+                &held,
+                &[binance("ETHUSDT", binance_eth)],
+                &hyperliquid,
+                &FundingCalendar::new(),
+                dec!(0.0001),
+            )
+        };
+        assert!(exits(dec!(0.0001)).is_empty());
+        // Narrowed to 0.00005, then flipped
+        assert_eq!(exits(dec!(0.00155)), vec!["ETHUSDT".to_string()]);
+        assert_eq!(exits(dec!(0.002)), vec!["ETHUSDT".to_string()]);
+        // No Binance rate: kept
+        assert!(spread_exits(
+            &held,
+            &[],
+            &hyperliquid,
+            &FundingCalendar::new(),
+            dec!(0.0001)
+        )
+        .is_empty());
+    }
+}
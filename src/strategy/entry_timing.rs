@@ -0,0 +1,416 @@
+//! Micro-timing of entries inside the entry window.
+//!
+//! Executing the futures leg the moment an entry becomes ready trades against
+//! whatever flow is in the book at that instant. When the top of the futures book
+//! leans towards a better price later (bids outweighing asks before a short, asks
+//! outweighing bids before a long), or the mid is still moving that way, the
+//! taker would be selling into a rally or buying into a sell-off. Such entries are
+//! held for up to `max_wait_minutes`, then executed, or dropped until the next
+//! funding period when the book is still heavily one-sided. Each timed entry
+//! keeps the futures mid from when it first became ready, so its outcome is
+//! measured against naive immediate execution.
+
+use crate::config::EntryTimingConfig;
+use crate::exchange::{BookTicker, OrderSide};
+use crate::persistence::PersistedEntryTiming;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{HashMap, HashSet};
+
+const BPS: Decimal = dec!(10000);
+
+/// Top-of-book imbalance in [-1, 1]; positive when bids outweigh asks.
+pub fn book_imbalance(ticker: &BookTicker) -> Decimal {
+    let depth = ticker.bid_qty + ticker.ask_qty;
+    if depth <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (ticker.bid_qty - ticker.ask_qty) / depth
+}
+
+/// Mid improvement of `decision_mid` over `baseline_mid` for a futures leg on
+/// `side`, in bps (positive = better than executing at the baseline).
+pub fn timing_gain_bps(side: OrderSide, baseline_mid: Decimal, decision_mid: Decimal) -> Decimal {
+    if baseline_mid <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let gain = (decision_mid - baseline_mid) / baseline_mid * BPS;
+    match side {
+        OrderSide::Sell => gain,
+        OrderSide::Buy => -gain,
+    }
+}
+
+/// An entry released (or dropped) by the timer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedEntry {
+    pub symbol: String,
+    /// Futures leg side
+    pub side: OrderSide,
+    /// Futures mid when the entry first became ready
+    pub baseline_mid: Decimal,
+    /// Futures mid at the decision
+    pub decision_mid: Decimal,
+    pub waited: Duration,
+    /// Released by the deadline rather than by the book
+    pub forced: bool,
+}
+
+impl TimedEntry {
+    /// Mid improvement over immediate execution, in bps.
+    pub fn gain_bps(&self) -> Decimal {
+        timing_gain_bps(self.side, self.baseline_mid, self.decision_mid)
+    }
+
+    pub fn to_persisted(&self, outcome: &str, now: DateTime<Utc>) -> PersistedEntryTiming {
+        PersistedEntryTiming {
+            timestamp: now,
+            symbol: self.symbol.clone(),
+            side: match self.side {
+                OrderSide::Buy => "BUY",
+                OrderSide::Sell => "SELL",
+            }
+            .to_string(),
+            outcome: outcome.to_string(),
+            waited_secs: self.waited.num_seconds(),
+            baseline_mid: self.baseline_mid,
+            decision_mid: self.decision_mid,
+        }
+    }
+}
+
+/// What to do with a ready entry this cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimingDecision {
+    Execute(TimedEntry),
+    /// Hold the entry for another cycle
+    Wait {
+        reason: String,
+    },
+    /// Drop the entry; it may be retried from `until`
+    Abort {
+        entry: TimedEntry,
+        imbalance: Decimal,
+        until: DateTime<Utc>,
+    },
+    /// Dropped earlier and held out until `until`
+    Aborted {
+        until: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct PendingEntry {
+    since: DateTime<Utc>,
+    baseline_mid: Decimal,
+    last_mid: Decimal,
+}
+
+/// Holds ready entries until the futures book stops leaning against them.
+#[derive(Debug, Clone)]
+pub struct EntryTimer {
+    config: EntryTimingConfig,
+    pending: HashMap<String, PendingEntry>,
+    /// Aborted symbols and when they may be retried
+    aborted: HashMap<String, DateTime<Utc>>,
+}
+
+impl EntryTimer {
+    pub fn new(config: EntryTimingConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+            aborted: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Forget held entries that are no longer ready.
+    pub fn retain_ready(&mut self, ready: &HashSet<&str>) {
+        self.pending
+            .retain(|symbol, _| ready.contains(symbol.as_str()));
+    }
+
+    /// Decide on a ready entry for `symbol` whose futures leg trades on `side`.
+    ///
+    /// An aborted entry is held out until `retry_after` (normally the next
+    /// funding time).
+    pub fn decide(
+        &mut self,
+        symbol: &str,
+        side: OrderSide,
+        ticker: &BookTicker,
+        now: DateTime<Utc>,
+        retry_after: DateTime<Utc>,
+    ) -> TimingDecision {
+        if let Some(&until) = self.aborted.get(symbol) {
+            if now < until {
+                return TimingDecision::Aborted { until };
+            }
+            self.aborted.remove(symbol);
+        }
+
+        let mid = (ticker.bid_price + ticker.ask_price) / Decimal::TWO;
+        let immediate = TimedEntry {
+            symbol: symbol.to_string(),
+            side,
+            baseline_mid: mid,
+            decision_mid: mid,
+            waited: Duration::zero(),
+            forced: false,
+        };
+        if !self.config.enabled || mid <= Decimal::ZERO {
+            return TimingDecision::Execute(immediate);
+        }
+
+        // Positive when the book or the mid favors executing later
+        let (imbalance, momentum_bps) = {
+            let pending = self
+                .pending
+                .entry(symbol.to_string())
+                .or_insert(PendingEntry {
+                    since: now,
+                    baseline_mid: mid,
+                    last_mid: mid,
+                });
+            let momentum = timing_gain_bps(side, pending.last_mid, mid);
+            pending.last_mid = mid;
+            let imbalance = match side {
+                OrderSide::Sell => book_imbalance(ticker),
+                OrderSide::Buy => -book_imbalance(ticker),
+            };
+            (imbalance, momentum)
+        };
+        let pending = &self.pending[symbol];
+        let entry = TimedEntry {
+            baseline_mid: pending.baseline_mid,
+            waited: now - pending.since,
+            ..immediate
+        };
+
+        if entry.waited >= Duration::minutes(self.config.max_wait_minutes as i64) {
+            self.pending.remove(symbol);
+            if imbalance >= self.config.abort_imbalance {
+                self.aborted.insert(symbol.to_string(), retry_after);
+                return TimingDecision::Abort {
+                    entry,
+                    imbalance,
+                    until: retry_after,
+                };
+            }
+            return TimingDecision::Execute(TimedEntry {
+                forced: true,
+                ..entry
+            });
+        }
+        if imbalance >= self.config.wait_imbalance {
+            return TimingDecision::Wait {
+                reason: format!("book {:.0}% one-sided", imbalance * dec!(100)),
+            };
+        }
+        if momentum_bps > Decimal::ZERO && momentum_bps >= self.config.momentum_bps {
+            return TimingDecision::Wait {
+                reason: format!("mid moved {:.1} bps in its favor", momentum_bps),
+            };
+        }
+        self.pending.remove(symbol);
+        TimingDecision::Execute(entry)
+    }
+}
+
+/// Timed entries against immediate execution.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryTimingSummary {
+    pub executed: usize,
+    /// Executed entries that were held at least one cycle
+    pub held: usize,
+    pub aborted: usize,
+    /// Mean mid improvement of executed entries over immediate execution
+    pub avg_gain_bps: Decimal,
+    /// Mean hold of executed entries
+    pub avg_wait_secs: i64,
+}
+
+impl EntryTimingSummary {
+    pub fn from_timings(timings: &[PersistedEntryTiming]) -> Self {
+        let executed: Vec<&PersistedEntryTiming> =
+            timings.iter().filter(|t| t.outcome == "executed").collect();
+        let mut summary = Self {
+            executed: executed.len(),
+            held: executed.iter().filter(|t| t.waited_secs > 0).count(),
+            aborted: timings.iter().filter(|t| t.outcome == "aborted").count(),
+            ..Default::default()
+        };
+        if !executed.is_empty() {
+            let gains: Decimal = executed
+                .iter()
+                .map(|t| {
+                    let side = if t.side == "BUY" {
+                        OrderSide::Buy
+                    } else {
+                        OrderSide::Sell
+                    };
+                    timing_gain_bps(side, t.baseline_mid, t.decision_mid)
+                })
+                .sum();
+            summary.avg_gain_bps = gains / Decimal::from(executed.len());
+            summary.avg_wait_secs =
+                executed.iter().map(|t| t.waited_secs).sum::<i64>() / executed.len() as i64;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(bid: Decimal, bid_qty: Decimal, ask_qty: Decimal) -> BookTicker {
+        BookTicker {
+            symbol: "ALTUSDT".to_string(),
+            bid_price: bid,
+            bid_qty,
+            ask_price: bid + dec!(0.02),
+            ask_qty,
+        }
+    }
+
+    fn timer() -> EntryTimer {
+        EntryTimer::new(EntryTimingConfig {
+            enabled: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_holds_short_into_bid_pressure_then_releases() {
+        let mut timer = timer();
+        let now = Utc::now();
+        let retry = now + Duration::hours(4);
+
+        // Bids outweigh asks: selling now would sell into buying
+        let decision = timer.decide(
+            "ALTUSDT",
+            OrderSide::Sell,
+            &ticker(dec!(9.99), dec!(800), dec!(200)),
+            now,
+            retry,
+        );
+        assert!(matches!(decision, TimingDecision::Wait { .. }));
+
+        // Mid still rising with a balanced book: keep holding
+        let decision = timer.decide(
+            "ALTUSDT",
+            OrderSide::Sell,
+            &ticker(dec!(10.04), dec!(500), dec!(500)),
+            now + Duration::minutes(1),
+            retry,
+        );
+        assert!(matches!(decision, TimingDecision::Wait { .. }));
+
+        // Pressure gone: executed 50 bps above the first mid
+        let TimingDecision::Execute(entry) = timer.decide(
+            "ALTUSDT",
+            OrderSide::Sell,
+            &ticker(dec!(10.04), dec!(400), dec!(600)),
+            now + Duration::minutes(2),
+            retry,
+        ) else {
+            panic!("expected execution");
+        };
+        assert!(!entry.forced);
+        assert_eq!(entry.waited, Duration::minutes(2));
+        assert_eq!(entry.baseline_mid, dec!(10.00));
+        assert_eq!(entry.gain_bps(), dec!(50));
+        // A long at the same prices would have lost as much
+        assert_eq!(
+            timing_gain_bps(OrderSide::Buy, entry.baseline_mid, entry.decision_mid),
+            dec!(-50)
+        );
+    }
+
+    #[test]
+    fn test_deadline_forces_or_aborts() {
+        let mut timer = timer();
+        let now = Utc::now();
+        let retry = now + Duration::hours(4);
+        let asks_heavy = ticker(dec!(9.99), dec!(100), dec!(900));
+
+        // A long facing heavy asks waits, then aborts at the deadline
+        assert!(matches!(
+            timer.decide("ALTUSDT", OrderSide::Buy, &asks_heavy, now, retry),
+            TimingDecision::Wait { .. }
+        ));
+        let decision = timer.decide(
+            "ALTUSDT",
+            OrderSide::Buy,
+            &asks_heavy,
+            now + Duration::minutes(10),
+            retry,
+        );
+        assert!(matches!(decision, TimingDecision::Abort { until, .. } if until == retry));
+        assert!(matches!(
+            timer.decide(
+                "ALTUSDT",
+                OrderSide::Buy,
+                &asks_heavy,
+                now + Duration::minutes(11),
+                retry
+            ),
+            TimingDecision::Aborted { .. }
+        ));
+
+        // Moderate pressure at the deadline executes anyway
+        let moderate = ticker(dec!(9.99), dec!(300), dec!(700));
+        timer.decide("BTCUSDT", OrderSide::Buy, &moderate, now, retry);
+        let decision = timer.decide(
+            "BTCUSDT",
+            OrderSide::Buy,
+            &moderate,
+            now + Duration::minutes(10),
+            retry,
+        );
+        assert!(matches!(
+            decision,
+            TimingDecision::Execute(TimedEntry { forced: true, .. })
+        ));
+
+        // Disabled: always immediate
+        let mut timer = EntryTimer::new(EntryTimingConfig::default());
+        assert!(matches!(
+            timer.decide("ALTUSDT", OrderSide::Buy, &asks_heavy, now, retry),
+            TimingDecision::Execute(TimedEntry { forced: false, .. })
+        ));
+    }
+
+    #[test]
+    fn test_summary_against_immediate() {
+        let now = Utc::now();
+        let timing = |side: &str, outcome: &str, waited_secs: i64, decision_mid: Decimal| {
+            PersistedEntryTiming {
+                timestamp: now,
+                symbol: "ALTUSDT".to_string(),
+                side: side.to_string(),
+                outcome: outcome.to_string(),
+                waited_secs,
+                baseline_mid: dec!(100),
+                decision_mid,
+            }
+        };
+        let summary = EntryTimingSummary::from_timings(&[
+            timing("SELL", "executed", 120, dec!(100.2)),
+            timing("BUY", "executed", 0, dec!(100)),
+            timing("BUY", "executed", 60, dec!(99.9)),
+            timing("SELL", "aborted", 600, dec!(101)),
+        ]);
+        assert_eq!(summary.executed, 3);
+        assert_eq!(summary.held, 2);
+        assert_eq!(summary.aborted, 1);
+        assert_eq!(summary.avg_gain_bps, dec!(10));
+        assert_eq!(summary.avg_wait_secs, 60);
+    }
+}
@@ -0,0 +1,395 @@
+//! Execution quality: implementation shortfall per entry and exit.
+//!
+//! Each order leg is compared with the decision price, the price the allocation
+//! was sized at. The gap, signed so that paying up is positive, is the
+//! implementation shortfall: spread crossing, slippage and drift while orders
+//! wait. It is reported per execution in basis points of notional, aggregated by
+//! ISO week, and the recent entry shortfall is added to the taker fee in the entry
+//! cost estimate.
+
+use crate::exchange::{FeeSchedule, OrderResponse, OrderSide};
+use crate::persistence::PersistedExecutionFill;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Estimated taker fee per leg as a fraction of notional.
+pub const TAKER_FEE_RATE: Decimal = dec!(0.0004);
+
+/// Weeks of entries the cost model is fitted on.
+pub const COST_MODEL_WEEKS: i64 = 4;
+
+const BPS: Decimal = dec!(10000);
+
+/// Whether an execution opened or closed exposure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ExecutionKind {
+    Entry,
+    Exit,
+}
+
+impl ExecutionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionKind::Entry => "entry",
+            ExecutionKind::Exit => "exit",
+        }
+    }
+}
+
+/// Fill record for one order leg.
+///
+/// Returns `None` when nothing was executed or the exchange reported no average
+/// price.
+pub fn leg_fill(
+    execution_id: &str,
+    kind: ExecutionKind,
+    leg: &str,
+    decision_price: Decimal,
+    order: &OrderResponse,
+) -> Option<PersistedExecutionFill> {
+    if order.executed_qty <= Decimal::ZERO || order.avg_price <= Decimal::ZERO {
+        return None;
+    }
+    Some(PersistedExecutionFill {
+        timestamp: Utc::now(),
+        execution_id: execution_id.to_string(),
+        symbol: order.symbol.clone(),
+        kind: kind.as_str().to_string(),
+        leg: leg.to_string(),
+        side: match order.side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+        .to_string(),
+        quantity: order.executed_qty,
+        decision_price,
+        fill_price: order.avg_price,
+    })
+}
+
+/// Shortfall of one leg in USD (positive = worse than the decision price).
+pub fn leg_shortfall(fill: &PersistedExecutionFill) -> Decimal {
+    let diff = (fill.fill_price - fill.decision_price) * fill.quantity;
+    if fill.side == "BUY" {
+        diff
+    } else {
+        -diff
+    }
+}
+
+/// Shortfall of one entry or exit across its legs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionShortfall {
+    pub execution_id: String,
+    pub symbol: String,
+    /// "entry" or "exit"
+    pub kind: String,
+    pub timestamp: DateTime<Utc>,
+    /// Notional of the larger leg at the decision price
+    pub notional: Decimal,
+    /// Summed shortfall of all legs in USD
+    pub shortfall_usd: Decimal,
+}
+
+impl ExecutionShortfall {
+    /// Group leg fills by execution, in order of first fill.
+    pub fn from_fills(fills: &[PersistedExecutionFill]) -> Vec<Self> {
+        let mut executions: Vec<Self> = Vec::new();
+        for fill in fills {
+            let notional = fill.quantity * fill.decision_price;
+            match executions
+                .iter_mut()
+                .find(|e| e.execution_id == fill.execution_id)
+            {
+                Some(execution) => {
+                    execution.notional = execution.notional.max(notional);
+                    execution.shortfall_usd += leg_shortfall(fill);
+                }
+                None => executions.push(Self {
+                    execution_id: fill.execution_id.clone(),
+                    symbol: fill.symbol.clone(),
+                    kind: fill.kind.clone(),
+                    timestamp: fill.timestamp,
+                    notional,
+                    shortfall_usd: leg_shortfall(fill),
+                }),
+            }
+        }
+        executions
+    }
+
+    /// Shortfall in basis points of notional.
+    pub fn shortfall_bps(&self) -> Decimal {
+        bps(self.shortfall_usd, self.notional)
+    }
+}
+
+/// Executions of one ISO week (Monday to Sunday, UTC).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyShortfall {
+    pub week_start: NaiveDate,
+    pub entries: usize,
+    pub exits: usize,
+    pub notional: Decimal,
+    pub shortfall_usd: Decimal,
+    /// Worst single execution in basis points
+    pub worst_bps: Decimal,
+}
+
+impl WeeklyShortfall {
+    /// Notional-weighted shortfall in basis points.
+    pub fn shortfall_bps(&self) -> Decimal {
+        bps(self.shortfall_usd, self.notional)
+    }
+}
+
+/// Per-execution and weekly implementation shortfall.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecutionQualityReport {
+    /// Executions in order of first fill
+    pub executions: Vec<ExecutionShortfall>,
+    /// Weeks with executions, oldest first
+    pub weeks: Vec<WeeklyShortfall>,
+}
+
+impl ExecutionQualityReport {
+    pub fn build(fills: &[PersistedExecutionFill]) -> Self {
+        let executions = ExecutionShortfall::from_fills(fills);
+        let mut weeks: BTreeMap<NaiveDate, WeeklyShortfall> = BTreeMap::new();
+        for execution in &executions {
+            let date = execution.timestamp.date_naive();
+            let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let week = weeks.entry(week_start).or_insert(WeeklyShortfall {
+                week_start,
+                entries: 0,
+                exits: 0,
+                notional: Decimal::ZERO,
+                shortfall_usd: Decimal::ZERO,
+                worst_bps: Decimal::MIN,
+            });
+            if execution.kind == ExecutionKind::Entry.as_str() {
+                week.entries += 1;
+            } else {
+                week.exits += 1;
+            }
+            week.notional += execution.notional;
+            week.shortfall_usd += execution.shortfall_usd;
+            week.worst_bps = week.worst_bps.max(execution.shortfall_bps());
+        }
+
+        Self {
+            executions,
+            weeks: weeks.into_values().collect(),
+        }
+    }
+
+    /// The `limit` executions with the highest shortfall in basis points.
+    pub fn worst(&self, limit: usize) -> Vec<&ExecutionShortfall> {
+        let mut executions: Vec<&ExecutionShortfall> = self.executions.iter().collect();
+        executions.sort_by_key(|e| Reverse(e.shortfall_bps()));
+        executions.truncate(limit);
+        executions
+    }
+}
+
+/// Entry cost estimate: entry fee plus the observed entry shortfall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryCostModel {
+    /// Average entry shortfall as a fraction of notional (never negative)
+    shortfall_rate: Decimal,
+    fees: FeeSchedule,
+    /// Whether entries rest on the book and pay the maker rate
+    maker_entries: bool,
+}
+
+impl Default for EntryCostModel {
+    fn default() -> Self {
+        Self {
+            shortfall_rate: Decimal::ZERO,
+            fees: FeeSchedule {
+                maker_rate: TAKER_FEE_RATE,
+                taker_rate: TAKER_FEE_RATE,
+            },
+            maker_entries: false,
+        }
+    }
+}
+
+impl EntryCostModel {
+    /// Fit on the entries among `executions`.
+    ///
+    /// Favourable fills are not credited: a negative average shortfall counts as zero.
+    pub fn from_executions(executions: &[ExecutionShortfall]) -> Self {
+        let (notional, shortfall) = executions
+            .iter()
+            .filter(|e| e.kind == ExecutionKind::Entry.as_str())
+            .fold((Decimal::ZERO, Decimal::ZERO), |(n, s), e| {
+                (n + e.notional, s + e.shortfall_usd)
+            });
+        let shortfall_rate = if notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            (shortfall / notional).max(Decimal::ZERO)
+        };
+        Self {
+            shortfall_rate,
+            ..Default::default()
+        }
+    }
+
+    /// Charge entries by `fees`: the maker rate when they rest on the book,
+    /// otherwise the taker rate. A rebate lowers the estimate, never below zero.
+    pub fn with_fees(mut self, fees: FeeSchedule, maker_entries: bool) -> Self {
+        self.fees = fees;
+        self.maker_entries = maker_entries;
+        self
+    }
+
+    /// Fee schedule entries are charged by.
+    pub fn fees(&self) -> FeeSchedule {
+        self.fees
+    }
+
+    /// Fee rate entries are charged; negative when maker entries earn a rebate.
+    pub fn fee_rate(&self) -> Decimal {
+        if self.maker_entries {
+            self.fees.maker_rate
+        } else {
+            self.fees.taker_rate
+        }
+    }
+
+    /// Average entry shortfall as a fraction of notional.
+    pub fn shortfall_rate(&self) -> Decimal {
+        self.shortfall_rate
+    }
+
+    /// Fee plus shortfall as a fraction of notional traded, never negative.
+    pub fn cost_rate(&self) -> Decimal {
+        (self.fee_rate() + self.shortfall_rate).max(Decimal::ZERO)
+    }
+
+    /// Estimated cost of entering a position of `notional` USD.
+    pub fn entry_cost(&self, notional: Decimal) -> Decimal {
+        notional * self.cost_rate()
+    }
+}
+
+fn bps(amount: Decimal, notional: Decimal) -> Decimal {
+    if notional.is_zero() {
+        Decimal::ZERO
+    } else {
+        amount / notional * BPS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(
+        execution_id: &str,
+        kind: &str,
+        side: &str,
+        day: &str,
+        decision_price: Decimal,
+        fill_price: Decimal,
+    ) -> PersistedExecutionFill {
+        PersistedExecutionFill {
+            timestamp: DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", day))
+                .unwrap()
+                .with_timezone(&Utc),
+            execution_id: execution_id.to_string(),
+            symbol: "BTCUSDT".to_string(),
+            kind: kind.to_string(),
+            leg: "futures".to_string(),
+            side: side.to_string(),
+            quantity: dec!(1),
+            decision_price,
+            fill_price,
+        }
+    }
+
+    #[test]
+    fn test_shortfall_per_execution_and_week() {
+        let report = ExecutionQualityReport::build(&[
+            // Short futures sold 5 below, spot bought 3 above: 8 on 1000 = 80 bps
+            fill("a", "entry", "SELL", "2024-03-04", dec!(1000), dec!(995)),
+            fill("a", "entry", "BUY", "2024-03-04", dec!(1000), dec!(1003)),
+            // Exit bought back below the decision price: favourable
+            fill("b", "exit", "BUY", "2024-03-10", dec!(1000), dec!(999)),
+            // Following Monday
+            fill("c", "entry", "SELL", "2024-03-11", dec!(2000), dec!(1998)),
+        ]);
+
+        assert_eq!(report.executions.len(), 3);
+        assert_eq!(report.executions[0].notional, dec!(1000));
+        assert_eq!(report.executions[0].shortfall_usd, dec!(8));
+        assert_eq!(report.executions[0].shortfall_bps(), dec!(80));
+        assert_eq!(report.executions[1].shortfall_bps(), dec!(-10));
+
+        assert_eq!(report.weeks.len(), 2);
+        let week = &report.weeks[0];
+        assert_eq!(
+            week.week_start,
+            NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+        );
+        assert_eq!((week.entries, week.exits), (1, 1));
+        assert_eq!(week.shortfall_usd, dec!(7));
+        assert_eq!(week.shortfall_bps(), dec!(35));
+        assert_eq!(week.worst_bps, dec!(80));
+        assert_eq!(report.weeks[1].shortfall_bps(), dec!(10));
+
+        assert_eq!(report.worst(1)[0].execution_id, "a");
+    }
+
+    #[test]
+    fn test_entry_cost_model() {
+        assert_eq!(EntryCostModel::default().entry_cost(dec!(1000)), dec!(0.4));
+
+        let executions = ExecutionShortfall::from_fills(&[
+            fill("a", "entry", "SELL", "2024-03-04", dec!(1000), dec!(998)),
+            fill("b", "entry", "BUY", "2024-03-05", dec!(1000), dec!(1000)),
+            fill("c", "exit", "BUY", "2024-03-06", dec!(1000), dec!(1050)),
+        ]);
+        // Exits are ignored: 2 on 2000 of entries
+        let model = EntryCostModel::from_executions(&executions);
+        assert_eq!(model.shortfall_rate(), dec!(0.001));
+        assert_eq!(model.entry_cost(dec!(1000)), dec!(1.4));
+
+        // Favourable entries don't lower the cost below fees
+        let favourable = ExecutionShortfall::from_fills(&[fill(
+            "d",
+            "entry",
+            "BUY",
+            "2024-03-04",
+            dec!(1000),
+            dec!(990),
+        )]);
+        assert_eq!(
+            EntryCostModel::from_executions(&favourable).shortfall_rate(),
+            Decimal::ZERO
+        );
+
+        // Maker entries at a rebate tier: -0.005% + 0.1% shortfall
+        let rebate = FeeSchedule {
+            maker_rate: dec!(-0.00005),
+            taker_rate: dec!(0.0004),
+        };
+        assert_eq!(
+            model.with_fees(rebate, true).entry_cost(dec!(1000)),
+            dec!(0.95)
+        );
+        assert_eq!(model.with_fees(rebate, false).fee_rate(), dec!(0.0004));
+        assert_eq!(
+            EntryCostModel::default()
+                .with_fees(rebate, true)
+                .entry_cost(dec!(1000)),
+            Decimal::ZERO
+        );
+    }
+}
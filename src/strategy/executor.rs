@@ -1,19 +1,178 @@
 //! Order execution and position management.
 
-use crate::config::ExecutionConfig;
+use crate::config::{ExecutionConfig, ExistingHoldingsConfig, NotionalCapsConfig};
 use crate::exchange::{
-    BinanceClient, MarginOrder, MarginType, NewOrder, OrderResponse, OrderSide, OrderStatus,
-    OrderType, SideEffectType, TimeInForce,
+    error_class, ExchangeClient, HyperliquidClient, HyperliquidFill, MarginOrder, MarginType,
+    NewOrder, OrderLookup, OrderResponse, OrderSide, OrderStatus, OrderType, OrderUpdate,
+    SideEffectType, TimeInForce,
 };
+use crate::persistence::{InstanceLock, PersistedOrderIntent, PersistenceManager};
 use crate::strategy::allocator::{PositionAllocation, PositionReduction};
-use anyhow::{anyhow, Result};
-use rust_decimal::Decimal;
+use crate::strategy::FlashRelease;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use rust_decimal::{Decimal, RoundingStrategy};
 use rust_decimal_macros::dec;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 use std::collections::HashMap;
 
+/// Attribution parts of a client order ID sent by this bot.
+///
+/// Layout: `{strategy tag}{instance}-{position}-{action}-{order}`, e.g.
+/// `fffa1b2c3-lq2x9k1a-4-5`. Every leg of a position, from entry to exit,
+/// carries the same position ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientOrderTag<'a> {
+    /// Bot instance that sent the order
+    pub instance_id: &'a str,
+    /// Position the order belongs to
+    pub position_id: &'a str,
+}
+
+impl<'a> ClientOrderTag<'a> {
+    /// Parse a client order ID sent under `strategy_tag`.
+    ///
+    /// Returns `None` for orders placed by hand, by other software, or by a bot
+    /// version that did not tag its orders.
+    pub fn parse(strategy_tag: &str, client_order_id: &'a str) -> Option<Self> {
+        let mut parts = client_order_id.strip_prefix(strategy_tag)?.split('-');
+        let (instance_id, position_id) = (parts.next()?, parts.next()?);
+        let valid = instance_id.len() == InstanceLock::ID_LEN
+            && instance_id.chars().all(|c| c.is_ascii_hexdigit())
+            && !position_id.is_empty()
+            && parts.count() == 2;
+        valid.then_some(Self {
+            instance_id,
+            position_id,
+        })
+    }
+}
+
+/// Orders sent under `strategy_tag` by a bot instance other than the `known` ones.
+pub fn foreign_instance_orders<'a>(
+    orders: &'a [OrderLookup],
+    strategy_tag: &str,
+    known: &[&str],
+) -> Vec<&'a OrderLookup> {
+    orders
+        .iter()
+        .filter(|order| {
+            ClientOrderTag::parse(strategy_tag, &order.client_order_id)
+                .is_some_and(|tag| !known.contains(&tag.instance_id))
+        })
+        .collect()
+}
+
+/// New position ID: the entry time in milliseconds, base 36 (8 characters).
+fn new_position_id(now: DateTime<Utc>) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut millis = now.timestamp_millis().max(0) as u64;
+    let mut id = Vec::new();
+    loop {
+        id.push(DIGITS[(millis % 36) as usize]);
+        millis /= 36;
+        if millis == 0 {
+            break;
+        }
+    }
+    id.reverse();
+    String::from_utf8(id).unwrap_or_default()
+}
+
+/// Futures order of this bot; limit orders are post-only.
+fn futures_order(
+    symbol: &str,
+    side: OrderSide,
+    order_type: OrderType,
+    quantity: Decimal,
+    price: Option<Decimal>,
+    client_order_id: Option<String>,
+) -> NewOrder {
+    NewOrder {
+        symbol: symbol.to_string(),
+        side,
+        position_side: None,
+        order_type,
+        quantity: Some(quantity),
+        price,
+        time_in_force: (order_type == OrderType::Limit).then_some(TimeInForce::Gtx),
+        reduce_only: None,
+        new_client_order_id: client_order_id,
+    }
+}
+
+/// Response of an order completed from its lookup.
+///
+/// An order that executed at a price the lookup doesn't give is unsettled.
+fn looked_up_response(
+    found: OrderLookup,
+    order_type: OrderType,
+    quantity: Option<Decimal>,
+    price: Option<Decimal>,
+    time_in_force: Option<TimeInForce>,
+) -> Result<OrderResponse> {
+    let avg_price = match found.fill_price() {
+        Some(avg_price) => avg_price,
+        None if found.executed_qty.is_zero() => Decimal::ZERO,
+        None => {
+            return Err(anyhow!(UnsettledOrder {
+                symbol: found.symbol.clone(),
+                client_order_id: Some(found.client_order_id.clone()),
+                reason: format!(
+                    "{} order {} filled {} at a price its lookup doesn't give",
+                    found.symbol, found.client_order_id, found.executed_qty
+                ),
+            }))
+        }
+    };
+    Ok(OrderResponse {
+        order_id: found.order_id,
+        symbol: found.symbol,
+        status: found.status,
+        client_order_id: found.client_order_id,
+        price: price.unwrap_or_default(),
+        avg_price,
+        orig_qty: quantity.unwrap_or(found.executed_qty),
+        executed_qty: found.executed_qty,
+        side: found.side,
+        order_type,
+        time_in_force,
+        update_time: found.time,
+    })
+}
+
+/// Order whose request failed and whose fate the venue didn't settle: it may
+/// have executed, so it must be neither sent again nor taken as refused.
+#[derive(Debug, Clone)]
+pub struct UnsettledOrder {
+    pub symbol: String,
+    /// Client order ID it was sent under, if any
+    pub client_order_id: Option<String>,
+    /// Why it couldn't be settled
+    pub reason: String,
+}
+
+impl UnsettledOrder {
+    /// The unsettled order behind `error`, if any.
+    pub fn find(error: &anyhow::Error) -> Option<&Self> {
+        error.downcast_ref()
+    }
+}
+
+impl fmt::Display for UnsettledOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+impl std::error::Error for UnsettledOrder {}
+
 /// Pre-entry margin validation context.
 /// Used to validate margin safety before opening new positions.
 #[derive(Debug, Clone)]
@@ -24,6 +183,8 @@ pub struct MarginContext {
     pub margin_balance: Decimal,
     /// Total position notional value (USDT)
     pub total_position_value: Decimal,
+    /// Position notional per futures symbol (USDT), for group notional caps
+    pub position_notionals: HashMap<String, Decimal>,
     /// Minimum margin ratio to maintain (e.g., 2.0 = 200%)
     pub min_margin_ratio: Decimal,
 }
@@ -66,10 +227,326 @@ impl MarginContext {
     }
 }
 
+/// Wallet an entry leg draws its margin from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundsWallet {
+    /// USDⓈ-M futures wallet (futures leg)
+    Futures,
+    /// Cross margin account (spot leg)
+    CrossMargin,
+    /// Unified trading account (both legs)
+    Unified,
+}
+
+impl std::fmt::Display for FundsWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FundsWallet::Futures => write!(f, "futures"),
+            FundsWallet::CrossMargin => write!(f, "cross margin"),
+            FundsWallet::Unified => write!(f, "unified"),
+        }
+    }
+}
+
+/// A wallet that cannot fund its leg of an entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundsShortfall {
+    pub wallet: FundsWallet,
+    /// Initial margin of the leg plus the wallet's reserve (USDT)
+    pub required: Decimal,
+    /// Funds the wallet has for it (USDT)
+    pub available: Decimal,
+}
+
+/// Balances of the futures wallet and cross margin account, checked separately.
+///
+/// The two wallets do not share collateral: a surplus in one cannot pay for the
+/// other leg, and the exchange only rejects the spot leg after the futures leg has
+/// filled. Each wallet must cover its own leg's initial margin while keeping
+/// `reserve_buffer` of its equity free. A unified account (Bybit) has a single
+/// wallet, which must cover both legs together.
+#[derive(Debug, Clone)]
+pub struct SegregatedFunds {
+    /// Futures available balance (USDT)
+    futures_available: Decimal,
+    /// Futures margin balance (USDT)
+    futures_equity: Decimal,
+    /// Free USDT in the cross margin account, which pays for spot buys
+    margin_free_usdt: Decimal,
+    /// Cross margin net equity (USDT), which collateralizes spot shorts
+    margin_equity: Decimal,
+    /// Cross margin equity already committed to spot shorts (USDT)
+    margin_committed: Decimal,
+    /// Fraction of each wallet's equity kept free
+    reserve_buffer: Decimal,
+    /// Symbol allowed into the reserve, and the fraction released to it
+    released: Option<(String, Decimal)>,
+    /// Both legs draw on the futures balance
+    unified: bool,
+}
+
+impl SegregatedFunds {
+    /// Equity required per unit of borrowed spot sold short (Cross Margin 3x
+    /// lends up to twice the account equity).
+    const SHORT_SPOT_EQUITY_RATE: Decimal = dec!(0.5);
+
+    pub fn new(
+        futures_available: Decimal,
+        futures_equity: Decimal,
+        margin_free_usdt: Decimal,
+        margin_equity: Decimal,
+        reserve_buffer: Decimal,
+    ) -> Self {
+        Self {
+            futures_available,
+            futures_equity,
+            margin_free_usdt,
+            margin_equity,
+            margin_committed: Decimal::ZERO,
+            reserve_buffer,
+            released: None,
+            unified: false,
+        }
+    }
+
+    /// Funds of a unified account, whose `available` margin pays for both legs.
+    pub fn unified(available: Decimal, equity: Decimal, reserve_buffer: Decimal) -> Self {
+        Self {
+            unified: true,
+            ..Self::new(
+                available,
+                equity,
+                Decimal::ZERO,
+                Decimal::ZERO,
+                reserve_buffer,
+            )
+        }
+    }
+
+    /// Let `release`'s symbol use its share of each wallet's reserve.
+    pub fn release_reserve(&mut self, release: &FlashRelease) {
+        self.released = Some((release.symbol.clone(), release.release));
+    }
+
+    /// Initial margin each wallet needs for `allocation`.
+    fn requirements(allocation: &PositionAllocation) -> [(FundsWallet, Decimal); 2] {
+        let notional = allocation.target_size_usdt;
+        let futures = notional / Decimal::from(allocation.leverage.max(1));
+        let spot = if allocation.hedge_with_holdings {
+            // Coins already held form the long leg
+            Decimal::ZERO
+        } else if allocation.funding_rate > Decimal::ZERO {
+            // Spot bought outright with free USDT
+            notional
+        } else {
+            notional * Self::SHORT_SPOT_EQUITY_RATE
+        };
+        [
+            (FundsWallet::Futures, futures),
+            (FundsWallet::CrossMargin, spot),
+        ]
+    }
+
+    /// Initial margin each wallet of the account needs for `allocation`.
+    fn wallet_requirements(&self, allocation: &PositionAllocation) -> Vec<(FundsWallet, Decimal)> {
+        let legs = Self::requirements(allocation);
+        if self.unified {
+            let total = legs.iter().map(|(_, margin)| *margin).sum();
+            vec![(FundsWallet::Unified, total)]
+        } else {
+            legs.to_vec()
+        }
+    }
+
+    /// Funds a wallet can put towards its leg of `allocation`, before the reserve.
+    fn available(&self, wallet: FundsWallet, allocation: &PositionAllocation) -> Decimal {
+        match wallet {
+            FundsWallet::Futures | FundsWallet::Unified => self.futures_available,
+            FundsWallet::CrossMargin => {
+                let equity = self.margin_equity - self.margin_committed;
+                if allocation.funding_rate > Decimal::ZERO {
+                    self.margin_free_usdt.min(equity)
+                } else {
+                    equity
+                }
+            }
+        }
+    }
+
+    /// Part of a wallet's equity kept free for `allocation`.
+    fn reserve(&self, wallet: FundsWallet, allocation: &PositionAllocation) -> Decimal {
+        let buffer = match &self.released {
+            Some((symbol, released)) if *symbol == allocation.symbol => {
+                (self.reserve_buffer - released).max(Decimal::ZERO)
+            }
+            _ => self.reserve_buffer,
+        };
+        match wallet {
+            FundsWallet::Futures | FundsWallet::Unified => self.futures_equity * buffer,
+            FundsWallet::CrossMargin => self.margin_equity * buffer,
+        }
+    }
+
+    /// Wallets that cannot fund their leg of `allocation` (empty if both can).
+    pub fn shortfalls(&self, allocation: &PositionAllocation) -> Vec<FundsShortfall> {
+        self.wallet_requirements(allocation)
+            .into_iter()
+            .filter(|(_, margin)| *margin > Decimal::ZERO)
+            .filter_map(|(wallet, margin)| {
+                let required = margin + self.reserve(wallet, allocation);
+                let available = self.available(wallet, allocation);
+                (available < required).then_some(FundsShortfall {
+                    wallet,
+                    required,
+                    available,
+                })
+            })
+            .collect()
+    }
+
+    /// Deduct an accepted entry so later entries in the same cycle see what is left.
+    pub fn commit(&mut self, allocation: &PositionAllocation) {
+        let [(_, futures), (_, spot)] = Self::requirements(allocation);
+        if self.unified {
+            self.futures_available -= futures + spot;
+            return;
+        }
+        self.futures_available -= futures;
+        if allocation.funding_rate > Decimal::ZERO {
+            self.margin_free_usdt -= spot;
+        } else {
+            self.margin_committed += spot;
+        }
+    }
+}
+
+/// Role of a live order leg, recorded in the order journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderLeg {
+    EntryFutures,
+    EntrySpot,
+    UnwindFutures,
+    ExitFutures,
+    ReduceFutures,
+    ReduceSpot,
+    HaltCloseFutures,
+    /// Binance leg of a cross-venue spread trade
+    SpreadEntryFutures,
+    SpreadExitFutures,
+    /// Short on a COIN-margined perpetual against held coins
+    CoinEntryFutures,
+    CoinExitFutures,
+    /// Entry futures leg hedged by declared spot holdings instead of a spot order
+    HoldingsEntryFutures,
+    /// Opposite perpetual hedging an entry, journaled under the perpetual's symbol
+    EntryPerpHedge,
+    /// Hyperliquid leg of a cross-venue spread trade, journaled under its coin
+    SpreadEntryHyperliquid,
+    SpreadExitHyperliquid,
+}
+
+impl OrderLeg {
+    const ALL: [OrderLeg; 15] = [
+        OrderLeg::EntryFutures,
+        OrderLeg::EntrySpot,
+        OrderLeg::UnwindFutures,
+        OrderLeg::ExitFutures,
+        OrderLeg::ReduceFutures,
+        OrderLeg::ReduceSpot,
+        OrderLeg::HaltCloseFutures,
+        OrderLeg::SpreadEntryFutures,
+        OrderLeg::SpreadExitFutures,
+        OrderLeg::CoinEntryFutures,
+        OrderLeg::CoinExitFutures,
+        OrderLeg::HoldingsEntryFutures,
+        OrderLeg::EntryPerpHedge,
+        OrderLeg::SpreadEntryHyperliquid,
+        OrderLeg::SpreadExitHyperliquid,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderLeg::EntryFutures => "entry_futures",
+            OrderLeg::EntrySpot => "entry_spot",
+            OrderLeg::UnwindFutures => "unwind_futures",
+            OrderLeg::ExitFutures => "exit_futures",
+            OrderLeg::ReduceFutures => "reduce_futures",
+            OrderLeg::ReduceSpot => "reduce_spot",
+            OrderLeg::HaltCloseFutures => "halt_close_futures",
+            OrderLeg::SpreadEntryFutures => "spread_entry_futures",
+            OrderLeg::SpreadExitFutures => "spread_exit_futures",
+            OrderLeg::CoinEntryFutures => "coin_entry_futures",
+            OrderLeg::CoinExitFutures => "coin_exit_futures",
+            OrderLeg::HoldingsEntryFutures => "holdings_entry_futures",
+            OrderLeg::EntryPerpHedge => "entry_perp_hedge",
+            OrderLeg::SpreadEntryHyperliquid => "spread_entry_hyperliquid",
+            OrderLeg::SpreadExitHyperliquid => "spread_exit_hyperliquid",
+        }
+    }
+
+    /// Parse a leg role recorded in the journal.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|leg| leg.as_str() == s)
+    }
+
+    /// Whether the leg is a cross margin (spot) order rather than a futures order.
+    pub fn is_spot(&self) -> bool {
+        matches!(self, OrderLeg::EntrySpot | OrderLeg::ReduceSpot)
+    }
+
+    /// Whether the leg is a COIN-margined futures order.
+    pub fn is_coin(&self) -> bool {
+        matches!(self, OrderLeg::CoinEntryFutures | OrderLeg::CoinExitFutures)
+    }
+
+    /// Whether the leg is an order on Hyperliquid rather than the trading venue.
+    pub fn is_hyperliquid(&self) -> bool {
+        matches!(
+            self,
+            OrderLeg::SpreadEntryHyperliquid | OrderLeg::SpreadExitHyperliquid
+        )
+    }
+
+    /// Whether the leg closes the whole futures position.
+    pub fn closes_position(&self) -> bool {
+        matches!(
+            self,
+            OrderLeg::UnwindFutures
+                | OrderLeg::ExitFutures
+                | OrderLeg::HaltCloseFutures
+                | OrderLeg::SpreadExitFutures
+                | OrderLeg::CoinExitFutures
+                | OrderLeg::SpreadExitHyperliquid
+        )
+    }
+}
+
+/// A journaled order resolved against the exchange at startup.
+#[derive(Debug)]
+pub struct JournalReconciliation {
+    pub intent: PersistedOrderIntent,
+    /// Order found on the exchange, `None` if it never arrived
+    pub order: Option<OrderLookup>,
+}
+
 /// Handles order execution for funding fee farming positions.
 pub struct OrderExecutor {
     config: ExecutionConfig,
     precisions: HashMap<String, u8>,
+    /// Quantity precision per spot symbol, where the venue reports a lot size
+    spot_precisions: HashMap<String, u8>,
+    /// Smallest futures order notional per symbol, as the venue reports it
+    min_notionals: HashMap<String, Decimal>,
+    notional_caps: NotionalCapsConfig,
+    existing_holdings: ExistingHoldingsConfig,
+    /// Write-ahead journal of live orders (None disables journaling)
+    journal: Option<PersistenceManager>,
+    /// Sequence for correlation and client order IDs
+    order_sequence: AtomicU64,
+    /// Start of every client order ID: strategy tag plus instance ID when set
+    order_prefix: String,
+    /// Position ID per futures symbol, shared by every order of the position
+    position_ids: Mutex<HashMap<String, String>>,
 }
 
 /// Result of a position entry attempt.
@@ -82,12 +559,44 @@ pub struct EntryResult {
     pub error: Option<String>,
 }
 
+/// An entry sized and set up, waiting for its futures order.
+#[derive(Debug)]
+struct PendingEntry<'a> {
+    allocation: &'a PositionAllocation,
+    quantity: Decimal,
+    spot_side: OrderSide,
+    futures_side: OrderSide,
+    /// Correlation ID shared by every leg of the entry
+    correlation_id: String,
+    /// Whether the entry opens the symbol's position, rather than adding to it
+    new_position: bool,
+}
+
+impl PendingEntry<'_> {
+    /// Journal role of the entry's futures order.
+    fn futures_leg(&self) -> OrderLeg {
+        if self.allocation.hedge_with_holdings {
+            OrderLeg::HoldingsEntryFutures
+        } else {
+            OrderLeg::EntryFutures
+        }
+    }
+}
+
 impl OrderExecutor {
     /// Create a new order executor.
     pub fn new(config: ExecutionConfig) -> Self {
         Self {
-            config,
             precisions: HashMap::new(),
+            spot_precisions: HashMap::new(),
+            min_notionals: HashMap::new(),
+            notional_caps: NotionalCapsConfig::default(),
+            existing_holdings: ExistingHoldingsConfig::default(),
+            journal: None,
+            order_sequence: AtomicU64::new(0),
+            order_prefix: config.client_order_tag.clone(),
+            position_ids: Mutex::new(HashMap::new()),
+            config,
         }
     }
 
@@ -96,6 +605,124 @@ impl OrderExecutor {
         self.precisions = precisions;
     }
 
+    /// Update spot symbol precisions.
+    pub fn set_spot_precisions(&mut self, precisions: HashMap<String, u8>) {
+        self.spot_precisions = precisions;
+    }
+
+    /// Update the smallest futures order notional per symbol.
+    pub fn set_min_notionals(&mut self, min_notionals: HashMap<String, Decimal>) {
+        self.min_notionals = min_notionals;
+    }
+
+    /// Why `symbol` can't be entered with `quantity` at `price` on this venue, if it can't.
+    ///
+    /// Testnets list fewer symbols than production and enforce different minimum
+    /// notionals, so a symbol missing from the loaded precisions or an order below
+    /// its minimum is refused before anything is placed.
+    fn venue_rejection(&self, symbol: &str, quantity: Decimal, price: Decimal) -> Option<String> {
+        if !self.precisions.is_empty() && !self.precisions.contains_key(symbol) {
+            return Some(format!("{} is not listed on this venue", symbol));
+        }
+        let min_notional = self.min_notionals.get(symbol)?;
+        let notional = quantity * price;
+        (notional < *min_notional).then(|| {
+            format!(
+                "Entry notional {:.2} is below the venue minimum of {}",
+                notional, min_notional
+            )
+        })
+    }
+
+    /// Set absolute per-symbol notional caps checked before entry.
+    pub fn set_notional_caps(&mut self, notional_caps: NotionalCapsConfig) {
+        self.notional_caps = notional_caps;
+    }
+
+    /// Set spot holdings that hedge futures shorts without buying spot.
+    pub fn set_existing_holdings(&mut self, existing_holdings: ExistingHoldingsConfig) {
+        self.existing_holdings = existing_holdings;
+    }
+
+    /// Journal every order intent before it is sent.
+    ///
+    /// Orders are tagged with a client order ID so intents left pending by a
+    /// crash can be looked up on the exchange with `reconcile_journal`.
+    pub fn set_journal(&mut self, journal: PersistenceManager) {
+        self.journal = Some(journal);
+    }
+
+    /// Tag client order IDs with this bot instance's ID.
+    ///
+    /// Lets a starting instance recognise recent orders sent by another instance
+    /// trading the same account.
+    pub fn set_instance_id(&mut self, instance_id: &str) {
+        self.order_prefix = format!("{}{}", self.config.client_order_tag, instance_id);
+    }
+
+    /// Restore the position IDs of positions still open according to the journal.
+    ///
+    /// A symbol's position is open when its latest futures order was an entry or
+    /// a reduction. Returns the number of restored IDs.
+    pub fn restore_position_ids(&self) -> Result<usize> {
+        let Some(journal) = &self.journal else {
+            return Ok(0);
+        };
+
+        let futures_legs: Vec<&str> = OrderLeg::ALL
+            .iter()
+            .filter(|leg| !leg.is_spot() && !leg.is_hyperliquid())
+            .map(|leg| leg.as_str())
+            .collect();
+        let mut ids = self.position_ids.lock().unwrap_or_else(|e| e.into_inner());
+        for intent in journal.get_latest_order_intents(&futures_legs)? {
+            let open = OrderLeg::parse(&intent.leg).is_some_and(|leg| !leg.closes_position());
+            if let (true, Some((position_id, _))) = (open, intent.correlation_id.split_once('-')) {
+                ids.insert(intent.symbol, position_id.to_string());
+            }
+        }
+        Ok(ids.len())
+    }
+
+    /// Whether a position ID is assigned for a futures symbol.
+    fn has_position_id(&self, symbol: &str) -> bool {
+        let ids = self.position_ids.lock().unwrap_or_else(|e| e.into_inner());
+        ids.contains_key(symbol)
+    }
+
+    /// Position ID for a futures symbol, assigning a new one if none is open.
+    fn position_id(&self, symbol: &str) -> String {
+        let mut ids = self.position_ids.lock().unwrap_or_else(|e| e.into_inner());
+        ids.entry(symbol.to_string())
+            .or_insert_with(|| new_position_id(Utc::now()))
+            .clone()
+    }
+
+    /// Forget a symbol's position ID once the position is closed.
+    fn release_position_id(&self, symbol: &str) {
+        let mut ids = self.position_ids.lock().unwrap_or_else(|e| e.into_inner());
+        ids.remove(symbol);
+    }
+
+    /// Futures quantity for an entry, limited to the declared holdings when they
+    /// form the long leg. Rounds down so the short never exceeds the coins held.
+    pub fn entry_quantity(&self, allocation: &PositionAllocation, quantity: Decimal) -> Decimal {
+        if !allocation.hedge_with_holdings {
+            return quantity;
+        }
+
+        let precision = self
+            .precisions
+            .get(&allocation.symbol)
+            .copied()
+            .unwrap_or(3);
+        let held = self
+            .existing_holdings
+            .held_quantity(&allocation.base_asset)
+            .round_dp_with_strategy(precision as u32, RoundingStrategy::ToZero);
+        quantity.min(held)
+    }
+
     /// Execute a delta-neutral entry with pre-entry margin validation.
     ///
     /// This is the preferred entry method for production use. It validates
@@ -103,20 +730,144 @@ impl OrderExecutor {
     ///
     /// # Arguments
     /// * `client` - Binance client for order execution
-    /// * `allocation` - Position allocation to execute
+    /// * `allocation` - Position allocation details
     /// * `current_price` - Current market price for the symbol
     /// * `margin_context` - Current margin state for validation
     ///
     /// # Returns
     /// * `Ok(EntryResult)` - Entry succeeded or failed with details
-    /// * `Err` - Pre-entry validation failed (no orders placed)
+    /// * `Err` - Account setup failed, a naked leg could not be unwound, or the
+    ///   futures order may have executed unseen
     pub async fn enter_position_validated(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         allocation: &PositionAllocation,
         current_price: Decimal,
         margin_context: &MarginContext,
     ) -> Result<EntryResult> {
+        self.enter_positions(client, &[(allocation, current_price)], Some(margin_context))
+            .await
+            .pop()
+            .context("Entry produced no result")?
+    }
+
+    /// Execute a delta-neutral entry (spot + futures hedge).
+    ///
+    /// For positive funding: Long spot + Short futures (we receive funding)
+    /// For negative funding: Short spot (margin borrow) + Long futures (we receive funding)
+    ///
+    /// Note: For production use, prefer `enter_position_validated` which includes
+    /// pre-entry margin validation.
+    pub async fn enter_position(
+        &self,
+        client: &dyn ExchangeClient,
+        allocation: &PositionAllocation,
+        current_price: Decimal,
+    ) -> Result<EntryResult> {
+        self.enter_positions(client, &[(allocation, current_price)], None)
+            .await
+            .pop()
+            .context("Entry produced no result")?
+    }
+
+    /// Execute several delta-neutral entries, sending their futures legs together.
+    ///
+    /// The futures orders of every entry go out in one batch, and the spot hedges
+    /// then follow concurrently, so no hedge waits on the entries before it. A
+    /// futures leg the batch fails is retried on its own, while the hedges of the
+    /// legs that filled go ahead. With a `margin_context`
+    /// each entry is validated before any order is placed, counting the entries
+    /// accepted before it. Returns one result per entry, in order.
+    pub async fn enter_positions(
+        &self,
+        client: &dyn ExchangeClient,
+        entries: &[(&PositionAllocation, Decimal)],
+        margin_context: Option<&MarginContext>,
+    ) -> Vec<Result<EntryResult>> {
+        let mut margin_context = margin_context.cloned();
+        let mut results: Vec<Option<Result<EntryResult>>> = Vec::with_capacity(entries.len());
+        let mut pending = Vec::new();
+        for &(allocation, current_price) in entries {
+            if let Some(rejected) = margin_context
+                .as_ref()
+                .and_then(|ctx| self.entry_precheck(allocation, ctx))
+            {
+                results.push(Some(Ok(rejected)));
+                continue;
+            }
+            match self.prepare_entry(client, allocation, current_price).await {
+                Ok(Ok(entry)) => {
+                    if let Some(ctx) = margin_context.as_mut() {
+                        ctx.total_position_value += allocation.target_size_usdt;
+                        *ctx.position_notionals
+                            .entry(allocation.symbol.clone())
+                            .or_default() += allocation.target_size_usdt;
+                    }
+                    pending.push((results.len(), entry));
+                    results.push(None);
+                }
+                Ok(Err(rejected)) => results.push(Some(Ok(rejected))),
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let batch = self.place_entry_futures_orders(client, &pending).await;
+        let completed = join_all(pending.into_iter().zip(batch).map(
+            |((slot, entry), (order, batch_result))| async move {
+                let futures_result = self
+                    .retry_entry_futures_order(client, &entry, order, batch_result)
+                    .await;
+                let result = self.complete_entry(client, entry, futures_result).await;
+                (slot, result)
+            },
+        ))
+        .await;
+        for (slot, result) in completed {
+            results[slot] = Some(result);
+        }
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(anyhow!("Entry produced no result"))))
+            .collect()
+    }
+
+    /// Notional cap and margin checks of an entry, before any order is placed.
+    ///
+    /// Returns the failed entry if a check rejects it.
+    fn entry_precheck(
+        &self,
+        allocation: &PositionAllocation,
+        margin_context: &MarginContext,
+    ) -> Option<EntryResult> {
+        // PHASE 1.4: Absolute notional caps, symbol and group (defense in depth -
+        // allocator already clamps)
+        if let Some(cap) = self
+            .notional_caps
+            .max_notional_for(&allocation.symbol, &margin_context.position_notionals)
+        {
+            let held = margin_context
+                .position_notionals
+                .get(&allocation.symbol)
+                .map_or(Decimal::ZERO, |notional| notional.abs());
+            let projected = held + allocation.target_size_usdt;
+            if projected > cap {
+                error!(
+                    symbol = %allocation.symbol,
+                    target_size = %allocation.target_size_usdt,
+                    %projected,
+                    %cap,
+                    "❌ Pre-entry notional cap exceeded - rejecting position"
+                );
+                return Some(EntryResult {
+                    symbol: allocation.symbol.clone(),
+                    spot_order: None,
+                    futures_order: None,
+                    success: false,
+                    error: Some(format!("Notional cap exceeded: {} > {}", projected, cap)),
+                });
+            }
+        }
+
         // PHASE 1.5: Pre-entry margin validation
         // Validate margin BEFORE placing any orders
         if let Err(e) = margin_context.validate_position_entry(allocation.target_size_usdt) {
@@ -126,7 +877,7 @@ impl OrderExecutor {
                 error = %e,
                 "❌ Pre-entry margin validation failed - rejecting position"
             );
-            return Ok(EntryResult {
+            return Some(EntryResult {
                 symbol: allocation.symbol.clone(),
                 spot_order: None,
                 futures_order: None,
@@ -141,44 +892,58 @@ impl OrderExecutor {
             projected_margin = %margin_context.projected_margin_ratio(allocation.target_size_usdt),
             "✅ Pre-entry margin validation passed"
         );
-
-        // Proceed with atomic position entry
-        self.enter_position(client, allocation, current_price).await
+        None
     }
 
-    /// Execute a delta-neutral entry (spot + futures hedge).
-    ///
-    /// For positive funding: Long spot + Short futures (we receive funding)
-    /// For negative funding: Short spot (margin borrow) + Long futures (we receive funding)
+    /// Size an entry and set up its futures symbol, up to the futures order.
     ///
-    /// Note: For production use, prefer `enter_position_validated` which includes
-    /// pre-entry margin validation.
-    pub async fn enter_position(
+    /// Returns the entry ready for its futures leg, or the failed entry when it
+    /// is rejected before any order is placed.
+    async fn prepare_entry<'a>(
         &self,
-        client: &BinanceClient,
-        allocation: &PositionAllocation,
+        client: &dyn ExchangeClient,
+        allocation: &'a PositionAllocation,
         current_price: Decimal,
-    ) -> Result<EntryResult> {
+    ) -> Result<std::result::Result<PendingEntry<'a>, EntryResult>> {
         let symbol = &allocation.symbol;
-        let spot_symbol = &allocation.spot_symbol;
         let is_positive_funding = allocation.funding_rate > Decimal::ZERO;
 
         info!(
             %symbol,
-            %spot_symbol,
+            spot_symbol = %allocation.spot_symbol,
             target_size = %allocation.target_size_usdt,
             funding_rate = %allocation.funding_rate,
             positive = is_positive_funding,
             "Entering delta-neutral position"
         );
 
-        // Set up futures account for this symbol
-        self.prepare_futures_symbol(client, symbol, allocation.leverage)
-            .await?;
-
         // Calculate quantity based on price
         let quantity = allocation.target_size_usdt / current_price;
         let quantity = self.round_quantity(quantity, symbol);
+        let quantity = self.entry_quantity(allocation, quantity);
+        if quantity <= Decimal::ZERO {
+            return Ok(Err(EntryResult {
+                symbol: symbol.clone(),
+                spot_order: None,
+                futures_order: None,
+                success: false,
+                error: Some("Entry quantity is zero".to_string()),
+            }));
+        }
+        if let Some(reason) = self.venue_rejection(symbol, quantity, current_price) {
+            warn!(%symbol, %quantity, reason = %reason, "Entry rejected by venue rules");
+            return Ok(Err(EntryResult {
+                symbol: symbol.clone(),
+                spot_order: None,
+                futures_order: None,
+                success: false,
+                error: Some(reason),
+            }));
+        }
+
+        // Set up futures account for this symbol
+        self.prepare_futures_symbol(client, symbol, allocation.leverage)
+            .await?;
 
         // Determine order sides based on funding direction
         let (spot_side, futures_side) = if is_positive_funding {
@@ -189,10 +954,127 @@ impl OrderExecutor {
             (OrderSide::Sell, OrderSide::Buy)
         };
 
-        // Execute futures order first (more critical for funding capture)
-        let futures_result = self
-            .place_futures_order_with_retry(client, symbol, futures_side, quantity, 3)
-            .await;
+        // All legs of this entry share a correlation ID in the order journal
+        let new_position = !self.has_position_id(symbol);
+        let correlation_id = self.next_correlation_id(symbol);
+
+        Ok(Ok(PendingEntry {
+            allocation,
+            quantity,
+            spot_side,
+            futures_side,
+            correlation_id,
+            new_position,
+        }))
+    }
+
+    /// Send the futures legs of `entries` in one batch.
+    ///
+    /// Returns one result per entry, in order, with the order sent for it; an
+    /// order that couldn't be journaled is not sent.
+    async fn place_entry_futures_orders(
+        &self,
+        client: &dyn ExchangeClient,
+        entries: &[(usize, PendingEntry<'_>)],
+    ) -> Vec<(Option<NewOrder>, Result<OrderResponse>)> {
+        // Every order is journaled before the batch goes out; one that can't be is not sent
+        let orders: Vec<Result<NewOrder>> = entries
+            .iter()
+            .map(|(_, entry)| {
+                let symbol = &entry.allocation.symbol;
+                let client_order_id = self.journal_intent(
+                    &entry.correlation_id,
+                    entry.futures_leg(),
+                    symbol,
+                    entry.futures_side,
+                    Some(entry.quantity),
+                )?;
+                Ok(futures_order(
+                    symbol,
+                    entry.futures_side,
+                    OrderType::Market,
+                    entry.quantity,
+                    None,
+                    client_order_id,
+                ))
+            })
+            .collect();
+        let batch: Vec<NewOrder> = orders
+            .iter()
+            .filter_map(|order| order.as_ref().ok().cloned())
+            .collect();
+        let mut placed = client.place_futures_orders(&batch).await.into_iter();
+
+        orders
+            .into_iter()
+            .map(|order| match order {
+                Ok(order) => {
+                    let result = placed
+                        .next()
+                        .unwrap_or_else(|| Err(anyhow!("Batch order result missing")));
+                    self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+                    (Some(order), result)
+                }
+                Err(e) => (None, Err(e)),
+            })
+            .collect()
+    }
+
+    /// Settle a futures leg the batch failed, retrying it alone when it never
+    /// reached the exchange.
+    async fn retry_entry_futures_order(
+        &self,
+        client: &dyn ExchangeClient,
+        entry: &PendingEntry<'_>,
+        order: Option<NewOrder>,
+        batch_result: Result<OrderResponse>,
+    ) -> Result<OrderResponse> {
+        let (Some(order), Err(e)) = (order, &batch_result) else {
+            return batch_result;
+        };
+        match self.settle_failed_order(client, &order, e).await {
+            Ok(Some(response)) => Ok(response),
+            Ok(None) => {
+                warn!(
+                    symbol = %order.symbol,
+                    error = %e,
+                    "Batched futures order failed, retrying alone"
+                );
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                self.place_futures_order_with_retry(
+                    client,
+                    &order.symbol,
+                    entry.futures_side,
+                    entry.quantity,
+                    2, // The batch was the first of three attempts
+                    &entry.correlation_id,
+                    entry.futures_leg(),
+                )
+                .await
+            }
+            Err(unknown) => Err(unknown),
+        }
+    }
+
+    /// Finish an entry once its futures order returned: hedge it with spot, or
+    /// unwind it if the hedge fails, then check the legs match.
+    async fn complete_entry(
+        &self,
+        client: &dyn ExchangeClient,
+        entry: PendingEntry<'_>,
+        futures_result: Result<OrderResponse>,
+    ) -> Result<EntryResult> {
+        let PendingEntry {
+            allocation,
+            quantity,
+            spot_side,
+            futures_side,
+            correlation_id,
+            new_position,
+        } = entry;
+        let symbol = &allocation.symbol;
+        let spot_symbol = &allocation.spot_symbol;
+        let is_positive_funding = allocation.funding_rate > Decimal::ZERO;
 
         let futures_order = match futures_result {
             Ok(order) if order.status == OrderStatus::Filled => {
@@ -217,18 +1099,50 @@ impl OrderExecutor {
                 });
             }
             Err(e) => {
-                error!(%symbol, error = %e, "Failed to place futures order");
-                return Ok(EntryResult {
-                    symbol: symbol.clone(),
-                    spot_order: None,
-                    futures_order: None,
-                    success: false,
-                    error: Some(e.to_string()),
-                });
+                // An unsettled order may have opened the position regardless
+                let reconciled = match UnsettledOrder::find(&e) {
+                    Some(unsettled) => {
+                        self.reconcile_unsettled_entry(
+                            client,
+                            unsettled,
+                            futures_side,
+                            new_position,
+                        )
+                        .await?
+                    }
+                    None => None,
+                };
+                let Some(order) = reconciled else {
+                    error!(%symbol, error = %e, "Failed to place futures order");
+                    return Ok(EntryResult {
+                        symbol: symbol.clone(),
+                        spot_order: None,
+                        futures_order: None,
+                        success: false,
+                        error: Some(e.to_string()),
+                    });
+                };
+                Some(order)
             }
         };
 
-        // Now execute spot hedge
+        // Existing holdings already form the long leg
+        if allocation.hedge_with_holdings {
+            info!(
+                %symbol,
+                base_asset = %allocation.base_asset,
+                "Existing spot holdings hedge the short - skipping spot buy"
+            );
+            return Ok(EntryResult {
+                symbol: symbol.clone(),
+                spot_order: None,
+                futures_order,
+                success: true,
+                error: None,
+            });
+        }
+
+        // Now execute spot hedge
         let actual_futures_qty = futures_order
             .as_ref()
             .map(|o| o.executed_qty)
@@ -241,6 +1155,7 @@ impl OrderExecutor {
                 spot_side,
                 actual_futures_qty,
                 is_positive_funding,
+                &correlation_id,
             )
             .await;
 
@@ -282,11 +1197,16 @@ impl OrderExecutor {
                                 unwind_side,
                                 f_order.executed_qty,
                                 3, // Each attempt has 3 internal retries
+                                &correlation_id,
+                                OrderLeg::UnwindFutures,
                             )
                             .await
                         {
                             Ok(_) => {
                                 info!(%symbol, attempt, "✅ Emergency futures unwind successful");
+                                if new_position {
+                                    self.release_position_id(symbol);
+                                }
                                 unwind_success = true;
                                 break;
                             }
@@ -419,14 +1339,84 @@ impl OrderExecutor {
         })
     }
 
+    /// Reconcile an entry whose futures order is unsettled against the positions.
+    ///
+    /// Only a new position tells: none open means the order never executed
+    /// (`None`), and one on the order's side is its fill, returned to be hedged.
+    /// A top-up, a position on the other side or positions that can't be read
+    /// leave the fill unknown, an error for the operator.
+    async fn reconcile_unsettled_entry(
+        &self,
+        client: &dyn ExchangeClient,
+        unsettled: &UnsettledOrder,
+        side: OrderSide,
+        new_position: bool,
+    ) -> Result<Option<OrderResponse>> {
+        let symbol = &unsettled.symbol;
+        if !new_position {
+            bail!(
+                "{} - the position it tops up hides whether it executed, check it by hand",
+                unsettled
+            );
+        }
+        let positions = client
+            .get_positions()
+            .await
+            .with_context(|| format!("{} - and its position couldn't be checked", unsettled))?;
+        let Some(position) = positions
+            .iter()
+            .find(|position| &position.symbol == symbol && !position.position_amt.is_zero())
+        else {
+            info!(%symbol, "Unsettled futures order left no position - it never executed");
+            return Ok(None);
+        };
+
+        let filled_side = if position.position_amt > Decimal::ZERO {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        if filled_side != side {
+            bail!(
+                "{} - and a {:?} position of {} is open against it",
+                unsettled,
+                filled_side,
+                position.position_amt
+            );
+        }
+
+        let quantity = position.position_amt.abs();
+        warn!(
+            %symbol,
+            %quantity,
+            entry_price = %position.entry_price,
+            "Unsettled futures order opened the position - hedging it"
+        );
+        Ok(Some(OrderResponse {
+            order_id: 0,
+            symbol: symbol.clone(),
+            status: OrderStatus::Filled,
+            client_order_id: unsettled.client_order_id.clone().unwrap_or_default(),
+            price: Decimal::ZERO,
+            avg_price: position.entry_price,
+            orig_qty: quantity,
+            executed_qty: quantity,
+            side,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            update_time: Utc::now().timestamp_millis(),
+        }))
+    }
+
     /// Place a spot margin order for hedging.
     async fn place_spot_margin_order(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         symbol: &str,
         side: OrderSide,
         quantity: Decimal,
         is_positive_funding: bool,
+        correlation_id: &str,
     ) -> Result<OrderResponse> {
         // For positive funding (buying spot): NO_SIDE_EFFECT (normal buy)
         // For negative funding (selling spot): MARGIN_BUY to auto-borrow the asset
@@ -446,19 +1436,53 @@ impl OrderExecutor {
             time_in_force: None,
             is_isolated: Some(false), // Cross margin for capital efficiency
             side_effect_type: Some(side_effect),
+            new_client_order_id: None,
         };
 
-        client.place_margin_order(&order).await
+        self.submit_margin_order(client, order, correlation_id, OrderLeg::EntrySpot)
+            .await
+    }
+
+    /// Send a cross margin order, journaling it first when a journal is set.
+    ///
+    /// A request whose outcome is unknown is settled by looking the order up;
+    /// it is never sent again.
+    async fn submit_margin_order(
+        &self,
+        client: &dyn ExchangeClient,
+        mut order: MarginOrder,
+        correlation_id: &str,
+        leg: OrderLeg,
+    ) -> Result<OrderResponse> {
+        order.new_client_order_id = self.journal_intent(
+            correlation_id,
+            leg,
+            &order.symbol,
+            order.side,
+            order.quantity,
+        )?;
+        let result = client.place_margin_order(&order).await;
+        self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+        match result {
+            Err(e) => match self.settle_failed_margin_order(client, &order, &e).await? {
+                Some(response) => Ok(response),
+                None => Err(e),
+            },
+            placed => placed,
+        }
     }
 
     /// Place a futures order with retry logic.
+    #[allow(clippy::too_many_arguments)]
     async fn place_futures_order_with_retry(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         symbol: &str,
         side: OrderSide,
         quantity: Decimal,
         max_retries: u8,
+        correlation_id: &str,
+        leg: OrderLeg,
     ) -> Result<OrderResponse> {
         self.place_order_with_retry(
             client,
@@ -468,6 +1492,8 @@ impl OrderExecutor {
             quantity,
             None,
             max_retries,
+            correlation_id,
+            leg,
         )
         .await
     }
@@ -475,7 +1501,7 @@ impl OrderExecutor {
     /// Exit an existing position.
     pub async fn exit_position(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         symbol: &str,
         current_position: Decimal,
     ) -> Result<OrderResponse> {
@@ -494,8 +1520,218 @@ impl OrderExecutor {
             "Exiting position"
         );
 
-        self.place_order_with_retry(client, symbol, side, OrderType::Market, quantity, None, 3)
-            .await
+        let correlation_id = self.next_correlation_id(symbol);
+        let result = self
+            .place_futures_order_with_retry(
+                client,
+                symbol,
+                side,
+                quantity,
+                3,
+                &correlation_id,
+                OrderLeg::ExitFutures,
+            )
+            .await;
+        if result.is_ok() {
+            self.release_position_id(symbol);
+        }
+        result
+    }
+
+    /// Close a futures position with a reduce-only market order (emergency halt).
+    pub async fn close_futures_position(
+        &self,
+        client: &dyn ExchangeClient,
+        symbol: &str,
+        position_amt: Decimal,
+    ) -> Result<OrderResponse> {
+        let side = if position_amt > Decimal::ZERO {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        };
+        let quantity = position_amt.abs();
+        let correlation_id = self.next_correlation_id(symbol);
+
+        let order = NewOrder {
+            symbol: symbol.to_string(),
+            side,
+            position_side: None,
+            order_type: OrderType::Market,
+            quantity: Some(quantity),
+            price: None,
+            time_in_force: None,
+            reduce_only: Some(true),
+            new_client_order_id: self.journal_intent(
+                &correlation_id,
+                OrderLeg::HaltCloseFutures,
+                symbol,
+                side,
+                Some(quantity),
+            )?,
+        };
+
+        let result = client.place_futures_order(&order).await;
+        self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+        if result.is_ok() {
+            self.release_position_id(symbol);
+        }
+        result
+    }
+
+    /// Futures quantity rounded down to the symbol's precision.
+    pub fn floor_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
+        let precision = self.precisions.get(symbol).copied().unwrap_or(3);
+        quantity.round_dp_with_strategy(precision as u32, RoundingStrategy::ToZero)
+    }
+
+    /// Trade the Binance futures leg of a cross-venue spread with a market order.
+    ///
+    /// Exits are reduce-only and release the symbol's position ID.
+    pub async fn place_spread_leg(
+        &self,
+        client: &dyn ExchangeClient,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Decimal,
+        exit: bool,
+    ) -> Result<OrderResponse> {
+        let quantity = self.floor_quantity(quantity, symbol);
+        if quantity <= Decimal::ZERO {
+            bail!("{} spread leg quantity rounds to zero", symbol);
+        }
+        let leg = if exit {
+            OrderLeg::SpreadExitFutures
+        } else {
+            OrderLeg::SpreadEntryFutures
+        };
+        let correlation_id = self.next_correlation_id(symbol);
+
+        let order = NewOrder {
+            symbol: symbol.to_string(),
+            side,
+            position_side: None,
+            order_type: OrderType::Market,
+            quantity: Some(quantity),
+            price: None,
+            time_in_force: None,
+            reduce_only: exit.then_some(true),
+            new_client_order_id: self.journal_intent(
+                &correlation_id,
+                leg,
+                symbol,
+                side,
+                Some(quantity),
+            )?,
+        };
+
+        let result = client.place_futures_order(&order).await;
+        self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+        if exit && result.is_ok() {
+            self.release_position_id(symbol);
+        }
+        result
+    }
+
+    /// Trade the Hyperliquid leg of a cross-venue spread with an immediate-or-cancel
+    /// order, journaled under `coin` with the position of `symbol`.
+    ///
+    /// The order is tagged with the cloid of its client order ID, so a crash or
+    /// an unanswered request is resolved by looking it up. A failed order stays
+    /// pending in the journal until then.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_hyperliquid_leg(
+        &self,
+        hyperliquid: &HyperliquidClient,
+        symbol: &str,
+        coin: &str,
+        is_buy: bool,
+        size: Decimal,
+        slippage: Decimal,
+        exit: bool,
+    ) -> Result<HyperliquidFill> {
+        let leg = if exit {
+            OrderLeg::SpreadExitHyperliquid
+        } else {
+            OrderLeg::SpreadEntryHyperliquid
+        };
+        let side = if is_buy {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        let correlation_id = self.next_correlation_id(symbol);
+        let client_order_id = self
+            .journal_intent(&correlation_id, leg, coin, side, Some(size))?
+            .unwrap_or_else(|| self.next_client_order_id(&correlation_id));
+
+        let fill = hyperliquid
+            .place_ioc_order(
+                coin,
+                is_buy,
+                size,
+                slippage,
+                exit,
+                &HyperliquidClient::cloid(&client_order_id),
+            )
+            .await?;
+        if let Some(journal) = &self.journal {
+            if let Err(e) = journal.complete_order_intent(
+                &client_order_id,
+                fill.order_id as i64,
+                fill.filled_size,
+            ) {
+                warn!(%client_order_id, error = %e, "Failed to mark journaled order completed");
+            }
+        }
+        Ok(fill)
+    }
+
+    /// Trade a COIN-margined perpetual short with a market order of whole contracts.
+    ///
+    /// Exits are reduce-only buys and release the contract's position ID.
+    pub async fn place_coin_margined_leg(
+        &self,
+        client: &dyn ExchangeClient,
+        symbol: &str,
+        contracts: Decimal,
+        exit: bool,
+    ) -> Result<OrderResponse> {
+        let contracts = contracts.floor();
+        if contracts <= Decimal::ZERO {
+            bail!("{} COIN-margined leg is under one contract", symbol);
+        }
+        let (leg, side) = if exit {
+            (OrderLeg::CoinExitFutures, OrderSide::Buy)
+        } else {
+            (OrderLeg::CoinEntryFutures, OrderSide::Sell)
+        };
+        let correlation_id = self.next_correlation_id(symbol);
+
+        let order = NewOrder {
+            symbol: symbol.to_string(),
+            side,
+            position_side: None,
+            order_type: OrderType::Market,
+            quantity: Some(contracts),
+            price: None,
+            time_in_force: None,
+            reduce_only: exit.then_some(true),
+            new_client_order_id: self.journal_intent(
+                &correlation_id,
+                leg,
+                symbol,
+                side,
+                Some(contracts),
+            )?,
+        };
+
+        let result = client.place_coin_futures_order(&order).await;
+        self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+        if exit && result.is_ok() {
+            self.release_position_id(symbol);
+        }
+        result
     }
 
     /// Reduce an oversized position to maintain optimal allocation.
@@ -504,7 +1740,7 @@ impl OrderExecutor {
     /// delta neutrality while freeing up capital for better opportunities.
     pub async fn reduce_position(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         reduction: &PositionReduction,
         current_price: Decimal,
         futures_position: Decimal, // Current futures position (positive=long, negative=short)
@@ -547,8 +1783,17 @@ impl OrderExecutor {
             OrderSide::Sell // Close long
         };
 
+        let correlation_id = self.next_correlation_id(symbol);
         let futures_result = self
-            .place_futures_order_with_retry(client, symbol, futures_side, reduction_quantity, 3)
+            .place_futures_order_with_retry(
+                client,
+                symbol,
+                futures_side,
+                reduction_quantity,
+                3,
+                &correlation_id,
+                OrderLeg::ReduceFutures,
+            )
             .await;
 
         let futures_order = match futures_result {
@@ -565,6 +1810,23 @@ impl OrderExecutor {
             }
         };
 
+        // Declared holdings are never sold on exit
+        if is_short_futures && self.existing_holdings.covers(&reduction.base_asset) {
+            info!(
+                %symbol,
+                base_asset = %reduction.base_asset,
+                %reduction_quantity,
+                "Futures reduced - keeping existing spot holdings"
+            );
+            return Ok(EntryResult {
+                symbol: symbol.clone(),
+                spot_order: None,
+                futures_order,
+                success: true,
+                error: None,
+            });
+        }
+
         // Step 2: Reduce spot position (opposite side of futures)
         let spot_side = if is_short_futures {
             // Was long spot to hedge short futures, sell spot
@@ -591,9 +1853,12 @@ impl OrderExecutor {
             time_in_force: None,
             is_isolated: Some(false),
             side_effect_type: Some(side_effect),
+            new_client_order_id: None,
         };
 
-        let spot_result = client.place_margin_order(&spot_order).await;
+        let spot_result = self
+            .submit_margin_order(client, spot_order, &correlation_id, OrderLeg::ReduceSpot)
+            .await;
 
         let spot_order_response = match spot_result {
             Ok(order) => Some(order),
@@ -630,7 +1895,7 @@ impl OrderExecutor {
     /// Prepare futures symbol (set leverage and margin type).
     async fn prepare_futures_symbol(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         symbol: &str,
         leverage: u8,
     ) -> Result<()> {
@@ -644,38 +1909,36 @@ impl OrderExecutor {
     }
 
     /// Place an order with retry logic.
+    #[allow(clippy::too_many_arguments)]
     async fn place_order_with_retry(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         symbol: &str,
         side: OrderSide,
         order_type: OrderType,
         quantity: Decimal,
         price: Option<Decimal>,
         max_retries: u8,
+        correlation_id: &str,
+        leg: OrderLeg,
     ) -> Result<OrderResponse> {
         let mut last_error = None;
 
         for attempt in 1..=max_retries {
-            let order = NewOrder {
-                symbol: symbol.to_string(),
-                side,
-                position_side: None,
-                order_type,
-                quantity: Some(quantity),
-                price,
-                time_in_force: if order_type == OrderType::Limit {
-                    Some(TimeInForce::Gtx) // Post-only
-                } else {
-                    None
-                },
-                reduce_only: None,
-                new_client_order_id: None,
-            };
-
-            match client.place_futures_order(&order).await {
+            // Each attempt is a separate order on the exchange, journaled under its own ID
+            let client_order_id =
+                self.journal_intent(correlation_id, leg, symbol, side, Some(quantity))?;
+            let order = futures_order(symbol, side, order_type, quantity, price, client_order_id);
+
+            let result = client.place_futures_order(&order).await;
+            self.journal_outcome(order.new_client_order_id.as_deref(), &result);
+            match result {
                 Ok(response) => return Ok(response),
                 Err(e) => {
+                    // Sending it again is only safe once it's known not to be on the book
+                    if let Some(response) = self.settle_failed_order(client, &order, &e).await? {
+                        return Ok(response);
+                    }
                     warn!(
                         %symbol,
                         attempt,
@@ -685,32 +1948,767 @@ impl OrderExecutor {
                     );
                     last_error = Some(e);
 
-                    if attempt < max_retries {
-                        tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
-                    }
+                    if attempt < max_retries {
+                        tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+                    }
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow!("Unknown error")))
+    }
+
+    /// Settle what became of a futures order whose request failed.
+    ///
+    /// A refused order is safe to send again (`None`). A timeout, server error
+    /// or unreadable response leaves the outcome unknown, so the order is looked
+    /// up by client order ID: found, it is journaled and returned; never
+    /// received, it is safe to send again. An order that can't be looked up is
+    /// an [`UnsettledOrder`] error, as sending it again could double it.
+    async fn settle_failed_order(
+        &self,
+        client: &dyn ExchangeClient,
+        order: &NewOrder,
+        error: &anyhow::Error,
+    ) -> Result<Option<OrderResponse>> {
+        let client_order_id = order.new_client_order_id.as_deref();
+        let Some(found) = self
+            .look_up_failed_order(client, false, &order.symbol, client_order_id, error)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let response = looked_up_response(
+            found,
+            order.order_type,
+            order.quantity,
+            order.price,
+            order.time_in_force,
+        )?;
+        self.journal_outcome(client_order_id, &Ok(response.clone()));
+        Ok(Some(response))
+    }
+
+    /// Settle what became of a margin order whose request failed, as
+    /// [`settle_failed_order`](Self::settle_failed_order) does for futures.
+    async fn settle_failed_margin_order(
+        &self,
+        client: &dyn ExchangeClient,
+        order: &MarginOrder,
+        error: &anyhow::Error,
+    ) -> Result<Option<OrderResponse>> {
+        let client_order_id = order.new_client_order_id.as_deref();
+        let Some(found) = self
+            .look_up_failed_order(client, true, &order.symbol, client_order_id, error)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let response = looked_up_response(
+            found,
+            order.order_type,
+            order.quantity,
+            order.price,
+            order.time_in_force,
+        )?;
+        self.journal_outcome(client_order_id, &Ok(response.clone()));
+        Ok(Some(response))
+    }
+
+    /// Look up an order whose request failed with `error`, if it may have executed.
+    ///
+    /// Returns `None` when the order was refused or never reached the venue.
+    async fn look_up_failed_order(
+        &self,
+        client: &dyn ExchangeClient,
+        margin: bool,
+        symbol: &str,
+        client_order_id: Option<&str>,
+        error: &anyhow::Error,
+    ) -> Result<Option<OrderLookup>> {
+        if !error_class(error).may_have_executed() {
+            return Ok(None);
+        }
+        let Some(client_order_id) = client_order_id else {
+            return Err(anyhow!(UnsettledOrder {
+                symbol: symbol.to_string(),
+                client_order_id: None,
+                reason: format!(
+                    "{} order outcome unknown and it has no client order ID to check: {:#}",
+                    symbol, error
+                ),
+            }));
+        };
+
+        let lookup = if margin {
+            client
+                .get_margin_order_by_client_id(symbol, client_order_id)
+                .await
+        } else {
+            client
+                .get_futures_order_by_client_id(symbol, client_order_id)
+                .await
+        };
+        let found = lookup.map_err(|lookup_error| {
+            lookup_error.context(UnsettledOrder {
+                symbol: symbol.to_string(),
+                client_order_id: Some(client_order_id.to_string()),
+                reason: format!(
+                    "{} order {} outcome unknown ({:#}) and the lookup failed - not resent",
+                    symbol, client_order_id, error
+                ),
+            })
+        })?;
+
+        if let Some(found) = &found {
+            warn!(
+                %symbol,
+                %client_order_id,
+                order_id = found.order_id,
+                status = ?found.status,
+                error = %error,
+                "Order request failed but the order reached the book - not resending"
+            );
+        }
+        Ok(found)
+    }
+
+    /// New correlation ID grouping the legs of one entry, exit or reduction.
+    ///
+    /// Starts with the position ID of the futures symbol.
+    fn next_correlation_id(&self, symbol: &str) -> String {
+        format!(
+            "{}-{}",
+            self.position_id(symbol),
+            self.order_sequence.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// Client order ID of the next order of `correlation_id`.
+    fn next_client_order_id(&self, correlation_id: &str) -> String {
+        format!(
+            "{}-{}-{}",
+            self.order_prefix,
+            correlation_id,
+            self.order_sequence.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// Journal an order intent before sending it.
+    ///
+    /// Returns the client order ID to tag the order with, or `None` when journaling
+    /// is disabled. A journal write failure aborts the order: an unjournaled live
+    /// order could not be recovered after a crash.
+    fn journal_intent(
+        &self,
+        correlation_id: &str,
+        leg: OrderLeg,
+        symbol: &str,
+        side: OrderSide,
+        quantity: Option<Decimal>,
+    ) -> Result<Option<String>> {
+        let Some(journal) = &self.journal else {
+            return Ok(None);
+        };
+
+        let client_order_id = self.next_client_order_id(correlation_id);
+        journal
+            .journal_order_intent(
+                &client_order_id,
+                correlation_id,
+                symbol,
+                leg.as_str(),
+                &format!("{:?}", side).to_uppercase(),
+                quantity.unwrap_or_default(),
+            )
+            .context("Failed to journal order intent - order not sent")?;
+        Ok(Some(client_order_id))
+    }
+
+    /// Mark a journaled order completed once the exchange acknowledged it.
+    ///
+    /// Errors leave the intent pending: the order may still have reached the
+    /// exchange, so only its user data stream update or startup reconciliation
+    /// can resolve it.
+    fn journal_outcome(&self, client_order_id: Option<&str>, result: &Result<OrderResponse>) {
+        let (Some(journal), Some(client_order_id), Ok(order)) =
+            (&self.journal, client_order_id, result)
+        else {
+            return;
+        };
+
+        if let Err(e) =
+            journal.complete_order_intent(client_order_id, order.order_id, order.executed_qty)
+        {
+            warn!(%client_order_id, error = %e, "Failed to mark journaled order completed");
+        }
+    }
+
+    /// Resolve the journaled order of a user data stream order update.
+    ///
+    /// Orders in a final status are marked completed with their cumulative filled
+    /// quantity, which replaces the quantity of the REST acknowledgement, or failed
+    /// if the exchange rejected them. Returns whether the update was final; orders
+    /// the bot didn't journal are left alone.
+    pub fn apply_order_update(&self, update: &OrderUpdate) -> Result<bool> {
+        let Some(journal) = &self.journal else {
+            return Ok(false);
+        };
+        if !update.is_final() {
+            return Ok(false);
+        }
+
+        if update.status == "REJECTED" {
+            journal.fail_order_intent(&update.client_order_id)?;
+        } else {
+            let filled = update
+                .filled()
+                .with_context(|| format!("Invalid filled quantity '{}'", update.filled_qty))?;
+            journal.complete_order_intent(&update.client_order_id, update.order_id, filled)?;
+        }
+        Ok(true)
+    }
+
+    /// Resolve journaled orders left pending by a crash or failed request.
+    ///
+    /// Each pending intent is looked up on the exchange by client order ID. Orders
+    /// found there are marked completed and orders the exchange never saw are marked
+    /// failed. Intents whose lookup errors stay pending for the next attempt.
+    /// Hyperliquid legs are left to [`Self::reconcile_hyperliquid_journal`].
+    pub async fn reconcile_journal(
+        &self,
+        client: &dyn ExchangeClient,
+    ) -> Result<Vec<JournalReconciliation>> {
+        let Some(journal) = &self.journal else {
+            return Ok(Vec::new());
+        };
+
+        let mut resolved = Vec::new();
+        for intent in journal.get_incomplete_order_intents()? {
+            let leg = OrderLeg::parse(&intent.leg);
+            // Resolved against Hyperliquid by `reconcile_hyperliquid_journal`
+            if leg.is_some_and(|leg| leg.is_hyperliquid()) {
+                continue;
+            }
+            let lookup = if leg.is_some_and(|leg| leg.is_spot()) {
+                client
+                    .get_margin_order_by_client_id(&intent.symbol, &intent.client_order_id)
+                    .await
+            } else if leg.is_some_and(|leg| leg.is_coin()) {
+                client
+                    .get_coin_futures_order_by_client_id(&intent.symbol, &intent.client_order_id)
+                    .await
+            } else {
+                client
+                    .get_futures_order_by_client_id(&intent.symbol, &intent.client_order_id)
+                    .await
+            };
+
+            let order = match lookup {
+                Ok(order) => order,
+                Err(e) => {
+                    warn!(
+                        client_order_id = %intent.client_order_id,
+                        error = %e,
+                        "Failed to look up journaled order - leaving pending"
+                    );
+                    continue;
+                }
+            };
+
+            match &order {
+                Some(found) => journal.complete_order_intent(
+                    &intent.client_order_id,
+                    found.order_id,
+                    found.executed_qty,
+                )?,
+                None => journal.fail_order_intent(&intent.client_order_id)?,
+            }
+            resolved.push(JournalReconciliation { intent, order });
+        }
+
+        Ok(resolved)
+    }
+
+    /// Resolve pending Hyperliquid legs by looking their cloids up.
+    ///
+    /// Same rules as [`Self::reconcile_journal`]. Returns the number resolved.
+    pub async fn reconcile_hyperliquid_journal(
+        &self,
+        hyperliquid: &HyperliquidClient,
+    ) -> Result<usize> {
+        let Some(journal) = &self.journal else {
+            return Ok(0);
+        };
+
+        let mut resolved = 0;
+        for intent in journal.get_incomplete_order_intents()? {
+            if !OrderLeg::parse(&intent.leg).is_some_and(|leg| leg.is_hyperliquid()) {
+                continue;
+            }
+            let cloid = HyperliquidClient::cloid(&intent.client_order_id);
+            match hyperliquid.get_order_by_cloid(&intent.symbol, &cloid).await {
+                Ok(Some(fill)) => journal.complete_order_intent(
+                    &intent.client_order_id,
+                    fill.order_id as i64,
+                    fill.filled_size,
+                )?,
+                Ok(None) => journal.fail_order_intent(&intent.client_order_id)?,
+                Err(e) => {
+                    warn!(
+                        client_order_id = %intent.client_order_id,
+                        %cloid,
+                        error = %e,
+                        "Failed to look up journaled Hyperliquid order - leaving pending"
+                    );
+                    continue;
+                }
+            }
+            resolved += 1;
+        }
+        Ok(resolved)
+    }
+
+    /// Round quantity to valid precision for the symbol.
+    fn round_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
+        let precision = self.precisions.get(symbol).copied().unwrap_or(3);
+        quantity.round_dp(precision as u32)
+    }
+
+    /// Round quantity to the lot size of a spot symbol, falling back to the
+    /// precision of the futures symbol it hedges.
+    fn round_spot_quantity(&self, quantity: Decimal, spot_symbol: &str, symbol: &str) -> Decimal {
+        match self.spot_precisions.get(spot_symbol) {
+            Some(precision) => quantity.round_dp(*precision as u32),
+            None => self.round_quantity(quantity, symbol),
+        }
+    }
+
+    /// Check if position entry should proceed based on slippage.
+    pub fn check_slippage(&self, expected_price: Decimal, actual_price: Decimal) -> bool {
+        let slippage = ((actual_price - expected_price) / expected_price).abs();
+        slippage <= self.config.slippage_tolerance
+    }
+
+    /// Tracker cancelling this executor's orders left open past `order_timeout_secs`.
+    ///
+    /// Create it after [`Self::set_instance_id`], so it only watches this
+    /// instance's orders.
+    pub fn order_tracker(&self) -> OrderTracker {
+        OrderTracker::new(self.order_prefix.clone(), self.config.order_timeout_secs)
+    }
+
+    /// Re-submit the hedge of stale entry futures legs the order tracker cancelled.
+    ///
+    /// The cancel is journaled as the leg's final outcome. An entry is hedged on
+    /// the instrument it journaled: a perpetual when it has a perp hedge leg,
+    /// spot otherwise. When the hedge legs of the same entry filled more than its
+    /// futures legs, the difference is sent again as a market futures order so
+    /// the hedge fill doesn't stay unhedged; when the futures legs filled more,
+    /// the difference is hedged with a market order on the hedge instrument,
+    /// rounded to its own precision. Legs hedged by held coins (declared
+    /// holdings or COIN-margined shorts) need no hedge order and are skipped.
+    /// Entries with a leg still pending are left alone, as its fill is unknown.
+    /// Returns the hedges placed; a failed hedge is logged as an unhedged leg.
+    pub async fn rehedge_cancelled_orders(
+        &self,
+        client: &dyn ExchangeClient,
+        cancelled: &[OrderResponse],
+    ) -> Vec<OrderResponse> {
+        let Some(journal) = &self.journal else {
+            return Vec::new();
+        };
+
+        let mut hedges = Vec::new();
+        for order in cancelled {
+            self.journal_outcome(Some(&order.client_order_id), &Ok(order.clone()));
+
+            let intents = match journal.get_correlated_order_intents(&order.client_order_id) {
+                Ok(intents) => intents,
+                Err(e) => {
+                    error!(
+                        client_order_id = %order.client_order_id,
+                        error = %e,
+                        "Failed to read the entry of a cancelled order - spot leg may be unhedged"
+                    );
+                    continue;
+                }
+            };
+            let Some(cancelled_intent) = intents
+                .iter()
+                .find(|intent| intent.client_order_id == order.client_order_id)
+            else {
+                continue;
+            };
+            let correlation_id = cancelled_intent.correlation_id.clone();
+            match OrderLeg::parse(&cancelled_intent.leg) {
+                Some(OrderLeg::EntryFutures) => {}
+                Some(OrderLeg::HoldingsEntryFutures | OrderLeg::CoinEntryFutures) => {
+                    info!(
+                        symbol = %order.symbol,
+                        %correlation_id,
+                        "Cancelled futures leg is hedged by held coins - nothing to re-hedge"
+                    );
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let entry_legs = [
+                OrderLeg::EntrySpot.as_str(),
+                OrderLeg::EntryFutures.as_str(),
+                OrderLeg::EntryPerpHedge.as_str(),
+            ];
+            if intents.iter().any(|intent| {
+                entry_legs.contains(&intent.leg.as_str()) && intent.status == "pending"
+            }) {
+                warn!(
+                    symbol = %order.symbol,
+                    %correlation_id,
+                    "Entry of a cancelled futures leg has a leg still pending - not re-hedging"
+                );
+                continue;
+            }
+
+            let filled = |leg: OrderLeg| -> Decimal {
+                intents
+                    .iter()
+                    .filter(|intent| intent.leg == leg.as_str() && intent.status == "completed")
+                    .filter_map(|intent| intent.executed_qty)
+                    .sum()
+            };
+            // The perpetual an entry journaled a hedge on, if it wasn't hedged with spot
+            let perp_symbol = intents
+                .iter()
+                .find(|intent| intent.leg == OrderLeg::EntryPerpHedge.as_str())
+                .map(|intent| intent.symbol.as_str());
+            let hedge_leg = match perp_symbol {
+                Some(_) => OrderLeg::EntryPerpHedge,
+                None => OrderLeg::EntrySpot,
+            };
+            let hedge_filled = filled(hedge_leg);
+            let futures_filled = filled(OrderLeg::EntryFutures);
+            let hedge_side = match order.side {
+                OrderSide::Buy => OrderSide::Sell,
+                OrderSide::Sell => OrderSide::Buy,
+            };
+
+            let (unhedged_leg, hedge) = if hedge_filled > futures_filled {
+                let unhedged = self.round_quantity(hedge_filled - futures_filled, &order.symbol);
+                if unhedged <= Decimal::ZERO {
+                    continue;
+                }
+                warn!(
+                    symbol = %order.symbol,
+                    %unhedged,
+                    %correlation_id,
+                    "Re-submitting the hedge of a cancelled futures leg"
+                );
+                let hedge = self
+                    .place_futures_order_with_retry(
+                        client,
+                        &order.symbol,
+                        order.side,
+                        unhedged,
+                        3,
+                        &correlation_id,
+                        OrderLeg::EntryFutures,
+                    )
+                    .await;
+                let leg = match perp_symbol {
+                    Some(_) => "perp hedge",
+                    None => "spot",
+                };
+                (leg, hedge)
+            } else if let Some(perp_symbol) = perp_symbol {
+                let unhedged = self.round_quantity(futures_filled - hedge_filled, perp_symbol);
+                if unhedged <= Decimal::ZERO {
+                    continue;
+                }
+                warn!(
+                    symbol = %order.symbol,
+                    %perp_symbol,
+                    %unhedged,
+                    %correlation_id,
+                    "Hedging the fill of a cancelled futures leg with its perpetual"
+                );
+                let hedge = self
+                    .place_futures_order_with_retry(
+                        client,
+                        perp_symbol,
+                        hedge_side,
+                        unhedged,
+                        3,
+                        &correlation_id,
+                        OrderLeg::EntryPerpHedge,
+                    )
+                    .await;
+                ("futures", hedge)
+            } else {
+                let spot_symbol = intents
+                    .iter()
+                    .find(|intent| intent.leg == OrderLeg::EntrySpot.as_str())
+                    .map_or(order.symbol.as_str(), |intent| intent.symbol.as_str());
+                let unhedged = self.round_spot_quantity(
+                    futures_filled - hedge_filled,
+                    spot_symbol,
+                    &order.symbol,
+                );
+                if unhedged <= Decimal::ZERO {
+                    continue;
+                }
+                warn!(
+                    symbol = %order.symbol,
+                    %spot_symbol,
+                    %unhedged,
+                    %correlation_id,
+                    "Hedging the fill of a cancelled futures leg with spot"
+                );
+                let hedge = self
+                    .place_spot_margin_order(
+                        client,
+                        spot_symbol,
+                        hedge_side,
+                        unhedged,
+                        order.side == OrderSide::Sell,
+                        &correlation_id,
+                    )
+                    .await;
+                ("futures", hedge)
+            };
+
+            match hedge {
+                Ok(hedge) => hedges.push(hedge),
+                Err(e) => error!(
+                    symbol = %order.symbol,
+                    error = %e,
+                    "Failed to re-submit hedge - {} leg is unhedged",
+                    unhedged_leg
+                ),
+            }
+        }
+
+        hedges
+    }
+
+    /// Cancel every open futures and margin order this executor placed.
+    ///
+    /// Safety action for halts, shutdown and circuit-breaker trips: no resting
+    /// order may fill after the bot stopped managing its positions. Orders are
+    /// listed, cancelled and listed again until none is left, up to
+    /// `CANCEL_ALL_ATTEMPTS` rounds; orders still open after the last round are
+    /// returned in the report. Futures and margin orders are listed apart, so a
+    /// book that can't be listed doesn't keep the other's orders open; it is
+    /// reported unconfirmed. Orders without this executor's client order ID
+    /// prefix (placed by hand or by another instance) are never touched.
+    pub async fn cancel_all_open_orders(&self, client: &dyn ExchangeClient) -> CancelAllReport {
+        let mut report = CancelAllReport::default();
+
+        for attempt in 1..=CANCEL_ALL_ATTEMPTS {
+            let mut open = Vec::new();
+            let mut unlisted = 0;
+            for margin in [false, true] {
+                match self.own_open_orders(client, margin).await {
+                    Ok(orders) => open.extend(orders.into_iter().map(|order| (order, margin))),
+                    Err(e) => {
+                        warn!(attempt, margin, error = %e, "Failed to list open orders to cancel");
+                        unlisted += 1;
+                    }
+                }
+            }
+            if open.is_empty() {
+                if unlisted == 0 {
+                    return report;
+                }
+                tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+                continue;
+            }
+
+            let mut failed = unlisted;
+            for (order, margin) in open {
+                let result = if margin {
+                    client
+                        .cancel_margin_order(&order.symbol, order.order_id)
+                        .await
+                        .map(|_| ())
+                } else {
+                    client
+                        .cancel_futures_order(&order.symbol, order.order_id)
+                        .await
+                        .map(|_| ())
+                };
+                match result {
+                    Ok(()) => {
+                        info!(
+                            symbol = %order.symbol,
+                            order_id = order.order_id,
+                            client_order_id = %order.client_order_id,
+                            margin,
+                            "Cancelled open order"
+                        );
+                        report.cancelled += 1;
+                    }
+                    Err(e) => {
+                        warn!(
+                            symbol = %order.symbol,
+                            order_id = order.order_id,
+                            margin,
+                            attempt,
+                            error = %e,
+                            "Failed to cancel open order"
+                        );
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed > 0 && attempt < CANCEL_ALL_ATTEMPTS {
+                tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+            }
+        }
+
+        // Confirm the last round took
+        for (margin, book) in [(false, "futures"), (true, "margin")] {
+            match self.own_open_orders(client, margin).await {
+                Ok(orders) => report.remaining.extend(orders),
+                Err(e) => {
+                    warn!(book, error = %e, "Failed to confirm open orders were cancelled");
+                    report.unconfirmed.push(book);
+                }
+            }
+        }
+        report
+    }
+
+    /// This executor's open orders of one book: margin if `margin`, else futures.
+    async fn own_open_orders(
+        &self,
+        client: &dyn ExchangeClient,
+        margin: bool,
+    ) -> Result<Vec<OrderLookup>> {
+        let orders = if margin {
+            client.get_open_margin_orders().await?
+        } else {
+            client.get_open_futures_orders().await?
+        };
+        Ok(orders
+            .into_iter()
+            .filter(|order| order.client_order_id.starts_with(&self.order_prefix))
+            .collect())
+    }
+}
+
+/// Rounds of list-and-cancel before [`OrderExecutor::cancel_all_open_orders`] gives up.
+const CANCEL_ALL_ATTEMPTS: u32 = 3;
+
+/// Outcome of [`OrderExecutor::cancel_all_open_orders`].
+#[derive(Debug, Default)]
+pub struct CancelAllReport {
+    /// Orders cancelled
+    pub cancelled: usize,
+    /// Orders still open after the last round
+    pub remaining: Vec<OrderLookup>,
+    /// Books ("futures", "margin") that couldn't be listed to confirm
+    pub unconfirmed: Vec<&'static str>,
+}
+
+impl CancelAllReport {
+    /// Whether no order of this bot is left open.
+    pub fn confirmed(&self) -> bool {
+        self.remaining.is_empty() && self.unconfirmed.is_empty()
+    }
+}
+
+/// Cancels this bot's futures orders left open longer than the order timeout.
+///
+/// A post-only futures leg the price moved away from rests unfilled, holding
+/// margin while its spot leg goes unhedged. Each poll lists the open orders,
+/// keeps those whose client order ID starts with the bot's prefix (orders
+/// placed by hand are never touched) and cancels the ones placed longer ago
+/// than the timeout. Orders a venue lists without a placement time are aged
+/// from the poll that first saw them.
+#[derive(Debug)]
+pub struct OrderTracker {
+    /// Start of every client order ID this bot sends
+    order_prefix: String,
+    timeout: chrono::Duration,
+    /// When each open order without a placement time was first seen
+    first_seen: HashMap<i64, DateTime<Utc>>,
+}
+
+impl OrderTracker {
+    /// Tracker of orders tagged with `order_prefix`, stale after `timeout_secs`.
+    pub fn new(order_prefix: impl Into<String>, timeout_secs: u64) -> Self {
+        Self {
+            order_prefix: order_prefix.into(),
+            timeout: chrono::Duration::seconds(timeout_secs as i64),
+            first_seen: HashMap::new(),
+        }
+    }
+
+    /// Poll open futures orders and cancel the stale ones, returning the cancels.
+    ///
+    /// A failed cancel is logged and tried again next poll, unless the order
+    /// filled in between and is no longer listed.
+    pub async fn poll(
+        &mut self,
+        client: &dyn ExchangeClient,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<OrderResponse>> {
+        let open: Vec<OrderLookup> = client
+            .get_open_futures_orders()
+            .await?
+            .into_iter()
+            .filter(|order| order.client_order_id.starts_with(&self.order_prefix))
+            .collect();
+        self.first_seen
+            .retain(|order_id, _| open.iter().any(|order| order.order_id == *order_id));
+
+        let mut cancelled = Vec::new();
+        for order in open {
+            let placed_at = match DateTime::from_timestamp_millis(order.time) {
+                Some(time) if order.time > 0 => time,
+                _ => *self.first_seen.entry(order.order_id).or_insert(now),
+            };
+            let age = now - placed_at;
+            if age < self.timeout {
+                continue;
+            }
+
+            match client
+                .cancel_futures_order(&order.symbol, order.order_id)
+                .await
+            {
+                Ok(response) => {
+                    warn!(
+                        symbol = %order.symbol,
+                        order_id = order.order_id,
+                        client_order_id = %order.client_order_id,
+                        executed_qty = %order.executed_qty,
+                        age_secs = age.num_seconds(),
+                        "Cancelled order left open past the order timeout"
+                    );
+                    self.first_seen.remove(&order.order_id);
+                    cancelled.push(response);
                 }
+                Err(e) => warn!(
+                    symbol = %order.symbol,
+                    order_id = order.order_id,
+                    error = %e,
+                    "Failed to cancel stale order - retrying next poll"
+                ),
             }
         }
 
-        Err(last_error.unwrap_or_else(|| anyhow!("Unknown error")))
-    }
-
-    /// Round quantity to valid precision for the symbol.
-    fn round_quantity(&self, quantity: Decimal, symbol: &str) -> Decimal {
-        let precision = self.precisions.get(symbol).copied().unwrap_or(3);
-        quantity.round_dp(precision as u32)
-    }
-
-    /// Check if position entry should proceed based on slippage.
-    pub fn check_slippage(&self, expected_price: Decimal, actual_price: Decimal) -> bool {
-        let slippage = ((actual_price - expected_price) / expected_price).abs();
-        slippage <= self.config.slippage_tolerance
+        Ok(cancelled)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exchange::{ApiError, MockBinanceClient, SpotSymbolInfo};
+    use reqwest::StatusCode;
 
     // =========================================================================
     // Test Helpers
@@ -722,6 +2720,8 @@ mod tests {
             max_leverage: 10,
             slippage_tolerance: dec!(0.0005),
             order_timeout_secs: 30,
+            client_order_tag: "fff".to_string(),
+            ..Default::default()
         })
     }
 
@@ -734,7 +2734,900 @@ mod tests {
             leverage: 5,
             funding_rate,
             priority: 1,
+            hedge_with_holdings: false,
+        }
+    }
+
+    // =========================================================================
+    // Batched Entry Tests
+    // =========================================================================
+
+    #[tokio::test]
+    async fn test_enter_positions_validates_the_batch_as_a_whole() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        client
+            .seed_market("ETHUSDT", dec!(-0.0005), dec!(2500))
+            .await;
+        client.seed_market("SOLUSDT", dec!(0.0005), dec!(100)).await;
+        let executor = test_executor();
+        let btc = test_allocation("BTCUSDT", dec!(0.0005), dec!(5000));
+        let eth = test_allocation("ETHUSDT", dec!(-0.0005), dec!(5000));
+        let sol = test_allocation("SOLUSDT", dec!(0.0005), dec!(0));
+        // Margin for one $5,000 entry: the second would breach it with the first counted
+        let ctx = MarginContext {
+            available_balance: dec!(6000),
+            margin_balance: dec!(6000),
+            total_position_value: Decimal::ZERO,
+            position_notionals: HashMap::new(),
+            min_margin_ratio: dec!(1),
+        };
+
+        let results = executor
+            .enter_positions(
+                &client,
+                &[(&btc, dec!(50000)), (&eth, dec!(2500)), (&sol, dec!(100))],
+                Some(&ctx),
+            )
+            .await;
+        assert_eq!(results.len(), 3);
+        let results: Vec<EntryResult> = results.into_iter().map(|r| r.unwrap()).collect();
+        assert!(results[0].success);
+        assert_eq!(results[0].symbol, "BTCUSDT");
+        assert!(!results[1].success);
+        assert!(results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Margin validation failed"));
+        assert_eq!(results[2].error.as_deref(), Some("Entry quantity is zero"));
+
+        let state = client.get_state().await;
+        assert_eq!(state.positions["BTCUSDT"].futures_qty, dec!(-0.1));
+        assert_eq!(state.positions["BTCUSDT"].spot_qty, dec!(0.1));
+        assert!(!state.positions.contains_key("ETHUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_leg_is_retried_alone() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        let executor = test_executor();
+        let allocation = test_allocation("BTCUSDT", dec!(0.0005), dec!(5000));
+        let entry = PendingEntry {
+            allocation: &allocation,
+            quantity: dec!(0.1),
+            spot_side: OrderSide::Buy,
+            futures_side: OrderSide::Sell,
+            correlation_id: executor.next_correlation_id("BTCUSDT"),
+            new_position: true,
+        };
+        let order = futures_order(
+            "BTCUSDT",
+            OrderSide::Sell,
+            OrderType::Market,
+            dec!(0.1),
+            None,
+            Some("fff-lq2x9k1a-0-1".to_string()),
+        );
+        let rejected = || {
+            Err(anyhow::Error::new(ApiError::from_response(
+                "place_futures_orders",
+                StatusCode::BAD_REQUEST,
+                r#"{"code":-2019,"msg":"Margin is insufficient."}"#,
+            )))
+        };
+
+        // Refused by the batch and never on the book: sent again on its own
+        let retried = executor
+            .retry_entry_futures_order(&client, &entry, Some(order), rejected())
+            .await
+            .unwrap();
+        assert_eq!(retried.status, OrderStatus::Filled);
+        assert_eq!(retried.executed_qty, dec!(0.1));
+
+        // An order that was never sent is not retried
+        assert!(executor
+            .retry_entry_futures_order(&client, &entry, None, rejected())
+            .await
+            .is_err());
+        assert_eq!(
+            client.get_state().await.positions["BTCUSDT"].futures_qty,
+            dec!(-0.1)
+        );
+    }
+
+    // =========================================================================
+    // Order Journal Tests
+    // =========================================================================
+
+    #[test]
+    fn test_order_leg_roundtrip() {
+        for leg in OrderLeg::ALL {
+            assert_eq!(OrderLeg::parse(leg.as_str()), Some(leg));
+        }
+        assert_eq!(OrderLeg::parse("unknown"), None);
+        assert!(OrderLeg::EntrySpot.is_spot());
+        assert!(OrderLeg::CoinExitFutures.is_coin());
+        assert!(!OrderLeg::CoinExitFutures.is_spot());
+        assert!(!OrderLeg::UnwindFutures.is_spot());
+    }
+
+    #[test]
+    fn test_journal_intent_tags_orders() {
+        let mut executor = test_executor();
+        assert_eq!(
+            executor
+                .journal_intent(
+                    "fff1-0",
+                    OrderLeg::EntryFutures,
+                    "BTCUSDT",
+                    OrderSide::Sell,
+                    None
+                )
+                .unwrap(),
+            None
+        );
+
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        executor.set_instance_id("a1b2c3");
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let futures_id = executor
+            .journal_intent(
+                &correlation_id,
+                OrderLeg::EntryFutures,
+                "BTCUSDT",
+                OrderSide::Sell,
+                Some(dec!(0.1)),
+            )
+            .unwrap()
+            .unwrap();
+        let spot_id = executor
+            .journal_intent(
+                &correlation_id,
+                OrderLeg::EntrySpot,
+                "BTCUSDT",
+                OrderSide::Buy,
+                Some(dec!(0.1)),
+            )
+            .unwrap()
+            .unwrap();
+
+        // Unique per order, within Binance's 36 character limit
+        assert_ne!(futures_id, spot_id);
+        assert!(futures_id.starts_with(&format!("fffa1b2c3-{}-", correlation_id)));
+        assert!(futures_id.len() <= 36);
+
+        // Both legs are attributed to the same position
+        let futures_tag = ClientOrderTag::parse("fff", &futures_id).unwrap();
+        let spot_tag = ClientOrderTag::parse("fff", &spot_id).unwrap();
+        assert_eq!(futures_tag.instance_id, "a1b2c3");
+        assert_eq!(futures_tag.position_id, spot_tag.position_id);
+        assert!(correlation_id.starts_with(futures_tag.position_id));
+
+        let journal = executor.journal.as_ref().unwrap();
+        let pending = journal.get_incomplete_order_intents().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].side, "SELL");
+        assert_eq!(pending[1].leg, "entry_spot");
+    }
+
+    #[test]
+    fn test_order_updates_resolve_journal() {
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let journal_order = |leg| {
+            executor
+                .journal_intent(
+                    &correlation_id,
+                    leg,
+                    "BTCUSDT",
+                    OrderSide::Sell,
+                    Some(dec!(0.1)),
+                )
+                .unwrap()
+                .unwrap()
+        };
+        let filled_id = journal_order(OrderLeg::EntryFutures);
+        let rejected_id = journal_order(OrderLeg::ExitFutures);
+
+        let update = |client_order_id: &str, status: &str, filled: &str| -> OrderUpdate {
+            serde_json::from_value(serde_json::json!({
+                "s": "BTCUSDT", "c": client_order_id, "i": 42, "x": "TRADE", "X": status,
+                "S": "SELL", "o": "MARKET", "q": "0.1", "z": filled, "ap": "62000"
+            }))
+            .unwrap()
+        };
+
+        // A partial fill leaves the order pending
+        assert!(!executor
+            .apply_order_update(&update(&filled_id, "PARTIALLY_FILLED", "0.04"))
+            .unwrap());
+        assert!(executor
+            .apply_order_update(&update(&filled_id, "FILLED", "0.1"))
+            .unwrap());
+        assert!(executor
+            .apply_order_update(&update(&rejected_id, "REJECTED", "0"))
+            .unwrap());
+
+        let journal = executor.journal.as_ref().unwrap();
+        assert!(journal.get_incomplete_order_intents().unwrap().is_empty());
+        let intents = journal
+            .get_latest_order_intents(&[OrderLeg::EntryFutures.as_str()])
+            .unwrap();
+        assert_eq!(intents[0].status, "completed");
+        assert_eq!(intents[0].order_id, Some(42));
+        assert_eq!(intents[0].executed_qty, Some(dec!(0.1)));
+    }
+
+    #[tokio::test]
+    async fn test_order_tracker_cancels_own_stale_orders() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let now = Utc::now();
+        let rest = |client_order_id: &str, placed_at: DateTime<Utc>| {
+            let order = futures_order(
+                "BTCUSDT",
+                OrderSide::Sell,
+                OrderType::Limit,
+                dec!(0.1),
+                Some(dec!(50000)),
+                Some(client_order_id.to_string()),
+            );
+            let client = &client;
+            async move { client.seed_resting_order(&order, placed_at).await }
+        };
+        let ago = |secs| now - chrono::Duration::seconds(secs);
+        let stale = rest("fffa1b2c3-lq2x9k1a-0-1", ago(90)).await;
+        let fresh = rest("fffa1b2c3-lq2x9k1a-0-2", ago(10)).await;
+        // Another instance's and a hand-placed order, however old, are left alone
+        rest("fff9f8e7d-lq2xbbbb-0-1", ago(3600)).await;
+        rest("web_8Fh3kLq", ago(3600)).await;
+
+        let mut executor = test_executor();
+        executor.set_instance_id("a1b2c3");
+        let mut tracker = executor.order_tracker();
+
+        let cancelled = tracker.poll(&client, now).await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, stale.order_id);
+        assert_eq!(cancelled[0].status, OrderStatus::Canceled);
+
+        let open = client.get_open_futures_orders().await.unwrap();
+        assert_eq!(open.len(), 3);
+        assert!(open.iter().any(|order| order.order_id == fresh.order_id));
+
+        // The fresh order goes stale in turn
+        let later = now + chrono::Duration::seconds(25);
+        let cancelled = tracker.poll(&client, later).await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, fresh.order_id);
+    }
+
+    #[tokio::test]
+    async fn test_order_tracker_ages_untimed_orders_from_first_sight() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let order = futures_order(
+            "ETHUSDT",
+            OrderSide::Buy,
+            OrderType::Limit,
+            dec!(1),
+            Some(dec!(2300)),
+            Some("fff-lq2x9k1a-0-1".to_string()),
+        );
+        // Placed at the epoch, reported like a venue that gives no placement time
+        client
+            .seed_resting_order(&order, DateTime::UNIX_EPOCH)
+            .await;
+
+        let mut tracker = OrderTracker::new("fff", 30);
+        let now = Utc::now();
+        assert!(tracker.poll(&client, now).await.unwrap().is_empty());
+        assert!(tracker
+            .poll(&client, now + chrono::Duration::seconds(29))
+            .await
+            .unwrap()
+            .is_empty());
+        let cancelled = tracker
+            .poll(&client, now + chrono::Duration::seconds(30))
+            .await
+            .unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert!(tracker.first_seen.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rehedge_partially_filled_pair() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        executor.set_instance_id("a1b2c3");
+
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let journal_order = |leg, side| {
+            executor
+                .journal_intent(&correlation_id, leg, "BTCUSDT", side, Some(dec!(0.1)))
+                .unwrap()
+                .unwrap()
+        };
+        let response = |client_order_id: &str, side, status, executed_qty| OrderResponse {
+            order_id: 7,
+            symbol: "BTCUSDT".to_string(),
+            status,
+            client_order_id: client_order_id.to_string(),
+            price: dec!(50000),
+            avg_price: dec!(50000),
+            orig_qty: dec!(0.1),
+            executed_qty,
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            update_time: 0,
+        };
+
+        // The spot leg filled in full, the futures leg only 0.04 before going stale
+        let spot_id = journal_order(OrderLeg::EntrySpot, OrderSide::Buy);
+        let spot = response(&spot_id, OrderSide::Buy, OrderStatus::Filled, dec!(0.1));
+        executor.journal_outcome(Some(&spot_id), &Ok(spot));
+        let futures_id = journal_order(OrderLeg::EntryFutures, OrderSide::Sell);
+        let cancelled = [response(
+            &futures_id,
+            OrderSide::Sell,
+            OrderStatus::Canceled,
+            dec!(0.04),
+        )];
+
+        let hedges = executor.rehedge_cancelled_orders(&client, &cancelled).await;
+        assert_eq!(hedges.len(), 1);
+        assert_eq!(hedges[0].side, OrderSide::Sell);
+        assert_eq!(hedges[0].executed_qty, dec!(0.06));
+
+        // The hedge is journaled with the entry, which is now fully hedged
+        assert!(hedges[0]
+            .client_order_id
+            .starts_with(&format!("fffa1b2c3-{}-", correlation_id)));
+        assert!(executor
+            .rehedge_cancelled_orders(&client, &cancelled)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rehedge_futures_fill_with_spot() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        executor.set_instance_id("a1b2c3");
+
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let journal_order = |leg, side| {
+            executor
+                .journal_intent(&correlation_id, leg, "BTCUSDT", side, Some(dec!(0.1)))
+                .unwrap()
+                .unwrap()
+        };
+        let response = |client_order_id: &str, side, status, executed_qty| OrderResponse {
+            order_id: 7,
+            symbol: "BTCUSDT".to_string(),
+            status,
+            client_order_id: client_order_id.to_string(),
+            price: dec!(50000),
+            avg_price: dec!(50000),
+            orig_qty: dec!(0.1),
+            executed_qty,
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            update_time: 0,
+        };
+
+        // The futures leg filled 0.1 before going stale, the spot leg only 0.04
+        let spot_id = journal_order(OrderLeg::EntrySpot, OrderSide::Buy);
+        let spot = response(
+            &spot_id,
+            OrderSide::Buy,
+            OrderStatus::PartiallyFilled,
+            dec!(0.04),
+        );
+        executor.journal_outcome(Some(&spot_id), &Ok(spot));
+        let futures_id = journal_order(OrderLeg::EntryFutures, OrderSide::Sell);
+        let cancelled = [response(
+            &futures_id,
+            OrderSide::Sell,
+            OrderStatus::Canceled,
+            dec!(0.1),
+        )];
+
+        // The short is hedged by buying the missing spot
+        let hedges = executor.rehedge_cancelled_orders(&client, &cancelled).await;
+        assert_eq!(hedges.len(), 1);
+        assert_eq!(hedges[0].side, OrderSide::Buy);
+        assert_eq!(hedges[0].executed_qty, dec!(0.06));
+        assert_eq!(
+            client.get_state().await.positions["BTCUSDT"].spot_qty,
+            dec!(0.06)
+        );
+
+        assert!(hedges[0]
+            .client_order_id
+            .starts_with(&format!("fffa1b2c3-{}-", correlation_id)));
+        assert!(executor
+            .rehedge_cancelled_orders(&client, &cancelled)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rehedge_skips_legs_hedged_by_holdings() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        executor.set_instance_id("a1b2c3");
+
+        // A short against declared holdings has no spot leg of its own
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let futures_id = executor
+            .journal_intent(
+                &correlation_id,
+                OrderLeg::HoldingsEntryFutures,
+                "BTCUSDT",
+                OrderSide::Sell,
+                Some(dec!(0.1)),
+            )
+            .unwrap()
+            .unwrap();
+        let cancelled = [OrderResponse {
+            order_id: 7,
+            symbol: "BTCUSDT".to_string(),
+            status: OrderStatus::Canceled,
+            client_order_id: futures_id,
+            price: dec!(50000),
+            avg_price: dec!(50000),
+            orig_qty: dec!(0.1),
+            executed_qty: dec!(0.04),
+            side: OrderSide::Sell,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            update_time: 0,
+        }];
+
+        // The held coins hedge the partial fill: no spot is bought on top
+        let hedges = executor.rehedge_cancelled_orders(&client, &cancelled).await;
+        assert!(hedges.is_empty());
+        assert!(!client.get_state().await.positions.contains_key("BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_rehedge_futures_fill_on_perp_hedge() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        client
+            .seed_market("BTCUSDC", dec!(0.0001), dec!(50000))
+            .await;
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        executor.set_instance_id("a1b2c3");
+
+        let correlation_id = executor.next_correlation_id("BTCUSDT");
+        let journal_order = |leg, symbol: &str, side| {
+            executor
+                .journal_intent(&correlation_id, leg, symbol, side, Some(dec!(0.1)))
+                .unwrap()
+                .unwrap()
+        };
+        let response = |client_order_id: &str, symbol: &str, side, status, executed_qty| {
+            OrderResponse {
+                order_id: 7,
+                symbol: symbol.to_string(),
+                status,
+                client_order_id: client_order_id.to_string(),
+                price: dec!(50000),
+                avg_price: dec!(50000),
+                orig_qty: dec!(0.1),
+                executed_qty,
+                side,
+                order_type: OrderType::Limit,
+                time_in_force: Some(TimeInForce::Gtc),
+                update_time: 0,
+            }
+        };
+
+        // The USDC perp hedge bought 0.04, the USDT short filled 0.1 before going stale
+        let perp_id = journal_order(OrderLeg::EntryPerpHedge, "BTCUSDC", OrderSide::Buy);
+        let perp = response(
+            &perp_id,
+            "BTCUSDC",
+            OrderSide::Buy,
+            OrderStatus::PartiallyFilled,
+            dec!(0.04),
+        );
+        executor.journal_outcome(Some(&perp_id), &Ok(perp));
+        let futures_id = journal_order(OrderLeg::EntryFutures, "BTCUSDT", OrderSide::Sell);
+        let cancelled = [response(
+            &futures_id,
+            "BTCUSDT",
+            OrderSide::Sell,
+            OrderStatus::Canceled,
+            dec!(0.1),
+        )];
+
+        // The missing 0.06 is bought on the perp, not on spot
+        let hedges = executor.rehedge_cancelled_orders(&client, &cancelled).await;
+        assert_eq!(hedges.len(), 1);
+        assert_eq!(hedges[0].symbol, "BTCUSDC");
+        assert_eq!(hedges[0].side, OrderSide::Buy);
+        assert_eq!(hedges[0].executed_qty, dec!(0.06));
+        let state = client.get_state().await;
+        assert_eq!(state.positions["BTCUSDC"].futures_qty, dec!(0.06));
+        assert!(!state.positions.contains_key("BTCUSDT"));
+
+        assert!(executor
+            .rehedge_cancelled_orders(&client, &cancelled)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_order_is_looked_up_before_resending() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let executor = test_executor();
+        let order = |client_order_id: Option<&str>| {
+            futures_order(
+                "BTCUSDT",
+                OrderSide::Sell,
+                OrderType::Market,
+                dec!(0.1),
+                None,
+                client_order_id.map(str::to_string),
+            )
+        };
+        let error = |status, body| {
+            anyhow::Error::new(ApiError::from_response("place_futures_order", status, body))
+        };
+        let timeout = error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            r#"{"code":-1007,"msg":"Timeout waiting for response from backend server."}"#,
+        );
+        let rejected = error(
+            StatusCode::BAD_REQUEST,
+            r#"{"code":-2019,"msg":"Margin is insufficient."}"#,
+        );
+
+        // The timed-out order reached the book: it is returned, not resent
+        let sent = order(Some("fff-lq2x9k1a-0-1"));
+        let resting = client.seed_resting_order(&sent, Utc::now()).await;
+        let settled = executor
+            .settle_failed_order(&client, &sent, &timeout)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(settled.order_id, resting.order_id);
+        assert_eq!(settled.orig_qty, dec!(0.1));
+
+        // Never received, or refused outright: safe to send again
+        let lost = order(Some("fff-lq2x9k1a-0-2"));
+        assert!(executor
+            .settle_failed_order(&client, &lost, &timeout)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(executor
+            .settle_failed_order(&client, &lost, &rejected)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Without a client order ID the outcome can't be checked
+        let unsettled = executor
+            .settle_failed_order(&client, &order(None), &timeout)
+            .await
+            .unwrap_err();
+        assert!(UnsettledOrder::find(&unsettled).is_some());
+    }
+
+    #[test]
+    fn test_looked_up_fill_price() {
+        let found = |executed_qty, avg_price, cummulative_quote_qty| OrderLookup {
+            order_id: 1,
+            symbol: "BTCUSDT".to_string(),
+            status: OrderStatus::Filled,
+            client_order_id: "fff-lq2x9k1a-0-1".to_string(),
+            executed_qty,
+            avg_price,
+            cummulative_quote_qty,
+            side: OrderSide::Buy,
+            time: 0,
+        };
+        let response = |found| looked_up_response(found, OrderType::Market, None, None, None);
+
+        // Futures report the average price, spot and margin the quote amount
+        let futures = response(found(dec!(0.1), Some(dec!(50000)), None)).unwrap();
+        assert_eq!(futures.avg_price, dec!(50000));
+        let margin = response(found(dec!(0.1), None, Some(dec!(5001)))).unwrap();
+        assert_eq!(margin.avg_price, dec!(50010));
+
+        // Nothing executed needs no price; a fill without one is unsettled
+        let unfilled = response(found(Decimal::ZERO, None, None)).unwrap();
+        assert_eq!(unfilled.avg_price, Decimal::ZERO);
+        let unpriced = response(found(dec!(0.1), Some(Decimal::ZERO), None)).unwrap_err();
+        assert!(UnsettledOrder::find(&unpriced).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unsettled_entry_reconciled_against_positions() {
+        let client = MockBinanceClient::new(dec!(100000));
+        client
+            .seed_market("BTCUSDT", dec!(0.0005), dec!(50000))
+            .await;
+        let executor = test_executor();
+        let unsettled = UnsettledOrder {
+            symbol: "BTCUSDT".to_string(),
+            client_order_id: Some("fff-lq2x9k1a-0-1".to_string()),
+            reason: "BTCUSDT order fff-lq2x9k1a-0-1 outcome unknown".to_string(),
+        };
+
+        // No position: the order never executed
+        assert!(executor
+            .reconcile_unsettled_entry(&client, &unsettled, OrderSide::Sell, true)
+            .await
+            .unwrap()
+            .is_none());
+
+        // The short it opened is its fill, to be hedged
+        client
+            .place_futures_order(&futures_order(
+                "BTCUSDT",
+                OrderSide::Sell,
+                OrderType::Market,
+                dec!(0.1),
+                None,
+                None,
+            ))
+            .await
+            .unwrap();
+        let fill = executor
+            .reconcile_unsettled_entry(&client, &unsettled, OrderSide::Sell, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fill.executed_qty, dec!(0.1));
+        assert_eq!(fill.side, OrderSide::Sell);
+        assert!(fill.avg_price > Decimal::ZERO);
+
+        // A position on the other side, or one being topped up, can't tell
+        assert!(executor
+            .reconcile_unsettled_entry(&client, &unsettled, OrderSide::Buy, true)
+            .await
+            .is_err());
+        assert!(executor
+            .reconcile_unsettled_entry(&client, &unsettled, OrderSide::Sell, false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_open_orders_clears_futures_and_margin() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let order = |client_order_id: &str| {
+            futures_order(
+                "BTCUSDT",
+                OrderSide::Buy,
+                OrderType::Limit,
+                dec!(0.1),
+                Some(dec!(49000)),
+                Some(client_order_id.to_string()),
+            )
+        };
+        let now = Utc::now();
+        client
+            .seed_resting_order(&order("fffa1b2c3-lq2x9k1a-0-1"), now)
+            .await;
+        client
+            .seed_resting_margin_order(&order("fffa1b2c3-lq2x9k1a-0-2"), now)
+            .await;
+        // Another instance's and a hand-placed order stay open
+        client
+            .seed_resting_order(&order("fff9f8e7d-lq2xbbbb-0-1"), now)
+            .await;
+        client
+            .seed_resting_margin_order(&order("web_8Fh3kLq"), now)
+            .await;
+
+        let mut executor = test_executor();
+        executor.set_instance_id("a1b2c3");
+
+        let report = executor.cancel_all_open_orders(&client).await;
+        assert_eq!(report.cancelled, 2);
+        assert!(report.confirmed());
+        assert_eq!(client.get_open_futures_orders().await.unwrap().len(), 1);
+        assert_eq!(client.get_open_margin_orders().await.unwrap().len(), 1);
+
+        // Nothing left to cancel
+        let report = executor.cancel_all_open_orders(&client).await;
+        assert_eq!(report.cancelled, 0);
+        assert!(report.confirmed());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_open_orders_survives_a_margin_listing_outage() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let order = |client_order_id: &str| {
+            futures_order(
+                "BTCUSDT",
+                OrderSide::Buy,
+                OrderType::Limit,
+                dec!(0.1),
+                Some(dec!(49000)),
+                Some(client_order_id.to_string()),
+            )
+        };
+        let now = Utc::now();
+        client
+            .seed_resting_order(&order("fffa1b2c3-lq2x9k1a-0-1"), now)
+            .await;
+        client
+            .seed_resting_margin_order(&order("fffa1b2c3-lq2x9k1a-0-2"), now)
+            .await;
+        client.set_margin_listing_down(true);
+
+        let mut executor = test_executor();
+        executor.set_instance_id("a1b2c3");
+
+        // The futures order is cancelled all the same; margin is left unconfirmed
+        let report = executor.cancel_all_open_orders(&client).await;
+        assert_eq!(report.cancelled, 1);
+        assert!(client.get_open_futures_orders().await.unwrap().is_empty());
+        assert_eq!(report.unconfirmed, vec!["margin"]);
+        assert!(!report.confirmed());
+
+        client.set_margin_listing_down(false);
+        let report = executor.cancel_all_open_orders(&client).await;
+        assert_eq!(report.cancelled, 1);
+        assert!(report.confirmed());
+    }
+
+    #[test]
+    fn test_foreign_instance_orders() {
+        let order = |client_order_id: &str| OrderLookup {
+            order_id: 1,
+            symbol: "BTCUSDT".to_string(),
+            status: OrderStatus::Filled,
+            client_order_id: client_order_id.to_string(),
+            executed_qty: dec!(0.1),
+            avg_price: None,
+            cummulative_quote_qty: None,
+            side: OrderSide::Sell,
+            time: 0,
+        };
+        let orders = vec![
+            order("fffa1b2c3-lq2x9k1a-0-1"),
+            order("fff9f8e7d-lq2x9k1a-0-1"),
+            order("fffdeadbe-lq2xbbbb-2-3"),
+            // Untagged bot orders, other strategy tags and other software are ignored
+            order("fff1700000000000-0-1"),
+            order("ffarm9f8e7d-lq2x9k1a-0-1"),
+            order("web_8Fh3kLq"),
+        ];
+
+        let foreign = foreign_instance_orders(&orders, "fff", &["a1b2c3", "deadbe"]);
+        assert_eq!(foreign.len(), 1);
+        assert_eq!(
+            ClientOrderTag::parse("fff", &foreign[0].client_order_id),
+            Some(ClientOrderTag {
+                instance_id: "9f8e7d",
+                position_id: "lq2x9k1a",
+            })
+        );
+    }
+
+    #[test]
+    fn test_position_id_spans_entry_to_exit() {
+        let executor = test_executor();
+        let entry = executor.next_correlation_id("BTCUSDT");
+        let reduce = executor.next_correlation_id("BTCUSDT");
+        let other = executor.next_correlation_id("ETHUSDT");
+
+        let position = |correlation_id: &str| correlation_id.split('-').next().unwrap().to_string();
+        assert_eq!(position(&entry), position(&reduce));
+        assert_ne!(entry, reduce);
+        assert_eq!(position(&entry).len(), 8);
+
+        // Released on exit; the next entry opens a new position
+        executor.release_position_id("BTCUSDT");
+        assert!(!executor.has_position_id("BTCUSDT"));
+        assert!(executor.has_position_id("ETHUSDT"));
+        assert_eq!(position(&other), executor.position_id("ETHUSDT"));
+    }
+
+    #[test]
+    fn test_restore_position_ids_from_journal() {
+        let journal = PersistenceManager::new(":memory:").unwrap();
+        for (i, (correlation_id, symbol, leg)) in [
+            ("aaaa-0", "BTCUSDT", OrderLeg::EntryFutures),
+            ("aaaa-0", "BTC", OrderLeg::EntrySpot),
+            ("aaaa-3", "BTCUSDT", OrderLeg::ReduceFutures),
+            ("bbbb-5", "ETHUSDT", OrderLeg::EntryFutures),
+            ("bbbb-7", "ETHUSDT", OrderLeg::ExitFutures),
+            // Hyperliquid legs are journaled under their coin
+            ("cccc-0", "SOL", OrderLeg::SpreadEntryHyperliquid),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            journal
+                .journal_order_intent(
+                    &format!("fffa1b2c3-{}-{}", correlation_id, i),
+                    correlation_id,
+                    symbol,
+                    leg.as_str(),
+                    "SELL",
+                    dec!(1),
+                )
+                .unwrap();
         }
+
+        let mut executor = test_executor();
+        executor.set_journal(journal);
+        assert_eq!(executor.restore_position_ids().unwrap(), 1);
+        assert_eq!(executor.position_id("BTCUSDT"), "aaaa");
+        assert!(!executor.has_position_id("ETHUSDT"));
+        assert!(!executor.has_position_id("SOL"));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_journal_leaves_hyperliquid_legs() {
+        let client = MockBinanceClient::new(dec!(100000));
+        let mut executor = test_executor();
+        executor.set_journal(PersistenceManager::new(":memory:").unwrap());
+        let correlation_id = executor.next_correlation_id("SOLUSDT");
+        let binance_id = executor
+            .journal_intent(
+                &correlation_id,
+                OrderLeg::SpreadEntryFutures,
+                "SOLUSDT",
+                OrderSide::Sell,
+                Some(dec!(1)),
+            )
+            .unwrap()
+            .unwrap();
+        let hyperliquid_id = executor
+            .journal_intent(
+                &correlation_id,
+                OrderLeg::SpreadEntryHyperliquid,
+                "SOL",
+                OrderSide::Buy,
+                Some(dec!(1)),
+            )
+            .unwrap()
+            .unwrap();
+
+        // Binance never saw its leg; the Hyperliquid leg isn't Binance's to resolve
+        let resolved = executor.reconcile_journal(&client).await.unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].intent.client_order_id, binance_id);
+        let pending = executor
+            .journal
+            .as_ref()
+            .unwrap()
+            .get_incomplete_order_intents()
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].client_order_id, hyperliquid_id);
     }
 
     // =========================================================================
@@ -807,6 +3700,35 @@ mod tests {
         assert_eq!(rounded, dec!(1.23457));
     }
 
+    #[test]
+    fn test_round_spot_quantity() {
+        let mut executor = test_executor();
+        executor.set_precisions(HashMap::from([("BTCUSDT".to_string(), 3)]));
+        let info: SpotSymbolInfo = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT", "baseAsset": "BTC", "quoteAsset": "USDT",
+                "status": "TRADING", "isMarginTradingAllowed": true,
+                "filters": [
+                    {"filterType": "PRICE_FILTER", "tickSize": "0.01000000"},
+                    {"filterType": "LOT_SIZE", "minQty": "0.00001000", "stepSize": "0.00001000"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(info.quantity_precision(), Some(5));
+        executor.set_spot_precisions(HashMap::from([(
+            info.symbol.clone(),
+            info.quantity_precision().unwrap(),
+        )]));
+
+        // Spot rounds to its lot size, finer than the futures precision
+        let rounded = executor.round_spot_quantity(dec!(0.123456), "BTCUSDT", "BTCUSDT");
+        assert_eq!(rounded, dec!(0.12346));
+        // Without a spot lot size, the futures precision applies
+        let rounded = executor.round_spot_quantity(dec!(0.123456), "ETHUSDT", "BTCUSDT");
+        assert_eq!(rounded, dec!(0.123));
+    }
+
     #[test]
     fn test_round_quantity_zero_precision() {
         let mut executor = test_executor();
@@ -985,6 +3907,8 @@ mod tests {
             max_leverage: 10,
             slippage_tolerance: dec!(0.001),
             order_timeout_secs: 60,
+            client_order_tag: "fff".to_string(),
+            ..Default::default()
         };
 
         let executor = OrderExecutor::new(config);
@@ -1007,6 +3931,90 @@ mod tests {
         assert_eq!(executor.precisions.get("ETHUSDT"), Some(&4u8));
     }
 
+    #[test]
+    fn test_venue_rules_from_exchange_info() {
+        let info: crate::exchange::FuturesExchangeInfo = serde_json::from_str(
+            r#"{"symbols": [
+                {"symbol": "BTCUSDT", "quantityPrecision": 3, "pricePrecision": 1,
+                 "contractType": "PERPETUAL", "status": "TRADING", "baseAsset": "BTC",
+                 "quoteAsset": "USDT", "filters": [
+                    {"filterType": "LOT_SIZE", "minQty": "0.001", "stepSize": "0.001"},
+                    {"filterType": "MIN_NOTIONAL", "notional": "100"}
+                 ]},
+                {"symbol": "ETHUSDT", "quantityPrecision": 3, "pricePrecision": 2,
+                 "contractType": "PERPETUAL", "status": "TRADING", "baseAsset": "ETH",
+                 "quoteAsset": "USDT"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(info.symbols[0].min_notional(), Some(dec!(100)));
+        assert_eq!(info.symbols[1].min_notional(), None);
+
+        let mut executor = test_executor();
+        // Nothing loaded: nothing to check against
+        assert_eq!(
+            executor.venue_rejection("SOLUSDT", dec!(1), dec!(150)),
+            None
+        );
+
+        executor.set_precisions(
+            info.symbols
+                .iter()
+                .map(|s| (s.symbol.clone(), s.quantity_precision))
+                .collect(),
+        );
+        executor.set_min_notionals(
+            info.symbols
+                .iter()
+                .filter_map(|s| Some((s.symbol.clone(), s.min_notional()?)))
+                .collect(),
+        );
+        assert_eq!(
+            executor.venue_rejection("BTCUSDT", dec!(0.002), dec!(60000)),
+            None
+        );
+        // Testnet minimums are higher than production's
+        assert!(executor
+            .venue_rejection("BTCUSDT", dec!(0.001), dec!(60000))
+            .unwrap()
+            .contains("below the venue minimum of 100"));
+        assert_eq!(
+            executor.venue_rejection("ETHUSDT", dec!(0.01), dec!(3000)),
+            None
+        );
+        // Symbols the venue doesn't list are refused
+        assert!(executor
+            .venue_rejection("SOLUSDT", dec!(1), dec!(150))
+            .unwrap()
+            .contains("not listed"));
+    }
+
+    #[test]
+    fn test_entry_quantity_limited_to_existing_holdings() {
+        use crate::config::HeldAsset;
+
+        let mut executor = test_executor();
+        executor.set_existing_holdings(ExistingHoldingsConfig {
+            enabled: true,
+            assets: vec![HeldAsset {
+                asset: "BTC".to_string(),
+                quantity: dec!(0.12349),
+            }],
+            ..Default::default()
+        });
+
+        let mut allocation = test_allocation("BTCUSDT", dec!(0.001), dec!(10000));
+        allocation.hedge_with_holdings = true;
+
+        // Held quantity is rounded down to the default 3 decimals
+        assert_eq!(executor.entry_quantity(&allocation, dec!(0.2)), dec!(0.123));
+        assert_eq!(executor.entry_quantity(&allocation, dec!(0.05)), dec!(0.05));
+
+        // Regular entries are unaffected
+        allocation.hedge_with_holdings = false;
+        assert_eq!(executor.entry_quantity(&allocation, dec!(0.2)), dec!(0.2));
+    }
+
     // =========================================================================
     // Margin Context Tests (Pre-Entry Validation)
     // =========================================================================
@@ -1020,6 +4028,7 @@ mod tests {
             available_balance: margin_balance, // Simplified
             margin_balance,
             total_position_value,
+            position_notionals: HashMap::new(),
             min_margin_ratio,
         }
     }
@@ -1104,4 +4113,124 @@ mod tests {
         let result = ctx.validate_position_entry(dec!(4200));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_entry_precheck_enforces_group_caps() {
+        let mut executor = test_executor();
+        executor.set_notional_caps(NotionalCapsConfig {
+            max_per_symbol: dec!(20_000),
+            symbols: vec![],
+            groups: vec![crate::config::GroupNotionalCap {
+                name: "memes".to_string(),
+                symbols: vec!["DOGEUSDT".to_string(), "PEPEUSDT".to_string()],
+                max_notional: dec!(10_000),
+            }],
+        });
+        let mut ctx = test_margin_context(dec!(1_000_000), dec!(8_000), dec!(1));
+        ctx.position_notionals = HashMap::from([("PEPEUSDT".to_string(), dec!(8_000))]);
+
+        // Within the symbol cap, but 8k of the group's 10k is already held
+        let doge = test_allocation("DOGEUSDT", dec!(0.0005), dec!(3_000));
+        let rejected = executor.entry_precheck(&doge, &ctx).unwrap();
+        assert_eq!(
+            rejected.error.as_deref(),
+            Some("Notional cap exceeded: 3000 > 2000")
+        );
+
+        let doge = test_allocation("DOGEUSDT", dec!(0.0005), dec!(2_000));
+        assert!(executor.entry_precheck(&doge, &ctx).is_none());
+    }
+
+    // =========================================================================
+    // Segregated Funds Tests (Pre-Entry Wallet Check)
+    // =========================================================================
+
+    #[test]
+    fn test_segregated_funds_checks_each_wallet() {
+        // Plenty of futures margin, but the margin account cannot buy the spot leg
+        let funds = SegregatedFunds::new(dec!(5000), dec!(5000), dec!(900), dec!(900), dec!(0.10));
+
+        // $1000 at 5x: futures needs 200 + 500 reserve, spot needs 1000 + 90 reserve
+        let long_spot = test_allocation("BTCUSDT", dec!(0.0005), dec!(1000));
+        assert_eq!(
+            funds.shortfalls(&long_spot),
+            vec![FundsShortfall {
+                wallet: FundsWallet::CrossMargin,
+                required: dec!(1090),
+                available: dec!(900),
+            }]
+        );
+
+        // Shorting spot only needs half the notional as margin equity
+        let short_spot = test_allocation("BTCUSDT", dec!(-0.0005), dec!(1000));
+        assert!(funds.shortfalls(&short_spot).is_empty());
+
+        // Held coins form the long leg, so only the futures wallet is checked
+        let mut held = long_spot.clone();
+        held.hedge_with_holdings = true;
+        assert!(funds.shortfalls(&held).is_empty());
+    }
+
+    #[test]
+    fn test_segregated_funds_commit_reduces_headroom() {
+        let mut funds =
+            SegregatedFunds::new(dec!(1000), dec!(1000), dec!(5000), dec!(5000), dec!(0.10));
+        let allocation = test_allocation("ETHUSDT", dec!(-0.0005), dec!(2000));
+
+        // Futures: 400 + 100 reserve out of 1000
+        assert!(funds.shortfalls(&allocation).is_empty());
+        funds.commit(&allocation);
+
+        // Only 600 left: a second entry of the same size still fits, a third does not
+        assert!(funds.shortfalls(&allocation).is_empty());
+        funds.commit(&allocation);
+        let shortfalls = funds.shortfalls(&allocation);
+        assert_eq!(shortfalls.len(), 1);
+        assert_eq!(shortfalls[0].wallet, FundsWallet::Futures);
+        assert_eq!(shortfalls[0].available, dec!(200));
+    }
+
+    #[test]
+    fn test_unified_funds_cover_both_legs_together() {
+        let mut funds = SegregatedFunds::unified(dec!(2000), dec!(2000), dec!(0.10));
+
+        // $1000 long spot at 5x: 200 futures + 1000 spot + 200 reserve from one pool
+        let long_spot = test_allocation("BTCUSDT", dec!(0.0005), dec!(1000));
+        assert!(funds.shortfalls(&long_spot).is_empty());
+        funds.commit(&long_spot);
+        assert_eq!(
+            funds.shortfalls(&long_spot),
+            vec![FundsShortfall {
+                wallet: FundsWallet::Unified,
+                required: dec!(1400),
+                available: dec!(800),
+            }]
+        );
+
+        // Shorting spot needs half the notional: 200 + 500 + 200 reserve
+        let short_spot = test_allocation("ETHUSDT", dec!(-0.0005), dec!(1000));
+        assert_eq!(funds.shortfalls(&short_spot)[0].required, dec!(900));
+    }
+
+    #[test]
+    fn test_segregated_funds_flash_release() {
+        let mut funds =
+            SegregatedFunds::new(dec!(1000), dec!(1000), dec!(5000), dec!(5000), dec!(0.10));
+        // Futures: 920 + 100 reserve exceeds 1000
+        let meme = test_allocation("MEMEUSDT", dec!(-0.0005), dec!(4600));
+        let btc = test_allocation("BTCUSDT", dec!(-0.0005), dec!(4600));
+        assert_eq!(funds.shortfalls(&meme).len(), 1);
+
+        // Half the reserve released to MEME only
+        let now = Utc::now();
+        funds.release_reserve(&FlashRelease {
+            symbol: "MEMEUSDT".to_string(),
+            funding_rate: dec!(0.012),
+            release: dec!(0.05),
+            started_at: now,
+            expires_at: now + chrono::Duration::hours(8),
+        });
+        assert!(funds.shortfalls(&meme).is_empty());
+        assert_eq!(funds.shortfalls(&btc)[0].required, dec!(1020));
+    }
 }
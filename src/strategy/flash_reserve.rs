@@ -0,0 +1,244 @@
+//! Flash reserve releases.
+//!
+//! `capital.reserve_buffer` keeps part of equity free for margin safety. When one
+//! symbol pays exceptional funding (at least `flash_reserve.min_funding_rate` per
+//! 8h), that symbol alone may deploy `release_fraction` of the reserve for
+//! `duration_hours`. The release ends early once the rate falls back below the
+//! threshold. Only one event runs at a time and the next waits out a cooldown.
+//! After an event the rebalancer shrinks the position back to its normal target,
+//! and an alert is raised if the reserve isn't whole again within the grace period.
+
+use crate::config::FlashReserveConfig;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fmt;
+
+/// Part of the reserve lent to one symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlashRelease {
+    pub symbol: String,
+    /// 8h-equivalent funding rate (absolute) that triggered the release
+    pub funding_rate: Decimal,
+    /// Share of equity released
+    pub release: Decimal,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl fmt::Display for FlashRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {:.3}%/8h: {:.1}% of equity from the reserve until {}",
+            self.symbol,
+            self.funding_rate * dec!(100),
+            self.release * dec!(100),
+            self.expires_at.format("%H:%M UTC")
+        )
+    }
+}
+
+/// Why a release ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashEnd {
+    Expired,
+    /// The rate fell below the flash threshold
+    RateFaded,
+}
+
+/// Change in the release state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlashReserveEvent {
+    Started(FlashRelease),
+    Ended {
+        release: FlashRelease,
+        reason: FlashEnd,
+    },
+}
+
+/// Decides when part of the reserve may be deployed, and checks it comes back.
+#[derive(Debug, Clone)]
+pub struct FlashReserve {
+    config: FlashReserveConfig,
+    reserve_buffer: Decimal,
+    active: Option<FlashRelease>,
+    /// When the last release ended; the next waits for the cooldown
+    last_ended: Option<DateTime<Utc>>,
+    /// Deadline for the reserve to be whole again after the last release
+    restore_by: Option<DateTime<Utc>>,
+}
+
+impl FlashReserve {
+    pub fn new(config: FlashReserveConfig, reserve_buffer: Decimal) -> Self {
+        Self {
+            config,
+            reserve_buffer,
+            active: None,
+            last_ended: None,
+            restore_by: None,
+        }
+    }
+
+    /// Release in effect, if any.
+    pub fn active(&self) -> Option<&FlashRelease> {
+        self.active.as_ref()
+    }
+
+    /// Start or end a release from this cycle's 8h-equivalent funding rates.
+    ///
+    /// An active release ends at expiry or once its symbol's rate is seen below
+    /// the threshold. Otherwise, after the cooldown, the highest qualifying rate
+    /// starts a new one.
+    pub fn update<'a>(
+        &mut self,
+        rates: impl IntoIterator<Item = (&'a str, Decimal)>,
+        now: DateTime<Utc>,
+    ) -> Option<FlashReserveEvent> {
+        if !self.config.enabled {
+            return None;
+        }
+        let threshold = self.config.min_funding_rate;
+        let rates: Vec<(&str, Decimal)> = rates
+            .into_iter()
+            .map(|(symbol, rate)| (symbol, rate.abs()))
+            .collect();
+
+        if let Some(release) = &self.active {
+            let reason = if now >= release.expires_at {
+                FlashEnd::Expired
+            } else if rates
+                .iter()
+                .any(|(symbol, rate)| *symbol == release.symbol && *rate < threshold)
+            {
+                FlashEnd::RateFaded
+            } else {
+                return None;
+            };
+            let release = self.active.take()?;
+            self.last_ended = Some(now);
+            self.restore_by = Some(now + Duration::hours(self.config.restore_grace_hours as i64));
+            return Some(FlashReserveEvent::Ended { release, reason });
+        }
+
+        let cooldown = Duration::hours(self.config.cooldown_hours as i64);
+        if self.last_ended.is_some_and(|ended| now < ended + cooldown) {
+            return None;
+        }
+        let (symbol, rate) = rates
+            .into_iter()
+            .filter(|(_, rate)| *rate >= threshold)
+            .max_by_key(|(_, rate)| *rate)?;
+        let release = FlashRelease {
+            symbol: symbol.to_string(),
+            funding_rate: rate,
+            release: self.reserve_buffer * self.config.release_fraction,
+            started_at: now,
+            expires_at: now + Duration::hours(self.config.duration_hours as i64),
+        };
+        self.active = Some(release.clone());
+        Some(FlashReserveEvent::Started(release))
+    }
+
+    /// Reserve shortfall (share of equity) once the restoration deadline passed.
+    ///
+    /// `free` is the share of equity not locked as margin. The check stops once
+    /// the reserve is whole again; while it isn't, the shortfall is reported
+    /// once per grace period.
+    pub fn check_restored(&mut self, free: Decimal, now: DateTime<Utc>) -> Option<Decimal> {
+        let deadline = self.restore_by?;
+        if free >= self.reserve_buffer {
+            self.restore_by = None;
+            return None;
+        }
+        if now < deadline {
+            return None;
+        }
+        self.restore_by =
+            Some(now + Duration::hours(self.config.restore_grace_hours.max(1) as i64));
+        Some(self.reserve_buffer - free)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 20, hour, 0, 0).unwrap()
+    }
+
+    fn flash_reserve() -> FlashReserve {
+        FlashReserve::new(
+            FlashReserveConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            dec!(0.10),
+        )
+    }
+
+    #[test]
+    fn test_release_lifecycle() {
+        let mut reserve = flash_reserve();
+        assert!(reserve.update([("BTCUSDT", dec!(0.002))], at(0)).is_none());
+
+        // The best qualifying rate gets half the 10% reserve for 8h
+        let Some(FlashReserveEvent::Started(release)) = reserve.update(
+            [("ALTUSDT", dec!(-0.012)), ("MEMEUSDT", dec!(0.015))],
+            at(1),
+        ) else {
+            panic!("expected a release");
+        };
+        assert_eq!(release.symbol, "MEMEUSDT");
+        assert_eq!(release.release, dec!(0.05));
+        assert_eq!(release.expires_at, at(9));
+
+        // One event at a time; a missing rate doesn't end it
+        assert!(reserve.update([("ALTUSDT", dec!(0.02))], at(2)).is_none());
+        assert_eq!(reserve.active().unwrap().symbol, "MEMEUSDT");
+        assert!(matches!(
+            reserve.update([], at(9)),
+            Some(FlashReserveEvent::Ended {
+                reason: FlashEnd::Expired,
+                ..
+            })
+        ));
+
+        // Cooldown before the next event, which ends once the rate fades
+        assert!(reserve.update([("ALTUSDT", dec!(0.02))], at(20)).is_none());
+        let next = Utc.with_ymd_and_hms(2024, 3, 21, 9, 0, 0).unwrap();
+        assert!(reserve.update([("ALTUSDT", dec!(0.02))], next).is_some());
+        assert!(matches!(
+            reserve.update([("ALTUSDT", dec!(0.004))], next + Duration::hours(1)),
+            Some(FlashReserveEvent::Ended {
+                reason: FlashEnd::RateFaded,
+                ..
+            })
+        ));
+
+        // Disabled: never releases
+        let mut disabled = FlashReserve::new(FlashReserveConfig::default(), dec!(0.10));
+        assert!(disabled.update([("ALTUSDT", dec!(0.05))], at(0)).is_none());
+    }
+
+    #[test]
+    fn test_restoration_check() {
+        let mut reserve = flash_reserve();
+        reserve.update([("ALTUSDT", dec!(0.02))], at(0));
+        // Dipping in is expected while the release runs
+        assert!(reserve.check_restored(dec!(0.05), at(4)).is_none());
+
+        reserve.update([], at(8));
+        // Two hours' grace, then reported once per grace period
+        assert!(reserve.check_restored(dec!(0.06), at(9)).is_none());
+        assert_eq!(reserve.check_restored(dec!(0.06), at(10)), Some(dec!(0.04)));
+        assert!(reserve.check_restored(dec!(0.07), at(11)).is_none());
+        assert_eq!(reserve.check_restored(dec!(0.08), at(12)), Some(dec!(0.02)));
+
+        // Whole again: the check stops
+        assert!(reserve.check_restored(dec!(0.10), at(13)).is_none());
+        assert!(reserve.check_restored(dec!(0.01), at(20)).is_none());
+    }
+}
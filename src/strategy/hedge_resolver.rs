@@ -0,0 +1,293 @@
+//! Hedge availability and cost of carry per symbol.
+//!
+//! A USDT perpetual is normally hedged with the opposite position in its spot
+//! margin pair. Assets without one, or that can't be borrowed when the hedge has
+//! to be short, can still be neutralized with an opposite perpetual: the asset's
+//! USDC-margined perp on Binance or its perp on another venue.
+//!
+//! When several hedges exist their carry is compared per 8h: borrow interest for
+//! a short spot hedge, funding paid by a perp hedge, and the premium paid to put a
+//! perp hedge on, spread over the holding horizon. The cheapest is chosen, the
+//! spot pair on ties.
+//!
+//! Perp hedges are only resolved with `pair_selection.futures_hedges` enabled. The
+//! executor trades spot hedges only: pairs resolved to a perp hedge are reported
+//! but never entered.
+
+use crate::exchange::{FundingRate, HedgeCarry, HedgeInstrument, HedgeOption, Venue};
+use rust_decimal::Decimal;
+use rustc_hash::FxHashMap;
+use std::collections::HashMap;
+
+/// Hours per comparison period.
+const CARRY_PERIOD_HOURS: u32 = 8;
+
+/// A USDC-margined perpetual that can hedge a base asset.
+#[derive(Debug, Clone)]
+struct UsdcPerp {
+    symbol: String,
+    funding_rate: Decimal,
+    mark_price: Option<Decimal>,
+}
+
+/// Resolves the instrument hedging each symbol.
+#[derive(Debug, Clone, Default)]
+pub struct HedgeResolver {
+    futures_hedges: bool,
+    /// Holding period the entry basis of a perp hedge is spread over
+    basis_horizon_hours: u32,
+    /// USDC-margined perpetuals per base asset, looked up for every scanned symbol
+    usdc_perps: FxHashMap<String, UsdcPerp>,
+    /// Perpetual funding (per 8h) per venue and coin, ordered by venue name
+    venue_perps: Vec<(Venue, HashMap<String, Decimal>)>,
+}
+
+impl HedgeResolver {
+    /// Resolver that considers perp hedges when `futures_hedges` is set.
+    pub fn new(futures_hedges: bool, basis_horizon_hours: u32) -> Self {
+        Self {
+            futures_hedges,
+            basis_horizon_hours,
+            ..Default::default()
+        }
+    }
+
+    /// Register the USDC-margined perpetuals among Binance funding rates.
+    pub fn with_usdc_perps(mut self, funding_rates: &[FundingRate]) -> Self {
+        for rate in funding_rates {
+            if let Some(base) = rate.symbol.strip_suffix("USDC") {
+                self.usdc_perps.insert(
+                    base.to_string(),
+                    UsdcPerp {
+                        symbol: rate.symbol.clone(),
+                        funding_rate: rate.funding_rate,
+                        mark_price: rate.mark_price,
+                    },
+                );
+            }
+        }
+        self
+    }
+
+    /// Register perpetual funding rates (per 8h, keyed by coin) on another venue.
+    pub fn with_venue_perps(mut self, venue: Venue, rates: &HashMap<String, Decimal>) -> Self {
+        self.venue_perps
+            .retain(|(registered, _)| *registered != venue);
+        self.venue_perps.push((venue, rates.clone()));
+        self.venue_perps
+            .sort_by_cached_key(|(venue, _)| venue.to_string());
+        self
+    }
+
+    /// Whether any perp can hedge `base_asset`.
+    pub fn has_perp_hedge(&self, base_asset: &str) -> bool {
+        self.futures_hedges
+            && (self.usdc_perps.contains_key(base_asset)
+                || self
+                    .venue_perps
+                    .iter()
+                    .any(|(_, rates)| rates.contains_key(base_asset)))
+    }
+
+    /// Hedges for `base_asset` when its futures leg earns `funding`, cheapest first.
+    ///
+    /// `spot_borrow_cost` is the borrow interest per 8h of the spot hedge, `None`
+    /// when the spot pair can't hedge the position.
+    pub fn options(
+        &self,
+        base_asset: &str,
+        funding: &FundingRate,
+        spot_borrow_cost: Option<Decimal>,
+    ) -> Vec<HedgeOption> {
+        let mut options = Vec::new();
+        if let Some(borrow) = spot_borrow_cost {
+            options.push(HedgeOption {
+                instrument: HedgeInstrument::SpotMargin,
+                carry: HedgeCarry {
+                    borrow,
+                    ..Default::default()
+                },
+            });
+        }
+        if self.futures_hedges {
+            if let Some(perp) = self.usdc_perps.get(base_asset) {
+                let instrument = HedgeInstrument::UsdcPerp {
+                    symbol: perp.symbol.clone(),
+                    funding_rate: perp.funding_rate,
+                };
+                options.push(self.perp_option(instrument, funding, perp.mark_price));
+            }
+            for (venue, rates) in &self.venue_perps {
+                if let Some(funding_rate) = rates.get(base_asset) {
+                    let instrument = HedgeInstrument::VenuePerp {
+                        venue: *venue,
+                        funding_rate: *funding_rate,
+                    };
+                    options.push(self.perp_option(instrument, funding, None));
+                }
+            }
+        }
+        // Stable: the spot hedge stays first on ties
+        options.sort_by_key(|option| option.carry.total());
+        options
+    }
+
+    /// Cheapest hedge for `base_asset`; see [`HedgeResolver::options`].
+    pub fn resolve(
+        &self,
+        base_asset: &str,
+        funding: &FundingRate,
+        spot_borrow_cost: Option<Decimal>,
+    ) -> Option<HedgeOption> {
+        self.options(base_asset, funding, spot_borrow_cost)
+            .into_iter()
+            .next()
+    }
+
+    fn perp_option(
+        &self,
+        instrument: HedgeInstrument,
+        funding: &FundingRate,
+        hedge_mark: Option<Decimal>,
+    ) -> HedgeOption {
+        let carry = HedgeCarry {
+            borrow: Decimal::ZERO,
+            funding: instrument.funding_carry(funding.funding_rate),
+            basis: self.basis_carry(funding, hedge_mark),
+        };
+        HedgeOption { instrument, carry }
+    }
+
+    /// Entry premium of the hedge over the futures leg per 8h of the horizon.
+    ///
+    /// A long hedge bought above the futures mark pays the premium; a short hedge
+    /// sold above it collects it. Zero when either mark is unknown.
+    fn basis_carry(&self, funding: &FundingRate, hedge_mark: Option<Decimal>) -> Decimal {
+        let (Some(futures_mark), Some(hedge_mark)) = (funding.mark_price, hedge_mark) else {
+            return Decimal::ZERO;
+        };
+        if futures_mark <= Decimal::ZERO || self.basis_horizon_hours == 0 {
+            return Decimal::ZERO;
+        }
+        let premium = (hedge_mark - futures_mark) / futures_mark;
+        let periods = Decimal::from(self.basis_horizon_hours) / Decimal::from(CARRY_PERIOD_HOURS);
+        let premium = if funding.funding_rate >= Decimal::ZERO {
+            premium
+        } else {
+            -premium
+        };
+        premium / periods
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn funding(symbol: &str, rate: Decimal, mark: Option<Decimal>) -> FundingRate {
+        FundingRate {
+            symbol: symbol.to_string(),
+            funding_rate: rate,
+            funding_time: 0,
+            mark_price: mark,
+        }
+    }
+
+    fn resolver(futures_hedges: bool) -> HedgeResolver {
+        HedgeResolver::new(futures_hedges, 72)
+            .with_usdc_perps(&[
+                funding("BTCUSDT", dec!(0.0003), None),
+                funding("NEWUSDC", dec!(0.0001), None),
+                funding("ALTUSDC", dec!(0.0002), None),
+                funding("FLATUSDC", Decimal::ZERO, None),
+            ])
+            .with_venue_perps(
+                Venue::Hyperliquid,
+                &HashMap::from([
+                    ("ALT".to_string(), dec!(-0.0001)),
+                    ("HLONLY".to_string(), dec!(0.00005)),
+                ]),
+            )
+    }
+
+    #[test]
+    fn test_cheapest_carry_wins() {
+        let resolver = resolver(true);
+        let alt_long = funding("ALTUSDT", dec!(0.001), None);
+
+        // Long spot hedge carries nothing, but the Hyperliquid perp pays longs
+        let options = resolver.options("ALT", &alt_long, Some(Decimal::ZERO));
+        assert_eq!(options.len(), 3);
+        assert_eq!(
+            options[0].instrument,
+            HedgeInstrument::VenuePerp {
+                venue: Venue::Hyperliquid,
+                funding_rate: dec!(-0.0001)
+            }
+        );
+        assert_eq!(options[0].carry.total(), dec!(-0.0001));
+        assert!(options[1].instrument.is_spot());
+
+        // Short hedge for negative funding: borrowing 0.05% loses to the USDC perp
+        let alt_short = funding("ALTUSDT", dec!(-0.001), None);
+        let hedge = resolver
+            .resolve("ALT", &alt_short, Some(dec!(0.0005)))
+            .unwrap();
+        assert_eq!(hedge.instrument.to_string(), "ALTUSDC perp");
+        assert_eq!(hedge.carry.funding, dec!(-0.0002));
+
+        // Ties go to spot
+        let flat = funding("FLATUSDT", dec!(0.001), None);
+        let hedge = resolver
+            .resolve("FLAT", &flat, Some(Decimal::ZERO))
+            .unwrap();
+        assert!(hedge.instrument.is_spot());
+
+        assert!(resolver.has_perp_hedge("HLONLY"));
+        assert!(!resolver.has_perp_hedge("BTC"));
+        assert_eq!(resolver.resolve("BTC", &alt_long, None), None);
+    }
+
+    #[test]
+    fn test_basis_spread_over_horizon() {
+        let resolver = HedgeResolver::new(true, 72).with_usdc_perps(&[funding(
+            "ALTUSDC",
+            Decimal::ZERO,
+            Some(dec!(100.09)),
+        )]);
+
+        // Long hedge 0.09% above the futures mark, over 9 periods
+        let long = resolver
+            .resolve(
+                "ALT",
+                &funding("ALTUSDT", dec!(0.001), Some(dec!(100))),
+                None,
+            )
+            .unwrap();
+        assert_eq!(long.carry.basis, dec!(0.0001));
+        // A short hedge collects the premium
+        let short = resolver
+            .resolve(
+                "ALT",
+                &funding("ALTUSDT", dec!(-0.001), Some(dec!(100))),
+                None,
+            )
+            .unwrap();
+        assert_eq!(short.carry.basis, dec!(-0.0001));
+        // Unknown futures mark: no basis
+        let unknown = resolver
+            .resolve("ALT", &funding("ALTUSDT", dec!(0.001), None), None)
+            .unwrap();
+        assert_eq!(unknown.carry.basis, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_perp_hedges_disabled() {
+        let resolver = resolver(false);
+        let alt = funding("ALTUSDT", dec!(0.001), None);
+        assert!(!resolver.has_perp_hedge("ALT"));
+        assert_eq!(resolver.resolve("ALT", &alt, None), None);
+        assert_eq!(resolver.options("ALT", &alt, Some(Decimal::ZERO)).len(), 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! Periodic review of the hedge each position holds.
+//!
+//! Borrow rates and funding move, so the hedge that was cheapest at entry may not
+//! stay that way. Each review compares the carry of the held hedge with the
+//! options available now and recommends a switch once the cost ordering flips by
+//! more than the configured threshold. A recommendation is made once per flip and
+//! cleared when the held hedge is the cheapest again.
+
+use crate::exchange::{HedgeInstrument, HedgeOption};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// A cheaper hedge for a held position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HedgeSwitch {
+    pub symbol: String,
+    /// Hedge held now
+    pub from: HedgeOption,
+    /// Cheapest hedge available
+    pub to: HedgeOption,
+}
+
+impl HedgeSwitch {
+    /// Carry saved per 8h by switching, as a fraction of notional.
+    pub fn savings(&self) -> Decimal {
+        self.from.carry.total() - self.to.carry.total()
+    }
+}
+
+/// Tracks switch recommendations per held position.
+#[derive(Debug)]
+pub struct HedgeSwitchMonitor {
+    /// Saving per 8h required before recommending a switch
+    threshold: Decimal,
+    /// Hedge last recommended per symbol
+    recommended: HashMap<String, HedgeInstrument>,
+}
+
+impl HedgeSwitchMonitor {
+    pub fn new(threshold: Decimal) -> Self {
+        Self {
+            threshold,
+            recommended: HashMap::new(),
+        }
+    }
+
+    /// Compare the hedge `symbol` holds with `options` (cheapest first).
+    ///
+    /// Returns a switch the first time another hedge is cheaper by more than the
+    /// threshold. Nothing is returned when the held hedge can't be priced.
+    pub fn review(
+        &mut self,
+        symbol: &str,
+        held: &HedgeInstrument,
+        options: &[HedgeOption],
+    ) -> Option<HedgeSwitch> {
+        let current = options
+            .iter()
+            .find(|option| option.instrument.same_instrument(held))?;
+        let cheapest = options.first()?;
+        if cheapest.instrument.same_instrument(held)
+            || current.carry.total() - cheapest.carry.total() <= self.threshold
+        {
+            self.recommended.remove(symbol);
+            return None;
+        }
+        if self
+            .recommended
+            .get(symbol)
+            .is_some_and(|recommended| recommended.same_instrument(&cheapest.instrument))
+        {
+            return None;
+        }
+
+        self.recommended
+            .insert(symbol.to_string(), cheapest.instrument.clone());
+        Some(HedgeSwitch {
+            symbol: symbol.to_string(),
+            from: current.clone(),
+            to: cheapest.clone(),
+        })
+    }
+
+    /// Forget positions that are no longer held.
+    pub fn retain_held(&mut self, held: &[String]) {
+        self.recommended.retain(|symbol, _| held.contains(symbol));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::HedgeCarry;
+    use rust_decimal_macros::dec;
+
+    fn spot(borrow: Decimal) -> HedgeOption {
+        HedgeOption {
+            instrument: HedgeInstrument::SpotMargin,
+            carry: HedgeCarry {
+                borrow,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn usdc_perp(funding: Decimal) -> HedgeOption {
+        HedgeOption {
+            instrument: HedgeInstrument::UsdcPerp {
+                symbol: "ALTUSDC".to_string(),
+                funding_rate: -funding,
+            },
+            carry: HedgeCarry {
+                funding,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn sorted(mut options: Vec<HedgeOption>) -> Vec<HedgeOption> {
+        options.sort_by_key(|option| option.carry.total());
+        options
+    }
+
+    #[test]
+    fn test_switch_recommended_once_per_flip() {
+        let mut monitor = HedgeSwitchMonitor::new(dec!(0.00005));
+        let held = HedgeInstrument::SpotMargin;
+
+        // Spot is cheapest
+        let options = sorted(vec![spot(dec!(0.0001)), usdc_perp(dec!(0.0002))]);
+        assert!(monitor.review("ALTUSDT", &held, &options).is_none());
+
+        // Perp cheaper, but within the threshold
+        let options = sorted(vec![spot(dec!(0.0001)), usdc_perp(dec!(0.00006))]);
+        assert!(monitor.review("ALTUSDT", &held, &options).is_none());
+
+        // Borrow rate jumps: the ordering flips
+        let options = sorted(vec![spot(dec!(0.0004)), usdc_perp(dec!(0.0001))]);
+        let switch = monitor.review("ALTUSDT", &held, &options).unwrap();
+        assert!(switch.from.instrument.is_spot());
+        assert_eq!(switch.to.instrument.to_string(), "ALTUSDC perp");
+        assert_eq!(switch.savings(), dec!(0.0003));
+        // Recommended once, even as the perp's rate moves
+        let options = sorted(vec![spot(dec!(0.0004)), usdc_perp(dec!(0.00005))]);
+        assert!(monitor.review("ALTUSDT", &held, &options).is_none());
+
+        // Flips back, then again: recommended anew
+        let options = sorted(vec![spot(dec!(0.0001)), usdc_perp(dec!(0.0002))]);
+        assert!(monitor.review("ALTUSDT", &held, &options).is_none());
+        let options = sorted(vec![spot(dec!(0.0004)), usdc_perp(dec!(0.0001))]);
+        assert!(monitor.review("ALTUSDT", &held, &options).is_some());
+
+        // The held hedge must be priced
+        assert!(monitor
+            .review("ALTUSDT", &held, &[usdc_perp(dec!(0.0001))])
+            .is_none());
+
+        monitor.retain_held(&[]);
+        assert!(monitor.recommended.is_empty());
+    }
+}
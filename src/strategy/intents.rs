@@ -0,0 +1,232 @@
+//! Entry intent registry to prevent duplicate entries across trading cycles.
+//!
+//! An intent is registered before any entry order is sent and stays pending until
+//! the position is confirmed on the exchange or the entry is explicitly cancelled.
+//! While an intent is pending, later scans that rediscover the same opportunity
+//! must not submit another entry for that symbol.
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// Lifecycle state of an entry intent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntentState {
+    /// Registered, orders not yet acknowledged
+    Pending,
+    /// Orders acknowledged, waiting for the position to show up
+    Submitted,
+}
+
+/// A pending entry for a single symbol.
+#[derive(Debug, Clone)]
+pub struct EntryIntent {
+    pub symbol: String,
+    /// Target position size in USDT
+    pub target_size_usdt: Decimal,
+    /// Funding rate that motivated the entry
+    pub funding_rate: Decimal,
+    pub state: IntentState,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Tracks in-flight entries keyed by symbol.
+#[derive(Debug, Default)]
+pub struct EntryIntentRegistry {
+    intents: HashMap<String, EntryIntent>,
+}
+
+impl EntryIntentRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an entry intent for a symbol.
+    ///
+    /// Returns `false` without modifying the registry if an intent is already
+    /// in flight for the symbol.
+    pub fn register(
+        &mut self,
+        symbol: &str,
+        target_size_usdt: Decimal,
+        funding_rate: Decimal,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if self.intents.contains_key(symbol) {
+            debug!(symbol = %symbol, "Entry intent already in flight");
+            return false;
+        }
+
+        self.intents.insert(
+            symbol.to_string(),
+            EntryIntent {
+                symbol: symbol.to_string(),
+                target_size_usdt,
+                funding_rate,
+                state: IntentState::Pending,
+                created_at: now,
+            },
+        );
+        true
+    }
+
+    /// Whether an entry is in flight for the symbol.
+    pub fn is_pending(&self, symbol: &str) -> bool {
+        self.intents.contains_key(symbol)
+    }
+
+    /// Get the intent for a symbol, if any.
+    pub fn get(&self, symbol: &str) -> Option<&EntryIntent> {
+        self.intents.get(symbol)
+    }
+
+    /// Mark the entry orders for a symbol as acknowledged by the exchange.
+    pub fn mark_submitted(&mut self, symbol: &str) {
+        if let Some(intent) = self.intents.get_mut(symbol) {
+            intent.state = IntentState::Submitted;
+        }
+    }
+
+    /// Remove the intent once the fill is confirmed.
+    pub fn confirm_fill(&mut self, symbol: &str) -> Option<EntryIntent> {
+        let intent = self.intents.remove(symbol);
+        if intent.is_some() {
+            debug!(symbol = %symbol, "Entry intent confirmed");
+        }
+        intent
+    }
+
+    /// Remove the intent because the entry was abandoned.
+    pub fn cancel(&mut self, symbol: &str) -> Option<EntryIntent> {
+        let intent = self.intents.remove(symbol);
+        if intent.is_some() {
+            info!(symbol = %symbol, "Entry intent cancelled");
+        }
+        intent
+    }
+
+    /// Confirm every intent whose position is now visible on the exchange.
+    ///
+    /// `positions` maps symbol to current position size; any non-zero size counts
+    /// as a confirmed fill. Intents that never reached `Submitted` are confirmed too,
+    /// since an errored entry may still have left a position behind. Returns the
+    /// confirmed symbols.
+    pub fn reconcile(&mut self, positions: &HashMap<String, Decimal>) -> Vec<String> {
+        let confirmed: Vec<String> = self
+            .intents
+            .values()
+            .filter(|intent| {
+                positions
+                    .get(&intent.symbol)
+                    .is_some_and(|size| !size.is_zero())
+            })
+            .map(|intent| intent.symbol.clone())
+            .collect();
+
+        for symbol in &confirmed {
+            self.confirm_fill(symbol);
+        }
+        confirmed
+    }
+
+    /// Intents that have been in flight longer than `max_age`.
+    pub fn stale(&self, max_age: Duration, now: DateTime<Utc>) -> Vec<&EntryIntent> {
+        self.intents
+            .values()
+            .filter(|intent| now - intent.created_at > max_age)
+            .collect()
+    }
+
+    /// Number of intents in flight.
+    pub fn len(&self) -> usize {
+        self.intents.len()
+    }
+
+    /// Whether no intents are in flight.
+    pub fn is_empty(&self) -> bool {
+        self.intents.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_register_rejects_duplicate_symbol() {
+        let mut registry = EntryIntentRegistry::new();
+        let now = Utc::now();
+
+        assert!(registry.register("BTCUSDT", dec!(1000), dec!(0.001), now));
+        assert!(!registry.register("BTCUSDT", dec!(2000), dec!(0.002), now));
+        assert!(registry.register("ETHUSDT", dec!(1000), dec!(0.001), now));
+
+        assert_eq!(registry.len(), 2);
+        // The original intent is kept
+        assert_eq!(
+            registry.get("BTCUSDT").unwrap().target_size_usdt,
+            dec!(1000)
+        );
+    }
+
+    #[test]
+    fn test_intent_persists_until_position_visible() {
+        let mut registry = EntryIntentRegistry::new();
+        registry.register("BTCUSDT", dec!(1000), dec!(0.001), Utc::now());
+
+        // Submitted but not yet visible on the exchange
+        registry.mark_submitted("BTCUSDT");
+        assert_eq!(
+            registry.get("BTCUSDT").unwrap().state,
+            IntentState::Submitted
+        );
+        assert!(registry.reconcile(&HashMap::new()).is_empty());
+        assert!(registry.is_pending("BTCUSDT"));
+
+        // A zero-size entry does not count as a fill
+        let mut positions = HashMap::new();
+        positions.insert("BTCUSDT".to_string(), Decimal::ZERO);
+        assert!(registry.reconcile(&positions).is_empty());
+
+        // Position shows up: intent is confirmed and removed
+        positions.insert("BTCUSDT".to_string(), dec!(1000));
+        assert_eq!(registry.reconcile(&positions), vec!["BTCUSDT".to_string()]);
+        assert!(!registry.is_pending("BTCUSDT"));
+    }
+
+    #[test]
+    fn test_cancel_allows_reentry() {
+        let mut registry = EntryIntentRegistry::new();
+        let now = Utc::now();
+        registry.register("BTCUSDT", dec!(1000), dec!(0.001), now);
+
+        assert!(registry.cancel("BTCUSDT").is_some());
+        assert!(registry.cancel("BTCUSDT").is_none());
+        assert!(registry.register("BTCUSDT", dec!(1000), dec!(0.001), now));
+    }
+
+    #[test]
+    fn test_stale_intents() {
+        let mut registry = EntryIntentRegistry::new();
+        let now = Utc::now();
+        registry.register(
+            "OLDUSDT",
+            dec!(1000),
+            dec!(0.001),
+            now - Duration::minutes(45),
+        );
+        registry.register(
+            "NEWUSDT",
+            dec!(1000),
+            dec!(0.001),
+            now - Duration::minutes(5),
+        );
+
+        let stale = registry.stale(Duration::minutes(30), now);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].symbol, "OLDUSDT");
+    }
+}
@@ -4,14 +4,90 @@
 //! - Market scanning and opportunity detection
 //! - Capital allocation across positions
 //! - Order execution and position management
+//! - Entry intent tracking across cycles
+//! - Micro-timing of entries from the futures book
 //! - Hedge rebalancing to maintain delta neutrality
+//! - Attribution of exchange orders to positions
+//! - Cross-venue funding spread detection and trading
+//! - Implementation shortfall of entries and exits
+//! - Partial closes at funding milestones
+//! - Hedge instrument resolution by cost of carry, with periodic switch reviews
+//! - Borrow cost versus funding income per asset
+//! - Notional-weighted portfolio funding APR
+//! - Proportional position reductions requested by risk checks
+//! - Symbol quality learned from the realized carry of past positions
+//! - Capital utilization following margin headroom
+//! - Temporary reserve releases for exceptional funding
+//! - Batched position reductions ordered by urgency
+//! - Capital capacity of the candidate pairs before slippage erodes the edge
+//! - Funding farming on COIN-margined perpetuals against held coins
+//! - Risk-free rate returns are compared against
+//! - Scheduled rebalancing of held positions toward target weights
 
 mod allocator;
+mod attribution;
+mod borrow_cost;
+mod capacity;
+mod coin_margined;
+mod cross_venue;
+mod entry_timing;
+mod execution_quality;
 mod executor;
+mod flash_reserve;
+mod hedge_resolver;
+mod hedge_switch;
+mod intents;
+mod portfolio_apr;
+mod portfolio_rebalance;
 mod rebalancer;
+mod reduction_plan;
+mod risk_actions;
+mod risk_free;
 mod scanner;
+mod symbol_quality;
+mod take_profit;
+mod utilization;
 
 pub use allocator::{CapitalAllocator, PositionAllocation, PositionReduction};
-pub use executor::{EntryResult, MarginContext, OrderExecutor};
-pub use rebalancer::{HedgeRebalancer, RebalanceAction, RebalanceConfig, RebalanceResult};
-pub use scanner::MarketScanner;
+pub use attribution::{AttributedOrder, AttributionReport};
+pub use borrow_cost::{
+    AssetBorrowCost, AssetWeek, BorrowCostReport, BorrowRecommendation, CAP_COST_RATIO,
+    EXCLUDE_COST_RATIO,
+};
+pub use capacity::{CapacityEstimate, CapacityInput, CapacityLimit, CapacityTerms, PairCapacity};
+pub use coin_margined::{
+    coin_margined_entries, coin_margined_exits, CoinMarginedEntry, CoinMarginedPosition,
+};
+pub use cross_venue::{
+    entry_notional, funding_spreads, spread_exits, CrossVenueMonitor, FundingSpread, SpreadPosition,
+};
+pub use entry_timing::{
+    book_imbalance, timing_gain_bps, EntryTimer, EntryTimingSummary, TimedEntry, TimingDecision,
+};
+pub use execution_quality::{
+    leg_fill, leg_shortfall, EntryCostModel, ExecutionKind, ExecutionQualityReport,
+    ExecutionShortfall, WeeklyShortfall, COST_MODEL_WEEKS, TAKER_FEE_RATE,
+};
+pub use executor::{
+    foreign_instance_orders, CancelAllReport, ClientOrderTag, EntryResult, FundsShortfall,
+    FundsWallet, JournalReconciliation, MarginContext, OrderExecutor, OrderLeg, OrderTracker,
+    SegregatedFunds,
+};
+pub use flash_reserve::{FlashEnd, FlashRelease, FlashReserve, FlashReserveEvent};
+pub use hedge_resolver::HedgeResolver;
+pub use hedge_switch::{HedgeSwitch, HedgeSwitchMonitor};
+pub use intents::{EntryIntent, EntryIntentRegistry, IntentState};
+pub use portfolio_apr::{HeldFunding, PortfolioApr, PositionApr, RoundTripFees};
+pub use portfolio_rebalance::{PortfolioRebalance, PortfolioRebalancer};
+pub use rebalancer::{
+    ActionEstimate, HedgeRebalancer, RebalanceAction, RebalanceConfig, RebalanceResult,
+};
+pub use reduction_plan::{ReductionPlan, ReductionUrgency};
+pub use risk_actions::{
+    PositionReductionResult, ReductionDirective, ReductionScope, RiskActionExecutor,
+};
+pub use risk_free::{fetch_treasury_bill_rate, RiskFreeRate};
+pub use scanner::{volume_floor, MarketData, MarketScanner, ScanCache};
+pub use symbol_quality::{SymbolQuality, SymbolRecord};
+pub use take_profit::{TakeProfitMonitor, TakeProfitTrigger};
+pub use utilization::{UtilizationController, UtilizationDecision, UtilizationLimit};
@@ -0,0 +1,231 @@
+//! Portfolio funding APR: the headline yield of the book.
+//!
+//! Every cycle each held position's latest predicted funding rate is annualized
+//! with its settlement schedule and signed by the side the position holds, so a
+//! rate that flipped since entry counts against it. A short spot hedge also pays
+//! borrow interest, estimated from the asset's hourly borrow rate, and every
+//! position pays fees to enter and exit, spread over the expected hold. Positions
+//! are weighted by entry notional.
+
+use crate::exchange::FundingSchedule;
+use crate::persistence::PersistedFundingApr;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+const HOURS_PER_YEAR: Decimal = dec!(8760);
+
+/// A held position and its latest predicted funding.
+#[derive(Debug, Clone)]
+pub struct HeldFunding {
+    pub symbol: String,
+    /// Entry notional (USDT)
+    pub notional: Decimal,
+    /// Latest predicted rate per settlement
+    pub funding_rate: Decimal,
+    pub schedule: FundingSchedule,
+    /// Whether the futures leg is short (entered on positive funding)
+    pub short_futures: bool,
+    /// Hourly borrow rate of the base asset, when known
+    pub borrow_rate: Option<Decimal>,
+}
+
+/// Round-trip trading cost of a position, spread over its expected hold.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoundTripFees {
+    /// Cost of entering, as a fraction of notional; exits are assumed to cost the same
+    pub entry_cost_rate: Decimal,
+    /// Hours a position is expected to be held
+    pub holding_hours: u32,
+}
+
+impl RoundTripFees {
+    /// Entry and exit cost per year held; zero without a holding period.
+    pub fn annualized(&self) -> Decimal {
+        if self.holding_hours == 0 {
+            return Decimal::ZERO;
+        }
+        self.entry_cost_rate * Decimal::TWO * HOURS_PER_YEAR / Decimal::from(self.holding_hours)
+    }
+}
+
+/// Annualized funding and costs of one position.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionApr {
+    pub symbol: String,
+    pub notional: Decimal,
+    /// Funding earned at the predicted rate (negative when the position pays)
+    pub funding_apr: Decimal,
+    /// Amortized round-trip fees, plus borrow interest of a short spot hedge
+    pub cost_apr: Decimal,
+}
+
+impl PositionApr {
+    pub fn from_held(held: &HeldFunding, fees: &RoundTripFees) -> Self {
+        let annual = held.funding_rate * held.schedule.periods_per_year();
+        let (funding_apr, borrow_apr) = if held.short_futures {
+            (annual, Decimal::ZERO)
+        } else {
+            // Long futures are hedged by borrowing and selling the base asset
+            let borrow = held.borrow_rate.unwrap_or_default() * HOURS_PER_YEAR;
+            (-annual, borrow)
+        };
+        Self {
+            symbol: held.symbol.clone(),
+            notional: held.notional,
+            funding_apr,
+            cost_apr: borrow_apr + fees.annualized(),
+        }
+    }
+
+    pub fn net_apr(&self) -> Decimal {
+        self.funding_apr - self.cost_apr
+    }
+}
+
+/// Notional-weighted funding APR of all held positions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PortfolioApr {
+    pub positions: Vec<PositionApr>,
+}
+
+impl PortfolioApr {
+    pub fn compute(held: &[HeldFunding], fees: &RoundTripFees) -> Self {
+        Self {
+            positions: held
+                .iter()
+                .map(|held| PositionApr::from_held(held, fees))
+                .collect(),
+        }
+    }
+
+    /// Total entry notional (USDT).
+    pub fn notional(&self) -> Decimal {
+        self.positions.iter().map(|p| p.notional).sum()
+    }
+
+    /// Weighted funding APR before costs.
+    pub fn gross_apr(&self) -> Decimal {
+        self.weighted(|p| p.funding_apr)
+    }
+
+    /// Weighted estimated cost APR.
+    pub fn cost_apr(&self) -> Decimal {
+        self.weighted(|p| p.cost_apr)
+    }
+
+    /// Weighted funding APR net of estimated costs; zero without positions.
+    pub fn net_apr(&self) -> Decimal {
+        self.weighted(PositionApr::net_apr)
+    }
+
+    /// The metric as persisted at `timestamp`, compared against `risk_free_rate`.
+    pub fn record(&self, timestamp: DateTime<Utc>, risk_free_rate: Decimal) -> PersistedFundingApr {
+        PersistedFundingApr {
+            timestamp,
+            notional: self.notional(),
+            gross_apr: self.gross_apr(),
+            cost_apr: self.cost_apr(),
+            net_apr: self.net_apr(),
+            position_count: self.positions.len(),
+            risk_free_rate,
+        }
+    }
+
+    fn weighted(&self, value: impl Fn(&PositionApr) -> Decimal) -> Decimal {
+        let notional = self.notional();
+        if notional <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        self.positions
+            .iter()
+            .map(|p| value(p) * p.notional)
+            .sum::<Decimal>()
+            / notional
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Position entered on the current rate's sign, borrowing at 0.001%/h.
+    fn held(symbol: &str, notional: Decimal, rate: Decimal, interval_hours: u32) -> HeldFunding {
+        HeldFunding {
+            symbol: symbol.to_string(),
+            notional,
+            funding_rate: rate,
+            schedule: FundingSchedule::every_hours(interval_hours).unwrap(),
+            short_futures: rate >= Decimal::ZERO,
+            borrow_rate: Some(dec!(0.00001)),
+        }
+    }
+
+    #[test]
+    fn test_notional_weighted_net_apr() {
+        let portfolio = PortfolioApr::compute(
+            &[
+                // 0.01% per 8h = 10.95% APR
+                held("BTCUSDT", dec!(3000), dec!(0.0001), 8),
+                // Negative funding on a 4h schedule: 0.02% x 2190 = 43.8%
+                held("ALTUSDT", dec!(1000), dec!(-0.0002), 4),
+            ],
+            &RoundTripFees::default(),
+        );
+
+        let btc = &portfolio.positions[0];
+        assert_eq!(btc.funding_apr, dec!(0.1095));
+        // A long spot hedge borrows nothing
+        assert_eq!(btc.cost_apr, Decimal::ZERO);
+        let alt = &portfolio.positions[1];
+        assert_eq!(alt.funding_apr, dec!(0.438));
+        assert_eq!(alt.cost_apr, dec!(0.0876));
+
+        assert_eq!(portfolio.notional(), dec!(4000));
+        assert_eq!(portfolio.gross_apr(), dec!(0.191625));
+        assert_eq!(portfolio.cost_apr(), dec!(0.0219));
+        assert_eq!(portfolio.net_apr(), dec!(0.169725));
+
+        let record = portfolio.record(Utc::now(), dec!(0.045));
+        assert_eq!(record.net_apr, dec!(0.169725));
+        assert_eq!(record.risk_free_rate, dec!(0.045));
+        assert_eq!(record.position_count, 2);
+    }
+
+    #[test]
+    fn test_flipped_rate_counts_against_position() {
+        // Entered short on positive funding; the rate has since turned negative
+        let mut btc = held("BTCUSDT", dec!(1000), dec!(-0.0001), 8);
+        btc.short_futures = true;
+        let portfolio = PortfolioApr::compute(&[btc], &RoundTripFees::default());
+        assert_eq!(portfolio.net_apr(), dec!(-0.1095));
+
+        assert_eq!(PortfolioApr::default().net_apr(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_round_trip_fees_reduce_net_apr() {
+        let btc = [held("BTCUSDT", dec!(1000), dec!(0.0001), 8)];
+        let free = PortfolioApr::compute(&btc, &RoundTripFees::default());
+
+        // 0.05% to enter and again to exit, over a 73h hold: 0.1% x 120 = 12%
+        let fees = RoundTripFees {
+            entry_cost_rate: dec!(0.0005),
+            holding_hours: 73,
+        };
+        let charged = PortfolioApr::compute(&btc, &fees);
+        assert_eq!(charged.gross_apr(), free.gross_apr());
+        assert_eq!(charged.cost_apr(), dec!(0.12));
+        // Fees turn a 10.95% carry into a loss
+        assert_eq!(charged.net_apr(), dec!(-0.0105));
+        assert!(free.net_apr() > Decimal::ZERO);
+
+        // Without a holding period nothing is amortized
+        let unset = RoundTripFees {
+            holding_hours: 0,
+            ..fees
+        };
+        assert_eq!(unset.annualized(), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,238 @@
+//! Scheduled rebalance of held positions toward target weights.
+//!
+//! Per-cycle allocation only tops the book up with new entries, and per-position
+//! reductions only trim what grew past its target by the rebalance threshold. As
+//! funding rates move, the book drifts away from what allocation would build
+//! today. Once per interval the allocation is re-run as if the book were flat and
+//! capital moves from overweight held positions to underweight ones.
+//!
+//! Growth is funded only by what the shrinks free, so a rebalance never adds
+//! exposure. A rebalance with nothing to shrink or nothing to grow is empty.
+
+use super::allocator::{PositionAllocation, PositionReduction};
+use super::reduction_plan::ReductionUrgency;
+use crate::config::PortfolioRebalanceConfig;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Shifts of one portfolio rebalance.
+#[derive(Debug, Clone, Default)]
+pub struct PortfolioRebalance {
+    /// Overweight positions, reduced to their target
+    pub shrink: Vec<PositionReduction>,
+    /// Underweight positions; `target_size_usdt` is the top-up, best priority first
+    pub grow: Vec<PositionAllocation>,
+}
+
+impl PortfolioRebalance {
+    pub fn is_empty(&self) -> bool {
+        self.shrink.is_empty() || self.grow.is_empty()
+    }
+
+    /// Top-ups funded by `freed` USDT, best priority first.
+    ///
+    /// The last funded top-up is trimmed to what remains; top-ups smaller than
+    /// `min_shift_usdt` are dropped.
+    pub fn funded_growth(
+        &self,
+        freed: Decimal,
+        min_shift_usdt: Decimal,
+    ) -> Vec<PositionAllocation> {
+        let mut remaining = freed;
+        let mut funded = Vec::new();
+        for alloc in &self.grow {
+            let top_up = alloc.target_size_usdt.min(remaining);
+            if top_up <= Decimal::ZERO || top_up < min_shift_usdt {
+                continue;
+            }
+            remaining -= top_up;
+            funded.push(PositionAllocation {
+                target_size_usdt: top_up,
+                ..alloc.clone()
+            });
+        }
+        funded
+    }
+}
+
+/// Schedules portfolio rebalances and plans their shifts.
+#[derive(Debug)]
+pub struct PortfolioRebalancer {
+    config: PortfolioRebalanceConfig,
+    last_run: Option<DateTime<Utc>>,
+}
+
+impl PortfolioRebalancer {
+    pub fn new(config: PortfolioRebalanceConfig) -> Self {
+        Self {
+            config,
+            last_run: None,
+        }
+    }
+
+    /// Whether a rebalance is due; the first is due once enabled.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.config.enabled
+            && self
+                .last_run
+                .is_none_or(|last| now - last >= Duration::hours(self.config.interval_hours as i64))
+    }
+
+    /// Start the next interval.
+    pub fn mark_run(&mut self, now: DateTime<Utc>) {
+        self.last_run = Some(now);
+    }
+
+    /// Shifts that move held positions toward `targets`.
+    ///
+    /// `targets` come from allocating against a flat book, best first. Held
+    /// symbols without a target are left to orphan handling.
+    pub fn plan(
+        &self,
+        targets: &[PositionAllocation],
+        current_positions: &HashMap<String, Decimal>,
+    ) -> PortfolioRebalance {
+        let mut rebalance = PortfolioRebalance::default();
+
+        for target in targets {
+            let current = current_positions
+                .get(&target.symbol)
+                .copied()
+                .unwrap_or(Decimal::ZERO)
+                .abs();
+            if current.is_zero() {
+                continue;
+            }
+            let drift = target.target_size_usdt - current;
+            if drift.abs() < current * self.config.min_drift
+                || drift.abs() < self.config.min_shift_usdt
+            {
+                continue;
+            }
+
+            if drift > Decimal::ZERO {
+                rebalance.grow.push(PositionAllocation {
+                    target_size_usdt: drift,
+                    ..target.clone()
+                });
+            } else {
+                rebalance.shrink.push(PositionReduction {
+                    symbol: target.symbol.clone(),
+                    spot_symbol: target.spot_symbol.clone(),
+                    base_asset: target.base_asset.clone(),
+                    current_size_usdt: current,
+                    target_size_usdt: target.target_size_usdt,
+                    reduction_usdt: -drift,
+                    funding_rate: target.funding_rate,
+                    exceeds_notional_cap: false,
+                    urgency: ReductionUrgency::PortfolioRebalance,
+                });
+            }
+        }
+
+        if rebalance.is_empty() {
+            return PortfolioRebalance::default();
+        }
+        rebalance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn target(
+        symbol: &str,
+        size: Decimal,
+        funding_rate: Decimal,
+        priority: u8,
+    ) -> PositionAllocation {
+        PositionAllocation {
+            symbol: symbol.to_string(),
+            spot_symbol: symbol.to_string(),
+            base_asset: symbol.trim_end_matches("USDT").to_string(),
+            target_size_usdt: size,
+            leverage: 3,
+            funding_rate,
+            priority,
+            hedge_with_holdings: false,
+        }
+    }
+
+    fn rebalancer() -> PortfolioRebalancer {
+        PortfolioRebalancer::new(PortfolioRebalanceConfig {
+            enabled: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_schedule() {
+        let now = Utc::now();
+        assert!(!PortfolioRebalancer::new(PortfolioRebalanceConfig::default()).is_due(now));
+
+        let mut rebalancer = rebalancer();
+        assert!(rebalancer.is_due(now));
+        rebalancer.mark_run(now);
+        assert!(!rebalancer.is_due(now + Duration::hours(23)));
+        assert!(rebalancer.is_due(now + Duration::hours(24)));
+    }
+
+    #[test]
+    fn test_plan_shifts_between_held_positions() {
+        let targets = vec![
+            target("SOLUSDT", dec!(4000), dec!(0.0008), 1),
+            target("ETHUSDT", dec!(3000), dec!(0.0004), 2),
+            target("BTCUSDT", dec!(2000), dec!(0.0001), 3),
+            target("DOGEUSDT", dec!(1000), dec!(0.0003), 4),
+        ];
+        let current: HashMap<String, Decimal> = [
+            ("SOLUSDT".to_string(), dec!(2000)),
+            ("ETHUSDT".to_string(), dec!(-3100)),
+            ("BTCUSDT".to_string(), dec!(5000)),
+        ]
+        .into_iter()
+        .collect();
+
+        let rebalance = rebalancer().plan(&targets, &current);
+
+        // ETH is within the drift tolerance; DOGE is not held, so it is a new entry
+        assert_eq!(rebalance.shrink.len(), 1);
+        let shrink = &rebalance.shrink[0];
+        assert_eq!(shrink.symbol, "BTCUSDT");
+        assert_eq!(shrink.reduction_usdt, dec!(3000));
+        assert_eq!(shrink.target_size_usdt, dec!(2000));
+        assert_eq!(shrink.urgency, ReductionUrgency::PortfolioRebalance);
+
+        assert_eq!(rebalance.grow.len(), 1);
+        assert_eq!(rebalance.grow[0].symbol, "SOLUSDT");
+        assert_eq!(rebalance.grow[0].target_size_usdt, dec!(2000));
+
+        // Growth only uses what the shrinks actually freed
+        let funded = rebalance.funded_growth(dec!(1500), dec!(100));
+        assert_eq!(funded.len(), 1);
+        assert_eq!(funded[0].target_size_usdt, dec!(1500));
+        assert!(rebalance.funded_growth(dec!(50), dec!(100)).is_empty());
+    }
+
+    #[test]
+    fn test_plan_needs_both_sides() {
+        let targets = vec![
+            target("SOLUSDT", dec!(4000), dec!(0.0008), 1),
+            target("BTCUSDT", dec!(2000), dec!(0.0001), 2),
+        ];
+        // Both underweight: nothing to fund the growth from
+        let current: HashMap<String, Decimal> = [
+            ("SOLUSDT".to_string(), dec!(2000)),
+            ("BTCUSDT".to_string(), dec!(1000)),
+        ]
+        .into_iter()
+        .collect();
+
+        let rebalance = rebalancer().plan(&targets, &current);
+        assert!(rebalance.is_empty());
+        assert!(rebalance.shrink.is_empty() && rebalance.grow.is_empty());
+    }
+}
@@ -1,35 +1,66 @@
-//! Hedge rebalancing logic to maintain delta neutrality.
+//! Hedge rebalancing logic to keep each position inside its target hedge band.
 
+use crate::config::{ActionCostConfig, HedgeConfig};
 use crate::exchange::{
-    BinanceClient, DeltaNeutralPosition, MarginOrder, NewOrder, OrderResponse, OrderSide,
+    DeltaNeutralPosition, ExchangeClient, MarginOrder, NewOrder, OrderResponse, OrderSide,
     OrderType, SideEffectType,
 };
+use crate::strategy::TAKER_FEE_RATE;
 use anyhow::Result;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::fmt;
 use tracing::{debug, info, warn};
 
+/// Funding settlements are compared on an 8h basis.
+const FUNDING_PERIOD_HOURS: u32 = 8;
+
 /// Configuration for hedge rebalancing.
 #[derive(Debug, Clone)]
 pub struct RebalanceConfig {
-    /// Maximum allowed delta drift as a percentage (e.g., 0.05 = 5%)
-    pub max_delta_drift: Decimal,
+    /// Target hedge ratio and allowed drift from it, per symbol
+    pub hedge: HedgeConfig,
     /// Minimum rebalance size in USDT to avoid tiny trades
     pub min_rebalance_size: Decimal,
     /// Whether to auto-flip positions when funding direction reverses
     pub auto_flip_on_reversal: bool,
+    /// Benefit-versus-cost check of rebalances and reductions
+    pub action_cost: ActionCostConfig,
+    /// Execution cost of one leg as a fraction of notional (fee plus shortfall)
+    pub leg_cost_rate: Decimal,
 }
 
 impl Default for RebalanceConfig {
     fn default() -> Self {
         Self {
-            max_delta_drift: dec!(0.03),   // 3% drift triggers rebalance
+            hedge: HedgeConfig::default(), // Fully hedged, 3% drift triggers rebalance
             min_rebalance_size: dec!(100), // Min $100 trade
             auto_flip_on_reversal: true,
+            action_cost: ActionCostConfig::default(),
+            leg_cost_rate: TAKER_FEE_RATE,
         }
     }
 }
 
+/// Expected benefit and execution cost of a rebalancing trade, in USDT.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionEstimate {
+    pub benefit: Decimal,
+    pub cost: Decimal,
+}
+
+impl ActionEstimate {
+    pub fn net_benefit(&self) -> Decimal {
+        self.benefit - self.cost
+    }
+}
+
+impl fmt::Display for ActionEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "benefit ${:.2} vs cost ${:.2}", self.benefit, self.cost)
+    }
+}
+
 /// Action to take for rebalancing.
 #[derive(Debug, Clone)]
 pub enum RebalanceAction {
@@ -94,6 +125,43 @@ impl HedgeRebalancer {
         Self { config }
     }
 
+    /// Update the per-leg execution cost as the observed shortfall changes.
+    pub fn set_leg_cost_rate(&mut self, rate: Decimal) {
+        self.config.leg_cost_rate = rate;
+    }
+
+    /// Estimate of trading one leg to correct `drift_value` USDT of hedge drift.
+    pub fn adjustment_estimate(&self, drift_value: Decimal) -> ActionEstimate {
+        ActionEstimate {
+            benefit: drift_value * self.config.action_cost.drift_move,
+            cost: drift_value * self.config.leg_cost_rate,
+        }
+    }
+
+    /// Estimate of reducing a position by `reduction_usdt` to redeploy it at
+    /// `rate_gain` more funding per 8h.
+    ///
+    /// Both legs are closed and the freed capital enters both legs elsewhere.
+    pub fn reduction_estimate(
+        &self,
+        reduction_usdt: Decimal,
+        rate_gain: Decimal,
+    ) -> ActionEstimate {
+        let periods = Decimal::from(self.config.action_cost.benefit_horizon_hours)
+            / Decimal::from(FUNDING_PERIOD_HOURS);
+        ActionEstimate {
+            benefit: reduction_usdt * rate_gain * periods,
+            cost: reduction_usdt * self.config.leg_cost_rate * dec!(4),
+        }
+    }
+
+    /// Whether an action's net benefit clears the configured margin over its cost.
+    pub fn is_worthwhile(&self, estimate: &ActionEstimate) -> bool {
+        let action_cost = &self.config.action_cost;
+        !action_cost.enabled
+            || estimate.net_benefit() >= estimate.cost * action_cost.min_benefit_margin
+    }
+
     /// Analyze a position and determine if rebalancing is needed.
     pub fn analyze_position(
         &self,
@@ -110,14 +178,19 @@ impl HedgeRebalancer {
             return RebalanceAction::None;
         }
 
+        // Drift from the target hedge: net delta beyond the intended unhedged part
+        let target = self.config.hedge.target(&position.symbol);
+        let drift = position.net_delta - target.target_net_qty(position.futures_qty);
+
         // Delta percentage: how much the hedge has drifted as % of position
-        let delta_pct = position.net_delta.abs() / position_size;
+        let delta_pct = drift.abs() / position_size;
 
         debug!(
             symbol = %position.symbol,
             futures_qty = %position.futures_qty,
             spot_qty = %position.spot_qty,
             net_delta = %position.net_delta,
+            target_ratio = %target.ratio,
             delta_pct = %delta_pct,
             "Analyzing position delta"
         );
@@ -155,13 +228,13 @@ impl HedgeRebalancer {
         }
 
         // Check if delta drift exceeds threshold
-        if delta_pct <= self.config.max_delta_drift {
+        if delta_pct <= target.tolerance {
             return RebalanceAction::None;
         }
 
         // Determine which leg to adjust
         // We prefer adjusting the smaller leg to minimize transaction costs
-        let delta_value = position.net_delta.abs() * current_price;
+        let delta_value = drift.abs() * current_price;
         if delta_value < self.config.min_rebalance_size {
             debug!(
                 symbol = %position.symbol,
@@ -170,24 +243,35 @@ impl HedgeRebalancer {
             );
             return RebalanceAction::None;
         }
+        let estimate = self.adjustment_estimate(delta_value);
+        if !self.is_worthwhile(&estimate) {
+            debug!(
+                symbol = %position.symbol,
+                %delta_value,
+                benefit = %estimate.benefit,
+                cost = %estimate.cost,
+                "Rebalance costs more than it saves"
+            );
+            return RebalanceAction::None;
+        }
 
-        // If net_delta > 0, we have more long exposure than short
+        // If drift > 0, we have more long exposure than targeted
         // Need to either sell spot (if long spot) or sell futures (if long futures)
-        if position.net_delta > Decimal::ZERO {
+        if drift > Decimal::ZERO {
             // We're net long, need to reduce
             if position.spot_qty > Decimal::ZERO {
                 // Long spot, sell some
                 RebalanceAction::AdjustSpot {
                     symbol: position.spot_symbol.clone(),
                     side: OrderSide::Sell,
-                    quantity: position.net_delta,
+                    quantity: drift,
                 }
             } else {
                 // Long futures, sell some
                 RebalanceAction::AdjustFutures {
                     symbol: position.symbol.clone(),
                     side: OrderSide::Sell,
-                    quantity: position.net_delta,
+                    quantity: drift,
                 }
             }
         } else {
@@ -197,14 +281,14 @@ impl HedgeRebalancer {
                 RebalanceAction::AdjustSpot {
                     symbol: position.spot_symbol.clone(),
                     side: OrderSide::Buy,
-                    quantity: position.net_delta.abs(),
+                    quantity: drift.abs(),
                 }
             } else {
                 // Short futures, buy some back
                 RebalanceAction::AdjustFutures {
                     symbol: position.symbol.clone(),
                     side: OrderSide::Buy,
-                    quantity: position.net_delta.abs(),
+                    quantity: drift.abs(),
                 }
             }
         }
@@ -213,7 +297,7 @@ impl HedgeRebalancer {
     /// Execute a rebalancing action.
     pub async fn execute_rebalance(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         action: &RebalanceAction,
     ) -> Result<RebalanceResult> {
         match action {
@@ -247,6 +331,7 @@ impl HedgeRebalancer {
                     time_in_force: None,
                     is_isolated: Some(false),
                     side_effect_type: Some(SideEffectType::AutoBorrowRepay),
+                    new_client_order_id: None,
                 };
 
                 match client.place_margin_order(&order).await {
@@ -411,6 +496,7 @@ impl HedgeRebalancer {
                         time_in_force: None,
                         is_isolated: Some(false),
                         side_effect_type: Some(SideEffectType::AutoBorrowRepay),
+                        new_client_order_id: None,
                     };
 
                     match client.place_margin_order(&spot_order).await {
@@ -459,7 +545,7 @@ impl HedgeRebalancer {
     /// Check all positions and rebalance as needed.
     pub async fn check_and_rebalance(
         &self,
-        client: &BinanceClient,
+        client: &dyn ExchangeClient,
         positions: &[DeltaNeutralPosition],
         funding_rates: &std::collections::HashMap<String, Decimal>,
         prices: &std::collections::HashMap<String, Decimal>,
@@ -545,9 +631,10 @@ mod tests {
     #[test]
     fn test_rebalance_when_drift_exceeds_threshold() {
         let rebalancer = HedgeRebalancer::new(RebalanceConfig {
-            max_delta_drift: dec!(0.03),
+            hedge: HedgeConfig::default(),
             min_rebalance_size: dec!(100),
             auto_flip_on_reversal: true,
+            ..Default::default()
         });
 
         // 5% drift: short 1 futures, long 1.05 spot
@@ -563,4 +650,75 @@ mod tests {
             _ => panic!("Expected AdjustSpot action"),
         }
     }
+
+    #[test]
+    fn test_partial_hedge_keeps_target_band() {
+        let rebalancer = HedgeRebalancer::new(RebalanceConfig {
+            hedge: toml::from_str(
+                r#"
+                target_ratio = 0.95
+                tolerance = 0.02
+                "#,
+            )
+            .unwrap(),
+            ..Default::default()
+        });
+
+        // 96% hedged is within 2% of the 95% target
+        let position = test_position("BTCUSDT", dec!(-1), dec!(0.96));
+        let action = rebalancer.analyze_position(&position, dec!(0.0005), dec!(50000));
+        assert!(matches!(action, RebalanceAction::None));
+
+        // Fully hedged drifts 5% from the target: sell spot back to 95%
+        let position = test_position("BTCUSDT", dec!(-1), dec!(1));
+        match rebalancer.analyze_position(&position, dec!(0.0005), dec!(50000)) {
+            RebalanceAction::AdjustSpot { side, quantity, .. } => {
+                assert_eq!(side, OrderSide::Sell);
+                assert_eq!(quantity, dec!(0.05));
+            }
+            other => panic!("Expected AdjustSpot action, got {:?}", other),
+        }
+
+        // 90% hedged is 5% below the target: buy back futures toward 95%
+        let position = test_position("BTCUSDT", dec!(-1), dec!(0.9));
+        match rebalancer.analyze_position(&position, dec!(0.0005), dec!(50000)) {
+            RebalanceAction::AdjustFutures { side, quantity, .. } => {
+                assert_eq!(side, OrderSide::Buy);
+                assert_eq!(quantity, dec!(0.05));
+            }
+            other => panic!("Expected AdjustFutures action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_actions_must_repay_their_cost() {
+        let mut rebalancer = HedgeRebalancer::new(RebalanceConfig::default());
+        // 5% drift on 1 BTC: $2500 at risk of a 2% move, $1 in fees
+        let position = test_position("BTCUSDT", dec!(-1), dec!(1.05));
+        assert_eq!(
+            rebalancer.adjustment_estimate(dec!(2500)),
+            ActionEstimate {
+                benefit: dec!(50),
+                cost: dec!(1),
+            }
+        );
+        assert!(!matches!(
+            rebalancer.analyze_position(&position, dec!(0.0005), dec!(50000)),
+            RebalanceAction::None
+        ));
+
+        // Thin books can make the same correction cost more than it spares
+        rebalancer.set_leg_cost_rate(dec!(0.015));
+        assert!(matches!(
+            rebalancer.analyze_position(&position, dec!(0.0005), dec!(50000)),
+            RebalanceAction::None
+        ));
+
+        // Moving $1000 for 0.01%/8h more over 72h doesn't repay four legs of fees
+        rebalancer.set_leg_cost_rate(dec!(0.0004));
+        let marginal = rebalancer.reduction_estimate(dec!(1000), dec!(0.0001));
+        assert_eq!(marginal.net_benefit(), dec!(-0.7));
+        assert!(!rebalancer.is_worthwhile(&marginal));
+        assert!(rebalancer.is_worthwhile(&rebalancer.reduction_estimate(dec!(1000), dec!(0.001))));
+    }
 }
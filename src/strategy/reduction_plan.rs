@@ -0,0 +1,127 @@
+//! Batched execution of position reductions.
+//!
+//! Reductions gathered in a cycle (allocation drift, notional caps, exit
+//! liquidity, take-profits, risk exits) run as one planned batch instead of in
+//! the order they were found. The most urgent go first and, within an urgency,
+//! those releasing the most margin. Discretionary reductions smaller than
+//! `capital.min_reduction_usdt` are deferred: the target is recomputed every
+//! cycle, so the gap is traded once it is worth a trade rather than in slivers.
+
+use crate::strategy::PositionReduction;
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::fmt;
+
+/// Why a position is reduced, least urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReductionUrgency {
+    /// Overweight at a scheduled portfolio rebalance
+    PortfolioRebalance,
+    /// Oversized against its allocation target
+    Rebalance,
+    /// Partial close at a funding milestone
+    TakeProfit,
+    /// Held symbol that no longer qualifies
+    Orphaned,
+    /// Spot book too thin to unwind the position within its funding budget
+    ExitLiquidity,
+    /// Above an absolute notional cap
+    NotionalCap,
+    /// Forced exit requested by the risk orchestrator
+    RiskExit,
+}
+
+impl ReductionUrgency {
+    /// Reductions that run regardless of size.
+    pub fn is_mandatory(&self) -> bool {
+        *self >= ReductionUrgency::ExitLiquidity
+    }
+}
+
+impl fmt::Display for ReductionUrgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReductionUrgency::PortfolioRebalance => "portfolio rebalance",
+            ReductionUrgency::Rebalance => "rebalance",
+            ReductionUrgency::TakeProfit => "take-profit",
+            ReductionUrgency::Orphaned => "orphaned",
+            ReductionUrgency::ExitLiquidity => "exit liquidity",
+            ReductionUrgency::NotionalCap => "notional cap",
+            ReductionUrgency::RiskExit => "risk exit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Reductions of one cycle in execution order, and those deferred.
+#[derive(Debug, Clone, Default)]
+pub struct ReductionPlan {
+    pub batch: Vec<PositionReduction>,
+    /// Discretionary reductions below the minimum actionable notional
+    pub deferred: Vec<PositionReduction>,
+}
+
+impl ReductionPlan {
+    /// Order `reductions` by urgency, then margin released, deferring small
+    /// discretionary ones below `min_reduction_usdt`.
+    pub fn build(reductions: Vec<PositionReduction>, min_reduction_usdt: Decimal) -> Self {
+        let (mut batch, deferred): (Vec<_>, Vec<_>) = reductions
+            .into_iter()
+            .partition(|r| r.urgency.is_mandatory() || r.reduction_usdt >= min_reduction_usdt);
+        batch.sort_by_key(|r| (Reverse(r.urgency), Reverse(r.reduction_usdt)));
+        Self { batch, deferred }
+    }
+
+    /// Notional the batch reduces in total.
+    pub fn batch_notional(&self) -> Decimal {
+        self.batch.iter().map(|r| r.reduction_usdt).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn reduction(
+        symbol: &str,
+        reduction_usdt: Decimal,
+        urgency: ReductionUrgency,
+    ) -> PositionReduction {
+        PositionReduction {
+            symbol: symbol.to_string(),
+            spot_symbol: symbol.to_string(),
+            base_asset: symbol.trim_end_matches("USDT").to_string(),
+            current_size_usdt: dec!(5000),
+            target_size_usdt: dec!(5000) - reduction_usdt,
+            reduction_usdt,
+            funding_rate: dec!(0.0001),
+            exceeds_notional_cap: urgency == ReductionUrgency::NotionalCap,
+            urgency,
+        }
+    }
+
+    #[test]
+    fn test_batch_order_and_deferrals() {
+        let plan = ReductionPlan::build(
+            vec![
+                reduction("ETHUSDT", dec!(400), ReductionUrgency::Rebalance),
+                reduction("XRPUSDT", dec!(60), ReductionUrgency::Rebalance),
+                reduction("SOLUSDT", dec!(900), ReductionUrgency::Rebalance),
+                reduction("DOGEUSDT", dec!(50), ReductionUrgency::ExitLiquidity),
+                reduction("BTCUSDT", dec!(300), ReductionUrgency::NotionalCap),
+                reduction("ADAUSDT", dec!(80), ReductionUrgency::TakeProfit),
+            ],
+            dec!(100),
+        );
+
+        // Urgent first whatever their size, then the largest releases
+        let order: Vec<&str> = plan.batch.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(order, ["BTCUSDT", "DOGEUSDT", "SOLUSDT", "ETHUSDT"]);
+        assert_eq!(plan.batch_notional(), dec!(1650));
+
+        // Small discretionary reductions wait until they are worth a trade
+        let deferred: Vec<&str> = plan.deferred.iter().map(|r| r.symbol.as_str()).collect();
+        assert_eq!(deferred, ["XRPUSDT", "ADAUSDT"]);
+    }
+}
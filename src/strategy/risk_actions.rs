@@ -0,0 +1,372 @@
+//! Automated position reductions requested by risk checks.
+//!
+//! Drawdown, margin health and liquidation responses all shrink positions the
+//! same way: both legs of a delta-neutral position are cut by the same fraction
+//! with market orders, so the hedge ratio is kept. Each leg is retried with
+//! exponential backoff and every fill is recorded as a trade.
+
+use crate::exchange::{
+    DeltaNeutralPosition, MarginOrder, MockBinanceClient, NewOrder, OrderResponse, OrderSide,
+    OrderType, SideEffectType,
+};
+use crate::persistence::PersistenceManager;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::TAKER_FEE_RATE;
+
+/// Leg quantities below this are left alone.
+const MIN_REDUCE_QTY: Decimal = dec!(0.0001);
+
+/// Positions a directive applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReductionScope {
+    /// Every held position
+    All,
+    /// One futures symbol
+    Symbol(String),
+}
+
+/// A proportional reduction requested by a risk pathway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReductionDirective {
+    pub scope: ReductionScope,
+    /// Fraction of each leg to close (0.25 = 25%)
+    pub fraction: Decimal,
+    /// Why the reduction was requested, for logs
+    pub reason: String,
+}
+
+impl ReductionDirective {
+    /// Reduce every held position by `fraction`.
+    pub fn all(fraction: Decimal, reason: impl Into<String>) -> Self {
+        Self {
+            scope: ReductionScope::All,
+            fraction,
+            reason: reason.into(),
+        }
+    }
+
+    /// Reduce the position in `symbol` by `fraction`.
+    pub fn symbol(symbol: impl Into<String>, fraction: Decimal, reason: impl Into<String>) -> Self {
+        Self {
+            scope: ReductionScope::Symbol(symbol.into()),
+            fraction,
+            reason: reason.into(),
+        }
+    }
+
+    fn applies_to(&self, position: &DeltaNeutralPosition) -> bool {
+        match &self.scope {
+            ReductionScope::All => true,
+            ReductionScope::Symbol(symbol) => &position.symbol == symbol,
+        }
+    }
+}
+
+/// What a directive did to one position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PositionReductionResult {
+    pub symbol: String,
+    /// Futures quantity closed
+    pub futures_qty: Decimal,
+    /// Spot quantity closed
+    pub spot_qty: Decimal,
+    /// Legs that failed after all retries
+    pub errors: Vec<String>,
+}
+
+impl PositionReductionResult {
+    /// Whether every leg that needed reducing was reduced.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Executes reduction directives against the paper trading client.
+#[derive(Debug, Clone)]
+pub struct RiskActionExecutor {
+    max_attempts: u32,
+    base_backoff: Duration,
+}
+
+impl Default for RiskActionExecutor {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(100))
+    }
+}
+
+impl RiskActionExecutor {
+    /// Executor trying each leg up to `max_attempts` times, doubling `base_backoff`
+    /// between attempts.
+    pub fn new(max_attempts: u32, base_backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_backoff,
+        }
+    }
+
+    /// Reduce the positions `directive` applies to.
+    ///
+    /// Positions whose legs are both below the minimum quantity are skipped.
+    /// Fills are recorded as trades when `persistence` is given; a failure to
+    /// record is logged and doesn't fail the reduction.
+    pub async fn execute(
+        &self,
+        client: &MockBinanceClient,
+        positions: &[DeltaNeutralPosition],
+        directive: &ReductionDirective,
+        persistence: Option<&PersistenceManager>,
+    ) -> Vec<PositionReductionResult> {
+        let mut results = Vec::new();
+        for position in positions.iter().filter(|p| directive.applies_to(p)) {
+            let futures_qty = position.futures_qty.abs() * directive.fraction;
+            let spot_qty = position.spot_qty.abs() * directive.fraction;
+            if futures_qty < MIN_REDUCE_QTY && spot_qty < MIN_REDUCE_QTY {
+                continue;
+            }
+            info!(
+                symbol = %position.symbol,
+                fraction = %directive.fraction,
+                reason = %directive.reason,
+                "Reducing position"
+            );
+
+            let mut result = PositionReductionResult {
+                symbol: position.symbol.clone(),
+                ..Default::default()
+            };
+            if futures_qty >= MIN_REDUCE_QTY {
+                let order = NewOrder {
+                    symbol: position.symbol.clone(),
+                    side: closing_side(position.futures_qty),
+                    position_side: None,
+                    order_type: OrderType::Market,
+                    quantity: Some(futures_qty),
+                    price: None,
+                    time_in_force: None,
+                    reduce_only: Some(true),
+                    new_client_order_id: None,
+                };
+                match self
+                    .with_retries(&position.symbol, || client.place_futures_order(&order))
+                    .await
+                {
+                    Ok(fill) => {
+                        result.futures_qty = fill.executed_qty;
+                        record_fill(persistence, &fill, true);
+                    }
+                    Err(e) => result.errors.push(format!("Futures: {}", e)),
+                }
+            }
+            if spot_qty >= MIN_REDUCE_QTY {
+                let order = MarginOrder {
+                    symbol: position.spot_symbol.clone(),
+                    side: closing_side(position.spot_qty),
+                    order_type: OrderType::Market,
+                    quantity: Some(spot_qty),
+                    price: None,
+                    time_in_force: None,
+                    is_isolated: Some(false),
+                    side_effect_type: Some(SideEffectType::AutoBorrowRepay),
+                    new_client_order_id: None,
+                };
+                match self
+                    .with_retries(&position.spot_symbol, || client.place_margin_order(&order))
+                    .await
+                {
+                    Ok(fill) => {
+                        result.spot_qty = fill.executed_qty;
+                        record_fill(persistence, &fill, false);
+                    }
+                    Err(e) => result.errors.push(format!("Spot: {}", e)),
+                }
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    async fn with_retries<F, Fut>(&self, symbol: &str, place: F) -> anyhow::Result<OrderResponse>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<OrderResponse>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match place().await {
+                Ok(fill) => return Ok(fill),
+                Err(e) if attempt >= self.max_attempts => return Err(e),
+                Err(e) => {
+                    let backoff = self.base_backoff * 2_u32.pow(attempt - 1);
+                    warn!(
+                        %symbol,
+                        attempt,
+                        error = %e,
+                        "Reduction order failed, retrying in {:?}",
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Side of the order that shrinks a leg holding `qty`.
+fn closing_side(qty: Decimal) -> OrderSide {
+    if qty > Decimal::ZERO {
+        OrderSide::Sell
+    } else {
+        OrderSide::Buy
+    }
+}
+
+fn record_fill(persistence: Option<&PersistenceManager>, fill: &OrderResponse, is_futures: bool) {
+    let Some(persistence) = persistence else {
+        return;
+    };
+    let side = match fill.side {
+        OrderSide::Buy => "BUY",
+        OrderSide::Sell => "SELL",
+    };
+    let fee = fill.executed_qty * fill.avg_price * TAKER_FEE_RATE;
+    if let Err(e) = persistence.record_trade(
+        &fill.symbol,
+        side,
+        "MARKET",
+        fill.executed_qty,
+        fill.avg_price,
+        fee,
+        is_futures,
+    ) {
+        warn!(symbol = %fill.symbol, error = %e, "Failed to record reduction trade");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::OrderValve;
+    use std::collections::HashMap;
+
+    async fn client_with_positions() -> MockBinanceClient {
+        let client = MockBinanceClient::new(dec!(10000));
+        client
+            .update_market_data(
+                HashMap::new(),
+                HashMap::from([
+                    ("BTCUSDT".to_string(), dec!(50000)),
+                    ("ETHUSDT".to_string(), dec!(2500)),
+                ]),
+            )
+            .await;
+        // Short futures hedged with long spot, and the reverse
+        for (symbol, futures_side, spot_side, qty) in [
+            ("BTCUSDT", OrderSide::Sell, OrderSide::Buy, dec!(0.2)),
+            ("ETHUSDT", OrderSide::Buy, OrderSide::Sell, dec!(4)),
+        ] {
+            client
+                .place_futures_order(&NewOrder {
+                    symbol: symbol.to_string(),
+                    side: futures_side,
+                    position_side: None,
+                    order_type: OrderType::Market,
+                    quantity: Some(qty),
+                    price: None,
+                    time_in_force: None,
+                    reduce_only: None,
+                    new_client_order_id: None,
+                })
+                .await
+                .unwrap();
+            client
+                .place_margin_order(&MarginOrder {
+                    symbol: symbol.to_string(),
+                    side: spot_side,
+                    order_type: OrderType::Market,
+                    quantity: Some(qty),
+                    price: None,
+                    time_in_force: None,
+                    is_isolated: Some(false),
+                    side_effect_type: Some(SideEffectType::AutoBorrowRepay),
+                    new_client_order_id: None,
+                })
+                .await
+                .unwrap();
+        }
+        client
+    }
+
+    fn qty(positions: &[DeltaNeutralPosition], symbol: &str) -> (Decimal, Decimal) {
+        let p = positions.iter().find(|p| p.symbol == symbol).unwrap();
+        (p.futures_qty, p.spot_qty)
+    }
+
+    #[tokio::test]
+    async fn test_reduces_both_legs_proportionally() {
+        let client = client_with_positions().await;
+        let persistence = PersistenceManager::new(":memory:").unwrap();
+        let executor = RiskActionExecutor::default();
+
+        let positions = client.get_delta_neutral_positions().await;
+        let results = executor
+            .execute(
+                &client,
+                &positions,
+                &ReductionDirective::all(dec!(0.25), "margin health Orange"),
+                Some(&persistence),
+            )
+            .await;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_complete()));
+
+        let positions = client.get_delta_neutral_positions().await;
+        assert_eq!(qty(&positions, "BTCUSDT"), (dec!(-0.15), dec!(0.15)));
+        assert_eq!(qty(&positions, "ETHUSDT"), (dec!(3), dec!(-3)));
+        assert_eq!(persistence.get_recent_trades(10).unwrap().len(), 4);
+
+        // A symbol directive leaves the other positions alone
+        let results = executor
+            .execute(
+                &client,
+                &positions,
+                &ReductionDirective::symbol("ETHUSDT", dec!(0.5), "liquidation risk"),
+                None,
+            )
+            .await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].futures_qty, dec!(1.5));
+        let positions = client.get_delta_neutral_positions().await;
+        assert_eq!(qty(&positions, "BTCUSDT"), (dec!(-0.15), dec!(0.15)));
+        assert_eq!(qty(&positions, "ETHUSDT"), (dec!(1.5), dec!(-1.5)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_legs_reported_after_retries() {
+        let mut client = client_with_positions().await;
+        let positions = client.get_delta_neutral_positions().await;
+        // A tripped valve refuses every order
+        let valve = OrderValve::new(1, Decimal::ZERO);
+        let _ = valve.admit(chrono::Utc::now());
+        assert!(valve.admit(chrono::Utc::now()).is_err());
+        client.set_order_valve(valve);
+        let persistence = PersistenceManager::new(":memory:").unwrap();
+
+        let results = RiskActionExecutor::new(2, Duration::ZERO)
+            .execute(
+                &client,
+                &positions,
+                &ReductionDirective::symbol("BTCUSDT", dec!(0.5), "drawdown"),
+                Some(&persistence),
+            )
+            .await;
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].is_complete());
+        assert_eq!(results[0].errors.len(), 2);
+        assert!(persistence.get_recent_trades(10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Risk-free rate returns are compared against.
+//!
+//! A delta-neutral book still carries exchange, basis and liquidation risk, so its
+//! yield only earns that risk when it beats parking the capital in US Treasury
+//! bills. The rate is configured statically or read from the Treasury's FiscalData
+//! API, whose `avg_interest_rates` dataset reports the average rate on outstanding
+//! T-bills each month as a percentage string.
+
+use crate::config::RiskFreeConfig;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Timeout for FiscalData requests.
+const RISK_FREE_FETCH_TIMEOUT_SECS: u64 = 10;
+
+/// Annual risk-free rate and where it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RiskFreeRate {
+    /// Annual rate (0.045 = 4.5%)
+    pub annual_rate: Decimal,
+    /// Record date of a fetched T-bill rate; `None` when configured
+    pub as_of: Option<NaiveDate>,
+}
+
+impl RiskFreeRate {
+    /// A configured rate.
+    pub fn fixed(annual_rate: Decimal) -> Self {
+        Self {
+            annual_rate,
+            as_of: None,
+        }
+    }
+
+    /// The configured rate, or the latest T-bill rate when `fetch` is set.
+    ///
+    /// Falls back to the configured rate if the fetch fails.
+    pub async fn load(config: &RiskFreeConfig) -> Self {
+        let fixed = Self::fixed(config.annual_rate);
+        if !config.fetch {
+            return fixed;
+        }
+        match fetch_treasury_bill_rate(&config.url).await {
+            Ok(rate) => rate,
+            Err(e) => {
+                warn!(
+                    error = %format!("{:#}", e),
+                    fallback = %config.annual_rate,
+                    "Failed to fetch T-bill rate, using configured risk-free rate"
+                );
+                fixed
+            }
+        }
+    }
+
+    /// `apr` less the risk-free rate, both annual fractions.
+    pub fn excess(&self, apr: Decimal) -> Decimal {
+        apr - self.annual_rate
+    }
+}
+
+impl fmt::Display for RiskFreeRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = self.annual_rate * Decimal::ONE_HUNDRED;
+        match self.as_of {
+            Some(date) => write!(f, "{:.2}% (T-bills, {})", percent, date),
+            None => write!(f, "{:.2}% (configured)", percent),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FiscalDataResponse {
+    data: Vec<AverageInterestRate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AverageInterestRate {
+    record_date: NaiveDate,
+    /// Percent, e.g. "4.123"
+    avg_interest_rate_amt: String,
+}
+
+impl FiscalDataResponse {
+    /// The most recent record as an annual fraction.
+    fn latest(&self) -> Result<RiskFreeRate> {
+        let record = self
+            .data
+            .iter()
+            .max_by_key(|record| record.record_date)
+            .context("FiscalData response has no records")?;
+        let percent = Decimal::from_str(&record.avg_interest_rate_amt).with_context(|| {
+            format!(
+                "Invalid T-bill rate '{}' in FiscalData response",
+                record.avg_interest_rate_amt
+            )
+        })?;
+        Ok(RiskFreeRate {
+            annual_rate: percent / Decimal::ONE_HUNDRED,
+            as_of: Some(record.record_date),
+        })
+    }
+}
+
+/// Fetch the latest average T-bill rate from a FiscalData `avg_interest_rates` URL.
+pub async fn fetch_treasury_bill_rate(url: &str) -> Result<RiskFreeRate> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(RISK_FREE_FETCH_TIMEOUT_SECS))
+        .build()?;
+    let response: FiscalDataResponse = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to fetch T-bill rate from {}", url))?
+        .json()
+        .await
+        .context("Failed to parse FiscalData response")?;
+    response.latest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_latest_treasury_bill_rate() {
+        let response: FiscalDataResponse = serde_json::from_str(
+            r#"{"data": [
+                {"record_date": "2026-08-31", "security_type_desc": "Marketable",
+                 "security_desc": "Treasury Bills", "avg_interest_rate_amt": "4.210"},
+                {"record_date": "2026-09-30", "security_type_desc": "Marketable",
+                 "security_desc": "Treasury Bills", "avg_interest_rate_amt": "4.123"}
+            ], "meta": {"count": 2}}"#,
+        )
+        .unwrap();
+        let rate = response.latest().unwrap();
+        assert_eq!(rate.annual_rate, dec!(0.04123));
+        assert_eq!(rate.as_of, NaiveDate::from_ymd_opt(2026, 9, 30));
+        assert_eq!(rate.to_string(), "4.12% (T-bills, 2026-09-30)");
+
+        // A 15% APR beats 4.123% T-bills by 10.877 points
+        assert_eq!(rate.excess(dec!(0.15)), dec!(0.10877));
+
+        let empty: FiscalDataResponse = serde_json::from_str(r#"{"data": []}"#).unwrap();
+        assert!(empty.latest().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_configured_rate_without_fetch() {
+        let config = RiskFreeConfig {
+            annual_rate: dec!(0.045),
+            ..Default::default()
+        };
+        let rate = RiskFreeRate::load(&config).await;
+        assert_eq!(rate, RiskFreeRate::fixed(dec!(0.045)));
+        assert_eq!(rate.to_string(), "4.50% (configured)");
+    }
+}
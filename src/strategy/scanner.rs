@@ -1,19 +1,53 @@
 //! Market scanner for identifying funding rate opportunities.
 
 use crate::config::PairSelectionConfig;
-use crate::exchange::{BinanceClient, FundingRate, MarginAsset, QualifiedPair, SpotSymbolInfo};
+use crate::exchange::{
+    BookTicker, CoinContract, ExchangeClient, FundingCalendar, FundingCap, FundingRate,
+    MarginAsset, QualifiedPair, SpotSymbolInfo, Ticker24h, Venue,
+};
+use crate::strategy::{HedgeResolver, SymbolQuality};
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use rayon::iter::Either;
+use rayon::prelude::*;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use rustc_hash::FxHashMap;
 use std::collections::HashMap;
 use tracing::{info, instrument, trace, warn};
 
+/// Fewest candidates one parallel scoring task takes, so that small scans are not
+/// split finer than the work is worth.
+const QUALIFY_TASK_MIN_CANDIDATES: usize = 32;
+
+/// Whether scoring `candidates` is worth handing to the rayon pool.
+///
+/// A single-threaded pool, or too few candidates to split, would only add the
+/// hand-off to a pool thread and back; such scans are scored inline.
+fn scores_in_parallel(candidates: usize) -> bool {
+    candidates >= 2 * QUALIFY_TASK_MIN_CANDIDATES && rayon::current_num_threads() > 1
+}
+
+/// Run CPU-bound `work` from async code.
+///
+/// On a multi-threaded runtime the current worker moves its queued tasks to other
+/// workers first (`block_in_place`); a single-threaded runtime, which can't, runs
+/// the work inline.
+fn run_blocking<R>(work: impl FnOnce() -> R) -> R {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(work)
+        }
+        _ => work(),
+    }
+}
+
 /// Reasons for rejecting a pair during qualification.
 #[derive(Debug, Clone, Copy)]
 enum RejectReason {
     NotUsdt,
-    NoMargin,
+    NoHedge,
     NotBorrowable, // Can't short spot for negative funding
     LowVolume,
     WideSpread,
@@ -22,21 +56,283 @@ enum RejectReason {
     MissingData,
 }
 
+/// Why a pair was rejected close to qualifying, with the values that decided it.
+///
+/// Kept as numbers: near-misses are only formatted when they are logged.
+#[derive(Debug, Clone, Copy)]
+enum NearMissDetail {
+    /// Spot short with too little left to borrow
+    BorrowHeadroom {
+        headroom: Decimal,
+        min: Decimal,
+    },
+    /// Negative funding on an asset that can't be borrowed
+    NotBorrowable {
+        min_funding_rate: Decimal,
+    },
+    LowVolume {
+        volume: Decimal,
+        floor: Decimal,
+    },
+    WideSpread {
+        spread: Decimal,
+        max: Decimal,
+    },
+    LowFunding {
+        min: Decimal,
+    },
+    /// Net funding after the hedge's carry below the minimum
+    LowNetFunding {
+        hedge_cost: Decimal,
+        hedge_cost_label: &'static str,
+        net_funding: Decimal,
+        min: Decimal,
+    },
+}
+
 /// Details about a near-miss opportunity for diagnostic logging.
-#[derive(Debug, Clone)]
-struct NearMissOpportunity {
-    symbol: String,
+#[derive(Debug, Clone, Copy)]
+struct NearMissOpportunity<'a> {
+    symbol: &'a str,
     funding_rate: Decimal,
-    rejection_reason: String,
-    actual_value: String,
-    threshold: String,
+    detail: NearMissDetail,
+}
+
+impl NearMissOpportunity<'_> {
+    fn rejection_reason(&self) -> &'static str {
+        match self.detail {
+            NearMissDetail::BorrowHeadroom { .. } => "borrow_headroom",
+            NearMissDetail::NotBorrowable { .. } => "not_borrowable",
+            NearMissDetail::LowVolume { .. } => "low_volume",
+            NearMissDetail::WideSpread { .. } => "wide_spread",
+            NearMissDetail::LowFunding { .. } => "low_funding",
+            NearMissDetail::LowNetFunding { .. } => "low_net_funding",
+        }
+    }
+
     /// Proximity score: higher = closer to qualifying (0-100)
-    proximity: u8,
+    fn proximity(&self) -> u8 {
+        let funding_rate_abs = self.funding_rate.abs();
+        match self.detail {
+            NearMissDetail::BorrowHeadroom { headroom, min } => {
+                calculate_percentage_proximity(headroom, min)
+            }
+            NearMissDetail::NotBorrowable { min_funding_rate } => {
+                calculate_proximity_score(funding_rate_abs, min_funding_rate)
+            }
+            NearMissDetail::LowVolume { volume, floor } => {
+                calculate_percentage_proximity(volume, floor)
+            }
+            NearMissDetail::WideSpread { spread, max } => calculate_inverse_proximity(spread, max),
+            NearMissDetail::LowFunding { min } => {
+                calculate_percentage_proximity(funding_rate_abs, min)
+            }
+            NearMissDetail::LowNetFunding {
+                net_funding, min, ..
+            } => calculate_percentage_proximity(net_funding.max(Decimal::ZERO), min),
+        }
+    }
+
+    /// Actual value and threshold that rejected the pair.
+    fn describe(&self) -> (String, String) {
+        let funding_rate_abs = self.funding_rate.abs();
+        match self.detail {
+            NearMissDetail::BorrowHeadroom { headroom, min } => (
+                format!("${:.0} left to borrow", headroom),
+                format!("${:.0}", min),
+            ),
+            NearMissDetail::NotBorrowable { .. } => (
+                format!("funding={:.4}%", funding_rate_abs * dec!(100)),
+                "requires margin borrowing".to_string(),
+            ),
+            NearMissDetail::LowVolume { volume, floor } => (
+                format!("${:.0}M", volume / dec!(1_000_000)),
+                format!("${:.0}M", floor / dec!(1_000_000)),
+            ),
+            NearMissDetail::WideSpread { spread, max } => (
+                format!("{:.4}%", spread * dec!(100)),
+                format!("{:.4}%", max * dec!(100)),
+            ),
+            NearMissDetail::LowFunding { min } => (
+                format!("{:.4}%", funding_rate_abs * dec!(100)),
+                format!("{:.4}%", min * dec!(100)),
+            ),
+            NearMissDetail::LowNetFunding {
+                hedge_cost,
+                hedge_cost_label,
+                net_funding,
+                min,
+            } => (
+                format!(
+                    "{:.4}% (funding) - {:.4}% ({}) = {:.4}%",
+                    funding_rate_abs * dec!(100),
+                    hedge_cost * dec!(100),
+                    hedge_cost_label,
+                    net_funding * dec!(100)
+                ),
+                format!("{:.4}%", min * dec!(100)),
+            ),
+        }
+    }
+}
+
+/// Batched market data a scan qualifies pairs from.
+///
+/// [`MarketScanner::scan`] fetches it; [`MarketScanner::qualify_market`] runs the
+/// same pipeline over data already at hand, as the benchmarks do.
+#[derive(Debug, Clone, Default)]
+pub struct MarketData {
+    pub funding_rates: Vec<FundingRate>,
+    pub futures_tickers: Vec<Ticker24h>,
+    pub book_tickers: Vec<BookTicker>,
+    pub spot_info: Vec<SpotSymbolInfo>,
+    /// Spot 24h tickers; only symbols with a futures ticker count
+    pub spot_tickers: Vec<Ticker24h>,
+    pub margin_assets: Vec<MarginAsset>,
+}
+
+/// Market data of one symbol, gathered from the batched responses.
+#[derive(Debug, Clone, Copy, Default)]
+struct SymbolMarket<'a> {
+    /// Futures 24h quote volume
+    futures_volume: Option<Decimal>,
+    /// Spot 24h quote volume of the same symbol
+    spot_volume: Decimal,
+    /// Best bid and ask of the futures book
+    book: Option<(Decimal, Decimal)>,
+    /// Trading USDT spot pair
+    spot: Option<&'a SpotSymbolInfo>,
+}
+
+impl SymbolMarket<'_> {
+    /// Combined futures and spot volume; `None` without a futures ticker.
+    fn volume(&self) -> Option<Decimal> {
+        self.futures_volume.map(|volume| volume + self.spot_volume)
+    }
+
+    /// Relative bid/ask spread; `None` without a priced book.
+    ///
+    /// Computed on demand: most symbols never get past the funding pre-filter.
+    fn spread(&self) -> Option<Decimal> {
+        let (bid, ask) = self.book?;
+        // (ask - bid) / mid, with one division instead of two
+        let sum = bid + ask;
+        (sum > Decimal::ZERO).then(|| (ask - bid) * dec!(2) / sum)
+    }
+
+    /// Whether the relative spread is above `max`; `None` without a priced book.
+    ///
+    /// Compared without dividing, so pairs rejected on funding never pay for
+    /// [`Self::spread`].
+    fn spread_above(&self, max: Decimal) -> Option<bool> {
+        let (bid, ask) = self.book?;
+        let sum = bid + ask;
+        (sum > Decimal::ZERO).then(|| (ask - bid) * dec!(2) > max * sum)
+    }
+}
+
+/// Batched market data indexed by symbol, built once per scan.
+///
+/// Keys borrow the fetched responses, so indexing clones no symbol names.
+#[derive(Debug, Default)]
+struct MarketIndex<'a> {
+    symbols: FxHashMap<&'a str, SymbolMarket<'a>>,
+    /// Borrowable margin assets by asset name
+    margin_assets: FxHashMap<&'a str, &'a MarginAsset>,
+}
+
+impl<'a> MarketIndex<'a> {
+    fn new(
+        futures_tickers: &'a [Ticker24h],
+        book_tickers: &'a [BookTicker],
+        spot_info: &'a [SpotSymbolInfo],
+    ) -> Self {
+        let mut symbols: FxHashMap<&str, SymbolMarket> = FxHashMap::default();
+        symbols.reserve(futures_tickers.len().max(book_tickers.len()));
+        for ticker in futures_tickers {
+            symbols.entry(&ticker.symbol).or_default().futures_volume = Some(ticker.quote_volume);
+        }
+        for book in book_tickers {
+            symbols.entry(&book.symbol).or_default().book = Some((book.bid_price, book.ask_price));
+        }
+        for spot in spot_info
+            .iter()
+            .filter(|s| s.status == "TRADING" && s.quote_asset == "USDT")
+        {
+            symbols.entry(&spot.symbol).or_default().spot = Some(spot);
+        }
+        Self {
+            symbols,
+            margin_assets: FxHashMap::default(),
+        }
+    }
+
+    /// Add spot volume to the symbols with a futures ticker.
+    fn add_spot_volumes(&mut self, spot_tickers: &[Ticker24h]) {
+        for ticker in spot_tickers {
+            if let Some(market) = self.symbols.get_mut(ticker.symbol.as_str()) {
+                market.spot_volume += ticker.quote_volume;
+            }
+        }
+    }
+
+    /// Index the borrowable `assets` for borrow rate lookups.
+    fn add_margin_assets(&mut self, assets: &'a [MarginAsset]) {
+        self.margin_assets.reserve(assets.len());
+        self.margin_assets.extend(
+            assets
+                .iter()
+                .filter(|a| a.borrowable)
+                .map(|a| (a.asset.as_str(), a)),
+        );
+    }
+
+    fn get(&self, symbol: &str) -> SymbolMarket<'a> {
+        self.symbols.get(symbol).copied().unwrap_or_default()
+    }
+
+    /// Index of per-symbol maps; each spread becomes a book around a mid of 1.
+    #[cfg(test)]
+    fn from_maps(
+        volume_map: &'a HashMap<String, Decimal>,
+        spread_map: &'a HashMap<String, Decimal>,
+        spot_margin_map: &HashMap<String, &'a SpotSymbolInfo>,
+        margin_asset_map: &HashMap<String, &'a MarginAsset>,
+    ) -> Self {
+        let mut index = Self::default();
+        for (symbol, volume) in volume_map {
+            index.symbols.entry(symbol).or_default().futures_volume = Some(*volume);
+        }
+        for (symbol, spread) in spread_map {
+            let half = *spread / dec!(2);
+            index.symbols.entry(symbol).or_default().book =
+                Some((Decimal::ONE - half, Decimal::ONE + half));
+        }
+        for spot in spot_margin_map.values() {
+            index.symbols.entry(&spot.symbol).or_default().spot = Some(*spot);
+        }
+        for asset in margin_asset_map.values() {
+            index.margin_assets.insert(&asset.asset, *asset);
+        }
+        index
+    }
 }
 
 /// Scans the market for profitable funding rate opportunities.
 pub struct MarketScanner {
     config: PairSelectionConfig,
+    /// Perpetual funding (per 8h) on other venues, for perp hedges
+    venue_hedge_rates: HashMap<Venue, HashMap<String, Decimal>>,
+    /// Quality learned from realized performance, scaling scores
+    symbol_quality: SymbolQuality,
+    /// Funding cap and floor of symbols with adjusted limits
+    funding_caps: HashMap<String, FundingCap>,
+    /// COIN-margined perpetuals keyed by base asset
+    coin_contracts: HashMap<String, CoinContract>,
+    /// USDT left to borrow per base asset, where checked
+    borrow_headroom: HashMap<String, Decimal>,
+    /// Least headroom a spot short hedge needs
+    min_borrow_headroom: Decimal,
 }
 
 /// Calculate a proximity score (0-100) for how close a value is to reaching a threshold.
@@ -71,6 +367,33 @@ fn calculate_proximity_score(funding_rate: Decimal, min_funding_rate: Decimal) -
     score.to_u8().unwrap_or(50).clamp(0, 100)
 }
 
+/// Volume floor of a scan: `min_volume_24h`, raised to the `min_volume_percentile`
+/// of the current 24h `volumes` when one is set.
+///
+/// Symbols without volume (halted or settling) are left out of the distribution.
+pub fn volume_floor(
+    config: &PairSelectionConfig,
+    volumes: impl IntoIterator<Item = Decimal>,
+) -> Decimal {
+    if config.min_volume_percentile.is_zero() {
+        return config.min_volume_24h;
+    }
+    let mut volumes: Vec<Decimal> = volumes
+        .into_iter()
+        .filter(|volume| *volume > Decimal::ZERO)
+        .collect();
+    if volumes.is_empty() {
+        return config.min_volume_24h;
+    }
+    let rank = (config.min_volume_percentile * Decimal::from(volumes.len()))
+        .floor()
+        .to_usize()
+        .unwrap_or(0)
+        .min(volumes.len() - 1);
+    let (_, at_rank, _) = volumes.select_nth_unstable(rank);
+    config.min_volume_24h.max(*at_rank)
+}
+
 /// Get fallback borrow rate for an asset when margin data is unavailable.
 ///
 /// Rates are based on typical borrow rates observed on Binance:
@@ -95,22 +418,96 @@ fn get_fallback_borrow_rate(asset: &str, config_default: Decimal) -> Decimal {
 impl MarketScanner {
     /// Create a new market scanner with the given configuration.
     pub fn new(config: PairSelectionConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            venue_hedge_rates: HashMap::new(),
+            symbol_quality: SymbolQuality::default(),
+            funding_caps: HashMap::new(),
+            coin_contracts: HashMap::new(),
+            borrow_headroom: HashMap::new(),
+            min_borrow_headroom: Decimal::ZERO,
+        }
+    }
+
+    /// Replace the perpetual funding rates (per 8h, keyed by coin) on `venue` that
+    /// perp hedges are resolved against.
+    pub fn set_venue_hedge_rates(&mut self, venue: Venue, rates: HashMap<String, Decimal>) {
+        self.venue_hedge_rates.insert(venue, rates);
+    }
+
+    /// Replace the per-symbol funding caps that pinned rates are detected with.
+    pub fn set_funding_caps(&mut self, caps: HashMap<String, FundingCap>) {
+        self.funding_caps = caps;
+    }
+
+    /// Replace the COIN-margined perpetuals (keyed by base asset) attached to
+    /// qualified pairs.
+    pub fn set_coin_contracts(&mut self, contracts: HashMap<String, CoinContract>) {
+        self.coin_contracts = contracts;
+    }
+
+    /// Replace the USDT left to borrow per base asset; spot short hedges need
+    /// at least `min_usdt` of it.
+    pub fn set_borrow_headroom(&mut self, headroom: HashMap<String, Decimal>, min_usdt: Decimal) {
+        self.borrow_headroom = headroom;
+        self.min_borrow_headroom = min_usdt;
+    }
+
+    /// Whether the funding rate of `symbol` is pinned at its cap or floor.
+    fn is_capped(&self, symbol: &str, rate: Decimal) -> bool {
+        self.funding_caps
+            .get(symbol)
+            .is_some_and(|cap| cap.pins(rate))
+    }
+
+    /// Replace the symbol quality scores that qualified pairs are ranked with.
+    pub fn set_symbol_quality(&mut self, quality: SymbolQuality) {
+        self.symbol_quality = quality;
+    }
+
+    /// Drop blacklisted symbols and scale the remaining scores by symbol quality.
+    ///
+    /// Returns the number of pairs dropped.
+    pub fn apply_symbol_quality(&self, pairs: &mut Vec<QualifiedPair>) -> usize {
+        let before = pairs.len();
+        pairs.retain(|pair| !self.symbol_quality.is_blacklisted(&pair.symbol));
+        for pair in pairs.iter_mut() {
+            pair.score *= self.symbol_quality.multiplier(&pair.symbol);
+        }
+        before - pairs.len()
     }
 
     /// Scan the market and return qualified pairs sorted by score.
-    /// Only returns pairs that have spot margin trading enabled for hedging.
+    /// Only returns pairs that can be hedged: spot margin trading, or a perp hedge
+    /// when `futures_hedges` is enabled (see [`HedgeResolver`]).
+    ///
+    /// Scanning is two-stage: batched futures data and spot symbol info feed a
+    /// coarse pre-filter that keeps the top `prefilter_top_k` candidates, and only
+    /// those get spot volume, margin and full qualification lookups.
     #[instrument(skip(self, client))]
-    pub async fn scan(&self, client: &BinanceClient) -> Result<Vec<QualifiedPair>> {
-        // Fetch public data in parallel (required)
-        let (funding_rates, futures_tickers, book_tickers, spot_info, spot_tickers) = tokio::try_join!(
+    pub async fn scan(&self, client: &dyn ExchangeClient) -> Result<Vec<QualifiedPair>> {
+        // Stage 1: batched public data (required)
+        let (funding_rates, futures_tickers, book_tickers, spot_info) = tokio::try_join!(
             client.get_funding_rates(),
             client.get_24h_tickers(),
             client.get_book_tickers(),
             client.get_spot_exchange_info(),
-            client.get_spot_24h_tickers(),
         )?;
 
+        let mut index = MarketIndex::new(&futures_tickers, &book_tickers, &spot_info);
+        let hedge_resolver = self.hedge_resolver(&funding_rates);
+        let volume_floor = self.scan_volume_floor(&futures_tickers);
+        let candidates =
+            self.prefilter_candidates(&funding_rates, &index, volume_floor, &hedge_resolver);
+
+        // Stage 2: spot volume for the candidates only
+        let spot_tickers = if self.config.prefilter_top_k == 0 {
+            client.get_spot_24h_tickers().await?
+        } else {
+            let symbols: Vec<String> = candidates.iter().map(|fr| fr.symbol.clone()).collect();
+            client.get_spot_24h_tickers_for(&symbols).await?
+        };
+
         // Fetch margin assets separately (requires auth, may fail in read-only mode)
         let margin_assets = match client.get_margin_all_assets().await {
             Ok(assets) => assets,
@@ -125,56 +522,90 @@ impl MarketScanner {
 
         info!(
             funding_count = funding_rates.len(),
+            candidates = candidates.len(),
             futures_ticker_count = futures_tickers.len(),
             spot_ticker_count = spot_tickers.len(),
             spot_symbols = spot_info.len(),
             margin_assets = margin_assets.len(),
+            %volume_floor,
             "Fetched market data"
         );
 
-        // Build combined volume map (futures + spot volume for better liquidity assessment)
-        // Start with futures volume
-        let mut volume_map: HashMap<String, Decimal> = futures_tickers
-            .iter()
-            .map(|t| (t.symbol.clone(), t.quote_volume))
-            .collect();
-
-        // Add spot volume to the same symbols
-        for spot_ticker in &spot_tickers {
-            if let Some(futures_vol) = volume_map.get_mut(&spot_ticker.symbol) {
-                *futures_vol += spot_ticker.quote_volume;
-            }
-        }
+        // Combined futures + spot volume for better liquidity assessment
+        index.add_spot_volumes(&spot_tickers);
+        index.add_margin_assets(&margin_assets);
+
+        // Scoring keeps rayon busy for the whole scan; the worker hands its other
+        // tasks to the rest of the runtime instead of stalling them
+        Ok(run_blocking(|| {
+            self.qualify_candidates(
+                &candidates,
+                &index,
+                volume_floor,
+                &hedge_resolver,
+                funding_rates.len(),
+            )
+        }))
+    }
 
-        let spread_map: HashMap<String, Decimal> = book_tickers
-            .iter()
-            .filter_map(|b| {
-                let mid = (b.bid_price + b.ask_price) / dec!(2);
-                if mid > Decimal::ZERO {
-                    Some((b.symbol.clone(), (b.ask_price - b.bid_price) / mid))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// Qualify pairs from `market` without fetching anything, as [`Self::scan`]
+    /// would from the same responses.
+    pub fn qualify_market(&self, market: &MarketData) -> Vec<QualifiedPair> {
+        let mut index = MarketIndex::new(
+            &market.futures_tickers,
+            &market.book_tickers,
+            &market.spot_info,
+        );
+        let hedge_resolver = self.hedge_resolver(&market.funding_rates);
+        let volume_floor = self.scan_volume_floor(&market.futures_tickers);
+        let candidates =
+            self.prefilter_candidates(&market.funding_rates, &index, volume_floor, &hedge_resolver);
+        index.add_spot_volumes(&market.spot_tickers);
+        index.add_margin_assets(&market.margin_assets);
+        self.qualify_candidates(
+            &candidates,
+            &index,
+            volume_floor,
+            &hedge_resolver,
+            market.funding_rates.len(),
+        )
+    }
 
-        // Index spot symbols by symbol name for margin availability check
-        let spot_margin_map: HashMap<String, &SpotSymbolInfo> = spot_info
-            .iter()
-            .filter(|s| s.status == "TRADING" && s.quote_asset == "USDT")
-            .map(|s| (s.symbol.clone(), s))
-            .collect();
+    /// Hedge resolver over Binance USDC perps and the other venues' perps.
+    fn hedge_resolver(&self, funding_rates: &[FundingRate]) -> HedgeResolver {
+        let mut hedge_resolver =
+            HedgeResolver::new(self.config.futures_hedges, self.config.basis_horizon_hours)
+                .with_usdc_perps(funding_rates);
+        for (venue, rates) in &self.venue_hedge_rates {
+            hedge_resolver = hedge_resolver.with_venue_perps(*venue, rates);
+        }
+        hedge_resolver
+    }
 
-        // Index margin assets by asset name for borrow rate lookup
-        let margin_asset_map: HashMap<String, &MarginAsset> = margin_assets
-            .iter()
-            .filter(|a| a.borrowable)
-            .map(|a| (a.asset.clone(), a))
-            .collect();
+    /// The percentile floor follows the futures volume of every USDT perpetual.
+    fn scan_volume_floor(&self, futures_tickers: &[Ticker24h]) -> Decimal {
+        volume_floor(
+            &self.config,
+            futures_tickers
+                .iter()
+                .filter(|t| t.symbol.ends_with("USDT"))
+                .map(|t| t.quote_volume),
+        )
+    }
 
+    /// Fully qualify the pre-filtered `candidates`, sorted by score, logging
+    /// rejection counts and near-misses.
+    fn qualify_candidates<'a>(
+        &self,
+        candidates: &[&'a FundingRate],
+        index: &MarketIndex,
+        volume_floor: Decimal,
+        hedge_resolver: &HedgeResolver,
+        total_scanned: usize,
+    ) -> Vec<QualifiedPair> {
         // Track rejection reasons for summary logging
         let mut rejected_no_usdt = 0usize;
-        let mut rejected_no_margin = 0usize;
+        let mut rejected_no_hedge = 0usize;
         let mut rejected_not_borrowable = 0usize;
         let mut rejected_low_volume = 0usize;
         let mut rejected_wide_spread = 0usize;
@@ -185,186 +616,298 @@ impl MarketScanner {
         // Track near-miss opportunities for diagnostic logging
         let mut near_misses: Vec<NearMissOpportunity> = Vec::new();
 
-        // Filter and score pairs
-        let mut qualified: Vec<QualifiedPair> = funding_rates
-            .iter()
-            .filter_map(|fr| {
-                match self.qualify_pair_with_details(
-                    fr,
-                    &volume_map,
-                    &spread_map,
-                    &spot_margin_map,
-                    &margin_asset_map,
-                ) {
-                    Ok(pair) => Some(pair),
-                    Err((reason, near_miss)) => {
-                        match reason {
-                            RejectReason::NotUsdt => rejected_no_usdt += 1,
-                            RejectReason::NoMargin => rejected_no_margin += 1,
-                            RejectReason::NotBorrowable => rejected_not_borrowable += 1,
-                            RejectReason::LowVolume => rejected_low_volume += 1,
-                            RejectReason::WideSpread => rejected_wide_spread += 1,
-                            RejectReason::LowFunding => rejected_low_funding += 1,
-                            RejectReason::LowNetFunding => rejected_low_net_funding += 1,
-                            RejectReason::MissingData => rejected_missing_data += 1,
-                        }
-                        // Collect near-misses (only for pairs that got past initial filters)
-                        if let Some(nm) = near_miss {
-                            near_misses.push(nm);
-                        }
-                        None
-                    }
+        let mut reject = |(reason, near_miss): (RejectReason, Option<NearMissOpportunity<'a>>)| {
+            match reason {
+                RejectReason::NotUsdt => rejected_no_usdt += 1,
+                RejectReason::NoHedge => rejected_no_hedge += 1,
+                RejectReason::NotBorrowable => rejected_not_borrowable += 1,
+                RejectReason::LowVolume => rejected_low_volume += 1,
+                RejectReason::WideSpread => rejected_wide_spread += 1,
+                RejectReason::LowFunding => rejected_low_funding += 1,
+                RejectReason::LowNetFunding => rejected_low_net_funding += 1,
+                RejectReason::MissingData => rejected_missing_data += 1,
+            }
+            // Collect near-misses (only for pairs that got past initial filters)
+            if let Some(nm) = near_miss {
+                near_misses.push(nm);
+            }
+        };
+
+        // Scoring is CPU-bound and independent per symbol, so it runs in parallel;
+        // results come back in candidate order, keeping equal scores ranked alike.
+        // Rejections are small: only qualifying pairs carry a full `QualifiedPair`
+        let qualify = |fr: &&'a FundingRate| {
+            self.qualify_pair_with_details(fr, index, volume_floor, hedge_resolver)
+        };
+        // At most one pair per candidate: reserving up front keeps pushes from
+        // copying the pairs already qualified
+        let mut qualified: Vec<QualifiedPair> = Vec::new();
+        if scores_in_parallel(candidates.len()) {
+            let rejections: Vec<_>;
+            (qualified, rejections) = candidates
+                .par_iter()
+                .with_min_len(QUALIFY_TASK_MIN_CANDIDATES)
+                .map(qualify)
+                .partition_map(|outcome| match outcome {
+                    Ok(pair) => Either::Left(pair),
+                    Err(rejection) => Either::Right(rejection),
+                });
+            rejections.into_iter().for_each(&mut reject);
+        } else {
+            qualified.reserve(candidates.len());
+            for outcome in candidates.iter().map(qualify) {
+                match outcome {
+                    Ok(pair) => qualified.push(pair),
+                    Err(rejection) => reject(rejection),
                 }
-            })
-            .collect();
+            }
+        }
 
-        // Sort by score (descending) - pairs with higher net profitability first
-        qualified.sort_by(|a, b| b.score.cmp(&a.score));
+        let rejected_blacklisted = self.apply_symbol_quality(&mut qualified);
+        let capped = qualified.iter().filter(|p| p.funding_capped).count();
+
+        // Sort by score (descending) - pairs with higher net profitability first.
+        // Pairs are large: sort keys, then move each pair once
+        qualified.sort_by_cached_key(|p| std::cmp::Reverse(p.score));
 
-        let total_scanned = funding_rates.len();
         info!(
             total_scanned,
+            prefiltered_out = total_scanned - candidates.len(),
             qualified = qualified.len(),
             rejected_no_usdt,
-            rejected_no_margin,
+            rejected_no_hedge,
             rejected_not_borrowable,
             rejected_low_volume,
             rejected_wide_spread,
             rejected_low_funding,
             rejected_low_net_funding,
             rejected_missing_data,
+            rejected_blacklisted,
+            capped,
             "Market scan complete"
         );
 
         // Log near-miss opportunities when few pairs qualify (for diagnostic visibility)
         if qualified.len() < 3 && !near_misses.is_empty() {
             // Sort near-misses by proximity (highest = closest to qualifying)
-            near_misses.sort_by(|a, b| b.proximity.cmp(&a.proximity));
-
-            // Take top 5 near-misses
-            let top_near_misses: Vec<_> = near_misses.into_iter().take(5).collect();
+            near_misses.sort_by_cached_key(|nm| std::cmp::Reverse(nm.proximity()));
 
             info!("📊 Top near-miss opportunities (closest to qualifying):");
-            for nm in &top_near_misses {
+            for nm in near_misses.iter().take(5) {
+                let (actual_value, threshold) = nm.describe();
                 info!(
                     "   {} | funding={:.4}% | rejected: {} (actual={}, threshold={})",
                     nm.symbol,
                     nm.funding_rate * dec!(100),
-                    nm.rejection_reason,
-                    nm.actual_value,
-                    nm.threshold
+                    nm.rejection_reason(),
+                    actual_value,
+                    threshold
                 );
             }
         }
 
-        Ok(qualified)
+        qualified
     }
 
-    /// Check if a pair qualifies with detailed rejection info for near-miss tracking.
-    fn qualify_pair_with_details(
+    /// Coarse pre-filter over batched data: keep the top `prefilter_top_k` symbols.
+    ///
+    /// Only USDT perpetuals with a margin-enabled spot market or a perp hedge and an
+    /// absolute funding rate at or above `min_funding_rate` are kept (anything else is
+    /// rejected during qualification anyway). Candidates are ranked by absolute funding
+    /// rate, discounted when futures volume alone is below `volume_floor` and when
+    /// the rate is pinned at the funding cap.
+    fn prefilter_candidates<'a>(
         &self,
-        funding: &FundingRate,
-        volume_map: &HashMap<String, Decimal>,
-        spread_map: &HashMap<String, Decimal>,
-        spot_margin_map: &HashMap<String, &SpotSymbolInfo>,
-        margin_asset_map: &HashMap<String, &MarginAsset>,
-    ) -> Result<QualifiedPair, (RejectReason, Option<NearMissOpportunity>)> {
-        let symbol = &funding.symbol;
+        funding_rates: &'a [FundingRate],
+        index: &MarketIndex,
+        volume_floor: Decimal,
+        hedge_resolver: &HedgeResolver,
+    ) -> Vec<&'a FundingRate> {
+        if self.config.prefilter_top_k == 0 {
+            return funding_rates.iter().collect();
+        }
 
-        // Must be USDT perpetual - early filter, not a near-miss
-        if !symbol.ends_with("USDT") {
-            return Err((RejectReason::NotUsdt, None));
+        // Only volume short of the floor needs a division
+        let liquidity = |market: &SymbolMarket| {
+            let volume = market.futures_volume.unwrap_or_default();
+            if volume_floor.is_zero() || volume >= volume_floor {
+                Decimal::ONE
+            } else {
+                volume / volume_floor
+            }
+        };
+
+        let mut ranked: Vec<(Decimal, usize, &FundingRate)> =
+            Vec::with_capacity(funding_rates.len());
+        for (position, fr) in funding_rates.iter().enumerate() {
+            let Some(base) = fr.symbol.strip_suffix("USDT") else {
+                continue;
+            };
+            let funding_rate_abs = fr.funding_rate.abs();
+            if funding_rate_abs < self.config.min_funding_rate {
+                continue;
+            }
+            let market = index.get(&fr.symbol);
+            let hedgeable = market.spot.is_some_and(|s| s.is_margin_trading_allowed)
+                || hedge_resolver.has_perp_hedge(base);
+            if !hedgeable {
+                continue;
+            }
+            let mut coarse_score = funding_rate_abs * liquidity(&market);
+            if self.is_capped(&fr.symbol, fr.funding_rate) {
+                coarse_score *= self.config.capped_funding_persistence;
+            }
+            ranked.push((coarse_score, position, fr));
+        }
+
+        // Highest score first, ties in input order: select the top k, then sort
+        // only those
+        let top_k = self.config.prefilter_top_k;
+        let by_rank = |a: &(Decimal, usize, &FundingRate), b: &(Decimal, usize, &FundingRate)| {
+            b.0.cmp(&a.0).then(a.1.cmp(&b.1))
+        };
+        if ranked.len() > top_k {
+            ranked.select_nth_unstable_by(top_k - 1, by_rank);
+            ranked.truncate(top_k);
         }
+        ranked.sort_unstable_by(by_rank);
+        ranked.into_iter().map(|(_, _, fr)| fr).collect()
+    }
 
-        // Derive spot symbol (same as futures for USDT pairs)
-        let spot_symbol = symbol.clone();
+    /// Check if a pair qualifies with detailed rejection info for near-miss tracking.
+    ///
+    /// The pair's combined futures and spot volume must reach `volume_floor`.
+    /// Names are only copied out of `funding` once the pair qualifies.
+    #[allow(clippy::result_large_err)]
+    fn qualify_pair_with_details<'a>(
+        &self,
+        funding: &'a FundingRate,
+        index: &MarketIndex,
+        volume_floor: Decimal,
+        hedge_resolver: &HedgeResolver,
+    ) -> Result<QualifiedPair, (RejectReason, Option<NearMissOpportunity<'a>>)> {
+        let symbol = funding.symbol.as_str();
 
+        // Must be USDT perpetual - early filter, not a near-miss
         // Extract base asset (e.g., "BTC" from "BTCUSDT")
-        let base_asset = symbol
-            .strip_suffix("USDT")
-            .ok_or((RejectReason::NotUsdt, None))?
-            .to_string();
+        let Some(base_asset) = symbol.strip_suffix("USDT") else {
+            return Err((RejectReason::NotUsdt, None));
+        };
+        let near_miss = |detail| {
+            Some(NearMissOpportunity {
+                symbol,
+                funding_rate: funding.funding_rate,
+                detail,
+            })
+        };
 
-        // Check if spot margin trading is available
-        let spot_info = spot_margin_map.get(&spot_symbol);
-        let margin_available = spot_info
-            .map(|s| s.is_margin_trading_allowed)
-            .unwrap_or(false);
+        // Spot symbol is the same as futures for USDT pairs
+        let market = index.get(symbol);
 
-        if !margin_available {
-            trace!(symbol, "No spot margin trading available - cannot hedge");
-            // This is an infrastructure limitation, not a near-miss
-            return Err((RejectReason::NoMargin, None));
-        }
+        // Check if spot margin trading is available
+        let margin_available = market.spot.is_some_and(|s| s.is_margin_trading_allowed);
 
         // Check if base asset is borrowable (needed for shorting spot)
-        let margin_asset = margin_asset_map.get(&base_asset);
+        let margin_asset = index.margin_assets.get(base_asset);
         let borrow_rate = margin_asset.and_then(|a| a.margin_interest_rate);
 
         // For negative funding rates, we need to short spot (borrow base asset)
-        if funding.funding_rate < Decimal::ZERO {
-            if margin_asset.is_none() {
+        let spot_short = funding.funding_rate < Decimal::ZERO;
+        let spot_hedgeable = margin_available && (!spot_short || margin_asset.is_some());
+        let spot_borrow_cost = spot_hedgeable.then(|| {
+            if !spot_short {
+                return Decimal::ZERO;
+            }
+            let daily_rate = borrow_rate.unwrap_or_else(|| {
+                let fallback =
+                    get_fallback_borrow_rate(base_asset, self.config.default_borrow_rate);
+                trace!(
+                    symbol,
+                    %base_asset,
+                    %fallback,
+                    "Using fallback borrow rate (margin data unavailable)"
+                );
+                fallback
+            });
+            daily_rate / dec!(3)
+        });
+        let has_perp_hedge = hedge_resolver.has_perp_hedge(base_asset);
+
+        if !margin_available && !has_perp_hedge {
+            trace!(
+                symbol,
+                "No spot margin trading or perp hedge available - cannot hedge"
+            );
+            // This is an infrastructure limitation, not a near-miss
+            return Err((RejectReason::NoHedge, None));
+        }
+
+        // A spot short can't be opened once the exchange has little left to lend
+        let borrow_headroom_usdt = self.borrow_headroom.get(base_asset).copied();
+        let borrow_exhausted = spot_short
+            && borrow_headroom_usdt.is_some_and(|headroom| headroom < self.min_borrow_headroom);
+        let spot_borrow_cost = spot_borrow_cost.filter(|_| !borrow_exhausted);
+
+        if spot_borrow_cost.is_none() && !has_perp_hedge {
+            if let Some(headroom) = borrow_headroom_usdt.filter(|_| borrow_exhausted) {
                 trace!(
                     symbol,
                     base_asset,
-                    funding_rate = %funding.funding_rate,
-                    "Rejecting: negative funding requires borrowing, but asset not in margin system"
+                    %headroom,
+                    "Rejecting: too little left to borrow for a spot short"
                 );
-                // Track as near-miss if funding rate is significant
                 return Err((
                     RejectReason::NotBorrowable,
-                    Some(NearMissOpportunity {
-                        symbol: symbol.clone(),
-                        funding_rate: funding.funding_rate,
-                        rejection_reason: "not_borrowable".to_string(),
-                        actual_value: format!("funding={:.4}%", funding.funding_rate.abs() * dec!(100)),
-                        threshold: "requires margin borrowing".to_string(),
-                        proximity: calculate_proximity_score(funding.funding_rate.abs(), self.config.min_funding_rate),
+                    near_miss(NearMissDetail::BorrowHeadroom {
+                        headroom,
+                        min: self.min_borrow_headroom,
                     }),
                 ));
             }
+            trace!(
+                symbol,
+                base_asset,
+                funding_rate = %funding.funding_rate,
+                "Rejecting: negative funding requires borrowing, but asset not in margin system"
+            );
+            // Track as near-miss if funding rate is significant
+            return Err((
+                RejectReason::NotBorrowable,
+                near_miss(NearMissDetail::NotBorrowable {
+                    min_funding_rate: self.config.min_funding_rate,
+                }),
+            ));
         }
 
         // Get volume
-        let volume = match volume_map.get(symbol) {
-            Some(&v) => v,
-            None => return Err((RejectReason::MissingData, None)),
+        let Some(volume) = market.volume() else {
+            return Err((RejectReason::MissingData, None));
         };
 
-        if volume < self.config.min_volume_24h {
-            trace!(symbol, %volume, "Volume below threshold");
-            let proximity = calculate_percentage_proximity(volume, self.config.min_volume_24h);
+        if volume < volume_floor {
+            trace!(symbol, %volume, %volume_floor, "Volume below threshold");
             return Err((
                 RejectReason::LowVolume,
-                Some(NearMissOpportunity {
-                    symbol: symbol.clone(),
-                    funding_rate: funding.funding_rate,
-                    rejection_reason: "low_volume".to_string(),
-                    actual_value: format!("${:.0}M", volume / dec!(1_000_000)),
-                    threshold: format!("${:.0}M", self.config.min_volume_24h / dec!(1_000_000)),
-                    proximity,
+                near_miss(NearMissDetail::LowVolume {
+                    volume,
+                    floor: volume_floor,
                 }),
             ));
         }
 
-        // Get spread
-        let spread = match spread_map.get(symbol) {
-            Some(&s) => s,
-            None => return Err((RejectReason::MissingData, None)),
+        // Check spread
+        let Some(wide_spread) = market.spread_above(self.config.max_spread) else {
+            return Err((RejectReason::MissingData, None));
         };
 
-        if spread > self.config.max_spread {
+        if wide_spread {
+            let spread = market.spread().unwrap_or_default();
             trace!(symbol, %spread, "Spread above threshold");
-            let proximity = calculate_inverse_proximity(spread, self.config.max_spread);
             return Err((
                 RejectReason::WideSpread,
-                Some(NearMissOpportunity {
-                    symbol: symbol.clone(),
-                    funding_rate: funding.funding_rate,
-                    rejection_reason: "wide_spread".to_string(),
-                    actual_value: format!("{:.4}%", spread * dec!(100)),
-                    threshold: format!("{:.4}%", self.config.max_spread * dec!(100)),
-                    proximity,
+                near_miss(NearMissDetail::WideSpread {
+                    spread,
+                    max: self.config.max_spread,
                 }),
             ));
         }
@@ -373,39 +916,26 @@ impl MarketScanner {
         let funding_rate_abs = funding.funding_rate.abs();
         if funding_rate_abs < self.config.min_funding_rate {
             trace!(symbol, %funding_rate_abs, "Funding rate below threshold");
-            let proximity = calculate_percentage_proximity(funding_rate_abs, self.config.min_funding_rate);
             return Err((
                 RejectReason::LowFunding,
-                Some(NearMissOpportunity {
-                    symbol: symbol.clone(),
-                    funding_rate: funding.funding_rate,
-                    rejection_reason: "low_funding".to_string(),
-                    actual_value: format!("{:.4}%", funding_rate_abs * dec!(100)),
-                    threshold: format!("{:.4}%", self.config.min_funding_rate * dec!(100)),
-                    proximity,
+                near_miss(NearMissDetail::LowFunding {
+                    min: self.config.min_funding_rate,
                 }),
             ));
         }
 
-        // Calculate net profitability considering borrow costs
-        let borrow_cost_per_8h = if funding.funding_rate < Decimal::ZERO {
-            let daily_rate = borrow_rate.unwrap_or_else(|| {
-                let fallback =
-                    get_fallback_borrow_rate(&base_asset, self.config.default_borrow_rate);
-                trace!(
-                    symbol,
-                    %base_asset,
-                    %fallback,
-                    "Using fallback borrow rate (margin data unavailable)"
-                );
-                fallback
-            });
-            daily_rate / dec!(3)
-        } else {
-            Decimal::ZERO
+        // Hedges are only compared for pairs that got this far
+        let hedge_options = hedge_resolver.options(base_asset, funding, spot_borrow_cost);
+        let Some(hedge) = hedge_options.first().cloned() else {
+            // Ruled out above: the spot pair or a perp can hedge
+            return Err((RejectReason::NoHedge, None));
         };
 
-        let net_funding = funding_rate_abs - borrow_cost_per_8h;
+        // Calculate net profitability considering the hedge's carry: borrow costs
+        // (spot hedges) or funding and basis (perp hedges)
+        let borrow_cost_per_8h = hedge.carry.borrow;
+        let hedge_carry = hedge.carry.funding + hedge.carry.basis;
+        let net_funding = funding_rate_abs - (borrow_cost_per_8h + hedge_carry);
 
         // CRITICAL: Reject pairs where net funding (after borrow costs) is too low
         if net_funding < self.config.min_net_funding {
@@ -414,54 +944,68 @@ impl MarketScanner {
                 %net_funding,
                 %funding_rate_abs,
                 %borrow_cost_per_8h,
+                %hedge_carry,
                 min_required = %self.config.min_net_funding,
                 "Rejecting: net funding too low after borrow costs"
             );
-            let proximity = calculate_percentage_proximity(net_funding.max(Decimal::ZERO), self.config.min_net_funding);
+            let (hedge_cost, hedge_cost_label) = if hedge.instrument.is_spot() {
+                (borrow_cost_per_8h, "borrow")
+            } else {
+                (hedge_carry, "hedge carry")
+            };
             return Err((
                 RejectReason::LowNetFunding,
-                Some(NearMissOpportunity {
-                    symbol: symbol.clone(),
-                    funding_rate: funding.funding_rate,
-                    rejection_reason: "low_net_funding".to_string(),
-                    actual_value: format!("{:.4}% (funding) - {:.4}% (borrow) = {:.4}%",
-                        funding_rate_abs * dec!(100),
-                        borrow_cost_per_8h * dec!(100),
-                        net_funding * dec!(100)),
-                    threshold: format!("{:.4}%", self.config.min_net_funding * dec!(100)),
-                    proximity,
+                near_miss(NearMissDetail::LowNetFunding {
+                    hedge_cost,
+                    hedge_cost_label,
+                    net_funding,
+                    min: self.config.min_net_funding,
                 }),
             ));
         }
 
-        // Calculate score - prioritize net profitability
-        let funding_score = net_funding * dec!(10000);
-        let volume_score = (volume / dec!(1_000_000_000)).min(dec!(1));
-        let spread_score = dec!(1) / (spread * dec!(10000) + dec!(1));
+        // The book was priced for the spread check
+        let spread = market.spread().unwrap_or_default();
+
+        // Calculate score - prioritize net profitability. A rate pinned at the cap
+        // snaps back once the premium eases, so only part of it counts.
+        let funding_capped = self.is_capped(symbol, funding.funding_rate);
+        // Weights are folded into the terms: funding 0.5 per basis point, volume
+        // 0.25 (full at $1B), spread 0.2, margin safety 0.05
+        let mut funding_score = net_funding * dec!(5000);
+        if funding_capped {
+            funding_score *= self.config.capped_funding_persistence;
+        }
+        let volume_score = if volume >= dec!(1_000_000_000) {
+            dec!(0.25)
+        } else {
+            volume * dec!(0.00000000025)
+        };
+        let spread_score = dec!(0.2) / (spread * dec!(10000) + Decimal::ONE);
         let margin_safety = if margin_asset.is_some() {
-            dec!(1)
+            dec!(0.05)
         } else {
-            dec!(0.5)
+            dec!(0.025)
         };
 
-        let score = funding_score * dec!(0.5)
-            + volume_score * dec!(0.25)
-            + spread_score * dec!(0.2)
-            + margin_safety * dec!(0.05);
+        let score = funding_score + volume_score + spread_score + margin_safety;
 
         trace!(
             symbol,
             %funding.funding_rate,
             %net_funding,
             %borrow_cost_per_8h,
+            hedge = %hedge.instrument,
+            funding_capped,
             %score,
             "Pair qualified"
         );
 
+        let coin_contract = self.coin_contracts.get(base_asset).cloned();
         Ok(QualifiedPair {
-            symbol: symbol.clone(),
-            spot_symbol,
-            base_asset,
+            symbol: symbol.to_string(),
+            spot_symbol: symbol.to_string(),
+            base_asset: base_asset.to_string(),
             funding_rate: funding.funding_rate,
             next_funding_time: funding.funding_time,
             volume_24h: volume,
@@ -470,10 +1014,15 @@ impl MarketScanner {
             margin_available,
             borrow_rate,
             score,
+            hedge: hedge.instrument,
+            hedge_options,
+            funding_capped,
+            coin_contract,
+            borrow_headroom_usdt,
         })
     }
 
-    /// Check if a pair qualifies and calculate its score (wrapper for tests).
+    /// Check if a pair qualifies and calculate its score (wrapper for tests, spot hedges only).
     /// A pair must have:
     /// 1. USDT perpetual futures available
     /// 2. Spot margin trading enabled for hedging
@@ -490,41 +1039,20 @@ impl MarketScanner {
     ) -> Option<QualifiedPair> {
         self.qualify_pair_with_details(
             funding,
-            volume_map,
-            spread_map,
-            spot_margin_map,
-            margin_asset_map,
+            &MarketIndex::from_maps(volume_map, spread_map, spot_margin_map, margin_asset_map),
+            self.config.min_volume_24h,
+            &HedgeResolver::default(),
         )
         .ok()
     }
 
-    /// Get the next funding time for a symbol (in milliseconds since epoch).
+    /// Get the next funding time on the default schedule (in milliseconds since epoch).
+    ///
+    /// Symbols on shorter intervals settle earlier; see [`FundingCalendar`].
     pub fn next_funding_time() -> i64 {
-        use chrono::{Timelike, Utc};
-
-        let now = Utc::now();
-        let hour = now.hour();
-
-        // Funding times: 00:00, 08:00, 16:00 UTC
-        let next_funding_hour = match hour {
-            0..=7 => 8,
-            8..=15 => 16,
-            _ => 24, // Next day 00:00
-        };
-
-        let next_funding = if next_funding_hour == 24 {
-            now.date_naive()
-                .succ_opt()
-                .unwrap()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-        } else {
-            now.date_naive()
-                .and_hms_opt(next_funding_hour, 0, 0)
-                .unwrap()
-        };
-
-        next_funding.and_utc().timestamp_millis()
+        FundingCalendar::venue_default(Venue::Binance)
+            .next_settlement(Utc::now())
+            .timestamp_millis()
     }
 
     /// Time until next funding in seconds.
@@ -535,24 +1063,77 @@ impl MarketScanner {
     }
 }
 
+/// Last successful scan, kept for cycles where scanning fails.
+///
+/// Cached pairs are only good for valuing and rebalancing what is already held;
+/// callers must not open new positions from them.
+#[derive(Debug)]
+pub struct ScanCache {
+    pairs: Vec<QualifiedPair>,
+    scanned_at: Option<DateTime<Utc>>,
+    max_age: Duration,
+}
+
+impl ScanCache {
+    /// Create an empty cache whose contents expire after `max_age`.
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            pairs: Vec::new(),
+            scanned_at: None,
+            max_age,
+        }
+    }
+
+    /// Replace the cached pairs with a successful scan.
+    pub fn store(&mut self, pairs: &[QualifiedPair], now: DateTime<Utc>) {
+        self.pairs = pairs.to_vec();
+        self.scanned_at = Some(now);
+    }
+
+    /// Age of the cached scan, if any.
+    pub fn age(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.scanned_at.map(|at| now - at)
+    }
+
+    /// Cached pairs if the last scan is within the freshness window.
+    pub fn fresh(&self, now: DateTime<Utc>) -> Option<&[QualifiedPair]> {
+        self.age(now)
+            .filter(|age| *age <= self.max_age)
+            .map(|_| self.pairs.as_slice())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::exchange::{FundingRate, MarginAsset, SpotSymbolInfo};
+    use crate::exchange::{FundingRate, HedgeInstrument, MarginAsset, SpotSymbolInfo};
 
     // =========================================================================
     // Test Helpers
     // =========================================================================
 
+    const MIN_VOLUME: Decimal = dec!(50_000_000);
+
     fn test_config() -> PairSelectionConfig {
         PairSelectionConfig {
-            min_volume_24h: dec!(50_000_000),
+            min_volume_24h: MIN_VOLUME,
+            min_volume_percentile: Decimal::ZERO,
             min_funding_rate: dec!(0.0001),
             max_spread: dec!(0.0002),
             min_open_interest: dec!(50_000_000),
             max_positions: 5,
             default_borrow_rate: dec!(0.001), // 0.1% daily fallback
             min_net_funding: dec!(0.0001),    // 0.01% minimum net funding per 8h
+            prefilter_top_k: 0,
+            scan_cache_max_age_minutes: 10,
+            futures_hedges: false,
+            basis_horizon_hours: 72,
+            hedge_switch_threshold: dec!(0.00005),
+            hedge_review_minutes: 60,
+            symbol_quality_lookback_days: 30,
+            max_symbol_failures: 3,
+            capped_funding_persistence: dec!(0.5),
+            max_open_interest_share: dec!(0.02),
         }
     }
 
@@ -572,6 +1153,7 @@ mod tests {
             quote_asset: "USDT".to_string(),
             status: "TRADING".to_string(),
             is_margin_trading_allowed: margin_allowed,
+            filters: Vec::new(),
         }
     }
 
@@ -584,6 +1166,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn setup_test_data() -> (
         HashMap<String, Decimal>,        // volume_map
         HashMap<String, Decimal>,        // spread_map
@@ -799,12 +1382,23 @@ mod tests {
         // Create config with high min_net_funding to force rejection
         let config = PairSelectionConfig {
             min_volume_24h: dec!(50_000_000),
+            min_volume_percentile: Decimal::ZERO,
             min_funding_rate: dec!(0.0001),
             max_spread: dec!(0.0002),
             min_open_interest: dec!(50_000_000),
             max_positions: 5,
             default_borrow_rate: dec!(0.01), // 1% daily - very high
             min_net_funding: dec!(0.005),    // Require 0.5% net funding
+            prefilter_top_k: 0,
+            scan_cache_max_age_minutes: 10,
+            futures_hedges: false,
+            basis_horizon_hours: 72,
+            hedge_switch_threshold: dec!(0.00005),
+            hedge_review_minutes: 60,
+            symbol_quality_lookback_days: 30,
+            max_symbol_failures: 3,
+            capped_funding_persistence: dec!(0.5),
+            max_open_interest_share: dec!(0.02),
         };
         let scanner = MarketScanner::new(config);
         let (volume_map, spread_map, spot_map, margin_map) = setup_test_data();
@@ -822,7 +1416,10 @@ mod tests {
             scanner.qualify_pair(&funding, &volume_map, &spread_map, &spot_ref, &margin_ref);
 
         // Should be rejected due to low net funding
-        assert!(result.is_none(), "Expected rejection due to low net funding after borrow costs");
+        assert!(
+            result.is_none(),
+            "Expected rejection due to low net funding after borrow costs"
+        );
     }
 
     #[test]
@@ -918,6 +1515,39 @@ mod tests {
         assert!(btc_pair.unwrap().score > eth_pair.unwrap().score);
     }
 
+    #[test]
+    fn test_capped_funding_discounted() {
+        let mut scanner = MarketScanner::new(test_config());
+        let (volume_map, spread_map, spot_map, margin_map) = setup_test_data();
+        let spot_ref: HashMap<String, &SpotSymbolInfo> =
+            spot_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let margin_ref: HashMap<String, &MarginAsset> =
+            margin_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let qualify = |scanner: &MarketScanner, rate| {
+            let funding = make_funding_rate("BTCUSDT", rate);
+            scanner
+                .qualify_pair(&funding, &volume_map, &spread_map, &spot_ref, &margin_ref)
+                .unwrap()
+        };
+        let uncapped = qualify(&scanner, dec!(0.002));
+        assert!(!uncapped.funding_capped);
+
+        // Pinned at the cap: half the funding score
+        scanner.set_funding_caps(HashMap::from([(
+            "BTCUSDT".to_string(),
+            FundingCap {
+                cap: dec!(0.002),
+                floor: dec!(-0.002),
+            },
+        )]));
+        let capped = qualify(&scanner, dec!(0.002));
+        assert!(capped.funding_capped);
+        assert_eq!((uncapped.score - capped.score).round_dp(8), dec!(5));
+
+        // Below the cap scores as usual
+        assert!(!qualify(&scanner, dec!(0.0019)).funding_capped);
+    }
+
     // =========================================================================
     // Margin Availability Tests
     // =========================================================================
@@ -1145,4 +1775,463 @@ mod tests {
             "Asset lookup should be case insensitive"
         );
     }
+
+    // =========================================================================
+    // Pre-filter Tests
+    // =========================================================================
+
+    fn make_ticker(symbol: &str, quote_volume: Decimal) -> Ticker24h {
+        Ticker24h {
+            symbol: symbol.to_string(),
+            price_change: Decimal::ZERO,
+            price_change_percent: Decimal::ZERO,
+            last_price: Decimal::ZERO,
+            high_price: Decimal::ZERO,
+            low_price: Decimal::ZERO,
+            volume: Decimal::ZERO,
+            quote_volume,
+            open_time: 0,
+            close_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_prefilter_keeps_top_k_by_liquidity_weighted_funding() {
+        let mut config = test_config();
+        config.prefilter_top_k = 2;
+        let scanner = MarketScanner::new(config);
+
+        let funding_rates = vec![
+            make_funding_rate("BTCUSDT", dec!(0.0005)),
+            make_funding_rate("ETHUSDT", dec!(-0.0008)),
+            // Highest rate but thin futures volume: 0.002 * 0.1 = 0.0002
+            make_funding_rate("SOLUSDT", dec!(0.002)),
+            // Below min_funding_rate
+            make_funding_rate("XRPUSDT", dec!(0.00005)),
+            // No spot market
+            make_funding_rate("1000PEPEUSDT", dec!(0.003)),
+            // Spot market without margin trading
+            make_funding_rate("NOMARGINUSDT", dec!(0.003)),
+        ];
+        let tickers = vec![
+            make_ticker("BTCUSDT", dec!(1_000_000_000)),
+            make_ticker("ETHUSDT", dec!(500_000_000)),
+            make_ticker("SOLUSDT", dec!(5_000_000)),
+            make_ticker("XRPUSDT", dec!(900_000_000)),
+            make_ticker("1000PEPEUSDT", dec!(900_000_000)),
+            make_ticker("NOMARGINUSDT", dec!(900_000_000)),
+        ];
+        let mut spot_infos: Vec<SpotSymbolInfo> = ["BTCUSDT", "ETHUSDT", "SOLUSDT", "XRPUSDT"]
+            .iter()
+            .map(|s| make_spot_info(s, true))
+            .collect();
+        spot_infos.push(make_spot_info("NOMARGINUSDT", false));
+
+        let candidates = scanner.prefilter_candidates(
+            &funding_rates,
+            &MarketIndex::new(&tickers, &[], &spot_infos),
+            MIN_VOLUME,
+            &HedgeResolver::default(),
+        );
+        let symbols: Vec<&str> = candidates.iter().map(|fr| fr.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["ETHUSDT", "BTCUSDT"]);
+    }
+
+    #[test]
+    fn test_volume_floor_combines_absolute_and_percentile() {
+        let mut volumes: Vec<Decimal> = (1..=10)
+            .map(|tens| Decimal::from(tens * 10_000_000))
+            .collect();
+        // Halted symbols are left out of the distribution
+        volumes.push(Decimal::ZERO);
+        assert_eq!(volume_floor(&test_config(), volumes.clone()), MIN_VOLUME);
+
+        let top_30 = PairSelectionConfig {
+            min_volume_percentile: dec!(0.7),
+            ..test_config()
+        };
+        assert_eq!(volume_floor(&top_30, volumes.clone()), dec!(80_000_000));
+        let strict = PairSelectionConfig {
+            min_volume_24h: dec!(95_000_000),
+            ..top_30.clone()
+        };
+        assert_eq!(volume_floor(&strict, volumes), dec!(95_000_000));
+        assert_eq!(volume_floor(&top_30, [Decimal::ZERO]), MIN_VOLUME);
+    }
+
+    #[test]
+    fn test_prefilter_disabled_keeps_everything() {
+        let scanner = MarketScanner::new(test_config());
+        let funding_rates = vec![
+            make_funding_rate("BTCUSDT", dec!(0.0005)),
+            make_funding_rate("NOSPOTUSDT", dec!(0.00001)),
+        ];
+
+        let candidates = scanner.prefilter_candidates(
+            &funding_rates,
+            &MarketIndex::default(),
+            MIN_VOLUME,
+            &HedgeResolver::default(),
+        );
+        assert_eq!(candidates.len(), 2);
+    }
+
+    // =========================================================================
+    // Perp Hedge Tests
+    // =========================================================================
+
+    #[test]
+    fn test_perp_hedge_for_symbol_without_spot_margin() {
+        let config = PairSelectionConfig {
+            futures_hedges: true,
+            ..test_config()
+        };
+        let scanner = MarketScanner::new(config);
+        let (mut volume_map, mut spread_map, spot_map, margin_map) = setup_test_data();
+        volume_map.insert("NOMARGINUSDT".to_string(), dec!(200_000_000));
+        spread_map.insert("NOMARGINUSDT".to_string(), dec!(0.0001));
+        let spot_ref: HashMap<String, &SpotSymbolInfo> =
+            spot_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let margin_ref: HashMap<String, &MarginAsset> =
+            margin_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let funding = make_funding_rate("NOMARGINUSDT", dec!(0.001));
+
+        // Without a perp hedge the symbol can't be hedged
+        let unhedged = scanner.qualify_pair_with_details(
+            &funding,
+            &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &margin_ref),
+            MIN_VOLUME,
+            &HedgeResolver::new(true, 72),
+        );
+        assert!(matches!(unhedged, Err((RejectReason::NoHedge, None))));
+
+        // Long USDC perp hedge pays 0.03% of the 0.1% earned
+        let resolver = HedgeResolver::new(true, 72)
+            .with_usdc_perps(&[make_funding_rate("NOMARGINUSDC", dec!(0.0003))]);
+        let pair = scanner
+            .qualify_pair_with_details(
+                &funding,
+                &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &margin_ref),
+                MIN_VOLUME,
+                &resolver,
+            )
+            .unwrap();
+        assert_eq!(pair.hedge.to_string(), "NOMARGINUSDC perp");
+        assert!(!pair.margin_available);
+
+        // Spot-hedged pairs keep their spot hedge
+        let btc = scanner
+            .qualify_pair_with_details(
+                &make_funding_rate("BTCUSDT", dec!(0.001)),
+                &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &margin_ref),
+                MIN_VOLUME,
+                &resolver,
+            )
+            .unwrap();
+        assert!(btc.hedge.is_spot());
+        // Same funding, but the hedge leg's carry lowers the score
+        assert!(pair.score < btc.score);
+
+        // Hedge funding can eat the whole rate
+        let costly = HedgeResolver::new(true, 72)
+            .with_usdc_perps(&[make_funding_rate("NOMARGINUSDC", dec!(0.00095))]);
+        let rejected = scanner.qualify_pair_with_details(
+            &funding,
+            &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &margin_ref),
+            MIN_VOLUME,
+            &costly,
+        );
+        assert!(matches!(rejected, Err((RejectReason::LowNetFunding, _))));
+    }
+
+    #[test]
+    fn test_perp_hedge_replaces_unborrowable_spot_short() {
+        let config = PairSelectionConfig {
+            futures_hedges: true,
+            ..test_config()
+        };
+        let scanner = MarketScanner::new(config);
+        let (volume_map, spread_map, spot_map, _) = setup_test_data();
+        let spot_ref: HashMap<String, &SpotSymbolInfo> =
+            spot_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        // ETH spot exists but can't be borrowed for the short hedge
+        let funding = make_funding_rate("ETHUSDT", dec!(-0.001));
+        let resolver = HedgeResolver::new(true, 72).with_venue_perps(
+            Venue::Hyperliquid,
+            &HashMap::from([("ETH".to_string(), dec!(-0.0002))]),
+        );
+
+        let pair = scanner
+            .qualify_pair_with_details(
+                &funding,
+                &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &HashMap::new()),
+                MIN_VOLUME,
+                &resolver,
+            )
+            .unwrap();
+        assert_eq!(
+            pair.hedge,
+            HedgeInstrument::VenuePerp {
+                venue: Venue::Hyperliquid,
+                funding_rate: dec!(-0.0002)
+            }
+        );
+        // Short hedge receives the negative funding: no borrow cost, carry adds up
+        assert_eq!(pair.hedge.funding_carry(funding.funding_rate), dec!(0.0002));
+
+        // Spot short rejection is unchanged without the perp hedge
+        let rejected = scanner.qualify_pair_with_details(
+            &funding,
+            &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &HashMap::new()),
+            MIN_VOLUME,
+            &HedgeResolver::new(true, 72),
+        );
+        assert!(matches!(
+            rejected,
+            Err((RejectReason::NotBorrowable, Some(_)))
+        ));
+    }
+
+    #[test]
+    fn test_exhausted_borrow_headroom_drops_spot_short() {
+        let mut scanner = MarketScanner::new(PairSelectionConfig {
+            futures_hedges: true,
+            ..test_config()
+        });
+        scanner.set_borrow_headroom(
+            HashMap::from([
+                ("BTC".to_string(), dec!(50000)),
+                ("ETH".to_string(), dec!(400)),
+            ]),
+            dec!(1000),
+        );
+        let (volume_map, spread_map, spot_map, margin_map) = setup_test_data();
+        let spot_ref: HashMap<String, &SpotSymbolInfo> =
+            spot_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let margin_ref: HashMap<String, &MarginAsset> =
+            margin_map.iter().map(|(k, v)| (k.clone(), v)).collect();
+        let qualify = |funding: &FundingRate, resolver: &HedgeResolver| {
+            scanner
+                .qualify_pair_with_details(
+                    funding,
+                    &MarketIndex::from_maps(&volume_map, &spread_map, &spot_ref, &margin_ref),
+                    MIN_VOLUME,
+                    resolver,
+                )
+                .map_err(|(reason, near_miss)| (reason, near_miss.map(|nm| nm.rejection_reason())))
+        };
+        let spot_only = HedgeResolver::new(true, 72);
+
+        // Enough headroom: the spot short qualifies and carries it
+        let btc = qualify(&make_funding_rate("BTCUSDT", dec!(-0.001)), &spot_only).unwrap();
+        assert!(btc.hedge.is_spot());
+        assert_eq!(btc.borrow_headroom_usdt, Some(dec!(50000)));
+
+        // ETH is nearly exhausted: rejected, unless a perp can hedge instead
+        let eth = make_funding_rate("ETHUSDT", dec!(-0.001));
+        let Err((reason, near_miss)) = qualify(&eth, &spot_only) else {
+            panic!("expected a rejection");
+        };
+        assert!(matches!(reason, RejectReason::NotBorrowable));
+        assert_eq!(near_miss, Some("borrow_headroom"));
+        let with_perp = HedgeResolver::new(true, 72).with_venue_perps(
+            Venue::Hyperliquid,
+            &HashMap::from([("ETH".to_string(), dec!(-0.0002))]),
+        );
+        assert!(!qualify(&eth, &with_perp).unwrap().hedge.is_spot());
+
+        // A long spot hedge borrows nothing
+        let eth_long = qualify(&make_funding_rate("ETHUSDT", dec!(0.001)), &spot_only).unwrap();
+        assert!(eth_long.hedge.is_spot());
+    }
+
+    #[test]
+    fn test_prefilter_keeps_perp_hedged_symbols() {
+        let scanner = MarketScanner::new(PairSelectionConfig {
+            prefilter_top_k: 5,
+            futures_hedges: true,
+            ..test_config()
+        });
+        let funding_rates = vec![
+            make_funding_rate("BTCUSDT", dec!(0.001)),
+            make_funding_rate("NEWUSDT", dec!(0.002)),
+            make_funding_rate("NEWUSDC", dec!(0.0005)),
+        ];
+        let spot_info = [make_spot_info("BTCUSDT", true)];
+        let index = MarketIndex::new(&[], &[], &spot_info);
+
+        let spot_only = scanner.prefilter_candidates(
+            &funding_rates,
+            &index,
+            MIN_VOLUME,
+            &HedgeResolver::default(),
+        );
+        assert_eq!(spot_only.len(), 1);
+
+        let resolver = HedgeResolver::new(true, 72).with_usdc_perps(&funding_rates);
+        let candidates =
+            scanner.prefilter_candidates(&funding_rates, &index, MIN_VOLUME, &resolver);
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates.iter().any(|fr| fr.symbol == "NEWUSDT"));
+    }
+
+    // =========================================================================
+    // Scan Cache Tests
+    // =========================================================================
+
+    fn make_pair(symbol: &str) -> QualifiedPair {
+        QualifiedPair {
+            symbol: symbol.to_string(),
+            spot_symbol: symbol.to_string(),
+            base_asset: symbol.strip_suffix("USDT").unwrap_or(symbol).to_string(),
+            funding_rate: dec!(0.0005),
+            next_funding_time: 0,
+            volume_24h: dec!(1_000_000_000),
+            spread: dec!(0.0001),
+            open_interest: Decimal::ZERO,
+            margin_available: true,
+            borrow_rate: None,
+            score: dec!(5),
+            hedge: HedgeInstrument::SpotMargin,
+            hedge_options: Vec::new(),
+            funding_capped: false,
+            coin_contract: None,
+            borrow_headroom_usdt: None,
+        }
+    }
+
+    #[test]
+    fn test_scan_cache_expires_after_max_age() {
+        let mut cache = ScanCache::new(Duration::minutes(10));
+        let now = Utc::now();
+        assert!(cache.fresh(now).is_none());
+
+        cache.store(&[make_pair("BTCUSDT")], now);
+        assert_eq!(cache.fresh(now + Duration::minutes(10)).unwrap().len(), 1);
+        assert!(cache.fresh(now + Duration::minutes(11)).is_none());
+        assert_eq!(
+            cache.age(now + Duration::minutes(11)),
+            Some(Duration::minutes(11))
+        );
+    }
+
+    #[test]
+    fn test_scan_cache_store_replaces_previous_scan() {
+        let mut cache = ScanCache::new(Duration::minutes(10));
+        let now = Utc::now();
+        cache.store(&[make_pair("BTCUSDT"), make_pair("ETHUSDT")], now);
+
+        // An empty successful scan is still the latest truth
+        cache.store(&[], now + Duration::minutes(1));
+        assert!(cache.fresh(now + Duration::minutes(2)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_symbol_quality_scales_and_blacklists() {
+        use crate::persistence::PersistedPositionExit;
+
+        let now = Utc::now();
+        let exit = |symbol: &str, net_carry: Decimal| PersistedPositionExit {
+            closed_at: now,
+            symbol: symbol.to_string(),
+            opened_at: now - Duration::hours(24),
+            exit_kind: "forced".to_string(),
+            funding_received: Decimal::ZERO,
+            costs: -net_carry,
+            net_carry,
+        };
+        let mut scanner = MarketScanner::new(test_config());
+        scanner.set_symbol_quality(SymbolQuality::from_exits(
+            &[
+                exit("ETHUSDT", dec!(-4)),
+                exit("ALTUSDT", dec!(-1)),
+                exit("ALTUSDT", dec!(-2)),
+                exit("ALTUSDT", dec!(-3)),
+            ],
+            3,
+        ));
+
+        let mut pairs = vec![
+            make_pair("BTCUSDT"),
+            make_pair("ETHUSDT"),
+            make_pair("ALTUSDT"),
+        ];
+        assert_eq!(scanner.apply_symbol_quality(&mut pairs), 1);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].score, dec!(5));
+        // One losing exit: smoothed win rate 1/3 of 1/2 even odds
+        assert!(pairs[1].score < dec!(3.34) && pairs[1].score > dec!(3.33));
+    }
+
+    #[test]
+    fn test_parallel_qualification_ranks_ties_in_candidate_order() {
+        let symbols: Vec<String> = (0..100).map(|i| format!("S{}USDT", i)).collect();
+        let rate = |i: usize| {
+            if i.is_multiple_of(3) {
+                dec!(0.001)
+            } else {
+                dec!(0.0005)
+            }
+        };
+        let market = MarketData {
+            funding_rates: symbols
+                .iter()
+                .enumerate()
+                .map(|(i, s)| make_funding_rate(s, rate(i)))
+                .collect(),
+            futures_tickers: symbols
+                .iter()
+                .map(|s| make_ticker(s, dec!(1_000_000_000)))
+                .collect(),
+            book_tickers: symbols
+                .iter()
+                .map(|s| BookTicker {
+                    symbol: s.clone(),
+                    bid_price: dec!(99.995),
+                    bid_qty: dec!(10),
+                    ask_price: dec!(100.005),
+                    ask_qty: dec!(10),
+                })
+                .collect(),
+            spot_info: symbols.iter().map(|s| make_spot_info(s, true)).collect(),
+            spot_tickers: Vec::new(),
+            margin_assets: symbols
+                .iter()
+                .map(|s| make_margin_asset(s.strip_suffix("USDT").unwrap(), dec!(0.0001)))
+                .collect(),
+        };
+        let scanner = MarketScanner::new(test_config());
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        let pairs = pool.install(|| scanner.qualify_market(&market));
+        let ranked: Vec<&str> = pairs.iter().map(|p| p.symbol.as_str()).collect();
+        let expected: Vec<&str> = (0..100)
+            .filter(|i: &usize| i.is_multiple_of(3))
+            .chain((0..100).filter(|i: &usize| !i.is_multiple_of(3)))
+            .map(|i| symbols[i].as_str())
+            .collect();
+        assert_eq!(ranked, expected);
+    }
+
+    #[test]
+    fn test_run_blocking_on_either_runtime_flavor() {
+        // block_in_place would panic on a single-threaded runtime
+        let single = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert_eq!(single.block_on(async { run_blocking(|| 2) }), 2);
+
+        let multi = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .build()
+            .unwrap();
+        let on_worker = multi.block_on(async { tokio::spawn(async { run_blocking(|| 2) }).await });
+        assert_eq!(on_worker.unwrap(), 2);
+
+        // Outside any runtime
+        assert_eq!(run_blocking(|| 2), 2);
+    }
 }
@@ -0,0 +1,159 @@
+//! Symbol quality learned from realized performance.
+//!
+//! Some symbols qualify on paper cycle after cycle and still lose money once held:
+//! funding flips soon after entry, borrow costs run ahead of the estimate, or the
+//! position is forced out. Closed positions are recorded with their net carry
+//! (funding minus costs), and each symbol's recent exits give it a quality
+//! multiplier on the scanner score: the win rate, smoothed towards even odds so a
+//! single exit can't dominate, relative to those odds and never above 1. A symbol
+//! whose last `max_failures` exits all lost carry is blacklisted until a failure
+//! ages out of the lookback window.
+
+use crate::persistence::PersistedPositionExit;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Realized exits of one symbol.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolRecord {
+    pub exits: u32,
+    /// Exits with negative net carry
+    pub failures: u32,
+    /// Failures since the last profitable exit
+    pub consecutive_failures: u32,
+    /// Net carry summed over the exits (USDT)
+    pub net_carry: Decimal,
+}
+
+impl SymbolRecord {
+    /// Laplace-smoothed share of exits that kept positive carry (0.5 without history).
+    pub fn win_rate(&self) -> Decimal {
+        Decimal::from(self.exits - self.failures + 1) / Decimal::from(self.exits + 2)
+    }
+}
+
+/// Per-symbol quality scores from recent exits.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolQuality {
+    records: HashMap<String, SymbolRecord>,
+    /// Consecutive failures that blacklist a symbol (0 = never)
+    max_failures: u32,
+}
+
+impl SymbolQuality {
+    /// Build scores from exits ordered oldest first.
+    pub fn from_exits(exits: &[PersistedPositionExit], max_failures: u32) -> Self {
+        let mut records: HashMap<String, SymbolRecord> = HashMap::new();
+        for exit in exits {
+            let record = records.entry(exit.symbol.clone()).or_default();
+            record.exits += 1;
+            record.net_carry += exit.net_carry;
+            if exit.net_carry < Decimal::ZERO {
+                record.failures += 1;
+                record.consecutive_failures += 1;
+            } else {
+                record.consecutive_failures = 0;
+            }
+        }
+        Self {
+            records,
+            max_failures,
+        }
+    }
+
+    pub fn record(&self, symbol: &str) -> Option<&SymbolRecord> {
+        self.records.get(symbol)
+    }
+
+    /// Score multiplier for `symbol`: 1 without history, lower the more exits lost carry.
+    pub fn multiplier(&self, symbol: &str) -> Decimal {
+        self.records.get(symbol).map_or(Decimal::ONE, |record| {
+            (record.win_rate() * Decimal::TWO).min(Decimal::ONE)
+        })
+    }
+
+    /// Whether `symbol`'s recent exits failed often enough in a row to stop entering it.
+    pub fn is_blacklisted(&self, symbol: &str) -> bool {
+        self.max_failures > 0
+            && self
+                .records
+                .get(symbol)
+                .is_some_and(|record| record.consecutive_failures >= self.max_failures)
+    }
+
+    /// Blacklisted symbols, sorted.
+    pub fn blacklisted(&self) -> Vec<&str> {
+        let mut symbols: Vec<&str> = self
+            .records
+            .keys()
+            .map(String::as_str)
+            .filter(|symbol| self.is_blacklisted(symbol))
+            .collect();
+        symbols.sort();
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use rust_decimal_macros::dec;
+
+    fn exits(outcomes: &[(&str, Decimal)]) -> Vec<PersistedPositionExit> {
+        let now = Utc::now();
+        outcomes
+            .iter()
+            .map(|&(symbol, net_carry)| PersistedPositionExit {
+                closed_at: now,
+                symbol: symbol.to_string(),
+                opened_at: now - Duration::hours(24),
+                exit_kind: "routine".to_string(),
+                funding_received: net_carry + dec!(5),
+                costs: dec!(5),
+                net_carry,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_losing_symbols_penalized() {
+        let quality = SymbolQuality::from_exits(
+            &exits(&[
+                ("ALTUSDT", dec!(-2)),
+                ("BTCUSDT", dec!(10)),
+                ("ALTUSDT", dec!(-1)),
+                ("BTCUSDT", dec!(-1)),
+            ]),
+            3,
+        );
+
+        // No history, or as many wins as losses: no penalty
+        assert_eq!(quality.multiplier("ETHUSDT"), Decimal::ONE);
+        assert_eq!(quality.multiplier("BTCUSDT"), Decimal::ONE);
+        // Two losses: smoothed win rate 1/4, half of even odds
+        assert_eq!(quality.multiplier("ALTUSDT"), dec!(0.5));
+        let alt = quality.record("ALTUSDT").unwrap();
+        assert_eq!(alt.net_carry, dec!(-3));
+        assert_eq!(alt.consecutive_failures, 2);
+        assert!(!quality.is_blacklisted("ALTUSDT"));
+    }
+
+    #[test]
+    fn test_blacklist_after_consecutive_failures() {
+        let mut outcomes = vec![("ALTUSDT", dec!(-1)); 3];
+        let quality = SymbolQuality::from_exits(&exits(&outcomes), 3);
+        assert!(quality.is_blacklisted("ALTUSDT"));
+        assert_eq!(quality.blacklisted(), vec!["ALTUSDT"]);
+
+        // A profitable exit resets the streak
+        outcomes.insert(1, ("ALTUSDT", dec!(4)));
+        let quality = SymbolQuality::from_exits(&exits(&outcomes), 3);
+        assert!(!quality.is_blacklisted("ALTUSDT"));
+        assert!(quality.multiplier("ALTUSDT") < Decimal::ONE);
+
+        // 0 disables the blacklist
+        let quality = SymbolQuality::from_exits(&exits(&[("ALTUSDT", dec!(-1)); 5]), 0);
+        assert!(quality.blacklisted().is_empty());
+    }
+}
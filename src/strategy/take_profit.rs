@@ -0,0 +1,226 @@
+//! Partial closes at funding milestones.
+//!
+//! A position that has earned a multiple of its entry fees has paid for itself;
+//! closing part of it locks the gain in and frees capital for the next opportunity
+//! while a core position keeps collecting. Milestones are measured on net funding
+//! since the previous take, so each take has to be earned afresh.
+//!
+//! Take counts live in memory. After a restart the baseline is the net funding at
+//! first sight, so a restart never triggers a take by itself.
+
+use crate::config::TakeProfitConfig;
+use crate::risk::TrackedPosition;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// A position that reached its next funding milestone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TakeProfitTrigger {
+    pub symbol: String,
+    /// Net funding since the previous take (USD)
+    pub funding_gain: Decimal,
+    /// Milestone that was reached (USD)
+    pub milestone: Decimal,
+    /// Notional to close (USDT)
+    pub reduction_usdt: Decimal,
+    /// 1-based number of this take
+    pub take: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TakeState {
+    takes: u32,
+    /// Net funding when the position was last reduced (or first seen)
+    baseline: Decimal,
+}
+
+/// Tracks take-profit milestones per position.
+#[derive(Debug)]
+pub struct TakeProfitMonitor {
+    config: TakeProfitConfig,
+    positions: HashMap<String, TakeState>,
+}
+
+impl TakeProfitMonitor {
+    pub fn new(config: TakeProfitConfig) -> Self {
+        Self {
+            config,
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Net funding of a position: funding received minus borrow interest.
+    pub fn net_funding(tracked: &TrackedPosition) -> Decimal {
+        tracked.total_funding_received - tracked.interest_paid
+    }
+
+    /// Funding gain a position of `notional` must earn before its next take.
+    ///
+    /// The lower of the enabled milestones; `None` when neither is enabled.
+    pub fn milestone(&self, tracked: &TrackedPosition, notional: Decimal) -> Option<Decimal> {
+        let by_fees = (self.config.fee_multiple > Decimal::ZERO)
+            .then(|| tracked.entry_fees * self.config.fee_multiple);
+        let by_notional =
+            (self.config.notional_pct > Decimal::ZERO).then(|| notional * self.config.notional_pct);
+        match (by_fees, by_notional) {
+            (Some(fees), Some(notional)) => Some(fees.min(notional)),
+            (fees, notional) => fees.or(notional),
+        }
+    }
+
+    /// Check a held position of current `notional` against its next milestone.
+    ///
+    /// The first check of a position sets its baseline.
+    pub fn check(
+        &mut self,
+        tracked: &TrackedPosition,
+        notional: Decimal,
+    ) -> Option<TakeProfitTrigger> {
+        if !self.config.enabled || notional <= Decimal::ZERO {
+            return None;
+        }
+        let net_funding = Self::net_funding(tracked);
+        let state = *self
+            .positions
+            .entry(tracked.symbol.clone())
+            .or_insert(TakeState {
+                takes: 0,
+                baseline: net_funding,
+            });
+        if state.takes >= self.config.max_takes {
+            return None;
+        }
+
+        let milestone = self.milestone(tracked, notional)?;
+        let funding_gain = net_funding - state.baseline;
+        if milestone <= Decimal::ZERO || funding_gain < milestone {
+            return None;
+        }
+        Some(TakeProfitTrigger {
+            symbol: tracked.symbol.clone(),
+            funding_gain,
+            milestone,
+            reduction_usdt: notional * self.config.reduce_fraction,
+            take: state.takes + 1,
+        })
+    }
+
+    /// Record a completed partial close; the next milestone starts from here.
+    pub fn record_take(&mut self, tracked: &TrackedPosition) {
+        let net_funding = Self::net_funding(tracked);
+        let state = self
+            .positions
+            .entry(tracked.symbol.clone())
+            .or_insert(TakeState {
+                takes: 0,
+                baseline: net_funding,
+            });
+        state.takes += 1;
+        state.baseline = net_funding;
+    }
+
+    /// Partial closes made on a position so far.
+    pub fn takes(&self, symbol: &str) -> u32 {
+        self.positions.get(symbol).map_or(0, |state| state.takes)
+    }
+
+    /// Forget positions that are no longer held.
+    pub fn retain_held(&mut self, held: &[String]) {
+        self.positions.retain(|symbol, _| held.contains(symbol));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::PositionEntry;
+    use rust_decimal_macros::dec;
+
+    fn tracked(entry_fees: Decimal, funding: Decimal, interest: Decimal) -> TrackedPosition {
+        let mut position = TrackedPosition::new(
+            "BTCUSDT".to_string(),
+            PositionEntry {
+                symbol: "BTCUSDT".to_string(),
+                entry_price: dec!(50000),
+                quantity: dec!(0.2),
+                position_value: dec!(10000),
+                expected_funding_rate: dec!(0.0003),
+                entry_fees,
+                opened_at: None,
+                first_funding_at: None,
+            },
+        );
+        position.total_funding_received = funding;
+        position.interest_paid = interest;
+        position
+    }
+
+    fn config() -> TakeProfitConfig {
+        TakeProfitConfig {
+            enabled: true,
+            fee_multiple: dec!(5),
+            notional_pct: dec!(0.01),
+            reduce_fraction: dec!(0.25),
+            max_takes: 2,
+        }
+    }
+
+    #[test]
+    fn test_takes_at_milestones_until_core_remains() {
+        let mut monitor = TakeProfitMonitor::new(config());
+        // 5x $8 fees = $40 is reached before 1% of $10,000
+        assert!(monitor
+            .check(&tracked(dec!(8), Decimal::ZERO, Decimal::ZERO), dec!(10000))
+            .is_none());
+        assert!(monitor
+            .check(&tracked(dec!(8), dec!(42), dec!(3)), dec!(10000))
+            .is_none());
+
+        let position = tracked(dec!(8), dec!(45), dec!(3));
+        let trigger = monitor.check(&position, dec!(10000)).unwrap();
+        assert_eq!(trigger.milestone, dec!(40));
+        assert_eq!(trigger.funding_gain, dec!(42));
+        assert_eq!(trigger.reduction_usdt, dec!(2500));
+        assert_eq!(trigger.take, 1);
+        monitor.record_take(&position);
+
+        // The next milestone counts from the take
+        assert!(monitor
+            .check(&tracked(dec!(8), dec!(70), dec!(3)), dec!(7500))
+            .is_none());
+        let position = tracked(dec!(8), dec!(85), dec!(3));
+        assert_eq!(monitor.check(&position, dec!(7500)).unwrap().take, 2);
+        monitor.record_take(&position);
+        assert_eq!(monitor.takes("BTCUSDT"), 2);
+
+        // Core position is kept
+        assert!(monitor
+            .check(&tracked(dec!(8), dec!(500), dec!(3)), dec!(5625))
+            .is_none());
+
+        monitor.retain_held(&[]);
+        assert_eq!(monitor.takes("BTCUSDT"), 0);
+    }
+
+    #[test]
+    fn test_first_sight_sets_baseline() {
+        let mut monitor = TakeProfitMonitor::new(TakeProfitConfig {
+            fee_multiple: Decimal::ZERO,
+            ..config()
+        });
+        // Restored with funding well past the milestone: no take on restart
+        assert!(monitor
+            .check(&tracked(dec!(8), dec!(300), Decimal::ZERO), dec!(10000))
+            .is_none());
+        // Only the notional milestone applies: 1% of $10,000
+        let trigger = monitor
+            .check(&tracked(dec!(8), dec!(400), Decimal::ZERO), dec!(10000))
+            .unwrap();
+        assert_eq!(trigger.milestone, dec!(100));
+
+        let mut disabled = TakeProfitMonitor::new(TakeProfitConfig::default());
+        assert!(disabled
+            .check(&tracked(dec!(8), dec!(1000), Decimal::ZERO), dec!(10000))
+            .is_none());
+    }
+}
@@ -0,0 +1,297 @@
+//! Dynamic capital utilization.
+//!
+//! `capital.max_utilization` caps how much equity is deployed, but how much is
+//! safe to deploy moves with the account. Three headroom factors between 0 and 1
+//! are derived each risk cycle: margin health (Green 1, Yellow 0.5, worse 0),
+//! effective leverage (1 up to 1x, falling to 0 at the leverage limit) and the
+//! daily mark price volatility of held symbols against a target (1 at or below
+//! it, shrinking in proportion above it). The tightest factor sets the target
+//! between `min_utilization` and the cap. Cuts apply at once; increases wait for
+//! Green margin health and climb by `step` per cycle, so capital returns
+//! gradually after stress.
+
+use crate::config::DynamicUtilizationConfig;
+use crate::risk::MarginHealth;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Price samples closer together than this are skipped.
+const MIN_SAMPLE_INTERVAL: Duration = Duration::minutes(1);
+
+/// Weight of the newest sample in the variance average.
+const VARIANCE_DECAY: f64 = 0.1;
+
+/// Which headroom factor set the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtilizationLimit {
+    /// Every factor has full headroom
+    Cap,
+    MarginHealth,
+    Leverage,
+    Volatility,
+}
+
+impl UtilizationLimit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UtilizationLimit::Cap => "cap",
+            UtilizationLimit::MarginHealth => "margin health",
+            UtilizationLimit::Leverage => "leverage",
+            UtilizationLimit::Volatility => "volatility",
+        }
+    }
+}
+
+/// Outcome of one controller update, with its reasoning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtilizationDecision {
+    pub previous: Decimal,
+    pub utilization: Decimal,
+    pub target: Decimal,
+    pub limit: UtilizationLimit,
+    pub health: MarginHealth,
+    /// Gross futures notional over equity
+    pub leverage: Decimal,
+    /// Highest daily volatility among held symbols, once measured
+    pub volatility: Option<Decimal>,
+}
+
+impl UtilizationDecision {
+    pub fn changed(&self) -> bool {
+        self.utilization != self.previous
+    }
+}
+
+impl fmt::Display for UtilizationDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}% -> {:.1}% (target {:.1}%, limited by {}; health {:?}, leverage {:.2}x, volatility {})",
+            self.previous * dec!(100),
+            self.utilization * dec!(100),
+            self.target * dec!(100),
+            self.limit.as_str(),
+            self.health,
+            self.leverage,
+            self.volatility
+                .map(|v| format!("{:.2}%/day", v * dec!(100)))
+                .unwrap_or_else(|| "unmeasured".to_string())
+        )
+    }
+}
+
+/// Sets capital utilization from margin headroom.
+#[derive(Debug, Clone)]
+pub struct UtilizationController {
+    config: DynamicUtilizationConfig,
+    /// Ceiling (`capital.max_utilization`)
+    max_utilization: Decimal,
+    max_leverage: Decimal,
+    current: Decimal,
+    /// Last mark price per held symbol
+    prices: HashMap<String, (DateTime<Utc>, Decimal)>,
+    /// Moving average of squared log returns per hour, per held symbol
+    variance: HashMap<String, f64>,
+}
+
+impl UtilizationController {
+    /// Controller starting at the cap.
+    pub fn new(
+        config: DynamicUtilizationConfig,
+        max_utilization: Decimal,
+        max_leverage: u8,
+    ) -> Self {
+        Self {
+            config,
+            max_utilization,
+            max_leverage: Decimal::from(max_leverage),
+            current: max_utilization,
+            prices: HashMap::new(),
+            variance: HashMap::new(),
+        }
+    }
+
+    /// Utilization currently deployed.
+    pub fn current(&self) -> Decimal {
+        self.current
+    }
+
+    /// Record mark prices of the held symbols; symbols not in `prices` are dropped.
+    pub fn observe_prices(&mut self, prices: &HashMap<String, Decimal>, now: DateTime<Utc>) {
+        self.prices.retain(|symbol, _| prices.contains_key(symbol));
+        self.variance
+            .retain(|symbol, _| prices.contains_key(symbol));
+        for (symbol, &price) in prices {
+            if price <= Decimal::ZERO {
+                continue;
+            }
+            let Some(&(at, last)) = self.prices.get(symbol) else {
+                self.prices.insert(symbol.clone(), (now, price));
+                continue;
+            };
+            if now - at < MIN_SAMPLE_INTERVAL {
+                continue;
+            }
+            let hours = (now - at).num_seconds() as f64 / 3600.0;
+            let ratio = (price / last).to_f64().unwrap_or(1.0);
+            let hourly = ratio.ln().powi(2) / hours;
+            self.variance
+                .entry(symbol.clone())
+                .and_modify(|v| *v += VARIANCE_DECAY * (hourly - *v))
+                .or_insert(hourly);
+            self.prices.insert(symbol.clone(), (now, price));
+        }
+    }
+
+    /// Highest daily volatility among held symbols.
+    pub fn volatility(&self) -> Option<Decimal> {
+        self.variance
+            .values()
+            .map(|hourly| (hourly * 24.0).sqrt())
+            .fold(None, |max: Option<f64>, v| {
+                Some(max.map_or(v, |m| m.max(v)))
+            })
+            .and_then(Decimal::from_f64_retain)
+            .map(|v| v.round_dp(6))
+    }
+
+    /// Re-target utilization from margin `health` and effective `leverage`.
+    pub fn update(&mut self, health: MarginHealth, leverage: Decimal) -> UtilizationDecision {
+        let volatility = self.volatility();
+        let health_factor = match health {
+            MarginHealth::Green => Decimal::ONE,
+            MarginHealth::Yellow => dec!(0.5),
+            MarginHealth::Orange | MarginHealth::Red => Decimal::ZERO,
+        };
+        let leverage_factor = if self.max_leverage <= Decimal::ONE || leverage <= Decimal::ONE {
+            Decimal::ONE
+        } else {
+            ((self.max_leverage - leverage) / (self.max_leverage - Decimal::ONE))
+                .clamp(Decimal::ZERO, Decimal::ONE)
+        };
+        let volatility_factor = match volatility {
+            Some(v) if v > self.config.target_daily_volatility => {
+                self.config.target_daily_volatility / v
+            }
+            _ => Decimal::ONE,
+        };
+
+        let (factor, limit) = [
+            (health_factor, UtilizationLimit::MarginHealth),
+            (leverage_factor, UtilizationLimit::Leverage),
+            (volatility_factor, UtilizationLimit::Volatility),
+        ]
+        .into_iter()
+        .filter(|(factor, _)| *factor < Decimal::ONE)
+        .min_by_key(|(factor, _)| *factor)
+        .unwrap_or((Decimal::ONE, UtilizationLimit::Cap));
+
+        let floor = self.config.min_utilization.min(self.max_utilization);
+        let target = (floor + (self.max_utilization - floor) * factor).round_dp(4);
+
+        let previous = self.current;
+        if target < previous {
+            self.current = target;
+        } else if health == MarginHealth::Green {
+            self.current = (previous + self.config.step).min(target);
+        }
+
+        UtilizationDecision {
+            previous,
+            utilization: self.current,
+            target,
+            limit,
+            health,
+            leverage,
+            volatility,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_controller() -> UtilizationController {
+        UtilizationController::new(DynamicUtilizationConfig::default(), dec!(0.85), 10)
+    }
+
+    #[test]
+    fn test_shrinks_at_once_and_recovers_in_steps() {
+        let mut controller = test_controller();
+        let decision = controller.update(MarginHealth::Green, dec!(0.8));
+        assert!(!decision.changed());
+        assert_eq!(decision.limit, UtilizationLimit::Cap);
+
+        // Yellow: halfway between the 30% floor and the 85% cap
+        let decision = controller.update(MarginHealth::Yellow, dec!(0.8));
+        assert_eq!(decision.utilization, dec!(0.575));
+        assert_eq!(decision.limit, UtilizationLimit::MarginHealth);
+
+        // Orange drops to the floor
+        assert_eq!(
+            controller
+                .update(MarginHealth::Orange, dec!(0.8))
+                .utilization,
+            dec!(0.30)
+        );
+
+        // Back to Green: climb by one step per cycle
+        let decision = controller.update(MarginHealth::Green, dec!(0.8));
+        assert_eq!(decision.utilization, dec!(0.35));
+        assert_eq!(decision.target, dec!(0.85));
+        assert_eq!(
+            controller
+                .update(MarginHealth::Green, dec!(0.8))
+                .utilization,
+            dec!(0.40)
+        );
+
+        // Yellow above its target holds instead of climbing
+        assert_eq!(
+            controller
+                .update(MarginHealth::Yellow, dec!(0.8))
+                .utilization,
+            dec!(0.40)
+        );
+    }
+
+    #[test]
+    fn test_leverage_and_volatility_limits() {
+        let mut controller = test_controller();
+        // 5.5x of a 10x limit leaves half the headroom
+        let decision = controller.update(MarginHealth::Green, dec!(5.5));
+        assert_eq!(decision.limit, UtilizationLimit::Leverage);
+        assert_eq!(decision.utilization, dec!(0.575));
+
+        // A 1% hourly swing is ~4.9%/day, still within the 5% target
+        let mut controller = test_controller();
+        let now = Utc::now();
+        let mut prices = HashMap::from([("ALTUSDT".to_string(), dec!(100))]);
+        controller.observe_prices(&prices, now);
+        prices.insert("ALTUSDT".to_string(), dec!(101));
+        controller.observe_prices(&prices, now + Duration::hours(1));
+        let volatility = controller.volatility().unwrap();
+        assert!(volatility > dec!(0.048) && volatility < dec!(0.05));
+        assert_eq!(
+            controller.update(MarginHealth::Green, dec!(1)).limit,
+            UtilizationLimit::Cap
+        );
+
+        // A 4% hourly swing far exceeds it
+        prices.insert("ALTUSDT".to_string(), dec!(105));
+        controller.observe_prices(&prices, now + Duration::hours(2));
+        let decision = controller.update(MarginHealth::Green, dec!(1));
+        assert_eq!(decision.limit, UtilizationLimit::Volatility);
+        assert!(decision.utilization < dec!(0.85));
+        assert!(decision.to_string().contains("limited by volatility"));
+
+        // Closing the position forgets its volatility
+        controller.observe_prices(&HashMap::new(), now + Duration::hours(3));
+        assert_eq!(controller.volatility(), None);
+    }
+}
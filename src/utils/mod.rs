@@ -1,5 +1,7 @@
 //! Shared utilities for the funding fee farmer.
 
 mod decimal;
+mod time;
 
 pub use decimal::*;
+pub use time::*;
@@ -0,0 +1,117 @@
+//! Time-zone aware display and scheduling helpers.
+//!
+//! Everything is stored and computed in UTC; these helpers only convert at the edge
+//! when times are shown to a person (status CLI, status reports, alerts).
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+/// Time zone used to display timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayTimezone(Tz);
+
+impl Default for DisplayTimezone {
+    fn default() -> Self {
+        Self(Tz::UTC)
+    }
+}
+
+impl DisplayTimezone {
+    /// Parse an IANA time zone name (e.g. "UTC", "Asia/Seoul", "America/New_York").
+    pub fn parse(name: &str) -> Result<Self> {
+        name.parse::<Tz>()
+            .map(Self)
+            .map_err(|_| anyhow!("unknown time zone '{}' (expected an IANA name)", name))
+    }
+
+    /// IANA name of the zone.
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Format a UTC timestamp in this zone with a `chrono` format string.
+    pub fn format(&self, dt: DateTime<Utc>, fmt: &str) -> String {
+        dt.with_timezone(&self.0).format(fmt).to_string()
+    }
+
+    /// Full timestamp, e.g. "2024-03-01 17:00:00 KST".
+    pub fn datetime(&self, dt: DateTime<Utc>) -> String {
+        self.format(dt, "%Y-%m-%d %H:%M:%S %Z")
+    }
+
+    /// Short timestamp, e.g. "2024-03-01 17:00".
+    pub fn short(&self, dt: DateTime<Utc>) -> String {
+        self.format(dt, "%Y-%m-%d %H:%M")
+    }
+
+    /// Humanized countdown with the local clock time, e.g. "in 2h13m (01:00 KST)".
+    pub fn countdown(&self, at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        let clock = self.format(at, "%H:%M %Z");
+        if at <= now {
+            format!("now ({})", clock)
+        } else {
+            format!("in {} ({})", humanize_duration(at - now), clock)
+        }
+    }
+}
+
+/// Compact human-readable duration, e.g. "45m", "2h13m", "3d4h".
+///
+/// Negative durations are shown as zero.
+pub fn humanize_duration(duration: Duration) -> String {
+    let total_minutes = duration.num_minutes().max(0);
+    let days = total_minutes / (24 * 60);
+    let hours = (total_minutes / 60) % 24;
+    let minutes = total_minutes % 60;
+
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{:02}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_display_in_configured_zone() {
+        let seoul = DisplayTimezone::parse("Asia/Seoul").unwrap();
+        assert_eq!(
+            seoul.datetime(at("2024-03-01T16:00:00Z")),
+            "2024-03-02 01:00:00 KST"
+        );
+        assert_eq!(
+            DisplayTimezone::default().datetime(at("2024-03-01T16:00:00Z")),
+            "2024-03-01 16:00:00 UTC"
+        );
+        assert!(DisplayTimezone::parse("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_humanize_duration() {
+        assert_eq!(humanize_duration(Duration::minutes(45)), "45m");
+        assert_eq!(humanize_duration(Duration::minutes(133)), "2h13m");
+        assert_eq!(humanize_duration(Duration::minutes(120)), "2h00m");
+        assert_eq!(humanize_duration(Duration::hours(76)), "3d4h");
+        assert_eq!(humanize_duration(Duration::minutes(-5)), "0m");
+    }
+
+    #[test]
+    fn test_countdown() {
+        let now = at("2024-03-01T13:47:00Z");
+        let next = at("2024-03-01T16:00:00Z");
+
+        let seoul = DisplayTimezone::parse("Asia/Seoul").unwrap();
+        assert_eq!(seoul.countdown(next, now), "in 2h13m (01:00 KST)");
+        assert_eq!(seoul.countdown(now, next), "now (22:47 KST)");
+    }
+}